@@ -0,0 +1,439 @@
+//! [`Arbitrary`] implementations for fuzzing and property testing.
+//!
+//! Generated array metadata is valid Zarr V3 array metadata using the `regular` chunk grid, the `default` chunk key encoding, and a codec chain of `transpose`, `bytes`, `gzip`/`zstd`, and `crc32c` codecs.
+//! The fill value is consistent with the data type.
+//! Array subsets have at most 4 dimensions and bounded extents, so their end indices and number of elements do not overflow.
+
+use std::num::NonZeroU64;
+
+use ::arbitrary::{Arbitrary, Error, Result, Unstructured};
+
+use crate::{
+    array::{ChunkKeySeparator, ChunkShape, DimensionName, Endianness, FillValue},
+    array_subset::ArraySubset,
+    metadata::{
+        v3::{
+            chunk_grid::regular::{self, RegularChunkGridConfiguration},
+            chunk_key_encoding::default::{self, DefaultChunkKeyEncodingConfiguration},
+            codec::{
+                bytes::{self, BytesCodecConfiguration, BytesCodecConfigurationV1},
+                crc32c::{self, Crc32cCodecConfiguration, Crc32cCodecConfigurationV1},
+                gzip::{
+                    self, GzipCodecConfiguration, GzipCodecConfigurationV1, GzipCompressionLevel,
+                },
+                transpose::{
+                    self, TransposeCodecConfiguration, TransposeCodecConfigurationV1,
+                    TransposeOrder,
+                },
+                zstd::{
+                    self, ZstdCodecConfiguration, ZstdCodecConfigurationV1, ZstdCompressionLevel,
+                },
+            },
+            fill_value::{FillValueFloat, FillValueFloatStringNonFinite, FillValueMetadata},
+            ArrayMetadataV3, MetadataV3,
+        },
+        AdditionalFields, ArrayMetadata,
+    },
+};
+
+/// The maximum number of dimensions of generated shapes and array subsets.
+const MAX_DIMENSIONS: usize = 4;
+
+/// The maximum length of a dimension of generated shapes and array subsets.
+const MAX_LENGTH: u64 = 1024;
+
+/// The maximum length of a dimension of generated chunk shapes.
+const MAX_CHUNK_LENGTH: u64 = 64;
+
+fn arbitrary_dimensionality(u: &mut Unstructured) -> Result<usize> {
+    u.int_in_range(0..=MAX_DIMENSIONS)
+}
+
+fn arbitrary_permutation(u: &mut Unstructured, dimensionality: usize) -> Result<Vec<usize>> {
+    let mut permutation: Vec<usize> = (0..dimensionality).collect();
+    for i in (1..dimensionality).rev() {
+        let j = u.int_in_range(0..=i)?;
+        permutation.swap(i, j);
+    }
+    Ok(permutation)
+}
+
+fn arbitrary_chunk_shape(u: &mut Unstructured, dimensionality: usize) -> Result<ChunkShape> {
+    (0..dimensionality)
+        .map(|_| {
+            let length = u.int_in_range(1..=MAX_CHUNK_LENGTH)?;
+            Ok(NonZeroU64::new(length).expect("chunk length is non-zero"))
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(ChunkShape::from)
+}
+
+fn arbitrary_float(u: &mut Unstructured) -> Result<FillValueFloat> {
+    Ok(match u.int_in_range(0..=3)? {
+        0 => FillValueFloat::NonFinite(FillValueFloatStringNonFinite::PosInfinity),
+        1 => FillValueFloat::NonFinite(FillValueFloatStringNonFinite::NegInfinity),
+        2 => FillValueFloat::NonFinite(FillValueFloatStringNonFinite::NaN),
+        _ => {
+            let value = f64::arbitrary(u)?;
+            if value.is_finite() {
+                FillValueFloat::Float(value)
+            } else {
+                FillValueFloat::Float(0.0)
+            }
+        }
+    })
+}
+
+/// Create metadata with a configuration, omitting an empty configuration so that the metadata round trips through JSON.
+fn metadata_with_configuration<TConfiguration: serde::Serialize>(
+    name: &str,
+    configuration: &TConfiguration,
+) -> Result<MetadataV3> {
+    let metadata = MetadataV3::new_with_serializable_configuration(name, configuration)
+        .map_err(|_| Error::IncorrectFormat)?;
+    if metadata
+        .configuration()
+        .is_some_and(serde_json::Map::is_empty)
+    {
+        Ok(MetadataV3::new(name))
+    } else {
+        Ok(metadata)
+    }
+}
+
+/// Generate a data type, a consistent fill value, and the data type size.
+fn arbitrary_data_type_and_fill_value(
+    u: &mut Unstructured,
+) -> Result<(MetadataV3, FillValueMetadata, usize)> {
+    let (name, fill_value, size) = match u.int_in_range(0..=12)? {
+        0 => ("bool", FillValueMetadata::Bool(u.arbitrary()?), 1),
+        1 => ("int8", FillValueMetadata::Int(i8::arbitrary(u)?.into()), 1),
+        2 => (
+            "int16",
+            FillValueMetadata::Int(i16::arbitrary(u)?.into()),
+            2,
+        ),
+        3 => (
+            "int32",
+            FillValueMetadata::Int(i32::arbitrary(u)?.into()),
+            4,
+        ),
+        4 => ("int64", FillValueMetadata::Int(i64::arbitrary(u)?), 8),
+        5 => (
+            "uint8",
+            FillValueMetadata::UInt(u8::arbitrary(u)?.into()),
+            1,
+        ),
+        6 => (
+            "uint16",
+            FillValueMetadata::UInt(u16::arbitrary(u)?.into()),
+            2,
+        ),
+        7 => (
+            "uint32",
+            FillValueMetadata::UInt(u32::arbitrary(u)?.into()),
+            4,
+        ),
+        8 => ("uint64", FillValueMetadata::UInt(u64::arbitrary(u)?), 8),
+        9 => ("float32", FillValueMetadata::Float(arbitrary_float(u)?), 4),
+        10 => ("float64", FillValueMetadata::Float(arbitrary_float(u)?), 8),
+        11 => (
+            "complex128",
+            FillValueMetadata::Complex(arbitrary_float(u)?, arbitrary_float(u)?),
+            16,
+        ),
+        _ => (
+            "r16",
+            FillValueMetadata::ByteArray(<[u8; 2]>::arbitrary(u)?.to_vec()),
+            2,
+        ),
+    };
+    Ok((MetadataV3::new(name), fill_value, size))
+}
+
+impl<'a> Arbitrary<'a> for ArraySubset {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let dimensionality = arbitrary_dimensionality(u)?;
+        let start = (0..dimensionality)
+            .map(|_| u.int_in_range(0..=MAX_LENGTH))
+            .collect::<Result<Vec<_>>>()?;
+        let shape = (0..dimensionality)
+            .map(|_| u.int_in_range(0..=MAX_LENGTH))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self::new_with_start_shape(start, shape).expect("start and shape have equal length"))
+    }
+}
+
+impl<'a> Arbitrary<'a> for FillValue {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::from(Vec::<u8>::arbitrary(u)?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for FillValueFloat {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        arbitrary_float(u)
+    }
+}
+
+impl<'a> Arbitrary<'a> for FillValueMetadata {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=6)? {
+            0 => Self::Bool(u.arbitrary()?),
+            1 => Self::UInt(u.arbitrary()?),
+            2 => Self::Int(u.arbitrary()?),
+            3 => Self::Float(u.arbitrary()?),
+            4 => Self::ByteArray(u.arbitrary()?),
+            5 => Self::Complex(u.arbitrary()?, u.arbitrary()?),
+            _ => Self::String(u.arbitrary()?),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Endianness {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(if u.arbitrary()? {
+            Self::Little
+        } else {
+            Self::Big
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for ChunkKeySeparator {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(if u.arbitrary()? {
+            Self::Slash
+        } else {
+            Self::Dot
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for ChunkShape {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let dimensionality = arbitrary_dimensionality(u)?;
+        arbitrary_chunk_shape(u, dimensionality)
+    }
+}
+
+impl<'a> Arbitrary<'a> for RegularChunkGridConfiguration {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::new(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for DefaultChunkKeyEncodingConfiguration {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::new(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for BytesCodecConfigurationV1 {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::new(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for BytesCodecConfiguration {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::V1(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Crc32cCodecConfigurationV1 {
+    fn arbitrary(_u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::new())
+    }
+}
+
+impl<'a> Arbitrary<'a> for Crc32cCodecConfiguration {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::V1(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for GzipCompressionLevel {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Self::try_from(u.int_in_range(0..=9u32)?).map_err(|_| Error::IncorrectFormat)
+    }
+}
+
+impl<'a> Arbitrary<'a> for GzipCodecConfigurationV1 {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::new(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for GzipCodecConfiguration {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::V1(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for TransposeOrder {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let dimensionality = u.int_in_range(1..=MAX_DIMENSIONS)?;
+        Self::new(&arbitrary_permutation(u, dimensionality)?).map_err(|_| Error::IncorrectFormat)
+    }
+}
+
+impl<'a> Arbitrary<'a> for TransposeCodecConfigurationV1 {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::new(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for TransposeCodecConfiguration {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::V1(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for ZstdCompressionLevel {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::new(u.int_in_range(-7..=22)?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for ZstdCodecConfigurationV1 {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::new(u.arbitrary()?, u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for ZstdCodecConfiguration {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::V1(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for ArrayMetadataV3 {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let dimensionality = arbitrary_dimensionality(u)?;
+        let shape = (0..dimensionality)
+            .map(|_| u.int_in_range(0..=MAX_LENGTH))
+            .collect::<Result<Vec<_>>>()?;
+        let (data_type, fill_value, data_type_size) = arbitrary_data_type_and_fill_value(u)?;
+
+        let chunk_grid = metadata_with_configuration(
+            regular::IDENTIFIER,
+            &RegularChunkGridConfiguration::new(arbitrary_chunk_shape(u, dimensionality)?),
+        )?;
+        let chunk_key_encoding = metadata_with_configuration(
+            default::IDENTIFIER,
+            &DefaultChunkKeyEncodingConfiguration::new(u.arbitrary()?),
+        )?;
+
+        let mut codecs = Vec::new();
+        if dimensionality > 0 && u.arbitrary()? {
+            let order = TransposeOrder::new(&arbitrary_permutation(u, dimensionality)?)
+                .map_err(|_| Error::IncorrectFormat)?;
+            codecs.push(metadata_with_configuration(
+                transpose::IDENTIFIER,
+                &TransposeCodecConfigurationV1::new(order),
+            )?);
+        }
+        let endian = if data_type_size == 1 {
+            None
+        } else {
+            Some(u.arbitrary()?)
+        };
+        codecs.push(metadata_with_configuration(
+            bytes::IDENTIFIER,
+            &BytesCodecConfigurationV1::new(endian),
+        )?);
+        match u.int_in_range(0..=2)? {
+            0 => {}
+            1 => codecs.push(metadata_with_configuration(
+                gzip::IDENTIFIER,
+                &GzipCodecConfigurationV1::arbitrary(u)?,
+            )?),
+            _ => codecs.push(metadata_with_configuration(
+                zstd::IDENTIFIER,
+                &ZstdCodecConfigurationV1::arbitrary(u)?,
+            )?),
+        }
+        if u.arbitrary()? {
+            codecs.push(metadata_with_configuration(
+                crc32c::IDENTIFIER,
+                &Crc32cCodecConfigurationV1::new(),
+            )?);
+        }
+
+        let mut attributes = serde_json::Map::new();
+        for _ in 0..u.int_in_range(0..=2)? {
+            attributes.insert(
+                String::arbitrary(u)?,
+                serde_json::Value::String(u.arbitrary()?),
+            );
+        }
+        let dimension_names = if u.arbitrary()? {
+            Some(
+                (0..dimensionality)
+                    .map(|_| {
+                        Ok(Option::<String>::arbitrary(u)?
+                            .map_or_else(DimensionName::default, DimensionName::new))
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+            )
+        } else {
+            None
+        };
+
+        Ok(Self::new(
+            shape,
+            data_type,
+            chunk_grid,
+            chunk_key_encoding,
+            fill_value,
+            codecs,
+            attributes,
+            vec![],
+            dimension_names,
+            AdditionalFields::default(),
+        ))
+    }
+}
+
+impl<'a> Arbitrary<'a> for ArrayMetadata {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::V3(u.arbitrary()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pseudo_random_bytes(len: u32) -> Vec<u8> {
+        (0..len)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 13).to_le_bytes()[0])
+            .collect()
+    }
+
+    #[test]
+    fn arbitrary_array_metadata_round_trip() {
+        let bytes = pseudo_random_bytes(16384);
+        let mut u = Unstructured::new(&bytes);
+        while !u.is_empty() {
+            let Ok(metadata) = ArrayMetadata::arbitrary(&mut u) else {
+                break;
+            };
+            let json = serde_json::to_string(&metadata).unwrap();
+            let metadata_round_trip: ArrayMetadata = serde_json::from_str(&json).unwrap();
+            assert_eq!(metadata.to_string(), metadata_round_trip.to_string());
+        }
+    }
+
+    #[test]
+    fn arbitrary_array_subset() {
+        let bytes = pseudo_random_bytes(4096);
+        let mut u = Unstructured::new(&bytes);
+        while !u.is_empty() {
+            let subset = ArraySubset::arbitrary(&mut u).unwrap();
+            assert!(subset.dimensionality() <= MAX_DIMENSIONS);
+            assert!(subset.end_exc().iter().all(|&end| end <= 2 * MAX_LENGTH));
+        }
+    }
+}
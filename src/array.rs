@@ -26,21 +26,43 @@ mod array_bytes;
 mod array_errors;
 mod array_metadata_options;
 mod array_representation;
+mod buffer_pool;
 mod bytes_representation;
 mod chunk_cache;
 pub mod chunk_grid;
 pub mod chunk_key_encoding;
 mod chunk_shape;
+mod chunk_sidecars;
+mod chunk_statistics;
+mod chunk_value_index;
 pub mod codec;
+mod codec_profile;
 pub mod concurrency;
 pub mod data_type;
 mod dimension_name;
 mod element;
 mod endianness;
 mod fill_value;
+pub mod lazy;
 mod nan_representations;
+mod output_regions;
+mod sparse_array_bytes;
 mod unsafe_cell_slice;
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "dlpack")]
+pub mod dlpack;
+#[cfg(feature = "image")]
+pub mod image;
+#[cfg(feature = "nalgebra")]
+pub mod nalgebra;
+#[cfg(feature = "npy")]
+pub mod npy;
+
+#[cfg(feature = "async")]
+mod cancellation_token;
+
 #[cfg(feature = "sharding")]
 mod array_sharded_ext;
 #[cfg(feature = "sharding")]
@@ -48,30 +70,52 @@ mod array_sync_sharded_readable_ext;
 
 use std::sync::Arc;
 
+pub(crate) use codec_profile::builtin_codec_profiles;
+
+#[cfg(feature = "async")]
+pub use self::{
+    array_async_write_guard::AsyncArrayWriteGuard, cancellation_token::CancellationToken,
+};
 pub use self::{
     array_builder::ArrayBuilder,
-    array_bytes::{ArrayBytes, ArrayBytesError, RawBytes, RawBytesOffsets},
-    array_errors::{ArrayCreateError, ArrayError},
+    array_bytes::{
+        raw_bytes_heap_size, raw_bytes_offsets_heap_size, ArrayBytes, ArrayBytesError, RawBytes,
+        RawBytesOffsets,
+    },
+    array_errors::{ArrayCreateError, ArrayError, ChunkError},
     array_metadata_options::ArrayMetadataOptions,
     array_representation::{ArrayRepresentation, ArraySize, ChunkRepresentation},
+    buffer_pool::BufferPool,
     bytes_representation::BytesRepresentation,
     chunk_grid::ChunkGrid,
     chunk_key_encoding::{ChunkKeyEncoding, ChunkKeySeparator},
     chunk_shape::{chunk_shape_to_array_shape, ChunkShape},
+    chunk_statistics::{ChunkStatistics, CHUNK_STATISTICS_PREFIX},
+    chunk_value_index::{ChunkValueIndex, CHUNK_VALUE_INDEX_PREFIX},
     codec::ArrayCodecTraits,
     codec::CodecChain,
-    concurrency::RecommendedConcurrency,
+    codec_profile::{
+        CodecProfile, CODEC_PROFILE_ARCHIVAL, CODEC_PROFILE_BALANCED, CODEC_PROFILE_FAST,
+        CODEC_PROFILE_IMAGING,
+    },
+    concurrency::{
+        AdaptiveConcurrency, ConcurrencyPlan, ConcurrencyStrategy, RecommendedConcurrency,
+    },
     data_type::{DataType, DataTypeSize},
     dimension_name::DimensionName,
-    element::{Element, ElementFixedLength, ElementOwned},
+    element::{Element, ElementFixedLength, ElementOwned, NativeBytesRepresentation},
     endianness::{Endianness, NATIVE_ENDIAN},
     fill_value::FillValue,
     nan_representations::{ZARR_NAN_BF16, ZARR_NAN_F16, ZARR_NAN_F32, ZARR_NAN_F64},
+    output_regions::{partition_output, OutputPartitionError, OutputRegion},
+    sparse_array_bytes::SparseArrayBytes,
     unsafe_cell_slice::UnsafeCellSlice,
 };
 pub use crate::metadata::v2::ArrayMetadataV2;
 pub use crate::metadata::v3::{fill_value::FillValueMetadata, ArrayMetadataV3};
 pub use crate::metadata::ArrayMetadata;
+#[cfg(feature = "derive")]
+pub use zarrs_derive::ZarrsElement;
 
 pub use chunk_cache::array_chunk_cache_sync_readable_ext::ArrayChunkCacheExt;
 pub use chunk_cache::{
@@ -88,11 +132,20 @@ pub use array_sync_sharded_readable_ext::{ArrayShardedReadableExt, ArrayShardedR
 use serde::Serialize;
 use thiserror::Error;
 
+use codec::{ArrayToBytesCodecTraits, CodecOptions};
+use concurrency::concurrency_chunks_and_codec;
+
 use crate::{
     array_subset::{ArraySubset, IncompatibleDimensionalityError},
-    metadata::{array_metadata_v2_to_v3, AdditionalFields, MetadataConvertVersion},
+    metadata::{
+        array_metadata_v2_to_v3, AdditionalFields, MetadataConvertVersion, MetadataDiagnostic,
+        MetadataValidationMode,
+    },
     node::NodePath,
-    storage::{data_key, storage_transformer::StorageTransformerChain, StoreKey},
+    storage::{
+        data_key, meta_key, meta_key_v2_array, meta_key_v2_attributes,
+        storage_transformer::StorageTransformerChain, StoreKey, StoreValueVersion,
+    },
 };
 
 /// An ND index to an element in an array.
@@ -267,11 +320,19 @@ pub struct NonZeroError;
 /// This crate is async runtime-agnostic.
 /// Async methods do not spawn tasks internally, so asynchronous storage calls are concurrent but not parallel.
 /// Codec encoding and decoding operations still execute in parallel (where supported) in an asynchronous context.
+/// By default, chunks are encoded and decoded inline, which can stall the async runtime with CPU intensive codecs.
+/// An `AsyncCodecExecutor` set with `CodecOptions::set_async_codec_executor` can move this work to a blocking thread pool.
 ///
 /// Due the lack of parallelism, methods like [`async_retrieve_array_subset`](Array::async_retrieve_array_subset) or [`async_retrieve_chunks`](Array::async_retrieve_chunks) do not parallelise over chunks and can be slow compared to the sync API.
 /// Parallelism over chunks can be achieved by spawning tasks outside of `zarrs`.
 /// A crate like [`async-scoped`](https://crates.io/crates/async-scoped) can enable spawning non-`'static` futures.
 /// If executing many tasks concurrently, consider reducing the codec [`concurrent_target`](crate::array::codec::CodecOptions::set_concurrent_target).
+///
+/// ### Async Cancellation
+/// Dropping the future of an async method aborts its pending store requests.
+/// Async methods can also be cancelled with a `CancellationToken` set with `CodecOptions::set_cancellation_token`, in which case they return [`ArrayError::Cancelled`].
+/// Each chunk is written with a single store request, but chunks written before cancellation are not reverted.
+/// An `AsyncArrayWriteGuard` records the chunks written by multi-chunk stores so that they can be inspected or rolled back.
 #[derive(Debug)]
 pub struct Array<TStorage: ?Sized> {
     /// The storage (including storage transformers).
@@ -289,7 +350,7 @@ pub struct Array<TStorage: ?Sized> {
     /// Provides an element value to use for uninitialised portions of the Zarr array. It encodes the underlying data type.
     fill_value: FillValue,
     /// Specifies a list of codecs to be used for encoding and decoding chunks.
-    codecs: CodecChain,
+    codecs: Arc<CodecChain>,
     // /// Optional user defined attributes.
     // attributes: serde_json::Map<String, serde_json::Value>,
     /// An optional list of storage transformers.
@@ -300,12 +361,33 @@ pub struct Array<TStorage: ?Sized> {
     // additional_fields: AdditionalFields,
     /// Metadata used to create the array
     metadata: ArrayMetadata,
+    /// The versions of the metadata keys in the store when the metadata was last read or written, [`None`] if absent.
+    metadata_versions: Vec<(StoreKey, Option<StoreValueVersion>)>,
+    /// Warnings recorded when coercing malformed metadata.
+    metadata_diagnostics: Vec<MetadataDiagnostic>,
+    /// An optional shard index cache and the sharding codec of the array it applies to.
+    #[cfg(feature = "sharding")]
+    shard_index_cache: Option<(codec::ShardIndexCache, Arc<codec::ShardingCodec>)>,
+    /// An optional chunk value index maintained on write.
+    chunk_value_index: Option<ChunkValueIndex>,
+    /// Whether chunk statistics are maintained on write.
+    chunk_statistics: bool,
+}
+
+/// Returns the store keys of array metadata.
+pub(crate) fn metadata_keys(path: &NodePath, metadata: &ArrayMetadata) -> Vec<StoreKey> {
+    match metadata {
+        ArrayMetadata::V3(_) => vec![meta_key(path)],
+        ArrayMetadata::V2(_) => vec![meta_key_v2_array(path), meta_key_v2_attributes(path)],
+    }
 }
 
 impl<TStorage: ?Sized> Array<TStorage> {
     /// Create an array in `storage` at `path` with `metadata`.
     /// This does **not** write to the store, use [`store_metadata`](Array<WritableStorageTraits>::store_metadata) to write `metadata` to `storage`.
     ///
+    /// Malformed metadata is rejected or coerced depending on the global [metadata validation mode](crate::config::Config#metadata-validation-mode).
+    ///
     /// # Errors
     /// Returns [`ArrayCreateError`] if:
     ///  - any metadata is invalid or,
@@ -314,6 +396,22 @@ impl<TStorage: ?Sized> Array<TStorage> {
         storage: Arc<TStorage>,
         path: &str,
         metadata: ArrayMetadata,
+    ) -> Result<Self, ArrayCreateError> {
+        Self::new_with_metadata_opt(storage, path, metadata, &MetadataValidationMode::default())
+    }
+
+    /// Create an array in `storage` at `path` with `metadata` and a non-default [`MetadataValidationMode`].
+    /// This does **not** write to the store, use [`store_metadata`](Array<WritableStorageTraits>::store_metadata) to write `metadata` to `storage`.
+    ///
+    /// # Errors
+    /// Returns [`ArrayCreateError`] if:
+    ///  - any metadata is invalid or,
+    ///  - a plugin (e.g. data type/chunk grid/chunk key encoding/codec/storage transformer) is invalid.
+    pub fn new_with_metadata_opt(
+        storage: Arc<TStorage>,
+        path: &str,
+        metadata: ArrayMetadata,
+        validation_mode: &MetadataValidationMode,
     ) -> Result<Self, ArrayCreateError> {
         let path = NodePath::new(path)?;
 
@@ -334,25 +432,66 @@ impl<TStorage: ?Sized> Array<TStorage> {
                 metadata_v3.shape.len(),
             ));
         }
-        let fill_value = data_type
-            .fill_value_from_metadata(&metadata_v3.fill_value)
-            .map_err(ArrayCreateError::InvalidFillValueMetadata)?;
-        let codecs = CodecChain::from_metadata(&metadata_v3.codecs)
-            .map_err(ArrayCreateError::CodecsCreateError)?;
+        let mut metadata_diagnostics = Vec::new();
+        let fill_value = match data_type.fill_value_from_metadata(&metadata_v3.fill_value) {
+            Ok(fill_value) => fill_value,
+            Err(err) => match validation_mode {
+                MetadataValidationMode::Strict => {
+                    return Err(ArrayCreateError::InvalidFillValueMetadata(err));
+                }
+                MetadataValidationMode::Lenient => {
+                    let fill_value =
+                        data_type.fill_value_from_metadata_lenient(&metadata_v3.fill_value);
+                    metadata_diagnostics.push(MetadataDiagnostic::warning(
+                        "/fill_value",
+                        format!(
+                            "{err}, coerced to {}",
+                            data_type.metadata_fill_value(&fill_value)
+                        ),
+                    ));
+                    fill_value
+                }
+            },
+        };
+        let codecs = Arc::new(
+            CodecChain::from_metadata(&metadata_v3.codecs)
+                .map_err(ArrayCreateError::CodecsCreateError)?,
+        );
         let storage_transformers =
             StorageTransformerChain::from_metadata(&metadata_v3.storage_transformers)
                 .map_err(ArrayCreateError::StorageTransformersCreateError)?;
         let chunk_key_encoding = ChunkKeyEncoding::from_metadata(&metadata_v3.chunk_key_encoding)
             .map_err(ArrayCreateError::ChunkKeyEncodingCreateError)?;
-        if let Some(dimension_names) = &metadata_v3.dimension_names {
-            if dimension_names.len() != metadata_v3.shape.len() {
-                return Err(ArrayCreateError::InvalidDimensionNames(
-                    dimension_names.len(),
-                    metadata_v3.shape.len(),
-                ));
+        let mut dimension_names = metadata_v3.dimension_names;
+        if let Some(names) = &dimension_names {
+            if names.len() != metadata_v3.shape.len() {
+                match validation_mode {
+                    MetadataValidationMode::Strict => {
+                        return Err(ArrayCreateError::InvalidDimensionNames(
+                            names.len(),
+                            metadata_v3.shape.len(),
+                        ));
+                    }
+                    MetadataValidationMode::Lenient => {
+                        metadata_diagnostics.push(MetadataDiagnostic::warning(
+                            "/dimension_names",
+                            format!(
+                                "got {} dimension names for an array with {} dimensions, dropped dimension names",
+                                names.len(),
+                                metadata_v3.shape.len()
+                            ),
+                        ));
+                        dimension_names = None;
+                    }
+                }
             }
         }
 
+        let metadata_versions = metadata_keys(&path, &metadata)
+            .into_iter()
+            .map(|key| (key, None))
+            .collect();
+
         Ok(Self {
             storage,
             path,
@@ -365,11 +504,45 @@ impl<TStorage: ?Sized> Array<TStorage> {
             // attributes: metadata_v3.attributes,
             // additional_fields: metadata_v3.additional_fields,
             storage_transformers,
-            dimension_names: metadata_v3.dimension_names,
+            dimension_names,
+            metadata_versions,
+            metadata_diagnostics,
             metadata,
+            #[cfg(feature = "sharding")]
+            shard_index_cache: None,
+            chunk_value_index: None,
+            chunk_statistics: false,
         })
     }
 
+    /// Invalidate the cached shard index of the chunk at `chunk_indices`.
+    #[cfg_attr(
+        not(feature = "sharding"),
+        allow(unused_variables, clippy::unused_self)
+    )]
+    fn invalidate_shard_index(&self, chunk_indices: &[u64]) {
+        #[cfg(feature = "sharding")]
+        if let Some((shard_index_cache, _)) = &self.shard_index_cache {
+            shard_index_cache.invalidate(chunk_indices);
+        }
+    }
+
+    /// Set the versions of the metadata keys in the store.
+    pub(crate) fn set_metadata_versions(
+        &mut self,
+        metadata_versions: Vec<(StoreKey, Option<StoreValueVersion>)>,
+    ) {
+        self.metadata_versions = metadata_versions;
+    }
+
+    /// Get the warnings recorded when coercing malformed metadata.
+    ///
+    /// This is empty unless the array was created or opened with the [lenient](crate::metadata::MetadataValidationMode::Lenient) metadata validation mode.
+    #[must_use]
+    pub fn metadata_diagnostics(&self) -> &[MetadataDiagnostic] {
+        &self.metadata_diagnostics
+    }
+
     /// Get the node path.
     #[must_use]
     pub const fn path(&self) -> &NodePath {
@@ -417,7 +590,7 @@ impl<TStorage: ?Sized> Array<TStorage> {
 
     /// Get the codecs.
     #[must_use]
-    pub const fn codecs(&self) -> &CodecChain {
+    pub fn codecs(&self) -> &CodecChain {
         &self.codecs
     }
 
@@ -454,6 +627,45 @@ impl<TStorage: ?Sized> Array<TStorage> {
         self
     }
 
+    /// Get the shard index cache.
+    #[cfg(feature = "sharding")]
+    #[must_use]
+    pub fn shard_index_cache(&self) -> Option<&codec::ShardIndexCache> {
+        self.shard_index_cache.as_ref().map(|(cache, _)| cache)
+    }
+
+    /// Set the shard index cache.
+    ///
+    /// Partial decoders of the array reuse decoded shard indexes in the cache rather than retrieving and decoding the index of a shard for every partial read.
+    /// The cache is only used if the array to bytes codec is `sharding_indexed` and there are no array to array or bytes to bytes codecs.
+    /// Entries are invalidated when this array stores or erases a shard, but not if a shard is modified through another [`Array`] or by another process.
+    #[cfg(feature = "sharding")]
+    pub fn set_shard_index_cache(
+        &mut self,
+        shard_index_cache: Option<codec::ShardIndexCache>,
+    ) -> &mut Self {
+        self.shard_index_cache = shard_index_cache.and_then(|shard_index_cache| {
+            Some((shard_index_cache, Arc::new(self.sharding_codec()?)))
+        });
+        self
+    }
+
+    /// Return the `sharding_indexed` codec of the array if it is the array to bytes codec and there are no array to array or bytes to bytes codecs.
+    #[cfg(feature = "sharding")]
+    fn sharding_codec(&self) -> Option<codec::ShardingCodec> {
+        if !self.codecs.array_to_array_codecs().is_empty()
+            || !self.codecs.bytes_to_bytes_codecs().is_empty()
+        {
+            return None;
+        }
+        let codec_metadata = self.codecs.array_to_bytes_codec().create_metadata()?;
+        if codec_metadata.name() != codec::array_to_bytes::sharding::IDENTIFIER {
+            return None;
+        }
+        let configuration = codec_metadata.to_configuration().ok()?;
+        codec::ShardingCodec::new_with_configuration(&configuration).ok()
+    }
+
     /// Get the attributes.
     #[must_use]
     pub const fn attributes(&self) -> &serde_json::Map<String, serde_json::Value> {
@@ -568,6 +780,11 @@ impl<TStorage: ?Sized> Array<TStorage> {
         data_key(self.path(), chunk_indices, self.chunk_key_encoding())
     }
 
+    /// Add the context of the chunk at `chunk_indices` to `error`.
+    pub(crate) fn chunk_error(&self, chunk_indices: &[u64], error: ArrayError) -> ArrayError {
+        error.with_chunk_context(chunk_indices, self.chunk_key(chunk_indices))
+    }
+
     /// Return the origin of the chunk at `chunk_indices`.
     ///
     /// # Errors
@@ -692,6 +909,128 @@ impl<TStorage: ?Sized> Array<TStorage> {
             .chunks_in_array_subset(array_subset, self.shape())
     }
 
+    /// Plan the chunk and codec concurrency of retrieving or storing `array_subset` with `options`.
+    ///
+    /// The plan matches that used by [`retrieve_array_subset_opt`](Array::retrieve_array_subset_opt) and related methods.
+    /// It is deterministic for a given array, subset and options, unless `options` has an adaptive [`ConcurrencyStrategy`] that has recorded measurements.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if `array_subset` is incompatible with the array shape or dimensionality.
+    pub fn concurrency_plan(
+        &self,
+        array_subset: &ArraySubset,
+        options: &CodecOptions,
+    ) -> Result<ConcurrencyPlan, ArrayError> {
+        let chunks = if array_subset.dimensionality() == self.dimensionality() {
+            self.chunks_in_array_subset(array_subset)?
+        } else {
+            None
+        };
+        let Some(chunks) = chunks else {
+            return Err(ArrayError::InvalidArraySubset(
+                array_subset.clone(),
+                self.shape().to_vec(),
+            ));
+        };
+        let num_chunks = chunks.num_elements_usize();
+        let output_size = ArraySize::new(self.data_type().size(), array_subset.num_elements());
+        if num_chunks == 0 {
+            return Ok(ConcurrencyPlan::new(
+                0,
+                options.concurrent_target(),
+                output_size.fixed_size().map(|size| size as u64),
+            ));
+        }
+
+        let chunk_representation =
+            self.chunk_array_representation(&vec![0; self.dimensionality()])?;
+        let (chunk_concurrent_limit, codec_concurrent_target) = if num_chunks == 1 {
+            self.memory_limited_chunk_concurrency(
+                1,
+                &chunk_representation,
+                output_size.fixed_size().map_or(0, |size| size as u64),
+                options,
+            )?;
+            (1, options.concurrent_target())
+        } else {
+            let codec_concurrency = self.recommended_codec_concurrency(&chunk_representation)?;
+            let (chunk_concurrent_limit, codec_options) = concurrency_chunks_and_codec(
+                options.concurrent_target(),
+                num_chunks,
+                options,
+                &codec_concurrency,
+            );
+            let chunk_concurrent_limit = self.memory_limited_chunk_concurrency(
+                chunk_concurrent_limit,
+                &chunk_representation,
+                output_size.fixed_size().map_or(0, |size| size as u64),
+                options,
+            )?;
+            (
+                std::cmp::min(chunk_concurrent_limit, num_chunks),
+                codec_options.concurrent_target(),
+            )
+        };
+
+        let decoded_chunk_size = chunk_representation.fixed_size().map(|size| size as u64);
+        let encoded_chunk_size = self
+            .codecs()
+            .compute_encoded_size(&chunk_representation)?
+            .size();
+        let peak_memory = output_size
+            .fixed_size()
+            .zip(decoded_chunk_size)
+            .zip(encoded_chunk_size)
+            .map(|((output_size, decoded_chunk_size), encoded_chunk_size)| {
+                output_size as u64
+                    + chunk_concurrent_limit as u64 * (decoded_chunk_size + encoded_chunk_size)
+            });
+        Ok(ConcurrencyPlan::new(
+            chunk_concurrent_limit,
+            codec_concurrent_target,
+            peak_memory,
+        ))
+    }
+
+    /// Limit `chunk_concurrent_limit` so that an operation fits within the [memory limit](CodecOptions::memory_limit) of `options`.
+    ///
+    /// `output_size` is the size in bytes of the output allocated by the operation.
+    /// Each chunk in flight is assumed to hold its decoded and encoded bytes.
+    ///
+    /// # Errors
+    /// Returns [`ArrayError::MemoryLimitExceeded`] if the output and a single chunk exceed the memory limit.
+    pub(crate) fn memory_limited_chunk_concurrency(
+        &self,
+        chunk_concurrent_limit: usize,
+        chunk_representation: &ChunkRepresentation,
+        output_size: u64,
+        options: &CodecOptions,
+    ) -> Result<usize, ArrayError> {
+        let Some(memory_limit) = options.memory_limit() else {
+            return Ok(chunk_concurrent_limit);
+        };
+        let Some(decoded_chunk_size) = chunk_representation.fixed_size() else {
+            return Ok(chunk_concurrent_limit);
+        };
+        let encoded_chunk_size = self
+            .codecs()
+            .compute_encoded_size(chunk_representation)?
+            .size()
+            .unwrap_or_default();
+        let chunk_memory = decoded_chunk_size as u64 + encoded_chunk_size;
+        let memory_required = output_size + chunk_memory;
+        if memory_required > memory_limit {
+            return Err(ArrayError::MemoryLimitExceeded(
+                memory_required,
+                memory_limit,
+            ));
+        }
+        let max_concurrent_chunks = (memory_limit - output_size) / chunk_memory.max(1);
+        Ok(chunk_concurrent_limit
+            .min(usize::try_from(max_concurrent_chunks).unwrap_or(usize::MAX))
+            .max(1))
+    }
+
     /// Calculate the recommended codec concurrency.
     fn recommended_codec_concurrency(
         &self,
@@ -721,6 +1060,8 @@ mod array_sync_writable;
 
 mod array_sync_readable_writable;
 
+mod array_sync_partial_encoder;
+
 #[cfg(feature = "async")]
 mod array_async_readable;
 
@@ -730,6 +1071,9 @@ mod array_async_writable;
 #[cfg(feature = "async")]
 mod array_async_readable_writable;
 
+#[cfg(feature = "async")]
+mod array_async_write_guard;
+
 /// Transmute from `Vec<u8>` to `Vec<T>`.
 #[must_use]
 pub fn convert_from_bytes_slice<T: bytemuck::Pod>(from: &[u8]) -> Vec<T> {
@@ -862,90 +1206,1646 @@ mod tests {
     }
 
     #[test]
-    fn array_set_shape_and_attributes() {
-        let store = MemoryStore::new();
-        let array_path = "/group/array";
-        let mut array = ArrayBuilder::new(
-            vec![8, 8], // array shape
-            DataType::Float32,
+    fn array_concurrency_plan() {
+        let store = Arc::new(MemoryStore::new());
+        let array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt8,
             vec![4, 4].try_into().unwrap(),
-            FillValue::from(ZARR_NAN_F32),
+            FillValue::from(0u8),
         )
-        .bytes_to_bytes_codecs(vec![
-            #[cfg(feature = "gzip")]
-            Box::new(codec::GzipCodec::new(5).unwrap()),
-        ])
-        .build(store.into(), array_path)
+        .build(store, "/array")
         .unwrap();
+        let options = CodecOptions::builder()
+            .concurrent_target(4)
+            .concurrency_strategy(ConcurrencyStrategy::Static)
+            .build();
 
-        array.set_shape(vec![16, 16]);
+        let plan = array
+            .concurrency_plan(&ArraySubset::new_with_shape(vec![8, 8]), &options)
+            .unwrap();
+        assert!((1..=4).contains(&plan.chunk_concurrent_limit()));
+        assert!(plan.codec_concurrent_target() >= 1);
+        assert_eq!(
+            plan.peak_memory(),
+            Some(64 + plan.chunk_concurrent_limit() as u64 * (16 + 16))
+        );
+
+        let plan = array
+            .concurrency_plan(&ArraySubset::new_with_ranges(&[1..3, 1..3]), &options)
+            .unwrap();
+        assert_eq!(plan, ConcurrencyPlan::new(1, 4, Some(4 + 16 + 16)));
+
+        let plan = array
+            .concurrency_plan(&ArraySubset::new_with_shape(vec![0, 0]), &options)
+            .unwrap();
+        assert_eq!(plan, ConcurrencyPlan::new(0, 4, Some(0)));
+
+        assert!(array
+            .concurrency_plan(&ArraySubset::new_with_shape(vec![8]), &options)
+            .is_err());
+    }
+
+    #[test]
+    fn array_metadata_validation_mode() {
+        let store = Arc::new(MemoryStore::new());
+        let metadata: ArrayMetadataV3 = serde_json::from_str(
+            r#"{
+                "zarr_format": 3,
+                "node_type": "array",
+                "shape": [8, 8],
+                "data_type": "uint8",
+                "chunk_grid": {"name": "regular", "configuration": {"chunk_shape": [4, 4]}},
+                "chunk_key_encoding": {"name": "default"},
+                "fill_value": 300,
+                "codecs": [{"name": "bytes"}],
+                "dimension_names": ["x"]
+            }"#,
+        )
+        .unwrap();
+        let metadata = ArrayMetadata::V3(metadata);
+
+        assert!(matches!(
+            Array::new_with_metadata(store.clone(), "/array", metadata.clone()),
+            Err(ArrayCreateError::InvalidFillValueMetadata(_))
+        ));
+
+        let array = Array::new_with_metadata_opt(
+            store,
+            "/array",
+            metadata,
+            &MetadataValidationMode::Lenient,
+        )
+        .unwrap();
+        assert_eq!(array.fill_value(), &FillValue::from(u8::MAX));
+        assert_eq!(array.dimension_names(), &None);
+        let pointers: Vec<_> = array
+            .metadata_diagnostics()
+            .iter()
+            .map(MetadataDiagnostic::pointer)
+            .collect();
+        assert_eq!(pointers, vec!["/fill_value", "/dimension_names"]);
+    }
+
+    #[test]
+    fn array_memory_limit() {
+        let store = Arc::new(MemoryStore::new());
+        let array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt8,
+            vec![4, 4].try_into().unwrap(),
+            FillValue::from(0u8),
+        )
+        .build(store, "/array")
+        .unwrap();
+        let array_subset = ArraySubset::new_with_shape(vec![8, 8]);
         array
-            .attributes_mut()
-            .insert("test".to_string(), "apple".into());
+            .store_array_subset_elements::<u8>(&array_subset, &[1; 64])
+            .unwrap();
 
-        assert_eq!(array.shape(), &[16, 16]);
+        // The output and two chunks (decoded and encoded)
+        let options = CodecOptions::builder()
+            .concurrent_target(4)
+            .concurrency_strategy(ConcurrencyStrategy::Static)
+            .memory_limit(Some(64 + 2 * (16 + 16)))
+            .build();
+        let plan = array.concurrency_plan(&array_subset, &options).unwrap();
+        assert!((1..=2).contains(&plan.chunk_concurrent_limit()));
         assert_eq!(
-            array.attributes().get_key_value("test"),
-            Some((
-                &"test".to_string(),
-                &serde_json::Value::String("apple".to_string())
-            ))
+            array
+                .retrieve_array_subset_elements_opt::<u8>(&array_subset, &options)
+                .unwrap(),
+            vec![1; 64]
         );
+        array
+            .store_array_subset_elements_opt::<u8>(&array_subset, &[2; 64], &options)
+            .unwrap();
+
+        // The output and less than one chunk
+        let options = CodecOptions::builder().memory_limit(Some(64 + 16)).build();
+        assert!(matches!(
+            array.retrieve_array_subset_elements_opt::<u8>(&array_subset, &options),
+            Err(ArrayError::MemoryLimitExceeded(96, 80))
+        ));
+        assert!(matches!(
+            array.concurrency_plan(&array_subset, &options),
+            Err(ArrayError::MemoryLimitExceeded(96, 80))
+        ));
     }
 
     #[test]
-    fn array_subset_round_trip() {
-        let store = Arc::new(MemoryStore::default());
+    fn array_metadata_stale() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
         let array_path = "/array";
+        let mut array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt8,
+            vec![4, 4].try_into()?,
+            FillValue::from(0u8),
+        )
+        .build(store.clone(), array_path)?;
+        assert!(!array.metadata_is_stale()?);
+        array.store_metadata_if_unchanged()?;
+        assert!(!array.metadata_is_stale()?);
+
+        let mut array_other = Array::open(store.clone(), array_path)?;
+        assert!(!array_other.metadata_is_stale()?);
+        array_other
+            .attributes_mut()
+            .insert("key".to_string(), "value".into());
+        array_other.store_metadata_if_unchanged()?;
+
+        assert!(array.metadata_is_stale()?);
+        array.attributes_mut().insert("key".to_string(), 0.into());
+        assert!(matches!(
+            array.store_metadata_if_unchanged(),
+            Err(crate::storage::StorageError::VersionMismatch(_))
+        ));
+
+        array.refresh_metadata()?;
+        assert!(!array.metadata_is_stale()?);
+        assert_eq!(array.attributes().get("key"), Some(&"value".into()));
+        array.attributes_mut().insert("key".to_string(), 0.into());
+        array.store_metadata_if_unchanged()?;
+        assert!(array_other.metadata_is_stale()?);
+        Ok(())
+    }
+
+    #[cfg(feature = "crc32c")]
+    #[test]
+    fn array_error_chunk_context() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::storage::WritableStorageTraits;
+
+        let store = Arc::new(MemoryStore::new());
         let array = ArrayBuilder::new(
-            vec![8, 8], // array shape
-            DataType::Float32,
-            vec![4, 4].try_into().unwrap(), // regular chunk shape
-            FillValue::from(1f32),
+            vec![8, 8],
+            DataType::UInt8,
+            vec![4, 4].try_into()?,
+            FillValue::from(0u8),
+        )
+        .bytes_to_bytes_codecs(vec![Box::new(codec::Crc32cCodec::new())])
+        .build(store.clone(), "/array")?;
+        array.store_array_subset_elements::<u8>(
+            &ArraySubset::new_with_shape(vec![8, 8]),
+            &[1; 64],
+        )?;
+
+        // Corrupt the checksum of one chunk
+        let key = array.chunk_key(&[1, 0]);
+        store.set(&key, vec![1; 20].into())?;
+
+        let err = array
+            .retrieve_array_subset(&ArraySubset::new_with_shape(vec![8, 8]))
+            .unwrap_err();
+        assert_eq!(err.chunk_indices(), Some([1, 0].as_slice()));
+        assert_eq!(err.store_key(), Some(&key));
+        let ArrayError::ChunkError(chunk_error) = &err else {
+            panic!("expected a chunk error, got {err}");
+        };
+        assert!(matches!(
+            chunk_error.error(),
+            ArrayError::CodecError(codec::CodecError::InvalidChecksum)
+        ));
+        assert_eq!(
+            err.to_string(),
+            "chunk [1, 0] with key array/c/1/0: the checksum is invalid"
+        );
+
+        // Single chunk operations are not wrapped
+        assert!(matches!(
+            array.retrieve_chunk(&[1, 0]),
+            Err(ArrayError::CodecError(codec::CodecError::InvalidChecksum))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn array_adaptive_concurrency() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        let array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt8,
+            vec![4, 4].try_into()?,
+            FillValue::from(0u8),
+        )
+        .build(store, "/array")?;
+
+        let adaptive = AdaptiveConcurrency::new();
+        let options = codec::CodecOptions::builder()
+            .concurrency_strategy(ConcurrencyStrategy::Adaptive(adaptive.clone()))
+            .build();
+        let subset = ArraySubset::new_with_ranges(&[0..8, 0..4]);
+        let elements: Vec<u8> = (0..32).collect();
+        array.store_array_subset_elements_opt(&subset, &elements, &options)?;
+        assert!(adaptive.storage_latency().is_some());
+        assert!(adaptive.codec_time().is_some());
+
+        adaptive.reset();
+        assert_eq!(
+            array.retrieve_array_subset_elements_opt::<u8>(&subset, &options)?,
+            elements
+        );
+        assert!(adaptive.storage_latency().is_some());
+        assert!(adaptive.codec_time().is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn array_buffer_pool() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        let array = ArrayBuilder::new(
+            vec![256, 256],
+            DataType::UInt16,
+            vec![64, 64].try_into()?,
+            FillValue::from(0u16),
         )
         .bytes_to_bytes_codecs(vec![
             #[cfg(feature = "gzip")]
-            Box::new(codec::GzipCodec::new(5).unwrap()),
+            Box::new(codec::GzipCodec::new(5)?),
         ])
-        // .storage_transformers(vec![].into())
-        .build(store, array_path)
-        .unwrap();
+        .build(store, "/array")?;
+
+        let buffer_pool = BufferPool::new(16 * 1024 * 1024);
+        let options = codec::CodecOptions::builder()
+            .buffer_pool(Some(buffer_pool.clone()))
+            .build();
+        let elements: Vec<u16> = (0..=u16::MAX).map(|i| i % 1000).collect();
+        array.store_array_subset_elements_opt(
+            &ArraySubset::new_with_shape(vec![256, 256]),
+            &elements,
+            &options,
+        )?;
+
+        let subset = ArraySubset::new_with_ranges(&[10..250, 20..230]);
+        let expected = array.retrieve_array_subset_elements::<u16>(&subset)?;
+        for _ in 0..2 {
+            assert_eq!(
+                array.retrieve_array_subset_elements_opt::<u16>(&subset, &options)?,
+                expected
+            );
+            assert!(!buffer_pool.is_empty());
+        }
+        Ok(())
+    }
 
-        array
-            .store_array_subset_elements::<f32>(
-                &ArraySubset::new_with_ranges(&[3..6, 3..6]),
-                &[1.0, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9],
-            )
-            .unwrap();
+    #[test]
+    fn array_retrieve_bytes() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        let array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt8,
+            vec![4, 4].try_into()?,
+            FillValue::from(0u8),
+        )
+        .build(store.clone(), "/array")?;
+        let elements: Vec<u8> = (0..64).collect();
+        array.store_array_subset_elements(&ArraySubset::new_with_shape(vec![8, 8]), &elements)?;
 
-        let subset_all = ArraySubset::new_with_shape(array.shape().to_vec());
-        let data_all = array
-            .retrieve_array_subset_elements::<f32>(&subset_all)
-            .unwrap();
+        let chunk = array.retrieve_chunk_bytes(&[0, 1])?;
         assert_eq!(
-            data_all,
-            vec![
-                //     (0,0)       |     (0, 1)
-                //0  1    2    3   |4    5    6    7
-                1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, // 0
-                1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, // 1
-                1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, // 2
-                1.0, 1.0, 1.0, 1.0, 0.2, 0.3, 1.0, 1.0, //_3____________
-                1.0, 1.0, 1.0, 0.4, 0.5, 0.6, 1.0, 1.0, // 4
-                1.0, 1.0, 1.0, 0.7, 0.8, 0.9, 1.0, 1.0, // 5 (1, 1)
-                1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, // 6
-                1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, // 7
-            ]
+            chunk.as_ref(),
+            array.retrieve_chunk(&[0, 1])?.into_fixed()?.as_ref()
         );
-        assert!(array
-            .retrieve_chunk_elements_if_exists::<f32>(&[0; 2])
-            .unwrap()
-            .is_none());
-        #[cfg(feature = "ndarray")]
-        assert!(array
-            .retrieve_chunk_ndarray_if_exists::<f32>(&[0; 2])
-            .unwrap()
-            .is_none());
+        assert_eq!(chunk.slice(0..4).as_ref(), &[4, 5, 6, 7]);
+
+        let subset = ArraySubset::new_with_ranges(&[1..3, 3..6]);
+        assert_eq!(
+            array.retrieve_array_subset_bytes(&subset)?.as_ref(),
+            &[11, 12, 13, 19, 20, 21]
+        );
+
+        assert_eq!(
+            array.retrieve_encoded_chunk_bytes(&[0, 1])?.as_deref(),
+            array.retrieve_encoded_chunk(&[0, 1])?.as_deref()
+        );
+        assert!(array.retrieve_encoded_chunk_bytes(&[5, 5])?.is_none());
+
+        let array = ArrayBuilder::new(
+            vec![8],
+            DataType::String,
+            vec![4].try_into()?,
+            FillValue::from(""),
+        )
+        .build(store, "/array_string")?;
+        assert!(array.retrieve_chunk_bytes(&[0]).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "sharding")]
+    #[test]
+    fn array_shard_coalesce_gap() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::storage::{
+            storage_transformer::{
+                PerformanceMetricsStorageTransformer, StorageTransformerExtension,
+            },
+            ReadableWritableStorage,
+        };
+
+        let performance_metrics = Arc::new(PerformanceMetricsStorageTransformer::new());
+        let store: ReadableWritableStorage = performance_metrics
+            .clone()
+            .create_readable_writable_transformer(Arc::new(MemoryStore::new()));
+        let array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt16,
+            vec![8, 8].try_into()?,
+            FillValue::from(0u16),
+        )
+        .array_to_bytes_codec(Box::new(
+            codec::array_to_bytes::sharding::ShardingCodecBuilder::new(vec![2, 2].try_into()?)
+                .build(),
+        ))
+        .build(store, "/array")?;
+        let elements: Vec<u16> = (0..64).map(|i| if i % 3 == 0 { 0 } else { i }).collect();
+        array.store_array_subset_elements(&ArraySubset::new_with_shape(vec![8, 8]), &elements)?;
+
+        let subset = ArraySubset::new_with_ranges(&[0..8, 1..7]);
+        let expected = array.retrieve_array_subset_elements::<u16>(&subset)?;
+        let mut reads = Vec::new();
+        for shard_coalesce_gap in [None, Some(0)] {
+            let options = codec::CodecOptions::builder()
+                .shard_coalesce_gap(shard_coalesce_gap)
+                .build();
+            let reads_start = performance_metrics.reads();
+            assert_eq!(
+                array.retrieve_array_subset_elements_opt::<u16>(&subset, &options)?,
+                expected
+            );
+            reads.push(performance_metrics.reads() - reads_start);
+        }
+        // The shard index and at least one request per inner chunk, or the shard index and one coalesced request
+        assert!(reads[0] > 16);
+        assert_eq!(reads[1], 2);
+        Ok(())
+    }
+
+    #[test]
+    fn array_chunk_sparse() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::storage::ListableStorageTraits;
+
+        let store = Arc::new(MemoryStore::new());
+        let array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt16,
+            vec![4, 4].try_into()?,
+            FillValue::from(0u16),
+        )
+        .array_to_bytes_codec(Box::new(codec::SparseCodec::new()))
+        .build(store.clone(), "/array")?;
+
+        let sparse =
+            SparseArrayBytes::new(16, 2, vec![1, 15], transmute_to_bytes_vec(vec![5u16, 7]))?;
+        array.store_chunk_sparse(&[0, 1], &sparse)?;
+        assert_eq!(array.retrieve_chunk_sparse(&[0, 1])?, sparse);
+        assert_eq!(
+            array.retrieve_array_subset_elements::<u16>(&ArraySubset::new_with_ranges(&[
+                0..1,
+                4..6
+            ]))?,
+            vec![0, 5]
+        );
+        assert!(array.retrieve_chunk_sparse(&[1, 1])?.is_fill_value());
+        assert!(array
+            .store_chunk_sparse(&[0, 0], &SparseArrayBytes::new_fill_value(4, 2))
+            .is_err());
+
+        // A chunk composed entirely of the fill value is erased
+        array.store_chunk_sparse(&[0, 1], &SparseArrayBytes::new_fill_value(16, 2))?;
+        assert!(store.list()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn array_chunk_value_index() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::storage::ListableStorageTraits;
+
+        let store = Arc::new(MemoryStore::new());
+        let mut array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt16,
+            vec![4, 4].try_into()?,
+            FillValue::from(0u16),
+        )
+        .build(store.clone(), "/array")?;
+        array.set_chunk_value_index(Some(ChunkValueIndex::Exact));
+
+        array.store_chunk_elements(&[0, 0], &[3u16; 16])?;
+        let mut elements = vec![0u16; 16];
+        elements[5] = 7;
+        array.store_chunk_elements(&[1, 1], &elements)?;
+        array.store_chunk_sparse(
+            &[0, 1],
+            &SparseArrayBytes::new(16, 2, vec![2], transmute_to_bytes_vec(vec![7u16]))?,
+        )?;
+        assert_eq!(array.chunks_containing(&3u16)?, vec![vec![0, 0]]);
+        assert_eq!(
+            array.chunks_containing(&7u16)?,
+            vec![vec![0, 1], vec![1, 1]]
+        );
+        assert_eq!(
+            array.chunks_containing(&0u16)?,
+            vec![vec![0, 1], vec![1, 1]]
+        );
+        assert!(array.chunks_containing(&4u16)?.is_empty());
+        assert!(array.chunks_containing(&4u8).is_err());
+
+        // Erasing a chunk erases its index
+        array.erase_chunk(&[1, 1])?;
+        assert_eq!(array.chunks_containing(&7u16)?, vec![vec![0, 1]]);
+
+        // A bloom filter may have false positives, but never false negatives
+        array.set_chunk_value_index(Some(ChunkValueIndex::BloomFilter {
+            num_bits: 256,
+            num_hashes: 3,
+        }));
+        array.store_chunk_elements(&[1, 0], &[9u16; 16])?;
+        assert!(array.chunks_containing(&9u16)?.contains(&vec![1, 0]));
+
+        // The index is not maintained if unset
+        array.set_chunk_value_index(None);
+        array.erase_chunks(&ArraySubset::new_with_shape(vec![2, 2]))?;
+        assert_eq!(store.list()?.len(), 3);
+        assert_eq!(array.chunks_containing(&3u16)?, vec![vec![0, 0]]);
+        Ok(())
+    }
+
+    #[test]
+    fn array_chunk_statistics() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        let mut array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::Float32,
+            vec![4, 4].try_into()?,
+            FillValue::from(0.0f32),
+        )
+        .build(store.clone(), "/array")?;
+        array.set_chunk_statistics_enabled(true);
+
+        array.store_chunk_elements(&[0, 0], &(0..16u8).map(f32::from).collect::<Vec<_>>())?;
+        array.store_chunk_elements(&[0, 1], &[-1.0f32; 16])?;
+        array.store_chunk_sparse(
+            &[1, 1],
+            &SparseArrayBytes::new(16, 4, vec![3], transmute_to_bytes_vec(vec![100.0f32]))?,
+        )?;
+
+        let statistics = array.retrieve_chunk_statistics(&[1, 1])?.unwrap();
+        assert_eq!(statistics.num_elements(), 16);
+        assert_eq!(statistics.num_fill_value(), 15);
+        assert_eq!(statistics.num_nan(), 0);
+        assert_eq!(
+            statistics.min_max::<f32>(array.data_type())?,
+            Some((0.0, 100.0))
+        );
+        assert!(array.retrieve_chunk_statistics(&[1, 0])?.is_none());
+
+        assert_eq!(array.chunks_where(10.0f32..)?, vec![vec![0, 0], vec![1, 1]]);
+        assert_eq!(array.chunks_where(..0.0f32)?, vec![vec![0, 1]]);
+        assert_eq!(array.chunks_where(100.5f32..)?, Vec::<Vec<u64>>::new());
+        assert!(array.chunks_where(0u8..).is_err());
+
+        // Erasing a chunk erases its statistics
+        array.erase_chunk(&[0, 0])?;
+        assert_eq!(array.chunks_where(10.0f32..)?, vec![vec![1, 1]]);
+
+        // Statistics are not supported for all data types
+        let mut array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::String,
+            vec![4, 4].try_into()?,
+            FillValue::from(""),
+        )
+        .build(store, "/string")?;
+        array.set_chunk_statistics_enabled(true);
+        assert!(!array.chunk_statistics_enabled());
+        Ok(())
+    }
+
+    #[cfg(feature = "sharding")]
+    #[test]
+    fn array_shard_index_cache() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        let mut array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt16,
+            vec![4, 4].try_into()?,
+            FillValue::from(0u16),
+        )
+        .array_to_bytes_codec(Box::new(
+            codec::array_to_bytes::sharding::ShardingCodecBuilder::new(vec![2, 2].try_into()?)
+                .build(),
+        ))
+        .build(store, "/array")?;
+        let elements: Vec<u16> = (0..64).collect();
+        array.store_array_subset_elements(&ArraySubset::new_with_shape(vec![8, 8]), &elements)?;
+
+        let shard_index_cache = codec::ShardIndexCache::new(1024 * 1024);
+        array.set_shard_index_cache(Some(shard_index_cache.clone()));
+        assert!(array.shard_index_cache().is_some());
+        assert!(shard_index_cache.is_empty());
+
+        let subset = ArraySubset::new_with_ranges(&[1..3, 3..6]);
+        let expected: Vec<u16> = vec![11, 12, 13, 19, 20, 21];
+        for _ in 0..2 {
+            assert_eq!(
+                array.retrieve_array_subset_elements::<u16>(&subset)?,
+                expected
+            );
+        }
+        // The indexes of shards [0, 0] and [0, 1] are cached (4 inner chunks * offset/size)
+        assert_eq!(shard_index_cache.len(), 2);
+        assert_eq!(shard_index_cache.size(), 2 * 4 * 2 * size_of::<u64>());
+
+        // Missing shards are cached
+        array.erase_chunk(&[1, 1])?;
+        assert_eq!(
+            array.retrieve_chunk_subset_elements::<u16>(
+                &[1, 1],
+                &ArraySubset::new_with_ranges(&[0..2, 0..2])
+            )?,
+            vec![0; 4]
+        );
+        assert_eq!(shard_index_cache.get(&[1, 1]), Some(None));
+
+        // Storing a shard invalidates its cached index
+        array.store_chunk_elements::<u16>(&[0, 1], &[1; 16])?;
+        assert_eq!(shard_index_cache.get(&[0, 1]), None);
+        assert_eq!(
+            array.retrieve_array_subset_elements::<u16>(&subset)?,
+            vec![11, 1, 1, 19, 1, 1]
+        );
+        array.store_chunk_elements::<u16>(&[1, 1], &[2; 16])?;
+        assert_eq!(shard_index_cache.get(&[1, 1]), None);
+
+        // The cache is not used by an unsharded array
+        let mut array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt16,
+            vec![4, 4].try_into()?,
+            FillValue::from(0u16),
+        )
+        .build(Arc::new(MemoryStore::new()), "/array")?;
+        array.set_shard_index_cache(Some(shard_index_cache));
+        assert!(array.shard_index_cache().is_none());
+        Ok(())
+    }
+
+    #[cfg(feature = "sharding")]
+    #[test]
+    fn array_store_chunk_subset_partial_encoder() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::{array::codec::CodecTraits, storage::ReadableStorageTraits};
+
+        let store = Arc::new(MemoryStore::new());
+        let array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt16,
+            vec![4, 4].try_into()?,
+            FillValue::from(0u16),
+        )
+        .array_to_bytes_codec(Box::new(
+            codec::array_to_bytes::sharding::ShardingCodecBuilder::new(vec![2, 2].try_into()?)
+                .build(),
+        ))
+        .build(store.clone(), "/array")?;
+        assert!(!array.codecs().partial_encoder_encodes_all());
+        let shard_size = || store.size_key(&array.chunk_key(&[0, 0]));
+
+        // Only the updated inner chunks are written to the shard
+        array.store_array_subset_elements::<u16>(
+            &ArraySubset::new_with_ranges(&[0..1, 0..1]),
+            &[1],
+        )?;
+        let index_size = shard_size()?.unwrap() - 8;
+        array.store_array_subset_elements::<u16>(
+            &ArraySubset::new_with_ranges(&[3..4, 3..4]),
+            &[2],
+        )?;
+        assert_eq!(shard_size()?, Some(index_size + 16));
+        array.store_array_subset_elements::<u16>(
+            &ArraySubset::new_with_ranges(&[1..3, 1..2]),
+            &[3, 4],
+        )?;
+        assert_eq!(shard_size()?, Some(index_size + 32));
+        assert_eq!(
+            array.retrieve_chunk_elements::<u16>(&[0, 0])?,
+            vec![1, 0, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 0, 0, 2]
+        );
+
+        // The shard is compacted when most of it is unreferenced
+        array.store_array_subset_elements::<u16>(
+            &ArraySubset::new_with_ranges(&[0..1, 0..1]),
+            &[5],
+        )?;
+        assert_eq!(shard_size()?, Some(index_size + 40));
+        array.store_array_subset_elements::<u16>(
+            &ArraySubset::new_with_ranges(&[0..1, 1..2]),
+            &[6],
+        )?;
+        assert_eq!(shard_size()?, Some(index_size + 48));
+        array.store_array_subset_elements::<u16>(
+            &ArraySubset::new_with_ranges(&[1..2, 0..1]),
+            &[7],
+        )?;
+        assert_eq!(shard_size()?, Some(index_size + 24));
+        assert_eq!(
+            array.retrieve_chunk_elements::<u16>(&[0, 0])?,
+            vec![5, 6, 0, 0, 7, 3, 0, 0, 0, 4, 0, 0, 0, 0, 0, 2]
+        );
+
+        // Inner chunks with the fill value are removed, and empty shards are erased
+        array.store_array_subset_elements::<u16>(
+            &ArraySubset::new_with_ranges(&[3..4, 3..4]),
+            &[0],
+        )?;
+        assert_eq!(
+            array.retrieve_chunk_elements::<u16>(&[0, 0])?,
+            vec![5, 6, 0, 0, 7, 3, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0]
+        );
+        array.store_array_subset_elements::<u16>(
+            &ArraySubset::new_with_ranges(&[0..3, 0..2]),
+            &[0; 6],
+        )?;
+        assert_eq!(shard_size()?, None);
+        Ok(())
+    }
+
+    #[cfg(feature = "sharding")]
+    #[test]
+    fn array_partial_encoder() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        let array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt16,
+            vec![4, 4].try_into()?,
+            FillValue::from(0u16),
+        )
+        .array_to_bytes_codec(Box::new(
+            codec::array_to_bytes::sharding::ShardingCodecBuilder::new(vec![2, 2].try_into()?)
+                .build(),
+        ))
+        .build(store, "/array")?;
+
+        // The partial encoder is held across updates
+        let partial_encoder = array.partial_encoder(&[0, 1])?;
+        assert_eq!(partial_encoder.data_type(), &DataType::UInt16);
+        for i in 0..4u16 {
+            let subset = ArraySubset::new_with_ranges(&[u64::from(i)..u64::from(i) + 1, 0..4]);
+            partial_encoder.partial_encode(&[(
+                &subset,
+                ArrayBytes::from(transmute_to_bytes_vec(vec![i + 1; 4])),
+            )])?;
+        }
+        assert_eq!(
+            array.retrieve_array_subset_elements::<u16>(&ArraySubset::new_with_ranges(&[
+                0..4,
+                3..5
+            ]))?,
+            vec![0, 1, 0, 2, 0, 3, 0, 4]
+        );
+        assert!(partial_encoder
+            .partial_encode(&[(
+                &ArraySubset::new_with_ranges(&[0..5, 0..1]),
+                ArrayBytes::from(transmute_to_bytes_vec(vec![0u16; 5]))
+            )])
+            .is_err());
+        partial_encoder.erase()?;
+        assert!(array.retrieve_chunk_if_exists(&[0, 1])?.is_none());
+
+        // Chunk sidecars are maintained by the partial encoder
+        let mut array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt16,
+            vec![4, 4].try_into()?,
+            FillValue::from(0u16),
+        )
+        .build(Arc::new(MemoryStore::new()), "/array")?;
+        array.set_chunk_statistics_enabled(true);
+        let partial_encoder = array.partial_encoder(&[1, 1])?;
+        partial_encoder.partial_encode(&[(
+            &ArraySubset::new_with_ranges(&[1..2, 1..3]),
+            ArrayBytes::from(transmute_to_bytes_vec(vec![5u16, 7])),
+        )])?;
+        let statistics = array.retrieve_chunk_statistics(&[1, 1])?.unwrap();
+        assert_eq!(statistics.min_max::<u16>(array.data_type())?, Some((0, 7)));
+        partial_encoder.erase()?;
+        assert!(array.retrieve_chunk_statistics(&[1, 1])?.is_none());
+        Ok(())
+    }
+
+    #[cfg(feature = "sharding")]
+    #[test]
+    fn array_shard_writer() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::storage::{
+            storage_transformer::{
+                PerformanceMetricsStorageTransformer, StorageTransformerExtension,
+            },
+            ReadableWritableStorage,
+        };
+        use codec::array_to_bytes::sharding::{ShardingCodecBuilder, ShardingIndexLocation};
+
+        for index_location in [ShardingIndexLocation::Start, ShardingIndexLocation::End] {
+            let performance_metrics = Arc::new(PerformanceMetricsStorageTransformer::new());
+            let store: ReadableWritableStorage = performance_metrics
+                .clone()
+                .create_readable_writable_transformer(Arc::new(MemoryStore::new()));
+            let array = ArrayBuilder::new(
+                vec![8, 8],
+                DataType::UInt16,
+                vec![4, 4].try_into()?,
+                FillValue::from(0u16),
+            )
+            .array_to_bytes_codec(Box::new(
+                ShardingCodecBuilder::new(vec![2, 2].try_into()?)
+                    .index_location(index_location)
+                    .build(),
+            ))
+            .build(store, "/array")?;
+            array.store_chunk_elements::<u16>(&[1, 0], &[9; 16])?;
+
+            // Inner chunks are written out of order, and one is the fill value
+            let options = CodecOptions::default();
+            let (reads_start, writes_start) =
+                (performance_metrics.reads(), performance_metrics.writes());
+            let bytes_written_start = performance_metrics.bytes_written();
+            array.store_shard_with_writer(&[1, 0], |shard_writer| {
+                assert_eq!(shard_writer.chunks_per_shard(), &[2, 2]);
+                for (inner_chunk_indices, value) in
+                    [([1, 1], 4u16), ([0, 0], 1), ([1, 0], 0), ([0, 1], 2)]
+                {
+                    shard_writer.write_inner_chunk(
+                        &inner_chunk_indices,
+                        ArrayBytes::from(transmute_to_bytes_vec(vec![value; 4])),
+                        &options,
+                    )?;
+                }
+                assert!(shard_writer
+                    .write_inner_chunk(
+                        &[2, 0],
+                        ArrayBytes::from(transmute_to_bytes_vec(vec![1u16; 4])),
+                        &options,
+                    )
+                    .is_err());
+                Ok(())
+            })?;
+            // The shard is written sequentially without reading it back: 3 inner chunks and the shard index
+            assert_eq!(performance_metrics.reads(), reads_start);
+            assert_eq!(performance_metrics.writes(), writes_start + 1);
+            assert_eq!(
+                performance_metrics.bytes_written() - bytes_written_start,
+                3 * 8 + 4 * 16 + 4
+            );
+            assert_eq!(
+                array.retrieve_chunk_elements::<u16>(&[1, 0])?,
+                vec![1, 1, 2, 2, 1, 1, 2, 2, 0, 0, 4, 4, 0, 0, 4, 4]
+            );
+
+            // A shard is abandoned if writing its inner chunks fails
+            assert!(array
+                .store_shard_with_writer(&[1, 0], |shard_writer| {
+                    shard_writer.write_inner_chunk(
+                        &[0, 0],
+                        ArrayBytes::from(transmute_to_bytes_vec(vec![7u16; 4])),
+                        &options,
+                    )?;
+                    Err(ArrayError::InvalidChunkGridIndicesError(vec![2, 0]))
+                })
+                .is_err());
+            assert_eq!(
+                array.retrieve_chunk_elements::<u16>(&[1, 0])?,
+                vec![1, 1, 2, 2, 1, 1, 2, 2, 0, 0, 4, 4, 0, 0, 4, 4]
+            );
+
+            // A shard with no inner chunks written is erased
+            array.store_shard_with_writer(&[1, 0], |_| Ok(()))?;
+            assert!(array.retrieve_chunk_if_exists(&[1, 0])?.is_none());
+        }
+
+        // An array that is not sharded is not supported
+        let array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt16,
+            vec![4, 4].try_into()?,
+            FillValue::from(0u16),
+        )
+        .build(Arc::new(MemoryStore::new()), "/array")?;
+        assert!(array.store_shard_with_writer(&[0, 0], |_| Ok(())).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "sharding")]
+    #[test]
+    fn array_store_inner_chunks_iter() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        let array = ArrayBuilder::new(
+            vec![8, 6],
+            DataType::UInt16,
+            vec![4, 4].try_into()?,
+            FillValue::from(0u16),
+        )
+        .array_to_bytes_codec(Box::new(
+            codec::array_to_bytes::sharding::ShardingCodecBuilder::new(vec![2, 2].try_into()?)
+                .build(),
+        ))
+        .build(store, "/array")?;
+
+        // Inner chunks are streamed in row-major order with a memory limit of a few inner chunks
+        let inner_chunk_grid_indices = ArraySubset::new_with_shape(vec![4, 3]).indices();
+        let inner_chunks = inner_chunk_grid_indices
+            .iter()
+            .enumerate()
+            .filter(|(_, inner_chunk_indices)| inner_chunk_indices != &[3, 2])
+            .map(|(i, inner_chunk_indices)| {
+                let value = u16::try_from(i).unwrap() + 1;
+                (
+                    inner_chunk_indices,
+                    ArrayBytes::from(transmute_to_bytes_vec(vec![value; 4])),
+                )
+            });
+        let mut options = CodecOptions::default();
+        options.set_memory_limit(Some(64));
+        array.store_inner_chunks_iter_opt(inner_chunks, &options)?;
+        assert_eq!(
+            array.retrieve_array_subset_elements::<u16>(&ArraySubset::new_with_ranges(&[
+                0..8,
+                3..5
+            ]))?,
+            vec![2, 3, 2, 3, 5, 6, 5, 6, 8, 9, 8, 9, 11, 0, 11, 0]
+        );
+        assert_eq!(
+            array.retrieve_chunk_elements::<u16>(&[1, 1])?,
+            vec![9, 9, 0, 0, 9, 9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+
+        // An inner chunk of a shard that has already been finalized is an error
+        let inner_chunks = [[0, 0], [0, 1], [1, 0], [1, 1], [0, 0]].map(|inner_chunk_indices| {
+            (
+                inner_chunk_indices.to_vec(),
+                ArrayBytes::from(transmute_to_bytes_vec(vec![1u16; 4])),
+            )
+        });
+        assert!(array.store_inner_chunks_iter(inner_chunks).is_err());
+
+        // An array that is not sharded is not supported
+        let array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt16,
+            vec![4, 4].try_into()?,
+            FillValue::from(0u16),
+        )
+        .build(Arc::new(MemoryStore::new()), "/array")?;
+        assert!(array
+            .store_inner_chunks_iter([(
+                vec![0, 0],
+                ArrayBytes::from(transmute_to_bytes_vec(vec![1u16; 16]))
+            )])
+            .is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "sharding")]
+    #[test]
+    fn array_store_inner_chunks_iter_streaming() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::storage::{
+            Bytes, StorageError, StoreKey, StoreKeyStartValue, StorePrefix, StoreValueWriter,
+            WritableStorageTraits,
+        };
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// A store which counts the bytes streamed to it.
+        struct StreamingStore {
+            store: Arc<MemoryStore>,
+            bytes_streamed: AtomicUsize,
+        }
+
+        struct StreamingStoreValueWriter<'a> {
+            store: &'a StreamingStore,
+            key: StoreKey,
+            value: Vec<u8>,
+        }
+
+        impl StoreValueWriter for StreamingStoreValueWriter<'_> {
+            fn write(&mut self, bytes: Bytes) -> Result<(), StorageError> {
+                self.store
+                    .bytes_streamed
+                    .fetch_add(bytes.len(), Ordering::SeqCst);
+                self.value.extend_from_slice(&bytes);
+                Ok(())
+            }
+
+            fn finish(self: Box<Self>) -> Result<(), StorageError> {
+                self.store.store.set(&self.key, self.value.into())
+            }
+        }
+
+        impl WritableStorageTraits for StreamingStore {
+            fn set(&self, key: &StoreKey, value: Bytes) -> Result<(), StorageError> {
+                self.store.set(key, value)
+            }
+
+            fn set_partial_values(
+                &self,
+                key_start_values: &[StoreKeyStartValue],
+            ) -> Result<(), StorageError> {
+                self.store.set_partial_values(key_start_values)
+            }
+
+            fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+                self.store.erase(key)
+            }
+
+            fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+                self.store.erase_prefix(prefix)
+            }
+
+            fn set_streaming(
+                &self,
+                key: &StoreKey,
+            ) -> Result<Box<dyn StoreValueWriter + '_>, StorageError> {
+                Ok(Box::new(StreamingStoreValueWriter {
+                    store: self,
+                    key: key.clone(),
+                    value: Vec::new(),
+                }))
+            }
+        }
+
+        let store = Arc::new(StreamingStore {
+            store: Arc::new(MemoryStore::new()),
+            bytes_streamed: AtomicUsize::new(0),
+        });
+        let array = ArrayBuilder::new(
+            vec![8, 6],
+            DataType::UInt16,
+            vec![8, 6].try_into()?,
+            FillValue::from(0u16),
+        )
+        .array_to_bytes_codec(Box::new(
+            codec::array_to_bytes::sharding::ShardingCodecBuilder::new(vec![2, 2].try_into()?)
+                .build(),
+        ))
+        .build(store.clone(), "/array")?;
+
+        // Inner chunks are streamed to the store as they are taken from the iterator, rather than buffered until the shard is complete
+        let memory_limit = 64;
+        let bytes_taken = AtomicUsize::new(0);
+        let inner_chunk_grid_indices = ArraySubset::new_with_shape(vec![4, 3]).indices();
+        let inner_chunks =
+            inner_chunk_grid_indices
+                .iter()
+                .enumerate()
+                .map(|(i, inner_chunk_indices)| {
+                    let bytes_taken = bytes_taken.fetch_add(8, Ordering::SeqCst) + 8;
+                    let bytes_streamed = store.bytes_streamed.load(Ordering::SeqCst);
+                    assert!(bytes_taken.saturating_sub(bytes_streamed) <= memory_limit);
+                    let value = u16::try_from(i).unwrap() + 1;
+                    (
+                        inner_chunk_indices,
+                        ArrayBytes::from(transmute_to_bytes_vec(vec![value; 4])),
+                    )
+                });
+        let mut options = CodecOptions::default();
+        options.set_memory_limit(Some(memory_limit as u64));
+        array.store_inner_chunks_iter_opt(inner_chunks, &options)?;
+        assert!(store.bytes_streamed.load(Ordering::SeqCst) > 12 * 8);
+
+        let array =
+            Array::new_with_metadata(store.store.clone(), "/array", array.metadata().clone())?;
+        let elements: Vec<u16> = ArraySubset::new_with_shape(vec![8, 6])
+            .indices()
+            .iter()
+            .map(|indices| u16::try_from(indices[0] / 2 * 3 + indices[1] / 2).unwrap() + 1)
+            .collect();
+        assert_eq!(array.retrieve_chunk_elements::<u16>(&[0, 0])?, elements);
+        Ok(())
+    }
+
+    #[test]
+    fn array_update_attributes() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        let array_path = "/array";
+        let mut array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt8,
+            vec![4, 4].try_into()?,
+            FillValue::from(0u8),
+        )
+        .build(store.clone(), array_path)?;
+        array.store_metadata()?;
+        let mut array_other = Array::open(store.clone(), array_path)?;
+
+        let patch = |json: &str| serde_json::from_str::<serde_json::Map<_, _>>(json).unwrap();
+        let attributes = |array: &Array<MemoryStore>| {
+            let mut attributes = array.attributes().clone();
+            attributes.remove("_zarrs");
+            attributes
+        };
+        array.update_attributes(&patch(r#"{"a": 1, "b": {"c": 2}}"#), false)?;
+        array_other.update_attributes(&patch(r#"{"b": {"d": 3}}"#), true)?;
+        assert!(!array_other.metadata_is_stale()?);
+        assert_eq!(
+            attributes(&array_other),
+            patch(r#"{"a": 1, "b": {"c": 2, "d": 3}}"#)
+        );
+        array.update_attributes(&patch(r#"{"a": null}"#), true)?;
+        assert_eq!(
+            attributes(&Array::open(store.clone(), array_path)?),
+            patch(r#"{"b": {"c": 2, "d": 3}}"#)
+        );
+
+        // A concurrent write after the metadata is read
+        let result = array.update_attributes_with(
+            |attributes| {
+                attributes.insert("e".to_string(), 4.into());
+                array_other
+                    .update_attributes(&patch(r#"{"f": 5}"#), false)
+                    .unwrap();
+            },
+            true,
+        );
+        assert!(matches!(
+            result,
+            Err(ArrayCreateError::StorageError(
+                crate::storage::StorageError::VersionMismatch(_)
+            ))
+        ));
+        assert_eq!(
+            attributes(&Array::open(store.clone(), array_path)?),
+            patch(r#"{"b": {"c": 2, "d": 3}, "f": 5}"#)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn array_metadata_backup() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::storage::{
+            meta_key, meta_key_backup, ReadableStorageTraits, WritableStorageTraits,
+        };
+
+        let store = Arc::new(MemoryStore::new());
+        let array_path = "/array";
+        let mut array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt8,
+            vec![4, 4].try_into()?,
+            FillValue::from(0u8),
+        )
+        .build(store.clone(), array_path)?;
+        let key = meta_key(&NodePath::new(array_path)?);
+        let key_backup = meta_key_backup(&key);
+        assert_eq!(key_backup.as_str(), "array/zarr.json.bak");
+
+        // Nothing to back up or restore
+        array.store_metadata_with_backup()?;
+        assert!(store.get(&key_backup)?.is_none());
+        assert!(array.restore_metadata_backup().is_err());
+
+        array
+            .attributes_mut()
+            .insert("key".to_string(), "value".into());
+        array.store_metadata_with_backup()?;
+        assert!(store.get(&key_backup)?.is_some());
+
+        // Recover from corrupted metadata
+        store.set(&key, "{".into())?;
+        assert!(Array::open(store.clone(), array_path).is_err());
+        array.restore_metadata_backup()?;
+        assert!(!array.attributes().contains_key("key"));
+        assert!(!Array::open(store.clone(), array_path)?
+            .attributes()
+            .contains_key("key"));
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn array_metadata_stale_async() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(crate::storage::store::AsyncObjectStore::new(
+            object_store::memory::InMemory::new(),
+        ));
+        let array_path = "/array";
+        let mut array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt8,
+            vec![4, 4].try_into()?,
+            FillValue::from(0u8),
+        )
+        .build(store.clone(), array_path)?;
+        assert!(!array.async_metadata_is_stale().await?);
+        array.async_store_metadata_if_unchanged().await?;
+        assert!(!array.async_metadata_is_stale().await?);
+
+        let mut array_other = Array::async_open(store.clone(), array_path).await?;
+        array_other
+            .attributes_mut()
+            .insert("key".to_string(), "value".into());
+        array_other.async_store_metadata_if_unchanged().await?;
+
+        assert!(array.async_metadata_is_stale().await?);
+        assert!(matches!(
+            array.async_store_metadata_if_unchanged().await,
+            Err(crate::storage::StorageError::VersionMismatch(_))
+        ));
+
+        array.async_refresh_metadata().await?;
+        assert!(!array.async_metadata_is_stale().await?);
+        assert_eq!(array.attributes().get("key"), Some(&"value".into()));
+
+        let patch = serde_json::json!({"key": null, "other": 1});
+        array_other
+            .async_update_attributes(patch.as_object().unwrap(), true)
+            .await?;
+        array
+            .async_update_attributes_with(
+                |attributes| {
+                    attributes.insert("another".to_string(), 2.into());
+                },
+                true,
+            )
+            .await?;
+        let mut attributes = Array::async_open(store.clone(), array_path)
+            .await?
+            .attributes()
+            .clone();
+        attributes.remove("_zarrs");
+        assert_eq!(
+            &attributes,
+            serde_json::json!({"other": 1, "another": 2})
+                .as_object()
+                .unwrap()
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn array_store_array_subset_max_in_flight_chunks_async(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(crate::storage::store::AsyncObjectStore::new(
+            object_store::memory::InMemory::new(),
+        ));
+        let array = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt8,
+            vec![2, 2].try_into()?,
+            FillValue::from(0u8),
+        )
+        .build(store.clone(), "/array")?;
+
+        let mut options = codec::CodecOptions::default();
+        options.set_max_in_flight_chunks(Some(2));
+        assert_eq!(options.max_in_flight_chunks(), Some(2));
+        let subset_top = ArraySubset::new_with_ranges(&[0..4, 0..8]);
+        let subset_bottom = ArraySubset::new_with_ranges(&[4..8, 0..8]);
+        let (top, bottom) = futures::join!(
+            array.async_store_array_subset_elements_opt::<u8>(&subset_top, &[1; 32], &options),
+            array.async_store_array_subset_elements_opt::<u8>(&subset_bottom, &[2; 32], &options),
+        );
+        top?;
+        bottom?;
+        array
+            .async_store_array_subset_elements_opt::<u8>(
+                &ArraySubset::new_with_ranges(&[3..5, 3..5]),
+                &[3; 4],
+                &options,
+            )
+            .await?;
+
+        let elements = array
+            .async_retrieve_array_subset_elements::<u8>(&ArraySubset::new_with_shape(
+                array.shape().to_vec(),
+            ))
+            .await?;
+        let expected: Vec<u8> = (0..8)
+            .flat_map(|i| {
+                (0..8).map(move |j| {
+                    if (3..5).contains(&i) && (3..5).contains(&j) {
+                        3
+                    } else if i < 4 {
+                        1
+                    } else {
+                        2
+                    }
+                })
+            })
+            .collect();
+        assert_eq!(elements, expected);
+
+        // All permits are released once the stores complete
+        let semaphore = options.in_flight_chunks_semaphore().unwrap();
+        let permits = [semaphore.try_acquire_arc(), semaphore.try_acquire_arc()];
+        assert!(permits.iter().all(Option::is_some));
+        assert_eq!(
+            options.into_builder().build().max_in_flight_chunks(),
+            Some(2)
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn array_chunks_stream_async() -> Result<(), Box<dyn std::error::Error>> {
+        use futures::{StreamExt, TryStreamExt};
+
+        let store = Arc::new(crate::storage::store::AsyncObjectStore::new(
+            object_store::memory::InMemory::new(),
+        ));
+        let array = ArrayBuilder::new(
+            vec![4, 4],
+            DataType::UInt8,
+            vec![2, 2].try_into()?,
+            FillValue::from(0u8),
+        )
+        .build(store.clone(), "/array")?;
+        array
+            .async_store_chunk_elements::<u8>(&[0, 1], &[1, 2, 3, 4])
+            .await?;
+        array
+            .async_store_chunk_elements::<u8>(&[1, 1], &[5, 6, 7, 8])
+            .await?;
+
+        let options = codec::CodecOptions::default();
+        let chunks = ArraySubset::new_with_ranges(&[0..2, 1..2]);
+        let chunks_decoded: Vec<_> = array
+            .async_chunks_stream(&chunks, &options)
+            .map_ok(|(chunk_indices, bytes)| (chunk_indices, bytes.into_fixed().unwrap().to_vec()))
+            .try_collect()
+            .await?;
+        assert_eq!(
+            chunks_decoded,
+            [
+                (vec![0, 1], vec![1, 2, 3, 4]),
+                (vec![1, 1], vec![5, 6, 7, 8])
+            ]
+        );
+
+        let mut chunks_decoded: Vec<_> = array
+            .async_chunks_stream_unordered(&ArraySubset::new_with_shape(vec![2, 2]), &options)
+            .map_ok(|(chunk_indices, bytes)| (chunk_indices, bytes.into_fixed().unwrap().to_vec()))
+            .try_collect()
+            .await?;
+        chunks_decoded.sort();
+        assert_eq!(
+            chunks_decoded,
+            [
+                (vec![0, 0], vec![0; 4]),
+                (vec![0, 1], vec![1, 2, 3, 4]),
+                (vec![1, 0], vec![0; 4]),
+                (vec![1, 1], vec![5, 6, 7, 8])
+            ]
+        );
+
+        let mut stream = std::pin::pin!(
+            array.async_chunks_stream(&ArraySubset::new_with_shape(vec![1]), &options)
+        );
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(ArrayError::InvalidArraySubset(..)))
+        ));
+        assert!(stream.next().await.is_none());
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    #[allow(clippy::too_many_lines)]
+    async fn array_store_chunks_cancellation_async() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::storage::{
+            store::AsyncObjectStore, AsyncBytes, AsyncWritableStorageTraits, StorageError,
+            StoreKey, StoreKeyStartValue, StorePrefix,
+        };
+
+        /// A store where setting the pending key never completes.
+        struct PendingStore {
+            store: Arc<AsyncObjectStore<object_store::memory::InMemory>>,
+            pending_key: StoreKey,
+        }
+
+        #[async_trait::async_trait]
+        impl AsyncWritableStorageTraits for PendingStore {
+            async fn set(&self, key: &StoreKey, value: AsyncBytes) -> Result<(), StorageError> {
+                if key == &self.pending_key {
+                    futures::future::pending().await
+                } else {
+                    self.store.set(key, value).await
+                }
+            }
+
+            async fn set_partial_values(
+                &self,
+                key_start_values: &[StoreKeyStartValue],
+            ) -> Result<(), StorageError> {
+                self.store.set_partial_values(key_start_values).await
+            }
+
+            async fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+                self.store.erase(key).await
+            }
+
+            async fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+                self.store.erase_prefix(prefix).await
+            }
+        }
+
+        let store = Arc::new(AsyncObjectStore::new(object_store::memory::InMemory::new()));
+        let pending_store = Arc::new(PendingStore {
+            store: store.clone(),
+            pending_key: StoreKey::new("array/c/1/1")?,
+        });
+        let builder = ArrayBuilder::new(
+            vec![4, 4],
+            DataType::UInt8,
+            vec![2, 2].try_into()?,
+            FillValue::from(0u8),
+        );
+        let array = builder.build(pending_store, "/array")?;
+        let array_read = builder.build(store, "/array")?;
+
+        // Cancelling the token aborts the pending chunk and the store returns an error
+        let cancellation_token = CancellationToken::new();
+        let mut options = codec::CodecOptions::default();
+        options.set_cancellation_token(Some(cancellation_token.clone()));
+        let chunks = ArraySubset::new_with_shape(vec![2, 2]);
+        let guard = array.async_write_guard();
+        let (result, ()) = futures::join!(
+            guard.async_store_chunks_opt(&chunks, vec![1u8; 16], &options),
+            async {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                cancellation_token.cancel();
+            }
+        );
+        assert!(matches!(result, Err(ArrayError::Cancelled)));
+        assert_eq!(guard.written_chunks(), [[0, 0], [0, 1], [1, 0]]);
+        assert_eq!(guard.incomplete_chunks(), [[1, 1]]);
+        assert_eq!(
+            array_read
+                .async_retrieve_chunk_elements::<u8>(&[1, 0])
+                .await?,
+            [1; 4]
+        );
+        assert!(array_read
+            .async_retrieve_chunk_if_exists(&[1, 1])
+            .await?
+            .is_none());
+
+        // Rolling back erases the written chunks
+        guard.async_rollback().await?;
+        for chunk_indices in &chunks.indices() {
+            assert!(array_read
+                .async_retrieve_chunk_if_exists(&chunk_indices)
+                .await?
+                .is_none());
+        }
+
+        // Stores with a cancelled token do not write any chunks
+        assert!(matches!(
+            array
+                .async_store_chunks_opt(
+                    &ArraySubset::new_with_shape(vec![1, 1]),
+                    vec![1u8; 4],
+                    &options
+                )
+                .await,
+            Err(ArrayError::Cancelled)
+        ));
+        assert!(array_read
+            .async_retrieve_chunk_if_exists(&[0, 0])
+            .await?
+            .is_none());
+
+        // Dropping the store future leaves the chunk incomplete
+        let guard = array.async_write_guard();
+        let options = codec::CodecOptions::default();
+        assert!(tokio::time::timeout(
+            std::time::Duration::from_millis(10),
+            guard.async_store_chunk_opt(&[1, 1], vec![1u8; 4], &options)
+        )
+        .await
+        .is_err());
+        assert!(guard.written_chunks().is_empty());
+        assert_eq!(guard.incomplete_chunks(), [[1, 1]]);
+        assert_eq!(guard.commit(), Vec::<ArrayIndices>::new());
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn array_async_codec_executor() -> Result<(), Box<dyn std::error::Error>> {
+        use codec::{AsyncCodecExecutor, AsyncSpawnBlockingFn};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let store = Arc::new(crate::storage::store::AsyncObjectStore::new(
+            object_store::memory::InMemory::new(),
+        ));
+        let array = ArrayBuilder::new(
+            vec![4, 4],
+            DataType::UInt16,
+            vec![2, 2].try_into()?,
+            FillValue::from(0u16),
+        )
+        .bytes_to_bytes_codecs(vec![
+            #[cfg(feature = "gzip")]
+            Box::new(codec::GzipCodec::new(5)?),
+        ])
+        .build(store, "/array")?;
+
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let spawn_blocking: AsyncSpawnBlockingFn = Arc::new({
+            let spawned = spawned.clone();
+            move |task| {
+                spawned.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async move {
+                    tokio::task::spawn_blocking(task).await.unwrap();
+                })
+            }
+        });
+        let thread_pool = rayon::ThreadPoolBuilder::new().num_threads(1).build()?;
+        let elements: Vec<u16> = (0..16).collect();
+        for executor in [
+            AsyncCodecExecutor::Inline,
+            AsyncCodecExecutor::RayonPool(Arc::new(thread_pool)),
+            AsyncCodecExecutor::SpawnBlocking(spawn_blocking),
+        ] {
+            let options = codec::CodecOptions::builder()
+                .async_codec_executor(executor)
+                .build();
+            array
+                .async_store_array_subset_elements_opt(
+                    &ArraySubset::new_with_shape(array.shape().to_vec()),
+                    &elements,
+                    &options,
+                )
+                .await?;
+            assert_eq!(
+                array
+                    .async_retrieve_array_subset_elements_opt::<u16>(
+                        &ArraySubset::new_with_shape(array.shape().to_vec()),
+                        &options
+                    )
+                    .await?,
+                elements
+            );
+            array
+                .async_erase_chunks(&ArraySubset::new_with_shape(vec![2, 2]))
+                .await?;
+        }
+        // 4 chunks are encoded and decoded with the spawn blocking executor
+        assert_eq!(spawned.load(Ordering::SeqCst), 8);
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    #[should_panic(expected = "codec panic")]
+    async fn async_codec_executor_panic() {
+        let thread_pool = rayon::ThreadPoolBuilder::new().build().unwrap();
+        codec::AsyncCodecExecutor::RayonPool(Arc::new(thread_pool))
+            .execute(|| panic!("codec panic"))
+            .await;
+    }
+
+    #[test]
+    fn array_set_shape_and_attributes() {
+        let store = MemoryStore::new();
+        let array_path = "/group/array";
+        let mut array = ArrayBuilder::new(
+            vec![8, 8], // array shape
+            DataType::Float32,
+            vec![4, 4].try_into().unwrap(),
+            FillValue::from(ZARR_NAN_F32),
+        )
+        .bytes_to_bytes_codecs(vec![
+            #[cfg(feature = "gzip")]
+            Box::new(codec::GzipCodec::new(5).unwrap()),
+        ])
+        .build(store.into(), array_path)
+        .unwrap();
+
+        array.set_shape(vec![16, 16]);
+        array
+            .attributes_mut()
+            .insert("test".to_string(), "apple".into());
+
+        assert_eq!(array.shape(), &[16, 16]);
+        assert_eq!(
+            array.attributes().get_key_value("test"),
+            Some((
+                &"test".to_string(),
+                &serde_json::Value::String("apple".to_string())
+            ))
+        );
+    }
+
+    #[test]
+    fn array_subset_round_trip() {
+        let store = Arc::new(MemoryStore::default());
+        let array_path = "/array";
+        let array = ArrayBuilder::new(
+            vec![8, 8], // array shape
+            DataType::Float32,
+            vec![4, 4].try_into().unwrap(), // regular chunk shape
+            FillValue::from(1f32),
+        )
+        .bytes_to_bytes_codecs(vec![
+            #[cfg(feature = "gzip")]
+            Box::new(codec::GzipCodec::new(5).unwrap()),
+        ])
+        // .storage_transformers(vec![].into())
+        .build(store, array_path)
+        .unwrap();
+
+        array
+            .store_array_subset_elements::<f32>(
+                &ArraySubset::new_with_ranges(&[3..6, 3..6]),
+                &[1.0, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9],
+            )
+            .unwrap();
+
+        let subset_all = ArraySubset::new_with_shape(array.shape().to_vec());
+        let data_all = array
+            .retrieve_array_subset_elements::<f32>(&subset_all)
+            .unwrap();
+        assert_eq!(
+            data_all,
+            vec![
+                //     (0,0)       |     (0, 1)
+                //0  1    2    3   |4    5    6    7
+                1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, // 0
+                1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, // 1
+                1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, // 2
+                1.0, 1.0, 1.0, 1.0, 0.2, 0.3, 1.0, 1.0, //_3____________
+                1.0, 1.0, 1.0, 0.4, 0.5, 0.6, 1.0, 1.0, // 4
+                1.0, 1.0, 1.0, 0.7, 0.8, 0.9, 1.0, 1.0, // 5 (1, 1)
+                1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, // 6
+                1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, // 7
+            ]
+        );
+        assert!(array
+            .retrieve_chunk_elements_if_exists::<f32>(&[0; 2])
+            .unwrap()
+            .is_none());
+        #[cfg(feature = "ndarray")]
+        assert!(array
+            .retrieve_chunk_ndarray_if_exists::<f32>(&[0; 2])
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn array_retrieve_into_view() {
+        let store = Arc::new(MemoryStore::default());
+        let array = ArrayBuilder::new(
+            vec![4, 4], // array shape
+            DataType::UInt8,
+            vec![2, 2].try_into().unwrap(), // regular chunk shape
+            FillValue::from(0u8),
+        )
+        .build(store, "/array")
+        .unwrap();
+        array
+            .store_array_subset_elements::<u8>(
+                &ArraySubset::new_with_shape(vec![4, 4]),
+                &(0..16).collect::<Vec<u8>>(),
+            )
+            .unwrap();
+
+        // Decode a 2x3 subset into a window of a 4x6 canvas
+        let mut canvas = vec![255u8; 4 * 6];
+        array
+            .retrieve_array_subset_into_view(
+                &ArraySubset::new_with_ranges(&[1..3, 1..4]),
+                &mut canvas,
+                &[4, 6],
+                &[2, 3],
+            )
+            .unwrap();
+        assert_eq!(
+            canvas,
+            vec![
+                255, 255, 255, 255, 255, 255, //
+                255, 255, 255, 255, 255, 255, //
+                255, 255, 255, 5, 6, 7, //
+                255, 255, 255, 9, 10, 11, //
+            ]
+        );
+
+        // The window is out of bounds of the canvas
+        assert!(array
+            .retrieve_array_subset_into_view(
+                &ArraySubset::new_with_ranges(&[1..3, 1..4]),
+                &mut canvas,
+                &[4, 6],
+                &[3, 3],
+            )
+            .is_err());
+        // The element type does not match the data type
+        assert!(matches!(
+            array.retrieve_array_subset_into_view(
+                &ArraySubset::new_with_ranges(&[0..1, 0..1]),
+                &mut [0u16],
+                &[1, 1],
+                &[0, 0],
+            ),
+            Err(ArrayError::IncompatibleElementType)
+        ));
     }
 
     #[allow(dead_code)]
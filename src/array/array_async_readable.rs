@@ -1,32 +1,36 @@
-use std::{borrow::Cow, sync::Arc};
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
 
-use futures::{StreamExt, TryStreamExt};
+use futures::{Stream, StreamExt, TryStreamExt};
+use web_time::Instant;
 
 use crate::{
-    array_subset::ArraySubset,
+    array_subset::{ArraySubset, IncompatibleDimensionalityError},
     metadata::MetadataRetrieveVersion,
     node::NodePath,
     storage::{
-        meta_key, meta_key_v2_array, meta_key_v2_attributes, AsyncBytes,
-        AsyncReadableStorageTraits, StorageError, StorageHandle,
+        data_key, meta_key, meta_key_v2_array, meta_key_v2_attributes, AsyncBytes,
+        AsyncReadableStorageTraits, MaybeAsyncBytes, StorageError, StorageHandle, StoreKey,
     },
+    vec_spare_capacity_to_mut_slice,
 };
 
 use super::{
-    array_bytes::{merge_chunks_vlen, update_bytes_flen},
+    array_bytes::merge_chunks_vlen,
+    cancellation_token::async_cancellable,
     codec::{
         options::CodecOptions, ArrayToBytesCodecTraits, AsyncArrayPartialDecoderTraits,
-        AsyncStoragePartialDecoder,
+        AsyncStoragePartialDecoder, CodecError,
     },
     concurrency::concurrency_chunks_and_codec,
     element::ElementOwned,
-    unsafe_cell_slice::UnsafeCellSlice,
-    Array, ArrayBytes, ArrayCreateError, ArrayError, ArrayMetadata, ArrayMetadataV2,
-    ArrayMetadataV3, ArraySize, DataTypeSize,
+    output_regions::partition_output,
+    unravel_index, Array, ArrayBytes, ArrayCreateError, ArrayError, ArrayIndices, ArrayMetadata,
+    ArrayMetadataV2, ArrayMetadataV3, ArraySize, ChunkRepresentation, DataTypeSize,
+    SparseArrayBytes,
 };
 
 #[cfg(feature = "ndarray")]
-use super::elements_to_ndarray;
+use super::{elements_to_ndarray, iter_u64_to_usize};
 
 impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
     /// Async variant of [`new`](Array::open).
@@ -60,35 +64,79 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
         if let MetadataRetrieveVersion::Default | MetadataRetrieveVersion::V3 = version {
             // Try V3
             let key_v3 = meta_key(&node_path);
-            if let Some(metadata) = storage.get(&key_v3).await? {
-                let metadata: ArrayMetadataV3 = serde_json::from_slice(&metadata)
-                    .map_err(|err| StorageError::InvalidMetadata(key_v3, err.to_string()))?;
-                return Self::new_with_metadata(storage, path, ArrayMetadata::V3(metadata));
+            if let Some((metadata, metadata_version)) = storage.get_with_version(&key_v3).await? {
+                let metadata: ArrayMetadataV3 =
+                    serde_json::from_slice(&metadata).map_err(|err| {
+                        StorageError::InvalidMetadata(key_v3.clone(), err.to_string())
+                    })?;
+                let mut array =
+                    Self::new_with_metadata(storage, path, ArrayMetadata::V3(metadata))?;
+                array.set_metadata_versions(vec![(key_v3, Some(metadata_version))]);
+                return Ok(array);
             }
         }
 
         if let MetadataRetrieveVersion::Default | MetadataRetrieveVersion::V2 = version {
             // Try V2
             let key_v2 = meta_key_v2_array(&node_path);
-            if let Some(metadata) = storage.get(&key_v2).await? {
-                let mut metadata: ArrayMetadataV2 = serde_json::from_slice(&metadata)
-                    .map_err(|err| StorageError::InvalidMetadata(key_v2, err.to_string()))?;
+            if let Some((metadata, metadata_version)) = storage.get_with_version(&key_v2).await? {
+                let mut metadata: ArrayMetadataV2 =
+                    serde_json::from_slice(&metadata).map_err(|err| {
+                        StorageError::InvalidMetadata(key_v2.clone(), err.to_string())
+                    })?;
 
                 let attributes_key = meta_key_v2_attributes(&node_path);
-                let attributes = storage.get(&attributes_key).await?;
-                if let Some(attributes) = attributes {
+                let attributes = storage.get_with_version(&attributes_key).await?;
+                let attributes_version = if let Some((attributes, attributes_version)) = attributes
+                {
                     metadata.attributes = serde_json::from_slice(&attributes).map_err(|err| {
-                        StorageError::InvalidMetadata(attributes_key, err.to_string())
+                        StorageError::InvalidMetadata(attributes_key.clone(), err.to_string())
                     })?;
-                }
+                    Some(attributes_version)
+                } else {
+                    None
+                };
 
-                return Self::new_with_metadata(storage, path, ArrayMetadata::V2(metadata));
+                let mut array =
+                    Self::new_with_metadata(storage, path, ArrayMetadata::V2(metadata))?;
+                array.set_metadata_versions(vec![
+                    (key_v2, Some(metadata_version)),
+                    (attributes_key, attributes_version),
+                ]);
+                return Ok(array);
             }
         }
 
         Err(ArrayCreateError::MissingMetadata)
     }
 
+    /// Async variant of [`metadata_is_stale`](Array::metadata_is_stale).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_metadata_is_stale(&self) -> Result<bool, StorageError> {
+        Ok(self.async_stale_metadata_key().await?.is_some())
+    }
+
+    /// Async variant of [`stale_metadata_key`](Array::stale_metadata_key).
+    pub(crate) async fn async_stale_metadata_key(&self) -> Result<Option<&StoreKey>, StorageError> {
+        for (key, version) in &self.metadata_versions {
+            if self.storage.get_version(key).await? != *version {
+                return Ok(Some(key));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Async variant of [`refresh_metadata`](Array::refresh_metadata).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_refresh_metadata(&mut self) -> Result<(), ArrayCreateError> {
+        let version = match self.metadata {
+            ArrayMetadata::V3(_) => MetadataRetrieveVersion::V3,
+            ArrayMetadata::V2(_) => MetadataRetrieveVersion::V2,
+        };
+        *self = Self::async_open_opt(self.storage.clone(), self.path.as_str(), &version).await?;
+        Ok(())
+    }
+
     /// Async variant of [`retrieve_chunk_if_exists`](Array::retrieve_chunk_if_exists).
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
     pub async fn async_retrieve_chunk_if_exists(
@@ -153,6 +201,16 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
             .await
     }
 
+    /// Async variant of [`retrieve_chunk_sparse`](Array::retrieve_chunk_sparse).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_retrieve_chunk_sparse(
+        &self,
+        chunk_indices: &[u64],
+    ) -> Result<SparseArrayBytes, ArrayError> {
+        self.async_retrieve_chunk_sparse_opt(chunk_indices, &CodecOptions::default())
+            .await
+    }
+
     /// Async variant of [`retrieve_chunk_elements`](Array::retrieve_chunk_elements).
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
     pub async fn async_retrieve_chunk_elements<T: ElementOwned + Send + Sync>(
@@ -163,6 +221,16 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
             .await
     }
 
+    /// Async variant of [`retrieve_chunk_bytes`](Array::retrieve_chunk_bytes).
+    #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+    pub async fn async_retrieve_chunk_bytes(
+        &self,
+        chunk_indices: &[u64],
+    ) -> Result<AsyncBytes, ArrayError> {
+        self.async_retrieve_chunk_bytes_opt(chunk_indices, &CodecOptions::default())
+            .await
+    }
+
     #[cfg(feature = "ndarray")]
     /// Async variant of [`retrieve_chunk_ndarray`](Array::retrieve_chunk_ndarray).
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
@@ -174,6 +242,17 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
             .await
     }
 
+    #[cfg(feature = "ndarray")]
+    /// Async variant of [`retrieve_chunk_ndarray_arc`](Array::retrieve_chunk_ndarray_arc).
+    #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+    pub async fn async_retrieve_chunk_ndarray_arc<T: ElementOwned + Send + Sync>(
+        &self,
+        chunk_indices: &[u64],
+    ) -> Result<ndarray::ArcArray<T, ndarray::IxDyn>, ArrayError> {
+        self.async_retrieve_chunk_ndarray_arc_opt(chunk_indices, &CodecOptions::default())
+            .await
+    }
+
     /// Async variant of [`retrieve_chunks`](Array::retrieve_chunks).
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
     pub async fn async_retrieve_chunks(
@@ -267,6 +346,16 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
             .await
     }
 
+    /// Async variant of [`retrieve_array_subset_bytes`](Array::retrieve_array_subset_bytes).
+    #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+    pub async fn async_retrieve_array_subset_bytes(
+        &self,
+        array_subset: &ArraySubset,
+    ) -> Result<AsyncBytes, ArrayError> {
+        self.async_retrieve_array_subset_bytes_opt(array_subset, &CodecOptions::default())
+            .await
+    }
+
     #[cfg(feature = "ndarray")]
     /// Async variant of [`retrieve_array_subset_ndarray`](Array::retrieve_array_subset_ndarray).
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
@@ -278,6 +367,36 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
             .await
     }
 
+    #[cfg(feature = "ndarray")]
+    /// Async variant of [`retrieve_array_subset_ndarray_arc`](Array::retrieve_array_subset_ndarray_arc).
+    #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+    pub async fn async_retrieve_array_subset_ndarray_arc<T: ElementOwned + Send + Sync>(
+        &self,
+        array_subset: &ArraySubset,
+    ) -> Result<ndarray::ArcArray<T, ndarray::IxDyn>, ArrayError> {
+        self.async_retrieve_array_subset_ndarray_arc_opt(array_subset, &CodecOptions::default())
+            .await
+    }
+
+    /// Async variant of [`retrieve_array_subset_into_view`](Array::retrieve_array_subset_into_view).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_retrieve_array_subset_into_view<T: ElementOwned + Send + Sync>(
+        &self,
+        array_subset: &ArraySubset,
+        output: &mut [T],
+        output_shape: &[u64],
+        output_offset: &[u64],
+    ) -> Result<(), ArrayError> {
+        self.async_retrieve_array_subset_into_view_opt(
+            array_subset,
+            output,
+            output_shape,
+            output_offset,
+            &CodecOptions::default(),
+        )
+        .await
+    }
+
     /// Async variant of [`partial_decoder`](Array::partial_decoder).
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
     pub async fn async_partial_decoder<'a>(
@@ -294,6 +413,10 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
 
     /// Async variant of [`retrieve_chunk_if_exists_opt`](Array::retrieve_chunk_if_exists_opt).
     #[allow(clippy::missing_errors_doc)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), chunk_indices = ?chunk_indices))
+    )]
     pub async fn async_retrieve_chunk_if_exists_opt(
         &self,
         chunk_indices: &[u64],
@@ -308,34 +431,250 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
         let storage_transformer = self
             .storage_transformers()
             .create_async_readable_transformer(storage_handle);
-        let chunk_encoded = crate::storage::async_retrieve_chunk(
-            &*storage_transformer,
-            self.path(),
-            chunk_indices,
-            self.chunk_key_encoding(),
-        )
-        .await
-        .map_err(ArrayError::StorageError)?;
+        let start = Instant::now();
+        let chunk_encoded = async_cancellable(options.cancellation_token(), async {
+            crate::storage::async_retrieve_chunk(
+                &*storage_transformer,
+                self.path(),
+                chunk_indices,
+                self.chunk_key_encoding(),
+            )
+            .await
+            .map_err(ArrayError::StorageError)
+        })
+        .await?;
+        options
+            .concurrency_strategy()
+            .record_storage_latency(start.elapsed());
         if let Some(chunk_encoded) = chunk_encoded {
-            let chunk_representation = self.chunk_array_representation(chunk_indices)?;
-            let bytes = self
-                .codecs()
-                .decode(
-                    Cow::Borrowed(&chunk_encoded),
-                    &chunk_representation,
-                    options,
-                )
-                .map_err(ArrayError::CodecError)?;
-            bytes.validate(
-                chunk_representation.num_elements(),
-                chunk_representation.data_type().size(),
-            )?;
-            Ok(Some(bytes.into_owned()))
+            Ok(Some(
+                self.async_decode_chunk_encoded(chunk_indices, chunk_encoded, options)
+                    .await?,
+            ))
         } else {
             Ok(None)
         }
     }
 
+    /// Decode the encoded chunk at `chunk_indices`.
+    async fn async_decode_chunk_encoded(
+        &self,
+        chunk_indices: &[u64],
+        chunk_encoded: AsyncBytes,
+        options: &CodecOptions,
+    ) -> Result<ArrayBytes<'static>, ArrayError> {
+        let chunk_representation = self.chunk_array_representation(chunk_indices)?;
+        #[cfg(feature = "metrics")]
+        let encoded_size = chunk_encoded.len();
+        let start = Instant::now();
+        let bytes = self
+            .async_decode_chunk(chunk_encoded, &chunk_representation, options)
+            .await
+            .map_err(ArrayError::CodecError)?;
+        options
+            .concurrency_strategy()
+            .record_codec_time(start.elapsed());
+        bytes.validate(
+            chunk_representation.num_elements(),
+            chunk_representation.data_type().size(),
+        )?;
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_chunk_read(encoded_size, bytes.size());
+        Ok(bytes)
+    }
+
+    /// Retrieve the encoded chunks in `chunks` that are entirely within `array_subset` with [`AsyncReadableStorageTraits::get_values_coalesced`].
+    async fn async_retrieve_encoded_chunks_coalesced(
+        &self,
+        chunks: &ArraySubset,
+        array_subset: &ArraySubset,
+        max_waste_ratio: f64,
+        options: &CodecOptions,
+    ) -> Result<HashMap<Vec<u64>, MaybeAsyncBytes>, ArrayError> {
+        let mut chunks_indices = Vec::new();
+        for chunk_indices in &chunks.indices() {
+            let chunk_subset = self.chunk_subset(&chunk_indices)?;
+            if chunk_subset.overlap(array_subset)? == chunk_subset {
+                chunks_indices.push(chunk_indices);
+            }
+        }
+        let keys: Vec<StoreKey> = chunks_indices
+            .iter()
+            .map(|chunk_indices| data_key(self.path(), chunk_indices, self.chunk_key_encoding()))
+            .collect();
+
+        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+        let storage_transformer = self
+            .storage_transformers()
+            .create_async_readable_transformer(storage_handle);
+        let start = Instant::now();
+        let chunks_encoded = async_cancellable(options.cancellation_token(), async {
+            storage_transformer
+                .get_values_coalesced(&keys, max_waste_ratio)
+                .await
+                .map_err(ArrayError::StorageError)
+        })
+        .await?;
+        options
+            .concurrency_strategy()
+            .record_storage_latency(start.elapsed());
+        Ok(chunks_indices.into_iter().zip(chunks_encoded).collect())
+    }
+
+    /// Retrieve the fixed length bytes of `array_subset` intersecting `chunks` into a window of `output`.
+    ///
+    /// `output` holds the elements of an array with `output_shape`, and the window starts at `output_offset` with the shape of `array_subset`.
+    #[allow(clippy::too_many_arguments)]
+    async fn async_retrieve_array_subset_into(
+        &self,
+        array_subset: &ArraySubset,
+        chunks: &ArraySubset,
+        data_type_size: usize,
+        output: &mut [u8],
+        output_shape: &[u64],
+        output_offset: &[u64],
+        options: &CodecOptions,
+    ) -> Result<(), ArrayError> {
+        debug_assert_eq!(output_offset.len(), array_subset.dimensionality());
+        let num_chunks = chunks.num_elements_usize();
+        if num_chunks == 0 {
+            return Ok(());
+        }
+        let chunk_representation =
+            self.chunk_array_representation(&vec![0; self.dimensionality()])?;
+
+        // Calculate chunk/codec concurrency
+        let codec_concurrency = self.recommended_codec_concurrency(&chunk_representation)?;
+        let (chunk_concurrent_limit, options) = concurrency_chunks_and_codec(
+            options.concurrent_target(),
+            num_chunks,
+            options,
+            &codec_concurrency,
+        );
+        let chunk_concurrent_limit = self.memory_limited_chunk_concurrency(
+            chunk_concurrent_limit,
+            &chunk_representation,
+            (array_subset.num_elements_usize() * data_type_size) as u64,
+            &options,
+        )?;
+
+        // Batch the retrieval of the chunks read in full
+        let chunks_encoded = if let Some(max_waste_ratio) = options.chunk_read_max_waste_ratio() {
+            self.async_retrieve_encoded_chunks_coalesced(
+                chunks,
+                array_subset,
+                max_waste_ratio,
+                &options,
+            )
+            .await?
+        } else {
+            HashMap::new()
+        };
+        let chunks_encoded = &chunks_encoded;
+
+        // Split the output into a disjoint region for each chunk
+        let chunk_indices: Vec<ArrayIndices> = chunks.indices().iter().collect();
+        let output_subsets = chunk_indices
+            .iter()
+            .map(|chunk_indices| {
+                let chunk_subset = self.chunk_subset(chunk_indices)?;
+                let chunk_subset_overlap = chunk_subset
+                    .overlap(array_subset)?
+                    .relative_to(array_subset.start())?;
+                Ok(ArraySubset::new_with_start_shape(
+                    std::iter::zip(chunk_subset_overlap.start(), output_offset)
+                        .map(|(start, offset)| start + offset)
+                        .collect(),
+                    chunk_subset_overlap.shape().to_vec(),
+                )?)
+            })
+            .collect::<Result<Vec<_>, ArrayError>>()?;
+        let output_regions =
+            partition_output(output, output_shape, data_type_size, output_subsets)?;
+
+        futures::stream::iter(chunk_indices.into_iter().zip(output_regions))
+            .map(Ok)
+            .try_for_each_concurrent(
+                Some(chunk_concurrent_limit),
+                |(chunk_indices, mut output_region)| {
+                    let options = options.clone();
+                    async move {
+                        let chunk_subset = self.chunk_subset(&chunk_indices)?;
+                        let chunk_subset_overlap = chunk_subset.overlap(array_subset)?;
+                        let chunk_subset_bytes = self
+                            .async_retrieve_chunk_subset_prefetched(
+                                &chunk_indices,
+                                &chunk_subset_overlap.relative_to(chunk_subset.start())?,
+                                chunks_encoded,
+                                &options,
+                            )
+                            .await
+                            .map_err(|err| self.chunk_error(&chunk_indices, err))?;
+                        let chunk_subset_bytes = chunk_subset_bytes.into_fixed()?;
+                        output_region.write(&chunk_subset_bytes)?;
+                        options.recycle_bytes(chunk_subset_bytes);
+                        Ok::<_, ArrayError>(())
+                    }
+                },
+            )
+            .await
+    }
+
+    /// Retrieve the `chunk_subset` of the chunk at `chunk_indices`, decoding the chunk from `chunks_encoded` if it was retrieved in advance.
+    async fn async_retrieve_chunk_subset_prefetched(
+        &self,
+        chunk_indices: &[u64],
+        chunk_subset: &ArraySubset,
+        chunks_encoded: &HashMap<Vec<u64>, MaybeAsyncBytes>,
+        options: &CodecOptions,
+    ) -> Result<ArrayBytes<'_>, ArrayError> {
+        match chunks_encoded.get(chunk_indices) {
+            Some(Some(encoded)) => {
+                self.async_decode_chunk_encoded(chunk_indices, encoded.clone(), options)
+                    .await
+            }
+            Some(None) => {
+                let array_size =
+                    ArraySize::new(self.data_type().size(), chunk_subset.num_elements());
+                Ok(ArrayBytes::new_fill_value(array_size, self.fill_value()))
+            }
+            None => {
+                self.async_retrieve_chunk_subset_opt(chunk_indices, chunk_subset, options)
+                    .await
+            }
+        }
+    }
+
+    /// Decode an encoded chunk with the async codec executor of `options`.
+    async fn async_decode_chunk(
+        &self,
+        chunk_encoded: AsyncBytes,
+        chunk_representation: &ChunkRepresentation,
+        options: &CodecOptions,
+    ) -> Result<ArrayBytes<'static>, CodecError> {
+        let executor = options.async_codec_executor();
+        if executor.is_inline() {
+            return Ok(self
+                .codecs()
+                .decode(Cow::Borrowed(&chunk_encoded), chunk_representation, options)?
+                .into_owned());
+        }
+        let codecs = self.codecs.clone();
+        let chunk_representation = chunk_representation.clone();
+        let options = options.clone();
+        executor
+            .execute(move || {
+                Ok(codecs
+                    .decode(
+                        Cow::Borrowed(&chunk_encoded),
+                        &chunk_representation,
+                        &options,
+                    )?
+                    .into_owned())
+            })
+            .await
+    }
+
     /// Async variant of [`retrieve_chunk_opt`](Array::retrieve_chunk_opt).
     #[allow(clippy::missing_errors_doc)]
     pub async fn async_retrieve_chunk_opt(
@@ -356,6 +695,43 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
         }
     }
 
+    /// Async variant of [`retrieve_chunk_sparse_opt`](Array::retrieve_chunk_sparse_opt).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_retrieve_chunk_sparse_opt(
+        &self,
+        chunk_indices: &[u64],
+        options: &CodecOptions,
+    ) -> Result<SparseArrayBytes, ArrayError> {
+        let chunk_representation = self.chunk_array_representation(chunk_indices)?;
+        let Some(element_size) = self.data_type().fixed_size() else {
+            return Err(CodecError::ExpectedFixedLengthBytes.into());
+        };
+        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+        let storage_transformer = self
+            .storage_transformers()
+            .create_async_readable_transformer(storage_handle);
+        let chunk_encoded = crate::storage::async_retrieve_chunk(
+            &*storage_transformer,
+            self.path(),
+            chunk_indices,
+            self.chunk_key_encoding(),
+        )
+        .await
+        .map_err(ArrayError::StorageError)?;
+        if let Some(chunk_encoded) = chunk_encoded {
+            Ok(self.codecs().decode_sparse(
+                Cow::Borrowed(&chunk_encoded),
+                &chunk_representation,
+                options,
+            )?)
+        } else {
+            Ok(SparseArrayBytes::new_fill_value(
+                chunk_representation.num_elements(),
+                element_size,
+            ))
+        }
+    }
+
     /// Async variant of [`retrieve_chunk_elements_if_exists_opt`](Array::retrieve_chunk_elements_if_exists_opt).
     #[allow(clippy::missing_errors_doc)]
     pub async fn async_retrieve_chunk_elements_if_exists_opt<T: ElementOwned + Send + Sync>(
@@ -388,6 +764,19 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
         Ok(elements)
     }
 
+    /// Async variant of [`retrieve_chunk_bytes_opt`](Array::retrieve_chunk_bytes_opt).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_retrieve_chunk_bytes_opt(
+        &self,
+        chunk_indices: &[u64],
+        options: &CodecOptions,
+    ) -> Result<AsyncBytes, ArrayError> {
+        Ok(self
+            .async_retrieve_chunk_opt(chunk_indices, options)
+            .await?
+            .into_fixed_shared()?)
+    }
+
     #[cfg(feature = "ndarray")]
     /// Async variant of [`retrieve_chunk_ndarray_if_exists_opt`](Array::retrieve_chunk_ndarray_if_exists_opt).
     #[allow(clippy::missing_errors_doc)]
@@ -430,6 +819,20 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
         elements_to_ndarray(&shape, elements)
     }
 
+    #[cfg(feature = "ndarray")]
+    /// Async variant of [`retrieve_chunk_ndarray_arc_opt`](Array::retrieve_chunk_ndarray_arc_opt).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_retrieve_chunk_ndarray_arc_opt<T: ElementOwned + Send + Sync>(
+        &self,
+        chunk_indices: &[u64],
+        options: &CodecOptions,
+    ) -> Result<ndarray::ArcArray<T, ndarray::IxDyn>, ArrayError> {
+        Ok(self
+            .async_retrieve_chunk_ndarray_opt(chunk_indices, options)
+            .await?
+            .into_shared())
+    }
+
     /// Retrieve the encoded bytes of the chunks in `chunks`.
     ///
     /// The chunks are in order of the chunk indices returned by `chunks.indices().into_iter()`.
@@ -470,6 +873,10 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
 
     /// Async variant of [`retrieve_chunks_opt`](Array::retrieve_chunks_opt).
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), chunks = %chunks))
+    )]
     pub async fn async_retrieve_chunks_opt(
         &self,
         chunks: &ArraySubset,
@@ -508,15 +915,120 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
         options: &CodecOptions,
     ) -> Result<ndarray::ArrayD<T>, ArrayError> {
         let array_subset = self.chunks_subset(chunks)?;
-        let elements = self
-            .async_retrieve_chunks_elements_opt(chunks, options)
-            .await?;
-        elements_to_ndarray(array_subset.shape(), elements)
+        self.async_retrieve_array_subset_ndarray_opt(&array_subset, options)
+            .await
+    }
+
+    /// Return a stream of the decoded chunks in `chunks` with their chunk indices, in the order of their indices.
+    ///
+    /// Chunks are retrieved and decoded concurrently, and each chunk is yielded as soon as it and all preceding chunks are available.
+    /// Unlike [`async_retrieve_chunks_opt`](Array::async_retrieve_chunks_opt), consumers can process chunks without waiting for all chunks to be assembled into a single subset.
+    /// [`async_chunks_stream_unordered`](Array::async_chunks_stream_unordered) yields chunks in the order that they are decoded instead.
+    ///
+    /// # Errors
+    /// The stream yields an [`ArrayError`] if
+    ///  - `chunks` is not compatible with the array dimensionality, or
+    ///  - there is a codec decoding error, or
+    ///  - an underlying store error.
+    pub fn async_chunks_stream<'a>(
+        &'a self,
+        chunks: &ArraySubset,
+        options: &CodecOptions,
+    ) -> impl Stream<Item = Result<(ArrayIndices, ArrayBytes<'a>), ArrayError>> + 'a {
+        self.async_chunks_stream_impl(chunks, options, true)
+    }
+
+    /// Return a stream of the decoded chunks in `chunks` with their chunk indices, in the order that they are decoded.
+    ///
+    /// See [`async_chunks_stream`](Array::async_chunks_stream).
+    ///
+    /// # Errors
+    /// The stream yields an [`ArrayError`] if
+    ///  - `chunks` is not compatible with the array dimensionality, or
+    ///  - there is a codec decoding error, or
+    ///  - an underlying store error.
+    pub fn async_chunks_stream_unordered<'a>(
+        &'a self,
+        chunks: &ArraySubset,
+        options: &CodecOptions,
+    ) -> impl Stream<Item = Result<(ArrayIndices, ArrayBytes<'a>), ArrayError>> + 'a {
+        self.async_chunks_stream_impl(chunks, options, false)
+    }
+
+    fn async_chunks_stream_impl<'a>(
+        &'a self,
+        chunks: &ArraySubset,
+        options: &CodecOptions,
+        ordered: bool,
+    ) -> impl Stream<Item = Result<(ArrayIndices, ArrayBytes<'a>), ArrayError>> + 'a {
+        if chunks.dimensionality() != self.dimensionality() {
+            return futures::stream::once(futures::future::ready(Err(
+                ArrayError::InvalidArraySubset(chunks.clone(), self.shape().to_vec()),
+            )))
+            .right_stream();
+        }
+
+        // Calculate chunk/codec concurrency
+        let num_chunks = chunks.num_elements_usize();
+        let concurrency = self
+            .chunk_array_representation(&vec![0; self.dimensionality()])
+            .and_then(|chunk_representation| {
+                let codec_concurrency =
+                    self.recommended_codec_concurrency(&chunk_representation)?;
+                let (chunk_concurrent_limit, options) = concurrency_chunks_and_codec(
+                    options.concurrent_target(),
+                    num_chunks,
+                    options,
+                    &codec_concurrency,
+                );
+                let chunk_concurrent_limit = self.memory_limited_chunk_concurrency(
+                    chunk_concurrent_limit,
+                    &chunk_representation,
+                    0,
+                    &options,
+                )?;
+                Ok((chunk_concurrent_limit, options))
+            });
+        let (chunk_concurrent_limit, options) = match concurrency {
+            Ok(concurrency) => concurrency,
+            Err(err) => {
+                return futures::stream::once(futures::future::ready(Err(err))).right_stream();
+            }
+        };
+        let chunk_concurrent_limit = chunk_concurrent_limit.max(1);
+
+        let chunks = chunks.clone();
+        let futures = futures::stream::iter((0..chunks.num_elements()).map(move |index| {
+            let chunk_indices: ArrayIndices =
+                std::iter::zip(unravel_index(index, chunks.shape()), chunks.start())
+                    .map(|(index, start)| index + start)
+                    .collect();
+            let options = options.clone();
+            async move {
+                let chunk_bytes = self
+                    .async_retrieve_chunk_opt(&chunk_indices, &options)
+                    .await
+                    .map_err(|err| self.chunk_error(&chunk_indices, err))?;
+                Ok((chunk_indices, chunk_bytes))
+            }
+        }));
+        if ordered {
+            futures.buffered(chunk_concurrent_limit).left_stream()
+        } else {
+            futures
+                .buffer_unordered(chunk_concurrent_limit)
+                .right_stream()
+        }
+        .left_stream()
     }
 
     /// Async variant of [`retrieve_array_subset_opt`](Array::retrieve_array_subset_opt).
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
     #[allow(clippy::too_many_lines)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), array_subset = %array_subset))
+    )]
     pub async fn async_retrieve_array_subset_opt(
         &self,
         array_subset: &ArraySubset,
@@ -563,97 +1075,83 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
                     .await
                 }
             }
-            _ => {
-                // Calculate chunk/codec concurrency
-                let chunk_representation =
-                    self.chunk_array_representation(&vec![0; self.dimensionality()])?;
-                let codec_concurrency =
-                    self.recommended_codec_concurrency(&chunk_representation)?;
-                let (chunk_concurrent_limit, options) = concurrency_chunks_and_codec(
-                    options.concurrent_target(),
-                    num_chunks,
-                    options,
-                    &codec_concurrency,
-                );
+            _ => match self.data_type().size() {
+                DataTypeSize::Variable => {
+                    // Calculate chunk/codec concurrency
+                    let chunk_representation =
+                        self.chunk_array_representation(&vec![0; self.dimensionality()])?;
+                    let codec_concurrency =
+                        self.recommended_codec_concurrency(&chunk_representation)?;
+                    let (_chunk_concurrent_limit, options) = concurrency_chunks_and_codec(
+                        options.concurrent_target(),
+                        num_chunks,
+                        options,
+                        &codec_concurrency,
+                    );
 
-                match chunk_representation.data_type().size() {
-                    DataTypeSize::Variable => {
-                        let retrieve_chunk = |chunk_indices: Vec<u64>| {
-                            let options = options.clone();
-                            async move {
-                                let chunk_subset = self.chunk_subset(&chunk_indices)?;
-                                let chunk_subset_overlap = chunk_subset.overlap(array_subset)?;
-                                Ok::<_, ArrayError>((
-                                    self.async_retrieve_chunk_subset_opt(
-                                        &chunk_indices,
-                                        &chunk_subset_overlap.relative_to(chunk_subset.start())?,
-                                        &options,
-                                    )
-                                    .await?,
-                                    chunk_subset_overlap.relative_to(array_subset.start())?,
-                                ))
-                            }
+                    // Batch the retrieval of the chunks read in full
+                    let chunks_encoded =
+                        if let Some(max_waste_ratio) = options.chunk_read_max_waste_ratio() {
+                            self.async_retrieve_encoded_chunks_coalesced(
+                                &chunks,
+                                array_subset,
+                                max_waste_ratio,
+                                &options,
+                            )
+                            .await?
+                        } else {
+                            HashMap::new()
                         };
+                    let chunks_encoded = &chunks_encoded;
 
-                        // TODO: chunk_concurrent_limit
-                        let chunk_bytes_and_subsets = futures::future::try_join_all(
-                            chunks.indices().iter().map(retrieve_chunk),
-                        )
-                        .await?;
-
-                        Ok(merge_chunks_vlen(
-                            chunk_bytes_and_subsets,
-                            array_subset.shape(),
-                        )?)
-                    }
-                    DataTypeSize::Fixed(data_type_size) => {
-                        let size_output =
-                            usize::try_from(array_subset.num_elements() * data_type_size as u64)
-                                .unwrap();
-                        let mut output = Vec::with_capacity(size_output);
-                        {
-                            let output =
-                                UnsafeCellSlice::new_from_vec_with_spare_capacity(&mut output);
-                            let retrieve_chunk = |chunk_indices: Vec<u64>| {
-                                let options = options.clone();
-                                async move {
-                                    let chunk_subset = self.chunk_subset(&chunk_indices)?;
-                                    let chunk_subset_overlap =
-                                        chunk_subset.overlap(array_subset)?;
-                                    let chunk_subset_bytes = self
-                                        .async_retrieve_chunk_subset_opt(
-                                            &chunk_indices,
-                                            &chunk_subset_overlap
-                                                .relative_to(chunk_subset.start())?,
-                                            &options,
-                                        )
-                                        .await?;
-                                    let chunk_subset_bytes = chunk_subset_bytes.into_fixed()?;
-                                    let output = unsafe { output.get() };
-                                    update_bytes_flen(
-                                        output,
-                                        array_subset.shape(),
-                                        &chunk_subset_bytes,
-                                        &chunk_subset_overlap.relative_to(array_subset.start())?,
-                                        data_type_size,
-                                    );
-                                    Ok::<_, ArrayError>(())
-                                }
-                            };
-
-                            futures::stream::iter(&chunks.indices())
-                                .map(Ok)
-                                .try_for_each_concurrent(
-                                    Some(chunk_concurrent_limit),
-                                    retrieve_chunk,
+                    let retrieve_chunk = |chunk_indices: Vec<u64>| {
+                        let options = options.clone();
+                        async move {
+                            let chunk_subset = self.chunk_subset(&chunk_indices)?;
+                            let chunk_subset_overlap = chunk_subset.overlap(array_subset)?;
+                            Ok::<_, ArrayError>((
+                                self.async_retrieve_chunk_subset_prefetched(
+                                    &chunk_indices,
+                                    &chunk_subset_overlap.relative_to(chunk_subset.start())?,
+                                    chunks_encoded,
+                                    &options,
                                 )
-                                .await?;
+                                .await
+                                .map_err(|err| self.chunk_error(&chunk_indices, err))?,
+                                chunk_subset_overlap.relative_to(array_subset.start())?,
+                            ))
                         }
-                        unsafe { output.set_len(size_output) };
-                        Ok(ArrayBytes::from(output))
-                    }
+                    };
+
+                    // TODO: chunk_concurrent_limit
+                    let chunk_bytes_and_subsets =
+                        futures::future::try_join_all(chunks.indices().iter().map(retrieve_chunk))
+                            .await?;
+
+                    Ok(merge_chunks_vlen(
+                        &chunk_bytes_and_subsets,
+                        array_subset.shape(),
+                    )?)
                 }
-            }
+                DataTypeSize::Fixed(data_type_size) => {
+                    let size_output =
+                        usize::try_from(array_subset.num_elements() * data_type_size as u64)
+                            .unwrap();
+                    let mut output = options.take_buffer(size_output);
+                    self.async_retrieve_array_subset_into(
+                        array_subset,
+                        &chunks,
+                        data_type_size,
+                        unsafe { &mut vec_spare_capacity_to_mut_slice(&mut output)[..size_output] },
+                        array_subset.shape(),
+                        &vec![0; array_subset.dimensionality()],
+                        options,
+                    )
+                    .await?;
+                    unsafe { output.set_len(size_output) };
+                    Ok(ArrayBytes::from(output))
+                }
+            },
         }
     }
 
@@ -671,22 +1169,148 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
         Ok(elements)
     }
 
+    /// Async variant of [`retrieve_array_subset_bytes_opt`](Array::retrieve_array_subset_bytes_opt).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_retrieve_array_subset_bytes_opt(
+        &self,
+        array_subset: &ArraySubset,
+        options: &CodecOptions,
+    ) -> Result<AsyncBytes, ArrayError> {
+        Ok(self
+            .async_retrieve_array_subset_opt(array_subset, options)
+            .await?
+            .into_fixed_shared()?)
+    }
+
     #[cfg(feature = "ndarray")]
     /// Async variant of [`retrieve_array_subset_ndarray_opt`](Array::retrieve_array_subset_ndarray_opt).
-    #[allow(clippy::missing_errors_doc)]
+    #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
     pub async fn async_retrieve_array_subset_ndarray_opt<T: ElementOwned + Send + Sync>(
         &self,
         array_subset: &ArraySubset,
         options: &CodecOptions,
     ) -> Result<ndarray::ArrayD<T>, ArrayError> {
-        let elements = self
-            .async_retrieve_array_subset_elements_opt(array_subset, options)
+        let DataTypeSize::Fixed(data_type_size) = self.data_type().size() else {
+            let elements = self
+                .async_retrieve_array_subset_elements_opt(array_subset, options)
+                .await?;
+            return elements_to_ndarray(array_subset.shape(), elements);
+        };
+        let Some(native_bytes) = T::native_bytes_representation(self.data_type()) else {
+            let elements = self
+                .async_retrieve_array_subset_elements_opt(array_subset, options)
+                .await?;
+            return elements_to_ndarray(array_subset.shape(), elements);
+        };
+
+        if array_subset.dimensionality() != self.dimensionality() {
+            return Err(ArrayError::InvalidArraySubset(
+                array_subset.clone(),
+                self.shape().to_vec(),
+            ));
+        }
+        let Some(chunks) = self.chunks_in_array_subset(array_subset)? else {
+            return Err(ArrayError::InvalidArraySubset(
+                array_subset.clone(),
+                self.shape().to_vec(),
+            ));
+        };
+
+        // Decode directly into the elements of the output array
+        let mut array = ndarray::ArrayD::<T>::from_elem(
+            iter_u64_to_usize(array_subset.shape().iter()),
+            native_bytes.zeroed(),
+        );
+        {
+            let output = native_bytes.as_bytes_mut(
+                array
+                    .as_slice_mut()
+                    .expect("a new array has a standard layout"),
+            );
+            self.async_retrieve_array_subset_into(
+                array_subset,
+                &chunks,
+                data_type_size,
+                output,
+                array_subset.shape(),
+                &vec![0; array_subset.dimensionality()],
+                options,
+            )
             .await?;
-        elements_to_ndarray(array_subset.shape(), elements)
+        }
+        Ok(array)
+    }
+
+    #[cfg(feature = "ndarray")]
+    /// Async variant of [`retrieve_array_subset_ndarray_arc_opt`](Array::retrieve_array_subset_ndarray_arc_opt).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_retrieve_array_subset_ndarray_arc_opt<T: ElementOwned + Send + Sync>(
+        &self,
+        array_subset: &ArraySubset,
+        options: &CodecOptions,
+    ) -> Result<ndarray::ArcArray<T, ndarray::IxDyn>, ArrayError> {
+        Ok(self
+            .async_retrieve_array_subset_ndarray_opt(array_subset, options)
+            .await?
+            .into_shared())
+    }
+
+    /// Async variant of [`retrieve_array_subset_into_view_opt`](Array::retrieve_array_subset_into_view_opt).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_retrieve_array_subset_into_view_opt<T: ElementOwned + Send + Sync>(
+        &self,
+        array_subset: &ArraySubset,
+        output: &mut [T],
+        output_shape: &[u64],
+        output_offset: &[u64],
+        options: &CodecOptions,
+    ) -> Result<(), ArrayError> {
+        let DataTypeSize::Fixed(data_type_size) = self.data_type().size() else {
+            return Err(ArrayError::IncompatibleElementType);
+        };
+        let Some(native_bytes) = T::native_bytes_representation(self.data_type()) else {
+            return Err(ArrayError::IncompatibleElementType);
+        };
+        if output_offset.len() != array_subset.dimensionality() {
+            return Err(IncompatibleDimensionalityError::new(
+                output_offset.len(),
+                array_subset.dimensionality(),
+            )
+            .into());
+        }
+        if array_subset.dimensionality() != self.dimensionality() {
+            return Err(ArrayError::InvalidArraySubset(
+                array_subset.clone(),
+                self.shape().to_vec(),
+            ));
+        }
+        let Some(chunks) = self.chunks_in_array_subset(array_subset)? else {
+            return Err(ArrayError::InvalidArraySubset(
+                array_subset.clone(),
+                self.shape().to_vec(),
+            ));
+        };
+
+        // Decode directly into the elements of the output
+        let output = native_bytes.as_bytes_mut(output);
+        self.async_retrieve_array_subset_into(
+            array_subset,
+            &chunks,
+            data_type_size,
+            output,
+            output_shape,
+            output_offset,
+            options,
+        )
+        .await
     }
 
     /// Async variant of [`retrieve_chunk_subset_opt`](Array::retrieve_chunk_subset_opt).
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), chunk_indices = ?chunk_indices, chunk_subset = %chunk_subset))
+    )]
     pub async fn async_retrieve_chunk_subset_opt(
         &self,
         chunk_indices: &[u64],
@@ -701,23 +1325,24 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
             ));
         }
 
-        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
-        let storage_transformer = self
-            .storage_transformers()
-            .create_async_readable_transformer(storage_handle);
-        let input_handle = Arc::new(AsyncStoragePartialDecoder::new(
-            storage_transformer,
-            self.chunk_key(chunk_indices),
-        ));
-
-        let bytes = self
-            .codecs()
-            .async_partial_decoder(input_handle, &chunk_representation, options)
-            .await?
-            .partial_decode_opt(&[chunk_subset.clone()], options)
+        let bytes = if chunk_subset.start().iter().all(|&o| o == 0)
+            && chunk_subset.shape() == chunk_representation.shape_u64()
+        {
+            // Fast path if `chunk_subset` encompasses the whole chunk
+            self.async_retrieve_chunk_opt(chunk_indices, options)
+                .await?
+        } else {
+            async_cancellable(options.cancellation_token(), async {
+                Ok(self
+                    .async_partial_decoder_opt(chunk_indices, options)
+                    .await?
+                    .partial_decode_opt(std::slice::from_ref(chunk_subset), options)
+                    .await?
+                    .remove(0)
+                    .into_owned())
+            })
             .await?
-            .remove(0)
-            .into_owned();
+        };
         bytes.validate(chunk_subset.num_elements(), self.data_type().size())?;
         Ok(bytes)
     }
@@ -768,6 +1393,18 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
             self.chunk_key(chunk_indices),
         ));
         let chunk_representation = self.chunk_array_representation(chunk_indices)?;
+        #[cfg(feature = "sharding")]
+        if let Some((shard_index_cache, sharding_codec)) = &self.shard_index_cache {
+            return Ok(sharding_codec
+                .async_partial_decoder_with_shard_index_cache(
+                    input_handle,
+                    &chunk_representation,
+                    shard_index_cache,
+                    chunk_indices,
+                    options,
+                )
+                .await?);
+        }
         Ok(self
             .codecs()
             .async_partial_decoder(input_handle, &chunk_representation, options)
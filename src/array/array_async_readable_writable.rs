@@ -1,15 +1,157 @@
+use std::sync::Arc;
+
 use futures::{StreamExt, TryStreamExt};
 
 use crate::{
-    array::ArrayBytes, array_subset::ArraySubset, storage::AsyncReadableWritableStorageTraits,
+    array::ArrayBytes,
+    array_subset::ArraySubset,
+    metadata::attributes_merge_patch,
+    storage::{
+        async_backup_metadata, AsyncReadableWritableStorageTraits, StorageError, StorageHandle,
+    },
 };
 
 use super::{
     array_bytes::update_array_bytes, codec::options::CodecOptions,
-    concurrency::concurrency_chunks_and_codec, Array, ArrayError, Element,
+    concurrency::concurrency_chunks_and_codec, metadata_keys, Array, ArrayCreateError, ArrayError,
+    ArrayMetadataOptions, Element,
 };
 
 impl<TStorage: ?Sized + AsyncReadableWritableStorageTraits + 'static> Array<TStorage> {
+    /// Async variant of [`store_metadata`](Array::store_metadata).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_store_metadata(&self) -> Result<(), StorageError> {
+        self.async_store_metadata_opt(&ArrayMetadataOptions::default())
+            .await
+    }
+
+    /// Async variant of [`store_metadata_opt`](Array::store_metadata_opt).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_store_metadata_opt(
+        &self,
+        options: &ArrayMetadataOptions,
+    ) -> Result<(), StorageError> {
+        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+        let storage_transformer = self
+            .storage_transformers()
+            .create_async_writable_transformer(storage_handle);
+
+        // Get the metadata with options applied and store
+        let metadata = self.metadata_opt(options);
+        crate::group::async_store_array_implicit_group_metadata(
+            &self.storage,
+            self.path(),
+            &metadata,
+        )
+        .await?;
+        crate::storage::async_create_array_opt(
+            &*storage_transformer,
+            self.path(),
+            &metadata,
+            options.metadata_json_format(),
+        )
+        .await
+    }
+
+    /// Async variant of [`store_metadata_if_unchanged`](Array::store_metadata_if_unchanged).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_store_metadata_if_unchanged(&mut self) -> Result<(), StorageError> {
+        self.async_store_metadata_if_unchanged_opt(&ArrayMetadataOptions::default())
+            .await
+    }
+
+    /// Async variant of [`store_metadata_if_unchanged_opt`](Array::store_metadata_if_unchanged_opt).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_store_metadata_if_unchanged_opt(
+        &mut self,
+        options: &ArrayMetadataOptions,
+    ) -> Result<(), StorageError> {
+        if let Some(key) = self.async_stale_metadata_key().await? {
+            return Err(StorageError::VersionMismatch(key.clone()));
+        }
+        self.async_store_metadata_and_versions(options).await
+    }
+
+    /// Async variant of [`store_metadata_with_backup`](Array::store_metadata_with_backup).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_store_metadata_with_backup(&mut self) -> Result<(), StorageError> {
+        self.async_store_metadata_with_backup_opt(&ArrayMetadataOptions::default())
+            .await
+    }
+
+    /// Async variant of [`store_metadata_with_backup_opt`](Array::store_metadata_with_backup_opt).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_store_metadata_with_backup_opt(
+        &mut self,
+        options: &ArrayMetadataOptions,
+    ) -> Result<(), StorageError> {
+        let keys = metadata_keys(self.path(), &self.metadata_opt(options));
+        let versions = async_backup_metadata(&*self.storage, &keys).await?;
+        for (key, version) in keys.into_iter().zip(versions) {
+            if self.storage.get_version(&key).await? != version {
+                return Err(StorageError::VersionMismatch(key));
+            }
+        }
+        self.async_store_metadata_and_versions(options).await
+    }
+
+    /// Async variant of [`restore_metadata_backup`](Array::restore_metadata_backup).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_restore_metadata_backup(&mut self) -> Result<(), ArrayCreateError> {
+        let keys = metadata_keys(self.path(), self.metadata());
+        crate::storage::async_restore_metadata_backup(&*self.storage, &keys).await?;
+        self.async_refresh_metadata().await
+    }
+
+    /// Async variant of [`store_metadata_and_versions`](Array::store_metadata_and_versions).
+    async fn async_store_metadata_and_versions(
+        &mut self,
+        options: &ArrayMetadataOptions,
+    ) -> Result<(), StorageError> {
+        self.async_store_metadata_opt(options).await?;
+        let mut metadata_versions = Vec::new();
+        for key in metadata_keys(self.path(), &self.metadata_opt(options)) {
+            let version = self.storage.get_version(&key).await?;
+            metadata_versions.push((key, version));
+        }
+        self.set_metadata_versions(metadata_versions);
+        Ok(())
+    }
+
+    /// Async variant of [`update_attributes`](Array::update_attributes).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_update_attributes(
+        &mut self,
+        patch: &serde_json::Map<String, serde_json::Value>,
+        if_unchanged: bool,
+    ) -> Result<(), ArrayCreateError> {
+        self.async_update_attributes_with(
+            |attributes| attributes_merge_patch(attributes, patch),
+            if_unchanged,
+        )
+        .await
+    }
+
+    /// Async variant of [`update_attributes_with`](Array::update_attributes_with).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_update_attributes_with<
+        F: FnOnce(&mut serde_json::Map<String, serde_json::Value>),
+    >(
+        &mut self,
+        update: F,
+        if_unchanged: bool,
+    ) -> Result<(), ArrayCreateError> {
+        self.async_refresh_metadata().await?;
+        update(self.attributes_mut());
+        let options = ArrayMetadataOptions::default();
+        if if_unchanged {
+            self.async_store_metadata_if_unchanged_opt(&options).await?;
+        } else {
+            self.async_store_metadata_and_versions(&options).await?;
+        }
+        Ok(())
+    }
+
     /// Async variant of [`store_chunk_subset`](Array::store_chunk_subset).
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
     pub async fn async_store_chunk_subset<'a>(
@@ -116,6 +258,10 @@ impl<TStorage: ?Sized + AsyncReadableWritableStorageTraits + 'static> Array<TSto
 
     /// Async variant of [`store_chunk_subset_opt`](Array::store_chunk_subset_opt).
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), chunk_indices = ?chunk_indices, chunk_subset = %chunk_subset))
+    )]
     pub async fn async_store_chunk_subset_opt<'a>(
         &self,
         chunk_indices: &[u64],
@@ -159,7 +305,7 @@ impl<TStorage: ?Sized + AsyncReadableWritableStorageTraits + 'static> Array<TSto
             let chunk_bytes_new = update_array_bytes(
                 chunk_bytes_old,
                 chunk_shape,
-                chunk_subset_bytes,
+                &chunk_subset_bytes,
                 chunk_subset,
                 self.data_type().size(),
             );
@@ -218,6 +364,10 @@ impl<TStorage: ?Sized + AsyncReadableWritableStorageTraits + 'static> Array<TSto
     /// Async variant of [`store_array_subset_opt`](Array::store_array_subset_opt).
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
     #[allow(clippy::too_many_lines)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), array_subset = %array_subset))
+    )]
     pub async fn async_store_array_subset_opt<'a>(
         &self,
         array_subset: &ArraySubset,
@@ -242,6 +392,10 @@ impl<TStorage: ?Sized + AsyncReadableWritableStorageTraits + 'static> Array<TSto
         };
         let num_chunks = chunks.num_elements_usize();
         if num_chunks == 1 {
+            let _permit = match options.in_flight_chunks_semaphore() {
+                Some(semaphore) => Some(semaphore.acquire_arc().await),
+                None => None,
+            };
             let chunk_indices = chunks.start();
             let chunk_subset = self.chunk_subset(chunk_indices)?;
             if array_subset == &chunk_subset {
@@ -273,23 +427,33 @@ impl<TStorage: ?Sized + AsyncReadableWritableStorageTraits + 'static> Array<TSto
                 options,
                 &codec_concurrency,
             );
+            let chunk_concurrent_limit = self.memory_limited_chunk_concurrency(
+                chunk_concurrent_limit,
+                &chunk_representation,
+                0,
+                &options,
+            )?;
 
+            let subset_bytes = &subset_bytes;
             let store_chunk = |chunk_indices: Vec<u64>| {
-                let chunk_subset = self.chunk_subset(&chunk_indices).unwrap(); // FIXME: unwrap
-                let overlap = unsafe { array_subset.overlap_unchecked(&chunk_subset) };
-                let chunk_subset_in_array_subset =
-                    unsafe { overlap.relative_to_unchecked(array_subset.start()) };
-                let array_subset_in_chunk_subset =
-                    unsafe { overlap.relative_to_unchecked(chunk_subset.start()) };
-                let chunk_subset_bytes = subset_bytes
-                    .extract_array_subset(
+                let options = options.clone();
+                async move {
+                    // Wait for a chunk to complete before extracting another if the in flight chunk limit is reached
+                    let _permit = match options.in_flight_chunks_semaphore() {
+                        Some(semaphore) => Some(semaphore.acquire_arc().await),
+                        None => None,
+                    };
+                    let chunk_subset = self.chunk_subset(&chunk_indices)?;
+                    let overlap = unsafe { array_subset.overlap_unchecked(&chunk_subset) };
+                    let chunk_subset_in_array_subset =
+                        unsafe { overlap.relative_to_unchecked(array_subset.start()) };
+                    let array_subset_in_chunk_subset =
+                        unsafe { overlap.relative_to_unchecked(chunk_subset.start()) };
+                    let chunk_subset_bytes = subset_bytes.extract_array_subset(
                         &chunk_subset_in_array_subset,
                         array_subset.shape(),
                         self.data_type(),
-                    )
-                    .unwrap(); // FIXME: unwrap
-                let options = options.clone();
-                async move {
+                    )?;
                     self.async_store_chunk_subset_opt(
                         &chunk_indices,
                         &array_subset_in_chunk_subset,
@@ -297,6 +461,7 @@ impl<TStorage: ?Sized + AsyncReadableWritableStorageTraits + 'static> Array<TSto
                         &options,
                     )
                     .await
+                    .map_err(|err| self.chunk_error(&chunk_indices, err))
                 }
             };
 
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use futures::{StreamExt, TryStreamExt};
+use web_time::Instant;
 
 use crate::{
     array::ArrayBytes,
@@ -13,35 +14,16 @@ use crate::{
 };
 
 use super::{
-    codec::{options::CodecOptions, ArrayToBytesCodecTraits},
+    cancellation_token::async_cancellable,
+    codec::{options::CodecOptions, ArrayToBytesCodecTraits, CodecError},
     concurrency::concurrency_chunks_and_codec,
-    Array, ArrayError, ArrayMetadata, ArrayMetadataOptions, Element,
+    Array, ArrayError, ArrayMetadata, ChunkRepresentation, Element, SparseArrayBytes,
 };
 
-impl<TStorage: ?Sized + AsyncWritableStorageTraits + 'static> Array<TStorage> {
-    /// Async variant of [`store_metadata`](Array::store_metadata).
-    #[allow(clippy::missing_errors_doc)]
-    pub async fn async_store_metadata(&self) -> Result<(), StorageError> {
-        self.async_store_metadata_opt(&ArrayMetadataOptions::default())
-            .await
-    }
-
-    /// Async variant of [`store_metadata_opt`](Array::store_metadata_opt).
-    #[allow(clippy::missing_errors_doc)]
-    pub async fn async_store_metadata_opt(
-        &self,
-        options: &ArrayMetadataOptions,
-    ) -> Result<(), StorageError> {
-        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
-        let storage_transformer = self
-            .storage_transformers()
-            .create_async_writable_transformer(storage_handle);
-
-        // Get the metadata with options applied and store
-        let metadata = self.metadata_opt(options);
-        crate::storage::async_create_array(&*storage_transformer, self.path(), &metadata).await
-    }
+#[cfg(feature = "sharding")]
+use super::ArrayShardedExt;
 
+impl<TStorage: ?Sized + AsyncWritableStorageTraits + 'static> Array<TStorage> {
     /// Async variant of [`store_chunk`](Array::store_chunk).
     #[allow(clippy::missing_errors_doc)]
     pub async fn async_store_chunk<'a>(
@@ -53,6 +35,17 @@ impl<TStorage: ?Sized + AsyncWritableStorageTraits + 'static> Array<TStorage> {
             .await
     }
 
+    /// Async variant of [`store_chunk_sparse`](Array::store_chunk_sparse).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_store_chunk_sparse(
+        &self,
+        chunk_indices: &[u64],
+        chunk_sparse: &SparseArrayBytes,
+    ) -> Result<(), ArrayError> {
+        self.async_store_chunk_sparse_opt(chunk_indices, chunk_sparse, &CodecOptions::default())
+            .await
+    }
+
     /// Async variant of [`store_chunk_elements`](Array::store_chunk_elements).
     #[allow(clippy::missing_errors_doc)]
     pub async fn async_store_chunk_elements<T: Element + Send + Sync>(
@@ -160,6 +153,10 @@ impl<TStorage: ?Sized + AsyncWritableStorageTraits + 'static> Array<TStorage> {
 
     /// Async variant of [`erase_chunk`](Array::erase_chunk).
     #[allow(clippy::missing_errors_doc)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), chunk_indices = ?chunk_indices))
+    )]
     pub async fn async_erase_chunk(&self, chunk_indices: &[u64]) -> Result<(), StorageError> {
         let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
         let storage_transformer = self
@@ -171,11 +168,19 @@ impl<TStorage: ?Sized + AsyncWritableStorageTraits + 'static> Array<TStorage> {
             chunk_indices,
             self.chunk_key_encoding(),
         )
-        .await
+        .await?;
+        self.invalidate_shard_index(chunk_indices);
+        self.async_store_chunk_sidecars(&*storage_transformer, chunk_indices, None)
+            .await?;
+        Ok(())
     }
 
     /// Async variant of [`erase_chunks`](Array::erase_chunks).
     #[allow(clippy::missing_errors_doc)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), chunks = %chunks))
+    )]
     pub async fn async_erase_chunks(&self, chunks: &ArraySubset) -> Result<(), StorageError> {
         let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
         let storage_transformer = self
@@ -190,7 +195,11 @@ impl<TStorage: ?Sized + AsyncWritableStorageTraits + 'static> Array<TStorage> {
                     &chunk_indices,
                     self.chunk_key_encoding(),
                 )
-                .await
+                .await?;
+                self.invalidate_shard_index(&chunk_indices);
+                self.async_store_chunk_sidecars(&*storage_transformer, &chunk_indices, None)
+                    .await?;
+                Ok(())
             }
         };
         futures::stream::iter(chunks.indices().into_iter())
@@ -205,6 +214,10 @@ impl<TStorage: ?Sized + AsyncWritableStorageTraits + 'static> Array<TStorage> {
 
     /// Async variant of [`store_chunk_opt`](Array::store_chunk_opt).
     #[allow(clippy::missing_errors_doc)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), chunk_indices = ?chunk_indices))
+    )]
     pub async fn async_store_chunk_opt<'a>(
         &self,
         chunk_indices: &[u64],
@@ -223,6 +236,117 @@ impl<TStorage: ?Sized + AsyncWritableStorageTraits + 'static> Array<TStorage> {
         let is_fill_value =
             !options.store_empty_chunks() && chunk_bytes.is_fill_value(self.fill_value());
         if is_fill_value {
+            async_cancellable(options.cancellation_token(), async {
+                Ok(self.async_erase_chunk(chunk_indices).await?)
+            })
+            .await?;
+        } else {
+            let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+            let storage_transformer = self
+                .storage_transformers()
+                .create_async_writable_transformer(storage_handle);
+            #[cfg(feature = "metrics")]
+            let decoded_size = chunk_bytes.size();
+            let chunk_sidecars = self.chunk_sidecars(&chunk_bytes);
+            let start = Instant::now();
+            let chunk_encoded = self
+                .async_encode_chunk(chunk_bytes, &chunk_array_representation, options)
+                .await
+                .map_err(ArrayError::CodecError)?;
+            options
+                .concurrency_strategy()
+                .record_codec_time(start.elapsed());
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_chunk_written(decoded_size, chunk_encoded.len());
+            let chunk_encoded = AsyncBytes::from(chunk_encoded);
+            // Shards can be large, so they are streamed to stores which support writing in parts
+            #[cfg(feature = "sharding")]
+            let is_sharded = self.is_sharded();
+            #[cfg(not(feature = "sharding"))]
+            let is_sharded = false;
+            let start = Instant::now();
+            let stored = async_cancellable(options.cancellation_token(), async {
+                if is_sharded {
+                    crate::storage::async_store_chunk_streaming(
+                        &*storage_transformer,
+                        self.path(),
+                        chunk_indices,
+                        self.chunk_key_encoding(),
+                        chunk_encoded,
+                    )
+                    .await?;
+                } else {
+                    crate::storage::async_store_chunk(
+                        &*storage_transformer,
+                        self.path(),
+                        chunk_indices,
+                        self.chunk_key_encoding(),
+                        chunk_encoded,
+                    )
+                    .await?;
+                }
+                Ok(())
+            })
+            .await;
+            // The shard may have been partially written if the store failed or was cancelled
+            self.invalidate_shard_index(chunk_indices);
+            stored?;
+            self.async_store_chunk_sidecars(
+                &*storage_transformer,
+                chunk_indices,
+                Some(&chunk_sidecars),
+            )
+            .await?;
+            options
+                .concurrency_strategy()
+                .record_storage_latency(start.elapsed());
+        }
+        Ok(())
+    }
+
+    /// Encode a chunk with the async codec executor of `options`.
+    async fn async_encode_chunk(
+        &self,
+        chunk_bytes: ArrayBytes<'_>,
+        chunk_representation: &ChunkRepresentation,
+        options: &CodecOptions,
+    ) -> Result<Vec<u8>, CodecError> {
+        let executor = options.async_codec_executor();
+        if executor.is_inline() {
+            return Ok(self
+                .codecs()
+                .encode(chunk_bytes, chunk_representation, options)?
+                .into_owned());
+        }
+        let codecs = self.codecs.clone();
+        let chunk_bytes = chunk_bytes.into_owned();
+        let chunk_representation = chunk_representation.clone();
+        let options = options.clone();
+        executor
+            .execute(move || {
+                Ok(codecs
+                    .encode(chunk_bytes, &chunk_representation, &options)?
+                    .into_owned())
+            })
+            .await
+    }
+
+    /// Async variant of [`store_chunk_sparse_opt`](Array::store_chunk_sparse_opt).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_store_chunk_sparse_opt(
+        &self,
+        chunk_indices: &[u64],
+        chunk_sparse: &SparseArrayBytes,
+        options: &CodecOptions,
+    ) -> Result<(), ArrayError> {
+        // Validation
+        let chunk_array_representation = self.chunk_array_representation(chunk_indices)?;
+        chunk_sparse.validate(
+            chunk_array_representation.num_elements(),
+            chunk_array_representation.fill_value().size(),
+        )?;
+
+        if chunk_sparse.is_fill_value() && !options.store_empty_chunks() {
             self.async_erase_chunk(chunk_indices).await?;
         } else {
             let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
@@ -231,15 +355,21 @@ impl<TStorage: ?Sized + AsyncWritableStorageTraits + 'static> Array<TStorage> {
                 .create_async_writable_transformer(storage_handle);
             let chunk_encoded = self
                 .codecs()
-                .encode(chunk_bytes, &chunk_array_representation, options)
+                .encode_sparse(chunk_sparse, &chunk_array_representation, options)
                 .map_err(ArrayError::CodecError)?;
-            let chunk_encoded = AsyncBytes::from(chunk_encoded.to_vec());
             crate::storage::async_store_chunk(
                 &*storage_transformer,
                 self.path(),
                 chunk_indices,
                 self.chunk_key_encoding(),
-                chunk_encoded,
+                AsyncBytes::from(chunk_encoded.into_owned()),
+            )
+            .await?;
+            self.invalidate_shard_index(chunk_indices);
+            self.async_store_chunk_sidecars(
+                &*storage_transformer,
+                chunk_indices,
+                Some(&self.chunk_sidecars_sparse(chunk_sparse)),
             )
             .await?;
         }
@@ -283,27 +413,43 @@ impl<TStorage: ?Sized + AsyncWritableStorageTraits + 'static> Array<TStorage> {
     }
 
     /// Async variant of [`store_chunks_opt`](Array::store_chunks_opt).
-    #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
-    #[allow(clippy::similar_names)]
+    #[allow(clippy::missing_errors_doc)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), chunks = %chunks))
+    )]
     pub async fn async_store_chunks_opt<'a>(
         &self,
         chunks: &ArraySubset,
         chunks_bytes: impl Into<ArrayBytes<'a>> + Send,
         options: &CodecOptions,
+    ) -> Result<(), ArrayError> {
+        self.async_store_chunks_opt_impl(chunks, chunks_bytes.into(), options, &|_, _| {})
+            .await
+    }
+
+    /// Store `chunks`, calling `on_chunk` with the chunk indices and `false` before each chunk is stored and `true` after it has been stored.
+    #[allow(clippy::missing_panics_doc, clippy::similar_names)]
+    pub(super) async fn async_store_chunks_opt_impl(
+        &self,
+        chunks: &ArraySubset,
+        chunks_bytes: ArrayBytes<'_>,
+        options: &CodecOptions,
+        on_chunk: &(dyn Fn(&[u64], bool) + Sync),
     ) -> Result<(), ArrayError> {
         let num_chunks = chunks.num_elements_usize();
         match num_chunks {
             0 => {
-                let chunks_bytes = chunks_bytes.into();
                 chunks_bytes.validate(0, self.data_type().size())?;
             }
             1 => {
                 let chunk_indices = chunks.start();
+                on_chunk(chunk_indices, false);
                 self.async_store_chunk_opt(chunk_indices, chunks_bytes, options)
                     .await?;
+                on_chunk(chunk_indices, true);
             }
             _ => {
-                let chunks_bytes = chunks_bytes.into();
                 let array_subset = self.chunks_subset(chunks)?;
                 chunks_bytes.validate(array_subset.num_elements(), self.data_type().size())?;
 
@@ -318,6 +464,12 @@ impl<TStorage: ?Sized + AsyncWritableStorageTraits + 'static> Array<TStorage> {
                     options,
                     &codec_concurrency,
                 );
+                let chunk_concurrent_limit = self.memory_limited_chunk_concurrency(
+                    chunk_concurrent_limit,
+                    &chunk_representation,
+                    0,
+                    &options,
+                )?;
 
                 let store_chunk = |chunk_indices: Vec<u64>| {
                     let chunk_subset = self.chunk_subset(&chunk_indices).unwrap(); // FIXME: unwrap
@@ -330,8 +482,12 @@ impl<TStorage: ?Sized + AsyncWritableStorageTraits + 'static> Array<TStorage> {
                         .unwrap(); // FIXME: unwrap
                     let options = options.clone();
                     async move {
+                        on_chunk(&chunk_indices, false);
                         self.async_store_chunk_opt(&chunk_indices, chunk_bytes, &options)
                             .await
+                            .map_err(|err| self.chunk_error(&chunk_indices, err))?;
+                        on_chunk(&chunk_indices, true);
+                        Ok::<_, ArrayError>(())
                     }
                 };
                 futures::stream::iter(&chunks.indices())
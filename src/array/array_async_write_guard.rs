@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use futures::{StreamExt, TryStreamExt};
+use parking_lot::Mutex;
+
+use crate::{
+    array_subset::ArraySubset,
+    storage::{AsyncWritableStorageTraits, StorageError},
+};
+
+use super::{codec::options::CodecOptions, Array, ArrayBytes, ArrayError, ArrayIndices};
+
+/// A guard tracking the chunks written by async chunk stores.
+///
+/// Async multi-chunk stores such as [`async_store_chunks_opt`](Array::async_store_chunks_opt) write chunks concurrently.
+/// If the store future is dropped or its [`CancellationToken`](crate::array::CancellationToken) is cancelled, pending store requests are aborted but chunks that were already written remain in the store.
+/// Storing through a write guard records each chunk as it is written, so that the outcome of an interrupted or failed store can be inspected with [`written_chunks`](AsyncArrayWriteGuard::written_chunks) and [`incomplete_chunks`](AsyncArrayWriteGuard::incomplete_chunks), and optionally undone with [`async_rollback`](AsyncArrayWriteGuard::async_rollback).
+///
+/// Each chunk is written with a single store request, so whether an incomplete chunk is visible depends on the atomicity of the store.
+/// A rollback erases written and incomplete chunks, it does not restore their previous values.
+///
+/// ```rust
+/// # use zarrs::array::{codec::CodecOptions, Array, ArrayError};
+/// # use zarrs::array_subset::ArraySubset;
+/// # use zarrs::storage::AsyncWritableStorageTraits;
+/// async fn store_or_roll_back<TStorage: ?Sized + AsyncWritableStorageTraits + 'static>(
+///     array: &Array<TStorage>,
+///     chunks: &ArraySubset,
+///     chunks_elements: Vec<u8>,
+///     options: &CodecOptions,
+/// ) -> Result<(), ArrayError> {
+///     let guard = array.async_write_guard();
+///     match guard.async_store_chunks_opt(chunks, chunks_elements, options).await {
+///         Ok(()) => {
+///             guard.commit();
+///             Ok(())
+///         }
+///         Err(err) => {
+///             // Erase the chunks written before the store was cancelled or failed
+///             guard.async_rollback().await?;
+///             Err(err)
+///         }
+///     }
+/// }
+/// ```
+#[must_use = "the guard should be committed or rolled back"]
+pub struct AsyncArrayWriteGuard<'a, TStorage: ?Sized> {
+    array: &'a Array<TStorage>,
+    /// The chunks that have been stored through the guard, and whether their store request completed.
+    chunks: Mutex<BTreeMap<ArrayIndices, bool>>,
+}
+
+impl<TStorage: ?Sized> core::fmt::Debug for AsyncArrayWriteGuard<'_, TStorage> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AsyncArrayWriteGuard")
+            .field("array", &self.array.path())
+            .field("chunks", &self.chunks.lock())
+            .finish()
+    }
+}
+
+impl<TStorage: ?Sized + AsyncWritableStorageTraits + 'static> Array<TStorage> {
+    /// Create an [`AsyncArrayWriteGuard`] for tracking the chunks written by async chunk stores.
+    pub fn async_write_guard(&self) -> AsyncArrayWriteGuard<'_, TStorage> {
+        AsyncArrayWriteGuard {
+            array: self,
+            chunks: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl<TStorage: ?Sized + AsyncWritableStorageTraits + 'static> AsyncArrayWriteGuard<'_, TStorage> {
+    fn record(&self, chunk_indices: &[u64], complete: bool) {
+        self.chunks.lock().insert(chunk_indices.to_vec(), complete);
+    }
+
+    /// Store a chunk with [`async_store_chunk_opt`](Array::async_store_chunk_opt) and record it.
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_store_chunk_opt<'a>(
+        &self,
+        chunk_indices: &[u64],
+        chunk_bytes: impl Into<ArrayBytes<'a>> + Send,
+        options: &CodecOptions,
+    ) -> Result<(), ArrayError> {
+        self.record(chunk_indices, false);
+        self.array
+            .async_store_chunk_opt(chunk_indices, chunk_bytes, options)
+            .await?;
+        self.record(chunk_indices, true);
+        Ok(())
+    }
+
+    /// Store chunks with [`async_store_chunks_opt`](Array::async_store_chunks_opt) and record each chunk.
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_store_chunks_opt<'a>(
+        &self,
+        chunks: &ArraySubset,
+        chunks_bytes: impl Into<ArrayBytes<'a>> + Send,
+        options: &CodecOptions,
+    ) -> Result<(), ArrayError> {
+        self.array
+            .async_store_chunks_opt_impl(
+                chunks,
+                chunks_bytes.into(),
+                options,
+                &|chunk_indices, complete| self.record(chunk_indices, complete),
+            )
+            .await
+    }
+
+    /// Return the indices of the chunks that have been written.
+    #[must_use]
+    pub fn written_chunks(&self) -> Vec<ArrayIndices> {
+        self.chunks
+            .lock()
+            .iter()
+            .filter(|(_, complete)| **complete)
+            .map(|(chunk_indices, _)| chunk_indices.clone())
+            .collect()
+    }
+
+    /// Return the indices of the chunks with a store request that was started but did not complete.
+    ///
+    /// This includes chunks with a store request that failed, was cancelled, or is still pending.
+    #[must_use]
+    pub fn incomplete_chunks(&self) -> Vec<ArrayIndices> {
+        self.chunks
+            .lock()
+            .iter()
+            .filter(|(_, complete)| !**complete)
+            .map(|(chunk_indices, _)| chunk_indices.clone())
+            .collect()
+    }
+
+    /// Commit the writes, returning the indices of the written chunks.
+    pub fn commit(self) -> Vec<ArrayIndices> {
+        self.written_chunks()
+    }
+
+    /// Roll back the writes by erasing the written and incomplete chunks.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying error with the store.
+    pub async fn async_rollback(self) -> Result<(), StorageError> {
+        let chunks = std::mem::take(&mut *self.chunks.lock());
+        futures::stream::iter(chunks.keys())
+            .map(Ok)
+            .try_for_each_concurrent(None, |chunk_indices| {
+                self.array.async_erase_chunk(chunk_indices)
+            })
+            .await
+    }
+}
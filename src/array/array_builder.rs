@@ -1,18 +1,34 @@
 use std::sync::Arc;
 
-use crate::{metadata::v3::AdditionalFields, node::NodePath, storage::StorageTransformerChain};
+use crate::{
+    config::global_config,
+    group::store_missing_group_metadata,
+    metadata::v3::AdditionalFields,
+    node::NodePath,
+    storage::{ReadableWritableStorageTraits, StorageTransformerChain},
+};
+
+#[cfg(feature = "async")]
+use crate::{
+    group::async_store_missing_group_metadata, storage::AsyncReadableWritableStorageTraits,
+};
 
 use super::{
     chunk_key_encoding::{ChunkKeyEncoding, DefaultChunkKeyEncoding},
-    codec::{
-        array_to_bytes::vlen::VlenCodec, ArrayToArrayCodecTraits, ArrayToBytesCodecTraits,
-        BytesCodec, BytesToBytesCodecTraits,
-    },
+    codec::{ArrayToArrayCodecTraits, ArrayToBytesCodecTraits, BytesToBytesCodecTraits},
+    codec_profile::default_array_to_bytes_codec,
     data_type::IncompatibleFillValueError,
-    Array, ArrayCreateError, ArrayMetadata, ArrayMetadataV3, ArrayShape, ChunkGrid,
+    metadata_keys, Array, ArrayCreateError, ArrayMetadata, ArrayMetadataV3, ArrayShape, ChunkGrid,
     ChunkKeySeparator, CodecChain, DataType, DimensionName, FillValue,
 };
 
+#[cfg(feature = "sharding")]
+use super::{
+    chunk_shape_to_array_shape,
+    codec::array_to_bytes::sharding::{ShardingCodecBuilder, ShardingIndexLocation},
+    ChunkShape,
+};
+
 /// An [`Array`] builder.
 ///
 /// The array builder is initialised from an array shape, data type, chunk grid, and fill value.
@@ -80,6 +96,14 @@ pub struct ArrayBuilder {
     pub dimension_names: Option<Vec<DimensionName>>,
     /// Additional fields.
     pub additional_fields: AdditionalFields,
+    /// Inner chunk shape.
+    ///
+    /// If set, the array codecs are the inner chunk codecs of a `sharding_indexed` codec and the chunk grid defines the shard shape.
+    #[cfg(feature = "sharding")]
+    pub inner_chunk_shape: Option<ChunkShape>,
+    /// Shard index location.
+    #[cfg(feature = "sharding")]
+    pub shard_index_location: ShardingIndexLocation,
 }
 
 impl ArrayBuilder {
@@ -94,7 +118,7 @@ impl ArrayBuilder {
         chunk_grid: ChunkGrid,
         fill_value: FillValue,
     ) -> Self {
-        let is_fixed_size = data_type.fixed_size().is_some();
+        let array_to_bytes_codec = default_array_to_bytes_codec(&data_type);
         Self {
             shape,
             data_type,
@@ -102,17 +126,16 @@ impl ArrayBuilder {
             chunk_key_encoding: ChunkKeyEncoding::new(DefaultChunkKeyEncoding::default()),
             fill_value,
             array_to_array_codecs: Vec::default(),
-            array_to_bytes_codec: if is_fixed_size {
-                Box::<BytesCodec>::default()
-            } else {
-                Box::<VlenCodec>::default()
-                // Box::<VlenV2Codec>::default()
-            },
+            array_to_bytes_codec,
             bytes_to_bytes_codecs: Vec::default(),
             attributes: serde_json::Map::default(),
             storage_transformers: StorageTransformerChain::default(),
             dimension_names: None,
             additional_fields: AdditionalFields::default(),
+            #[cfg(feature = "sharding")]
+            inner_chunk_shape: None,
+            #[cfg(feature = "sharding")]
+            shard_index_location: ShardingIndexLocation::default(),
         }
     }
 
@@ -213,6 +236,25 @@ impl ArrayBuilder {
         self
     }
 
+    /// Set the array to array, array to bytes, and bytes to bytes codecs from the [`CodecProfile`](crate::array::CodecProfile) registered as `name` in the global [`Config`](crate::config::Config#codec-profiles).
+    ///
+    /// The codecs are created for the current data type, so this should be called after changing the data type.
+    ///
+    /// # Errors
+    /// Returns [`ArrayCreateError::UnknownCodecProfile`] if no codec profile is registered as `name`.
+    pub fn codec_profile(&mut self, name: &str) -> Result<&mut Self, ArrayCreateError> {
+        let codec_profile = global_config()
+            .codec_profiles()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| ArrayCreateError::UnknownCodecProfile(name.to_string()))?;
+        let codecs = codec_profile.codecs(&self.data_type);
+        self.array_to_array_codecs = codecs.array_to_array_codecs().to_vec();
+        self.array_to_bytes_codec = codecs.array_to_bytes_codec().clone();
+        self.bytes_to_bytes_codecs = codecs.bytes_to_bytes_codecs().to_vec();
+        Ok(self)
+    }
+
     /// Set the user defined attributes.
     ///
     /// If left unmodified, the user defined attributes of the array will be empty.
@@ -230,7 +272,7 @@ impl ArrayBuilder {
     /// Use this cautiously. In general, store user defined attributes using [`ArrayBuilder::attributes`].
     ///
     /// Note that array metadata must not contain any additional fields, unless they are annotated with `"must_understand": false`.
-    /// `zarrs` will error when opening an array with additional fields without this annotation.
+    /// `zarrs` will error when opening an array with additional fields without this annotation, unless they are [supported](crate::config::Config#supported-additional-fields).
     pub fn additional_fields(&mut self, additional_fields: AdditionalFields) -> &mut Self {
         self.additional_fields = additional_fields;
         self
@@ -268,6 +310,52 @@ impl ArrayBuilder {
         self
     }
 
+    /// Shard the array into inner chunks with `inner_chunk_shape`.
+    ///
+    /// The array is encoded with a `sharding_indexed` codec, where the chunk grid defines the shard shape.
+    /// The array to array, array to bytes, and bytes to bytes codecs of the builder become the inner chunk codecs of the `sharding_indexed` codec.
+    /// The shard index is encoded with the `bytes` codec with native endian encoding and the `crc32c checksum` codec (if supported), and it is located at the index location set with [`shard_index_location`](ArrayBuilder::shard_index_location).
+    ///
+    /// The inner chunk shape must evenly divide the chunk shape, otherwise [`build`](ArrayBuilder::build) fails.
+    /// If left unmodified, the array is not sharded.
+    #[cfg(feature = "sharding")]
+    pub fn inner_chunk_shape(&mut self, inner_chunk_shape: Option<ChunkShape>) -> &mut Self {
+        self.inner_chunk_shape = inner_chunk_shape;
+        self
+    }
+
+    /// Set the shard index location.
+    ///
+    /// This only applies if the array is sharded with [`inner_chunk_shape`](ArrayBuilder::inner_chunk_shape).
+    /// If left unmodified, the shard index is located at the end of the shard.
+    #[cfg(feature = "sharding")]
+    pub fn shard_index_location(
+        &mut self,
+        shard_index_location: ShardingIndexLocation,
+    ) -> &mut Self {
+        self.shard_index_location = shard_index_location;
+        self
+    }
+
+    /// Return the codec chain of the array.
+    fn codec_chain(&self) -> CodecChain {
+        #[cfg(feature = "sharding")]
+        if let Some(inner_chunk_shape) = &self.inner_chunk_shape {
+            let sharding_codec = ShardingCodecBuilder::new(inner_chunk_shape.clone())
+                .array_to_array_codecs(self.array_to_array_codecs.clone())
+                .array_to_bytes_codec(self.array_to_bytes_codec.clone())
+                .bytes_to_bytes_codecs(self.bytes_to_bytes_codecs.clone())
+                .index_location(self.shard_index_location)
+                .build();
+            return CodecChain::new(vec![], Box::new(sharding_codec), vec![]);
+        }
+        CodecChain::new(
+            self.array_to_array_codecs.clone(),
+            self.array_to_bytes_codec.clone(),
+            self.bytes_to_bytes_codecs.clone(),
+        )
+    }
+
     /// Build into an [`Array`].
     ///
     /// # Errors
@@ -305,11 +393,26 @@ impl ArrayBuilder {
             }
         }
 
-        let codec_chain = CodecChain::new(
-            self.array_to_array_codecs.clone(),
-            self.array_to_bytes_codec.clone(),
-            self.bytes_to_bytes_codecs.clone(),
-        );
+        #[cfg(feature = "sharding")]
+        if let Some(inner_chunk_shape) = &self.inner_chunk_shape {
+            let inner_chunk_shape = chunk_shape_to_array_shape(inner_chunk_shape.as_slice());
+            if let Ok(Some(chunk_shape)) = self
+                .chunk_grid
+                .chunk_shape_u64(&vec![0; self.shape.len()], &self.shape)
+            {
+                if inner_chunk_shape.len() != chunk_shape.len()
+                    || std::iter::zip(&chunk_shape, &inner_chunk_shape)
+                        .any(|(chunk, inner_chunk)| chunk % inner_chunk != 0)
+                {
+                    return Err(ArrayCreateError::InvalidInnerChunkShape(
+                        inner_chunk_shape,
+                        chunk_shape,
+                    ));
+                }
+            }
+        }
+
+        let codec_chain = self.codec_chain();
 
         let array_metadata = ArrayMetadata::V3(ArrayMetadataV3::new(
             self.shape.clone(),
@@ -324,6 +427,11 @@ impl ArrayBuilder {
             self.additional_fields.clone(),
         ));
 
+        let metadata_versions = metadata_keys(&path, &array_metadata)
+            .into_iter()
+            .map(|key| (key, None))
+            .collect();
+
         Ok(Array {
             storage,
             path,
@@ -332,16 +440,18 @@ impl ArrayBuilder {
             chunk_grid: self.chunk_grid.clone(),
             chunk_key_encoding: self.chunk_key_encoding.clone(),
             fill_value: self.fill_value.clone(),
-            codecs: CodecChain::new(
-                self.array_to_array_codecs.clone(),
-                self.array_to_bytes_codec.clone(),
-                self.bytes_to_bytes_codecs.clone(),
-            ),
+            codecs: Arc::new(codec_chain),
             storage_transformers: self.storage_transformers.clone(),
             // attributes: self.attributes.clone(),
             dimension_names: self.dimension_names.clone(),
             // additional_fields: self.additional_fields.clone(),
+            metadata_versions,
+            metadata_diagnostics: Vec::new(),
             metadata: array_metadata,
+            #[cfg(feature = "sharding")]
+            shard_index_cache: None,
+            chunk_value_index: None,
+            chunk_statistics: false,
         })
     }
 
@@ -358,12 +468,63 @@ impl ArrayBuilder {
     ) -> Result<Arc<Array<TStorage>>, ArrayCreateError> {
         Ok(Arc::new(self.build(storage, path)?))
     }
+
+    /// Build into an [`Array`], creating any missing ancestor groups.
+    ///
+    /// Ancestor groups without metadata are stored with default Zarr V3 metadata, as in [`create_hierarchy`](crate::group::create_hierarchy).
+    /// Like [`build`](ArrayBuilder::build), the array metadata is not stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ArrayCreateError`] if there is an error creating the array or its ancestor groups.
+    pub fn build_with_parents<TStorage: ?Sized + ReadableWritableStorageTraits>(
+        &self,
+        storage: Arc<TStorage>,
+        path: &str,
+    ) -> Result<Array<TStorage>, ArrayCreateError> {
+        let array = self.build(storage, path)?;
+        if let Some(parent) = parent_path(array.path()) {
+            store_missing_group_metadata(&array.storage, &parent)?;
+        }
+        Ok(array)
+    }
+
+    #[cfg(feature = "async")]
+    /// Async variant of [`build_with_parents`](ArrayBuilder::build_with_parents).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_build_with_parents<TStorage: ?Sized + AsyncReadableWritableStorageTraits>(
+        &self,
+        storage: Arc<TStorage>,
+        path: &str,
+    ) -> Result<Array<TStorage>, ArrayCreateError> {
+        let array = self.build(storage, path)?;
+        if let Some(parent) = parent_path(array.path()) {
+            async_store_missing_group_metadata(&array.storage, &parent).await?;
+        }
+        Ok(array)
+    }
+}
+
+/// Return the path of the parent of the node at `path`, or [`None`] if it is the root node.
+fn parent_path(path: &NodePath) -> Option<NodePath> {
+    match path.as_str().rsplit_once('/') {
+        Some(("", name)) if !name.is_empty() => Some(NodePath::root()),
+        Some((parent, name)) if !name.is_empty() => {
+            Some(NodePath::new(parent).expect("the parent of a valid path is valid"))
+        }
+        _ => None,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        array::{chunk_grid::RegularChunkGrid, chunk_key_encoding::V2ChunkKeyEncoding},
+        array::{
+            chunk_grid::RegularChunkGrid, chunk_key_encoding::V2ChunkKeyEncoding,
+            CODEC_PROFILE_ARCHIVAL, CODEC_PROFILE_BALANCED, CODEC_PROFILE_FAST,
+            CODEC_PROFILE_IMAGING,
+        },
+        array_subset::ArraySubset,
         storage::{storage_transformer::UsageLogStorageTransformer, store::MemoryStore},
     };
 
@@ -456,4 +617,106 @@ mod tests {
         builder.dimension_names(["z", "y", "x"].into());
         assert!(builder.build(storage.clone(), "/").is_err());
     }
+
+    #[test]
+    fn array_builder_codec_profile() {
+        let storage = Arc::new(MemoryStore::new());
+        let mut builder = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt16,
+            vec![4, 4].try_into().unwrap(),
+            FillValue::from(0u16),
+        );
+        for name in [
+            CODEC_PROFILE_FAST,
+            CODEC_PROFILE_BALANCED,
+            CODEC_PROFILE_ARCHIVAL,
+            CODEC_PROFILE_IMAGING,
+        ] {
+            let array = builder
+                .codec_profile(name)
+                .unwrap()
+                .build(storage.clone(), "/array")
+                .unwrap();
+            let elements: Vec<u16> = (0..64).collect();
+            let array_subset = ArraySubset::new_with_shape(vec![8, 8]);
+            array
+                .store_array_subset_elements(&array_subset, &elements)
+                .unwrap();
+            assert_eq!(
+                array
+                    .retrieve_array_subset_elements::<u16>(&array_subset)
+                    .unwrap(),
+                elements
+            );
+        }
+        #[cfg(all(feature = "zstd", feature = "crc32c"))]
+        {
+            builder.codec_profile(CODEC_PROFILE_ARCHIVAL).unwrap();
+            assert_eq!(builder.bytes_to_bytes_codecs.len(), 2);
+        }
+        assert!(matches!(
+            builder.codec_profile("unknown"),
+            Err(ArrayCreateError::UnknownCodecProfile(_))
+        ));
+    }
+
+    #[cfg(feature = "sharding")]
+    #[test]
+    fn array_builder_inner_chunk_shape() {
+        use crate::array::ArrayShardedExt;
+
+        let storage = Arc::new(MemoryStore::new());
+        let mut builder = ArrayBuilder::new(
+            vec![8, 8],
+            DataType::UInt16,
+            vec![4, 4].try_into().unwrap(),
+            FillValue::from(0u16),
+        );
+        builder
+            .inner_chunk_shape(Some(vec![2, 2].try_into().unwrap()))
+            .shard_index_location(ShardingIndexLocation::Start);
+        #[cfg(feature = "gzip")]
+        builder.bytes_to_bytes_codecs(vec![Box::new(
+            crate::array::codec::GzipCodec::new(5).unwrap(),
+        )]);
+        let array = builder.build(storage.clone(), "/array").unwrap();
+        assert!(array.is_sharded());
+        assert_eq!(array.inner_chunk_grid_shape(), Some(vec![4, 4]));
+        assert!(array.codecs().bytes_to_bytes_codecs().is_empty());
+        let codec_metadata = array
+            .codecs()
+            .array_to_bytes_codec()
+            .create_metadata()
+            .unwrap();
+        let configuration: crate::array::codec::ShardingCodecConfiguration =
+            codec_metadata.to_configuration().unwrap();
+        let crate::array::codec::ShardingCodecConfiguration::V1(configuration) = configuration;
+        assert_eq!(configuration.index_location, ShardingIndexLocation::Start);
+        #[cfg(feature = "gzip")]
+        assert_eq!(configuration.codecs.len(), 2);
+
+        let elements: Vec<u16> = (0..64).collect();
+        let array_subset = ArraySubset::new_with_shape(vec![8, 8]);
+        array
+            .store_array_subset_elements(&array_subset, &elements)
+            .unwrap();
+        assert_eq!(
+            array
+                .retrieve_array_subset_elements::<u16>(&array_subset)
+                .unwrap(),
+            elements
+        );
+
+        // The inner chunk shape must evenly divide the chunk shape
+        builder.inner_chunk_shape(Some(vec![3, 2].try_into().unwrap()));
+        assert!(matches!(
+            builder.build(storage.clone(), "/array"),
+            Err(ArrayCreateError::InvalidInnerChunkShape(_, _))
+        ));
+        builder.inner_chunk_shape(Some(vec![2, 2, 2].try_into().unwrap()));
+        assert!(builder.build(storage.clone(), "/array").is_err());
+        builder.inner_chunk_shape(None);
+        assert!(!builder.build(storage, "/array").unwrap().is_sharded());
+    }
 }
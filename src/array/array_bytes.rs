@@ -1,6 +1,10 @@
-use std::borrow::Cow;
+use std::{
+    borrow::{Borrow, Cow},
+    mem::size_of,
+};
 
 use itertools::Itertools;
+use rayon::prelude::*;
 use thiserror::Error;
 
 use crate::{
@@ -9,9 +13,13 @@ use crate::{
 };
 
 use super::{
-    codec::CodecError, ravel_indices, ArrayShape, ArraySize, DataType, DataTypeSize, FillValue,
+    codec::CodecError, ravel_indices, ArrayError, ArrayShape, ArraySize, DataType, DataTypeSize,
+    Element, FillValue, UnsafeCellSlice,
 };
 
+#[cfg(feature = "ndarray")]
+use super::{elements_to_ndarray, iter_u64_to_usize, ElementOwned};
+
 /// Array element bytes.
 pub type RawBytes<'a> = Cow<'a, [u8]>;
 
@@ -35,6 +43,9 @@ pub enum ArrayBytesError {
     /// Invalid use of a fixed length method.
     #[error("Used a fixed length (flen) method on a variable length (vlen) array")]
     UsedFixedLengthMethodOnVariableLengthArray,
+    /// The bytes are not aligned to the element type.
+    #[error("the bytes are not aligned to the {_0} byte alignment of the element type")]
+    UnalignedBytes(usize),
 }
 
 impl<'a> ArrayBytes<'a> {
@@ -88,6 +99,16 @@ impl<'a> ArrayBytes<'a> {
         }
     }
 
+    /// Convert the array bytes into shared fixed size bytes.
+    ///
+    /// Owned bytes are not copied.
+    ///
+    /// # Errors
+    /// Returns a [`CodecError::ExpectedFixedLengthBytes`] if the bytes are variable length.
+    pub(crate) fn into_fixed_shared(self) -> Result<bytes::Bytes, CodecError> {
+        Ok(bytes::Bytes::from(self.into_fixed()?.into_owned()))
+    }
+
     /// Convert the array bytes into variable sized bytes and element byte offsets.
     ///
     /// # Errors
@@ -109,6 +130,142 @@ impl<'a> ArrayBytes<'a> {
         }
     }
 
+    /// Returns the size (in bytes) of the heap memory owned by the array bytes.
+    ///
+    /// Unlike [`size`](ArrayBytes::size), this includes the element offsets of a variable sized array and the spare capacity of owned allocations.
+    /// Borrowed bytes and offsets do not contribute to the heap size.
+    #[must_use]
+    pub fn heap_size(&self) -> usize {
+        match self {
+            Self::Fixed(bytes) => raw_bytes_heap_size(bytes),
+            Self::Variable(bytes, offsets) => {
+                raw_bytes_heap_size(bytes) + raw_bytes_offsets_heap_size(offsets)
+            }
+        }
+    }
+
+    /// Return a view of fixed length bytes as a slice of elements.
+    ///
+    /// Unlike [`ElementOwned::from_array_bytes`](crate::array::ElementOwned::from_array_bytes), the elements are not copied.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if
+    ///  - `T` is incompatible with `data_type`,
+    ///  - the bytes are variable length, or
+    ///  - the bytes are not aligned to `T` ([`ArrayBytesError::UnalignedBytes`]), in which case [`iter_elements`](ArrayBytes::iter_elements) can be used instead.
+    pub fn as_slice<T: Element + bytemuck::Pod>(
+        &self,
+        data_type: &DataType,
+    ) -> Result<&[T], ArrayError> {
+        T::validate_data_type(data_type)?;
+        let Self::Fixed(bytes) = self else {
+            return Err(CodecError::ExpectedFixedLengthBytes.into());
+        };
+        bytemuck::try_cast_slice(bytes).map_err(|err| match err {
+            bytemuck::PodCastError::TargetAlignmentGreaterAndInputNotAligned => {
+                ArrayBytesError::UnalignedBytes(std::mem::align_of::<T>()).into()
+            }
+            _ => ArrayError::InvalidBytesInputSize(bytes.len(), size_of::<T>() as u64),
+        })
+    }
+
+    /// Return an iterator over the elements of fixed length bytes.
+    ///
+    /// The elements are read without any alignment requirement.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if
+    ///  - `T` is incompatible with `data_type`,
+    ///  - the bytes are variable length, or
+    ///  - the length of the bytes is not a multiple of the size of `T`.
+    pub fn iter_elements<T: Element + bytemuck::Pod>(
+        &self,
+        data_type: &DataType,
+    ) -> Result<impl ExactSizeIterator<Item = T> + '_, ArrayError> {
+        T::validate_data_type(data_type)?;
+        let Self::Fixed(bytes) = self else {
+            return Err(CodecError::ExpectedFixedLengthBytes.into());
+        };
+        if bytes.len() % size_of::<T>() != 0 {
+            return Err(ArrayError::InvalidBytesInputSize(
+                bytes.len(),
+                size_of::<T>() as u64,
+            ));
+        }
+        Ok(bytes
+            .chunks_exact(size_of::<T>())
+            .map(bytemuck::pod_read_unaligned))
+    }
+
+    /// Return an iterator over the bytes of each element of variable length bytes.
+    ///
+    /// # Errors
+    /// Returns a [`CodecError::ExpectedVariableLengthBytes`] if the bytes are fixed length.
+    pub fn iter_variable(&self) -> Result<impl ExactSizeIterator<Item = &[u8]> + '_, CodecError> {
+        let Self::Variable(bytes, offsets) = self else {
+            return Err(CodecError::ExpectedVariableLengthBytes);
+        };
+        Ok(offsets
+            .windows(2)
+            .map(|offsets| &bytes[offsets[0]..offsets[1]]))
+    }
+
+    /// Return an iterator over each element of variable length bytes as a string.
+    ///
+    /// Each item is an [`ArrayError::InvalidElementValue`] error if the bytes of the element are not valid UTF-8.
+    ///
+    /// # Errors
+    /// Returns a [`CodecError::ExpectedVariableLengthBytes`] if the bytes are fixed length.
+    pub fn iter_str(
+        &self,
+    ) -> Result<impl ExactSizeIterator<Item = Result<&str, ArrayError>> + '_, CodecError> {
+        Ok(self
+            .iter_variable()?
+            .map(|bytes| std::str::from_utf8(bytes).map_err(|_| ArrayError::InvalidElementValue)))
+    }
+
+    #[cfg(feature = "ndarray")]
+    /// Return the array bytes as an [`ndarray::CowArray`] with `shape`.
+    ///
+    /// The array borrows the bytes without a copy if they are fixed length, the native representation of `T` (see [`ElementOwned::native_bytes_representation`]), and aligned to `T`.
+    /// Otherwise, the elements are copied into an owned array.
+    /// This is useful for viewing chunks held in a [chunk cache](crate::array::ChunkCache) without copying.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if `T` is incompatible with `data_type` or the number of elements does not match `shape`.
+    pub fn as_ndarray_cow<T: ElementOwned>(
+        &self,
+        data_type: &DataType,
+        shape: &[u64],
+    ) -> Result<ndarray::CowArray<'_, T, ndarray::IxDyn>, ArrayError> {
+        if let Self::Fixed(bytes) = self {
+            if let Some(elements) = T::native_bytes_representation(data_type)
+                .and_then(|native_bytes| native_bytes.try_from_bytes(bytes))
+            {
+                // A view may be created from a slice longer than the shape, so check the length exactly
+                let size_error = || -> ArrayError {
+                    CodecError::UnexpectedChunkDecodedSize(
+                        bytes.len(),
+                        shape.iter().product::<u64>() * size_of::<T>() as u64,
+                    )
+                    .into()
+                };
+                if elements.len() as u64 != shape.iter().product::<u64>() {
+                    return Err(size_error());
+                }
+                return ndarray::ArrayViewD::from_shape(iter_u64_to_usize(shape.iter()), elements)
+                    .map(ndarray::CowArray::from)
+                    .map_err(|_| size_error());
+            }
+        }
+        let bytes = match self {
+            Self::Fixed(bytes) => ArrayBytes::new_flen(&bytes[..]),
+            Self::Variable(bytes, offsets) => ArrayBytes::new_vlen(&bytes[..], &offsets[..]),
+        };
+        let elements = T::from_array_bytes(data_type, bytes)?;
+        Ok(elements_to_ndarray(shape, elements)?.into())
+    }
+
     /// Return the byte offsets for variable sized bytes. Returns [`None`] for fixed size bytes.
     #[must_use]
     pub fn offsets(&self) -> Option<&RawBytesOffsets<'a>> {
@@ -203,6 +360,28 @@ impl<'a> ArrayBytes<'a> {
 }
 
 /// Validate fixed length array bytes for a given array size.
+/// Returns the size (in bytes) of the heap memory owned by [`RawBytes`].
+///
+/// This is the capacity of owned bytes, or zero for borrowed bytes.
+#[must_use]
+pub fn raw_bytes_heap_size(bytes: &RawBytes) -> usize {
+    match bytes {
+        Cow::Borrowed(_) => 0,
+        Cow::Owned(bytes) => bytes.capacity(),
+    }
+}
+
+/// Returns the size (in bytes) of the heap memory owned by [`RawBytesOffsets`].
+///
+/// This is the capacity of owned offsets multiplied by the size of [`usize`], or zero for borrowed offsets.
+#[must_use]
+pub fn raw_bytes_offsets_heap_size(offsets: &RawBytesOffsets) -> usize {
+    match offsets {
+        Cow::Borrowed(_) => 0,
+        Cow::Owned(offsets) => offsets.capacity() * size_of::<usize>(),
+    }
+}
+
 fn validate_bytes_flen(bytes: &RawBytes, array_size: u64) -> Result<(), CodecError> {
     if bytes.len() as u64 == array_size {
         Ok(())
@@ -260,8 +439,16 @@ fn validate_bytes(
     }
 }
 
+/// The number of subset bytes above which [`update_bytes_flen`] copies in parallel.
+pub(crate) const UPDATE_BYTES_FLEN_PARALLEL_THRESHOLD: usize = 4 * 1024 * 1024;
+
+/// The maximum number of bytes copied by a single task when [`update_bytes_flen`] copies in parallel.
+pub(crate) const UPDATE_BYTES_FLEN_PARALLEL_BLOCK_SIZE: usize = 1024 * 1024;
+
 /// This function is used internally by various array/codec methods to write the bytes of a chunk subset into an output with an associated array subset.
 /// This approach only works for fixed length data types.
+///
+/// If `subset_bytes` is large, the copy is split across contiguous runs (and blocks of long runs) and performed in parallel.
 pub fn update_bytes_flen(
     output_bytes: &mut [u8],
     output_shape: &[u64],
@@ -281,15 +468,46 @@ pub fn update_bytes_flen(
     let contiguous_indices =
         unsafe { subset.contiguous_linearised_indices_unchecked(output_shape) };
     let length = contiguous_indices.contiguous_elements_usize() * data_type_size;
-    let mut decoded_offset = 0;
-    // TODO: Par iteration?
-    for (array_subset_element_index, _num_elements) in &contiguous_indices {
-        let output_offset = usize::try_from(array_subset_element_index).unwrap() * data_type_size;
-        debug_assert!((output_offset + length) <= output_bytes.len());
-        debug_assert!((decoded_offset + length) <= subset_bytes.len());
-        output_bytes[output_offset..output_offset + length]
-            .copy_from_slice(&subset_bytes[decoded_offset..decoded_offset + length]);
-        decoded_offset += length;
+    if length == 0 {
+        return;
+    }
+
+    if subset_bytes.len() > UPDATE_BYTES_FLEN_PARALLEL_THRESHOLD {
+        let output_offsets: Vec<usize> = contiguous_indices
+            .iter()
+            .map(|(array_subset_element_index, _num_elements)| {
+                usize::try_from(array_subset_element_index).unwrap() * data_type_size
+            })
+            .collect();
+        let output_bytes = UnsafeCellSlice::new(output_bytes);
+        subset_bytes
+            .par_chunks_exact(length)
+            .zip(output_offsets)
+            .for_each(|(subset_bytes, output_offset)| {
+                debug_assert!((output_offset + length) <= output_bytes.len());
+                subset_bytes
+                    .par_chunks(UPDATE_BYTES_FLEN_PARALLEL_BLOCK_SIZE)
+                    .enumerate()
+                    .for_each(|(block_index, block)| {
+                        let output_offset =
+                            output_offset + block_index * UPDATE_BYTES_FLEN_PARALLEL_BLOCK_SIZE;
+                        // SAFETY: each contiguous run (and each block within it) is written to a disjoint region of the output
+                        let output_bytes = unsafe { output_bytes.get() };
+                        output_bytes[output_offset..output_offset + block.len()]
+                            .copy_from_slice(block);
+                    });
+            });
+    } else {
+        let mut decoded_offset = 0;
+        for (array_subset_element_index, _num_elements) in &contiguous_indices {
+            let output_offset =
+                usize::try_from(array_subset_element_index).unwrap() * data_type_size;
+            debug_assert!((output_offset + length) <= output_bytes.len());
+            debug_assert!((decoded_offset + length) <= subset_bytes.len());
+            output_bytes[output_offset..output_offset + length]
+                .copy_from_slice(&subset_bytes[decoded_offset..decoded_offset + length]);
+            decoded_offset += length;
+        }
     }
 }
 
@@ -361,7 +579,7 @@ pub fn update_bytes_vlen<'a>(
 pub fn update_array_bytes<'a>(
     output_bytes: ArrayBytes,
     output_shape: ArrayShape,
-    subset_bytes: ArrayBytes,
+    subset_bytes: &ArrayBytes,
     subset: &ArraySubset,
     data_type_size: DataTypeSize,
 ) -> ArrayBytes<'a> {
@@ -374,8 +592,8 @@ pub fn update_array_bytes<'a>(
             &chunk_bytes,
             &chunk_offsets,
             output_shape,
-            &chunk_subset_bytes,
-            &chunk_subset_offsets,
+            chunk_subset_bytes,
+            chunk_subset_offsets,
             subset,
         ),
         (
@@ -387,7 +605,7 @@ pub fn update_array_bytes<'a>(
             update_bytes_flen(
                 &mut chunk_bytes,
                 &output_shape,
-                &chunk_subset_bytes,
+                chunk_subset_bytes,
                 subset,
                 data_type_size,
             );
@@ -402,8 +620,9 @@ pub fn update_array_bytes<'a>(
 /// Merge a set of chunks into an array subset.
 ///
 /// This function is used internally by [`retrieve_array_subset_opt`] and [`async_retrieve_array_subset_opt`].
-pub fn merge_chunks_vlen<'a>(
-    chunk_bytes_and_subsets: Vec<(ArrayBytes<'_>, ArraySubset)>,
+/// The chunk bytes can be owned, borrowed, or shared (e.g. [`Arc<ArrayBytes>`](std::sync::Arc) from a chunk cache), so they are not copied before merging.
+pub fn merge_chunks_vlen<'a, 'b, TBytes: Borrow<ArrayBytes<'b>>>(
+    chunk_bytes_and_subsets: &[(TBytes, ArraySubset)],
     array_shape: &[u64],
 ) -> Result<ArrayBytes<'a>, CodecError> {
     let num_elements = usize::try_from(array_shape.iter().product::<u64>()).unwrap();
@@ -412,7 +631,7 @@ pub fn merge_chunks_vlen<'a>(
     {
         // Validate the input
         let mut element_in_input = vec![0; num_elements];
-        for (_, chunk_subset) in &chunk_bytes_and_subsets {
+        for (_, chunk_subset) in chunk_bytes_and_subsets {
             // println!("{chunk_subset:?}");
             let indices = chunk_subset.linearised_indices(array_shape).unwrap();
             for idx in &indices {
@@ -426,8 +645,11 @@ pub fn merge_chunks_vlen<'a>(
     // Get the size of each element
     // TODO: Go parallel
     let mut element_sizes = vec![0; num_elements];
-    for (chunk_bytes, chunk_subset) in &chunk_bytes_and_subsets {
-        let chunk_offsets = chunk_bytes.offsets().unwrap();
+    for (chunk_bytes, chunk_subset) in chunk_bytes_and_subsets {
+        let chunk_offsets = chunk_bytes
+            .borrow()
+            .offsets()
+            .ok_or(CodecError::ExpectedVariableLengthBytes)?;
         debug_assert_eq!(chunk_offsets.len() as u64, chunk_subset.num_elements() + 1);
         let indices = chunk_subset.linearised_indices(array_shape).unwrap();
         debug_assert_eq!(chunk_offsets.len(), indices.len() + 1);
@@ -451,7 +673,9 @@ pub fn merge_chunks_vlen<'a>(
     // TODO: Go parallel
     let mut bytes = vec![0; *offsets.last().unwrap()];
     for (chunk_bytes, chunk_subset) in chunk_bytes_and_subsets {
-        let (chunk_bytes, chunk_offsets) = chunk_bytes.into_variable()?;
+        let ArrayBytes::Variable(chunk_bytes, chunk_offsets) = chunk_bytes.borrow() else {
+            return Err(CodecError::ExpectedVariableLengthBytes);
+        };
         let indices = chunk_subset.linearised_indices(array_shape).unwrap();
         for (subset_idx, (&chunk_curr, &chunk_next)) in
             indices.iter().zip(chunk_offsets.iter().tuple_windows())
@@ -582,6 +806,86 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn array_bytes_typed_views() -> Result<(), Box<dyn Error>> {
+        let data = [0u32, 1, 2, 3, 4];
+        let bytes = Element::into_array_bytes(&DataType::UInt32, &data)?;
+        assert_eq!(bytes.as_slice::<u32>(&DataType::UInt32)?, &data);
+        assert_eq!(
+            bytes
+                .iter_elements::<u32>(&DataType::UInt32)?
+                .collect::<Vec<_>>(),
+            data
+        );
+        assert!(matches!(
+            bytes.as_slice::<u16>(&DataType::UInt32),
+            Err(ArrayError::IncompatibleElementType)
+        ));
+        assert!(bytes.iter_variable().is_err());
+
+        // Unaligned bytes can be iterated but not viewed as a slice
+        let data_unaligned =
+            Element::into_array_bytes(&DataType::UInt32, &[0u32; 3])?.into_fixed()?;
+        let bytes_unaligned = ArrayBytes::new_flen(&data_unaligned[1..9]);
+        assert!(matches!(
+            bytes_unaligned.as_slice::<u32>(&DataType::UInt32),
+            Err(ArrayError::ArrayBytesError(
+                ArrayBytesError::UnalignedBytes(4)
+            ))
+        ));
+        assert_eq!(
+            bytes_unaligned
+                .iter_elements::<u32>(&DataType::UInt32)?
+                .collect::<Vec<_>>(),
+            [0, 0]
+        );
+
+        let data = ["a", "bb", "ccc"];
+        let bytes = Element::into_array_bytes(&DataType::String, &data)?;
+        assert_eq!(
+            bytes.iter_variable()?.collect::<Vec<_>>(),
+            [b"a".as_slice(), b"bb", b"ccc"]
+        );
+        assert_eq!(bytes.iter_str()?.collect::<Result<Vec<_>, _>>()?, data);
+        assert!(bytes.as_slice::<u8>(&DataType::UInt8).is_err());
+        let bytes_invalid = ArrayBytes::new_vlen(vec![0xff], vec![0, 1]);
+        assert!(bytes_invalid.iter_str()?.next().unwrap().is_err());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn array_bytes_as_ndarray_cow() -> Result<(), Box<dyn Error>> {
+        let data = [0u32, 1, 2, 3, 4, 5];
+        let bytes = Element::into_array_bytes(&DataType::UInt32, &data)?;
+        let array = bytes.as_ndarray_cow::<u32>(&DataType::UInt32, &[2, 3])?;
+        assert!(array.is_view());
+        assert_eq!(array, ndarray::array![[0u32, 1, 2], [3, 4, 5]].into_dyn());
+        assert!(bytes
+            .as_ndarray_cow::<u32>(&DataType::UInt32, &[2, 2])
+            .is_err());
+
+        let data = ["a", "bb"];
+        let bytes = Element::into_array_bytes(&DataType::String, &data)?;
+        let array = bytes.as_ndarray_cow::<String>(&DataType::String, &[2])?;
+        assert!(array.is_owned());
+        assert_eq!(array.iter().collect::<Vec<_>>(), ["a", "bb"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn array_bytes_heap_size() {
+        let bytes = vec![0u8; 6];
+        let offsets = vec![0usize, 1, 3, 6];
+        assert_eq!(ArrayBytes::new_flen(bytes.as_slice()).heap_size(), 0);
+        assert_eq!(ArrayBytes::new_flen(bytes.clone()).heap_size(), 6);
+        let array_bytes = ArrayBytes::new_vlen(bytes, offsets);
+        assert_eq!(array_bytes.size(), 6);
+        assert_eq!(array_bytes.heap_size(), 6 + 4 * size_of::<usize>());
+    }
+
     #[test]
     fn test_flen_update_subset() {
         let mut bytes_array = vec![0u8; 4 * 4];
@@ -606,4 +910,46 @@ mod tests {
             vec![0, 0, 0, 0, 0, 1, 2, 0, 0, 0, 0, 0, 3, 4, 0, 0]
         );
     }
+
+    #[test]
+    fn test_flen_update_subset_parallel() {
+        // Exceeds the parallel threshold with both many short runs and a few long runs
+        for (output_shape, subset) in [
+            (
+                vec![4096, 2048],
+                ArraySubset::new_with_ranges(&[1..4095, 3..2047]),
+            ),
+            (
+                vec![4, 2048, 1024],
+                ArraySubset::new_with_ranges(&[1..4, 0..2048, 0..1024]),
+            ),
+        ] {
+            let num_elements = usize::try_from(output_shape.iter().product::<u64>()).unwrap();
+            let subset_bytes: Vec<u8> = (0..subset.num_elements_usize())
+                .map(|i| u8::try_from(i % 251).unwrap())
+                .collect();
+            assert!(subset_bytes.len() > UPDATE_BYTES_FLEN_PARALLEL_THRESHOLD);
+
+            let mut bytes_array = vec![0u8; num_elements];
+            update_bytes_flen(
+                &mut bytes_array,
+                &output_shape,
+                &subset_bytes.as_slice().into(),
+                &subset,
+                1,
+            );
+
+            let mut bytes_array_expected = vec![0u8; num_elements];
+            for (subset_idx, array_idx) in subset
+                .linearised_indices(&output_shape)
+                .unwrap()
+                .iter()
+                .enumerate()
+            {
+                bytes_array_expected[usize::try_from(array_idx).unwrap()] =
+                    subset_bytes[subset_idx];
+            }
+            assert_eq!(bytes_array, bytes_array_expected);
+        }
+    }
 }
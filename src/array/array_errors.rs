@@ -2,10 +2,11 @@ use thiserror::Error;
 
 use crate::{
     array_subset::{ArraySubset, IncompatibleDimensionalityError},
+    byte_range::ByteRange,
     metadata::v3::UnsupportedAdditionalFieldError,
-    node::NodePathError,
+    node::{NodeNameError, NodePathError},
     plugin::PluginCreateError,
-    storage::StorageError,
+    storage::{StorageError, StoreKey},
 };
 
 use super::{
@@ -13,7 +14,7 @@ use super::{
     data_type::{
         IncompatibleFillValueError, IncompatibleFillValueMetadataError, UnsupportedDataTypeError,
     },
-    ArrayIndices, ArrayShape,
+    ArrayBytesError, ArrayIndices, ArrayShape, OutputPartitionError,
 };
 
 /// An array creation error.
@@ -22,6 +23,9 @@ pub enum ArrayCreateError {
     /// An invalid node path
     #[error(transparent)]
     NodePathError(#[from] NodePathError),
+    /// An invalid node name.
+    #[error(transparent)]
+    NodeNameError(#[from] NodeNameError),
     /// Unsupported additional field.
     #[error(transparent)]
     UnsupportedAdditionalFieldError(#[from] UnsupportedAdditionalFieldError),
@@ -61,6 +65,12 @@ pub enum ArrayCreateError {
     /// The Zarr V2 array is unsupported.
     #[error("unsupported Zarr V2 array: {_0}")]
     UnsupportedZarrV2Array(String),
+    /// The codec profile is not registered.
+    #[error("codec profile {_0} is not registered")]
+    UnknownCodecProfile(String),
+    /// The inner chunk shape is incompatible with the chunk shape.
+    #[error("inner chunk shape {_0:?} does not evenly divide chunk shape {_1:?}")]
+    InvalidInnerChunkShape(ArrayShape, ArrayShape),
 }
 
 /// Array errors.
@@ -109,4 +119,113 @@ pub enum ArrayError {
     ///  - a string with invalid utf-8 encoding.
     #[error("Invalid element value")]
     InvalidElementValue,
+    /// The operation was cancelled.
+    #[error("the operation was cancelled")]
+    Cancelled,
+    /// The operation requires more memory than the [memory limit](crate::array::codec::CodecOptions::memory_limit).
+    #[error("the operation requires at least {_0} bytes of memory, which exceeds the memory limit of {_1} bytes")]
+    MemoryLimitExceeded(u64, u64),
+    /// An error partitioning an output buffer.
+    #[error(transparent)]
+    OutputPartitionError(#[from] OutputPartitionError),
+    /// An error accessing array bytes.
+    #[error(transparent)]
+    ArrayBytesError(#[from] ArrayBytesError),
+    /// An error retrieving or storing a chunk in a multi-chunk operation.
+    #[error(transparent)]
+    ChunkError(Box<ChunkError>),
+}
+
+impl ArrayError {
+    /// Add the context of the chunk at `chunk_indices` with `key` to the error.
+    ///
+    /// Errors that already have chunk context and [`ArrayError::Cancelled`] are returned unchanged.
+    #[must_use]
+    pub fn with_chunk_context(self, chunk_indices: &[u64], key: StoreKey) -> Self {
+        match self {
+            Self::ChunkError(_) | Self::Cancelled => self,
+            _ => Self::ChunkError(Box::new(ChunkError::new(chunk_indices.to_vec(), key, self))),
+        }
+    }
+
+    /// Return the indices of the chunk involved in the error, if known.
+    #[must_use]
+    pub fn chunk_indices(&self) -> Option<&[u64]> {
+        match self {
+            Self::ChunkError(err) => Some(err.chunk_indices()),
+            Self::InvalidChunkGridIndicesError(chunk_indices)
+            | Self::InvalidChunkSubset(_, chunk_indices, _) => Some(chunk_indices),
+            _ => None,
+        }
+    }
+
+    /// Return the store key involved in the error, if known.
+    #[must_use]
+    pub fn store_key(&self) -> Option<&StoreKey> {
+        match self {
+            Self::ChunkError(err) => Some(err.key()),
+            Self::StorageError(err) => err.store_key(),
+            Self::CodecError(err) => err.store_key(),
+            _ => None,
+        }
+    }
+
+    /// Return the byte range involved in the error, if known.
+    #[must_use]
+    pub fn byte_range(&self) -> Option<ByteRange> {
+        match self {
+            Self::ChunkError(err) => err.error().byte_range(),
+            Self::StorageError(err) => err.byte_range(),
+            Self::CodecError(err) => err.byte_range(),
+            _ => None,
+        }
+    }
+}
+
+/// An error retrieving or storing a chunk.
+///
+/// Multi-chunk operations (e.g. [`Array::retrieve_array_subset`](crate::array::Array::retrieve_array_subset)) wrap the error of a failing chunk so that the chunk can be identified.
+#[derive(Debug, Error)]
+#[error("chunk {chunk_indices:?} with key {key}: {error}")]
+pub struct ChunkError {
+    chunk_indices: ArrayIndices,
+    key: StoreKey,
+    #[source]
+    error: ArrayError,
+}
+
+impl ChunkError {
+    /// Create a new [`ChunkError`] for the chunk at `chunk_indices` with `key`.
+    #[must_use]
+    pub fn new(chunk_indices: ArrayIndices, key: StoreKey, error: ArrayError) -> Self {
+        Self {
+            chunk_indices,
+            key,
+            error,
+        }
+    }
+
+    /// Return the chunk indices.
+    #[must_use]
+    pub fn chunk_indices(&self) -> &[u64] {
+        &self.chunk_indices
+    }
+
+    /// Return the store key of the chunk.
+    #[must_use]
+    pub const fn key(&self) -> &StoreKey {
+        &self.key
+    }
+
+    /// Return the underlying error.
+    #[must_use]
+    pub const fn error(&self) -> &ArrayError {
+        &self.error
+    }
+
+    /// Convert into the underlying error.
+    #[must_use]
+    pub fn into_error(self) -> ArrayError {
+        self.error
+    }
 }
@@ -1,10 +1,14 @@
-use crate::{config::global_config, metadata::MetadataConvertVersion};
+use crate::{
+    config::global_config,
+    metadata::{MetadataConvertVersion, MetadataJsonFormat},
+};
 
 /// Options for writing array metadata.
 #[derive(Debug, Clone)]
 pub struct ArrayMetadataOptions {
     experimental_codec_store_metadata_if_encode_only: bool,
     convert_version: MetadataConvertVersion,
+    json_format: MetadataJsonFormat,
     include_zarrs_metadata: bool,
 }
 
@@ -13,6 +17,7 @@ impl Default for ArrayMetadataOptions {
         Self {
             experimental_codec_store_metadata_if_encode_only: false,
             convert_version: MetadataConvertVersion::default(),
+            json_format: MetadataJsonFormat::default(),
             include_zarrs_metadata: global_config().include_zarrs_metadata(),
         }
     }
@@ -49,6 +54,18 @@ impl ArrayMetadataOptions {
         self
     }
 
+    /// Get the [metadata JSON format](crate::config::Config#metadata-json-format) configuration.
+    #[must_use]
+    pub fn metadata_json_format(&self) -> &MetadataJsonFormat {
+        &self.json_format
+    }
+
+    /// Set the [metadata JSON format](crate::config::Config#metadata-json-format) configuration.
+    pub fn set_metadata_json_format(&mut self, json_format: MetadataJsonFormat) -> &mut Self {
+        self.json_format = json_format;
+        self
+    }
+
     /// Get the [include zarrs metadata](crate::config::Config#include-zarrs-metadata) configuration.
     #[must_use]
     pub fn include_zarrs_metadata(&self) -> bool {
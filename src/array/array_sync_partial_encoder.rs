@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use crate::{
+    array_subset::ArraySubset,
+    storage::{ReadableWritableStorageTraits, StorageHandle},
+};
+
+use super::{
+    codec::{ArrayPartialEncoderTraits, CodecError, CodecOptions},
+    Array, ArrayBytes, ArrayIndices, DataType,
+};
+
+/// A partial encoder for the chunk of an [`Array`].
+///
+/// Wraps the partial encoder of the codec chain to invalidate the cached shard index and update the chunk sidecars of the chunk after each update.
+pub(super) struct ArrayChunkPartialEncoder<'a, TStorage: ?Sized> {
+    array: &'a Array<TStorage>,
+    chunk_indices: ArrayIndices,
+    inner: Arc<dyn ArrayPartialEncoderTraits + 'a>,
+}
+
+impl<'a, TStorage: ?Sized> ArrayChunkPartialEncoder<'a, TStorage> {
+    /// Create a new partial encoder for the chunk at `chunk_indices` of `array`.
+    pub(super) fn new(
+        array: &'a Array<TStorage>,
+        chunk_indices: ArrayIndices,
+        inner: Arc<dyn ArrayPartialEncoderTraits + 'a>,
+    ) -> Self {
+        Self {
+            array,
+            chunk_indices,
+            inner,
+        }
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits + 'static> ArrayPartialEncoderTraits
+    for ArrayChunkPartialEncoder<'_, TStorage>
+{
+    fn data_type(&self) -> &DataType {
+        self.inner.data_type()
+    }
+
+    fn erase(&self) -> Result<(), CodecError> {
+        self.inner.erase()?;
+        self.array.invalidate_shard_index(&self.chunk_indices);
+        self.update_chunk_sidecars()
+    }
+
+    fn partial_encode_opt(
+        &self,
+        subsets_and_bytes: &[(&ArraySubset, ArrayBytes<'_>)],
+        options: &CodecOptions,
+    ) -> Result<(), CodecError> {
+        self.inner.partial_encode_opt(subsets_and_bytes, options)?;
+        self.array.invalidate_shard_index(&self.chunk_indices);
+        self.update_chunk_sidecars()
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits + 'static>
+    ArrayChunkPartialEncoder<'_, TStorage>
+{
+    /// Update the sidecars of the chunk from its stored value, if any sidecar is maintained on write.
+    fn update_chunk_sidecars(&self) -> Result<(), CodecError> {
+        if !self.array.has_chunk_sidecars() {
+            return Ok(());
+        }
+        let chunk_bytes = self
+            .array
+            .retrieve_chunk_if_exists(&self.chunk_indices)
+            .map_err(|err| CodecError::Other(err.to_string()))?;
+        let chunk_sidecars = chunk_bytes.map(|bytes| self.array.chunk_sidecars(&bytes));
+        let storage_handle = Arc::new(StorageHandle::new(self.array.storage.clone()));
+        let storage_transformer = self
+            .array
+            .storage_transformers()
+            .create_writable_transformer(storage_handle);
+        Ok(self.array.store_chunk_sidecars(
+            &*storage_transformer,
+            &self.chunk_indices,
+            chunk_sidecars.as_ref(),
+        )?)
+    }
+}
@@ -1,33 +1,39 @@
-use std::{borrow::Cow, sync::Arc};
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
 
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use rayon_iter_concurrent_limit::iter_concurrent_limit;
+use web_time::Instant;
 
 use crate::{
     array::{ArrayBytes, ArrayMetadataV2},
-    array_subset::ArraySubset,
+    array_subset::{ArraySubset, IncompatibleDimensionalityError},
     metadata::MetadataRetrieveVersion,
     node::NodePath,
     storage::{
-        meta_key, meta_key_v2_array, meta_key_v2_attributes, ReadableStorageTraits, StorageError,
-        StorageHandle,
+        data_key, meta_key, meta_key_v2_array, meta_key_v2_attributes, Bytes, MaybeBytes,
+        ReadableStorageTraits, StorageError, StorageHandle, StoreKey,
     },
+    vec_spare_capacity_to_mut_slice,
 };
 
 use super::{
-    array_bytes::{merge_chunks_vlen, update_bytes_flen},
+    array_bytes::merge_chunks_vlen,
     codec::{
-        options::CodecOptions, ArrayPartialDecoderTraits, ArrayToBytesCodecTraits,
+        options::CodecOptions, ArrayPartialDecoderTraits, ArrayToBytesCodecTraits, CodecError,
         StoragePartialDecoder,
     },
     concurrency::concurrency_chunks_and_codec,
     element::ElementOwned,
-    unsafe_cell_slice::UnsafeCellSlice,
-    Array, ArrayCreateError, ArrayError, ArrayMetadata, ArrayMetadataV3, ArraySize, DataTypeSize,
+    output_regions::{partition_output, OutputRegion},
+    Array, ArrayCreateError, ArrayError, ArrayIndices, ArrayMetadata, ArrayMetadataV3, ArraySize,
+    DataTypeSize, SparseArrayBytes,
 };
 
 #[cfg(feature = "ndarray")]
-use super::elements_to_ndarray;
+use super::{elements_to_ndarray, iter_u64_to_usize};
+
+#[cfg(feature = "dlpack")]
+use super::{chunk_shape_to_array_shape, dlpack::DLPackTensor};
 
 impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
     /// Open an existing array in `storage` at `path` with default [`MetadataRetrieveVersion`].
@@ -64,35 +70,89 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
         if let MetadataRetrieveVersion::Default | MetadataRetrieveVersion::V3 = version {
             // Try V3
             let key_v3 = meta_key(&node_path);
-            if let Some(metadata) = storage.get(&key_v3)? {
-                let metadata: ArrayMetadataV3 = serde_json::from_slice(&metadata)
-                    .map_err(|err| StorageError::InvalidMetadata(key_v3, err.to_string()))?;
-                return Self::new_with_metadata(storage, path, ArrayMetadata::V3(metadata));
+            if let Some((metadata, metadata_version)) = storage.get_with_version(&key_v3)? {
+                let metadata: ArrayMetadataV3 =
+                    serde_json::from_slice(&metadata).map_err(|err| {
+                        StorageError::InvalidMetadata(key_v3.clone(), err.to_string())
+                    })?;
+                let mut array =
+                    Self::new_with_metadata(storage, path, ArrayMetadata::V3(metadata))?;
+                array.set_metadata_versions(vec![(key_v3, Some(metadata_version))]);
+                return Ok(array);
             }
         }
 
         if let MetadataRetrieveVersion::Default | MetadataRetrieveVersion::V2 = version {
             // Try V2
             let key_v2 = meta_key_v2_array(&node_path);
-            if let Some(metadata) = storage.get(&key_v2)? {
-                let mut metadata: ArrayMetadataV2 = serde_json::from_slice(&metadata)
-                    .map_err(|err| StorageError::InvalidMetadata(key_v2, err.to_string()))?;
+            if let Some((metadata, metadata_version)) = storage.get_with_version(&key_v2)? {
+                let mut metadata: ArrayMetadataV2 =
+                    serde_json::from_slice(&metadata).map_err(|err| {
+                        StorageError::InvalidMetadata(key_v2.clone(), err.to_string())
+                    })?;
 
                 let attributes_key = meta_key_v2_attributes(&node_path);
-                let attributes = storage.get(&attributes_key)?;
-                if let Some(attributes) = attributes {
+                let attributes = storage.get_with_version(&attributes_key)?;
+                let attributes_version = if let Some((attributes, attributes_version)) = attributes
+                {
                     metadata.attributes = serde_json::from_slice(&attributes).map_err(|err| {
-                        StorageError::InvalidMetadata(attributes_key, err.to_string())
+                        StorageError::InvalidMetadata(attributes_key.clone(), err.to_string())
                     })?;
-                }
+                    Some(attributes_version)
+                } else {
+                    None
+                };
 
-                return Self::new_with_metadata(storage, path, ArrayMetadata::V2(metadata));
+                let mut array =
+                    Self::new_with_metadata(storage, path, ArrayMetadata::V2(metadata))?;
+                array.set_metadata_versions(vec![
+                    (key_v2, Some(metadata_version)),
+                    (attributes_key, attributes_version),
+                ]);
+                return Ok(array);
             }
         }
 
         Err(ArrayCreateError::MissingMetadata)
     }
 
+    /// Returns true if the metadata in the store has changed since it was last read or written by this array.
+    ///
+    /// The versions of metadata keys are recorded when the array is opened and when metadata is stored with [`store_metadata_if_unchanged`](Array::store_metadata_if_unchanged).
+    /// An array that was not opened from the store expects its metadata to be absent.
+    /// Versions are provided by the store, see [`ReadableStorageTraits::get_with_version`].
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying store error.
+    pub fn metadata_is_stale(&self) -> Result<bool, StorageError> {
+        Ok(self.stale_metadata_key()?.is_some())
+    }
+
+    /// Returns the first metadata key with a version that has changed since it was last read or written by this array.
+    pub(crate) fn stale_metadata_key(&self) -> Result<Option<&StoreKey>, StorageError> {
+        for (key, version) in &self.metadata_versions {
+            if self.storage.get_version(key)? != *version {
+                return Ok(Some(key));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Read the metadata of the array from the store, replacing its current metadata.
+    ///
+    /// The metadata is read with the same Zarr version as the current metadata.
+    ///
+    /// # Errors
+    /// Returns [`ArrayCreateError`] if there is a storage error or any metadata is invalid.
+    pub fn refresh_metadata(&mut self) -> Result<(), ArrayCreateError> {
+        let version = match self.metadata {
+            ArrayMetadata::V3(_) => MetadataRetrieveVersion::V3,
+            ArrayMetadata::V2(_) => MetadataRetrieveVersion::V2,
+        };
+        *self = Self::open_opt(self.storage.clone(), self.path.as_str(), &version)?;
+        Ok(())
+    }
+
     /// Read and decode the chunk at `chunk_indices` into its bytes if it exists with default codec options.
     ///
     /// # Errors
@@ -169,6 +229,29 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
         .map(|maybe_bytes| maybe_bytes.map(|bytes| bytes.to_vec()))
     }
 
+    /// Retrieve the encoded bytes of a chunk as shared [`Bytes`].
+    ///
+    /// Unlike [`retrieve_encoded_chunk`](Array::retrieve_encoded_chunk), the bytes returned by the store are not copied.
+    ///
+    /// # Errors
+    /// Returns an [`StorageError`] if there is an underlying store error.
+    pub fn retrieve_encoded_chunk_bytes(
+        &self,
+        chunk_indices: &[u64],
+    ) -> Result<Option<Bytes>, StorageError> {
+        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+        let storage_transformer = self
+            .storage_transformers()
+            .create_readable_transformer(storage_handle);
+
+        crate::storage::retrieve_chunk(
+            &*storage_transformer,
+            self.path(),
+            chunk_indices,
+            self.chunk_key_encoding(),
+        )
+    }
+
     /// Read and decode the chunk at `chunk_indices` into its bytes or the fill value if it does not exist with default codec options.
     ///
     /// # Errors
@@ -183,6 +266,25 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
         self.retrieve_chunk_opt(chunk_indices, &CodecOptions::default())
     }
 
+    /// Read and decode the chunk at `chunk_indices` into its sparse representation with default codec options.
+    ///
+    /// Only the elements that differ from the fill value are held in memory.
+    /// If the array uses the `sparse` codec with no array to array codecs, the chunk is decoded without a dense intermediate.
+    /// A chunk that does not exist is entirely fill value.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if
+    ///  - `chunk_indices` are invalid,
+    ///  - the data type is not fixed size,
+    ///  - there is a codec decoding error, or
+    ///  - an underlying store error.
+    pub fn retrieve_chunk_sparse(
+        &self,
+        chunk_indices: &[u64],
+    ) -> Result<SparseArrayBytes, ArrayError> {
+        self.retrieve_chunk_sparse_opt(chunk_indices, &CodecOptions::default())
+    }
+
     /// Read and decode the chunk at `chunk_indices` into a vector of its elements or the fill value if it does not exist.
     ///
     /// # Errors
@@ -199,6 +301,20 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
         self.retrieve_chunk_elements_opt(chunk_indices, &CodecOptions::default())
     }
 
+    /// Read and decode the chunk at `chunk_indices` into shared [`Bytes`] or the fill value if it does not exist.
+    ///
+    /// The decoded bytes are not copied, so they can be handed to consumers of [`Bytes`] (e.g. HTTP response bodies) and sliced without copying.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if
+    ///  - the data type is variable length,
+    ///  - `chunk_indices` are invalid,
+    ///  - there is a codec decoding error, or
+    ///  - an underlying store error.
+    pub fn retrieve_chunk_bytes(&self, chunk_indices: &[u64]) -> Result<Bytes, ArrayError> {
+        self.retrieve_chunk_bytes_opt(chunk_indices, &CodecOptions::default())
+    }
+
     #[cfg(feature = "ndarray")]
     /// Read and decode the chunk at `chunk_indices` into an [`ndarray::ArrayD`]. It is filled with the fill value if it does not exist.
     ///
@@ -219,6 +335,41 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
         self.retrieve_chunk_ndarray_opt(chunk_indices, &CodecOptions::default())
     }
 
+    #[cfg(feature = "ndarray")]
+    /// Read and decode the chunk at `chunk_indices` into a shared [`ndarray::ArcArray`]. It is filled with the fill value if it does not exist.
+    ///
+    /// The decoded elements are moved into the shared array without a copy, so the result can be cheaply cloned and shared between threads.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if:
+    ///  - the size of `T` does not match the data type size,
+    ///  - the decoded bytes cannot be transmuted,
+    ///  - the chunk indices are invalid,
+    ///  - there is a codec decoding error, or
+    ///  - an underlying store error.
+    ///
+    /// # Panics
+    /// Will panic if a chunk dimension is larger than `usize::MAX`.
+    pub fn retrieve_chunk_ndarray_arc<T: ElementOwned>(
+        &self,
+        chunk_indices: &[u64],
+    ) -> Result<ndarray::ArcArray<T, ndarray::IxDyn>, ArrayError> {
+        self.retrieve_chunk_ndarray_arc_opt(chunk_indices, &CodecOptions::default())
+    }
+
+    #[cfg(feature = "dlpack")]
+    /// Read and decode the chunk at `chunk_indices` into a [`DLPackTensor`]. It is filled with the fill value if it does not exist.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if:
+    ///  - the data type has no `DLPack` equivalent,
+    ///  - the chunk indices are invalid,
+    ///  - there is a codec decoding error, or
+    ///  - an underlying store error.
+    pub fn retrieve_chunk_dlpack(&self, chunk_indices: &[u64]) -> Result<DLPackTensor, ArrayError> {
+        self.retrieve_chunk_dlpack_opt(chunk_indices, &CodecOptions::default())
+    }
+
     /// Retrieve the encoded bytes of the chunks in `chunks`.
     ///
     /// The chunks are in order of the chunk indices returned by `chunks.indices().into_iter()`.
@@ -396,6 +547,23 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
         self.retrieve_array_subset_elements_opt(array_subset, &CodecOptions::default())
     }
 
+    /// Read and decode the `array_subset` of array into shared [`Bytes`].
+    ///
+    /// The decoded bytes are not copied, so they can be handed to consumers of [`Bytes`] (e.g. HTTP response bodies) and sliced without copying.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if:
+    ///  - the data type is variable length,
+    ///  - an array subset is invalid or out of bounds of the array,
+    ///  - there is a codec decoding error, or
+    ///  - an underlying store error.
+    pub fn retrieve_array_subset_bytes(
+        &self,
+        array_subset: &ArraySubset,
+    ) -> Result<Bytes, ArrayError> {
+        self.retrieve_array_subset_bytes_opt(array_subset, &CodecOptions::default())
+    }
+
     #[cfg(feature = "ndarray")]
     /// Read and decode the `array_subset` of array into an [`ndarray::ArrayD`].
     ///
@@ -414,6 +582,71 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
         self.retrieve_array_subset_ndarray_opt(array_subset, &CodecOptions::default())
     }
 
+    #[cfg(feature = "ndarray")]
+    /// Read and decode the `array_subset` of array into a shared [`ndarray::ArcArray`].
+    ///
+    /// The decoded elements are moved into the shared array without a copy, so the result can be cheaply cloned and shared between threads.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if:
+    ///  - an array subset is invalid or out of bounds of the array,
+    ///  - there is a codec decoding error, or
+    ///  - an underlying store error.
+    ///
+    /// # Panics
+    /// Will panic if any dimension in `chunk_subset` is `usize::MAX` or larger.
+    pub fn retrieve_array_subset_ndarray_arc<T: ElementOwned>(
+        &self,
+        array_subset: &ArraySubset,
+    ) -> Result<ndarray::ArcArray<T, ndarray::IxDyn>, ArrayError> {
+        self.retrieve_array_subset_ndarray_arc_opt(array_subset, &CodecOptions::default())
+    }
+
+    /// Read and decode the `array_subset` of array into a window of `output`.
+    ///
+    /// `output` holds the elements of a larger array with `output_shape` in C order, such as a mosaic assembled from several arrays.
+    /// The elements of `array_subset` are decoded directly into the window of `output` starting at `output_offset` with the shape of `array_subset`.
+    /// Elements of `output` outside of the window are left unchanged.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if:
+    ///  - the data type is variable length or the bytes of the data type are not the native representation of `T`,
+    ///  - an array subset is invalid or out of bounds of the array,
+    ///  - the length of `output` does not match `output_shape`, or the window is out of bounds of `output_shape`,
+    ///  - there is a codec decoding error, or
+    ///  - an underlying store error.
+    pub fn retrieve_array_subset_into_view<T: ElementOwned>(
+        &self,
+        array_subset: &ArraySubset,
+        output: &mut [T],
+        output_shape: &[u64],
+        output_offset: &[u64],
+    ) -> Result<(), ArrayError> {
+        self.retrieve_array_subset_into_view_opt(
+            array_subset,
+            output,
+            output_shape,
+            output_offset,
+            &CodecOptions::default(),
+        )
+    }
+
+    #[cfg(feature = "dlpack")]
+    /// Read and decode the `array_subset` of array into a [`DLPackTensor`].
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if:
+    ///  - the data type has no `DLPack` equivalent,
+    ///  - an array subset is invalid or out of bounds of the array,
+    ///  - there is a codec decoding error, or
+    ///  - an underlying store error.
+    pub fn retrieve_array_subset_dlpack(
+        &self,
+        array_subset: &ArraySubset,
+    ) -> Result<DLPackTensor, ArrayError> {
+        self.retrieve_array_subset_dlpack_opt(array_subset, &CodecOptions::default())
+    }
+
     /// Initialises a partial decoder for the chunk at `chunk_indices`.
     ///
     /// # Errors
@@ -431,6 +664,10 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
 
     /// Explicit options version of [`retrieve_chunk_if_exists`](Array::retrieve_chunk_if_exists).
     #[allow(clippy::missing_errors_doc)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), chunk_indices = ?chunk_indices))
+    )]
     pub fn retrieve_chunk_if_exists_opt(
         &self,
         chunk_indices: &[u64],
@@ -445,6 +682,7 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
         let storage_transformer = self
             .storage_transformers()
             .create_readable_transformer(storage_handle);
+        let start = Instant::now();
         let chunk_encoded = crate::storage::retrieve_chunk(
             &*storage_transformer,
             self.path(),
@@ -452,24 +690,12 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
             self.chunk_key_encoding(),
         )
         .map_err(ArrayError::StorageError)?;
-        if let Some(chunk_encoded) = chunk_encoded {
-            let chunk_representation = self.chunk_array_representation(chunk_indices)?;
-            let bytes = self
-                .codecs()
-                .decode(
-                    Cow::Borrowed(&chunk_encoded),
-                    &chunk_representation,
-                    options,
-                )
-                .map_err(ArrayError::CodecError)?;
-            bytes.validate(
-                chunk_representation.num_elements(),
-                chunk_representation.data_type().size(),
-            )?;
-            Ok(Some(bytes.into_owned()))
-        } else {
-            Ok(None)
-        }
+        options
+            .concurrency_strategy()
+            .record_storage_latency(start.elapsed());
+        chunk_encoded
+            .map(|chunk_encoded| self.decode_chunk_encoded(chunk_indices, &chunk_encoded, options))
+            .transpose()
     }
 
     /// Explicit options version of [`retrieve_chunk`](Array::retrieve_chunk).
@@ -490,6 +716,42 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
         }
     }
 
+    /// Explicit options version of [`retrieve_chunk_sparse`](Array::retrieve_chunk_sparse).
+    #[allow(clippy::missing_errors_doc)]
+    pub fn retrieve_chunk_sparse_opt(
+        &self,
+        chunk_indices: &[u64],
+        options: &CodecOptions,
+    ) -> Result<SparseArrayBytes, ArrayError> {
+        let chunk_representation = self.chunk_array_representation(chunk_indices)?;
+        let Some(element_size) = self.data_type().fixed_size() else {
+            return Err(CodecError::ExpectedFixedLengthBytes.into());
+        };
+        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+        let storage_transformer = self
+            .storage_transformers()
+            .create_readable_transformer(storage_handle);
+        let chunk_encoded = crate::storage::retrieve_chunk(
+            &*storage_transformer,
+            self.path(),
+            chunk_indices,
+            self.chunk_key_encoding(),
+        )
+        .map_err(ArrayError::StorageError)?;
+        if let Some(chunk_encoded) = chunk_encoded {
+            Ok(self.codecs().decode_sparse(
+                Cow::Borrowed(&chunk_encoded),
+                &chunk_representation,
+                options,
+            )?)
+        } else {
+            Ok(SparseArrayBytes::new_fill_value(
+                chunk_representation.num_elements(),
+                element_size,
+            ))
+        }
+    }
+
     /// Explicit options version of [`retrieve_chunk_elements_if_exists`](Array::retrieve_chunk_elements_if_exists).
     #[allow(clippy::missing_errors_doc)]
     pub fn retrieve_chunk_elements_if_exists_opt<T: ElementOwned>(
@@ -517,6 +779,34 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
         )
     }
 
+    /// Explicit options version of [`retrieve_chunk_bytes`](Array::retrieve_chunk_bytes).
+    #[allow(clippy::missing_errors_doc)]
+    pub fn retrieve_chunk_bytes_opt(
+        &self,
+        chunk_indices: &[u64],
+        options: &CodecOptions,
+    ) -> Result<Bytes, ArrayError> {
+        Ok(self
+            .retrieve_chunk_opt(chunk_indices, options)?
+            .into_fixed_shared()?)
+    }
+
+    #[cfg(feature = "dlpack")]
+    /// Explicit options version of [`retrieve_chunk_dlpack`](Array::retrieve_chunk_dlpack).
+    #[allow(clippy::missing_errors_doc)]
+    pub fn retrieve_chunk_dlpack_opt(
+        &self,
+        chunk_indices: &[u64],
+        options: &CodecOptions,
+    ) -> Result<DLPackTensor, ArrayError> {
+        let shape = chunk_shape_to_array_shape(&self.chunk_shape(chunk_indices)?);
+        DLPackTensor::new(
+            self.retrieve_chunk_opt(chunk_indices, options)?,
+            self.data_type(),
+            &shape,
+        )
+    }
+
     #[cfg(feature = "ndarray")]
     /// Explicit options version of [`retrieve_chunk_ndarray_if_exists`](Array::retrieve_chunk_ndarray_if_exists).
     #[allow(clippy::missing_errors_doc)]
@@ -555,8 +845,25 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
         )
     }
 
+    #[cfg(feature = "ndarray")]
+    /// Explicit options version of [`retrieve_chunk_ndarray_arc`](Array::retrieve_chunk_ndarray_arc).
+    #[allow(clippy::missing_errors_doc)]
+    pub fn retrieve_chunk_ndarray_arc_opt<T: ElementOwned>(
+        &self,
+        chunk_indices: &[u64],
+        options: &CodecOptions,
+    ) -> Result<ndarray::ArcArray<T, ndarray::IxDyn>, ArrayError> {
+        Ok(self
+            .retrieve_chunk_ndarray_opt(chunk_indices, options)?
+            .into_shared())
+    }
+
     /// Explicit options version of [`retrieve_chunks`](Array::retrieve_chunks).
     #[allow(clippy::missing_errors_doc)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), chunks = %chunks))
+    )]
     pub fn retrieve_chunks_opt(
         &self,
         chunks: &ArraySubset,
@@ -592,13 +899,16 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
         options: &CodecOptions,
     ) -> Result<ndarray::ArrayD<T>, ArrayError> {
         let array_subset = self.chunks_subset(chunks)?;
-        let elements = self.retrieve_chunks_elements_opt::<T>(chunks, options)?;
-        elements_to_ndarray(array_subset.shape(), elements)
+        self.retrieve_array_subset_ndarray_opt(&array_subset, options)
     }
 
     /// Explicit options version of [`retrieve_array_subset`](Array::retrieve_array_subset).
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
     #[allow(clippy::too_many_lines)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), array_subset = %array_subset))
+    )]
     pub fn retrieve_array_subset_opt(
         &self,
         array_subset: &ArraySubset,
@@ -644,90 +954,83 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
                     )
                 }
             }
-            _ => {
-                let chunk_representation =
-                    self.chunk_array_representation(&vec![0; self.dimensionality()])?;
-
-                // Calculate chunk/codec concurrency
-                let codec_concurrency =
-                    self.recommended_codec_concurrency(&chunk_representation)?;
-                let (chunk_concurrent_limit, options) = concurrency_chunks_and_codec(
-                    options.concurrent_target(),
-                    num_chunks,
-                    options,
-                    &codec_concurrency,
-                );
-
-                match chunk_representation.data_type().size() {
-                    DataTypeSize::Variable => {
-                        // Retrieve all the chunks
-                        let retrieve_chunk = |chunk_indices: Vec<u64>| -> Result<
-                            (ArrayBytes<'_>, ArraySubset),
-                            ArrayError,
-                        > {
-                            let chunk_subset = self.chunk_subset(&chunk_indices)?;
-                            let chunk_subset_overlap = chunk_subset.overlap(array_subset)?;
-                            Ok((
-                                self.retrieve_chunk_subset_opt(
-                                    &chunk_indices,
-                                    &chunk_subset_overlap.relative_to(chunk_subset.start())?,
-                                    &options,
-                                )?,
-                                chunk_subset_overlap.relative_to(array_subset.start())?,
-                            ))
+            _ => match self.data_type().size() {
+                DataTypeSize::Variable => {
+                    let chunk_representation =
+                        self.chunk_array_representation(&vec![0; self.dimensionality()])?;
+
+                    // Calculate chunk/codec concurrency
+                    let codec_concurrency =
+                        self.recommended_codec_concurrency(&chunk_representation)?;
+                    let (chunk_concurrent_limit, options) = concurrency_chunks_and_codec(
+                        options.concurrent_target(),
+                        num_chunks,
+                        options,
+                        &codec_concurrency,
+                    );
+
+                    // Batch the retrieval of the chunks read in full
+                    let chunks_encoded =
+                        if let Some(max_waste_ratio) = options.chunk_read_max_waste_ratio() {
+                            self.retrieve_encoded_chunks_coalesced(
+                                &chunks,
+                                array_subset,
+                                max_waste_ratio,
+                                &options,
+                            )?
+                        } else {
+                            HashMap::new()
                         };
-                        let chunk_indices = chunks.indices();
-                        let chunk_bytes_and_subsets = iter_concurrent_limit!(
-                            chunk_concurrent_limit,
-                            chunk_indices,
-                            map,
-                            retrieve_chunk
-                        )
-                        .collect::<Result<Vec<_>, _>>()?;
-
-                        Ok(merge_chunks_vlen(
-                            chunk_bytes_and_subsets,
-                            array_subset.shape(),
-                        )?)
-                    }
-                    DataTypeSize::Fixed(data_type_size) => {
-                        // Allocate the output
-                        let size_output = array_subset.num_elements_usize() * data_type_size;
-                        let mut output = Vec::with_capacity(size_output);
-
-                        {
-                            let output =
-                                UnsafeCellSlice::new_from_vec_with_spare_capacity(&mut output);
-                            let retrieve_chunk = |chunk_indices: Vec<u64>| {
-                                let chunk_subset = self.chunk_subset(&chunk_indices)?;
-                                let chunk_subset_overlap = chunk_subset.overlap(array_subset)?;
-                                let chunk_subset_bytes = self.retrieve_chunk_subset_opt(
-                                    &chunk_indices,
-                                    &chunk_subset_overlap.relative_to(chunk_subset.start())?,
-                                    &options,
-                                )?;
-                                update_bytes_flen(
-                                    unsafe { output.get() },
-                                    array_subset.shape(),
-                                    &chunk_subset_bytes.into_fixed()?,
-                                    &chunk_subset_overlap.relative_to(array_subset.start())?,
-                                    data_type_size,
-                                );
-                                Ok::<_, ArrayError>(())
-                            };
-                            let indices = chunks.indices();
-                            iter_concurrent_limit!(
-                                chunk_concurrent_limit,
-                                indices,
-                                try_for_each,
-                                retrieve_chunk
-                            )?;
-                        }
-                        unsafe { output.set_len(size_output) };
-                        Ok(ArrayBytes::from(output))
-                    }
+
+                    // Retrieve all the chunks
+                    let retrieve_chunk = |chunk_indices: Vec<u64>| -> Result<
+                        (ArrayBytes<'_>, ArraySubset),
+                        ArrayError,
+                    > {
+                        let chunk_subset = self.chunk_subset(&chunk_indices)?;
+                        let chunk_subset_overlap = chunk_subset.overlap(array_subset)?;
+                        Ok((
+                            self.retrieve_chunk_subset_prefetched(
+                                &chunk_indices,
+                                &chunk_subset_overlap.relative_to(chunk_subset.start())?,
+                                &chunks_encoded,
+                                &options,
+                            )
+                            .map_err(|err| self.chunk_error(&chunk_indices, err))?,
+                            chunk_subset_overlap.relative_to(array_subset.start())?,
+                        ))
+                    };
+                    let chunk_indices = chunks.indices();
+                    let chunk_bytes_and_subsets = iter_concurrent_limit!(
+                        chunk_concurrent_limit,
+                        chunk_indices,
+                        map,
+                        retrieve_chunk
+                    )
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                    Ok(merge_chunks_vlen(
+                        &chunk_bytes_and_subsets,
+                        array_subset.shape(),
+                    )?)
                 }
-            }
+                DataTypeSize::Fixed(data_type_size) => {
+                    // Allocate the output
+                    let size_output = array_subset.num_elements_usize() * data_type_size;
+                    let mut output = options.take_buffer(size_output);
+                    self.retrieve_array_subset_into(
+                        array_subset,
+                        &chunks,
+                        data_type_size,
+                        unsafe { &mut vec_spare_capacity_to_mut_slice(&mut output)[..size_output] },
+                        array_subset.shape(),
+                        &vec![0; array_subset.dimensionality()],
+                        options,
+                    )?;
+                    unsafe { output.set_len(size_output) };
+                    Ok(ArrayBytes::from(output))
+                }
+            },
         }
     }
 
@@ -744,20 +1047,155 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
         )
     }
 
+    /// Explicit options version of [`retrieve_array_subset_bytes`](Array::retrieve_array_subset_bytes).
+    #[allow(clippy::missing_errors_doc)]
+    pub fn retrieve_array_subset_bytes_opt(
+        &self,
+        array_subset: &ArraySubset,
+        options: &CodecOptions,
+    ) -> Result<Bytes, ArrayError> {
+        Ok(self
+            .retrieve_array_subset_opt(array_subset, options)?
+            .into_fixed_shared()?)
+    }
+
+    #[cfg(feature = "dlpack")]
+    /// Explicit options version of [`retrieve_array_subset_dlpack`](Array::retrieve_array_subset_dlpack).
+    #[allow(clippy::missing_errors_doc)]
+    pub fn retrieve_array_subset_dlpack_opt(
+        &self,
+        array_subset: &ArraySubset,
+        options: &CodecOptions,
+    ) -> Result<DLPackTensor, ArrayError> {
+        DLPackTensor::new(
+            self.retrieve_array_subset_opt(array_subset, options)?,
+            self.data_type(),
+            array_subset.shape(),
+        )
+    }
+
     #[cfg(feature = "ndarray")]
     /// Explicit options version of [`retrieve_array_subset_ndarray`](Array::retrieve_array_subset_ndarray).
-    #[allow(clippy::missing_errors_doc)]
+    #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
     pub fn retrieve_array_subset_ndarray_opt<T: ElementOwned>(
         &self,
         array_subset: &ArraySubset,
         options: &CodecOptions,
     ) -> Result<ndarray::ArrayD<T>, ArrayError> {
-        let elements = self.retrieve_array_subset_elements_opt::<T>(array_subset, options)?;
-        elements_to_ndarray(array_subset.shape(), elements)
+        let DataTypeSize::Fixed(data_type_size) = self.data_type().size() else {
+            let elements = self.retrieve_array_subset_elements_opt::<T>(array_subset, options)?;
+            return elements_to_ndarray(array_subset.shape(), elements);
+        };
+        let Some(native_bytes) = T::native_bytes_representation(self.data_type()) else {
+            let elements = self.retrieve_array_subset_elements_opt::<T>(array_subset, options)?;
+            return elements_to_ndarray(array_subset.shape(), elements);
+        };
+
+        if array_subset.dimensionality() != self.dimensionality() {
+            return Err(ArrayError::InvalidArraySubset(
+                array_subset.clone(),
+                self.shape().to_vec(),
+            ));
+        }
+        let Some(chunks) = self.chunks_in_array_subset(array_subset)? else {
+            return Err(ArrayError::InvalidArraySubset(
+                array_subset.clone(),
+                self.shape().to_vec(),
+            ));
+        };
+
+        // Decode directly into the elements of the output array
+        let mut array = ndarray::ArrayD::<T>::from_elem(
+            iter_u64_to_usize(array_subset.shape().iter()),
+            native_bytes.zeroed(),
+        );
+        {
+            let output = native_bytes.as_bytes_mut(
+                array
+                    .as_slice_mut()
+                    .expect("a new array has a standard layout"),
+            );
+            self.retrieve_array_subset_into(
+                array_subset,
+                &chunks,
+                data_type_size,
+                output,
+                array_subset.shape(),
+                &vec![0; array_subset.dimensionality()],
+                options,
+            )?;
+        }
+        Ok(array)
+    }
+
+    #[cfg(feature = "ndarray")]
+    /// Explicit options version of [`retrieve_array_subset_ndarray_arc`](Array::retrieve_array_subset_ndarray_arc).
+    #[allow(clippy::missing_errors_doc)]
+    pub fn retrieve_array_subset_ndarray_arc_opt<T: ElementOwned>(
+        &self,
+        array_subset: &ArraySubset,
+        options: &CodecOptions,
+    ) -> Result<ndarray::ArcArray<T, ndarray::IxDyn>, ArrayError> {
+        Ok(self
+            .retrieve_array_subset_ndarray_opt(array_subset, options)?
+            .into_shared())
+    }
+
+    /// Explicit options version of [`retrieve_array_subset_into_view`](Array::retrieve_array_subset_into_view).
+    #[allow(clippy::missing_errors_doc)]
+    pub fn retrieve_array_subset_into_view_opt<T: ElementOwned>(
+        &self,
+        array_subset: &ArraySubset,
+        output: &mut [T],
+        output_shape: &[u64],
+        output_offset: &[u64],
+        options: &CodecOptions,
+    ) -> Result<(), ArrayError> {
+        let DataTypeSize::Fixed(data_type_size) = self.data_type().size() else {
+            return Err(ArrayError::IncompatibleElementType);
+        };
+        let Some(native_bytes) = T::native_bytes_representation(self.data_type()) else {
+            return Err(ArrayError::IncompatibleElementType);
+        };
+        if output_offset.len() != array_subset.dimensionality() {
+            return Err(IncompatibleDimensionalityError::new(
+                output_offset.len(),
+                array_subset.dimensionality(),
+            )
+            .into());
+        }
+        if array_subset.dimensionality() != self.dimensionality() {
+            return Err(ArrayError::InvalidArraySubset(
+                array_subset.clone(),
+                self.shape().to_vec(),
+            ));
+        }
+        let Some(chunks) = self.chunks_in_array_subset(array_subset)? else {
+            return Err(ArrayError::InvalidArraySubset(
+                array_subset.clone(),
+                self.shape().to_vec(),
+            ));
+        };
+
+        // Decode directly into the elements of the output
+        let output = native_bytes.as_bytes_mut(output);
+        self.retrieve_array_subset_into(
+            array_subset,
+            &chunks,
+            data_type_size,
+            output,
+            output_shape,
+            output_offset,
+            options,
+        )
     }
 
     /// Explicit options version of [`retrieve_chunk_subset`](Array::retrieve_chunk_subset).
     #[allow(clippy::missing_errors_doc)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), chunk_indices = ?chunk_indices, chunk_subset = %chunk_subset))
+    )]
     pub fn retrieve_chunk_subset_opt(
         &self,
         chunk_indices: &[u64],
@@ -776,19 +1214,9 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
             && chunk_subset.shape() == chunk_representation.shape_u64()
         {
             // Fast path if `chunk_subset` encompasses the whole chunk
-            self.retrieve_chunk(chunk_indices)?
+            self.retrieve_chunk_opt(chunk_indices, options)?
         } else {
-            let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
-            let storage_transformer = self
-                .storage_transformers()
-                .create_readable_transformer(storage_handle);
-            let input_handle = Arc::new(StoragePartialDecoder::new(
-                storage_transformer,
-                self.chunk_key(chunk_indices),
-            ));
-
-            self.codecs()
-                .partial_decoder(input_handle, &chunk_representation, options)?
+            self.partial_decoder_opt(chunk_indices, options)?
                 .partial_decode_opt(&[chunk_subset.clone()], options)?
                 .remove(0)
                 .into_owned()
@@ -841,8 +1269,183 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
             self.chunk_key(chunk_indices),
         ));
         let chunk_representation = self.chunk_array_representation(chunk_indices)?;
+        #[cfg(feature = "sharding")]
+        if let Some((shard_index_cache, sharding_codec)) = &self.shard_index_cache {
+            return Ok(sharding_codec.partial_decoder_with_shard_index_cache(
+                input_handle,
+                &chunk_representation,
+                shard_index_cache,
+                chunk_indices,
+                options,
+            )?);
+        }
         Ok(self
             .codecs()
             .partial_decoder(input_handle, &chunk_representation, options)?)
     }
+
+    /// Decode the encoded chunk at `chunk_indices`.
+    fn decode_chunk_encoded(
+        &self,
+        chunk_indices: &[u64],
+        chunk_encoded: &[u8],
+        options: &CodecOptions,
+    ) -> Result<ArrayBytes<'static>, ArrayError> {
+        let chunk_representation = self.chunk_array_representation(chunk_indices)?;
+        let start = Instant::now();
+        let bytes = self
+            .codecs()
+            .decode(Cow::Borrowed(chunk_encoded), &chunk_representation, options)
+            .map_err(ArrayError::CodecError)?;
+        options
+            .concurrency_strategy()
+            .record_codec_time(start.elapsed());
+        bytes.validate(
+            chunk_representation.num_elements(),
+            chunk_representation.data_type().size(),
+        )?;
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_chunk_read(chunk_encoded.len(), bytes.size());
+        Ok(bytes.into_owned())
+    }
+
+    /// Retrieve the encoded chunks in `chunks` that are entirely within `array_subset` with [`ReadableStorageTraits::get_values_coalesced`].
+    fn retrieve_encoded_chunks_coalesced(
+        &self,
+        chunks: &ArraySubset,
+        array_subset: &ArraySubset,
+        max_waste_ratio: f64,
+        options: &CodecOptions,
+    ) -> Result<HashMap<Vec<u64>, MaybeBytes>, ArrayError> {
+        let mut chunks_indices = Vec::new();
+        for chunk_indices in &chunks.indices() {
+            let chunk_subset = self.chunk_subset(&chunk_indices)?;
+            if chunk_subset.overlap(array_subset)? == chunk_subset {
+                chunks_indices.push(chunk_indices);
+            }
+        }
+        let keys: Vec<StoreKey> = chunks_indices
+            .iter()
+            .map(|chunk_indices| data_key(self.path(), chunk_indices, self.chunk_key_encoding()))
+            .collect();
+
+        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+        let storage_transformer = self
+            .storage_transformers()
+            .create_readable_transformer(storage_handle);
+        let start = Instant::now();
+        let chunks_encoded = storage_transformer.get_values_coalesced(&keys, max_waste_ratio)?;
+        options
+            .concurrency_strategy()
+            .record_storage_latency(start.elapsed());
+        Ok(chunks_indices.into_iter().zip(chunks_encoded).collect())
+    }
+
+    /// Retrieve the `chunk_subset` of the chunk at `chunk_indices`, decoding the chunk from `chunks_encoded` if it was retrieved in advance.
+    fn retrieve_chunk_subset_prefetched(
+        &self,
+        chunk_indices: &[u64],
+        chunk_subset: &ArraySubset,
+        chunks_encoded: &HashMap<Vec<u64>, MaybeBytes>,
+        options: &CodecOptions,
+    ) -> Result<ArrayBytes<'_>, ArrayError> {
+        match chunks_encoded.get(chunk_indices) {
+            Some(Some(encoded)) => self.decode_chunk_encoded(chunk_indices, encoded, options),
+            Some(None) => {
+                let array_size =
+                    ArraySize::new(self.data_type().size(), chunk_subset.num_elements());
+                Ok(ArrayBytes::new_fill_value(array_size, self.fill_value()))
+            }
+            None => self.retrieve_chunk_subset_opt(chunk_indices, chunk_subset, options),
+        }
+    }
+
+    /// Retrieve the fixed length bytes of `array_subset` intersecting `chunks` into a window of `output`.
+    ///
+    /// `output` holds the elements of an array with `output_shape`, and the window starts at `output_offset` with the shape of `array_subset`.
+    #[allow(clippy::too_many_arguments)]
+    fn retrieve_array_subset_into(
+        &self,
+        array_subset: &ArraySubset,
+        chunks: &ArraySubset,
+        data_type_size: usize,
+        output: &mut [u8],
+        output_shape: &[u64],
+        output_offset: &[u64],
+        options: &CodecOptions,
+    ) -> Result<(), ArrayError> {
+        debug_assert_eq!(output_offset.len(), array_subset.dimensionality());
+        let num_chunks = chunks.num_elements_usize();
+        if num_chunks == 0 {
+            return Ok(());
+        }
+        let chunk_representation =
+            self.chunk_array_representation(&vec![0; self.dimensionality()])?;
+
+        // Calculate chunk/codec concurrency
+        let codec_concurrency = self.recommended_codec_concurrency(&chunk_representation)?;
+        let (chunk_concurrent_limit, options) = concurrency_chunks_and_codec(
+            options.concurrent_target(),
+            num_chunks,
+            options,
+            &codec_concurrency,
+        );
+        let chunk_concurrent_limit = self.memory_limited_chunk_concurrency(
+            chunk_concurrent_limit,
+            &chunk_representation,
+            (array_subset.num_elements_usize() * data_type_size) as u64,
+            &options,
+        )?;
+
+        // Batch the retrieval of the chunks read in full
+        let chunks_encoded = if let Some(max_waste_ratio) = options.chunk_read_max_waste_ratio() {
+            self.retrieve_encoded_chunks_coalesced(chunks, array_subset, max_waste_ratio, &options)?
+        } else {
+            HashMap::new()
+        };
+
+        // Split the output into a disjoint region for each chunk
+        let chunk_indices: Vec<ArrayIndices> = chunks.indices().iter().collect();
+        let output_subsets = chunk_indices
+            .iter()
+            .map(|chunk_indices| {
+                let chunk_subset = self.chunk_subset(chunk_indices)?;
+                let chunk_subset_overlap = chunk_subset
+                    .overlap(array_subset)?
+                    .relative_to(array_subset.start())?;
+                Ok(ArraySubset::new_with_start_shape(
+                    std::iter::zip(chunk_subset_overlap.start(), output_offset)
+                        .map(|(start, offset)| start + offset)
+                        .collect(),
+                    chunk_subset_overlap.shape().to_vec(),
+                )?)
+            })
+            .collect::<Result<Vec<_>, ArrayError>>()?;
+        let output_regions =
+            partition_output(output, output_shape, data_type_size, output_subsets)?;
+
+        let retrieve_chunk = |(chunk_indices, mut output_region): (ArrayIndices, OutputRegion)| {
+            let chunk_subset = self.chunk_subset(&chunk_indices)?;
+            let chunk_subset_overlap = chunk_subset.overlap(array_subset)?;
+            let chunk_subset_bytes = self
+                .retrieve_chunk_subset_prefetched(
+                    &chunk_indices,
+                    &chunk_subset_overlap.relative_to(chunk_subset.start())?,
+                    &chunks_encoded,
+                    &options,
+                )
+                .map_err(|err| self.chunk_error(&chunk_indices, err))?;
+            let chunk_subset_bytes = chunk_subset_bytes.into_fixed()?;
+            output_region.write(&chunk_subset_bytes)?;
+            options.recycle_bytes(chunk_subset_bytes);
+            Ok::<_, ArrayError>(())
+        };
+        let chunks_and_regions: Vec<_> = chunk_indices.into_iter().zip(output_regions).collect();
+        iter_concurrent_limit!(
+            chunk_concurrent_limit,
+            chunks_and_regions,
+            try_for_each,
+            retrieve_chunk
+        )
+    }
 }
@@ -1,17 +1,197 @@
+use std::sync::Arc;
+
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
-use crate::{array::ArrayBytes, array_subset::ArraySubset, storage::ReadableWritableStorageTraits};
+use crate::{
+    array::ArrayBytes,
+    array_subset::ArraySubset,
+    metadata::attributes_merge_patch,
+    storage::{backup_metadata, ReadableWritableStorageTraits, StorageError, StorageHandle},
+};
 
 use super::{
-    array_bytes::update_array_bytes, codec::options::CodecOptions,
-    concurrency::concurrency_chunks_and_codec, Array, ArrayError, Element,
+    array_bytes::update_array_bytes,
+    array_sync_partial_encoder::ArrayChunkPartialEncoder,
+    codec::{
+        options::CodecOptions, ArrayPartialEncoderTraits, ArrayToBytesCodecTraits, CodecTraits,
+        StoragePartialDecoder, StoragePartialEncoder,
+    },
+    concurrency::concurrency_chunks_and_codec,
+    metadata_keys, Array, ArrayCreateError, ArrayError, ArrayMetadataOptions, Element,
 };
 
 impl<TStorage: ?Sized + ReadableWritableStorageTraits + 'static> Array<TStorage> {
+    /// Store metadata with default [`ArrayMetadataOptions`].
+    ///
+    /// The metadata is created with [`Array::metadata_opt`].
+    ///
+    /// # Errors
+    /// Returns [`StorageError`] if an ancestor group has no metadata and implicit groups are disallowed, or there is an underlying store error.
+    pub fn store_metadata(&self) -> Result<(), StorageError> {
+        self.store_metadata_opt(&ArrayMetadataOptions::default())
+    }
+
+    /// Store metadata with non-default [`ArrayMetadataOptions`].
+    ///
+    /// The metadata is created with [`Array::metadata_opt`].
+    ///
+    /// Ancestor groups without metadata are handled according to the [`ImplicitGroupPolicy`](crate::metadata::ImplicitGroupPolicy).
+    /// If it is [`CreateOnWrite`](crate::metadata::ImplicitGroupPolicy::CreateOnWrite), their metadata is stored with default metadata of the same Zarr version as the array.
+    ///
+    /// # Errors
+    /// Returns [`StorageError`] if an ancestor group has no metadata and implicit groups are disallowed, or there is an underlying store error.
+    pub fn store_metadata_opt(&self, options: &ArrayMetadataOptions) -> Result<(), StorageError> {
+        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+        let storage_transformer = self
+            .storage_transformers()
+            .create_writable_transformer(storage_handle);
+
+        // Get the metadata with options applied and store
+        let metadata = self.metadata_opt(options);
+        crate::group::store_array_implicit_group_metadata(&self.storage, self.path(), &metadata)?;
+        crate::storage::create_array_opt(
+            &*storage_transformer,
+            self.path(),
+            &metadata,
+            options.metadata_json_format(),
+        )
+    }
+
+    /// Store metadata with default [`ArrayMetadataOptions`] if the metadata in the store is unchanged.
+    ///
+    /// See [`store_metadata_if_unchanged_opt`](Array::store_metadata_if_unchanged_opt).
+    #[allow(clippy::missing_errors_doc)]
+    pub fn store_metadata_if_unchanged(&mut self) -> Result<(), StorageError> {
+        self.store_metadata_if_unchanged_opt(&ArrayMetadataOptions::default())
+    }
+
+    /// Store metadata with non-default [`ArrayMetadataOptions`] if the metadata in the store is unchanged.
+    ///
+    /// This guards against overwriting metadata (e.g. attributes) that was updated by another process since it was read by this array.
+    /// The versions of the stored metadata keys are recorded on success.
+    ///
+    /// The check is not atomic, so a concurrent write between checking and storing the metadata is not detected.
+    ///
+    /// # Errors
+    /// Returns [`StorageError::VersionMismatch`] if the [metadata is stale](Array::metadata_is_stale), or a [`StorageError`] if there is an underlying store error.
+    pub fn store_metadata_if_unchanged_opt(
+        &mut self,
+        options: &ArrayMetadataOptions,
+    ) -> Result<(), StorageError> {
+        if let Some(key) = self.stale_metadata_key()? {
+            return Err(StorageError::VersionMismatch(key.clone()));
+        }
+        self.store_metadata_and_versions(options)
+    }
+
+    /// Store metadata with default [`ArrayMetadataOptions`] after backing up the metadata in the store.
+    ///
+    /// See [`store_metadata_with_backup_opt`](Array::store_metadata_with_backup_opt).
+    #[allow(clippy::missing_errors_doc)]
+    pub fn store_metadata_with_backup(&mut self) -> Result<(), StorageError> {
+        self.store_metadata_with_backup_opt(&ArrayMetadataOptions::default())
+    }
+
+    /// Store metadata with non-default [`ArrayMetadataOptions`] after backing up the metadata in the store.
+    ///
+    /// The metadata in the store is copied to its [backup key](crate::storage::meta_key_backup) (e.g. `zarr.json.bak`) before it is replaced.
+    /// If the stored metadata is later lost or corrupted (e.g. by a crash mid-write or a concurrent writer), it can be recovered with [`restore_metadata_backup`](Array::restore_metadata_backup).
+    /// The versions of the stored metadata keys are recorded on success.
+    ///
+    /// The metadata is only replaced if it was not changed after it was backed up, so a concurrent write cannot be overwritten without a backup.
+    /// The storage traits do not support conditional writes, so the check is not atomic.
+    ///
+    /// # Errors
+    /// Returns [`StorageError::VersionMismatch`] if the metadata was changed after it was backed up, or a [`StorageError`] if there is an underlying store error.
+    pub fn store_metadata_with_backup_opt(
+        &mut self,
+        options: &ArrayMetadataOptions,
+    ) -> Result<(), StorageError> {
+        let keys = metadata_keys(self.path(), &self.metadata_opt(options));
+        let versions = backup_metadata(&*self.storage, &keys)?;
+        for (key, version) in keys.into_iter().zip(versions) {
+            if self.storage.get_version(&key)? != version {
+                return Err(StorageError::VersionMismatch(key));
+            }
+        }
+        self.store_metadata_and_versions(options)
+    }
+
+    /// Restore the metadata in the store from its backup and [refresh](Array::refresh_metadata) the metadata of this array.
+    ///
+    /// The backup is created by [`store_metadata_with_backup`](Array::store_metadata_with_backup).
+    ///
+    /// # Errors
+    /// Returns an [`ArrayCreateError`] if there is no metadata backup, the backup cannot be restored, or the restored metadata is invalid.
+    pub fn restore_metadata_backup(&mut self) -> Result<(), ArrayCreateError> {
+        let keys = metadata_keys(self.path(), self.metadata());
+        crate::storage::restore_metadata_backup(&*self.storage, &keys)?;
+        self.refresh_metadata()
+    }
+
+    /// Store metadata and record the versions of the stored metadata keys.
+    fn store_metadata_and_versions(
+        &mut self,
+        options: &ArrayMetadataOptions,
+    ) -> Result<(), StorageError> {
+        self.store_metadata_opt(options)?;
+        let metadata_versions = metadata_keys(self.path(), &self.metadata_opt(options))
+            .into_iter()
+            .map(|key| {
+                let version = self.storage.get_version(&key)?;
+                Ok((key, version))
+            })
+            .collect::<Result<Vec<_>, StorageError>>()?;
+        self.set_metadata_versions(metadata_versions);
+        Ok(())
+    }
+
+    /// Update the attributes in the store with an [RFC 7386](https://www.rfc-editor.org/rfc/rfc7386) JSON merge `patch`.
+    ///
+    /// See [`update_attributes_with`](Array::update_attributes_with).
+    #[allow(clippy::missing_errors_doc)]
+    pub fn update_attributes(
+        &mut self,
+        patch: &serde_json::Map<String, serde_json::Value>,
+        if_unchanged: bool,
+    ) -> Result<(), ArrayCreateError> {
+        self.update_attributes_with(
+            |attributes| attributes_merge_patch(attributes, patch),
+            if_unchanged,
+        )
+    }
+
+    /// Update the attributes in the store with the `update` function.
+    ///
+    /// The metadata is [refreshed](Array::refresh_metadata) from the store, the attributes are updated, and the metadata is stored.
+    /// This composes attribute updates from other writers that happen before the metadata is refreshed, rather than overwriting them.
+    /// Any changes to the metadata of this array that have not been stored are discarded.
+    ///
+    /// If `if_unchanged` is true, the metadata is only stored if it was not changed after it was refreshed (see [`store_metadata_if_unchanged`](Array::store_metadata_if_unchanged)).
+    ///
+    /// # Errors
+    /// Returns an [`ArrayCreateError`] if the metadata cannot be refreshed, or [`StorageError::VersionMismatch`] if `if_unchanged` is true and the metadata was changed after it was refreshed.
+    pub fn update_attributes_with<F: FnOnce(&mut serde_json::Map<String, serde_json::Value>)>(
+        &mut self,
+        update: F,
+        if_unchanged: bool,
+    ) -> Result<(), ArrayCreateError> {
+        self.refresh_metadata()?;
+        update(self.attributes_mut());
+        let options = ArrayMetadataOptions::default();
+        if if_unchanged {
+            self.store_metadata_if_unchanged_opt(&options)?;
+        } else {
+            self.store_metadata_and_versions(&options)?;
+        }
+        Ok(())
+    }
+
     /// Encode `chunk_subset_bytes` and store in `chunk_subset` of the chunk at `chunk_indices` with default codec options.
     ///
     /// Use [`store_chunk_subset_opt`](Array::store_chunk_subset_opt) to control codec options.
     /// Prefer to use [`store_chunk`](Array::store_chunk) where possible, since this function may decode the chunk before updating it and reencoding it.
+    /// If the codec chain supports partial encoding (e.g. `sharding` without bytes to bytes codecs), only the parts of the chunk intersecting `chunk_subset` are decoded and reencoded.
     ///
     /// # Errors
     /// Returns an [`ArrayError`] if
@@ -141,12 +321,34 @@ impl<TStorage: ?Sized + ReadableWritableStorageTraits + 'static> Array<TStorage>
         self.store_array_subset_ndarray_opt(subset_start, subset_array, &CodecOptions::default())
     }
 
+    /// Initialises a partial encoder for the chunk at `chunk_indices`.
+    ///
+    /// The partial encoder updates array subsets of the chunk in the store.
+    /// If the codec chain supports partial encoding (e.g. `sharding` without bytes to bytes codecs), only the parts of the chunk intersecting an update are decoded and reencoded.
+    /// Otherwise, the entire chunk is decoded and reencoded on each update.
+    ///
+    /// Holding a partial encoder across many incremental updates to one chunk avoids reinitialising it (e.g. decoding a shard index) on each update.
+    /// The partial encoder assumes that the chunk is not concurrently modified elsewhere while it is held.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if initialisation of the partial encoder fails.
+    pub fn partial_encoder<'a>(
+        &'a self,
+        chunk_indices: &[u64],
+    ) -> Result<Arc<dyn ArrayPartialEncoderTraits + 'a>, ArrayError> {
+        self.partial_encoder_opt(chunk_indices, &CodecOptions::default())
+    }
+
     /////////////////////////////////////////////////////////////////////////////
     // Advanced methods
     /////////////////////////////////////////////////////////////////////////////
 
     /// Explicit options version of [`store_chunk_subset`](Array::store_chunk_subset).
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), chunk_indices = ?chunk_indices, chunk_subset = %chunk_subset))
+    )]
     pub fn store_chunk_subset_opt<'a>(
         &self,
         chunk_indices: &[u64],
@@ -171,6 +373,12 @@ impl<TStorage: ?Sized + ReadableWritableStorageTraits + 'static> Array<TStorage>
         if chunk_subset.shape() == chunk_shape && chunk_subset.start().iter().all(|&x| x == 0) {
             // The subset spans the whole chunk, so store the bytes directly and skip decoding
             self.store_chunk_opt(chunk_indices, chunk_subset_bytes, options)
+        } else if !self.codecs().partial_encoder_encodes_all() && !self.has_chunk_sidecars() {
+            // Only update the parts of the chunk intersecting the subset (e.g. inner chunks of a shard)
+            let chunk_subset_bytes = chunk_subset_bytes.into();
+            self.partial_encoder_opt(chunk_indices, options)?
+                .partial_encode_opt(&[(chunk_subset, chunk_subset_bytes)], options)?;
+            Ok(())
         } else {
             let chunk_subset_bytes = chunk_subset_bytes.into();
             chunk_subset_bytes.validate(chunk_subset.num_elements(), self.data_type().size())?;
@@ -188,7 +396,7 @@ impl<TStorage: ?Sized + ReadableWritableStorageTraits + 'static> Array<TStorage>
             let chunk_bytes_new = update_array_bytes(
                 chunk_bytes_old,
                 chunk_shape,
-                chunk_subset_bytes,
+                &chunk_subset_bytes,
                 chunk_subset,
                 self.data_type().size(),
             );
@@ -198,6 +406,39 @@ impl<TStorage: ?Sized + ReadableWritableStorageTraits + 'static> Array<TStorage>
         }
     }
 
+    /// Explicit options version of [`partial_encoder`](Array::partial_encoder).
+    #[allow(clippy::missing_errors_doc)]
+    pub fn partial_encoder_opt<'a>(
+        &'a self,
+        chunk_indices: &[u64],
+        options: &CodecOptions,
+    ) -> Result<Arc<dyn ArrayPartialEncoderTraits + 'a>, ArrayError> {
+        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+        let chunk_key = self.chunk_key(chunk_indices);
+        let input_handle = Arc::new(StoragePartialDecoder::new(
+            self.storage_transformers()
+                .create_readable_transformer(storage_handle.clone()),
+            chunk_key.clone(),
+        ));
+        let output_handle = Arc::new(StoragePartialEncoder::new(
+            self.storage_transformers()
+                .create_writable_transformer(storage_handle),
+            chunk_key,
+        ));
+        let chunk_representation = self.chunk_array_representation(chunk_indices)?;
+        let partial_encoder = self.codecs().partial_encoder(
+            input_handle,
+            output_handle,
+            &chunk_representation,
+            options,
+        )?;
+        Ok(Arc::new(ArrayChunkPartialEncoder::new(
+            self,
+            chunk_indices.to_vec(),
+            partial_encoder,
+        )))
+    }
+
     /// Explicit options version of [`store_chunk_subset_elements`](Array::store_chunk_subset_elements).
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
     pub fn store_chunk_subset_elements_opt<T: Element>(
@@ -237,6 +478,10 @@ impl<TStorage: ?Sized + ReadableWritableStorageTraits + 'static> Array<TStorage>
     /// Explicit options version of [`store_array_subset`](Array::store_array_subset).
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
     #[allow(clippy::too_many_lines)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), array_subset = %array_subset))
+    )]
     pub fn store_array_subset_opt<'a>(
         &self,
         array_subset: &ArraySubset,
@@ -289,6 +534,12 @@ impl<TStorage: ?Sized + ReadableWritableStorageTraits + 'static> Array<TStorage>
                 options,
                 &codec_concurrency,
             );
+            let chunk_concurrent_limit = self.memory_limited_chunk_concurrency(
+                chunk_concurrent_limit,
+                &chunk_representation,
+                0,
+                &options,
+            )?;
 
             let store_chunk = |chunk_indices: Vec<u64>| -> Result<(), ArrayError> {
                 let chunk_subset_in_array = self.chunk_subset(&chunk_indices)?;
@@ -308,6 +559,7 @@ impl<TStorage: ?Sized + ReadableWritableStorageTraits + 'static> Array<TStorage>
                     chunk_subset_bytes,
                     &options,
                 )
+                .map_err(|err| self.chunk_error(&chunk_indices, err))
             };
 
             let indices = chunks.indices();
@@ -386,16 +386,18 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> ArrayShardedReadableExt
                         .collect::<Result<Vec<_>, _>>()?;
 
                         Ok(merge_chunks_vlen(
-                            chunk_bytes_and_subsets,
+                            &chunk_bytes_and_subsets,
                             array_subset.shape(),
                         )?)
                     }
                     DataTypeSize::Fixed(data_type_size) => {
                         let size_output = array_subset.num_elements_usize() * data_type_size;
-                        let mut output = Vec::with_capacity(size_output);
+                        let mut output = options.take_buffer(size_output);
                         {
-                            let output =
-                                UnsafeCellSlice::new_from_vec_with_spare_capacity(&mut output);
+                            let output = UnsafeCellSlice::new_from_vec_with_spare_capacity_len(
+                                &mut output,
+                                size_output,
+                            );
                             let retrieve_shard_into_slice = |shard_indices: Vec<u64>| {
                                 let shard_subset = self.chunk_subset(&shard_indices)?;
                                 let shard_subset_overlap = shard_subset.overlap(array_subset)?;
@@ -412,14 +414,16 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> ArrayShardedReadableExt
                                         &options,
                                     )?
                                     .remove(0)
-                                    .into_owned();
+                                    .into_owned()
+                                    .into_fixed()?;
                                 update_bytes_flen(
                                     unsafe { output.get() },
                                     array_subset.shape(),
-                                    &bytes.into_fixed()?,
+                                    &bytes,
                                     &shard_subset_overlap.relative_to(array_subset.start())?,
                                     data_type_size,
                                 );
+                                options.recycle_bytes(bytes);
                                 Ok::<_, ArrayError>(())
                             };
                             let indices = shards.indices();
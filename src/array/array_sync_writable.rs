@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use rayon_iter_concurrent_limit::iter_concurrent_limit;
+use web_time::Instant;
 
 use crate::{
     array::ArrayBytes,
@@ -16,37 +17,13 @@ use crate::{
 use super::{
     codec::{options::CodecOptions, ArrayToBytesCodecTraits},
     concurrency::concurrency_chunks_and_codec,
-    Array, ArrayError, ArrayMetadata, ArrayMetadataOptions, Element,
+    Array, ArrayError, ArrayMetadata, Element, SparseArrayBytes,
 };
 
-impl<TStorage: ?Sized + WritableStorageTraits + 'static> Array<TStorage> {
-    /// Store metadata with default [`ArrayMetadataOptions`].
-    ///
-    /// The metadata is created with [`Array::metadata_opt`].
-    ///
-    /// # Errors
-    /// Returns [`StorageError`] if there is an underlying store error.
-    pub fn store_metadata(&self) -> Result<(), StorageError> {
-        self.store_metadata_opt(&ArrayMetadataOptions::default())
-    }
-
-    /// Store metadata with non-default [`ArrayMetadataOptions`].
-    ///
-    /// The metadata is created with [`Array::metadata_opt`].
-    ///
-    /// # Errors
-    /// Returns [`StorageError`] if there is an underlying store error.
-    pub fn store_metadata_opt(&self, options: &ArrayMetadataOptions) -> Result<(), StorageError> {
-        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
-        let storage_transformer = self
-            .storage_transformers()
-            .create_writable_transformer(storage_handle);
-
-        // Get the metadata with options applied and store
-        let metadata = self.metadata_opt(options);
-        crate::storage::create_array(&*storage_transformer, self.path(), &metadata)
-    }
+#[cfg(feature = "sharding")]
+use super::{codec::CodecError, ArrayIndices, ArrayShardedExt, ChunkRepresentation};
 
+impl<TStorage: ?Sized + WritableStorageTraits + 'static> Array<TStorage> {
     /// Encode `chunk_bytes` and store at `chunk_indices`.
     ///
     /// Use [`store_chunk_opt`](Array::store_chunk_opt) to control codec options.
@@ -66,6 +43,26 @@ impl<TStorage: ?Sized + WritableStorageTraits + 'static> Array<TStorage> {
         self.store_chunk_opt(chunk_indices, chunk_bytes, &CodecOptions::default())
     }
 
+    /// Encode the sparse representation of a chunk `chunk_sparse` and store at `chunk_indices`.
+    ///
+    /// Use [`store_chunk_sparse_opt`](Array::store_chunk_sparse_opt) to control codec options.
+    /// If the array uses the `sparse` codec with no array to array codecs, the chunk is encoded without a dense intermediate.
+    /// A chunk composed entirely of the fill value will not be written to the store.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if
+    ///  - `chunk_indices` are invalid,
+    ///  - the number of elements or element size of `chunk_sparse` does not match the chunk,
+    ///  - there is a codec encoding error, or
+    ///  - an underlying store error.
+    pub fn store_chunk_sparse(
+        &self,
+        chunk_indices: &[u64],
+        chunk_sparse: &SparseArrayBytes,
+    ) -> Result<(), ArrayError> {
+        self.store_chunk_sparse_opt(chunk_indices, chunk_sparse, &CodecOptions::default())
+    }
+
     /// Encode `chunk_elements` and store at `chunk_indices`.
     ///
     /// Use [`store_chunk_elements_opt`](Array::store_chunk_elements_opt) to control codec options.
@@ -192,12 +189,45 @@ impl<TStorage: ?Sized + WritableStorageTraits + 'static> Array<TStorage> {
         }
     }
 
+    /// Store already encoded bytes of the chunk at `chunk_indices`.
+    ///
+    /// The encoded bytes are stored as is, so they must be encoded with the codecs of the array.
+    /// This is useful for copying chunks that are encoded compatibly elsewhere without decoding and re-encoding them.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying store error.
+    pub fn store_encoded_chunk(
+        &self,
+        chunk_indices: &[u64],
+        encoded_chunk_bytes: Bytes,
+    ) -> Result<(), StorageError> {
+        let chunk_sidecars = self.chunk_sidecars_encoded(chunk_indices, &encoded_chunk_bytes)?;
+        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+        let storage_transformer = self
+            .storage_transformers()
+            .create_writable_transformer(storage_handle);
+        crate::storage::store_chunk(
+            &*storage_transformer,
+            self.path(),
+            chunk_indices,
+            self.chunk_key_encoding(),
+            encoded_chunk_bytes,
+        )?;
+        self.invalidate_shard_index(chunk_indices);
+        self.store_chunk_sidecars(&*storage_transformer, chunk_indices, Some(&chunk_sidecars))?;
+        Ok(())
+    }
+
     /// Erase the chunk at `chunk_indices`.
     ///
     /// Succeeds if the chunk does not exist.
     ///
     /// # Errors
     /// Returns a [`StorageError`] if there is an underlying store error.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), chunk_indices = ?chunk_indices))
+    )]
     pub fn erase_chunk(&self, chunk_indices: &[u64]) -> Result<(), StorageError> {
         let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
         let storage_transformer = self
@@ -208,13 +238,20 @@ impl<TStorage: ?Sized + WritableStorageTraits + 'static> Array<TStorage> {
             self.path(),
             chunk_indices,
             self.chunk_key_encoding(),
-        )
+        )?;
+        self.invalidate_shard_index(chunk_indices);
+        self.store_chunk_sidecars(&*storage_transformer, chunk_indices, None)?;
+        Ok(())
     }
 
     /// Erase the chunks in `chunks`.
     ///
     /// # Errors
     /// Returns a [`StorageError`] if there is an underlying store error.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), chunks = %chunks))
+    )]
     pub fn erase_chunks(&self, chunks: &ArraySubset) -> Result<(), StorageError> {
         let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
         let storage_transformer = self
@@ -226,18 +263,86 @@ impl<TStorage: ?Sized + WritableStorageTraits + 'static> Array<TStorage> {
                 self.path(),
                 &chunk_indices,
                 self.chunk_key_encoding(),
-            )
+            )?;
+            self.invalidate_shard_index(&chunk_indices);
+            self.store_chunk_sidecars(&*storage_transformer, &chunk_indices, None)?;
+            Ok(())
         };
 
         chunks.indices().into_par_iter().try_for_each(erase_chunk)
     }
 
+    /// Store the shard at `shard_indices` in one pass from its inner chunks.
+    ///
+    /// `write_inner_chunks` is called with a [`ShardWriter`](crate::array::codec::ShardWriter) streaming the shard to the store.
+    /// Inner chunks can be written in any order with [`ShardWriter::write_inner_chunk`](crate::array::codec::ShardWriter::write_inner_chunk), and the shard is finalized once `write_inner_chunks` returns.
+    /// Inner chunks that are not written are the fill value, and any existing shard at `shard_indices` is replaced.
+    /// If `write_inner_chunks` returns an error, the shard is abandoned.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if
+    ///  - `shard_indices` are invalid,
+    ///  - the array to bytes codec is not `sharding_indexed`, or there are array to array or bytes to bytes codecs,
+    ///  - the array maintains chunk sidecars,
+    ///  - `write_inner_chunks` returns an error,
+    ///  - there is a codec encoding error, or
+    ///  - there is an underlying store error.
+    #[cfg(feature = "sharding")]
+    pub fn store_shard_with_writer(
+        &self,
+        shard_indices: &[u64],
+        write_inner_chunks: impl FnOnce(&crate::array::codec::ShardWriter<'_>) -> Result<(), ArrayError>,
+    ) -> Result<(), ArrayError> {
+        self.store_shard_with_writer_opt(
+            shard_indices,
+            write_inner_chunks,
+            &CodecOptions::default(),
+        )
+    }
+
+    /// Encode and store the inner chunks of a sharded array from an iterator of inner chunk indices and bytes.
+    ///
+    /// The inner chunk indices are in the inner chunk grid of the array, and the inner chunks can be in any order.
+    /// This is suited to converting a stream of data (e.g. from an instrument) into a sharded array without buffering entire shards.
+    ///
+    /// Each shard is streamed to the store with a [`ShardWriter`](crate::array::codec::ShardWriter) as its inner chunks are encoded.
+    /// With a [`ShardingIndexLocation::End`](crate::array::codec::array_to_bytes::sharding::ShardingIndexLocation::End) index, only the shard indices of the shards being built are held in memory besides a batch of inner chunks.
+    /// With a [`ShardingIndexLocation::Start`](crate::array::codec::array_to_bytes::sharding::ShardingIndexLocation::Start) index, the encoded inner chunks of a shard are also held in memory until it is finalized.
+    /// A shard is finalized as soon as all of its inner chunks within the array have been stored, and any remaining shards are finalized once the iterator is exhausted.
+    /// Inner chunks that are not in the iterator are the fill value, and any existing data in a shard that is built is replaced.
+    /// An inner chunk must not be in the iterator after its shard has been finalized.
+    ///
+    /// Inner chunks are taken from the iterator in batches and encoded concurrently.
+    /// The batch size is reduced to fit the decoded and encoded bytes of each inner chunk within the memory limit of the codec options (see [`CodecOptions::set_memory_limit`]).
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if
+    ///  - the array to bytes codec is not `sharding_indexed`, or there are array to array or bytes to bytes codecs,
+    ///  - the array maintains chunk sidecars,
+    ///  - any inner chunk indices are invalid or are in a shard that has already been finalized,
+    ///  - the bytes of an inner chunk are incompatible with the inner chunk representation,
+    ///  - there is a codec encoding error, or
+    ///  - there is an underlying store error.
+    ///
+    /// Shards that are being built when an error occurs are abandoned.
+    #[cfg(feature = "sharding")]
+    pub fn store_inner_chunks_iter<'a>(
+        &self,
+        inner_chunks: impl IntoIterator<Item = (ArrayIndices, ArrayBytes<'a>)>,
+    ) -> Result<(), ArrayError> {
+        self.store_inner_chunks_iter_opt(inner_chunks, &CodecOptions::default())
+    }
+
     /////////////////////////////////////////////////////////////////////////////
     // Advanced methods
     /////////////////////////////////////////////////////////////////////////////
 
     /// Explicit options version of [`store_chunk`](Array::store_chunk).
     #[allow(clippy::missing_errors_doc)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), chunk_indices = ?chunk_indices))
+    )]
     pub fn store_chunk_opt<'a>(
         &self,
         chunk_indices: &[u64],
@@ -262,10 +367,72 @@ impl<TStorage: ?Sized + WritableStorageTraits + 'static> Array<TStorage> {
             let storage_transformer = self
                 .storage_transformers()
                 .create_writable_transformer(storage_handle);
+            #[cfg(feature = "metrics")]
+            let decoded_size = chunk_bytes.size();
+            let chunk_sidecars = self.chunk_sidecars(&chunk_bytes);
+            let start = Instant::now();
             let chunk_encoded = self
                 .codecs()
                 .encode(chunk_bytes, &chunk_array_representation, options)
                 .map_err(ArrayError::CodecError)?;
+            options
+                .concurrency_strategy()
+                .record_codec_time(start.elapsed());
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_chunk_written(decoded_size, chunk_encoded.len());
+            // Shards can be large, so they are streamed to stores which support writing in parts
+            #[cfg(feature = "sharding")]
+            let is_sharded = self.is_sharded();
+            #[cfg(not(feature = "sharding"))]
+            let is_sharded = false;
+            let store_chunk = if is_sharded {
+                crate::storage::store_chunk_streaming
+            } else {
+                crate::storage::store_chunk
+            };
+            let start = Instant::now();
+            store_chunk(
+                &*storage_transformer,
+                self.path(),
+                chunk_indices,
+                self.chunk_key_encoding(),
+                Bytes::from(chunk_encoded.into_owned()),
+            )?;
+            self.invalidate_shard_index(chunk_indices);
+            self.store_chunk_sidecars(&*storage_transformer, chunk_indices, Some(&chunk_sidecars))?;
+            options
+                .concurrency_strategy()
+                .record_storage_latency(start.elapsed());
+        }
+        Ok(())
+    }
+
+    /// Explicit options version of [`store_chunk_sparse`](Array::store_chunk_sparse).
+    #[allow(clippy::missing_errors_doc)]
+    pub fn store_chunk_sparse_opt(
+        &self,
+        chunk_indices: &[u64],
+        chunk_sparse: &SparseArrayBytes,
+        options: &CodecOptions,
+    ) -> Result<(), ArrayError> {
+        // Validation
+        let chunk_array_representation = self.chunk_array_representation(chunk_indices)?;
+        chunk_sparse.validate(
+            chunk_array_representation.num_elements(),
+            chunk_array_representation.fill_value().size(),
+        )?;
+
+        if chunk_sparse.is_fill_value() && !options.store_empty_chunks() {
+            self.erase_chunk(chunk_indices)?;
+        } else {
+            let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+            let storage_transformer = self
+                .storage_transformers()
+                .create_writable_transformer(storage_handle);
+            let chunk_encoded = self
+                .codecs()
+                .encode_sparse(chunk_sparse, &chunk_array_representation, options)
+                .map_err(ArrayError::CodecError)?;
             crate::storage::store_chunk(
                 &*storage_transformer,
                 self.path(),
@@ -273,6 +440,12 @@ impl<TStorage: ?Sized + WritableStorageTraits + 'static> Array<TStorage> {
                 self.chunk_key_encoding(),
                 Bytes::from(chunk_encoded.into_owned()),
             )?;
+            self.invalidate_shard_index(chunk_indices);
+            self.store_chunk_sidecars(
+                &*storage_transformer,
+                chunk_indices,
+                Some(&self.chunk_sidecars_sparse(chunk_sparse)),
+            )?;
         }
         Ok(())
     }
@@ -314,6 +487,10 @@ impl<TStorage: ?Sized + WritableStorageTraits + 'static> Array<TStorage> {
     /// Explicit options version of [`store_chunks`](Array::store_chunks).
     #[allow(clippy::similar_names)]
     #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %self.path(), chunks = %chunks))
+    )]
     pub fn store_chunks_opt<'a>(
         &self,
         chunks: &ArraySubset,
@@ -346,6 +523,12 @@ impl<TStorage: ?Sized + WritableStorageTraits + 'static> Array<TStorage> {
                     options,
                     &codec_concurrency,
                 );
+                let chunk_concurrent_limit = self.memory_limited_chunk_concurrency(
+                    chunk_concurrent_limit,
+                    &chunk_representation,
+                    0,
+                    &options,
+                )?;
 
                 let store_chunk = |chunk_indices: Vec<u64>| -> Result<(), ArrayError> {
                     let chunk_subset = self.chunk_subset(&chunk_indices)?;
@@ -355,6 +538,7 @@ impl<TStorage: ?Sized + WritableStorageTraits + 'static> Array<TStorage> {
                         self.data_type(),
                     )?;
                     self.store_chunk_opt(&chunk_indices, chunk_bytes, &options)
+                        .map_err(|err| self.chunk_error(&chunk_indices, err))
                 };
 
                 let indices = chunks.indices();
@@ -399,4 +583,230 @@ impl<TStorage: ?Sized + WritableStorageTraits + 'static> Array<TStorage> {
             ))
         }
     }
+
+    /// Explicit options version of [`store_shard_with_writer`](Array::store_shard_with_writer).
+    #[allow(clippy::missing_errors_doc)]
+    #[cfg(feature = "sharding")]
+    pub fn store_shard_with_writer_opt(
+        &self,
+        shard_indices: &[u64],
+        write_inner_chunks: impl FnOnce(&crate::array::codec::ShardWriter<'_>) -> Result<(), ArrayError>,
+        options: &CodecOptions,
+    ) -> Result<(), ArrayError> {
+        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+        let storage_transformer = self
+            .storage_transformers()
+            .create_writable_transformer(storage_handle);
+        let shard_writer = self.shard_writer(&*storage_transformer, shard_indices)?;
+        write_inner_chunks(&shard_writer)?;
+        shard_writer
+            .finalize(options)
+            .map_err(|err| self.chunk_error(shard_indices, err.into()))?;
+        self.invalidate_shard_index(shard_indices);
+        Ok(())
+    }
+
+    /// Create a [`ShardWriter`](crate::array::codec::ShardWriter) for the shard at `shard_indices` streaming to `storage`.
+    #[cfg(feature = "sharding")]
+    fn shard_writer<'s>(
+        &self,
+        storage: &'s dyn WritableStorageTraits,
+        shard_indices: &[u64],
+    ) -> Result<crate::array::codec::ShardWriter<'s>, ArrayError> {
+        let Some(sharding_codec) = self.sharding_codec() else {
+            return Err(CodecError::Other(
+                "a shard writer requires a sharding_indexed array to bytes codec without array to array or bytes to bytes codecs".to_string(),
+            )
+            .into());
+        };
+        if self.has_chunk_sidecars() {
+            return Err(CodecError::Other(
+                "a shard writer is not supported for an array that maintains chunk sidecars"
+                    .to_string(),
+            )
+            .into());
+        }
+        let shard_representation = self.chunk_array_representation(shard_indices)?;
+        Ok(sharding_codec.shard_writer(
+            storage,
+            self.chunk_key(shard_indices),
+            &shard_representation,
+        )?)
+    }
+
+    /// Explicit options version of [`store_inner_chunks_iter`](Array::store_inner_chunks_iter).
+    #[allow(clippy::missing_errors_doc, clippy::too_many_lines)]
+    #[cfg(feature = "sharding")]
+    pub fn store_inner_chunks_iter_opt<'a>(
+        &self,
+        inner_chunks: impl IntoIterator<Item = (ArrayIndices, ArrayBytes<'a>)>,
+        options: &CodecOptions,
+    ) -> Result<(), ArrayError> {
+        /// A shard being built and its inner chunks stored so far.
+        struct ShardInProgress<'s> {
+            shard_writer: crate::array::codec::ShardWriter<'s>,
+            inner_chunks_stored: std::collections::HashSet<ArrayIndices>,
+            num_inner_chunks: u64,
+        }
+
+        let Some(inner_chunk_shape) = self.inner_chunk_shape() else {
+            return Err(CodecError::Other(
+                "storing inner chunks requires a sharding_indexed array to bytes codec".to_string(),
+            )
+            .into());
+        };
+        let inner_chunk_grid = self.inner_chunk_grid();
+        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+        let storage_transformer = self
+            .storage_transformers()
+            .create_writable_transformer(storage_handle);
+        let inner_chunk_representation = unsafe {
+            ChunkRepresentation::new_unchecked(
+                inner_chunk_shape.as_slice().to_vec(),
+                self.data_type().clone(),
+                self.fill_value().clone(),
+            )
+        };
+
+        // Calculate inner chunk/codec concurrency
+        let codec_concurrency = self.recommended_codec_concurrency(&inner_chunk_representation)?;
+        let (chunk_concurrent_limit, codec_options) = concurrency_chunks_and_codec(
+            options.concurrent_target(),
+            options.concurrent_target(),
+            options,
+            &codec_concurrency,
+        );
+        let chunk_concurrent_limit = self.memory_limited_chunk_concurrency(
+            chunk_concurrent_limit,
+            &inner_chunk_representation,
+            0,
+            &codec_options,
+        )?;
+
+        // Locate an inner chunk in its shard
+        let shard_and_inner_chunk_indices =
+            |inner_chunk_indices: &[u64]| -> Result<(ArrayIndices, ArrayIndices), ArrayError> {
+                let invalid_indices =
+                    || ArrayError::InvalidChunkGridIndicesError(inner_chunk_indices.to_vec());
+                let inner_chunk_subset = inner_chunk_grid
+                    .subset(inner_chunk_indices, self.shape())?
+                    .ok_or_else(invalid_indices)?;
+                let shards = self
+                    .chunks_in_array_subset(&inner_chunk_subset)?
+                    .ok_or_else(invalid_indices)?;
+                if shards.num_elements() != 1 {
+                    return Err(invalid_indices());
+                }
+                let shard_indices = shards.start().to_vec();
+                let shard_origin = self.chunk_origin(&shard_indices)?;
+                let inner_chunk_indices_in_shard = std::iter::zip(
+                    std::iter::zip(inner_chunk_subset.start(), &shard_origin),
+                    inner_chunk_shape.as_slice(),
+                )
+                .map(|((start, origin), size)| (start - origin) / size.get())
+                .collect();
+                Ok((shard_indices, inner_chunk_indices_in_shard))
+            };
+
+        let mut shards_in_progress: std::collections::HashMap<ArrayIndices, ShardInProgress> =
+            std::collections::HashMap::new();
+        let mut shards_finalized: std::collections::HashSet<ArrayIndices> =
+            std::collections::HashSet::new();
+        let mut inner_chunks = inner_chunks.into_iter();
+        loop {
+            let batch: Vec<_> = inner_chunks.by_ref().take(chunk_concurrent_limit).collect();
+            if batch.is_empty() {
+                break;
+            }
+
+            // Start any new shards
+            let mut batch_located = Vec::with_capacity(batch.len());
+            for (inner_chunk_indices, bytes) in batch {
+                let (shard_indices, inner_chunk_indices_in_shard) =
+                    shard_and_inner_chunk_indices(&inner_chunk_indices)?;
+                if shards_finalized.contains(&shard_indices) {
+                    return Err(ArrayError::InvalidChunkGridIndicesError(
+                        inner_chunk_indices,
+                    ));
+                }
+                if !shards_in_progress.contains_key(&shard_indices) {
+                    let num_inner_chunks = std::iter::zip(
+                        self.chunk_subset_bounded(&shard_indices)?.shape(),
+                        inner_chunk_shape.as_slice(),
+                    )
+                    .map(|(shape, size)| shape.div_ceil(size.get()))
+                    .product();
+                    shards_in_progress.insert(
+                        shard_indices.clone(),
+                        ShardInProgress {
+                            shard_writer: self
+                                .shard_writer(&*storage_transformer, &shard_indices)?,
+                            inner_chunks_stored: std::collections::HashSet::new(),
+                            num_inner_chunks,
+                        },
+                    );
+                }
+                batch_located.push((shard_indices, inner_chunk_indices_in_shard, bytes));
+            }
+
+            let batch_stored: Vec<_> = batch_located
+                .iter()
+                .map(|(shard_indices, inner_chunk_indices_in_shard, _)| {
+                    (shard_indices.clone(), inner_chunk_indices_in_shard.clone())
+                })
+                .collect();
+
+            // Encode and write the inner chunks of the batch concurrently
+            let write_inner_chunk = |(shard_indices, inner_chunk_indices_in_shard, bytes): (
+                ArrayIndices,
+                ArrayIndices,
+                ArrayBytes<'a>,
+            )|
+             -> Result<(), ArrayError> {
+                shards_in_progress[&shard_indices]
+                    .shard_writer
+                    .write_inner_chunk(&inner_chunk_indices_in_shard, bytes, &codec_options)
+                    .map_err(|err| self.chunk_error(&shard_indices, err.into()))
+            };
+            iter_concurrent_limit!(
+                chunk_concurrent_limit,
+                batch_located,
+                try_for_each,
+                write_inner_chunk
+            )?;
+
+            // Finalize any complete shards
+            for (shard_indices, inner_chunk_indices_in_shard) in batch_stored {
+                let Some(current_shard) = shards_in_progress.get_mut(&shard_indices) else {
+                    // The shard was finalized by an earlier inner chunk in the batch
+                    continue;
+                };
+                current_shard
+                    .inner_chunks_stored
+                    .insert(inner_chunk_indices_in_shard);
+                if current_shard.inner_chunks_stored.len() as u64 != current_shard.num_inner_chunks
+                {
+                    continue;
+                }
+                if let Some(current_shard) = shards_in_progress.remove(&shard_indices) {
+                    current_shard
+                        .shard_writer
+                        .finalize(options)
+                        .map_err(|err| self.chunk_error(&shard_indices, err.into()))?;
+                    self.invalidate_shard_index(&shard_indices);
+                    shards_finalized.insert(shard_indices);
+                }
+            }
+        }
+
+        // Finalize any incomplete shards
+        for (shard_indices, current_shard) in shards_in_progress {
+            current_shard
+                .shard_writer
+                .finalize(options)
+                .map_err(|err| self.chunk_error(&shard_indices, err.into()))?;
+            self.invalidate_shard_index(&shard_indices);
+        }
+        Ok(())
+    }
 }
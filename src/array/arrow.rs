@@ -0,0 +1,660 @@
+//! [Apache Arrow](https://arrow.apache.org/) interoperability.
+//!
+//! Converts [`ArrayBytes`] to and from Arrow arrays and record batches, for passing Zarr data to Arrow based libraries such as `DataFusion` and `Polars`.
+//!
+//! Data types are mapped as follows:
+//!
+//! | Zarr data type                        | Arrow data type                          |
+//! |---------------------------------------|------------------------------------------|
+//! | `bool`                                | `Boolean`                                |
+//! | `int8` .. `uint64`                    | `Int8` .. `UInt64`                       |
+//! | `float16`, `float32`, `float64`       | `Float16`, `Float32`, `Float64`          |
+//! | `complex64`, `complex128`             | `FixedSizeList(Float32 / Float64, 2)`    |
+//! | `bfloat16`, `r*`                      | `FixedSizeBinary`                        |
+//! | `string`                              | `LargeUtf8`                              |
+//! | `binary`                              | `LargeBinary`                            |
+//!
+//! Owned element bytes are moved into an Arrow buffer without copying, unless they are insufficiently aligned for the Arrow data type.
+//! Borrowed element bytes are copied once.
+//! Boolean elements are always copied, as Arrow bit-packs booleans.
+//! Variable length element offsets are always copied, as Arrow uses [`i64`] offsets.
+//!
+//! N-dimensional arrays are flattened in C (row-major) order.
+//! The shape is stored in the [`SHAPE_METADATA_KEY`] metadata of the record batch field.
+//!
+//! One dimensional arrays of equal length (e.g. the variables of a catalog) can be streamed as a table with an [`ArrayRecordBatchReader`].
+//! It yields one record batch per chunk, split at the chunk boundaries of every column, and implements [`RecordBatchReader`] for Arrow based query engines and `DataFrame` libraries.
+//!
+//! This module requires the `arrow` feature.
+//!
+//! ### Example
+//! ```rust
+//! # use zarrs::array::{ArrayBytes, DataType};
+//! use zarrs::array::arrow::{array_bytes_to_record_batch, record_batch_to_array_bytes};
+//!
+//! let bytes = ArrayBytes::new_flen([1u16, 2, 3, 4, 5, 6].iter().flat_map(|v| v.to_ne_bytes()).collect::<Vec<u8>>());
+//! let batch = array_bytes_to_record_batch(bytes.clone(), &DataType::UInt16, &[2, 3], "values")?;
+//! assert_eq!(batch.num_rows(), 6);
+//!
+//! let (bytes_out, shape) = record_batch_to_array_bytes(&batch, &DataType::UInt16)?;
+//! assert_eq!(bytes_out, bytes);
+//! assert_eq!(shape, vec![2, 3]);
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
+
+use arrow_array::{
+    cast::AsArray, make_array, types, Array as _, ArrayRef, RecordBatch, RecordBatchReader,
+};
+use arrow_buffer::{BooleanBuffer, Buffer};
+use arrow_data::ArrayData;
+use arrow_schema::{ArrowError, DataType as ArrowDataType, Field, Schema, SchemaRef};
+use thiserror::Error;
+
+use crate::{array_subset::ArraySubset, storage::ReadableStorageTraits};
+
+use super::{
+    codec::{CodecError, CodecOptions},
+    Array, ArrayBytes, ArrayError, ArrayShape, DataType, DataTypeSize, RawBytes,
+};
+
+/// The field metadata key holding the JSON encoded shape of a flattened N-dimensional array.
+pub const SHAPE_METADATA_KEY: &str = "zarrs:shape";
+
+/// An Arrow conversion error.
+#[derive(Debug, Error)]
+pub enum ArrowConversionError {
+    /// The data type has no Arrow equivalent.
+    #[error("the data type {_0} has no Arrow equivalent")]
+    UnsupportedDataType(String),
+    /// The Arrow data type is incompatible with the Zarr data type.
+    #[error("the Arrow data type {_0} is incompatible with the data type {_1}")]
+    IncompatibleDataType(ArrowDataType, String),
+    /// The Arrow array has null values.
+    #[error("the Arrow array has {_0} null values, which cannot be represented in array bytes")]
+    NullValues(usize),
+    /// The number of elements does not match the shape.
+    #[error("the number of elements {_0} does not match the shape {_1:?}")]
+    IncompatibleShape(u64, ArrayShape),
+    /// The record batch does not have exactly one column.
+    #[error("the record batch has {_0} columns, expected 1")]
+    InvalidNumberOfColumns(usize),
+    /// The shape metadata is invalid.
+    #[error("invalid shape metadata {_0}")]
+    InvalidShapeMetadata(String),
+    /// A column of a table is incompatible with the other columns.
+    #[error("the column {_0} is incompatible: {_1}")]
+    IncompatibleColumn(String, String),
+    /// A codec error.
+    #[error(transparent)]
+    CodecError(#[from] CodecError),
+    /// An array error.
+    #[error(transparent)]
+    ArrayError(#[from] ArrayError),
+    /// An Arrow error.
+    #[error(transparent)]
+    ArrowError(#[from] ArrowError),
+}
+
+/// Return the Arrow data type corresponding to a Zarr `data_type`.
+///
+/// # Errors
+/// Returns [`ArrowConversionError::UnsupportedDataType`] if the data type is an extension data type with no Arrow equivalent.
+pub fn data_type_to_arrow(data_type: &DataType) -> Result<ArrowDataType, ArrowConversionError> {
+    Ok(match data_type {
+        DataType::Bool => ArrowDataType::Boolean,
+        DataType::Int8 => ArrowDataType::Int8,
+        DataType::Int16 => ArrowDataType::Int16,
+        DataType::Int32 => ArrowDataType::Int32,
+        DataType::Int64 => ArrowDataType::Int64,
+        DataType::UInt8 => ArrowDataType::UInt8,
+        DataType::UInt16 => ArrowDataType::UInt16,
+        DataType::UInt32 => ArrowDataType::UInt32,
+        DataType::UInt64 => ArrowDataType::UInt64,
+        DataType::Float16 => ArrowDataType::Float16,
+        DataType::Float32 => ArrowDataType::Float32,
+        DataType::Float64 => ArrowDataType::Float64,
+        DataType::Complex64 => complex_arrow_data_type(ArrowDataType::Float32),
+        DataType::Complex128 => complex_arrow_data_type(ArrowDataType::Float64),
+        DataType::BFloat16 => ArrowDataType::FixedSizeBinary(2),
+        DataType::RawBits(size) => ArrowDataType::FixedSizeBinary(
+            i32::try_from(*size)
+                .map_err(|_| ArrowConversionError::UnsupportedDataType(data_type.name()))?,
+        ),
+        DataType::String => ArrowDataType::LargeUtf8,
+        DataType::Binary => ArrowDataType::LargeBinary,
+        #[allow(unreachable_patterns)]
+        _ => return Err(ArrowConversionError::UnsupportedDataType(data_type.name())),
+    })
+}
+
+fn complex_arrow_data_type(component: ArrowDataType) -> ArrowDataType {
+    ArrowDataType::FixedSizeList(Arc::new(Field::new("item", component, false)), 2)
+}
+
+/// Move or copy `bytes` into an Arrow [`Buffer`].
+fn raw_bytes_to_buffer(bytes: RawBytes<'_>) -> Buffer {
+    match bytes {
+        Cow::Owned(bytes) => Buffer::from_vec(bytes),
+        Cow::Borrowed(bytes) => Buffer::from_slice_ref(bytes),
+    }
+}
+
+/// Convert array `bytes` with `data_type` to an Arrow array.
+///
+/// # Errors
+/// Returns an [`ArrowConversionError`] if
+///  - the data type has no Arrow equivalent,
+///  - `bytes` are not fixed/variable length matching `data_type`, or
+///  - the Arrow array cannot be created (e.g. string elements are not valid UTF-8).
+///
+/// # Panics
+/// Panics if a variable length element offset exceeds [`i64::MAX`].
+pub fn array_bytes_to_arrow(
+    bytes: ArrayBytes<'_>,
+    data_type: &DataType,
+) -> Result<ArrayRef, ArrowConversionError> {
+    let arrow_data_type = data_type_to_arrow(data_type)?;
+    let array_data = match data_type.size() {
+        DataTypeSize::Fixed(data_type_size) => {
+            let bytes = bytes.into_fixed()?;
+            if bytes.len() % data_type_size != 0 {
+                return Err(CodecError::UnexpectedChunkDecodedSize(
+                    bytes.len(),
+                    (bytes.len() - bytes.len() % data_type_size) as u64,
+                )
+                .into());
+            }
+            let num_elements = bytes.len() / data_type_size;
+            match &arrow_data_type {
+                ArrowDataType::Boolean => {
+                    let values: BooleanBuffer = bytes.iter().map(|b| *b != 0).collect();
+                    ArrayData::builder(arrow_data_type)
+                        .len(num_elements)
+                        .add_buffer(values.into_inner())
+                        .build()?
+                }
+                ArrowDataType::FixedSizeList(field, _) => {
+                    let child_data = ArrayData::builder(field.data_type().clone())
+                        .len(num_elements * 2)
+                        .add_buffer(raw_bytes_to_buffer(bytes))
+                        .build_aligned()?;
+                    ArrayData::builder(arrow_data_type)
+                        .len(num_elements)
+                        .add_child_data(child_data)
+                        .build()?
+                }
+                _ => ArrayData::builder(arrow_data_type)
+                    .len(num_elements)
+                    .add_buffer(raw_bytes_to_buffer(bytes))
+                    .build_aligned()?,
+            }
+        }
+        DataTypeSize::Variable => {
+            let (bytes, offsets) = bytes.into_variable()?;
+            let num_elements = offsets.len().saturating_sub(1);
+            let offsets = offsets
+                .iter()
+                .map(|offset| i64::try_from(*offset).unwrap())
+                .collect::<Vec<_>>();
+            ArrayData::builder(arrow_data_type)
+                .len(num_elements)
+                .add_buffer(Buffer::from_vec(offsets))
+                .add_buffer(raw_bytes_to_buffer(bytes))
+                .build()?
+        }
+    };
+    Ok(make_array(array_data))
+}
+
+/// Convert an Arrow `array` to array bytes with `data_type`.
+///
+/// Fixed length element bytes and variable length element bytes borrow from `array` without copying.
+/// Boolean elements and variable length element offsets are copied.
+///
+/// # Errors
+/// Returns an [`ArrowConversionError`] if
+///  - the Arrow data type of `array` does not match `data_type`, or
+///  - `array` has null values.
+///
+/// # Panics
+/// Panics if a variable length element offset is negative or exceeds [`usize::MAX`].
+pub fn arrow_to_array_bytes<'a>(
+    array: &'a dyn arrow_array::Array,
+    data_type: &DataType,
+) -> Result<ArrayBytes<'a>, ArrowConversionError> {
+    let arrow_data_type = data_type_to_arrow(data_type)?;
+    if array.data_type() != &arrow_data_type {
+        return Err(ArrowConversionError::IncompatibleDataType(
+            array.data_type().clone(),
+            data_type.name(),
+        ));
+    }
+    if array.null_count() > 0 {
+        return Err(ArrowConversionError::NullValues(array.null_count()));
+    }
+
+    macro_rules! primitive {
+        ( $type:ty ) => {
+            ArrayBytes::new_flen(array.as_primitive::<$type>().values().inner().as_slice())
+        };
+    }
+
+    Ok(match data_type {
+        DataType::Bool => ArrayBytes::new_flen(
+            array
+                .as_boolean()
+                .values()
+                .iter()
+                .map(u8::from)
+                .collect::<Vec<_>>(),
+        ),
+        DataType::Int8 => primitive!(types::Int8Type),
+        DataType::Int16 => primitive!(types::Int16Type),
+        DataType::Int32 => primitive!(types::Int32Type),
+        DataType::Int64 => primitive!(types::Int64Type),
+        DataType::UInt8 => primitive!(types::UInt8Type),
+        DataType::UInt16 => primitive!(types::UInt16Type),
+        DataType::UInt32 => primitive!(types::UInt32Type),
+        DataType::UInt64 => primitive!(types::UInt64Type),
+        DataType::Float16 => primitive!(types::Float16Type),
+        DataType::Float32 => primitive!(types::Float32Type),
+        DataType::Float64 => primitive!(types::Float64Type),
+        DataType::Complex64 | DataType::Complex128 => {
+            let array = array.as_fixed_size_list();
+            let (values, component_size) = if let DataType::Complex64 = data_type {
+                let values = array.values().as_primitive::<types::Float32Type>();
+                (values.values().inner(), std::mem::size_of::<f32>())
+            } else {
+                let values = array.values().as_primitive::<types::Float64Type>();
+                (values.values().inner(), std::mem::size_of::<f64>())
+            };
+            let offset = usize::try_from(array.value_offset(0)).unwrap() * component_size;
+            ArrayBytes::new_flen(
+                &values.as_slice()[offset..offset + array.len() * 2 * component_size],
+            )
+        }
+        DataType::BFloat16 | DataType::RawBits(_) => {
+            let array = array.as_fixed_size_binary();
+            let data_type_size = usize::try_from(array.value_length()).unwrap();
+            ArrayBytes::new_flen(&array.value_data()[..array.len() * data_type_size])
+        }
+        DataType::String => {
+            let array = array.as_string::<i64>();
+            vlen_array_bytes(array.values().as_slice(), array.value_offsets())
+        }
+        DataType::Binary => {
+            let array = array.as_binary::<i64>();
+            vlen_array_bytes(array.values().as_slice(), array.value_offsets())
+        }
+        #[allow(unreachable_patterns)]
+        _ => return Err(ArrowConversionError::UnsupportedDataType(data_type.name())),
+    })
+}
+
+/// Create variable length array bytes from Arrow `values` and `offsets`, rebasing the offsets to start at zero.
+fn vlen_array_bytes<'a>(values: &'a [u8], offsets: &[i64]) -> ArrayBytes<'a> {
+    let first = offsets
+        .first()
+        .map_or(0, |offset| usize::try_from(*offset).unwrap());
+    let last = offsets
+        .last()
+        .map_or(0, |offset| usize::try_from(*offset).unwrap());
+    let offsets = offsets
+        .iter()
+        .map(|offset| usize::try_from(*offset).unwrap() - first)
+        .collect::<Vec<_>>();
+    ArrayBytes::new_vlen(&values[first..last], offsets)
+}
+
+/// Convert array `bytes` with `data_type` and `shape` to a single column Arrow record batch.
+///
+/// The column is named `name`, and its elements are flattened in C order.
+/// The shape is stored in the [`SHAPE_METADATA_KEY`] field metadata as a JSON array.
+///
+/// # Errors
+/// Returns an [`ArrowConversionError`] if the bytes cannot be converted to an Arrow array or the number of elements does not match `shape`.
+pub fn array_bytes_to_record_batch(
+    bytes: ArrayBytes<'_>,
+    data_type: &DataType,
+    shape: &[u64],
+    name: &str,
+) -> Result<RecordBatch, ArrowConversionError> {
+    let array = array_bytes_to_arrow(bytes, data_type)?;
+    let num_elements: u64 = shape.iter().product();
+    if array.len() as u64 != num_elements {
+        return Err(ArrowConversionError::IncompatibleShape(
+            array.len() as u64,
+            shape.to_vec(),
+        ));
+    }
+    let field =
+        Field::new(name, array.data_type().clone(), false).with_metadata(HashMap::from([(
+            SHAPE_METADATA_KEY.to_string(),
+            serde_json::Value::from(shape).to_string(),
+        )]));
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(vec![field])),
+        vec![array],
+    )?)
+}
+
+/// Convert a single column Arrow record `batch` to array bytes with `data_type` and the array shape.
+///
+/// The shape is read from the [`SHAPE_METADATA_KEY`] field metadata if present, otherwise it is one dimensional.
+///
+/// # Errors
+/// Returns an [`ArrowConversionError`] if
+///  - `batch` does not have exactly one column,
+///  - the shape metadata is invalid or inconsistent with the number of rows, or
+///  - the column cannot be converted to array bytes.
+pub fn record_batch_to_array_bytes<'a>(
+    batch: &'a RecordBatch,
+    data_type: &DataType,
+) -> Result<(ArrayBytes<'a>, ArrayShape), ArrowConversionError> {
+    if batch.num_columns() != 1 {
+        return Err(ArrowConversionError::InvalidNumberOfColumns(
+            batch.num_columns(),
+        ));
+    }
+    let num_rows = batch.num_rows() as u64;
+    let shape = if let Some(shape) = batch
+        .schema_ref()
+        .field(0)
+        .metadata()
+        .get(SHAPE_METADATA_KEY)
+    {
+        let shape: ArrayShape = serde_json::from_str(shape)
+            .map_err(|_| ArrowConversionError::InvalidShapeMetadata(shape.clone()))?;
+        if shape.iter().product::<u64>() != num_rows {
+            return Err(ArrowConversionError::IncompatibleShape(num_rows, shape));
+        }
+        shape
+    } else {
+        vec![num_rows]
+    };
+    let bytes = arrow_to_array_bytes(batch.column(0).as_ref(), data_type)?;
+    Ok((bytes, shape))
+}
+
+/// A chunk-aligned reader of the record batches of a table of one dimensional arrays.
+///
+/// Each array is a column of the table, and all arrays must have the same length.
+/// A record batch is retrieved for each interval between consecutive chunk boundaries of any column, so each batch decodes at most one chunk per column.
+pub struct ArrayRecordBatchReader<'a, TStorage: ?Sized> {
+    columns: Vec<&'a Array<TStorage>>,
+    schema: SchemaRef,
+    boundaries: Vec<u64>,
+    batch_index: usize,
+    options: CodecOptions,
+}
+
+impl<'a, TStorage: ?Sized + ReadableStorageTraits + 'static> ArrayRecordBatchReader<'a, TStorage> {
+    /// Create a new record batch reader of named one dimensional array `columns`.
+    ///
+    /// # Errors
+    /// Returns an [`ArrowConversionError`] if
+    ///  - there are no columns,
+    ///  - a column is not one dimensional, has an unbounded chunk grid, or differs in length from the first column, or
+    ///  - the data type of a column has no Arrow equivalent.
+    pub fn new(columns: &[(&str, &'a Array<TStorage>)]) -> Result<Self, ArrowConversionError> {
+        Self::new_opt(columns, &CodecOptions::default())
+    }
+
+    /// Explicit options version of [`new`](Self::new).
+    #[allow(clippy::missing_errors_doc)]
+    pub fn new_opt(
+        columns: &[(&str, &'a Array<TStorage>)],
+        options: &CodecOptions,
+    ) -> Result<Self, ArrowConversionError> {
+        let Some((_, first)) = columns.first() else {
+            return Err(ArrowConversionError::InvalidNumberOfColumns(0));
+        };
+        let length = first.shape().first().copied().unwrap_or_default();
+        let mut boundaries = std::collections::BTreeSet::from([0, length]);
+        let mut fields = Vec::with_capacity(columns.len());
+        for (name, array) in columns {
+            if array.dimensionality() != 1 {
+                return Err(ArrowConversionError::IncompatibleColumn(
+                    (*name).to_string(),
+                    format!(
+                        "the array has {} dimensions, expected 1",
+                        array.dimensionality()
+                    ),
+                ));
+            }
+            if array.shape()[0] != length {
+                return Err(ArrowConversionError::IncompatibleColumn(
+                    (*name).to_string(),
+                    format!(
+                        "the array has length {}, expected {length}",
+                        array.shape()[0]
+                    ),
+                ));
+            }
+            let num_chunks = array
+                .chunk_grid_shape()
+                .and_then(|chunk_grid_shape| chunk_grid_shape.first().copied())
+                .ok_or_else(|| {
+                    ArrowConversionError::IncompatibleColumn(
+                        (*name).to_string(),
+                        "the chunk grid is unbounded".to_string(),
+                    )
+                })?;
+            for chunk_index in 0..num_chunks {
+                boundaries.insert(array.chunk_subset_bounded(&[chunk_index])?.start()[0]);
+            }
+            fields.push(Field::new(
+                *name,
+                data_type_to_arrow(array.data_type())?,
+                false,
+            ));
+        }
+        Ok(Self {
+            columns: columns.iter().map(|(_, array)| *array).collect(),
+            schema: Arc::new(Schema::new(fields)),
+            boundaries: boundaries.into_iter().collect(),
+            batch_index: 0,
+            options: options.clone(),
+        })
+    }
+
+    /// Return the number of record batches.
+    #[must_use]
+    pub fn num_batches(&self) -> usize {
+        self.boundaries.len().saturating_sub(1)
+    }
+
+    fn retrieve_batch(&self, rows: &ArraySubset) -> Result<RecordBatch, ArrowConversionError> {
+        let arrays = self
+            .columns
+            .iter()
+            .map(|array| {
+                let bytes = array.retrieve_array_subset_opt(rows, &self.options)?;
+                array_bytes_to_arrow(bytes, array.data_type())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(RecordBatch::try_new(self.schema.clone(), arrays)?)
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Iterator
+    for ArrayRecordBatchReader<'_, TStorage>
+{
+    type Item = Result<RecordBatch, ArrowError>;
+
+    #[allow(clippy::single_range_in_vec_init)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (start, end) = (
+            *self.boundaries.get(self.batch_index)?,
+            *self.boundaries.get(self.batch_index + 1)?,
+        );
+        self.batch_index += 1;
+        Some(
+            self.retrieve_batch(&ArraySubset::new_with_ranges(&[start..end]))
+                .map_err(|err| match err {
+                    ArrowConversionError::ArrowError(err) => err,
+                    err => ArrowError::ExternalError(Box::new(err)),
+                }),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.num_batches() - self.batch_index.min(self.num_batches());
+        (remaining, Some(remaining))
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits + 'static> RecordBatchReader
+    for ArrayRecordBatchReader<'_, TStorage>
+{
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arrow_fixed_round_trip() {
+        let elements: Vec<f32> = vec![0.0, 1.5, -2.0, 3.25];
+        let bytes = ArrayBytes::new_flen(bytemuck::cast_slice::<f32, u8>(&elements).to_vec());
+        let array = array_bytes_to_arrow(bytes.clone(), &DataType::Float32).unwrap();
+        assert_eq!(
+            &array.as_primitive::<types::Float32Type>().values()[..],
+            elements.as_slice()
+        );
+        assert_eq!(
+            arrow_to_array_bytes(array.as_ref(), &DataType::Float32).unwrap(),
+            bytes
+        );
+    }
+
+    #[test]
+    fn arrow_bool_complex_round_trip() {
+        let bytes = ArrayBytes::new_flen(vec![1u8, 0, 0, 1, 1]);
+        let array = array_bytes_to_arrow(bytes.clone(), &DataType::Bool).unwrap();
+        assert_eq!(array.as_boolean().true_count(), 3);
+        assert_eq!(
+            arrow_to_array_bytes(array.as_ref(), &DataType::Bool).unwrap(),
+            bytes
+        );
+
+        let elements: Vec<f64> = vec![1.0, 2.0, 3.0, 4.0];
+        let bytes = ArrayBytes::new_flen(bytemuck::cast_slice::<f64, u8>(&elements).to_vec());
+        let array = array_bytes_to_arrow(bytes.clone(), &DataType::Complex128).unwrap();
+        assert_eq!(array.len(), 2);
+        assert_eq!(
+            arrow_to_array_bytes(array.slice(1, 1).as_ref(), &DataType::Complex128).unwrap(),
+            ArrayBytes::new_flen(bytemuck::cast_slice::<f64, u8>(&elements[2..]))
+        );
+    }
+
+    #[test]
+    fn arrow_vlen_round_trip() {
+        let bytes = ArrayBytes::new_vlen(b"abcdef".to_vec(), vec![0, 1, 1, 3, 6]);
+        let array = array_bytes_to_arrow(bytes.clone(), &DataType::String).unwrap();
+        let strings = array.as_string::<i64>();
+        assert_eq!(strings.value(2), "bc");
+        assert_eq!(
+            arrow_to_array_bytes(array.as_ref(), &DataType::String).unwrap(),
+            bytes
+        );
+        assert_eq!(
+            arrow_to_array_bytes(array.slice(2, 2).as_ref(), &DataType::String).unwrap(),
+            ArrayBytes::new_vlen(b"bcdef".as_slice(), vec![0, 2, 5])
+        );
+        assert!(arrow_to_array_bytes(array.as_ref(), &DataType::Binary).is_err());
+        assert!(array_bytes_to_arrow(
+            ArrayBytes::new_vlen(vec![0xff], vec![0, 1]),
+            &DataType::String
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn arrow_record_batch_round_trip() {
+        let bytes = ArrayBytes::new_flen((0u8..6).collect::<Vec<_>>());
+        let batch =
+            array_bytes_to_record_batch(bytes.clone(), &DataType::UInt8, &[2, 3], "a").unwrap();
+        assert_eq!(batch.schema().field(0).name(), "a");
+        let (bytes_out, shape) = record_batch_to_array_bytes(&batch, &DataType::UInt8).unwrap();
+        assert_eq!(bytes_out, bytes);
+        assert_eq!(shape, vec![2, 3]);
+
+        assert!(array_bytes_to_record_batch(bytes, &DataType::UInt8, &[2, 2], "a").is_err());
+    }
+
+    #[test]
+    fn arrow_record_batch_reader() {
+        use crate::{
+            array::{ArrayBuilder, FillValue},
+            storage::store::MemoryStore,
+        };
+
+        let store = Arc::new(MemoryStore::new());
+        let ids = ArrayBuilder::new(
+            vec![7],
+            DataType::UInt32,
+            vec![3].try_into().unwrap(),
+            FillValue::from(0u32),
+        )
+        .build(store.clone(), "/id")
+        .unwrap();
+        ids.store_array_subset_elements(
+            &ArraySubset::new_with_ranges(&[0..7]),
+            &[0u32, 1, 2, 3, 4, 5, 6],
+        )
+        .unwrap();
+        let names = ArrayBuilder::new(
+            vec![7],
+            DataType::String,
+            vec![4].try_into().unwrap(),
+            FillValue::from(""),
+        )
+        .build(store.clone(), "/name")
+        .unwrap();
+        names
+            .store_array_subset_elements(
+                &ArraySubset::new_with_ranges(&[0..7]),
+                &["a", "b", "c", "d", "e", "f", "g"],
+            )
+            .unwrap();
+
+        let reader = ArrayRecordBatchReader::new(&[("id", &ids), ("name", &names)]).unwrap();
+        assert_eq!(reader.schema().fields().len(), 2);
+        // Chunk boundaries at 0, 3, 4, 6, 7
+        assert_eq!(reader.num_batches(), 4);
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            batches
+                .iter()
+                .map(RecordBatch::num_rows)
+                .collect::<Vec<_>>(),
+            vec![3, 1, 2, 1]
+        );
+        assert_eq!(
+            batches[2]
+                .column(0)
+                .as_primitive::<types::UInt32Type>()
+                .values()
+                .as_ref(),
+            &[4, 5]
+        );
+        assert_eq!(batches[2].column(1).as_string::<i64>().value(1), "f");
+
+        let short = ArrayBuilder::new(
+            vec![6],
+            DataType::UInt32,
+            vec![3].try_into().unwrap(),
+            FillValue::from(0u32),
+        )
+        .build(store, "/short")
+        .unwrap();
+        assert!(ArrayRecordBatchReader::new(&[("id", &ids), ("short", &short)]).is_err());
+        assert!(ArrayRecordBatchReader::<MemoryStore>::new(&[]).is_err());
+    }
+}
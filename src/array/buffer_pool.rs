@@ -0,0 +1,163 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use parking_lot::Mutex;
+
+/// Buffers smaller than this capacity are not retained by a [`BufferPool`].
+const BUFFER_POOL_MIN_CAPACITY: usize = 4096;
+
+/// A pool of reusable byte buffers.
+///
+/// A buffer pool is set on [`CodecOptions`](crate::array::codec::CodecOptions) with [`set_buffer_pool`](crate::array::codec::CodecOptions::set_buffer_pool).
+/// Codecs and array subset assembly then draw their scratch and output buffers from the pool and return intermediate buffers to it once they are no longer needed, rather than repeatedly allocating and freeing large buffers.
+///
+/// A buffer is only handed out for a request if its capacity is at most twice the requested capacity.
+/// Buffers smaller than 4 KiB are not retained, and buffers are dropped rather than retained if the pool would exceed its maximum retained bytes.
+/// Clones of a buffer pool share the same buffers.
+#[derive(Clone)]
+pub struct BufferPool {
+    max_retained_bytes: usize,
+    state: Arc<Mutex<BufferPoolState>>,
+}
+
+#[derive(Default)]
+struct BufferPoolState {
+    /// Retained buffers keyed by capacity.
+    buffers: BTreeMap<usize, Vec<Vec<u8>>>,
+    retained_bytes: usize,
+}
+
+impl core::fmt::Debug for BufferPool {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BufferPool")
+            .field("max_retained_bytes", &self.max_retained_bytes)
+            .field("retained_bytes", &self.retained_bytes())
+            .finish_non_exhaustive()
+    }
+}
+
+impl BufferPool {
+    /// Create a new buffer pool retaining at most `max_retained_bytes` of buffer capacity.
+    #[must_use]
+    pub fn new(max_retained_bytes: usize) -> Self {
+        Self {
+            max_retained_bytes,
+            state: Arc::default(),
+        }
+    }
+
+    /// Return the maximum retained bytes of the pool.
+    #[must_use]
+    pub fn max_retained_bytes(&self) -> usize {
+        self.max_retained_bytes
+    }
+
+    /// Return the total capacity of the buffers retained by the pool.
+    #[must_use]
+    pub fn retained_bytes(&self) -> usize {
+        self.state.lock().retained_bytes
+    }
+
+    /// Return the number of buffers retained by the pool.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.state.lock().buffers.values().map(Vec::len).sum()
+    }
+
+    /// Returns true if the pool does not retain any buffers.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Take an empty buffer with a capacity of at least `capacity` from the pool.
+    ///
+    /// A new buffer is allocated if the pool has no suitable buffer.
+    #[must_use]
+    pub fn take(&self, capacity: usize) -> Vec<u8> {
+        if capacity >= BUFFER_POOL_MIN_CAPACITY {
+            let mut state = self.state.lock();
+            let entry = state
+                .buffers
+                .range_mut(capacity..=capacity.saturating_mul(2))
+                .next()
+                .map(|(&buffer_capacity, buffers)| (buffer_capacity, buffers.pop()));
+            if let Some((buffer_capacity, buffer)) = entry {
+                if state.buffers[&buffer_capacity].is_empty() {
+                    state.buffers.remove(&buffer_capacity);
+                }
+                if let Some(buffer) = buffer {
+                    state.retained_bytes -= buffer_capacity;
+                    return buffer;
+                }
+            }
+        }
+        Vec::with_capacity(capacity)
+    }
+
+    /// Return a buffer to the pool.
+    ///
+    /// The buffer is cleared, and it is dropped if it is too small or the pool is full.
+    pub fn give(&self, mut buffer: Vec<u8>) {
+        let capacity = buffer.capacity();
+        if capacity < BUFFER_POOL_MIN_CAPACITY {
+            return;
+        }
+        let mut state = self.state.lock();
+        if state.retained_bytes + capacity <= self.max_retained_bytes {
+            buffer.clear();
+            state.retained_bytes += capacity;
+            state.buffers.entry(capacity).or_default().push(buffer);
+        }
+    }
+
+    /// Drop all buffers retained by the pool.
+    pub fn clear(&self) {
+        let mut state = self.state.lock();
+        state.buffers.clear();
+        state.retained_bytes = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffer_pool() {
+        let pool = BufferPool::new(64 * 1024);
+        assert!(pool.is_empty());
+
+        // Small buffers are not retained
+        pool.give(Vec::with_capacity(16));
+        assert!(pool.is_empty());
+
+        let mut buffer = pool.take(16 * 1024);
+        assert!(buffer.capacity() >= 16 * 1024);
+        buffer.extend_from_slice(&[1, 2, 3]);
+        let ptr = buffer.as_ptr();
+        let capacity = buffer.capacity();
+        pool.give(buffer);
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.retained_bytes(), capacity);
+
+        // Too large or too small a request does not reuse the buffer
+        assert!(pool.take(64 * 1024).capacity() >= 64 * 1024);
+        assert!(pool.take(4 * 1024).capacity() < capacity);
+        assert_eq!(pool.len(), 1);
+
+        // A similarly sized request reuses the buffer
+        let buffer = pool.take(12 * 1024);
+        assert_eq!(buffer.as_ptr(), ptr);
+        assert!(buffer.is_empty());
+        assert!(pool.is_empty());
+        assert_eq!(pool.retained_bytes(), 0);
+
+        // Buffers exceeding the maximum retained bytes are dropped
+        pool.give(buffer);
+        pool.give(Vec::with_capacity(60 * 1024));
+        assert_eq!(pool.len(), 1);
+        pool.clear();
+        assert!(pool.is_empty());
+        assert_eq!(pool.retained_bytes(), 0);
+    }
+}
@@ -0,0 +1,82 @@
+use std::{future::Future, sync::Arc};
+
+use futures::{
+    channel::oneshot,
+    future::{Either, FutureExt, Shared},
+};
+use parking_lot::Mutex;
+
+use super::ArrayError;
+
+/// A token for cancelling async array operations.
+///
+/// A cancellation token is set on [`CodecOptions`](crate::array::codec::CodecOptions) with [`set_cancellation_token`](crate::array::codec::CodecOptions::set_cancellation_token).
+/// Cancelling the token aborts the pending store requests of all async operations using those options, which then return [`ArrayError::Cancelled`].
+/// Clones of a token share the same cancellation state.
+#[derive(Clone)]
+pub struct CancellationToken {
+    sender: Arc<Mutex<Option<oneshot::Sender<()>>>>,
+    receiver: Shared<oneshot::Receiver<()>>,
+}
+
+impl core::fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CancellationToken {
+    /// Create a new cancellation token.
+    #[must_use]
+    pub fn new() -> Self {
+        let (sender, receiver) = oneshot::channel();
+        Self {
+            sender: Arc::new(Mutex::new(Some(sender))),
+            receiver: receiver.shared(),
+        }
+    }
+
+    /// Cancel the token.
+    pub fn cancel(&self) {
+        // Dropping the sender resolves the receiver
+        self.sender.lock().take();
+    }
+
+    /// Returns true if the token has been cancelled.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.sender.lock().is_none()
+    }
+
+    /// Returns a future that completes when the token is cancelled.
+    pub async fn cancelled(&self) {
+        let _ = self.receiver.clone().await;
+    }
+}
+
+/// Await `future` unless `cancellation_token` is cancelled first, in which case `future` is dropped.
+pub(crate) async fn async_cancellable<T>(
+    cancellation_token: Option<&CancellationToken>,
+    future: impl Future<Output = Result<T, ArrayError>>,
+) -> Result<T, ArrayError> {
+    let Some(cancellation_token) = cancellation_token else {
+        return future.await;
+    };
+    if cancellation_token.is_cancelled() {
+        return Err(ArrayError::Cancelled);
+    }
+    let future = std::pin::pin!(future);
+    let cancelled = std::pin::pin!(cancellation_token.cancelled());
+    match futures::future::select(future, cancelled).await {
+        Either::Left((result, _)) => result,
+        Either::Right(((), _)) => Err(ArrayError::Cancelled),
+    }
+}
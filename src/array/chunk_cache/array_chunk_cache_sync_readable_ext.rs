@@ -146,8 +146,12 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> ArrayChunkCacheExt<TSto
         options: &CodecOptions,
     ) -> Result<Arc<ArrayBytes<'static>>, ArrayError> {
         if let Some(chunk) = cache.get(chunk_indices) {
+            #[cfg(feature = "metrics")]
+            crate::metrics::increment_counter(crate::metrics::Metric::ChunkCacheHits, 1);
             Ok(chunk)
         } else {
+            #[cfg(feature = "metrics")]
+            crate::metrics::increment_counter(crate::metrics::Metric::ChunkCacheMisses, 1);
             let chunk = Arc::new(
                 self.retrieve_chunk_opt(chunk_indices, options)?
                     .into_owned(),
@@ -318,26 +322,20 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> ArrayChunkCacheExt<TSto
 
         // Merge
         match self.data_type().size() {
-            DataTypeSize::Variable => {
-                // Arc<ArrayBytes> -> ArrayBytes (not copied, but a bit wasteful, change merge_chunks_vlen?)
-                let chunk_bytes_and_subsets = chunk_bytes_and_subsets
-                    .iter()
-                    .map(|(chunk_bytes, chunk_subset)| {
-                        (ArrayBytes::clone(chunk_bytes), chunk_subset.clone())
-                    })
-                    .collect();
-                Ok(merge_chunks_vlen(
-                    chunk_bytes_and_subsets,
-                    array_subset.shape(),
-                )?)
-            }
+            DataTypeSize::Variable => Ok(merge_chunks_vlen(
+                &chunk_bytes_and_subsets,
+                array_subset.shape(),
+            )?),
             DataTypeSize::Fixed(data_type_size) => {
                 // Allocate the output
                 let size_output = array_subset.num_elements_usize() * data_type_size;
-                let mut output = Vec::with_capacity(size_output);
+                let mut output = options.take_buffer(size_output);
 
                 {
-                    let output = UnsafeCellSlice::new_from_vec_with_spare_capacity(&mut output);
+                    let output = UnsafeCellSlice::new_from_vec_with_spare_capacity_len(
+                        &mut output,
+                        size_output,
+                    );
                     let update_output =
                         |(chunk_subset_bytes, chunk_subset): (Arc<ArrayBytes>, ArraySubset)| {
                             // Extract the overlapping bytes
@@ -348,13 +346,15 @@ impl<TStorage: ?Sized + ReadableStorageTraits + 'static> ArrayChunkCacheExt<TSto
                                 self.data_type(),
                             )?;
 
+                            let chunk_subset_bytes = chunk_subset_bytes.into_fixed()?;
                             update_bytes_flen(
                                 unsafe { output.get() },
                                 array_subset.shape(),
-                                &chunk_subset_bytes.into_fixed()?,
+                                &chunk_subset_bytes,
                                 &chunk_subset_overlap.relative_to(array_subset.start())?,
                                 data_type_size,
                             );
+                            options.recycle_bytes(chunk_subset_bytes);
                             Ok::<_, ArrayError>(())
                         };
                     iter_concurrent_limit!(
@@ -506,7 +506,8 @@ mod tests {
             .unwrap();
 
         // Create a cache with a size limit equivalent to 2 chunks
-        let chunk_size = 4 * 4 * size_of::<u8>();
+        let chunk_size =
+            size_of::<ArrayBytes>() + ArrayBytes::new_flen(vec![0u8; 4 * 4]).heap_size();
         let cache = ChunkCacheLruSizeLimit::new(2 * chunk_size as u64);
 
         assert_eq!(performance_metrics.reads(), 0);
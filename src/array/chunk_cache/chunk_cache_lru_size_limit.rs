@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{mem::size_of, sync::Arc};
 
 use moka::{
     policy::EvictionPolicy,
@@ -12,6 +12,8 @@ use super::ChunkCache;
 type ChunkIndices = ArrayIndices;
 
 /// A chunk cache with a fixed size capacity.
+///
+/// The size of a cached chunk is the size of its [`ArrayBytes`] plus its [`heap_size`](ArrayBytes::heap_size), which includes the element offsets of variable length chunks.
 pub struct ChunkCacheLruSizeLimit {
     cache: Cache<ChunkIndices, Arc<ArrayBytes<'static>>>,
 }
@@ -22,7 +24,9 @@ impl ChunkCacheLruSizeLimit {
     pub fn new(capacity: u64) -> Self {
         let cache = CacheBuilder::new(capacity)
             .eviction_policy(EvictionPolicy::lru())
-            .weigher(|_k, v: &Arc<ArrayBytes<'_>>| u32::try_from(v.size()).unwrap_or(u32::MAX))
+            .weigher(|_k, v: &Arc<ArrayBytes<'_>>| {
+                u32::try_from(size_of::<ArrayBytes>() + v.heap_size()).unwrap_or(u32::MAX)
+            })
             .build();
         Self { cache }
     }
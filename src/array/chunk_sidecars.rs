@@ -0,0 +1,232 @@
+use std::borrow::Cow;
+
+use crate::{
+    node::NodePath,
+    storage::{
+        Bytes, ReadableListableStorageTraits, StorageError, StoreKey, StorePrefix,
+        WritableStorageTraits,
+    },
+};
+
+#[cfg(feature = "async")]
+use crate::storage::{AsyncBytes, AsyncReadableListableStorageTraits, AsyncWritableStorageTraits};
+
+use super::{
+    chunk_statistics::ChunkStatistics,
+    chunk_value_index::ChunkValueSummary,
+    codec::{array_to_bytes::bytes::reverse_endianness, ArrayToBytesCodecTraits, CodecOptions},
+    Array, ArrayBytes, ArrayIndices, DataType, Endianness, SparseArrayBytes,
+    CHUNK_STATISTICS_PREFIX, CHUNK_VALUE_INDEX_PREFIX, NATIVE_ENDIAN,
+};
+
+/// The sidecar values of a chunk maintained on write, which are [`None`] if disabled.
+#[derive(Debug, Default)]
+pub(crate) struct ChunkSidecars {
+    value_summary: Option<ChunkValueSummary>,
+    statistics: Option<ChunkStatistics>,
+}
+
+/// The keys and values of the sidecars of a chunk, where the values are [`None`] if the sidecars should be erased.
+type ChunkSidecarValues = Vec<(StoreKey, Option<Vec<u8>>)>;
+
+/// Convert a native endian value of `data_type` to little endian, or vice versa.
+pub(super) fn swap_le(value: &[u8], data_type: &DataType) -> Vec<u8> {
+    let mut value = value.to_vec();
+    if NATIVE_ENDIAN == Endianness::Big {
+        reverse_endianness(&mut value, data_type);
+    }
+    value
+}
+
+/// Returns the prefix of the chunk sidecars with `sidecar_prefix` of the array at `path`.
+pub(super) fn chunk_sidecar_prefix(path: &NodePath, sidecar_prefix: &str) -> String {
+    let path = path.as_str().strip_prefix('/').unwrap_or(path.as_str());
+    if path.is_empty() {
+        sidecar_prefix.to_string()
+    } else {
+        format!("{path}/{sidecar_prefix}")
+    }
+}
+
+/// Returns the key of the chunk sidecar with `sidecar_prefix` of the chunk at `chunk_indices` of the array at `path`.
+pub(super) fn chunk_sidecar_key(
+    path: &NodePath,
+    sidecar_prefix: &str,
+    chunk_indices: &[u64],
+) -> Result<StoreKey, StorageError> {
+    let chunk_indices = chunk_indices
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(".");
+    Ok(StoreKey::new(format!(
+        "{}{chunk_indices}",
+        chunk_sidecar_prefix(path, sidecar_prefix)
+    ))?)
+}
+
+/// Parse the chunk indices from the key of a chunk sidecar with `prefix`.
+fn parse_chunk_sidecar_key(key: &StoreKey, prefix: &str) -> Option<ArrayIndices> {
+    key.as_str()
+        .strip_prefix(prefix)?
+        .split('.')
+        .map(|index| index.parse().ok())
+        .collect()
+}
+
+/// Retrieve the chunk sidecars with `sidecar_prefix` of the array at `path`, in increasing order of chunk indices.
+pub(super) fn retrieve_chunk_sidecars(
+    storage: &dyn ReadableListableStorageTraits,
+    path: &NodePath,
+    sidecar_prefix: &str,
+) -> Result<Vec<(ArrayIndices, Bytes)>, StorageError> {
+    let prefix = chunk_sidecar_prefix(path, sidecar_prefix);
+    let mut sidecars = Vec::new();
+    for key in storage.list_prefix(&StorePrefix::new(prefix.clone())?)? {
+        if let Some(chunk_indices) = parse_chunk_sidecar_key(&key, &prefix) {
+            if let Some(sidecar) = storage.get(&key)? {
+                sidecars.push((chunk_indices, sidecar));
+            }
+        }
+    }
+    sidecars.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(sidecars)
+}
+
+#[cfg(feature = "async")]
+/// Async variant of [`retrieve_chunk_sidecars`].
+pub(super) async fn async_retrieve_chunk_sidecars(
+    storage: &dyn AsyncReadableListableStorageTraits,
+    path: &NodePath,
+    sidecar_prefix: &str,
+) -> Result<Vec<(ArrayIndices, AsyncBytes)>, StorageError> {
+    let prefix = chunk_sidecar_prefix(path, sidecar_prefix);
+    let mut sidecars = Vec::new();
+    for key in storage
+        .list_prefix(&StorePrefix::new(prefix.clone())?)
+        .await?
+    {
+        if let Some(chunk_indices) = parse_chunk_sidecar_key(&key, &prefix) {
+            if let Some(sidecar) = storage.get(&key).await? {
+                sidecars.push((chunk_indices, sidecar));
+            }
+        }
+    }
+    sidecars.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(sidecars)
+}
+
+impl<TStorage: ?Sized> Array<TStorage> {
+    /// Returns true if any sidecar is maintained on write.
+    pub(super) fn has_chunk_sidecars(&self) -> bool {
+        self.chunk_value_index.is_some() || self.chunk_statistics
+    }
+
+    /// Returns the sidecars of the dense `bytes` of a chunk.
+    pub(crate) fn chunk_sidecars(&self, bytes: &ArrayBytes) -> ChunkSidecars {
+        ChunkSidecars {
+            value_summary: self.chunk_value_summary(bytes),
+            statistics: self.chunk_statistics_of(bytes),
+        }
+    }
+
+    /// Returns the sidecars of the sparse `bytes` of a chunk.
+    pub(crate) fn chunk_sidecars_sparse(&self, sparse: &SparseArrayBytes) -> ChunkSidecars {
+        ChunkSidecars {
+            value_summary: self.chunk_value_summary_sparse(sparse),
+            statistics: self.chunk_statistics_of_sparse(sparse),
+        }
+    }
+
+    /// Returns the sidecars of the already encoded bytes of the chunk at `chunk_indices`.
+    ///
+    /// The chunk is only decoded if a sidecar is maintained on write.
+    pub(crate) fn chunk_sidecars_encoded(
+        &self,
+        chunk_indices: &[u64],
+        encoded_chunk_bytes: &[u8],
+    ) -> Result<ChunkSidecars, StorageError> {
+        if !self.has_chunk_sidecars() {
+            return Ok(ChunkSidecars::default());
+        }
+        let chunk_representation = self
+            .chunk_array_representation(chunk_indices)
+            .map_err(|err| StorageError::Other(err.to_string()))?;
+        let bytes = self
+            .codecs()
+            .decode(
+                Cow::Borrowed(encoded_chunk_bytes),
+                &chunk_representation,
+                &CodecOptions::default(),
+            )
+            .map_err(|err| StorageError::Other(err.to_string()))?;
+        Ok(self.chunk_sidecars(&bytes))
+    }
+
+    /// Returns the keys and values of the enabled sidecars of the chunk at `chunk_indices`, where the values are [`None`] if the sidecars should be erased.
+    fn chunk_sidecar_values(
+        &self,
+        chunk_indices: &[u64],
+        sidecars: Option<&ChunkSidecars>,
+    ) -> Result<ChunkSidecarValues, StorageError> {
+        let mut values = Vec::new();
+        if self.chunk_value_index.is_some() {
+            values.push((
+                chunk_sidecar_key(self.path(), CHUNK_VALUE_INDEX_PREFIX, chunk_indices)?,
+                sidecars
+                    .and_then(|sidecars| sidecars.value_summary.as_ref())
+                    .map(ChunkValueSummary::to_bytes),
+            ));
+        }
+        if self.chunk_statistics {
+            values.push((
+                chunk_sidecar_key(self.path(), CHUNK_STATISTICS_PREFIX, chunk_indices)?,
+                sidecars
+                    .and_then(|sidecars| sidecars.statistics.as_ref())
+                    .map(|statistics| statistics.to_bytes(self.data_type())),
+            ));
+        }
+        Ok(values)
+    }
+}
+
+impl<TStorage: ?Sized + WritableStorageTraits + 'static> Array<TStorage> {
+    /// Store the `sidecars` of the chunk at `chunk_indices`, or erase them if [`None`].
+    ///
+    /// Only sidecars maintained on write are stored or erased.
+    pub(crate) fn store_chunk_sidecars(
+        &self,
+        storage: &dyn WritableStorageTraits,
+        chunk_indices: &[u64],
+        sidecars: Option<&ChunkSidecars>,
+    ) -> Result<(), StorageError> {
+        for (key, value) in self.chunk_sidecar_values(chunk_indices, sidecars)? {
+            if let Some(value) = value {
+                storage.set(&key, Bytes::from(value))?;
+            } else {
+                storage.erase(&key)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl<TStorage: ?Sized + AsyncWritableStorageTraits + 'static> Array<TStorage> {
+    /// Async variant of [`store_chunk_sidecars`](Array::store_chunk_sidecars).
+    pub(crate) async fn async_store_chunk_sidecars(
+        &self,
+        storage: &dyn AsyncWritableStorageTraits,
+        chunk_indices: &[u64],
+        sidecars: Option<&ChunkSidecars>,
+    ) -> Result<(), StorageError> {
+        for (key, value) in self.chunk_sidecar_values(chunk_indices, sidecars)? {
+            if let Some(value) = value {
+                storage.set(&key, AsyncBytes::from(value)).await?;
+            } else {
+                storage.erase(&key).await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,439 @@
+use std::{
+    ops::{Bound, RangeBounds},
+    sync::Arc,
+};
+
+use crate::storage::{ListableStorageTraits, ReadableStorageTraits, StorageError, StorageHandle};
+
+#[cfg(feature = "async")]
+use crate::storage::{AsyncListableStorageTraits, AsyncReadableStorageTraits};
+
+use super::{
+    chunk_sidecars::{chunk_sidecar_key, retrieve_chunk_sidecars, swap_le},
+    Array, ArrayBytes, ArrayError, ArrayIndices, DataType, ElementOwned, SparseArrayBytes,
+};
+
+#[cfg(feature = "async")]
+use super::chunk_sidecars::async_retrieve_chunk_sidecars;
+
+/// The prefix under which chunk statistics are stored.
+///
+/// The statistics of the chunk at `chunk_indices` of the array at `path` are stored at `{path}/__zarrs_stats/{chunk_indices}`, where the chunk indices are separated by `.`.
+/// Node discovery skips prefixes starting with `__`.
+pub const CHUNK_STATISTICS_PREFIX: &str = "__zarrs_stats/";
+
+/// The statistics of a chunk, recorded in a sidecar value when the chunk is stored.
+///
+/// See [`Array::set_chunk_statistics_enabled`], [`Array::retrieve_chunk_statistics`], and [`Array::chunks_where`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkStatistics {
+    num_elements: u64,
+    num_fill_value: u64,
+    num_nan: u64,
+    /// The native endian minimum and maximum elements that are not NaN.
+    min_max: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+/// Returns true if chunk statistics are supported for `data_type`.
+fn chunk_statistics_supported(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Bool
+            | DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+            | DataType::Float16
+            | DataType::Float32
+            | DataType::Float64
+            | DataType::BFloat16
+    )
+}
+
+/// Returns the statistics of native endian `values` of `T` which occur `count` times.
+fn chunk_statistics<'a, T: bytemuck::Pod + PartialOrd>(
+    fill_value: &[u8],
+    values: impl Iterator<Item = (&'a [u8], u64)>,
+) -> ChunkStatistics {
+    let mut statistics = ChunkStatistics {
+        num_elements: 0,
+        num_fill_value: 0,
+        num_nan: 0,
+        min_max: None,
+    };
+    let mut min_max: Option<(T, T)> = None;
+    for (bytes, count) in values {
+        statistics.num_elements += count;
+        if bytes == fill_value {
+            statistics.num_fill_value += count;
+        }
+        let value: T = bytemuck::pod_read_unaligned(bytes);
+        if value.partial_cmp(&value).is_none() {
+            statistics.num_nan += count;
+            continue;
+        }
+        min_max = Some(match min_max {
+            Some((min, max)) if value < min => (value, max),
+            Some((min, max)) if value > max => (min, value),
+            Some(min_max) => min_max,
+            None => (value, value),
+        });
+    }
+    statistics.min_max = min_max.map(|(min, max)| {
+        (
+            bytemuck::bytes_of(&min).to_vec(),
+            bytemuck::bytes_of(&max).to_vec(),
+        )
+    });
+    statistics
+}
+
+impl ChunkStatistics {
+    /// Create the statistics of the native endian `values` of `data_type` which occur `count` times.
+    ///
+    /// Returns [`None`] if chunk statistics are not supported for `data_type`.
+    fn new<'a>(
+        data_type: &DataType,
+        fill_value: &[u8],
+        values: impl Iterator<Item = (&'a [u8], u64)>,
+    ) -> Option<Self> {
+        Some(match data_type {
+            DataType::Bool | DataType::UInt8 => chunk_statistics::<u8>(fill_value, values),
+            DataType::Int8 => chunk_statistics::<i8>(fill_value, values),
+            DataType::Int16 => chunk_statistics::<i16>(fill_value, values),
+            DataType::Int32 => chunk_statistics::<i32>(fill_value, values),
+            DataType::Int64 => chunk_statistics::<i64>(fill_value, values),
+            DataType::UInt16 => chunk_statistics::<u16>(fill_value, values),
+            DataType::UInt32 => chunk_statistics::<u32>(fill_value, values),
+            DataType::UInt64 => chunk_statistics::<u64>(fill_value, values),
+            DataType::Float16 => chunk_statistics::<half::f16>(fill_value, values),
+            DataType::Float32 => chunk_statistics::<f32>(fill_value, values),
+            DataType::Float64 => chunk_statistics::<f64>(fill_value, values),
+            DataType::BFloat16 => chunk_statistics::<half::bf16>(fill_value, values),
+            _ => return None,
+        })
+    }
+
+    /// Return the number of elements.
+    #[must_use]
+    pub const fn num_elements(&self) -> u64 {
+        self.num_elements
+    }
+
+    /// Return the number of elements equal to the fill value.
+    #[must_use]
+    pub const fn num_fill_value(&self) -> u64 {
+        self.num_fill_value
+    }
+
+    /// Return the number of NaN elements.
+    #[must_use]
+    pub const fn num_nan(&self) -> u64 {
+        self.num_nan
+    }
+
+    /// Return the minimum and maximum elements that are not NaN, or [`None`] if all elements are NaN.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if `T` is incompatible with `data_type`.
+    pub fn min_max<T: ElementOwned>(
+        &self,
+        data_type: &DataType,
+    ) -> Result<Option<(T, T)>, ArrayError> {
+        let Some((min, max)) = &self.min_max else {
+            return Ok(None);
+        };
+        let mut min_max =
+            T::from_array_bytes(data_type, ArrayBytes::new_flen([&min[..], max].concat()))?
+                .into_iter();
+        Ok(min_max.next().zip(min_max.next()))
+    }
+
+    /// Returns true if any element of the chunk may be within `range`.
+    fn overlaps<T: ElementOwned + PartialOrd>(
+        &self,
+        data_type: &DataType,
+        range: &impl RangeBounds<T>,
+    ) -> Result<bool, ArrayError> {
+        let Some((min, max)) = self.min_max::<T>(data_type)? else {
+            return Ok(false);
+        };
+        let above_start = match range.start_bound() {
+            Bound::Included(start) => max >= *start,
+            Bound::Excluded(start) => max > *start,
+            Bound::Unbounded => true,
+        };
+        let below_end = match range.end_bound() {
+            Bound::Included(end) => min <= *end,
+            Bound::Excluded(end) => min < *end,
+            Bound::Unbounded => true,
+        };
+        Ok(above_start && below_end)
+    }
+
+    pub(super) fn to_bytes(&self, data_type: &DataType) -> Vec<u8> {
+        let mut bytes = [self.num_elements, self.num_fill_value, self.num_nan]
+            .iter()
+            .flat_map(|count| count.to_le_bytes())
+            .collect::<Vec<u8>>();
+        if let Some((min, max)) = &self.min_max {
+            bytes.push(1);
+            bytes.extend(swap_le(min, data_type));
+            bytes.extend(swap_le(max, data_type));
+        } else {
+            bytes.push(0);
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8], data_type: &DataType) -> Result<Self, StorageError> {
+        let invalid = || StorageError::Other("invalid chunk statistics".to_string());
+        let element_size = data_type.fixed_size().ok_or_else(invalid)?;
+        if bytes.len() < 24 {
+            return Err(invalid());
+        }
+        let (counts, min_max) = bytes.split_at(24);
+        let counts: Vec<u64> = counts
+            .chunks_exact(8)
+            .map(|count| u64::from_le_bytes(count.try_into().unwrap()))
+            .collect();
+        let min_max = match min_max.split_first() {
+            Some((0, [])) => None,
+            Some((1, min_max)) if min_max.len() == 2 * element_size => {
+                let (min, max) = min_max.split_at(element_size);
+                Some((swap_le(min, data_type), swap_le(max, data_type)))
+            }
+            _ => return Err(invalid()),
+        };
+        Ok(Self {
+            num_elements: counts[0],
+            num_fill_value: counts[1],
+            num_nan: counts[2],
+            min_max,
+        })
+    }
+}
+
+impl<TStorage: ?Sized> Array<TStorage> {
+    /// Returns true if chunk statistics are recorded on write.
+    #[must_use]
+    pub const fn chunk_statistics_enabled(&self) -> bool {
+        self.chunk_statistics
+    }
+
+    /// Enable or disable recording chunk statistics on write.
+    ///
+    /// If enabled, the [`ChunkStatistics`] of each chunk stored or erased through this [`Array`] are recorded in a sidecar value under [`CHUNK_STATISTICS_PREFIX`], which [`chunks_where`](Array::chunks_where) queries to prune chunks without retrieving them.
+    /// Chunks that were stored with statistics disabled, or modified through another [`Array`] or by another process with statistics disabled, have no statistics.
+    /// Chunk statistics are only supported for boolean, integer, and floating point data types, and enabling them is ignored otherwise.
+    pub fn set_chunk_statistics_enabled(&mut self, enabled: bool) -> &mut Self {
+        self.chunk_statistics = enabled && chunk_statistics_supported(self.data_type());
+        self
+    }
+
+    /// Returns the statistics of the dense `bytes` of a chunk, or [`None`] if chunk statistics are disabled.
+    pub(super) fn chunk_statistics_of(&self, bytes: &ArrayBytes) -> Option<ChunkStatistics> {
+        if !self.chunk_statistics {
+            return None;
+        }
+        let element_size = self.data_type().fixed_size()?;
+        let ArrayBytes::Fixed(bytes) = bytes else {
+            return None;
+        };
+        ChunkStatistics::new(
+            self.data_type(),
+            self.fill_value().as_ne_bytes(),
+            bytes.chunks_exact(element_size).map(|value| (value, 1)),
+        )
+    }
+
+    /// Returns the statistics of the sparse `bytes` of a chunk, or [`None`] if chunk statistics are disabled.
+    pub(super) fn chunk_statistics_of_sparse(
+        &self,
+        sparse: &SparseArrayBytes,
+    ) -> Option<ChunkStatistics> {
+        if !self.chunk_statistics {
+            return None;
+        }
+        let fill_value = self.fill_value().as_ne_bytes();
+        let num_fill_value = sparse.num_elements() - sparse.num_stored() as u64;
+        ChunkStatistics::new(
+            self.data_type(),
+            fill_value,
+            sparse
+                .iter()
+                .map(|(_, value)| (value, 1))
+                .chain((num_fill_value > 0).then_some((fill_value, num_fill_value))),
+        )
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
+    /// Retrieve the statistics of the chunk at `chunk_indices`.
+    ///
+    /// Returns [`None`] if the chunk has no statistics, which is the case if it does not exist or was not stored with chunk statistics enabled.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if the chunk statistics are invalid or there is an underlying store error.
+    pub fn retrieve_chunk_statistics(
+        &self,
+        chunk_indices: &[u64],
+    ) -> Result<Option<ChunkStatistics>, ArrayError> {
+        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+        let storage_transformer = self
+            .storage_transformers()
+            .create_readable_transformer(storage_handle);
+        let key = chunk_sidecar_key(self.path(), CHUNK_STATISTICS_PREFIX, chunk_indices)?;
+        Ok(storage_transformer
+            .get(&key)?
+            .map(|bytes| ChunkStatistics::from_bytes(&bytes, self.data_type()))
+            .transpose()?)
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits + ListableStorageTraits + 'static> Array<TStorage> {
+    /// Returns the indices of the chunks with an element that may be within `range` according to their statistics, in increasing order.
+    ///
+    /// Only chunks stored with [chunk statistics enabled](Array::set_chunk_statistics_enabled) are considered, and no chunks are retrieved.
+    /// Chunks that do not exist are not considered, even if the fill value is within `range`.
+    /// NaN elements are never within `range`.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if
+    ///  - `T` is incompatible with the data type,
+    ///  - chunk statistics are invalid, or
+    ///  - there is an underlying store error.
+    pub fn chunks_where<T: ElementOwned + PartialOrd>(
+        &self,
+        range: impl RangeBounds<T>,
+    ) -> Result<Vec<ArrayIndices>, ArrayError> {
+        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+        let storage_transformer = self
+            .storage_transformers()
+            .create_readable_listable_transformer(storage_handle);
+        let mut chunks = Vec::new();
+        for (chunk_indices, statistics) in
+            retrieve_chunk_sidecars(&*storage_transformer, self.path(), CHUNK_STATISTICS_PREFIX)?
+        {
+            let statistics = ChunkStatistics::from_bytes(&statistics, self.data_type())?;
+            if statistics.overlaps(self.data_type(), &range)? {
+                chunks.push(chunk_indices);
+            }
+        }
+        Ok(chunks)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<TStorage: ?Sized + AsyncReadableStorageTraits + 'static> Array<TStorage> {
+    /// Async variant of [`retrieve_chunk_statistics`](Array::retrieve_chunk_statistics).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_retrieve_chunk_statistics(
+        &self,
+        chunk_indices: &[u64],
+    ) -> Result<Option<ChunkStatistics>, ArrayError> {
+        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+        let storage_transformer = self
+            .storage_transformers()
+            .create_async_readable_transformer(storage_handle);
+        let key = chunk_sidecar_key(self.path(), CHUNK_STATISTICS_PREFIX, chunk_indices)?;
+        Ok(storage_transformer
+            .get(&key)
+            .await?
+            .map(|bytes| ChunkStatistics::from_bytes(&bytes, self.data_type()))
+            .transpose()?)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<TStorage: ?Sized + AsyncReadableStorageTraits + AsyncListableStorageTraits + 'static>
+    Array<TStorage>
+{
+    /// Async variant of [`chunks_where`](Array::chunks_where).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_chunks_where<T: ElementOwned + PartialOrd>(
+        &self,
+        range: impl RangeBounds<T>,
+    ) -> Result<Vec<ArrayIndices>, ArrayError> {
+        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+        let storage_transformer = self
+            .storage_transformers()
+            .create_async_readable_listable_transformer(storage_handle);
+        let mut chunks = Vec::new();
+        for (chunk_indices, statistics) in async_retrieve_chunk_sidecars(
+            &*storage_transformer,
+            self.path(),
+            CHUNK_STATISTICS_PREFIX,
+        )
+        .await?
+        {
+            let statistics = ChunkStatistics::from_bytes(&statistics, self.data_type())?;
+            if statistics.overlaps(self.data_type(), &range)? {
+                chunks.push(chunk_indices);
+            }
+        }
+        Ok(chunks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_statistics() {
+        let values = [1.5f32, f32::NAN, 0.0, -2.0, 0.0];
+        let statistics = ChunkStatistics::new(
+            &DataType::Float32,
+            &0.0f32.to_ne_bytes(),
+            values.iter().map(|value| (bytemuck::bytes_of(value), 1)),
+        )
+        .unwrap();
+        assert_eq!(statistics.num_elements(), 5);
+        assert_eq!(statistics.num_fill_value(), 2);
+        assert_eq!(statistics.num_nan(), 1);
+        assert_eq!(
+            statistics.min_max::<f32>(&DataType::Float32).unwrap(),
+            Some((-2.0, 1.5))
+        );
+        assert!(statistics.min_max::<f64>(&DataType::Float32).is_err());
+        assert!(statistics
+            .overlaps(&DataType::Float32, &(1.0f32..))
+            .unwrap());
+        assert!(statistics
+            .overlaps(&DataType::Float32, &(..=-2.0f32))
+            .unwrap());
+        assert!(!statistics
+            .overlaps(&DataType::Float32, &(..-2.0f32))
+            .unwrap());
+        assert!(statistics
+            .overlaps(&DataType::Float32, &(1.5f32..))
+            .unwrap());
+        assert!(!statistics
+            .overlaps(&DataType::Float32, &(2.0f32..3.0))
+            .unwrap());
+        assert_eq!(
+            ChunkStatistics::from_bytes(
+                &statistics.to_bytes(&DataType::Float32),
+                &DataType::Float32
+            )
+            .unwrap(),
+            statistics
+        );
+        assert!(ChunkStatistics::from_bytes(&[0; 24], &DataType::Float32).is_err());
+
+        let nan = ChunkStatistics::new(
+            &DataType::Float32,
+            &0.0f32.to_ne_bytes(),
+            std::iter::once((&f32::NAN.to_ne_bytes()[..], 4)),
+        )
+        .unwrap();
+        assert_eq!(nan.num_nan(), 4);
+        assert!(!nan.overlaps::<f32>(&DataType::Float32, &(..)).unwrap());
+        assert!(ChunkStatistics::new(&DataType::String, &[], std::iter::empty()).is_none());
+    }
+}
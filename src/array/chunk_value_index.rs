@@ -0,0 +1,318 @@
+use std::{collections::HashSet, sync::Arc};
+
+use crate::storage::{ListableStorageTraits, ReadableStorageTraits, StorageError, StorageHandle};
+
+#[cfg(feature = "async")]
+use crate::storage::{AsyncListableStorageTraits, AsyncReadableStorageTraits};
+
+use super::{
+    chunk_sidecars::{retrieve_chunk_sidecars, swap_le},
+    Array, ArrayBytes, ArrayError, ArrayIndices, DataType, Element, SparseArrayBytes,
+};
+
+#[cfg(feature = "async")]
+use super::chunk_sidecars::async_retrieve_chunk_sidecars;
+
+/// The prefix under which chunk value indexes are stored.
+///
+/// The index of the chunk at `chunk_indices` of the array at `path` is stored at `{path}/__zarrs_values/{chunk_indices}`, where the chunk indices are separated by `.`.
+/// Node discovery skips prefixes starting with `__`.
+pub const CHUNK_VALUE_INDEX_PREFIX: &str = "__zarrs_values/";
+
+/// A chunk value index, which records the unique values of each chunk of an array in a sidecar value when the chunk is stored.
+///
+/// See [`Array::set_chunk_value_index`] and [`Array::chunks_containing`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkValueIndex {
+    /// The exact set of unique values of each chunk.
+    ///
+    /// The index of a chunk grows with the number of unique values.
+    Exact,
+    /// A bloom filter of the unique values of each chunk.
+    ///
+    /// The index of a chunk is `num_bits / 8` bytes, and a query may return chunks that do not contain the value.
+    /// The false positive rate is minimised for `n` unique values in a chunk with `num_hashes = num_bits / n * ln(2)`.
+    BloomFilter {
+        /// The number of bits of the bloom filter.
+        num_bits: u64,
+        /// The number of hash functions.
+        num_hashes: u32,
+    },
+}
+
+/// The index of the unique values of a chunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ChunkValueSummary {
+    /// The sorted unique little endian values.
+    Exact(Vec<u8>),
+    /// A bloom filter of the unique little endian values.
+    BloomFilter { num_hashes: u32, bits: Vec<u8> },
+}
+
+const SUMMARY_EXACT: u8 = 0;
+const SUMMARY_BLOOM_FILTER: u8 = 1;
+
+/// Returns the bits of the bloom filter with `num_bits` bits and `num_hashes` hashes that are set for `value`.
+fn bloom_filter_bits(value: &[u8], num_bits: u64, num_hashes: u32) -> impl Iterator<Item = u64> {
+    // FNV-1a, with a second hash derived with the splitmix64 finaliser for double hashing
+    let hash1 = value.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    let mut hash2 = hash1;
+    hash2 = (hash2 ^ (hash2 >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash2 = (hash2 ^ (hash2 >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash2 = (hash2 ^ (hash2 >> 31)) | 1;
+    (0..u64::from(num_hashes)).map(move |i| hash1.wrapping_add(i.wrapping_mul(hash2)) % num_bits)
+}
+
+impl ChunkValueSummary {
+    /// Create the summary of the native endian `values` of `data_type`.
+    fn new<'a>(
+        index: ChunkValueIndex,
+        values: impl IntoIterator<Item = &'a [u8]>,
+        data_type: &DataType,
+    ) -> Self {
+        let mut unique: Vec<Vec<u8>> = values
+            .into_iter()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .map(|value| swap_le(value, data_type))
+            .collect();
+        unique.sort_unstable();
+        match index {
+            ChunkValueIndex::Exact => Self::Exact(unique.concat()),
+            ChunkValueIndex::BloomFilter {
+                num_bits,
+                num_hashes,
+            } => {
+                let num_bits = num_bits.max(1);
+                let mut bits = vec![0u8; usize::try_from(num_bits.div_ceil(8)).unwrap()];
+                for value in &unique {
+                    for bit in bloom_filter_bits(value, num_bits, num_hashes) {
+                        bits[usize::try_from(bit / 8).unwrap()] |= 1 << (bit % 8);
+                    }
+                }
+                Self::BloomFilter { num_hashes, bits }
+            }
+        }
+    }
+
+    /// Returns true if the chunk may contain the little endian `value`.
+    fn may_contain(&self, value: &[u8]) -> bool {
+        match self {
+            Self::Exact(values) => {
+                // The values are sorted, so binary search over the elements
+                let element_size = value.len().max(1);
+                let (mut low, mut high) = (0, values.len() / element_size);
+                while low < high {
+                    let mid = (low + high) / 2;
+                    match values[mid * element_size..(mid + 1) * element_size].cmp(value) {
+                        std::cmp::Ordering::Less => low = mid + 1,
+                        std::cmp::Ordering::Greater => high = mid,
+                        std::cmp::Ordering::Equal => return true,
+                    }
+                }
+                false
+            }
+            Self::BloomFilter { num_hashes, bits } => {
+                let num_bits = bits.len() as u64 * 8;
+                num_bits > 0
+                    && bloom_filter_bits(value, num_bits, *num_hashes)
+                        .all(|bit| bits[usize::try_from(bit / 8).unwrap()] & (1 << (bit % 8)) != 0)
+            }
+        }
+    }
+
+    pub(super) fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Exact(values) => [&[SUMMARY_EXACT][..], values].concat(),
+            Self::BloomFilter { num_hashes, bits } => {
+                [&[SUMMARY_BLOOM_FILTER][..], &num_hashes.to_le_bytes(), bits].concat()
+            }
+        }
+    }
+
+    fn from_bytes(bytes: &[u8], element_size: usize) -> Result<Self, StorageError> {
+        let invalid = || StorageError::Other("invalid chunk value index".to_string());
+        match bytes.split_first() {
+            Some((&SUMMARY_EXACT, values)) if values.len() % element_size.max(1) == 0 => {
+                Ok(Self::Exact(values.to_vec()))
+            }
+            Some((&SUMMARY_BLOOM_FILTER, bloom_filter)) if bloom_filter.len() >= 4 => {
+                let (num_hashes, bits) = bloom_filter.split_at(4);
+                Ok(Self::BloomFilter {
+                    num_hashes: u32::from_le_bytes(num_hashes.try_into().unwrap()),
+                    bits: bits.to_vec(),
+                })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl<TStorage: ?Sized> Array<TStorage> {
+    /// Get the chunk value index.
+    #[must_use]
+    pub const fn chunk_value_index(&self) -> Option<ChunkValueIndex> {
+        self.chunk_value_index
+    }
+
+    /// Set the chunk value index.
+    ///
+    /// If set, the unique values of each chunk stored or erased through this [`Array`] are recorded in a sidecar value under [`CHUNK_VALUE_INDEX_PREFIX`], which [`chunks_containing`](Array::chunks_containing) queries without retrieving any chunks.
+    /// Chunks that were stored without the index set, or modified through another [`Array`] or by another process without the index set, are not indexed.
+    /// The index is ignored if the data type is not fixed size.
+    pub fn set_chunk_value_index(
+        &mut self,
+        chunk_value_index: Option<ChunkValueIndex>,
+    ) -> &mut Self {
+        self.chunk_value_index =
+            chunk_value_index.filter(|_| self.data_type().fixed_size().is_some());
+        self
+    }
+
+    /// Returns the chunk value summary of the dense `bytes` of a chunk, or [`None`] if the array has no chunk value index.
+    pub(super) fn chunk_value_summary(&self, bytes: &ArrayBytes) -> Option<ChunkValueSummary> {
+        let index = self.chunk_value_index?;
+        let element_size = self.data_type().fixed_size()?;
+        let ArrayBytes::Fixed(bytes) = bytes else {
+            return None;
+        };
+        Some(ChunkValueSummary::new(
+            index,
+            bytes.chunks_exact(element_size),
+            self.data_type(),
+        ))
+    }
+
+    /// Returns the chunk value summary of the sparse `bytes` of a chunk, or [`None`] if the array has no chunk value index.
+    pub(super) fn chunk_value_summary_sparse(
+        &self,
+        sparse: &SparseArrayBytes,
+    ) -> Option<ChunkValueSummary> {
+        let index = self.chunk_value_index?;
+        let fill_value = (sparse.num_stored() as u64) < sparse.num_elements();
+        let values = sparse.iter().map(|(_, value)| value);
+        Some(if fill_value {
+            ChunkValueSummary::new(
+                index,
+                values.chain(std::iter::once(self.fill_value().as_ne_bytes())),
+                self.data_type(),
+            )
+        } else {
+            ChunkValueSummary::new(index, values, self.data_type())
+        })
+    }
+
+    /// Returns the little endian bytes of `value`.
+    fn chunk_value_index_value<T: Element>(&self, value: &T) -> Result<Vec<u8>, ArrayError> {
+        let bytes = T::into_array_bytes(self.data_type(), std::slice::from_ref(value))?;
+        Ok(swap_le(&bytes.into_fixed()?, self.data_type()))
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits + ListableStorageTraits + 'static> Array<TStorage> {
+    /// Returns the indices of the chunks that may contain `value` according to the chunk value index, in increasing order.
+    ///
+    /// Only chunks indexed with [`set_chunk_value_index`](Array::set_chunk_value_index) set are considered, and no chunks are retrieved.
+    /// The fill value of chunks that do not exist is not indexed.
+    /// If chunks were indexed with [`ChunkValueIndex::BloomFilter`], chunks that do not contain `value` may be returned.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if
+    ///  - `value` is incompatible with the data type,
+    ///  - a chunk value index is invalid, or
+    ///  - there is an underlying store error.
+    pub fn chunks_containing<T: Element>(
+        &self,
+        value: &T,
+    ) -> Result<Vec<ArrayIndices>, ArrayError> {
+        let value = self.chunk_value_index_value(value)?;
+        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+        let storage_transformer = self
+            .storage_transformers()
+            .create_readable_listable_transformer(storage_handle);
+        let mut chunks = Vec::new();
+        for (chunk_indices, summary) in
+            retrieve_chunk_sidecars(&*storage_transformer, self.path(), CHUNK_VALUE_INDEX_PREFIX)?
+        {
+            if ChunkValueSummary::from_bytes(&summary, value.len())?.may_contain(&value) {
+                chunks.push(chunk_indices);
+            }
+        }
+        Ok(chunks)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<TStorage: ?Sized + AsyncReadableStorageTraits + AsyncListableStorageTraits + 'static>
+    Array<TStorage>
+{
+    /// Async variant of [`chunks_containing`](Array::chunks_containing).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_chunks_containing<T: Element>(
+        &self,
+        value: &T,
+    ) -> Result<Vec<ArrayIndices>, ArrayError> {
+        let value = self.chunk_value_index_value(value)?;
+        let storage_handle = Arc::new(StorageHandle::new(self.storage.clone()));
+        let storage_transformer = self
+            .storage_transformers()
+            .create_async_readable_listable_transformer(storage_handle);
+        let mut chunks = Vec::new();
+        for (chunk_indices, summary) in async_retrieve_chunk_sidecars(
+            &*storage_transformer,
+            self.path(),
+            CHUNK_VALUE_INDEX_PREFIX,
+        )
+        .await?
+        {
+            if ChunkValueSummary::from_bytes(&summary, value.len())?.may_contain(&value) {
+                chunks.push(chunk_indices);
+            }
+        }
+        Ok(chunks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_value_summary() {
+        let values: Vec<&[u8]> = vec![&[3], &[1], &[3], &[200]];
+        let exact =
+            ChunkValueSummary::new(ChunkValueIndex::Exact, values.clone(), &DataType::UInt8);
+        assert_eq!(exact, ChunkValueSummary::Exact(vec![1, 3, 200]));
+        assert!(exact.may_contain(&[3]));
+        assert!(!exact.may_contain(&[2]));
+        assert_eq!(
+            ChunkValueSummary::from_bytes(&exact.to_bytes(), 1).unwrap(),
+            exact
+        );
+
+        let bloom_filter = ChunkValueSummary::new(
+            ChunkValueIndex::BloomFilter {
+                num_bits: 1024,
+                num_hashes: 4,
+            },
+            values,
+            &DataType::UInt8,
+        );
+        assert!([1, 3, 200]
+            .iter()
+            .all(|value| bloom_filter.may_contain(&[*value])));
+        assert!(
+            (0..=255u8)
+                .filter(|value| bloom_filter.may_contain(&[*value]))
+                .count()
+                < 16
+        );
+        assert_eq!(
+            ChunkValueSummary::from_bytes(&bloom_filter.to_bytes(), 1).unwrap(),
+            bloom_filter
+        );
+        assert!(ChunkValueSummary::from_bytes(&[2], 1).is_err());
+    }
+}
@@ -3,6 +3,7 @@
 //! Array chunks can be encoded using a sequence of codecs, each of which specifies a bidirectional transform (an encode transform and a decode transform).
 //! A codec can map array to an array, an array to bytes, or bytes to bytes.
 //! A codec may support partial decoding to extract a byte range or array subset without needing to decode the entire input.
+//! A codec may support partial encoding to update an array subset without needing to encode the entire output.
 //!
 //! A [`CodecChain`] represents a codec sequence consisting of any number of array to array and bytes to bytes codecs, and one array to bytes codec.
 //! A codec chain is itself an array to bytes codec.
@@ -15,8 +16,19 @@ pub mod array_to_bytes;
 pub mod bytes_to_bytes;
 pub mod options;
 
+#[cfg(any(test, feature = "codec_test_suite"))]
+pub mod codec_test_suite;
+
 pub use options::{CodecOptions, CodecOptionsBuilder};
 
+mod codec_scratch;
+pub use codec_scratch::CodecScratch;
+
+#[cfg(feature = "async")]
+mod async_codec_executor;
+#[cfg(feature = "async")]
+pub use async_codec_executor::{AsyncBlockingTask, AsyncCodecExecutor, AsyncSpawnBlockingFn};
+
 // Array to array
 #[cfg(feature = "bitround")]
 pub use array_to_array::bitround::{
@@ -34,9 +46,14 @@ pub use array_to_bytes::codec_chain::CodecChain;
 pub use array_to_bytes::pcodec::{
     PcodecCodec, PcodecCodecConfiguration, PcodecCodecConfigurationV1,
 };
+pub use array_to_bytes::rle::{RleCodec, RleCodecConfiguration, RleCodecConfigurationV1};
 #[cfg(feature = "sharding")]
 pub use array_to_bytes::sharding::{
-    ShardingCodec, ShardingCodecConfiguration, ShardingCodecConfigurationV1,
+    ShardIndexCache, ShardWriter, ShardingCodec, ShardingCodecConfiguration,
+    ShardingCodecConfigurationV1,
+};
+pub use array_to_bytes::sparse::{
+    SparseCodec, SparseCodecConfiguration, SparseCodecConfigurationV1,
 };
 #[cfg(feature = "zfp")]
 pub use array_to_bytes::zfp::{ZfpCodec, ZfpCodecConfiguration, ZfpCodecConfigurationV1};
@@ -66,15 +83,19 @@ pub use bytes_partial_decoder_cache::BytesPartialDecoderCache;
 mod byte_interval_partial_decoder;
 pub use byte_interval_partial_decoder::ByteIntervalPartialDecoder;
 
+mod array_to_bytes_partial_encoder_default;
+pub use array_to_bytes_partial_encoder_default::ArrayToBytesPartialEncoderDefault;
+
 #[cfg(feature = "async")]
 pub use byte_interval_partial_decoder::AsyncByteIntervalPartialDecoder;
 
 use crate::{
     array_subset::{ArraySubset, IncompatibleArraySubsetAndShapeError},
     byte_range::{ByteOffset, ByteRange, InvalidByteRangeError},
+    config::global_config,
     metadata::v3::MetadataV3,
     plugin::{Plugin, PluginCreateError},
-    storage::{ReadableStorage, StorageError, StoreKey},
+    storage::{ReadableStorage, StorageError, StoreKey, StoreKeyStartValue, WritableStorage},
 };
 
 #[cfg(feature = "async")]
@@ -91,7 +112,7 @@ use super::{
     concurrency::RecommendedConcurrency, ArrayMetadataOptions, BytesRepresentation,
     ChunkRepresentation, ChunkShape, DataType,
 };
-use super::{ArrayBytes, RawBytes};
+use super::{ArrayBytes, RawBytes, SparseArrayBytes};
 
 /// A codec plugin.
 pub type CodecPlugin = Plugin<Codec>;
@@ -116,6 +137,19 @@ impl Codec {
     pub fn from_metadata(metadata: &MetadataV3) -> Result<Self, PluginCreateError> {
         for plugin in inventory::iter::<CodecPlugin> {
             if plugin.match_name(metadata.name()) {
+                let experimental_disabled = {
+                    let config = global_config();
+                    !config.experimental_codecs_enabled()
+                        && config
+                            .experimental_codec_names()
+                            .contains_key(plugin.identifier())
+                };
+                if experimental_disabled {
+                    return Err(PluginCreateError::Other(format!(
+                        "experimental codec {} is disabled in the global config",
+                        metadata.name()
+                    )));
+                }
                 return plugin.create(metadata);
             }
         }
@@ -152,6 +186,12 @@ impl Codec {
                 array_to_bytes::vlen_v2::IDENTIFIER => {
                     return array_to_bytes::vlen_v2::create_codec_vlen_v2(metadata);
                 }
+                array_to_bytes::sparse::IDENTIFIER => {
+                    return array_to_bytes::sparse::create_codec_sparse(metadata);
+                }
+                array_to_bytes::rle::IDENTIFIER => {
+                    return array_to_bytes::rle::create_codec_rle(metadata);
+                }
                 #[cfg(feature = "blosc")]
                 bytes_to_bytes::blosc::IDENTIFIER => {
                     return bytes_to_bytes::blosc::create_codec_blosc(metadata);
@@ -207,6 +247,13 @@ pub trait CodecTraits: Send + Sync {
     /// Indicates if a partial decoder decodes all bytes from its input handle and its output should be cached for optimal performance.
     /// If true, a cache will be inserted at some point *after* it in a [`CodecChain`] partial decoder.
     fn partial_decoder_decodes_all(&self) -> bool;
+
+    /// Indicates if a partial encoder decodes and encodes all bytes of its output, rather than only the parts affected by an update.
+    ///
+    /// This is true for most codecs, and false for codecs such as `sharding` that can update parts of their output in place.
+    fn partial_encoder_encodes_all(&self) -> bool {
+        true
+    }
 }
 
 /// Traits for both array to array and array to bytes codecs.
@@ -276,6 +323,17 @@ pub trait BytesPartialDecoderTraits: Send + Sync {
             .partial_decode(&[ByteRange::FromStart(0, None)], options)?
             .map(|mut v| v.remove(0)))
     }
+
+    /// Return the size of the input in bytes.
+    ///
+    /// Returns [`None`] if partial decoding of the input handle returns [`None`].
+    /// The default implementation decodes all bytes, implementations should override this if the size is cheaper to query.
+    ///
+    /// # Errors
+    /// Returns [`CodecError`] if a codec fails.
+    fn size(&self, options: &CodecOptions) -> Result<Option<u64>, CodecError> {
+        Ok(self.decode(options)?.map(|bytes| bytes.len() as u64))
+    }
 }
 
 #[cfg(feature = "async")]
@@ -380,6 +438,65 @@ pub trait AsyncArrayPartialDecoderTraits: Send + Sync {
     ) -> Result<Vec<ArrayBytes<'_>>, CodecError>;
 }
 
+/// Partial bytes encoder traits.
+pub trait BytesPartialEncoderTraits: Send + Sync {
+    /// Erase the output.
+    ///
+    /// # Errors
+    /// Returns [`CodecError`] if erasing the output fails.
+    fn erase(&self) -> Result<(), CodecError>;
+
+    /// Encode (replace) all bytes of the output.
+    ///
+    /// # Errors
+    /// Returns [`CodecError`] if a codec fails.
+    fn encode(&self, bytes: RawBytes<'_>, options: &CodecOptions) -> Result<(), CodecError>;
+
+    /// Partially encode bytes at byte offsets of the output, extending the output if needed.
+    ///
+    /// # Errors
+    /// Returns [`CodecError`] if a codec fails.
+    fn partial_encode(
+        &self,
+        offsets_and_bytes: &[(ByteOffset, RawBytes<'_>)],
+        options: &CodecOptions,
+    ) -> Result<(), CodecError>;
+}
+
+/// Partial array encoder traits.
+pub trait ArrayPartialEncoderTraits: Send + Sync {
+    /// Return the data type of the partial encoder.
+    fn data_type(&self) -> &DataType;
+
+    /// Erase the chunk.
+    ///
+    /// # Errors
+    /// Returns [`CodecError`] if erasing the chunk fails.
+    fn erase(&self) -> Result<(), CodecError>;
+
+    /// Partially encode a chunk with default codec options.
+    ///
+    /// Each array subset of the chunk is updated with its corresponding bytes.
+    /// Use [`partial_encode_opt`](ArrayPartialEncoderTraits::partial_encode_opt) to control codec options.
+    ///
+    /// # Errors
+    /// Returns [`CodecError`] if a codec fails, an array subset is invalid, or the bytes are incompatible with an array subset.
+    fn partial_encode(
+        &self,
+        subsets_and_bytes: &[(&ArraySubset, ArrayBytes<'_>)],
+    ) -> Result<(), CodecError> {
+        self.partial_encode_opt(subsets_and_bytes, &CodecOptions::default())
+    }
+
+    /// Explicit options version of [`partial_encode`](ArrayPartialEncoderTraits::partial_encode).
+    #[allow(clippy::missing_errors_doc)]
+    fn partial_encode_opt(
+        &self,
+        subsets_and_bytes: &[(&ArraySubset, ArrayBytes<'_>)],
+        options: &CodecOptions,
+    ) -> Result<(), CodecError>;
+}
+
 /// A [`ReadableStorage`] store value partial decoder.
 pub struct StoragePartialDecoder {
     storage: ReadableStorage,
@@ -394,20 +511,88 @@ impl StoragePartialDecoder {
 }
 
 impl BytesPartialDecoderTraits for StoragePartialDecoder {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            skip_all,
+            fields(key = %self.key, byte_ranges = decoded_regions.len(), bytes = tracing::field::Empty)
+        )
+    )]
     fn partial_decode(
         &self,
         decoded_regions: &[ByteRange],
         _options: &CodecOptions,
     ) -> Result<Option<Vec<RawBytes<'_>>>, CodecError> {
+        #[cfg(feature = "metrics")]
+        let start = web_time::Instant::now();
+        let values = self
+            .storage
+            .get_partial_values_key(&self.key, decoded_regions)?;
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_duration(crate::metrics::Metric::StoreGetDuration, start.elapsed());
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record(
+            "bytes",
+            values
+                .as_ref()
+                .map(|vec_bytes| vec_bytes.iter().map(bytes::Bytes::len).sum::<usize>()),
+        );
+        Ok(values.map(|vec_bytes| {
+            vec_bytes
+                .into_iter()
+                .map(|bytes| Cow::Owned(bytes.to_vec()))
+                .collect()
+        }))
+    }
+
+    fn size(&self, _options: &CodecOptions) -> Result<Option<u64>, CodecError> {
+        Ok(self.storage.size_key(&self.key)?)
+    }
+}
+
+/// A [`WritableStorage`] store value partial encoder.
+pub struct StoragePartialEncoder {
+    storage: WritableStorage,
+    key: StoreKey,
+}
+
+impl StoragePartialEncoder {
+    /// Create a new storage partial encoder.
+    pub fn new(storage: WritableStorage, key: StoreKey) -> Self {
+        Self { storage, key }
+    }
+}
+
+impl BytesPartialEncoderTraits for StoragePartialEncoder {
+    fn erase(&self) -> Result<(), CodecError> {
+        Ok(self.storage.erase(&self.key)?)
+    }
+
+    fn encode(&self, bytes: RawBytes<'_>, _options: &CodecOptions) -> Result<(), CodecError> {
         Ok(self
             .storage
-            .get_partial_values_key(&self.key, decoded_regions)?
-            .map(|vec_bytes| {
-                vec_bytes
-                    .into_iter()
-                    .map(|bytes| Cow::Owned(bytes.to_vec()))
-                    .collect()
-            }))
+            .set(&self.key, bytes::Bytes::from(bytes.into_owned()))?)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            skip_all,
+            fields(key = %self.key, byte_ranges = offsets_and_bytes.len())
+        )
+    )]
+    fn partial_encode(
+        &self,
+        offsets_and_bytes: &[(ByteOffset, RawBytes<'_>)],
+        _options: &CodecOptions,
+    ) -> Result<(), CodecError> {
+        let key_start_values = offsets_and_bytes
+            .iter()
+            .map(|(offset, bytes)| StoreKeyStartValue::new(self.key.clone(), *offset, bytes))
+            .collect::<Vec<_>>();
+        Ok(self.storage.set_partial_values(&key_start_values)?)
     }
 }
 
@@ -429,6 +614,14 @@ impl AsyncStoragePartialDecoder {
 #[cfg(feature = "async")]
 #[async_trait::async_trait]
 impl AsyncBytesPartialDecoderTraits for AsyncStoragePartialDecoder {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            skip_all,
+            fields(key = %self.key, byte_ranges = decoded_regions.len())
+        )
+    )]
     async fn partial_decode(
         &self,
         decoded_regions: &[ByteRange],
@@ -436,8 +629,9 @@ impl AsyncBytesPartialDecoderTraits for AsyncStoragePartialDecoder {
     ) -> Result<Option<Vec<RawBytes<'_>>>, CodecError> {
         Ok(self
             .storage
-            .get_partial_values_key(&self.key, decoded_regions)
+            .get_partial_values_batched(&[(&self.key, decoded_regions)])
             .await?
+            .remove(0)
             .map(|vec_bytes| {
                 vec_bytes
                     .into_iter()
@@ -546,6 +740,40 @@ pub trait ArrayToBytesCodecTraits:
         options: &CodecOptions,
     ) -> Result<ArrayBytes<'a>, CodecError>;
 
+    /// Encode a chunk from a sparse representation.
+    ///
+    /// The default implementation encodes the dense representation of `sparse`.
+    /// Codecs with a sparse encoded representation should override this to avoid a dense intermediate.
+    ///
+    /// # Errors
+    /// Returns [`CodecError`] if a codec fails or `sparse` is incompatible with `decoded_representation`.
+    fn encode_sparse(
+        &self,
+        sparse: &SparseArrayBytes,
+        decoded_representation: &ChunkRepresentation,
+        options: &CodecOptions,
+    ) -> Result<RawBytes<'static>, CodecError> {
+        let bytes = sparse.to_dense(decoded_representation.fill_value())?;
+        self.encode(bytes, decoded_representation, options)
+    }
+
+    /// Decode a chunk to a sparse representation.
+    ///
+    /// The default implementation decodes the chunk to a dense representation and then sparsifies it.
+    /// Codecs with a sparse encoded representation should override this to avoid a dense intermediate.
+    ///
+    /// # Errors
+    /// Returns [`CodecError`] if a codec fails or the decoded output is incompatible with `decoded_representation`.
+    fn decode_sparse(
+        &self,
+        bytes: RawBytes<'_>,
+        decoded_representation: &ChunkRepresentation,
+        options: &CodecOptions,
+    ) -> Result<SparseArrayBytes, CodecError> {
+        let bytes = self.decode(bytes, decoded_representation, options)?;
+        SparseArrayBytes::from_array_bytes(&bytes, decoded_representation.fill_value())
+    }
+
     /// Initialise a partial decoder.
     ///
     /// # Errors
@@ -557,6 +785,28 @@ pub trait ArrayToBytesCodecTraits:
         options: &CodecOptions,
     ) -> Result<Arc<dyn ArrayPartialDecoderTraits + 'a>, CodecError>;
 
+    /// Initialise a partial encoder.
+    ///
+    /// `input_handle` reads the existing encoded chunk and `output_handle` writes the updated encoded chunk.
+    /// The default implementation is an [`ArrayToBytesPartialEncoderDefault`], which decodes and encodes the entire chunk.
+    ///
+    /// # Errors
+    /// Returns a [`CodecError`] if initialisation fails.
+    fn partial_encoder<'a>(
+        &'a self,
+        input_handle: Arc<dyn BytesPartialDecoderTraits + 'a>,
+        output_handle: Arc<dyn BytesPartialEncoderTraits + 'a>,
+        decoded_representation: &ChunkRepresentation,
+        _options: &CodecOptions,
+    ) -> Result<Arc<dyn ArrayPartialEncoderTraits + 'a>, CodecError> {
+        Ok(Arc::new(ArrayToBytesPartialEncoderDefault::new(
+            input_handle,
+            output_handle,
+            decoded_representation.clone(),
+            self,
+        )))
+    }
+
     #[cfg(feature = "async")]
     /// Initialise an asynchronous partial decoder.
     ///
@@ -777,6 +1027,27 @@ pub enum CodecError {
     ExpectedVariableLengthBytes,
 }
 
+impl CodecError {
+    /// Return the store key involved in the error, if known.
+    #[must_use]
+    pub fn store_key(&self) -> Option<&StoreKey> {
+        match self {
+            Self::StorageError(err) => err.store_key(),
+            _ => None,
+        }
+    }
+
+    /// Return the byte range involved in the error, if known.
+    #[must_use]
+    pub fn byte_range(&self) -> Option<ByteRange> {
+        match self {
+            Self::InvalidByteRangeError(err) => Some(err.byte_range()),
+            Self::StorageError(err) => err.byte_range(),
+            _ => None,
+        }
+    }
+}
+
 impl From<&str> for CodecError {
     fn from(err: &str) -> Self {
         Self::Other(err.to_string())
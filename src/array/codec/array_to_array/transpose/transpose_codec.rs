@@ -184,6 +184,13 @@ impl ArrayToArrayCodecTraits for TransposeCodec {
         &self,
         decoded_representation: &ChunkRepresentation,
     ) -> Result<ChunkRepresentation, CodecError> {
+        if self.order.0.len() != decoded_representation.dimensionality() {
+            return Err(CodecError::Other(format!(
+                "transpose order {:?} does not match the chunk dimensionality {}",
+                self.order.0,
+                decoded_representation.dimensionality()
+            )));
+        }
         let transposed_shape = permute(decoded_representation.shape(), &self.order);
         Ok(unsafe {
             ChunkRepresentation::new_unchecked(
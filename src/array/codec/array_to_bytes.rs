@@ -2,6 +2,8 @@
 
 pub mod bytes;
 pub mod codec_chain;
+pub mod rle;
+pub mod sparse;
 pub mod vlen;
 pub mod vlen_v2;
 
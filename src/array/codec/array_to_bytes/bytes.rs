@@ -4,6 +4,7 @@
 //!
 //! See <https://zarr-specs.readthedocs.io/en/latest/v3/codecs/bytes/v1.0.html>.
 
+mod byte_swap;
 mod bytes_codec;
 mod bytes_partial_decoder;
 
@@ -46,25 +47,13 @@ pub fn reverse_endianness(v: &mut [u8], data_type: &DataType) {
     match data_type {
         DataType::Bool | DataType::Int8 | DataType::UInt8 | DataType::RawBits(_) => {}
         DataType::Int16 | DataType::UInt16 | DataType::Float16 | DataType::BFloat16 => {
-            let swap = |chunk: &mut [u8]| {
-                let bytes = u16::from_ne_bytes(unsafe { chunk.try_into().unwrap_unchecked() });
-                chunk.copy_from_slice(bytes.swap_bytes().to_ne_bytes().as_slice());
-            };
-            v.chunks_exact_mut(2).for_each(swap);
+            byte_swap::swap_bytes::<2>(v);
         }
         DataType::Int32 | DataType::UInt32 | DataType::Float32 | DataType::Complex64 => {
-            let swap = |chunk: &mut [u8]| {
-                let bytes = u32::from_ne_bytes(unsafe { chunk.try_into().unwrap_unchecked() });
-                chunk.copy_from_slice(bytes.swap_bytes().to_ne_bytes().as_slice());
-            };
-            v.chunks_exact_mut(4).for_each(swap);
+            byte_swap::swap_bytes::<4>(v);
         }
         DataType::Int64 | DataType::UInt64 | DataType::Float64 | DataType::Complex128 => {
-            let swap = |chunk: &mut [u8]| {
-                let bytes = u64::from_ne_bytes(unsafe { chunk.try_into().unwrap_unchecked() });
-                chunk.copy_from_slice(bytes.swap_bytes().to_ne_bytes().as_slice());
-            };
-            v.chunks_exact_mut(8).for_each(swap);
+            byte_swap::swap_bytes::<8>(v);
         }
         // Variable-sized data types are not supported and are rejected outside of this function
         DataType::String | DataType::Binary => unreachable!(),
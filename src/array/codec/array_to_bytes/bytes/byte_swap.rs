@@ -0,0 +1,158 @@
+//! Byte swapping of `N`-byte elements.
+//!
+//! On `x86_64`, elements are swapped with AVX2 or SSSE3 shuffles if supported by the CPU (detected at runtime).
+//! Otherwise, elements are swapped individually.
+
+/// Swap the bytes of each `N`-byte element of `v`.
+///
+/// The length of `v` must be a multiple of `N`, and `N` must be 2, 4, or 8.
+pub(super) fn swap_bytes<const N: usize>(v: &mut [u8]) {
+    debug_assert!(matches!(N, 2 | 4 | 8));
+    debug_assert_eq!(v.len() % N, 0);
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if std::arch::is_x86_feature_detected!("avx2") {
+            // SAFETY: AVX2 is supported
+            unsafe { x86_64::swap_bytes_avx2::<N>(v) };
+            return;
+        }
+        if std::arch::is_x86_feature_detected!("ssse3") {
+            // SAFETY: SSSE3 is supported
+            unsafe { x86_64::swap_bytes_ssse3::<N>(v) };
+            return;
+        }
+    }
+
+    swap_bytes_scalar::<N>(v);
+}
+
+/// Swap the bytes of each `N`-byte element of `v` individually.
+fn swap_bytes_scalar<const N: usize>(v: &mut [u8]) {
+    match N {
+        2 => v.chunks_exact_mut(2).for_each(|chunk| {
+            let bytes = u16::from_ne_bytes(unsafe { chunk.try_into().unwrap_unchecked() });
+            chunk.copy_from_slice(bytes.swap_bytes().to_ne_bytes().as_slice());
+        }),
+        4 => v.chunks_exact_mut(4).for_each(|chunk| {
+            let bytes = u32::from_ne_bytes(unsafe { chunk.try_into().unwrap_unchecked() });
+            chunk.copy_from_slice(bytes.swap_bytes().to_ne_bytes().as_slice());
+        }),
+        8 => v.chunks_exact_mut(8).for_each(|chunk| {
+            let bytes = u64::from_ne_bytes(unsafe { chunk.try_into().unwrap_unchecked() });
+            chunk.copy_from_slice(bytes.swap_bytes().to_ne_bytes().as_slice());
+        }),
+        _ => v.chunks_exact_mut(N).for_each(<[u8]>::reverse),
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86_64 {
+    use std::arch::x86_64::{
+        __m128i, _mm256_broadcastsi128_si256, _mm256_loadu_si256, _mm256_shuffle_epi8,
+        _mm256_storeu_si256, _mm_loadu_si128, _mm_shuffle_epi8, _mm_storeu_si128,
+    };
+
+    use super::swap_bytes_scalar;
+
+    /// Return the shuffle mask reversing each `N`-byte element of a 16-byte lane.
+    const fn shuffle_mask<const N: usize>() -> [u8; 16] {
+        let mut mask = [0; 16];
+        let mut i = 0;
+        while i < 16 {
+            #[allow(clippy::cast_possible_truncation)]
+            {
+                mask[i] = ((i / N) * N + (N - 1 - i % N)) as u8;
+            }
+            i += 1;
+        }
+        mask
+    }
+
+    /// Load the shuffle mask reversing each `N`-byte element of a 16-byte lane.
+    #[target_feature(enable = "ssse3")]
+    unsafe fn load_shuffle_mask<const N: usize>() -> __m128i {
+        let mask = shuffle_mask::<N>();
+        _mm_loadu_si128(mask.as_ptr().cast())
+    }
+
+    /// Swap the bytes of each `N`-byte element of `v` with SSSE3 shuffles.
+    ///
+    /// # Safety
+    /// The CPU must support SSSE3.
+    #[target_feature(enable = "ssse3")]
+    pub(super) unsafe fn swap_bytes_ssse3<const N: usize>(v: &mut [u8]) {
+        let mask = load_shuffle_mask::<N>();
+        let mut chunks = v.chunks_exact_mut(16);
+        for chunk in &mut chunks {
+            let x = _mm_loadu_si128(chunk.as_ptr().cast());
+            _mm_storeu_si128(chunk.as_mut_ptr().cast(), _mm_shuffle_epi8(x, mask));
+        }
+        swap_bytes_scalar::<N>(chunks.into_remainder());
+    }
+
+    /// Swap the bytes of each `N`-byte element of `v` with AVX2 shuffles.
+    ///
+    /// # Safety
+    /// The CPU must support AVX2.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn swap_bytes_avx2<const N: usize>(v: &mut [u8]) {
+        // AVX2 shuffles within each 16-byte lane, so the same mask is used for both lanes
+        let mask = _mm256_broadcastsi128_si256(load_shuffle_mask::<N>());
+        let mut chunks = v.chunks_exact_mut(32);
+        for chunk in &mut chunks {
+            let x = _mm256_loadu_si256(chunk.as_ptr().cast());
+            _mm256_storeu_si256(chunk.as_mut_ptr().cast(), _mm256_shuffle_epi8(x, mask));
+        }
+        swap_bytes_ssse3::<N>(chunks.into_remainder());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swap_bytes_reference<const N: usize>(v: &[u8]) -> Vec<u8> {
+        v.chunks_exact(N)
+            .flat_map(|chunk| chunk.iter().rev().copied())
+            .collect()
+    }
+
+    fn swap_bytes_test<const N: usize>() {
+        // Lengths exercising the vector loops and the scalar remainder
+        for num_elements in [0, 1, 7, 8, 15, 16, 17, 33, 100] {
+            #[allow(clippy::cast_possible_truncation)]
+            let bytes: Vec<u8> = (0..num_elements * N).map(|i| i as u8).collect();
+            let expected = swap_bytes_reference::<N>(&bytes);
+
+            let mut swapped = bytes.clone();
+            swap_bytes::<N>(&mut swapped);
+            assert_eq!(swapped, expected);
+
+            let mut swapped = bytes.clone();
+            swap_bytes_scalar::<N>(&mut swapped);
+            assert_eq!(swapped, expected);
+
+            #[cfg(target_arch = "x86_64")]
+            {
+                if std::arch::is_x86_feature_detected!("ssse3") {
+                    let mut swapped = bytes.clone();
+                    unsafe { x86_64::swap_bytes_ssse3::<N>(&mut swapped) };
+                    assert_eq!(swapped, expected);
+                }
+                if std::arch::is_x86_feature_detected!("avx2") {
+                    let mut swapped = bytes.clone();
+                    unsafe { x86_64::swap_bytes_avx2::<N>(&mut swapped) };
+                    assert_eq!(swapped, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn byte_swap() {
+        swap_bytes_test::<2>();
+        swap_bytes_test::<4>();
+        swap_bytes_test::<8>();
+    }
+}
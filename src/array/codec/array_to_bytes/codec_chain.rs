@@ -6,13 +6,14 @@ use crate::{
     array::{
         codec::{
             ArrayCodecTraits, ArrayPartialDecoderCache, ArrayPartialDecoderTraits,
-            ArrayToArrayCodecTraits, ArrayToBytesCodecTraits, BytesPartialDecoderCache,
-            BytesPartialDecoderTraits, BytesToBytesCodecTraits, Codec, CodecError, CodecOptions,
+            ArrayPartialEncoderTraits, ArrayToArrayCodecTraits, ArrayToBytesCodecTraits,
+            ArrayToBytesPartialEncoderDefault, BytesPartialDecoderCache, BytesPartialDecoderTraits,
+            BytesPartialEncoderTraits, BytesToBytesCodecTraits, Codec, CodecError, CodecOptions,
             CodecTraits,
         },
         concurrency::RecommendedConcurrency,
         ArrayBytes, ArrayMetadataOptions, BytesRepresentation, ChunkRepresentation, ChunkShape,
-        RawBytes,
+        RawBytes, SparseArrayBytes,
     },
     metadata::v3::MetadataV3,
     plugin::PluginCreateError,
@@ -222,10 +223,24 @@ impl CodecTraits for CodecChain {
     fn partial_decoder_decodes_all(&self) -> bool {
         false
     }
+
+    fn partial_encoder_encodes_all(&self) -> bool {
+        !self.array_to_array.is_empty()
+            || !self.bytes_to_bytes.is_empty()
+            || self.array_to_bytes.partial_encoder_encodes_all()
+    }
 }
 
 #[cfg_attr(feature = "async", async_trait::async_trait)]
 impl ArrayToBytesCodecTraits for CodecChain {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            skip_all,
+            fields(decoded_bytes = bytes.size(), encoded_bytes = tracing::field::Empty)
+        )
+    )]
     fn encode<'a>(
         &self,
         mut bytes: ArrayBytes<'a>,
@@ -259,9 +274,19 @@ impl ArrayToBytesCodecTraits for CodecChain {
             decoded_representation = codec.compute_encoded_size(&decoded_representation);
         }
 
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("encoded_bytes", bytes.len());
         Ok(bytes)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            skip_all,
+            fields(encoded_bytes = bytes.len(), decoded_bytes = tracing::field::Empty)
+        )
+    )]
     fn decode<'a>(
         &self,
         mut bytes: RawBytes<'a>,
@@ -298,9 +323,73 @@ impl ArrayToBytesCodecTraits for CodecChain {
             decoded_representation.num_elements(),
             decoded_representation.data_type().size(),
         )?;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("decoded_bytes", bytes.size());
         Ok(bytes)
     }
 
+    fn encode_sparse(
+        &self,
+        sparse: &SparseArrayBytes,
+        decoded_representation: &ChunkRepresentation,
+        options: &CodecOptions,
+    ) -> Result<RawBytes<'static>, CodecError> {
+        sparse.validate(
+            decoded_representation.num_elements(),
+            decoded_representation.fill_value().size(),
+        )?;
+
+        if !self.array_to_array.is_empty() {
+            // array->array codecs operate on dense bytes
+            let bytes = sparse.to_dense(decoded_representation.fill_value())?;
+            return self.encode(bytes, decoded_representation, options);
+        }
+
+        // array->bytes
+        let mut bytes =
+            self.array_to_bytes
+                .encode_sparse(sparse, decoded_representation, options)?;
+
+        // bytes->bytes
+        for codec in &self.bytes_to_bytes {
+            bytes = codec.encode(bytes, options)?;
+        }
+        Ok(bytes)
+    }
+
+    fn decode_sparse(
+        &self,
+        mut bytes: RawBytes<'_>,
+        decoded_representation: &ChunkRepresentation,
+        options: &CodecOptions,
+    ) -> Result<SparseArrayBytes, CodecError> {
+        if !self.array_to_array.is_empty() {
+            // array->array codecs operate on dense bytes
+            let bytes = self.decode(bytes, decoded_representation, options)?;
+            return SparseArrayBytes::from_array_bytes(&bytes, decoded_representation.fill_value());
+        }
+
+        let bytes_representations = self.get_bytes_representations(decoded_representation)?;
+
+        // bytes->bytes
+        for (codec, bytes_representation) in std::iter::zip(
+            self.bytes_to_bytes.iter().rev(),
+            bytes_representations.iter().rev().skip(1),
+        ) {
+            bytes = codec.decode(bytes, bytes_representation, options)?;
+        }
+
+        // bytes->array
+        let sparse = self
+            .array_to_bytes
+            .decode_sparse(bytes, decoded_representation, options)?;
+        sparse.validate(
+            decoded_representation.num_elements(),
+            decoded_representation.fill_value().size(),
+        )?;
+        Ok(sparse)
+    }
+
     fn partial_decoder<'a>(
         &'a self,
         mut input_handle: Arc<dyn BytesPartialDecoderTraits + 'a>,
@@ -361,6 +450,31 @@ impl ArrayToBytesCodecTraits for CodecChain {
         Ok(input_handle)
     }
 
+    fn partial_encoder<'a>(
+        &'a self,
+        input_handle: Arc<dyn BytesPartialDecoderTraits + 'a>,
+        output_handle: Arc<dyn BytesPartialEncoderTraits + 'a>,
+        decoded_representation: &ChunkRepresentation,
+        options: &CodecOptions,
+    ) -> Result<Arc<dyn ArrayPartialEncoderTraits + 'a>, CodecError> {
+        if self.array_to_array.is_empty() && self.bytes_to_bytes.is_empty() {
+            // The array to bytes codec can write directly to the output
+            self.array_to_bytes.partial_encoder(
+                input_handle,
+                output_handle,
+                decoded_representation,
+                options,
+            )
+        } else {
+            Ok(Arc::new(ArrayToBytesPartialEncoderDefault::new(
+                input_handle,
+                output_handle,
+                decoded_representation.clone(),
+                self,
+            )))
+        }
+    }
+
     #[cfg(feature = "async")]
     async fn async_partial_decoder<'a>(
         &'a self,
@@ -0,0 +1,330 @@
+//! The `rle` array to bytes codec.
+//!
+//! Run-length encodes chunks, which suits integer label volumes with long constant runs.
+//! Chunks are encoded as the number of runs (a little endian `u64`), the run index, and then the little endian bytes of the value of each run.
+//! The run index holds the exclusive linearised end of each run as a little endian `u64`, so the partial decoder only reads the values of the runs that intersect the decoded regions.
+//! Only fixed size data types are supported.
+//!
+//! Label data run-length encoded and then compressed with `zstd` is typically both smaller and faster to encode and decode than with `gzip` alone.
+//!
+//! <div class="warning">
+//! This codec is experimental and is incompatible with other Zarr V3 implementations.
+//! </div>
+//!
+//! See [`RleCodecConfigurationV1`] for example `JSON` metadata.
+
+mod rle_codec;
+mod rle_partial_decoder;
+
+use std::mem::size_of;
+
+pub use crate::metadata::v3::codec::rle::{RleCodecConfiguration, RleCodecConfigurationV1};
+pub use rle::IDENTIFIER;
+
+pub use rle_codec::RleCodec;
+
+use crate::{
+    array::{
+        codec::{Codec, CodecError, CodecPlugin},
+        DataType, Endianness, NATIVE_ENDIAN,
+    },
+    config::global_config,
+    metadata::v3::{codec::rle, MetadataV3},
+    plugin::{PluginCreateError, PluginMetadataInvalidError},
+};
+
+use super::bytes::reverse_endianness;
+
+// Register the codec.
+inventory::submit! {
+    CodecPlugin::new(IDENTIFIER, is_name_rle, create_codec_rle)
+}
+
+fn is_name_rle(name: &str) -> bool {
+    name.eq(IDENTIFIER)
+        || name
+            == global_config()
+                .experimental_codec_names()
+                .get(IDENTIFIER)
+                .expect("experimental codec identifier in global map")
+}
+
+pub(crate) fn create_codec_rle(metadata: &MetadataV3) -> Result<Codec, PluginCreateError> {
+    let configuration: RleCodecConfiguration = metadata
+        .to_configuration()
+        .map_err(|_| PluginMetadataInvalidError::new(IDENTIFIER, "codec", metadata.clone()))?;
+    let codec = Box::new(RleCodec::new_with_configuration(&configuration));
+    Ok(Codec::ArrayToBytes(codec))
+}
+
+/// The size of the header holding the number of runs.
+const HEADER_SIZE: usize = size_of::<u64>();
+
+/// The size of the end of a run in the run index.
+const RUN_END_SIZE: usize = size_of::<u64>();
+
+/// Returns the upper bound of the encoded size of `num_elements` elements of `element_size` bytes.
+fn encoded_size_bound(num_elements: u64, element_size: usize) -> u64 {
+    HEADER_SIZE as u64 + num_elements * (RUN_END_SIZE + element_size) as u64
+}
+
+/// Returns the byte offset of the run values of an encoded chunk with `num_runs` runs.
+fn values_offset(num_runs: usize) -> usize {
+    HEADER_SIZE + num_runs * RUN_END_SIZE
+}
+
+/// Run-length encode the native endian `bytes` of elements of `element_size` bytes.
+fn encode_rle(bytes: &[u8], element_size: usize, data_type: &DataType) -> Vec<u8> {
+    let mut run_ends: Vec<u64> = Vec::new();
+    let mut values: Vec<u8> = Vec::new();
+    for (index, element) in bytes.chunks_exact(element_size).enumerate() {
+        if values.is_empty() || values[values.len() - element_size..] != *element {
+            if !values.is_empty() {
+                run_ends.push(index as u64);
+            }
+            values.extend_from_slice(element);
+        }
+    }
+    if !values.is_empty() {
+        run_ends.push((bytes.len() / element_size) as u64);
+    }
+    if NATIVE_ENDIAN == Endianness::Big {
+        reverse_endianness(&mut values, data_type);
+    }
+
+    let mut encoded = Vec::with_capacity(values_offset(run_ends.len()) + values.len());
+    encoded.extend_from_slice(&(run_ends.len() as u64).to_le_bytes());
+    for run_end in run_ends {
+        encoded.extend_from_slice(&run_end.to_le_bytes());
+    }
+    encoded.extend_from_slice(&values);
+    encoded
+}
+
+/// Parse the number of runs from the header of an encoded chunk.
+fn decode_num_runs(header: &[u8], num_elements: u64) -> Result<usize, CodecError> {
+    let num_runs = header
+        .get(..HEADER_SIZE)
+        .map(|num_runs| u64::from_le_bytes(num_runs.try_into().unwrap()))
+        .ok_or(CodecError::UnexpectedChunkDecodedSize(
+            header.len(),
+            HEADER_SIZE as u64,
+        ))?;
+    if num_runs > num_elements {
+        return Err(CodecError::Other(format!(
+            "rle encoded chunk has {num_runs} runs, but the chunk only has {num_elements} elements"
+        )));
+    }
+    Ok(usize::try_from(num_runs).unwrap())
+}
+
+/// Parse and validate the run index of an encoded chunk.
+fn decode_run_ends(run_index: &[u8], num_elements: u64) -> Result<Vec<u64>, CodecError> {
+    let run_ends: Vec<u64> = run_index
+        .chunks_exact(RUN_END_SIZE)
+        .map(|run_end| u64::from_le_bytes(run_end.try_into().unwrap()))
+        .collect();
+    let valid = run_ends
+        .first()
+        .map_or(num_elements == 0, |first| *first > 0)
+        && run_ends.windows(2).all(|window| window[0] < window[1])
+        && run_ends.last().map_or(true, |last| *last == num_elements);
+    if valid {
+        Ok(run_ends)
+    } else {
+        Err(CodecError::Other(format!(
+            "rle encoded run ends must be strictly increasing and end at {num_elements}"
+        )))
+    }
+}
+
+/// Decode a run-length encoded chunk of `num_elements` elements of `element_size` bytes to native endian bytes.
+fn decode_rle(
+    encoded: &[u8],
+    num_elements: u64,
+    element_size: usize,
+    data_type: &DataType,
+) -> Result<Vec<u8>, CodecError> {
+    let num_runs = decode_num_runs(encoded, num_elements)?;
+    let values_offset = values_offset(num_runs);
+    let encoded_size = values_offset + num_runs * element_size;
+    if encoded.len() != encoded_size {
+        return Err(CodecError::UnexpectedChunkDecodedSize(
+            encoded.len(),
+            encoded_size as u64,
+        ));
+    }
+    let run_ends = decode_run_ends(&encoded[HEADER_SIZE..values_offset], num_elements)?;
+    let mut values = encoded[values_offset..].to_vec();
+    if NATIVE_ENDIAN == Endianness::Big {
+        reverse_endianness(&mut values, data_type);
+    }
+
+    let mut decoded = Vec::with_capacity(usize::try_from(num_elements).unwrap() * element_size);
+    let mut run_start = 0;
+    for (run_end, value) in std::iter::zip(run_ends, values.chunks_exact(element_size)) {
+        let run_length = usize::try_from(run_end - run_start).unwrap();
+        decoded.extend(std::iter::repeat(value).take(run_length).flatten());
+        run_start = run_end;
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{num::NonZeroU64, sync::Arc};
+
+    use crate::{
+        array::{
+            codec::{ArrayToBytesCodecTraits, CodecOptions},
+            transmute_from_bytes_vec, transmute_to_bytes_vec, ArrayBytes, BytesRepresentation,
+            ChunkRepresentation, FillValue,
+        },
+        array_subset::ArraySubset,
+    };
+
+    use super::*;
+
+    fn chunk_representation() -> ChunkRepresentation {
+        ChunkRepresentation::new(
+            vec![NonZeroU64::new(4).unwrap(), NonZeroU64::new(8).unwrap()],
+            DataType::UInt32,
+            FillValue::from(0u32),
+        )
+        .unwrap()
+    }
+
+    fn elements() -> Vec<u32> {
+        (0..32u32).map(|i| [0, 7, 7, 3][(i / 9) as usize]).collect()
+    }
+
+    #[test]
+    fn codec_rle_round_trip() {
+        let chunk_representation = chunk_representation();
+        let bytes: ArrayBytes = transmute_to_bytes_vec(elements()).into();
+        let codec = RleCodec::new();
+
+        let encoded = codec
+            .encode(
+                bytes.clone(),
+                &chunk_representation,
+                &CodecOptions::default(),
+            )
+            .unwrap();
+        // 8 byte header, 3 runs (0, 7, 3) with an 8 byte end and a 4 byte value
+        assert_eq!(encoded.len(), 8 + 3 * (8 + 4));
+        let BytesRepresentation::BoundedSize(bound) =
+            codec.compute_encoded_size(&chunk_representation).unwrap()
+        else {
+            panic!()
+        };
+        assert!(encoded.len() as u64 <= bound);
+
+        let decoded = codec
+            .decode(encoded, &chunk_representation, &CodecOptions::default())
+            .unwrap();
+        assert_eq!(bytes, decoded);
+    }
+
+    #[test]
+    fn codec_rle_invalid() {
+        let chunk_representation = chunk_representation();
+        let codec = RleCodec::new();
+        let encode = |run_ends: &[u64]| {
+            let mut encoded = (run_ends.len() as u64).to_le_bytes().to_vec();
+            for run_end in run_ends {
+                encoded.extend_from_slice(&run_end.to_le_bytes());
+            }
+            encoded.resize(encoded.len() + run_ends.len() * 4, 0);
+            encoded
+        };
+        for run_ends in [&[16, 8, 32][..], &[8, 16], &[0, 32], &[]] {
+            assert!(codec
+                .decode(
+                    encode(run_ends).into(),
+                    &chunk_representation,
+                    &CodecOptions::default()
+                )
+                .is_err());
+        }
+        assert!(codec
+            .decode(
+                encode(&[8, 32]).into(),
+                &chunk_representation,
+                &CodecOptions::default()
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn codec_rle_partial_decode() {
+        let chunk_representation = chunk_representation();
+        let elements = elements();
+        let bytes: ArrayBytes = transmute_to_bytes_vec(elements.clone()).into();
+        let codec = RleCodec::new();
+        let encoded = codec
+            .encode(bytes, &chunk_representation, &CodecOptions::default())
+            .unwrap();
+
+        let decoded_regions = [
+            ArraySubset::new_with_ranges(&[0..2, 6..8]),
+            ArraySubset::new_with_ranges(&[3..4, 0..8]),
+            ArraySubset::new_with_ranges(&[1..3, 0..0]),
+        ];
+        let input_handle = Arc::new(std::io::Cursor::new(encoded));
+        let partial_decoder = codec
+            .partial_decoder(
+                input_handle,
+                &chunk_representation,
+                &CodecOptions::default(),
+            )
+            .unwrap();
+        let decoded_partial_chunk = partial_decoder
+            .partial_decode_opt(&decoded_regions, &CodecOptions::default())
+            .unwrap()
+            .into_iter()
+            .map(|bytes| transmute_from_bytes_vec::<u32>(bytes.into_fixed().unwrap().to_vec()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            decoded_partial_chunk,
+            vec![vec![0, 0, 7, 7], elements[24..32].to_vec(), vec![]]
+        );
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn codec_rle_async_partial_decode() {
+        let chunk_representation = chunk_representation();
+        let elements = elements();
+        let bytes: ArrayBytes = transmute_to_bytes_vec(elements.clone()).into();
+        let codec = RleCodec::new();
+        let encoded = codec
+            .encode(bytes, &chunk_representation, &CodecOptions::default())
+            .unwrap();
+
+        let decoded_regions = [ArraySubset::new_with_ranges(&[1..3, 2..5])];
+        let input_handle = Arc::new(std::io::Cursor::new(encoded));
+        let partial_decoder = codec
+            .async_partial_decoder(
+                input_handle,
+                &chunk_representation,
+                &CodecOptions::default(),
+            )
+            .await
+            .unwrap();
+        let decoded_partial_chunk = partial_decoder
+            .partial_decode_opt(&decoded_regions, &CodecOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            transmute_from_bytes_vec::<u32>(
+                decoded_partial_chunk[0]
+                    .clone()
+                    .into_fixed()
+                    .unwrap()
+                    .to_vec()
+            ),
+            vec![7, 7, 7, 7, 7, 7]
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use crate::{
+    array::{
+        codec::{
+            ArrayCodecTraits, ArrayPartialDecoderTraits, ArrayToBytesCodecTraits,
+            BytesPartialDecoderTraits, CodecError, CodecOptions, CodecTraits,
+            RecommendedConcurrency,
+        },
+        ArrayBytes, ArrayMetadataOptions, BytesRepresentation, ChunkRepresentation, RawBytes,
+    },
+    config::global_config,
+    metadata::v3::MetadataV3,
+};
+
+#[cfg(feature = "async")]
+use crate::array::codec::{AsyncArrayPartialDecoderTraits, AsyncBytesPartialDecoderTraits};
+
+use super::{
+    decode_rle, encode_rle, encoded_size_bound, RleCodecConfiguration, RleCodecConfigurationV1,
+};
+
+/// The `rle` codec implementation.
+#[derive(Debug, Clone, Default)]
+pub struct RleCodec {}
+
+impl RleCodec {
+    /// Create a new `rle` codec.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Create a new `rle` codec from configuration.
+    #[must_use]
+    pub fn new_with_configuration(_configuration: &RleCodecConfiguration) -> Self {
+        Self {}
+    }
+}
+
+/// Returns the element size of a decoded representation, or an error if the data type is not fixed size.
+pub(super) fn element_size(
+    decoded_representation: &ChunkRepresentation,
+) -> Result<usize, CodecError> {
+    decoded_representation
+        .data_type()
+        .fixed_size()
+        .ok_or_else(|| {
+            CodecError::UnsupportedDataType(
+                decoded_representation.data_type().clone(),
+                super::IDENTIFIER.to_string(),
+            )
+        })
+}
+
+impl CodecTraits for RleCodec {
+    fn create_metadata_opt(&self, _options: &ArrayMetadataOptions) -> Option<MetadataV3> {
+        let configuration = RleCodecConfigurationV1 {};
+        Some(
+            MetadataV3::new_with_serializable_configuration(
+                global_config()
+                    .experimental_codec_names()
+                    .get(super::IDENTIFIER)
+                    .expect("experimental codec identifier in global map"),
+                &configuration,
+            )
+            .unwrap(),
+        )
+    }
+
+    fn partial_decoder_should_cache_input(&self) -> bool {
+        false
+    }
+
+    fn partial_decoder_decodes_all(&self) -> bool {
+        false
+    }
+}
+
+impl ArrayCodecTraits for RleCodec {
+    fn recommended_concurrency(
+        &self,
+        _decoded_representation: &ChunkRepresentation,
+    ) -> Result<RecommendedConcurrency, CodecError> {
+        Ok(RecommendedConcurrency::new_maximum(1))
+    }
+}
+
+#[cfg_attr(feature = "async", async_trait::async_trait)]
+impl ArrayToBytesCodecTraits for RleCodec {
+    fn encode<'a>(
+        &self,
+        bytes: ArrayBytes<'a>,
+        decoded_representation: &ChunkRepresentation,
+        _options: &CodecOptions,
+    ) -> Result<RawBytes<'a>, CodecError> {
+        let element_size = element_size(decoded_representation)?;
+        bytes.validate(
+            decoded_representation.num_elements(),
+            decoded_representation.data_type().size(),
+        )?;
+        let bytes = bytes.into_fixed()?;
+        Ok(encode_rle(&bytes, element_size, decoded_representation.data_type()).into())
+    }
+
+    fn decode<'a>(
+        &self,
+        bytes: RawBytes<'a>,
+        decoded_representation: &ChunkRepresentation,
+        _options: &CodecOptions,
+    ) -> Result<ArrayBytes<'a>, CodecError> {
+        let decoded = decode_rle(
+            &bytes,
+            decoded_representation.num_elements(),
+            element_size(decoded_representation)?,
+            decoded_representation.data_type(),
+        )?;
+        Ok(ArrayBytes::from(decoded))
+    }
+
+    fn partial_decoder<'a>(
+        &self,
+        input_handle: Arc<dyn BytesPartialDecoderTraits + 'a>,
+        decoded_representation: &ChunkRepresentation,
+        _options: &CodecOptions,
+    ) -> Result<Arc<dyn ArrayPartialDecoderTraits + 'a>, CodecError> {
+        element_size(decoded_representation)?;
+        Ok(Arc::new(
+            super::rle_partial_decoder::RlePartialDecoder::new(
+                input_handle,
+                decoded_representation.clone(),
+            ),
+        ))
+    }
+
+    #[cfg(feature = "async")]
+    async fn async_partial_decoder<'a>(
+        &'a self,
+        input_handle: Arc<dyn AsyncBytesPartialDecoderTraits + 'a>,
+        decoded_representation: &ChunkRepresentation,
+        _options: &CodecOptions,
+    ) -> Result<Arc<dyn AsyncArrayPartialDecoderTraits + 'a>, CodecError> {
+        element_size(decoded_representation)?;
+        Ok(Arc::new(
+            super::rle_partial_decoder::AsyncRlePartialDecoder::new(
+                input_handle,
+                decoded_representation.clone(),
+            ),
+        ))
+    }
+
+    fn compute_encoded_size(
+        &self,
+        decoded_representation: &ChunkRepresentation,
+    ) -> Result<BytesRepresentation, CodecError> {
+        let element_size = element_size(decoded_representation)?;
+        Ok(BytesRepresentation::BoundedSize(encoded_size_bound(
+            decoded_representation.num_elements(),
+            element_size,
+        )))
+    }
+}
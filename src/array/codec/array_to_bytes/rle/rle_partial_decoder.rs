@@ -0,0 +1,309 @@
+use std::{ops::Range, sync::Arc};
+
+use crate::{
+    array::{
+        codec::{
+            ArrayPartialDecoderTraits, ArraySubset, BytesPartialDecoderTraits, CodecError,
+            CodecOptions,
+        },
+        ArrayBytes, ArraySize, ChunkRepresentation, DataType, Endianness, RawBytes, NATIVE_ENDIAN,
+    },
+    array_subset::IncompatibleArraySubsetAndShapeError,
+    byte_range::ByteRange,
+};
+
+#[cfg(feature = "async")]
+use crate::array::codec::{AsyncArrayPartialDecoderTraits, AsyncBytesPartialDecoderTraits};
+
+use super::{
+    decode_num_runs, decode_run_ends, reverse_endianness, rle_codec::element_size, values_offset,
+    HEADER_SIZE, RUN_END_SIZE,
+};
+
+/// Partial decoder for the `rle` codec.
+pub struct RlePartialDecoder<'a> {
+    input_handle: Arc<dyn BytesPartialDecoderTraits + 'a>,
+    decoded_representation: ChunkRepresentation,
+}
+
+impl<'a> RlePartialDecoder<'a> {
+    /// Create a new partial decoder for the `rle` codec.
+    pub fn new(
+        input_handle: Arc<dyn BytesPartialDecoderTraits + 'a>,
+        decoded_representation: ChunkRepresentation,
+    ) -> Self {
+        Self {
+            input_handle,
+            decoded_representation,
+        }
+    }
+}
+
+fn header_byte_range() -> ByteRange {
+    ByteRange::FromStart(0, Some(HEADER_SIZE as u64))
+}
+
+fn run_index_byte_range(num_runs: usize) -> ByteRange {
+    ByteRange::FromStart(HEADER_SIZE as u64, Some((num_runs * RUN_END_SIZE) as u64))
+}
+
+fn truncated_error() -> CodecError {
+    CodecError::Other("rle encoded chunk is truncated".to_string())
+}
+
+fn fill_value_regions<'a>(
+    decoded_regions: &[ArraySubset],
+    decoded_representation: &ChunkRepresentation,
+) -> Vec<ArrayBytes<'a>> {
+    decoded_regions
+        .iter()
+        .map(|decoded_region| {
+            let array_size = ArraySize::new(
+                decoded_representation.data_type().size(),
+                decoded_region.num_elements(),
+            );
+            ArrayBytes::new_fill_value(array_size, decoded_representation.fill_value())
+        })
+        .collect()
+}
+
+/// Returns the contiguous linearised element ranges of `decoded_region` within `chunk_shape`.
+fn contiguous_element_ranges(
+    decoded_region: &ArraySubset,
+    chunk_shape: &[u64],
+) -> Result<Vec<Range<u64>>, CodecError> {
+    if !decoded_region.inbounds(chunk_shape) {
+        return Err(IncompatibleArraySubsetAndShapeError::new(
+            decoded_region.clone(),
+            chunk_shape.to_vec(),
+        )
+        .into());
+    }
+    if decoded_region.num_elements() == 0 {
+        return Ok(vec![]);
+    }
+    let contiguous_indices = decoded_region.contiguous_linearised_indices(chunk_shape)?;
+    let contiguous_elements = contiguous_indices.contiguous_elements();
+    Ok(contiguous_indices
+        .iter()
+        .map(|(start, _)| start..start + contiguous_elements)
+        .collect())
+}
+
+/// Returns the index of the run containing the element at `index`.
+fn run_containing(run_ends: &[u64], index: u64) -> usize {
+    run_ends.partition_point(|run_end| *run_end <= index)
+}
+
+/// Returns the sorted and merged ranges of the runs intersecting `decoded_regions`.
+fn intersecting_runs(
+    run_ends: &[u64],
+    decoded_regions: &[ArraySubset],
+    chunk_shape: &[u64],
+) -> Result<Vec<Range<usize>>, CodecError> {
+    let mut runs = Vec::new();
+    for decoded_region in decoded_regions {
+        for elements in contiguous_element_ranges(decoded_region, chunk_shape)? {
+            runs.push(
+                run_containing(run_ends, elements.start)
+                    ..run_containing(run_ends, elements.end - 1) + 1,
+            );
+        }
+    }
+    runs.sort_by_key(|runs| runs.start);
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(runs.len());
+    for runs in runs {
+        match merged.last_mut() {
+            Some(last) if runs.start <= last.end => last.end = last.end.max(runs.end),
+            _ => merged.push(runs),
+        }
+    }
+    Ok(merged)
+}
+
+/// Returns the byte ranges of the values of `runs` in an encoded chunk with `num_runs` runs.
+fn value_byte_ranges(
+    num_runs: usize,
+    runs: &[Range<usize>],
+    element_size: usize,
+) -> Vec<ByteRange> {
+    let values_offset = values_offset(num_runs);
+    runs.iter()
+        .map(|runs| {
+            ByteRange::FromStart(
+                (values_offset + runs.start * element_size) as u64,
+                Some((runs.len() * element_size) as u64),
+            )
+        })
+        .collect()
+}
+
+/// Decode `decoded_regions` from the run ends and the little endian `values` of the intersecting `runs`.
+fn decode_regions<'a>(
+    run_ends: &[u64],
+    runs: &[Range<usize>],
+    mut values: Vec<RawBytes>,
+    decoded_regions: &[ArraySubset],
+    decoded_representation: &ChunkRepresentation,
+) -> Result<Vec<ArrayBytes<'a>>, CodecError> {
+    let element_size = element_size(decoded_representation)?;
+    if values.len() != runs.len() {
+        return Err(truncated_error());
+    }
+    for (values, runs) in std::iter::zip(&mut values, runs) {
+        if values.len() != runs.len() * element_size {
+            return Err(truncated_error());
+        }
+        if NATIVE_ENDIAN == Endianness::Big {
+            reverse_endianness(values.to_mut(), decoded_representation.data_type());
+        }
+    }
+    let value = |run: usize| {
+        let i = runs.partition_point(|runs| runs.end <= run);
+        let offset = (run - runs[i].start) * element_size;
+        &values[i][offset..offset + element_size]
+    };
+
+    let chunk_shape = decoded_representation.shape_u64();
+    let mut decoded = Vec::with_capacity(decoded_regions.len());
+    for decoded_region in decoded_regions {
+        let mut bytes = Vec::with_capacity(decoded_region.num_elements_usize() * element_size);
+        for elements in contiguous_element_ranges(decoded_region, &chunk_shape)? {
+            let mut index = elements.start;
+            let mut run = run_containing(run_ends, index);
+            while index < elements.end {
+                let run_end = run_ends[run].min(elements.end);
+                let run_length = usize::try_from(run_end - index).unwrap();
+                bytes.extend(std::iter::repeat(value(run)).take(run_length).flatten());
+                index = run_end;
+                run += 1;
+            }
+        }
+        decoded.push(ArrayBytes::from(bytes));
+    }
+    Ok(decoded)
+}
+
+impl ArrayPartialDecoderTraits for RlePartialDecoder<'_> {
+    fn data_type(&self) -> &DataType {
+        self.decoded_representation.data_type()
+    }
+
+    fn partial_decode_opt(
+        &self,
+        decoded_regions: &[ArraySubset],
+        options: &CodecOptions,
+    ) -> Result<Vec<ArrayBytes<'_>>, CodecError> {
+        let num_elements = self.decoded_representation.num_elements();
+        let Some(header) = self
+            .input_handle
+            .partial_decode_concat(&[header_byte_range()], options)?
+        else {
+            return Ok(fill_value_regions(
+                decoded_regions,
+                &self.decoded_representation,
+            ));
+        };
+        let num_runs = decode_num_runs(&header, num_elements)?;
+
+        // Read the run index, then only the values of the runs intersecting the decoded regions
+        let run_index = self
+            .input_handle
+            .partial_decode_concat(&[run_index_byte_range(num_runs)], options)?
+            .ok_or_else(truncated_error)?;
+        let run_ends = decode_run_ends(&run_index, num_elements)?;
+        let runs = intersecting_runs(
+            &run_ends,
+            decoded_regions,
+            &self.decoded_representation.shape_u64(),
+        )?;
+        let byte_ranges =
+            value_byte_ranges(num_runs, &runs, element_size(&self.decoded_representation)?);
+        let values = self
+            .input_handle
+            .partial_decode(&byte_ranges, options)?
+            .ok_or_else(truncated_error)?;
+        decode_regions(
+            &run_ends,
+            &runs,
+            values,
+            decoded_regions,
+            &self.decoded_representation,
+        )
+    }
+}
+
+#[cfg(feature = "async")]
+/// Asynchronous partial decoder for the `rle` codec.
+pub struct AsyncRlePartialDecoder<'a> {
+    input_handle: Arc<dyn AsyncBytesPartialDecoderTraits + 'a>,
+    decoded_representation: ChunkRepresentation,
+}
+
+#[cfg(feature = "async")]
+impl<'a> AsyncRlePartialDecoder<'a> {
+    /// Create a new partial decoder for the `rle` codec.
+    pub fn new(
+        input_handle: Arc<dyn AsyncBytesPartialDecoderTraits + 'a>,
+        decoded_representation: ChunkRepresentation,
+    ) -> Self {
+        Self {
+            input_handle,
+            decoded_representation,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl AsyncArrayPartialDecoderTraits for AsyncRlePartialDecoder<'_> {
+    fn data_type(&self) -> &DataType {
+        self.decoded_representation.data_type()
+    }
+
+    async fn partial_decode_opt(
+        &self,
+        decoded_regions: &[ArraySubset],
+        options: &CodecOptions,
+    ) -> Result<Vec<ArrayBytes<'_>>, CodecError> {
+        let num_elements = self.decoded_representation.num_elements();
+        let Some(header) = self
+            .input_handle
+            .partial_decode_concat(&[header_byte_range()], options)
+            .await?
+        else {
+            return Ok(fill_value_regions(
+                decoded_regions,
+                &self.decoded_representation,
+            ));
+        };
+        let num_runs = decode_num_runs(&header, num_elements)?;
+
+        // Read the run index, then only the values of the runs intersecting the decoded regions
+        let run_index = self
+            .input_handle
+            .partial_decode_concat(&[run_index_byte_range(num_runs)], options)
+            .await?
+            .ok_or_else(truncated_error)?;
+        let run_ends = decode_run_ends(&run_index, num_elements)?;
+        let runs = intersecting_runs(
+            &run_ends,
+            decoded_regions,
+            &self.decoded_representation.shape_u64(),
+        )?;
+        let byte_ranges =
+            value_byte_ranges(num_runs, &runs, element_size(&self.decoded_representation)?);
+        let values = self
+            .input_handle
+            .partial_decode(&byte_ranges, options)
+            .await?
+            .ok_or_else(truncated_error)?;
+        decode_regions(
+            &run_ends,
+            &runs,
+            values,
+            decoded_regions,
+            &self.decoded_representation,
+        )
+    }
+}
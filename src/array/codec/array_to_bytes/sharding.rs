@@ -10,9 +10,12 @@
 //! See [`ShardingCodecConfigurationV1`] for example `JSON` metadata.
 //! The [`ShardingCodecBuilder`] can help with creating a [`ShardingCodec`].
 
+mod shard_index_cache;
+mod shard_writer;
 mod sharding_codec;
 mod sharding_codec_builder;
 mod sharding_partial_decoder;
+mod sharding_partial_encoder;
 
 use std::{borrow::Cow, num::NonZeroU64};
 
@@ -20,6 +23,8 @@ pub use crate::metadata::v3::codec::sharding::{
     ShardingCodecConfiguration, ShardingCodecConfigurationV1, ShardingIndexLocation,
 };
 
+pub use shard_index_cache::ShardIndexCache;
+pub use shard_writer::ShardWriter;
 pub use sharding_codec::ShardingCodec;
 pub use sharding_codec_builder::ShardingCodecBuilder;
 
@@ -110,6 +115,45 @@ fn decode_shard_index(
         .collect())
 }
 
+/// A byte range of a shard covering the encoded bytes of one or more inner chunks.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct CoalescedByteRange {
+    /// The offset of the byte range in the shard.
+    pub offset: u64,
+    /// The size of the byte range.
+    pub size: u64,
+    /// The indices of the inner chunks within the byte range.
+    pub inner_chunks: Vec<usize>,
+}
+
+/// Coalesce the byte ranges of inner chunks that are separated by at most `gap` bytes.
+///
+/// `byte_ranges` is an iterator of inner chunk indices with the offset and size of their encoded bytes in the shard.
+pub(crate) fn coalesce_byte_ranges(
+    byte_ranges: impl IntoIterator<Item = (usize, u64, u64)>,
+    gap: u64,
+) -> Vec<CoalescedByteRange> {
+    let mut byte_ranges: Vec<_> = byte_ranges.into_iter().collect();
+    byte_ranges.sort_unstable_by_key(|(_, offset, _)| *offset);
+
+    let mut coalesced: Vec<CoalescedByteRange> = Vec::new();
+    for (inner_chunk, offset, size) in byte_ranges {
+        if let Some(last) = coalesced.last_mut() {
+            if offset <= (last.offset + last.size).saturating_add(gap) {
+                last.size = last.size.max(offset + size - last.offset);
+                last.inner_chunks.push(inner_chunk);
+                continue;
+            }
+        }
+        coalesced.push(CoalescedByteRange {
+            offset,
+            size,
+            inner_chunks: vec![inner_chunk],
+        });
+    }
+    coalesced
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -412,15 +456,18 @@ mod tests {
             for all_fill_value in [true, false] {
                 for unbounded in [true, false] {
                     for parallel in [true, false] {
-                        let concurrent_target = get_concurrent_target(parallel);
-                        let options =
-                            CodecOptionsBuilder::new().concurrent_target(concurrent_target);
-                        codec_sharding_partial_decode(
-                            &options.build(),
-                            unbounded,
-                            all_fill_value,
-                            index_at_end,
-                        );
+                        for shard_coalesce_gap in [None, Some(0), Some(1024)] {
+                            let concurrent_target = get_concurrent_target(parallel);
+                            let options = CodecOptionsBuilder::new()
+                                .concurrent_target(concurrent_target)
+                                .shard_coalesce_gap(shard_coalesce_gap);
+                            codec_sharding_partial_decode(
+                                &options.build(),
+                                unbounded,
+                                all_fill_value,
+                                index_at_end,
+                            );
+                        }
                     }
                 }
             }
@@ -496,16 +543,19 @@ mod tests {
             for all_fill_value in [true, false] {
                 for unbounded in [true, false] {
                     for parallel in [true, false] {
-                        let concurrent_target = get_concurrent_target(parallel);
-                        let options =
-                            CodecOptionsBuilder::new().concurrent_target(concurrent_target);
-                        codec_sharding_async_partial_decode(
-                            &options.build(),
-                            unbounded,
-                            all_fill_value,
-                            index_at_end,
-                        )
-                        .await;
+                        for shard_coalesce_gap in [None, Some(0), Some(1024)] {
+                            let concurrent_target = get_concurrent_target(parallel);
+                            let options = CodecOptionsBuilder::new()
+                                .concurrent_target(concurrent_target)
+                                .shard_coalesce_gap(shard_coalesce_gap);
+                            codec_sharding_async_partial_decode(
+                                &options.build(),
+                                unbounded,
+                                all_fill_value,
+                                index_at_end,
+                            )
+                            .await;
+                        }
                     }
                 }
             }
@@ -600,4 +650,57 @@ mod tests {
         let answer: Vec<u8> = vec![4, 8];
         assert_eq!(answer, decoded_partial_chunk);
     }
+
+    #[test]
+    fn codec_sharding_coalesce_byte_ranges() {
+        let byte_ranges = [
+            (0, 100, 10),
+            (1, 0, 10),
+            (2, 12, 8),
+            (3, 40, 10),
+            (4, 110, 5),
+        ];
+        assert_eq!(
+            coalesce_byte_ranges(byte_ranges, 0),
+            vec![
+                CoalescedByteRange {
+                    offset: 0,
+                    size: 10,
+                    inner_chunks: vec![1]
+                },
+                CoalescedByteRange {
+                    offset: 12,
+                    size: 8,
+                    inner_chunks: vec![2]
+                },
+                CoalescedByteRange {
+                    offset: 40,
+                    size: 10,
+                    inner_chunks: vec![3]
+                },
+                CoalescedByteRange {
+                    offset: 100,
+                    size: 15,
+                    inner_chunks: vec![0, 4]
+                },
+            ]
+        );
+        assert_eq!(
+            coalesce_byte_ranges(byte_ranges, 20),
+            vec![
+                CoalescedByteRange {
+                    offset: 0,
+                    size: 50,
+                    inner_chunks: vec![1, 2, 3]
+                },
+                CoalescedByteRange {
+                    offset: 100,
+                    size: 15,
+                    inner_chunks: vec![0, 4]
+                },
+            ]
+        );
+        assert_eq!(coalesce_byte_ranges(byte_ranges, u64::MAX).len(), 1);
+        assert!(coalesce_byte_ranges([], 0).is_empty());
+    }
 }
@@ -0,0 +1,93 @@
+use std::sync::Arc;
+
+use moka::{
+    policy::EvictionPolicy,
+    sync::{Cache, CacheBuilder},
+};
+
+use crate::array::ArrayIndices;
+
+type ShardIndices = ArrayIndices;
+
+/// A decoded shard index, or [`None`] if the shard does not exist.
+type CachedShardIndex = Option<Arc<Vec<u64>>>;
+
+/// A cache of decoded shard indexes with a fixed size capacity.
+///
+/// A shard index cache is set on an [`Array`](crate::array::Array) with [`set_shard_index_cache`](crate::array::Array::set_shard_index_cache).
+/// Partial decoders of the array then reuse the decoded index of a shard rather than retrieving and decoding it for every partial read.
+/// Entries are keyed by shard chunk indices, so a cache should not be shared between arrays.
+///
+/// Clones of a shard index cache share the same entries.
+#[derive(Clone)]
+pub struct ShardIndexCache {
+    cache: Cache<ShardIndices, CachedShardIndex>,
+}
+
+impl core::fmt::Debug for ShardIndexCache {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ShardIndexCache")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ShardIndexCache {
+    /// Create a new [`ShardIndexCache`] with a capacity in bytes of `capacity`.
+    #[must_use]
+    pub fn new(capacity: u64) -> Self {
+        let cache = CacheBuilder::new(capacity)
+            .eviction_policy(EvictionPolicy::lru())
+            .weigher(|_k, v: &CachedShardIndex| {
+                let size = v.as_ref().map_or(0, |shard_index| {
+                    core::mem::size_of_val(shard_index.as_slice())
+                });
+                u32::try_from(size.max(1)).unwrap_or(u32::MAX)
+            })
+            .build();
+        Self { cache }
+    }
+
+    /// Return the cached shard index of the shard at `shard_indices`.
+    ///
+    /// The outer [`None`] indicates a cache miss, and the inner [`None`] indicates that the shard does not exist.
+    #[must_use]
+    pub fn get(&self, shard_indices: &[u64]) -> Option<CachedShardIndex> {
+        self.cache.get(&shard_indices.to_vec())
+    }
+
+    /// Insert the shard index of the shard at `shard_indices`, or [`None`] if the shard does not exist.
+    pub fn insert(&self, shard_indices: ShardIndices, shard_index: Option<Arc<Vec<u64>>>) {
+        self.cache.insert(shard_indices, shard_index);
+    }
+
+    /// Remove the shard index of the shard at `shard_indices`.
+    pub fn invalidate(&self, shard_indices: &[u64]) {
+        self.cache.invalidate(&shard_indices.to_vec());
+    }
+
+    /// Remove all cached shard indexes.
+    pub fn clear(&self) {
+        self.cache.invalidate_all();
+    }
+
+    /// Return the size of the cache in bytes.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.cache.run_pending_tasks();
+        usize::try_from(self.cache.weighted_size()).unwrap_or(usize::MAX)
+    }
+
+    /// Return the number of cached shard indexes.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.cache.run_pending_tasks();
+        usize::try_from(self.cache.entry_count()).unwrap_or(usize::MAX)
+    }
+
+    /// Returns true if the cache is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
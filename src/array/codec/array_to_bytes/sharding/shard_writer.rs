@@ -0,0 +1,208 @@
+use parking_lot::Mutex;
+
+use crate::{
+    array::{
+        chunk_shape_to_array_shape,
+        codec::{ArrayToBytesCodecTraits, CodecChain, CodecError, CodecOptions},
+        ravel_indices, transmute_to_bytes_vec, ArrayBytes, ArrayShape, ChunkRepresentation,
+        ChunkShape, RawBytes,
+    },
+    storage::{Bytes, StoreKey, StoreValueWriter, WritableStorageTraits},
+};
+
+use super::{
+    calculate_chunks_per_shard, compute_index_encoded_size, sharding_index_decoded_representation,
+    ShardingIndexLocation,
+};
+
+/// A writer for creating a shard in one pass from its inner chunks.
+///
+/// Inner chunks can be written in any order, and concurrently, with [`write_inner_chunk`](ShardWriter::write_inner_chunk).
+/// The shard is streamed to the store with a [`StoreValueWriter`], and the shard index is written by [`finalize`](ShardWriter::finalize).
+/// The shard is not stored until it is finalized, and dropping a shard writer abandons the shard.
+///
+/// With [`ShardingIndexLocation::End`], encoded inner chunks are written to the store as they arrive and only the shard index is held in memory.
+/// With [`ShardingIndexLocation::Start`], encoded inner chunks are staged in memory until the shard index preceding them is written.
+///
+/// Inner chunks that are not written or are entirely the fill value are empty in the shard.
+/// If an inner chunk is written more than once, the shard references its last write.
+pub struct ShardWriter<'a> {
+    storage: &'a dyn WritableStorageTraits,
+    key: StoreKey,
+    chunk_representation: ChunkRepresentation,
+    chunks_per_shard: ArrayShape,
+    inner_codecs: CodecChain,
+    index_codecs: CodecChain,
+    index_location: ShardingIndexLocation,
+    index_representation: ChunkRepresentation,
+    state: Mutex<ShardWriterState<'a>>,
+}
+
+/// The mutable state of a [`ShardWriter`].
+struct ShardWriterState<'a> {
+    writer: Box<dyn StoreValueWriter + 'a>,
+    shard_index: Vec<u64>,
+    /// The offset of the next inner chunk in the shard.
+    offset: u64,
+    /// Encoded inner chunks staged until the shard index at the start of the shard is written.
+    staged_chunks: Vec<Bytes>,
+}
+
+impl<'a> ShardWriter<'a> {
+    /// Create a new shard writer streaming to `key` in `storage`.
+    pub(super) fn new(
+        storage: &'a dyn WritableStorageTraits,
+        key: StoreKey,
+        shard_representation: &ChunkRepresentation,
+        chunk_shape: &ChunkShape,
+        inner_codecs: CodecChain,
+        index_codecs: CodecChain,
+        index_location: ShardingIndexLocation,
+    ) -> Result<Self, CodecError> {
+        let chunks_per_shard =
+            calculate_chunks_per_shard(shard_representation.shape(), chunk_shape.as_slice())?;
+        let index_representation =
+            sharding_index_decoded_representation(chunks_per_shard.as_slice());
+        let index_encoded_size = compute_index_encoded_size(&index_codecs, &index_representation)?;
+        let chunk_representation = unsafe {
+            ChunkRepresentation::new_unchecked(
+                chunk_shape.as_slice().to_vec(),
+                shard_representation.data_type().clone(),
+                shard_representation.fill_value().clone(),
+            )
+        };
+
+        let writer = storage.set_streaming(&key)?;
+        let shard_index = vec![u64::MAX; index_representation.num_elements_usize()];
+        let offset = match index_location {
+            ShardingIndexLocation::Start => index_encoded_size,
+            ShardingIndexLocation::End => 0,
+        };
+        Ok(Self {
+            storage,
+            key,
+            chunk_representation,
+            chunks_per_shard: chunk_shape_to_array_shape(chunks_per_shard.as_slice()),
+            inner_codecs,
+            index_codecs,
+            index_location,
+            index_representation,
+            state: Mutex::new(ShardWriterState {
+                writer,
+                shard_index,
+                offset,
+                staged_chunks: Vec::new(),
+            }),
+        })
+    }
+
+    /// Return the number of inner chunks along each dimension of the shard.
+    #[must_use]
+    pub fn chunks_per_shard(&self) -> &[u64] {
+        &self.chunks_per_shard
+    }
+
+    /// Encode `bytes` and write it to the inner chunk at `inner_chunk_indices` of the shard.
+    ///
+    /// # Errors
+    /// Returns a [`CodecError`] if
+    ///  - `inner_chunk_indices` are invalid,
+    ///  - `bytes` are incompatible with the inner chunk representation,
+    ///  - there is a codec encoding error, or
+    ///  - there is an underlying store error.
+    ///
+    /// # Panics
+    /// Panics if the number of inner chunks in the shard exceeds [`usize::MAX`].
+    pub fn write_inner_chunk(
+        &self,
+        inner_chunk_indices: &[u64],
+        bytes: ArrayBytes<'_>,
+        options: &CodecOptions,
+    ) -> Result<(), CodecError> {
+        if inner_chunk_indices.len() != self.chunks_per_shard.len()
+            || std::iter::zip(inner_chunk_indices, &self.chunks_per_shard)
+                .any(|(index, chunks)| index >= chunks)
+        {
+            return Err(CodecError::Other(format!(
+                "invalid inner chunk indices {inner_chunk_indices:?} for a shard with {:?} inner chunks",
+                self.chunks_per_shard
+            )));
+        }
+        bytes.validate(
+            self.chunk_representation.num_elements(),
+            self.chunk_representation.data_type().size(),
+        )?;
+        let shard_index_idx =
+            usize::try_from(ravel_indices(inner_chunk_indices, &self.chunks_per_shard) * 2)
+                .unwrap();
+
+        // Encode the inner chunk without holding the lock
+        let encoded_chunk = if bytes.is_fill_value(self.chunk_representation.fill_value()) {
+            None
+        } else {
+            Some(
+                self.inner_codecs
+                    .encode(bytes, &self.chunk_representation, options)?,
+            )
+        };
+
+        // Append the inner chunk to the shard
+        let mut state = self.state.lock();
+        if let Some(encoded_chunk) = encoded_chunk {
+            let size = encoded_chunk.len() as u64;
+            let encoded_chunk = Bytes::from(encoded_chunk.into_owned());
+            match self.index_location {
+                ShardingIndexLocation::Start => state.staged_chunks.push(encoded_chunk),
+                ShardingIndexLocation::End => state.writer.write(encoded_chunk)?,
+            }
+            let offset = state.offset;
+            state.shard_index[shard_index_idx] = offset;
+            state.shard_index[shard_index_idx + 1] = size;
+            state.offset += size;
+        } else {
+            state.shard_index[shard_index_idx] = u64::MAX;
+            state.shard_index[shard_index_idx + 1] = u64::MAX;
+        }
+        Ok(())
+    }
+
+    /// Finalize the shard by writing the shard index and storing the shard.
+    ///
+    /// If no inner chunk has been written (or they are all the fill value) and empty chunks are not stored, the shard is erased instead.
+    ///
+    /// # Errors
+    /// Returns a [`CodecError`] if there is a codec encoding error or an underlying store error.
+    pub fn finalize(self, options: &CodecOptions) -> Result<(), CodecError> {
+        let ShardWriterState {
+            mut writer,
+            shard_index,
+            offset: _,
+            staged_chunks,
+        } = self.state.into_inner();
+        if shard_index.iter().all(|&i| i == u64::MAX) && !options.store_empty_chunks() {
+            drop(writer);
+            return Ok(self.storage.erase(&self.key)?);
+        }
+
+        let shard_index_bytes: RawBytes = transmute_to_bytes_vec(shard_index).into();
+        let encoded_shard_index = Bytes::from(
+            self.index_codecs
+                .encode(
+                    shard_index_bytes.into(),
+                    &self.index_representation,
+                    options,
+                )?
+                .into_owned(),
+        );
+        match self.index_location {
+            ShardingIndexLocation::Start => {
+                writer.write(encoded_shard_index)?;
+                for encoded_chunk in staged_chunks {
+                    writer.write(encoded_chunk)?;
+                }
+            }
+            ShardingIndexLocation::End => writer.write(encoded_shard_index)?,
+        }
+        Ok(writer.finish()?)
+    }
+}
@@ -9,9 +9,9 @@ use crate::{
         array_bytes::{merge_chunks_vlen, update_bytes_flen},
         chunk_shape_to_array_shape,
         codec::{
-            ArrayCodecTraits, ArrayPartialDecoderTraits, ArrayToBytesCodecTraits,
-            BytesPartialDecoderTraits, CodecChain, CodecError, CodecOptions, CodecTraits,
-            RecommendedConcurrency,
+            ArrayCodecTraits, ArrayPartialDecoderTraits, ArrayPartialEncoderTraits,
+            ArrayToBytesCodecTraits, BytesPartialDecoderTraits, BytesPartialEncoderTraits,
+            CodecChain, CodecError, CodecOptions, CodecTraits, RecommendedConcurrency,
         },
         concurrency::calc_concurrency_outer_inner,
         transmute_to_bytes_vec, unravel_index,
@@ -22,6 +22,7 @@ use crate::{
     array_subset::ArraySubset,
     metadata::v3::MetadataV3,
     plugin::PluginCreateError,
+    storage::{StoreKey, WritableStorageTraits},
 };
 
 #[cfg(feature = "async")]
@@ -29,8 +30,9 @@ use crate::array::codec::{AsyncArrayPartialDecoderTraits, AsyncBytesPartialDecod
 
 use super::{
     calculate_chunks_per_shard, compute_index_encoded_size, decode_shard_index,
-    sharding_index_decoded_representation, sharding_partial_decoder, ShardingCodecConfiguration,
-    ShardingCodecConfigurationV1, ShardingIndexLocation, IDENTIFIER,
+    sharding_index_decoded_representation, sharding_partial_decoder, sharding_partial_encoder,
+    ShardIndexCache, ShardWriter, ShardingCodecConfiguration, ShardingCodecConfigurationV1,
+    ShardingIndexLocation, IDENTIFIER,
 };
 
 use rayon::prelude::*;
@@ -83,6 +85,79 @@ impl ShardingCodec {
             configuration.index_location,
         ))
     }
+
+    /// Create a partial decoder for the shard at `shard_indices`, reusing its shard index in `shard_index_cache` if present.
+    pub(crate) fn partial_decoder_with_shard_index_cache<'a>(
+        &'a self,
+        input_handle: Arc<dyn BytesPartialDecoderTraits + 'a>,
+        decoded_representation: &ChunkRepresentation,
+        shard_index_cache: &ShardIndexCache,
+        shard_indices: &[u64],
+        options: &CodecOptions,
+    ) -> Result<Arc<dyn ArrayPartialDecoderTraits + 'a>, CodecError> {
+        Ok(Arc::new(
+            sharding_partial_decoder::ShardingPartialDecoder::new_with_shard_index_cache(
+                input_handle,
+                decoded_representation.clone(),
+                self.chunk_shape.clone(),
+                &self.inner_codecs,
+                &self.index_codecs,
+                self.index_location,
+                shard_index_cache,
+                shard_indices,
+                options,
+            )?,
+        ))
+    }
+
+    #[cfg(feature = "async")]
+    /// Create an asynchronous partial decoder for the shard at `shard_indices`, reusing its shard index in `shard_index_cache` if present.
+    pub(crate) async fn async_partial_decoder_with_shard_index_cache<'a>(
+        &'a self,
+        input_handle: Arc<dyn AsyncBytesPartialDecoderTraits + 'a>,
+        decoded_representation: &ChunkRepresentation,
+        shard_index_cache: &ShardIndexCache,
+        shard_indices: &[u64],
+        options: &CodecOptions,
+    ) -> Result<Arc<dyn AsyncArrayPartialDecoderTraits + 'a>, CodecError> {
+        Ok(Arc::new(
+            sharding_partial_decoder::AsyncShardingPartialDecoder::new_with_shard_index_cache(
+                input_handle,
+                decoded_representation.clone(),
+                self.chunk_shape.clone(),
+                &self.inner_codecs,
+                &self.index_codecs,
+                self.index_location,
+                shard_index_cache,
+                shard_indices,
+                options,
+            )
+            .await?,
+        ))
+    }
+
+    /// Create a [`ShardWriter`] for creating the shard with `shard_representation` at `key` in `storage` in one pass.
+    ///
+    /// Any existing value of `key` is replaced when the shard writer is finalized.
+    ///
+    /// # Errors
+    /// Returns a [`CodecError`] if the shard representation is incompatible with the inner chunk shape or there is an underlying store error.
+    pub fn shard_writer<'a>(
+        &self,
+        storage: &'a dyn WritableStorageTraits,
+        key: StoreKey,
+        shard_representation: &ChunkRepresentation,
+    ) -> Result<ShardWriter<'a>, CodecError> {
+        ShardWriter::new(
+            storage,
+            key,
+            shard_representation,
+            &self.chunk_shape,
+            self.inner_codecs.clone(),
+            self.index_codecs.clone(),
+            self.index_location,
+        )
+    }
 }
 
 impl CodecTraits for ShardingCodec {
@@ -103,6 +178,10 @@ impl CodecTraits for ShardingCodec {
     fn partial_decoder_decodes_all(&self) -> bool {
         false
     }
+
+    fn partial_encoder_encodes_all(&self) -> bool {
+        false
+    }
 }
 
 /// Repeat the fill value into a contiguous vec
@@ -258,13 +337,12 @@ impl ArrayToBytesCodecTraits for ShardingCodec {
                 .collect::<Result<Vec<_>, _>>()?;
 
                 // Convert into an array
-                merge_chunks_vlen(chunk_bytes_and_subsets, &shard_representation.shape_u64())
+                merge_chunks_vlen(&chunk_bytes_and_subsets, &shard_representation.shape_u64())
             }
             DataTypeSize::Fixed(data_type_size) => {
                 // Allocate an array for the output
-                let mut decoded_shard = Vec::<u8>::with_capacity(
-                    shard_representation.num_elements_usize() * data_type_size,
-                );
+                let size_output = shard_representation.num_elements_usize() * data_type_size;
+                let mut decoded_shard = options.take_buffer(size_output);
 
                 let contiguous_fill_value = if any_empty {
                     Some(get_contiguous_fill_value(
@@ -277,8 +355,10 @@ impl ArrayToBytesCodecTraits for ShardingCodec {
                 };
 
                 {
-                    let output =
-                        UnsafeCellSlice::new_from_vec_with_spare_capacity(&mut decoded_shard);
+                    let output = UnsafeCellSlice::new_from_vec_with_spare_capacity_len(
+                        &mut decoded_shard,
+                        size_output,
+                    );
                     let decode_chunk = |chunk_index: usize| {
                         let chunk_subset = self
                             .chunk_index_to_subset(chunk_index as u64, chunks_per_shard.as_slice());
@@ -320,13 +400,15 @@ impl ArrayToBytesCodecTraits for ShardingCodec {
                                 &chunk_representation,
                                 &options,
                             )?;
+                            let decoded_chunk = decoded_chunk.into_fixed()?;
                             update_bytes_flen(
                                 output,
                                 &shard_representation.shape_u64(),
-                                &decoded_chunk.into_fixed()?,
+                                &decoded_chunk,
                                 &chunk_subset,
                                 data_type_size,
                             );
+                            options.recycle_bytes(decoded_chunk);
                         };
 
                         Ok::<_, CodecError>(())
@@ -339,7 +421,7 @@ impl ArrayToBytesCodecTraits for ShardingCodec {
                         decode_chunk
                     )?;
                 }
-                unsafe { decoded_shard.set_len(decoded_shard.capacity()) };
+                unsafe { decoded_shard.set_len(size_output) };
                 Ok(ArrayBytes::from(decoded_shard))
             }
         }
@@ -364,6 +446,27 @@ impl ArrayToBytesCodecTraits for ShardingCodec {
         ))
     }
 
+    fn partial_encoder<'a>(
+        &'a self,
+        input_handle: Arc<dyn BytesPartialDecoderTraits + 'a>,
+        output_handle: Arc<dyn BytesPartialEncoderTraits + 'a>,
+        decoded_representation: &ChunkRepresentation,
+        options: &CodecOptions,
+    ) -> Result<Arc<dyn ArrayPartialEncoderTraits + 'a>, CodecError> {
+        Ok(Arc::new(
+            sharding_partial_encoder::ShardingPartialEncoder::new(
+                input_handle,
+                output_handle,
+                decoded_representation.clone(),
+                self.chunk_shape.clone(),
+                &self.inner_codecs,
+                &self.index_codecs,
+                self.index_location,
+                options,
+            )?,
+        ))
+    }
+
     #[cfg(feature = "async")]
     async fn async_partial_decoder<'a>(
         &'a self,
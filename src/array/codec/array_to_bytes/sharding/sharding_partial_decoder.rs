@@ -1,4 +1,4 @@
-use std::{num::NonZeroU64, sync::Arc};
+use std::{borrow::Cow, num::NonZeroU64, sync::Arc};
 
 use rayon::prelude::*;
 
@@ -15,7 +15,7 @@ use crate::{
         concurrency::{calc_concurrency_outer_inner, RecommendedConcurrency},
         ravel_indices,
         unsafe_cell_slice::UnsafeCellSlice,
-        ArrayBytes, ArraySize, ChunkRepresentation, ChunkShape, DataType, DataTypeSize,
+        ArrayBytes, ArraySize, ChunkRepresentation, ChunkShape, DataType, DataTypeSize, RawBytes,
     },
     byte_range::ByteRange,
 };
@@ -27,8 +27,9 @@ use crate::array::codec::{
 };
 
 use super::{
-    calculate_chunks_per_shard, compute_index_encoded_size, decode_shard_index,
-    sharding_index_decoded_representation, ShardingIndexLocation,
+    calculate_chunks_per_shard, coalesce_byte_ranges, compute_index_encoded_size,
+    decode_shard_index, sharding_index_decoded_representation, CoalescedByteRange, ShardIndexCache,
+    ShardingIndexLocation,
 };
 
 /// Partial decoder for the sharding codec.
@@ -37,7 +38,7 @@ pub struct ShardingPartialDecoder<'a> {
     decoded_representation: ChunkRepresentation,
     chunk_grid: RegularChunkGrid,
     inner_codecs: &'a CodecChain,
-    shard_index: Option<Vec<u64>>,
+    shard_index: Option<Arc<Vec<u64>>>,
 }
 
 impl<'a> ShardingPartialDecoder<'a> {
@@ -59,6 +60,45 @@ impl<'a> ShardingPartialDecoder<'a> {
             &decoded_representation,
             options,
         )?;
+        Ok(Self {
+            input_handle,
+            decoded_representation,
+            chunk_grid: RegularChunkGrid::new(chunk_shape),
+            inner_codecs,
+            shard_index: shard_index.map(Arc::new),
+        })
+    }
+
+    /// Create a new partial decoder for the sharding codec, reusing the shard index in `shard_index_cache` if present.
+    ///
+    /// The shard index is decoded and inserted into `shard_index_cache` on a cache miss.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new_with_shard_index_cache(
+        input_handle: Arc<dyn BytesPartialDecoderTraits + 'a>,
+        decoded_representation: ChunkRepresentation,
+        chunk_shape: ChunkShape,
+        inner_codecs: &'a CodecChain,
+        index_codecs: &'a CodecChain,
+        index_location: ShardingIndexLocation,
+        shard_index_cache: &ShardIndexCache,
+        shard_indices: &[u64],
+        options: &CodecOptions,
+    ) -> Result<Self, CodecError> {
+        let shard_index = if let Some(shard_index) = shard_index_cache.get(shard_indices) {
+            shard_index
+        } else {
+            let shard_index = Self::decode_shard_index(
+                &*input_handle,
+                index_codecs,
+                index_location,
+                chunk_shape.as_slice(),
+                &decoded_representation,
+                options,
+            )?
+            .map(Arc::new);
+            shard_index_cache.insert(shard_indices.to_vec(), shard_index.clone());
+            shard_index
+        };
         Ok(Self {
             input_handle,
             decoded_representation,
@@ -69,7 +109,7 @@ impl<'a> ShardingPartialDecoder<'a> {
     }
 
     /// Returns `None` if there is no shard.
-    fn decode_shard_index(
+    pub(super) fn decode_shard_index(
         input_handle: &dyn BytesPartialDecoderTraits,
         index_codecs: &'a CodecChain,
         index_location: ShardingIndexLocation,
@@ -185,6 +225,38 @@ impl ArrayPartialDecoderTraits for ShardingPartialDecoder<'_> {
 
         let mut out = Vec::with_capacity(array_subsets.len());
         for array_subset in array_subsets {
+            if let Some(gap) = options.shard_coalesce_gap() {
+                let inner_chunks = inner_chunks_in_subset(
+                    array_subset,
+                    &chunk_representation,
+                    &chunks_per_shard,
+                    shard_index,
+                );
+                let coalesced_byte_ranges = coalesce_inner_chunk_byte_ranges(&inner_chunks, gap);
+                let encoded_byte_ranges = if coalesced_byte_ranges.is_empty() {
+                    vec![]
+                } else {
+                    self.input_handle
+                        .partial_decode(
+                            &coalesced_byte_ranges_to_byte_ranges(&coalesced_byte_ranges),
+                            &options,
+                        )
+                        .map_err(map_out_of_bounds_error)?
+                        .ok_or_else(missing_shard_error)?
+                };
+                out.push(decode_coalesced_inner_chunks(
+                    self.inner_codecs,
+                    &chunk_representation,
+                    array_subset,
+                    &inner_chunks,
+                    &coalesced_byte_ranges,
+                    &encoded_byte_ranges,
+                    inner_chunk_concurrent_limit,
+                    &options,
+                )?);
+                continue;
+            }
+
             let chunks = unsafe { array_subset.chunks_unchecked(chunk_representation.shape()) };
 
             match self.decoded_representation.element_size() {
@@ -261,7 +333,7 @@ impl ArrayPartialDecoderTraits for ShardingPartialDecoder<'_> {
 
                     // Convert into an array
                     let out_array_subset =
-                        merge_chunks_vlen(chunk_bytes_and_subsets, array_subset.shape())?;
+                        merge_chunks_vlen(&chunk_bytes_and_subsets, array_subset.shape())?;
                     out.push(out_array_subset);
                 }
                 DataTypeSize::Fixed(data_type_size) => {
@@ -356,7 +428,7 @@ pub struct AsyncShardingPartialDecoder<'a> {
     decoded_representation: ChunkRepresentation,
     chunk_grid: RegularChunkGrid,
     inner_codecs: &'a CodecChain,
-    shard_index: Option<Vec<u64>>,
+    shard_index: Option<Arc<Vec<u64>>>,
 }
 
 #[cfg(feature = "async")]
@@ -380,6 +452,46 @@ impl<'a> AsyncShardingPartialDecoder<'a> {
             options,
         )
         .await?;
+        Ok(Self {
+            input_handle,
+            decoded_representation,
+            chunk_grid: RegularChunkGrid::new(chunk_shape),
+            inner_codecs,
+            shard_index: shard_index.map(Arc::new),
+        })
+    }
+
+    /// Create a new partial decoder for the sharding codec, reusing the shard index in `shard_index_cache` if present.
+    ///
+    /// The shard index is decoded and inserted into `shard_index_cache` on a cache miss.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn new_with_shard_index_cache(
+        input_handle: Arc<dyn AsyncBytesPartialDecoderTraits + 'a>,
+        decoded_representation: ChunkRepresentation,
+        chunk_shape: ChunkShape,
+        inner_codecs: &'a CodecChain,
+        index_codecs: &'a CodecChain,
+        index_location: ShardingIndexLocation,
+        shard_index_cache: &ShardIndexCache,
+        shard_indices: &[u64],
+        options: &CodecOptions,
+    ) -> Result<AsyncShardingPartialDecoder<'a>, CodecError> {
+        let shard_index = if let Some(shard_index) = shard_index_cache.get(shard_indices) {
+            shard_index
+        } else {
+            let shard_index = Self::decode_shard_index(
+                &*input_handle,
+                index_codecs,
+                index_location,
+                chunk_shape.as_slice(),
+                &decoded_representation,
+                options,
+            )
+            .await?
+            .map(Arc::new);
+            shard_index_cache.insert(shard_indices.to_vec(), shard_index.clone());
+            shard_index
+        };
         Ok(Self {
             input_handle,
             decoded_representation,
@@ -493,6 +605,39 @@ impl AsyncArrayPartialDecoderTraits for AsyncShardingPartialDecoder<'_> {
         let mut out = Vec::with_capacity(array_subsets.len());
         // TODO: Could go parallel here?
         for array_subset in array_subsets {
+            if let Some(gap) = options.shard_coalesce_gap() {
+                let inner_chunks = inner_chunks_in_subset(
+                    array_subset,
+                    &chunk_representation,
+                    &chunks_per_shard,
+                    shard_index,
+                );
+                let coalesced_byte_ranges = coalesce_inner_chunk_byte_ranges(&inner_chunks, gap);
+                let encoded_byte_ranges = if coalesced_byte_ranges.is_empty() {
+                    vec![]
+                } else {
+                    self.input_handle
+                        .partial_decode(
+                            &coalesced_byte_ranges_to_byte_ranges(&coalesced_byte_ranges),
+                            options,
+                        )
+                        .await
+                        .map_err(map_out_of_bounds_error)?
+                        .ok_or_else(missing_shard_error)?
+                };
+                out.push(decode_coalesced_inner_chunks(
+                    self.inner_codecs,
+                    &chunk_representation,
+                    array_subset,
+                    &inner_chunks,
+                    &coalesced_byte_ranges,
+                    &encoded_byte_ranges,
+                    options.concurrent_target(),
+                    options,
+                )?);
+                continue;
+            }
+
             match self.decoded_representation.element_size() {
                 DataTypeSize::Variable => {
                     let chunks =
@@ -568,7 +713,7 @@ impl AsyncArrayPartialDecoderTraits for AsyncShardingPartialDecoder<'_> {
 
                     // Convert into an array
                     let out_array_subset =
-                        merge_chunks_vlen(chunk_bytes_and_subsets, array_subset.shape())?;
+                        merge_chunks_vlen(&chunk_bytes_and_subsets, array_subset.shape())?;
                     out.push(out_array_subset);
                 }
                 DataTypeSize::Fixed(data_type_size) => {
@@ -597,80 +742,74 @@ impl AsyncArrayPartialDecoderTraits for AsyncShardingPartialDecoder<'_> {
                     let mut shard = Vec::with_capacity(shard_size);
                     let shard_slice = UnsafeCellSlice::new_from_vec_with_spare_capacity(&mut shard);
 
-                    // Decode unfilled chunks
-                    let results = futures::future::join_all(
+                    // Retrieve the encoded unfilled chunks with a single vectored request
+                    let (unfilled_chunk_subsets, unfilled_byte_ranges): (Vec<_>, Vec<_>) =
                         chunk_info
                             .iter()
                             .filter_map(|(chunk_subset, offset_size)| {
-                                offset_size
-                                    .as_ref()
-                                    .map(|offset_size| (chunk_subset, offset_size))
-                            })
-                            .map(|(chunk_subset, (offset, size))| {
-                                let chunk_representation = chunk_representation.clone();
-                                async move {
-                                let partial_decoder = self
-                                    .inner_codecs
-                                    .async_partial_decoder(
-                                        Arc::new(AsyncByteIntervalPartialDecoder::new(
-                                            &*self.input_handle,
+                                offset_size.as_ref().map(|(offset, size)| {
+                                    (
+                                        chunk_subset,
+                                        ByteRange::FromStart(
                                             u64::try_from(*offset).unwrap(),
-                                            u64::try_from(*size).unwrap(),
-                                        )),
-                                        &chunk_representation,
-                                        options, // TODO: Adjust options for partial decoding?
+                                            Some(u64::try_from(*size).unwrap()),
+                                        ),
                                     )
-                                    .await
-                                    .map_err(|err| if let CodecError::InvalidByteRangeError(_) = err {
-                                        CodecError::Other(
-                                            "The shard index references out-of-bounds bytes. The chunk may be corrupted."
-                                                .to_string(),
-                                        )
-                                    } else {
-                                        err
-                                    })?;
-                                let chunk_subset_overlap = unsafe { array_subset.overlap_unchecked(chunk_subset) };
-                                // Partial decoding is actually really slow with the blosc codec! Assume sharded chunks are small, and just decode the whole thing and extract bytes
-                                // TODO: Investigate further
-                                // let decoded_chunk = partial_decoder
-                                //     .partial_decode(&[chunk_subset_overlap.relative_to(chunk_subset.start())?])
-                                //     .await?
-                                //     .remove(0);
-                                let decoded_chunk = partial_decoder
-                                    .partial_decode_opt(
-                                        &[ArraySubset::new_with_shape(chunk_subset.shape().to_vec())],
-                                        options,
-                                    ) // TODO: Adjust options for partial decoding
-                                    .await?
-                                    .remove(0).into_owned();
-                                let decoded_chunk = decoded_chunk
-                                    .extract_array_subset(
-                                        &chunk_subset_overlap.relative_to(chunk_subset.start()).unwrap(),
-                                        chunk_subset.shape(),
-                                        self.decoded_representation.data_type()
-                                    )?
-                                    .into_fixed()?
-                                    .into_owned();
-                                Ok::<_, CodecError>((decoded_chunk, chunk_subset_overlap))
-                            }}),
-                        )
-                        .await;
-                    // FIXME: Concurrency limit for futures
+                                })
+                            })
+                            .unzip();
+                    let encoded_chunks = if unfilled_byte_ranges.is_empty() {
+                        vec![]
+                    } else {
+                        self.input_handle
+                            .partial_decode(&unfilled_byte_ranges, options)
+                            .await
+                            .map_err(|err| if let CodecError::InvalidByteRangeError(_) = err {
+                                CodecError::Other(
+                                    "The shard index references out-of-bounds bytes. The chunk may be corrupted."
+                                        .to_string(),
+                                )
+                            } else {
+                                err
+                            })?
+                            .ok_or_else(|| {
+                                CodecError::Other(
+                                    "The shard is missing but the shard index was decoded."
+                                        .to_string(),
+                                )
+                            })?
+                    };
 
-                    if !results.is_empty() {
+                    // Decode unfilled chunks
+                    // Partial decoding is actually really slow with the blosc codec! Assume sharded chunks are small, and just decode the whole thing and extract bytes
+                    let unfilled_chunks =
+                        std::iter::zip(unfilled_chunk_subsets, encoded_chunks).collect::<Vec<_>>();
+                    if !unfilled_chunks.is_empty() {
                         rayon_iter_concurrent_limit::iter_concurrent_limit!(
                             options.concurrent_target(),
-                            results,
+                            unfilled_chunks,
                             try_for_each,
-                            |subset_and_decoded_chunk| {
-                                let (chunk_subset_bytes, chunk_subset_overlap): (
-                                    Vec<u8>,
-                                    ArraySubset,
-                                ) = subset_and_decoded_chunk?;
+                            |(chunk_subset, encoded_chunk): (&ArraySubset, RawBytes)| {
+                                let decoded_chunk = self.inner_codecs.decode(
+                                    encoded_chunk,
+                                    &chunk_representation,
+                                    options,
+                                )?;
+                                let chunk_subset_overlap =
+                                    unsafe { array_subset.overlap_unchecked(chunk_subset) };
+                                let chunk_subset_bytes = decoded_chunk
+                                    .extract_array_subset(
+                                        &chunk_subset_overlap
+                                            .relative_to(chunk_subset.start())
+                                            .unwrap(),
+                                        chunk_subset.shape(),
+                                        self.decoded_representation.data_type(),
+                                    )?
+                                    .into_fixed()?;
                                 update_bytes_flen(
                                     unsafe { shard_slice.get() },
                                     array_subset.shape(),
-                                    &chunk_subset_bytes.into(),
+                                    &chunk_subset_bytes,
                                     &chunk_subset_overlap
                                         .relative_to(array_subset.start())
                                         .unwrap(),
@@ -726,3 +865,179 @@ impl AsyncArrayPartialDecoderTraits for AsyncShardingPartialDecoder<'_> {
         Ok(out)
     }
 }
+
+/// An inner chunk intersecting an array subset, with the offset and size of its encoded bytes in the shard or [`None`] if it is empty.
+type InnerChunk = (ArraySubset, Option<(u64, u64)>);
+
+/// Return the inner chunks intersecting `array_subset`.
+fn inner_chunks_in_subset(
+    array_subset: &ArraySubset,
+    chunk_representation: &ChunkRepresentation,
+    chunks_per_shard: &[u64],
+    shard_index: &[u64],
+) -> Vec<InnerChunk> {
+    unsafe { array_subset.chunks_unchecked(chunk_representation.shape()) }
+        .into_iter()
+        .map(|(chunk_indices, chunk_subset)| {
+            let shard_index_idx: usize =
+                usize::try_from(ravel_indices(&chunk_indices, chunks_per_shard) * 2).unwrap();
+            let offset = shard_index[shard_index_idx];
+            let size = shard_index[shard_index_idx + 1];
+            if offset == u64::MAX && size == u64::MAX {
+                (chunk_subset, None)
+            } else {
+                (chunk_subset, Some((offset, size)))
+            }
+        })
+        .collect()
+}
+
+/// Coalesce the byte ranges of the non-empty `inner_chunks` that are separated by at most `gap` bytes.
+fn coalesce_inner_chunk_byte_ranges(
+    inner_chunks: &[InnerChunk],
+    gap: u64,
+) -> Vec<CoalescedByteRange> {
+    coalesce_byte_ranges(
+        inner_chunks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (_, offset_size))| {
+                offset_size.map(|(offset, size)| (i, offset, size))
+            }),
+        gap,
+    )
+}
+
+fn coalesced_byte_ranges_to_byte_ranges(
+    coalesced_byte_ranges: &[CoalescedByteRange],
+) -> Vec<ByteRange> {
+    coalesced_byte_ranges
+        .iter()
+        .map(|byte_range| ByteRange::FromStart(byte_range.offset, Some(byte_range.size)))
+        .collect()
+}
+
+fn out_of_bounds_error() -> CodecError {
+    CodecError::Other(
+        "The shard index references out-of-bounds bytes. The chunk may be corrupted.".to_string(),
+    )
+}
+
+fn map_out_of_bounds_error(err: CodecError) -> CodecError {
+    if let CodecError::InvalidByteRangeError(_) = err {
+        out_of_bounds_error()
+    } else {
+        err
+    }
+}
+
+fn missing_shard_error() -> CodecError {
+    CodecError::Other("The shard is missing but the shard index was decoded.".to_string())
+}
+
+/// Decode the `inner_chunks` intersecting `array_subset` from the encoded bytes of their coalesced byte ranges.
+#[allow(clippy::too_many_arguments)]
+fn decode_coalesced_inner_chunks(
+    inner_codecs: &CodecChain,
+    chunk_representation: &ChunkRepresentation,
+    array_subset: &ArraySubset,
+    inner_chunks: &[InnerChunk],
+    coalesced_byte_ranges: &[CoalescedByteRange],
+    encoded_byte_ranges: &[RawBytes],
+    concurrent_limit: usize,
+    options: &CodecOptions,
+) -> Result<ArrayBytes<'static>, CodecError> {
+    // Locate the encoded bytes of each inner chunk within the coalesced byte ranges
+    let mut encoded_inner_chunks: Vec<Option<&[u8]>> = vec![None; inner_chunks.len()];
+    for (coalesced_byte_range, encoded_byte_range) in
+        std::iter::zip(coalesced_byte_ranges, encoded_byte_ranges)
+    {
+        for &i in &coalesced_byte_range.inner_chunks {
+            let (offset, size) = inner_chunks[i]
+                .1
+                .expect("coalesced inner chunks are not empty");
+            let start = usize::try_from(offset - coalesced_byte_range.offset).unwrap();
+            let end = start + usize::try_from(size).unwrap();
+            let encoded_inner_chunk = encoded_byte_range
+                .get(start..end)
+                .ok_or_else(out_of_bounds_error)?;
+            encoded_inner_chunks[i] = Some(encoded_inner_chunk);
+        }
+    }
+    let inner_chunks = std::iter::zip(inner_chunks, encoded_inner_chunks).collect::<Vec<_>>();
+
+    let decode_inner_chunk_subset =
+        |((chunk_subset, _), encoded_inner_chunk): (&InnerChunk, Option<&[u8]>)| {
+            let chunk_subset_overlap = unsafe { array_subset.overlap_unchecked(chunk_subset) };
+            let chunk_subset_bytes = if let Some(encoded_inner_chunk) = encoded_inner_chunk {
+                let decoded_chunk = inner_codecs.decode(
+                    Cow::Borrowed(encoded_inner_chunk),
+                    chunk_representation,
+                    options,
+                )?;
+                if chunk_subset_overlap == *chunk_subset {
+                    decoded_chunk.into_owned()
+                } else {
+                    decoded_chunk
+                        .extract_array_subset(
+                            &chunk_subset_overlap
+                                .relative_to(chunk_subset.start())
+                                .unwrap(),
+                            chunk_subset.shape(),
+                            chunk_representation.data_type(),
+                        )?
+                        .into_owned()
+                }
+            } else {
+                ArrayBytes::new_fill_value(
+                    ArraySize::new(
+                        chunk_representation.data_type().size(),
+                        chunk_subset_overlap.num_elements(),
+                    ),
+                    chunk_representation.fill_value(),
+                )
+            };
+            Ok::<_, CodecError>((
+                chunk_subset_bytes,
+                chunk_subset_overlap
+                    .relative_to(array_subset.start())
+                    .unwrap(),
+            ))
+        };
+
+    match chunk_representation.data_type().size() {
+        DataTypeSize::Variable => {
+            let chunk_bytes_and_subsets = rayon_iter_concurrent_limit::iter_concurrent_limit!(
+                concurrent_limit,
+                inner_chunks,
+                map,
+                decode_inner_chunk_subset
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+            merge_chunks_vlen(&chunk_bytes_and_subsets, array_subset.shape())
+        }
+        DataTypeSize::Fixed(data_type_size) => {
+            let array_subset_size = array_subset.num_elements_usize() * data_type_size;
+            let mut out_array_subset = vec![0; array_subset_size];
+            let out_array_subset_slice = UnsafeCellSlice::new(out_array_subset.as_mut_slice());
+            rayon_iter_concurrent_limit::iter_concurrent_limit!(
+                concurrent_limit,
+                inner_chunks,
+                try_for_each,
+                |inner_chunk| {
+                    let (chunk_subset_bytes, chunk_subset) =
+                        decode_inner_chunk_subset(inner_chunk)?;
+                    update_bytes_flen(
+                        unsafe { out_array_subset_slice.get() },
+                        array_subset.shape(),
+                        &chunk_subset_bytes.into_fixed()?,
+                        &chunk_subset,
+                        data_type_size,
+                    );
+                    Ok::<_, CodecError>(())
+                }
+            )?;
+            Ok(ArrayBytes::from(out_array_subset))
+        }
+    }
+}
@@ -0,0 +1,411 @@
+use std::{borrow::Cow, collections::BTreeMap, sync::Arc};
+
+use parking_lot::Mutex;
+use rayon::prelude::*;
+
+use crate::{
+    array::{
+        array_bytes::update_array_bytes,
+        chunk_shape_to_array_shape,
+        codec::{
+            ArrayCodecTraits, ArrayPartialEncoderTraits, ArrayToBytesCodecTraits,
+            BytesPartialDecoderTraits, BytesPartialEncoderTraits, CodecChain, CodecError,
+            CodecOptions,
+        },
+        concurrency::{calc_concurrency_outer_inner, RecommendedConcurrency},
+        ravel_indices, transmute_to_bytes_vec, ArrayBytes, ChunkRepresentation, ChunkShape,
+        DataType, RawBytes,
+    },
+    array_subset::{ArraySubset, IncompatibleArraySubsetAndShapeError},
+    byte_range::{ByteOffset, ByteRange},
+};
+
+use super::{
+    calculate_chunks_per_shard, compute_index_encoded_size, sharding_index_decoded_representation,
+    sharding_partial_decoder::ShardingPartialDecoder, ShardingIndexLocation,
+};
+
+/// Partial encoder for the sharding codec.
+///
+/// Only the inner chunks intersecting an update are decoded and encoded.
+/// Updated inner chunks are appended to the shard and the shard index is rewritten, so the encoded bytes of replaced inner chunks become unreferenced.
+/// The shard is compacted once unreferenced bytes exceed the referenced bytes.
+///
+/// The shard index is decoded on creation and kept up to date by the partial encoder, so it assumes that the shard is not concurrently modified elsewhere.
+pub struct ShardingPartialEncoder<'a> {
+    input_handle: Arc<dyn BytesPartialDecoderTraits + 'a>,
+    output_handle: Arc<dyn BytesPartialEncoderTraits + 'a>,
+    decoded_representation: ChunkRepresentation,
+    chunk_shape: ChunkShape,
+    inner_codecs: &'a CodecChain,
+    index_codecs: &'a CodecChain,
+    index_location: ShardingIndexLocation,
+    index_representation: ChunkRepresentation,
+    index_encoded_size: u64,
+    /// The decoded shard index and the encoded size of the shard.
+    shard_index_and_size: Mutex<(Vec<u64>, u64)>,
+}
+
+impl<'a> ShardingPartialEncoder<'a> {
+    /// Create a new partial encoder for the sharding codec.
+    ///
+    /// The shard index is decoded from `input_handle`, and the shard is empty if it is missing.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        input_handle: Arc<dyn BytesPartialDecoderTraits + 'a>,
+        output_handle: Arc<dyn BytesPartialEncoderTraits + 'a>,
+        decoded_representation: ChunkRepresentation,
+        chunk_shape: ChunkShape,
+        inner_codecs: &'a CodecChain,
+        index_codecs: &'a CodecChain,
+        index_location: ShardingIndexLocation,
+        options: &CodecOptions,
+    ) -> Result<Self, CodecError> {
+        let chunks_per_shard =
+            calculate_chunks_per_shard(decoded_representation.shape(), chunk_shape.as_slice())?;
+        let index_representation =
+            sharding_index_decoded_representation(chunks_per_shard.as_slice());
+        let index_encoded_size = compute_index_encoded_size(index_codecs, &index_representation)?;
+
+        // Decode the shard index
+        let shard_index = ShardingPartialDecoder::decode_shard_index(
+            &*input_handle,
+            index_codecs,
+            index_location,
+            chunk_shape.as_slice(),
+            &decoded_representation,
+            options,
+        )?;
+        let shard_size = if shard_index.is_some() {
+            input_handle.size(options)?.unwrap_or_default()
+        } else {
+            0
+        };
+        let shard_index = shard_index
+            .unwrap_or_else(|| vec![u64::MAX; index_representation.num_elements_usize()]);
+
+        Ok(Self {
+            input_handle,
+            output_handle,
+            decoded_representation,
+            chunk_shape,
+            inner_codecs,
+            index_codecs,
+            index_location,
+            index_representation,
+            index_encoded_size,
+            shard_index_and_size: Mutex::new((shard_index, shard_size)),
+        })
+    }
+
+    /// Decode the inner chunk at `chunk_index` with `chunk_subset` in the shard and apply the updates which intersect it.
+    ///
+    /// Returns [`None`] if the updated inner chunk is entirely the fill value.
+    fn update_inner_chunk(
+        &self,
+        shard_index: &[u64],
+        chunk_index: u64,
+        chunk_subset: &ArraySubset,
+        chunk_representation: &ChunkRepresentation,
+        subsets_and_bytes: &[(&ArraySubset, ArrayBytes<'_>)],
+        options: &CodecOptions,
+    ) -> Result<Option<Vec<u8>>, CodecError> {
+        let shard_index_idx = usize::try_from(chunk_index * 2).unwrap();
+        let offset = shard_index[shard_index_idx];
+        let size = shard_index[shard_index_idx + 1];
+
+        // Decode the existing inner chunk, unless it is missing or entirely overwritten
+        let overwritten = subsets_and_bytes
+            .iter()
+            .any(|(subset, _)| unsafe { subset.overlap_unchecked(chunk_subset) } == *chunk_subset);
+        let mut chunk_bytes = if overwritten || (offset == u64::MAX && size == u64::MAX) {
+            ArrayBytes::new_fill_value(
+                chunk_representation.size(),
+                chunk_representation.fill_value(),
+            )
+        } else {
+            let encoded_chunk = self
+                .input_handle
+                .partial_decode(&[ByteRange::FromStart(offset, Some(size))], options)?
+                .ok_or_else(|| CodecError::Other("the shard is missing".to_string()))?
+                .remove(0);
+            self.inner_codecs
+                .decode(encoded_chunk, chunk_representation, options)?
+                .into_owned()
+        };
+
+        // Apply the updates
+        let data_type = chunk_representation.data_type();
+        for (subset, bytes) in subsets_and_bytes {
+            let overlap = unsafe { subset.overlap_unchecked(chunk_subset) };
+            if overlap.is_empty() {
+                continue;
+            }
+            let overlap_bytes = bytes.extract_array_subset(
+                &overlap.relative_to(subset.start()).unwrap(),
+                subset.shape(),
+                data_type,
+            )?;
+            chunk_bytes = update_array_bytes(
+                chunk_bytes,
+                chunk_subset.shape().to_vec(),
+                &overlap_bytes,
+                &overlap.relative_to(chunk_subset.start()).unwrap(),
+                data_type.size(),
+            );
+        }
+
+        // Encode the inner chunk
+        if chunk_bytes.is_fill_value(chunk_representation.fill_value()) {
+            Ok(None)
+        } else {
+            Ok(Some(
+                self.inner_codecs
+                    .encode(chunk_bytes, chunk_representation, options)?
+                    .into_owned(),
+            ))
+        }
+    }
+
+    /// Encode the shard index.
+    fn encode_shard_index(
+        &self,
+        shard_index: &[u64],
+        options: &CodecOptions,
+    ) -> Result<Vec<u8>, CodecError> {
+        let shard_index_bytes: RawBytes = transmute_to_bytes_vec(shard_index.to_vec()).into();
+        Ok(self
+            .index_codecs
+            .encode(
+                shard_index_bytes.into(),
+                &self.index_representation,
+                options,
+            )?
+            .into_owned())
+    }
+
+    /// Rewrite the entire shard with only its referenced inner chunks and return its encoded size.
+    ///
+    /// The encoded bytes of inner chunks in `updated_chunks` are used instead of those referenced by `shard_index`.
+    fn compact(
+        &self,
+        shard_index: &mut [u64],
+        mut updated_chunks: BTreeMap<u64, Vec<u8>>,
+        options: &CodecOptions,
+    ) -> Result<u64, CodecError> {
+        // Read the referenced inner chunks which were not updated
+        let retained_chunks: Vec<u64> = (0..shard_index.len() as u64 / 2)
+            .filter(|chunk_index| {
+                let shard_index_idx = usize::try_from(chunk_index * 2).unwrap();
+                shard_index[shard_index_idx] != u64::MAX
+                    && !updated_chunks.contains_key(chunk_index)
+            })
+            .collect();
+        if !retained_chunks.is_empty() {
+            let byte_ranges: Vec<ByteRange> = retained_chunks
+                .iter()
+                .map(|chunk_index| {
+                    let shard_index_idx = usize::try_from(chunk_index * 2).unwrap();
+                    ByteRange::FromStart(
+                        shard_index[shard_index_idx],
+                        Some(shard_index[shard_index_idx + 1]),
+                    )
+                })
+                .collect();
+            let encoded_chunks = self
+                .input_handle
+                .partial_decode(&byte_ranges, options)?
+                .ok_or_else(|| CodecError::Other("the shard is missing".to_string()))?;
+            for (chunk_index, encoded_chunk) in std::iter::zip(retained_chunks, encoded_chunks) {
+                updated_chunks.insert(chunk_index, encoded_chunk.into_owned());
+            }
+        }
+
+        // Assemble the shard
+        let mut shard = match self.index_location {
+            ShardingIndexLocation::Start => {
+                vec![0; usize::try_from(self.index_encoded_size).unwrap()]
+            }
+            ShardingIndexLocation::End => vec![],
+        };
+        for (chunk_index, encoded_chunk) in updated_chunks {
+            let shard_index_idx = usize::try_from(chunk_index * 2).unwrap();
+            shard_index[shard_index_idx] = shard.len() as u64;
+            shard_index[shard_index_idx + 1] = encoded_chunk.len() as u64;
+            shard.extend_from_slice(&encoded_chunk);
+        }
+        let encoded_shard_index = self.encode_shard_index(shard_index, options)?;
+        match self.index_location {
+            ShardingIndexLocation::Start => {
+                shard[..encoded_shard_index.len()].copy_from_slice(&encoded_shard_index);
+            }
+            ShardingIndexLocation::End => shard.extend_from_slice(&encoded_shard_index),
+        }
+        let shard_size = shard.len() as u64;
+        self.output_handle.encode(Cow::Owned(shard), options)?;
+        Ok(shard_size)
+    }
+}
+
+impl ArrayPartialEncoderTraits for ShardingPartialEncoder<'_> {
+    fn data_type(&self) -> &DataType {
+        self.decoded_representation.data_type()
+    }
+
+    fn erase(&self) -> Result<(), CodecError> {
+        let mut shard_index_and_size = self.shard_index_and_size.lock();
+        self.output_handle.erase()?;
+        shard_index_and_size.0.fill(u64::MAX);
+        shard_index_and_size.1 = 0;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn partial_encode_opt(
+        &self,
+        subsets_and_bytes: &[(&ArraySubset, ArrayBytes<'_>)],
+        options: &CodecOptions,
+    ) -> Result<(), CodecError> {
+        let shard_shape = self.decoded_representation.shape_u64();
+        let data_type_size = self.decoded_representation.data_type().size();
+        for (subset, bytes) in subsets_and_bytes {
+            if !subset.inbounds(&shard_shape) {
+                return Err(IncompatibleArraySubsetAndShapeError::new(
+                    (*subset).clone(),
+                    shard_shape,
+                )
+                .into());
+            }
+            bytes.validate(subset.num_elements(), data_type_size)?;
+        }
+
+        let chunk_representation = unsafe {
+            ChunkRepresentation::new_unchecked(
+                self.chunk_shape.as_slice().to_vec(),
+                self.decoded_representation.data_type().clone(),
+                self.decoded_representation.fill_value().clone(),
+            )
+        };
+        let chunks_per_shard = calculate_chunks_per_shard(
+            self.decoded_representation.shape(),
+            self.chunk_shape.as_slice(),
+        )?;
+        let chunks_per_shard = chunk_shape_to_array_shape(chunks_per_shard.as_slice());
+        let index_encoded_size = self.index_encoded_size;
+
+        // Hold the shard index for the duration of the update
+        let mut shard_index_and_size = self.shard_index_and_size.lock();
+        let (shard_index_old, shard_size) = &*shard_index_and_size;
+        let shard_size = *shard_size;
+        let mut shard_index = shard_index_old.clone();
+
+        // Get the inner chunks intersecting the updates
+        let mut inner_chunks = BTreeMap::new();
+        for (subset, _) in subsets_and_bytes {
+            let chunks = unsafe { subset.chunks_unchecked(self.chunk_shape.as_slice()) };
+            for (chunk_indices, chunk_subset) in &chunks {
+                inner_chunks
+                    .entry(ravel_indices(&chunk_indices, &chunks_per_shard))
+                    .or_insert(chunk_subset);
+            }
+        }
+        if inner_chunks.is_empty() {
+            return Ok(());
+        }
+
+        // Calculate inner chunk/codec concurrency
+        let (inner_chunk_concurrent_limit, concurrency_limit_codec) = calc_concurrency_outer_inner(
+            options.concurrent_target(),
+            &RecommendedConcurrency::new_maximum(std::cmp::min(
+                options.concurrent_target(),
+                inner_chunks.len(),
+            )),
+            &self
+                .inner_codecs
+                .recommended_concurrency(&chunk_representation)?,
+        );
+        let options = options
+            .into_builder()
+            .concurrent_target(concurrency_limit_codec)
+            .build();
+
+        // Decode, update, and encode the intersecting inner chunks
+        let updated_chunks = rayon_iter_concurrent_limit::iter_concurrent_limit!(
+            inner_chunk_concurrent_limit,
+            inner_chunks.into_iter().collect::<Vec<_>>(),
+            map,
+            |(chunk_index, chunk_subset): (u64, ArraySubset)| {
+                self.update_inner_chunk(
+                    shard_index_old,
+                    chunk_index,
+                    &chunk_subset,
+                    &chunk_representation,
+                    subsets_and_bytes,
+                    &options,
+                )
+                .map(|encoded_chunk| (chunk_index, encoded_chunk))
+            }
+        )
+        .collect::<Result<Vec<_>, CodecError>>()?;
+
+        // Update the shard index, appending the updated inner chunks to the shard
+        let mut offset: ByteOffset = match self.index_location {
+            ShardingIndexLocation::Start => shard_size.max(index_encoded_size),
+            ShardingIndexLocation::End => shard_size.saturating_sub(index_encoded_size),
+        };
+        let mut offsets_and_bytes = Vec::with_capacity(updated_chunks.len() + 1);
+        for (chunk_index, encoded_chunk) in &updated_chunks {
+            let shard_index_idx = usize::try_from(chunk_index * 2).unwrap();
+            if let Some(encoded_chunk) = encoded_chunk {
+                shard_index[shard_index_idx] = offset;
+                shard_index[shard_index_idx + 1] = encoded_chunk.len() as u64;
+                offsets_and_bytes.push((offset, Cow::Borrowed(encoded_chunk.as_slice())));
+                offset += encoded_chunk.len() as u64;
+            } else {
+                shard_index[shard_index_idx] = u64::MAX;
+                shard_index[shard_index_idx + 1] = u64::MAX;
+            }
+        }
+
+        // Erase the shard if it is empty
+        if shard_index.iter().all(|&i| i == u64::MAX) && !options.store_empty_chunks() {
+            self.output_handle.erase()?;
+            *shard_index_and_size = (shard_index, 0);
+            return Ok(());
+        }
+
+        // Compact the shard if most of its bytes are unreferenced
+        let referenced_size = shard_index
+            .chunks_exact(2)
+            .filter(|entry| entry[0] != u64::MAX)
+            .map(|entry| entry[1])
+            .sum::<u64>();
+        let encoded_size = match self.index_location {
+            ShardingIndexLocation::Start => offset,
+            ShardingIndexLocation::End => offset + index_encoded_size,
+        };
+        if encoded_size - index_encoded_size > 2 * referenced_size {
+            let updated_chunks = updated_chunks
+                .into_iter()
+                .filter_map(|(chunk_index, encoded_chunk)| {
+                    encoded_chunk.map(|encoded_chunk| (chunk_index, encoded_chunk))
+                })
+                .collect();
+            let shard_size = self.compact(&mut shard_index, updated_chunks, &options)?;
+            *shard_index_and_size = (shard_index, shard_size);
+            return Ok(());
+        }
+
+        // Write the updated inner chunks and the shard index
+        let encoded_shard_index = self.encode_shard_index(&shard_index, &options)?;
+        let index_offset = match self.index_location {
+            ShardingIndexLocation::Start => 0,
+            ShardingIndexLocation::End => offset,
+        };
+        offsets_and_bytes.push((index_offset, Cow::Owned(encoded_shard_index)));
+        self.output_handle
+            .partial_encode(&offsets_and_bytes, &options)?;
+        *shard_index_and_size = (shard_index, encoded_size.max(shard_size));
+        Ok(())
+    }
+}
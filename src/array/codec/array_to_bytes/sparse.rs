@@ -0,0 +1,313 @@
+//! The `sparse` array to bytes codec.
+//!
+//! Encodes only the elements of a chunk that differ from the fill value, which suits segmentation and label volumes that are almost entirely fill value.
+//! Chunks are encoded as the number of stored elements (a little endian `u64`), the delta encoded linearised indices of the stored elements as unsigned LEB128 varints, and then the little endian bytes of the stored elements.
+//! Only fixed size data types are supported.
+//!
+//! Use [`Array::retrieve_chunk_sparse`](crate::array::Array::retrieve_chunk_sparse) and [`Array::store_chunk_sparse`](crate::array::Array::store_chunk_sparse) to decode and encode chunks without a dense intermediate.
+//!
+//! <div class="warning">
+//! This codec is experimental and is incompatible with other Zarr V3 implementations.
+//! </div>
+//!
+//! See [`SparseCodecConfigurationV1`] for example `JSON` metadata.
+
+mod sparse_codec;
+mod sparse_partial_decoder;
+
+use std::mem::size_of;
+
+pub use crate::metadata::v3::codec::sparse::{
+    SparseCodecConfiguration, SparseCodecConfigurationV1,
+};
+pub use sparse::IDENTIFIER;
+
+pub use sparse_codec::SparseCodec;
+
+use crate::{
+    array::{
+        codec::{Codec, CodecError, CodecPlugin},
+        DataType, Endianness, SparseArrayBytes, NATIVE_ENDIAN,
+    },
+    config::global_config,
+    metadata::v3::{codec::sparse, MetadataV3},
+    plugin::{PluginCreateError, PluginMetadataInvalidError},
+};
+
+use super::bytes::reverse_endianness;
+
+// Register the codec.
+inventory::submit! {
+    CodecPlugin::new(IDENTIFIER, is_name_sparse, create_codec_sparse)
+}
+
+fn is_name_sparse(name: &str) -> bool {
+    name.eq(IDENTIFIER)
+        || name
+            == global_config()
+                .experimental_codec_names()
+                .get(IDENTIFIER)
+                .expect("experimental codec identifier in global map")
+}
+
+pub(crate) fn create_codec_sparse(metadata: &MetadataV3) -> Result<Codec, PluginCreateError> {
+    let configuration: SparseCodecConfiguration = metadata
+        .to_configuration()
+        .map_err(|_| PluginMetadataInvalidError::new(IDENTIFIER, "codec", metadata.clone()))?;
+    let codec = Box::new(SparseCodec::new_with_configuration(&configuration));
+    Ok(Codec::ArrayToBytes(codec))
+}
+
+/// The maximum length of an unsigned LEB128 encoded `u64`.
+const VARINT_MAX_LEN: usize = 10;
+
+#[allow(clippy::cast_possible_truncation)]
+fn write_varint(encoded: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        encoded.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    encoded.push(value as u8);
+}
+
+fn read_varint(encoded: &[u8], offset: &mut usize) -> Result<u64, CodecError> {
+    let mut value = 0u64;
+    for shift in (0..VARINT_MAX_LEN * 7).step_by(7) {
+        let byte = *encoded
+            .get(*offset)
+            .ok_or_else(|| CodecError::Other("sparse encoded indices are truncated".to_string()))?;
+        *offset += 1;
+        value |= u64::from(byte & 0x7F)
+            .checked_shl(u32::try_from(shift).unwrap())
+            .unwrap_or_default();
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(CodecError::Other(
+        "sparse encoded index exceeds u64::MAX".to_string(),
+    ))
+}
+
+/// Returns the upper bound of the encoded size of `num_elements` elements of `element_size` bytes.
+fn encoded_size_bound(num_elements: u64, element_size: usize) -> u64 {
+    size_of::<u64>() as u64 + num_elements * (VARINT_MAX_LEN + element_size) as u64
+}
+
+fn encode_sparse_bytes(sparse: &SparseArrayBytes, data_type: &DataType) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(
+        size_of::<u64>() + sparse.num_stored() * VARINT_MAX_LEN + sparse.values().len(),
+    );
+    encoded.extend_from_slice(&(sparse.num_stored() as u64).to_le_bytes());
+    let mut previous = 0;
+    for &index in sparse.indices() {
+        write_varint(&mut encoded, index - previous);
+        previous = index;
+    }
+    let values_offset = encoded.len();
+    encoded.extend_from_slice(sparse.values());
+    if NATIVE_ENDIAN == Endianness::Big {
+        reverse_endianness(&mut encoded[values_offset..], data_type);
+    }
+    encoded
+}
+
+fn decode_sparse_bytes(
+    encoded: &[u8],
+    num_elements: u64,
+    data_type: &DataType,
+    element_size: usize,
+) -> Result<SparseArrayBytes, CodecError> {
+    let num_stored = encoded
+        .get(..size_of::<u64>())
+        .map(|num_stored| u64::from_le_bytes(num_stored.try_into().unwrap()))
+        .ok_or(CodecError::UnexpectedChunkDecodedSize(
+            encoded.len(),
+            size_of::<u64>() as u64,
+        ))?;
+    if num_stored > num_elements {
+        return Err(CodecError::Other(format!(
+            "sparse encoded chunk has {num_stored} stored elements, but the chunk only has {num_elements} elements"
+        )));
+    }
+    let num_stored = usize::try_from(num_stored).unwrap();
+
+    let mut offset = size_of::<u64>();
+    let mut indices = Vec::with_capacity(num_stored);
+    let mut index = 0u64;
+    for i in 0..num_stored {
+        let delta = read_varint(encoded, &mut offset)?;
+        if i > 0 && delta == 0 {
+            return Err(CodecError::Other(
+                "sparse encoded indices are not strictly increasing".to_string(),
+            ));
+        }
+        index = index.checked_add(delta).ok_or_else(|| {
+            CodecError::Other("sparse encoded index exceeds u64::MAX".to_string())
+        })?;
+        indices.push(index);
+    }
+
+    let values_len = num_stored * element_size;
+    if encoded.len() != offset + values_len {
+        return Err(CodecError::UnexpectedChunkDecodedSize(
+            encoded.len(),
+            (offset + values_len) as u64,
+        ));
+    }
+    let mut values = encoded[offset..].to_vec();
+    if NATIVE_ENDIAN == Endianness::Big {
+        reverse_endianness(&mut values, data_type);
+    }
+    SparseArrayBytes::new(num_elements, element_size, indices, values)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{num::NonZeroU64, sync::Arc};
+
+    use crate::{
+        array::{
+            codec::{ArrayToBytesCodecTraits, BytesToBytesCodecTraits, CodecOptions, CodecTraits},
+            transmute_to_bytes_vec, ArrayBytes, BytesRepresentation, ChunkRepresentation,
+            CodecChain, FillValue,
+        },
+        array_subset::ArraySubset,
+    };
+
+    use super::*;
+
+    fn chunk_representation() -> ChunkRepresentation {
+        ChunkRepresentation::new(
+            vec![NonZeroU64::new(4).unwrap(), NonZeroU64::new(300).unwrap()],
+            DataType::UInt16,
+            FillValue::from(0u16),
+        )
+        .unwrap()
+    }
+
+    fn elements() -> Vec<u16> {
+        let mut elements = vec![0u16; 4 * 300];
+        elements[3] = 1;
+        elements[299] = 2;
+        elements[300] = 3;
+        elements[1199] = u16::MAX;
+        elements
+    }
+
+    #[test]
+    fn codec_sparse_round_trip() {
+        let chunk_representation = chunk_representation();
+        let bytes: ArrayBytes = transmute_to_bytes_vec(elements()).into();
+        let codec = SparseCodec::new();
+
+        let encoded = codec
+            .encode(
+                bytes.clone(),
+                &chunk_representation,
+                &CodecOptions::default(),
+            )
+            .unwrap();
+        // 8 byte header, 4 varint index deltas (3, 296, 1, 899), 4 values
+        assert_eq!(encoded.len(), 8 + 6 + 4 * 2);
+        let BytesRepresentation::BoundedSize(bound) =
+            codec.compute_encoded_size(&chunk_representation).unwrap()
+        else {
+            panic!()
+        };
+        assert!(encoded.len() as u64 <= bound);
+
+        let decoded = codec
+            .decode(
+                encoded.clone(),
+                &chunk_representation,
+                &CodecOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(bytes, decoded);
+
+        let sparse = codec
+            .decode_sparse(encoded, &chunk_representation, &CodecOptions::default())
+            .unwrap();
+        assert_eq!(sparse.indices(), &[3, 299, 300, 1199]);
+        assert!(codec
+            .decode_sparse(
+                vec![1, 0, 0, 0, 0, 0, 0, 0, 3].into(),
+                &chunk_representation,
+                &CodecOptions::default()
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn codec_sparse_codec_chain() {
+        let chunk_representation = chunk_representation();
+        let bytes: ArrayBytes = transmute_to_bytes_vec(elements()).into();
+        let sparse =
+            SparseArrayBytes::from_array_bytes(&bytes, chunk_representation.fill_value()).unwrap();
+        let bytes_to_bytes: Vec<Box<dyn BytesToBytesCodecTraits>> = vec![
+            #[cfg(feature = "crc32c")]
+            Box::new(crate::array::codec::Crc32cCodec::new()),
+        ];
+        let codec_chain = CodecChain::new(vec![], Box::new(SparseCodec::new()), bytes_to_bytes);
+
+        let encoded = codec_chain
+            .encode_sparse(&sparse, &chunk_representation, &CodecOptions::default())
+            .unwrap();
+        let decoded = codec_chain
+            .decode_sparse(encoded, &chunk_representation, &CodecOptions::default())
+            .unwrap();
+        assert_eq!(sparse, decoded);
+    }
+
+    #[test]
+    fn codec_sparse_partial_decode() {
+        let chunk_representation = chunk_representation();
+        let elements = elements();
+        let bytes: ArrayBytes = transmute_to_bytes_vec(elements.clone()).into();
+        let codec = SparseCodec::new();
+        let encoded = codec
+            .encode(bytes, &chunk_representation, &CodecOptions::default())
+            .unwrap();
+
+        let decoded_regions = [
+            ArraySubset::new_with_ranges(&[0..2, 298..300]),
+            ArraySubset::new_with_ranges(&[1..2, 0..4]),
+            ArraySubset::new_with_ranges(&[3..4, 299..300]),
+        ];
+        let input_handle = Arc::new(std::io::Cursor::new(encoded));
+        let partial_decoder = codec
+            .partial_decoder(
+                input_handle,
+                &chunk_representation,
+                &CodecOptions::default(),
+            )
+            .unwrap();
+        let decoded_partial_chunk = partial_decoder
+            .partial_decode_opt(&decoded_regions, &CodecOptions::default())
+            .unwrap()
+            .into_iter()
+            .map(|bytes| {
+                crate::array::transmute_from_bytes_vec::<u16>(bytes.into_fixed().unwrap().to_vec())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            decoded_partial_chunk,
+            vec![vec![0, 2, 0, 0], vec![3, 0, 0, 0], vec![u16::MAX]]
+        );
+    }
+
+    #[test]
+    fn codec_sparse_unsupported_data_type() {
+        let chunk_representation = ChunkRepresentation::new(
+            vec![NonZeroU64::new(4).unwrap()],
+            DataType::String,
+            FillValue::from(""),
+        )
+        .unwrap();
+        assert!(SparseCodec::new()
+            .compute_encoded_size(&chunk_representation)
+            .is_err());
+        assert!(SparseCodec::new().create_metadata().is_some());
+    }
+}
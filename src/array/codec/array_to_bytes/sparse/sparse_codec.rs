@@ -0,0 +1,185 @@
+use std::sync::Arc;
+
+use crate::{
+    array::{
+        codec::{
+            ArrayCodecTraits, ArrayPartialDecoderTraits, ArrayToBytesCodecTraits,
+            BytesPartialDecoderTraits, CodecError, CodecOptions, CodecTraits,
+            RecommendedConcurrency,
+        },
+        ArrayBytes, ArrayMetadataOptions, BytesRepresentation, ChunkRepresentation, RawBytes,
+        SparseArrayBytes,
+    },
+    config::global_config,
+    metadata::v3::MetadataV3,
+};
+
+#[cfg(feature = "async")]
+use crate::array::codec::{AsyncArrayPartialDecoderTraits, AsyncBytesPartialDecoderTraits};
+
+use super::{
+    decode_sparse_bytes, encode_sparse_bytes, encoded_size_bound, SparseCodecConfiguration,
+    SparseCodecConfigurationV1,
+};
+
+/// The `sparse` codec implementation.
+#[derive(Debug, Clone, Default)]
+pub struct SparseCodec {}
+
+impl SparseCodec {
+    /// Create a new `sparse` codec.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Create a new `sparse` codec from configuration.
+    #[must_use]
+    pub fn new_with_configuration(_configuration: &SparseCodecConfiguration) -> Self {
+        Self {}
+    }
+}
+
+/// Returns the element size of a decoded representation, or an error if the data type is not fixed size.
+fn element_size(decoded_representation: &ChunkRepresentation) -> Result<usize, CodecError> {
+    decoded_representation
+        .data_type()
+        .fixed_size()
+        .ok_or_else(|| {
+            CodecError::UnsupportedDataType(
+                decoded_representation.data_type().clone(),
+                super::IDENTIFIER.to_string(),
+            )
+        })
+}
+
+impl CodecTraits for SparseCodec {
+    fn create_metadata_opt(&self, _options: &ArrayMetadataOptions) -> Option<MetadataV3> {
+        let configuration = SparseCodecConfigurationV1 {};
+        Some(
+            MetadataV3::new_with_serializable_configuration(
+                global_config()
+                    .experimental_codec_names()
+                    .get(super::IDENTIFIER)
+                    .expect("experimental codec identifier in global map"),
+                &configuration,
+            )
+            .unwrap(),
+        )
+    }
+
+    fn partial_decoder_should_cache_input(&self) -> bool {
+        false
+    }
+
+    fn partial_decoder_decodes_all(&self) -> bool {
+        true
+    }
+}
+
+impl ArrayCodecTraits for SparseCodec {
+    fn recommended_concurrency(
+        &self,
+        _decoded_representation: &ChunkRepresentation,
+    ) -> Result<RecommendedConcurrency, CodecError> {
+        Ok(RecommendedConcurrency::new_maximum(1))
+    }
+}
+
+#[cfg_attr(feature = "async", async_trait::async_trait)]
+impl ArrayToBytesCodecTraits for SparseCodec {
+    fn encode<'a>(
+        &self,
+        bytes: ArrayBytes<'a>,
+        decoded_representation: &ChunkRepresentation,
+        options: &CodecOptions,
+    ) -> Result<RawBytes<'a>, CodecError> {
+        element_size(decoded_representation)?;
+        bytes.validate(
+            decoded_representation.num_elements(),
+            decoded_representation.data_type().size(),
+        )?;
+        let sparse =
+            SparseArrayBytes::from_array_bytes(&bytes, decoded_representation.fill_value())?;
+        self.encode_sparse(&sparse, decoded_representation, options)
+    }
+
+    fn decode<'a>(
+        &self,
+        bytes: RawBytes<'a>,
+        decoded_representation: &ChunkRepresentation,
+        options: &CodecOptions,
+    ) -> Result<ArrayBytes<'a>, CodecError> {
+        self.decode_sparse(bytes, decoded_representation, options)?
+            .to_dense(decoded_representation.fill_value())
+    }
+
+    fn encode_sparse(
+        &self,
+        sparse: &SparseArrayBytes,
+        decoded_representation: &ChunkRepresentation,
+        _options: &CodecOptions,
+    ) -> Result<RawBytes<'static>, CodecError> {
+        sparse.validate(
+            decoded_representation.num_elements(),
+            element_size(decoded_representation)?,
+        )?;
+        Ok(encode_sparse_bytes(sparse, decoded_representation.data_type()).into())
+    }
+
+    fn decode_sparse(
+        &self,
+        bytes: RawBytes<'_>,
+        decoded_representation: &ChunkRepresentation,
+        _options: &CodecOptions,
+    ) -> Result<SparseArrayBytes, CodecError> {
+        decode_sparse_bytes(
+            &bytes,
+            decoded_representation.num_elements(),
+            decoded_representation.data_type(),
+            element_size(decoded_representation)?,
+        )
+    }
+
+    fn partial_decoder<'a>(
+        &self,
+        input_handle: Arc<dyn BytesPartialDecoderTraits + 'a>,
+        decoded_representation: &ChunkRepresentation,
+        _options: &CodecOptions,
+    ) -> Result<Arc<dyn ArrayPartialDecoderTraits + 'a>, CodecError> {
+        element_size(decoded_representation)?;
+        Ok(Arc::new(
+            super::sparse_partial_decoder::SparsePartialDecoder::new(
+                input_handle,
+                decoded_representation.clone(),
+            ),
+        ))
+    }
+
+    #[cfg(feature = "async")]
+    async fn async_partial_decoder<'a>(
+        &'a self,
+        input_handle: Arc<dyn AsyncBytesPartialDecoderTraits + 'a>,
+        decoded_representation: &ChunkRepresentation,
+        _options: &CodecOptions,
+    ) -> Result<Arc<dyn AsyncArrayPartialDecoderTraits + 'a>, CodecError> {
+        element_size(decoded_representation)?;
+        Ok(Arc::new(
+            super::sparse_partial_decoder::AsyncSparsePartialDecoder::new(
+                input_handle,
+                decoded_representation.clone(),
+            ),
+        ))
+    }
+
+    fn compute_encoded_size(
+        &self,
+        decoded_representation: &ChunkRepresentation,
+    ) -> Result<BytesRepresentation, CodecError> {
+        let element_size = element_size(decoded_representation)?;
+        Ok(BytesRepresentation::BoundedSize(encoded_size_bound(
+            decoded_representation.num_elements(),
+            element_size,
+        )))
+    }
+}
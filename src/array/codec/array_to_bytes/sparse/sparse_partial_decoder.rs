@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use crate::array::{
+    codec::{
+        ArrayPartialDecoderTraits, ArraySubset, BytesPartialDecoderTraits, CodecError, CodecOptions,
+    },
+    ArrayBytes, ArraySize, ChunkRepresentation, DataType, RawBytes,
+};
+
+#[cfg(feature = "async")]
+use crate::array::codec::{AsyncArrayPartialDecoderTraits, AsyncBytesPartialDecoderTraits};
+
+/// Partial decoder for the `sparse` codec.
+pub struct SparsePartialDecoder<'a> {
+    input_handle: Arc<dyn BytesPartialDecoderTraits + 'a>,
+    decoded_representation: ChunkRepresentation,
+}
+
+impl<'a> SparsePartialDecoder<'a> {
+    /// Create a new partial decoder for the `sparse` codec.
+    pub fn new(
+        input_handle: Arc<dyn BytesPartialDecoderTraits + 'a>,
+        decoded_representation: ChunkRepresentation,
+    ) -> Self {
+        Self {
+            input_handle,
+            decoded_representation,
+        }
+    }
+}
+
+fn do_partial_decode<'a>(
+    encoded: Option<RawBytes>,
+    decoded_regions: &[ArraySubset],
+    decoded_representation: &ChunkRepresentation,
+) -> Result<Vec<ArrayBytes<'a>>, CodecError> {
+    let fill_value = decoded_representation.fill_value();
+    let Some(encoded) = encoded else {
+        // Chunk is empty, all decoded regions are the fill value
+        return Ok(decoded_regions
+            .iter()
+            .map(|decoded_region| {
+                let array_size = ArraySize::new(
+                    decoded_representation.data_type().size(),
+                    decoded_region.num_elements(),
+                );
+                ArrayBytes::new_fill_value(array_size, fill_value)
+            })
+            .collect());
+    };
+
+    // The chunk is decoded to its sparse representation, so it is never densified
+    let sparse = super::decode_sparse_bytes(
+        &encoded,
+        decoded_representation.num_elements(),
+        decoded_representation.data_type(),
+        fill_value.size(),
+    )?;
+    let chunk_shape = decoded_representation.shape_u64();
+    decoded_regions
+        .iter()
+        .map(|decoded_region| sparse.extract_array_subset(decoded_region, &chunk_shape, fill_value))
+        .collect()
+}
+
+impl ArrayPartialDecoderTraits for SparsePartialDecoder<'_> {
+    fn data_type(&self) -> &DataType {
+        self.decoded_representation.data_type()
+    }
+
+    fn partial_decode_opt(
+        &self,
+        decoded_regions: &[ArraySubset],
+        options: &CodecOptions,
+    ) -> Result<Vec<ArrayBytes<'_>>, CodecError> {
+        let encoded = self.input_handle.decode(options)?;
+        do_partial_decode(encoded, decoded_regions, &self.decoded_representation)
+    }
+}
+
+#[cfg(feature = "async")]
+/// Asynchronous partial decoder for the `sparse` codec.
+pub struct AsyncSparsePartialDecoder<'a> {
+    input_handle: Arc<dyn AsyncBytesPartialDecoderTraits + 'a>,
+    decoded_representation: ChunkRepresentation,
+}
+
+#[cfg(feature = "async")]
+impl<'a> AsyncSparsePartialDecoder<'a> {
+    /// Create a new partial decoder for the `sparse` codec.
+    pub fn new(
+        input_handle: Arc<dyn AsyncBytesPartialDecoderTraits + 'a>,
+        decoded_representation: ChunkRepresentation,
+    ) -> Self {
+        Self {
+            input_handle,
+            decoded_representation,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl AsyncArrayPartialDecoderTraits for AsyncSparsePartialDecoder<'_> {
+    fn data_type(&self) -> &DataType {
+        self.decoded_representation.data_type()
+    }
+
+    async fn partial_decode_opt(
+        &self,
+        decoded_regions: &[ArraySubset],
+        options: &CodecOptions,
+    ) -> Result<Vec<ArrayBytes<'_>>, CodecError> {
+        let encoded = self.input_handle.decode(options).await?;
+        do_partial_decode(encoded, decoded_regions, &self.decoded_representation)
+    }
+}
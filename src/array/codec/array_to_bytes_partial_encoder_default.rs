@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use crate::{
+    array::{array_bytes::update_array_bytes, ArrayBytes, ChunkRepresentation, DataType},
+    array_subset::{ArraySubset, IncompatibleArraySubsetAndShapeError},
+};
+
+use super::{
+    ArrayPartialEncoderTraits, ArrayToBytesCodecTraits, BytesPartialDecoderTraits,
+    BytesPartialEncoderTraits, CodecError, CodecOptions,
+};
+
+/// The default array to bytes partial encoder.
+///
+/// Decodes the entire chunk from the input handle, updates it, and then encodes the entire chunk to the output handle.
+/// The chunk is erased instead if it is entirely the fill value and empty chunks are not stored.
+pub struct ArrayToBytesPartialEncoderDefault<'a, T: ?Sized> {
+    input_handle: Arc<dyn BytesPartialDecoderTraits + 'a>,
+    output_handle: Arc<dyn BytesPartialEncoderTraits + 'a>,
+    decoded_representation: ChunkRepresentation,
+    codec: &'a T,
+}
+
+impl<'a, T: ?Sized> ArrayToBytesPartialEncoderDefault<'a, T> {
+    /// Create a new default array to bytes partial encoder.
+    pub fn new(
+        input_handle: Arc<dyn BytesPartialDecoderTraits + 'a>,
+        output_handle: Arc<dyn BytesPartialEncoderTraits + 'a>,
+        decoded_representation: ChunkRepresentation,
+        codec: &'a T,
+    ) -> Self {
+        Self {
+            input_handle,
+            output_handle,
+            decoded_representation,
+            codec,
+        }
+    }
+}
+
+impl<T: ?Sized + ArrayToBytesCodecTraits> ArrayPartialEncoderTraits
+    for ArrayToBytesPartialEncoderDefault<'_, T>
+{
+    fn data_type(&self) -> &DataType {
+        self.decoded_representation.data_type()
+    }
+
+    fn erase(&self) -> Result<(), CodecError> {
+        self.output_handle.erase()
+    }
+
+    fn partial_encode_opt(
+        &self,
+        subsets_and_bytes: &[(&ArraySubset, ArrayBytes<'_>)],
+        options: &CodecOptions,
+    ) -> Result<(), CodecError> {
+        let chunk_shape = self.decoded_representation.shape_u64();
+        let data_type_size = self.decoded_representation.data_type().size();
+        for (subset, bytes) in subsets_and_bytes {
+            if !subset.inbounds(&chunk_shape) {
+                return Err(IncompatibleArraySubsetAndShapeError::new(
+                    (*subset).clone(),
+                    chunk_shape,
+                )
+                .into());
+            }
+            bytes.validate(subset.num_elements(), data_type_size)?;
+        }
+
+        // Decode the entire chunk
+        let encoded_value = self.input_handle.decode(options)?;
+        let mut decoded_value = match encoded_value {
+            Some(encoded_value) => self
+                .codec
+                .decode(encoded_value, &self.decoded_representation, options)?
+                .into_owned(),
+            None => ArrayBytes::new_fill_value(
+                self.decoded_representation.size(),
+                self.decoded_representation.fill_value(),
+            ),
+        };
+        decoded_value.validate(self.decoded_representation.num_elements(), data_type_size)?;
+
+        // Update the chunk
+        for (subset, bytes) in subsets_and_bytes {
+            decoded_value = update_array_bytes(
+                decoded_value,
+                chunk_shape.clone(),
+                bytes,
+                subset,
+                data_type_size,
+            );
+        }
+
+        // Encode the entire chunk
+        if !options.store_empty_chunks()
+            && decoded_value.is_fill_value(self.decoded_representation.fill_value())
+        {
+            self.output_handle.erase()
+        } else {
+            let encoded_value =
+                self.codec
+                    .encode(decoded_value, &self.decoded_representation, options)?;
+            self.output_handle.encode(encoded_value, options)
+        }
+    }
+}
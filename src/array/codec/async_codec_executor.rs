@@ -0,0 +1,108 @@
+//! Executors for codec encoding and decoding in async array operations.
+
+use std::sync::Arc;
+
+use futures::{channel::oneshot, future::BoxFuture};
+
+/// A blocking task to be run by an [`AsyncSpawnBlockingFn`].
+pub type AsyncBlockingTask = Box<dyn FnOnce() + Send + 'static>;
+
+/// A function that runs a blocking task on a thread where blocking is acceptable, returning a future that completes when the task has run.
+///
+/// For example, with `tokio`:
+/// ```rust
+/// # use std::sync::Arc;
+/// # use zarrs::array::codec::{AsyncCodecExecutor, AsyncSpawnBlockingFn};
+/// let spawn_blocking: AsyncSpawnBlockingFn = Arc::new(|task| {
+///     Box::pin(async move {
+///         let _ = tokio::task::spawn_blocking(task).await;
+///     })
+/// });
+/// let executor = AsyncCodecExecutor::SpawnBlocking(spawn_blocking);
+/// ```
+pub type AsyncSpawnBlockingFn =
+    Arc<dyn Fn(AsyncBlockingTask) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// The executor of codec encoding and decoding in async array operations.
+///
+/// Encoding and decoding can be CPU intensive (e.g. with `blosc` or `zstd`), and running them inline stalls the async runtime.
+/// An executor other than [`Inline`](AsyncCodecExecutor::Inline) moves chunk encoding and decoding off the async runtime.
+/// This requires that chunk bytes are owned, so it may incur an additional copy for stores.
+///
+/// Partial decoding runs inline regardless of the executor.
+#[derive(Clone, Default)]
+pub enum AsyncCodecExecutor {
+    /// Run codecs inline in the calling future.
+    #[default]
+    Inline,
+    /// Run codecs on a dedicated [`rayon`] thread pool.
+    RayonPool(Arc<rayon::ThreadPool>),
+    /// Run codecs with a user supplied spawn blocking function, such as [`tokio::task::spawn_blocking`](https://docs.rs/tokio/latest/tokio/task/fn.spawn_blocking.html).
+    SpawnBlocking(AsyncSpawnBlockingFn),
+}
+
+impl core::fmt::Debug for AsyncCodecExecutor {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Inline => write!(f, "Inline"),
+            Self::RayonPool(thread_pool) => f
+                .debug_tuple("RayonPool")
+                .field(&thread_pool.current_num_threads())
+                .finish(),
+            Self::SpawnBlocking(_) => write!(f, "SpawnBlocking"),
+        }
+    }
+}
+
+impl AsyncCodecExecutor {
+    /// Returns true if the executor runs codecs inline.
+    #[must_use]
+    pub const fn is_inline(&self) -> bool {
+        matches!(self, Self::Inline)
+    }
+
+    /// Run `task` with the executor and return its output.
+    ///
+    /// # Panics
+    /// Resumes the panic if `task` panics.
+    /// Panics if a [`SpawnBlocking`](AsyncCodecExecutor::SpawnBlocking) function does not run the task.
+    pub async fn execute<T: Send + 'static>(&self, task: impl FnOnce() -> T + Send + 'static) -> T {
+        match self {
+            Self::Inline => task(),
+            Self::RayonPool(thread_pool) => {
+                let (sender, receiver) = oneshot::channel();
+                thread_pool.spawn(catch_unwind_into(task, sender));
+                receive(receiver).await
+            }
+            Self::SpawnBlocking(spawn_blocking) => {
+                let (sender, receiver) = oneshot::channel();
+                spawn_blocking(Box::new(catch_unwind_into(task, sender))).await;
+                receive(receiver).await
+            }
+        }
+    }
+}
+
+type TaskOutput<T> = std::thread::Result<T>;
+
+/// Wrap `task` so that its output or panic is sent to `sender`.
+fn catch_unwind_into<T: Send + 'static>(
+    task: impl FnOnce() -> T + Send + 'static,
+    sender: oneshot::Sender<TaskOutput<T>>,
+) -> impl FnOnce() + Send + 'static {
+    move || {
+        let output = std::panic::catch_unwind(std::panic::AssertUnwindSafe(task));
+        let _ = sender.send(output);
+    }
+}
+
+/// Receive the output of a task, resuming its panic.
+async fn receive<T>(receiver: oneshot::Receiver<TaskOutput<T>>) -> T {
+    match receiver
+        .await
+        .expect("the spawn blocking function should run the task")
+    {
+        Ok(output) => output,
+        Err(panic) => std::panic::resume_unwind(panic),
+    }
+}
@@ -85,23 +85,26 @@ impl BytesToBytesCodecTraits for Bz2Codec {
     fn encode<'a>(
         &self,
         decoded_value: RawBytes<'a>,
-        _options: &CodecOptions,
+        options: &CodecOptions,
     ) -> Result<RawBytes<'a>, CodecError> {
         let mut encoder = bzip2::read::BzEncoder::new(Cursor::new(decoded_value), self.compression);
-        let mut out: Vec<u8> = Vec::new();
+        let mut out = options.take_buffer(0);
         encoder.read_to_end(&mut out)?;
+        options.recycle_bytes(encoder.into_inner().into_inner());
         Ok(Cow::Owned(out))
     }
 
     fn decode<'a>(
         &self,
         encoded_value: RawBytes<'a>,
-        _decoded_representation: &BytesRepresentation,
-        _options: &CodecOptions,
+        decoded_representation: &BytesRepresentation,
+        options: &CodecOptions,
     ) -> Result<RawBytes<'a>, CodecError> {
         let mut decoder = bzip2::read::BzDecoder::new(Cursor::new(encoded_value));
-        let mut out: Vec<u8> = Vec::new();
+        let mut out = options
+            .take_buffer(usize::try_from(decoded_representation.size().unwrap_or(0)).unwrap_or(0));
         decoder.read_to_end(&mut out)?;
+        options.recycle_bytes(decoder.into_inner().into_inner());
         Ok(Cow::Owned(out))
     }
 
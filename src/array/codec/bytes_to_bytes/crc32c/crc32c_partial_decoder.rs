@@ -13,6 +13,37 @@ use crate::array::codec::AsyncBytesPartialDecoderTraits;
 
 use super::CHECKSUM_SIZE;
 
+/// Map decoded byte ranges to encoded byte ranges, skipping the trailing checksum for ranges relative to the end.
+fn encoded_regions(decoded_regions: &[ByteRange]) -> Vec<ByteRange> {
+    decoded_regions
+        .iter()
+        .map(|byte_range| match byte_range {
+            ByteRange::FromStart(_, _) => *byte_range,
+            ByteRange::FromEnd(offset, length) => {
+                ByteRange::FromEnd(offset + CHECKSUM_SIZE as u64, *length)
+            }
+        })
+        .collect()
+}
+
+/// Drop the trailing checksum from bytes read to the end of the encoded bytes.
+fn strip_checksum<'a>(
+    bytes: Vec<RawBytes<'a>>,
+    decoded_regions: &[ByteRange],
+) -> Vec<RawBytes<'a>> {
+    bytes
+        .into_iter()
+        .zip(decoded_regions)
+        .map(|(bytes, byte_range)| match byte_range {
+            ByteRange::FromStart(_, None) => {
+                let length = bytes.len().saturating_sub(CHECKSUM_SIZE);
+                Cow::Owned(bytes[..length].to_vec())
+            }
+            ByteRange::FromStart(_, Some(_)) | ByteRange::FromEnd(_, _) => bytes,
+        })
+        .collect()
+}
+
 /// Partial decoder for the `crc32c` (CRC32C checksum) codec.
 pub struct Crc32cPartialDecoder<'a> {
     input_handle: Arc<dyn BytesPartialDecoderTraits + 'a>,
@@ -31,34 +62,15 @@ impl BytesPartialDecoderTraits for Crc32cPartialDecoder<'_> {
         decoded_regions: &[ByteRange],
         options: &CodecOptions,
     ) -> Result<Option<Vec<RawBytes<'_>>>, CodecError> {
-        let bytes = self.input_handle.partial_decode(decoded_regions, options)?;
+        let encoded_regions = encoded_regions(decoded_regions);
+        let bytes = self
+            .input_handle
+            .partial_decode(&encoded_regions, options)?;
         let Some(bytes) = bytes else {
             return Ok(None);
         };
 
-        // Drop trailing checksum
-        let mut output = Vec::with_capacity(bytes.len());
-        for (bytes, byte_range) in bytes.into_iter().zip(decoded_regions) {
-            let bytes = match byte_range {
-                ByteRange::FromStart(_, Some(_)) => bytes,
-                ByteRange::FromStart(_, None) => {
-                    let length = bytes.len() - CHECKSUM_SIZE;
-                    Cow::Owned(bytes[..length].to_vec())
-                }
-                ByteRange::FromEnd(offset, _) => {
-                    if *offset < CHECKSUM_SIZE as u64 {
-                        let length = bytes.len() as u64 - (CHECKSUM_SIZE as u64 - offset);
-                        let length = usize::try_from(length).unwrap();
-                        Cow::Owned(bytes[..length].to_vec())
-                    } else {
-                        bytes
-                    }
-                }
-            };
-            output.push(bytes);
-        }
-
-        Ok(Some(output))
+        Ok(Some(strip_checksum(bytes, decoded_regions)))
     }
 }
 
@@ -84,36 +96,15 @@ impl AsyncBytesPartialDecoderTraits for AsyncCrc32cPartialDecoder<'_> {
         decoded_regions: &[ByteRange],
         options: &CodecOptions,
     ) -> Result<Option<Vec<RawBytes<'_>>>, CodecError> {
+        let encoded_regions = encoded_regions(decoded_regions);
         let bytes = self
             .input_handle
-            .partial_decode(decoded_regions, options)
+            .partial_decode(&encoded_regions, options)
             .await?;
         let Some(bytes) = bytes else {
             return Ok(None);
         };
 
-        // Drop trailing checksum
-        let mut output = Vec::with_capacity(bytes.len());
-        for (bytes, byte_range) in bytes.into_iter().zip(decoded_regions) {
-            let bytes = match byte_range {
-                ByteRange::FromStart(_, Some(_)) => bytes,
-                ByteRange::FromStart(_, None) => {
-                    let length = bytes.len() - CHECKSUM_SIZE;
-                    Cow::Owned(bytes[..length].to_vec())
-                }
-                ByteRange::FromEnd(offset, _) => {
-                    if *offset < CHECKSUM_SIZE as u64 {
-                        let length = bytes.len() as u64 - (CHECKSUM_SIZE as u64 - offset);
-                        let length = usize::try_from(length).unwrap();
-                        Cow::Owned(bytes[..length].to_vec())
-                    } else {
-                        bytes
-                    }
-                }
-            };
-            output.push(bytes);
-        }
-
-        Ok(Some(output))
+        Ok(Some(strip_checksum(bytes, decoded_regions)))
     }
 }
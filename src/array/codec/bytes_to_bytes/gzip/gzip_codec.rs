@@ -80,26 +80,29 @@ impl BytesToBytesCodecTraits for GzipCodec {
     fn encode<'a>(
         &self,
         decoded_value: RawBytes<'a>,
-        _options: &CodecOptions,
+        options: &CodecOptions,
     ) -> Result<RawBytes<'a>, CodecError> {
         let mut encoder = GzEncoder::new(
             Cursor::new(decoded_value),
             flate2::Compression::new(self.compression_level.as_u32()),
         );
-        let mut out: Vec<u8> = Vec::new();
+        let mut out = options.take_buffer(0);
         encoder.read_to_end(&mut out)?;
+        options.recycle_bytes(encoder.into_inner().into_inner());
         Ok(Cow::Owned(out))
     }
 
     fn decode<'a>(
         &self,
         encoded_value: RawBytes<'a>,
-        _decoded_representation: &BytesRepresentation,
-        _options: &CodecOptions,
+        decoded_representation: &BytesRepresentation,
+        options: &CodecOptions,
     ) -> Result<RawBytes<'a>, CodecError> {
         let mut decoder = GzDecoder::new(Cursor::new(encoded_value));
-        let mut out: Vec<u8> = Vec::new();
+        let mut out = options
+            .take_buffer(usize::try_from(decoded_representation.size().unwrap_or(0)).unwrap_or(0));
         decoder.read_to_end(&mut out)?;
+        options.recycle_bytes(decoder.into_inner().into_inner());
         Ok(Cow::Owned(out))
     }
 
@@ -135,7 +138,7 @@ impl BytesToBytesCodecTraits for GzipCodec {
                 const HEADER_TRAILER_OVERHEAD: u64 = 10 + 8; // TODO: validate that extra headers are not populated
                 const BLOCK_SIZE: u64 = 32768;
                 const BLOCK_OVERHEAD: u64 = 5;
-                let blocks_overhead = BLOCK_OVERHEAD * size.div_ceil(BLOCK_SIZE);
+                let blocks_overhead = BLOCK_OVERHEAD * size.div_ceil(BLOCK_SIZE).max(1);
                 BytesRepresentation::BoundedSize(size + HEADER_TRAILER_OVERHEAD + blocks_overhead)
             })
     }
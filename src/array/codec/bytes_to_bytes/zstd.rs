@@ -43,7 +43,7 @@ mod tests {
 
     use crate::{
         array::{
-            codec::{BytesToBytesCodecTraits, CodecOptions},
+            codec::{BytesToBytesCodecTraits, CodecOptions, CodecScratch},
             BytesRepresentation,
         },
         byte_range::ByteRange,
@@ -75,6 +75,35 @@ mod tests {
         assert_eq!(bytes, decoded.to_vec());
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn codec_zstd_round_trip_scratch() {
+        let elements: Vec<u16> = (0..32).collect();
+        let bytes = crate::array::transmute_to_bytes_vec(elements);
+        let bytes_representation = BytesRepresentation::FixedSize(bytes.len() as u64);
+
+        let configuration: ZstdCodecConfiguration = serde_json::from_str(JSON_VALID).unwrap();
+        let codec = ZstdCodec::new_with_configuration(&configuration);
+
+        let codec_scratch = CodecScratch::new();
+        let options = CodecOptions::builder()
+            .codec_scratch(Some(codec_scratch.clone()))
+            .build();
+        for _ in 0..2 {
+            let encoded = codec.encode(Cow::Borrowed(&bytes), &options).unwrap();
+            let decoded = codec
+                .decode(encoded.clone(), &bytes_representation, &options)
+                .unwrap();
+            assert_eq!(bytes, decoded.to_vec());
+            let decoded = codec
+                .decode(encoded, &bytes_representation, &CodecOptions::default())
+                .unwrap();
+            assert_eq!(bytes, decoded.to_vec());
+        }
+        // One compression and one decompression context
+        assert_eq!(codec_scratch.len(), 2);
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn codec_zstd_partial_decode() {
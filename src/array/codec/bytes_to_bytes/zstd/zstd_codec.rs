@@ -77,9 +77,28 @@ impl BytesToBytesCodecTraits for ZstdCodec {
     fn encode<'a>(
         &self,
         decoded_value: RawBytes<'a>,
-        _options: &CodecOptions,
+        options: &CodecOptions,
     ) -> Result<RawBytes<'a>, CodecError> {
-        let mut result = Vec::<u8>::new();
+        if let Some(codec_scratch) = options.codec_scratch() {
+            // Reuse a compression context
+            let result = codec_scratch.with_state(
+                || zstd::bulk::Compressor::new(self.compression),
+                |compressor| {
+                    compressor.set_compression_level(self.compression)?;
+                    compressor.set_parameter(zstd::stream::raw::CParameter::ChecksumFlag(
+                        self.checksum,
+                    ))?;
+                    let mut result =
+                        options.take_buffer(zstd_safe::compress_bound(decoded_value.len()));
+                    compressor.compress_to_buffer(&decoded_value, &mut result)?;
+                    Ok::<_, std::io::Error>(result)
+                },
+            )?;
+            options.recycle_bytes(decoded_value);
+            return Ok(Cow::Owned(result));
+        }
+
+        let mut result = options.take_buffer(0);
         let mut encoder = zstd::Encoder::new(&mut result, self.compression)?;
         encoder.include_checksum(self.checksum)?;
         // if parallel {
@@ -88,18 +107,35 @@ impl BytesToBytesCodecTraits for ZstdCodec {
         // }
         std::io::copy(&mut std::io::Cursor::new(&decoded_value), &mut encoder)?;
         encoder.finish()?;
+        options.recycle_bytes(decoded_value);
         Ok(Cow::Owned(result))
     }
 
     fn decode<'a>(
         &self,
         encoded_value: RawBytes<'a>,
-        _decoded_representation: &BytesRepresentation,
-        _options: &CodecOptions,
+        decoded_representation: &BytesRepresentation,
+        options: &CodecOptions,
     ) -> Result<RawBytes<'a>, CodecError> {
-        zstd::decode_all(std::io::Cursor::new(&encoded_value))
-            .map_err(CodecError::IOError)
-            .map(Cow::Owned)
+        if let (Some(codec_scratch), Some(size)) =
+            (options.codec_scratch(), decoded_representation.size())
+        {
+            // Reuse a decompression context
+            let result =
+                codec_scratch.with_state(zstd::bulk::Decompressor::new, |decompressor| {
+                    let mut result = options.take_buffer(usize::try_from(size).unwrap());
+                    decompressor.decompress_to_buffer(&encoded_value, &mut result)?;
+                    Ok::<_, std::io::Error>(result)
+                })?;
+            options.recycle_bytes(encoded_value);
+            return Ok(Cow::Owned(result));
+        }
+
+        let mut result = options
+            .take_buffer(usize::try_from(decoded_representation.size().unwrap_or(0)).unwrap_or(0));
+        zstd::stream::copy_decode(std::io::Cursor::new(&encoded_value), &mut result)?;
+        options.recycle_bytes(encoded_value);
+        Ok(Cow::Owned(result))
     }
 
     fn partial_decoder<'a>(
@@ -0,0 +1,129 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+
+/// Codec states keyed by type.
+type CodecStates = HashMap<TypeId, Vec<Box<dyn Any + Send>>>;
+
+/// Reusable codec state, such as compression and decompression contexts.
+///
+/// Codec scratch space is set on [`CodecOptions`](crate::array::codec::CodecOptions) with [`set_codec_scratch`](crate::array::codec::CodecOptions::set_codec_scratch).
+/// Codecs supporting reusable state take a state of their chosen type from the scratch space for the duration of an encode or decode and then return it, rather than initialising new state for every chunk.
+/// Each concurrent codec operation (typically one per thread) takes its own state, so states are never shared between threads at the same time.
+///
+/// Clones of a codec scratch space share the same states.
+#[derive(Clone, Default)]
+pub struct CodecScratch {
+    states: Arc<Mutex<CodecStates>>,
+}
+
+impl core::fmt::Debug for CodecScratch {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CodecScratch")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl CodecScratch {
+    /// Create a new empty codec scratch space.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `f` with a state of type `T` from the scratch space, initialising a new state with `init` if none is available.
+    ///
+    /// The state is returned to the scratch space if `f` succeeds.
+    /// It is dropped if `f` fails, as it may have been left in an inconsistent state.
+    ///
+    /// # Errors
+    /// Returns an error if `init` or `f` fails.
+    pub fn with_state<T: Any + Send, R, E>(
+        &self,
+        init: impl FnOnce() -> Result<T, E>,
+        f: impl FnOnce(&mut T) -> Result<R, E>,
+    ) -> Result<R, E> {
+        let state = self
+            .states
+            .lock()
+            .get_mut(&TypeId::of::<T>())
+            .and_then(Vec::pop);
+        let mut state = match state.map(<Box<dyn Any + Send>>::downcast::<T>) {
+            Some(Ok(state)) => state,
+            Some(Err(_)) => unreachable!("codec scratch states are keyed by type"),
+            None => Box::new(init()?),
+        };
+        let result = f(&mut state)?;
+        self.states
+            .lock()
+            .entry(TypeId::of::<T>())
+            .or_default()
+            .push(state);
+        Ok(result)
+    }
+
+    /// Return the number of states retained by the scratch space.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.states.lock().values().map(Vec::len).sum()
+    }
+
+    /// Returns true if the scratch space does not retain any states.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop all states retained by the scratch space.
+    pub fn clear(&self) {
+        self.states.lock().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codec_scratch() {
+        let scratch = CodecScratch::new();
+        assert!(scratch.is_empty());
+
+        let initialised = std::cell::Cell::new(0);
+        let init = || {
+            initialised.set(initialised.get() + 1);
+            Ok::<_, ()>(0u32)
+        };
+        for i in 1..=3 {
+            let state = scratch.with_state(init, |state: &mut u32| {
+                *state += 1;
+                Ok(*state)
+            });
+            assert_eq!(state, Ok(i));
+        }
+        assert_eq!(initialised.get(), 1);
+        assert_eq!(scratch.len(), 1);
+
+        // States of different types are distinct
+        scratch
+            .with_state(|| Ok::<_, ()>(String::new()), |_| Ok(()))
+            .unwrap();
+        assert_eq!(scratch.len(), 2);
+
+        // A state is dropped if it fails
+        assert_eq!(
+            scratch.with_state(init, |_: &mut u32| Err::<(), _>(())),
+            Err(())
+        );
+        assert_eq!(scratch.len(), 1);
+        assert_eq!(initialised.get(), 1);
+
+        scratch.clear();
+        assert!(scratch.is_empty());
+    }
+}
@@ -0,0 +1,580 @@
+//! A conformance test suite for codec implementations.
+//!
+//! Codecs that do not behave as expected can corrupt data in ways that are only detected long after it has been written, so codec implementors are encouraged to run these tests against their codecs.
+//!
+//! The tests for array to bytes codecs (including a [`CodecChain`]) cover:
+//!  - encoding and decoding round trips ([`round_trip`]),
+//!  - partial decoding equivalence with a full decode ([`partial_decoding`]),
+//!  - fill value and missing chunks ([`empty_chunk`]), and
+//!  - concurrent encoding and decoding ([`concurrency`]).
+//!
+//! [`all`] runs every array to bytes codec test.
+//! [`array_to_array_all`] runs every test against an array to array codec in a [`CodecChain`] with the [`BytesCodec`].
+//! The `bytes_` tests and [`bytes_all`] are the bytes to bytes codec equivalents.
+//!
+//! Each test encodes a caller supplied chunk, so a codec can be tested with any data type and chunk shape it supports.
+//! Round trips must be exact, so lossy codecs should be tested with data they can represent exactly.
+//! A test panics if the codec does not behave as expected, and returns an error if a codec operation fails unexpectedly.
+//!
+//! This module requires the `codec_test_suite` feature.
+//!
+//! ### Example
+//! ```rust
+//! # use std::num::NonZeroU64;
+//! # use zarrs::array::{ArrayBytes, ChunkRepresentation, DataType, codec::BytesCodec};
+//! # #[cfg(feature = "codec_test_suite")]
+//! # {
+//! let shape = vec![NonZeroU64::new(4).unwrap(); 2];
+//! let chunk_representation = ChunkRepresentation::new(shape, DataType::UInt8, 0u8.into())?;
+//! let bytes = ArrayBytes::new_flen((0..16).collect::<Vec<u8>>());
+//! zarrs::array::codec::codec_test_suite::all(&BytesCodec::default(), &chunk_representation, &bytes)?;
+//! # }
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use std::{error::Error, sync::Arc};
+
+use crate::{
+    array::{ArrayBytes, ArraySize, BytesRepresentation, ChunkRepresentation, RawBytes},
+    array_subset::ArraySubset,
+    byte_range::{extract_byte_ranges, ByteRange},
+};
+
+use super::{
+    ArrayToArrayCodecTraits, ArrayToBytesCodecTraits, BytesCodec, BytesPartialDecoderTraits,
+    BytesToBytesCodecTraits, CodecChain, CodecError, CodecOptions,
+};
+
+/// The number of concurrent threads in the [`concurrency`] and [`bytes_concurrency`] tests.
+const CONCURRENCY: usize = 8;
+
+/// The number of round trips by each concurrent thread in the [`concurrency`] and [`bytes_concurrency`] tests.
+const CONCURRENCY_ITERATIONS: usize = 16;
+
+/// A bytes partial decoder for a missing chunk.
+struct MissingBytesPartialDecoder;
+
+impl BytesPartialDecoderTraits for MissingBytesPartialDecoder {
+    fn partial_decode(
+        &self,
+        _decoded_regions: &[ByteRange],
+        _options: &CodecOptions,
+    ) -> Result<Option<Vec<RawBytes<'_>>>, CodecError> {
+        Ok(None)
+    }
+}
+
+/// Return the array subsets of a chunk with `shape` used by the partial decoding tests.
+///
+/// These are the whole chunk, the first and last elements, and an interior region.
+fn partial_decoding_subsets(shape: &[u64]) -> Vec<ArraySubset> {
+    let whole = shape.iter().map(|size| 0..*size).collect::<Vec<_>>();
+    let first = shape.iter().map(|_| 0..1).collect::<Vec<_>>();
+    let last = shape.iter().map(|size| size - 1..*size).collect::<Vec<_>>();
+    let interior = shape
+        .iter()
+        .map(|size| {
+            let start = size / 4;
+            start..(size * 3 / 4).max(start + 1)
+        })
+        .collect::<Vec<_>>();
+    [whole, first, last, interior]
+        .iter()
+        .map(|ranges| ArraySubset::new_with_ranges(ranges))
+        .collect()
+}
+
+/// Return the byte ranges of a value with `size` bytes used by the partial decoding tests.
+///
+/// These are the whole value, an interior range, and a suffix.
+fn partial_decoding_byte_ranges(size: u64) -> Vec<ByteRange> {
+    vec![
+        ByteRange::FromStart(0, None),
+        ByteRange::FromStart(size / 4, Some(size / 2)),
+        ByteRange::FromEnd(0, Some(size / 3)),
+    ]
+}
+
+/// Check that an encoded value of `size` bytes is consistent with the `encoded_representation` of a codec.
+fn check_encoded_size(size: usize, encoded_representation: BytesRepresentation) {
+    let size = size as u64;
+    match encoded_representation {
+        BytesRepresentation::FixedSize(expected) => assert_eq!(
+            size, expected,
+            "the encoded size must match a fixed encoded representation"
+        ),
+        BytesRepresentation::BoundedSize(bound) => assert!(
+            size <= bound,
+            "the encoded size {size} must not exceed a bounded encoded representation of {bound}"
+        ),
+        BytesRepresentation::UnboundedSize => {}
+    }
+}
+
+/// Run every array to bytes codec test in the codec test suite with the chunk `bytes`.
+///
+/// # Errors
+/// Returns an error if a codec operation fails unexpectedly.
+///
+/// # Panics
+/// Panics if the codec does not behave as expected.
+pub fn all(
+    codec: &dyn ArrayToBytesCodecTraits,
+    decoded_representation: &ChunkRepresentation,
+    bytes: &ArrayBytes,
+) -> Result<(), Box<dyn Error>> {
+    round_trip(codec, decoded_representation, bytes)?;
+    partial_decoding(codec, decoded_representation, bytes)?;
+    empty_chunk(codec, decoded_representation)?;
+    concurrency(codec, decoded_representation, bytes)?;
+    Ok(())
+}
+
+/// Run every codec test in the codec test suite against an array to array `codec` with the chunk `bytes`.
+///
+/// The `codec` is tested in a [`CodecChain`] with the [`BytesCodec`], so its encoded data type must have a fixed size.
+/// The encoded representation returned by the codec is also checked against its encoded output.
+///
+/// # Errors
+/// Returns an error if a codec operation fails unexpectedly.
+///
+/// # Panics
+/// Panics if the codec does not behave as expected.
+pub fn array_to_array_all(
+    codec: Box<dyn ArrayToArrayCodecTraits>,
+    decoded_representation: &ChunkRepresentation,
+    bytes: &ArrayBytes,
+) -> Result<(), Box<dyn Error>> {
+    let encoded_representation = codec.compute_encoded_size(decoded_representation)?;
+    let encoded = codec.encode(
+        bytes.clone(),
+        decoded_representation,
+        &CodecOptions::default(),
+    )?;
+    encoded.validate(
+        encoded_representation.num_elements(),
+        encoded_representation.data_type().size(),
+    )?;
+
+    let codec_chain = CodecChain::new(vec![codec], Box::new(BytesCodec::default()), vec![]);
+    all(&codec_chain, decoded_representation, bytes)
+}
+
+/// Test that the chunk `bytes` is unchanged by encoding and then decoding.
+///
+/// # Errors
+/// Returns an error if a codec operation fails unexpectedly.
+///
+/// # Panics
+/// Panics if the codec does not behave as expected.
+pub fn round_trip(
+    codec: &dyn ArrayToBytesCodecTraits,
+    decoded_representation: &ChunkRepresentation,
+    bytes: &ArrayBytes,
+) -> Result<(), Box<dyn Error>> {
+    let options = CodecOptions::default();
+    bytes.validate(
+        decoded_representation.num_elements(),
+        decoded_representation.data_type().size(),
+    )?;
+
+    let encoded = codec.encode(bytes.clone(), decoded_representation, &options)?;
+    check_encoded_size(
+        encoded.len(),
+        codec.compute_encoded_size(decoded_representation)?,
+    );
+    let decoded = codec.decode(encoded, decoded_representation, &options)?;
+    assert_eq!(
+        &decoded, bytes,
+        "a decoded chunk must match the chunk before encoding"
+    );
+    Ok(())
+}
+
+/// Test that partially decoding array subsets of the encoded chunk `bytes` matches a full decode.
+///
+/// # Errors
+/// Returns an error if a codec operation fails unexpectedly.
+///
+/// # Panics
+/// Panics if the codec does not behave as expected.
+pub fn partial_decoding(
+    codec: &dyn ArrayToBytesCodecTraits,
+    decoded_representation: &ChunkRepresentation,
+    bytes: &ArrayBytes,
+) -> Result<(), Box<dyn Error>> {
+    let options = CodecOptions::default();
+    let shape = decoded_representation.shape_u64();
+    let data_type = decoded_representation.data_type();
+    let encoded = codec.encode(bytes.clone(), decoded_representation, &options)?;
+    let decoded = codec.decode(encoded.clone(), decoded_representation, &options)?;
+
+    let input_handle = Arc::new(std::io::Cursor::new(encoded.as_ref()));
+    let partial_decoder = codec.partial_decoder(input_handle, decoded_representation, &options)?;
+    assert_eq!(
+        partial_decoder.data_type(),
+        data_type,
+        "a partial decoder must have the decoded data type"
+    );
+
+    let subsets = partial_decoding_subsets(&shape);
+    let decoded_subsets = partial_decoder.partial_decode_opt(&subsets, &options)?;
+    assert_eq!(
+        decoded_subsets.len(),
+        subsets.len(),
+        "partial decoding must return one output per array subset"
+    );
+    for (subset, decoded_subset) in subsets.iter().zip(&decoded_subsets) {
+        let expected = decoded.extract_array_subset(subset, &shape, data_type)?;
+        assert_eq!(
+            decoded_subset, &expected,
+            "partial decoding of {subset} must match a full decode"
+        );
+    }
+    Ok(())
+}
+
+/// Test the encoding of a chunk filled with the fill value and the partial decoding of a missing chunk.
+///
+/// # Errors
+/// Returns an error if a codec operation fails unexpectedly.
+///
+/// # Panics
+/// Panics if the codec does not behave as expected.
+pub fn empty_chunk(
+    codec: &dyn ArrayToBytesCodecTraits,
+    decoded_representation: &ChunkRepresentation,
+) -> Result<(), Box<dyn Error>> {
+    let options = CodecOptions::default();
+    let shape = decoded_representation.shape_u64();
+    let fill_value = decoded_representation.fill_value();
+    let bytes = ArrayBytes::new_fill_value(decoded_representation.size(), fill_value);
+
+    let encoded = codec.encode(bytes.clone(), decoded_representation, &options)?;
+    let decoded = codec.decode(encoded, decoded_representation, &options)?;
+    assert!(
+        decoded.is_fill_value(fill_value),
+        "a decoded chunk filled with the fill value must be filled with the fill value"
+    );
+    assert_eq!(
+        decoded, bytes,
+        "a decoded chunk must match the chunk before encoding"
+    );
+
+    let partial_decoder = codec.partial_decoder(
+        Arc::new(MissingBytesPartialDecoder),
+        decoded_representation,
+        &options,
+    )?;
+    let subsets = partial_decoding_subsets(&shape);
+    for (subset, decoded_subset) in subsets
+        .iter()
+        .zip(partial_decoder.partial_decode_opt(&subsets, &options)?)
+    {
+        let expected = ArrayBytes::new_fill_value(
+            ArraySize::new(
+                decoded_representation.data_type().size(),
+                subset.num_elements(),
+            ),
+            fill_value,
+        );
+        assert_eq!(
+            decoded_subset, expected,
+            "partial decoding of {subset} of a missing chunk must return the fill value"
+        );
+    }
+    Ok(())
+}
+
+/// Test concurrent encoding, decoding, and partial decoding of the chunk `bytes` with a shared codec.
+///
+/// # Errors
+/// Returns an error if a codec operation fails unexpectedly.
+///
+/// # Panics
+/// Panics if the codec does not behave as expected.
+pub fn concurrency(
+    codec: &dyn ArrayToBytesCodecTraits,
+    decoded_representation: &ChunkRepresentation,
+    bytes: &ArrayBytes,
+) -> Result<(), Box<dyn Error>> {
+    let options = CodecOptions::default();
+    let encoded_expected = codec.encode(bytes.clone(), decoded_representation, &options)?;
+    let whole = ArraySubset::new_with_shape(decoded_representation.shape_u64());
+
+    std::thread::scope(|scope| {
+        let threads = (0..CONCURRENCY)
+            .map(|_| {
+                let (options, whole) = (&options, &whole);
+                scope.spawn(move || -> Result<(), CodecError> {
+                    for _ in 0..CONCURRENCY_ITERATIONS {
+                        let encoded =
+                            codec.encode(bytes.clone(), decoded_representation, options)?;
+                        let input_handle = Arc::new(std::io::Cursor::new(encoded.as_ref()));
+                        let partial_decoder =
+                            codec.partial_decoder(input_handle, decoded_representation, options)?;
+                        assert_eq!(
+                            &partial_decoder
+                                .partial_decode_opt(std::slice::from_ref(whole), options)?[0],
+                            bytes,
+                            "a concurrent partial decode is incorrect"
+                        );
+                        drop(partial_decoder);
+                        assert_eq!(
+                            &codec.decode(encoded, decoded_representation, options)?,
+                            bytes,
+                            "a concurrent round trip is incorrect"
+                        );
+                    }
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+        threads
+            .into_iter()
+            .try_for_each(|thread| thread.join().unwrap())
+    })?;
+
+    assert_eq!(
+        &codec.decode(encoded_expected, decoded_representation, &options)?,
+        bytes,
+        "a round trip after concurrent use is incorrect"
+    );
+    Ok(())
+}
+
+/// Run every bytes to bytes codec test in the codec test suite with the value `bytes`.
+///
+/// # Errors
+/// Returns an error if a codec operation fails unexpectedly.
+///
+/// # Panics
+/// Panics if the codec does not behave as expected.
+pub fn bytes_all(codec: &dyn BytesToBytesCodecTraits, bytes: &[u8]) -> Result<(), Box<dyn Error>> {
+    bytes_round_trip(codec, bytes)?;
+    bytes_partial_decoding(codec, bytes)?;
+    bytes_empty(codec)?;
+    bytes_concurrency(codec, bytes)?;
+    Ok(())
+}
+
+/// Test that the value `bytes` is unchanged by encoding and then decoding.
+///
+/// # Errors
+/// Returns an error if a codec operation fails unexpectedly.
+///
+/// # Panics
+/// Panics if the codec does not behave as expected.
+pub fn bytes_round_trip(
+    codec: &dyn BytesToBytesCodecTraits,
+    bytes: &[u8],
+) -> Result<(), Box<dyn Error>> {
+    let options = CodecOptions::default();
+    let decoded_representation = BytesRepresentation::FixedSize(bytes.len() as u64);
+
+    let encoded = codec.encode(RawBytes::from(bytes), &options)?;
+    check_encoded_size(
+        encoded.len(),
+        codec.compute_encoded_size(&decoded_representation),
+    );
+    let decoded = codec.decode(encoded, &decoded_representation, &options)?;
+    assert_eq!(
+        decoded.as_ref(),
+        bytes,
+        "a decoded value must match the value before encoding"
+    );
+    Ok(())
+}
+
+/// Test that partially decoding byte ranges of the encoded value `bytes` matches a full decode.
+///
+/// # Errors
+/// Returns an error if a codec operation fails unexpectedly.
+///
+/// # Panics
+/// Panics if the codec does not behave as expected.
+pub fn bytes_partial_decoding(
+    codec: &dyn BytesToBytesCodecTraits,
+    bytes: &[u8],
+) -> Result<(), Box<dyn Error>> {
+    let options = CodecOptions::default();
+    let decoded_representation = BytesRepresentation::FixedSize(bytes.len() as u64);
+    let encoded = codec.encode(RawBytes::from(bytes), &options)?;
+
+    let input_handle = Arc::new(std::io::Cursor::new(encoded.as_ref()));
+    let partial_decoder = codec.partial_decoder(input_handle, &decoded_representation, &options)?;
+    let byte_ranges = partial_decoding_byte_ranges(bytes.len() as u64);
+    let decoded = partial_decoder
+        .partial_decode(&byte_ranges, &options)?
+        .expect("partial decoding of an existing value must return bytes");
+    let expected = extract_byte_ranges(bytes, &byte_ranges)?;
+    assert_eq!(
+        decoded.len(),
+        byte_ranges.len(),
+        "partial decoding must return one output per byte range"
+    );
+    for ((byte_range, decoded), expected) in byte_ranges.iter().zip(&decoded).zip(&expected) {
+        assert_eq!(
+            decoded.as_ref(),
+            expected.as_slice(),
+            "partial decoding of {byte_range:?} must match a full decode"
+        );
+    }
+    Ok(())
+}
+
+/// Test the encoding of an empty value and the partial decoding of a missing value.
+///
+/// # Errors
+/// Returns an error if a codec operation fails unexpectedly.
+///
+/// # Panics
+/// Panics if the codec does not behave as expected.
+pub fn bytes_empty(codec: &dyn BytesToBytesCodecTraits) -> Result<(), Box<dyn Error>> {
+    bytes_round_trip(codec, &[])?;
+
+    let options = CodecOptions::default();
+    let partial_decoder = codec.partial_decoder(
+        Arc::new(MissingBytesPartialDecoder),
+        &BytesRepresentation::UnboundedSize,
+        &options,
+    )?;
+    assert!(
+        partial_decoder
+            .partial_decode(&[ByteRange::FromStart(0, None)], &options)?
+            .is_none(),
+        "partial decoding of a missing value must return None"
+    );
+    Ok(())
+}
+
+/// Test concurrent encoding, decoding, and partial decoding of the value `bytes` with a shared codec.
+///
+/// # Errors
+/// Returns an error if a codec operation fails unexpectedly.
+///
+/// # Panics
+/// Panics if the codec does not behave as expected.
+pub fn bytes_concurrency(
+    codec: &dyn BytesToBytesCodecTraits,
+    bytes: &[u8],
+) -> Result<(), Box<dyn Error>> {
+    let options = CodecOptions::default();
+    let decoded_representation = BytesRepresentation::FixedSize(bytes.len() as u64);
+
+    std::thread::scope(|scope| {
+        let threads = (0..CONCURRENCY)
+            .map(|_| {
+                let (options, decoded_representation) = (&options, &decoded_representation);
+                scope.spawn(move || -> Result<(), CodecError> {
+                    for _ in 0..CONCURRENCY_ITERATIONS {
+                        let encoded = codec.encode(RawBytes::from(bytes), options)?;
+                        let input_handle = Arc::new(std::io::Cursor::new(encoded.as_ref()));
+                        let partial_decoder =
+                            codec.partial_decoder(input_handle, decoded_representation, options)?;
+                        assert_eq!(
+                            partial_decoder.decode(options)?.as_deref(),
+                            Some(bytes),
+                            "a concurrent partial decode is incorrect"
+                        );
+                        drop(partial_decoder);
+                        assert_eq!(
+                            codec
+                                .decode(encoded, decoded_representation, options)?
+                                .as_ref(),
+                            bytes,
+                            "a concurrent round trip is incorrect"
+                        );
+                    }
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+        threads
+            .into_iter()
+            .try_for_each(|thread| thread.join().unwrap())
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+
+    use crate::array::{codec::array_to_bytes::vlen::VlenCodec, DataType};
+
+    use super::*;
+
+    fn chunk_representation_u16() -> ChunkRepresentation {
+        ChunkRepresentation::new(
+            vec![NonZeroU64::new(5).unwrap(), NonZeroU64::new(7).unwrap()],
+            DataType::UInt16,
+            7u16.into(),
+        )
+        .unwrap()
+    }
+
+    fn chunk_bytes_u16() -> ArrayBytes<'static> {
+        ArrayBytes::new_flen(crate::array::transmute_to_bytes_vec(
+            (0u16..35).collect::<Vec<_>>(),
+        ))
+    }
+
+    #[test]
+    fn codec_test_suite_bytes() {
+        all(
+            &BytesCodec::big(),
+            &chunk_representation_u16(),
+            &chunk_bytes_u16(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn codec_test_suite_string() {
+        let chunk_representation = ChunkRepresentation::new(
+            vec![NonZeroU64::new(2).unwrap(), NonZeroU64::new(3).unwrap()],
+            DataType::String,
+            "".into(),
+        )
+        .unwrap();
+        let bytes = ArrayBytes::new_vlen(
+            b"abbcccddddeeeeeffffff".to_vec(),
+            vec![0, 1, 3, 6, 10, 15, 21],
+        );
+        all(&VlenCodec::default(), &chunk_representation, &bytes).unwrap();
+    }
+
+    #[cfg(feature = "transpose")]
+    #[test]
+    fn codec_test_suite_transpose() {
+        use crate::array::codec::{array_to_array::transpose::TransposeOrder, TransposeCodec};
+
+        array_to_array_all(
+            Box::new(TransposeCodec::new(TransposeOrder::new(&[1, 0]).unwrap())),
+            &chunk_representation_u16(),
+            &chunk_bytes_u16(),
+        )
+        .unwrap();
+    }
+
+    #[cfg(feature = "crc32c")]
+    #[test]
+    fn codec_test_suite_crc32c() {
+        bytes_all(
+            &crate::array::codec::Crc32cCodec::new(),
+            &[1, 2, 3, 4, 5, 6],
+        )
+        .unwrap();
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn codec_test_suite_gzip() {
+        bytes_all(
+            &crate::array::codec::GzipCodec::new(5).unwrap(),
+            &(0u8..=255).collect::<Vec<_>>(),
+        )
+        .unwrap();
+    }
+}
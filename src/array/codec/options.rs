@@ -1,6 +1,20 @@
 //! Codec options for encoding and decoding.
 
-use crate::config::global_config;
+#[cfg(feature = "async")]
+use std::sync::Arc;
+
+#[cfg(feature = "async")]
+use crate::array::CancellationToken;
+
+#[cfg(feature = "async")]
+use super::AsyncCodecExecutor;
+
+use super::CodecScratch;
+
+use crate::{
+    array::{concurrency::ConcurrencyStrategy, BufferPool, RawBytes},
+    config::global_config,
+};
 
 /// Codec options for encoding/decoding.
 ///
@@ -10,6 +24,18 @@ pub struct CodecOptions {
     validate_checksums: bool,
     store_empty_chunks: bool,
     concurrent_target: usize,
+    concurrency_strategy: ConcurrencyStrategy,
+    buffer_pool: Option<BufferPool>,
+    codec_scratch: Option<CodecScratch>,
+    shard_coalesce_gap: Option<u64>,
+    chunk_read_max_waste_ratio: Option<f64>,
+    memory_limit: Option<u64>,
+    #[cfg(feature = "async")]
+    in_flight_chunks: Option<(usize, Arc<async_lock::Semaphore>)>,
+    #[cfg(feature = "async")]
+    cancellation_token: Option<CancellationToken>,
+    #[cfg(feature = "async")]
+    async_codec_executor: AsyncCodecExecutor,
 }
 
 impl Default for CodecOptions {
@@ -18,6 +44,18 @@ impl Default for CodecOptions {
             validate_checksums: global_config().validate_checksums(),
             store_empty_chunks: global_config().store_empty_chunks(),
             concurrent_target: global_config().codec_concurrent_target(),
+            concurrency_strategy: ConcurrencyStrategy::default(),
+            buffer_pool: None,
+            codec_scratch: None,
+            shard_coalesce_gap: None,
+            chunk_read_max_waste_ratio: None,
+            memory_limit: None,
+            #[cfg(feature = "async")]
+            in_flight_chunks: None,
+            #[cfg(feature = "async")]
+            cancellation_token: None,
+            #[cfg(feature = "async")]
+            async_codec_executor: AsyncCodecExecutor::default(),
         }
     }
 }
@@ -36,6 +74,18 @@ impl CodecOptions {
             validate_checksums: self.validate_checksums,
             store_empty_chunks: self.store_empty_chunks,
             concurrent_target: self.concurrent_target,
+            concurrency_strategy: self.concurrency_strategy.clone(),
+            buffer_pool: self.buffer_pool.clone(),
+            codec_scratch: self.codec_scratch.clone(),
+            shard_coalesce_gap: self.shard_coalesce_gap,
+            chunk_read_max_waste_ratio: self.chunk_read_max_waste_ratio,
+            memory_limit: self.memory_limit,
+            #[cfg(feature = "async")]
+            max_in_flight_chunks: self.max_in_flight_chunks(),
+            #[cfg(feature = "async")]
+            cancellation_token: self.cancellation_token.clone(),
+            #[cfg(feature = "async")]
+            async_codec_executor: self.async_codec_executor.clone(),
         }
     }
 
@@ -74,6 +124,207 @@ impl CodecOptions {
         self.concurrent_target = concurrent_target;
         self
     }
+
+    /// Return the strategy for balancing chunk and codec concurrency.
+    #[must_use]
+    pub fn concurrency_strategy(&self) -> &ConcurrencyStrategy {
+        &self.concurrency_strategy
+    }
+
+    /// Set the strategy for balancing chunk and codec concurrency.
+    ///
+    /// Defaults to [`ConcurrencyStrategy::Static`].
+    pub fn set_concurrency_strategy(
+        &mut self,
+        concurrency_strategy: ConcurrencyStrategy,
+    ) -> &mut Self {
+        self.concurrency_strategy = concurrency_strategy;
+        self
+    }
+
+    /// Return the buffer pool, or [`None`] if buffers are not pooled.
+    #[must_use]
+    pub fn buffer_pool(&self) -> Option<&BufferPool> {
+        self.buffer_pool.as_ref()
+    }
+
+    /// Set the buffer pool that codecs and array subset assembly draw buffers from and return buffers to.
+    ///
+    /// Defaults to [`None`], in which case buffers are allocated and freed as required.
+    pub fn set_buffer_pool(&mut self, buffer_pool: Option<BufferPool>) -> &mut Self {
+        self.buffer_pool = buffer_pool;
+        self
+    }
+
+    /// Take an empty buffer with a capacity of at least `capacity` from the buffer pool, or allocate a new buffer if there is no buffer pool.
+    #[must_use]
+    pub fn take_buffer(&self, capacity: usize) -> Vec<u8> {
+        self.buffer_pool.as_ref().map_or_else(
+            || Vec::with_capacity(capacity),
+            |buffer_pool| buffer_pool.take(capacity),
+        )
+    }
+
+    /// Return a buffer that is no longer needed to the buffer pool, or drop it if there is no buffer pool.
+    pub fn recycle_buffer(&self, buffer: Vec<u8>) {
+        if let Some(buffer_pool) = &self.buffer_pool {
+            buffer_pool.give(buffer);
+        }
+    }
+
+    /// Return bytes that are no longer needed to the buffer pool if they are owned.
+    pub fn recycle_bytes(&self, bytes: RawBytes) {
+        if let RawBytes::Owned(buffer) = bytes {
+            self.recycle_buffer(buffer);
+        }
+    }
+
+    /// Return the codec scratch space, or [`None`] if codecs do not reuse state.
+    #[must_use]
+    pub fn codec_scratch(&self) -> Option<&CodecScratch> {
+        self.codec_scratch.as_ref()
+    }
+
+    /// Set the codec scratch space that codecs draw reusable state (e.g. compression contexts) from.
+    ///
+    /// Defaults to [`None`], in which case codecs initialise new state for every encode and decode.
+    pub fn set_codec_scratch(&mut self, codec_scratch: Option<CodecScratch>) -> &mut Self {
+        self.codec_scratch = codec_scratch;
+        self
+    }
+
+    /// Return the gap tolerance for coalescing inner chunk byte range requests in sharding partial decoders, or [`None`] if requests are not coalesced.
+    #[must_use]
+    pub fn shard_coalesce_gap(&self) -> Option<u64> {
+        self.shard_coalesce_gap
+    }
+
+    /// Set the gap tolerance in bytes for coalescing inner chunk byte range requests in sharding partial decoders.
+    ///
+    /// If set, the encoded inner chunks of a shard intersecting a partial read are retrieved with byte range requests that each cover all inner chunks separated by at most `shard_coalesce_gap` bytes.
+    /// This reduces the number of requests to high-latency stores (e.g. object stores) at the cost of retrieving the bytes in the gaps, and each retrieved inner chunk is decoded in full.
+    ///
+    /// Defaults to [`None`], in which case each inner chunk is retrieved and partially decoded independently.
+    pub fn set_shard_coalesce_gap(&mut self, shard_coalesce_gap: Option<u64>) -> &mut Self {
+        self.shard_coalesce_gap = shard_coalesce_gap;
+        self
+    }
+
+    /// Return the maximum waste ratio for batching whole chunk reads, or [`None`] if chunks are read independently.
+    #[must_use]
+    pub fn chunk_read_max_waste_ratio(&self) -> Option<f64> {
+        self.chunk_read_max_waste_ratio
+    }
+
+    /// Set the maximum waste ratio for batching whole chunk reads when retrieving multiple chunks.
+    ///
+    /// If set, the encoded chunks read in full by a multi-chunk retrieval are retrieved together with [`ReadableStorageTraits::get_values_coalesced`](crate::storage::ReadableStorageTraits::get_values_coalesced).
+    /// Stores holding chunks in nearby regions of a common underlying object (e.g. kerchunk reference stores) merge them into fewer larger reads, as long as the fraction of unrequested bytes in each read does not exceed `chunk_read_max_waste_ratio`.
+    ///
+    /// Defaults to [`None`], in which case each chunk is retrieved independently.
+    pub fn set_chunk_read_max_waste_ratio(
+        &mut self,
+        chunk_read_max_waste_ratio: Option<f64>,
+    ) -> &mut Self {
+        self.chunk_read_max_waste_ratio = chunk_read_max_waste_ratio;
+        self
+    }
+
+    /// Return the memory limit in bytes of array operations, or [`None`] if unlimited.
+    #[must_use]
+    pub fn memory_limit(&self) -> Option<u64> {
+        self.memory_limit
+    }
+
+    /// Set the memory limit in bytes of array operations.
+    ///
+    /// If set, multi-chunk array operations reduce the number of chunks processed concurrently so that the output of a retrieval and the decoded and encoded bytes of each chunk in flight fit within `memory_limit`.
+    /// If the output and a single chunk do not fit, the operation fails with [`ArrayError::MemoryLimitExceeded`](crate::array::ArrayError::MemoryLimitExceeded) before any chunks are processed.
+    /// Memory internal to codecs is not included, and the limit is not applied to arrays with a variable size data type.
+    ///
+    /// Defaults to [`None`], in which case memory usage is not limited.
+    pub fn set_memory_limit(&mut self, memory_limit: Option<u64>) -> &mut Self {
+        self.memory_limit = memory_limit;
+        self
+    }
+
+    #[cfg(feature = "async")]
+    /// Return the maximum number of chunks in flight in async array subset stores, or [`None`] if unlimited.
+    #[must_use]
+    pub fn max_in_flight_chunks(&self) -> Option<usize> {
+        self.in_flight_chunks
+            .as_ref()
+            .map(|(max_in_flight_chunks, _)| *max_in_flight_chunks)
+    }
+
+    #[cfg(feature = "async")]
+    /// Set the maximum number of chunks in flight in async array subset stores.
+    ///
+    /// A chunk is in flight from when its bytes are extracted from the array subset until it has been encoded and stored.
+    /// The limit is enforced by a semaphore that is shared by clones of these options, so it also applies across concurrent calls using the same options.
+    /// Stores wait for a chunk to complete before extracting the next chunk once the limit is reached, which applies backpressure to the producer of the array subsets.
+    ///
+    /// # Panics
+    /// Panics if `max_in_flight_chunks` is zero.
+    pub fn set_max_in_flight_chunks(&mut self, max_in_flight_chunks: Option<usize>) -> &mut Self {
+        self.in_flight_chunks = max_in_flight_chunks.map(|max_in_flight_chunks| {
+            assert!(
+                max_in_flight_chunks > 0,
+                "max_in_flight_chunks must be non-zero"
+            );
+            (
+                max_in_flight_chunks,
+                Arc::new(async_lock::Semaphore::new(max_in_flight_chunks)),
+            )
+        });
+        self
+    }
+
+    #[cfg(feature = "async")]
+    /// Return the semaphore limiting the number of chunks in flight in async array subset stores.
+    pub(crate) fn in_flight_chunks_semaphore(&self) -> Option<&Arc<async_lock::Semaphore>> {
+        self.in_flight_chunks
+            .as_ref()
+            .map(|(_, semaphore)| semaphore)
+    }
+
+    #[cfg(feature = "async")]
+    /// Return the cancellation token of async array operations.
+    #[must_use]
+    pub fn cancellation_token(&self) -> Option<&CancellationToken> {
+        self.cancellation_token.as_ref()
+    }
+
+    #[cfg(feature = "async")]
+    /// Set the cancellation token of async array operations.
+    ///
+    /// Cancelling the token aborts the pending store requests of async array operations using these options, which then return [`ArrayError::Cancelled`](crate::array::ArrayError::Cancelled).
+    pub fn set_cancellation_token(
+        &mut self,
+        cancellation_token: Option<CancellationToken>,
+    ) -> &mut Self {
+        self.cancellation_token = cancellation_token;
+        self
+    }
+
+    #[cfg(feature = "async")]
+    /// Return the executor of codec encoding and decoding in async array operations.
+    #[must_use]
+    pub fn async_codec_executor(&self) -> &AsyncCodecExecutor {
+        &self.async_codec_executor
+    }
+
+    #[cfg(feature = "async")]
+    /// Set the executor of codec encoding and decoding in async array operations.
+    ///
+    /// Defaults to [`AsyncCodecExecutor::Inline`].
+    pub fn set_async_codec_executor(
+        &mut self,
+        async_codec_executor: AsyncCodecExecutor,
+    ) -> &mut Self {
+        self.async_codec_executor = async_codec_executor;
+        self
+    }
 }
 
 /// Builder for [`CodecOptions`].
@@ -84,6 +335,18 @@ pub struct CodecOptionsBuilder {
     validate_checksums: bool,
     store_empty_chunks: bool,
     concurrent_target: usize,
+    concurrency_strategy: ConcurrencyStrategy,
+    buffer_pool: Option<BufferPool>,
+    codec_scratch: Option<CodecScratch>,
+    shard_coalesce_gap: Option<u64>,
+    chunk_read_max_waste_ratio: Option<f64>,
+    memory_limit: Option<u64>,
+    #[cfg(feature = "async")]
+    max_in_flight_chunks: Option<usize>,
+    #[cfg(feature = "async")]
+    cancellation_token: Option<CancellationToken>,
+    #[cfg(feature = "async")]
+    async_codec_executor: AsyncCodecExecutor,
 }
 
 impl Default for CodecOptionsBuilder {
@@ -100,17 +363,45 @@ impl CodecOptionsBuilder {
             validate_checksums: global_config().validate_checksums(),
             store_empty_chunks: global_config().store_empty_chunks(),
             concurrent_target: global_config().codec_concurrent_target(),
+            concurrency_strategy: ConcurrencyStrategy::default(),
+            buffer_pool: None,
+            codec_scratch: None,
+            shard_coalesce_gap: None,
+            chunk_read_max_waste_ratio: None,
+            memory_limit: None,
+            #[cfg(feature = "async")]
+            max_in_flight_chunks: None,
+            #[cfg(feature = "async")]
+            cancellation_token: None,
+            #[cfg(feature = "async")]
+            async_codec_executor: AsyncCodecExecutor::default(),
         }
     }
 
     /// Build into encode options.
     #[must_use]
     pub fn build(&self) -> CodecOptions {
-        CodecOptions {
+        #[allow(unused_mut)]
+        let mut options = CodecOptions {
             validate_checksums: self.validate_checksums,
             store_empty_chunks: self.store_empty_chunks,
             concurrent_target: self.concurrent_target,
-        }
+            concurrency_strategy: self.concurrency_strategy.clone(),
+            buffer_pool: self.buffer_pool.clone(),
+            codec_scratch: self.codec_scratch.clone(),
+            shard_coalesce_gap: self.shard_coalesce_gap,
+            chunk_read_max_waste_ratio: self.chunk_read_max_waste_ratio,
+            memory_limit: self.memory_limit,
+            #[cfg(feature = "async")]
+            in_flight_chunks: None,
+            #[cfg(feature = "async")]
+            cancellation_token: self.cancellation_token.clone(),
+            #[cfg(feature = "async")]
+            async_codec_executor: self.async_codec_executor.clone(),
+        };
+        #[cfg(feature = "async")]
+        options.set_max_in_flight_chunks(self.max_in_flight_chunks);
+        options
     }
 
     /// Set whether or not to validate checksums.
@@ -133,4 +424,88 @@ impl CodecOptionsBuilder {
         self.concurrent_target = concurrent_target;
         self
     }
+
+    /// Set the strategy for balancing chunk and codec concurrency.
+    ///
+    /// See [`CodecOptions::set_concurrency_strategy`].
+    #[must_use]
+    pub fn concurrency_strategy(mut self, concurrency_strategy: ConcurrencyStrategy) -> Self {
+        self.concurrency_strategy = concurrency_strategy;
+        self
+    }
+
+    /// Set the buffer pool that codecs and array subset assembly draw buffers from and return buffers to.
+    ///
+    /// See [`CodecOptions::set_buffer_pool`].
+    #[must_use]
+    pub fn buffer_pool(mut self, buffer_pool: Option<BufferPool>) -> Self {
+        self.buffer_pool = buffer_pool;
+        self
+    }
+
+    /// Set the codec scratch space that codecs draw reusable state from.
+    ///
+    /// See [`CodecOptions::set_codec_scratch`].
+    #[must_use]
+    pub fn codec_scratch(mut self, codec_scratch: Option<CodecScratch>) -> Self {
+        self.codec_scratch = codec_scratch;
+        self
+    }
+
+    /// Set the gap tolerance for coalescing inner chunk byte range requests in sharding partial decoders.
+    ///
+    /// See [`CodecOptions::set_shard_coalesce_gap`].
+    #[must_use]
+    pub fn shard_coalesce_gap(mut self, shard_coalesce_gap: Option<u64>) -> Self {
+        self.shard_coalesce_gap = shard_coalesce_gap;
+        self
+    }
+
+    /// Set the maximum waste ratio for batching whole chunk reads when retrieving multiple chunks.
+    ///
+    /// See [`CodecOptions::set_chunk_read_max_waste_ratio`].
+    #[must_use]
+    pub fn chunk_read_max_waste_ratio(mut self, chunk_read_max_waste_ratio: Option<f64>) -> Self {
+        self.chunk_read_max_waste_ratio = chunk_read_max_waste_ratio;
+        self
+    }
+
+    /// Set the memory limit in bytes of array operations.
+    ///
+    /// See [`CodecOptions::set_memory_limit`].
+    #[must_use]
+    pub fn memory_limit(mut self, memory_limit: Option<u64>) -> Self {
+        self.memory_limit = memory_limit;
+        self
+    }
+
+    #[cfg(feature = "async")]
+    /// Set the maximum number of chunks in flight in async array subset stores.
+    ///
+    /// See [`CodecOptions::set_max_in_flight_chunks`].
+    #[must_use]
+    pub fn max_in_flight_chunks(mut self, max_in_flight_chunks: Option<usize>) -> Self {
+        self.max_in_flight_chunks = max_in_flight_chunks;
+        self
+    }
+
+    #[cfg(feature = "async")]
+    /// Set the cancellation token of async array operations.
+    ///
+    /// See [`CodecOptions::set_cancellation_token`].
+    #[must_use]
+    pub fn cancellation_token(mut self, cancellation_token: Option<CancellationToken>) -> Self {
+        self.cancellation_token = cancellation_token;
+        self
+    }
+
+    #[cfg(feature = "async")]
+    /// Set the executor of codec encoding and decoding in async array operations.
+    ///
+    /// See [`CodecOptions::set_async_codec_executor`].
+    #[must_use]
+    pub fn async_codec_executor(mut self, async_codec_executor: AsyncCodecExecutor) -> Self {
+        self.async_codec_executor = async_codec_executor;
+        self
+    }
 }
@@ -0,0 +1,180 @@
+use std::{collections::HashMap, sync::Arc};
+
+#[cfg(feature = "crc32c")]
+use super::codec::Crc32cCodec;
+#[cfg(all(feature = "gzip", not(feature = "zstd")))]
+use super::codec::GzipCodec;
+#[cfg(feature = "zstd")]
+use super::codec::ZstdCodec;
+#[cfg(feature = "blosc")]
+use super::codec::{
+    bytes_to_bytes::blosc::{BloscCompressionLevel, BloscCompressor, BloscShuffleMode},
+    BloscCodec,
+};
+use super::{
+    codec::{
+        array_to_bytes::vlen::VlenCodec, ArrayToBytesCodecTraits, BytesCodec,
+        BytesToBytesCodecTraits,
+    },
+    CodecChain, DataType,
+};
+
+/// The name of the `fast` [`CodecProfile`].
+pub const CODEC_PROFILE_FAST: &str = "fast";
+
+/// The name of the `balanced` [`CodecProfile`].
+pub const CODEC_PROFILE_BALANCED: &str = "balanced";
+
+/// The name of the `archival` [`CodecProfile`].
+pub const CODEC_PROFILE_ARCHIVAL: &str = "archival";
+
+/// The name of the `imaging` [`CodecProfile`].
+pub const CODEC_PROFILE_IMAGING: &str = "imaging";
+
+/// A codec profile, which creates a codec chain for a data type.
+///
+/// Codec profiles are registered by name in the global [`Config`](crate::config::Config#codec-profiles) and selected with [`ArrayBuilder::codec_profile`](crate::array::ArrayBuilder::codec_profile).
+/// They enable a compression policy to be standardised without repeating codec constructors.
+///
+/// The built-in profiles are:
+///  - `fast`: `zstd` at level 1.
+///  - `balanced`: `zstd` at level 5, or `gzip` at level 5 if the `zstd` feature is disabled.
+///  - `archival`: `zstd` at level 19 and a `crc32c` checksum.
+///  - `imaging`: `blosc` with `zstd` at level 5 and byte shuffling (bit shuffling for single byte data types).
+///
+/// Each profile uses the `bytes` codec with native endian encoding (or `vlen` for variable size data types), and omits codecs that are not enabled by crate features.
+///
+/// For example:
+/// ```rust
+/// # use zarrs::{array::{CodecChain, CodecProfile, codec::BytesCodec}, config::global_config_mut};
+/// global_config_mut().codec_profiles_mut().insert(
+///     "uncompressed_big_endian".to_string(),
+///     CodecProfile::new(|_data_type| {
+///         CodecChain::new(vec![], Box::new(BytesCodec::big()), vec![])
+///     }),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct CodecProfile(Arc<dyn Fn(&DataType) -> CodecChain + Send + Sync>);
+
+impl core::fmt::Debug for CodecProfile {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "CodecProfile")
+    }
+}
+
+impl CodecProfile {
+    /// Create a new codec profile from a function creating a codec chain for a data type.
+    pub fn new(codecs: impl Fn(&DataType) -> CodecChain + Send + Sync + 'static) -> Self {
+        Self(Arc::new(codecs))
+    }
+
+    /// Create the codec chain of the profile for `data_type`.
+    #[must_use]
+    pub fn codecs(&self, data_type: &DataType) -> CodecChain {
+        (self.0)(data_type)
+    }
+}
+
+/// Return the default array to bytes codec for `data_type`.
+pub(crate) fn default_array_to_bytes_codec(
+    data_type: &DataType,
+) -> Box<dyn ArrayToBytesCodecTraits> {
+    if data_type.fixed_size().is_some() {
+        Box::<BytesCodec>::default()
+    } else {
+        Box::<VlenCodec>::default()
+        // Box::<VlenV2Codec>::default()
+    }
+}
+
+fn profile_codec_chain(
+    data_type: &DataType,
+    bytes_to_bytes_codecs: Vec<Box<dyn BytesToBytesCodecTraits>>,
+) -> CodecChain {
+    CodecChain::new(
+        vec![],
+        default_array_to_bytes_codec(data_type),
+        bytes_to_bytes_codecs,
+    )
+}
+
+fn codec_profile_fast(data_type: &DataType) -> CodecChain {
+    profile_codec_chain(
+        data_type,
+        vec![
+            #[cfg(feature = "zstd")]
+            Box::new(ZstdCodec::new(1, false)),
+        ],
+    )
+}
+
+fn codec_profile_balanced(data_type: &DataType) -> CodecChain {
+    profile_codec_chain(
+        data_type,
+        vec![
+            #[cfg(feature = "zstd")]
+            Box::new(ZstdCodec::new(5, false)),
+            #[cfg(all(feature = "gzip", not(feature = "zstd")))]
+            Box::new(GzipCodec::new(5).expect("the gzip compression level is valid")),
+        ],
+    )
+}
+
+fn codec_profile_archival(data_type: &DataType) -> CodecChain {
+    profile_codec_chain(
+        data_type,
+        vec![
+            #[cfg(feature = "zstd")]
+            Box::new(ZstdCodec::new(19, false)),
+            #[cfg(feature = "crc32c")]
+            Box::new(Crc32cCodec::new()),
+        ],
+    )
+}
+
+fn codec_profile_imaging(data_type: &DataType) -> CodecChain {
+    #[allow(unused_mut)]
+    let mut bytes_to_bytes_codecs: Vec<Box<dyn BytesToBytesCodecTraits>> = vec![];
+    #[cfg(feature = "blosc")]
+    {
+        let typesize = data_type.fixed_size();
+        let shuffle_mode = match typesize {
+            Some(1) => BloscShuffleMode::BitShuffle,
+            Some(_) => BloscShuffleMode::Shuffle,
+            None => BloscShuffleMode::NoShuffle,
+        };
+        if let Ok(codec) = BloscCodec::new(
+            BloscCompressor::Zstd,
+            BloscCompressionLevel::try_from(5u8).expect("valid blosc compression level"),
+            None,
+            shuffle_mode,
+            typesize,
+        ) {
+            bytes_to_bytes_codecs.push(Box::new(codec));
+        }
+    }
+    profile_codec_chain(data_type, bytes_to_bytes_codecs)
+}
+
+/// Return the built-in codec profiles.
+pub(crate) fn builtin_codec_profiles() -> HashMap<String, CodecProfile> {
+    HashMap::from([
+        (
+            CODEC_PROFILE_FAST.to_string(),
+            CodecProfile::new(codec_profile_fast),
+        ),
+        (
+            CODEC_PROFILE_BALANCED.to_string(),
+            CodecProfile::new(codec_profile_balanced),
+        ),
+        (
+            CODEC_PROFILE_ARCHIVAL.to_string(),
+            CodecProfile::new(codec_profile_archival),
+        ),
+        (
+            CODEC_PROFILE_IMAGING.to_string(),
+            CodecProfile::new(codec_profile_imaging),
+        ),
+    ])
+}
@@ -13,6 +13,10 @@
 //     Maximum,
 // }
 
+use std::{sync::Arc, time::Duration};
+
+use parking_lot::Mutex;
+
 use crate::config::global_config;
 
 use super::codec::CodecOptions;
@@ -119,7 +123,240 @@ pub fn calc_concurrency_outer_inner(
     (concurrency_outer, concurrency_inner)
 }
 
+/// The strategy for balancing chunk and codec concurrency in array operations.
+#[derive(Debug, Clone, Default)]
+pub enum ConcurrencyStrategy {
+    /// Balance chunk and codec concurrency with static heuristics based on the recommended concurrency of the codecs.
+    #[default]
+    Static,
+    /// Rebalance chunk and codec concurrency (and the prefetch depth) from measured storage latency and codec time.
+    Adaptive(AdaptiveConcurrency),
+}
+
+impl ConcurrencyStrategy {
+    /// Record the storage latency of retrieving or storing a chunk if the strategy is adaptive.
+    pub(crate) fn record_storage_latency(&self, latency: Duration) {
+        if let Self::Adaptive(adaptive) = self {
+            adaptive.record_storage_latency(latency);
+        }
+    }
+
+    /// Record the time taken to decode or encode a chunk if the strategy is adaptive.
+    pub(crate) fn record_codec_time(&self, time: Duration) {
+        if let Self::Adaptive(adaptive) = self {
+            adaptive.record_codec_time(time);
+        }
+    }
+}
+
+/// The smoothing factor of the exponential moving averages of [`AdaptiveConcurrency`].
+const ADAPTIVE_CONCURRENCY_SMOOTHING: f64 = 0.2;
+
+/// The default maximum prefetch depth of [`AdaptiveConcurrency`] relative to the concurrency target.
+const ADAPTIVE_CONCURRENCY_DEFAULT_MAX_PREFETCH_FACTOR: usize = 4;
+
+#[derive(Debug, Default)]
+struct AdaptiveConcurrencyMeasurements {
+    /// Exponential moving average of the storage latency per chunk (seconds).
+    storage_latency: Option<f64>,
+    /// Exponential moving average of the codec time per chunk (seconds).
+    codec_time: Option<f64>,
+}
+
+fn update_moving_average(average: &mut Option<f64>, value: f64) {
+    *average = Some(average.map_or(value, |average| {
+        average + ADAPTIVE_CONCURRENCY_SMOOTHING * (value - average)
+    }));
+}
+
+/// Adaptive chunk and codec concurrency.
+///
+/// Array operations record the storage latency and codec (encode/decode) time of each whole chunk they retrieve or store.
+/// The concurrency of each subsequent array operation (including those already in progress which process chunks with nested operations) is balanced from these measurements:
+///  - if storage latency dominates, chunk concurrency is preferred over codec concurrency so that storage requests overlap, and
+///  - additional chunks are kept in flight (prefetched) in proportion to the ratio of storage latency to codec time.
+///
+/// Measurements are shared by clones, so a single [`AdaptiveConcurrency`] can be used across many operations (and [`CodecOptions`]) to continually refine the balance.
+/// Before any measurements are recorded, concurrency is balanced as per [`ConcurrencyStrategy::Static`].
+#[derive(Debug, Clone, Default)]
+pub struct AdaptiveConcurrency {
+    max_prefetch_depth: Option<usize>,
+    measurements: Arc<Mutex<AdaptiveConcurrencyMeasurements>>,
+}
+
+impl AdaptiveConcurrency {
+    /// Create a new adaptive concurrency strategy with no measurements.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum prefetch depth (the number of chunks in flight in addition to those required to reach the concurrency target).
+    ///
+    /// Defaults to four times the concurrency target of an operation if [`None`].
+    #[must_use]
+    pub fn with_max_prefetch_depth(mut self, max_prefetch_depth: Option<usize>) -> Self {
+        self.max_prefetch_depth = max_prefetch_depth;
+        self
+    }
+
+    /// Return the maximum prefetch depth, or [`None`] if it is relative to the concurrency target.
+    #[must_use]
+    pub fn max_prefetch_depth(&self) -> Option<usize> {
+        self.max_prefetch_depth
+    }
+
+    /// Record the storage latency of retrieving or storing a chunk.
+    pub fn record_storage_latency(&self, latency: Duration) {
+        update_moving_average(
+            &mut self.measurements.lock().storage_latency,
+            latency.as_secs_f64(),
+        );
+    }
+
+    /// Record the time taken to decode or encode a chunk.
+    pub fn record_codec_time(&self, time: Duration) {
+        update_moving_average(&mut self.measurements.lock().codec_time, time.as_secs_f64());
+    }
+
+    /// Return the moving average of the storage latency per chunk, or [`None`] if no storage latency has been recorded.
+    #[must_use]
+    pub fn storage_latency(&self) -> Option<Duration> {
+        self.measurements
+            .lock()
+            .storage_latency
+            .map(Duration::from_secs_f64)
+    }
+
+    /// Return the moving average of the codec time per chunk, or [`None`] if no codec time has been recorded.
+    #[must_use]
+    pub fn codec_time(&self) -> Option<Duration> {
+        self.measurements
+            .lock()
+            .codec_time
+            .map(Duration::from_secs_f64)
+    }
+
+    /// Clear all measurements.
+    pub fn reset(&self) {
+        *self.measurements.lock() = AdaptiveConcurrencyMeasurements::default();
+    }
+
+    /// Return the ratio of storage latency to codec time, or [`None`] if either has not been recorded.
+    fn storage_to_codec_ratio(&self) -> Option<f64> {
+        let measurements = self.measurements.lock();
+        let storage_latency = measurements.storage_latency?;
+        let codec_time = measurements.codec_time?;
+        if codec_time > 0.0 {
+            Some(storage_latency / codec_time)
+        } else if storage_latency > 0.0 {
+            Some(f64::INFINITY)
+        } else {
+            Some(0.0)
+        }
+    }
+
+    /// Calculate the chunk and codec concurrent limits.
+    ///
+    /// Return is (chunk, codec).
+    fn calc_concurrency_chunks_and_codec(
+        &self,
+        concurrency_target: usize,
+        recommended_concurrency_chunks: &RecommendedConcurrency,
+        recommended_concurrency_codec: &RecommendedConcurrency,
+    ) -> (usize, usize) {
+        let (mut concurrency_chunks, mut concurrency_codec) = calc_concurrency_outer_inner(
+            concurrency_target,
+            recommended_concurrency_chunks,
+            recommended_concurrency_codec,
+        );
+        let Some(storage_to_codec_ratio) = self.storage_to_codec_ratio() else {
+            return (concurrency_chunks, concurrency_codec);
+        };
+
+        if storage_to_codec_ratio > 1.0 {
+            // Storage bound, so overlap storage requests rather than parallelising within codecs
+            concurrency_codec = recommended_concurrency_codec.min();
+            concurrency_chunks = std::cmp::min(
+                concurrency_target.div_ceil(concurrency_codec),
+                recommended_concurrency_chunks.max(),
+            )
+            .max(recommended_concurrency_chunks.min());
+        }
+
+        // Keep enough chunks in flight to hide storage latency behind decoding/encoding
+        let max_prefetch_depth = self.max_prefetch_depth.unwrap_or(
+            concurrency_target.saturating_mul(ADAPTIVE_CONCURRENCY_DEFAULT_MAX_PREFETCH_FACTOR),
+        );
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            clippy::cast_precision_loss
+        )]
+        let prefetch_depth = std::cmp::min(
+            (concurrency_chunks as f64 * storage_to_codec_ratio).ceil() as usize,
+            max_prefetch_depth,
+        );
+        concurrency_chunks = std::cmp::min(
+            concurrency_chunks.saturating_add(prefetch_depth),
+            recommended_concurrency_chunks.max(),
+        );
+
+        (concurrency_chunks, concurrency_codec)
+    }
+}
+
+/// A plan of the chunk and codec concurrency of an array operation.
+///
+/// See [`Array::concurrency_plan`](crate::array::Array::concurrency_plan).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConcurrencyPlan {
+    chunk_concurrent_limit: usize,
+    codec_concurrent_target: usize,
+    peak_memory: Option<u64>,
+}
+
+impl ConcurrencyPlan {
+    /// Create a new concurrency plan.
+    #[must_use]
+    pub const fn new(
+        chunk_concurrent_limit: usize,
+        codec_concurrent_target: usize,
+        peak_memory: Option<u64>,
+    ) -> Self {
+        Self {
+            chunk_concurrent_limit,
+            codec_concurrent_target,
+            peak_memory,
+        }
+    }
+
+    /// Return the maximum number of chunks processed concurrently.
+    #[must_use]
+    pub const fn chunk_concurrent_limit(&self) -> usize {
+        self.chunk_concurrent_limit
+    }
+
+    /// Return the concurrent target of the codecs of each chunk.
+    #[must_use]
+    pub const fn codec_concurrent_target(&self) -> usize {
+        self.codec_concurrent_target
+    }
+
+    /// Return the expected peak memory usage in bytes.
+    ///
+    /// This is the size of the output plus the encoded and decoded size of each chunk in flight.
+    /// Memory internal to codecs is not included.
+    /// Returns [`None`] if the data type has a variable size or the encoded size of a chunk is unbounded.
+    #[must_use]
+    pub const fn peak_memory(&self) -> Option<u64> {
+        self.peak_memory
+    }
+}
+
 /// Calculate the outer concurrency and inner options for a codec.
+///
+/// The balance is determined by the [`ConcurrencyStrategy`] of `codec_options`.
 #[must_use]
 pub fn concurrency_chunks_and_codec(
     concurrency_target: usize,
@@ -131,11 +368,19 @@ pub fn concurrency_chunks_and_codec(
     let chunk_concurrent_minimum = global_config().chunk_concurrent_minimum();
     let min_concurrent_chunks = std::cmp::min(chunk_concurrent_minimum, num_chunks);
     let max_concurrent_chunks = std::cmp::max(chunk_concurrent_minimum, num_chunks);
-    let (self_concurrent_limit, codec_concurrent_limit) = calc_concurrency_outer_inner(
-        concurrency_target,
-        &RecommendedConcurrency::new(min_concurrent_chunks..max_concurrent_chunks),
-        codec_concurrency,
-    );
+    let chunks_concurrency =
+        RecommendedConcurrency::new(min_concurrent_chunks..max_concurrent_chunks);
+    let (self_concurrent_limit, codec_concurrent_limit) = match codec_options.concurrency_strategy()
+    {
+        ConcurrencyStrategy::Static => {
+            calc_concurrency_outer_inner(concurrency_target, &chunks_concurrency, codec_concurrency)
+        }
+        ConcurrencyStrategy::Adaptive(adaptive) => adaptive.calc_concurrency_chunks_and_codec(
+            concurrency_target,
+            &chunks_concurrency,
+            codec_concurrency,
+        ),
+    };
     let codec_options = codec_options
         .into_builder()
         .concurrent_target(codec_concurrent_limit)
@@ -179,4 +424,68 @@ mod tests {
         );
         assert_eq!((self_limit, inner_limit), (2, 14));
     }
+
+    #[test]
+    fn adaptive_concurrent_limits() {
+        let target = 8;
+        let chunks = RecommendedConcurrency::new(4..100);
+        let codec = RecommendedConcurrency::new(1..8);
+
+        // No measurements: static
+        let adaptive = AdaptiveConcurrency::new();
+        assert_eq!(
+            adaptive.calc_concurrency_chunks_and_codec(target, &chunks, &codec),
+            calc_concurrency_outer_inner(target, &chunks, &codec)
+        );
+        assert_eq!(
+            calc_concurrency_outer_inner(target, &chunks, &codec),
+            (4, 2)
+        );
+
+        // Codec bound: static balance with a small prefetch depth
+        adaptive.record_storage_latency(Duration::from_millis(1));
+        adaptive.record_codec_time(Duration::from_millis(10));
+        assert_eq!(
+            adaptive.calc_concurrency_chunks_and_codec(target, &chunks, &codec),
+            (5, 2)
+        );
+
+        // Storage bound: prefer chunk concurrency and prefetch
+        adaptive.reset();
+        adaptive.record_storage_latency(Duration::from_millis(20));
+        adaptive.record_codec_time(Duration::from_millis(10));
+        assert_eq!(
+            adaptive.calc_concurrency_chunks_and_codec(target, &chunks, &codec),
+            (24, 1)
+        );
+
+        // Limited by the maximum prefetch depth and the number of chunks
+        let adaptive = adaptive.with_max_prefetch_depth(Some(4));
+        assert_eq!(
+            adaptive.calc_concurrency_chunks_and_codec(target, &chunks, &codec),
+            (12, 1)
+        );
+        assert_eq!(
+            adaptive.calc_concurrency_chunks_and_codec(
+                target,
+                &RecommendedConcurrency::new(4..10),
+                &codec
+            ),
+            (10, 1)
+        );
+    }
+
+    #[test]
+    fn adaptive_concurrency_moving_average() {
+        let adaptive = AdaptiveConcurrency::new();
+        assert!(adaptive.storage_latency().is_none());
+        assert!(adaptive.codec_time().is_none());
+        adaptive.record_storage_latency(Duration::from_millis(10));
+        adaptive
+            .clone()
+            .record_storage_latency(Duration::from_millis(20));
+        assert!((adaptive.storage_latency().unwrap().as_secs_f64() - 0.012).abs() < 1e-9);
+        adaptive.reset();
+        assert!(adaptive.storage_latency().is_none());
+    }
 }
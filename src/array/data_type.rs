@@ -271,6 +271,42 @@ impl DataType {
         // })
     }
 
+    /// Create a fill value from metadata, coercing fill value metadata that is incompatible with the data type.
+    ///
+    /// A numeric (or boolean) fill value is converted to a numeric or boolean data type with saturation.
+    /// Any other incompatible fill value is replaced by zero, or an empty value for a variable size data type.
+    #[must_use]
+    pub fn fill_value_from_metadata_lenient(&self, fill_value: &FillValueMetadata) -> FillValue {
+        if let Ok(fill_value) = self.fill_value_from_metadata(fill_value) {
+            return fill_value;
+        }
+        let number = fill_value.try_as_float::<f64>().or_else(|| {
+            fill_value
+                .try_as_bool()
+                .map(|bool| f64::from(u8::from(bool)))
+        });
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        match (self, number) {
+            (Self::Bool, Some(number)) => FillValue::from(number != 0.0),
+            (Self::Int8, Some(number)) => FillValue::from(number as i8),
+            (Self::Int16, Some(number)) => FillValue::from(number as i16),
+            (Self::Int32, Some(number)) => FillValue::from(number as i32),
+            (Self::Int64, Some(number)) => FillValue::from(number as i64),
+            (Self::UInt8, Some(number)) => FillValue::from(number as u8),
+            (Self::UInt16, Some(number)) => FillValue::from(number as u16),
+            (Self::UInt32, Some(number)) => FillValue::from(number as u32),
+            (Self::UInt64, Some(number)) => FillValue::from(number as u64),
+            (Self::Float16, Some(number)) => FillValue::from(f16::from_f64(number)),
+            (Self::Float32, Some(number)) => FillValue::from(number as f32),
+            (Self::Float64, Some(number)) => FillValue::from(number),
+            (Self::BFloat16, Some(number)) => FillValue::from(bf16::from_f64(number)),
+            _ => match self.fixed_size() {
+                Some(size) => FillValue::new(vec![0; size]),
+                None => FillValue::new(vec![]),
+            },
+        }
+    }
+
     /// Create a fill value from metadata.
     ///
     /// # Errors
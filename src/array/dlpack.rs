@@ -0,0 +1,314 @@
+//! [DLPack](https://dmlc.github.io/dlpack/latest/) export of array data.
+//!
+//! A [`DLPackTensor`] owns retrieved array bytes and describes them with a `DLPack` data type, shape, and strides.
+//! It can be converted into a [`DLManagedTensor`] with [`DLPackTensor::into_managed_tensor`] for consumption by GPU frameworks and Python runtimes (e.g. wrapped in a `"dltensor"` `PyCapsule`).
+//!
+//! Owned array bytes are moved into the tensor without copying, and borrowed array bytes are copied once.
+//! Only fixed size data types with a `DLPack` equivalent are supported: `bool`, integers, floats, `bfloat16`, and complex numbers.
+//!
+//! This module requires the `dlpack` feature.
+
+use std::{ffi::c_void, ptr::NonNull};
+
+use super::{ArrayBytes, ArrayError, DataType};
+
+/// The `DLPack` major version implemented by this module.
+pub const DLPACK_MAJOR_VERSION: u32 = 0;
+
+/// The `DLPack` minor version implemented by this module.
+pub const DLPACK_MINOR_VERSION: u32 = 8;
+
+/// A `DLPack` device type (`DLDeviceType`).
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DLDeviceType {
+    /// CPU device (`kDLCPU`).
+    Cpu = 1,
+}
+
+/// A `DLPack` device (`DLDevice`).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DLDevice {
+    /// The device type.
+    pub device_type: DLDeviceType,
+    /// The device index.
+    pub device_id: i32,
+}
+
+/// A `DLPack` data type code (`DLDataTypeCode`).
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DLDataTypeCode {
+    /// Signed integer (`kDLInt`).
+    Int = 0,
+    /// Unsigned integer (`kDLUInt`).
+    UInt = 1,
+    /// IEEE floating point (`kDLFloat`).
+    Float = 2,
+    /// Brain floating point (`kDLBfloat`).
+    Bfloat = 4,
+    /// Complex number with IEEE floating point components (`kDLComplex`).
+    Complex = 5,
+    /// Boolean (`kDLBool`).
+    Bool = 6,
+}
+
+/// A `DLPack` data type (`DLDataType`).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DLDataType {
+    /// The type code.
+    pub code: DLDataTypeCode,
+    /// The number of bits of each lane.
+    pub bits: u8,
+    /// The number of lanes.
+    pub lanes: u16,
+}
+
+impl DLDataType {
+    /// Return the `DLPack` data type of a Zarr `data_type`, or [`None`] if it has no `DLPack` equivalent.
+    #[must_use]
+    pub fn from_data_type(data_type: &DataType) -> Option<Self> {
+        let (code, bits) = match data_type {
+            DataType::Bool => (DLDataTypeCode::Bool, 8),
+            DataType::Int8 => (DLDataTypeCode::Int, 8),
+            DataType::Int16 => (DLDataTypeCode::Int, 16),
+            DataType::Int32 => (DLDataTypeCode::Int, 32),
+            DataType::Int64 => (DLDataTypeCode::Int, 64),
+            DataType::UInt8 => (DLDataTypeCode::UInt, 8),
+            DataType::UInt16 => (DLDataTypeCode::UInt, 16),
+            DataType::UInt32 => (DLDataTypeCode::UInt, 32),
+            DataType::UInt64 => (DLDataTypeCode::UInt, 64),
+            DataType::Float16 => (DLDataTypeCode::Float, 16),
+            DataType::Float32 => (DLDataTypeCode::Float, 32),
+            DataType::Float64 => (DLDataTypeCode::Float, 64),
+            DataType::BFloat16 => (DLDataTypeCode::Bfloat, 16),
+            DataType::Complex64 => (DLDataTypeCode::Complex, 64),
+            DataType::Complex128 => (DLDataTypeCode::Complex, 128),
+            _ => return None,
+        };
+        Some(Self {
+            code,
+            bits,
+            lanes: 1,
+        })
+    }
+}
+
+/// A `DLPack` tensor (`DLTensor`).
+#[repr(C)]
+#[derive(Debug)]
+pub struct DLTensor {
+    /// The opaque data pointer.
+    pub data: *mut c_void,
+    /// The device of the data.
+    pub device: DLDevice,
+    /// The number of dimensions.
+    pub ndim: i32,
+    /// The data type.
+    pub dtype: DLDataType,
+    /// The shape of the tensor.
+    pub shape: *mut i64,
+    /// The strides of the tensor in elements.
+    pub strides: *mut i64,
+    /// The offset in bytes to the beginning pointer to data.
+    pub byte_offset: u64,
+}
+
+/// A `DLPack` managed tensor (`DLManagedTensor`).
+///
+/// The consumer must call the `deleter` once it no longer needs the tensor.
+#[repr(C)]
+#[derive(Debug)]
+pub struct DLManagedTensor {
+    /// The tensor.
+    pub dl_tensor: DLTensor,
+    /// The context of the producer.
+    pub manager_ctx: *mut c_void,
+    /// Destructor releasing the tensor and its context.
+    pub deleter: Option<unsafe extern "C" fn(*mut DLManagedTensor)>,
+}
+
+/// Owned array data with a `DLPack` data type, shape, and C contiguous strides.
+#[derive(Debug, Clone)]
+pub struct DLPackTensor {
+    bytes: Vec<u8>,
+    dtype: DLDataType,
+    shape: Vec<i64>,
+    strides: Vec<i64>,
+}
+
+impl DLPackTensor {
+    /// Create a new `DLPack` tensor from array `bytes` with `data_type` and `shape`.
+    ///
+    /// Owned `bytes` are not copied.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if
+    ///  - `data_type` has no `DLPack` equivalent,
+    ///  - `bytes` are variable length, or
+    ///  - the length of `bytes` does not match `shape`.
+    pub fn new(
+        bytes: ArrayBytes<'_>,
+        data_type: &DataType,
+        shape: &[u64],
+    ) -> Result<Self, ArrayError> {
+        let dtype =
+            DLDataType::from_data_type(data_type).ok_or(ArrayError::IncompatibleElementType)?;
+        let bytes = bytes.into_fixed()?.into_owned();
+        let expected_size =
+            shape.iter().product::<u64>() * data_type.fixed_size().unwrap_or_default() as u64;
+        if bytes.len() as u64 != expected_size {
+            return Err(ArrayError::InvalidBytesInputSize(
+                bytes.len(),
+                expected_size,
+            ));
+        }
+
+        let shape = shape
+            .iter()
+            .map(|size| i64::try_from(*size))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ArrayError::InvalidBytesInputSize(bytes.len(), expected_size))?;
+        let mut strides = vec![1; shape.len()];
+        for i in (0..shape.len().saturating_sub(1)).rev() {
+            strides[i] = strides[i + 1] * shape[i + 1];
+        }
+        Ok(Self {
+            bytes,
+            dtype,
+            shape,
+            strides,
+        })
+    }
+
+    /// Return the `DLPack` data type.
+    #[must_use]
+    pub const fn dtype(&self) -> DLDataType {
+        self.dtype
+    }
+
+    /// Return the shape.
+    #[must_use]
+    pub fn shape(&self) -> &[i64] {
+        &self.shape
+    }
+
+    /// Return the strides in elements.
+    #[must_use]
+    pub fn strides(&self) -> &[i64] {
+        &self.strides
+    }
+
+    /// Return the underlying bytes.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Convert into a [`DLManagedTensor`] on the CPU device.
+    ///
+    /// The data is not copied.
+    /// Ownership is transferred to the consumer, which must call the `deleter` of the managed tensor exactly once to release it.
+    ///
+    /// # Panics
+    /// Panics if the dimensionality exceeds [`i32::MAX`].
+    #[must_use]
+    pub fn into_managed_tensor(self) -> NonNull<DLManagedTensor> {
+        let ndim = i32::try_from(self.shape.len()).unwrap();
+        let mut context = Box::new(DLPackManagedContext {
+            managed_tensor: DLManagedTensor {
+                dl_tensor: DLTensor {
+                    data: std::ptr::null_mut(),
+                    device: DLDevice {
+                        device_type: DLDeviceType::Cpu,
+                        device_id: 0,
+                    },
+                    ndim,
+                    dtype: self.dtype,
+                    shape: std::ptr::null_mut(),
+                    strides: std::ptr::null_mut(),
+                    byte_offset: 0,
+                },
+                manager_ctx: std::ptr::null_mut(),
+                deleter: Some(dlpack_managed_tensor_deleter),
+            },
+            tensor: self,
+        });
+        // The heap allocations of the tensor do not move when the context is boxed
+        let tensor = &mut context.tensor;
+        context.managed_tensor.dl_tensor.data = tensor.bytes.as_mut_ptr().cast::<c_void>();
+        context.managed_tensor.dl_tensor.shape = tensor.shape.as_mut_ptr();
+        context.managed_tensor.dl_tensor.strides = tensor.strides.as_mut_ptr();
+        let context = Box::into_raw(context);
+        unsafe {
+            (*context).managed_tensor.manager_ctx = context.cast::<c_void>();
+            NonNull::new_unchecked(std::ptr::addr_of_mut!((*context).managed_tensor))
+        }
+    }
+}
+
+/// The boxed context of a [`DLManagedTensor`] created by [`DLPackTensor::into_managed_tensor`].
+#[repr(C)]
+struct DLPackManagedContext {
+    managed_tensor: DLManagedTensor,
+    tensor: DLPackTensor,
+}
+
+unsafe extern "C" fn dlpack_managed_tensor_deleter(managed_tensor: *mut DLManagedTensor) {
+    if !managed_tensor.is_null() {
+        let context = unsafe { (*managed_tensor).manager_ctx }.cast::<DLPackManagedContext>();
+        drop(unsafe { Box::from_raw(context) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dlpack_tensor() {
+        let bytes: Vec<u8> = [1u16, 2, 3, 4, 5, 6]
+            .iter()
+            .flat_map(|v| v.to_ne_bytes())
+            .collect();
+        let tensor = DLPackTensor::new(bytes.clone().into(), &DataType::UInt16, &[2, 3]).unwrap();
+        assert_eq!(
+            tensor.dtype(),
+            DLDataType {
+                code: DLDataTypeCode::UInt,
+                bits: 16,
+                lanes: 1
+            }
+        );
+        assert_eq!(tensor.shape(), &[2, 3]);
+        assert_eq!(tensor.strides(), &[3, 1]);
+
+        let managed = tensor.into_managed_tensor();
+        unsafe {
+            let dl_tensor = &managed.as_ref().dl_tensor;
+            assert_eq!(dl_tensor.ndim, 2);
+            assert_eq!(*dl_tensor.shape.add(1), 3);
+            assert_eq!(*dl_tensor.strides, 3);
+            assert_eq!(
+                std::slice::from_raw_parts(dl_tensor.data.cast::<u8>(), bytes.len()),
+                bytes.as_slice()
+            );
+            (managed.as_ref().deleter.unwrap())(managed.as_ptr());
+        }
+    }
+
+    #[test]
+    fn dlpack_tensor_invalid() {
+        assert!(DLPackTensor::new(vec![0u8; 4].into(), &DataType::RawBits(2), &[2]).is_err());
+        assert!(DLPackTensor::new(vec![0u8; 4].into(), &DataType::UInt16, &[3]).is_err());
+        assert!(DLPackTensor::new(
+            ArrayBytes::new_vlen(vec![], vec![0, 0]),
+            &DataType::String,
+            &[1]
+        )
+        .is_err());
+        assert!(DLPackTensor::new(vec![0u8; 16].into(), &DataType::Complex64, &[2]).is_ok());
+    }
+}
@@ -6,6 +6,8 @@ use ArrayError::IncompatibleElementType as IET;
 use super::{convert_from_bytes_slice, transmute_to_bytes, ArrayBytes, ArrayError, DataType};
 
 /// A trait representing an array element type.
+///
+/// With the `derive` feature, [`Element`] and [`ElementOwned`] can be derived for plain-old-data structs with `#[derive(ZarrsElement)]`.
 pub trait Element: Sized + Clone {
     /// Validate the data type.
     ///
@@ -33,6 +35,65 @@ pub trait ElementOwned: Element {
         data_type: &DataType,
         bytes: ArrayBytes<'_>,
     ) -> Result<Vec<Self>, ArrayError>;
+
+    /// Returns a [`NativeBytesRepresentation`] if the fixed length bytes of `data_type` are the native in-memory representation of elements of this type.
+    ///
+    /// If returned, array subsets can be decoded directly into an allocation of elements without an intermediate copy.
+    /// A [`NativeBytesRepresentation`] can only be created for [`bytemuck::Pod`] types.
+    /// The default implementation returns [`None`].
+    #[must_use]
+    fn native_bytes_representation(
+        data_type: &DataType,
+    ) -> Option<NativeBytesRepresentation<Self>> {
+        let _ = data_type;
+        None
+    }
+}
+
+/// Evidence that elements of type `T` can be viewed as bytes and created from any bytes.
+///
+/// See [`ElementOwned::native_bytes_representation`].
+pub struct NativeBytesRepresentation<T> {
+    zeroed: fn() -> T,
+    as_bytes_mut: fn(&mut [T]) -> &mut [u8],
+    try_from_bytes: fn(&[u8]) -> Result<&[T], bytemuck::PodCastError>,
+}
+
+impl<T: bytemuck::Pod> NativeBytesRepresentation<T> {
+    /// Create a new native bytes representation of the [`bytemuck::Pod`] type `T`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            zeroed: T::zeroed,
+            as_bytes_mut: bytemuck::cast_slice_mut,
+            try_from_bytes: bytemuck::try_cast_slice,
+        }
+    }
+}
+
+impl<T: bytemuck::Pod> Default for NativeBytesRepresentation<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> NativeBytesRepresentation<T> {
+    /// Return an element with all bytes zero.
+    #[cfg_attr(not(feature = "ndarray"), allow(dead_code))]
+    pub(crate) fn zeroed(&self) -> T {
+        (self.zeroed)()
+    }
+
+    /// Return the bytes of `elements`.
+    pub(crate) fn as_bytes_mut<'a>(&self, elements: &'a mut [T]) -> &'a mut [u8] {
+        (self.as_bytes_mut)(elements)
+    }
+
+    /// Return the elements of `bytes`, or [`None`] if `bytes` is not aligned to `T` or its length is not a multiple of the size of `T`.
+    #[cfg_attr(not(feature = "ndarray"), allow(dead_code))]
+    pub(crate) fn try_from_bytes<'a>(&self, bytes: &'a [u8]) -> Option<&'a [T]> {
+        (self.try_from_bytes)(bytes).ok()
+    }
 }
 
 /// A marker trait for a fixed length element.
@@ -115,6 +176,14 @@ macro_rules! impl_element_pod {
                 let bytes = bytes.into_fixed()?;
                 Ok(convert_from_bytes_slice::<Self>(&bytes))
             }
+
+            fn native_bytes_representation(
+                data_type: &DataType,
+            ) -> Option<NativeBytesRepresentation<Self>> {
+                Self::validate_data_type(data_type)
+                    .is_ok()
+                    .then(NativeBytesRepresentation::new)
+            }
         }
     };
 }
@@ -161,6 +230,14 @@ impl<const N: usize> ElementOwned for [u8; N] {
         let bytes = bytes.into_fixed()?;
         Ok(convert_from_bytes_slice::<Self>(&bytes))
     }
+
+    fn native_bytes_representation(
+        data_type: &DataType,
+    ) -> Option<NativeBytesRepresentation<Self>> {
+        Self::validate_data_type(data_type)
+            .is_ok()
+            .then(NativeBytesRepresentation::new)
+    }
 }
 
 macro_rules! impl_element_string {
@@ -0,0 +1,369 @@
+//! [`image`](::image) crate integration for 2-D slices.
+//!
+//! [`Array::retrieve_array_subset_image`] retrieves a 2-D array subset as a [`DynamicImage`], and [`Array::store_array_subset_image`] stores an [`ImageBuffer`] into an array subset.
+//!
+//! An image subset has the shape `[1, ..., 1, height, width]` for greyscale images, or `[1, ..., 1, height, width, channels]` with 3 (RGB) or 4 (RGBA) channels.
+//! A retrieved subset is interpreted as RGB or RGBA if it has three dimensions that are not 1 and the last has a size of 3 or 4.
+//!
+//! Retrieved elements are mapped to pixels as follows:
+//!  - `uint8` and `bool` elements are mapped to 8-bit pixels (`bool` as 0 or 255),
+//!  - `uint16` elements are mapped to 16-bit pixels, and
+//!  - other numeric elements are linearly mapped to 8-bit pixels from the minimum and maximum values of the subset.
+//!
+//! An [`ImageWindow`] overrides this mapping, linearly mapping elements from the window minimum and maximum to 8-bit pixels and clamping elements outside of the window.
+//!
+//! This module requires the `image` feature.
+//!
+//! ### Example
+//! ```rust
+//! # use std::sync::Arc;
+//! # use zarrs::array::{ArrayBuilder, DataType, FillValue};
+//! # use zarrs::array_subset::ArraySubset;
+//! use zarrs::array::image::ImageWindow;
+//!
+//! # let store = Arc::new(zarrs::storage::store::MemoryStore::new());
+//! let array = ArrayBuilder::new(vec![1, 2, 2], DataType::Float32, vec![1, 2, 2].try_into()?, FillValue::from(0.0f32))
+//!     .build(store, "/array")?;
+//! array.store_array_subset_elements::<f32>(&ArraySubset::new_with_shape(vec![1, 2, 2]), &[0.0, 0.5, 1.0, 2.0])?;
+//!
+//! let subset = ArraySubset::new_with_shape(vec![1, 2, 2]);
+//! let image = array.retrieve_array_subset_image(&subset, Some(ImageWindow::new(0.0, 1.0)))?;
+//! assert_eq!(image.to_luma8().into_raw(), vec![0, 128, 255, 255]);
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use ::image::{DynamicImage, ImageBuffer, Pixel};
+
+use crate::{
+    array_subset::ArraySubset,
+    storage::{ReadableStorageTraits, ReadableWritableStorageTraits},
+};
+
+use super::{Array, ArrayError, DataType, Element};
+
+/// A linear window mapping element values to 8-bit pixel intensities.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageWindow {
+    min: f64,
+    max: f64,
+}
+
+impl ImageWindow {
+    /// Create a new window mapping `min` to 0 and `max` to 255.
+    #[must_use]
+    pub const fn new(min: f64, max: f64) -> Self {
+        Self { min, max }
+    }
+
+    /// Return the element value mapped to 0.
+    #[must_use]
+    pub const fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// Return the element value mapped to 255.
+    #[must_use]
+    pub const fn max(&self) -> f64 {
+        self.max
+    }
+
+    /// Map `value` to an 8-bit pixel intensity.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn apply(&self, value: f64) -> u8 {
+        let range = self.max - self.min;
+        if range > 0.0 {
+            ((value - self.min) / range * 255.0)
+                .round()
+                .clamp(0.0, 255.0) as u8
+        } else if value > self.min {
+            255
+        } else {
+            0
+        }
+    }
+}
+
+/// Return the height, width, and number of channels of an image `array_subset`.
+fn image_dimensions(
+    array_subset: &ArraySubset,
+    array_shape: &[u64],
+) -> Result<(u32, u32, u8), ArrayError> {
+    let invalid = || ArrayError::InvalidArraySubset(array_subset.clone(), array_shape.to_vec());
+    let shape = array_subset.shape();
+    let non_unit_dimensions = shape.iter().filter(|size| **size != 1).count();
+    let channels = match shape.last() {
+        Some(3) if non_unit_dimensions == 3 => 3,
+        Some(4) if non_unit_dimensions == 3 => 4,
+        _ => 1,
+    };
+    let spatial_end = shape.len() - usize::from(channels > 1);
+    if spatial_end < 2 || shape[..spatial_end - 2].iter().any(|size| *size != 1) {
+        return Err(invalid());
+    }
+    let height = u32::try_from(shape[spatial_end - 2]).map_err(|_| invalid())?;
+    let width = u32::try_from(shape[spatial_end - 1]).map_err(|_| invalid())?;
+    Ok((height, width, channels))
+}
+
+/// Create a [`DynamicImage`] from 8-bit `pixels`.
+fn dynamic_image_u8(
+    width: u32,
+    height: u32,
+    channels: u8,
+    pixels: Vec<u8>,
+) -> Option<DynamicImage> {
+    match channels {
+        1 => ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageLuma8),
+        3 => ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgb8),
+        4 => ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgba8),
+        _ => None,
+    }
+}
+
+/// Create a [`DynamicImage`] from 16-bit `pixels`.
+fn dynamic_image_u16(
+    width: u32,
+    height: u32,
+    channels: u8,
+    pixels: Vec<u16>,
+) -> Option<DynamicImage> {
+    match channels {
+        1 => ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageLuma16),
+        3 => ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgb16),
+        4 => ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgba16),
+        _ => None,
+    }
+}
+
+/// Window `elements` to 8-bit pixels, defaulting to the minimum and maximum of the elements.
+fn window_elements<T: Copy + Into<f64>>(elements: &[T], window: Option<ImageWindow>) -> Vec<u8> {
+    let window = window.unwrap_or_else(|| {
+        let (min, max) = elements
+            .iter()
+            .map(|element| (*element).into())
+            .filter(|element: &f64| element.is_finite())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), element| {
+                (min.min(element), max.max(element))
+            });
+        ImageWindow::new(min, max)
+    });
+    elements
+        .iter()
+        .map(|element| window.apply((*element).into()))
+        .collect()
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
+    /// Read and decode the 2-D `array_subset` of array into a [`DynamicImage`], optionally with a `window`.
+    ///
+    /// See the [module documentation](crate::array::image) for the shape of image subsets and the mapping of elements to pixels.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if:
+    ///  - the array subset does not have an image shape,
+    ///  - the data type is not numeric or boolean,
+    ///  - an array subset is invalid or out of bounds of the array,
+    ///  - there is a codec decoding error, or
+    ///  - an underlying store error.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn retrieve_array_subset_image(
+        &self,
+        array_subset: &ArraySubset,
+        window: Option<ImageWindow>,
+    ) -> Result<DynamicImage, ArrayError> {
+        let (height, width, channels) = image_dimensions(array_subset, self.shape())?;
+
+        macro_rules! windowed {
+            ( $type:ty ) => {
+                window_elements(
+                    &self.retrieve_array_subset_elements::<$type>(array_subset)?,
+                    window,
+                )
+            };
+        }
+        macro_rules! windowed_lossy {
+            ( $type:ty ) => {
+                window_elements(
+                    &self
+                        .retrieve_array_subset_elements::<$type>(array_subset)?
+                        .into_iter()
+                        .map(|element| element as f64)
+                        .collect::<Vec<_>>(),
+                    window,
+                )
+            };
+        }
+
+        let image = match (self.data_type(), window) {
+            (DataType::UInt8, None) => dynamic_image_u8(
+                width,
+                height,
+                channels,
+                self.retrieve_array_subset_elements::<u8>(array_subset)?,
+            ),
+            (DataType::UInt16, None) => dynamic_image_u16(
+                width,
+                height,
+                channels,
+                self.retrieve_array_subset_elements::<u16>(array_subset)?,
+            ),
+            (DataType::Bool, _) => dynamic_image_u8(
+                width,
+                height,
+                channels,
+                self.retrieve_array_subset_elements::<bool>(array_subset)?
+                    .into_iter()
+                    .map(|element| if element { 255 } else { 0 })
+                    .collect(),
+            ),
+            (DataType::UInt8, Some(_)) => dynamic_image_u8(width, height, channels, windowed!(u8)),
+            (DataType::UInt16, Some(_)) => {
+                dynamic_image_u8(width, height, channels, windowed!(u16))
+            }
+            (DataType::UInt32, _) => dynamic_image_u8(width, height, channels, windowed!(u32)),
+            (DataType::UInt64, _) => {
+                dynamic_image_u8(width, height, channels, windowed_lossy!(u64))
+            }
+            (DataType::Int8, _) => dynamic_image_u8(width, height, channels, windowed!(i8)),
+            (DataType::Int16, _) => dynamic_image_u8(width, height, channels, windowed!(i16)),
+            (DataType::Int32, _) => dynamic_image_u8(width, height, channels, windowed!(i32)),
+            (DataType::Int64, _) => dynamic_image_u8(width, height, channels, windowed_lossy!(i64)),
+            (DataType::Float16, _) => {
+                dynamic_image_u8(width, height, channels, windowed!(half::f16))
+            }
+            (DataType::BFloat16, _) => {
+                dynamic_image_u8(width, height, channels, windowed!(half::bf16))
+            }
+            (DataType::Float32, _) => dynamic_image_u8(width, height, channels, windowed!(f32)),
+            (DataType::Float64, _) => dynamic_image_u8(width, height, channels, windowed!(f64)),
+            _ => return Err(ArrayError::IncompatibleElementType),
+        };
+        image.ok_or_else(|| {
+            ArrayError::InvalidArraySubset(array_subset.clone(), self.shape().to_vec())
+        })
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits + 'static> Array<TStorage> {
+    /// Encode `image` and store it in the array subset starting at `subset_start`.
+    ///
+    /// The array subset has the shape `[1, ..., 1, height, width]` for greyscale images, or `[1, ..., 1, height, width, channels]` for images with multiple channels.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if:
+    ///  - the dimensionality of `subset_start` is incompatible with the image,
+    ///  - the subpixel type of the image does not match the data type,
+    ///  - the array subset is out of bounds of the array, or
+    ///  - an underlying store or codec error.
+    pub fn store_array_subset_image<P: Pixel>(
+        &self,
+        subset_start: &[u64],
+        image: &ImageBuffer<P, Vec<P::Subpixel>>,
+    ) -> Result<(), ArrayError>
+    where
+        P::Subpixel: Element,
+    {
+        let channels = u64::from(P::CHANNEL_COUNT);
+        let image_shape = if channels > 1 {
+            vec![
+                u64::from(image.height()),
+                u64::from(image.width()),
+                channels,
+            ]
+        } else {
+            vec![u64::from(image.height()), u64::from(image.width())]
+        };
+        let Some(leading) = subset_start.len().checked_sub(image_shape.len()) else {
+            return Err(ArrayError::InvalidDataShape(
+                vec![subset_start.len()],
+                vec![image_shape.len()],
+            ));
+        };
+        let subset_shape = std::iter::repeat(1)
+            .take(leading)
+            .chain(image_shape)
+            .collect::<Vec<_>>();
+        let array_subset = ArraySubset::new_with_start_shape(subset_start.to_vec(), subset_shape)?;
+        self.store_array_subset_elements::<P::Subpixel>(&array_subset, image.as_raw())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ::image::{GrayImage, Luma, Rgb, RgbImage};
+
+    use crate::{
+        array::{ArrayBuilder, FillValue},
+        storage::store::MemoryStore,
+    };
+
+    use super::*;
+
+    #[test]
+    fn image_window() {
+        let window = ImageWindow::new(-1.0, 1.0);
+        assert_eq!(window.apply(-2.0), 0);
+        assert_eq!(window.apply(0.0), 128);
+        assert_eq!(window.apply(1.0), 255);
+        assert_eq!(ImageWindow::new(1.0, 1.0).apply(2.0), 255);
+    }
+
+    #[test]
+    fn image_luma_round_trip() {
+        let store = Arc::new(MemoryStore::new());
+        let array = ArrayBuilder::new(
+            vec![2, 4, 3],
+            DataType::UInt16,
+            vec![1, 2, 2].try_into().unwrap(),
+            FillValue::from(0u16),
+        )
+        .build(store, "/array")
+        .unwrap();
+
+        let image = ImageBuffer::from_fn(3, 4, |x, y| Luma([u16::try_from(x + y * 3).unwrap()]));
+        array.store_array_subset_image(&[1, 0, 0], &image).unwrap();
+        let subset = ArraySubset::new_with_ranges(&[1..2, 0..4, 0..3]);
+        let image_out = array.retrieve_array_subset_image(&subset, None).unwrap();
+        assert_eq!(image_out.as_luma16(), Some(&image));
+
+        let image_windowed = array
+            .retrieve_array_subset_image(&subset, Some(ImageWindow::new(0.0, 11.0)))
+            .unwrap();
+        let image_windowed: &GrayImage = image_windowed.as_luma8().unwrap();
+        assert_eq!(image_windowed.get_pixel(0, 0), &Luma([0]));
+        assert_eq!(image_windowed.get_pixel(2, 3), &Luma([255]));
+
+        assert!(array
+            .retrieve_array_subset_image(&ArraySubset::new_with_ranges(&[0..2, 0..4, 0..2]), None)
+            .is_err());
+        let image_u8 = ImageBuffer::from_pixel(3, 4, Luma([0u8]));
+        assert!(array
+            .store_array_subset_image(&[0, 0, 0], &image_u8)
+            .is_err());
+    }
+
+    #[test]
+    fn image_rgb_round_trip() {
+        let store = Arc::new(MemoryStore::new());
+        let array = ArrayBuilder::new(
+            vec![2, 2, 3],
+            DataType::UInt8,
+            vec![2, 2, 3].try_into().unwrap(),
+            FillValue::from(0u8),
+        )
+        .build(store, "/array")
+        .unwrap();
+        let image: RgbImage = ImageBuffer::from_fn(2, 2, |x, y| {
+            let value = u8::try_from(x + y * 2).unwrap();
+            Rgb([value, value + 10, value + 20])
+        });
+        array.store_array_subset_image(&[0, 0, 0], &image).unwrap();
+        let image_out = array
+            .retrieve_array_subset_image(&ArraySubset::new_with_shape(vec![2, 2, 3]), None)
+            .unwrap();
+        assert_eq!(image_out.as_rgb8(), Some(&image));
+    }
+}
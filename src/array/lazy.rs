@@ -0,0 +1,369 @@
+//! Lazy chunkwise evaluation of element-wise expressions.
+//!
+//! [`Array::lazy`] creates a [`LazyArray`], an element-wise expression over arrays and scalars that is not evaluated until requested.
+//! Expressions are composed with the [`Add`], [`Sub`], [`Mul`], and [`Div`] operators (or their methods) with either scalars or other [`LazyArray`]s, and with [`LazyArray::map`].
+//! The expression is evaluated at the same array indices of each array, so arrays in an expression usually have the same shape.
+//!
+//! [`LazyArray::retrieve`] evaluates an expression over an array subset, and [`LazyArray::store_to`] evaluates it chunk-by-chunk of a destination array in parallel.
+//! Only the chunks being evaluated are held in memory, so derived arrays of any size can be produced without a hand-written chunk loop.
+//!
+//! ### Example
+//! ```rust
+//! # use std::sync::Arc;
+//! # use zarrs::array::{ArrayBuilder, DataType};
+//! # use zarrs::array_subset::ArraySubset;
+//! # use zarrs::storage::store::MemoryStore;
+//! use std::ops::{Add, Mul};
+//!
+//! # let store = Arc::new(MemoryStore::new());
+//! # let builder = ArrayBuilder::new(vec![4, 4], DataType::Float32, vec![2, 2].try_into()?, 0.0f32.into());
+//! # let array = builder.build(store.clone(), "/array")?;
+//! # let other = builder.build(store.clone(), "/other")?;
+//! # let dst = builder.build(store.clone(), "/dst")?;
+//! # array.store_array_subset_elements(&ArraySubset::new_with_shape(vec![4, 4]), &[1.0f32; 16])?;
+//! # other.store_array_subset_elements(&ArraySubset::new_with_shape(vec![4, 4]), &[3.0f32; 16])?;
+//! array.lazy::<f32>().mul(2.0).add(other.lazy()).store_to(&dst)?;
+//! assert_eq!(
+//!     dst.retrieve_array_subset_elements::<f32>(&ArraySubset::new_with_shape(vec![4, 4]))?,
+//!     vec![5.0; 16]
+//! );
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use std::{
+    ops::{Add, Div, Mul, Sub},
+    sync::Arc,
+};
+
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use rayon_iter_concurrent_limit::iter_concurrent_limit;
+
+use crate::{
+    array_subset::ArraySubset,
+    storage::{ReadableStorageTraits, ReadableWritableStorageTraits},
+};
+
+use super::{codec::CodecOptions, Array, ArrayError, ElementOwned};
+
+/// An element of a [`LazyArray`].
+pub trait LazyElement:
+    ElementOwned
+    + Copy
+    + Send
+    + Sync
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+{
+}
+
+impl<T> LazyElement for T where
+    T: ElementOwned
+        + Copy
+        + Send
+        + Sync
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+{
+}
+
+/// An array that a [`LazyArray`] can retrieve elements from.
+trait LazySource<T>: Send + Sync {
+    fn retrieve(
+        &self,
+        array_subset: &ArraySubset,
+        options: &CodecOptions,
+    ) -> Result<Vec<T>, ArrayError>;
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits + 'static, T: LazyElement> LazySource<T>
+    for Array<TStorage>
+{
+    fn retrieve(
+        &self,
+        array_subset: &ArraySubset,
+        options: &CodecOptions,
+    ) -> Result<Vec<T>, ArrayError> {
+        self.retrieve_array_subset_elements_opt(array_subset, options)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum LazyOperator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl LazyOperator {
+    fn apply<T: LazyElement>(self, lhs: T, rhs: T) -> T {
+        match self {
+            Self::Add => lhs + rhs,
+            Self::Sub => lhs - rhs,
+            Self::Mul => lhs * rhs,
+            Self::Div => lhs / rhs,
+        }
+    }
+}
+
+#[derive(Clone)]
+enum LazyExpression<'a, T> {
+    Array(&'a dyn LazySource<T>),
+    Scalar(T),
+    Binary(LazyOperator, Box<LazyArray<'a, T>>, Box<LazyArray<'a, T>>),
+    Map(
+        Box<LazyArray<'a, T>>,
+        Arc<dyn Fn(T) -> T + Send + Sync + 'a>,
+    ),
+}
+
+/// The evaluation of a [`LazyArray`] over an array subset.
+enum LazyEvaluation<T> {
+    Scalar(T),
+    Elements(Vec<T>),
+}
+
+/// A lazily evaluated element-wise expression over arrays and scalars.
+///
+/// See the [module documentation](self).
+#[derive(Clone)]
+pub struct LazyArray<'a, T> {
+    expression: LazyExpression<'a, T>,
+}
+
+impl<T: LazyElement> From<T> for LazyArray<'_, T> {
+    fn from(value: T) -> Self {
+        Self {
+            expression: LazyExpression::Scalar(value),
+        }
+    }
+}
+
+impl<'a, T: LazyElement> LazyArray<'a, T> {
+    fn binary(self, operator: LazyOperator, rhs: impl Into<Self>) -> Self {
+        Self {
+            expression: LazyExpression::Binary(operator, Box::new(self), Box::new(rhs.into())),
+        }
+    }
+
+    /// Apply `f` to each element.
+    #[must_use]
+    pub fn map(self, f: impl Fn(T) -> T + Send + Sync + 'a) -> Self {
+        Self {
+            expression: LazyExpression::Map(Box::new(self), Arc::new(f)),
+        }
+    }
+
+    fn evaluate(
+        &self,
+        array_subset: &ArraySubset,
+        options: &CodecOptions,
+    ) -> Result<LazyEvaluation<T>, ArrayError> {
+        Ok(match &self.expression {
+            LazyExpression::Array(array) => {
+                LazyEvaluation::Elements(array.retrieve(array_subset, options)?)
+            }
+            LazyExpression::Scalar(value) => LazyEvaluation::Scalar(*value),
+            LazyExpression::Binary(operator, lhs, rhs) => {
+                let operator = *operator;
+                match (
+                    lhs.evaluate(array_subset, options)?,
+                    rhs.evaluate(array_subset, options)?,
+                ) {
+                    (LazyEvaluation::Scalar(lhs), LazyEvaluation::Scalar(rhs)) => {
+                        LazyEvaluation::Scalar(operator.apply(lhs, rhs))
+                    }
+                    (LazyEvaluation::Elements(mut lhs), LazyEvaluation::Scalar(rhs)) => {
+                        for lhs in &mut lhs {
+                            *lhs = operator.apply(*lhs, rhs);
+                        }
+                        LazyEvaluation::Elements(lhs)
+                    }
+                    (LazyEvaluation::Scalar(lhs), LazyEvaluation::Elements(mut rhs)) => {
+                        for rhs in &mut rhs {
+                            *rhs = operator.apply(lhs, *rhs);
+                        }
+                        LazyEvaluation::Elements(rhs)
+                    }
+                    (LazyEvaluation::Elements(mut lhs), LazyEvaluation::Elements(rhs)) => {
+                        lhs.iter_mut()
+                            .zip(rhs)
+                            .for_each(|(lhs, rhs)| *lhs = operator.apply(*lhs, rhs));
+                        LazyEvaluation::Elements(lhs)
+                    }
+                }
+            }
+            LazyExpression::Map(array, f) => match array.evaluate(array_subset, options)? {
+                LazyEvaluation::Scalar(value) => LazyEvaluation::Scalar(f(value)),
+                LazyEvaluation::Elements(mut elements) => {
+                    for element in &mut elements {
+                        *element = f(*element);
+                    }
+                    LazyEvaluation::Elements(elements)
+                }
+            },
+        })
+    }
+
+    /// Evaluate the expression over `array_subset`.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if an array in the expression cannot be retrieved over `array_subset` or its data type is incompatible with `T`.
+    pub fn retrieve(&self, array_subset: &ArraySubset) -> Result<Vec<T>, ArrayError> {
+        self.retrieve_opt(array_subset, &CodecOptions::default())
+    }
+
+    /// Explicit options version of [`retrieve`](LazyArray::retrieve).
+    #[allow(clippy::missing_errors_doc)]
+    pub fn retrieve_opt(
+        &self,
+        array_subset: &ArraySubset,
+        options: &CodecOptions,
+    ) -> Result<Vec<T>, ArrayError> {
+        Ok(match self.evaluate(array_subset, options)? {
+            LazyEvaluation::Scalar(value) => vec![value; array_subset.num_elements_usize()],
+            LazyEvaluation::Elements(elements) => elements,
+        })
+    }
+
+    /// Evaluate the expression over the entire shape of `dst` and store it in `dst`.
+    ///
+    /// The expression is evaluated and stored chunk-by-chunk of `dst` in parallel.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if an array in the expression cannot be retrieved over the shape of `dst`, its data type is incompatible with `T`, or there is a failure storing a chunk of `dst`.
+    pub fn store_to<TStorage: ?Sized + ReadableWritableStorageTraits + 'static>(
+        &self,
+        dst: &Array<TStorage>,
+    ) -> Result<(), ArrayError> {
+        self.store_to_opt(dst, &CodecOptions::default())
+    }
+
+    /// Explicit options version of [`store_to`](LazyArray::store_to).
+    ///
+    /// Up to [`concurrent_target`](CodecOptions::concurrent_target) chunks are evaluated at once, which bounds memory usage.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn store_to_opt<TStorage: ?Sized + ReadableWritableStorageTraits + 'static>(
+        &self,
+        dst: &Array<TStorage>,
+        options: &CodecOptions,
+    ) -> Result<(), ArrayError> {
+        let array_subset = ArraySubset::new_with_shape(dst.shape().to_vec());
+        let Some(chunks) = dst.chunks_in_array_subset(&array_subset)? else {
+            return Err(ArrayError::InvalidArraySubset(
+                array_subset,
+                dst.shape().to_vec(),
+            ));
+        };
+        let chunk_concurrent_limit = options.concurrent_target().max(1);
+        let mut chunk_options = options.clone();
+        chunk_options.set_concurrent_target(1);
+        let store_chunk = |chunk_indices: Vec<u64>| -> Result<(), ArrayError> {
+            let chunk_subset = dst.chunk_subset_bounded(&chunk_indices)?;
+            let elements = self.retrieve_opt(&chunk_subset, &chunk_options)?;
+            dst.store_array_subset_elements_opt(&chunk_subset, &elements, &chunk_options)
+                .map_err(|err| dst.chunk_error(&chunk_indices, err))
+        };
+        let indices = chunks.indices();
+        iter_concurrent_limit!(chunk_concurrent_limit, indices, try_for_each, store_chunk)
+    }
+}
+
+impl<'a, T: LazyElement, R: Into<LazyArray<'a, T>>> Add<R> for LazyArray<'a, T> {
+    type Output = Self;
+
+    fn add(self, rhs: R) -> Self {
+        self.binary(LazyOperator::Add, rhs)
+    }
+}
+
+impl<'a, T: LazyElement, R: Into<LazyArray<'a, T>>> Sub<R> for LazyArray<'a, T> {
+    type Output = Self;
+
+    fn sub(self, rhs: R) -> Self {
+        self.binary(LazyOperator::Sub, rhs)
+    }
+}
+
+impl<'a, T: LazyElement, R: Into<LazyArray<'a, T>>> Mul<R> for LazyArray<'a, T> {
+    type Output = Self;
+
+    fn mul(self, rhs: R) -> Self {
+        self.binary(LazyOperator::Mul, rhs)
+    }
+}
+
+impl<'a, T: LazyElement, R: Into<LazyArray<'a, T>>> Div<R> for LazyArray<'a, T> {
+    type Output = Self;
+
+    fn div(self, rhs: R) -> Self {
+        self.binary(LazyOperator::Div, rhs)
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
+    /// Create a [`LazyArray`] of the elements of this array.
+    ///
+    /// See the [`lazy`](crate::array::lazy) module.
+    #[must_use]
+    pub fn lazy<T: LazyElement>(&self) -> LazyArray<'_, T> {
+        LazyArray {
+            expression: LazyExpression::Array(self),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        array::{ArrayBuilder, DataType},
+        storage::store::MemoryStore,
+    };
+
+    use super::*;
+
+    #[test]
+    fn lazy_array() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        let builder = ArrayBuilder::new(
+            vec![5, 3],
+            DataType::Int32,
+            vec![2, 2].try_into()?,
+            0i32.into(),
+        );
+        let a = builder.build(store.clone(), "/a")?;
+        let b = builder.build(store.clone(), "/b")?;
+        let dst = builder.build(store.clone(), "/dst")?;
+        let array_subset = ArraySubset::new_with_shape(vec![5, 3]);
+        a.store_array_subset_elements(&array_subset, &(0..15).collect::<Vec<i32>>())?;
+        b.store_array_subset_elements(&array_subset, &[2; 15])?;
+
+        let expression = (a.lazy::<i32>() - 1) * b.lazy() / 2;
+        assert_eq!(
+            expression.retrieve(&ArraySubset::new_with_ranges(&[1..2, 0..3]))?,
+            vec![2, 3, 4]
+        );
+        expression
+            .clone()
+            .map(|value| value.max(0))
+            .store_to(&dst)?;
+        assert_eq!(
+            dst.retrieve_array_subset_elements::<i32>(&array_subset)?,
+            (0..15).map(|value| (value - 1).max(0)).collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            LazyArray::from(3i32).add(4).retrieve(&array_subset)?,
+            vec![7; 15]
+        );
+        assert!(a.lazy::<f32>().retrieve(&array_subset).is_err());
+        assert!(expression
+            .retrieve(&ArraySubset::new_with_shape(vec![5]))
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,231 @@
+//! [`nalgebra`](::nalgebra) matrix integration for 2-D subsets.
+//!
+//! [`Array::retrieve_array_subset_dmatrix`] and [`Array::retrieve_array_subset_smatrix`] retrieve a 2-D array subset as a dynamically or statically sized matrix, and [`Array::store_array_subset_matrix`] stores any matrix into an array subset.
+//!
+//! A matrix subset has the shape `[1, ..., 1, rows, columns]`.
+//! Array elements are in C (row-major) order, whereas `nalgebra` matrices are column-major, so elements are transposed on retrieval and storage.
+//!
+//! This module requires the `nalgebra` feature.
+//!
+//! ### Example
+//! ```rust
+//! # use std::sync::Arc;
+//! # use zarrs::array::{ArrayBuilder, DataType, FillValue};
+//! # use zarrs::array_subset::ArraySubset;
+//! use nalgebra::{Matrix2x3, SMatrix};
+//!
+//! # let store = Arc::new(zarrs::storage::store::MemoryStore::new());
+//! let array = ArrayBuilder::new(vec![4, 4], DataType::Float64, vec![2, 2].try_into()?, FillValue::from(0.0f64))
+//!     .build(store, "/array")?;
+//! let matrix = Matrix2x3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0);
+//! array.store_array_subset_matrix(&[1, 0], &matrix)?;
+//!
+//! let dmatrix = array.retrieve_array_subset_dmatrix::<f64>(&ArraySubset::new_with_ranges(&[1..3, 0..3]))?;
+//! assert_eq!(dmatrix, matrix);
+//! let smatrix: SMatrix<f64, 2, 2> = array.retrieve_array_subset_smatrix(&[1, 1])?;
+//! assert_eq!(smatrix, SMatrix::<f64, 2, 2>::new(2.0, 3.0, 5.0, 6.0));
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use ::nalgebra::{DMatrix, Dim, Matrix, RawStorage, SMatrix, Scalar};
+
+use crate::{
+    array_subset::ArraySubset,
+    storage::{ReadableStorageTraits, ReadableWritableStorageTraits},
+};
+
+use super::{Array, ArrayError, Element, ElementOwned};
+
+/// Return the number of rows and columns of a matrix `array_subset`.
+fn matrix_dimensions(
+    array_subset: &ArraySubset,
+    array_shape: &[u64],
+) -> Result<(usize, usize), ArrayError> {
+    let invalid = || ArrayError::InvalidArraySubset(array_subset.clone(), array_shape.to_vec());
+    let shape = array_subset.shape();
+    let Some(leading) = shape.len().checked_sub(2) else {
+        return Err(invalid());
+    };
+    if shape[..leading].iter().any(|size| *size != 1) {
+        return Err(invalid());
+    }
+    let rows = usize::try_from(shape[leading]).map_err(|_| invalid())?;
+    let columns = usize::try_from(shape[leading + 1]).map_err(|_| invalid())?;
+    Ok((rows, columns))
+}
+
+/// Return the matrix array subset with `rows` and `columns` starting at `subset_start`.
+fn matrix_subset(
+    subset_start: &[u64],
+    rows: usize,
+    columns: usize,
+) -> Result<ArraySubset, ArrayError> {
+    let Some(leading) = subset_start.len().checked_sub(2) else {
+        return Err(ArrayError::InvalidDataShape(
+            vec![subset_start.len()],
+            vec![2],
+        ));
+    };
+    let subset_shape = std::iter::repeat(1)
+        .take(leading)
+        .chain([rows as u64, columns as u64])
+        .collect::<Vec<_>>();
+    Ok(ArraySubset::new_with_start_shape(
+        subset_start.to_vec(),
+        subset_shape,
+    )?)
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits + 'static> Array<TStorage> {
+    /// Read and decode the 2-D `array_subset` of array into a [`DMatrix`].
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if:
+    ///  - the array subset does not have a matrix shape,
+    ///  - the size of `T` does not match the data type size,
+    ///  - an array subset is invalid or out of bounds of the array,
+    ///  - there is a codec decoding error, or
+    ///  - an underlying store error.
+    pub fn retrieve_array_subset_dmatrix<T: ElementOwned + Scalar>(
+        &self,
+        array_subset: &ArraySubset,
+    ) -> Result<DMatrix<T>, ArrayError> {
+        let (rows, columns) = matrix_dimensions(array_subset, self.shape())?;
+        let elements = self.retrieve_array_subset_elements::<T>(array_subset)?;
+        Ok(DMatrix::from_row_slice(rows, columns, &elements))
+    }
+
+    /// Read and decode the `R`x`C` array subset starting at `subset_start` into an [`SMatrix`].
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if:
+    ///  - `subset_start` has fewer than two dimensions,
+    ///  - the size of `T` does not match the data type size,
+    ///  - the array subset is invalid or out of bounds of the array,
+    ///  - there is a codec decoding error, or
+    ///  - an underlying store error.
+    pub fn retrieve_array_subset_smatrix<
+        T: ElementOwned + Scalar,
+        const R: usize,
+        const C: usize,
+    >(
+        &self,
+        subset_start: &[u64],
+    ) -> Result<SMatrix<T, R, C>, ArrayError> {
+        let array_subset = matrix_subset(subset_start, R, C)?;
+        let elements = self.retrieve_array_subset_elements::<T>(&array_subset)?;
+        Ok(SMatrix::from_row_slice(&elements))
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits + 'static> Array<TStorage> {
+    /// Encode `matrix` and store it in the array subset starting at `subset_start`.
+    ///
+    /// The array subset has the shape `[1, ..., 1, rows, columns]`.
+    ///
+    /// # Errors
+    /// Returns an [`ArrayError`] if:
+    ///  - `subset_start` has fewer than two dimensions,
+    ///  - the size of `T` does not match the data type size,
+    ///  - the dimensionality of `subset_start` does not match the array, or
+    ///  - an underlying store or codec error.
+    pub fn store_array_subset_matrix<T, R, C, S>(
+        &self,
+        subset_start: &[u64],
+        matrix: &Matrix<T, R, C, S>,
+    ) -> Result<(), ArrayError>
+    where
+        T: Element + Scalar,
+        R: Dim,
+        C: Dim,
+        S: RawStorage<T, R, C>,
+    {
+        let (rows, columns) = matrix.shape();
+        let array_subset = matrix_subset(subset_start, rows, columns)?;
+        let elements = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| matrix[(row, column)].clone()))
+            .collect::<Vec<_>>();
+        self.store_array_subset_elements::<T>(&array_subset, &elements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ::nalgebra::{DMatrix, Matrix3, Vector3};
+
+    use crate::{
+        array::{ArrayBuilder, DataType, FillValue},
+        storage::store::MemoryStore,
+    };
+
+    use super::*;
+
+    #[test]
+    fn nalgebra_round_trip() {
+        let store = Arc::new(MemoryStore::new());
+        let array = ArrayBuilder::new(
+            vec![2, 4, 5],
+            DataType::Int32,
+            vec![1, 2, 2].try_into().unwrap(),
+            FillValue::from(0i32),
+        )
+        .build(store, "/array")
+        .unwrap();
+
+        let matrix = DMatrix::from_fn(3, 4, |row, column| i32::try_from(row * 4 + column).unwrap());
+        array
+            .store_array_subset_matrix(&[1, 1, 0], &matrix)
+            .unwrap();
+        assert_eq!(
+            array
+                .retrieve_array_subset_elements::<i32>(&ArraySubset::new_with_ranges(&[
+                    1..2,
+                    1..2,
+                    0..4
+                ]))
+                .unwrap(),
+            vec![0, 1, 2, 3]
+        );
+        assert_eq!(
+            array
+                .retrieve_array_subset_dmatrix::<i32>(&ArraySubset::new_with_ranges(&[
+                    1..2,
+                    1..4,
+                    0..4
+                ]))
+                .unwrap(),
+            matrix
+        );
+        let smatrix: Matrix3<i32> = array.retrieve_array_subset_smatrix(&[1, 1, 1]).unwrap();
+        assert_eq!(smatrix, Matrix3::new(1, 2, 3, 5, 6, 7, 9, 10, 11));
+
+        // Vectors are single column matrices
+        array
+            .store_array_subset_matrix(&[0, 0, 4], &Vector3::new(7, 8, 9))
+            .unwrap();
+        let column: Vector3<i32> = array.retrieve_array_subset_smatrix(&[0, 0, 4]).unwrap();
+        assert_eq!(column, Vector3::new(7, 8, 9));
+
+        // Invalid shapes
+        assert!(array
+            .retrieve_array_subset_dmatrix::<i32>(&ArraySubset::new_with_ranges(&[
+                0..2,
+                0..2,
+                0..2
+            ]))
+            .is_err());
+        assert!(array
+            .retrieve_array_subset_smatrix::<i32, 2, 2>(&[0])
+            .is_err());
+        assert!(array
+            .retrieve_array_subset_dmatrix::<f64>(&ArraySubset::new_with_ranges(&[
+                0..1,
+                0..2,
+                0..2
+            ]))
+            .is_err());
+        assert!(array.store_array_subset_matrix(&[3, 0], &matrix).is_err());
+    }
+}
@@ -0,0 +1,510 @@
+//! [NumPy](https://numpy.org/) `.npy` and `.npz` interchange.
+//!
+//! [`array_from_npy`] creates an [`Array`] from a `.npy` file, inferring its data type and shape.
+//! The fill value is zero and the array is a single chunk unless a chunk shape is provided.
+//!
+//! [`array_subset_to_npy`] exports an array subset to a `.npy` file, and [`array_subsets_to_npz`] (requires the `zip` feature) exports multiple array subsets to a `.npz` archive.
+//!
+//! `.npy` files with data types `b1`, `i1`..`i8`, `u1`..`u8`, `f2`..`f8`, `c8`, `c16`, and `V<n>` (raw bits) of either endianness and C or Fortran order are supported.
+//!
+//! This module requires the `npy` feature.
+//!
+//! ### Example
+//! ```rust
+//! # use std::sync::Arc;
+//! # use zarrs::array_subset::ArraySubset;
+//! # use zarrs::storage::store::MemoryStore;
+//! use zarrs::array::npy::{array_from_npy, array_subset_to_npy};
+//!
+//! let mut npy = Vec::new();
+//! # let array = zarrs::array::ArrayBuilder::new(vec![2, 3], zarrs::array::DataType::Float32, vec![2, 3].try_into()?, 0.0f32.into()).build(Arc::new(MemoryStore::new()), "/a")?;
+//! # array.store_array_subset_elements::<f32>(&ArraySubset::new_with_shape(array.shape().to_vec()), &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0])?;
+//! array_subset_to_npy(&array, &ArraySubset::new_with_ranges(&[0..2, 1..3]), &mut npy)?;
+//!
+//! let store = Arc::new(MemoryStore::new());
+//! let array_npy = array_from_npy(store, "/array", npy.as_slice(), None)?;
+//! assert_eq!(array_npy.shape(), &[2, 2]);
+//! assert_eq!(array_npy.retrieve_array_subset_elements::<f32>(&ArraySubset::new_with_shape(array_npy.shape().to_vec()))?, vec![1.0, 2.0, 4.0, 5.0]);
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use std::{
+    io::{Read, Write},
+    num::NonZeroU64,
+    sync::Arc,
+};
+
+use thiserror::Error;
+
+use crate::{
+    array_subset::ArraySubset,
+    storage::{ReadableStorageTraits, ReadableWritableStorageTraits, StorageError},
+};
+
+use super::{
+    Array, ArrayBuilder, ArrayCreateError, ArrayError, ArrayShape, ChunkShape, DataType, FillValue,
+};
+
+/// The `.npy` magic string.
+const NPY_MAGIC: &[u8] = b"\x93NUMPY";
+
+/// A `.npy`/`.npz` error.
+#[derive(Debug, Error)]
+pub enum NpyError {
+    /// An IO error.
+    #[error(transparent)]
+    IOError(#[from] std::io::Error),
+    /// The `.npy` header is invalid.
+    #[error("invalid npy header: {_0}")]
+    InvalidHeader(String),
+    /// The data type is not supported.
+    #[error("unsupported npy data type {_0}")]
+    UnsupportedDataType(String),
+    /// The `.npy` data size does not match its header.
+    #[error("npy data has size {_0}, expected {_1}")]
+    InvalidDataSize(usize, u64),
+    /// An array creation error.
+    #[error(transparent)]
+    ArrayCreateError(#[from] ArrayCreateError),
+    /// An array error.
+    #[error(transparent)]
+    ArrayError(#[from] ArrayError),
+    /// A storage error.
+    #[error(transparent)]
+    StorageError(#[from] StorageError),
+    /// A zip error.
+    #[cfg(feature = "zip")]
+    #[error(transparent)]
+    ZipError(#[from] zip::result::ZipError),
+}
+
+/// The decoded contents of a `.npy` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Npy {
+    data_type: DataType,
+    shape: ArrayShape,
+    bytes: Vec<u8>,
+}
+
+impl Npy {
+    /// Return the data type.
+    #[must_use]
+    pub const fn data_type(&self) -> &DataType {
+        &self.data_type
+    }
+
+    /// Return the shape.
+    #[must_use]
+    pub fn shape(&self) -> &[u64] {
+        &self.shape
+    }
+
+    /// Return the element bytes in C order and native endianness.
+    #[must_use]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Convert into the data type, shape, and element bytes.
+    #[must_use]
+    pub fn into_parts(self) -> (DataType, ArrayShape, Vec<u8>) {
+        (self.data_type, self.shape, self.bytes)
+    }
+}
+
+/// Return the `.npy` type string (without byte order) and the size of the byte swapped components of a data type.
+fn npy_type(data_type: &DataType) -> Option<(String, usize)> {
+    Some(match data_type {
+        DataType::Bool => ("b1".to_string(), 1),
+        DataType::Int8 => ("i1".to_string(), 1),
+        DataType::Int16 => ("i2".to_string(), 2),
+        DataType::Int32 => ("i4".to_string(), 4),
+        DataType::Int64 => ("i8".to_string(), 8),
+        DataType::UInt8 => ("u1".to_string(), 1),
+        DataType::UInt16 => ("u2".to_string(), 2),
+        DataType::UInt32 => ("u4".to_string(), 4),
+        DataType::UInt64 => ("u8".to_string(), 8),
+        DataType::Float16 => ("f2".to_string(), 2),
+        DataType::Float32 => ("f4".to_string(), 4),
+        DataType::Float64 => ("f8".to_string(), 8),
+        DataType::Complex64 => ("c8".to_string(), 4),
+        DataType::Complex128 => ("c16".to_string(), 8),
+        DataType::RawBits(size) => (format!("V{size}"), 1),
+        _ => return None,
+    })
+}
+
+/// Parse a `.npy` `descr` into a data type and whether it is big endian.
+fn parse_descr(descr: &str) -> Result<(DataType, bool), NpyError> {
+    let (big_endian, type_str) = if let Some(type_str) = descr.strip_prefix('>') {
+        (true, type_str)
+    } else if let Some(type_str) = descr.strip_prefix('=') {
+        (cfg!(target_endian = "big"), type_str)
+    } else {
+        (false, descr.trim_start_matches(['<', '|']))
+    };
+    let data_type = match type_str {
+        "b1" => DataType::Bool,
+        "i1" => DataType::Int8,
+        "i2" => DataType::Int16,
+        "i4" => DataType::Int32,
+        "i8" => DataType::Int64,
+        "u1" => DataType::UInt8,
+        "u2" => DataType::UInt16,
+        "u4" => DataType::UInt32,
+        "u8" => DataType::UInt64,
+        "f2" => DataType::Float16,
+        "f4" => DataType::Float32,
+        "f8" => DataType::Float64,
+        "c8" => DataType::Complex64,
+        "c16" => DataType::Complex128,
+        _ => match type_str.strip_prefix('V').map(str::parse::<usize>) {
+            Some(Ok(size)) if size > 0 => DataType::RawBits(size),
+            _ => return Err(NpyError::UnsupportedDataType(descr.to_string())),
+        },
+    };
+    Ok((data_type, big_endian))
+}
+
+/// Return the string value of `key` in a `.npy` header dictionary.
+fn header_value<'a>(header: &'a str, key: &str) -> Result<&'a str, NpyError> {
+    let missing = || NpyError::InvalidHeader(format!("missing {key}"));
+    let start = header.find(&format!("'{key}'")).ok_or_else(missing)? + key.len() + 2;
+    let value = header[start..]
+        .trim_start()
+        .strip_prefix(':')
+        .ok_or_else(missing)?
+        .trim_start();
+    let end = if value.starts_with('(') {
+        value.find(')').map(|end| end + 1)
+    } else if let Some(quoted) = value.strip_prefix('\'') {
+        quoted.find('\'').map(|end| end + 2)
+    } else {
+        value.find([',', '}'])
+    }
+    .ok_or_else(missing)?;
+    Ok(value[..end].trim())
+}
+
+/// Reorder the elements of Fortran ordered `bytes` to C order.
+fn fortran_to_c_order(bytes: &[u8], shape: &[u64], element_size: usize) -> Vec<u8> {
+    let mut output = vec![0; bytes.len()];
+    let num_elements = bytes.len() / element_size;
+    let mut indices = vec![0u64; shape.len()];
+    for c_index in 0..num_elements {
+        // Fortran linear index of the current C order indices
+        let mut f_index = 0;
+        let mut stride = 1;
+        for (index, size) in indices.iter().zip(shape) {
+            f_index += index * stride;
+            stride *= size;
+        }
+        let f_index = usize::try_from(f_index).unwrap();
+        output[c_index * element_size..(c_index + 1) * element_size]
+            .copy_from_slice(&bytes[f_index * element_size..(f_index + 1) * element_size]);
+        // Increment the C order indices
+        for (index, size) in indices.iter_mut().zip(shape).rev() {
+            *index += 1;
+            if *index < *size {
+                break;
+            }
+            *index = 0;
+        }
+    }
+    output
+}
+
+/// Read a `.npy` file from `reader`.
+///
+/// The element bytes are converted to native endianness and C order.
+///
+/// # Errors
+/// Returns an [`NpyError`] if the file is invalid, has an unsupported data type, or there is an IO error.
+///
+/// # Panics
+/// Panics if the number of elements exceeds [`usize::MAX`].
+pub fn read_npy(mut reader: impl Read) -> Result<Npy, NpyError> {
+    let mut preamble = [0u8; 8];
+    reader.read_exact(&mut preamble)?;
+    if &preamble[..6] != NPY_MAGIC {
+        return Err(NpyError::InvalidHeader("invalid magic string".to_string()));
+    }
+    let header_len = match preamble[6] {
+        1 => {
+            let mut header_len = [0u8; 2];
+            reader.read_exact(&mut header_len)?;
+            usize::from(u16::from_le_bytes(header_len))
+        }
+        2 | 3 => {
+            let mut header_len = [0u8; 4];
+            reader.read_exact(&mut header_len)?;
+            usize::try_from(u32::from_le_bytes(header_len)).unwrap()
+        }
+        version => {
+            return Err(NpyError::InvalidHeader(format!(
+                "unsupported version {version}"
+            )))
+        }
+    };
+    let mut header = vec![0u8; header_len];
+    reader.read_exact(&mut header)?;
+    let header = String::from_utf8(header)
+        .map_err(|_| NpyError::InvalidHeader("header is not valid UTF-8".to_string()))?;
+
+    let descr = header_value(&header, "descr")?.trim_matches('\'');
+    let (data_type, big_endian) = parse_descr(descr)?;
+    let fortran_order = match header_value(&header, "fortran_order")? {
+        "True" => true,
+        "False" => false,
+        value => return Err(NpyError::InvalidHeader(format!("fortran_order {value}"))),
+    };
+    let shape = header_value(&header, "shape")?
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .split(',')
+        .map(str::trim)
+        .filter(|size| !size.is_empty())
+        .map(|size| {
+            size.trim_end_matches('L')
+                .parse::<u64>()
+                .map_err(|_| NpyError::InvalidHeader(format!("shape {size}")))
+        })
+        .collect::<Result<ArrayShape, _>>()?;
+
+    let element_size = data_type.fixed_size().unwrap();
+    let expected_size = shape.iter().product::<u64>() * element_size as u64;
+    let mut bytes = Vec::with_capacity(usize::try_from(expected_size).unwrap());
+    reader.read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != expected_size {
+        return Err(NpyError::InvalidDataSize(bytes.len(), expected_size));
+    }
+
+    let (_, component_size) = npy_type(&data_type).unwrap();
+    if big_endian != cfg!(target_endian = "big") && component_size > 1 {
+        for component in bytes.chunks_exact_mut(component_size) {
+            component.reverse();
+        }
+    }
+    if fortran_order && shape.len() > 1 {
+        bytes = fortran_to_c_order(&bytes, &shape, element_size);
+    }
+
+    Ok(Npy {
+        data_type,
+        shape,
+        bytes,
+    })
+}
+
+/// Write element `bytes` with `data_type` and `shape` in C order and native endianness to `writer` as a `.npy` file.
+///
+/// # Errors
+/// Returns an [`NpyError`] if the data type is unsupported, the size of `bytes` does not match `shape`, or there is an IO error.
+pub fn write_npy(
+    mut writer: impl Write,
+    bytes: &[u8],
+    data_type: &DataType,
+    shape: &[u64],
+) -> Result<(), NpyError> {
+    let (type_str, component_size) =
+        npy_type(data_type).ok_or_else(|| NpyError::UnsupportedDataType(data_type.name()))?;
+    let expected_size =
+        shape.iter().product::<u64>() * data_type.fixed_size().unwrap_or_default() as u64;
+    if bytes.len() as u64 != expected_size {
+        return Err(NpyError::InvalidDataSize(bytes.len(), expected_size));
+    }
+
+    let byte_order = if component_size == 1 {
+        '|'
+    } else if cfg!(target_endian = "big") {
+        '>'
+    } else {
+        '<'
+    };
+    let shape_str = match shape {
+        [size] => format!("({size},)"),
+        shape => format!(
+            "({})",
+            shape
+                .iter()
+                .map(u64::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    let mut header = format!(
+        "{{'descr': '{byte_order}{type_str}', 'fortran_order': False, 'shape': {shape_str}, }}"
+    );
+    // Pad the header with spaces and a newline so the data is 64 byte aligned
+    let unpadded_len = NPY_MAGIC.len() + 2 + 2 + header.len() + 1;
+    header.extend(std::iter::repeat(' ').take((64 - unpadded_len % 64) % 64));
+    header.push('\n');
+
+    let header_len = u16::try_from(header.len())
+        .map_err(|_| NpyError::InvalidHeader("header is too long".to_string()))?;
+    writer.write_all(NPY_MAGIC)?;
+    writer.write_all(&[1, 0])?;
+    writer.write_all(&header_len.to_le_bytes())?;
+    writer.write_all(header.as_bytes())?;
+    writer.write_all(bytes)?;
+    Ok(())
+}
+
+/// Create an array in `storage` at `path` from the `.npy` file in `reader`.
+///
+/// The data type and shape are inferred from the `.npy` file, and the fill value is zero.
+/// The array has a single chunk if `chunk_shape` is [`None`].
+/// The array metadata and data are stored.
+///
+/// # Errors
+/// Returns an [`NpyError`] if the `.npy` file cannot be read, or if there is an array creation or storage error.
+pub fn array_from_npy<TStorage: ?Sized + ReadableWritableStorageTraits + 'static>(
+    storage: Arc<TStorage>,
+    path: &str,
+    reader: impl Read,
+    chunk_shape: Option<ChunkShape>,
+) -> Result<Array<TStorage>, NpyError> {
+    let (data_type, shape, bytes) = read_npy(reader)?.into_parts();
+    let chunk_shape = if let Some(chunk_shape) = chunk_shape {
+        chunk_shape
+    } else {
+        shape
+            .iter()
+            .map(|size| NonZeroU64::new(*size).unwrap_or(NonZeroU64::MIN))
+            .collect::<Vec<_>>()
+            .into()
+    };
+    let fill_value = FillValue::new(vec![0; data_type.fixed_size().unwrap_or_default()]);
+    let array =
+        ArrayBuilder::new(shape, data_type, chunk_shape.into(), fill_value).build(storage, path)?;
+    array.store_metadata()?;
+    array.store_array_subset(&ArraySubset::new_with_shape(array.shape().to_vec()), bytes)?;
+    Ok(array)
+}
+
+/// Retrieve `array_subset` of `array` and write it to `writer` as a `.npy` file.
+///
+/// # Errors
+/// Returns an [`NpyError`] if the data type is unsupported, or if there is a retrieval or IO error.
+pub fn array_subset_to_npy<TStorage: ?Sized + ReadableStorageTraits + 'static>(
+    array: &Array<TStorage>,
+    array_subset: &ArraySubset,
+    writer: impl Write,
+) -> Result<(), NpyError> {
+    if npy_type(array.data_type()).is_none() {
+        return Err(NpyError::UnsupportedDataType(array.data_type().name()));
+    }
+    let bytes = array
+        .retrieve_array_subset(array_subset)?
+        .into_fixed()
+        .map_err(ArrayError::from)?;
+    write_npy(writer, &bytes, array.data_type(), array_subset.shape())
+}
+
+#[cfg(feature = "zip")]
+/// Retrieve the array subsets of `array` and write them to `writer` as a `.npz` archive.
+///
+/// Each array subset is written to `<name>.npy` in the archive, where `name` is its paired name in `array_subsets`.
+///
+/// # Errors
+/// Returns an [`NpyError`] if the data type is unsupported, or if there is a retrieval, zip, or IO error.
+pub fn array_subsets_to_npz<TStorage: ?Sized + ReadableStorageTraits + 'static>(
+    array: &Array<TStorage>,
+    array_subsets: &[(&str, ArraySubset)],
+    writer: impl Write + std::io::Seek,
+) -> Result<(), NpyError> {
+    let mut zip = zip::ZipWriter::new(writer);
+    for (name, array_subset) in array_subsets {
+        zip.start_file(
+            format!("{name}.npy"),
+            zip::write::SimpleFileOptions::default(),
+        )?;
+        array_subset_to_npy(array, array_subset, &mut zip)?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::store::MemoryStore;
+
+    use super::*;
+
+    #[test]
+    fn npy_round_trip() {
+        let bytes: Vec<u8> = [1i32, -2, 3, -4, 5, -6]
+            .iter()
+            .flat_map(|v| v.to_ne_bytes())
+            .collect();
+        let mut npy = Vec::new();
+        write_npy(&mut npy, &bytes, &DataType::Int32, &[2, 3]).unwrap();
+        assert_eq!((npy.len() - bytes.len()) % 64, 0);
+        let npy = read_npy(npy.as_slice()).unwrap();
+        assert_eq!(npy.data_type(), &DataType::Int32);
+        assert_eq!(npy.shape(), &[2, 3]);
+        assert_eq!(npy.bytes(), bytes.as_slice());
+    }
+
+    #[test]
+    fn npy_read_big_endian_fortran() {
+        let header = "{'descr': '>u2', 'fortran_order': True, 'shape': (2, 3), }";
+        let mut npy = NPY_MAGIC.to_vec();
+        npy.extend([1, 0]);
+        npy.extend(u16::try_from(header.len() + 1).unwrap().to_le_bytes());
+        npy.extend(header.as_bytes());
+        npy.push(b'\n');
+        // Fortran order [[0, 1, 2], [3, 4, 5]]
+        for element in [0u16, 3, 1, 4, 2, 5] {
+            npy.extend(element.to_be_bytes());
+        }
+        let npy = read_npy(npy.as_slice()).unwrap();
+        assert_eq!(npy.data_type(), &DataType::UInt16);
+        assert_eq!(npy.shape(), &[2, 3]);
+        let expected: Vec<u8> = (0u16..6).flat_map(u16::to_ne_bytes).collect();
+        assert_eq!(npy.bytes(), expected.as_slice());
+    }
+
+    #[test]
+    fn npy_array_round_trip() {
+        let store = Arc::new(MemoryStore::new());
+        let mut npy = Vec::new();
+        write_npy(&mut npy, &[1, 0, 1, 1], &DataType::Bool, &[4]).unwrap();
+        let array = array_from_npy(store.clone(), "/array", npy.as_slice(), None).unwrap();
+        assert_eq!(array.data_type(), &DataType::Bool);
+        assert_eq!(array.fill_value(), &FillValue::from(false));
+        assert_eq!(
+            array
+                .retrieve_array_subset_elements::<bool>(&ArraySubset::new_with_shape(
+                    array.shape().to_vec()
+                ))
+                .unwrap(),
+            vec![true, false, true, true]
+        );
+
+        let mut npy_out = Vec::new();
+        array_subset_to_npy(
+            &array,
+            &ArraySubset::new_with_shape(array.shape().to_vec()),
+            &mut npy_out,
+        )
+        .unwrap();
+        assert_eq!(npy, npy_out);
+
+        let array = array_from_npy(
+            store,
+            "/chunked",
+            npy.as_slice(),
+            Some(vec![2].try_into().unwrap()),
+        )
+        .unwrap();
+        assert_eq!(array.chunk_grid_shape(), Some(vec![2]));
+    }
+
+    #[test]
+    fn npy_invalid() {
+        assert!(read_npy(b"\x93NUMPX\x01\x00".as_slice()).is_err());
+        assert!(write_npy(Vec::new(), &[0; 4], &DataType::UInt16, &[3]).is_err());
+        assert!(write_npy(Vec::new(), &[], &DataType::String, &[0]).is_err());
+    }
+}
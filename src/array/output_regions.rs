@@ -0,0 +1,255 @@
+use rayon::prelude::*;
+use thiserror::Error;
+
+use crate::array_subset::{ArraySubset, IncompatibleArraySubsetAndShapeError};
+
+use super::array_bytes::{
+    UPDATE_BYTES_FLEN_PARALLEL_BLOCK_SIZE, UPDATE_BYTES_FLEN_PARALLEL_THRESHOLD,
+};
+
+/// An error partitioning an output buffer into [`OutputRegion`]s.
+#[derive(Debug, Error)]
+pub enum OutputPartitionError {
+    /// The output length does not match its shape.
+    #[error("output has length {_0}, expected {_1}")]
+    InvalidOutputLength(usize, usize),
+    /// An array subset is incompatible with the output shape.
+    #[error(transparent)]
+    IncompatibleArraySubsetAndShape(#[from] IncompatibleArraySubsetAndShapeError),
+    /// An array subset overlaps another array subset.
+    #[error("array subset {_0} overlaps another array subset")]
+    OverlappingSubsets(ArraySubset),
+    /// The bytes written to a region do not match its length.
+    #[error("got bytes with length {_0}, expected {_1}")]
+    InvalidBytesLength(usize, usize),
+}
+
+/// A disjoint mutable region of an output buffer holding the elements of an array subset.
+///
+/// Created with [`partition_output`].
+/// The regions of an output can be written concurrently without `unsafe` code, unlike an [`UnsafeCellSlice`](crate::array::UnsafeCellSlice).
+#[derive(Debug)]
+pub struct OutputRegion<'a> {
+    subset: ArraySubset,
+    runs: Vec<&'a mut [u8]>,
+}
+
+impl OutputRegion<'_> {
+    /// Return the array subset of the region relative to the output.
+    #[must_use]
+    pub const fn subset(&self) -> &ArraySubset {
+        &self.subset
+    }
+
+    /// Return the length of the region in bytes.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.runs.iter().map(|run| run.len()).sum()
+    }
+
+    /// Returns true if the region is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.runs.iter().all(|run| run.is_empty())
+    }
+
+    /// Write the bytes of the elements of the subset of the region in C order.
+    ///
+    /// If `bytes` is large, the copy is split across contiguous runs (and blocks of long runs) and performed in parallel.
+    ///
+    /// # Errors
+    /// Returns [`OutputPartitionError::InvalidBytesLength`] if the length of `bytes` does not match the length of the region.
+    pub fn write(&mut self, bytes: &[u8]) -> Result<(), OutputPartitionError> {
+        let len = self.len();
+        if bytes.len() != len {
+            return Err(OutputPartitionError::InvalidBytesLength(bytes.len(), len));
+        }
+        let mut rest = bytes;
+        let runs_bytes: Vec<&[u8]> = self
+            .runs
+            .iter()
+            .map(|run| {
+                let (run_bytes, tail) = rest.split_at(run.len());
+                rest = tail;
+                run_bytes
+            })
+            .collect();
+        if len > UPDATE_BYTES_FLEN_PARALLEL_THRESHOLD {
+            self.runs
+                .par_iter_mut()
+                .zip(runs_bytes)
+                .for_each(|(run, run_bytes)| {
+                    run.par_chunks_mut(UPDATE_BYTES_FLEN_PARALLEL_BLOCK_SIZE)
+                        .zip(run_bytes.par_chunks(UPDATE_BYTES_FLEN_PARALLEL_BLOCK_SIZE))
+                        .for_each(|(block, block_bytes)| block.copy_from_slice(block_bytes));
+                });
+        } else {
+            for (run, run_bytes) in std::iter::zip(&mut self.runs, runs_bytes) {
+                run.copy_from_slice(run_bytes);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Split `output` into a disjoint mutable [`OutputRegion`] for each of `subsets`.
+///
+/// `output` holds the elements of an array with `output_shape` in C order, each with `data_type_size` bytes.
+/// A region holds the contiguous runs of elements of its subset in `output`.
+///
+/// # Errors
+/// Returns an [`OutputPartitionError`] if
+///  - the length of `output` does not match `output_shape` and `data_type_size`,
+///  - a subset is incompatible with `output_shape`, or
+///  - subsets overlap.
+///
+/// # Panics
+/// Panics if the number of elements in `output_shape` exceeds [`usize::MAX`].
+pub fn partition_output<'a>(
+    output: &'a mut [u8],
+    output_shape: &[u64],
+    data_type_size: usize,
+    subsets: Vec<ArraySubset>,
+) -> Result<Vec<OutputRegion<'a>>, OutputPartitionError> {
+    let output_len =
+        usize::try_from(output_shape.iter().product::<u64>()).unwrap() * data_type_size;
+    if output.len() != output_len {
+        return Err(OutputPartitionError::InvalidOutputLength(
+            output.len(),
+            output_len,
+        ));
+    }
+
+    // The byte offset, byte length, and subset index of each contiguous run
+    let mut runs: Vec<(usize, usize, usize)> = Vec::new();
+    for (subset_index, subset) in subsets.iter().enumerate() {
+        let contiguous_indices = subset.contiguous_linearised_indices(output_shape)?;
+        let length = contiguous_indices.contiguous_elements_usize() * data_type_size;
+        if length == 0 {
+            continue;
+        }
+        runs.extend(contiguous_indices.iter().map(|(index, _)| {
+            (
+                usize::try_from(index).unwrap() * data_type_size,
+                length,
+                subset_index,
+            )
+        }));
+    }
+    runs.sort_unstable();
+
+    let mut regions: Vec<OutputRegion> = subsets
+        .into_iter()
+        .map(|subset| OutputRegion {
+            subset,
+            runs: Vec::new(),
+        })
+        .collect();
+    let mut rest = output;
+    let mut position = 0;
+    for (offset, length, subset_index) in runs {
+        if offset < position {
+            return Err(OutputPartitionError::OverlappingSubsets(
+                regions[subset_index].subset.clone(),
+            ));
+        }
+        let (_, tail) = std::mem::take(&mut rest).split_at_mut(offset - position);
+        let (run, tail) = tail.split_at_mut(length);
+        regions[subset_index].runs.push(run);
+        rest = tail;
+        position = offset + length;
+    }
+    Ok(regions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_regions() {
+        let mut output = vec![0u8; 4 * 4 * 2];
+        let subsets = vec![
+            ArraySubset::new_with_ranges(&[0..2, 0..2]),
+            ArraySubset::new_with_ranges(&[0..2, 2..4]),
+            ArraySubset::new_with_ranges(&[2..4, 0..4]),
+        ];
+        let mut regions = partition_output(&mut output, &[4, 4], 2, subsets).unwrap();
+        assert_eq!(regions.len(), 3);
+        assert_eq!(regions[0].len(), 8);
+        assert_eq!(regions[2].len(), 16);
+        assert!(matches!(
+            regions[0].write(&[1; 6]),
+            Err(OutputPartitionError::InvalidBytesLength(6, 8))
+        ));
+        for (value, region) in regions.iter_mut().enumerate() {
+            let value = u8::try_from(value).unwrap() + 1;
+            region.write(&vec![value; region.len()]).unwrap();
+        }
+        let expected: Vec<u8> = [
+            [1; 4], [2; 4], [1; 4], [2; 4], [3; 4], [3; 4], [3; 4], [3; 4],
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn output_regions_write_parallel() {
+        let output_shape = [2048u64, 4096];
+        let mut output = vec![0u8; 2048 * 4096];
+        let subsets = vec![
+            ArraySubset::new_with_ranges(&[0..2048, 0..2100]),
+            ArraySubset::new_with_ranges(&[0..2048, 2100..4096]),
+        ];
+        let mut regions = partition_output(&mut output, &output_shape, 1, subsets).unwrap();
+        assert!(regions[0].len() > UPDATE_BYTES_FLEN_PARALLEL_THRESHOLD);
+        for region in &mut regions {
+            let bytes: Vec<u8> = (0..region.len())
+                .map(|i| u8::try_from(i % 251).unwrap())
+                .collect();
+            region.write(&bytes).unwrap();
+        }
+        for (row, row_bytes) in output.chunks_exact(4096).enumerate() {
+            for (column, byte) in row_bytes.iter().enumerate() {
+                let i = if column < 2100 {
+                    row * 2100 + column
+                } else {
+                    row * 1996 + column - 2100
+                };
+                assert_eq!(usize::from(*byte), i % 251);
+            }
+        }
+    }
+
+    #[test]
+    fn output_regions_invalid() {
+        let mut output = vec![0u8; 16];
+        assert!(matches!(
+            partition_output(&mut output, &[4, 4], 2, vec![]),
+            Err(OutputPartitionError::InvalidOutputLength(16, 32))
+        ));
+        assert!(matches!(
+            partition_output(
+                &mut output,
+                &[4, 4],
+                1,
+                vec![
+                    ArraySubset::new_with_ranges(&[0..2, 0..2]),
+                    ArraySubset::new_with_ranges(&[1..3, 1..3]),
+                ]
+            ),
+            Err(OutputPartitionError::OverlappingSubsets(_))
+        ));
+        assert!(matches!(
+            partition_output(
+                &mut output,
+                &[4, 4],
+                1,
+                vec![ArraySubset::new_with_ranges(&[0..5, 0..2])]
+            ),
+            Err(OutputPartitionError::IncompatibleArraySubsetAndShape(_))
+        ));
+    }
+}
@@ -0,0 +1,285 @@
+use crate::array_subset::{ArraySubset, IncompatibleArraySubsetAndShapeError};
+
+use super::{codec::CodecError, ravel_indices, unravel_index, ArrayBytes, FillValue, RawBytes};
+
+/// A sparse representation of the bytes of an array with a fixed size data type.
+///
+/// Only the elements that differ from the fill value are stored, as their linearised indices and native endian values.
+/// Segmentation and label volumes are typically almost entirely fill value, so the memory used by a sparse representation is a small fraction of that of [`ArrayBytes`].
+///
+/// See [`Array::retrieve_chunk_sparse`](crate::array::Array::retrieve_chunk_sparse) and [`Array::store_chunk_sparse`](crate::array::Array::store_chunk_sparse).
+/// The `sparse` codec (see [`SparseCodec`](crate::array::codec::array_to_bytes::sparse::SparseCodec)) encodes and decodes chunks without a dense intermediate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseArrayBytes {
+    num_elements: u64,
+    element_size: usize,
+    indices: Vec<u64>,
+    values: Vec<u8>,
+}
+
+impl SparseArrayBytes {
+    /// Create a new sparse array of `num_elements` elements of `element_size` bytes.
+    ///
+    /// `values` holds the native endian bytes of the element at each of the linearised `indices`, which must be strictly increasing.
+    ///
+    /// # Errors
+    /// Returns a [`CodecError`] if the indices are not strictly increasing, exceed `num_elements`, or the length of `values` is not the number of indices multiplied by `element_size`.
+    pub fn new(
+        num_elements: u64,
+        element_size: usize,
+        indices: Vec<u64>,
+        values: Vec<u8>,
+    ) -> Result<Self, CodecError> {
+        if values.len() != indices.len() * element_size {
+            return Err(CodecError::UnexpectedChunkDecodedSize(
+                values.len(),
+                (indices.len() * element_size) as u64,
+            ));
+        }
+        if indices.windows(2).any(|window| window[0] >= window[1])
+            || indices.last().is_some_and(|index| *index >= num_elements)
+        {
+            return Err(CodecError::Other(format!(
+                "sparse array indices must be strictly increasing and less than {num_elements}"
+            )));
+        }
+        Ok(Self {
+            num_elements,
+            element_size,
+            indices,
+            values,
+        })
+    }
+
+    /// Create a new sparse array of `num_elements` elements of `element_size` bytes composed entirely of the fill value.
+    #[must_use]
+    pub const fn new_fill_value(num_elements: u64, element_size: usize) -> Self {
+        Self {
+            num_elements,
+            element_size,
+            indices: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Create a sparse array from the dense native endian `bytes` of an array with `fill_value`.
+    ///
+    /// # Errors
+    /// Returns a [`CodecError`] if the length of `bytes` is not a multiple of the size of the fill value.
+    pub fn from_dense(bytes: &[u8], fill_value: &FillValue) -> Result<Self, CodecError> {
+        let element_size = fill_value.size();
+        if element_size == 0 || bytes.len() % element_size != 0 {
+            return Err(CodecError::Other(format!(
+                "the length of the bytes {} is not a multiple of the fill value size {element_size}",
+                bytes.len()
+            )));
+        }
+        let fill_value = fill_value.as_ne_bytes();
+        let mut indices = Vec::new();
+        let mut values = Vec::new();
+        for (index, element) in bytes.chunks_exact(element_size).enumerate() {
+            if element != fill_value {
+                indices.push(index as u64);
+                values.extend_from_slice(element);
+            }
+        }
+        Ok(Self {
+            num_elements: (bytes.len() / element_size) as u64,
+            element_size,
+            indices,
+            values,
+        })
+    }
+
+    /// Create a sparse array from the [`ArrayBytes`] of an array with `fill_value`.
+    ///
+    /// # Errors
+    /// Returns a [`CodecError`] if `bytes` are variable length or a [`from_dense`](SparseArrayBytes::from_dense) error condition is met.
+    pub fn from_array_bytes(
+        bytes: &ArrayBytes,
+        fill_value: &FillValue,
+    ) -> Result<Self, CodecError> {
+        match bytes {
+            ArrayBytes::Fixed(bytes) => Self::from_dense(bytes, fill_value),
+            ArrayBytes::Variable(..) => Err(CodecError::ExpectedFixedLengthBytes),
+        }
+    }
+
+    /// Returns the number of elements of the array, including fill value elements.
+    #[must_use]
+    pub const fn num_elements(&self) -> u64 {
+        self.num_elements
+    }
+
+    /// Returns the size of each element in bytes.
+    #[must_use]
+    pub const fn element_size(&self) -> usize {
+        self.element_size
+    }
+
+    /// Returns the number of elements that differ from the fill value.
+    #[must_use]
+    pub fn num_stored(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Returns true if the array is composed entirely of the fill value.
+    #[must_use]
+    pub fn is_fill_value(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Returns the linearised indices of the elements that differ from the fill value in increasing order.
+    #[must_use]
+    pub fn indices(&self) -> &[u64] {
+        &self.indices
+    }
+
+    /// Returns the native endian bytes of the elements that differ from the fill value.
+    #[must_use]
+    pub fn values(&self) -> &[u8] {
+        &self.values
+    }
+
+    /// Returns an iterator over the linearised indices and native endian bytes of the elements that differ from the fill value.
+    #[must_use]
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (u64, &[u8])> + '_ {
+        std::iter::zip(
+            self.indices.iter().copied(),
+            self.values.chunks_exact(self.element_size.max(1)),
+        )
+    }
+
+    /// Returns the indices and values.
+    #[must_use]
+    pub fn into_parts(self) -> (Vec<u64>, Vec<u8>) {
+        (self.indices, self.values)
+    }
+
+    /// Returns the size in bytes of the heap allocations of the sparse array.
+    #[must_use]
+    pub fn heap_size(&self) -> usize {
+        self.indices.capacity() * std::mem::size_of::<u64>() + self.values.capacity()
+    }
+
+    /// Validate that the sparse array has `num_elements` elements of `element_size` bytes.
+    ///
+    /// # Errors
+    /// Returns a [`CodecError`] if the number of elements or the element size does not match.
+    pub fn validate(&self, num_elements: u64, element_size: usize) -> Result<(), CodecError> {
+        if self.num_elements == num_elements && self.element_size == element_size {
+            Ok(())
+        } else {
+            Err(CodecError::Other(format!(
+                "sparse array has {} elements of {} bytes, expected {num_elements} elements of {element_size} bytes",
+                self.num_elements, self.element_size
+            )))
+        }
+    }
+
+    fn check_fill_value(&self, fill_value: &FillValue) -> Result<(), CodecError> {
+        if fill_value.size() == self.element_size {
+            Ok(())
+        } else {
+            Err(CodecError::Other(format!(
+                "the fill value size {} does not match the sparse array element size {}",
+                fill_value.size(),
+                self.element_size
+            )))
+        }
+    }
+
+    /// Convert to a dense [`ArrayBytes`] with `fill_value`.
+    ///
+    /// # Errors
+    /// Returns a [`CodecError`] if the size of `fill_value` does not match the element size.
+    ///
+    /// # Panics
+    /// Panics if the size of the dense array exceeds [`usize::MAX`].
+    pub fn to_dense(&self, fill_value: &FillValue) -> Result<ArrayBytes<'static>, CodecError> {
+        self.check_fill_value(fill_value)?;
+        let num_elements = usize::try_from(self.num_elements).unwrap();
+        let mut bytes = fill_value.as_ne_bytes().repeat(num_elements);
+        for (index, value) in self.iter() {
+            let offset = usize::try_from(index).unwrap() * self.element_size;
+            bytes[offset..offset + self.element_size].copy_from_slice(value);
+        }
+        Ok(ArrayBytes::new_flen(RawBytes::from(bytes)))
+    }
+
+    /// Extract the dense bytes of `subset` of the array with `array_shape` and `fill_value`.
+    ///
+    /// Only the elements that differ from the fill value are visited, so the array is never densified.
+    ///
+    /// # Errors
+    /// Returns a [`CodecError`] if `subset` is not within `array_shape`, `array_shape` does not match the number of elements, or the size of `fill_value` does not match the element size.
+    ///
+    /// # Panics
+    /// Panics if the size of the subset exceeds [`usize::MAX`].
+    pub fn extract_array_subset(
+        &self,
+        subset: &ArraySubset,
+        array_shape: &[u64],
+        fill_value: &FillValue,
+    ) -> Result<ArrayBytes<'static>, CodecError> {
+        self.check_fill_value(fill_value)?;
+        if !subset.inbounds(array_shape) || array_shape.iter().product::<u64>() != self.num_elements
+        {
+            return Err(IncompatibleArraySubsetAndShapeError::new(
+                subset.clone(),
+                array_shape.to_vec(),
+            )
+            .into());
+        }
+        let mut bytes = fill_value.as_ne_bytes().repeat(subset.num_elements_usize());
+        for (index, value) in self.iter() {
+            let indices = unravel_index(index, array_shape);
+            if subset.contains(&indices) {
+                let indices_subset: Vec<u64> = std::iter::zip(&indices, subset.start())
+                    .map(|(index, start)| index - start)
+                    .collect();
+                let offset = usize::try_from(ravel_indices(&indices_subset, subset.shape()))
+                    .unwrap()
+                    * self.element_size;
+                bytes[offset..offset + self.element_size].copy_from_slice(value);
+            }
+        }
+        Ok(ArrayBytes::new_flen(RawBytes::from(bytes)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparse_array_bytes() -> Result<(), Box<dyn std::error::Error>> {
+        let fill_value = FillValue::from(0u16);
+        let dense: Vec<u8> = crate::array::transmute_to_bytes_vec(vec![0u16, 5, 0, 0, 0, 7]);
+        let sparse = SparseArrayBytes::from_dense(&dense, &fill_value)?;
+        assert_eq!(sparse.num_elements(), 6);
+        assert_eq!(sparse.num_stored(), 2);
+        assert_eq!(sparse.indices(), &[1, 5]);
+        assert_eq!(
+            sparse.iter().collect::<Vec<_>>(),
+            vec![(1, &5u16.to_ne_bytes()[..]), (5, &7u16.to_ne_bytes()[..])]
+        );
+        assert_eq!(sparse.to_dense(&fill_value)?, ArrayBytes::from(dense));
+
+        let subset = ArraySubset::new_with_ranges(&[1..2, 1..3]);
+        assert_eq!(
+            sparse.extract_array_subset(&subset, &[2, 3], &fill_value)?,
+            ArrayBytes::from(crate::array::transmute_to_bytes_vec(vec![0u16, 7]))
+        );
+        assert!(sparse
+            .extract_array_subset(&subset, &[3, 3], &fill_value)
+            .is_err());
+        assert!(sparse.to_dense(&FillValue::from(0u8)).is_err());
+
+        assert!(SparseArrayBytes::new(4, 1, vec![1, 1], vec![1, 2]).is_err());
+        assert!(SparseArrayBytes::new(4, 1, vec![4], vec![1]).is_err());
+        assert!(SparseArrayBytes::new(4, 1, vec![1], vec![1, 2]).is_err());
+        assert!(SparseArrayBytes::new_fill_value(4, 1).is_fill_value());
+        Ok(())
+    }
+}
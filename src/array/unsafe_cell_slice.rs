@@ -24,6 +24,15 @@ impl<'a, T: Copy> UnsafeCellSlice<'a, T> {
         Self::new(unsafe { vec_spare_capacity_to_mut_slice(vec) })
     }
 
+    /// Create a new [`UnsafeCellSlice`] from the first `len` elements of the spare capacity in a [`Vec`].
+    ///
+    /// # Panics
+    /// Panics if `len` exceeds the spare capacity of `vec`.
+    #[must_use]
+    pub fn new_from_vec_with_spare_capacity_len(vec: &'a mut Vec<T>, len: usize) -> Self {
+        Self::new(&mut unsafe { vec_spare_capacity_to_mut_slice(vec) }[..len])
+    }
+
     /// Get a mutable reference to the underlying slice.
     ///
     /// # Safety
@@ -128,9 +128,16 @@ impl InvalidByteRangeError {
     pub fn new(byte_range: ByteRange, bytes_len: u64) -> Self {
         Self(byte_range, bytes_len)
     }
+
+    /// Return the invalid byte range.
+    #[must_use]
+    pub const fn byte_range(&self) -> ByteRange {
+        self.0
+    }
 }
 
-fn validate_byte_ranges(
+/// Returns [`InvalidByteRangeError`] if any bytes are requested beyond `bytes_len`.
+pub(crate) fn validate_byte_ranges(
     byte_ranges: &[ByteRange],
     bytes_len: u64,
 ) -> Result<(), InvalidByteRangeError> {
@@ -7,13 +7,21 @@ use std::{
     sync::{OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard},
 };
 
+use crate::array::{builtin_codec_profiles, CodecProfile};
 #[cfg(doc)]
 use crate::array::{codec::CodecOptions, ArrayMetadataOptions};
-use crate::metadata::{v3::codec, MetadataConvertVersion, MetadataEraseVersion};
+use crate::metadata::{
+    v3::{codec, SupportedAdditionalField},
+    ImplicitGroupPolicy, MetadataConvertVersion, MetadataEraseVersion, MetadataJsonFormat,
+    MetadataValidationMode,
+};
 
 /// Global configuration options for the `zarrs` crate.
 ///
 /// Retrieve the global [`Config`] with [`global_config`] and modify it with [`global_config_mut`].
+/// Temporarily override the global [`Config`] with [`global_config_override`].
+///
+/// Library defaults (e.g. of [`CodecOptions`] and [`ArrayMetadataOptions`]) are derived from the global [`Config`], so an application can change them without passing custom options through every call path.
 ///
 /// ## Codec / Chunk Options
 ///
@@ -35,7 +43,7 @@ use crate::metadata::{v3::codec, MetadataConvertVersion, MetadataEraseVersion};
 /// If `true`, the aforementioned test is skipped and all chunks are stored.
 ///
 /// ### Codec Concurrent Target
-/// > default: [`std::thread::available_parallelism`]`()`
+/// > default: [`std::thread::available_parallelism`]`()`, or 1 if it is unavailable
 ///
 /// [`CodecOptions::concurrent_target()`] defaults to [`Config::codec_concurrent_target()`].
 ///
@@ -75,6 +83,25 @@ use crate::metadata::{v3::codec, MetadataConvertVersion, MetadataEraseVersion};
 /// The default behaviour for the `erase_metadata` methods of [`crate::array::Array`] and [`crate::group::Group`].
 /// Determines whether to erase metadata of a specific Zarr version, the same version as the array/group was created with, or all known versions.
 ///
+/// ### Metadata JSON Format
+/// > default: [`MetadataJsonFormat::pretty()`] (indented with two spaces, keys in their default order)
+///
+/// [`ArrayMetadataOptions::metadata_json_format`](crate::array::ArrayMetadataOptions::metadata_json_format) and [`GroupMetadataOptions::metadata_json_format`](crate::group::GroupMetadataOptions::metadata_json_format) default to [`Config::metadata_json_format`].
+///
+/// Determines the indentation and key ordering of metadata documents written by the `store_metadata` methods of [`crate::array::Array`] and [`crate::group::Group`].
+/// Sorting keys produces stable output that is easier to compare between metadata versions.
+///
+/// ### Implicit Group Policy
+/// > default: [`ImplicitGroupPolicy::Allow`]
+///
+/// Determines whether a node without metadata is opened as an implicit group by [`Node::open`](crate::node::Node::open) and [`Group::open`](crate::group::Group::open), and whether the metadata of implicit groups is stored (or required) when creating a child node with [`Group::create_group`](crate::group::Group::create_group), [`Group::create_array`](crate::group::Group::create_array), or [`Array::store_metadata`](crate::array::Array::store_metadata).
+///
+/// ### Metadata Validation Mode
+/// > default: [`MetadataValidationMode::Strict`]
+///
+/// Determines whether malformed array metadata (e.g. a fill value that is out of range of the data type) is rejected or coerced when creating or opening an array.
+/// See [`MetadataValidationMode`].
+///
 /// ### Include `zarrs` Metadata
 /// > default: [`true`]
 ///
@@ -90,12 +117,42 @@ use crate::metadata::{v3::codec, MetadataConvertVersion, MetadataEraseVersion};
 ///  }
 /// ```
 ///
+/// ### Experimental Codecs Enabled
+/// > default: [`true`]
+///
+/// If `false`, creating an experimental codec (e.g. when opening an array) fails.
+/// An experimental codec is any codec with an entry in the [experimental codec names](#experimental-codec-names).
+/// Applications that must only produce and consume arrays readable by other Zarr V3 implementations can opt out of experimental codecs.
+///
 /// ### Experimental Codec Names
 /// > default: See the [crate root documentation](crate#array-support).
 ///
 /// Sets the names used when serialising and deserialising the names of experimental codecs.
 /// Deserialisation also accepts the standard `IDENTIFIER` of the codec.
-#[derive(Debug)]
+///
+/// ### Codec Profiles
+/// > default: `fast`, `balanced`, `archival`, and `imaging` (see [`CodecProfile`])
+///
+/// The codec profiles selectable by name with [`ArrayBuilder::codec_profile`](crate::array::ArrayBuilder::codec_profile).
+/// Custom profiles can be registered with [`Config::codec_profiles_mut`].
+///
+/// ### Supported Additional Fields
+/// > default: none
+///
+/// Zarr V3 array and group metadata with an additional field that is not annotated with `"must_understand": false` is rejected, unless the name of the field is registered here.
+/// A [`SupportedAdditionalField`] can optionally validate that the field deserialises to a type.
+/// For example:
+/// ```rust
+/// # use zarrs::{config::global_config_mut, metadata::v3::SupportedAdditionalField};
+/// #[derive(serde::Deserialize)]
+/// struct MyExtension {
+///     version: u32,
+/// }
+/// global_config_mut()
+///     .supported_additional_fields_mut()
+///     .insert("my_extension".to_string(), SupportedAdditionalField::new_with_type::<MyExtension>());
+/// ```
+#[derive(Debug, Clone)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct Config {
     validate_checksums: bool,
@@ -105,8 +162,14 @@ pub struct Config {
     experimental_codec_store_metadata_if_encode_only: bool,
     metadata_convert_version: MetadataConvertVersion,
     metadata_erase_version: MetadataEraseVersion,
+    metadata_json_format: MetadataJsonFormat,
+    implicit_group_policy: ImplicitGroupPolicy,
+    metadata_validation_mode: MetadataValidationMode,
     include_zarrs_metadata: bool,
+    experimental_codecs_enabled: bool,
     experimental_codec_names: HashMap<&'static str, String>,
+    codec_profiles: HashMap<String, CodecProfile>,
+    supported_additional_fields: HashMap<String, SupportedAdditionalField>,
 }
 
 #[allow(clippy::derivable_impls)]
@@ -124,6 +187,8 @@ impl Default for Config {
             (codec::pcodec::IDENTIFIER, "https://codec.zarrs.dev/array_to_bytes/pcodec".to_string()),
             (codec::vlen::IDENTIFIER, "https://codec.zarrs.dev/array_to_bytes/vlen".to_string()),
             (codec::vlen_v2::IDENTIFIER, "https://codec.zarrs.dev/array_to_bytes/vlen_v2".to_string()),
+            (codec::sparse::IDENTIFIER, "https://codec.zarrs.dev/array_to_bytes/sparse".to_string()),
+            (codec::rle::IDENTIFIER, "https://codec.zarrs.dev/array_to_bytes/rle".to_string()),
             // Bytes to bytes
             #[cfg(feature = "bz2")]
             (codec::bz2::IDENTIFIER, "https://codec.zarrs.dev/bytes_to_bytes/bz2".to_string()),
@@ -134,15 +199,23 @@ impl Default for Config {
         Self {
             validate_checksums: true,
             store_empty_chunks: false,
-            codec_concurrent_target: std::thread::available_parallelism().unwrap().get()
+            // available_parallelism is unsupported on some targets (e.g. wasm32-unknown-unknown)
+            codec_concurrent_target: std::thread::available_parallelism()
+                .map_or(1, std::num::NonZeroUsize::get)
                 * concurrency_multiply
                 + concurrency_add,
             chunk_concurrent_minimum: 4,
             experimental_codec_store_metadata_if_encode_only: false,
             metadata_convert_version: MetadataConvertVersion::Default,
             metadata_erase_version: MetadataEraseVersion::Default,
+            metadata_json_format: MetadataJsonFormat::pretty(),
+            implicit_group_policy: ImplicitGroupPolicy::Allow,
+            metadata_validation_mode: MetadataValidationMode::Strict,
             include_zarrs_metadata: true,
+            experimental_codecs_enabled: true,
             experimental_codec_names,
+            codec_profiles: builtin_codec_profiles(),
+            supported_additional_fields: HashMap::new(),
         }
     }
 }
@@ -235,6 +308,42 @@ impl Config {
         self
     }
 
+    /// Get the [metadata JSON format](#metadata-json-format) configuration.
+    #[must_use]
+    pub fn metadata_json_format(&self) -> &MetadataJsonFormat {
+        &self.metadata_json_format
+    }
+
+    /// Set the [metadata JSON format](#metadata-json-format) configuration.
+    pub fn set_metadata_json_format(&mut self, format: MetadataJsonFormat) -> &mut Self {
+        self.metadata_json_format = format;
+        self
+    }
+
+    /// Get the [implicit group policy](#implicit-group-policy) configuration.
+    #[must_use]
+    pub fn implicit_group_policy(&self) -> &ImplicitGroupPolicy {
+        &self.implicit_group_policy
+    }
+
+    /// Set the [implicit group policy](#implicit-group-policy) configuration.
+    pub fn set_implicit_group_policy(&mut self, policy: ImplicitGroupPolicy) -> &mut Self {
+        self.implicit_group_policy = policy;
+        self
+    }
+
+    /// Get the [metadata validation mode](#metadata-validation-mode) configuration.
+    #[must_use]
+    pub fn metadata_validation_mode(&self) -> &MetadataValidationMode {
+        &self.metadata_validation_mode
+    }
+
+    /// Set the [metadata validation mode](#metadata-validation-mode) configuration.
+    pub fn set_metadata_validation_mode(&mut self, mode: MetadataValidationMode) -> &mut Self {
+        self.metadata_validation_mode = mode;
+        self
+    }
+
     /// Get the [include zarrs metadata](#include-zarrs-metadata) configuration.
     #[must_use]
     pub fn include_zarrs_metadata(&self) -> bool {
@@ -247,6 +356,18 @@ impl Config {
         self
     }
 
+    /// Get the [experimental codecs enabled](#experimental-codecs-enabled) configuration.
+    #[must_use]
+    pub fn experimental_codecs_enabled(&self) -> bool {
+        self.experimental_codecs_enabled
+    }
+
+    /// Set the [experimental codecs enabled](#experimental-codecs-enabled) configuration.
+    pub fn set_experimental_codecs_enabled(&mut self, enabled: bool) -> &mut Self {
+        self.experimental_codecs_enabled = enabled;
+        self
+    }
+
     /// Get the [experimental codec names](#experimental-codec-names) configuration.
     #[must_use]
     pub fn experimental_codec_names(&self) -> &HashMap<&'static str, String> {
@@ -257,6 +378,30 @@ impl Config {
     pub fn experimental_codec_names_mut(&mut self) -> &mut HashMap<&'static str, String> {
         &mut self.experimental_codec_names
     }
+
+    /// Get the [codec profiles](#codec-profiles) configuration.
+    #[must_use]
+    pub fn codec_profiles(&self) -> &HashMap<String, CodecProfile> {
+        &self.codec_profiles
+    }
+
+    /// Get a mutable reference to the [codec profiles](#codec-profiles) configuration.
+    pub fn codec_profiles_mut(&mut self) -> &mut HashMap<String, CodecProfile> {
+        &mut self.codec_profiles
+    }
+
+    /// Get the [supported additional fields](#supported-additional-fields) configuration.
+    #[must_use]
+    pub fn supported_additional_fields(&self) -> &HashMap<String, SupportedAdditionalField> {
+        &self.supported_additional_fields
+    }
+
+    /// Get a mutable reference to the [supported additional fields](#supported-additional-fields) configuration.
+    pub fn supported_additional_fields_mut(
+        &mut self,
+    ) -> &mut HashMap<String, SupportedAdditionalField> {
+        &mut self.supported_additional_fields
+    }
 }
 
 static CONFIG: OnceLock<RwLock<Config>> = OnceLock::new();
@@ -283,6 +428,53 @@ pub fn global_config_mut() -> RwLockWriteGuard<'static, Config> {
         .unwrap()
 }
 
+/// A scoped override of the global `zarrs` configuration.
+///
+/// Created with [`global_config_override`].
+/// The global configuration is restored to its state before the override when this guard is dropped.
+#[derive(Debug)]
+#[must_use = "the global configuration is restored when the override is dropped"]
+pub struct GlobalConfigOverride {
+    previous: Option<Config>,
+}
+
+impl Drop for GlobalConfigOverride {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            *global_config_mut() = previous;
+        }
+    }
+}
+
+/// Override the global `zarrs` configuration until the returned [`GlobalConfigOverride`] is dropped.
+///
+/// `modify` is applied to the global configuration, and the configuration before modification is restored when the guard is dropped.
+/// Note that the override is visible to all threads while the guard is held, and any other changes to the global configuration made while it is held are discarded when it is dropped.
+///
+/// For example:
+/// ```rust
+/// # use zarrs::config::{global_config, global_config_override};
+/// assert!(global_config().validate_checksums());
+/// {
+///     let _override = global_config_override(|config| {
+///         config.set_validate_checksums(false);
+///     });
+///     assert!(!global_config().validate_checksums());
+/// }
+/// assert!(global_config().validate_checksums());
+/// ```
+///
+/// # Panics
+/// This function panics if the underlying lock has been poisoned and might panic if the global config is already held by the current thread.
+pub fn global_config_override(modify: impl FnOnce(&mut Config)) -> GlobalConfigOverride {
+    let mut config = global_config_mut();
+    let previous = config.clone();
+    modify(&mut config);
+    GlobalConfigOverride {
+        previous: Some(previous),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
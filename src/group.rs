@@ -5,6 +5,7 @@
 //! See <https://zarr-specs.readthedocs.io/en/latest/v3/core/v3.0.html#group>.
 //!
 //! Use [`GroupBuilder`] to setup a new group, or use [`Group::open`] to read and/or write an existing group.
+//! Use [`create_hierarchy`] to create a group and any missing intermediate groups.
 //!
 //! A group can optionally store attributes in metadata in an accompanying `zarr.json` file. For example:
 //! ```json
@@ -20,6 +21,7 @@
 //! See <https://zarr-specs.readthedocs.io/en/latest/v3/core/v3.0.html#group-metadata> for more information on group metadata.
 
 mod group_builder;
+mod group_consolidate_metadata_options;
 mod group_metadata_options;
 
 use std::sync::Arc;
@@ -28,22 +30,36 @@ use derive_more::Display;
 use thiserror::Error;
 
 use crate::{
+    array::{Array, ArrayBuilder, ArrayCreateError, ArrayMetadata},
     metadata::{
-        group_metadata_v2_to_v3, v3::UnsupportedAdditionalFieldError, AdditionalFields,
-        GroupMetadataV2, MetadataConvertVersion, MetadataEraseVersion, MetadataRetrieveVersion,
+        attributes_merge_patch, group_metadata_v2_to_v3, v3::UnsupportedAdditionalFieldError,
+        AdditionalFields, ConsolidatedMetadata, ConsolidatedMetadataV2, GroupMetadataV2,
+        ImplicitGroupPolicy, MetadataConvertVersion, MetadataEraseVersion, MetadataJsonFormat,
+        MetadataRetrieveVersion,
     },
-    node::{NodePath, NodePathError},
+    node::{Node, NodeCreateError, NodeMetadata, NodeName, NodeNameError, NodePath, NodePathError},
     storage::{
-        meta_key, meta_key_v2_attributes, meta_key_v2_group, ReadableStorageTraits, StorageError,
-        StorageHandle, WritableStorageTraits,
+        backup_metadata, get_child_nodes_opt, meta_key, meta_key_v2_array, meta_key_v2_attributes,
+        meta_key_v2_consolidated, meta_key_v2_group, ListableStorageTraits, ReadableStorageTraits,
+        ReadableWritableListableStorageTraits, ReadableWritableStorageTraits, StorageError,
+        StorageHandle, StoreKey, WritableStorageTraits,
     },
 };
 
 #[cfg(feature = "async")]
-use crate::storage::{AsyncReadableStorageTraits, AsyncWritableStorageTraits};
+use futures::stream::BoxStream;
+
+#[cfg(feature = "async")]
+use crate::storage::{
+    async_backup_metadata, async_child_nodes_stream, async_get_child_nodes_opt,
+    AsyncListableStorageTraits, AsyncReadableStorageTraits,
+    AsyncReadableWritableListableStorageTraits, AsyncReadableWritableStorageTraits,
+    AsyncWritableStorageTraits,
+};
 
 pub use self::group_builder::GroupBuilder;
 pub use crate::metadata::{v3::GroupMetadataV3, GroupMetadata};
+pub use group_consolidate_metadata_options::GroupConsolidateMetadataOptions;
 pub use group_metadata_options::GroupMetadataOptions;
 
 /// A group.
@@ -208,6 +224,12 @@ impl<TStorage: ?Sized + ReadableStorageTraits> Group<TStorage> {
 
         // No metadata has been found
         match version {
+            MetadataRetrieveVersion::Default | MetadataRetrieveVersion::V3
+                if ImplicitGroupPolicy::default() == ImplicitGroupPolicy::Error =>
+            {
+                // Implicit groups are disallowed
+                Err(GroupCreateError::MissingMetadata)
+            }
             MetadataRetrieveVersion::Default | MetadataRetrieveVersion::V3 => {
                 // V3 supports missing metadata
                 Self::new_with_metadata(
@@ -277,6 +299,12 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits> Group<TStorage> {
 
         // No metadata has been found
         match version {
+            MetadataRetrieveVersion::Default | MetadataRetrieveVersion::V3
+                if ImplicitGroupPolicy::default() == ImplicitGroupPolicy::Error =>
+            {
+                // Implicit groups are disallowed
+                Err(GroupCreateError::MissingMetadata)
+            }
             MetadataRetrieveVersion::Default | MetadataRetrieveVersion::V3 => {
                 // V3 supports missing metadata
                 Self::new_with_metadata(
@@ -293,25 +321,805 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits> Group<TStorage> {
     }
 }
 
+/// Return the store keys of the metadata of a group at `path`.
+fn group_metadata_keys(path: &NodePath, metadata: &GroupMetadata) -> Vec<StoreKey> {
+    match metadata {
+        GroupMetadata::V3(_) => vec![meta_key(path)],
+        GroupMetadata::V2(_) => vec![meta_key_v2_group(path), meta_key_v2_attributes(path)],
+    }
+}
+
+/// Return the [`MetadataRetrieveVersion`] matching the Zarr version of group `metadata`.
+fn metadata_retrieve_version(metadata: &GroupMetadata) -> MetadataRetrieveVersion {
+    match metadata {
+        GroupMetadata::V3(_) => MetadataRetrieveVersion::V3,
+        GroupMetadata::V2(_) => MetadataRetrieveVersion::V2,
+    }
+}
+
+/// Return the root path, the ancestors of `path`, and `path`, in that order.
+fn node_path_and_ancestors(path: &NodePath) -> Vec<NodePath> {
+    let mut paths = vec![NodePath::root()];
+    let mut ancestor = String::new();
+    for name in path.as_str().split('/').filter(|name| !name.is_empty()) {
+        ancestor.push('/');
+        ancestor.push_str(name);
+        paths.push(NodePath::new(&ancestor).expect("ancestors of a valid path are valid"));
+    }
+    paths
+}
+
+/// Returns true if the node at `path` has Zarr V3 or V2 metadata.
+fn node_has_metadata<TStorage: ?Sized + ReadableStorageTraits>(
+    storage: &TStorage,
+    path: &NodePath,
+) -> Result<bool, StorageError> {
+    for key in [
+        meta_key(path),
+        meta_key_v2_group(path),
+        meta_key_v2_array(path),
+    ] {
+        if storage.size_key(&key)?.is_some() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(feature = "async")]
+/// Async variant of [`node_has_metadata`].
+async fn async_node_has_metadata<TStorage: ?Sized + AsyncReadableStorageTraits>(
+    storage: &TStorage,
+    path: &NodePath,
+) -> Result<bool, StorageError> {
+    for key in [
+        meta_key(path),
+        meta_key_v2_group(path),
+        meta_key_v2_array(path),
+    ] {
+        if storage.size_key(&key).await?.is_some() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Return default Zarr V2 group metadata.
+fn default_group_metadata_v2() -> GroupMetadata {
+    GroupMetadata::V2(GroupMetadataV2 {
+        zarr_format: monostate::MustBe!(2u64),
+        attributes: serde_json::Map::default(),
+        additional_fields: AdditionalFields::default(),
+    })
+}
+
+/// Return the ancestors of the array at `path` and default group metadata of the same Zarr version as the array.
+fn array_ancestors_and_group_metadata(
+    path: &NodePath,
+    array_metadata: &ArrayMetadata,
+) -> (Vec<NodePath>, GroupMetadata) {
+    let mut ancestors = node_path_and_ancestors(path);
+    ancestors.pop();
+    let group_metadata = match array_metadata {
+        ArrayMetadata::V3(_) => GroupMetadata::V3(GroupMetadataV3::default()),
+        ArrayMetadata::V2(_) => default_group_metadata_v2(),
+    };
+    (ancestors, group_metadata)
+}
+
+/// Return the error for a group at `path` without metadata if implicit groups are disallowed by the [`ImplicitGroupPolicy`].
+fn implicit_group_error(path: &NodePath) -> StorageError {
+    StorageError::Other(format!(
+        "the group at {path} has no metadata and implicit groups are disallowed"
+    ))
+}
+
+/// Handle the groups at `paths` without metadata according to the [`ImplicitGroupPolicy`].
+///
+/// If the policy is [`CreateOnWrite`](ImplicitGroupPolicy::CreateOnWrite), the metadata of a group without metadata is stored with `group_metadata`.
+/// If the policy is [`Error`](ImplicitGroupPolicy::Error), an error is returned for the first group without metadata.
+fn store_implicit_group_metadata<TStorage: ?Sized + ReadableWritableStorageTraits>(
+    storage: &Arc<TStorage>,
+    paths: Vec<NodePath>,
+    group_metadata: impl Fn(&NodePath) -> GroupMetadata,
+) -> Result<(), StorageError> {
+    let implicit_group_policy = ImplicitGroupPolicy::default();
+    if implicit_group_policy == ImplicitGroupPolicy::Allow {
+        return Ok(());
+    }
+    for path in paths {
+        if node_has_metadata(&**storage, &path)? {
+            continue;
+        }
+        if implicit_group_policy == ImplicitGroupPolicy::Error {
+            return Err(implicit_group_error(&path));
+        }
+        let group = Group {
+            storage: storage.clone(),
+            metadata: group_metadata(&path),
+            path,
+        };
+        group.store_metadata()?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+/// Async variant of [`store_implicit_group_metadata`].
+async fn async_store_implicit_group_metadata<
+    TStorage: ?Sized + AsyncReadableWritableStorageTraits,
+>(
+    storage: &Arc<TStorage>,
+    paths: Vec<NodePath>,
+    group_metadata: impl Fn(&NodePath) -> GroupMetadata,
+) -> Result<(), StorageError> {
+    let implicit_group_policy = ImplicitGroupPolicy::default();
+    if implicit_group_policy == ImplicitGroupPolicy::Allow {
+        return Ok(());
+    }
+    for path in paths {
+        if async_node_has_metadata(&**storage, &path).await? {
+            continue;
+        }
+        if implicit_group_policy == ImplicitGroupPolicy::Error {
+            return Err(implicit_group_error(&path));
+        }
+        let group = Group {
+            storage: storage.clone(),
+            metadata: group_metadata(&path),
+            path,
+        };
+        group.async_store_metadata().await?;
+    }
+    Ok(())
+}
+
+/// Handle the ancestor groups without metadata of the array at `path` with `array_metadata` according to the [`ImplicitGroupPolicy`].
+///
+/// Missing ancestors are stored with default metadata of the same Zarr version as the array.
+pub(crate) fn store_array_implicit_group_metadata<
+    TStorage: ?Sized + ReadableWritableStorageTraits,
+>(
+    storage: &Arc<TStorage>,
+    path: &NodePath,
+    array_metadata: &ArrayMetadata,
+) -> Result<(), StorageError> {
+    let (ancestors, group_metadata) = array_ancestors_and_group_metadata(path, array_metadata);
+    store_implicit_group_metadata(storage, ancestors, |_| group_metadata.clone())
+}
+
+#[cfg(feature = "async")]
+/// Async variant of [`store_array_implicit_group_metadata`].
+pub(crate) async fn async_store_array_implicit_group_metadata<
+    TStorage: ?Sized + AsyncReadableWritableStorageTraits,
+>(
+    storage: &Arc<TStorage>,
+    path: &NodePath,
+    array_metadata: &ArrayMetadata,
+) -> Result<(), StorageError> {
+    let (ancestors, group_metadata) = array_ancestors_and_group_metadata(path, array_metadata);
+    async_store_implicit_group_metadata(storage, ancestors, |_| group_metadata.clone()).await
+}
+
+/// Create a group at `path` and at any of its ancestors without metadata, and return the group at `path`.
+///
+/// Missing groups are stored with default Zarr V3 metadata.
+/// Existing nodes are not modified, so this is a no-op if every node in the path already has metadata.
+///
+/// For example, `create_hierarchy(&store, "/experiments/run5/raw")` stores the metadata of the groups `/`, `/experiments`, `/experiments/run5`, and `/experiments/run5/raw` if they are missing.
+///
+/// # Errors
+/// Returns [`GroupCreateError`] if `path` is not a valid node path, the metadata of the group at `path` is invalid, or there is an underlying store error.
+pub fn create_hierarchy<TStorage: ?Sized + ReadableWritableStorageTraits>(
+    storage: &Arc<TStorage>,
+    path: &str,
+) -> Result<Group<TStorage>, GroupCreateError> {
+    let path = NodePath::new(path)?;
+    store_missing_group_metadata(storage, &path)?;
+    Group::open(storage.clone(), path.as_str())
+}
+
+/// Store default Zarr V3 group metadata at `path` and at any of its ancestors without metadata.
+pub(crate) fn store_missing_group_metadata<TStorage: ?Sized + ReadableWritableStorageTraits>(
+    storage: &Arc<TStorage>,
+    path: &NodePath,
+) -> Result<(), StorageError> {
+    for path in node_path_and_ancestors(path) {
+        if !node_has_metadata(&**storage, &path)? {
+            let group = Group {
+                storage: storage.clone(),
+                path,
+                metadata: GroupMetadata::V3(GroupMetadataV3::default()),
+            };
+            group.store_metadata()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+/// Async variant of [`create_hierarchy`].
+#[allow(clippy::missing_errors_doc)]
+pub async fn async_create_hierarchy<TStorage: ?Sized + AsyncReadableWritableStorageTraits>(
+    storage: &Arc<TStorage>,
+    path: &str,
+) -> Result<Group<TStorage>, GroupCreateError> {
+    let path = NodePath::new(path)?;
+    async_store_missing_group_metadata(storage, &path).await?;
+    Group::async_open(storage.clone(), path.as_str()).await
+}
+
+#[cfg(feature = "async")]
+/// Async variant of [`store_missing_group_metadata`].
+pub(crate) async fn async_store_missing_group_metadata<
+    TStorage: ?Sized + AsyncReadableWritableStorageTraits,
+>(
+    storage: &Arc<TStorage>,
+    path: &NodePath,
+) -> Result<(), StorageError> {
+    for path in node_path_and_ancestors(path) {
+        if !async_node_has_metadata(&**storage, &path).await? {
+            let group = Group {
+                storage: storage.clone(),
+                path,
+                metadata: GroupMetadata::V3(GroupMetadataV3::default()),
+            };
+            group.async_store_metadata().await?;
+        }
+    }
+    Ok(())
+}
+
 /// A group creation error.
 #[derive(Debug, Error)]
 pub enum GroupCreateError {
     /// An invalid node path
     #[error(transparent)]
     NodePathError(#[from] NodePathError),
+    /// An invalid node name.
+    #[error(transparent)]
+    NodeNameError(#[from] NodeNameError),
     /// Unsupported additional field.
     #[error(transparent)]
     UnsupportedAdditionalFieldError(UnsupportedAdditionalFieldError),
     /// Storage error.
     #[error(transparent)]
     StorageError(#[from] StorageError),
-    /// Missing metadata (Zarr V2 only).
-    #[error("group metadata is missing (Zarr V2 only)")]
+    /// Missing metadata.
+    ///
+    /// Zarr V2 groups require metadata, as do Zarr V3 groups if implicit groups are disallowed by the [`ImplicitGroupPolicy`].
+    #[error("group metadata is missing")]
     MissingMetadata,
 }
 
+/// A group metadata consolidation error.
+#[derive(Debug, Error)]
+pub enum GroupConsolidateMetadataError {
+    /// A node creation error.
+    #[error(transparent)]
+    NodeCreateError(#[from] NodeCreateError),
+    /// Storage error.
+    #[error(transparent)]
+    StorageError(#[from] StorageError),
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits + ListableStorageTraits> Group<TStorage> {
+    /// Return the children of the group.
+    ///
+    /// If `recursive` is true, the descendants of child groups are also retrieved and can be accessed with [`Node::children`].
+    ///
+    /// # Errors
+    /// Returns [`StorageError`] if any metadata is invalid or there is an underlying store error.
+    pub fn children(&self, recursive: bool) -> Result<Vec<Node>, StorageError> {
+        get_child_nodes_opt(&self.storage, &self.path, recursive)
+    }
+
+    /// Return the child groups of the group.
+    ///
+    /// # Errors
+    /// Returns [`GroupCreateError`] if any metadata is invalid or there is an underlying store error.
+    pub fn child_groups(&self) -> Result<Vec<Self>, GroupCreateError> {
+        self.child_groups_from_nodes(self.children(false)?)
+    }
+
+    /// Return the child arrays of the group.
+    ///
+    /// # Errors
+    /// Returns [`ArrayCreateError`] if any metadata is invalid or there is an underlying store error.
+    pub fn child_arrays(&self) -> Result<Vec<Array<TStorage>>, ArrayCreateError> {
+        self.child_arrays_from_nodes(self.children(false)?)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<TStorage: ?Sized + AsyncReadableStorageTraits + AsyncListableStorageTraits> Group<TStorage> {
+    /// Async variant of [`children`](Group::children).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_children(&self, recursive: bool) -> Result<Vec<Node>, StorageError> {
+        async_get_child_nodes_opt(&self.storage, &self.path, recursive).await
+    }
+
+    /// Return a stream of the direct children of the group.
+    ///
+    /// Children are discovered incrementally with paginated store listing and returned as soon as their metadata is retrieved, rather than after listing all children.
+    /// Children are not necessarily returned in order, and their descendants are not retrieved.
+    ///
+    /// Any [`StorageError`] is returned by the stream.
+    #[must_use]
+    pub fn async_children_stream(&self) -> BoxStream<'_, Result<Node, StorageError>> {
+        async_child_nodes_stream(&self.storage, &self.path)
+    }
+
+    /// Async variant of [`child_groups`](Group::child_groups).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_child_groups(&self) -> Result<Vec<Self>, GroupCreateError> {
+        self.child_groups_from_nodes(self.async_children(false).await?)
+    }
+
+    /// Async variant of [`child_arrays`](Group::child_arrays).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_child_arrays(&self) -> Result<Vec<Array<TStorage>>, ArrayCreateError> {
+        self.child_arrays_from_nodes(self.async_children(false).await?)
+    }
+}
+
 impl<TStorage: ?Sized + ReadableStorageTraits> Group<TStorage> {}
 
+impl<TStorage: ?Sized> Group<TStorage> {
+    /// Return the path of a child of the group with `name`.
+    fn child_path(&self, name: &str) -> Result<String, NodeNameError> {
+        if name.is_empty() || !NodeName::validate(name) {
+            return Err(NodeNameError(name.to_string()));
+        }
+        Ok(format!(
+            "{}/{name}",
+            self.path.as_str().trim_end_matches('/')
+        ))
+    }
+
+    /// Return default group metadata of the same Zarr version as the group.
+    fn default_child_metadata(&self) -> GroupMetadata {
+        match self.metadata {
+            GroupMetadata::V3(_) => GroupMetadata::V3(GroupMetadataV3::default()),
+            GroupMetadata::V2(_) => default_group_metadata_v2(),
+        }
+    }
+
+    /// Create a child group with `name` and default metadata of the same Zarr version as the group.
+    fn child_group(&self, name: &str) -> Result<Self, GroupCreateError> {
+        Self::new_with_metadata(
+            self.storage.clone(),
+            &self.child_path(name)?,
+            self.default_child_metadata(),
+        )
+    }
+
+    /// Create the groups among child `nodes`.
+    fn child_groups_from_nodes(&self, nodes: Vec<Node>) -> Result<Vec<Self>, GroupCreateError> {
+        nodes
+            .into_iter()
+            .filter_map(|node| match node.metadata() {
+                NodeMetadata::Group(metadata) => Some(Self::new_with_metadata(
+                    self.storage.clone(),
+                    node.path().as_str(),
+                    metadata.clone(),
+                )),
+                NodeMetadata::Array(_) => None,
+            })
+            .collect()
+    }
+
+    /// Create the arrays among child `nodes`.
+    fn child_arrays_from_nodes(
+        &self,
+        nodes: Vec<Node>,
+    ) -> Result<Vec<Array<TStorage>>, ArrayCreateError> {
+        nodes
+            .into_iter()
+            .filter_map(|node| match node.metadata() {
+                NodeMetadata::Array(metadata) => Some(Array::new_with_metadata(
+                    self.storage.clone(),
+                    node.path().as_str(),
+                    metadata.clone(),
+                )),
+                NodeMetadata::Group(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Consolidated metadata of a Zarr V3 or V2 group.
+enum GroupConsolidatedMetadata {
+    V3(ConsolidatedMetadata),
+    V2(ConsolidatedMetadataV2),
+}
+
+impl<TStorage: ?Sized> Group<TStorage> {
+    /// Consolidate the metadata of the live hierarchy below the group at `node`.
+    fn consolidate_metadata_node(
+        &self,
+        node: &Node,
+        options: &GroupConsolidateMetadataOptions,
+    ) -> Result<GroupConsolidatedMetadata, GroupConsolidateMetadataError> {
+        let path = self.path.as_str().trim_end_matches('/');
+        match self.metadata {
+            GroupMetadata::V3(_) => {
+                let mut consolidated_metadata = node.consolidate_metadata();
+                consolidated_metadata
+                    .metadata
+                    .retain(|relative, _| !options.is_skipped(&format!("{path}/{relative}")));
+                Ok(GroupConsolidatedMetadata::V3(consolidated_metadata))
+            }
+            GroupMetadata::V2(_) => {
+                let mut consolidated_metadata = node.consolidate_metadata_v2().map_err(|err| {
+                    StorageError::InvalidMetadata(
+                        meta_key_v2_consolidated(&self.path),
+                        err.to_string(),
+                    )
+                })?;
+                consolidated_metadata.metadata.retain(|key, _| {
+                    key.rsplit_once('/').map_or(true, |(relative, _)| {
+                        !options.is_skipped(&format!("{path}/{relative}"))
+                    })
+                });
+                Ok(GroupConsolidatedMetadata::V2(consolidated_metadata))
+            }
+        }
+    }
+
+    /// Returns true if `consolidated_metadata` differs from the consolidated metadata of the group.
+    ///
+    /// `current_v2` is the `.zmetadata` document of a Zarr V2 group.
+    fn consolidated_metadata_differs(
+        &self,
+        consolidated_metadata: &GroupConsolidatedMetadata,
+        current_v2: Option<&[u8]>,
+    ) -> bool {
+        match (consolidated_metadata, &self.metadata) {
+            (GroupConsolidatedMetadata::V3(consolidated_metadata), GroupMetadata::V3(metadata)) => {
+                metadata.consolidated_metadata().map_or(true, |current| {
+                    current.as_ref() != Some(consolidated_metadata)
+                })
+            }
+            (GroupConsolidatedMetadata::V2(consolidated_metadata), _) => {
+                current_v2
+                    .and_then(|current| {
+                        serde_json::from_slice::<ConsolidatedMetadataV2>(current).ok()
+                    })
+                    .as_ref()
+                    != Some(consolidated_metadata)
+            }
+            (GroupConsolidatedMetadata::V3(_), GroupMetadata::V2(_)) => true,
+        }
+    }
+
+    /// Serialise Zarr V2 consolidated metadata.
+    fn consolidated_metadata_v2_json(
+        &self,
+        consolidated_metadata: &ConsolidatedMetadataV2,
+    ) -> Result<Vec<u8>, StorageError> {
+        MetadataJsonFormat::default()
+            .serialize(consolidated_metadata)
+            .map_err(|err| {
+                StorageError::InvalidMetadata(meta_key_v2_consolidated(&self.path), err.to_string())
+            })
+    }
+
+    /// Set the consolidated metadata of a Zarr V3 group.
+    fn set_consolidated_metadata(
+        &mut self,
+        consolidated_metadata: ConsolidatedMetadata,
+    ) -> Result<(), StorageError> {
+        if let GroupMetadata::V3(metadata) = &mut self.metadata {
+            metadata
+                .set_consolidated_metadata(Some(consolidated_metadata))
+                .map_err(|err| {
+                    StorageError::InvalidMetadata(meta_key(&self.path), err.to_string())
+                })?;
+        }
+        Ok(())
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableListableStorageTraits> Group<TStorage> {
+    /// Returns true if the consolidated metadata of the group is missing or differs from the metadata of the hierarchy below the group in the store.
+    ///
+    /// The consolidated metadata of a Zarr V2 group is read from its `.zmetadata` document.
+    ///
+    /// # Errors
+    /// Returns [`GroupConsolidateMetadataError`] if any metadata is invalid or there is an underlying store error.
+    pub fn consolidated_metadata_is_stale(
+        &self,
+        options: &GroupConsolidateMetadataOptions,
+    ) -> Result<bool, GroupConsolidateMetadataError> {
+        let node = Node::open(&self.storage, self.path.as_str())?;
+        let consolidated_metadata = self.consolidate_metadata_node(&node, options)?;
+        self.consolidated_metadata_is_stale_impl(&consolidated_metadata)
+    }
+
+    fn consolidated_metadata_is_stale_impl(
+        &self,
+        consolidated_metadata: &GroupConsolidatedMetadata,
+    ) -> Result<bool, GroupConsolidateMetadataError> {
+        let current_v2 = match consolidated_metadata {
+            GroupConsolidatedMetadata::V3(_) => None,
+            GroupConsolidatedMetadata::V2(_) => {
+                self.storage.get(&meta_key_v2_consolidated(&self.path))?
+            }
+        };
+        Ok(self.consolidated_metadata_differs(consolidated_metadata, current_v2.as_deref()))
+    }
+
+    /// Consolidate the metadata of the hierarchy below the group and store it with default [`GroupConsolidateMetadataOptions`].
+    ///
+    /// See [`consolidate_and_store_opt`](Group::consolidate_and_store_opt).
+    ///
+    /// # Errors
+    /// Returns [`GroupConsolidateMetadataError`] if any metadata is invalid or there is an underlying store error.
+    pub fn consolidate_and_store(&mut self) -> Result<bool, GroupConsolidateMetadataError> {
+        self.consolidate_and_store_opt(&GroupConsolidateMetadataOptions::default())
+    }
+
+    /// Consolidate the metadata of the hierarchy below the group and store it with non-default [`GroupConsolidateMetadataOptions`].
+    ///
+    /// The metadata of the hierarchy is read from the store and:
+    ///  - for a Zarr V3 group, set as the [`ConsolidatedMetadata`] of the group and the group metadata is stored, or
+    ///  - for a Zarr V2 group, stored as a `.zmetadata` document ([`ConsolidatedMetadataV2`]) alongside the group metadata.
+    ///
+    /// A hierarchy with consolidated metadata can be opened with [`Node::open_consolidated`].
+    ///
+    /// Returns true if the consolidated metadata was stored, which is always the case unless [`only_if_stale`](GroupConsolidateMetadataOptions::only_if_stale) is set.
+    ///
+    /// # Errors
+    /// Returns [`GroupConsolidateMetadataError`] if any metadata is invalid or there is an underlying store error.
+    pub fn consolidate_and_store_opt(
+        &mut self,
+        options: &GroupConsolidateMetadataOptions,
+    ) -> Result<bool, GroupConsolidateMetadataError> {
+        let node = Node::open(&self.storage, self.path.as_str())?;
+        let consolidated_metadata = self.consolidate_metadata_node(&node, options)?;
+        if options.only_if_stale()
+            && !self.consolidated_metadata_is_stale_impl(&consolidated_metadata)?
+        {
+            return Ok(false);
+        }
+        match consolidated_metadata {
+            GroupConsolidatedMetadata::V3(consolidated_metadata) => {
+                self.set_consolidated_metadata(consolidated_metadata)?;
+                self.store_metadata()?;
+            }
+            GroupConsolidatedMetadata::V2(consolidated_metadata) => {
+                let json = self.consolidated_metadata_v2_json(&consolidated_metadata)?;
+                self.storage
+                    .set(&meta_key_v2_consolidated(&self.path), json.into())?;
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<TStorage: ?Sized + AsyncReadableWritableListableStorageTraits> Group<TStorage> {
+    /// Async variant of [`consolidated_metadata_is_stale`](Group::consolidated_metadata_is_stale).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_consolidated_metadata_is_stale(
+        &self,
+        options: &GroupConsolidateMetadataOptions,
+    ) -> Result<bool, GroupConsolidateMetadataError> {
+        let node = Node::async_open(self.storage.clone(), self.path.as_str()).await?;
+        let consolidated_metadata = self.consolidate_metadata_node(&node, options)?;
+        self.async_consolidated_metadata_is_stale_impl(&consolidated_metadata)
+            .await
+    }
+
+    async fn async_consolidated_metadata_is_stale_impl(
+        &self,
+        consolidated_metadata: &GroupConsolidatedMetadata,
+    ) -> Result<bool, GroupConsolidateMetadataError> {
+        let current_v2 = match consolidated_metadata {
+            GroupConsolidatedMetadata::V3(_) => None,
+            GroupConsolidatedMetadata::V2(_) => {
+                self.storage
+                    .get(&meta_key_v2_consolidated(&self.path))
+                    .await?
+            }
+        };
+        Ok(self.consolidated_metadata_differs(consolidated_metadata, current_v2.as_deref()))
+    }
+
+    /// Async variant of [`consolidate_and_store`](Group::consolidate_and_store).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_consolidate_and_store(
+        &mut self,
+    ) -> Result<bool, GroupConsolidateMetadataError> {
+        self.async_consolidate_and_store_opt(&GroupConsolidateMetadataOptions::default())
+            .await
+    }
+
+    /// Async variant of [`consolidate_and_store_opt`](Group::consolidate_and_store_opt).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_consolidate_and_store_opt(
+        &mut self,
+        options: &GroupConsolidateMetadataOptions,
+    ) -> Result<bool, GroupConsolidateMetadataError> {
+        let node = Node::async_open(self.storage.clone(), self.path.as_str()).await?;
+        let consolidated_metadata = self.consolidate_metadata_node(&node, options)?;
+        if options.only_if_stale()
+            && !self
+                .async_consolidated_metadata_is_stale_impl(&consolidated_metadata)
+                .await?
+        {
+            return Ok(false);
+        }
+        match consolidated_metadata {
+            GroupConsolidatedMetadata::V3(consolidated_metadata) => {
+                self.set_consolidated_metadata(consolidated_metadata)?;
+                self.async_store_metadata().await?;
+            }
+            GroupConsolidatedMetadata::V2(consolidated_metadata) => {
+                let json = self.consolidated_metadata_v2_json(&consolidated_metadata)?;
+                self.storage
+                    .set(&meta_key_v2_consolidated(&self.path), json.into())
+                    .await?;
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits> Group<TStorage> {
+    /// Create a child group with `name`, store its metadata, and return it.
+    ///
+    /// The child group has default metadata of the same Zarr version as the group.
+    /// If the [`ImplicitGroupPolicy`] is [`CreateOnWrite`](ImplicitGroupPolicy::CreateOnWrite), the metadata of the group and its ancestors is stored if it is missing.
+    /// If it is [`Error`](ImplicitGroupPolicy::Error), the metadata of the group and its ancestors must already be stored.
+    ///
+    /// # Errors
+    /// Returns [`GroupCreateError`] if `name` is not a valid node name, the group or an ancestor has no metadata and implicit groups are disallowed, or there is an underlying store error.
+    pub fn create_group(&self, name: &str) -> Result<Self, GroupCreateError> {
+        let group = self.child_group(name)?;
+        self.store_implicit_group_metadata()?;
+        group.store_metadata()?;
+        Ok(group)
+    }
+
+    /// Create a child array with `name` from `builder`, store its metadata, and return it.
+    ///
+    /// If the [`ImplicitGroupPolicy`] is [`CreateOnWrite`](ImplicitGroupPolicy::CreateOnWrite), the metadata of the group and its ancestors is stored if it is missing.
+    /// If it is [`Error`](ImplicitGroupPolicy::Error), the metadata of the group and its ancestors must already be stored.
+    ///
+    /// # Errors
+    /// Returns [`ArrayCreateError`] if `name` is not a valid node name, the array configuration is invalid, the group or an ancestor has no metadata and implicit groups are disallowed, or there is an underlying store error.
+    pub fn create_array(
+        &self,
+        name: &str,
+        builder: &ArrayBuilder,
+    ) -> Result<Array<TStorage>, ArrayCreateError>
+    where
+        TStorage: 'static,
+    {
+        let array = builder.build(self.storage.clone(), &self.child_path(name)?)?;
+        self.store_implicit_group_metadata()?;
+        array.store_metadata()?;
+        Ok(array)
+    }
+
+    /// Update the attributes in the store with an [RFC 7386](https://www.rfc-editor.org/rfc/rfc7386) JSON merge `patch`.
+    ///
+    /// See [`update_attributes_with`](Group::update_attributes_with).
+    #[allow(clippy::missing_errors_doc)]
+    pub fn update_attributes(
+        &mut self,
+        patch: &serde_json::Map<String, serde_json::Value>,
+        if_unchanged: bool,
+    ) -> Result<(), GroupCreateError> {
+        self.update_attributes_with(
+            |attributes| attributes_merge_patch(attributes, patch),
+            if_unchanged,
+        )
+    }
+
+    /// Update the attributes in the store with the `update` function.
+    ///
+    /// The metadata is read from the store, the attributes are updated, and the metadata is stored.
+    /// This composes attribute updates from other writers that happen before the metadata is read, rather than overwriting them.
+    /// Any changes to the metadata of this group that have not been stored are discarded.
+    ///
+    /// If `if_unchanged` is true, the metadata is only stored if it was not changed after it was read.
+    /// The check is not atomic, so a concurrent write between checking and storing the metadata is not detected.
+    ///
+    /// # Errors
+    /// Returns a [`GroupCreateError`] if the metadata cannot be read, or [`StorageError::VersionMismatch`] if `if_unchanged` is true and the metadata was changed after it was read.
+    pub fn update_attributes_with<F: FnOnce(&mut serde_json::Map<String, serde_json::Value>)>(
+        &mut self,
+        update: F,
+        if_unchanged: bool,
+    ) -> Result<(), GroupCreateError> {
+        let keys = group_metadata_keys(&self.path, &self.metadata);
+        let versions = keys
+            .iter()
+            .map(|key| self.storage.get_version(key))
+            .collect::<Result<Vec<_>, _>>()?;
+        *self = Self::open_opt(
+            self.storage.clone(),
+            self.path.as_str(),
+            &metadata_retrieve_version(&self.metadata),
+        )?;
+        update(self.attributes_mut());
+        if if_unchanged {
+            for (key, version) in keys.into_iter().zip(versions) {
+                if self.storage.get_version(&key)? != version {
+                    return Err(StorageError::VersionMismatch(key).into());
+                }
+            }
+        }
+        self.store_metadata()?;
+        Ok(())
+    }
+
+    /// Store metadata with default [`GroupMetadataOptions`] after backing up the metadata in the store.
+    ///
+    /// See [`store_metadata_with_backup_opt`](Group::store_metadata_with_backup_opt).
+    #[allow(clippy::missing_errors_doc)]
+    pub fn store_metadata_with_backup(&self) -> Result<(), StorageError> {
+        self.store_metadata_with_backup_opt(&GroupMetadataOptions::default())
+    }
+
+    /// Store metadata with non-default [`GroupMetadataOptions`] after backing up the metadata in the store.
+    ///
+    /// The metadata in the store is copied to its [backup key](crate::storage::meta_key_backup) (e.g. `zarr.json.bak`) before it is replaced.
+    /// If the stored metadata is later lost or corrupted (e.g. by a crash mid-write or a concurrent writer), it can be recovered with [`restore_metadata_backup`](Group::restore_metadata_backup).
+    ///
+    /// The metadata is only replaced if it was not changed after it was backed up, so a concurrent write cannot be overwritten without a backup.
+    /// The storage traits do not support conditional writes, so the check is not atomic.
+    ///
+    /// # Errors
+    /// Returns [`StorageError::VersionMismatch`] if the metadata was changed after it was backed up, or a [`StorageError`] if there is an underlying store error.
+    pub fn store_metadata_with_backup_opt(
+        &self,
+        options: &GroupMetadataOptions,
+    ) -> Result<(), StorageError> {
+        let keys = group_metadata_keys(&self.path, &self.metadata_opt(options));
+        let versions = backup_metadata(&*self.storage, &keys)?;
+        for (key, version) in keys.into_iter().zip(versions) {
+            if self.storage.get_version(&key)? != version {
+                return Err(StorageError::VersionMismatch(key));
+            }
+        }
+        self.store_metadata_opt(options)
+    }
+
+    /// Restore the metadata in the store from its backup and reopen the group.
+    ///
+    /// The backup is created by [`store_metadata_with_backup`](Group::store_metadata_with_backup).
+    ///
+    /// # Errors
+    /// Returns a [`GroupCreateError`] if there is no metadata backup, the backup cannot be restored, or the restored metadata is invalid.
+    pub fn restore_metadata_backup(&mut self) -> Result<(), GroupCreateError> {
+        let keys = group_metadata_keys(&self.path, &self.metadata);
+        crate::storage::restore_metadata_backup(&*self.storage, &keys)?;
+        *self = Self::open_opt(
+            self.storage.clone(),
+            self.path.as_str(),
+            &metadata_retrieve_version(&self.metadata),
+        )?;
+        Ok(())
+    }
+
+    /// Handle the group and its ancestors without metadata according to the [`ImplicitGroupPolicy`].
+    ///
+    /// Ancestors have default metadata of the same Zarr version as the group.
+    fn store_implicit_group_metadata(&self) -> Result<(), StorageError> {
+        store_implicit_group_metadata(&self.storage, node_path_and_ancestors(&self.path), |path| {
+            if *path == self.path {
+                self.metadata.clone()
+            } else {
+                self.default_child_metadata()
+            }
+        })
+    }
+}
+
 impl<TStorage: ?Sized + WritableStorageTraits> Group<TStorage> {
     /// Store metadata with default [`GroupMetadataOptions`].
     ///
@@ -330,7 +1138,12 @@ impl<TStorage: ?Sized + WritableStorageTraits> Group<TStorage> {
 
         // Get the metadata with options applied and store
         let metadata = self.metadata_opt(options);
-        crate::storage::create_group(&*storage_handle, self.path(), &metadata)
+        crate::storage::create_group_opt(
+            &*storage_handle,
+            self.path(),
+            &metadata,
+            options.metadata_json_format(),
+        )
     }
 
     /// Erase the metadata with default [`MetadataEraseVersion`] options.
@@ -373,6 +1186,133 @@ impl<TStorage: ?Sized + WritableStorageTraits> Group<TStorage> {
     }
 }
 
+#[cfg(feature = "async")]
+impl<TStorage: ?Sized + AsyncReadableWritableStorageTraits> Group<TStorage> {
+    /// Async variant of [`create_group`](Group::create_group).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_create_group(&self, name: &str) -> Result<Self, GroupCreateError> {
+        let group = self.child_group(name)?;
+        self.async_store_implicit_group_metadata().await?;
+        group.async_store_metadata().await?;
+        Ok(group)
+    }
+
+    /// Async variant of [`create_array`](Group::create_array).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_create_array(
+        &self,
+        name: &str,
+        builder: &ArrayBuilder,
+    ) -> Result<Array<TStorage>, ArrayCreateError>
+    where
+        TStorage: 'static,
+    {
+        let array = builder.build(self.storage.clone(), &self.child_path(name)?)?;
+        self.async_store_implicit_group_metadata().await?;
+        array.async_store_metadata().await?;
+        Ok(array)
+    }
+
+    /// Async variant of [`update_attributes`](Group::update_attributes).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_update_attributes(
+        &mut self,
+        patch: &serde_json::Map<String, serde_json::Value>,
+        if_unchanged: bool,
+    ) -> Result<(), GroupCreateError> {
+        self.async_update_attributes_with(
+            |attributes| attributes_merge_patch(attributes, patch),
+            if_unchanged,
+        )
+        .await
+    }
+
+    /// Async variant of [`update_attributes_with`](Group::update_attributes_with).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_update_attributes_with<
+        F: FnOnce(&mut serde_json::Map<String, serde_json::Value>),
+    >(
+        &mut self,
+        update: F,
+        if_unchanged: bool,
+    ) -> Result<(), GroupCreateError> {
+        let keys = group_metadata_keys(&self.path, &self.metadata);
+        let mut versions = Vec::with_capacity(keys.len());
+        for key in &keys {
+            versions.push(self.storage.get_version(key).await?);
+        }
+        *self = Self::async_open_opt(
+            self.storage.clone(),
+            self.path.as_str(),
+            &metadata_retrieve_version(&self.metadata),
+        )
+        .await?;
+        update(self.attributes_mut());
+        if if_unchanged {
+            for (key, version) in keys.into_iter().zip(versions) {
+                if self.storage.get_version(&key).await? != version {
+                    return Err(StorageError::VersionMismatch(key).into());
+                }
+            }
+        }
+        self.async_store_metadata().await?;
+        Ok(())
+    }
+
+    /// Async variant of [`store_metadata_with_backup`](Group::store_metadata_with_backup).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_store_metadata_with_backup(&self) -> Result<(), StorageError> {
+        self.async_store_metadata_with_backup_opt(&GroupMetadataOptions::default())
+            .await
+    }
+
+    /// Async variant of [`store_metadata_with_backup_opt`](Group::store_metadata_with_backup_opt).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_store_metadata_with_backup_opt(
+        &self,
+        options: &GroupMetadataOptions,
+    ) -> Result<(), StorageError> {
+        let keys = group_metadata_keys(&self.path, &self.metadata_opt(options));
+        let versions = async_backup_metadata(&*self.storage, &keys).await?;
+        for (key, version) in keys.into_iter().zip(versions) {
+            if self.storage.get_version(&key).await? != version {
+                return Err(StorageError::VersionMismatch(key));
+            }
+        }
+        self.async_store_metadata_opt(options).await
+    }
+
+    /// Async variant of [`restore_metadata_backup`](Group::restore_metadata_backup).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_restore_metadata_backup(&mut self) -> Result<(), GroupCreateError> {
+        let keys = group_metadata_keys(&self.path, &self.metadata);
+        crate::storage::async_restore_metadata_backup(&*self.storage, &keys).await?;
+        *self = Self::async_open_opt(
+            self.storage.clone(),
+            self.path.as_str(),
+            &metadata_retrieve_version(&self.metadata),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Async variant of [`store_implicit_group_metadata`](Group::store_implicit_group_metadata).
+    async fn async_store_implicit_group_metadata(&self) -> Result<(), StorageError> {
+        async_store_implicit_group_metadata(
+            &self.storage,
+            node_path_and_ancestors(&self.path),
+            |path| {
+                if *path == self.path {
+                    self.metadata.clone()
+                } else {
+                    self.default_child_metadata()
+                }
+            },
+        )
+        .await
+    }
+}
+
 #[cfg(feature = "async")]
 impl<TStorage: ?Sized + AsyncWritableStorageTraits> Group<TStorage> {
     /// Async variant of [`store_metadata`](Group::store_metadata).
@@ -392,7 +1332,13 @@ impl<TStorage: ?Sized + AsyncWritableStorageTraits> Group<TStorage> {
 
         // Get the metadata with options applied and store
         let metadata = self.metadata_opt(options);
-        crate::storage::async_create_group(&storage_handle, self.path(), &metadata).await
+        crate::storage::async_create_group_opt(
+            &storage_handle,
+            self.path(),
+            &metadata,
+            options.metadata_json_format(),
+        )
+        .await
     }
 
     /// Async variant of [`erase_metadata`](Group::erase_metadata).
@@ -554,6 +1500,27 @@ mod tests {
         // );
     }
 
+    #[test]
+    fn group_metadata_json_format() -> Result<(), Box<dyn std::error::Error>> {
+        let store = std::sync::Arc::new(MemoryStore::new());
+        let mut group = GroupBuilder::new().build(store.clone(), "/")?;
+        group
+            .attributes_mut()
+            .insert("b".to_string(), serde_json::Value::from(1));
+        group
+            .attributes_mut()
+            .insert("a".to_string(), serde_json::Value::from(2));
+        let mut format = MetadataJsonFormat::compact();
+        format.set_sort_keys(true);
+        group
+            .store_metadata_opt(GroupMetadataOptions::default().set_metadata_json_format(format))?;
+        assert_eq!(
+            store.get(&StoreKey::new("zarr.json")?)?.unwrap(),
+            br#"{"attributes":{"a":2,"b":1},"node_type":"group","zarr_format":3}"#.as_slice()
+        );
+        Ok(())
+    }
+
     #[test]
     fn group_metadata_invalid_path() {
         let group_metadata: GroupMetadata = serde_json::from_str(JSON_VALID1).unwrap();
@@ -587,6 +1554,95 @@ mod tests {
         );
     }
 
+    #[test]
+    fn group_children() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::array::{ArrayBuilder, DataType, FillValue};
+
+        let store = std::sync::Arc::new(MemoryStore::new());
+        let array_builder = ArrayBuilder::new(
+            vec![2, 3],
+            DataType::UInt8,
+            vec![1, 1].try_into()?,
+            FillValue::from(0u8),
+        );
+        let group = GroupBuilder::new().build(store.clone(), "/")?;
+        group.store_metadata()?;
+        group
+            .create_group("a")?
+            .create_array("array", &array_builder)?;
+        group.create_array("b", &array_builder)?;
+        group.create_group("c")?;
+
+        let children = group.children(false)?;
+        assert_eq!(children.len(), 3);
+        assert!(children.iter().all(|child| child.children().is_empty()));
+        assert_eq!(group.children(true)?[0].children().len(), 1);
+        let child_groups = group.child_groups()?;
+        assert_eq!(
+            child_groups
+                .iter()
+                .map(|group| group.path().as_str())
+                .collect::<Vec<_>>(),
+            ["/a", "/c"]
+        );
+        assert_eq!(
+            child_groups[0].child_arrays()?[0].path().as_str(),
+            "/a/array"
+        );
+        assert_eq!(group.child_arrays()?[0].path().as_str(), "/b");
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn group_children_async() -> Result<(), Box<dyn std::error::Error>> {
+        use futures::TryStreamExt;
+
+        use crate::array::{ArrayBuilder, DataType, FillValue};
+
+        let store = std::sync::Arc::new(crate::storage::store::AsyncObjectStore::new(
+            object_store::memory::InMemory::new(),
+        ));
+        let array_builder = ArrayBuilder::new(
+            vec![2, 3],
+            DataType::UInt8,
+            vec![1, 1].try_into()?,
+            FillValue::from(0u8),
+        );
+        let group = GroupBuilder::new().build(store.clone(), "/")?;
+        group.async_store_metadata().await?;
+        group
+            .async_create_group("a")
+            .await?
+            .async_create_array("array", &array_builder)
+            .await?;
+        group.async_create_array("b", &array_builder).await?;
+
+        assert_eq!(group.async_children(false).await?.len(), 2);
+        assert_eq!(group.async_children(true).await?[0].children().len(), 1);
+        let mut children_streamed: Vec<String> = group
+            .async_children_stream()
+            .map_ok(|child| child.path().as_str().to_string())
+            .try_collect()
+            .await?;
+        children_streamed.sort();
+        assert_eq!(children_streamed, ["/a", "/b"]);
+        let child_groups = group.async_child_groups().await?;
+        assert_eq!(child_groups.len(), 1);
+        assert_eq!(
+            child_groups[0].async_child_arrays().await?[0]
+                .path()
+                .as_str(),
+            "/a/array"
+        );
+        assert_eq!(group.async_child_arrays().await?[0].path().as_str(), "/b");
+        assert_eq!(
+            Node::async_open(store, "/").await?.hierarchy_tree(),
+            "/\n  a\n    array [2, 3] uint8\n  b [2, 3] uint8\n"
+        );
+        Ok(())
+    }
+
     #[cfg(feature = "async")]
     #[tokio::test]
     async fn group_metadata_write_read_async() {
@@ -603,6 +1659,278 @@ mod tests {
         assert_eq!(group_copy.metadata(), group.metadata());
     }
 
+    #[test]
+    fn group_create_children() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::array::{ArrayBuilder, DataType, FillValue};
+
+        let store = std::sync::Arc::new(MemoryStore::new());
+        let array_builder = ArrayBuilder::new(
+            vec![2, 3],
+            DataType::UInt8,
+            vec![1, 1].try_into()?,
+            FillValue::from(0u8),
+        );
+        let group = GroupBuilder::new().build(store.clone(), "/")?;
+        group.store_metadata()?;
+        let child = group.create_group("a")?;
+        assert_eq!(child.path().as_str(), "/a");
+        let array = child.create_array("array", &array_builder)?;
+        assert_eq!(array.path().as_str(), "/a/array");
+        assert_eq!(
+            Group::open(store.clone(), "/a")?.metadata(),
+            child.metadata()
+        );
+        assert_eq!(
+            crate::array::Array::open(store.clone(), "/a/array")?.shape(),
+            &[2, 3]
+        );
+
+        assert!(group.create_group("").is_err());
+        assert!(group.create_group("a/b").is_err());
+        assert!(group.create_group("__a").is_err());
+        assert!(child.create_array("..", &array_builder).is_err());
+
+        // Child groups of a Zarr V2 group are Zarr V2 groups
+        let group_v2 = Group::new_with_metadata(
+            store.clone(),
+            "/v2",
+            GroupMetadata::V2(serde_json::from_str(r#"{"zarr_format": 2}"#)?),
+        )?;
+        group_v2.store_metadata()?;
+        group_v2.create_group("b")?;
+        assert!(store
+            .get(&meta_key_v2_group(&"/v2/b".try_into()?))?
+            .is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn group_create_hierarchy() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::array::{ArrayBuilder, DataType, FillValue};
+
+        let store = std::sync::Arc::new(MemoryStore::new());
+        let mut root = GroupBuilder::new().build(store.clone(), "/")?;
+        root.attributes_mut()
+            .insert("key".to_string(), "value".into());
+        root.store_metadata()?;
+
+        let group = create_hierarchy(&store, "/experiments/run5/raw")?;
+        assert_eq!(group.path().as_str(), "/experiments/run5/raw");
+        for path in ["/experiments", "/experiments/run5", "/experiments/run5/raw"] {
+            assert!(store.get(&meta_key(&path.try_into()?))?.is_some());
+        }
+        assert_eq!(
+            Group::open(store.clone(), "/")?.attributes().get("key"),
+            Some(&"value".into())
+        );
+
+        // Existing groups are unmodified
+        let mut group = group;
+        group
+            .attributes_mut()
+            .insert("key".to_string(), "value".into());
+        group.store_metadata()?;
+        assert_eq!(
+            create_hierarchy(&store, "/experiments/run5/raw")?.attributes(),
+            group.attributes()
+        );
+
+        let array = ArrayBuilder::new(
+            vec![2, 3],
+            DataType::UInt8,
+            vec![1, 1].try_into()?,
+            FillValue::from(0u8),
+        )
+        .build_with_parents(store.clone(), "/experiments/run6/array")?;
+        assert!(store
+            .get(&meta_key(&"/experiments/run6".try_into()?))?
+            .is_some());
+        assert!(store.get(&meta_key(array.path()))?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn group_update_attributes() -> Result<(), Box<dyn std::error::Error>> {
+        let store = std::sync::Arc::new(MemoryStore::new());
+        let mut group = GroupBuilder::new().build(store.clone(), "/group")?;
+        group.store_metadata()?;
+        let mut group_other = Group::open(store.clone(), "/group")?;
+
+        let patch = |json: &str| serde_json::from_str::<serde_json::Map<_, _>>(json).unwrap();
+        group.update_attributes(&patch(r#"{"a": 1}"#), true)?;
+        group_other.update_attributes(&patch(r#"{"b": 2}"#), true)?;
+        assert_eq!(
+            Group::open(store.clone(), "/group")?.attributes(),
+            &patch(r#"{"a": 1, "b": 2}"#)
+        );
+
+        let result = group.update_attributes_with(
+            |attributes| {
+                attributes.remove("a");
+                group_other
+                    .update_attributes(&patch(r#"{"c": 3}"#), false)
+                    .unwrap();
+            },
+            true,
+        );
+        assert!(matches!(
+            result,
+            Err(GroupCreateError::StorageError(
+                StorageError::VersionMismatch(_)
+            ))
+        ));
+        assert_eq!(
+            Group::open(store.clone(), "/group")?.attributes(),
+            &patch(r#"{"a": 1, "b": 2, "c": 3}"#)
+        );
+
+        // Zarr V2
+        let mut group_v2 = Group::new_with_metadata(
+            store.clone(),
+            "/v2",
+            GroupMetadata::V2(serde_json::from_str(r#"{"zarr_format": 2}"#)?),
+        )?;
+        group_v2.store_metadata()?;
+        group_v2.update_attributes(&patch(r#"{"a": 1}"#), true)?;
+        assert_eq!(
+            Group::open(store.clone(), "/v2")?.attributes(),
+            &patch(r#"{"a": 1}"#)
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn group_create_hierarchy_async() -> Result<(), Box<dyn std::error::Error>> {
+        let store = std::sync::Arc::new(crate::storage::store::AsyncObjectStore::new(
+            object_store::memory::InMemory::new(),
+        ));
+        let group = async_create_hierarchy(&store, "/a/b").await?;
+        assert_eq!(group.path().as_str(), "/a/b");
+        for path in ["/", "/a", "/a/b"] {
+            assert!(store.get(&meta_key(&path.try_into()?)).await?.is_some());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn group_consolidate_and_store() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::array::{ArrayBuilder, DataType, FillValue};
+
+        let store = std::sync::Arc::new(MemoryStore::new());
+        let array_builder = ArrayBuilder::new(
+            vec![2, 3],
+            DataType::UInt8,
+            vec![1, 1].try_into()?,
+            FillValue::from(0u8),
+        );
+        let mut group = GroupBuilder::new().build(store.clone(), "/")?;
+        group.store_metadata()?;
+        GroupBuilder::new()
+            .build(store.clone(), "/a")?
+            .store_metadata()?;
+        array_builder
+            .build(store.clone(), "/a/array")?
+            .store_metadata()?;
+        array_builder.build(store.clone(), "/b")?.store_metadata()?;
+
+        assert!(group.consolidated_metadata_is_stale(&GroupConsolidateMetadataOptions::default())?);
+        assert!(group.consolidate_and_store()?);
+        assert!(!group.consolidated_metadata_is_stale(&GroupConsolidateMetadataOptions::default())?);
+        assert_eq!(
+            Node::open_consolidated(&store, "/")?.hierarchy_tree(),
+            Node::open(&store, "/")?.hierarchy_tree()
+        );
+
+        let mut options = GroupConsolidateMetadataOptions::default();
+        options.set_only_if_stale(true);
+        assert!(!group.consolidate_and_store_opt(&options)?);
+
+        // The consolidated metadata becomes stale when the hierarchy changes
+        array_builder.build(store.clone(), "/c")?.store_metadata()?;
+        assert!(group.consolidated_metadata_is_stale(&options)?);
+        assert!(group.consolidate_and_store_opt(&options)?);
+        assert_eq!(Node::open_consolidated(&store, "/")?.children().len(), 3);
+
+        // Skipped nodes and their descendants are excluded
+        options.set_skip_nodes(vec!["/a".try_into()?]);
+        assert!(group.consolidate_and_store_opt(&options)?);
+        let GroupMetadata::V3(metadata) = Group::open(store, "/")?.metadata().clone() else {
+            panic!()
+        };
+        assert_eq!(
+            metadata
+                .consolidated_metadata()?
+                .unwrap()
+                .metadata
+                .keys()
+                .collect::<Vec<_>>(),
+            vec!["b", "c"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn group_consolidate_and_store_v2() -> Result<(), Box<dyn std::error::Error>> {
+        let store = std::sync::Arc::new(MemoryStore::new());
+        let zarray = br#"{"zarr_format": 2, "shape": [4], "chunks": [2], "dtype": "<u2", "compressor": null, "fill_value": 0, "order": "C", "filters": null}"#;
+        store.set(
+            &".zgroup".try_into()?,
+            br#"{"zarr_format": 2}"#.to_vec().into(),
+        )?;
+        store.set(
+            &"a/.zgroup".try_into()?,
+            br#"{"zarr_format": 2}"#.to_vec().into(),
+        )?;
+        store.set(
+            &"a/.zattrs".try_into()?,
+            br#"{"spam": "ham"}"#.to_vec().into(),
+        )?;
+        store.set(&"a/array/.zarray".try_into()?, zarray.to_vec().into())?;
+        store.set(&"b/.zarray".try_into()?, zarray.to_vec().into())?;
+
+        let mut group = Group::open(store.clone(), "/")?;
+        let options = GroupConsolidateMetadataOptions::default();
+        assert!(group.consolidated_metadata_is_stale(&options)?);
+        assert!(group.consolidate_and_store()?);
+        assert!(!group.consolidated_metadata_is_stale(&options)?);
+        let consolidated_metadata: ConsolidatedMetadataV2 =
+            serde_json::from_slice(&store.get(&".zmetadata".try_into()?)?.unwrap())?;
+        assert_eq!(
+            consolidated_metadata.metadata.keys().collect::<Vec<_>>(),
+            vec![
+                ".zgroup",
+                "a/.zattrs",
+                "a/.zgroup",
+                "a/array/.zarray",
+                "b/.zarray"
+            ]
+        );
+
+        // The hierarchy is opened from .zmetadata
+        let tree = Node::open(&store, "/")?.hierarchy_tree();
+        store.erase(&"a/array/.zarray".try_into()?)?;
+        let node = Node::open_consolidated(&store, "/")?;
+        assert_eq!(node.hierarchy_tree(), tree);
+        assert_eq!(
+            node.children()[0].metadata(),
+            Node::open(&store, "/a")?.metadata()
+        );
+        assert!(group.consolidated_metadata_is_stale(&options)?);
+
+        let mut options = GroupConsolidateMetadataOptions::default();
+        options
+            .set_only_if_stale(true)
+            .set_skip_nodes(vec!["/b".try_into()?]);
+        assert!(group.consolidate_and_store_opt(&options)?);
+        assert!(!group.consolidate_and_store_opt(&options)?);
+        assert_eq!(
+            Node::open_consolidated(&store, "/")?.hierarchy_tree(),
+            "/\n  a\n"
+        );
+        Ok(())
+    }
+
     #[test]
     fn group_default() {
         let store = std::sync::Arc::new(MemoryStore::new());
@@ -0,0 +1,50 @@
+use crate::node::NodePath;
+
+/// Options for consolidating group metadata.
+///
+/// Used with [`Group::consolidate_and_store_opt`](crate::group::Group::consolidate_and_store_opt) and its async variant.
+#[derive(Debug, Clone, Default)]
+pub struct GroupConsolidateMetadataOptions {
+    skip_nodes: Vec<NodePath>,
+    only_if_stale: bool,
+}
+
+impl GroupConsolidateMetadataOptions {
+    /// Get the paths of nodes excluded from consolidated metadata.
+    #[must_use]
+    pub fn skip_nodes(&self) -> &[NodePath] {
+        &self.skip_nodes
+    }
+
+    /// Set the paths of nodes excluded from consolidated metadata.
+    ///
+    /// The descendants of skipped nodes are also excluded.
+    pub fn set_skip_nodes(&mut self, skip_nodes: Vec<NodePath>) -> &mut Self {
+        self.skip_nodes = skip_nodes;
+        self
+    }
+
+    /// Get the only if stale configuration. Defaults to false.
+    #[must_use]
+    pub fn only_if_stale(&self) -> bool {
+        self.only_if_stale
+    }
+
+    /// Set the only if stale configuration.
+    ///
+    /// If true, consolidated metadata is only stored if it differs from the consolidated metadata of the group.
+    pub fn set_only_if_stale(&mut self, only_if_stale: bool) -> &mut Self {
+        self.only_if_stale = only_if_stale;
+        self
+    }
+
+    /// Returns true if the node at `path` should be excluded from consolidated metadata.
+    pub(crate) fn is_skipped(&self, path: &str) -> bool {
+        self.skip_nodes.iter().any(|skip| {
+            path == skip.as_str()
+                || path
+                    .strip_prefix(skip.as_str())
+                    .is_some_and(|suffix| suffix.starts_with('/') || skip.as_str() == "/")
+        })
+    }
+}
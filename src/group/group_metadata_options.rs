@@ -1,9 +1,10 @@
-use crate::metadata::MetadataConvertVersion;
+use crate::metadata::{MetadataConvertVersion, MetadataJsonFormat};
 
 /// Options for writing group metadata.
 #[derive(Debug, Clone, Default)]
 pub struct GroupMetadataOptions {
     convert_version: MetadataConvertVersion,
+    json_format: MetadataJsonFormat,
 }
 
 impl GroupMetadataOptions {
@@ -21,4 +22,16 @@ impl GroupMetadataOptions {
         self.convert_version = convert_version;
         self
     }
+
+    /// Get the [metadata JSON format](crate::config::Config#metadata-json-format) configuration.
+    #[must_use]
+    pub fn metadata_json_format(&self) -> &MetadataJsonFormat {
+        &self.json_format
+    }
+
+    /// Set the [metadata JSON format](crate::config::Config#metadata-json-format) configuration.
+    pub fn set_metadata_json_format(&mut self, json_format: MetadataJsonFormat) -> &mut Self {
+        self.json_format = json_format;
+        self
+    }
 }
@@ -163,7 +163,28 @@
 //!    - The async API is runtime-agnostic. This has some limitations that are detailed in the [`Array`](crate::array::Array) docs.
 //!    - The async API is not as performant as the sync API.
 //!  - Codecs: `bitround`, `bz2`, `pcodec`, `zfp`, `zstd`.
-//!  - Stores: `http`, `object_store`, `opendal`, `zip`.
+//!  - Stores: `fetch` (`wasm32` only), `http`, `io_uring` (Linux only), `kerchunk`, `mmap`, `object_store`, `opendal`, `zip`.
+//!  - Storage transformers: `content_digest`.
+//!  - `arrow`: [Apache Arrow](array::arrow) interoperability.
+//!  - `dlpack`: [`DLPack`](array::dlpack) export of retrieved data.
+//!  - `image`: [`image`](array::image) crate integration for 2-D slices.
+//!  - `nalgebra`: [`nalgebra`](array::nalgebra) matrix integration for 2-D subsets.
+//!  - `n5`: [N5](n5) dataset reading and writing.
+//!  - `npy`: [`NumPy`](array::npy) `.npy`/`.npz` import and export.
+//!  - `ome`: [OME-NGFF](ome) metadata types and [`Group`](group::Group) attribute methods.
+//!  - `tiff`: [TIFF/OME-TIFF ingestion](tiff) (enables `ome`).
+//!  - `xarray`: [xarray conventions](xarray) (`_ARRAY_DIMENSIONS` and coordinates).
+//!  - `tracing`: [`tracing`](https://docs.rs/tracing) spans for array, codec, and storage operations with array paths, chunk indices, and byte counts.
+//!  - `metrics`: [operational metrics](metrics) (chunks read/written, encoded/decoded bytes, chunk cache hits/misses, and store latencies) with a Prometheus exporter.
+//!  - `store_test_suite`: a [conformance test suite](storage::store_test_suite) for store implementations.
+//!  - `codec_test_suite`: a [conformance test suite](array::codec::codec_test_suite) for codec implementations.
+//!  - `arbitrary`: [`arbitrary::Arbitrary`](https://docs.rs/arbitrary) implementations for array metadata, codec configurations, array subsets, and fill values for fuzzing and property testing.
+//!  - `derive`: a [`ZarrsElement`](array::ZarrsElement) derive macro for storing plain-old-data structs as array elements.
+//!
+//! #### WebAssembly
+//! `zarrs` supports the `wasm32-unknown-unknown` target with `default-features = false`, as some default codecs depend on C libraries.
+//! Operations that would run in parallel with [`rayon`] run on the current thread.
+//! The `fetch` feature enables `FetchStore`, an async store for browsers and web workers that retrieves values with the Fetch API.
 //!
 //! ## `zarrs` Ecosystem
 //! - [zarrs_tools](https://github.com/LDeakin/zarrs_tools): Various tools for creating and manipulating Zarr V3 data.
@@ -185,16 +206,28 @@
 #![deny(clippy::missing_panics_doc)]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
 pub mod array;
 pub mod array_subset;
 pub mod byte_range;
 pub mod config;
 pub mod group;
 pub mod metadata;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "n5")]
+pub mod n5;
 pub mod node;
+#[cfg(feature = "ome")]
+pub mod ome;
 pub mod plugin;
 pub mod storage;
+#[cfg(feature = "tiff")]
+pub mod tiff;
 pub mod version;
+#[cfg(feature = "xarray")]
+pub mod xarray;
 
 /// Get a mutable slice of the spare capacity in a vector.
 unsafe fn vec_spare_capacity_to_mut_slice<T>(vec: &mut Vec<T>) -> &mut [T] {
@@ -6,6 +6,7 @@
 
 mod array;
 mod group;
+mod validation;
 
 /// Zarr V3 metadata.
 pub mod v3;
@@ -15,10 +16,15 @@ pub mod v2;
 
 pub use array::{array_metadata_v2_to_v3, ArrayMetadata, ArrayMetadataV2ToV3ConversionError};
 pub use group::{group_metadata_v2_to_v3, GroupMetadata};
-pub use v2::{ArrayMetadataV2, GroupMetadataV2, MetadataV2};
+pub use v2::{ArrayMetadataV2, ConsolidatedMetadataV2, GroupMetadataV2, MetadataV2};
 pub use v3::{
-    AdditionalFields, ArrayMetadataV3, ConfigurationInvalidError, GroupMetadataV3, MetadataV3,
-    UnsupportedAdditionalFieldError,
+    AdditionalField, AdditionalFields, ArrayMetadataV3, ConfigurationInvalidError,
+    ConsolidatedMetadata, ConsolidatedMetadataKind, GroupMetadataV3, MetadataV3,
+    SupportedAdditionalField, UnsupportedAdditionalFieldError,
+};
+pub use validation::{
+    validate_array_metadata, validate_group_metadata, MetadataDiagnostic,
+    MetadataDiagnosticSeverity,
 };
 
 use crate::config::global_config;
@@ -74,9 +80,202 @@ impl Default for MetadataEraseVersion {
     }
 }
 
+/// The handling of groups without metadata (implicit groups).
+///
+/// Zarr V3 originally permitted implicit groups, but they were later removed from the specification, and implementations disagree on whether a node without metadata is a group.
+/// Zarr V2 groups always require metadata, irrespective of this policy.
+///
+/// Defaults to the [global](crate::config::Config#implicit-group-policy) implicit group policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImplicitGroupPolicy {
+    /// A node without metadata is opened as an implicit group.
+    Allow,
+    /// Opening a node without metadata fails, prefixes without metadata are not listed as child nodes, and creating a child node of a group without metadata fails.
+    Error,
+    /// A node without metadata is opened as an implicit group, and the metadata of implicit groups is stored when creating a child node.
+    ///
+    /// See [`Group::create_group`](crate::group::Group::create_group), [`Group::create_array`](crate::group::Group::create_array), and [`Array::store_metadata`](crate::array::Array::store_metadata).
+    CreateOnWrite,
+}
+
+impl Default for ImplicitGroupPolicy {
+    fn default() -> Self {
+        *global_config().implicit_group_policy()
+    }
+}
+
+/// The validation of array metadata when creating or opening an array.
+///
+/// Real-world datasets (particularly Zarr V2 datasets) are frequently slightly malformed, such as having a fill value that is out of range of the data type.
+///
+/// Defaults to the [global](crate::config::Config#metadata-validation-mode) metadata validation mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataValidationMode {
+    /// Malformed metadata is rejected.
+    Strict,
+    /// Malformed metadata is coerced where possible, and a warning is recorded for each coercion.
+    ///
+    /// The warnings are accessible with [`Array::metadata_diagnostics`](crate::array::Array::metadata_diagnostics).
+    /// A fill value incompatible with the data type is converted with saturation if it is numeric, otherwise it is replaced by zero (or an empty value for a variable size data type).
+    /// Dimension names with an incorrect length are dropped.
+    Lenient,
+}
+
+impl Default for MetadataValidationMode {
+    fn default() -> Self {
+        *global_config().metadata_validation_mode()
+    }
+}
+
+/// JSON formatting options for metadata documents (e.g. `zarr.json`).
+///
+/// Used with [`ArrayMetadataOptions`](crate::array::ArrayMetadataOptions) and [`GroupMetadataOptions`](crate::group::GroupMetadataOptions).
+/// Defaults to the [global](crate::config::Config#metadata-json-format) metadata JSON format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetadataJsonFormat {
+    indent: Option<usize>,
+    sort_keys: bool,
+}
+
+impl Default for MetadataJsonFormat {
+    fn default() -> Self {
+        *global_config().metadata_json_format()
+    }
+}
+
+impl MetadataJsonFormat {
+    /// Create a pretty metadata JSON format, indented with two spaces and with keys in their default order.
+    #[must_use]
+    pub const fn pretty() -> Self {
+        Self {
+            indent: Some(2),
+            sort_keys: false,
+        }
+    }
+
+    /// Create a compact metadata JSON format, without whitespace and with keys in their default order.
+    #[must_use]
+    pub const fn compact() -> Self {
+        Self {
+            indent: None,
+            sort_keys: false,
+        }
+    }
+
+    /// Return the number of spaces per indentation level, or [`None`] if the output is compact.
+    #[must_use]
+    pub const fn indent(&self) -> Option<usize> {
+        self.indent
+    }
+
+    /// Set the number of spaces per indentation level, or [`None`] for compact output.
+    pub fn set_indent(&mut self, indent: Option<usize>) -> &mut Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Return true if object keys are sorted.
+    #[must_use]
+    pub const fn sort_keys(&self) -> bool {
+        self.sort_keys
+    }
+
+    /// Set whether object keys are sorted.
+    ///
+    /// If false, fields are written in the order defined by the specification and attributes are written in insertion order.
+    /// If true, the keys of all objects (including nested attributes) are sorted lexicographically, so the output is stable regardless of how the metadata was created.
+    pub fn set_sort_keys(&mut self, sort_keys: bool) -> &mut Self {
+        self.sort_keys = sort_keys;
+        self
+    }
+
+    /// Serialise `value` to JSON bytes with this format.
+    ///
+    /// # Errors
+    /// Returns a [`serde_json::Error`] if `value` cannot be serialised.
+    pub fn serialize<T: serde::Serialize + ?Sized>(
+        &self,
+        value: &T,
+    ) -> Result<Vec<u8>, serde_json::Error> {
+        fn sort_keys(value: serde_json::Value) -> serde_json::Value {
+            match value {
+                serde_json::Value::Object(object) => {
+                    let mut entries: Vec<_> = object.into_iter().collect();
+                    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| (key, sort_keys(value)))
+                        .collect::<serde_json::Map<_, _>>()
+                        .into()
+                }
+                serde_json::Value::Array(array) => {
+                    array.into_iter().map(sort_keys).collect::<Vec<_>>().into()
+                }
+                value => value,
+            }
+        }
+
+        if self.sort_keys {
+            let value = sort_keys(serde_json::to_value(value)?);
+            self.serialize_unsorted(&value)
+        } else {
+            self.serialize_unsorted(value)
+        }
+    }
+
+    fn serialize_unsorted<T: serde::Serialize + ?Sized>(
+        &self,
+        value: &T,
+    ) -> Result<Vec<u8>, serde_json::Error> {
+        if let Some(indent) = self.indent {
+            let indent = vec![b' '; indent];
+            let formatter = serde_json::ser::PrettyFormatter::with_indent(&indent);
+            let mut json = Vec::with_capacity(128);
+            let mut serializer = serde_json::Serializer::with_formatter(&mut json, formatter);
+            value.serialize(&mut serializer)?;
+            Ok(json)
+        } else {
+            serde_json::to_vec(value)
+        }
+    }
+}
+
+/// Apply an [RFC 7386](https://www.rfc-editor.org/rfc/rfc7386) JSON merge patch to `attributes`.
+///
+/// Members of `patch` with a `null` value are removed, objects are merged recursively, and any other value replaces the existing value.
+///
+/// Used by the `update_attributes` methods of [`Array`](crate::array::Array::update_attributes) and [`Group`](crate::group::Group::update_attributes).
+pub fn attributes_merge_patch(
+    attributes: &mut serde_json::Map<String, serde_json::Value>,
+    patch: &serde_json::Map<String, serde_json::Value>,
+) {
+    for (key, value) in patch {
+        match value {
+            serde_json::Value::Null => {
+                attributes.remove(key);
+            }
+            serde_json::Value::Object(patch) => {
+                let target = attributes
+                    .entry(key.clone())
+                    .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+                if !target.is_object() {
+                    *target = serde_json::Value::Object(serde_json::Map::new());
+                }
+                if let serde_json::Value::Object(target) = target {
+                    attributes_merge_patch(target, patch);
+                }
+            }
+            value => {
+                attributes.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::global_config_mut;
     use v3::{AdditionalFields, MetadataV3};
 
     #[test]
@@ -145,4 +344,101 @@ mod tests {
         let additional_fields = serde_json::from_str::<AdditionalFields>(json);
         assert!(additional_fields.is_err());
     }
+
+    #[test]
+    fn metadata_json_format() {
+        let metadata: GroupMetadataV3 = serde_json::from_str(
+            r#"{"zarr_format": 3, "node_type": "group", "attributes": {"b": [{"d": 1, "c": 2}], "a": 3}}"#,
+        )
+        .unwrap();
+        let to_string = |format: &MetadataJsonFormat| {
+            String::from_utf8(format.serialize(&metadata).unwrap()).unwrap()
+        };
+        assert_eq!(
+            to_string(&MetadataJsonFormat::compact()),
+            r#"{"zarr_format":3,"node_type":"group","attributes":{"b":[{"d":1,"c":2}],"a":3}}"#
+        );
+        assert_eq!(
+            to_string(MetadataJsonFormat::compact().set_sort_keys(true)),
+            r#"{"attributes":{"a":3,"b":[{"c":2,"d":1}]},"node_type":"group","zarr_format":3}"#
+        );
+        assert_eq!(
+            to_string(&MetadataJsonFormat::pretty()),
+            serde_json::to_string_pretty(&metadata).unwrap()
+        );
+        assert!(to_string(MetadataJsonFormat::pretty().set_indent(Some(4)))
+            .contains("\n    \"node_type\": \"group\""));
+    }
+
+    #[test]
+    fn attributes_merge_patch_rfc7386() {
+        // Examples from RFC 7386 Appendix A with object targets and patches
+        for (target, patch, result) in [
+            (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"a":null}"#, "{}"),
+            (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+            (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+            (
+                r#"{"a":{"b":"c"}}"#,
+                r#"{"a":{"b":"d","c":null}}"#,
+                r#"{"a":{"b":"d"}}"#,
+            ),
+            (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+            (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+            ("{}", r#"{"a":{"bb":{"ccc":null}}}"#, r#"{"a":{"bb":{}}}"#),
+        ] {
+            let mut attributes: serde_json::Map<String, serde_json::Value> =
+                serde_json::from_str(target).unwrap();
+            attributes_merge_patch(&mut attributes, &serde_json::from_str(patch).unwrap());
+            assert_eq!(
+                attributes,
+                serde_json::from_str::<serde_json::Map<_, _>>(result).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn additional_fields_supported() {
+        #[derive(serde::Deserialize)]
+        struct SupportedExtension {
+            #[allow(dead_code)]
+            version: u32,
+        }
+        global_config_mut()
+            .supported_additional_fields_mut()
+            .insert(
+                "supported_extension".to_string(),
+                SupportedAdditionalField::new_with_type::<SupportedExtension>(),
+            );
+        let metadata = |extension: &str| {
+            format!(
+                r#"{{"zarr_format": 3, "node_type": "group", "supported_extension": {extension}}}"#
+            )
+        };
+
+        let metadata_valid = metadata(r#"{"must_understand": true, "version": 1}"#);
+        let group_metadata = serde_json::from_str::<GroupMetadataV3>(&metadata_valid).unwrap();
+        let field = &group_metadata.additional_fields["supported_extension"];
+        assert!(field.must_understand());
+        assert_eq!(field.as_map()["version"], 1);
+        assert!(
+            serde_json::from_str::<GroupMetadataV3>(&metadata(r#"{"version": 1}"#))
+                .unwrap()
+                .additional_fields["supported_extension"]
+                .must_understand()
+        );
+        assert!(serde_json::from_str::<GroupMetadataV3>(&metadata(r#"{"version": "1"}"#)).is_err());
+        assert!(serde_json::from_str::<GroupMetadataV3>(
+            &metadata_valid.replace("supported_extension", "unsupported_extension")
+        )
+        .unwrap_err()
+        .to_string()
+        .starts_with("unsupported additional field unsupported_extension"));
+        assert!(crate::metadata::validate_group_metadata(
+            &serde_json::from_str(&metadata_valid).unwrap()
+        )
+        .is_empty());
+    }
 }
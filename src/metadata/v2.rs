@@ -21,7 +21,7 @@ pub mod codec {
 }
 
 pub use array::ArrayMetadataV2;
-pub use group::GroupMetadataV2;
+pub use group::{ConsolidatedMetadataV2, GroupMetadataV2};
 
 mod metadata;
 pub use metadata::MetadataV2;
@@ -1,7 +1,14 @@
+use std::collections::BTreeMap;
+
 use derive_more::{Display, From};
 use serde::{Deserialize, Serialize};
 
-use crate::metadata::AdditionalFields;
+use crate::{
+    metadata::{AdditionalFields, ArrayMetadata, GroupMetadata},
+    node::NodeMetadata,
+};
+
+use super::ArrayMetadataV2;
 
 /// Zarr V2 group metadata.
 #[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Debug, Display, From)]
@@ -18,3 +25,121 @@ pub struct GroupMetadataV2 {
     #[serde(default, flatten)]
     pub additional_fields: AdditionalFields,
 }
+
+/// Zarr V2 consolidated metadata, stored in a `.zmetadata` document alongside the `.zgroup` of a group.
+///
+/// Holds the metadata documents of a group and every node below it, so that a hierarchy can be opened without reading each document.
+/// This is not part of the Zarr V2 specification, but is a convention of `zarr-python` 2.x.
+///
+/// An example `.zmetadata` document:
+/// ```json
+/// {
+///     "metadata": {
+///         ".zgroup": { "zarr_format": 2 },
+///         "array/.zarray": { "zarr_format": 2, "shape": [10], ... },
+///         "array/.zattrs": { "spam": "ham" }
+///     },
+///     "zarr_consolidated_format": 1
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ConsolidatedMetadataV2 {
+    /// Metadata documents, keyed by store key relative to the group.
+    pub metadata: BTreeMap<String, serde_json::Value>,
+    /// The consolidated metadata format. Must be `1`.
+    pub zarr_consolidated_format: monostate::MustBe!(1u64),
+}
+
+impl ConsolidatedMetadataV2 {
+    /// Create consolidated metadata from metadata documents keyed by store key relative to the group.
+    #[must_use]
+    pub fn new(metadata: BTreeMap<String, serde_json::Value>) -> Self {
+        Self {
+            metadata,
+            zarr_consolidated_format: monostate::MustBe!(1u64),
+        }
+    }
+
+    /// Add the metadata documents of a node at `relative` path to the group (without a leading `/`, empty for the group itself).
+    ///
+    /// Zarr V3 metadata is ignored.
+    ///
+    /// # Errors
+    /// Returns a [`serde_json::Error`] if the metadata cannot be serialised.
+    pub fn add_node(
+        &mut self,
+        relative: &str,
+        metadata: &NodeMetadata,
+    ) -> Result<(), serde_json::Error> {
+        let key = |name: &str| {
+            if relative.is_empty() {
+                name.to_string()
+            } else {
+                format!("{relative}/{name}")
+            }
+        };
+        let attributes = match metadata {
+            NodeMetadata::Array(ArrayMetadata::V2(metadata)) => {
+                let mut metadata = metadata.clone();
+                let attributes = std::mem::take(&mut metadata.attributes);
+                let mut metadata = serde_json::to_value(metadata)?;
+                if let serde_json::Value::Object(metadata) = &mut metadata {
+                    // The node type is implied by the .zarray key
+                    metadata.remove("node_type");
+                }
+                self.metadata.insert(key(".zarray"), metadata);
+                attributes
+            }
+            NodeMetadata::Group(GroupMetadata::V2(metadata)) => {
+                let mut metadata = metadata.clone();
+                let attributes = std::mem::take(&mut metadata.attributes);
+                self.metadata
+                    .insert(key(".zgroup"), serde_json::to_value(metadata)?);
+                attributes
+            }
+            NodeMetadata::Array(ArrayMetadata::V3(_))
+            | NodeMetadata::Group(GroupMetadata::V3(_)) => return Ok(()),
+        };
+        if !attributes.is_empty() {
+            self.metadata
+                .insert(key(".zattrs"), serde_json::Value::Object(attributes));
+        }
+        Ok(())
+    }
+
+    /// Return the metadata of each node, keyed by node path relative to the group (without a leading `/`, empty for the group itself).
+    ///
+    /// # Errors
+    /// Returns a [`serde_json::Error`] if any metadata is invalid.
+    pub fn node_metadata(&self) -> Result<BTreeMap<String, NodeMetadata>, serde_json::Error> {
+        let mut node_metadata = BTreeMap::new();
+        for (key, value) in &self.metadata {
+            let (relative, name) = key.rsplit_once('/').unwrap_or(("", key));
+            let attributes = self
+                .metadata
+                .get(&if relative.is_empty() {
+                    ".zattrs".to_string()
+                } else {
+                    format!("{relative}/.zattrs")
+                })
+                .map(|attributes| serde_json::from_value(attributes.clone()))
+                .transpose()?
+                .unwrap_or_default();
+            let metadata = match name {
+                ".zarray" => {
+                    let mut metadata: ArrayMetadataV2 = serde_json::from_value(value.clone())?;
+                    metadata.attributes = attributes;
+                    NodeMetadata::Array(ArrayMetadata::V2(metadata))
+                }
+                ".zgroup" => {
+                    let mut metadata: GroupMetadataV2 = serde_json::from_value(value.clone())?;
+                    metadata.attributes = attributes;
+                    NodeMetadata::Group(GroupMetadata::V2(metadata))
+                }
+                _ => continue,
+            };
+            node_metadata.insert(relative.to_string(), metadata);
+        }
+        Ok(node_metadata)
+    }
+}
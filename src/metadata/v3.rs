@@ -5,6 +5,18 @@ pub mod group;
 pub mod array;
 
 /// Zarr V3 codec metadata.
+///
+/// Each codec module defines the codec `IDENTIFIER` and serde configuration types with constructors.
+/// Codec metadata can be created without a codec with [`MetadataV3::new_with_serializable_configuration`], e.g.
+/// ```rust
+/// # use zarrs::metadata::v3::MetadataV3;
+/// use zarrs::metadata::v3::codec::gzip::{self, GzipCodecConfigurationV1, GzipCompressionLevel};
+///
+/// let configuration = GzipCodecConfigurationV1::new(GzipCompressionLevel::try_from(5)?);
+/// let metadata = MetadataV3::new_with_serializable_configuration(gzip::IDENTIFIER, &configuration)?;
+/// assert_eq!(metadata.to_string(), r#"gzip {"level":5}"#);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
 pub mod codec {
     /// `bitround` codec metadata.
     pub mod bitround;
@@ -23,8 +35,12 @@ pub mod codec {
     pub mod gzip;
     /// `pcodec` codec metadata.
     pub mod pcodec;
+    /// `rle` codec metadata.
+    pub mod rle;
     /// `sharding` codec metadata.
     pub mod sharding;
+    /// `sparse` codec metadata.
+    pub mod sparse;
     /// `transpose` codec metadata.
     pub mod transpose;
     /// `vlen` codec metadata.
@@ -38,6 +54,8 @@ pub mod codec {
 }
 
 /// Zarr V3 chunk grid metadata.
+///
+/// Each chunk grid module defines the chunk grid `IDENTIFIER` and a serde configuration type with a constructor.
 pub mod chunk_grid {
     /// `rectangular` chunk grid metadata.
     pub mod rectangular;
@@ -46,6 +64,8 @@ pub mod chunk_grid {
 }
 
 /// Zarr V3 chunk key encoding metadata.
+///
+/// Each chunk key encoding module defines the chunk key encoding `IDENTIFIER` and a serde configuration type with a constructor.
 pub mod chunk_key_encoding {
     /// `default` chunk key encoding metadata.
     pub mod default;
@@ -56,11 +76,13 @@ pub mod chunk_key_encoding {
 pub mod fill_value;
 
 pub use array::ArrayMetadataV3;
-pub use group::GroupMetadataV3;
+pub use group::{ConsolidatedMetadata, ConsolidatedMetadataKind, GroupMetadataV3};
 
 pub use crate::array::ChunkKeySeparator;
 
 mod metadata;
+pub(crate) use metadata::deserialize_additional_fields;
 pub use metadata::{
-    AdditionalFields, ConfigurationInvalidError, MetadataV3, UnsupportedAdditionalFieldError,
+    AdditionalField, AdditionalFields, ConfigurationInvalidError, MetadataV3,
+    SupportedAdditionalField, UnsupportedAdditionalFieldError,
 };
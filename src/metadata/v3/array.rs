@@ -1,7 +1,7 @@
 use derive_more::Display;
 use serde::{Deserialize, Serialize};
 
-use super::{AdditionalFields, MetadataV3};
+use super::{deserialize_additional_fields, AdditionalFields, MetadataV3};
 use crate::array::{ArrayShape, DimensionName, FillValueMetadata};
 
 /// Zarr array metadata (storage specification v3).
@@ -91,7 +91,7 @@ pub struct ArrayMetadataV3 {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub dimension_names: Option<Vec<DimensionName>>,
     /// Additional fields.
-    #[serde(flatten)]
+    #[serde(flatten, deserialize_with = "deserialize_additional_fields")]
     pub additional_fields: AdditionalFields,
 }
 
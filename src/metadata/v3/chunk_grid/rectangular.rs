@@ -17,6 +17,14 @@ pub struct RectangularChunkGridConfiguration {
     pub chunk_shape: Vec<RectangularChunkGridDimensionConfiguration>,
 }
 
+impl RectangularChunkGridConfiguration {
+    /// Create a new `rectangular` chunk grid configuration with a fixed or varying chunk size for each dimension.
+    #[must_use]
+    pub const fn new(chunk_shape: Vec<RectangularChunkGridDimensionConfiguration>) -> Self {
+        Self { chunk_shape }
+    }
+}
+
 /// A chunk element in the `chunk_shape` field of `rectangular` chunk grid netadata.
 #[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Debug, From)]
 #[serde(untagged)]
@@ -20,6 +20,14 @@ pub struct RegularChunkGridConfiguration {
     pub chunk_shape: ChunkShape,
 }
 
+impl RegularChunkGridConfiguration {
+    /// Create a new `regular` chunk grid configuration with a `chunk_shape`.
+    #[must_use]
+    pub const fn new(chunk_shape: ChunkShape) -> Self {
+        Self { chunk_shape }
+    }
+}
+
 macro_rules! from_chunkgrid_regular_configuration {
     ( $t:ty ) => {
         impl From<$t> for RegularChunkGridConfiguration {
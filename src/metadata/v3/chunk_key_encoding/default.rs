@@ -20,3 +20,18 @@ pub struct DefaultChunkKeyEncodingConfiguration {
 const fn default_separator() -> ChunkKeySeparator {
     ChunkKeySeparator::Slash
 }
+
+impl DefaultChunkKeyEncodingConfiguration {
+    /// Create a new `default` chunk key encoding configuration with a chunk key `separator`.
+    #[must_use]
+    pub const fn new(separator: ChunkKeySeparator) -> Self {
+        Self { separator }
+    }
+}
+
+impl Default for DefaultChunkKeyEncodingConfiguration {
+    /// Create a `default` chunk key encoding configuration with the default `/` separator.
+    fn default() -> Self {
+        Self::new(default_separator())
+    }
+}
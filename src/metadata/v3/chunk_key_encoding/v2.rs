@@ -20,3 +20,18 @@ pub struct V2ChunkKeyEncodingConfiguration {
 const fn v2_separator() -> ChunkKeySeparator {
     ChunkKeySeparator::Dot
 }
+
+impl V2ChunkKeyEncodingConfiguration {
+    /// Create a new `v2` chunk key encoding configuration with a chunk key `separator`.
+    #[must_use]
+    pub const fn new(separator: ChunkKeySeparator) -> Self {
+        Self { separator }
+    }
+}
+
+impl Default for V2ChunkKeyEncodingConfiguration {
+    /// Create a `v2` chunk key encoding configuration with the default `.` separator.
+    fn default() -> Self {
+        Self::new(v2_separator())
+    }
+}
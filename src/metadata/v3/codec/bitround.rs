@@ -32,6 +32,14 @@ pub struct BitroundCodecConfigurationV1 {
     pub keepbits: u32,
 }
 
+impl BitroundCodecConfigurationV1 {
+    /// Create a new `bitround` codec configuration keeping `keepbits` mantissa bits.
+    #[must_use]
+    pub const fn new(keepbits: u32) -> Self {
+        Self { keepbits }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::metadata::v3::MetadataV3;
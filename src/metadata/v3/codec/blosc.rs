@@ -118,6 +118,43 @@ pub struct BloscCodecConfigurationV1 {
     pub blocksize: usize,
 }
 
+impl BloscCodecConfigurationV1 {
+    /// Create a new `blosc` codec configuration with a compressor and compression level.
+    ///
+    /// The shuffle mode defaults to [`BloscShuffleMode::NoShuffle`] and the block size is automatically determined.
+    #[must_use]
+    pub const fn new(cname: BloscCompressor, clevel: BloscCompressionLevel) -> Self {
+        Self {
+            cname,
+            clevel,
+            shuffle: BloscShuffleMode::NoShuffle,
+            typesize: None,
+            blocksize: 0,
+        }
+    }
+
+    /// Set the shuffle mode and the type size in bytes.
+    ///
+    /// The type size is required unless the shuffle mode is [`BloscShuffleMode::NoShuffle`].
+    #[must_use]
+    pub const fn with_shuffle(
+        mut self,
+        shuffle: BloscShuffleMode,
+        typesize: Option<usize>,
+    ) -> Self {
+        self.shuffle = shuffle;
+        self.typesize = typesize;
+        self
+    }
+
+    /// Set the compression block size. Automatically determined if 0.
+    #[must_use]
+    pub const fn with_blocksize(mut self, blocksize: usize) -> Self {
+        self.blocksize = blocksize;
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -137,6 +174,22 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn codec_blosc_configuration_new() {
+        let configuration = BloscCodecConfigurationV1::new(
+            BloscCompressor::LZ4,
+            BloscCompressionLevel::try_from(5).unwrap(),
+        )
+        .with_shuffle(BloscShuffleMode::Shuffle, Some(4));
+        assert_eq!(
+            serde_json::from_str::<BloscCodecConfigurationV1>(
+                r#"{"cname": "lz4", "clevel": 5, "shuffle": "shuffle", "typesize": 4, "blocksize": 0}"#
+            )
+            .unwrap(),
+            configuration
+        );
+    }
+
     #[test]
     fn codec_blosc_valid2() {
         serde_json::from_str::<BloscCodecConfiguration>(
@@ -15,11 +15,19 @@ pub enum Crc32cCodecConfiguration {
 /// `crc32c` (CRC32C checksum) codec configuration parameters (version 1.0).
 ///
 /// See <https://zarr-specs.readthedocs.io/en/latest/v3/codecs/crc32c/v1.0.html#configuration-parameters>.
-#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Debug, Display)]
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Debug, Display, Default)]
 #[serde(deny_unknown_fields)]
 #[display("{}", serde_json::to_string(self).unwrap_or_default())]
 pub struct Crc32cCodecConfigurationV1 {}
 
+impl Crc32cCodecConfigurationV1 {
+    /// Create a new `crc32c` codec configuration.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::metadata::v3::MetadataV3;
@@ -0,0 +1,47 @@
+use derive_more::{Display, From};
+use serde::{Deserialize, Serialize};
+
+/// The identifier for the `rle` codec.
+pub const IDENTIFIER: &str = "rle";
+
+/// A wrapper to handle various versions of `rle` codec configuration parameters.
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Debug, Display, From)]
+#[serde(untagged)]
+pub enum RleCodecConfiguration {
+    /// Version 1.0.
+    V1(RleCodecConfigurationV1),
+}
+
+/// Configuration parameters for the `rle` codec (version 1.0).
+///
+/// ### Example: encode with the `rle` codec
+/// ```rust
+/// # let JSON = r#"
+/// {}
+/// # "#;
+/// # use zarrs::metadata::v3::codec::rle::RleCodecConfigurationV1;
+/// # let configuration: RleCodecConfigurationV1 = serde_json::from_str(JSON).unwrap();
+/// ```
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Debug, Display, Default)]
+#[serde(deny_unknown_fields)]
+#[display("{}", serde_json::to_string(self).unwrap_or_default())]
+pub struct RleCodecConfigurationV1 {}
+
+impl RleCodecConfigurationV1 {
+    /// Create a new `rle` codec configuration.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codec_rle() {
+        serde_json::from_str::<RleCodecConfiguration>(r#"{}"#).unwrap();
+        assert!(serde_json::from_str::<RleCodecConfiguration>(r#"{"unknown":0}"#).is_err());
+    }
+}
@@ -66,6 +66,26 @@ pub struct ShardingCodecConfigurationV1 {
     pub index_location: ShardingIndexLocation,
 }
 
+impl ShardingCodecConfigurationV1 {
+    /// Create a new `sharding_indexed` codec configuration.
+    ///
+    /// `codecs` and `index_codecs` are the metadata of the inner chunk and shard index codecs.
+    #[must_use]
+    pub fn new(
+        chunk_shape: ChunkShape,
+        codecs: Vec<MetadataV3>,
+        index_codecs: Vec<MetadataV3>,
+        index_location: ShardingIndexLocation,
+    ) -> Self {
+        Self {
+            chunk_shape,
+            codecs,
+            index_codecs,
+            index_location,
+        }
+    }
+}
+
 /// The sharding index location.
 #[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug, Display)]
 #[serde(rename_all = "lowercase")]
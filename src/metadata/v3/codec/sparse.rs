@@ -0,0 +1,47 @@
+use derive_more::{Display, From};
+use serde::{Deserialize, Serialize};
+
+/// The identifier for the `sparse` codec.
+pub const IDENTIFIER: &str = "sparse";
+
+/// A wrapper to handle various versions of `sparse` codec configuration parameters.
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Debug, Display, From)]
+#[serde(untagged)]
+pub enum SparseCodecConfiguration {
+    /// Version 1.0.
+    V1(SparseCodecConfigurationV1),
+}
+
+/// Configuration parameters for the `sparse` codec (version 1.0).
+///
+/// ### Example: encode with the `sparse` codec
+/// ```rust
+/// # let JSON = r#"
+/// {}
+/// # "#;
+/// # use zarrs::metadata::v3::codec::sparse::SparseCodecConfigurationV1;
+/// # let configuration: SparseCodecConfigurationV1 = serde_json::from_str(JSON).unwrap();
+/// ```
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Debug, Display, Default)]
+#[serde(deny_unknown_fields)]
+#[display("{}", serde_json::to_string(self).unwrap_or_default())]
+pub struct SparseCodecConfigurationV1 {}
+
+impl SparseCodecConfigurationV1 {
+    /// Create a new `sparse` codec configuration.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codec_sparse() {
+        serde_json::from_str::<SparseCodecConfiguration>(r#"{}"#).unwrap();
+        assert!(serde_json::from_str::<SparseCodecConfiguration>(r#"{"unknown":0}"#).is_err());
+    }
+}
@@ -93,6 +93,24 @@ pub struct ZfpCodecConfigurationV1 {
     pub mode: ZfpMode,
 }
 
+impl ZfpCodecConfigurationV1 {
+    /// Create a new `zfp` codec configuration with a [`ZfpMode`] and no headers.
+    #[must_use]
+    pub const fn new(mode: ZfpMode) -> Self {
+        Self {
+            write_header: None,
+            mode,
+        }
+    }
+
+    /// Set whether or not to write headers.
+    #[must_use]
+    pub const fn with_write_header(mut self, write_header: bool) -> Self {
+        self.write_header = Some(write_header);
+        self
+    }
+}
+
 /// The zfp mode.
 #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
 #[serde(tag = "mode", rename_all = "snake_case")]
@@ -1,7 +1,11 @@
+use std::collections::BTreeMap;
+
 use derive_more::Display;
 use serde::{Deserialize, Serialize};
 
-use super::AdditionalFields;
+use crate::node::NodeMetadata;
+
+use super::{deserialize_additional_fields, AdditionalFields};
 
 /// Zarr group metadata (storage specification v3).
 ///
@@ -28,7 +32,7 @@ pub struct GroupMetadataV3 {
     #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
     pub attributes: serde_json::Map<String, serde_json::Value>,
     /// Additional fields.
-    #[serde(flatten)]
+    #[serde(flatten, deserialize_with = "deserialize_additional_fields")]
     pub additional_fields: AdditionalFields,
 }
 
@@ -53,3 +57,88 @@ impl GroupMetadataV3 {
         }
     }
 }
+
+impl GroupMetadataV3 {
+    /// Return the [`ConsolidatedMetadata`] of the group, if present.
+    ///
+    /// Consolidated metadata is stored in the `consolidated_metadata` additional field, as written by `zarr-python`.
+    ///
+    /// # Errors
+    /// Returns a [`serde_json::Error`] if the consolidated metadata is invalid.
+    pub fn consolidated_metadata(&self) -> Result<Option<ConsolidatedMetadata>, serde_json::Error> {
+        let Some(field) = self.additional_fields.get(CONSOLIDATED_METADATA_FIELD) else {
+            return Ok(None);
+        };
+        let mut field = field.as_map().clone();
+        if let Some(serde_json::Value::Object(metadata)) = field.get_mut("metadata") {
+            for node_metadata in metadata.values_mut() {
+                // Child groups may have a null consolidated_metadata field
+                if let serde_json::Value::Object(node_metadata) = node_metadata {
+                    if node_metadata
+                        .get(CONSOLIDATED_METADATA_FIELD)
+                        .is_some_and(serde_json::Value::is_null)
+                    {
+                        node_metadata.remove(CONSOLIDATED_METADATA_FIELD);
+                    }
+                }
+            }
+        }
+        serde_json::from_value(serde_json::Value::Object(field)).map(Some)
+    }
+
+    /// Set the [`ConsolidatedMetadata`] of the group, or remove it if [`None`].
+    ///
+    /// # Errors
+    /// Returns a [`serde_json::Error`] if the consolidated metadata cannot be serialised.
+    pub fn set_consolidated_metadata(
+        &mut self,
+        consolidated_metadata: Option<ConsolidatedMetadata>,
+    ) -> Result<(), serde_json::Error> {
+        if let Some(consolidated_metadata) = consolidated_metadata {
+            let serde_json::Value::Object(field) = serde_json::to_value(consolidated_metadata)?
+            else {
+                unreachable!("consolidated metadata serialises to an object")
+            };
+            self.additional_fields
+                .insert(CONSOLIDATED_METADATA_FIELD.to_string(), field.into());
+        } else {
+            self.additional_fields.remove(CONSOLIDATED_METADATA_FIELD);
+        }
+        Ok(())
+    }
+}
+
+/// The name of the consolidated metadata additional field of Zarr V3 group metadata.
+const CONSOLIDATED_METADATA_FIELD: &str = "consolidated_metadata";
+
+/// The kind of [`ConsolidatedMetadata`].
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ConsolidatedMetadataKind {
+    /// The consolidated metadata is stored inline in the group metadata.
+    Inline,
+}
+
+/// Consolidated metadata of a Zarr V3 group.
+///
+/// Holds the metadata of every node below a group, so that a hierarchy can be opened from the group metadata alone.
+/// This is not (yet) part of the Zarr V3 specification, but follows the convention of `zarr-python`.
+///
+/// An example `consolidated_metadata` field of group metadata:
+/// ```json
+/// "consolidated_metadata": {
+///     "kind": "inline",
+///     "must_understand": false,
+///     "metadata": {
+///         "group": { "zarr_format": 3, "node_type": "group" },
+///         "group/array": { "zarr_format": 3, "node_type": "array", ... }
+///     }
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ConsolidatedMetadata {
+    /// The kind of consolidated metadata.
+    pub kind: ConsolidatedMetadataKind,
+    /// Node metadata, keyed by node path relative to the group (without a leading `/`).
+    pub metadata: BTreeMap<String, NodeMetadata>,
+}
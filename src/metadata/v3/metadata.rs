@@ -2,6 +2,8 @@ use derive_more::From;
 use serde::{de::DeserializeOwned, ser::SerializeMap, Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::config::global_config;
+
 /// Metadata with a name and optional configuration.
 ///
 /// Represents most fields in Zarr V3 array metadata (see [`ArrayMetadataV3`](crate::metadata::v3::ArrayMetadataV3)), which is structured as JSON with a name and optional configuration, or just a string representing the name.
@@ -244,15 +246,37 @@ impl UnsupportedAdditionalFieldError {
 
 /// An additional field in array or group metadata.
 ///
-/// Must be an object with a `"must_understand": false` field.
-#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Debug, Default, From)]
+/// Must be an object with a `"must_understand": false` field, unless it is a [supported additional field](crate::config::Config#supported-additional-fields).
+#[derive(Serialize, Clone, Eq, PartialEq, Debug, Default, From)]
 pub struct AdditionalField {
-    must_understand: monostate::MustBe!(false),
+    must_understand: bool,
     #[serde(flatten)]
     fields: serde_json::Map<String, serde_json::Value>,
 }
 
+impl<'de> Deserialize<'de> for AdditionalField {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct AdditionalFieldNotMustUnderstand {
+            #[allow(dead_code)]
+            must_understand: monostate::MustBe!(false),
+            #[serde(flatten)]
+            fields: serde_json::Map<String, serde_json::Value>,
+        }
+        let field = AdditionalFieldNotMustUnderstand::deserialize(d)?;
+        Ok(field.fields.into())
+    }
+}
+
 impl AdditionalField {
+    /// Returns true if the field is annotated with `"must_understand": true` or has no `must_understand` annotation.
+    ///
+    /// This is only possible for a [supported additional field](crate::config::Config#supported-additional-fields).
+    #[must_use]
+    pub const fn must_understand(&self) -> bool {
+        self.must_understand
+    }
+
     /// Return the underlying map.
     #[must_use]
     pub const fn as_map(&self) -> &serde_json::Map<String, serde_json::Value> {
@@ -269,7 +293,7 @@ impl From<AdditionalField> for serde_json::Map<String, serde_json::Value> {
 impl From<serde_json::Map<String, serde_json::Value>> for AdditionalField {
     fn from(value: serde_json::Map<String, serde_json::Value>) -> Self {
         Self {
-            must_understand: monostate::MustBe!(false),
+            must_understand: false,
             fields: value,
         }
     }
@@ -286,5 +310,94 @@ impl From<serde_json::Map<String, serde_json::Value>> for AdditionalField {
 ///   "must_understand": false
 /// }
 /// ```
+/// Additional fields not annotated with `"must_understand": false` are rejected unless they are a [supported additional field](crate::config::Config#supported-additional-fields).
 // NOTE: It would be nice if this was just a serde_json::Map, but it only has implementations for `<String, serde_json::Value>`.
 pub type AdditionalFields = std::collections::BTreeMap<String, AdditionalField>;
+
+/// A supported additional field in array or group metadata.
+///
+/// See [`Config::supported_additional_fields_mut`](crate::config::Config::supported_additional_fields_mut).
+#[derive(Clone, Copy, Default)]
+pub struct SupportedAdditionalField {
+    validate_fn: Option<SupportedAdditionalFieldValidateFn>,
+}
+
+type SupportedAdditionalFieldValidateFn = fn(&serde_json::Value) -> Result<(), serde_json::Error>;
+
+impl core::fmt::Debug for SupportedAdditionalField {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SupportedAdditionalField")
+            .field("typed", &self.validate_fn.is_some())
+            .finish()
+    }
+}
+
+impl SupportedAdditionalField {
+    /// Create a supported additional field that accepts any object.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { validate_fn: None }
+    }
+
+    /// Create a supported additional field that accepts an object that can be deserialised to `T`.
+    #[must_use]
+    pub fn new_with_type<T: DeserializeOwned>() -> Self {
+        Self {
+            validate_fn: Some(|value| serde_json::from_value::<T>(value.clone()).map(|_| ())),
+        }
+    }
+
+    /// Validate the value of an additional field.
+    ///
+    /// # Errors
+    /// Returns a [`serde_json::Error`] if the field was registered with [`new_with_type`](Self::new_with_type) and `value` cannot be deserialised to its type.
+    pub fn validate(&self, value: &serde_json::Value) -> Result<(), serde_json::Error> {
+        self.validate_fn
+            .map_or(Ok(()), |validate_fn| validate_fn(value))
+    }
+}
+
+/// Deserialise the additional fields of array or group metadata.
+///
+/// [Supported additional fields](crate::config::Config#supported-additional-fields) are accepted regardless of `must_understand`.
+pub(crate) fn deserialize_additional_fields<'de, D: serde::Deserializer<'de>>(
+    d: D,
+) -> Result<AdditionalFields, D::Error> {
+    let fields = serde_json::Map::<String, serde_json::Value>::deserialize(d)?;
+    let config = global_config();
+    let mut additional_fields = AdditionalFields::new();
+    for (name, value) in fields {
+        let field = if let Some(supported) = config.supported_additional_fields().get(&name) {
+            supported.validate(&value).map_err(|err| {
+                serde::de::Error::custom(format!("invalid additional field {name}: {err}"))
+            })?;
+            let serde_json::Value::Object(mut fields) = value else {
+                return Err(serde::de::Error::custom(format!(
+                    "additional field {name} must be an object"
+                )));
+            };
+            let must_understand = match fields.remove("must_understand") {
+                None => true,
+                Some(serde_json::Value::Bool(must_understand)) => must_understand,
+                Some(_) => {
+                    return Err(serde::de::Error::custom(format!(
+                        "must_understand of additional field {name} must be a boolean"
+                    )))
+                }
+            };
+            AdditionalField {
+                must_understand,
+                fields,
+            }
+        } else {
+            serde_json::from_value(value.clone()).map_err(|_| {
+                serde::de::Error::custom(UnsupportedAdditionalFieldError {
+                    name: name.clone(),
+                    value,
+                })
+            })?
+        };
+        additional_fields.insert(name, field);
+    }
+    Ok(additional_fields)
+}
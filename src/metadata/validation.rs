@@ -0,0 +1,666 @@
+//! Zarr metadata validation.
+//!
+//! Deserialising invalid metadata into [`ArrayMetadata`](super::ArrayMetadata) or [`GroupMetadata`](super::GroupMetadata) fails with a generic error that does not identify the offending field.
+//! [`validate_array_metadata`] and [`validate_group_metadata`] instead check JSON metadata against the Zarr specification and return a [`MetadataDiagnostic`] for each problem, with a [JSON pointer](https://datatracker.ietf.org/doc/html/rfc6901) to the offending field.
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::{
+    array::{
+        codec::{
+            ArrayToArrayCodecTraits, ArrayToBytesCodecTraits, BytesToBytesCodecTraits, Codec,
+            CodecChain,
+        },
+        ChunkGrid, ChunkKeyEncoding, ChunkRepresentation, DataType, DimensionName, FillValue,
+        FillValueMetadata,
+    },
+    config::global_config,
+    plugin::PluginCreateError,
+    storage::StorageTransformerChain,
+};
+
+use super::{
+    array_metadata_v2_to_v3,
+    v2::{ArrayMetadataV2, GroupMetadataV2},
+    v3::MetadataV3,
+};
+
+/// The severity of a [`MetadataDiagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MetadataDiagnosticSeverity {
+    /// The metadata is valid, but uses a feature that is not supported by `zarrs`.
+    Warning,
+    /// The metadata is invalid.
+    Error,
+}
+
+impl core::fmt::Display for MetadataDiagnosticSeverity {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A metadata diagnostic.
+///
+/// Returned by [`validate_array_metadata`] and [`validate_group_metadata`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataDiagnostic {
+    severity: MetadataDiagnosticSeverity,
+    pointer: String,
+    message: String,
+}
+
+impl core::fmt::Display for MetadataDiagnostic {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "{}: {}", self.severity, self.message)
+        } else {
+            write!(f, "{} at {}: {}", self.severity, self.pointer, self.message)
+        }
+    }
+}
+
+impl MetadataDiagnostic {
+    fn new(severity: MetadataDiagnosticSeverity, pointer: &str, message: String) -> Self {
+        Self {
+            severity,
+            pointer: pointer.to_string(),
+            message,
+        }
+    }
+
+    fn error(pointer: &str, message: impl Into<String>) -> Self {
+        Self::new(MetadataDiagnosticSeverity::Error, pointer, message.into())
+    }
+
+    pub(crate) fn warning(pointer: &str, message: impl Into<String>) -> Self {
+        Self::new(MetadataDiagnosticSeverity::Warning, pointer, message.into())
+    }
+
+    /// Create a diagnostic from a plugin creation error, which is a warning if the plugin is unsupported.
+    fn plugin(pointer: &str, err: &PluginCreateError) -> Self {
+        match err {
+            PluginCreateError::Unsupported { .. } => Self::warning(pointer, err.to_string()),
+            _ => Self::error(pointer, err.to_string()),
+        }
+    }
+
+    /// Return the severity.
+    #[must_use]
+    pub const fn severity(&self) -> MetadataDiagnosticSeverity {
+        self.severity
+    }
+
+    /// Return the [JSON pointer](https://datatracker.ietf.org/doc/html/rfc6901) to the offending field, e.g. `/codecs/1`.
+    ///
+    /// The pointer is empty if the diagnostic refers to the metadata as a whole.
+    #[must_use]
+    pub fn pointer(&self) -> &str {
+        &self.pointer
+    }
+
+    /// Return the message.
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// The fields of Zarr V3 array metadata defined by the specification.
+const ARRAY_METADATA_V3_FIELDS: [&str; 11] = [
+    "zarr_format",
+    "node_type",
+    "shape",
+    "data_type",
+    "chunk_grid",
+    "chunk_key_encoding",
+    "fill_value",
+    "codecs",
+    "attributes",
+    "storage_transformers",
+    "dimension_names",
+];
+
+/// The fields of Zarr V3 group metadata defined by the specification.
+const GROUP_METADATA_V3_FIELDS: [&str; 3] = ["zarr_format", "node_type", "attributes"];
+
+/// Validate Zarr V3 or V2 array metadata.
+///
+/// Zarr V3 array metadata is checked for:
+///  - missing or malformed fields,
+///  - unknown fields not annotated with `"must_understand": false` that are not [supported](crate::config::Config#supported-additional-fields),
+///  - unsupported or invalid data types, chunk grids, chunk key encodings, codecs, and storage transformers,
+///  - a fill value incompatible with the data type,
+///  - a chunk grid or dimension names inconsistent with the shape, and
+///  - codecs that are out of order, missing an array to bytes codec, or incompatible with the data type.
+///
+/// Zarr V2 array metadata is checked after conversion to Zarr V3, so diagnostics refer to the metadata as a whole.
+///
+/// Returns an empty vector if the metadata is valid.
+#[must_use]
+pub fn validate_array_metadata(metadata: &Value) -> Vec<MetadataDiagnostic> {
+    let mut diagnostics = Vec::new();
+    let Some(object) = metadata.as_object() else {
+        diagnostics.push(MetadataDiagnostic::error(
+            "",
+            "metadata must be a JSON object",
+        ));
+        return diagnostics;
+    };
+    match object.get("zarr_format") {
+        Some(Value::Number(zarr_format)) if zarr_format.as_u64() == Some(3) => {
+            validate_array_metadata_v3(object, &mut diagnostics);
+        }
+        Some(Value::Number(zarr_format)) if zarr_format.as_u64() == Some(2) => {
+            validate_array_metadata_v2(metadata, &mut diagnostics);
+        }
+        Some(_) => diagnostics.push(MetadataDiagnostic::error(
+            "/zarr_format",
+            "zarr_format must be 3 or 2",
+        )),
+        None => diagnostics.push(MetadataDiagnostic::error(
+            "",
+            "missing required field zarr_format",
+        )),
+    }
+    diagnostics
+}
+
+/// Validate Zarr V3 or V2 group metadata.
+///
+/// Zarr V3 group metadata is checked for missing or malformed fields and unknown fields not annotated with `"must_understand": false` that are not [supported](crate::config::Config#supported-additional-fields).
+///
+/// Returns an empty vector if the metadata is valid.
+#[must_use]
+pub fn validate_group_metadata(metadata: &Value) -> Vec<MetadataDiagnostic> {
+    let mut diagnostics = Vec::new();
+    let Some(object) = metadata.as_object() else {
+        diagnostics.push(MetadataDiagnostic::error(
+            "",
+            "metadata must be a JSON object",
+        ));
+        return diagnostics;
+    };
+    match object.get("zarr_format") {
+        Some(Value::Number(zarr_format)) if zarr_format.as_u64() == Some(3) => {
+            validate_node_type(object, "group", &mut diagnostics);
+            validate_attributes(object, &mut diagnostics);
+            validate_additional_fields(object, &GROUP_METADATA_V3_FIELDS, &mut diagnostics);
+        }
+        Some(Value::Number(zarr_format)) if zarr_format.as_u64() == Some(2) => {
+            if let Err(err) = serde_json::from_value::<GroupMetadataV2>(metadata.clone()) {
+                diagnostics.push(MetadataDiagnostic::error("", err.to_string()));
+            }
+        }
+        Some(_) => diagnostics.push(MetadataDiagnostic::error(
+            "/zarr_format",
+            "zarr_format must be 3 or 2",
+        )),
+        None => diagnostics.push(MetadataDiagnostic::error(
+            "",
+            "missing required field zarr_format",
+        )),
+    }
+    diagnostics
+}
+
+/// Escape a field name for use in a JSON pointer.
+fn pointer(name: &str) -> String {
+    format!("/{}", name.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Deserialise a required field, or push a diagnostic if it is missing or malformed.
+fn required_field<T: DeserializeOwned>(
+    object: &Map<String, Value>,
+    name: &str,
+    diagnostics: &mut Vec<MetadataDiagnostic>,
+) -> Option<T> {
+    if object.contains_key(name) {
+        optional_field(object, name, diagnostics)
+    } else {
+        diagnostics.push(MetadataDiagnostic::error(
+            "",
+            format!("missing required field {name}"),
+        ));
+        None
+    }
+}
+
+/// Deserialise an optional field, or push a diagnostic if it is malformed.
+fn optional_field<T: DeserializeOwned>(
+    object: &Map<String, Value>,
+    name: &str,
+    diagnostics: &mut Vec<MetadataDiagnostic>,
+) -> Option<T> {
+    let value = object.get(name)?;
+    serde_json::from_value(value.clone())
+        .map_err(|err| diagnostics.push(MetadataDiagnostic::error(&pointer(name), err.to_string())))
+        .ok()
+}
+
+fn validate_node_type(
+    object: &Map<String, Value>,
+    node_type: &str,
+    diagnostics: &mut Vec<MetadataDiagnostic>,
+) {
+    match object.get("node_type") {
+        Some(Value::String(value)) if value == node_type => {}
+        Some(_) => diagnostics.push(MetadataDiagnostic::error(
+            "/node_type",
+            format!("node_type must be {node_type}"),
+        )),
+        None => diagnostics.push(MetadataDiagnostic::error(
+            "",
+            "missing required field node_type",
+        )),
+    }
+}
+
+fn validate_attributes(object: &Map<String, Value>, diagnostics: &mut Vec<MetadataDiagnostic>) {
+    if object
+        .get("attributes")
+        .is_some_and(|value| !value.is_object())
+    {
+        diagnostics.push(MetadataDiagnostic::error(
+            "/attributes",
+            "attributes must be a JSON object",
+        ));
+    }
+}
+
+/// Check that fields not defined by the specification are objects with `"must_understand": false` or valid supported additional fields.
+fn validate_additional_fields(
+    object: &Map<String, Value>,
+    known_fields: &[&str],
+    diagnostics: &mut Vec<MetadataDiagnostic>,
+) {
+    let config = global_config();
+    for (name, value) in object {
+        if known_fields.contains(&name.as_str()) {
+            continue;
+        }
+        if let Some(supported) = config.supported_additional_fields().get(name) {
+            if !value.is_object() {
+                diagnostics.push(MetadataDiagnostic::error(
+                    &pointer(name),
+                    format!("additional field {name} must be an object"),
+                ));
+            } else if let Err(err) = supported.validate(value) {
+                diagnostics.push(MetadataDiagnostic::error(&pointer(name), err.to_string()));
+            }
+            continue;
+        }
+        let must_understand = value.get("must_understand");
+        if must_understand != Some(&Value::Bool(false)) {
+            diagnostics.push(MetadataDiagnostic::error(
+                &pointer(name),
+                format!(
+                    "unsupported field {name} must be an object with \"must_understand\": false"
+                ),
+            ));
+        }
+    }
+}
+
+fn validate_array_metadata_v2(metadata: &Value, diagnostics: &mut Vec<MetadataDiagnostic>) {
+    let metadata_v2 = match serde_json::from_value::<ArrayMetadataV2>(metadata.clone()) {
+        Ok(metadata_v2) => metadata_v2,
+        Err(err) => {
+            diagnostics.push(MetadataDiagnostic::error("", err.to_string()));
+            return;
+        }
+    };
+    let metadata_v3 = match array_metadata_v2_to_v3(&metadata_v2) {
+        Ok(metadata_v3) => metadata_v3,
+        Err(err) => {
+            diagnostics.push(MetadataDiagnostic::warning("", err.to_string()));
+            return;
+        }
+    };
+    if let Ok(Value::Object(object)) = serde_json::to_value(metadata_v3) {
+        let mut diagnostics_v3 = Vec::new();
+        validate_array_metadata_v3(&object, &mut diagnostics_v3);
+        diagnostics.extend(diagnostics_v3.into_iter().map(|diagnostic| {
+            MetadataDiagnostic::new(
+                diagnostic.severity,
+                "",
+                format!(
+                    "{} (at {} of the equivalent Zarr V3 metadata)",
+                    diagnostic.message, diagnostic.pointer
+                ),
+            )
+        }));
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+fn validate_array_metadata_v3(
+    object: &Map<String, Value>,
+    diagnostics: &mut Vec<MetadataDiagnostic>,
+) {
+    validate_node_type(object, "array", diagnostics);
+    validate_attributes(object, diagnostics);
+    validate_additional_fields(object, &ARRAY_METADATA_V3_FIELDS, diagnostics);
+
+    let shape: Option<Vec<u64>> = required_field(object, "shape", diagnostics);
+
+    let data_type =
+        required_field::<MetadataV3>(object, "data_type", diagnostics).and_then(|metadata| {
+            DataType::from_metadata(&metadata)
+                .map_err(|err| {
+                    diagnostics.push(MetadataDiagnostic::warning("/data_type", err.to_string()));
+                })
+                .ok()
+        });
+
+    let fill_value = required_field::<FillValueMetadata>(object, "fill_value", diagnostics)
+        .and_then(|fill_value| {
+            let data_type = data_type.as_ref()?;
+            data_type
+                .fill_value_from_metadata(&fill_value)
+                .map_err(|err| {
+                    diagnostics.push(MetadataDiagnostic::error("/fill_value", err.to_string()));
+                })
+                .ok()
+        });
+
+    let chunk_grid =
+        required_field::<MetadataV3>(object, "chunk_grid", diagnostics).and_then(|metadata| {
+            ChunkGrid::from_metadata(&metadata)
+                .map_err(|err| {
+                    diagnostics.push(MetadataDiagnostic::plugin("/chunk_grid", &err));
+                })
+                .ok()
+        });
+    if let (Some(chunk_grid), Some(shape)) = (&chunk_grid, &shape) {
+        if chunk_grid.dimensionality() != shape.len() {
+            diagnostics.push(MetadataDiagnostic::error(
+                "/chunk_grid",
+                format!(
+                    "chunk grid dimensionality {} does not match array dimensionality {}",
+                    chunk_grid.dimensionality(),
+                    shape.len()
+                ),
+            ));
+        }
+    }
+
+    if let Some(metadata) = required_field::<MetadataV3>(object, "chunk_key_encoding", diagnostics)
+    {
+        if let Err(err) = ChunkKeyEncoding::from_metadata(&metadata) {
+            diagnostics.push(MetadataDiagnostic::plugin("/chunk_key_encoding", &err));
+        }
+    }
+
+    let codecs = required_field::<Vec<Value>>(object, "codecs", diagnostics)
+        .and_then(|codecs| validate_codecs(&codecs, diagnostics));
+
+    if let Some(metadatas) =
+        optional_field::<Vec<Value>>(object, "storage_transformers", diagnostics)
+    {
+        for (i, metadata) in metadatas.into_iter().enumerate() {
+            let pointer = format!("/storage_transformers/{i}");
+            match serde_json::from_value::<MetadataV3>(metadata) {
+                Ok(metadata) => {
+                    if let Err(err) = StorageTransformerChain::from_metadata(&[metadata]) {
+                        diagnostics.push(MetadataDiagnostic::plugin(&pointer, &err));
+                    }
+                }
+                Err(err) => diagnostics.push(MetadataDiagnostic::error(&pointer, err.to_string())),
+            }
+        }
+    }
+
+    if let (Some(dimension_names), Some(shape)) = (
+        optional_field::<Vec<DimensionName>>(object, "dimension_names", diagnostics),
+        &shape,
+    ) {
+        if dimension_names.len() != shape.len() {
+            diagnostics.push(MetadataDiagnostic::error(
+                "/dimension_names",
+                format!(
+                    "the number of dimension names {} does not match array dimensionality {}",
+                    dimension_names.len(),
+                    shape.len()
+                ),
+            ));
+        }
+    }
+
+    // Check that the codecs support the data type by computing the encoded size of the first chunk
+    if let (Some(codecs), Some(data_type), Some(fill_value), Some(chunk_grid), Some(shape)) =
+        (codecs, data_type, fill_value, chunk_grid, shape)
+    {
+        validate_codecs_data_type(
+            &codecs,
+            &chunk_grid,
+            &shape,
+            data_type,
+            fill_value,
+            diagnostics,
+        );
+    }
+}
+
+/// Check each codec and the order of the codec chain, and return the codec chain if it is valid.
+fn validate_codecs(
+    codecs: &[Value],
+    diagnostics: &mut Vec<MetadataDiagnostic>,
+) -> Option<CodecChain> {
+    let mut valid = true;
+    let mut known = true;
+    let mut array_to_array: Vec<Box<dyn ArrayToArrayCodecTraits>> = vec![];
+    let mut array_to_bytes: Option<Box<dyn ArrayToBytesCodecTraits>> = None;
+    let mut bytes_to_bytes: Vec<Box<dyn BytesToBytesCodecTraits>> = vec![];
+    for (i, metadata) in codecs.iter().enumerate() {
+        let pointer = format!("/codecs/{i}");
+        let codec = match serde_json::from_value::<MetadataV3>(metadata.clone()) {
+            Ok(metadata) => Codec::from_metadata(&metadata)
+                .map_err(|err| diagnostics.push(MetadataDiagnostic::plugin(&pointer, &err))),
+            Err(err) => {
+                diagnostics.push(MetadataDiagnostic::error(&pointer, err.to_string()));
+                Err(())
+            }
+        };
+        match codec {
+            Ok(Codec::ArrayToArray(codec)) => {
+                if array_to_bytes.is_some() || !bytes_to_bytes.is_empty() {
+                    diagnostics.push(MetadataDiagnostic::error(
+                        &pointer,
+                        "array to array codecs must precede the array to bytes codec",
+                    ));
+                    valid = false;
+                }
+                array_to_array.push(codec);
+            }
+            Ok(Codec::ArrayToBytes(codec)) => {
+                if array_to_bytes.is_some() {
+                    diagnostics.push(MetadataDiagnostic::error(
+                        &pointer,
+                        "multiple array to bytes codecs",
+                    ));
+                    valid = false;
+                } else if !bytes_to_bytes.is_empty() {
+                    diagnostics.push(MetadataDiagnostic::error(
+                        &pointer,
+                        "bytes to bytes codecs must follow the array to bytes codec",
+                    ));
+                    valid = false;
+                }
+                array_to_bytes.get_or_insert(codec);
+            }
+            Ok(Codec::BytesToBytes(codec)) => {
+                bytes_to_bytes.push(codec);
+            }
+            Err(()) => {
+                // The codec kind is unknown, so the order cannot be checked against it
+                known = false;
+            }
+        }
+    }
+    if let Some(array_to_bytes) = array_to_bytes {
+        (valid && known).then(|| CodecChain::new(array_to_array, array_to_bytes, bytes_to_bytes))
+    } else {
+        if known {
+            diagnostics.push(MetadataDiagnostic::error(
+                "/codecs",
+                "missing array to bytes codec",
+            ));
+        }
+        None
+    }
+}
+
+fn validate_codecs_data_type(
+    codecs: &CodecChain,
+    chunk_grid: &ChunkGrid,
+    shape: &[u64],
+    data_type: DataType,
+    fill_value: FillValue,
+    diagnostics: &mut Vec<MetadataDiagnostic>,
+) {
+    let Ok(Some(chunk_shape)) = chunk_grid.chunk_shape(&vec![0; shape.len()], shape) else {
+        return;
+    };
+    let Ok(chunk_representation) =
+        ChunkRepresentation::new(chunk_shape.into(), data_type, fill_value)
+    else {
+        return;
+    };
+    if let Err(err) = codecs.compute_encoded_size(&chunk_representation) {
+        diagnostics.push(MetadataDiagnostic::error(
+            "/codecs",
+            format!("codecs are incompatible with the data type or chunk shape: {err}"),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pointers(diagnostics: &[MetadataDiagnostic]) -> Vec<(MetadataDiagnosticSeverity, &str)> {
+        diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.severity(), diagnostic.pointer()))
+            .collect()
+    }
+
+    #[test]
+    fn validate_array_metadata_v3() {
+        let mut metadata = serde_json::json!({
+            "zarr_format": 3,
+            "node_type": "array",
+            "shape": [10, 10],
+            "data_type": "uint16",
+            "chunk_grid": {"name": "regular", "configuration": {"chunk_shape": [5, 5]}},
+            "chunk_key_encoding": {"name": "default"},
+            "fill_value": 0,
+            "codecs": [{"name": "bytes", "configuration": {"endian": "little"}}],
+            "dimension_names": ["y", "x"],
+            "extension": {"must_understand": false}
+        });
+        assert!(validate_array_metadata(&metadata).is_empty());
+
+        metadata["fill_value"] = serde_json::json!(-1);
+        metadata["dimension_names"] = serde_json::json!(["x"]);
+        metadata["extension"] = serde_json::json!({"must_understand": true});
+        metadata["a/b"] = serde_json::json!("c");
+        let diagnostics = validate_array_metadata(&metadata);
+        assert_eq!(
+            pointers(&diagnostics),
+            [
+                (MetadataDiagnosticSeverity::Error, "/extension"),
+                (MetadataDiagnosticSeverity::Error, "/a~1b"),
+                (MetadataDiagnosticSeverity::Error, "/fill_value"),
+                (MetadataDiagnosticSeverity::Error, "/dimension_names"),
+            ]
+        );
+        assert!(diagnostics[0]
+            .to_string()
+            .starts_with("error at /extension"));
+    }
+
+    #[test]
+    fn validate_array_metadata_v3_codecs() {
+        let mut metadata = serde_json::json!({
+            "zarr_format": 3,
+            "node_type": "array",
+            "shape": [10, 10],
+            "data_type": "uint16",
+            "chunk_grid": {"name": "regular", "configuration": {"chunk_shape": [5, 5, 5]}},
+            "chunk_key_encoding": {"name": "default"},
+            "fill_value": 0,
+            "codecs": [
+                {"name": "bytes", "configuration": {"endian": "little"}},
+                {"name": "transpose", "configuration": {"order": [1, 0]}},
+                {"name": "bytes", "configuration": {"endian": "little"}},
+                {"name": "unknown"}
+            ]
+        });
+        assert_eq!(
+            pointers(&validate_array_metadata(&metadata)),
+            [
+                (MetadataDiagnosticSeverity::Error, "/chunk_grid"),
+                (MetadataDiagnosticSeverity::Error, "/codecs/1"),
+                (MetadataDiagnosticSeverity::Error, "/codecs/2"),
+                (MetadataDiagnosticSeverity::Warning, "/codecs/3"),
+            ]
+        );
+
+        metadata["chunk_grid"]["configuration"]["chunk_shape"] = serde_json::json!([5, 5]);
+        metadata["codecs"] = serde_json::json!([
+            {"name": "transpose", "configuration": {"order": [2, 0, 1]}},
+            {"name": "bytes", "configuration": {"endian": "little"}}
+        ]);
+        assert_eq!(
+            pointers(&validate_array_metadata(&metadata)),
+            [(MetadataDiagnosticSeverity::Error, "/codecs")]
+        );
+
+        // The bytes codec does not support variable-sized data types
+        metadata["data_type"] = serde_json::json!("string");
+        metadata["fill_value"] = serde_json::json!("");
+        metadata["codecs"] = serde_json::json!([{"name": "bytes"}]);
+        assert_eq!(
+            pointers(&validate_array_metadata(&metadata)),
+            [(MetadataDiagnosticSeverity::Error, "/codecs")]
+        );
+        metadata["codecs"] = serde_json::json!([]);
+        assert_eq!(
+            pointers(&validate_array_metadata(&metadata)),
+            [(MetadataDiagnosticSeverity::Error, "/codecs")]
+        );
+    }
+
+    #[test]
+    fn validate_group_metadata_v3() {
+        let metadata = serde_json::json!({
+            "zarr_format": 3,
+            "node_type": "group",
+            "attributes": {"a": 1},
+            "consolidated_metadata": {"kind": "inline", "must_understand": false, "metadata": {}}
+        });
+        assert!(validate_group_metadata(&metadata).is_empty());
+
+        let metadata = serde_json::json!({
+            "zarr_format": 3,
+            "node_type": "array",
+            "attributes": [],
+            "extension": {}
+        });
+        assert_eq!(
+            pointers(&validate_group_metadata(&metadata)),
+            [
+                (MetadataDiagnosticSeverity::Error, "/node_type"),
+                (MetadataDiagnosticSeverity::Error, "/attributes"),
+                (MetadataDiagnosticSeverity::Error, "/extension"),
+            ]
+        );
+        assert!(validate_group_metadata(&serde_json::json!({"zarr_format": 2})).is_empty());
+    }
+}
@@ -0,0 +1,356 @@
+//! Operational metrics.
+//!
+//! When a [`MetricsRecorder`] is set with [`set_metrics_recorder`], `zarrs` records the [`Metric`]s of array, chunk cache, and store operations to it.
+//! This includes the number of chunks read and written, their encoded and decoded bytes, chunk cache hits and misses, and store latencies.
+//! The recorder is global, so long-running services can export metrics without wrapping every call site.
+//!
+//! [`PrometheusMetricsRecorder`] accumulates metrics and renders them in the [Prometheus text exposition format](https://prometheus.io/docs/instrumenting/exposition_formats/).
+//! Other monitoring systems can be supported by implementing [`MetricsRecorder`].
+//!
+//! This module requires the `metrics` feature.
+//!
+//! ### Example
+//! ```rust
+//! # use std::sync::Arc;
+//! # use zarrs::array::{ArrayBuilder, DataType, FillValue};
+//! use zarrs::metrics::{set_metrics_recorder, Metric, PrometheusMetricsRecorder};
+//!
+//! let recorder = Arc::new(PrometheusMetricsRecorder::new());
+//! set_metrics_recorder(Some(recorder.clone()));
+//!
+//! # let store = Arc::new(zarrs::storage::store::MemoryStore::new());
+//! let array = ArrayBuilder::new(vec![4, 4], DataType::UInt8, vec![2, 2].try_into()?, FillValue::from(0u8))
+//!     .build(store, "/array")?;
+//! array.store_chunk_elements::<u8>(&[0, 0], &[1, 2, 3, 4])?;
+//! array.retrieve_chunk(&[0, 0])?;
+//! assert!(recorder.counter(Metric::ChunksRead) >= 1);
+//! assert!(recorder.render().contains("# TYPE zarrs_chunks_written_total counter"));
+//! # set_metrics_recorder(None);
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use std::{collections::HashMap, fmt::Write, sync::Arc, time::Duration};
+
+use derive_more::Display;
+use parking_lot::{Mutex, RwLock};
+
+/// The kind of a [`Metric`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Display)]
+pub enum MetricKind {
+    /// A monotonically increasing count.
+    #[display("counter")]
+    Counter,
+    /// A distribution of observed values.
+    #[display("histogram")]
+    Histogram,
+}
+
+/// A metric recorded by `zarrs`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Metric {
+    /// The number of chunks read and decoded.
+    ChunksRead,
+    /// The number of chunks encoded and written.
+    ChunksWritten,
+    /// The number of encoded (e.g. compressed) bytes of chunks read.
+    ChunkReadEncodedBytes,
+    /// The number of decoded bytes of chunks read.
+    ChunkReadDecodedBytes,
+    /// The number of decoded bytes of chunks written.
+    ChunkWrittenDecodedBytes,
+    /// The number of encoded (e.g. compressed) bytes of chunks written.
+    ChunkWrittenEncodedBytes,
+    /// The number of chunk cache hits.
+    ChunkCacheHits,
+    /// The number of chunk cache misses.
+    ChunkCacheMisses,
+    /// The duration of store retrievals in seconds.
+    StoreGetDuration,
+    /// The duration of store writes in seconds.
+    StoreSetDuration,
+    /// The duration of store erasures in seconds.
+    StoreEraseDuration,
+}
+
+impl Metric {
+    /// All metrics.
+    pub const ALL: [Self; 11] = [
+        Self::ChunksRead,
+        Self::ChunksWritten,
+        Self::ChunkReadEncodedBytes,
+        Self::ChunkReadDecodedBytes,
+        Self::ChunkWrittenDecodedBytes,
+        Self::ChunkWrittenEncodedBytes,
+        Self::ChunkCacheHits,
+        Self::ChunkCacheMisses,
+        Self::StoreGetDuration,
+        Self::StoreSetDuration,
+        Self::StoreEraseDuration,
+    ];
+
+    /// Return the name of the metric.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::ChunksRead => "zarrs_chunks_read_total",
+            Self::ChunksWritten => "zarrs_chunks_written_total",
+            Self::ChunkReadEncodedBytes => "zarrs_chunk_read_encoded_bytes_total",
+            Self::ChunkReadDecodedBytes => "zarrs_chunk_read_decoded_bytes_total",
+            Self::ChunkWrittenDecodedBytes => "zarrs_chunk_written_decoded_bytes_total",
+            Self::ChunkWrittenEncodedBytes => "zarrs_chunk_written_encoded_bytes_total",
+            Self::ChunkCacheHits => "zarrs_chunk_cache_hits_total",
+            Self::ChunkCacheMisses => "zarrs_chunk_cache_misses_total",
+            Self::StoreGetDuration => "zarrs_store_get_duration_seconds",
+            Self::StoreSetDuration => "zarrs_store_set_duration_seconds",
+            Self::StoreEraseDuration => "zarrs_store_erase_duration_seconds",
+        }
+    }
+
+    /// Return a description of the metric.
+    #[must_use]
+    pub const fn help(&self) -> &'static str {
+        match self {
+            Self::ChunksRead => "The number of chunks read and decoded.",
+            Self::ChunksWritten => "The number of chunks encoded and written.",
+            Self::ChunkReadEncodedBytes => "The number of encoded bytes of chunks read.",
+            Self::ChunkReadDecodedBytes => "The number of decoded bytes of chunks read.",
+            Self::ChunkWrittenDecodedBytes => "The number of decoded bytes of chunks written.",
+            Self::ChunkWrittenEncodedBytes => "The number of encoded bytes of chunks written.",
+            Self::ChunkCacheHits => "The number of chunk cache hits.",
+            Self::ChunkCacheMisses => "The number of chunk cache misses.",
+            Self::StoreGetDuration => "The duration of store retrievals in seconds.",
+            Self::StoreSetDuration => "The duration of store writes in seconds.",
+            Self::StoreEraseDuration => "The duration of store erasures in seconds.",
+        }
+    }
+
+    /// Return the kind of the metric.
+    #[must_use]
+    pub const fn kind(&self) -> MetricKind {
+        match self {
+            Self::StoreGetDuration | Self::StoreSetDuration | Self::StoreEraseDuration => {
+                MetricKind::Histogram
+            }
+            _ => MetricKind::Counter,
+        }
+    }
+}
+
+impl std::fmt::Display for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Traits for a metrics recorder.
+pub trait MetricsRecorder: Send + Sync {
+    /// Increment the counter `metric` by `value`.
+    fn increment_counter(&self, metric: Metric, value: u64);
+
+    /// Record an observation of `value` in the histogram `metric`.
+    fn record_histogram(&self, metric: Metric, value: f64);
+}
+
+static METRICS_RECORDER: RwLock<Option<Arc<dyn MetricsRecorder>>> = parking_lot::const_rwlock(None);
+
+/// Set the global metrics recorder.
+///
+/// Metrics are not recorded if the recorder is [`None`] (the default).
+pub fn set_metrics_recorder(recorder: Option<Arc<dyn MetricsRecorder>>) {
+    *METRICS_RECORDER.write() = recorder;
+}
+
+/// Return the global metrics recorder.
+#[must_use]
+pub fn metrics_recorder() -> Option<Arc<dyn MetricsRecorder>> {
+    METRICS_RECORDER.read().clone()
+}
+
+/// Increment the counter `metric` of the global metrics recorder by `value`.
+pub(crate) fn increment_counter(metric: Metric, value: usize) {
+    if let Some(recorder) = METRICS_RECORDER.read().as_ref() {
+        recorder.increment_counter(metric, value as u64);
+    }
+}
+
+/// Record an observation of `duration` in the histogram `metric` of the global metrics recorder.
+pub(crate) fn record_duration(metric: Metric, duration: Duration) {
+    if let Some(recorder) = METRICS_RECORDER.read().as_ref() {
+        recorder.record_histogram(metric, duration.as_secs_f64());
+    }
+}
+
+/// Record a chunk read with `encoded_bytes` decoded to `decoded_bytes`.
+pub(crate) fn record_chunk_read(encoded_bytes: usize, decoded_bytes: usize) {
+    increment_counter(Metric::ChunksRead, 1);
+    increment_counter(Metric::ChunkReadEncodedBytes, encoded_bytes);
+    increment_counter(Metric::ChunkReadDecodedBytes, decoded_bytes);
+}
+
+/// Record a chunk write with `decoded_bytes` encoded to `encoded_bytes`.
+pub(crate) fn record_chunk_written(decoded_bytes: usize, encoded_bytes: usize) {
+    increment_counter(Metric::ChunksWritten, 1);
+    increment_counter(Metric::ChunkWrittenDecodedBytes, decoded_bytes);
+    increment_counter(Metric::ChunkWrittenEncodedBytes, encoded_bytes);
+}
+
+/// The default histogram bucket upper bounds of a [`PrometheusMetricsRecorder`] in seconds.
+pub const PROMETHEUS_DEFAULT_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// The state of a histogram in a [`PrometheusMetricsRecorder`].
+#[derive(Clone, Debug, Default)]
+struct PrometheusHistogram {
+    bucket_counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+/// A [`MetricsRecorder`] that renders metrics in the Prometheus text exposition format.
+///
+/// Serve the output of [`render`](PrometheusMetricsRecorder::render) from a `/metrics` endpoint to export metrics to Prometheus.
+#[derive(Debug)]
+pub struct PrometheusMetricsRecorder {
+    buckets: Vec<f64>,
+    counters: Mutex<HashMap<Metric, u64>>,
+    histograms: Mutex<HashMap<Metric, PrometheusHistogram>>,
+}
+
+impl Default for PrometheusMetricsRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PrometheusMetricsRecorder {
+    /// Create a new Prometheus metrics recorder with the [default buckets](PROMETHEUS_DEFAULT_BUCKETS).
+    #[must_use]
+    pub fn new() -> Self {
+        Self::new_with_buckets(PROMETHEUS_DEFAULT_BUCKETS.to_vec())
+    }
+
+    /// Create a new Prometheus metrics recorder with histogram bucket upper bounds `buckets`.
+    ///
+    /// The buckets are sorted and a `+Inf` bucket is always included.
+    #[must_use]
+    pub fn new_with_buckets(mut buckets: Vec<f64>) -> Self {
+        buckets.retain(|bucket| bucket.is_finite());
+        buckets.sort_by(f64::total_cmp);
+        buckets.dedup();
+        Self {
+            buckets,
+            counters: Mutex::default(),
+            histograms: Mutex::default(),
+        }
+    }
+
+    /// Return the value of the counter `metric`.
+    #[must_use]
+    pub fn counter(&self, metric: Metric) -> u64 {
+        self.counters
+            .lock()
+            .get(&metric)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Return the number of observations and their sum in the histogram `metric`.
+    #[must_use]
+    pub fn histogram(&self, metric: Metric) -> (u64, f64) {
+        self.histograms
+            .lock()
+            .get(&metric)
+            .map_or((0, 0.0), |histogram| (histogram.count, histogram.sum))
+    }
+
+    /// Render all metrics in the Prometheus text exposition format.
+    ///
+    /// # Panics
+    /// Panics if writing to a [`String`] fails, which is infallible.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let counters = self.counters.lock().clone();
+        let histograms = self.histograms.lock().clone();
+        let mut output = String::new();
+        for metric in Metric::ALL {
+            let name = metric.name();
+            writeln!(output, "# HELP {name} {}", metric.help()).unwrap();
+            writeln!(output, "# TYPE {name} {}", metric.kind()).unwrap();
+            match metric.kind() {
+                MetricKind::Counter => {
+                    let value = counters.get(&metric).copied().unwrap_or_default();
+                    writeln!(output, "{name} {value}").unwrap();
+                }
+                MetricKind::Histogram => {
+                    let histogram = histograms.get(&metric).cloned().unwrap_or_default();
+                    let mut cumulative = 0;
+                    for (i, bucket) in self.buckets.iter().enumerate() {
+                        cumulative += histogram.bucket_counts.get(i).copied().unwrap_or_default();
+                        writeln!(output, "{name}_bucket{{le=\"{bucket}\"}} {cumulative}").unwrap();
+                    }
+                    writeln!(output, "{name}_bucket{{le=\"+Inf\"}} {}", histogram.count).unwrap();
+                    writeln!(output, "{name}_sum {}", histogram.sum).unwrap();
+                    writeln!(output, "{name}_count {}", histogram.count).unwrap();
+                }
+            }
+        }
+        output
+    }
+}
+
+impl MetricsRecorder for PrometheusMetricsRecorder {
+    fn increment_counter(&self, metric: Metric, value: u64) {
+        *self.counters.lock().entry(metric).or_default() += value;
+    }
+
+    fn record_histogram(&self, metric: Metric, value: f64) {
+        let mut histograms = self.histograms.lock();
+        let histogram = histograms
+            .entry(metric)
+            .or_insert_with(|| PrometheusHistogram {
+                bucket_counts: vec![0; self.buckets.len()],
+                ..Default::default()
+            });
+        if let Some(i) = self.buckets.iter().position(|bucket| value <= *bucket) {
+            histogram.bucket_counts[i] += 1;
+        }
+        histogram.sum += value;
+        histogram.count += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_prometheus() {
+        let recorder = PrometheusMetricsRecorder::new_with_buckets(vec![1.0, 0.1]);
+        recorder.increment_counter(Metric::ChunksRead, 2);
+        recorder.increment_counter(Metric::ChunksRead, 3);
+        recorder.record_histogram(Metric::StoreGetDuration, 0.0625);
+        recorder.record_histogram(Metric::StoreGetDuration, 0.5);
+        recorder.record_histogram(Metric::StoreGetDuration, 4.0);
+        assert_eq!(recorder.counter(Metric::ChunksRead), 5);
+        assert_eq!(recorder.counter(Metric::ChunksWritten), 0);
+        assert_eq!(recorder.histogram(Metric::StoreGetDuration), (3, 4.5625));
+
+        let render = recorder.render();
+        assert!(render.contains(
+            "# HELP zarrs_chunks_read_total The number of chunks read and decoded.\n\
+             # TYPE zarrs_chunks_read_total counter\n\
+             zarrs_chunks_read_total 5\n"
+        ));
+        assert!(render.contains(
+            "# TYPE zarrs_store_get_duration_seconds histogram\n\
+             zarrs_store_get_duration_seconds_bucket{le=\"0.1\"} 1\n\
+             zarrs_store_get_duration_seconds_bucket{le=\"1\"} 2\n\
+             zarrs_store_get_duration_seconds_bucket{le=\"+Inf\"} 3\n\
+             zarrs_store_get_duration_seconds_sum 4.5625\n\
+             zarrs_store_get_duration_seconds_count 3\n"
+        ));
+        assert!(render.contains("zarrs_store_set_duration_seconds_count 0\n"));
+    }
+}
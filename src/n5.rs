@@ -0,0 +1,1017 @@
+//! N5 compatibility.
+//!
+//! [`N5Dataset`] reads and writes [N5](https://github.com/saalfeldlab/n5) datasets through the storage traits of this crate.
+//! An N5 dataset is a directory with an `attributes.json` file (holding the `dimensions`, `blockSize`, `dataType`, and `compression` of the dataset) and blocks stored at `<path>/<i0>/<i1>/...`.
+//! Each block has a big endian header (mode, number of dimensions, and block shape) followed by the compressed big endian elements of the block.
+//!
+//! N5 lists dimensions fastest varying first, whereas this crate uses C order (slowest varying first).
+//! [`N5Dataset`] exposes shapes, block indices, and array subsets in C order, so `dimensions` and `blockSize` are reversed relative to `attributes.json`.
+//! Retrieved elements are in native byte order.
+//!
+//! Blocks with `raw` compression are always supported.
+//! `gzip`, `bzip2`, and `zstd` compression require the `gzip`, `bz2`, and `zstd` features respectively.
+//! Missing blocks are read as zeros.
+//!
+//! This module requires the `n5` feature.
+//!
+//! ### Example
+//! ```rust
+//! # use std::sync::Arc;
+//! use zarrs::array::DataType;
+//! use zarrs::array_subset::ArraySubset;
+//! use zarrs::n5::{N5Compression, N5DataType, N5Dataset, N5DatasetAttributes};
+//! # let store = Arc::new(zarrs::storage::store::MemoryStore::new());
+//! let attributes = N5DatasetAttributes::new(
+//!     vec![4, 3], // C order
+//!     vec![2, 2],
+//!     N5DataType::UInt16,
+//!     N5Compression::Raw,
+//! );
+//! let dataset = N5Dataset::create(store.clone(), "/volume", attributes)?;
+//! let subset = ArraySubset::new_with_ranges(&[1..3, 0..3]);
+//! dataset.store_array_subset(&subset, &(0u16..6).flat_map(u16::to_ne_bytes).collect::<Vec<_>>().into())?;
+//!
+//! let dataset = N5Dataset::open(store, "/volume")?;
+//! assert_eq!(dataset.shape(), vec![4, 3]);
+//! assert_eq!(dataset.data_type(), DataType::UInt16);
+//! let elements = dataset.retrieve_array_subset_elements::<u16>(&subset)?;
+//! assert_eq!(elements, vec![0, 1, 2, 3, 4, 5]);
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+#[cfg(any(feature = "gzip", feature = "bz2"))]
+use std::io::Read;
+use std::{num::NonZeroU64, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    array::{ArrayBytes, ArrayError, ArrayShape, ChunkShape, DataType, ElementOwned, RawBytes},
+    array_subset::ArraySubset,
+    node::{NodePath, NodePathError},
+    storage::{ReadableStorageTraits, ReadableWritableStorageTraits, StorageError, StoreKey},
+};
+
+/// The N5 version written to the root `attributes.json` by [`N5Dataset::create`].
+pub const N5_VERSION: &str = "4.0.0";
+
+/// The name of the N5 attributes file.
+const N5_ATTRIBUTES_FILE_NAME: &str = "attributes.json";
+
+/// The N5 block mode of blocks with a header holding the block shape.
+const N5_BLOCK_MODE_DEFAULT: u16 = 0;
+
+/// The N5 block mode of blocks with a header holding the block shape and the number of elements.
+const N5_BLOCK_MODE_VARLENGTH: u16 = 1;
+
+/// An N5 error.
+#[derive(Debug, Error)]
+pub enum N5Error {
+    /// An IO error.
+    #[error(transparent)]
+    IOError(#[from] std::io::Error),
+    /// A storage error.
+    #[error(transparent)]
+    StorageError(#[from] StorageError),
+    /// An invalid node path.
+    #[error(transparent)]
+    NodePathError(#[from] NodePathError),
+    /// An array error.
+    #[error(transparent)]
+    ArrayError(#[from] ArrayError),
+    /// The dataset attributes are missing.
+    #[error("N5 dataset attributes are missing at {_0}")]
+    MissingAttributes(StoreKey),
+    /// The dataset attributes are invalid.
+    #[error("N5 dataset attributes are invalid: {_0}")]
+    InvalidAttributes(String),
+    /// The compression is unsupported.
+    #[error("unsupported N5 compression: {_0}")]
+    UnsupportedCompression(String),
+    /// A block is invalid.
+    #[error("N5 block {_0} is invalid: {_1}")]
+    InvalidBlock(StoreKey, String),
+}
+
+/// An N5 data type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[allow(missing_docs)]
+pub enum N5DataType {
+    UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    Float32,
+    Float64,
+}
+
+impl N5DataType {
+    /// Return the equivalent [`DataType`].
+    #[must_use]
+    pub fn to_data_type(self) -> DataType {
+        match self {
+            Self::UInt8 => DataType::UInt8,
+            Self::UInt16 => DataType::UInt16,
+            Self::UInt32 => DataType::UInt32,
+            Self::UInt64 => DataType::UInt64,
+            Self::Int8 => DataType::Int8,
+            Self::Int16 => DataType::Int16,
+            Self::Int32 => DataType::Int32,
+            Self::Int64 => DataType::Int64,
+            Self::Float32 => DataType::Float32,
+            Self::Float64 => DataType::Float64,
+        }
+    }
+
+    /// Return the size of an element in bytes.
+    #[must_use]
+    pub const fn size(self) -> usize {
+        match self {
+            Self::UInt8 | Self::Int8 => 1,
+            Self::UInt16 | Self::Int16 => 2,
+            Self::UInt32 | Self::Int32 | Self::Float32 => 4,
+            Self::UInt64 | Self::Int64 | Self::Float64 => 8,
+        }
+    }
+}
+
+/// An N5 block compression.
+///
+/// Compressions other than `raw`, `gzip`, `bzip2`, and `zstd` deserialise to [`N5Compression::Unsupported`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum N5Compression {
+    /// Uncompressed.
+    Raw,
+    /// Gzip (or zlib) compression.
+    Gzip {
+        /// The compression level.
+        #[serde(default = "n5_gzip_default_level")]
+        level: i32,
+        /// Use zlib rather than gzip framing.
+        #[serde(default, rename = "useZlib")]
+        use_zlib: bool,
+    },
+    /// Bzip2 compression.
+    Bzip2 {
+        /// The block size in units of 100 kB.
+        #[serde(default = "n5_bzip2_default_block_size", rename = "blockSize")]
+        block_size: u32,
+    },
+    /// Zstandard compression.
+    Zstd {
+        /// The compression level.
+        #[serde(default)]
+        level: i32,
+    },
+    /// A compression that is not supported by this crate.
+    #[serde(other)]
+    Unsupported,
+}
+
+const fn n5_gzip_default_level() -> i32 {
+    -1
+}
+
+const fn n5_bzip2_default_block_size() -> u32 {
+    9
+}
+
+/// The attributes of an N5 dataset.
+///
+/// Shapes are in C order (slowest varying dimension first), and are reversed when serialised.
+#[derive(Debug, Clone, PartialEq)]
+pub struct N5DatasetAttributes {
+    shape: ArrayShape,
+    block_shape: ArrayShape,
+    data_type: N5DataType,
+    compression: N5Compression,
+    attributes: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The serialised form of [`N5DatasetAttributes`].
+#[derive(Serialize, Deserialize)]
+struct N5DatasetAttributesJson {
+    dimensions: Vec<u64>,
+    #[serde(rename = "blockSize")]
+    block_size: Vec<u64>,
+    #[serde(rename = "dataType")]
+    data_type: N5DataType,
+    compression: N5Compression,
+    #[serde(flatten)]
+    attributes: serde_json::Map<String, serde_json::Value>,
+}
+
+impl N5DatasetAttributes {
+    /// Create new N5 dataset attributes with a `shape` and `block_shape` in C order.
+    #[must_use]
+    pub fn new(
+        shape: ArrayShape,
+        block_shape: ArrayShape,
+        data_type: N5DataType,
+        compression: N5Compression,
+    ) -> Self {
+        Self {
+            shape,
+            block_shape,
+            data_type,
+            compression,
+            attributes: serde_json::Map::default(),
+        }
+    }
+
+    /// Return the shape of the dataset in C order.
+    #[must_use]
+    pub fn shape(&self) -> &[u64] {
+        &self.shape
+    }
+
+    /// Return the block shape of the dataset in C order.
+    #[must_use]
+    pub fn block_shape(&self) -> &[u64] {
+        &self.block_shape
+    }
+
+    /// Return the data type.
+    #[must_use]
+    pub const fn data_type(&self) -> N5DataType {
+        self.data_type
+    }
+
+    /// Return the compression.
+    #[must_use]
+    pub const fn compression(&self) -> &N5Compression {
+        &self.compression
+    }
+
+    /// Return the user attributes stored alongside the dataset attributes.
+    #[must_use]
+    pub const fn attributes(&self) -> &serde_json::Map<String, serde_json::Value> {
+        &self.attributes
+    }
+
+    /// Return a mutable reference to the user attributes stored alongside the dataset attributes.
+    #[must_use]
+    pub fn attributes_mut(&mut self) -> &mut serde_json::Map<String, serde_json::Value> {
+        &mut self.attributes
+    }
+
+    fn validate(&self) -> Result<(), N5Error> {
+        // N5 lists dimensions in F order
+        let dimensions: Vec<u64> = self.shape.iter().rev().copied().collect();
+        let block_size: Vec<u64> = self.block_shape.iter().rev().copied().collect();
+        if dimensions.len() != block_size.len() {
+            return Err(N5Error::InvalidAttributes(format!(
+                "dimensions {dimensions:?} and blockSize {block_size:?} have different lengths"
+            )));
+        }
+        if block_size.contains(&0) {
+            return Err(N5Error::InvalidAttributes(format!(
+                "blockSize {block_size:?} has a zero element"
+            )));
+        }
+        Ok(())
+    }
+
+    fn from_json(json: N5DatasetAttributesJson) -> Result<Self, N5Error> {
+        let attributes = Self {
+            shape: json.dimensions.into_iter().rev().collect(),
+            block_shape: json.block_size.into_iter().rev().collect(),
+            data_type: json.data_type,
+            compression: json.compression,
+            attributes: json.attributes,
+        };
+        attributes.validate()?;
+        Ok(attributes)
+    }
+
+    fn to_json(&self) -> N5DatasetAttributesJson {
+        N5DatasetAttributesJson {
+            dimensions: self.shape.iter().rev().copied().collect(),
+            block_size: self.block_shape.iter().rev().copied().collect(),
+            data_type: self.data_type,
+            compression: self.compression.clone(),
+            attributes: self.attributes.clone(),
+        }
+    }
+}
+
+/// Return the key of `name` in the N5 node at `path`.
+fn n5_key(path: &NodePath, name: &str) -> Result<StoreKey, N5Error> {
+    let path = path.as_str().trim_matches('/');
+    if path.is_empty() {
+        Ok(StoreKey::new(name).map_err(StorageError::from)?)
+    } else {
+        Ok(StoreKey::new(format!("{path}/{name}")).map_err(StorageError::from)?)
+    }
+}
+
+/// Swap the byte order of each element in `bytes` between native and big endian.
+fn swap_big_endian(bytes: &mut [u8], element_size: usize) {
+    if cfg!(target_endian = "little") && element_size > 1 {
+        for element in bytes.chunks_exact_mut(element_size) {
+            element.reverse();
+        }
+    }
+}
+
+/// Copy `subset_bytes` into the `subset` of `output_bytes` with shape `output_shape`.
+fn copy_subset_bytes(
+    output_bytes: &mut [u8],
+    output_shape: &[u64],
+    subset_bytes: &[u8],
+    subset: &ArraySubset,
+    element_size: usize,
+) -> Result<(), N5Error> {
+    let contiguous_indices = subset
+        .contiguous_linearised_indices(output_shape)
+        .map_err(|_| ArrayError::InvalidArraySubset(subset.clone(), output_shape.to_vec()))?;
+    let length = contiguous_indices.contiguous_elements_usize() * element_size;
+    let mut subset_offset = 0;
+    for (index, _num_elements) in &contiguous_indices {
+        let output_offset = usize::try_from(index).unwrap() * element_size;
+        output_bytes[output_offset..output_offset + length]
+            .copy_from_slice(&subset_bytes[subset_offset..subset_offset + length]);
+        subset_offset += length;
+    }
+    Ok(())
+}
+
+/// An N5 dataset.
+#[derive(Debug)]
+pub struct N5Dataset<TStorage: ?Sized> {
+    storage: Arc<TStorage>,
+    path: NodePath,
+    attributes: N5DatasetAttributes,
+}
+
+impl<TStorage: ?Sized> N5Dataset<TStorage> {
+    /// Return the node path of the dataset.
+    #[must_use]
+    pub const fn path(&self) -> &NodePath {
+        &self.path
+    }
+
+    /// Return the dataset attributes.
+    #[must_use]
+    pub const fn attributes(&self) -> &N5DatasetAttributes {
+        &self.attributes
+    }
+
+    /// Return the shape of the dataset in C order.
+    #[must_use]
+    pub fn shape(&self) -> ArrayShape {
+        self.attributes.shape.clone()
+    }
+
+    /// Return the block shape of the dataset in C order.
+    ///
+    /// # Panics
+    /// Panics if the block shape has a zero element, which is rejected when the dataset is opened or created.
+    #[must_use]
+    pub fn block_shape(&self) -> ChunkShape {
+        self.attributes
+            .block_shape
+            .iter()
+            .map(|&size| NonZeroU64::new(size).expect("validated block shape"))
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// Return the data type of the dataset.
+    #[must_use]
+    pub fn data_type(&self) -> DataType {
+        self.attributes.data_type.to_data_type()
+    }
+
+    /// Return the store key of the block at `block_indices` (in C order).
+    ///
+    /// # Errors
+    /// Returns an error if the dimensionality of `block_indices` does not match the dataset.
+    pub fn block_key(&self, block_indices: &[u64]) -> Result<StoreKey, N5Error> {
+        if block_indices.len() != self.attributes.shape.len() {
+            return Err(ArrayError::InvalidChunkGridIndicesError(block_indices.to_vec()).into());
+        }
+        let name = block_indices
+            .iter()
+            .rev()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("/");
+        n5_key(&self.path, &name)
+    }
+
+    /// Return the array subset of the block at `block_indices` (in C order), bounded by the shape of the dataset.
+    ///
+    /// # Errors
+    /// Returns an error if `block_indices` are out of bounds.
+    pub fn block_subset(&self, block_indices: &[u64]) -> Result<ArraySubset, N5Error> {
+        let in_bounds = block_indices.len() == self.attributes.shape.len()
+            && std::iter::zip(
+                block_indices,
+                std::iter::zip(&self.attributes.shape, &self.attributes.block_shape),
+            )
+            .all(|(index, (size, block_size))| index * block_size < *size);
+        if !in_bounds {
+            return Err(ArrayError::InvalidChunkGridIndicesError(block_indices.to_vec()).into());
+        }
+        let ranges = std::iter::zip(
+            block_indices,
+            std::iter::zip(&self.attributes.shape, &self.attributes.block_shape),
+        )
+        .map(|(index, (size, block_size))| {
+            let start = index * block_size;
+            start..(start + block_size).min(*size)
+        })
+        .collect::<Vec<_>>();
+        Ok(ArraySubset::new_with_ranges(&ranges))
+    }
+
+    fn decompress(&self, key: &StoreKey, encoded: &[u8]) -> Result<Vec<u8>, N5Error> {
+        match &self.attributes.compression {
+            N5Compression::Raw => Ok(encoded.to_vec()),
+            #[cfg(feature = "gzip")]
+            N5Compression::Gzip { use_zlib, .. } => {
+                let mut decoded = Vec::new();
+                if *use_zlib {
+                    flate2::read::ZlibDecoder::new(encoded).read_to_end(&mut decoded)?;
+                } else {
+                    flate2::read::GzDecoder::new(encoded).read_to_end(&mut decoded)?;
+                }
+                Ok(decoded)
+            }
+            #[cfg(feature = "bz2")]
+            N5Compression::Bzip2 { .. } => {
+                let mut decoded = Vec::new();
+                bzip2::read::BzDecoder::new(encoded).read_to_end(&mut decoded)?;
+                Ok(decoded)
+            }
+            #[cfg(feature = "zstd")]
+            N5Compression::Zstd { .. } => Ok(zstd::decode_all(encoded)?),
+            compression => Err(N5Error::UnsupportedCompression(format!(
+                "{compression:?} (block {key})"
+            ))),
+        }
+    }
+
+    fn compress(&self, decoded: Vec<u8>) -> Result<Vec<u8>, N5Error> {
+        match &self.attributes.compression {
+            N5Compression::Raw => Ok(decoded),
+            #[cfg(feature = "gzip")]
+            N5Compression::Gzip { level, use_zlib } => {
+                let level = u32::try_from(*level)
+                    .map_or_else(|_| flate2::Compression::default(), flate2::Compression::new);
+                let mut encoded = Vec::new();
+                if *use_zlib {
+                    flate2::read::ZlibEncoder::new(decoded.as_slice(), level)
+                        .read_to_end(&mut encoded)?;
+                } else {
+                    flate2::read::GzEncoder::new(decoded.as_slice(), level)
+                        .read_to_end(&mut encoded)?;
+                }
+                Ok(encoded)
+            }
+            #[cfg(feature = "bz2")]
+            N5Compression::Bzip2 { block_size } => {
+                let mut encoded = Vec::new();
+                bzip2::read::BzEncoder::new(
+                    decoded.as_slice(),
+                    bzip2::Compression::new((*block_size).clamp(1, 9)),
+                )
+                .read_to_end(&mut encoded)?;
+                Ok(encoded)
+            }
+            #[cfg(feature = "zstd")]
+            N5Compression::Zstd { level } => Ok(zstd::encode_all(decoded.as_slice(), *level)?),
+            compression => Err(N5Error::UnsupportedCompression(format!("{compression:?}"))),
+        }
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits> N5Dataset<TStorage> {
+    /// Open the N5 dataset at `path` in `storage`.
+    ///
+    /// # Errors
+    /// Returns an [`N5Error`] if the path is invalid, the attributes are missing or invalid, or there is an underlying store error.
+    pub fn open(storage: Arc<TStorage>, path: &str) -> Result<Self, N5Error> {
+        let path = NodePath::new(path)?;
+        let key = n5_key(&path, N5_ATTRIBUTES_FILE_NAME)?;
+        let attributes = storage
+            .get(&key)?
+            .ok_or_else(|| N5Error::MissingAttributes(key.clone()))?;
+        let attributes: N5DatasetAttributesJson = serde_json::from_slice(&attributes)
+            .map_err(|err| StorageError::InvalidMetadata(key, err.to_string()))?;
+        Ok(Self {
+            storage,
+            path,
+            attributes: N5DatasetAttributes::from_json(attributes)?,
+        })
+    }
+
+    /// Retrieve the block at `block_indices` (in C order).
+    ///
+    /// The block is bounded by the shape of the dataset (see [`block_subset`](N5Dataset::block_subset)) and its elements are in native byte order.
+    /// Returns [`None`] if the block does not exist.
+    ///
+    /// # Errors
+    /// Returns an [`N5Error`] if `block_indices` are out of bounds, the block is invalid, or there is an underlying store error.
+    ///
+    /// # Panics
+    /// Panics if the number of elements in the block exceeds [`usize::MAX`].
+    pub fn retrieve_block(
+        &self,
+        block_indices: &[u64],
+    ) -> Result<Option<ArrayBytes<'static>>, N5Error> {
+        let block_subset = self.block_subset(block_indices)?;
+        let key = self.block_key(block_indices)?;
+        let Some(encoded) = self.storage.get(&key)? else {
+            return Ok(None);
+        };
+        let invalid = |message: &str| N5Error::InvalidBlock(key.clone(), message.to_string());
+
+        // Header: mode (u16), number of dimensions (u16), block shape (u32 per dimension), and the number of elements (u32) in varlength mode
+        let read_u16 = |offset: usize| -> Result<u16, N5Error> {
+            encoded
+                .get(offset..offset + 2)
+                .map(|bytes| u16::from_be_bytes(bytes.try_into().unwrap()))
+                .ok_or_else(|| invalid("truncated header"))
+        };
+        let read_u32 = |offset: usize| -> Result<u32, N5Error> {
+            encoded
+                .get(offset..offset + 4)
+                .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+                .ok_or_else(|| invalid("truncated header"))
+        };
+        let mode = read_u16(0)?;
+        let dimensionality = usize::from(read_u16(2)?);
+        if dimensionality != block_subset.dimensionality() {
+            return Err(invalid("header dimensionality does not match the dataset"));
+        }
+        let mut encoded_shape = (0..dimensionality)
+            .map(|i| read_u32(4 + 4 * i).map(u64::from))
+            .collect::<Result<Vec<_>, _>>()?;
+        encoded_shape.reverse();
+        let mut offset = 4 + 4 * dimensionality;
+        match mode {
+            N5_BLOCK_MODE_DEFAULT => {}
+            N5_BLOCK_MODE_VARLENGTH => offset += 4,
+            _ => return Err(invalid(&format!("unsupported block mode {mode}"))),
+        }
+        if std::iter::zip(&encoded_shape, block_subset.shape())
+            .any(|(encoded, bounded)| encoded < bounded)
+        {
+            return Err(invalid("header block shape is smaller than the block"));
+        }
+
+        let element_size = self.attributes.data_type.size();
+        let mut decoded = self.decompress(&key, encoded.get(offset..).unwrap_or_default())?;
+        let num_elements = usize::try_from(encoded_shape.iter().product::<u64>()).unwrap();
+        if decoded.len() < num_elements * element_size {
+            return Err(invalid("decoded block is too small"));
+        }
+        decoded.truncate(num_elements * element_size);
+        swap_big_endian(&mut decoded, element_size);
+
+        let decoded = ArrayBytes::new_flen(decoded);
+        if encoded_shape == block_subset.shape() {
+            Ok(Some(decoded))
+        } else {
+            let bounded = ArraySubset::new_with_shape(block_subset.shape().to_vec());
+            Ok(Some(
+                decoded
+                    .extract_array_subset(&bounded, &encoded_shape, &self.data_type())
+                    .map_err(ArrayError::from)?
+                    .into_owned(),
+            ))
+        }
+    }
+
+    /// Retrieve the `array_subset` (in C order) of the dataset.
+    ///
+    /// Elements are in native byte order, and elements in missing blocks are zero.
+    ///
+    /// # Errors
+    /// Returns an [`N5Error`] if `array_subset` is out of bounds, a block is invalid, or there is an underlying store error.
+    pub fn retrieve_array_subset(
+        &self,
+        array_subset: &ArraySubset,
+    ) -> Result<ArrayBytes<'static>, N5Error> {
+        if !array_subset.inbounds(&self.attributes.shape) {
+            return Err(ArrayError::InvalidArraySubset(
+                array_subset.clone(),
+                self.attributes.shape.clone(),
+            )
+            .into());
+        }
+        let element_size = self.attributes.data_type.size();
+        let mut output = vec![0; array_subset.num_elements_usize() * element_size];
+        let blocks = array_subset
+            .chunks(&self.block_shape())
+            .map_err(ArrayError::from)?;
+        for (block_indices, _) in &blocks {
+            let Some(block_bytes) = self.retrieve_block(&block_indices)? else {
+                continue;
+            };
+            let block_subset = self.block_subset(&block_indices)?;
+            let overlap = array_subset
+                .overlap(&block_subset)
+                .map_err(ArrayError::from)?;
+            let overlap_in_block = overlap
+                .relative_to(block_subset.start())
+                .map_err(ArrayError::from)?;
+            let overlap_bytes = block_bytes
+                .extract_array_subset(&overlap_in_block, block_subset.shape(), &self.data_type())
+                .map_err(ArrayError::from)?
+                .into_fixed()
+                .map_err(ArrayError::from)?;
+            let overlap_in_output = overlap
+                .relative_to(array_subset.start())
+                .map_err(ArrayError::from)?;
+            copy_subset_bytes(
+                &mut output,
+                array_subset.shape(),
+                &overlap_bytes,
+                &overlap_in_output,
+                element_size,
+            )?;
+        }
+        Ok(ArrayBytes::new_flen(output))
+    }
+
+    /// Retrieve the `array_subset` (in C order) of the dataset as a vector of elements.
+    ///
+    /// # Errors
+    /// Returns an [`N5Error`] if the element type is incompatible with the data type or [`retrieve_array_subset`](N5Dataset::retrieve_array_subset) fails.
+    pub fn retrieve_array_subset_elements<T: ElementOwned>(
+        &self,
+        array_subset: &ArraySubset,
+    ) -> Result<Vec<T>, N5Error> {
+        let bytes = self.retrieve_array_subset(array_subset)?;
+        Ok(T::from_array_bytes(&self.data_type(), bytes)?)
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits> N5Dataset<TStorage> {
+    /// Create an N5 dataset at `path` in `storage` with `attributes`.
+    ///
+    /// The N5 version is written to the root `attributes.json` if it is not already present.
+    ///
+    /// # Errors
+    /// Returns an [`N5Error`] if the path or attributes are invalid, or there is an underlying store error.
+    pub fn create(
+        storage: Arc<TStorage>,
+        path: &str,
+        attributes: N5DatasetAttributes,
+    ) -> Result<Self, N5Error> {
+        let path = NodePath::new(path)?;
+        attributes.validate()?;
+
+        let root_key = n5_key(&NodePath::root(), N5_ATTRIBUTES_FILE_NAME)?;
+        let mut root_attributes: serde_json::Map<String, serde_json::Value> = match storage
+            .get(&root_key)?
+        {
+            Some(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|err| StorageError::InvalidMetadata(root_key.clone(), err.to_string()))?,
+            None => serde_json::Map::default(),
+        };
+        if !root_attributes.contains_key("n5") {
+            root_attributes.insert("n5".to_string(), N5_VERSION.into());
+            storage.set(
+                &root_key,
+                serde_json::to_vec_pretty(&root_attributes)
+                    .map_err(|err| StorageError::Other(err.to_string()))?
+                    .into(),
+            )?;
+        }
+
+        let dataset = Self {
+            storage,
+            path,
+            attributes,
+        };
+        dataset.store_attributes()?;
+        Ok(dataset)
+    }
+
+    /// Store the dataset attributes.
+    ///
+    /// In the root node, the N5 version is preserved.
+    ///
+    /// # Errors
+    /// Returns an [`N5Error`] if there is an underlying store error.
+    pub fn store_attributes(&self) -> Result<(), N5Error> {
+        let key = n5_key(&self.path, N5_ATTRIBUTES_FILE_NAME)?;
+        let mut json = self.attributes.to_json();
+        if self.path.as_str() == "/" && !json.attributes.contains_key("n5") {
+            json.attributes.insert("n5".to_string(), N5_VERSION.into());
+        }
+        let json =
+            serde_json::to_vec_pretty(&json).map_err(|err| StorageError::Other(err.to_string()))?;
+        self.storage.set(&key, json.into())?;
+        Ok(())
+    }
+
+    /// Return a mutable reference to the user attributes of the dataset.
+    ///
+    /// Changes are persisted with [`store_attributes`](N5Dataset::store_attributes).
+    #[must_use]
+    pub fn user_attributes_mut(&mut self) -> &mut serde_json::Map<String, serde_json::Value> {
+        self.attributes.attributes_mut()
+    }
+
+    /// Store the block at `block_indices` (in C order).
+    ///
+    /// `block_bytes` must hold the elements of the block bounded by the shape of the dataset (see [`block_subset`](N5Dataset::block_subset)) in native byte order.
+    ///
+    /// # Errors
+    /// Returns an [`N5Error`] if `block_indices` are out of bounds, `block_bytes` has an unexpected length, or there is an underlying store error.
+    pub fn store_block(&self, block_indices: &[u64], block_bytes: &[u8]) -> Result<(), N5Error> {
+        let block_subset = self.block_subset(block_indices)?;
+        let element_size = self.attributes.data_type.size();
+        let expected_size = block_subset.num_elements() * element_size as u64;
+        if block_bytes.len() as u64 != expected_size {
+            return Err(ArrayError::InvalidBytesInputSize(block_bytes.len(), expected_size).into());
+        }
+        let key = self.block_key(block_indices)?;
+
+        let mut decoded = block_bytes.to_vec();
+        swap_big_endian(&mut decoded, element_size);
+        let encoded = self.compress(decoded)?;
+
+        let dimensionality = block_subset.dimensionality();
+        let mut bytes = Vec::with_capacity(4 + 4 * dimensionality + encoded.len());
+        bytes.extend_from_slice(&N5_BLOCK_MODE_DEFAULT.to_be_bytes());
+        bytes.extend_from_slice(
+            &u16::try_from(dimensionality)
+                .map_err(|_| N5Error::InvalidBlock(key.clone(), "too many dimensions".to_string()))?
+                .to_be_bytes(),
+        );
+        for &size in block_subset.shape().iter().rev() {
+            let size = u32::try_from(size).map_err(|_| {
+                N5Error::InvalidBlock(key.clone(), "block shape exceeds u32::MAX".to_string())
+            })?;
+            bytes.extend_from_slice(&size.to_be_bytes());
+        }
+        bytes.extend_from_slice(&encoded);
+        self.storage.set(&key, bytes.into())?;
+        Ok(())
+    }
+
+    /// Store `subset_bytes` in the `array_subset` (in C order) of the dataset.
+    ///
+    /// `subset_bytes` must hold the elements of the subset in native byte order.
+    /// Blocks that are partially covered by the subset are read, updated, and written back.
+    ///
+    /// # Errors
+    /// Returns an [`N5Error`] if `array_subset` is out of bounds, `subset_bytes` has an unexpected length, a block is invalid, or there is an underlying store error.
+    pub fn store_array_subset(
+        &self,
+        array_subset: &ArraySubset,
+        subset_bytes: &RawBytes,
+    ) -> Result<(), N5Error> {
+        if !array_subset.inbounds(&self.attributes.shape) {
+            return Err(ArrayError::InvalidArraySubset(
+                array_subset.clone(),
+                self.attributes.shape.clone(),
+            )
+            .into());
+        }
+        let element_size = self.attributes.data_type.size();
+        let expected_size = array_subset.num_elements() * element_size as u64;
+        if subset_bytes.len() as u64 != expected_size {
+            return Err(
+                ArrayError::InvalidBytesInputSize(subset_bytes.len(), expected_size).into(),
+            );
+        }
+        let subset_array_bytes = ArrayBytes::new_flen(subset_bytes.as_ref());
+
+        let blocks = array_subset
+            .chunks(&self.block_shape())
+            .map_err(ArrayError::from)?;
+        for (block_indices, _) in &blocks {
+            let block_subset = self.block_subset(&block_indices)?;
+            let overlap = array_subset
+                .overlap(&block_subset)
+                .map_err(ArrayError::from)?;
+            let overlap_bytes = subset_array_bytes
+                .extract_array_subset(
+                    &overlap
+                        .relative_to(array_subset.start())
+                        .map_err(ArrayError::from)?,
+                    array_subset.shape(),
+                    &self.data_type(),
+                )
+                .map_err(ArrayError::from)?
+                .into_fixed()
+                .map_err(ArrayError::from)?;
+            if overlap == block_subset {
+                self.store_block(&block_indices, &overlap_bytes)?;
+            } else {
+                let mut block_bytes = match self.retrieve_block(&block_indices)? {
+                    Some(block_bytes) => block_bytes
+                        .into_fixed()
+                        .map_err(ArrayError::from)?
+                        .into_owned(),
+                    None => vec![0; block_subset.num_elements_usize() * element_size],
+                };
+                copy_subset_bytes(
+                    &mut block_bytes,
+                    block_subset.shape(),
+                    &overlap_bytes,
+                    &overlap
+                        .relative_to(block_subset.start())
+                        .map_err(ArrayError::from)?,
+                    element_size,
+                )?;
+                self.store_block(&block_indices, &block_bytes)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::{store::MemoryStore, WritableStorageTraits};
+
+    use super::*;
+
+    fn attributes_json() -> &'static str {
+        r#"{
+            "dimensions": [5, 4],
+            "blockSize": [3, 2],
+            "dataType": "uint16",
+            "compression": { "type": "raw" },
+            "resolution": [1.0, 2.0]
+        }"#
+    }
+
+    fn block_bytes(n5_shape: &[u32], elements: &[u16]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&0u16.to_be_bytes());
+        bytes.extend_from_slice(&u16::try_from(n5_shape.len()).unwrap().to_be_bytes());
+        for size in n5_shape {
+            bytes.extend_from_slice(&size.to_be_bytes());
+        }
+        for element in elements {
+            bytes.extend_from_slice(&element.to_be_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn n5_attributes() {
+        let json: N5DatasetAttributesJson = serde_json::from_str(attributes_json()).unwrap();
+        let attributes = N5DatasetAttributes::from_json(json).unwrap();
+        assert_eq!(attributes.shape(), &[4, 5]);
+        assert_eq!(attributes.block_shape(), &[2, 3]);
+        assert_eq!(attributes.data_type(), N5DataType::UInt16);
+        assert_eq!(attributes.compression(), &N5Compression::Raw);
+        assert!(attributes.attributes().contains_key("resolution"));
+
+        let compression: N5Compression =
+            serde_json::from_str(r#"{ "type": "gzip", "level": 6 }"#).unwrap();
+        assert_eq!(
+            compression,
+            N5Compression::Gzip {
+                level: 6,
+                use_zlib: false
+            }
+        );
+        let compression: N5Compression =
+            serde_json::from_str(r#"{ "type": "lz4", "blockSize": 65536 }"#).unwrap();
+        assert_eq!(compression, N5Compression::Unsupported);
+    }
+
+    #[test]
+    fn n5_read() {
+        let store = Arc::new(MemoryStore::new());
+        store
+            .set(
+                &StoreKey::new("volume/attributes.json").unwrap(),
+                attributes_json().as_bytes().to_vec().into(),
+            )
+            .unwrap();
+        // Block (0, 0) in N5 order is full, block (1, 1) is truncated at the edge
+        store
+            .set(
+                &StoreKey::new("volume/0/0").unwrap(),
+                block_bytes(&[3, 2], &[0, 1, 2, 5, 6, 7]).into(),
+            )
+            .unwrap();
+        store
+            .set(
+                &StoreKey::new("volume/1/1").unwrap(),
+                block_bytes(&[2, 2], &[13, 14, 18, 19]).into(),
+            )
+            .unwrap();
+
+        let dataset = N5Dataset::open(store, "/volume").unwrap();
+        assert_eq!(dataset.shape(), vec![4, 5]);
+        assert_eq!(dataset.data_type(), DataType::UInt16);
+        assert_eq!(
+            dataset.block_key(&[1, 0]).unwrap(),
+            StoreKey::new("volume/0/1").unwrap()
+        );
+        assert_eq!(
+            dataset.block_subset(&[1, 1]).unwrap(),
+            ArraySubset::new_with_ranges(&[2..4, 3..5])
+        );
+        assert!(dataset.block_subset(&[2, 0]).is_err());
+
+        let elements = dataset
+            .retrieve_array_subset_elements::<u16>(&ArraySubset::new_with_ranges(&[0..4, 0..5]))
+            .unwrap();
+        assert_eq!(
+            elements,
+            vec![
+                0, 1, 2, 0, 0, //
+                5, 6, 7, 0, 0, //
+                0, 0, 0, 13, 14, //
+                0, 0, 0, 18, 19, //
+            ]
+        );
+        assert!(dataset.retrieve_block(&[0, 1]).unwrap().is_none());
+    }
+
+    #[test]
+    fn n5_write_read() {
+        let store = Arc::new(MemoryStore::new());
+        let attributes = N5DatasetAttributes::new(
+            vec![4, 5],
+            vec![2, 3],
+            N5DataType::Float32,
+            N5Compression::Raw,
+        );
+        let dataset = N5Dataset::create(store.clone(), "/volume", attributes).unwrap();
+        assert!(store
+            .get(&StoreKey::new("attributes.json").unwrap())
+            .unwrap()
+            .is_some());
+
+        let subset = ArraySubset::new_with_ranges(&[1..4, 2..5]);
+        let elements = (0u8..9).map(f32::from).collect::<Vec<_>>();
+        let bytes = elements
+            .iter()
+            .flat_map(|element| element.to_ne_bytes())
+            .collect::<Vec<_>>();
+        dataset.store_array_subset(&subset, &bytes.into()).unwrap();
+
+        // The edge block is truncated and big endian
+        let block = store
+            .get(&StoreKey::new("volume/1/1").unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(&block[..12], &[0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0, 2]);
+        assert_eq!(&block[12..16], &4.0f32.to_be_bytes());
+
+        let dataset = N5Dataset::open(store, "/volume").unwrap();
+        assert_eq!(
+            dataset
+                .retrieve_array_subset_elements::<f32>(&subset)
+                .unwrap(),
+            elements
+        );
+        assert_eq!(
+            dataset
+                .retrieve_array_subset_elements::<f32>(&ArraySubset::new_with_ranges(&[0..2, 0..5]))
+                .unwrap(),
+            vec![0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 2.0]
+        );
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn n5_gzip() {
+        let store = Arc::new(MemoryStore::new());
+        let attributes = N5DatasetAttributes::new(
+            vec![3, 3],
+            vec![2, 2],
+            N5DataType::Int64,
+            N5Compression::Gzip {
+                level: 5,
+                use_zlib: false,
+            },
+        );
+        let dataset = N5Dataset::create(store, "/", attributes).unwrap();
+        let subset = ArraySubset::new_with_ranges(&[0..3, 0..3]);
+        let elements = (-4i64..5).collect::<Vec<_>>();
+        let bytes = elements
+            .iter()
+            .flat_map(|element| element.to_ne_bytes())
+            .collect::<Vec<_>>();
+        dataset.store_array_subset(&subset, &bytes.into()).unwrap();
+        assert_eq!(
+            dataset
+                .retrieve_array_subset_elements::<i64>(&subset)
+                .unwrap(),
+            elements
+        );
+    }
+}
@@ -5,25 +5,45 @@
 //! A [`Node`] has an associated [`NodePath`], [`NodeMetadata`], and children.
 //!
 //! The [`Node::hierarchy_tree`] function can be used to create a string representation of a the hierarchy below a node.
+//! The [`Node::hierarchy_tree_opt`] function can additionally annotate arrays with chunk counts, stored and uncompressed sizes, and codecs.
+//! The [`Node::size_info`] function can be used to compute the stored and uncompressed size of the hierarchy below a node.
+//! The [`Node::find`] and [`Node::find_glob`] functions can be used to search the hierarchy below a node.
 
+mod node_copy_options;
+mod node_find;
+mod node_hierarchy_tree;
 mod node_metadata;
+mod node_modify;
 mod node_name;
 mod node_path;
+mod node_size_info;
 
-use std::sync::Arc;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
 
+pub use node_copy_options::{NodeCopyOptions, NodeCopyProgress, NodeCopyProgressCallback};
+pub use node_hierarchy_tree::NodeHierarchyTreeOptions;
 pub use node_metadata::NodeMetadata;
 pub use node_name::{NodeName, NodeNameError};
 pub use node_path::{NodePath, NodePathError};
+pub use node_size_info::NodeSizeInfo;
 use thiserror::Error;
 
+use node_hierarchy_tree::node_description;
+
 use crate::{
     array::ArrayMetadata,
-    group::GroupMetadataV3,
-    metadata::{ArrayMetadataV2, GroupMetadata, GroupMetadataV2, MetadataRetrieveVersion},
+    group::{GroupConsolidateMetadataError, GroupCreateError, GroupMetadataV3},
+    metadata::{
+        ArrayMetadataV2, ConsolidatedMetadata, ConsolidatedMetadataKind, ConsolidatedMetadataV2,
+        GroupMetadata, GroupMetadataV2, ImplicitGroupPolicy, MetadataRetrieveVersion,
+    },
     storage::{
-        get_child_nodes, meta_key, meta_key_v2_array, meta_key_v2_attributes, meta_key_v2_group,
-        ListableStorageTraits, ReadableStorageTraits, StorageError,
+        get_child_nodes, meta_key, meta_key_v2_array, meta_key_v2_attributes,
+        meta_key_v2_consolidated, meta_key_v2_group, ListableStorageTraits, ReadableStorageTraits,
+        StorageError, StoreKey,
     },
 };
 
@@ -59,11 +79,42 @@ pub enum NodeCreateError {
     /// Metadata version mismatch
     #[error("Found V2 metadata in V3 key or vice-versa")]
     MetadataVersionMismatch,
-    /// Missing metadata (Zarr V2 only).
-    #[error("group metadata is missing (Zarr V2 only)")]
+    /// Missing metadata.
+    ///
+    /// Zarr V2 groups require metadata, as do Zarr V3 groups if implicit groups are disallowed by the [`ImplicitGroupPolicy`].
+    #[error("group metadata is missing")]
     MissingMetadata,
 }
 
+/// A node modification error.
+#[derive(Debug, Error)]
+pub enum NodeModifyError {
+    /// An invalid node path.
+    #[error(transparent)]
+    NodePathError(#[from] NodePathError),
+    /// An invalid node name.
+    #[error(transparent)]
+    NodeNameError(#[from] NodeNameError),
+    /// A storage error.
+    #[error(transparent)]
+    StorageError(#[from] StorageError),
+    /// A group creation error.
+    #[error(transparent)]
+    GroupCreateError(#[from] GroupCreateError),
+    /// A group metadata consolidation error.
+    #[error(transparent)]
+    GroupConsolidateMetadataError(#[from] GroupConsolidateMetadataError),
+    /// A node already exists at the destination.
+    #[error("a node already exists at {0}")]
+    DestinationExists(NodePath),
+    /// The destination is the root node, the node itself, or below the node.
+    #[error("invalid destination {0}")]
+    InvalidDestination(NodePath),
+    /// The group has children and the operation is not recursive.
+    #[error("group {0} is not empty")]
+    NotEmpty(NodePath),
+}
+
 impl Node {
     fn get_metadata<TStorage: ?Sized + ReadableStorageTraits + ListableStorageTraits>(
         storage: &Arc<TStorage>,
@@ -88,38 +139,19 @@ impl Node {
         }
 
         if let MetadataRetrieveVersion::Default | MetadataRetrieveVersion::V2 = version {
-            // Try a Zarr V2 array
-            let array_key = meta_key_v2_array(path);
-            let attributes_key = meta_key_v2_attributes(path);
-            if let Some(metadata) = storage.get(&array_key)? {
-                let mut metadata: ArrayMetadataV2 = serde_json::from_slice(&metadata)
-                    .map_err(|err| StorageError::InvalidMetadata(array_key, err.to_string()))?;
-                let attributes = storage.get(&attributes_key)?;
-                if let Some(attributes) = attributes {
-                    metadata.attributes = serde_json::from_slice(&attributes).map_err(|err| {
-                        StorageError::InvalidMetadata(attributes_key, err.to_string())
-                    })?;
-                }
-                return Ok(NodeMetadata::Array(ArrayMetadata::V2(metadata)));
-            }
-
-            // Try a Zarr V2 group
-            let group_key = meta_key_v2_group(path);
-            if let Some(metadata) = storage.get(&group_key)? {
-                let mut metadata: GroupMetadataV2 = serde_json::from_slice(&metadata)
-                    .map_err(|err| StorageError::InvalidMetadata(group_key, err.to_string()))?;
-                let attributes = storage.get(&attributes_key)?;
-                if let Some(attributes) = attributes {
-                    metadata.attributes = serde_json::from_slice(&attributes).map_err(|err| {
-                        StorageError::InvalidMetadata(attributes_key, err.to_string())
-                    })?;
-                }
-                return Ok(NodeMetadata::Group(GroupMetadata::V2(metadata)));
+            if let Some(metadata) = Self::get_metadata_v2(storage, path)? {
+                return Ok(metadata);
             }
         }
 
         // No metadata has been found
         match version {
+            MetadataRetrieveVersion::Default | MetadataRetrieveVersion::V3
+                if ImplicitGroupPolicy::default() == ImplicitGroupPolicy::Error =>
+            {
+                // Implicit groups are disallowed
+                Err(NodeCreateError::MissingMetadata)
+            }
             MetadataRetrieveVersion::Default | MetadataRetrieveVersion::V3 => {
                 // V3 supports missing metadata
                 Ok(NodeMetadata::Group(GroupMetadata::V3(
@@ -133,6 +165,81 @@ impl Node {
         }
     }
 
+    /// Retrieve the Zarr V2 array or group metadata of the node at `path`, if it exists.
+    pub(crate) fn get_metadata_v2<TStorage: ?Sized + ReadableStorageTraits>(
+        storage: &Arc<TStorage>,
+        path: &NodePath,
+    ) -> Result<Option<NodeMetadata>, StorageError> {
+        // Try a Zarr V2 array
+        let array_key = meta_key_v2_array(path);
+        let attributes_key = meta_key_v2_attributes(path);
+        if let Some(metadata) = storage.get(&array_key)? {
+            let mut metadata: ArrayMetadataV2 = serde_json::from_slice(&metadata)
+                .map_err(|err| StorageError::InvalidMetadata(array_key, err.to_string()))?;
+            let attributes = storage.get(&attributes_key)?;
+            if let Some(attributes) = attributes {
+                metadata.attributes = serde_json::from_slice(&attributes).map_err(|err| {
+                    StorageError::InvalidMetadata(attributes_key, err.to_string())
+                })?;
+            }
+            return Ok(Some(NodeMetadata::Array(ArrayMetadata::V2(metadata))));
+        }
+
+        // Try a Zarr V2 group
+        let group_key = meta_key_v2_group(path);
+        if let Some(metadata) = storage.get(&group_key)? {
+            let mut metadata: GroupMetadataV2 = serde_json::from_slice(&metadata)
+                .map_err(|err| StorageError::InvalidMetadata(group_key, err.to_string()))?;
+            let attributes = storage.get(&attributes_key)?;
+            if let Some(attributes) = attributes {
+                metadata.attributes = serde_json::from_slice(&attributes).map_err(|err| {
+                    StorageError::InvalidMetadata(attributes_key, err.to_string())
+                })?;
+            }
+            return Ok(Some(NodeMetadata::Group(GroupMetadata::V2(metadata))));
+        }
+
+        Ok(None)
+    }
+
+    #[cfg(feature = "async")]
+    /// Asynchronously retrieve the Zarr V2 array or group metadata of the node at `path`, if it exists.
+    pub(crate) async fn async_get_metadata_v2<TStorage: ?Sized + AsyncReadableStorageTraits>(
+        storage: &Arc<TStorage>,
+        path: &NodePath,
+    ) -> Result<Option<NodeMetadata>, StorageError> {
+        // Try a Zarr V2 array
+        let array_key = meta_key_v2_array(path);
+        let attributes_key = meta_key_v2_attributes(path);
+        if let Some(metadata) = storage.get(&array_key).await? {
+            let mut metadata: ArrayMetadataV2 = serde_json::from_slice(&metadata)
+                .map_err(|err| StorageError::InvalidMetadata(array_key, err.to_string()))?;
+            let attributes = storage.get(&attributes_key).await?;
+            if let Some(attributes) = attributes {
+                metadata.attributes = serde_json::from_slice(&attributes).map_err(|err| {
+                    StorageError::InvalidMetadata(attributes_key, err.to_string())
+                })?;
+            }
+            return Ok(Some(NodeMetadata::Array(ArrayMetadata::V2(metadata))));
+        }
+
+        // Try a Zarr V2 group
+        let group_key = meta_key_v2_group(path);
+        if let Some(metadata) = storage.get(&group_key).await? {
+            let mut metadata: GroupMetadataV2 = serde_json::from_slice(&metadata)
+                .map_err(|err| StorageError::InvalidMetadata(group_key, err.to_string()))?;
+            let attributes = storage.get(&attributes_key).await?;
+            if let Some(attributes) = attributes {
+                metadata.attributes = serde_json::from_slice(&attributes).map_err(|err| {
+                    StorageError::InvalidMetadata(attributes_key, err.to_string())
+                })?;
+            }
+            return Ok(Some(NodeMetadata::Group(GroupMetadata::V2(metadata))));
+        }
+
+        Ok(None)
+    }
+
     #[cfg(feature = "async")]
     // Identical to get_metadata.. with awaits
     // "maybe async" one day?
@@ -161,38 +268,19 @@ impl Node {
         }
 
         if let MetadataRetrieveVersion::Default | MetadataRetrieveVersion::V2 = version {
-            // Try a Zarr V2 array
-            let array_key = meta_key_v2_array(path);
-            let attributes_key = meta_key_v2_attributes(path);
-            if let Some(metadata) = storage.get(&array_key).await? {
-                let mut metadata: ArrayMetadataV2 = serde_json::from_slice(&metadata)
-                    .map_err(|err| StorageError::InvalidMetadata(array_key, err.to_string()))?;
-                let attributes = storage.get(&attributes_key).await?;
-                if let Some(attributes) = attributes {
-                    metadata.attributes = serde_json::from_slice(&attributes).map_err(|err| {
-                        StorageError::InvalidMetadata(attributes_key, err.to_string())
-                    })?;
-                }
-                return Ok(NodeMetadata::Array(ArrayMetadata::V2(metadata)));
-            }
-
-            // Try a Zarr V2 group
-            let group_key = meta_key_v2_group(path);
-            if let Some(metadata) = storage.get(&group_key).await? {
-                let mut metadata: GroupMetadataV2 = serde_json::from_slice(&metadata)
-                    .map_err(|err| StorageError::InvalidMetadata(group_key, err.to_string()))?;
-                let attributes = storage.get(&attributes_key).await?;
-                if let Some(attributes) = attributes {
-                    metadata.attributes = serde_json::from_slice(&attributes).map_err(|err| {
-                        StorageError::InvalidMetadata(attributes_key, err.to_string())
-                    })?;
-                }
-                return Ok(NodeMetadata::Group(GroupMetadata::V2(metadata)));
+            if let Some(metadata) = Self::async_get_metadata_v2(storage, path).await? {
+                return Ok(metadata);
             }
         }
 
         // No metadata has been found
         match version {
+            MetadataRetrieveVersion::Default | MetadataRetrieveVersion::V3
+                if ImplicitGroupPolicy::default() == ImplicitGroupPolicy::Error =>
+            {
+                // Implicit groups are disallowed
+                Err(NodeCreateError::MissingMetadata)
+            }
             MetadataRetrieveVersion::Default | MetadataRetrieveVersion::V3 => {
                 // V3 supports missing metadata
                 Ok(NodeMetadata::Group(GroupMetadata::V3(
@@ -252,6 +340,172 @@ impl Node {
         Ok(node)
     }
 
+    /// Open a node at `path` and read metadata from `storage`, and children from consolidated metadata if available.
+    ///
+    /// If the node is a Zarr V3 group with [`ConsolidatedMetadata`], the entire hierarchy below it is constructed from its metadata without any further store requests.
+    /// If the node is a Zarr V2 group with a `.zmetadata` document ([`ConsolidatedMetadataV2`]), the hierarchy is constructed from that document.
+    /// Otherwise, this is equivalent to [`Node::open`].
+    ///
+    /// Consolidated metadata is not updated when the hierarchy is modified, so it may be stale.
+    ///
+    /// # Errors
+    /// Returns [`NodeCreateError`] if metadata is invalid or there is a failure to list child nodes.
+    pub fn open_consolidated<TStorage: ?Sized + ReadableStorageTraits + ListableStorageTraits>(
+        storage: &Arc<TStorage>,
+        path: &str,
+    ) -> Result<Self, NodeCreateError> {
+        let path: NodePath = path.try_into()?;
+        let metadata = Self::get_metadata(storage, &path, &MetadataRetrieveVersion::Default)?;
+        let consolidated_metadata = match Self::consolidated_metadata(&path, &metadata)? {
+            Some(consolidated_metadata) => Some(consolidated_metadata.metadata),
+            None if matches!(metadata, NodeMetadata::Group(GroupMetadata::V2(_))) => {
+                let key = meta_key_v2_consolidated(&path);
+                let consolidated_metadata = storage.get(&key)?;
+                consolidated_metadata
+                    .map(|consolidated_metadata| {
+                        Self::consolidated_metadata_v2(key, &consolidated_metadata)
+                    })
+                    .transpose()?
+            }
+            None => None,
+        };
+        let children = match consolidated_metadata {
+            Some(consolidated_metadata) => {
+                Self::consolidated_children(&path, "", &consolidated_metadata)?
+            }
+            None => match metadata {
+                NodeMetadata::Array(_) => Vec::default(),
+                NodeMetadata::Group(_) => get_child_nodes(storage, &path)?,
+            },
+        };
+        let node = Self {
+            path,
+            metadata,
+            children,
+        };
+        Ok(node)
+    }
+
+    #[cfg(feature = "async")]
+    /// Asynchronously open a node at `path` and read metadata from `storage`, and children from consolidated metadata if available.
+    ///
+    /// See [`Node::open_consolidated`].
+    ///
+    /// # Errors
+    /// Returns [`NodeCreateError`] if metadata is invalid or there is a failure to list child nodes.
+    pub async fn async_open_consolidated<
+        TStorage: ?Sized + AsyncReadableStorageTraits + AsyncListableStorageTraits,
+    >(
+        storage: Arc<TStorage>,
+        path: &str,
+    ) -> Result<Self, NodeCreateError> {
+        let path: NodePath = path.try_into()?;
+        let metadata =
+            Self::async_get_metadata(&storage, &path, &MetadataRetrieveVersion::Default).await?;
+        let consolidated_metadata = match Self::consolidated_metadata(&path, &metadata)? {
+            Some(consolidated_metadata) => Some(consolidated_metadata.metadata),
+            None if matches!(metadata, NodeMetadata::Group(GroupMetadata::V2(_))) => {
+                let key = meta_key_v2_consolidated(&path);
+                let consolidated_metadata = storage.get(&key).await?;
+                consolidated_metadata
+                    .map(|consolidated_metadata| {
+                        Self::consolidated_metadata_v2(key, &consolidated_metadata)
+                    })
+                    .transpose()?
+            }
+            None => None,
+        };
+        let children = match consolidated_metadata {
+            Some(consolidated_metadata) => {
+                Self::consolidated_children(&path, "", &consolidated_metadata)?
+            }
+            None => match metadata {
+                NodeMetadata::Array(_) => Vec::default(),
+                NodeMetadata::Group(_) => async_get_child_nodes(&storage, &path).await?,
+            },
+        };
+        let node = Self {
+            path,
+            metadata,
+            children,
+        };
+        Ok(node)
+    }
+
+    /// Returns the consolidated metadata of a node with `metadata` at `path`, if it is a Zarr V3 group with consolidated metadata.
+    fn consolidated_metadata(
+        path: &NodePath,
+        metadata: &NodeMetadata,
+    ) -> Result<Option<ConsolidatedMetadata>, StorageError> {
+        if let NodeMetadata::Group(GroupMetadata::V3(metadata)) = metadata {
+            metadata
+                .consolidated_metadata()
+                .map_err(|err| StorageError::InvalidMetadata(meta_key(path), err.to_string()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns the node metadata of the descendants of a Zarr V2 group from its `.zmetadata` document at `key`.
+    fn consolidated_metadata_v2(
+        key: StoreKey,
+        consolidated_metadata: &[u8],
+    ) -> Result<BTreeMap<String, NodeMetadata>, StorageError> {
+        let mut node_metadata =
+            serde_json::from_slice::<ConsolidatedMetadataV2>(consolidated_metadata)
+                .and_then(|consolidated_metadata| consolidated_metadata.node_metadata())
+                .map_err(|err| StorageError::InvalidMetadata(key, err.to_string()))?;
+        node_metadata.remove("");
+        Ok(node_metadata)
+    }
+
+    /// Create the children of the node at `path` from consolidated `metadata`.
+    ///
+    /// `relative` is the path of the node relative to the node with the consolidated metadata.
+    /// Groups without metadata are implicit groups.
+    fn consolidated_children(
+        path: &NodePath,
+        relative: &str,
+        metadata: &BTreeMap<String, NodeMetadata>,
+    ) -> Result<Vec<Self>, NodePathError> {
+        let prefix = if relative.is_empty() {
+            String::new()
+        } else {
+            format!("{relative}/")
+        };
+        let names: BTreeSet<&str> = metadata
+            .keys()
+            .filter_map(|key| key.strip_prefix(&prefix))
+            .filter_map(|suffix| suffix.split('/').next())
+            .filter(|name| !name.is_empty())
+            .collect();
+        names
+            .into_iter()
+            .map(|name| {
+                let child_relative = format!("{prefix}{name}");
+                let child_path = if path.as_str() == "/" {
+                    NodePath::new(&format!("/{name}"))?
+                } else {
+                    NodePath::new(&format!("{}/{name}", path.as_str()))?
+                };
+                let child_metadata = metadata.get(&child_relative).cloned().unwrap_or_else(|| {
+                    NodeMetadata::Group(GroupMetadata::V3(GroupMetadataV3::default()))
+                });
+                let children = match child_metadata {
+                    NodeMetadata::Array(_) => Vec::default(),
+                    NodeMetadata::Group(_) => {
+                        Self::consolidated_children(&child_path, &child_relative, metadata)?
+                    }
+                };
+                Ok(Self {
+                    path: child_path,
+                    metadata: child_metadata,
+                    children,
+                })
+            })
+            .collect()
+    }
+
     #[cfg(feature = "async")]
     #[deprecated(since = "0.15.0", note = "please use `async_open` instead")]
     /// Asynchronously open a node at `path` and read metadata and children from `storage` with default [`MetadataRetrieveVersion`].
@@ -348,6 +602,76 @@ impl Node {
         &self.children
     }
 
+    /// Consolidate the metadata of the hierarchy below the node.
+    ///
+    /// The [`ConsolidatedMetadata`] holds the metadata of all descendants of the node, keyed by their path relative to the node.
+    /// The consolidated metadata of descendant groups is omitted.
+    #[must_use]
+    pub fn consolidate_metadata(&self) -> ConsolidatedMetadata {
+        fn add_children(
+            metadata: &mut BTreeMap<String, NodeMetadata>,
+            relative: &str,
+            children: &[Node],
+        ) {
+            for child in children {
+                let name = child.name();
+                let child_relative = if relative.is_empty() {
+                    name.as_str().to_string()
+                } else {
+                    format!("{relative}/{}", name.as_str())
+                };
+                let mut child_metadata = child.metadata.clone();
+                if let NodeMetadata::Group(GroupMetadata::V3(group_metadata)) = &mut child_metadata
+                {
+                    group_metadata
+                        .additional_fields
+                        .remove("consolidated_metadata");
+                }
+                metadata.insert(child_relative.clone(), child_metadata);
+                add_children(metadata, &child_relative, &child.children);
+            }
+        }
+
+        let mut metadata = BTreeMap::new();
+        add_children(&mut metadata, "", &self.children);
+        ConsolidatedMetadata {
+            kind: ConsolidatedMetadataKind::Inline,
+            metadata,
+        }
+    }
+
+    /// Consolidate the Zarr V2 metadata of the node and the hierarchy below it.
+    ///
+    /// The [`ConsolidatedMetadataV2`] holds the `.zgroup`, `.zarray`, and `.zattrs` documents of the node and its descendants, keyed by their store key relative to the node.
+    /// Zarr V3 metadata is omitted.
+    ///
+    /// # Errors
+    /// Returns a [`serde_json::Error`] if any metadata cannot be serialised.
+    pub fn consolidate_metadata_v2(&self) -> Result<ConsolidatedMetadataV2, serde_json::Error> {
+        fn add_children(
+            consolidated_metadata: &mut ConsolidatedMetadataV2,
+            relative: &str,
+            children: &[Node],
+        ) -> Result<(), serde_json::Error> {
+            for child in children {
+                let name = child.name();
+                let child_relative = if relative.is_empty() {
+                    name.as_str().to_string()
+                } else {
+                    format!("{relative}/{}", name.as_str())
+                };
+                consolidated_metadata.add_node(&child_relative, &child.metadata)?;
+                add_children(consolidated_metadata, &child_relative, &child.children)?;
+            }
+            Ok(())
+        }
+
+        let mut consolidated_metadata = ConsolidatedMetadataV2::new(BTreeMap::new());
+        consolidated_metadata.add_node("", &self.metadata)?;
+        add_children(&mut consolidated_metadata, "", &self.children)?;
+        Ok(consolidated_metadata)
+    }
+
     /// Return a tree representation of a hierarchy as a string.
     ///
     /// Arrays are annotated with their shape and data type.
@@ -360,43 +684,19 @@ impl Node {
     /// ```
     #[must_use]
     pub fn hierarchy_tree(&self) -> String {
-        fn print_metadata(name: &str, string: &mut String, metadata: &NodeMetadata) {
-            match metadata {
-                NodeMetadata::Array(array_metadata) => {
-                    let s = match array_metadata {
-                        ArrayMetadata::V3(array_metadata) => {
-                            format!(
-                                "{} {:?} {}",
-                                name, array_metadata.shape, array_metadata.data_type
-                            )
-                        }
-                        ArrayMetadata::V2(array_metadata) => {
-                            format!(
-                                "{} {:?} {:?}",
-                                name, array_metadata.shape, array_metadata.dtype
-                            )
-                        }
-                    };
-                    string.push_str(&s);
-                }
-                NodeMetadata::Group(_) => {
-                    string.push_str(name);
-                }
-            };
-            string.push('\n');
-        }
-
         fn update_tree(string: &mut String, children: &[Node], depth: usize) {
             for child in children {
                 let name = child.name();
                 string.push_str(&" ".repeat(depth * 2));
-                print_metadata(name.as_str(), string, &child.metadata);
+                string.push_str(&node_description(name.as_str(), &child.metadata));
+                string.push('\n');
                 update_tree(string, &child.children, depth + 1);
             }
         }
 
         let mut string = String::default();
-        print_metadata("/", &mut string, &self.metadata);
+        string.push_str(&node_description("/", &self.metadata));
+        string.push('\n');
         update_tree(&mut string, &self.children, 1);
         string
     }
@@ -552,6 +852,255 @@ mod tests {
         );
     }
 
+    #[test]
+    fn node_consolidated() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        let array = ArrayBuilder::new(
+            vec![2, 3],
+            crate::array::DataType::UInt8,
+            vec![1, 1].try_into()?,
+            FillValue::from(0u8),
+        )
+        .build(store.clone(), "/a/array")?;
+        let array_metadata = serde_json::to_value(array.metadata())?;
+
+        // Only the root metadata is in the store
+        let root_metadata = serde_json::json!({
+            "zarr_format": 3,
+            "node_type": "group",
+            "consolidated_metadata": {
+                "kind": "inline",
+                "must_understand": false,
+                "metadata": {
+                    "a": {"zarr_format": 3, "node_type": "group", "consolidated_metadata": null},
+                    "a/array": array_metadata,
+                    "b/c/array": array_metadata,
+                }
+            }
+        });
+        store.set(
+            &"zarr.json".try_into()?,
+            serde_json::to_vec(&root_metadata)?.into(),
+        )?;
+        assert_eq!(Node::open(&store, "/")?.children().len(), 0);
+
+        let node = Node::open_consolidated(&store, "/")?;
+        assert_eq!(
+            node.hierarchy_tree(),
+            "/\n  a\n    array [2, 3] uint8\n  b\n    c\n      array [2, 3] uint8\n"
+        );
+        let node_array = &node.children()[0].children()[0];
+        assert_eq!(node_array.path().as_str(), "/a/array");
+        let NodeMetadata::Array(metadata) = node_array.metadata() else {
+            panic!()
+        };
+        let array = crate::array::Array::new_with_metadata(
+            store.clone(),
+            node_array.path().as_str(),
+            metadata.clone(),
+        )?;
+        assert_eq!(array.shape(), &[2, 3]);
+
+        // Consolidated metadata of a child group
+        let node = Node::open_consolidated(&store, "/a")?;
+        assert!(node.children().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn node_consolidated_invalid() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        store.set(
+            &"zarr.json".try_into()?,
+            br#"{"zarr_format": 3, "node_type": "group", "consolidated_metadata": {"kind": "inline", "must_understand": false, "metadata": {"a": {}}}}"#
+                .to_vec()
+                .into(),
+        )?;
+        assert!(Node::open_consolidated(&store, "/").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn node_hierarchy_tree_opt() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        crate::group::GroupBuilder::new()
+            .build(store.clone(), "/")?
+            .store_metadata()?;
+        let array = ArrayBuilder::new(
+            vec![4, 4],
+            crate::array::DataType::UInt16,
+            vec![2, 2].try_into()?,
+            FillValue::from(0u16),
+        )
+        .build(store.clone(), "/a")?;
+        array.store_metadata()?;
+        array.store_chunk_elements::<u16>(&[0, 0], &[1, 2, 3, 4])?;
+        array.store_chunk_elements::<u16>(&[1, 1], &[5, 6, 7, 8])?;
+
+        let node = Node::open(&store, "/")?;
+        assert_eq!(
+            node.hierarchy_tree_opt(&store, &NodeHierarchyTreeOptions::default())?,
+            node.hierarchy_tree()
+        );
+        assert_eq!(
+            node.hierarchy_tree_opt(&store, &NodeHierarchyTreeOptions::all())?,
+            "/\n  a [4, 4] uint16 (2/4 chunks, 16 B stored, 16 B uncompressed, 1.00x compression, bytes)\n"
+        );
+        assert_eq!(
+            node.hierarchy_tree_opt(
+                &store,
+                NodeHierarchyTreeOptions::default().set_chunk_counts(true)
+            )?,
+            "/\n  a [4, 4] uint16 (2/4 chunks)\n"
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn node_hierarchy_tree_opt_async() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(crate::storage::store::AsyncObjectStore::new(
+            object_store::memory::InMemory::new(),
+        ));
+        crate::group::GroupBuilder::new()
+            .build(store.clone(), "/")?
+            .async_store_metadata()
+            .await?;
+        let array = ArrayBuilder::new(
+            vec![4, 4],
+            crate::array::DataType::UInt16,
+            vec![2, 2].try_into()?,
+            FillValue::from(0u16),
+        )
+        .build(store.clone(), "/a")?;
+        array.async_store_metadata().await?;
+        array
+            .async_store_chunk_elements::<u16>(&[0, 0], &[1, 2, 3, 4])
+            .await?;
+
+        let node = Node::async_open(store.clone(), "/").await?;
+        assert_eq!(
+            node.async_hierarchy_tree_opt(&store, &NodeHierarchyTreeOptions::all())
+                .await?,
+            "/\n  a [4, 4] uint16 (1/4 chunks, 8 B stored, 8 B uncompressed, 1.00x compression, bytes)\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn node_size_info() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        crate::group::GroupBuilder::new()
+            .build(store.clone(), "/")?
+            .store_metadata()?;
+        let builder = ArrayBuilder::new(
+            vec![4, 4],
+            crate::array::DataType::UInt16,
+            vec![2, 2].try_into()?,
+            FillValue::from(0u16),
+        );
+        let array = builder.build(store.clone(), "/a")?;
+        array.store_metadata()?;
+        array.store_chunk_elements::<u16>(&[0, 0], &[1, 2, 3, 4])?;
+        array.store_chunk_elements::<u16>(&[1, 1], &[5, 6, 7, 8])?;
+        let array = builder.build(store.clone(), "/g/b")?;
+        array.store_metadata()?;
+        array.store_chunk_elements::<u16>(&[0, 1], &[1, 2, 3, 4])?;
+
+        let node = Node::open(&store, "/g/b")?;
+        let size_info = node.size_info(&store)?;
+        assert_eq!(size_info.chunk_keys(), 1);
+        assert_eq!(size_info.uncompressed_bytes(), Some(8));
+        assert_eq!(
+            size_info.stored_bytes(),
+            store.size_prefix(&"g/b/".try_into()?)?
+        );
+
+        let node = Node::open(&store, "/")?;
+        let size_info = node.size_info(&store)?;
+        assert_eq!(size_info.chunk_keys(), 3);
+        assert_eq!(size_info.uncompressed_bytes(), Some(24));
+        assert_eq!(size_info.stored_bytes(), store.size()?);
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn node_size_info_async() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(crate::storage::store::AsyncObjectStore::new(
+            object_store::memory::InMemory::new(),
+        ));
+        let array = ArrayBuilder::new(
+            vec![4, 4],
+            crate::array::DataType::UInt16,
+            vec![2, 2].try_into()?,
+            FillValue::from(0u16),
+        )
+        .build(store.clone(), "/a")?;
+        array.async_store_metadata().await?;
+        array
+            .async_store_chunk_elements::<u16>(&[0, 0], &[1, 2, 3, 4])
+            .await?;
+
+        let node = Node::async_open(store.clone(), "/a").await?;
+        let size_info = node.async_size_info(&store).await?;
+        assert_eq!(size_info.chunk_keys(), 1);
+        assert_eq!(size_info.uncompressed_bytes(), Some(8));
+        assert_eq!(size_info.stored_bytes(), store.size().await?);
+        Ok(())
+    }
+
+    #[test]
+    fn node_metadata_accessors() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        let array = ArrayBuilder::new(
+            vec![4, 4],
+            crate::array::DataType::UInt16,
+            vec![2, 2].try_into()?,
+            FillValue::from(0u16),
+        )
+        .build(store.clone(), "/a")?;
+        let metadata = NodeMetadata::Array(array.metadata().clone());
+        assert!(metadata.is_array());
+        assert!(!metadata.is_group());
+        assert!(metadata.as_array().is_some());
+        assert!(metadata.as_array_v3().is_some());
+        assert!(metadata.as_array_v2().is_none());
+        assert!(metadata.as_group().is_none());
+        assert_eq!(metadata.shape(), Some(&vec![4, 4]));
+        assert_eq!(metadata.data_type(), Some(crate::array::DataType::UInt16));
+
+        let metadata: NodeMetadata = serde_json::from_str(
+            r#"{
+                "zarr_format": 2,
+                "shape": [4, 4],
+                "chunks": [2, 2],
+                "dtype": "<u2",
+                "compressor": null,
+                "fill_value": 0,
+                "order": "C",
+                "filters": null
+            }"#,
+        )?;
+        assert!(metadata.as_array_v2().is_some());
+        assert_eq!(metadata.shape(), Some(&vec![4, 4]));
+        assert_eq!(metadata.data_type(), Some(crate::array::DataType::UInt16));
+
+        let mut group_metadata = GroupMetadataV3::default();
+        group_metadata
+            .attributes
+            .insert("key".to_string(), "value".into());
+        let metadata = NodeMetadata::Group(GroupMetadata::V3(group_metadata));
+        assert!(metadata.is_group());
+        assert!(metadata.as_group_v3().is_some());
+        assert!(metadata.as_group_v2().is_none());
+        assert!(metadata.as_array().is_none());
+        assert_eq!(metadata.shape(), None);
+        assert_eq!(metadata.data_type(), None);
+        assert_eq!(metadata.attributes().get("key"), Some(&"value".into()));
+        Ok(())
+    }
+
     #[test]
     fn node_root() {
         let node = Node::new_with_metadata(
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use crate::config::global_config;
+
+/// The progress of a node copy.
+///
+/// See [`NodeCopyOptions::set_progress_callback`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeCopyProgress {
+    keys_copied: usize,
+    keys_total: usize,
+    bytes_copied: u64,
+}
+
+impl NodeCopyProgress {
+    /// Return the number of keys copied.
+    #[must_use]
+    pub const fn keys_copied(&self) -> usize {
+        self.keys_copied
+    }
+
+    /// Return the total number of keys to copy.
+    #[must_use]
+    pub const fn keys_total(&self) -> usize {
+        self.keys_total
+    }
+
+    /// Return the number of bytes copied.
+    #[must_use]
+    pub const fn bytes_copied(&self) -> u64 {
+        self.bytes_copied
+    }
+
+    pub(crate) const fn new(keys_copied: usize, keys_total: usize, bytes_copied: u64) -> Self {
+        Self {
+            keys_copied,
+            keys_total,
+            bytes_copied,
+        }
+    }
+}
+
+/// A node copy progress callback.
+pub type NodeCopyProgressCallback = Arc<dyn Fn(NodeCopyProgress) + Send + Sync>;
+
+/// Options for copying a node.
+///
+/// Used with [`Node::copy_to`](crate::node::Node::copy_to) and its async variant.
+#[derive(Clone)]
+pub struct NodeCopyOptions {
+    concurrent_target: usize,
+    consolidate_metadata: bool,
+    progress_callback: Option<NodeCopyProgressCallback>,
+}
+
+impl Default for NodeCopyOptions {
+    fn default() -> Self {
+        Self {
+            concurrent_target: global_config().codec_concurrent_target(),
+            consolidate_metadata: false,
+            progress_callback: None,
+        }
+    }
+}
+
+impl core::fmt::Debug for NodeCopyOptions {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("NodeCopyOptions")
+            .field("concurrent_target", &self.concurrent_target)
+            .field("consolidate_metadata", &self.consolidate_metadata)
+            .field("progress_callback", &self.progress_callback.is_some())
+            .finish()
+    }
+}
+
+impl NodeCopyOptions {
+    /// Return the target number of keys to copy concurrently.
+    ///
+    /// Defaults to the [global](crate::config::Config#codec-concurrent-target) codec concurrent target.
+    #[must_use]
+    pub fn concurrent_target(&self) -> usize {
+        self.concurrent_target
+    }
+
+    /// Set the target number of keys to copy concurrently.
+    pub fn set_concurrent_target(&mut self, concurrent_target: usize) -> &mut Self {
+        self.concurrent_target = concurrent_target;
+        self
+    }
+
+    /// Get the consolidate metadata configuration. Defaults to false.
+    #[must_use]
+    pub fn consolidate_metadata(&self) -> bool {
+        self.consolidate_metadata
+    }
+
+    /// Set the consolidate metadata configuration.
+    ///
+    /// If true and the copied node is a group, its metadata is consolidated at the destination after copying.
+    /// Otherwise, any consolidated metadata is copied as is.
+    pub fn set_consolidate_metadata(&mut self, consolidate_metadata: bool) -> &mut Self {
+        self.consolidate_metadata = consolidate_metadata;
+        self
+    }
+
+    /// Get the progress callback.
+    #[must_use]
+    pub fn progress_callback(&self) -> Option<&NodeCopyProgressCallback> {
+        self.progress_callback.as_ref()
+    }
+
+    /// Set the progress callback, which is called after each key is copied.
+    ///
+    /// Keys may be copied concurrently, so the callback must be thread-safe and may be called out of order.
+    pub fn set_progress_callback(
+        &mut self,
+        progress_callback: Option<NodeCopyProgressCallback>,
+    ) -> &mut Self {
+        self.progress_callback = progress_callback;
+        self
+    }
+}
@@ -0,0 +1,159 @@
+use super::Node;
+
+/// Returns true if a path segment matches a pattern segment with `*` and `?` wildcards.
+fn segment_matches(pattern: &[char], segment: &[char]) -> bool {
+    match (pattern.first(), segment.first()) {
+        (None, None) => true,
+        (Some('*'), _) => {
+            segment_matches(&pattern[1..], segment)
+                || (!segment.is_empty() && segment_matches(pattern, &segment[1..]))
+        }
+        (Some('?'), Some(_)) => segment_matches(&pattern[1..], &segment[1..]),
+        (Some(p), Some(s)) if p == s => segment_matches(&pattern[1..], &segment[1..]),
+        _ => false,
+    }
+}
+
+/// Returns true if path segments match pattern segments, where `**` matches zero or more segments.
+fn segments_match(pattern: &[Vec<char>], segments: &[Vec<char>]) -> bool {
+    match (pattern.first(), segments.first()) {
+        (None, None) => true,
+        (Some(p), _) if p.as_slice() == ['*', '*'] => {
+            segments_match(&pattern[1..], segments)
+                || (!segments.is_empty() && segments_match(pattern, &segments[1..]))
+        }
+        (Some(p), Some(s)) => {
+            segment_matches(p, s) && segments_match(&pattern[1..], &segments[1..])
+        }
+        _ => false,
+    }
+}
+
+fn split_path(path: &str) -> Vec<Vec<char>> {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| segment.chars().collect())
+        .collect()
+}
+
+impl Node {
+    /// Find the node and its descendants that satisfy `predicate`.
+    ///
+    /// Nodes are visited depth-first, with each node visited before its children.
+    ///
+    /// ### Example
+    /// Find all `float32` arrays with a `units` attribute.
+    /// ```rust
+    /// # use zarrs::{array::DataType, node::Node};
+    /// # let store = std::sync::Arc::new(zarrs::storage::store::MemoryStore::new());
+    /// # let node = Node::open(&store, "/")?;
+    /// let arrays = node.find(|node| {
+    ///     node.metadata().data_type() == Some(DataType::Float32)
+    ///         && node.metadata().attributes().contains_key("units")
+    /// });
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn find<F: Fn(&Self) -> bool>(&self, predicate: F) -> Vec<&Self> {
+        fn visit<'a, F: Fn(&Node) -> bool>(
+            node: &'a Node,
+            predicate: &F,
+            found: &mut Vec<&'a Node>,
+        ) {
+            if predicate(node) {
+                found.push(node);
+            }
+            for child in &node.children {
+                visit(child, predicate, found);
+            }
+        }
+
+        let mut found = Vec::new();
+        visit(self, &predicate, &mut found);
+        found
+    }
+
+    /// Find the node and its descendants with a path matching the glob `pattern`.
+    ///
+    /// The pattern is matched against the full node path, segment by segment:
+    ///  - `*` matches any sequence of characters within a segment,
+    ///  - `?` matches any single character within a segment, and
+    ///  - `**` as a whole segment matches zero or more segments.
+    ///
+    /// For example, `/**/labels/*` matches every child of every `labels` group.
+    #[must_use]
+    pub fn find_glob(&self, pattern: &str) -> Vec<&Self> {
+        let pattern = split_path(pattern);
+        self.find(|node| segments_match(&pattern, &split_path(node.path().as_str())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        array::{ArrayBuilder, ArrayMetadata, DataType, FillValue},
+        group::GroupBuilder,
+        node::NodeMetadata,
+        storage::store::MemoryStore,
+    };
+
+    use super::*;
+
+    #[test]
+    fn node_find() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        let group = GroupBuilder::new().build(store.clone(), "/")?;
+        group.store_metadata()?;
+        let mut array_builder = ArrayBuilder::new(
+            vec![2, 2],
+            DataType::Float32,
+            vec![1, 1].try_into()?,
+            FillValue::from(0f32),
+        );
+        let image = group.create_group("image")?;
+        image.create_array("0", &array_builder)?;
+        let labels = image.create_group("labels")?;
+        array_builder.attributes(serde_json::from_str(r#"{"units": "um"}"#)?);
+        labels.create_array("cells", &array_builder)?;
+        labels.create_array(
+            "nuclei",
+            ArrayBuilder::new(
+                vec![2, 2],
+                DataType::UInt8,
+                vec![1, 1].try_into()?,
+                FillValue::from(0u8),
+            )
+            .attributes(serde_json::from_str(r#"{"units": "um"}"#)?),
+        )?;
+
+        let node = Node::open(&store, "/")?;
+        let paths = |nodes: Vec<&Node>| {
+            nodes
+                .into_iter()
+                .map(|node| node.path().as_str().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            paths(node.find_glob("/**/labels/*")),
+            ["/image/labels/cells", "/image/labels/nuclei"]
+        );
+        assert_eq!(paths(node.find_glob("/*/?")), ["/image/0"]);
+        assert_eq!(paths(node.find_glob("/**/n*i")), ["/image/labels/nuclei"]);
+        assert_eq!(paths(node.find_glob("/**")).len(), 6);
+        assert_eq!(paths(node.find_glob("/")), ["/"]);
+        assert!(node.find_glob("/labels").is_empty());
+
+        let float32_with_units = node.find(|node| {
+            matches!(
+                node.metadata(),
+                NodeMetadata::Array(ArrayMetadata::V3(metadata))
+                    if metadata.data_type.name() == "float32"
+                        && metadata.attributes.contains_key("units")
+            )
+        });
+        assert_eq!(paths(float32_with_units), ["/image/labels/cells"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,346 @@
+use std::{collections::HashSet, fmt::Write, sync::Arc};
+
+use crate::{
+    array::{Array, ArrayIndices, ArrayMetadata},
+    array_subset::ArraySubset,
+    storage::{ListableStorageTraits, ReadableStorageTraits, StorageError, StoreKey, StorePrefix},
+};
+
+#[cfg(feature = "async")]
+use crate::storage::{AsyncListableStorageTraits, AsyncReadableStorageTraits};
+
+use super::{Node, NodeMetadata};
+
+/// Options for a tree representation of a hierarchy.
+///
+/// Used with [`Node::hierarchy_tree_opt`] and its async variant.
+///
+/// Arrays are always annotated with their shape and data type.
+/// The other annotations are disabled by default, since chunk statistics require listing the keys of each array and retrieving the size of each stored chunk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct NodeHierarchyTreeOptions {
+    chunk_counts: bool,
+    stored_bytes: bool,
+    uncompressed_bytes: bool,
+    codecs: bool,
+}
+
+impl NodeHierarchyTreeOptions {
+    /// Create options with all annotations enabled.
+    #[must_use]
+    pub const fn all() -> Self {
+        Self {
+            chunk_counts: true,
+            stored_bytes: true,
+            uncompressed_bytes: true,
+            codecs: true,
+        }
+    }
+
+    /// Return true if arrays are annotated with the number of stored chunks and the number of chunks in the chunk grid.
+    #[must_use]
+    pub const fn chunk_counts(&self) -> bool {
+        self.chunk_counts
+    }
+
+    /// Set whether arrays are annotated with the number of stored chunks and the number of chunks in the chunk grid.
+    pub fn set_chunk_counts(&mut self, chunk_counts: bool) -> &mut Self {
+        self.chunk_counts = chunk_counts;
+        self
+    }
+
+    /// Return true if arrays are annotated with the total size in bytes of their stored chunks.
+    #[must_use]
+    pub const fn stored_bytes(&self) -> bool {
+        self.stored_bytes
+    }
+
+    /// Set whether arrays are annotated with the total size in bytes of their stored chunks.
+    pub fn set_stored_bytes(&mut self, stored_bytes: bool) -> &mut Self {
+        self.stored_bytes = stored_bytes;
+        self
+    }
+
+    /// Return true if arrays are annotated with the total decoded size in bytes of their stored chunks.
+    ///
+    /// The decoded size is unknown for arrays with a variable size data type.
+    /// The compression ratio is included if the stored size is also enabled.
+    #[must_use]
+    pub const fn uncompressed_bytes(&self) -> bool {
+        self.uncompressed_bytes
+    }
+
+    /// Set whether arrays are annotated with the total decoded size in bytes of their stored chunks.
+    pub fn set_uncompressed_bytes(&mut self, uncompressed_bytes: bool) -> &mut Self {
+        self.uncompressed_bytes = uncompressed_bytes;
+        self
+    }
+
+    /// Return true if arrays are annotated with a summary of their codec chain.
+    #[must_use]
+    pub const fn codecs(&self) -> bool {
+        self.codecs
+    }
+
+    /// Set whether arrays are annotated with a summary of their codec chain.
+    pub fn set_codecs(&mut self, codecs: bool) -> &mut Self {
+        self.codecs = codecs;
+        self
+    }
+
+    /// Returns true if the stored chunks of an array need to be enumerated.
+    const fn requires_chunks(self) -> bool {
+        self.chunk_counts || self.stored_bytes || self.uncompressed_bytes
+    }
+}
+
+/// Statistics of the stored chunks of an array.
+#[derive(Default)]
+struct ArrayChunkStatistics {
+    chunks_stored: u64,
+    chunks_total: Option<u64>,
+    stored_bytes: u64,
+    uncompressed_bytes: Option<u64>,
+}
+
+/// Return the description of a node in a hierarchy tree.
+///
+/// Arrays are described by their shape and data type.
+pub(super) fn node_description(name: &str, metadata: &NodeMetadata) -> String {
+    match metadata {
+        NodeMetadata::Array(ArrayMetadata::V3(array_metadata)) => {
+            format!(
+                "{} {:?} {}",
+                name, array_metadata.shape, array_metadata.data_type
+            )
+        }
+        NodeMetadata::Array(ArrayMetadata::V2(array_metadata)) => {
+            format!(
+                "{} {:?} {:?}",
+                name, array_metadata.shape, array_metadata.dtype
+            )
+        }
+        NodeMetadata::Group(_) => name.to_string(),
+    }
+}
+
+/// Flatten the hierarchy below `node` into nodes and their depth, with each node before its children.
+pub(super) fn flatten_tree(node: &Node) -> Vec<(usize, &Node)> {
+    fn visit<'a>(nodes: &mut Vec<(usize, &'a Node)>, node: &'a Node, depth: usize) {
+        nodes.push((depth, node));
+        for child in &node.children {
+            visit(nodes, child, depth + 1);
+        }
+    }
+
+    let mut nodes = Vec::new();
+    visit(&mut nodes, node, 0);
+    nodes
+}
+
+/// Return the chunk indices and keys of the stored chunks of `array`.
+///
+/// Returns [`None`] if the chunk grid is unbounded.
+pub(super) fn stored_chunks<TStorage: ?Sized>(
+    array: &Array<TStorage>,
+    keys: &HashSet<StoreKey>,
+) -> Option<(u64, Vec<(ArrayIndices, StoreKey)>)> {
+    let chunk_grid_shape = array.chunk_grid_shape()?;
+    let chunks = ArraySubset::new_with_shape(chunk_grid_shape);
+    let stored_chunks = chunks
+        .indices()
+        .into_iter()
+        .filter_map(|chunk_indices| {
+            let key = array.chunk_key(&chunk_indices);
+            keys.contains(&key).then_some((chunk_indices, key))
+        })
+        .collect();
+    Some((chunks.num_elements(), stored_chunks))
+}
+
+/// Return the decoded size in bytes of the chunk at `chunk_indices`, or [`None`] if the data type has a variable size.
+pub(super) fn chunk_uncompressed_bytes<TStorage: ?Sized>(
+    array: &Array<TStorage>,
+    chunk_indices: &[u64],
+) -> Option<u64> {
+    let data_type_size = array.data_type().fixed_size()?;
+    let chunk_shape = array.chunk_shape(chunk_indices).ok()?;
+    Some(chunk_shape.num_elements_u64() * data_type_size as u64)
+}
+
+/// Return the annotation of an array in a hierarchy tree.
+fn array_annotation<TStorage: ?Sized>(
+    array: &Array<TStorage>,
+    statistics: &ArrayChunkStatistics,
+    options: NodeHierarchyTreeOptions,
+) -> String {
+    let mut annotations = Vec::new();
+    if options.chunk_counts {
+        annotations.push(if let Some(chunks_total) = statistics.chunks_total {
+            format!("{}/{chunks_total} chunks", statistics.chunks_stored)
+        } else {
+            format!("{} chunks", statistics.chunks_stored)
+        });
+    }
+    if options.stored_bytes {
+        annotations.push(format!("{} B stored", statistics.stored_bytes));
+    }
+    if options.uncompressed_bytes {
+        if let Some(uncompressed_bytes) = statistics.uncompressed_bytes {
+            annotations.push(format!("{uncompressed_bytes} B uncompressed"));
+            if options.stored_bytes && statistics.stored_bytes > 0 {
+                #[allow(clippy::cast_precision_loss)]
+                let ratio = uncompressed_bytes as f64 / statistics.stored_bytes as f64;
+                annotations.push(format!("{ratio:.2}x compression"));
+            }
+        }
+    }
+    if options.codecs {
+        let codecs = array
+            .codecs()
+            .create_metadatas()
+            .iter()
+            .map(|metadata| metadata.name().to_string())
+            .collect::<Vec<_>>();
+        annotations.push(codecs.join(" > "));
+    }
+
+    if annotations.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", annotations.join(", "))
+    }
+}
+
+impl Node {
+    /// Return a tree representation of a hierarchy as a string with non-default [`NodeHierarchyTreeOptions`].
+    ///
+    /// Arrays are annotated with their shape and data type, and the chunk counts, stored and uncompressed sizes, and codecs enabled in `options`.
+    /// Chunk statistics are computed from the stored chunks of each array in `storage` when the tree is formatted.
+    /// For example:
+    /// ```text
+    /// /
+    ///   a
+    ///     baz [10000, 1000] float64 (100/100 chunks, 20971520 B stored, 80000000 B uncompressed, 3.81x compression, bytes > zstd)
+    ///     foo [10000, 1000] float64 (2/100 chunks, 1048576 B stored, 1600000 B uncompressed, 1.53x compression, bytes > zstd)
+    ///   b
+    /// ```
+    ///
+    /// Arrays with unsupported metadata are annotated with the error that prevented their statistics from being computed.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying store error.
+    pub fn hierarchy_tree_opt<
+        TStorage: ?Sized + ReadableStorageTraits + ListableStorageTraits + 'static,
+    >(
+        &self,
+        storage: &Arc<TStorage>,
+        options: &NodeHierarchyTreeOptions,
+    ) -> Result<String, StorageError> {
+        let mut string = String::default();
+        for (depth, node) in flatten_tree(self) {
+            let name = if depth == 0 {
+                "/".to_string()
+            } else {
+                node.name().as_str().to_string()
+            };
+            string.push_str(&" ".repeat(depth * 2));
+            string.push_str(&node_description(&name, &node.metadata));
+            if let NodeMetadata::Array(array_metadata) = &node.metadata {
+                match Array::new_with_metadata(
+                    storage.clone(),
+                    node.path.as_str(),
+                    array_metadata.clone(),
+                ) {
+                    Ok(array) => {
+                        let mut statistics = ArrayChunkStatistics::default();
+                        if options.requires_chunks() {
+                            let prefix: StorePrefix = (&node.path).try_into()?;
+                            let keys = storage.list_prefix(&prefix)?.into_iter().collect();
+                            if let Some((chunks_total, chunks)) = stored_chunks(&array, &keys) {
+                                statistics.chunks_total = Some(chunks_total);
+                                statistics.uncompressed_bytes = Some(0);
+                                for (chunk_indices, key) in chunks {
+                                    statistics.chunks_stored += 1;
+                                    if options.stored_bytes {
+                                        statistics.stored_bytes +=
+                                            storage.size_key(&key)?.unwrap_or_default();
+                                    }
+                                    statistics.uncompressed_bytes = statistics
+                                        .uncompressed_bytes
+                                        .zip(chunk_uncompressed_bytes(&array, &chunk_indices))
+                                        .map(|(total, chunk)| total + chunk);
+                                }
+                            }
+                        }
+                        string.push_str(&array_annotation(&array, &statistics, *options));
+                    }
+                    Err(err) => {
+                        let _ = write!(string, " ({err})");
+                    }
+                }
+            }
+            string.push('\n');
+        }
+        Ok(string)
+    }
+
+    #[cfg(feature = "async")]
+    /// Async variant of [`hierarchy_tree_opt`](Node::hierarchy_tree_opt).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_hierarchy_tree_opt<
+        TStorage: ?Sized + AsyncReadableStorageTraits + AsyncListableStorageTraits + 'static,
+    >(
+        &self,
+        storage: &Arc<TStorage>,
+        options: &NodeHierarchyTreeOptions,
+    ) -> Result<String, StorageError> {
+        let mut string = String::default();
+        for (depth, node) in flatten_tree(self) {
+            let name = if depth == 0 {
+                "/".to_string()
+            } else {
+                node.name().as_str().to_string()
+            };
+            string.push_str(&" ".repeat(depth * 2));
+            string.push_str(&node_description(&name, &node.metadata));
+            if let NodeMetadata::Array(array_metadata) = &node.metadata {
+                match Array::new_with_metadata(
+                    storage.clone(),
+                    node.path.as_str(),
+                    array_metadata.clone(),
+                ) {
+                    Ok(array) => {
+                        let mut statistics = ArrayChunkStatistics::default();
+                        if options.requires_chunks() {
+                            let prefix: StorePrefix = (&node.path).try_into()?;
+                            let keys = storage.list_prefix(&prefix).await?.into_iter().collect();
+                            if let Some((chunks_total, chunks)) = stored_chunks(&array, &keys) {
+                                statistics.chunks_total = Some(chunks_total);
+                                statistics.uncompressed_bytes = Some(0);
+                                for (chunk_indices, key) in chunks {
+                                    statistics.chunks_stored += 1;
+                                    if options.stored_bytes {
+                                        statistics.stored_bytes +=
+                                            storage.size_key(&key).await?.unwrap_or_default();
+                                    }
+                                    statistics.uncompressed_bytes = statistics
+                                        .uncompressed_bytes
+                                        .zip(chunk_uncompressed_bytes(&array, &chunk_indices))
+                                        .map(|(total, chunk)| total + chunk);
+                                }
+                            }
+                        }
+                        string.push_str(&array_annotation(&array, &statistics, *options));
+                    }
+                    Err(err) => {
+                        let _ = write!(string, " ({err})");
+                    }
+                }
+            }
+            string.push('\n');
+        }
+        Ok(string)
+    }
+}
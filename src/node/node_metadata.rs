@@ -1,4 +1,11 @@
-use crate::{array::ArrayMetadata, group::GroupMetadata};
+use crate::{
+    array::{ArrayMetadata, ArrayShape, DataType},
+    group::GroupMetadata,
+    metadata::{
+        v2::array::data_type_metadata_v2_to_v3_data_type, ArrayMetadataV2, ArrayMetadataV3,
+        GroupMetadataV2, GroupMetadataV3,
+    },
+};
 
 /// Node metadata ([`ArrayMetadata`] or [`GroupMetadata`]).
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Clone)]
@@ -10,3 +17,108 @@ pub enum NodeMetadata {
     /// Group metadata.
     Group(GroupMetadata),
 }
+
+impl NodeMetadata {
+    /// Returns true if the node is an array.
+    #[must_use]
+    pub const fn is_array(&self) -> bool {
+        matches!(self, Self::Array(_))
+    }
+
+    /// Returns true if the node is a group.
+    #[must_use]
+    pub const fn is_group(&self) -> bool {
+        matches!(self, Self::Group(_))
+    }
+
+    /// Returns the array metadata, or [`None`] if the node is a group.
+    #[must_use]
+    pub const fn as_array(&self) -> Option<&ArrayMetadata> {
+        match self {
+            Self::Array(metadata) => Some(metadata),
+            Self::Group(_) => None,
+        }
+    }
+
+    /// Returns the group metadata, or [`None`] if the node is an array.
+    #[must_use]
+    pub const fn as_group(&self) -> Option<&GroupMetadata> {
+        match self {
+            Self::Array(_) => None,
+            Self::Group(metadata) => Some(metadata),
+        }
+    }
+
+    /// Returns the Zarr V3 array metadata, or [`None`] if the node is not a Zarr V3 array.
+    #[must_use]
+    pub const fn as_array_v3(&self) -> Option<&ArrayMetadataV3> {
+        match self {
+            Self::Array(ArrayMetadata::V3(metadata)) => Some(metadata),
+            _ => None,
+        }
+    }
+
+    /// Returns the Zarr V2 array metadata, or [`None`] if the node is not a Zarr V2 array.
+    #[must_use]
+    pub const fn as_array_v2(&self) -> Option<&ArrayMetadataV2> {
+        match self {
+            Self::Array(ArrayMetadata::V2(metadata)) => Some(metadata),
+            _ => None,
+        }
+    }
+
+    /// Returns the Zarr V3 group metadata, or [`None`] if the node is not a Zarr V3 group.
+    #[must_use]
+    pub const fn as_group_v3(&self) -> Option<&GroupMetadataV3> {
+        match self {
+            Self::Group(GroupMetadata::V3(metadata)) => Some(metadata),
+            _ => None,
+        }
+    }
+
+    /// Returns the Zarr V2 group metadata, or [`None`] if the node is not a Zarr V2 group.
+    #[must_use]
+    pub const fn as_group_v2(&self) -> Option<&GroupMetadataV2> {
+        match self {
+            Self::Group(GroupMetadata::V2(metadata)) => Some(metadata),
+            _ => None,
+        }
+    }
+
+    /// Returns the array shape, or [`None`] if the node is a group.
+    #[must_use]
+    pub fn shape(&self) -> Option<&ArrayShape> {
+        match self {
+            Self::Array(ArrayMetadata::V3(metadata)) => Some(&metadata.shape),
+            Self::Array(ArrayMetadata::V2(metadata)) => Some(&metadata.shape),
+            Self::Group(_) => None,
+        }
+    }
+
+    /// Returns the array data type, or [`None`] if the node is a group or the data type is unsupported.
+    ///
+    /// The data type of a Zarr V2 array is converted to its Zarr V3 equivalent.
+    #[must_use]
+    pub fn data_type(&self) -> Option<DataType> {
+        match self {
+            Self::Array(ArrayMetadata::V3(metadata)) => {
+                DataType::from_metadata(&metadata.data_type).ok()
+            }
+            Self::Array(ArrayMetadata::V2(metadata)) => {
+                data_type_metadata_v2_to_v3_data_type(&metadata.dtype).ok()
+            }
+            Self::Group(_) => None,
+        }
+    }
+
+    /// Returns the attributes of the node.
+    #[must_use]
+    pub const fn attributes(&self) -> &serde_json::Map<String, serde_json::Value> {
+        match self {
+            Self::Array(ArrayMetadata::V3(metadata)) => &metadata.attributes,
+            Self::Array(ArrayMetadata::V2(metadata)) => &metadata.attributes,
+            Self::Group(GroupMetadata::V3(metadata)) => &metadata.attributes,
+            Self::Group(GroupMetadata::V2(metadata)) => &metadata.attributes,
+        }
+    }
+}
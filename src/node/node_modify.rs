@@ -0,0 +1,652 @@
+use std::sync::{
+    atomic::{AtomicU64, AtomicUsize, Ordering},
+    Arc,
+};
+
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use rayon_iter_concurrent_limit::iter_concurrent_limit;
+
+use crate::{
+    group::Group,
+    storage::{
+        meta_key, meta_key_v2_array, meta_key_v2_attributes, meta_key_v2_consolidated,
+        meta_key_v2_group, ListableStorageTraits, ReadableStorageTraits,
+        ReadableWritableListableStorageTraits, StorageError, StoreKey, StoreKeys, StorePrefix,
+    },
+};
+
+#[cfg(feature = "async")]
+use futures::{StreamExt, TryStreamExt};
+
+#[cfg(feature = "async")]
+use crate::storage::{
+    AsyncListableStorageTraits, AsyncReadableStorageTraits,
+    AsyncReadableWritableListableStorageTraits,
+};
+
+use super::{
+    Node, NodeCopyOptions, NodeCopyProgress, NodeMetadata, NodeModifyError, NodeName,
+    NodeNameError, NodePath,
+};
+
+/// The names of Zarr V3 and V2 metadata documents.
+const METADATA_NAMES: [&str; 5] = ["zarr.json", ".zarray", ".zgroup", ".zattrs", ".zmetadata"];
+
+/// Returns true if `key` is a metadata document.
+fn is_metadata_key(key: &StoreKey) -> bool {
+    let name = key.as_str().rsplit('/').next().unwrap_or_default();
+    METADATA_NAMES.contains(&name)
+}
+
+/// The store keys of the metadata documents of the node at `path`.
+fn node_metadata_keys(path: &NodePath) -> [StoreKey; 5] {
+    [
+        meta_key(path),
+        meta_key_v2_array(path),
+        meta_key_v2_group(path),
+        meta_key_v2_attributes(path),
+        meta_key_v2_consolidated(path),
+    ]
+}
+
+/// Pairs of source and destination keys.
+type KeyPairs = Vec<(StoreKey, StoreKey)>;
+
+fn node_prefix(path: &NodePath) -> Result<StorePrefix, StorageError> {
+    Ok(path.try_into()?)
+}
+
+/// Create a plan to copy `keys` under the `src` prefix to the `dst` prefix.
+///
+/// Returns pairs of source and destination keys for data, then for metadata ordered from the deepest to the shallowest node.
+/// Copying in this order means that a node only becomes visible at the destination once everything below it has been copied.
+fn copy_plan(
+    keys: StoreKeys,
+    src: &StorePrefix,
+    dst: &StorePrefix,
+) -> Result<(KeyPairs, KeyPairs), StorageError> {
+    let (mut metadata_keys, data_keys): (Vec<_>, Vec<_>) =
+        keys.into_iter().partition(is_metadata_key);
+    metadata_keys.sort_by_key(|key| std::cmp::Reverse(key.as_str().matches('/').count()));
+    let with_destination = |keys: Vec<StoreKey>| {
+        keys.into_iter()
+            .map(|key| {
+                let relative = &key.as_str()[src.as_str().len()..];
+                let key_dst = StoreKey::new(format!("{}{relative}", dst.as_str()))?;
+                Ok((key, key_dst))
+            })
+            .collect::<Result<Vec<_>, StorageError>>()
+    };
+    Ok((
+        with_destination(data_keys)?,
+        with_destination(metadata_keys)?,
+    ))
+}
+
+/// Tracks the progress of a node copy.
+struct CopyProgress<'a> {
+    options: &'a NodeCopyOptions,
+    keys_total: usize,
+    keys_copied: AtomicUsize,
+    bytes_copied: AtomicU64,
+}
+
+impl<'a> CopyProgress<'a> {
+    fn new(options: &'a NodeCopyOptions, keys_total: usize) -> Self {
+        Self {
+            options,
+            keys_total,
+            keys_copied: AtomicUsize::new(0),
+            bytes_copied: AtomicU64::new(0),
+        }
+    }
+
+    /// Record that a key with `bytes` bytes was copied.
+    fn copied(&self, bytes: usize) {
+        let keys_copied = self.keys_copied.fetch_add(1, Ordering::Relaxed) + 1;
+        let bytes_copied =
+            self.bytes_copied.fetch_add(bytes as u64, Ordering::Relaxed) + bytes as u64;
+        if let Some(progress_callback) = self.options.progress_callback() {
+            progress_callback(NodeCopyProgress::new(
+                keys_copied,
+                self.keys_total,
+                bytes_copied,
+            ));
+        }
+    }
+}
+
+impl Node {
+    /// Check that the node can be moved to `new_path` and return the source and destination prefixes.
+    fn move_prefixes(
+        &self,
+        new_path: &NodePath,
+    ) -> Result<(StorePrefix, StorePrefix), NodeModifyError> {
+        let src = node_prefix(&self.path)?;
+        let dst = node_prefix(new_path)?;
+        if dst.as_str().starts_with(src.as_str()) {
+            // The root node cannot be moved, and a node cannot be moved to itself or below itself
+            return Err(NodeModifyError::InvalidDestination(new_path.clone()));
+        }
+        Ok((src, dst))
+    }
+
+    /// The path of a sibling of the node with `name`.
+    fn sibling_path(&self, name: &str) -> Result<NodePath, NodeModifyError> {
+        if name.is_empty() || !NodeName::validate(name) {
+            return Err(NodeNameError(name.to_string()).into());
+        }
+        if self.is_root() {
+            return Err(NodeModifyError::InvalidDestination(self.path.clone()));
+        }
+        let parent = self
+            .path
+            .as_str()
+            .rsplit_once('/')
+            .map_or("", |(parent, _)| parent);
+        Ok(NodePath::new(&format!("{parent}/{name}"))?)
+    }
+
+    /// Set the path of the node to `new_path` and update the paths of its descendants.
+    fn set_path(&mut self, new_path: &NodePath) -> Result<(), NodeModifyError> {
+        let old_path = self.path.as_str().to_string();
+        self.set_path_prefix(&old_path, new_path.as_str())
+    }
+
+    fn set_path_prefix(&mut self, old_path: &str, new_path: &str) -> Result<(), NodeModifyError> {
+        let relative = &self.path.as_str()[old_path.len()..];
+        self.path = NodePath::new(&format!("{new_path}{relative}"))?;
+        for child in &mut self.children {
+            child.set_path_prefix(old_path, new_path)?;
+        }
+        Ok(())
+    }
+
+    /// Returns an error if the node is a group with children and `recursive` is false.
+    fn check_delete(&self, keys: &StoreKeys, recursive: bool) -> Result<(), NodeModifyError> {
+        if !recursive && matches!(self.metadata, NodeMetadata::Group(_)) {
+            let metadata_keys = node_metadata_keys(&self.path);
+            if keys.iter().any(|key| !metadata_keys.contains(key)) {
+                return Err(NodeModifyError::NotEmpty(self.path.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete the node from `storage`.
+    ///
+    /// All keys under the node prefix are erased, including array chunks.
+    /// The metadata of the node is erased first, so the node is no longer visible if erasing the remaining keys fails.
+    ///
+    /// A group with children can only be deleted if `recursive` is true.
+    /// An array can always be deleted.
+    ///
+    /// # Errors
+    /// Returns [`NodeModifyError::NotEmpty`] if the node is a group with children and `recursive` is false, or a [`NodeModifyError`] if there is an underlying store error.
+    pub fn delete<TStorage: ?Sized + ReadableWritableListableStorageTraits>(
+        &self,
+        storage: &Arc<TStorage>,
+        recursive: bool,
+    ) -> Result<(), NodeModifyError> {
+        let prefix = node_prefix(&self.path)?;
+        let keys = storage.list_prefix(&prefix)?;
+        self.check_delete(&keys, recursive)?;
+        storage.erase_values(&node_metadata_keys(&self.path))?;
+        storage.erase_prefix(&prefix)?;
+        Ok(())
+    }
+
+    /// Move the node to `new_path` in `storage`, including its metadata, chunks, and descendants.
+    ///
+    /// Keys are copied to the destination, then erased from the source.
+    /// Metadata is copied after data from the deepest to the shallowest node, so the node is only visible at `new_path` once it has been completely copied.
+    /// If copying fails, any keys copied to the destination are erased.
+    /// If erasing the source fails, the node remains at the destination and the source may be partially erased.
+    ///
+    /// The paths of the node and its children are updated on success.
+    ///
+    /// # Errors
+    /// Returns a [`NodeModifyError`] if:
+    ///  - `new_path` is not a valid node path,
+    ///  - the node is the root node, or `new_path` is the node path or below it,
+    ///  - a node already exists at `new_path`, or
+    ///  - there is an underlying store error.
+    pub fn move_to<TStorage: ?Sized + ReadableWritableListableStorageTraits>(
+        &mut self,
+        storage: &Arc<TStorage>,
+        new_path: &str,
+    ) -> Result<(), NodeModifyError> {
+        let new_path = NodePath::new(new_path)?;
+        let (src, dst) = self.move_prefixes(&new_path)?;
+        if !storage.list_prefix(&dst)?.is_empty() {
+            return Err(NodeModifyError::DestinationExists(new_path));
+        }
+
+        let (data, metadata) = copy_plan(storage.list_prefix(&src)?, &src, &dst)?;
+        let copy = || -> Result<(), StorageError> {
+            for (key_src, key_dst) in data.iter().chain(&metadata) {
+                if let Some(value) = storage.get(key_src)? {
+                    storage.set(key_dst, value)?;
+                }
+            }
+            Ok(())
+        };
+        if let Err(err) = copy() {
+            // Roll back, the destination was empty before copying
+            let _ = storage.erase_prefix(&dst);
+            return Err(err.into());
+        }
+
+        storage.erase_values(&node_metadata_keys(&self.path))?;
+        storage.erase_prefix(&src)?;
+        self.set_path(&new_path)
+    }
+
+    /// Rename the node to `name` in `storage`, keeping the same parent.
+    ///
+    /// See [`move_to`](Node::move_to).
+    ///
+    /// # Errors
+    /// Returns a [`NodeModifyError`] if `name` is not a valid node name, the node is the root node, a sibling named `name` already exists, or there is an underlying store error.
+    pub fn rename<TStorage: ?Sized + ReadableWritableListableStorageTraits>(
+        &mut self,
+        storage: &Arc<TStorage>,
+        name: &str,
+    ) -> Result<(), NodeModifyError> {
+        let new_path = self.sibling_path(name)?;
+        self.move_to(storage, new_path.as_str())
+    }
+
+    /// Async variant of [`delete`](Node::delete).
+    #[cfg(feature = "async")]
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_delete<TStorage: ?Sized + AsyncReadableWritableListableStorageTraits>(
+        &self,
+        storage: &Arc<TStorage>,
+        recursive: bool,
+    ) -> Result<(), NodeModifyError> {
+        let prefix = node_prefix(&self.path)?;
+        let keys = storage.list_prefix(&prefix).await?;
+        self.check_delete(&keys, recursive)?;
+        storage
+            .erase_values(&node_metadata_keys(&self.path))
+            .await?;
+        storage.erase_prefix(&prefix).await?;
+        Ok(())
+    }
+
+    /// Async variant of [`move_to`](Node::move_to).
+    ///
+    /// Data is copied concurrently.
+    #[cfg(feature = "async")]
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_move_to<TStorage: ?Sized + AsyncReadableWritableListableStorageTraits>(
+        &mut self,
+        storage: &Arc<TStorage>,
+        new_path: &str,
+    ) -> Result<(), NodeModifyError> {
+        let new_path = NodePath::new(new_path)?;
+        let (src, dst) = self.move_prefixes(&new_path)?;
+        if !storage.list_prefix(&dst).await?.is_empty() {
+            return Err(NodeModifyError::DestinationExists(new_path));
+        }
+
+        let (data, metadata) = copy_plan(storage.list_prefix(&src).await?, &src, &dst)?;
+        let copy_key = |key_src: &StoreKey, key_dst: &StoreKey| {
+            let (key_src, key_dst) = (key_src.clone(), key_dst.clone());
+            async move {
+                if let Some(value) = storage.get(&key_src).await? {
+                    storage.set(&key_dst, value).await?;
+                }
+                Ok::<_, StorageError>(())
+            }
+        };
+        let copy = async {
+            futures::future::try_join_all(
+                data.iter()
+                    .map(|(key_src, key_dst)| copy_key(key_src, key_dst)),
+            )
+            .await?;
+            for (key_src, key_dst) in &metadata {
+                copy_key(key_src, key_dst).await?;
+            }
+            Ok::<_, StorageError>(())
+        };
+        if let Err(err) = copy.await {
+            // Roll back, the destination was empty before copying
+            let _ = storage.erase_prefix(&dst).await;
+            return Err(err.into());
+        }
+
+        storage
+            .erase_values(&node_metadata_keys(&self.path))
+            .await?;
+        storage.erase_prefix(&src).await?;
+        self.set_path(&new_path)
+    }
+
+    /// Async variant of [`rename`](Node::rename).
+    #[cfg(feature = "async")]
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_rename<TStorage: ?Sized + AsyncReadableWritableListableStorageTraits>(
+        &mut self,
+        storage: &Arc<TStorage>,
+        name: &str,
+    ) -> Result<(), NodeModifyError> {
+        let new_path = self.sibling_path(name)?;
+        self.async_move_to(storage, new_path.as_str()).await
+    }
+}
+
+impl Node {
+    /// Copy the node from `storage` to `dst_path` in `dst_storage`, including its metadata, chunks, and descendants.
+    ///
+    /// Data is copied concurrently, then metadata is copied from the deepest to the shallowest node, so the node is only visible at `dst_path` once it has been completely copied.
+    /// If copying fails, any keys copied to the destination are erased.
+    ///
+    /// Consolidated metadata is copied as is, unless [`consolidate_metadata`](NodeCopyOptions::consolidate_metadata) is set.
+    ///
+    /// # Errors
+    /// Returns a [`NodeModifyError`] if:
+    ///  - `dst_path` is not a valid node path,
+    ///  - a node already exists at `dst_path` in `dst_storage`, or
+    ///  - there is an underlying store error.
+    pub fn copy_to<
+        TStorage: ?Sized + ReadableStorageTraits + ListableStorageTraits,
+        TDstStorage: ?Sized + ReadableWritableListableStorageTraits,
+    >(
+        &self,
+        storage: &Arc<TStorage>,
+        dst_storage: &Arc<TDstStorage>,
+        dst_path: &str,
+        options: &NodeCopyOptions,
+    ) -> Result<(), NodeModifyError> {
+        let dst_path = NodePath::new(dst_path)?;
+        let (src, dst) = (node_prefix(&self.path)?, node_prefix(&dst_path)?);
+        if !dst_storage.list_prefix(&dst)?.is_empty() {
+            return Err(NodeModifyError::DestinationExists(dst_path));
+        }
+
+        let (data, metadata) = copy_plan(storage.list_prefix(&src)?, &src, &dst)?;
+        let progress = CopyProgress::new(options, data.len() + metadata.len());
+        let copy_key = |(key_src, key_dst): &(StoreKey, StoreKey)| -> Result<(), StorageError> {
+            if let Some(value) = storage.get(key_src)? {
+                let bytes = value.len();
+                dst_storage.set(key_dst, value)?;
+                progress.copied(bytes);
+            }
+            Ok(())
+        };
+        let copy = || -> Result<(), StorageError> {
+            iter_concurrent_limit!(options.concurrent_target(), &data, try_for_each, copy_key)?;
+            metadata.iter().try_for_each(copy_key)
+        };
+        if let Err(err) = copy() {
+            // Roll back, the destination was empty before copying
+            let _ = dst_storage.erase_prefix(&dst);
+            return Err(err.into());
+        }
+
+        if let (NodeMetadata::Group(metadata), true) =
+            (&self.metadata, options.consolidate_metadata())
+        {
+            let mut group =
+                Group::new_with_metadata(dst_storage.clone(), dst_path.as_str(), metadata.clone())?;
+            group.consolidate_and_store()?;
+        }
+        Ok(())
+    }
+
+    /// Async variant of [`copy_to`](Node::copy_to).
+    #[cfg(feature = "async")]
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_copy_to<
+        TStorage: ?Sized + AsyncReadableStorageTraits + AsyncListableStorageTraits,
+        TDstStorage: ?Sized + AsyncReadableWritableListableStorageTraits,
+    >(
+        &self,
+        storage: &Arc<TStorage>,
+        dst_storage: &Arc<TDstStorage>,
+        dst_path: &str,
+        options: &NodeCopyOptions,
+    ) -> Result<(), NodeModifyError> {
+        let dst_path = NodePath::new(dst_path)?;
+        let (src, dst) = (node_prefix(&self.path)?, node_prefix(&dst_path)?);
+        if !dst_storage.list_prefix(&dst).await?.is_empty() {
+            return Err(NodeModifyError::DestinationExists(dst_path));
+        }
+
+        let (data, metadata) = copy_plan(storage.list_prefix(&src).await?, &src, &dst)?;
+        let progress = CopyProgress::new(options, data.len() + metadata.len());
+        let copy_key = |(key_src, key_dst): &(StoreKey, StoreKey)| {
+            let (key_src, key_dst) = (key_src.clone(), key_dst.clone());
+            let progress = &progress;
+            async move {
+                if let Some(value) = storage.get(&key_src).await? {
+                    let bytes = value.len();
+                    dst_storage.set(&key_dst, value).await?;
+                    progress.copied(bytes);
+                }
+                Ok::<_, StorageError>(())
+            }
+        };
+        let copy = async {
+            futures::stream::iter(&data)
+                .map(Ok)
+                .try_for_each_concurrent(Some(options.concurrent_target()), copy_key)
+                .await?;
+            for key_pair in &metadata {
+                copy_key(key_pair).await?;
+            }
+            Ok::<_, StorageError>(())
+        };
+        if let Err(err) = copy.await {
+            // Roll back, the destination was empty before copying
+            let _ = dst_storage.erase_prefix(&dst).await;
+            return Err(err.into());
+        }
+
+        if let (NodeMetadata::Group(metadata), true) =
+            (&self.metadata, options.consolidate_metadata())
+        {
+            let mut group =
+                Group::new_with_metadata(dst_storage.clone(), dst_path.as_str(), metadata.clone())?;
+            group.async_consolidate_and_store().await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        array::{ArrayBuilder, DataType, FillValue},
+        array_subset::ArraySubset,
+        group::GroupBuilder,
+        storage::{store::MemoryStore, ListableStorageTraits},
+    };
+
+    use super::*;
+
+    #[test]
+    fn node_delete_move_rename() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        let array_builder = ArrayBuilder::new(
+            vec![2, 2],
+            DataType::UInt8,
+            vec![1, 1].try_into()?,
+            FillValue::from(0u8),
+        );
+        let group = GroupBuilder::new().build(store.clone(), "/")?;
+        group.store_metadata()?;
+        let group_a = group.create_group("a")?;
+        let array = group_a.create_array("array", &array_builder)?;
+        array.store_chunk_elements(&[0, 1], &[1u8])?;
+        group.create_group("b")?;
+
+        // Invalid destinations
+        let mut node = Node::open(&store, "/a")?;
+        assert!(matches!(
+            node.move_to(&store, "/b"),
+            Err(NodeModifyError::DestinationExists(_))
+        ));
+        assert!(matches!(
+            node.move_to(&store, "/a/c"),
+            Err(NodeModifyError::InvalidDestination(_))
+        ));
+        assert!(node.rename(&store, "c/d").is_err());
+        assert!(Node::open(&store, "/")?.move_to(&store, "/c").is_err());
+
+        // Move and rename
+        node.move_to(&store, "/b/c")?;
+        assert_eq!(node.path().as_str(), "/b/c");
+        assert_eq!(node.children()[0].path().as_str(), "/b/c/array");
+        node.rename(&store, "d")?;
+        assert_eq!(
+            Node::open(&store, "/")?.hierarchy_tree(),
+            "/\n  b\n    d\n      array [2, 2] uint8\n"
+        );
+        assert_eq!(
+            crate::array::Array::open(store.clone(), "/b/d/array")?
+                .retrieve_array_subset_elements::<u8>(&ArraySubset::new_with_shape(vec![2, 2]))?,
+            vec![0, 1, 0, 0]
+        );
+
+        // Delete
+        let node = Node::open(&store, "/b")?;
+        assert!(matches!(
+            node.delete(&store, false),
+            Err(NodeModifyError::NotEmpty(_))
+        ));
+        Node::open(&store, "/b/d/array")?.delete(&store, false)?;
+        Node::open(&store, "/b/d")?.delete(&store, false)?;
+        node.delete(&store, true)?;
+        assert_eq!(store.list()?, &[meta_key(&NodePath::root())]);
+        Ok(())
+    }
+
+    #[test]
+    fn node_copy_to() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        let array_builder = ArrayBuilder::new(
+            vec![2, 2],
+            DataType::UInt8,
+            vec![1, 1].try_into()?,
+            FillValue::from(0u8),
+        );
+        let group = GroupBuilder::new().build(store.clone(), "/")?;
+        group.store_metadata()?;
+        let group_a = group.create_group("a")?;
+        let array = group_a.create_array("array", &array_builder)?;
+        array.store_chunk_elements(&[0, 1], &[1u8])?;
+        group.create_group("b")?;
+
+        let dst_store = Arc::new(MemoryStore::new());
+        let keys_copied = Arc::new(AtomicUsize::new(0));
+        let mut options = NodeCopyOptions::default();
+        options
+            .set_consolidate_metadata(true)
+            .set_progress_callback(Some(Arc::new({
+                let keys_copied = keys_copied.clone();
+                move |progress: NodeCopyProgress| {
+                    assert_eq!(progress.keys_total(), 3);
+                    keys_copied.fetch_max(progress.keys_copied(), Ordering::Relaxed);
+                }
+            })));
+        let node = Node::open(&store, "/a")?;
+        node.copy_to(&store, &dst_store, "/published", &options)?;
+        assert_eq!(keys_copied.load(Ordering::Relaxed), 3);
+        assert_eq!(
+            Node::open_consolidated(&dst_store, "/published")?.hierarchy_tree(),
+            "/\n  array [2, 2] uint8\n"
+        );
+        assert_eq!(
+            crate::array::Array::open(dst_store.clone(), "/published/array")?
+                .retrieve_array_subset_elements::<u8>(&ArraySubset::new_with_shape(vec![2, 2]))?,
+            vec![0, 1, 0, 0]
+        );
+        assert!(matches!(
+            node.copy_to(&store, &dst_store, "/published", &options),
+            Err(NodeModifyError::DestinationExists(_))
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn node_delete_move_rename_async() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(crate::storage::store::AsyncObjectStore::new(
+            object_store::memory::InMemory::new(),
+        ));
+        let array_builder = ArrayBuilder::new(
+            vec![2, 2],
+            DataType::UInt8,
+            vec![1, 1].try_into()?,
+            FillValue::from(0u8),
+        );
+        let group = GroupBuilder::new().build(store.clone(), "/")?;
+        group.async_store_metadata().await?;
+        let array = group.async_create_array("a", &array_builder).await?;
+        array.async_store_chunk_elements(&[0, 1], &[1u8]).await?;
+
+        let mut node = Node::async_open(store.clone(), "/a").await?;
+        node.async_rename(&store, "b").await?;
+        assert_eq!(node.path().as_str(), "/b");
+        assert_eq!(
+            crate::array::Array::async_open(store.clone(), "/b")
+                .await?
+                .async_retrieve_array_subset_elements::<u8>(&ArraySubset::new_with_shape(vec![
+                    2, 2
+                ]))
+                .await?,
+            vec![0, 1, 0, 0]
+        );
+        node.async_delete(&store, false).await?;
+        assert_eq!(Node::async_open(store, "/").await?.hierarchy_tree(), "/\n");
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn node_copy_to_async() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(crate::storage::store::AsyncObjectStore::new(
+            object_store::memory::InMemory::new(),
+        ));
+        let array_builder = ArrayBuilder::new(
+            vec![2, 2],
+            DataType::UInt8,
+            vec![1, 1].try_into()?,
+            FillValue::from(0u8),
+        );
+        let group = GroupBuilder::new().build(store.clone(), "/")?;
+        group.async_store_metadata().await?;
+        let array = group.async_create_array("a", &array_builder).await?;
+        array.async_store_chunk_elements(&[0, 1], &[1u8]).await?;
+
+        let dst_store = Arc::new(crate::storage::store::AsyncObjectStore::new(
+            object_store::memory::InMemory::new(),
+        ));
+        let mut options = NodeCopyOptions::default();
+        options.set_consolidate_metadata(true);
+        Node::async_open(store.clone(), "/")
+            .await?
+            .async_copy_to(&store, &dst_store, "/copy", &options)
+            .await?;
+        assert_eq!(
+            Node::async_open_consolidated(dst_store.clone(), "/copy")
+                .await?
+                .hierarchy_tree(),
+            "/\n  a [2, 2] uint8\n"
+        );
+        assert_eq!(
+            crate::array::Array::async_open(dst_store, "/copy/a")
+                .await?
+                .async_retrieve_array_subset_elements::<u8>(&ArraySubset::new_with_shape(vec![
+                    2, 2
+                ]))
+                .await?,
+            vec![0, 1, 0, 0]
+        );
+        Ok(())
+    }
+}
@@ -11,7 +11,7 @@ pub struct NodeName(String);
 /// An invalid node name.
 #[derive(Debug, Error)]
 #[error("invalid node name {0}")]
-pub struct NodeNameError(String);
+pub struct NodeNameError(pub(crate) String);
 
 impl NodeName {
     /// Create a new Zarr node name from `name`.
@@ -0,0 +1,145 @@
+use std::{collections::HashSet, sync::Arc};
+
+use crate::{
+    array::Array,
+    storage::{ListableStorageTraits, ReadableStorageTraits, StorageError, StoreKey, StorePrefix},
+};
+
+#[cfg(feature = "async")]
+use crate::storage::{AsyncListableStorageTraits, AsyncReadableStorageTraits};
+
+use super::{
+    node_hierarchy_tree::{chunk_uncompressed_bytes, flatten_tree, stored_chunks},
+    Node, NodeMetadata,
+};
+
+/// The storage accounting of a node.
+///
+/// Returned by [`Node::size_info`] and its async variant.
+/// The sizes of a group are aggregated over all arrays in the hierarchy below it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NodeSizeInfo {
+    stored_bytes: u64,
+    chunk_keys: u64,
+    uncompressed_bytes: Option<u64>,
+}
+
+impl NodeSizeInfo {
+    /// Return the total size in bytes of all values stored under the node prefix, including metadata.
+    #[must_use]
+    pub const fn stored_bytes(&self) -> u64 {
+        self.stored_bytes
+    }
+
+    /// Return the number of stored chunk keys.
+    #[must_use]
+    pub const fn chunk_keys(&self) -> u64 {
+        self.chunk_keys
+    }
+
+    /// Return the estimated decoded size in bytes of the stored chunks.
+    ///
+    /// Returns [`None`] if the decoded size of any array is unknown, such as for an array with a variable size data type, an unbounded chunk grid, or unsupported metadata.
+    #[must_use]
+    pub const fn uncompressed_bytes(&self) -> Option<u64> {
+        self.uncompressed_bytes
+    }
+}
+
+/// Return the number of stored chunks of `array` and their decoded size in bytes.
+fn array_chunk_keys_and_uncompressed_bytes<TStorage: ?Sized>(
+    array: &Array<TStorage>,
+    keys: &HashSet<StoreKey>,
+) -> (u64, Option<u64>) {
+    let Some((_, chunks)) = stored_chunks(array, keys) else {
+        return (0, None);
+    };
+    let mut uncompressed_bytes = Some(0);
+    for (chunk_indices, _) in &chunks {
+        uncompressed_bytes = uncompressed_bytes
+            .zip(chunk_uncompressed_bytes(array, chunk_indices))
+            .map(|(total, chunk)| total + chunk);
+    }
+    (chunks.len() as u64, uncompressed_bytes)
+}
+
+impl Node {
+    /// Return the storage accounting of the node.
+    ///
+    /// The stored size is the total size of all values under the node prefix in `storage`.
+    /// The chunk keys and estimated uncompressed size are computed from the stored chunks of each array in the hierarchy below the node, as identified by their metadata.
+    /// Only the hierarchy loaded into the node is considered, so the node should be opened (rather than created with [`Node::new_with_metadata`]) to account for all of its descendants.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying store error.
+    pub fn size_info<TStorage: ?Sized + ReadableStorageTraits + ListableStorageTraits + 'static>(
+        &self,
+        storage: &Arc<TStorage>,
+    ) -> Result<NodeSizeInfo, StorageError> {
+        let prefix: StorePrefix = (&self.path).try_into()?;
+        let mut size_info = NodeSizeInfo {
+            stored_bytes: storage.size_prefix(&prefix)?,
+            chunk_keys: 0,
+            uncompressed_bytes: Some(0),
+        };
+        for (_, node) in flatten_tree(self) {
+            if let NodeMetadata::Array(array_metadata) = &node.metadata {
+                let (chunk_keys, uncompressed_bytes) = if let Ok(array) = Array::new_with_metadata(
+                    storage.clone(),
+                    node.path.as_str(),
+                    array_metadata.clone(),
+                ) {
+                    let prefix: StorePrefix = (&node.path).try_into()?;
+                    let keys = storage.list_prefix(&prefix)?.into_iter().collect();
+                    array_chunk_keys_and_uncompressed_bytes(&array, &keys)
+                } else {
+                    (0, None)
+                };
+                size_info.chunk_keys += chunk_keys;
+                size_info.uncompressed_bytes = size_info
+                    .uncompressed_bytes
+                    .zip(uncompressed_bytes)
+                    .map(|(total, array)| total + array);
+            }
+        }
+        Ok(size_info)
+    }
+
+    #[cfg(feature = "async")]
+    /// Async variant of [`size_info`](Node::size_info).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_size_info<
+        TStorage: ?Sized + AsyncReadableStorageTraits + AsyncListableStorageTraits + 'static,
+    >(
+        &self,
+        storage: &Arc<TStorage>,
+    ) -> Result<NodeSizeInfo, StorageError> {
+        let prefix: StorePrefix = (&self.path).try_into()?;
+        let mut size_info = NodeSizeInfo {
+            stored_bytes: storage.size_prefix(&prefix).await?,
+            chunk_keys: 0,
+            uncompressed_bytes: Some(0),
+        };
+        for (_, node) in flatten_tree(self) {
+            if let NodeMetadata::Array(array_metadata) = &node.metadata {
+                let (chunk_keys, uncompressed_bytes) = if let Ok(array) = Array::new_with_metadata(
+                    storage.clone(),
+                    node.path.as_str(),
+                    array_metadata.clone(),
+                ) {
+                    let prefix: StorePrefix = (&node.path).try_into()?;
+                    let keys = storage.list_prefix(&prefix).await?.into_iter().collect();
+                    array_chunk_keys_and_uncompressed_bytes(&array, &keys)
+                } else {
+                    (0, None)
+                };
+                size_info.chunk_keys += chunk_keys;
+                size_info.uncompressed_bytes = size_info
+                    .uncompressed_bytes
+                    .zip(uncompressed_bytes)
+                    .map(|(total, array)| total + array);
+            }
+        }
+        Ok(size_info)
+    }
+}
@@ -0,0 +1,261 @@
+//! OME-NGFF metadata.
+//!
+//! Serde types and builders for [OME-NGFF](https://ngff.openmicroscopy.org/0.4/) (version 0.4) bioimaging metadata, and methods to get and set them in [`Group`] attributes:
+//!  - `multiscales`: [`Multiscale`] ([`Group::ome_multiscales`], [`Group::set_ome_multiscales`]),
+//!  - `omero`: [`Omero`] ([`Group::ome_omero`], [`Group::set_ome_omero`]),
+//!  - `labels`: ([`Group::ome_labels`], [`Group::set_ome_labels`]),
+//!  - `image-label`: [`ImageLabel`] ([`Group::ome_image_label`], [`Group::set_ome_image_label`]),
+//!  - `plate`: [`Plate`] ([`Group::ome_plate`], [`Group::set_ome_plate`]), and
+//!  - `well`: [`Well`] ([`Group::ome_well`], [`Group::set_ome_well`]).
+//!
+//! Metadata is validated against the constraints of the specification before it is set.
+//!
+//! ### Example
+//! ```rust
+//! # use std::sync::Arc;
+//! # use zarrs::group::GroupBuilder;
+//! # use zarrs::ome::{Axis, AxisType, MultiscaleBuilder};
+//! # let store = Arc::new(zarrs::storage::store::MemoryStore::new());
+//! let mut group = GroupBuilder::new().build(store, "/image")?;
+//! let multiscale = MultiscaleBuilder::new(vec![
+//!     Axis::new("y", AxisType::Space, Some("micrometer".to_string())),
+//!     Axis::new("x", AxisType::Space, Some("micrometer".to_string())),
+//! ])
+//! .dataset("0", vec![1.0, 1.0])
+//! .dataset("1", vec![2.0, 2.0])
+//! .build()?;
+//! group.set_ome_multiscales(&[multiscale])?;
+//! assert_eq!(group.ome_multiscales()?.unwrap()[0].datasets.len(), 2);
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+mod labels;
+mod multiscales;
+mod omero;
+mod plate;
+
+pub use labels::{ImageLabel, LabelColor, LabelProperties, LabelSource};
+pub use multiscales::{
+    Axis, AxisType, CoordinateTransformation, Dataset, Multiscale, MultiscaleBuilder,
+};
+pub use omero::{Omero, OmeroChannel, OmeroRenderingDefaults, OmeroWindow};
+pub use plate::{Plate, PlateAcquisition, PlateName, PlateWell, Well, WellImage};
+
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+use crate::group::Group;
+
+/// The supported OME-NGFF version.
+pub const OME_NGFF_VERSION: &str = "0.4";
+
+/// An OME-NGFF metadata error.
+#[derive(Debug, Error)]
+pub enum OmeMetadataError {
+    /// Invalid `multiscales` metadata.
+    #[error("invalid multiscales metadata: {0}")]
+    InvalidMultiscale(String),
+    /// Invalid `omero` metadata.
+    #[error("invalid omero metadata: {0}")]
+    InvalidOmero(String),
+    /// Invalid `labels` or `image-label` metadata.
+    #[error("invalid labels metadata: {0}")]
+    InvalidLabels(String),
+    /// Invalid `plate` or `well` metadata.
+    #[error("invalid plate metadata: {0}")]
+    InvalidPlate(String),
+    /// A serialisation or deserialisation error.
+    #[error(transparent)]
+    SerdeError(#[from] serde_json::Error),
+}
+
+impl<TStorage: ?Sized> Group<TStorage> {
+    fn ome_attribute<T: DeserializeOwned>(
+        &self,
+        name: &str,
+    ) -> Result<Option<T>, OmeMetadataError> {
+        self.attributes()
+            .get(name)
+            .map(|value| serde_json::from_value(value.clone()))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    fn set_ome_attribute<T: Serialize + ?Sized>(
+        &mut self,
+        name: &str,
+        value: &T,
+    ) -> Result<&mut Self, OmeMetadataError> {
+        let value = serde_json::to_value(value)?;
+        self.attributes_mut().insert(name.to_string(), value);
+        Ok(self)
+    }
+
+    /// Get the OME-NGFF `multiscales` attribute.
+    ///
+    /// # Errors
+    /// Returns [`OmeMetadataError`] if the attribute is present but cannot be deserialised.
+    pub fn ome_multiscales(&self) -> Result<Option<Vec<Multiscale>>, OmeMetadataError> {
+        self.ome_attribute("multiscales")
+    }
+
+    /// Validate and set the OME-NGFF `multiscales` attribute.
+    ///
+    /// # Errors
+    /// Returns [`OmeMetadataError`] if any multiscale image is invalid.
+    pub fn set_ome_multiscales(
+        &mut self,
+        multiscales: &[Multiscale],
+    ) -> Result<&mut Self, OmeMetadataError> {
+        for multiscale in multiscales {
+            multiscale.validate()?;
+        }
+        self.set_ome_attribute("multiscales", multiscales)
+    }
+
+    /// Get the OME-NGFF `omero` attribute.
+    ///
+    /// # Errors
+    /// Returns [`OmeMetadataError`] if the attribute is present but cannot be deserialised.
+    pub fn ome_omero(&self) -> Result<Option<Omero>, OmeMetadataError> {
+        self.ome_attribute("omero")
+    }
+
+    /// Validate and set the OME-NGFF `omero` attribute.
+    ///
+    /// # Errors
+    /// Returns [`OmeMetadataError`] if the rendering metadata is invalid.
+    pub fn set_ome_omero(&mut self, omero: &Omero) -> Result<&mut Self, OmeMetadataError> {
+        omero.validate()?;
+        self.set_ome_attribute("omero", omero)
+    }
+
+    /// Get the OME-NGFF `labels` attribute, the paths of label image groups relative to the `labels` group.
+    ///
+    /// # Errors
+    /// Returns [`OmeMetadataError`] if the attribute is present but cannot be deserialised.
+    pub fn ome_labels(&self) -> Result<Option<Vec<String>>, OmeMetadataError> {
+        self.ome_attribute("labels")
+    }
+
+    /// Validate and set the OME-NGFF `labels` attribute.
+    ///
+    /// # Errors
+    /// Returns [`OmeMetadataError::InvalidLabels`] if the label paths are not unique.
+    pub fn set_ome_labels(&mut self, labels: &[String]) -> Result<&mut Self, OmeMetadataError> {
+        let unique: std::collections::HashSet<&String> = labels.iter().collect();
+        if unique.len() != labels.len() {
+            return Err(OmeMetadataError::InvalidLabels(
+                "label paths must be unique".to_string(),
+            ));
+        }
+        self.set_ome_attribute("labels", labels)
+    }
+
+    /// Get the OME-NGFF `image-label` attribute.
+    ///
+    /// # Errors
+    /// Returns [`OmeMetadataError`] if the attribute is present but cannot be deserialised.
+    pub fn ome_image_label(&self) -> Result<Option<ImageLabel>, OmeMetadataError> {
+        self.ome_attribute("image-label")
+    }
+
+    /// Validate and set the OME-NGFF `image-label` attribute.
+    ///
+    /// # Errors
+    /// Returns [`OmeMetadataError`] if the label image metadata is invalid.
+    pub fn set_ome_image_label(
+        &mut self,
+        image_label: &ImageLabel,
+    ) -> Result<&mut Self, OmeMetadataError> {
+        image_label.validate()?;
+        self.set_ome_attribute("image-label", image_label)
+    }
+
+    /// Get the OME-NGFF `plate` attribute.
+    ///
+    /// # Errors
+    /// Returns [`OmeMetadataError`] if the attribute is present but cannot be deserialised.
+    pub fn ome_plate(&self) -> Result<Option<Plate>, OmeMetadataError> {
+        self.ome_attribute("plate")
+    }
+
+    /// Validate and set the OME-NGFF `plate` attribute.
+    ///
+    /// # Errors
+    /// Returns [`OmeMetadataError`] if the plate metadata is invalid.
+    pub fn set_ome_plate(&mut self, plate: &Plate) -> Result<&mut Self, OmeMetadataError> {
+        plate.validate()?;
+        self.set_ome_attribute("plate", plate)
+    }
+
+    /// Get the OME-NGFF `well` attribute.
+    ///
+    /// # Errors
+    /// Returns [`OmeMetadataError`] if the attribute is present but cannot be deserialised.
+    pub fn ome_well(&self) -> Result<Option<Well>, OmeMetadataError> {
+        self.ome_attribute("well")
+    }
+
+    /// Validate and set the OME-NGFF `well` attribute.
+    ///
+    /// # Errors
+    /// Returns [`OmeMetadataError`] if the well metadata is invalid.
+    pub fn set_ome_well(&mut self, well: &Well) -> Result<&mut Self, OmeMetadataError> {
+        well.validate()?;
+        self.set_ome_attribute("well", well)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{group::GroupBuilder, storage::store::MemoryStore};
+
+    use super::*;
+
+    #[test]
+    fn ome_group_attributes() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        let mut group = GroupBuilder::new().build(store, "/")?;
+        assert!(group.ome_multiscales()?.is_none());
+
+        let multiscale = MultiscaleBuilder::new(vec![
+            Axis::new("c", AxisType::Channel, None),
+            Axis::new("y", AxisType::Space, None),
+            Axis::new("x", AxisType::Space, None),
+        ])
+        .dataset("0", vec![1.0, 1.0, 1.0])
+        .build()?;
+        let omero: Omero = serde_json::from_str(
+            r#"{"channels": [{"color": "00FF00", "window": {"start": 0, "end": 1500, "min": 0, "max": 65535}}]}"#,
+        )?;
+        group
+            .set_ome_multiscales(std::slice::from_ref(&multiscale))?
+            .set_ome_omero(&omero)?
+            .set_ome_labels(&["cells".to_string()])?;
+        assert_eq!(group.ome_multiscales()?, Some(vec![multiscale.clone()]));
+        assert_eq!(group.ome_omero()?, Some(omero.clone()));
+        assert_eq!(group.ome_labels()?, Some(vec!["cells".to_string()]));
+        assert_eq!(group.attributes()["multiscales"][0]["version"], "0.4");
+
+        // Invalid metadata is not set
+        let mut omero_invalid = omero;
+        omero_invalid.channels[0].color = "green".to_string();
+        assert!(group.set_ome_omero(&omero_invalid).is_err());
+        assert!(group
+            .set_ome_labels(&["cells".to_string(), "cells".to_string()])
+            .is_err());
+        let mut multiscale_invalid = multiscale;
+        multiscale_invalid.axes.truncate(1);
+        assert!(group.set_ome_multiscales(&[multiscale_invalid]).is_err());
+        assert_eq!(group.ome_labels()?, Some(vec!["cells".to_string()]));
+
+        group
+            .attributes_mut()
+            .insert("plate".to_string(), serde_json::json!({"rows": []}));
+        assert!(group.ome_plate().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::OmeMetadataError;
+
+/// The colour of a label value in [`ImageLabel`] metadata.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct LabelColor {
+    /// The label value.
+    #[serde(rename = "label-value")]
+    pub label_value: u64,
+    /// The colour as RGBA values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rgba: Option<[u8; 4]>,
+}
+
+/// The properties of a label value in [`ImageLabel`] metadata.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct LabelProperties {
+    /// The label value.
+    #[serde(rename = "label-value")]
+    pub label_value: u64,
+    /// Arbitrary properties of the label value.
+    #[serde(flatten)]
+    pub properties: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The source image of [`ImageLabel`] metadata.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct LabelSource {
+    /// The path of the source image group relative to the label image group.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+}
+
+/// OME-NGFF `image-label` metadata of a label image group.
+///
+/// See <https://ngff.openmicroscopy.org/0.4/#label-md>.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ImageLabel {
+    /// The OME-NGFF version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The colours of label values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub colors: Option<Vec<LabelColor>>,
+    /// The properties of label values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub properties: Option<Vec<LabelProperties>>,
+    /// The source image.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<LabelSource>,
+}
+
+impl ImageLabel {
+    /// Validate the label image metadata.
+    ///
+    /// # Errors
+    /// Returns [`OmeMetadataError::InvalidLabels`] if label values of colours are not unique.
+    pub fn validate(&self) -> Result<(), OmeMetadataError> {
+        if let Some(colors) = &self.colors {
+            let label_values: HashSet<u64> = colors.iter().map(|color| color.label_value).collect();
+            if label_values.len() != colors.len() {
+                return Err(OmeMetadataError::InvalidLabels(
+                    "label values of colours must be unique".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,410 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::{OmeMetadataError, OME_NGFF_VERSION};
+
+/// The type of an [`Axis`].
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum AxisType {
+    /// A spatial axis.
+    Space,
+    /// A time axis.
+    Time,
+    /// A channel axis.
+    Channel,
+    /// Any other axis type.
+    #[serde(untagged)]
+    Custom(String),
+}
+
+/// An axis of a [`Multiscale`] image.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct Axis {
+    /// The name of the axis.
+    pub name: String,
+    /// The type of the axis.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub axis_type: Option<AxisType>,
+    /// The unit of the axis, e.g. `micrometer` or `second`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+}
+
+impl Axis {
+    /// Create a new axis with a `name`, `axis_type`, and optional `unit`.
+    #[must_use]
+    pub fn new(name: impl Into<String>, axis_type: AxisType, unit: Option<String>) -> Self {
+        Self {
+            name: name.into(),
+            axis_type: Some(axis_type),
+            unit,
+        }
+    }
+}
+
+/// A coordinate transformation of a [`Dataset`] or [`Multiscale`] image.
+///
+/// Only inline transformation parameters are supported (not `path`).
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CoordinateTransformation {
+    /// The identity transformation.
+    Identity,
+    /// A scale transformation, with a scale for each axis.
+    Scale {
+        /// The scale of each axis.
+        scale: Vec<f64>,
+    },
+    /// A translation transformation, with a translation for each axis.
+    Translation {
+        /// The translation of each axis.
+        translation: Vec<f64>,
+    },
+}
+
+impl CoordinateTransformation {
+    /// Returns the number of axes of the transformation, or [`None`] for the identity transformation.
+    fn len(&self) -> Option<usize> {
+        match self {
+            Self::Identity => None,
+            Self::Scale { scale } => Some(scale.len()),
+            Self::Translation { translation } => Some(translation.len()),
+        }
+    }
+}
+
+/// A resolution level of a [`Multiscale`] image.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Dataset {
+    /// The path of the array relative to the multiscale group.
+    pub path: String,
+    /// The transformations from array coordinates to physical coordinates.
+    ///
+    /// Must contain exactly one scale transformation, optionally followed by a translation transformation.
+    pub coordinate_transformations: Vec<CoordinateTransformation>,
+}
+
+/// An OME-NGFF multiscale image, an element of the `multiscales` attribute of an image group.
+///
+/// See <https://ngff.openmicroscopy.org/0.4/#multiscale-md>.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Multiscale {
+    /// The OME-NGFF version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The name of the image.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The axes of the image.
+    pub axes: Vec<Axis>,
+    /// The resolution levels of the image, from highest to lowest resolution.
+    pub datasets: Vec<Dataset>,
+    /// Transformations applied to all datasets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coordinate_transformations: Option<Vec<CoordinateTransformation>>,
+    /// The type of downscaling, e.g. `gaussian`.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub downscaling_type: Option<String>,
+    /// Additional information about the downscaling method.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+fn validate_transformations(
+    transformations: &[CoordinateTransformation],
+    dimensionality: usize,
+    dataset: bool,
+) -> Result<(), OmeMetadataError> {
+    let invalid = |reason: &str| Err(OmeMetadataError::InvalidMultiscale(reason.to_string()));
+    if transformations
+        .iter()
+        .filter_map(CoordinateTransformation::len)
+        .any(|len| len != dimensionality)
+    {
+        return invalid(
+            "the length of a coordinate transformation does not match the number of axes",
+        );
+    }
+    if dataset {
+        match transformations {
+            [CoordinateTransformation::Scale { .. }]
+            | [CoordinateTransformation::Scale { .. }, CoordinateTransformation::Translation { .. }] => {}
+            _ => {
+                return invalid(
+                    "the coordinate transformations of a dataset must be a scale, optionally followed by a translation",
+                )
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Multiscale {
+    /// Validate the multiscale image metadata.
+    ///
+    /// # Errors
+    /// Returns [`OmeMetadataError::InvalidMultiscale`] if:
+    ///  - there are not 2 to 5 axes, or axis names are not unique,
+    ///  - there are not 2 or 3 space axes, more than one time or channel axis, or the axes are not ordered time, channel or custom, then space,
+    ///  - there are no datasets, or
+    ///  - any coordinate transformations are invalid.
+    pub fn validate(&self) -> Result<(), OmeMetadataError> {
+        let invalid = |reason: &str| Err(OmeMetadataError::InvalidMultiscale(reason.to_string()));
+        let dimensionality = self.axes.len();
+        if !(2..=5).contains(&dimensionality) {
+            return invalid("there must be 2 to 5 axes");
+        }
+        let names: HashSet<&str> = self.axes.iter().map(|axis| axis.name.as_str()).collect();
+        if names.len() != dimensionality {
+            return invalid("axis names must be unique");
+        }
+
+        let count = |axis_type: &AxisType| {
+            self.axes
+                .iter()
+                .filter(|axis| axis.axis_type.as_ref() == Some(axis_type))
+                .count()
+        };
+        if !(2..=3).contains(&count(&AxisType::Space)) {
+            return invalid("there must be 2 or 3 space axes");
+        }
+        if count(&AxisType::Time) > 1 || count(&AxisType::Channel) > 1 {
+            return invalid("there must be at most one time axis and one channel axis");
+        }
+        let order = |axis: &Axis| match axis.axis_type {
+            Some(AxisType::Time) => 0,
+            Some(AxisType::Channel | AxisType::Custom(_)) | None => 1,
+            Some(AxisType::Space) => 2,
+        };
+        if !self
+            .axes
+            .windows(2)
+            .all(|axes| order(&axes[0]) <= order(&axes[1]))
+        {
+            return invalid("axes must be ordered time, channel or custom, then space");
+        }
+
+        if self.datasets.is_empty() {
+            return invalid("there must be at least one dataset");
+        }
+        for dataset in &self.datasets {
+            validate_transformations(&dataset.coordinate_transformations, dimensionality, true)?;
+        }
+        if let Some(coordinate_transformations) = &self.coordinate_transformations {
+            validate_transformations(coordinate_transformations, dimensionality, false)?;
+        }
+        Ok(())
+    }
+}
+
+/// A [`Multiscale`] builder.
+///
+/// ### Example
+/// ```rust
+/// # use zarrs::ome::{Axis, AxisType, MultiscaleBuilder};
+/// let multiscale = MultiscaleBuilder::new(vec![
+///     Axis::new("y", AxisType::Space, Some("micrometer".to_string())),
+///     Axis::new("x", AxisType::Space, Some("micrometer".to_string())),
+/// ])
+/// .name("image")
+/// .dataset("0", vec![0.5, 0.5])
+/// .dataset("1", vec![1.0, 1.0])
+/// .build()?;
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultiscaleBuilder {
+    name: Option<String>,
+    axes: Vec<Axis>,
+    datasets: Vec<Dataset>,
+    coordinate_transformations: Option<Vec<CoordinateTransformation>>,
+    downscaling_type: Option<String>,
+    metadata: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+impl MultiscaleBuilder {
+    /// Create a new multiscale image builder with `axes`.
+    #[must_use]
+    pub fn new(axes: Vec<Axis>) -> Self {
+        Self {
+            name: None,
+            axes,
+            datasets: Vec::new(),
+            coordinate_transformations: None,
+            downscaling_type: None,
+            metadata: None,
+        }
+    }
+
+    /// Set the name of the image.
+    pub fn name(&mut self, name: impl Into<String>) -> &mut Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Add a dataset (resolution level) at `path` with a `scale` for each axis.
+    ///
+    /// Datasets should be added from highest to lowest resolution.
+    pub fn dataset(&mut self, path: impl Into<String>, scale: Vec<f64>) -> &mut Self {
+        self.datasets.push(Dataset {
+            path: path.into(),
+            coordinate_transformations: vec![CoordinateTransformation::Scale { scale }],
+        });
+        self
+    }
+
+    /// Add a dataset (resolution level) at `path` with a `scale` and `translation` for each axis.
+    ///
+    /// Datasets should be added from highest to lowest resolution.
+    pub fn dataset_with_translation(
+        &mut self,
+        path: impl Into<String>,
+        scale: Vec<f64>,
+        translation: Vec<f64>,
+    ) -> &mut Self {
+        self.datasets.push(Dataset {
+            path: path.into(),
+            coordinate_transformations: vec![
+                CoordinateTransformation::Scale { scale },
+                CoordinateTransformation::Translation { translation },
+            ],
+        });
+        self
+    }
+
+    /// Set the transformations applied to all datasets.
+    pub fn coordinate_transformations(
+        &mut self,
+        coordinate_transformations: Vec<CoordinateTransformation>,
+    ) -> &mut Self {
+        self.coordinate_transformations = Some(coordinate_transformations);
+        self
+    }
+
+    /// Set the type of downscaling, e.g. `gaussian`, and additional information about the downscaling method.
+    pub fn downscaling(
+        &mut self,
+        downscaling_type: impl Into<String>,
+        metadata: Option<serde_json::Map<String, serde_json::Value>>,
+    ) -> &mut Self {
+        self.downscaling_type = Some(downscaling_type.into());
+        self.metadata = metadata;
+        self
+    }
+
+    /// Build into a [`Multiscale`].
+    ///
+    /// # Errors
+    /// Returns [`OmeMetadataError`] if the multiscale image metadata is invalid.
+    pub fn build(&self) -> Result<Multiscale, OmeMetadataError> {
+        let multiscale = Multiscale {
+            version: Some(OME_NGFF_VERSION.to_string()),
+            name: self.name.clone(),
+            axes: self.axes.clone(),
+            datasets: self.datasets.clone(),
+            coordinate_transformations: self.coordinate_transformations.clone(),
+            downscaling_type: self.downscaling_type.clone(),
+            metadata: self.metadata.clone(),
+        };
+        multiscale.validate()?;
+        Ok(multiscale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multiscale_spec_example() -> Result<(), Box<dyn std::error::Error>> {
+        let multiscale: Multiscale = serde_json::from_str(
+            r#"{
+                "version": "0.4",
+                "name": "example",
+                "axes": [
+                    {"name": "t", "type": "time", "unit": "millisecond"},
+                    {"name": "c", "type": "channel"},
+                    {"name": "z", "type": "space", "unit": "micrometer"},
+                    {"name": "y", "type": "space", "unit": "micrometer"},
+                    {"name": "x", "type": "space", "unit": "micrometer"}
+                ],
+                "datasets": [
+                    {
+                        "path": "0",
+                        "coordinateTransformations": [{"type": "scale", "scale": [1.0, 1.0, 0.5, 0.5, 0.5]}]
+                    },
+                    {
+                        "path": "1",
+                        "coordinateTransformations": [{"type": "scale", "scale": [1.0, 1.0, 1.0, 1.0, 1.0]}]
+                    }
+                ],
+                "coordinateTransformations": [{"type": "scale", "scale": [0.1, 1.0, 1.0, 1.0, 1.0]}],
+                "type": "gaussian",
+                "metadata": {"method": "skimage.transform.pyramid_gaussian"}
+            }"#,
+        )?;
+        multiscale.validate()?;
+        assert_eq!(multiscale.axes[1].axis_type, Some(AxisType::Channel));
+        assert_eq!(multiscale.downscaling_type.as_deref(), Some("gaussian"));
+        let json = serde_json::to_value(&multiscale)?;
+        assert_eq!(
+            json["datasets"][0]["coordinateTransformations"][0]["type"],
+            "scale"
+        );
+        assert_eq!(serde_json::from_value::<Multiscale>(json)?, multiscale);
+        Ok(())
+    }
+
+    #[test]
+    fn multiscale_builder() -> Result<(), Box<dyn std::error::Error>> {
+        let axes = vec![
+            Axis::new("c", AxisType::Custom("lifetime".to_string()), None),
+            Axis::new("y", AxisType::Space, None),
+            Axis::new("x", AxisType::Space, None),
+        ];
+        let multiscale = MultiscaleBuilder::new(axes.clone())
+            .dataset("0", vec![1.0, 1.0, 1.0])
+            .dataset_with_translation("1", vec![1.0, 2.0, 2.0], vec![0.0, 0.5, 0.5])
+            .build()?;
+        assert_eq!(multiscale.version.as_deref(), Some(OME_NGFF_VERSION));
+        assert_eq!(
+            serde_json::to_value(&multiscale.axes[0])?,
+            serde_json::json!({"name": "c", "type": "lifetime"})
+        );
+
+        // No datasets
+        assert!(MultiscaleBuilder::new(axes.clone()).build().is_err());
+        // Scale length mismatch
+        assert!(MultiscaleBuilder::new(axes.clone())
+            .dataset("0", vec![1.0, 1.0])
+            .build()
+            .is_err());
+        // Space axes before other axes
+        let mut axes_unordered = axes.clone();
+        axes_unordered.rotate_left(1);
+        assert!(MultiscaleBuilder::new(axes_unordered)
+            .dataset("0", vec![1.0, 1.0, 1.0])
+            .build()
+            .is_err());
+        // Duplicate axis names
+        assert!(MultiscaleBuilder::new(vec![
+            Axis::new("x", AxisType::Space, None),
+            Axis::new("x", AxisType::Space, None),
+        ])
+        .dataset("0", vec![1.0, 1.0])
+        .build()
+        .is_err());
+        // Translation without a scale
+        let mut multiscale_invalid = multiscale;
+        multiscale_invalid.datasets[0].coordinate_transformations =
+            vec![CoordinateTransformation::Translation {
+                translation: vec![0.0; 3],
+            }];
+        assert!(multiscale_invalid.validate().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+use super::OmeMetadataError;
+
+/// The rendering window of an [`OmeroChannel`].
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct OmeroWindow {
+    /// The start of the rendering window.
+    pub start: f64,
+    /// The end of the rendering window.
+    pub end: f64,
+    /// The minimum value of the channel.
+    pub min: f64,
+    /// The maximum value of the channel.
+    pub max: f64,
+}
+
+/// A channel of [`Omero`] rendering metadata.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct OmeroChannel {
+    /// True if the channel is rendered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active: Option<bool>,
+    /// The coefficient of the rendering family.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coefficient: Option<f64>,
+    /// The colour of the channel as a hexadecimal RGB string, e.g. `FF0000`.
+    pub color: String,
+    /// The rendering family, e.g. `linear`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub family: Option<String>,
+    /// True if the channel is rendered inverted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inverted: Option<bool>,
+    /// The label of the channel.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// The rendering window of the channel.
+    pub window: OmeroWindow,
+}
+
+/// Default rendering settings of [`Omero`] metadata.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct OmeroRenderingDefaults {
+    /// The default time point.
+    #[serde(rename = "defaultT", default, skip_serializing_if = "Option::is_none")]
+    pub default_t: Option<u64>,
+    /// The default z plane.
+    #[serde(rename = "defaultZ", default, skip_serializing_if = "Option::is_none")]
+    pub default_z: Option<u64>,
+    /// The colour model, e.g. `color` or `greyscale`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+/// OME-NGFF `omero` rendering metadata of an image group (transitional).
+///
+/// See <https://ngff.openmicroscopy.org/0.4/#omero-md>.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Omero {
+    /// The OMERO image ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    /// The name of the image.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The OME-NGFF version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The channels of the image.
+    pub channels: Vec<OmeroChannel>,
+    /// Default rendering settings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rdefs: Option<OmeroRenderingDefaults>,
+}
+
+impl Omero {
+    /// Validate the rendering metadata.
+    ///
+    /// # Errors
+    /// Returns [`OmeMetadataError::InvalidOmero`] if a channel colour is not a 6 digit hexadecimal string.
+    pub fn validate(&self) -> Result<(), OmeMetadataError> {
+        for channel in &self.channels {
+            if channel.color.len() != 6 || !channel.color.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(OmeMetadataError::InvalidOmero(format!(
+                    "channel colour {} is not a 6 digit hexadecimal string",
+                    channel.color
+                )));
+            }
+        }
+        Ok(())
+    }
+}
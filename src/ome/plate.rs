@@ -0,0 +1,203 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::OmeMetadataError;
+
+/// An acquisition of a [`Plate`].
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct PlateAcquisition {
+    /// The acquisition ID.
+    pub id: u64,
+    /// The name of the acquisition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The maximum number of fields of view of the acquisition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maximumfieldcount: Option<u64>,
+    /// A description of the acquisition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The start timestamp of the acquisition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub starttime: Option<u64>,
+    /// The end timestamp of the acquisition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endtime: Option<u64>,
+}
+
+/// A named row or column of a [`Plate`].
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct PlateName {
+    /// The name of the row or column.
+    pub name: String,
+}
+
+/// A well of a [`Plate`].
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlateWell {
+    /// The path of the well group relative to the plate group, `{row}/{column}`.
+    pub path: String,
+    /// The index of the row of the well.
+    pub row_index: usize,
+    /// The index of the column of the well.
+    pub column_index: usize,
+}
+
+/// OME-NGFF `plate` metadata of a high-content screening plate group.
+///
+/// See <https://ngff.openmicroscopy.org/0.4/#plate-md>.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct Plate {
+    /// The OME-NGFF version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The name of the plate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The acquisitions of the plate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acquisitions: Option<Vec<PlateAcquisition>>,
+    /// The columns of the plate.
+    pub columns: Vec<PlateName>,
+    /// The rows of the plate.
+    pub rows: Vec<PlateName>,
+    /// The maximum number of fields of view per well.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field_count: Option<u64>,
+    /// The wells of the plate.
+    pub wells: Vec<PlateWell>,
+}
+
+impl Plate {
+    /// Validate the plate metadata.
+    ///
+    /// # Errors
+    /// Returns [`OmeMetadataError::InvalidPlate`] if:
+    ///  - row or column names are not unique or not alphanumeric, or
+    ///  - a well refers to a row or column that does not exist, or its path is not `{row}/{column}`.
+    pub fn validate(&self) -> Result<(), OmeMetadataError> {
+        let invalid = |reason: String| Err(OmeMetadataError::InvalidPlate(reason));
+        for names in [&self.rows, &self.columns] {
+            let unique: HashSet<&str> = names.iter().map(|name| name.name.as_str()).collect();
+            if unique.len() != names.len() {
+                return invalid("row and column names must be unique".to_string());
+            }
+            if let Some(name) = names
+                .iter()
+                .find(|name| !name.name.chars().all(|c| c.is_ascii_alphanumeric()))
+            {
+                return invalid(format!(
+                    "row or column name {} is not alphanumeric",
+                    name.name
+                ));
+            }
+        }
+        for well in &self.wells {
+            let (Some(row), Some(column)) = (
+                self.rows.get(well.row_index),
+                self.columns.get(well.column_index),
+            ) else {
+                return invalid(format!(
+                    "well {} refers to a row or column that does not exist",
+                    well.path
+                ));
+            };
+            if well.path != format!("{}/{}", row.name, column.name) {
+                return invalid(format!(
+                    "well path {} does not match its row {} and column {}",
+                    well.path, row.name, column.name
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A field of view of a [`Well`].
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct WellImage {
+    /// The path of the image group relative to the well group.
+    pub path: String,
+    /// The ID of the acquisition of the image.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acquisition: Option<u64>,
+}
+
+/// OME-NGFF `well` metadata of a well group of a [`Plate`].
+///
+/// See <https://ngff.openmicroscopy.org/0.4/#well-md>.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct Well {
+    /// The OME-NGFF version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The fields of view of the well.
+    pub images: Vec<WellImage>,
+}
+
+impl Well {
+    /// Validate the well metadata.
+    ///
+    /// # Errors
+    /// Returns [`OmeMetadataError::InvalidPlate`] if image paths are not unique or not alphanumeric.
+    pub fn validate(&self) -> Result<(), OmeMetadataError> {
+        let paths: HashSet<&str> = self
+            .images
+            .iter()
+            .map(|image| image.path.as_str())
+            .collect();
+        if paths.len() != self.images.len() {
+            return Err(OmeMetadataError::InvalidPlate(
+                "well image paths must be unique".to_string(),
+            ));
+        }
+        if let Some(image) = self
+            .images
+            .iter()
+            .find(|image| !image.path.chars().all(|c| c.is_ascii_alphanumeric()))
+        {
+            return Err(OmeMetadataError::InvalidPlate(format!(
+                "well image path {} is not alphanumeric",
+                image.path
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plate_spec_example() -> Result<(), Box<dyn std::error::Error>> {
+        let mut plate: Plate = serde_json::from_str(
+            r#"{
+                "acquisitions": [{"id": 1, "maximumfieldcount": 2, "name": "Meas_01(2012-07-31_10-41-12)", "starttime": 1343731272000}],
+                "columns": [{"name": "1"}, {"name": "2"}],
+                "field_count": 2,
+                "name": "test",
+                "rows": [{"name": "A"}, {"name": "B"}],
+                "version": "0.4",
+                "wells": [
+                    {"path": "A/1", "rowIndex": 0, "columnIndex": 0},
+                    {"path": "B/2", "rowIndex": 1, "columnIndex": 1}
+                ]
+            }"#,
+        )?;
+        plate.validate()?;
+
+        plate.wells[1].path = "B/1".to_string();
+        assert!(plate.validate().is_err());
+        plate.wells[1].column_index = 2;
+        assert!(plate.validate().is_err());
+
+        let well: Well = serde_json::from_str(
+            r#"{"images": [{"acquisition": 1, "path": "0"}, {"acquisition": 1, "path": "1"}], "version": "0.4"}"#,
+        )?;
+        well.validate()?;
+        Ok(())
+    }
+}
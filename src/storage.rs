@@ -16,10 +16,17 @@ mod storage_handle;
 mod storage_sync;
 pub mod storage_transformer;
 mod storage_value_io;
+mod storage_value_writer;
 pub mod store;
 mod store_key;
 // pub mod store_lock;
 mod store_prefix;
+#[cfg(any(test, feature = "store_test_suite"))]
+pub mod store_test_suite;
+mod store_transaction;
+#[cfg(feature = "async")]
+mod store_version_history;
+mod store_watch;
 
 #[cfg(feature = "async")]
 mod storage_async;
@@ -39,21 +46,27 @@ pub use store_prefix::{StorePrefix, StorePrefixError, StorePrefixes};
 
 #[cfg(feature = "async")]
 pub use self::storage_async::{
-    async_create_array, async_create_group, async_discover_children, async_discover_nodes,
+    async_backup_metadata, async_child_nodes_stream, async_create_array, async_create_array_opt,
+    async_create_group, async_create_group_opt, async_discover_children, async_discover_nodes,
     async_erase_chunk, async_erase_metadata, async_erase_node, async_get_child_nodes,
-    async_node_exists, async_node_exists_listable, async_retrieve_chunk,
-    async_retrieve_partial_values, async_store_chunk, async_store_set_partial_values,
-    AsyncListableStorageTraits, AsyncReadableListableStorageTraits, AsyncReadableStorageTraits,
+    async_node_exists, async_node_exists_listable, async_restore_metadata_backup,
+    async_retrieve_chunk, async_retrieve_partial_values, async_store_chunk,
+    async_store_chunk_streaming, async_store_set_partial_values, AsyncListableStorageTraits,
+    AsyncReadableListableStorageTraits, AsyncReadableStorageTraits,
     AsyncReadableWritableListableStorageTraits, AsyncReadableWritableStorageTraits,
     AsyncWritableStorageTraits,
 };
 
+#[cfg(feature = "async")]
+pub(crate) use self::storage_async::async_get_child_nodes_opt;
+pub(crate) use self::storage_sync::get_child_nodes_opt;
 pub use self::storage_sync::{
-    create_array, create_group, discover_children, discover_nodes, erase_chunk, erase_metadata,
-    erase_node, get_child_nodes, node_exists, node_exists_listable, retrieve_chunk,
-    retrieve_partial_values, store_chunk, store_set_partial_values, ListableStorageTraits,
-    ReadableListableStorageTraits, ReadableStorageTraits, ReadableWritableListableStorageTraits,
-    ReadableWritableStorageTraits, WritableStorageTraits,
+    backup_metadata, create_array, create_array_opt, create_group, create_group_opt,
+    discover_children, discover_nodes, erase_chunk, erase_metadata, erase_node, get_child_nodes,
+    node_exists, node_exists_listable, restore_metadata_backup, retrieve_chunk,
+    retrieve_partial_values, store_chunk, store_chunk_streaming, store_set_partial_values,
+    ListableStorageTraits, ReadableListableStorageTraits, ReadableStorageTraits,
+    ReadableWritableListableStorageTraits, ReadableWritableStorageTraits, WritableStorageTraits,
 };
 pub use self::storage_transformer::StorageTransformerChain;
 
@@ -61,6 +74,30 @@ pub use self::storage_handle::StorageHandle;
 
 pub use storage_value_io::StorageValueIO;
 
+pub use storage_value_writer::{BufferedStoreValueWriter, StoreValueWriter};
+
+#[cfg(feature = "async")]
+pub use storage_value_writer::AsyncStoreValueWriter;
+
+pub use store_transaction::{
+    NonAtomicStoreTransaction, StoreTransaction, StoreTransactionOperation,
+};
+
+#[cfg(feature = "async")]
+pub use store_transaction::AsyncStoreTransaction;
+
+pub use store_watch::{
+    watch_polling, StorePoller, StoreWatchEvent, StoreWatchEvents, WatchableStorageTraits,
+};
+
+#[cfg(feature = "async")]
+pub use store_watch::{async_watch_polling, AsyncStoreWatchEvents};
+
+#[cfg(feature = "async")]
+pub use store_version_history::{
+    version_at, AsyncVersionHistoryStorageTraits, StoreValueVersionInfo,
+};
+
 /// [`Arc`] wrapped readable storage.
 pub type ReadableStorage = Arc<dyn ReadableStorageTraits>;
 
@@ -124,6 +161,54 @@ pub type AsyncBytes = bytes::Bytes;
 /// When a value is read from a store, it returns `MaybeAsyncBytes` which is [`None`] if the key is not available.
 pub type MaybeAsyncBytes = Option<AsyncBytes>;
 
+/// An iterator over pages of [`StoreKeys`].
+pub type StoreKeysPages<'a> = Box<dyn Iterator<Item = Result<StoreKeys, StorageError>> + Send + 'a>;
+
+/// An iterator over pages of [`StoreKeysPrefixes`].
+pub type StoreKeysPrefixesPages<'a> =
+    Box<dyn Iterator<Item = Result<StoreKeysPrefixes, StorageError>> + Send + 'a>;
+
+#[cfg(feature = "async")]
+/// A stream of pages of [`StoreKeys`].
+pub type AsyncStoreKeysPages<'a> = futures::stream::BoxStream<'a, Result<StoreKeys, StorageError>>;
+
+#[cfg(feature = "async")]
+/// A stream of pages of [`StoreKeysPrefixes`].
+pub type AsyncStoreKeysPrefixesPages<'a> =
+    futures::stream::BoxStream<'a, Result<StoreKeysPrefixes, StorageError>>;
+
+/// Split `items` into pages of at most `page_size` items.
+fn into_pages<I: Iterator>(
+    mut items: I,
+    page_size: std::num::NonZeroUsize,
+) -> impl Iterator<Item = Vec<I::Item>> {
+    std::iter::from_fn(move || {
+        let page: Vec<_> = items.by_ref().take(page_size.get()).collect();
+        (!page.is_empty()).then_some(page)
+    })
+}
+
+/// Split `keys_prefixes` into pages of at most `page_size` keys and prefixes.
+///
+/// Keys are paginated before prefixes.
+fn store_keys_prefixes_into_pages(
+    keys_prefixes: StoreKeysPrefixes,
+    page_size: std::num::NonZeroUsize,
+) -> impl Iterator<Item = StoreKeysPrefixes> {
+    let StoreKeysPrefixes { keys, prefixes } = keys_prefixes;
+    into_pages(keys.into_iter(), page_size)
+        .map(|keys| StoreKeysPrefixes {
+            keys,
+            prefixes: vec![],
+        })
+        .chain(
+            into_pages(prefixes.into_iter(), page_size).map(|prefixes| StoreKeysPrefixes {
+                keys: vec![],
+                prefixes,
+            }),
+        )
+}
+
 /// A [`StoreKey`] and [`ByteRange`].
 #[derive(Debug, Clone)]
 pub struct StoreKeyRange {
@@ -139,6 +224,18 @@ impl StoreKeyRange {
     pub const fn new(key: StoreKey, byte_range: ByteRange) -> Self {
         Self { key, byte_range }
     }
+
+    /// Returns the key.
+    #[must_use]
+    pub const fn key(&self) -> &StoreKey {
+        &self.key
+    }
+
+    /// Returns the byte range.
+    #[must_use]
+    pub const fn byte_range(&self) -> &ByteRange {
+        &self.byte_range
+    }
 }
 
 impl std::fmt::Display for StoreKeyRange {
@@ -194,6 +291,45 @@ impl StoreKeysPrefixes {
     }
 }
 
+/// An opaque version of a value in a store, such as an `ETag`.
+///
+/// The version of a value changes whenever the value changes.
+/// See [`ReadableStorageTraits::get_with_version`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct StoreValueVersion(String);
+
+impl StoreValueVersion {
+    /// Create a new store value version.
+    #[must_use]
+    pub fn new(version: impl Into<String>) -> Self {
+        Self(version.into())
+    }
+
+    /// Create a store value version from the content of a value.
+    ///
+    /// This is used by stores without native value versions.
+    /// Versions derived from content are only comparable within a process.
+    #[must_use]
+    pub fn from_content(value: &[u8]) -> Self {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        value.hash(&mut hasher);
+        Self(format!("{:x}-{:016x}", value.len(), hasher.finish()))
+    }
+
+    /// Returns the version as a string.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for StoreValueVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 /// A storage error.
 #[derive(Debug, Error)]
 pub enum StorageError {
@@ -209,6 +345,15 @@ pub enum StorageError {
     /// An error parsing the metadata for a key.
     #[error("error parsing metadata for {0}: {1}")]
     InvalidMetadata(StoreKey, String),
+    /// The version of a value does not match its expected version.
+    #[error("the version of {0} has changed")]
+    VersionMismatch(StoreKey),
+    /// The content digest of a value does not match its recorded digest.
+    #[error("the content digest of {0} does not match its recorded digest")]
+    ContentDigestMismatch(StoreKey),
+    /// The value read back after a write does not match the value written.
+    #[error("the value read back from {0} does not match the value written")]
+    WriteVerificationFailed(StoreKey),
     /// An invalid store prefix.
     #[error("invalid store prefix {0}")]
     StorePrefixError(#[from] StorePrefixError),
@@ -235,6 +380,30 @@ pub enum StorageError {
     Other(String),
 }
 
+impl StorageError {
+    /// Return the store key involved in the error, if known.
+    #[must_use]
+    pub fn store_key(&self) -> Option<&StoreKey> {
+        match self {
+            Self::InvalidMetadata(key, _)
+            | Self::VersionMismatch(key)
+            | Self::ContentDigestMismatch(key)
+            | Self::WriteVerificationFailed(key)
+            | Self::UnknownKeySize(key) => Some(key),
+            _ => None,
+        }
+    }
+
+    /// Return the byte range involved in the error, if known.
+    #[must_use]
+    pub fn byte_range(&self) -> Option<ByteRange> {
+        match self {
+            Self::InvalidByteRangeError(err) => Some(err.byte_range()),
+            _ => None,
+        }
+    }
+}
+
 impl From<&str> for StorageError {
     fn from(err: &str) -> Self {
         Self::Other(err.to_string())
@@ -290,6 +459,18 @@ pub fn meta_key_v2_attributes(path: &NodePath) -> StoreKey {
     meta_key_any(path, ".zattrs")
 }
 
+/// Return the Zarr V2 consolidated metadata key (.zmetadata) given a node path.
+#[must_use]
+pub fn meta_key_v2_consolidated(path: &NodePath) -> StoreKey {
+    meta_key_any(path, ".zmetadata")
+}
+
+/// Return the backup key of a metadata key (e.g. `zarr.json.bak`).
+#[must_use]
+pub fn meta_key_backup(key: &StoreKey) -> StoreKey {
+    unsafe { StoreKey::new_unchecked(format!("{}.bak", key.as_str())) }
+}
+
 /// Return the data key given a node path, chunk grid coordinates, and a chunk key encoding.
 #[must_use]
 pub fn data_key(
@@ -336,8 +517,236 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn transformer_usage_log_records() {
+        use self::storage_transformer::{
+            StorageOperation, StorageTransformerExtension, UsageLogRecord,
+            UsageLogStorageTransformer,
+        };
+
+        let records = Arc::new(std::sync::Mutex::new(Vec::<UsageLogRecord>::new()));
+        let usage_log = Arc::new(UsageLogStorageTransformer::new_with_sink(records.clone()));
+        let store = usage_log
+            .create_readable_writable_listable_transformer(Arc::new(MemoryStore::default()));
+
+        let key = StoreKey::new("a/b").unwrap();
+        store.set(&key, vec![0, 1, 2, 3].into()).unwrap();
+        store
+            .get_partial_values_key(&key, &[ByteRange::FromStart(1, Some(2))])
+            .unwrap();
+        store.list_dir(&StorePrefix::new("a/").unwrap()).unwrap();
+        assert!(store
+            .get_partial_values_key(&key, &[ByteRange::FromStart(3, Some(2))])
+            .is_err());
+
+        let records = records.lock().unwrap();
+        let operations: Vec<_> = records.iter().map(|record| record.operation).collect();
+        assert_eq!(
+            operations,
+            vec![
+                StorageOperation::Set,
+                StorageOperation::GetPartialValuesKey,
+                StorageOperation::ListDir,
+                StorageOperation::GetPartialValuesKey,
+            ]
+        );
+        assert_eq!(records[0].to_string(), "set(a/b, len=4) -> Ok(())");
+        assert_eq!(
+            records[1].to_string(),
+            "get_partial_values_key(a/b, [1..3]) -> len=Some([2])"
+        );
+        assert_eq!(
+            records[2].to_string(),
+            "list_dir(a/) -> (keys:[a/b], prefixes:[])"
+        );
+        assert!(records[3].response.is_err());
+    }
+
+    #[test]
+    fn transformer_read_only() {
+        use self::storage_transformer::ReadOnlyStorageTransformer;
+
+        let store = Arc::new(MemoryStore::default());
+        let key = StoreKey::new("a").unwrap();
+        store.set(&key, vec![0, 1].into()).unwrap();
+        let storage_transformer_chain =
+            StorageTransformerChain::new(vec![Arc::new(ReadOnlyStorageTransformer::new())]);
+        let transformer =
+            storage_transformer_chain.create_readable_writable_transformer(store.clone());
+        let transformer_listable =
+            storage_transformer_chain.create_listable_transformer(store.clone());
+        assert_eq!(transformer.get(&key).unwrap(), Some(vec![0, 1].into()));
+        assert_eq!(transformer_listable.list().unwrap(), vec![key.clone()]);
+        assert!(matches!(
+            transformer.set(&key, vec![2].into()),
+            Err(StorageError::ReadOnly)
+        ));
+        assert!(matches!(
+            transformer.erase_prefix(&StorePrefix::root()),
+            Err(StorageError::ReadOnly)
+        ));
+        assert_eq!(store.get(&key).unwrap(), Some(vec![0, 1].into()));
+    }
+
+    #[test]
+    fn transformer_write_verify() {
+        use self::storage_transformer::WriteVerifyStorageTransformer;
+
+        /// A store which corrupts values written to keys starting with `corrupt`.
+        struct CorruptingStore(MemoryStore);
+
+        impl ReadableStorageTraits for CorruptingStore {
+            fn get_partial_values_key(
+                &self,
+                key: &StoreKey,
+                byte_ranges: &[ByteRange],
+            ) -> Result<Option<Vec<Bytes>>, StorageError> {
+                self.0.get_partial_values_key(key, byte_ranges)
+            }
+
+            fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+                self.0.size_key(key)
+            }
+        }
+
+        impl WritableStorageTraits for CorruptingStore {
+            fn set(&self, key: &StoreKey, value: Bytes) -> Result<(), StorageError> {
+                if key.as_str().starts_with("corrupt") {
+                    self.0.set(key, value.slice(1..))
+                } else {
+                    self.0.set(key, value)
+                }
+            }
+
+            fn set_partial_values(
+                &self,
+                key_start_values: &[StoreKeyStartValue],
+            ) -> Result<(), StorageError> {
+                self.0.set_partial_values(key_start_values)
+            }
+
+            fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+                self.0.erase(key)
+            }
+
+            fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+                self.0.erase_prefix(prefix)
+            }
+        }
+
+        impl ReadableWritableStorageTraits for CorruptingStore {}
+
+        let store = Arc::new(CorruptingStore(MemoryStore::default()));
+        let storage_transformer_chain =
+            StorageTransformerChain::new(vec![Arc::new(WriteVerifyStorageTransformer::new())]);
+        let transformer =
+            storage_transformer_chain.create_readable_writable_transformer(store.clone());
+
+        let key = StoreKey::new("a").unwrap();
+        transformer.set(&key, vec![0, 1, 2].into()).unwrap();
+        transformer
+            .set_partial_values(&[StoreKeyStartValue::new(key.clone(), 1, &[3])])
+            .unwrap();
+        assert_eq!(transformer.get(&key).unwrap(), Some(vec![0, 3, 2].into()));
+
+        let key_corrupt = StoreKey::new("corrupt").unwrap();
+        assert!(matches!(
+            transformer.set(&key_corrupt, vec![0, 1, 2].into()),
+            Err(StorageError::WriteVerificationFailed(_))
+        ));
+
+        // Writes to write-only storage cannot be verified
+        let transformer_writable = storage_transformer_chain.create_writable_transformer(store);
+        assert!(matches!(
+            transformer_writable.set(&key, vec![0].into()),
+            Err(StorageError::Unsupported(_))
+        ));
+    }
+
+    #[cfg(feature = "content_digest")]
+    #[test]
+    fn transformer_content_digest() {
+        use self::storage_transformer::{ContentDigestAlgorithm, ContentDigestStorageTransformer};
+
+        let store = Arc::new(MemoryStore::default());
+        let key = StoreKey::new("a/b").unwrap();
+        let storage_transformer_chain = StorageTransformerChain::new(vec![Arc::new(
+            ContentDigestStorageTransformer::new(ContentDigestAlgorithm::Sha256),
+        )]);
+        let transformer =
+            storage_transformer_chain.create_readable_writable_transformer(store.clone());
+        let transformer_listable =
+            storage_transformer_chain.create_listable_transformer(store.clone());
+        transformer.set(&key, vec![0, 1, 2].into()).unwrap();
+        assert_eq!(
+            store
+                .get(&"__zarrs_digest/a/b".try_into().unwrap())
+                .unwrap(),
+            Some(
+                "sha256:ae4b3280e56e2faf83f414a6e3dabe9d5fbe18976544c05fed121accb85b53fc"
+                    .as_bytes()
+                    .to_vec()
+                    .into()
+            )
+        );
+        assert_eq!(transformer.get(&key).unwrap(), Some(vec![0, 1, 2].into()));
+        assert_eq!(transformer_listable.list().unwrap(), vec![key.clone()]);
+        assert_eq!(
+            transformer_listable
+                .list_dir(&StorePrefix::root())
+                .unwrap()
+                .prefixes(),
+            &[StorePrefix::new("a/").unwrap()]
+        );
+        assert_eq!(transformer_listable.size().unwrap(), 3);
+
+        // Partial writes update the digest
+        transformer
+            .set_partial_values(&[StoreKeyStartValue::new(key.clone(), 1, &[3])])
+            .unwrap();
+        assert_eq!(transformer.get(&key).unwrap(), Some(vec![0, 3, 2].into()));
+
+        // Bit rot is detected
+        store.set(&key, vec![0, 1, 2].into()).unwrap();
+        assert!(matches!(
+            transformer.get(&key),
+            Err(StorageError::ContentDigestMismatch(_))
+        ));
+        assert!(transformer
+            .get_partial_values_key(&key, &[ByteRange::FromStart(0, Some(1))])
+            .is_ok());
+        let storage_transformer_chain = StorageTransformerChain::new(vec![Arc::new(
+            ContentDigestStorageTransformer::default().verify_partial_reads(true),
+        )]);
+        let transformer_verify_partial =
+            storage_transformer_chain.create_readable_transformer(store.clone());
+        assert!(matches!(
+            transformer_verify_partial
+                .get_partial_values_key(&key, &[ByteRange::FromStart(0, Some(1))]),
+            Err(StorageError::ContentDigestMismatch(_))
+        ));
+
+        // Values without a digest
+        let key_no_digest = StoreKey::new("c").unwrap();
+        store.set(&key_no_digest, vec![0].into()).unwrap();
+        assert!(transformer.get(&key_no_digest).is_ok());
+        let storage_transformer_chain = StorageTransformerChain::new(vec![Arc::new(
+            ContentDigestStorageTransformer::default().require_digests(true),
+        )]);
+        let transformer_require =
+            storage_transformer_chain.create_readable_transformer(store.clone());
+        assert!(transformer_require.get(&key_no_digest).is_err());
+
+        transformer.erase(&key).unwrap();
+        assert!(store
+            .get(&"__zarrs_digest/a/b".try_into().unwrap())
+            .unwrap()
+            .is_none());
+    }
+
     #[test]
     fn transformers_multithreaded() {
+        use self::storage_transformer::StorageOperation;
         use rayon::prelude::*;
 
         let store = Arc::new(MemoryStore::default());
@@ -383,5 +792,36 @@ mod tests {
             storage_transformer_performance_metrics.writes(),
             storage_transformer_performance_metrics.reads()
         );
+
+        let set_bytes =
+            storage_transformer_performance_metrics.bytes_summary(StorageOperation::Set);
+        assert_eq!(set_bytes.count, 10);
+        assert_eq!(set_bytes.p50, Some(5));
+        assert_eq!(set_bytes.p99, Some(5));
+        assert_eq!(
+            storage_transformer_performance_metrics
+                .latency_summary(StorageOperation::List)
+                .count,
+            10
+        );
+        assert_eq!(
+            storage_transformer_performance_metrics
+                .latency_summary(StorageOperation::Get)
+                .count,
+            10
+        );
+        assert_eq!(
+            storage_transformer_performance_metrics
+                .latency_summary(StorageOperation::Erase)
+                .count,
+            0
+        );
+        for operation in StorageOperation::ALL {
+            println!(
+                "{operation}\n\tlatency (ns): {}\n\tbytes: {}",
+                storage_transformer_performance_metrics.latency_summary(operation),
+                storage_transformer_performance_metrics.bytes_summary(operation)
+            );
+        }
     }
 }
@@ -2,8 +2,19 @@
 //!
 //! Storage adapters can be layered on stores.
 
+pub mod hash_sharded;
+pub mod prefix;
+pub mod read_only;
+pub mod snapshot;
+pub mod tiered;
+pub mod trash;
+pub mod versioned;
+
 #[cfg(feature = "zip")]
 pub mod zip;
 
 #[cfg(feature = "async")]
 pub mod async_to_sync;
+
+#[cfg(feature = "async")]
+pub mod time_travel;
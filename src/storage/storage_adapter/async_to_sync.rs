@@ -8,8 +8,8 @@ use crate::{
     byte_range::ByteRange,
     storage::{
         AsyncListableStorageTraits, AsyncReadableStorageTraits, AsyncWritableStorageTraits, Bytes,
-        ListableStorageTraits, ReadableStorageTraits, StorageError, StoreKey, StoreKeys,
-        StoreKeysPrefixes, StorePrefix, WritableStorageTraits,
+        ListableStorageTraits, MaybeBytes, ReadableStorageTraits, StorageError, StoreKey,
+        StoreKeys, StoreKeysPrefixes, StorePrefix, WritableStorageTraits,
     },
 };
 
@@ -70,6 +70,14 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits, TBlockOn: AsyncToSyncBlockOn
     fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
         self.block_on(self.storage.size_key(key))
     }
+
+    fn get_values_coalesced(
+        &self,
+        keys: &[StoreKey],
+        max_waste_ratio: f64,
+    ) -> Result<Vec<MaybeBytes>, StorageError> {
+        self.block_on(self.storage.get_values_coalesced(keys, max_waste_ratio))
+    }
 }
 
 impl<TStorage: ?Sized + AsyncListableStorageTraits, TBlockOn: AsyncToSyncBlockOn>
@@ -0,0 +1,661 @@
+//! A hash sharded storage adapter.
+//!
+//! See [`HashShardedStorageAdapter`].
+
+use std::{collections::BTreeSet, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    byte_range::ByteRange,
+    storage::{
+        Bytes, ListableStorageTraits, MaybeBytes, ReadableStorageTraits,
+        ReadableWritableStorageTraits, StorageError, StoreKey, StoreKeyStartValue, StoreKeys,
+        StoreKeysPrefixes, StorePrefix, WritableStorageTraits,
+    },
+};
+
+#[cfg(feature = "async")]
+use crate::storage::{
+    AsyncBytes, AsyncListableStorageTraits, AsyncReadableStorageTraits,
+    AsyncReadableWritableStorageTraits, AsyncWritableStorageTraits, MaybeAsyncBytes,
+};
+
+/// The key of the hash sharded manifest, which is stored in every backend.
+///
+/// This key is hidden from listing.
+pub const HASH_SHARDED_MANIFEST_KEY: &str = "zarrs_hash_sharded.json";
+
+/// The default number of virtual nodes per backend on the consistent hash ring.
+pub const HASH_SHARDED_DEFAULT_VIRTUAL_NODES: usize = 64;
+
+/// The manifest of a [`HashShardedStorageAdapter`].
+///
+/// The manifest fully describes the consistent hash ring, so that keys can be located after reopening.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct HashShardedManifest {
+    /// The manifest version. Currently `1`.
+    pub version: u32,
+    /// The unique identifiers of the backends.
+    pub backends: Vec<String>,
+    /// The number of virtual nodes per backend on the consistent hash ring.
+    pub virtual_nodes: usize,
+}
+
+/// A hash sharded storage adapter create error.
+#[derive(Debug, Error)]
+pub enum HashShardedStorageAdapterCreateError {
+    /// No backends were supplied.
+    #[error("no backends were supplied")]
+    NoBackends,
+    /// Backend identifiers are not unique.
+    #[error("backend identifier {0} is not unique")]
+    DuplicateBackend(String),
+    /// The number of virtual nodes is zero.
+    #[error("the number of virtual nodes must be greater than zero")]
+    ZeroVirtualNodes,
+    /// The manifest is missing.
+    #[error("the hash sharded manifest is missing from backend {0}")]
+    MissingManifest(String),
+    /// The manifest is invalid.
+    #[error("the hash sharded manifest is invalid: {0}")]
+    InvalidManifest(String),
+    /// The manifest does not match the supplied backends.
+    #[error("the supplied backends {supplied:?} do not match the manifest backends {manifest:?}")]
+    ManifestMismatch {
+        /// The identifiers of the supplied backends.
+        supplied: Vec<String>,
+        /// The identifiers of the backends in the manifest.
+        manifest: Vec<String>,
+    },
+    /// An underlying storage error.
+    #[error(transparent)]
+    StorageError(#[from] StorageError),
+}
+
+/// A stable 64-bit hash (FNV-1a with a `splitmix64` finalizer).
+///
+/// The hash must never change, otherwise existing keys could not be located.
+fn stable_hash(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
+
+/// A hash sharded storage adapter.
+///
+/// Distributes keys across multiple backend stores by consistent hashing.
+/// This can increase the aggregate metadata/IO rate for hierarchies with millions of keys where a single filesystem mount or bucket is a bottleneck.
+///
+/// Each backend has a unique identifier, and is assigned a number of virtual nodes on a hash ring.
+/// A key is stored in the backend owning the first virtual node at or after the hash of the key.
+/// The [`HashShardedManifest`] describing the ring is stored at [`HASH_SHARDED_MANIFEST_KEY`] in every backend by [`store_manifest`](HashShardedStorageAdapter::store_manifest), and is validated by [`open`](HashShardedStorageAdapter::open).
+///
+/// Listing operations query every backend and merge the results.
+///
+/// Changing the backends changes the location of some keys (approximately `1/n` of keys when adding an `n`th backend).
+/// Existing keys are not migrated automatically.
+///
+/// ### Example
+/// ```rust
+/// # use std::sync::Arc;
+/// # use zarrs::storage::{store::MemoryStore, ListableStorageTraits, ReadableStorageTraits, WritableStorageTraits, ReadableWritableListableStorage};
+/// # use zarrs::storage::storage_adapter::hash_sharded::{HashShardedStorageAdapter, HASH_SHARDED_DEFAULT_VIRTUAL_NODES};
+/// let backends: Vec<(String, ReadableWritableListableStorage)> = vec![
+///     ("a".to_string(), Arc::new(MemoryStore::new())),
+///     ("b".to_string(), Arc::new(MemoryStore::new())),
+/// ];
+/// let store = HashShardedStorageAdapter::new(backends.clone(), HASH_SHARDED_DEFAULT_VIRTUAL_NODES)?;
+/// store.store_manifest()?;
+/// store.set(&"array/c/0/0".try_into()?, vec![0].into())?;
+///
+/// let store = HashShardedStorageAdapter::open(backends)?;
+/// assert_eq!(store.list()?, &["array/c/0/0".try_into()?]);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct HashShardedStorageAdapter<TStorage: ?Sized> {
+    backends: Vec<Arc<TStorage>>,
+    manifest: HashShardedManifest,
+    /// The hash ring: (hash, backend index), sorted by hash.
+    ring: Vec<(u64, usize)>,
+}
+
+impl<TStorage: ?Sized> HashShardedStorageAdapter<TStorage> {
+    /// Create a new hash sharded storage adapter from a list of uniquely identified `backends` and the number of `virtual_nodes` per backend.
+    ///
+    /// The manifest is not stored, see [`store_manifest`](HashShardedStorageAdapter::store_manifest).
+    ///
+    /// # Errors
+    /// Returns a [`HashShardedStorageAdapterCreateError`] if there are no backends, backend identifiers are not unique, or `virtual_nodes` is zero.
+    pub fn new(
+        backends: Vec<(String, Arc<TStorage>)>,
+        virtual_nodes: usize,
+    ) -> Result<Self, HashShardedStorageAdapterCreateError> {
+        let (ids, backends): (Vec<String>, Vec<Arc<TStorage>>) = backends.into_iter().unzip();
+        let manifest = HashShardedManifest {
+            version: 1,
+            backends: ids,
+            virtual_nodes,
+        };
+        Self::new_with_manifest(backends, manifest)
+    }
+
+    fn new_with_manifest(
+        backends: Vec<Arc<TStorage>>,
+        manifest: HashShardedManifest,
+    ) -> Result<Self, HashShardedStorageAdapterCreateError> {
+        if backends.is_empty() {
+            return Err(HashShardedStorageAdapterCreateError::NoBackends);
+        }
+        if manifest.virtual_nodes == 0 {
+            return Err(HashShardedStorageAdapterCreateError::ZeroVirtualNodes);
+        }
+        let mut unique = BTreeSet::new();
+        for id in &manifest.backends {
+            if !unique.insert(id) {
+                return Err(HashShardedStorageAdapterCreateError::DuplicateBackend(
+                    id.clone(),
+                ));
+            }
+        }
+
+        let mut ring = Vec::with_capacity(manifest.backends.len() * manifest.virtual_nodes);
+        for (index, id) in manifest.backends.iter().enumerate() {
+            for virtual_node in 0..manifest.virtual_nodes {
+                ring.push((
+                    stable_hash(format!("{id}#{virtual_node}").as_bytes()),
+                    index,
+                ));
+            }
+        }
+        ring.sort_unstable();
+
+        Ok(Self {
+            backends,
+            manifest,
+            ring,
+        })
+    }
+
+    /// Order `backends` according to `manifest`.
+    fn from_manifest(
+        mut backends: Vec<(String, Arc<TStorage>)>,
+        manifest: HashShardedManifest,
+    ) -> Result<Self, HashShardedStorageAdapterCreateError> {
+        if manifest.version != 1 {
+            return Err(HashShardedStorageAdapterCreateError::InvalidManifest(
+                format!("unsupported version {}", manifest.version),
+            ));
+        }
+        let mut supplied: Vec<String> = backends.iter().map(|(id, _)| id.clone()).collect();
+        let mut expected = manifest.backends.clone();
+        supplied.sort();
+        expected.sort();
+        if supplied != expected {
+            return Err(HashShardedStorageAdapterCreateError::ManifestMismatch {
+                supplied,
+                manifest: manifest.backends,
+            });
+        }
+        let ordered = manifest
+            .backends
+            .iter()
+            .map(|id| {
+                let index = backends.iter().position(|(id_i, _)| id_i == id).unwrap();
+                backends.swap_remove(index).1
+            })
+            .collect();
+        Self::new_with_manifest(ordered, manifest)
+    }
+
+    /// Returns the manifest.
+    #[must_use]
+    pub const fn manifest(&self) -> &HashShardedManifest {
+        &self.manifest
+    }
+
+    /// Returns the backends, in the order of the manifest.
+    #[must_use]
+    pub fn backends(&self) -> &[Arc<TStorage>] {
+        &self.backends
+    }
+
+    /// Returns the index (in the manifest) of the backend holding `key`.
+    #[must_use]
+    pub fn backend_index(&self, key: &StoreKey) -> usize {
+        let hash = stable_hash(key.as_str().as_bytes());
+        let position = self
+            .ring
+            .partition_point(|(node_hash, _)| *node_hash < hash);
+        self.ring[position % self.ring.len()].1
+    }
+
+    fn backend(&self, key: &StoreKey) -> &TStorage {
+        &self.backends[self.backend_index(key)]
+    }
+
+    fn manifest_key() -> StoreKey {
+        unsafe { StoreKey::new_unchecked(HASH_SHARDED_MANIFEST_KEY) }
+    }
+
+    fn manifest_bytes(&self) -> Result<Bytes, StorageError> {
+        serde_json::to_vec_pretty(&self.manifest)
+            .map(Bytes::from)
+            .map_err(|err| StorageError::InvalidMetadata(Self::manifest_key(), err.to_string()))
+    }
+
+    fn parse_manifest(
+        bytes: &[u8],
+    ) -> Result<HashShardedManifest, HashShardedStorageAdapterCreateError> {
+        serde_json::from_slice(bytes)
+            .map_err(|err| HashShardedStorageAdapterCreateError::InvalidManifest(err.to_string()))
+    }
+
+    fn check_key(key: &StoreKey) -> Result<(), StorageError> {
+        if key.as_str() == HASH_SHARDED_MANIFEST_KEY {
+            Err(StorageError::Other(format!(
+                "{HASH_SHARDED_MANIFEST_KEY} is reserved by the hash sharded storage adapter"
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Group `key_start_values` by backend index.
+    fn group_key_start_values<'a>(
+        &self,
+        key_start_values: &[StoreKeyStartValue<'a>],
+    ) -> Result<Vec<Vec<StoreKeyStartValue<'a>>>, StorageError> {
+        let mut grouped = vec![Vec::new(); self.backends.len()];
+        for key_start_value in key_start_values {
+            Self::check_key(&key_start_value.key)?;
+            grouped[self.backend_index(&key_start_value.key)].push(key_start_value.clone());
+        }
+        Ok(grouped)
+    }
+}
+
+/// Returns true if the manifest is in `keys_prefixes`.
+fn has_manifest(keys_prefixes: &StoreKeysPrefixes) -> bool {
+    keys_prefixes
+        .keys()
+        .iter()
+        .any(|key| key.as_str() == HASH_SHARDED_MANIFEST_KEY)
+}
+
+/// Merge the sorted lists of keys from each backend, excluding the manifest.
+fn merge_keys(keys: Vec<StoreKeys>) -> StoreKeys {
+    let mut keys: StoreKeys = keys
+        .into_iter()
+        .flatten()
+        .filter(|key| key.as_str() != HASH_SHARDED_MANIFEST_KEY)
+        .collect();
+    keys.sort();
+    keys
+}
+
+/// Merge the keys and prefixes from each backend, excluding the manifest.
+fn merge_keys_prefixes(keys_prefixes: Vec<StoreKeysPrefixes>) -> StoreKeysPrefixes {
+    let mut keys = Vec::new();
+    let mut prefixes = BTreeSet::new();
+    for keys_prefixes in keys_prefixes {
+        keys.push(keys_prefixes.keys);
+        prefixes.extend(keys_prefixes.prefixes);
+    }
+    StoreKeysPrefixes {
+        keys: merge_keys(keys),
+        prefixes: prefixes.into_iter().collect(),
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits> HashShardedStorageAdapter<TStorage> {
+    /// Open a hash sharded storage adapter from a list of uniquely identified `backends`.
+    ///
+    /// The manifest is read from the first backend and the backend identifiers must match those in the manifest (in any order).
+    ///
+    /// # Errors
+    /// Returns a [`HashShardedStorageAdapterCreateError`] if the manifest is missing or invalid, or does not match the supplied backends.
+    pub fn open(
+        backends: Vec<(String, Arc<TStorage>)>,
+    ) -> Result<Self, HashShardedStorageAdapterCreateError> {
+        let (id, backend) = backends
+            .first()
+            .ok_or(HashShardedStorageAdapterCreateError::NoBackends)?;
+        let manifest = backend
+            .get(&Self::manifest_key())?
+            .ok_or_else(|| HashShardedStorageAdapterCreateError::MissingManifest(id.clone()))?;
+        let manifest = Self::parse_manifest(&manifest)?;
+        Self::from_manifest(backends, manifest)
+    }
+}
+
+impl<TStorage: ?Sized + WritableStorageTraits> HashShardedStorageAdapter<TStorage> {
+    /// Store the manifest in every backend.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying error with a backend.
+    pub fn store_manifest(&self) -> Result<(), StorageError> {
+        let manifest = self.manifest_bytes()?;
+        for backend in &self.backends {
+            backend.set(&Self::manifest_key(), manifest.clone())?;
+        }
+        Ok(())
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
+    for HashShardedStorageAdapter<TStorage>
+{
+    fn get(&self, key: &StoreKey) -> Result<MaybeBytes, StorageError> {
+        self.backend(key).get(key)
+    }
+
+    fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<Bytes>>, StorageError> {
+        self.backend(key).get_partial_values_key(key, byte_ranges)
+    }
+
+    fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        self.backend(key).size_key(key)
+    }
+}
+
+impl<TStorage: ?Sized + ListableStorageTraits> ListableStorageTraits
+    for HashShardedStorageAdapter<TStorage>
+{
+    fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.list_prefix(&StorePrefix::root())
+    }
+
+    fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        let keys = self
+            .backends
+            .iter()
+            .map(|backend| backend.list_prefix(prefix))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(merge_keys(keys))
+    }
+
+    fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        let keys_prefixes = self
+            .backends
+            .iter()
+            .map(|backend| backend.list_dir(prefix))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(merge_keys_prefixes(keys_prefixes))
+    }
+
+    fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        let mut size = 0;
+        for backend in &self.backends {
+            size += backend.size_prefix(prefix)?;
+            if prefix.as_str().is_empty() && has_manifest(&backend.list_dir(prefix)?) {
+                // Exclude the manifest
+                size -= self.manifest_bytes()?.len() as u64;
+            }
+        }
+        Ok(size)
+    }
+}
+
+impl<TStorage: ?Sized + WritableStorageTraits> WritableStorageTraits
+    for HashShardedStorageAdapter<TStorage>
+{
+    fn set(&self, key: &StoreKey, value: Bytes) -> Result<(), StorageError> {
+        Self::check_key(key)?;
+        self.backend(key).set(key, value)
+    }
+
+    fn set_partial_values(
+        &self,
+        key_start_values: &[StoreKeyStartValue],
+    ) -> Result<(), StorageError> {
+        let grouped = self.group_key_start_values(key_start_values)?;
+        for (backend, key_start_values) in self.backends.iter().zip(grouped) {
+            if !key_start_values.is_empty() {
+                backend.set_partial_values(&key_start_values)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+        Self::check_key(key)?;
+        self.backend(key).erase(key)
+    }
+
+    fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+        for backend in &self.backends {
+            backend.erase_prefix(prefix)?;
+        }
+        if prefix.as_str().is_empty() {
+            // Restore the manifests
+            let manifest = self.manifest_bytes()?;
+            for backend in &self.backends {
+                backend.set(&Self::manifest_key(), manifest.clone())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits> ReadableWritableStorageTraits
+    for HashShardedStorageAdapter<TStorage>
+{
+}
+
+#[cfg(feature = "async")]
+impl<TStorage: ?Sized + AsyncReadableStorageTraits> HashShardedStorageAdapter<TStorage> {
+    /// Asynchronously open a hash sharded storage adapter from a list of uniquely identified `backends`.
+    ///
+    /// See [`open`](HashShardedStorageAdapter::open).
+    ///
+    /// # Errors
+    /// Returns a [`HashShardedStorageAdapterCreateError`] if the manifest is missing or invalid, or does not match the supplied backends.
+    pub async fn async_open(
+        backends: Vec<(String, Arc<TStorage>)>,
+    ) -> Result<Self, HashShardedStorageAdapterCreateError> {
+        let (id, backend) = backends
+            .first()
+            .ok_or(HashShardedStorageAdapterCreateError::NoBackends)?;
+        let manifest = backend
+            .get(&Self::manifest_key())
+            .await?
+            .ok_or_else(|| HashShardedStorageAdapterCreateError::MissingManifest(id.clone()))?;
+        let manifest = Self::parse_manifest(&manifest)?;
+        Self::from_manifest(backends, manifest)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<TStorage: ?Sized + AsyncWritableStorageTraits> HashShardedStorageAdapter<TStorage> {
+    /// Asynchronously store the manifest in every backend.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying error with a backend.
+    pub async fn async_store_manifest(&self) -> Result<(), StorageError> {
+        let manifest = self.manifest_bytes()?;
+        for backend in &self.backends {
+            backend.set(&Self::manifest_key(), manifest.clone()).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncReadableStorageTraits> AsyncReadableStorageTraits
+    for HashShardedStorageAdapter<TStorage>
+{
+    async fn get(&self, key: &StoreKey) -> Result<MaybeAsyncBytes, StorageError> {
+        self.backend(key).get(key).await
+    }
+
+    async fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<AsyncBytes>>, StorageError> {
+        self.backend(key)
+            .get_partial_values_key(key, byte_ranges)
+            .await
+    }
+
+    async fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        self.backend(key).size_key(key).await
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncListableStorageTraits> AsyncListableStorageTraits
+    for HashShardedStorageAdapter<TStorage>
+{
+    async fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.list_prefix(&StorePrefix::root()).await
+    }
+
+    async fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        let keys = futures::future::try_join_all(
+            self.backends
+                .iter()
+                .map(|backend| backend.list_prefix(prefix)),
+        )
+        .await?;
+        Ok(merge_keys(keys))
+    }
+
+    async fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        let keys_prefixes = futures::future::try_join_all(
+            self.backends.iter().map(|backend| backend.list_dir(prefix)),
+        )
+        .await?;
+        Ok(merge_keys_prefixes(keys_prefixes))
+    }
+
+    async fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        let mut size = 0;
+        for backend in &self.backends {
+            size += backend.size_prefix(prefix).await?;
+            if prefix.as_str().is_empty() && has_manifest(&backend.list_dir(prefix).await?) {
+                // Exclude the manifest
+                size -= self.manifest_bytes()?.len() as u64;
+            }
+        }
+        Ok(size)
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncWritableStorageTraits> AsyncWritableStorageTraits
+    for HashShardedStorageAdapter<TStorage>
+{
+    async fn set(&self, key: &StoreKey, value: AsyncBytes) -> Result<(), StorageError> {
+        Self::check_key(key)?;
+        self.backend(key).set(key, value).await
+    }
+
+    async fn set_partial_values(
+        &self,
+        key_start_values: &[StoreKeyStartValue],
+    ) -> Result<(), StorageError> {
+        let grouped = self.group_key_start_values(key_start_values)?;
+        for (backend, key_start_values) in self.backends.iter().zip(grouped) {
+            if !key_start_values.is_empty() {
+                backend.set_partial_values(&key_start_values).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+        Self::check_key(key)?;
+        self.backend(key).erase(key).await
+    }
+
+    async fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+        for backend in &self.backends {
+            backend.erase_prefix(prefix).await?;
+        }
+        if prefix.as_str().is_empty() {
+            // Restore the manifests
+            self.async_store_manifest().await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncReadableWritableStorageTraits> AsyncReadableWritableStorageTraits
+    for HashShardedStorageAdapter<TStorage>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::store::MemoryStore;
+
+    use super::*;
+
+    #[test]
+    fn hash_sharded() -> Result<(), Box<dyn std::error::Error>> {
+        let backends: Vec<(String, Arc<MemoryStore>)> = (0..4)
+            .map(|i| (format!("backend{i}"), Arc::new(MemoryStore::new())))
+            .collect();
+        let store = HashShardedStorageAdapter::new(backends.clone(), 16)?;
+        store.store_manifest()?;
+        for i in 0..100 {
+            store.set(&StoreKey::new(format!("array/c/{i}"))?, vec![0, 1].into())?;
+        }
+        store.set(&"zarr.json".try_into()?, vec![].into())?;
+        assert!(store
+            .set(&HASH_SHARDED_MANIFEST_KEY.try_into()?, vec![].into())
+            .is_err());
+
+        // Keys are distributed across all backends
+        for (_, backend) in &backends {
+            assert!(backend.list_prefix(&"array/".try_into()?)?.len() > 5);
+        }
+
+        // Reopen with the backends in a different order
+        let mut backends_reordered = backends.clone();
+        backends_reordered.reverse();
+        let store = HashShardedStorageAdapter::open(backends_reordered)?;
+        assert_eq!(store.list()?.len(), 101);
+        assert_eq!(store.size()?, 200);
+        assert_eq!(
+            store.get(&"array/c/42".try_into()?)?,
+            Some(vec![0, 1].into())
+        );
+        let list_dir = store.list_dir(&StorePrefix::root())?;
+        assert_eq!(list_dir.keys(), &["zarr.json".try_into()?]);
+        assert_eq!(list_dir.prefixes(), &["array/".try_into()?]);
+
+        store.erase_prefix(&StorePrefix::root())?;
+        assert!(store.list()?.is_empty());
+        assert!(HashShardedStorageAdapter::open(backends.clone()).is_ok());
+
+        // Mismatched backends
+        assert!(HashShardedStorageAdapter::open(backends[..3].to_vec()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn hash_sharded_stable() {
+        // The hash must never change
+        assert_eq!(stable_hash(b""), 0xf52a_15e9_a9b5_e89b);
+        assert_eq!(stable_hash(b"zarr.json"), 0x3766_2e6c_7da3_c277);
+    }
+}
@@ -0,0 +1,362 @@
+//! A prefix storage adapter.
+
+use crate::{
+    byte_range::ByteRange,
+    storage::{
+        Bytes, ListableStorageTraits, MaybeBytes, ReadableStorageTraits,
+        ReadableWritableStorageTraits, StorageError, StoreKey, StoreKeyRange, StoreKeyStartValue,
+        StoreKeys, StoreKeysPrefixes, StorePrefix, WritableStorageTraits,
+    },
+};
+
+#[cfg(feature = "async")]
+use crate::storage::{
+    AsyncBytes, AsyncListableStorageTraits, AsyncReadableStorageTraits,
+    AsyncReadableWritableStorageTraits, AsyncWritableStorageTraits, MaybeAsyncBytes,
+};
+
+use std::sync::Arc;
+
+/// A prefix storage adapter.
+///
+/// Exposes the keys under a [`StorePrefix`] of the underlying storage as a store rooted at `/`.
+/// Keys are translated in both directions: a key `k` of the adapter maps to `{prefix}k` in the underlying storage, and listing results are stripped of the prefix.
+///
+/// This enables a hierarchy nested within a store (e.g. deep within a bucket) to be opened by code expecting a root store.
+///
+/// ### Example
+/// ```rust
+/// # use std::sync::Arc;
+/// # use zarrs::storage::{store::MemoryStore, storage_adapter::prefix::PrefixStorageAdapter};
+/// # use zarrs::storage::{ListableStorageTraits, ReadableStorageTraits, WritableStorageTraits};
+/// let store = Arc::new(MemoryStore::new());
+/// store.set(&"path/to/hierarchy/zarr.json".try_into()?, vec![].into())?;
+/// let store = PrefixStorageAdapter::new(store, "path/to/hierarchy/".try_into()?);
+/// assert!(store.get(&"zarr.json".try_into()?)?.is_some());
+/// assert_eq!(store.list()?, &["zarr.json".try_into()?]);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct PrefixStorageAdapter<TStorage: ?Sized> {
+    storage: Arc<TStorage>,
+    prefix: StorePrefix,
+}
+
+impl<TStorage: ?Sized> PrefixStorageAdapter<TStorage> {
+    /// Create a new prefix storage adapter exposing the keys under `prefix` of `storage`.
+    #[must_use]
+    pub fn new(storage: Arc<TStorage>, prefix: StorePrefix) -> Self {
+        Self { storage, prefix }
+    }
+
+    /// Returns the prefix in the underlying storage.
+    #[must_use]
+    pub const fn prefix(&self) -> &StorePrefix {
+        &self.prefix
+    }
+
+    /// Map a key of the adapter to a key of the underlying storage.
+    fn inner_key(&self, key: &StoreKey) -> StoreKey {
+        unsafe { StoreKey::new_unchecked(format!("{}{}", self.prefix.as_str(), key.as_str())) }
+    }
+
+    /// Map a prefix of the adapter to a prefix of the underlying storage.
+    fn inner_prefix(&self, prefix: &StorePrefix) -> StorePrefix {
+        unsafe {
+            StorePrefix::new_unchecked(format!("{}{}", self.prefix.as_str(), prefix.as_str()))
+        }
+    }
+
+    fn inner_key_ranges(&self, key_ranges: &[StoreKeyRange]) -> Vec<StoreKeyRange> {
+        key_ranges
+            .iter()
+            .map(|key_range| {
+                StoreKeyRange::new(self.inner_key(&key_range.key), key_range.byte_range)
+            })
+            .collect()
+    }
+
+    fn inner_key_start_values<'a>(
+        &self,
+        key_start_values: &[StoreKeyStartValue<'a>],
+    ) -> Vec<StoreKeyStartValue<'a>> {
+        key_start_values
+            .iter()
+            .map(|ksv| StoreKeyStartValue::new(self.inner_key(&ksv.key), ksv.start, ksv.value))
+            .collect()
+    }
+
+    /// Map a key of the underlying storage to a key of the adapter.
+    fn outer_key(&self, key: &StoreKey) -> Result<StoreKey, StorageError> {
+        key.as_str()
+            .strip_prefix(self.prefix.as_str())
+            .map(|key| unsafe { StoreKey::new_unchecked(key) })
+            .ok_or_else(|| {
+                StorageError::Other(format!("key {key} is not under the prefix {}", self.prefix))
+            })
+    }
+
+    /// Map a prefix of the underlying storage to a prefix of the adapter.
+    fn outer_prefix(&self, prefix: &StorePrefix) -> Result<StorePrefix, StorageError> {
+        prefix
+            .as_str()
+            .strip_prefix(self.prefix.as_str())
+            .map(|prefix| unsafe { StorePrefix::new_unchecked(prefix) })
+            .ok_or_else(|| {
+                StorageError::Other(format!(
+                    "prefix {prefix} is not under the prefix {}",
+                    self.prefix
+                ))
+            })
+    }
+
+    fn outer_keys(&self, keys: &[StoreKey]) -> Result<StoreKeys, StorageError> {
+        keys.iter().map(|key| self.outer_key(key)).collect()
+    }
+
+    fn outer_keys_prefixes(
+        &self,
+        keys_prefixes: &StoreKeysPrefixes,
+    ) -> Result<StoreKeysPrefixes, StorageError> {
+        Ok(StoreKeysPrefixes {
+            keys: keys_prefixes
+                .keys()
+                .iter()
+                .map(|key| self.outer_key(key))
+                .collect::<Result<_, _>>()?,
+            prefixes: keys_prefixes
+                .prefixes()
+                .iter()
+                .map(|prefix| self.outer_prefix(prefix))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
+    for PrefixStorageAdapter<TStorage>
+{
+    fn get(&self, key: &StoreKey) -> Result<MaybeBytes, StorageError> {
+        self.storage.get(&self.inner_key(key))
+    }
+
+    fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<Bytes>>, StorageError> {
+        self.storage
+            .get_partial_values_key(&self.inner_key(key), byte_ranges)
+    }
+
+    fn get_partial_values(
+        &self,
+        key_ranges: &[StoreKeyRange],
+    ) -> Result<Vec<MaybeBytes>, StorageError> {
+        self.storage
+            .get_partial_values(&self.inner_key_ranges(key_ranges))
+    }
+
+    fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        self.storage.size_key(&self.inner_key(key))
+    }
+}
+
+impl<TStorage: ?Sized + ListableStorageTraits> ListableStorageTraits
+    for PrefixStorageAdapter<TStorage>
+{
+    fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.outer_keys(&self.storage.list_prefix(&self.prefix)?)
+    }
+
+    fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        self.outer_keys(&self.storage.list_prefix(&self.inner_prefix(prefix))?)
+    }
+
+    fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        self.outer_keys_prefixes(&self.storage.list_dir(&self.inner_prefix(prefix))?)
+    }
+
+    fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        self.storage.size_prefix(&self.inner_prefix(prefix))
+    }
+
+    fn size(&self) -> Result<u64, StorageError> {
+        self.storage.size_prefix(&self.prefix)
+    }
+}
+
+impl<TStorage: ?Sized + WritableStorageTraits> WritableStorageTraits
+    for PrefixStorageAdapter<TStorage>
+{
+    fn set(&self, key: &StoreKey, value: Bytes) -> Result<(), StorageError> {
+        self.storage.set(&self.inner_key(key), value)
+    }
+
+    fn set_partial_values(
+        &self,
+        key_start_values: &[StoreKeyStartValue],
+    ) -> Result<(), StorageError> {
+        self.storage
+            .set_partial_values(&self.inner_key_start_values(key_start_values))
+    }
+
+    fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+        self.storage.erase(&self.inner_key(key))
+    }
+
+    fn erase_values(&self, keys: &[StoreKey]) -> Result<(), StorageError> {
+        let keys: Vec<StoreKey> = keys.iter().map(|key| self.inner_key(key)).collect();
+        self.storage.erase_values(&keys)
+    }
+
+    fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+        self.storage.erase_prefix(&self.inner_prefix(prefix))
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits> ReadableWritableStorageTraits
+    for PrefixStorageAdapter<TStorage>
+{
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncReadableStorageTraits> AsyncReadableStorageTraits
+    for PrefixStorageAdapter<TStorage>
+{
+    async fn get(&self, key: &StoreKey) -> Result<MaybeAsyncBytes, StorageError> {
+        self.storage.get(&self.inner_key(key)).await
+    }
+
+    async fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<AsyncBytes>>, StorageError> {
+        self.storage
+            .get_partial_values_key(&self.inner_key(key), byte_ranges)
+            .await
+    }
+
+    async fn get_partial_values(
+        &self,
+        key_ranges: &[StoreKeyRange],
+    ) -> Result<Vec<MaybeAsyncBytes>, StorageError> {
+        self.storage
+            .get_partial_values(&self.inner_key_ranges(key_ranges))
+            .await
+    }
+
+    async fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        self.storage.size_key(&self.inner_key(key)).await
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncListableStorageTraits> AsyncListableStorageTraits
+    for PrefixStorageAdapter<TStorage>
+{
+    async fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.outer_keys(&self.storage.list_prefix(&self.prefix).await?)
+    }
+
+    async fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        self.outer_keys(&self.storage.list_prefix(&self.inner_prefix(prefix)).await?)
+    }
+
+    async fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        self.outer_keys_prefixes(&self.storage.list_dir(&self.inner_prefix(prefix)).await?)
+    }
+
+    async fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        self.storage.size_prefix(&self.inner_prefix(prefix)).await
+    }
+
+    async fn size(&self) -> Result<u64, StorageError> {
+        self.storage.size_prefix(&self.prefix).await
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncWritableStorageTraits> AsyncWritableStorageTraits
+    for PrefixStorageAdapter<TStorage>
+{
+    async fn set(&self, key: &StoreKey, value: AsyncBytes) -> Result<(), StorageError> {
+        self.storage.set(&self.inner_key(key), value).await
+    }
+
+    async fn set_partial_values(
+        &self,
+        key_start_values: &[StoreKeyStartValue],
+    ) -> Result<(), StorageError> {
+        self.storage
+            .set_partial_values(&self.inner_key_start_values(key_start_values))
+            .await
+    }
+
+    async fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+        self.storage.erase(&self.inner_key(key)).await
+    }
+
+    async fn erase_values(&self, keys: &[StoreKey]) -> Result<(), StorageError> {
+        let keys: Vec<StoreKey> = keys.iter().map(|key| self.inner_key(key)).collect();
+        self.storage.erase_values(&keys).await
+    }
+
+    async fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+        self.storage.erase_prefix(&self.inner_prefix(prefix)).await
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncReadableWritableStorageTraits> AsyncReadableWritableStorageTraits
+    for PrefixStorageAdapter<TStorage>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::store::MemoryStore;
+
+    use super::*;
+
+    #[test]
+    fn prefix() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        store.set(&"outside".try_into()?, vec![0].into())?;
+        let adapter = PrefixStorageAdapter::new(store.clone(), "a/b/".try_into()?);
+        adapter.set(&"c".try_into()?, vec![0, 1, 2].into())?;
+        adapter.set(&"d/e".try_into()?, vec![3].into())?;
+        adapter.set_partial_values(&[StoreKeyStartValue::new("d/e".try_into()?, 1, &[4])])?;
+        assert_eq!(store.get(&"a/b/c".try_into()?)?, Some(vec![0, 1, 2].into()));
+        assert_eq!(store.get(&"a/b/d/e".try_into()?)?, Some(vec![3, 4].into()));
+
+        assert_eq!(adapter.get(&"c".try_into()?)?, Some(vec![0, 1, 2].into()));
+        assert_eq!(
+            adapter.get_partial_values(&[StoreKeyRange::new(
+                "d/e".try_into()?,
+                ByteRange::FromEnd(0, Some(1))
+            )])?,
+            vec![Some(vec![4].into())]
+        );
+        assert_eq!(adapter.size_key(&"d/e".try_into()?)?, Some(2));
+        assert_eq!(adapter.list()?, &["c".try_into()?, "d/e".try_into()?]);
+        assert_eq!(
+            adapter.list_prefix(&"d/".try_into()?)?,
+            &["d/e".try_into()?]
+        );
+        let list_dir = adapter.list_dir(&StorePrefix::root())?;
+        assert_eq!(list_dir.keys(), &["c".try_into()?]);
+        assert_eq!(list_dir.prefixes(), &["d/".try_into()?]);
+        assert_eq!(adapter.size()?, 5);
+
+        adapter.erase_prefix(&StorePrefix::root())?;
+        assert!(adapter.list()?.is_empty());
+        assert_eq!(store.list()?, &["outside".try_into()?]);
+        Ok(())
+    }
+}
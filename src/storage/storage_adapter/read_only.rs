@@ -0,0 +1,189 @@
+//! A read only storage adapter.
+
+use crate::{
+    byte_range::ByteRange,
+    storage::{
+        Bytes, ListableStorageTraits, MaybeBytes, ReadableStorageTraits, StorageError, StoreKey,
+        StoreKeyRange, StoreKeys, StoreKeysPrefixes, StorePrefix,
+    },
+};
+
+#[cfg(feature = "async")]
+use crate::storage::{
+    AsyncBytes, AsyncListableStorageTraits, AsyncReadableStorageTraits, MaybeAsyncBytes,
+};
+
+use std::sync::Arc;
+
+/// A read only storage adapter.
+///
+/// The adapter only implements the readable and listable storage traits, even if the underlying store is writable.
+/// This guarantees at compile time that an [`Array`](crate::array::Array) or [`Group`](crate::group::Group) opened on the adapter cannot mutate the underlying store.
+///
+/// See [`ReadOnlyStorageTransformer`](crate::storage::storage_transformer::ReadOnlyStorageTransformer) for rejecting writes at runtime through a storage transformer chain.
+///
+/// ### Example
+/// ```rust
+/// # use std::sync::Arc;
+/// # use zarrs::storage::{store::MemoryStore, storage_adapter::read_only::ReadOnlyStorageAdapter};
+/// let store = Arc::new(MemoryStore::new());
+/// let store = Arc::new(ReadOnlyStorageAdapter::new(store));
+/// // zarrs::array::Array::open(store, "/array")?; // read only!
+/// ```
+#[derive(Debug)]
+pub struct ReadOnlyStorageAdapter<TStorage: ?Sized> {
+    storage: Arc<TStorage>,
+}
+
+impl<TStorage: ?Sized> ReadOnlyStorageAdapter<TStorage> {
+    /// Create a new read only storage adapter.
+    #[must_use]
+    pub fn new(storage: Arc<TStorage>) -> Self {
+        Self { storage }
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
+    for ReadOnlyStorageAdapter<TStorage>
+{
+    fn get(&self, key: &StoreKey) -> Result<MaybeBytes, StorageError> {
+        self.storage.get(key)
+    }
+
+    fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<Bytes>>, StorageError> {
+        self.storage.get_partial_values_key(key, byte_ranges)
+    }
+
+    fn get_partial_values(
+        &self,
+        key_ranges: &[StoreKeyRange],
+    ) -> Result<Vec<MaybeBytes>, StorageError> {
+        self.storage.get_partial_values(key_ranges)
+    }
+
+    fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        self.storage.size_key(key)
+    }
+
+    fn get_values_coalesced(
+        &self,
+        keys: &[StoreKey],
+        max_waste_ratio: f64,
+    ) -> Result<Vec<MaybeBytes>, StorageError> {
+        self.storage.get_values_coalesced(keys, max_waste_ratio)
+    }
+}
+
+impl<TStorage: ?Sized + ListableStorageTraits> ListableStorageTraits
+    for ReadOnlyStorageAdapter<TStorage>
+{
+    fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.storage.list()
+    }
+
+    fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        self.storage.list_prefix(prefix)
+    }
+
+    fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        self.storage.list_dir(prefix)
+    }
+
+    fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        self.storage.size_prefix(prefix)
+    }
+
+    fn size(&self) -> Result<u64, StorageError> {
+        self.storage.size()
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncReadableStorageTraits> AsyncReadableStorageTraits
+    for ReadOnlyStorageAdapter<TStorage>
+{
+    async fn get(&self, key: &StoreKey) -> Result<MaybeAsyncBytes, StorageError> {
+        self.storage.get(key).await
+    }
+
+    async fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<AsyncBytes>>, StorageError> {
+        self.storage.get_partial_values_key(key, byte_ranges).await
+    }
+
+    async fn get_partial_values(
+        &self,
+        key_ranges: &[StoreKeyRange],
+    ) -> Result<Vec<MaybeAsyncBytes>, StorageError> {
+        self.storage.get_partial_values(key_ranges).await
+    }
+
+    async fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        self.storage.size_key(key).await
+    }
+
+    async fn get_values_coalesced(
+        &self,
+        keys: &[StoreKey],
+        max_waste_ratio: f64,
+    ) -> Result<Vec<MaybeAsyncBytes>, StorageError> {
+        self.storage
+            .get_values_coalesced(keys, max_waste_ratio)
+            .await
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncListableStorageTraits> AsyncListableStorageTraits
+    for ReadOnlyStorageAdapter<TStorage>
+{
+    async fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.storage.list().await
+    }
+
+    async fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        self.storage.list_prefix(prefix).await
+    }
+
+    async fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        self.storage.list_dir(prefix).await
+    }
+
+    async fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        self.storage.size_prefix(prefix).await
+    }
+
+    async fn size(&self) -> Result<u64, StorageError> {
+        self.storage.size().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::{store::MemoryStore, WritableStorageTraits};
+
+    use super::*;
+
+    #[test]
+    fn read_only() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        store.set(&"a/b".try_into()?, vec![0, 1, 2].into())?;
+        let read_only = ReadOnlyStorageAdapter::new(store);
+        assert_eq!(
+            read_only.get(&"a/b".try_into()?)?,
+            Some(vec![0, 1, 2].into())
+        );
+        assert_eq!(read_only.list()?, &["a/b".try_into()?]);
+        assert_eq!(read_only.size()?, 3);
+        Ok(())
+    }
+}
@@ -0,0 +1,671 @@
+//! A copy-on-write snapshot storage adapter.
+//!
+//! See [`SnapshotStorageAdapter`].
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    byte_range::ByteRange,
+    node::NodePath,
+    storage::{
+        Bytes, ListableStorageTraits, MaybeBytes, ReadableStorageTraits,
+        ReadableWritableStorageTraits, StorageError, StoreKey, StoreKeyRange, StoreKeyStartValue,
+        StoreKeys, StoreKeysPrefixes, StorePrefix, StorePrefixes, StoreValueVersion,
+        WritableStorageTraits,
+    },
+};
+
+/// The prefix under which snapshots are stored.
+///
+/// The manifest of the snapshot with `id` is stored at `.snapshots/{id}/manifest.json`, and values preserved for the snapshot are stored at `.snapshots/{id}/values/{key}`.
+pub const SNAPSHOT_PREFIX: &str = ".snapshots/";
+
+/// The manifest of a snapshot, stored at `.snapshots/{id}/manifest.json`.
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotManifest {
+    /// The prefix of the snapshotted node.
+    prefix: String,
+    /// Maps the keys of the snapshot to the versions of their values at the time of the snapshot.
+    versions: BTreeMap<String, String>,
+}
+
+/// The state of a snapshot held by a [`SnapshotStorageAdapter`].
+#[derive(Debug)]
+struct SnapshotState {
+    versions: BTreeMap<StoreKey, StoreValueVersion>,
+    /// The keys with values preserved for the snapshot.
+    preserved: BTreeSet<StoreKey>,
+}
+
+fn snapshot_root() -> StorePrefix {
+    unsafe { StorePrefix::new_unchecked(SNAPSHOT_PREFIX.to_string()) }
+}
+
+fn snapshot_prefix(id: &str) -> Result<StorePrefix, StorageError> {
+    if id.is_empty() || id.contains('/') {
+        return Err(StorageError::Other(format!("invalid snapshot id {id:?}")));
+    }
+    Ok(StorePrefix::new(format!("{SNAPSHOT_PREFIX}{id}/"))?)
+}
+
+fn manifest_key(id: &str) -> Result<StoreKey, StorageError> {
+    Ok(StoreKey::new(format!(
+        "{}manifest.json",
+        snapshot_prefix(id)?.as_str()
+    ))?)
+}
+
+fn preserved_prefix(id: &str) -> Result<StorePrefix, StorageError> {
+    Ok(StorePrefix::new(format!(
+        "{}values/",
+        snapshot_prefix(id)?.as_str()
+    ))?)
+}
+
+fn preserved_key(id: &str, key: &StoreKey) -> Result<StoreKey, StorageError> {
+    Ok(StoreKey::new(format!(
+        "{}{}",
+        preserved_prefix(id)?.as_str(),
+        key.as_str()
+    ))?)
+}
+
+fn is_snapshot_key(key: &StoreKey) -> bool {
+    key.as_str().starts_with(SNAPSHOT_PREFIX)
+}
+
+fn check_key(key: &StoreKey) -> Result<(), StorageError> {
+    if is_snapshot_key(key) {
+        Err(StorageError::Other(format!(
+            "{key} is reserved by the snapshot storage adapter"
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn filter_keys(keys: StoreKeys) -> StoreKeys {
+    keys.into_iter()
+        .filter(|key| !is_snapshot_key(key))
+        .collect()
+}
+
+fn read_manifest<TStorage: ?Sized + ReadableStorageTraits>(
+    storage: &TStorage,
+    id: &str,
+) -> Result<SnapshotManifest, StorageError> {
+    let key = manifest_key(id)?;
+    let manifest = storage
+        .get(&key)?
+        .ok_or_else(|| StorageError::Other(format!("snapshot {id} not found")))?;
+    serde_json::from_slice(&manifest)
+        .map_err(|err| StorageError::InvalidMetadata(key, err.to_string()))
+}
+
+fn manifest_versions(
+    manifest: SnapshotManifest,
+) -> Result<BTreeMap<StoreKey, StoreValueVersion>, StorageError> {
+    manifest
+        .versions
+        .into_iter()
+        .map(|(key, version)| Ok((StoreKey::new(key)?, StoreValueVersion::new(version))))
+        .collect()
+}
+
+/// A copy-on-write snapshot storage adapter: nodes can be snapshotted and read as of a snapshot while writes continue.
+///
+/// [`snapshot`](SnapshotStorageAdapter::snapshot) records a manifest of the keys under a node (e.g. the metadata and chunks of an array) and the [`StoreValueVersion`]s of their values.
+/// Values are not copied when a snapshot is created.
+/// Instead, the first write or erase of a snapshotted key through the adapter preserves its value under [`SNAPSHOT_PREFIX`] before it is modified.
+/// [`StorageSnapshot`] reads the node as of a snapshot from the preserved values and the unmodified values in the underlying store, so an array opened on it returns the same data however much is written afterwards.
+///
+/// All writes to snapshotted nodes must go through a single adapter, since other writers do not preserve values.
+/// Snapshot reads fail rather than return modified data if a value was changed outside of the adapter.
+/// Preserved values are retained until the snapshot is [released](SnapshotStorageAdapter::release).
+///
+/// The snapshots are hidden from listing through the adapter, and keys under [`SNAPSHOT_PREFIX`] cannot be written or erased through the adapter.
+///
+/// ### Example
+/// ```rust
+/// # use std::sync::Arc;
+/// # use zarrs::array::{Array, ArrayBuilder, DataType, FillValue};
+/// # use zarrs::array_subset::ArraySubset;
+/// # use zarrs::storage::{store::MemoryStore, storage_adapter::snapshot::SnapshotStorageAdapter};
+/// let store = Arc::new(SnapshotStorageAdapter::new(Arc::new(MemoryStore::new()))?);
+/// let array = ArrayBuilder::new(vec![4], DataType::UInt8, vec![2].try_into()?, FillValue::from(0u8))
+///     .build(store.clone(), "/array")?;
+/// array.store_metadata()?;
+/// let subset = ArraySubset::new_with_shape(vec![4]);
+/// array.store_array_subset_elements::<u8>(&subset, &[1, 2, 3, 4])?;
+///
+/// // Snapshot the array and continue writing to it
+/// let id = store.snapshot(array.path())?;
+/// array.store_array_subset_elements::<u8>(&subset, &[5, 6, 7, 8])?;
+///
+/// // Open the array as of the snapshot
+/// let array_snapshot = Array::open(Arc::new(store.at_snapshot(&id)?), "/array")?;
+/// assert_eq!(
+///     array_snapshot.retrieve_array_subset_elements::<u8>(&subset)?,
+///     vec![1, 2, 3, 4]
+/// );
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct SnapshotStorageAdapter<TStorage: ?Sized> {
+    storage: Arc<TStorage>,
+    snapshots: Mutex<BTreeMap<String, SnapshotState>>,
+    /// Held for reading by writes, and for writing while a snapshot is created.
+    writes: RwLock<()>,
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits + ListableStorageTraits>
+    SnapshotStorageAdapter<TStorage>
+{
+    /// Create a new snapshot storage adapter, loading the existing snapshots in `storage`.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if a snapshot manifest is invalid or there is an underlying store error.
+    pub fn new(storage: Arc<TStorage>) -> Result<Self, StorageError> {
+        let mut snapshots = BTreeMap::new();
+        for prefix in storage.list_dir(&snapshot_root())?.prefixes() {
+            let id = &prefix.as_str()[SNAPSHOT_PREFIX.len()..prefix.as_str().len() - 1];
+            let versions = manifest_versions(read_manifest(&*storage, id)?)?;
+            let preserved_prefix = preserved_prefix(id)?;
+            let preserved = storage
+                .list_prefix(&preserved_prefix)?
+                .iter()
+                .map(|key| {
+                    Ok(StoreKey::new(
+                        &key.as_str()[preserved_prefix.as_str().len()..],
+                    )?)
+                })
+                .collect::<Result<_, StorageError>>()?;
+            snapshots.insert(
+                id.to_string(),
+                SnapshotState {
+                    versions,
+                    preserved,
+                },
+            );
+        }
+        Ok(Self {
+            storage,
+            snapshots: Mutex::new(snapshots),
+            writes: RwLock::new(()),
+        })
+    }
+}
+
+impl<TStorage: ?Sized> SnapshotStorageAdapter<TStorage> {
+    /// Returns the underlying store.
+    #[must_use]
+    pub fn storage(&self) -> &Arc<TStorage> {
+        &self.storage
+    }
+
+    /// Returns the IDs of the snapshots, oldest first.
+    #[must_use]
+    pub fn snapshot_ids(&self) -> Vec<String> {
+        self.snapshots.lock().keys().cloned().collect()
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits> SnapshotStorageAdapter<TStorage> {
+    /// Open the snapshot with `id` for reading.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if the snapshot does not exist or there is an underlying store error.
+    pub fn at_snapshot(&self, id: &str) -> Result<StorageSnapshot<TStorage>, StorageError> {
+        StorageSnapshot::open(self.storage.clone(), id)
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits + ListableStorageTraits>
+    SnapshotStorageAdapter<TStorage>
+{
+    /// Snapshot the node at `path` and its children.
+    ///
+    /// Writes through the adapter are blocked while the versions of the values under the node are recorded.
+    /// Returns the ID of the snapshot.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying store error.
+    pub fn snapshot(&self, path: &NodePath) -> Result<String, StorageError> {
+        let prefix = StorePrefix::try_from(path)?;
+        let _writes = self.writes.write();
+        let mut versions = BTreeMap::new();
+        for key in filter_keys(self.storage.list_prefix(&prefix)?) {
+            if let Some(version) = self.storage.get_version(&key)? {
+                versions.insert(key, version);
+            }
+        }
+
+        let mut snapshots = self.snapshots.lock();
+        let mut nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos());
+        let id = loop {
+            let id = format!("{nanos:020}");
+            if !snapshots.contains_key(&id) {
+                break id;
+            }
+            nanos += 1;
+        };
+
+        let manifest = SnapshotManifest {
+            prefix: prefix.as_str().to_string(),
+            versions: versions
+                .iter()
+                .map(|(key, version)| (key.as_str().to_string(), version.as_str().to_string()))
+                .collect(),
+        };
+        let manifest_key = manifest_key(&id)?;
+        let manifest = serde_json::to_vec_pretty(&manifest)
+            .map_err(|err| StorageError::InvalidMetadata(manifest_key.clone(), err.to_string()))?;
+        self.storage.set(&manifest_key, manifest.into())?;
+        snapshots.insert(
+            id.clone(),
+            SnapshotState {
+                versions,
+                preserved: BTreeSet::new(),
+            },
+        );
+        Ok(id)
+    }
+
+    /// Release the snapshot with `id` and delete its preserved values.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if the snapshot does not exist or there is an underlying store error.
+    pub fn release(&self, id: &str) -> Result<(), StorageError> {
+        let mut snapshots = self.snapshots.lock();
+        if snapshots.remove(id).is_none() {
+            return Err(StorageError::Other(format!("snapshot {id} not found")));
+        }
+        self.storage.erase_prefix(&snapshot_prefix(id)?)
+    }
+
+    /// Preserve the values at `keys` for the snapshots that include them before they are modified.
+    fn preserve<'a>(
+        &self,
+        keys: impl IntoIterator<Item = &'a StoreKey>,
+    ) -> Result<(), StorageError> {
+        let mut snapshots = self.snapshots.lock();
+        if snapshots.is_empty() {
+            return Ok(());
+        }
+        for key in keys {
+            let mut value: Option<Option<(Bytes, StoreValueVersion)>> = None;
+            for (id, snapshot) in snapshots.iter_mut() {
+                let Some(version) = snapshot.versions.get(key) else {
+                    continue;
+                };
+                if snapshot.preserved.contains(key) {
+                    continue;
+                }
+                if value.is_none() {
+                    value = Some(self.storage.get_with_version(key)?);
+                }
+                // A value changed outside of the adapter is not preserved, so snapshot reads of it fail
+                if let Some(Some((value, value_version))) = &value {
+                    if value_version == version {
+                        self.storage.set(&preserved_key(id, key)?, value.clone())?;
+                    }
+                }
+                snapshot.preserved.insert(key.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the snapshotted keys with `prefix`.
+    fn snapshot_keys(&self, prefix: &StorePrefix) -> BTreeSet<StoreKey> {
+        self.snapshots
+            .lock()
+            .values()
+            .flat_map(|snapshot| snapshot.versions.keys())
+            .filter(|key| key.has_prefix(prefix))
+            .cloned()
+            .collect()
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
+    for SnapshotStorageAdapter<TStorage>
+{
+    fn get(&self, key: &StoreKey) -> Result<MaybeBytes, StorageError> {
+        self.storage.get(key)
+    }
+
+    fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<Bytes>>, StorageError> {
+        self.storage.get_partial_values_key(key, byte_ranges)
+    }
+
+    fn get_partial_values(
+        &self,
+        key_ranges: &[StoreKeyRange],
+    ) -> Result<Vec<MaybeBytes>, StorageError> {
+        self.storage.get_partial_values(key_ranges)
+    }
+
+    fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        self.storage.size_key(key)
+    }
+
+    fn get_values_coalesced(
+        &self,
+        keys: &[StoreKey],
+        max_waste_ratio: f64,
+    ) -> Result<Vec<MaybeBytes>, StorageError> {
+        self.storage.get_values_coalesced(keys, max_waste_ratio)
+    }
+}
+
+impl<TStorage: ?Sized + ListableStorageTraits> ListableStorageTraits
+    for SnapshotStorageAdapter<TStorage>
+{
+    fn list(&self) -> Result<StoreKeys, StorageError> {
+        Ok(filter_keys(self.storage.list()?))
+    }
+
+    fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        Ok(filter_keys(self.storage.list_prefix(prefix)?))
+    }
+
+    fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        let keys_prefixes = self.storage.list_dir(prefix)?;
+        Ok(StoreKeysPrefixes {
+            keys: keys_prefixes.keys,
+            prefixes: keys_prefixes
+                .prefixes
+                .into_iter()
+                .filter(|prefix| prefix.as_str() != SNAPSHOT_PREFIX)
+                .collect(),
+        })
+    }
+
+    fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        let size = self.storage.size_prefix(prefix)?;
+        if SNAPSHOT_PREFIX.starts_with(prefix.as_str()) {
+            Ok(size - self.storage.size_prefix(&snapshot_root())?)
+        } else if prefix.as_str().starts_with(SNAPSHOT_PREFIX) {
+            Ok(0)
+        } else {
+            Ok(size)
+        }
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits + ListableStorageTraits> WritableStorageTraits
+    for SnapshotStorageAdapter<TStorage>
+{
+    fn set(&self, key: &StoreKey, value: Bytes) -> Result<(), StorageError> {
+        check_key(key)?;
+        let _writes = self.writes.read();
+        self.preserve([key])?;
+        self.storage.set(key, value)
+    }
+
+    fn set_partial_values(
+        &self,
+        key_start_values: &[StoreKeyStartValue],
+    ) -> Result<(), StorageError> {
+        for key_start_value in key_start_values {
+            check_key(&key_start_value.key)?;
+        }
+        let _writes = self.writes.read();
+        self.preserve(
+            key_start_values
+                .iter()
+                .map(|key_start_value| &key_start_value.key),
+        )?;
+        self.storage.set_partial_values(key_start_values)
+    }
+
+    fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+        self.erase_values(std::slice::from_ref(key))
+    }
+
+    fn erase_values(&self, keys: &[StoreKey]) -> Result<(), StorageError> {
+        for key in keys {
+            check_key(key)?;
+        }
+        let _writes = self.writes.read();
+        self.preserve(keys)?;
+        self.storage.erase_values(keys)
+    }
+
+    fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+        if SNAPSHOT_PREFIX.starts_with(prefix.as_str()) {
+            // Erase the keys individually to retain the snapshots
+            let keys = filter_keys(self.storage.list_prefix(prefix)?);
+            self.erase_values(&keys)
+        } else if prefix.as_str().starts_with(SNAPSHOT_PREFIX) {
+            Err(StorageError::Other(format!(
+                "{prefix} is reserved by the snapshot storage adapter"
+            )))
+        } else {
+            let _writes = self.writes.read();
+            self.preserve(&self.snapshot_keys(prefix))?;
+            self.storage.erase_prefix(prefix)
+        }
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits + ListableStorageTraits>
+    ReadableWritableStorageTraits for SnapshotStorageAdapter<TStorage>
+{
+}
+
+/// A read-only view of a node as of a snapshot of a [`SnapshotStorageAdapter`].
+///
+/// Values modified since the snapshot are read from the values preserved for the snapshot, and other values are read from the underlying store.
+/// Reads of a value that was modified outside of the [`SnapshotStorageAdapter`] since the snapshot return an error.
+#[derive(Debug)]
+pub struct StorageSnapshot<TStorage: ?Sized> {
+    storage: Arc<TStorage>,
+    id: String,
+    prefix: StorePrefix,
+    versions: BTreeMap<StoreKey, StoreValueVersion>,
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits> StorageSnapshot<TStorage> {
+    /// Open the snapshot with `id` in `storage`.
+    ///
+    /// A snapshot can be opened in a process other than the one writing through the [`SnapshotStorageAdapter`].
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if the snapshot does not exist or there is an underlying store error.
+    pub fn open(storage: Arc<TStorage>, id: &str) -> Result<Self, StorageError> {
+        let manifest = read_manifest(&*storage, id)?;
+        let prefix = StorePrefix::new(manifest.prefix.clone())?;
+        Ok(Self {
+            storage,
+            id: id.to_string(),
+            prefix,
+            versions: manifest_versions(manifest)?,
+        })
+    }
+
+    /// Returns the snapshot ID.
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the prefix of the snapshotted node.
+    #[must_use]
+    pub const fn prefix(&self) -> &StorePrefix {
+        &self.prefix
+    }
+
+    /// Returns the version of the value at `key` at the time of the snapshot, or [`None`] if `key` is not in the snapshot.
+    #[must_use]
+    pub fn version(&self, key: &StoreKey) -> Option<&StoreValueVersion> {
+        self.versions.get(key)
+    }
+
+    fn modified_error(key: &StoreKey) -> StorageError {
+        StorageError::Other(format!(
+            "{key} was modified outside of the snapshot storage adapter"
+        ))
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits for StorageSnapshot<TStorage> {
+    fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<Bytes>>, StorageError> {
+        let Some(version) = self.versions.get(key) else {
+            return Ok(None);
+        };
+        let key_preserved = preserved_key(&self.id, key)?;
+        if let Some(values) = self
+            .storage
+            .get_partial_values_key(&key_preserved, byte_ranges)?
+        {
+            return Ok(Some(values));
+        }
+        let values = self.storage.get_partial_values_key(key, byte_ranges)?;
+        let version_current = self.storage.get_version(key)?;
+        // The value may have been preserved and modified while it was read
+        if let Some(values) = self
+            .storage
+            .get_partial_values_key(&key_preserved, byte_ranges)?
+        {
+            return Ok(Some(values));
+        }
+        if version_current.as_ref() == Some(version) {
+            Ok(values)
+        } else {
+            Err(Self::modified_error(key))
+        }
+    }
+
+    fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        if !self.versions.contains_key(key) {
+            return Ok(None);
+        }
+        let key_preserved = preserved_key(&self.id, key)?;
+        if let Some(size) = self.storage.size_key(&key_preserved)? {
+            return Ok(Some(size));
+        }
+        let size = self.storage.size_key(key)?;
+        if let Some(size) = self.storage.size_key(&key_preserved)? {
+            return Ok(Some(size));
+        }
+        size.ok_or_else(|| Self::modified_error(key)).map(Some)
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits> ListableStorageTraits for StorageSnapshot<TStorage> {
+    fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.list_prefix(&StorePrefix::root())
+    }
+
+    fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        Ok(self
+            .versions
+            .keys()
+            .filter(|key| key.has_prefix(prefix))
+            .cloned()
+            .collect())
+    }
+
+    fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        let mut keys = StoreKeys::new();
+        let mut prefixes = BTreeSet::new();
+        for key in self.list_prefix(prefix)? {
+            let suffix = &key.as_str()[prefix.as_str().len()..];
+            if let Some((child, _)) = suffix.split_once('/') {
+                prefixes.insert(StorePrefix::new(format!("{}{child}/", prefix.as_str()))?);
+            } else {
+                keys.push(key);
+            }
+        }
+        Ok(StoreKeysPrefixes {
+            keys,
+            prefixes: prefixes.into_iter().collect::<StorePrefixes>(),
+        })
+    }
+
+    fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        let mut size = 0;
+        for key in self.list_prefix(prefix)? {
+            size += self.size_key(&key)?.unwrap_or_default();
+        }
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::store::MemoryStore;
+
+    use super::*;
+
+    #[test]
+    fn snapshot() -> Result<(), Box<dyn std::error::Error>> {
+        let storage = Arc::new(MemoryStore::new());
+        let store = SnapshotStorageAdapter::new(storage.clone())?;
+        store.set(&"a/b".try_into()?, vec![0, 1].into())?;
+        store.set(&"a/c".try_into()?, vec![2].into())?;
+        store.set(&"d".try_into()?, vec![3].into())?;
+
+        let id = store.snapshot(&"/a".try_into()?)?;
+        assert_eq!(store.snapshot_ids(), vec![id.clone()]);
+        store.set_partial_values(&[StoreKeyStartValue::new("a/b".try_into()?, 2, &[4])])?;
+        store.erase_prefix(&"a/".try_into()?)?;
+        store.set(&"a/e".try_into()?, vec![5].into())?;
+        assert_eq!(store.list()?, &["a/e".try_into()?, "d".try_into()?]);
+        assert_eq!(
+            store.list_dir(&StorePrefix::root())?.prefixes(),
+            &["a/".try_into()?]
+        );
+        assert_eq!(store.size()?, 2);
+        assert!(store
+            .set(&".snapshots/x".try_into()?, vec![0].into())
+            .is_err());
+
+        // The snapshot is unaffected by writes after it was created, including from a reopened adapter
+        let snapshot = store.at_snapshot(&id)?;
+        assert_eq!(snapshot.prefix().as_str(), "a/");
+        assert_eq!(snapshot.get(&"a/b".try_into()?)?, Some(vec![0, 1].into()));
+        assert_eq!(snapshot.get(&"a/c".try_into()?)?, Some(vec![2].into()));
+        assert_eq!(snapshot.get(&"a/e".try_into()?)?, None);
+        assert_eq!(snapshot.list()?, &["a/b".try_into()?, "a/c".try_into()?]);
+        assert_eq!(snapshot.size()?, 3);
+        let store = SnapshotStorageAdapter::new(storage.clone())?;
+        assert_eq!(store.snapshot_ids(), vec![id.clone()]);
+        store.set(&"a/b".try_into()?, vec![6].into())?;
+        assert_eq!(snapshot.get(&"a/b".try_into()?)?, Some(vec![0, 1].into()));
+
+        // Values modified outside of the adapter are not returned
+        let id_d = store.snapshot(&"/".try_into()?)?;
+        storage.set(&"d".try_into()?, vec![7].into())?;
+        assert!(store.at_snapshot(&id_d)?.get(&"d".try_into()?).is_err());
+
+        store.release(&id)?;
+        store.release(&id_d)?;
+        assert!(store.snapshot_ids().is_empty());
+        assert!(store.at_snapshot(&id).is_err());
+        assert_eq!(
+            storage.list()?,
+            &["a/b".try_into()?, "a/e".try_into()?, "d".try_into()?]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,565 @@
+//! A tiered storage adapter.
+//!
+//! See [`TieredStorageAdapter`].
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    byte_range::ByteRange,
+    storage::{
+        Bytes, ListableStorageTraits, MaybeBytes, ReadableStorageTraits,
+        ReadableWritableStorageTraits, StorageError, StoreKey, StoreKeyStartValue, StoreKeys,
+        StoreKeysPrefixes, StorePrefix, StorePrefixes, WritableStorageTraits,
+    },
+};
+
+/// The write policy of a [`TieredStorageAdapter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TieredStorageWritePolicy {
+    /// Writes are applied to the remote tier and the local tier immediately.
+    #[default]
+    WriteThrough,
+    /// Writes are applied to the local tier only and are propagated to the remote tier by [`TieredStorageAdapter::flush`].
+    WriteBack,
+}
+
+/// A pending write-back operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dirty {
+    Set,
+    Erase,
+}
+
+/// Pending write-back operations.
+#[derive(Debug, Default)]
+struct WriteBackState {
+    /// Prefixes erased since the last flush, in the order they were erased.
+    erased_prefixes: Vec<StorePrefix>,
+    /// Keys written or erased since the last flush. Takes precedence over `erased_prefixes`.
+    keys: BTreeMap<StoreKey, Dirty>,
+}
+
+impl WriteBackState {
+    fn is_empty(&self) -> bool {
+        self.erased_prefixes.is_empty() && self.keys.is_empty()
+    }
+
+    /// Returns [`Some`] if `key` has a pending operation, including an erasure via a prefix.
+    fn get(&self, key: &StoreKey) -> Option<Dirty> {
+        self.keys.get(key).copied().or_else(|| {
+            self.erased_prefixes
+                .iter()
+                .any(|prefix| key.has_prefix(prefix))
+                .then_some(Dirty::Erase)
+        })
+    }
+}
+
+/// A tiered storage adapter: a fast local store in front of a slow remote store.
+///
+/// Reads are served from the local tier where possible.
+/// On a miss, the *entire* value is retrieved from the remote tier and stored in the local tier before the request is served.
+/// This makes the local tier a persistent, store-level cache of the remote tier (e.g. a [`FilesystemStore`](crate::storage::store::FilesystemStore) in front of an S3 bucket).
+/// Missing keys are not cached.
+///
+/// Writes follow a [`TieredStorageWritePolicy`]:
+///  - [`WriteThrough`](TieredStorageWritePolicy::WriteThrough): writes are applied to the remote tier and then to the local tier.
+///  - [`WriteBack`](TieredStorageWritePolicy::WriteBack): writes are applied to the local tier only and are tracked until [`flush`](TieredStorageAdapter::flush) propagates them to the remote tier.
+///    Reads and listing reflect pending writes.
+///    Pending writes are lost if the adapter is dropped without flushing.
+///
+/// The remote tier is assumed not to be modified by other writers while the adapter is in use; the local tier is never invalidated automatically.
+/// Use [`clear_local`](TieredStorageAdapter::clear_local) to invalidate the local tier.
+///
+/// Asynchronous remote stores can be used via the [`AsyncToSyncStorageAdapter`](crate::storage::storage_adapter::async_to_sync::AsyncToSyncStorageAdapter).
+///
+/// ### Example
+/// ```rust
+/// # use std::sync::Arc;
+/// # use zarrs::storage::{store::MemoryStore, ReadableStorageTraits, WritableStorageTraits};
+/// # use zarrs::storage::storage_adapter::tiered::{TieredStorageAdapter, TieredStorageWritePolicy};
+/// let local = Arc::new(MemoryStore::new());
+/// let remote = Arc::new(MemoryStore::new());
+/// remote.set(&"zarr.json".try_into()?, vec![0, 1].into())?;
+/// let store = TieredStorageAdapter::new(local.clone(), remote, TieredStorageWritePolicy::WriteThrough);
+/// assert!(local.get(&"zarr.json".try_into()?)?.is_none());
+/// store.get(&"zarr.json".try_into()?)?;
+/// assert!(local.get(&"zarr.json".try_into()?)?.is_some());
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct TieredStorageAdapter<TLocal: ?Sized, TRemote: ?Sized> {
+    local: Arc<TLocal>,
+    remote: Arc<TRemote>,
+    write_policy: TieredStorageWritePolicy,
+    write_back: Mutex<WriteBackState>,
+}
+
+impl<TLocal: ?Sized, TRemote: ?Sized> TieredStorageAdapter<TLocal, TRemote> {
+    /// Create a new tiered storage adapter with a `local` (fast) and `remote` (slow) tier.
+    #[must_use]
+    pub fn new(
+        local: Arc<TLocal>,
+        remote: Arc<TRemote>,
+        write_policy: TieredStorageWritePolicy,
+    ) -> Self {
+        Self {
+            local,
+            remote,
+            write_policy,
+            write_back: Mutex::default(),
+        }
+    }
+
+    /// Returns the local tier.
+    #[must_use]
+    pub fn local(&self) -> &Arc<TLocal> {
+        &self.local
+    }
+
+    /// Returns the remote tier.
+    #[must_use]
+    pub fn remote(&self) -> &Arc<TRemote> {
+        &self.remote
+    }
+
+    /// Returns the write policy.
+    #[must_use]
+    pub const fn write_policy(&self) -> TieredStorageWritePolicy {
+        self.write_policy
+    }
+
+    /// Returns true if there are write-back operations which have not been flushed to the remote tier.
+    ///
+    /// # Panics
+    /// Panics if the write-back state mutex is poisoned.
+    #[must_use]
+    pub fn is_dirty(&self) -> bool {
+        !self.write_back.lock().unwrap().is_empty()
+    }
+
+    fn dirty(&self, key: &StoreKey) -> Option<Dirty> {
+        if self.write_policy == TieredStorageWritePolicy::WriteBack {
+            self.write_back.lock().unwrap().get(key)
+        } else {
+            None
+        }
+    }
+}
+
+impl<TLocal: ?Sized + ReadableWritableStorageTraits, TRemote: ?Sized + ReadableStorageTraits>
+    TieredStorageAdapter<TLocal, TRemote>
+{
+    /// Ensure `key` is in the local tier, retrieving it from the remote tier on a miss.
+    ///
+    /// Returns false if the key does not exist.
+    fn populate(&self, key: &StoreKey) -> Result<bool, StorageError> {
+        match self.dirty(key) {
+            Some(Dirty::Set) => Ok(true),
+            Some(Dirty::Erase) => Ok(false),
+            None => {
+                if self.local.size_key(key)?.is_some() {
+                    Ok(true)
+                } else if let Some(value) = self.remote.get(key)? {
+                    self.local.set(key, value)?;
+                    Ok(true)
+                } else {
+                    Ok(false)
+                }
+            }
+        }
+    }
+}
+
+impl<TLocal: ?Sized + ReadableWritableStorageTraits, TRemote: ?Sized>
+    TieredStorageAdapter<TLocal, TRemote>
+{
+    /// Erase all values in the local tier.
+    ///
+    /// With the [`WriteBack`](TieredStorageWritePolicy::WriteBack) policy, [`flush`](TieredStorageAdapter::flush) should be called first, otherwise pending writes are lost.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying error with the local tier.
+    pub fn clear_local(&self) -> Result<(), StorageError> {
+        self.local.erase_prefix(&StorePrefix::root())
+    }
+}
+
+impl<TLocal: ?Sized + ReadableStorageTraits, TRemote: ?Sized + WritableStorageTraits>
+    TieredStorageAdapter<TLocal, TRemote>
+{
+    /// Propagate pending write-back operations to the remote tier.
+    ///
+    /// This is a no-op with the [`WriteThrough`](TieredStorageWritePolicy::WriteThrough) policy.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying error with either tier.
+    /// Operations that were not propagated remain pending.
+    ///
+    /// # Panics
+    /// Panics if the write-back state mutex is poisoned.
+    pub fn flush(&self) -> Result<(), StorageError> {
+        let mut write_back = self.write_back.lock().unwrap();
+        while let Some(prefix) = write_back.erased_prefixes.first().cloned() {
+            self.remote.erase_prefix(&prefix)?;
+            write_back.erased_prefixes.remove(0);
+        }
+        while let Some((key, dirty)) = write_back.keys.pop_first() {
+            let result = match dirty {
+                Dirty::Set => match self.local.get(&key) {
+                    Ok(Some(value)) => self.remote.set(&key, value),
+                    Ok(None) => Err(StorageError::Other(format!(
+                        "write-back value for {key} is missing from the local tier"
+                    ))),
+                    Err(err) => Err(err),
+                },
+                Dirty::Erase => self.remote.erase(&key),
+            };
+            if let Err(err) = result {
+                write_back.keys.insert(key, dirty);
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<TLocal: ?Sized + ReadableWritableStorageTraits, TRemote: ?Sized + ReadableStorageTraits>
+    ReadableStorageTraits for TieredStorageAdapter<TLocal, TRemote>
+{
+    fn get(&self, key: &StoreKey) -> Result<MaybeBytes, StorageError> {
+        match self.dirty(key) {
+            Some(Dirty::Set) => self.local.get(key),
+            Some(Dirty::Erase) => Ok(None),
+            None => {
+                if let Some(value) = self.local.get(key)? {
+                    Ok(Some(value))
+                } else if let Some(value) = self.remote.get(key)? {
+                    self.local.set(key, value.clone())?;
+                    Ok(Some(value))
+                } else {
+                    Ok(None)
+                }
+            }
+        }
+    }
+
+    fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<Bytes>>, StorageError> {
+        if self.populate(key)? {
+            self.local.get_partial_values_key(key, byte_ranges)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        match self.dirty(key) {
+            Some(Dirty::Set) => self.local.size_key(key),
+            Some(Dirty::Erase) => Ok(None),
+            None => {
+                if let Some(size) = self.local.size_key(key)? {
+                    Ok(Some(size))
+                } else {
+                    self.remote.size_key(key)
+                }
+            }
+        }
+    }
+}
+
+/// Returns the keys and prefixes which are direct children of `prefix` from a list of `keys` under `prefix`.
+fn list_dir_from_keys(prefix: &StorePrefix, keys: &[StoreKey]) -> StoreKeysPrefixes {
+    let mut dir_keys = StoreKeys::new();
+    let mut dir_prefixes = BTreeSet::new();
+    for key in keys {
+        let suffix = &key.as_str()[prefix.as_str().len()..];
+        if let Some((child, _)) = suffix.split_once('/') {
+            dir_prefixes.insert(unsafe {
+                StorePrefix::new_unchecked(format!("{}{child}/", prefix.as_str()))
+            });
+        } else {
+            dir_keys.push(key.clone());
+        }
+    }
+    StoreKeysPrefixes {
+        keys: dir_keys,
+        prefixes: dir_prefixes.into_iter().collect::<StorePrefixes>(),
+    }
+}
+
+impl<TLocal: ?Sized + ReadableWritableStorageTraits, TRemote: ?Sized + ListableStorageTraits>
+    TieredStorageAdapter<TLocal, TRemote>
+{
+    /// List the keys under `prefix`, accounting for pending write-back operations.
+    ///
+    /// Returns [`None`] if there are no pending operations under `prefix`, in which case the remote tier is authoritative.
+    fn list_prefix_write_back(
+        &self,
+        prefix: &StorePrefix,
+    ) -> Result<Option<StoreKeys>, StorageError> {
+        if self.write_policy != TieredStorageWritePolicy::WriteBack {
+            return Ok(None);
+        }
+        let write_back = self.write_back.lock().unwrap();
+        let affected = write_back.keys.keys().any(|key| key.has_prefix(prefix))
+            || write_back.erased_prefixes.iter().any(|erased| {
+                erased.as_str().starts_with(prefix.as_str())
+                    || prefix.as_str().starts_with(erased.as_str())
+            });
+        if !affected {
+            return Ok(None);
+        }
+        let mut keys: BTreeSet<StoreKey> = self
+            .remote
+            .list_prefix(prefix)?
+            .into_iter()
+            .filter(|key| write_back.get(key).is_none())
+            .collect();
+        keys.extend(
+            write_back
+                .keys
+                .iter()
+                .filter(|(key, dirty)| **dirty == Dirty::Set && key.has_prefix(prefix))
+                .map(|(key, _)| key.clone()),
+        );
+        Ok(Some(keys.into_iter().collect()))
+    }
+}
+
+impl<
+        TLocal: ?Sized + ReadableWritableStorageTraits,
+        TRemote: ?Sized + ReadableStorageTraits + ListableStorageTraits,
+    > ListableStorageTraits for TieredStorageAdapter<TLocal, TRemote>
+{
+    fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.list_prefix(&StorePrefix::root())
+    }
+
+    fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        if let Some(keys) = self.list_prefix_write_back(prefix)? {
+            Ok(keys)
+        } else {
+            self.remote.list_prefix(prefix)
+        }
+    }
+
+    fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        if let Some(keys) = self.list_prefix_write_back(prefix)? {
+            Ok(list_dir_from_keys(prefix, &keys))
+        } else {
+            self.remote.list_dir(prefix)
+        }
+    }
+
+    fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        if let Some(keys) = self.list_prefix_write_back(prefix)? {
+            let mut size = 0;
+            for key in &keys {
+                size += self.size_key(key)?.unwrap_or_default();
+            }
+            Ok(size)
+        } else {
+            self.remote.size_prefix(prefix)
+        }
+    }
+}
+
+impl<
+        TLocal: ?Sized + ReadableWritableStorageTraits,
+        TRemote: ?Sized + ReadableWritableStorageTraits,
+    > WritableStorageTraits for TieredStorageAdapter<TLocal, TRemote>
+{
+    fn set(&self, key: &StoreKey, value: Bytes) -> Result<(), StorageError> {
+        match self.write_policy {
+            TieredStorageWritePolicy::WriteThrough => {
+                self.remote.set(key, value.clone())?;
+                self.local.set(key, value)
+            }
+            TieredStorageWritePolicy::WriteBack => {
+                self.local.set(key, value)?;
+                self.write_back
+                    .lock()
+                    .unwrap()
+                    .keys
+                    .insert(key.clone(), Dirty::Set);
+                Ok(())
+            }
+        }
+    }
+
+    fn set_partial_values(
+        &self,
+        key_start_values: &[StoreKeyStartValue],
+    ) -> Result<(), StorageError> {
+        match self.write_policy {
+            TieredStorageWritePolicy::WriteThrough => {
+                self.remote.set_partial_values(key_start_values)?;
+                // Invalidate the local tier, it is repopulated on the next read
+                let keys: Vec<StoreKey> = key_start_values
+                    .iter()
+                    .map(|key_start_value| key_start_value.key.clone())
+                    .collect();
+                self.local.erase_values(&keys)
+            }
+            TieredStorageWritePolicy::WriteBack => {
+                for key_start_value in key_start_values {
+                    if !self.populate(&key_start_value.key)? {
+                        // The value does not exist or is pending erasure, start from empty
+                        self.local.erase(&key_start_value.key)?;
+                    }
+                }
+                self.local.set_partial_values(key_start_values)?;
+                let mut write_back = self.write_back.lock().unwrap();
+                for key_start_value in key_start_values {
+                    write_back
+                        .keys
+                        .insert(key_start_value.key.clone(), Dirty::Set);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+        if self.write_policy == TieredStorageWritePolicy::WriteThrough {
+            self.remote.erase(key)?;
+        } else {
+            self.write_back
+                .lock()
+                .unwrap()
+                .keys
+                .insert(key.clone(), Dirty::Erase);
+        }
+        self.local.erase(key)
+    }
+
+    fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+        if self.write_policy == TieredStorageWritePolicy::WriteThrough {
+            self.remote.erase_prefix(prefix)?;
+        } else {
+            let mut write_back = self.write_back.lock().unwrap();
+            write_back.keys.retain(|key, _| !key.has_prefix(prefix));
+            write_back.erased_prefixes.push(prefix.clone());
+        }
+        self.local.erase_prefix(prefix)
+    }
+}
+
+impl<
+        TLocal: ?Sized + ReadableWritableStorageTraits,
+        TRemote: ?Sized + ReadableWritableStorageTraits,
+    > ReadableWritableStorageTraits for TieredStorageAdapter<TLocal, TRemote>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::store::MemoryStore;
+
+    use super::*;
+
+    type TestStores = (
+        Arc<MemoryStore>,
+        Arc<MemoryStore>,
+        TieredStorageAdapter<MemoryStore, MemoryStore>,
+    );
+
+    fn tiered(
+        write_policy: TieredStorageWritePolicy,
+    ) -> Result<TestStores, Box<dyn std::error::Error>> {
+        let local = Arc::new(MemoryStore::new());
+        let remote = Arc::new(MemoryStore::new());
+        remote.set(&"a/b".try_into()?, vec![0, 1, 2, 3].into())?;
+        remote.set(&"a/c/d".try_into()?, vec![4].into())?;
+        let store = TieredStorageAdapter::new(local.clone(), remote.clone(), write_policy);
+        Ok((local, remote, store))
+    }
+
+    #[test]
+    fn tiered_read_through() -> Result<(), Box<dyn std::error::Error>> {
+        let (local, remote, store) = tiered(TieredStorageWritePolicy::WriteThrough)?;
+        assert!(local.list()?.is_empty());
+        assert_eq!(
+            store.get_partial_values_key(&"a/b".try_into()?, &[ByteRange::FromEnd(0, Some(1))])?,
+            Some(vec![vec![3].into()])
+        );
+        assert_eq!(
+            local.get(&"a/b".try_into()?)?,
+            Some(vec![0, 1, 2, 3].into())
+        );
+        assert!(store.get(&"missing".try_into()?)?.is_none());
+
+        // Served from the local tier
+        remote.erase(&"a/b".try_into()?)?;
+        assert_eq!(
+            store.get(&"a/b".try_into()?)?,
+            Some(vec![0, 1, 2, 3].into())
+        );
+        store.clear_local()?;
+        assert!(store.get(&"a/b".try_into()?)?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn tiered_write_through() -> Result<(), Box<dyn std::error::Error>> {
+        let (local, remote, store) = tiered(TieredStorageWritePolicy::WriteThrough)?;
+        store.set(&"e".try_into()?, vec![5].into())?;
+        assert_eq!(remote.get(&"e".try_into()?)?, Some(vec![5].into()));
+        assert_eq!(local.get(&"e".try_into()?)?, Some(vec![5].into()));
+        store.set_partial_values(&[StoreKeyStartValue::new("e".try_into()?, 1, &[6])])?;
+        assert_eq!(remote.get(&"e".try_into()?)?, Some(vec![5, 6].into()));
+        assert_eq!(store.get(&"e".try_into()?)?, Some(vec![5, 6].into()));
+        store.erase_prefix(&"a/".try_into()?)?;
+        assert_eq!(remote.list()?, &["e".try_into()?]);
+        assert!(!store.is_dirty());
+        Ok(())
+    }
+
+    #[test]
+    fn tiered_write_back() -> Result<(), Box<dyn std::error::Error>> {
+        let (_local, remote, store) = tiered(TieredStorageWritePolicy::WriteBack)?;
+        store.set(&"a/e".try_into()?, vec![5].into())?;
+        store.set_partial_values(&[StoreKeyStartValue::new("a/b".try_into()?, 4, &[4])])?;
+        store.erase(&"a/c/d".try_into()?)?;
+        assert!(store.is_dirty());
+        assert!(remote.get(&"a/e".try_into()?)?.is_none());
+        assert_eq!(
+            remote.get(&"a/b".try_into()?)?,
+            Some(vec![0, 1, 2, 3].into())
+        );
+
+        assert_eq!(
+            store.get(&"a/b".try_into()?)?,
+            Some(vec![0, 1, 2, 3, 4].into())
+        );
+        assert!(store.get(&"a/c/d".try_into()?)?.is_none());
+        assert_eq!(store.list()?, &["a/b".try_into()?, "a/e".try_into()?]);
+        let list_dir = store.list_dir(&"a/".try_into()?)?;
+        assert_eq!(list_dir.keys(), &["a/b".try_into()?, "a/e".try_into()?]);
+        assert!(list_dir.prefixes().is_empty());
+        assert_eq!(store.size()?, 6);
+
+        store.flush()?;
+        assert!(!store.is_dirty());
+        assert_eq!(remote.list()?, &["a/b".try_into()?, "a/e".try_into()?]);
+        assert_eq!(
+            remote.get(&"a/b".try_into()?)?,
+            Some(vec![0, 1, 2, 3, 4].into())
+        );
+
+        store.erase_prefix(&"a/".try_into()?)?;
+        store.set(&"a/f".try_into()?, vec![6].into())?;
+        assert_eq!(store.list()?, &["a/f".try_into()?]);
+        assert!(store.get(&"a/b".try_into()?)?.is_none());
+        store.flush()?;
+        assert_eq!(remote.list()?, &["a/f".try_into()?]);
+        Ok(())
+    }
+}
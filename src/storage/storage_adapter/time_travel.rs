@@ -0,0 +1,331 @@
+//! A time travel storage adapter.
+//!
+//! See [`AsyncTimeTravelStorageAdapter`].
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    byte_range::ByteRange,
+    storage::{
+        version_at, AsyncBytes, AsyncListableStorageTraits, AsyncReadableStorageTraits,
+        AsyncVersionHistoryStorageTraits, StorageError, StoreKey, StoreKeys, StoreKeysPrefixes,
+        StorePrefix, StorePrefixes, StoreValueVersion,
+    },
+};
+
+/// A read-only time travel storage adapter: reads are pinned to the versions of values at a given time.
+///
+/// The adapter reads from a store that retains the version history of values (see [`AsyncVersionHistoryStorageTraits`]), such as an S3 bucket with object versioning.
+/// Each value is read at the latest version written at or before the timestamp of the adapter, and values created after the timestamp or erased before it are absent.
+/// The version of each key is resolved once and reused for all subsequent reads, so an analysis can be rerun against a hierarchy exactly as it existed at an earlier time.
+///
+/// Versions are compared by their last modified time as reported by the store, so the precision of the timestamp is limited by the store (e.g. to seconds).
+///
+/// ### Example
+/// ```rust,ignore
+/// # use std::{sync::Arc, time::{Duration, SystemTime}};
+/// # use zarrs::array::Array;
+/// # use zarrs::storage::storage_adapter::time_travel::AsyncTimeTravelStorageAdapter;
+/// // A store implementing `AsyncVersionHistoryStorageTraits`
+/// let store = Arc::new(versioned_store);
+/// let last_week = SystemTime::now() - Duration::from_secs(7 * 24 * 60 * 60);
+/// let store_last_week = Arc::new(AsyncTimeTravelStorageAdapter::new(store, last_week));
+/// let array = Array::async_open(store_last_week, "/array").await?;
+/// ```
+#[derive(Debug)]
+pub struct AsyncTimeTravelStorageAdapter<TStorage: ?Sized> {
+    storage: Arc<TStorage>,
+    timestamp: SystemTime,
+    /// The resolved version of each key at `timestamp`, [`None`] if the value did not exist.
+    versions: Mutex<HashMap<StoreKey, Option<StoreValueVersion>>>,
+}
+
+impl<TStorage: ?Sized> AsyncTimeTravelStorageAdapter<TStorage> {
+    /// Create a new time travel storage adapter reading `storage` as of `timestamp`.
+    #[must_use]
+    pub fn new(storage: Arc<TStorage>, timestamp: SystemTime) -> Self {
+        Self {
+            storage,
+            timestamp,
+            versions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the underlying store.
+    #[must_use]
+    pub fn storage(&self) -> &Arc<TStorage> {
+        &self.storage
+    }
+
+    /// Returns the timestamp that reads are pinned to.
+    #[must_use]
+    pub const fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+}
+
+impl<TStorage: ?Sized + AsyncVersionHistoryStorageTraits> AsyncTimeTravelStorageAdapter<TStorage> {
+    /// Returns the version of the value at `key` at the timestamp of the adapter, or [`None`] if the value did not exist.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    pub async fn version(&self, key: &StoreKey) -> Result<Option<StoreValueVersion>, StorageError> {
+        if let Some(version) = self.versions.lock().get(key) {
+            return Ok(version.clone());
+        }
+        let versions = self.storage.list_versions(key).await?;
+        let version = version_at(&versions, self.timestamp).cloned();
+        Ok(self
+            .versions
+            .lock()
+            .entry(key.clone())
+            .or_insert(version)
+            .clone())
+    }
+}
+
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncVersionHistoryStorageTraits> AsyncReadableStorageTraits
+    for AsyncTimeTravelStorageAdapter<TStorage>
+{
+    async fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<AsyncBytes>>, StorageError> {
+        let Some(version) = self.version(key).await? else {
+            return Ok(None);
+        };
+        self.storage
+            .get_partial_values_key_version(key, &version, byte_ranges)
+            .await?
+            .ok_or_else(|| StorageError::Other(format!("version {version} of {key} not found")))
+            .map(Some)
+    }
+
+    async fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        let Some(version) = self.version(key).await? else {
+            return Ok(None);
+        };
+        self.storage
+            .size_key_version(key, &version)
+            .await?
+            .ok_or_else(|| StorageError::Other(format!("version {version} of {key} not found")))
+            .map(Some)
+    }
+
+    async fn get_version(&self, key: &StoreKey) -> Result<Option<StoreValueVersion>, StorageError> {
+        self.version(key).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncVersionHistoryStorageTraits> AsyncListableStorageTraits
+    for AsyncTimeTravelStorageAdapter<TStorage>
+{
+    async fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.list_prefix(&StorePrefix::root()).await
+    }
+
+    async fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        let prefix_versions = self.storage.list_prefix_versions(prefix).await?;
+        let mut versions = self.versions.lock();
+        let mut keys = StoreKeys::new();
+        for (key, key_versions) in prefix_versions {
+            let version = versions
+                .entry(key.clone())
+                .or_insert_with(|| version_at(&key_versions, self.timestamp).cloned());
+            if version.is_some() {
+                keys.push(key);
+            }
+        }
+        Ok(keys)
+    }
+
+    async fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        let mut keys = StoreKeys::new();
+        let mut prefixes = BTreeSet::new();
+        for key in self.list_prefix(prefix).await? {
+            let suffix = &key.as_str()[prefix.as_str().len()..];
+            if let Some((child, _)) = suffix.split_once('/') {
+                prefixes.insert(StorePrefix::new(format!("{}{child}/", prefix.as_str()))?);
+            } else {
+                keys.push(key);
+            }
+        }
+        Ok(StoreKeysPrefixes {
+            keys,
+            prefixes: prefixes.into_iter().collect::<StorePrefixes>(),
+        })
+    }
+
+    async fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        let mut size = 0;
+        for key in self.list_prefix(prefix).await? {
+            size += self.size_key(&key).await?.unwrap_or_default();
+        }
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, time::Duration};
+
+    use crate::storage::StoreValueVersionInfo;
+
+    use super::*;
+
+    /// A store with a fixed version history, where version `n` of each value is `[n]` written at `n` seconds since the epoch.
+    struct HistoryStore {
+        history: BTreeMap<StoreKey, Vec<StoreValueVersionInfo>>,
+    }
+
+    impl HistoryStore {
+        fn new(history: &[(&str, u8, bool)]) -> Result<Self, Box<dyn std::error::Error>> {
+            let mut versions: BTreeMap<StoreKey, Vec<StoreValueVersionInfo>> = BTreeMap::new();
+            for (key, n, erased) in history {
+                versions
+                    .entry(StoreKey::new(*key)?)
+                    .or_default()
+                    .push(StoreValueVersionInfo::new(
+                        StoreValueVersion::new(n.to_string()),
+                        SystemTime::UNIX_EPOCH + Duration::from_secs(u64::from(*n)),
+                        *erased,
+                    ));
+            }
+            Ok(Self { history: versions })
+        }
+
+        fn value(version: &StoreValueVersion) -> Option<AsyncBytes> {
+            version.as_str().parse::<u8>().ok().map(|n| vec![n].into())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AsyncReadableStorageTraits for HistoryStore {
+        async fn get_partial_values_key(
+            &self,
+            _key: &StoreKey,
+            _byte_ranges: &[ByteRange],
+        ) -> Result<Option<Vec<AsyncBytes>>, StorageError> {
+            unimplemented!("reads are versioned")
+        }
+
+        async fn size_key(&self, _key: &StoreKey) -> Result<Option<u64>, StorageError> {
+            unimplemented!("reads are versioned")
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AsyncListableStorageTraits for HistoryStore {
+        async fn list(&self) -> Result<StoreKeys, StorageError> {
+            unimplemented!("listing is versioned")
+        }
+
+        async fn list_prefix(&self, _prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+            unimplemented!("listing is versioned")
+        }
+
+        async fn list_dir(&self, _prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+            unimplemented!("listing is versioned")
+        }
+
+        async fn size_prefix(&self, _prefix: &StorePrefix) -> Result<u64, StorageError> {
+            unimplemented!("listing is versioned")
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AsyncVersionHistoryStorageTraits for HistoryStore {
+        async fn list_versions(
+            &self,
+            key: &StoreKey,
+        ) -> Result<Vec<StoreValueVersionInfo>, StorageError> {
+            Ok(self.history.get(key).cloned().unwrap_or_default())
+        }
+
+        async fn list_prefix_versions(
+            &self,
+            prefix: &StorePrefix,
+        ) -> Result<BTreeMap<StoreKey, Vec<StoreValueVersionInfo>>, StorageError> {
+            Ok(self
+                .history
+                .iter()
+                .filter(|(key, _)| key.has_prefix(prefix))
+                .map(|(key, versions)| (key.clone(), versions.clone()))
+                .collect())
+        }
+
+        async fn get_partial_values_key_version(
+            &self,
+            _key: &StoreKey,
+            version: &StoreValueVersion,
+            byte_ranges: &[ByteRange],
+        ) -> Result<Option<Vec<AsyncBytes>>, StorageError> {
+            Ok(Self::value(version).map(|value| vec![value; byte_ranges.len()]))
+        }
+
+        async fn size_key_version(
+            &self,
+            _key: &StoreKey,
+            version: &StoreValueVersion,
+        ) -> Result<Option<u64>, StorageError> {
+            Ok(Self::value(version).map(|value| value.len() as u64))
+        }
+    }
+
+    #[tokio::test]
+    async fn time_travel() -> Result<(), Box<dyn std::error::Error>> {
+        let storage = Arc::new(HistoryStore::new(&[
+            ("a/b", 1, false),
+            ("a/b", 3, false),
+            ("a/c", 2, false),
+            ("a/c", 4, true),
+            ("a/d", 5, false),
+        ])?);
+        let at = |secs| {
+            AsyncTimeTravelStorageAdapter::new(
+                storage.clone(),
+                SystemTime::UNIX_EPOCH + Duration::from_secs(secs),
+            )
+        };
+
+        let store = at(0);
+        assert!(store.list().await?.is_empty());
+        assert_eq!(store.get(&"a/b".try_into()?).await?, None);
+
+        let store = at(2);
+        assert_eq!(store.get(&"a/b".try_into()?).await?, Some(vec![1].into()));
+        assert_eq!(store.get(&"a/c".try_into()?).await?, Some(vec![2].into()));
+        assert_eq!(
+            store.get_version(&"a/b".try_into()?).await?,
+            Some(StoreValueVersion::new("1"))
+        );
+        assert_eq!(
+            store.list_dir(&"a/".try_into()?).await?.keys(),
+            &["a/b".try_into()?, "a/c".try_into()?]
+        );
+        assert_eq!(store.size().await?, 2);
+
+        // "a/c" was erased at 4
+        let store = at(4);
+        assert_eq!(store.get(&"a/b".try_into()?).await?, Some(vec![3].into()));
+        assert_eq!(store.get(&"a/c".try_into()?).await?, None);
+        assert_eq!(store.list().await?, &["a/b".try_into()?]);
+
+        let store = at(10);
+        assert_eq!(store.list().await?, &["a/b".try_into()?, "a/d".try_into()?]);
+        assert_eq!(
+            store.list_dir(&StorePrefix::root()).await?.prefixes(),
+            &["a/".try_into()?]
+        );
+        Ok(())
+    }
+}
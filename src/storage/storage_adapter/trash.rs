@@ -0,0 +1,363 @@
+//! A trash storage adapter.
+//!
+//! See [`TrashStorageAdapter`].
+
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    byte_range::ByteRange,
+    storage::{
+        Bytes, ListableStorageTraits, MaybeBytes, ReadableStorageTraits,
+        ReadableWritableStorageTraits, StorageError, StoreKey, StoreKeyRange, StoreKeyStartValue,
+        StoreKeys, StoreKeysPrefixes, StorePrefix, WritableStorageTraits,
+    },
+};
+
+/// The prefix under which erased values are moved.
+///
+/// A value erased at `key` is moved to `.trash/{timestamp}/{key}`, where `timestamp` is the zero-padded number of nanoseconds since the Unix epoch at the time of the erase.
+pub const TRASH_PREFIX: &str = ".trash/";
+
+fn trash_root() -> StorePrefix {
+    unsafe { StorePrefix::new_unchecked(TRASH_PREFIX.to_string()) }
+}
+
+fn timestamp_nanos(timestamp: SystemTime) -> Result<u64, StorageError> {
+    timestamp
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .ok()
+        .and_then(|duration| u64::try_from(duration.as_nanos()).ok())
+        .ok_or_else(|| StorageError::Other(format!("invalid trash timestamp {timestamp:?}")))
+}
+
+/// Returns the trash prefix of values erased at `timestamp`.
+fn trash_prefix(timestamp: SystemTime) -> Result<StorePrefix, StorageError> {
+    Ok(StorePrefix::new(format!(
+        "{TRASH_PREFIX}{:020}/",
+        timestamp_nanos(timestamp)?
+    ))?)
+}
+
+/// Returns the erase timestamp of a trash prefix.
+fn trash_prefix_timestamp(prefix: &StorePrefix) -> Option<SystemTime> {
+    let nanos = prefix
+        .as_str()
+        .strip_prefix(TRASH_PREFIX)?
+        .strip_suffix('/')?
+        .parse::<u64>()
+        .ok()?;
+    Some(SystemTime::UNIX_EPOCH + Duration::from_nanos(nanos))
+}
+
+fn is_trash_key(key: &StoreKey) -> bool {
+    key.as_str().starts_with(TRASH_PREFIX)
+}
+
+fn check_key(key: &StoreKey) -> Result<(), StorageError> {
+    if is_trash_key(key) {
+        Err(StorageError::Other(format!(
+            "{key} is reserved by the trash storage adapter"
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn filter_keys(keys: StoreKeys) -> StoreKeys {
+    keys.into_iter().filter(|key| !is_trash_key(key)).collect()
+}
+
+/// A trash storage adapter: erased values are moved to a trash prefix instead of being deleted.
+///
+/// Erasing a key, multiple keys, or a prefix through the adapter (e.g. [`Array::erase_chunks`](crate::array::Array::erase_chunks) or [`erase_node`](crate::storage::erase_node)) moves the values under [`TRASH_PREFIX`] with the timestamp of the erase.
+/// Accidentally erased values can be recovered with [`restore`](TrashStorageAdapter::restore), and the trash is emptied with [`purge`](TrashStorageAdapter::purge) or [`purge_all`](TrashStorageAdapter::purge_all).
+///
+/// The trash is hidden from listing through the adapter, and keys under [`TRASH_PREFIX`] cannot be written or erased through the adapter.
+/// Moving a value reads and rewrites it, so erasing through the adapter is considerably slower than deleting values directly.
+///
+/// ### Example
+/// ```rust
+/// # use std::sync::Arc;
+/// # use zarrs::storage::{store::MemoryStore, ReadableStorageTraits, WritableStorageTraits};
+/// # use zarrs::storage::storage_adapter::trash::TrashStorageAdapter;
+/// let store = TrashStorageAdapter::new(Arc::new(MemoryStore::new()));
+/// store.set(&"array/c/0".try_into()?, vec![0].into())?;
+/// store.erase(&"array/c/0".try_into()?)?;
+/// assert_eq!(store.get(&"array/c/0".try_into()?)?, None);
+///
+/// let timestamps = store.trash_timestamps()?;
+/// store.restore(timestamps[0])?;
+/// assert_eq!(store.get(&"array/c/0".try_into()?)?, Some(vec![0].into()));
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct TrashStorageAdapter<TStorage: ?Sized> {
+    storage: Arc<TStorage>,
+}
+
+impl<TStorage: ?Sized> TrashStorageAdapter<TStorage> {
+    /// Create a new trash storage adapter.
+    #[must_use]
+    pub fn new(storage: Arc<TStorage>) -> Self {
+        Self { storage }
+    }
+
+    /// Returns the underlying store.
+    #[must_use]
+    pub fn storage(&self) -> &Arc<TStorage> {
+        &self.storage
+    }
+}
+
+impl<TStorage: ?Sized + ListableStorageTraits> TrashStorageAdapter<TStorage> {
+    /// Returns the timestamps of the erases with values in the trash, oldest first.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying store error.
+    pub fn trash_timestamps(&self) -> Result<Vec<SystemTime>, StorageError> {
+        let mut timestamps: Vec<SystemTime> = self
+            .storage
+            .list_dir(&trash_root())?
+            .prefixes
+            .iter()
+            .filter_map(trash_prefix_timestamp)
+            .collect();
+        timestamps.sort_unstable();
+        Ok(timestamps)
+    }
+
+    /// Returns the keys of the values erased at `timestamp`.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying store error.
+    pub fn trashed_keys(&self, timestamp: SystemTime) -> Result<StoreKeys, StorageError> {
+        let prefix = trash_prefix(timestamp)?;
+        self.storage
+            .list_prefix(&prefix)?
+            .iter()
+            .map(|key| Ok(StoreKey::new(&key.as_str()[prefix.as_str().len()..])?))
+            .collect()
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits + ListableStorageTraits>
+    TrashStorageAdapter<TStorage>
+{
+    /// Move the values at `keys` to the trash prefix of `timestamp`.
+    fn move_to_trash(&self, keys: &[StoreKey], timestamp: SystemTime) -> Result<(), StorageError> {
+        let prefix = trash_prefix(timestamp)?;
+        for key in keys {
+            if let Some(value) = self.storage.get(key)? {
+                let key_trash = StoreKey::new(format!("{}{}", prefix.as_str(), key.as_str()))?;
+                self.storage.set(&key_trash, value)?;
+                self.storage.erase(key)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Restore the values erased at `timestamp` and remove them from the trash.
+    ///
+    /// Restored values overwrite values written after the erase.
+    /// Returns the restored keys.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying store error.
+    pub fn restore(&self, timestamp: SystemTime) -> Result<StoreKeys, StorageError> {
+        let prefix = trash_prefix(timestamp)?;
+        let keys = self.trashed_keys(timestamp)?;
+        for key in &keys {
+            let key_trash = StoreKey::new(format!("{}{}", prefix.as_str(), key.as_str()))?;
+            if let Some(value) = self.storage.get(&key_trash)? {
+                self.storage.set(key, value)?;
+            }
+        }
+        self.storage.erase_prefix(&prefix)?;
+        Ok(keys)
+    }
+
+    /// Permanently delete the values erased before `erased_before` from the trash.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying store error.
+    pub fn purge(&self, erased_before: SystemTime) -> Result<(), StorageError> {
+        for timestamp in self.trash_timestamps()? {
+            if timestamp < erased_before {
+                self.storage.erase_prefix(&trash_prefix(timestamp)?)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Permanently delete all values from the trash.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying store error.
+    pub fn purge_all(&self) -> Result<(), StorageError> {
+        self.storage.erase_prefix(&trash_root())
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
+    for TrashStorageAdapter<TStorage>
+{
+    fn get(&self, key: &StoreKey) -> Result<MaybeBytes, StorageError> {
+        self.storage.get(key)
+    }
+
+    fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<Bytes>>, StorageError> {
+        self.storage.get_partial_values_key(key, byte_ranges)
+    }
+
+    fn get_partial_values(
+        &self,
+        key_ranges: &[StoreKeyRange],
+    ) -> Result<Vec<MaybeBytes>, StorageError> {
+        self.storage.get_partial_values(key_ranges)
+    }
+
+    fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        self.storage.size_key(key)
+    }
+
+    fn get_values_coalesced(
+        &self,
+        keys: &[StoreKey],
+        max_waste_ratio: f64,
+    ) -> Result<Vec<MaybeBytes>, StorageError> {
+        self.storage.get_values_coalesced(keys, max_waste_ratio)
+    }
+}
+
+impl<TStorage: ?Sized + ListableStorageTraits> ListableStorageTraits
+    for TrashStorageAdapter<TStorage>
+{
+    fn list(&self) -> Result<StoreKeys, StorageError> {
+        Ok(filter_keys(self.storage.list()?))
+    }
+
+    fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        Ok(filter_keys(self.storage.list_prefix(prefix)?))
+    }
+
+    fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        let keys_prefixes = self.storage.list_dir(prefix)?;
+        Ok(StoreKeysPrefixes {
+            keys: keys_prefixes.keys,
+            prefixes: keys_prefixes
+                .prefixes
+                .into_iter()
+                .filter(|prefix| prefix.as_str() != TRASH_PREFIX)
+                .collect(),
+        })
+    }
+
+    fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        let size = self.storage.size_prefix(prefix)?;
+        if TRASH_PREFIX.starts_with(prefix.as_str()) {
+            Ok(size - self.storage.size_prefix(&trash_root())?)
+        } else if prefix.as_str().starts_with(TRASH_PREFIX) {
+            Ok(0)
+        } else {
+            Ok(size)
+        }
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits + ListableStorageTraits> WritableStorageTraits
+    for TrashStorageAdapter<TStorage>
+{
+    fn set(&self, key: &StoreKey, value: Bytes) -> Result<(), StorageError> {
+        check_key(key)?;
+        self.storage.set(key, value)
+    }
+
+    fn set_partial_values(
+        &self,
+        key_start_values: &[StoreKeyStartValue],
+    ) -> Result<(), StorageError> {
+        for key_start_value in key_start_values {
+            check_key(&key_start_value.key)?;
+        }
+        self.storage.set_partial_values(key_start_values)
+    }
+
+    fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+        self.erase_values(std::slice::from_ref(key))
+    }
+
+    fn erase_values(&self, keys: &[StoreKey]) -> Result<(), StorageError> {
+        for key in keys {
+            check_key(key)?;
+        }
+        self.move_to_trash(keys, SystemTime::now())
+    }
+
+    fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+        let keys = filter_keys(self.storage.list_prefix(prefix)?);
+        self.move_to_trash(&keys, SystemTime::now())
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits + ListableStorageTraits>
+    ReadableWritableStorageTraits for TrashStorageAdapter<TStorage>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::store::MemoryStore;
+
+    use super::*;
+
+    #[test]
+    fn trash() -> Result<(), Box<dyn std::error::Error>> {
+        let storage = Arc::new(MemoryStore::new());
+        let store = TrashStorageAdapter::new(storage.clone());
+        store.set(&"a/b".try_into()?, vec![0, 1].into())?;
+        store.set(&"a/c".try_into()?, vec![2].into())?;
+        store.set(&"d".try_into()?, vec![3].into())?;
+
+        store.erase_prefix(&"a/".try_into()?)?;
+        assert_eq!(store.list()?, &["d".try_into()?]);
+        assert_eq!(
+            store.list_dir(&StorePrefix::root())?.prefixes(),
+            &Vec::<StorePrefix>::new()
+        );
+        assert_eq!(store.size()?, 1);
+        assert_eq!(storage.size()?, 4);
+        assert!(store.set(&".trash/x".try_into()?, vec![0].into()).is_err());
+
+        let timestamps = store.trash_timestamps()?;
+        assert_eq!(timestamps.len(), 1);
+        assert_eq!(
+            store.trashed_keys(timestamps[0])?,
+            &["a/b".try_into()?, "a/c".try_into()?]
+        );
+
+        std::thread::sleep(Duration::from_millis(1));
+        store.erase(&"d".try_into()?)?;
+        assert!(store.list()?.is_empty());
+        assert_eq!(store.trash_timestamps()?.len(), 2);
+
+        // Restore the erase of the prefix, and purge the erase of "d"
+        assert_eq!(store.restore(timestamps[0])?.len(), 2);
+        assert_eq!(store.get(&"a/b".try_into()?)?, Some(vec![0, 1].into()));
+        assert_eq!(store.list()?, &["a/b".try_into()?, "a/c".try_into()?]);
+        store.purge(SystemTime::now())?;
+        assert!(store.trash_timestamps()?.is_empty());
+        assert_eq!(storage.size()?, 3);
+
+        store.erase(&"a/b".try_into()?)?;
+        store.purge_all()?;
+        assert_eq!(storage.list()?, &["a/c".try_into()?]);
+        Ok(())
+    }
+}
@@ -0,0 +1,578 @@
+//! A versioned storage adapter.
+//!
+//! See [`VersionedStorageAdapter`].
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    hash::{BuildHasher, Hasher},
+    sync::{Arc, Mutex},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    byte_range::ByteRange,
+    storage::{
+        store_set_partial_values, Bytes, ListableStorageTraits, ReadableStorageTraits,
+        ReadableWritableStorageTraits, StorageError, StoreKey, StoreKeyStartValue, StoreKeys,
+        StoreKeysPrefixes, StorePrefix, StorePrefixes, WritableStorageTraits,
+    },
+};
+
+/// A reference to the head snapshot of a branch, stored at `refs/{branch}.json`.
+#[derive(Debug, Serialize, Deserialize)]
+struct VersionedBranchRef {
+    snapshot: String,
+}
+
+/// A snapshot, stored at `snapshots/{id}.json`.
+#[derive(Debug, Serialize, Deserialize)]
+struct VersionedSnapshot {
+    id: String,
+    parent: Option<String>,
+    message: String,
+    /// Maps the keys of the snapshot to the keys of their values in the underlying store.
+    manifest: BTreeMap<String, String>,
+}
+
+/// Information about a snapshot of a [`VersionedStorageAdapter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionedSnapshotInfo {
+    id: String,
+    parent: Option<String>,
+    message: String,
+}
+
+impl VersionedSnapshotInfo {
+    /// Returns the snapshot ID.
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the ID of the parent snapshot, or [`None`] if this is the first snapshot of a branch.
+    #[must_use]
+    pub fn parent(&self) -> Option<&str> {
+        self.parent.as_deref()
+    }
+
+    /// Returns the commit message.
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// The working state of a [`VersionedStorageAdapter`].
+#[derive(Debug, Default)]
+struct VersionedState {
+    /// The snapshot the manifest is based on.
+    snapshot: Option<String>,
+    /// Maps keys to the keys of their values in the underlying store.
+    manifest: BTreeMap<StoreKey, StoreKey>,
+    /// True if the manifest has changed since `snapshot`.
+    changed: bool,
+}
+
+/// Returns a new random identifier for a snapshot or value object.
+fn unique_id() -> String {
+    format!(
+        "{:016x}",
+        std::collections::hash_map::RandomState::new()
+            .build_hasher()
+            .finish()
+    )
+}
+
+fn branch_ref_key(branch: &str) -> Result<StoreKey, StorageError> {
+    if branch.is_empty() || branch.contains('/') {
+        return Err(StorageError::Other(format!(
+            "invalid branch name {branch:?}"
+        )));
+    }
+    Ok(StoreKey::new(format!("refs/{branch}.json"))?)
+}
+
+fn snapshot_key(id: &str) -> Result<StoreKey, StorageError> {
+    Ok(StoreKey::new(format!("snapshots/{id}.json"))?)
+}
+
+fn read_json<TStorage: ?Sized + ReadableStorageTraits, T: DeserializeOwned>(
+    storage: &TStorage,
+    key: &StoreKey,
+) -> Result<Option<T>, StorageError> {
+    storage
+        .get(key)?
+        .map(|value| {
+            serde_json::from_slice(&value)
+                .map_err(|err| StorageError::InvalidMetadata(key.clone(), err.to_string()))
+        })
+        .transpose()
+}
+
+fn write_json<TStorage: ?Sized + WritableStorageTraits, T: Serialize>(
+    storage: &TStorage,
+    key: &StoreKey,
+    value: &T,
+) -> Result<(), StorageError> {
+    let value = serde_json::to_vec_pretty(value)
+        .map_err(|err| StorageError::InvalidMetadata(key.clone(), err.to_string()))?;
+    storage.set(key, value.into())
+}
+
+fn read_branch_ref<TStorage: ?Sized + ReadableStorageTraits>(
+    storage: &TStorage,
+    branch: &str,
+) -> Result<Option<String>, StorageError> {
+    Ok(
+        read_json::<_, VersionedBranchRef>(storage, &branch_ref_key(branch)?)?
+            .map(|branch_ref| branch_ref.snapshot),
+    )
+}
+
+fn read_snapshot<TStorage: ?Sized + ReadableStorageTraits>(
+    storage: &TStorage,
+    id: &str,
+) -> Result<VersionedSnapshot, StorageError> {
+    read_json(storage, &snapshot_key(id)?)?
+        .ok_or_else(|| StorageError::Other(format!("snapshot {id} not found")))
+}
+
+fn read_state<TStorage: ?Sized + ReadableStorageTraits>(
+    storage: &TStorage,
+    snapshot: Option<String>,
+) -> Result<VersionedState, StorageError> {
+    let manifest = if let Some(snapshot) = &snapshot {
+        read_snapshot(storage, snapshot)?
+            .manifest
+            .into_iter()
+            .map(|(key, object)| Ok((StoreKey::new(key)?, StoreKey::new(object)?)))
+            .collect::<Result<_, StorageError>>()?
+    } else {
+        BTreeMap::default()
+    };
+    Ok(VersionedState {
+        snapshot,
+        manifest,
+        changed: false,
+    })
+}
+
+/// A versioned storage adapter: a manifest-based store with snapshots and time-travel reads.
+///
+/// Keys do not map directly to keys of the underlying store.
+/// Instead, every value is written once to an immutable object, and keys resolve to objects through a manifest.
+/// [`commit`](VersionedStorageAdapter::commit) persists the manifest as a snapshot and advances a branch to it.
+/// Earlier snapshots remain readable with [`at_snapshot`](VersionedStorageAdapter::at_snapshot), which enables reading previous versions of a hierarchy.
+///
+/// The underlying store has the following layout:
+///  - `refs/{branch}.json`: the ID of the head snapshot of a branch,
+///  - `snapshots/{id}.json`: the parent, message, and manifest of a snapshot, and
+///  - `objects/{id}`: the values.
+///
+/// The layout is inspired by [Icechunk](https://icechunk.io/), but it is not compatible with it.
+///
+/// Writes are only visible to this adapter until they are committed, and uncommitted writes are lost if the adapter is dropped.
+/// Objects of uncommitted or unreachable values are not garbage collected.
+///
+/// ### Example
+/// ```rust
+/// # use std::sync::Arc;
+/// # use zarrs::storage::{store::MemoryStore, ReadableStorageTraits, WritableStorageTraits};
+/// # use zarrs::storage::storage_adapter::versioned::VersionedStorageAdapter;
+/// let storage = Arc::new(MemoryStore::new());
+/// let store = VersionedStorageAdapter::new(storage.clone(), "main")?;
+/// store.set(&"zarr.json".try_into()?, vec![0].into())?;
+/// let v1 = store.commit("first")?;
+/// store.set(&"zarr.json".try_into()?, vec![1].into())?;
+/// store.commit("second")?;
+///
+/// let store_v1 = VersionedStorageAdapter::at_snapshot(storage, &v1)?;
+/// assert_eq!(store_v1.get(&"zarr.json".try_into()?)?, Some(vec![0].into()));
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct VersionedStorageAdapter<TStorage: ?Sized> {
+    storage: Arc<TStorage>,
+    branch: Option<String>,
+    state: Mutex<VersionedState>,
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits> VersionedStorageAdapter<TStorage> {
+    /// Create a new versioned storage adapter at the head of `branch` in `storage`.
+    ///
+    /// If `branch` does not exist, the adapter starts from an empty manifest and the first [`commit`](VersionedStorageAdapter::commit) creates it.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if the branch name is invalid, its head snapshot cannot be read, or there is an underlying store error.
+    pub fn new(storage: Arc<TStorage>, branch: &str) -> Result<Self, StorageError> {
+        let snapshot = read_branch_ref(&*storage, branch)?;
+        let state = read_state(&*storage, snapshot)?;
+        Ok(Self {
+            storage,
+            branch: Some(branch.to_string()),
+            state: Mutex::new(state),
+        })
+    }
+
+    /// Create a new read-only versioned storage adapter at the snapshot with `id` in `storage`.
+    ///
+    /// Write operations return [`StorageError::ReadOnly`].
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if the snapshot cannot be read or there is an underlying store error.
+    pub fn at_snapshot(storage: Arc<TStorage>, id: &str) -> Result<Self, StorageError> {
+        let state = read_state(&*storage, Some(id.to_string()))?;
+        Ok(Self {
+            storage,
+            branch: None,
+            state: Mutex::new(state),
+        })
+    }
+
+    /// Returns the history of the snapshot the adapter is based on, starting with that snapshot.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if a snapshot cannot be read or there is an underlying store error.
+    ///
+    /// # Panics
+    /// Panics if the state mutex is poisoned.
+    pub fn history(&self) -> Result<Vec<VersionedSnapshotInfo>, StorageError> {
+        let mut history = vec![];
+        let mut next = self.snapshot_id();
+        while let Some(id) = next {
+            let snapshot = read_snapshot(&*self.storage, &id)?;
+            history.push(VersionedSnapshotInfo {
+                id: snapshot.id,
+                parent: snapshot.parent.clone(),
+                message: snapshot.message,
+            });
+            next = snapshot.parent;
+        }
+        Ok(history)
+    }
+}
+
+impl<TStorage: ?Sized> VersionedStorageAdapter<TStorage> {
+    /// Returns the underlying store.
+    #[must_use]
+    pub fn storage(&self) -> &Arc<TStorage> {
+        &self.storage
+    }
+
+    /// Returns the branch, or [`None`] if the adapter is a read-only view of a snapshot.
+    #[must_use]
+    pub fn branch(&self) -> Option<&str> {
+        self.branch.as_deref()
+    }
+
+    /// Returns the ID of the snapshot the adapter is based on, or [`None`] if the branch has no snapshots.
+    ///
+    /// # Panics
+    /// Panics if the state mutex is poisoned.
+    #[must_use]
+    pub fn snapshot_id(&self) -> Option<String> {
+        self.state.lock().unwrap().snapshot.clone()
+    }
+
+    /// Returns true if there are changes which have not been committed.
+    ///
+    /// # Panics
+    /// Panics if the state mutex is poisoned.
+    #[must_use]
+    pub fn has_changes(&self) -> bool {
+        self.state.lock().unwrap().changed
+    }
+
+    fn check_writable(&self) -> Result<(), StorageError> {
+        if self.branch.is_some() {
+            Ok(())
+        } else {
+            Err(StorageError::ReadOnly)
+        }
+    }
+
+    fn object(&self, key: &StoreKey) -> Option<StoreKey> {
+        self.state.lock().unwrap().manifest.get(key).cloned()
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits> VersionedStorageAdapter<TStorage> {
+    /// Commit changes as a new snapshot with `message` and advance the branch to it.
+    ///
+    /// Returns the ID of the new snapshot.
+    /// Commits are permitted without changes.
+    ///
+    /// # Errors
+    /// Returns [`StorageError::ReadOnly`] if the adapter is a read-only view of a snapshot.
+    /// Returns a [`StorageError`] if the branch has been advanced by another writer since the adapter was created or last committed, or there is an underlying store error.
+    ///
+    /// Conflict detection is not atomic: concurrent commits to the same branch from multiple writers must be coordinated externally.
+    ///
+    /// # Panics
+    /// Panics if the state mutex is poisoned.
+    pub fn commit(&self, message: &str) -> Result<String, StorageError> {
+        let Some(branch) = &self.branch else {
+            return Err(StorageError::ReadOnly);
+        };
+        let mut state = self.state.lock().unwrap();
+        let head = read_branch_ref(&*self.storage, branch)?;
+        if head != state.snapshot {
+            return Err(StorageError::Other(format!(
+                "branch {branch} has been advanced to snapshot {} by another writer",
+                head.unwrap_or_default()
+            )));
+        }
+
+        let id = unique_id();
+        let snapshot = VersionedSnapshot {
+            id: id.clone(),
+            parent: state.snapshot.clone(),
+            message: message.to_string(),
+            manifest: state
+                .manifest
+                .iter()
+                .map(|(key, object)| (key.as_str().to_string(), object.as_str().to_string()))
+                .collect(),
+        };
+        write_json(&*self.storage, &snapshot_key(&id)?, &snapshot)?;
+        write_json(
+            &*self.storage,
+            &branch_ref_key(branch)?,
+            &VersionedBranchRef {
+                snapshot: id.clone(),
+            },
+        )?;
+        state.snapshot = Some(id.clone());
+        state.changed = false;
+        Ok(id)
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
+    for VersionedStorageAdapter<TStorage>
+{
+    fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<Bytes>>, StorageError> {
+        let Some(object) = self.object(key) else {
+            return Ok(None);
+        };
+        self.storage
+            .get_partial_values_key(&object, byte_ranges)?
+            .ok_or_else(|| StorageError::Other(format!("object {object} of {key} not found")))
+            .map(Some)
+    }
+
+    fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        let Some(object) = self.object(key) else {
+            return Ok(None);
+        };
+        self.storage
+            .size_key(&object)?
+            .ok_or_else(|| StorageError::Other(format!("object {object} of {key} not found")))
+            .map(Some)
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits> ListableStorageTraits
+    for VersionedStorageAdapter<TStorage>
+{
+    fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.list_prefix(&StorePrefix::root())
+    }
+
+    fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .manifest
+            .keys()
+            .filter(|key| key.has_prefix(prefix))
+            .cloned()
+            .collect())
+    }
+
+    fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        let mut keys = StoreKeys::new();
+        let mut prefixes = BTreeSet::new();
+        for key in self.list_prefix(prefix)? {
+            let suffix = &key.as_str()[prefix.as_str().len()..];
+            if let Some((child, _)) = suffix.split_once('/') {
+                prefixes.insert(StorePrefix::new(format!("{}{child}/", prefix.as_str()))?);
+            } else {
+                keys.push(key);
+            }
+        }
+        Ok(StoreKeysPrefixes {
+            keys,
+            prefixes: prefixes.into_iter().collect::<StorePrefixes>(),
+        })
+    }
+
+    fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        let mut size = 0;
+        for key in self.list_prefix(prefix)? {
+            size += self.size_key(&key)?.unwrap_or_default();
+        }
+        Ok(size)
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits> WritableStorageTraits
+    for VersionedStorageAdapter<TStorage>
+{
+    fn set(&self, key: &StoreKey, value: Bytes) -> Result<(), StorageError> {
+        self.check_writable()?;
+        let object = StoreKey::new(format!("objects/{}", unique_id()))?;
+        self.storage.set(&object, value)?;
+        let mut state = self.state.lock().unwrap();
+        state.manifest.insert(key.clone(), object);
+        state.changed = true;
+        Ok(())
+    }
+
+    fn set_partial_values(
+        &self,
+        key_start_values: &[StoreKeyStartValue],
+    ) -> Result<(), StorageError> {
+        self.check_writable()?;
+        // Objects are immutable, so updated values are written to new objects
+        store_set_partial_values(self, key_start_values)
+    }
+
+    fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+        self.check_writable()?;
+        let mut state = self.state.lock().unwrap();
+        if state.manifest.remove(key).is_some() {
+            state.changed = true;
+        }
+        Ok(())
+    }
+
+    fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+        self.check_writable()?;
+        let mut state = self.state.lock().unwrap();
+        let len = state.manifest.len();
+        state.manifest.retain(|key, _| !key.has_prefix(prefix));
+        if state.manifest.len() != len {
+            state.changed = true;
+        }
+        Ok(())
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits> ReadableWritableStorageTraits
+    for VersionedStorageAdapter<TStorage>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::store::MemoryStore;
+
+    use super::*;
+
+    #[test]
+    fn versioned_time_travel() -> Result<(), Box<dyn std::error::Error>> {
+        let storage = Arc::new(MemoryStore::new());
+        let store = VersionedStorageAdapter::new(storage.clone(), "main")?;
+        assert!(store.snapshot_id().is_none());
+        assert!(store.history()?.is_empty());
+
+        store.set(&"a/b".try_into()?, vec![0, 1].into())?;
+        store.set(&"a/c".try_into()?, vec![2].into())?;
+        assert!(store.has_changes());
+        let v1 = store.commit("v1")?;
+        assert!(!store.has_changes());
+
+        store.set_partial_values(&[StoreKeyStartValue::new("a/b".try_into()?, 2, &[3])])?;
+        store.erase(&"a/c".try_into()?)?;
+        let v2 = store.commit("v2")?;
+
+        // The head of the branch
+        let store_main = VersionedStorageAdapter::new(storage.clone(), "main")?;
+        assert_eq!(store_main.snapshot_id(), Some(v2.clone()));
+        assert_eq!(
+            store_main.get(&"a/b".try_into()?)?,
+            Some(vec![0, 1, 3].into())
+        );
+        assert_eq!(store_main.list()?, &["a/b".try_into()?]);
+
+        // Time travel
+        let store_v1 = VersionedStorageAdapter::at_snapshot(storage.clone(), &v1)?;
+        assert_eq!(store_v1.get(&"a/b".try_into()?)?, Some(vec![0, 1].into()));
+        assert_eq!(store_v1.get(&"a/c".try_into()?)?, Some(vec![2].into()));
+        assert_eq!(
+            store_v1.list_dir(&"a/".try_into()?)?.keys(),
+            &["a/b".try_into()?, "a/c".try_into()?]
+        );
+        assert_eq!(store_v1.size()?, 3);
+
+        let history = store_main.history()?;
+        assert_eq!(
+            history
+                .iter()
+                .map(VersionedSnapshotInfo::message)
+                .collect::<Vec<_>>(),
+            vec!["v2", "v1"]
+        );
+        assert_eq!(history[0].id(), v2);
+        assert_eq!(history[0].parent(), Some(v1.as_str()));
+        assert_eq!(history[1].parent(), None);
+
+        // Other branches are independent
+        let store_dev = VersionedStorageAdapter::new(storage, "dev")?;
+        assert!(store_dev.list()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn versioned_read_only_snapshot() -> Result<(), Box<dyn std::error::Error>> {
+        let storage = Arc::new(MemoryStore::new());
+        let store = VersionedStorageAdapter::new(storage.clone(), "main")?;
+        store.set(&"a".try_into()?, vec![0].into())?;
+        let v1 = store.commit("v1")?;
+
+        let store_v1 = VersionedStorageAdapter::at_snapshot(storage.clone(), &v1)?;
+        assert!(store_v1.branch().is_none());
+        assert!(matches!(
+            store_v1.set(&"a".try_into()?, vec![1].into()),
+            Err(StorageError::ReadOnly)
+        ));
+        assert!(matches!(
+            store_v1.erase(&"a".try_into()?),
+            Err(StorageError::ReadOnly)
+        ));
+        assert!(matches!(store_v1.commit("v2"), Err(StorageError::ReadOnly)));
+        assert!(VersionedStorageAdapter::at_snapshot(storage.clone(), "missing").is_err());
+        assert!(VersionedStorageAdapter::new(storage, "a/b").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn versioned_conflict() -> Result<(), Box<dyn std::error::Error>> {
+        let storage = Arc::new(MemoryStore::new());
+        let store_a = VersionedStorageAdapter::new(storage.clone(), "main")?;
+        let store_b = VersionedStorageAdapter::new(storage.clone(), "main")?;
+        store_a.set(&"a".try_into()?, vec![0].into())?;
+        store_b.set(&"b".try_into()?, vec![1].into())?;
+        let v1 = store_a.commit("a")?;
+        assert!(store_b.commit("b").is_err());
+        assert_eq!(
+            VersionedStorageAdapter::new(storage, "main")?.snapshot_id(),
+            Some(v1)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn versioned_store_test_suite() -> Result<(), Box<dyn std::error::Error>> {
+        let store = VersionedStorageAdapter::new(Arc::new(MemoryStore::new()), "main")?;
+        crate::storage::store_test_suite::all(&store, &"store_test_suite/".try_into()?)?;
+        Ok(())
+    }
+}
@@ -1,34 +1,209 @@
 //! A zip storage adapter.
+//!
+//! The zip file can be in any readable store supporting byte range requests, such as a HTTP server or an object store.
+//! The central directory is read lazily on first access (including zip64 archives), and values that are stored without compression are read with byte range requests.
 
 use crate::{
     array::codec::extract_byte_ranges_read,
-    byte_range::ByteRange,
+    byte_range::{ByteRange, InvalidByteRangeError},
     storage::{
-        storage_value_io::StorageValueIO, Bytes, ListableStorageTraits, ReadableStorageTraits,
-        StorageError, StoreKey, StoreKeys, StoreKeysPrefixes, StorePrefix, StorePrefixes,
+        Bytes, ListableStorageTraits, ReadableStorageTraits, StorageError, StoreKey, StoreKeys,
+        StoreKeysPrefixes, StorePrefix, StorePrefixes,
     },
 };
 
 use itertools::Itertools;
 use parking_lot::Mutex;
 use thiserror::Error;
-use zip::{result::ZipError, ZipArchive};
 
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+};
+
+/// The number of bytes at the end of a zip file that are retrieved when the central directory is read.
+///
+/// This holds the end of central directory record and usually the entire central directory.
+const ZIP_TAIL_SIZE: u64 = 64 * 1024;
+
+const ZIP_EOCD_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x05, 0x06];
+const ZIP_EOCD_SIZE: usize = 22;
+const ZIP64_EOCD_LOCATOR_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x06, 0x07];
+const ZIP64_EOCD_LOCATOR_SIZE: usize = 20;
+const ZIP64_EOCD_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x06, 0x06];
+const ZIP64_EOCD_SIZE: usize = 56;
+const ZIP_CENTRAL_HEADER_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x01, 0x02];
+const ZIP_CENTRAL_HEADER_SIZE: usize = 46;
+const ZIP_LOCAL_HEADER_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x03, 0x04];
+const ZIP_LOCAL_HEADER_SIZE: usize = 30;
+const ZIP64_EXTRA_FIELD_ID: u16 = 0x0001;
+
+const ZIP_FLAG_ENCRYPTED: u16 = 1 << 0;
+const ZIP_FLAG_DATA_DESCRIPTOR: u16 = 1 << 3;
+const ZIP_METHOD_STORED: u16 = 0;
+
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        bytes.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(
+        bytes.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+/// The location of the central directory of a zip file.
+enum ZipCentralDirectoryLocation {
+    /// The central directory offset and size.
+    Found(u64, u64),
+    /// The offset of a zip64 end of central directory record that must be retrieved to locate the central directory.
+    Zip64Record(u64),
+}
+
+/// Locate the central directory of a zip file from its `tail` at `tail_offset`.
+///
+/// Returns [`None`] if the end of central directory record is not in `tail` or is invalid.
+fn zip_central_directory_location(
+    tail: &[u8],
+    tail_offset: u64,
+) -> Option<ZipCentralDirectoryLocation> {
+    let eocd = (0..=tail.len().checked_sub(ZIP_EOCD_SIZE)?)
+        .rev()
+        .find(|&i| tail[i..i + 4] == ZIP_EOCD_SIGNATURE)?;
+    let entries = read_u16(tail, eocd + 10)?;
+    let cd_size = read_u32(tail, eocd + 12)?;
+    let cd_offset = read_u32(tail, eocd + 16)?;
+    let location = ZipCentralDirectoryLocation::Found(u64::from(cd_offset), u64::from(cd_size));
+    if entries != u16::MAX && cd_size != u32::MAX && cd_offset != u32::MAX {
+        return Some(location);
+    }
+
+    // zip64
+    let Some(locator) = eocd.checked_sub(ZIP64_EOCD_LOCATOR_SIZE) else {
+        return Some(location);
+    };
+    if tail[locator..locator + 4] != ZIP64_EOCD_LOCATOR_SIGNATURE {
+        return Some(location);
+    }
+    let record_offset = read_u64(tail, locator + 8)?;
+    match record_offset
+        .checked_sub(tail_offset)
+        .and_then(|record| usize::try_from(record).ok())
+    {
+        Some(record) => zip64_central_directory_location(tail.get(record..)?),
+        None => Some(ZipCentralDirectoryLocation::Zip64Record(record_offset)),
+    }
+}
+
+/// Locate the central directory of a zip file from its zip64 end of central directory `record`.
+fn zip64_central_directory_location(record: &[u8]) -> Option<ZipCentralDirectoryLocation> {
+    if record.len() < ZIP64_EOCD_SIZE || record[..4] != ZIP64_EOCD_SIGNATURE {
+        return None;
+    }
+    Some(ZipCentralDirectoryLocation::Found(
+        read_u64(record, 48)?,
+        read_u64(record, 40)?,
+    ))
+}
+
+/// A file in the central directory of a zip file.
+struct ZipEntry {
+    flags: u16,
+    compression_method: u16,
+    crc32: u32,
+    compressed_size: u64,
+    size: u64,
+    header_offset: u64,
+    /// The offset of the file data, which is known after its local file header is read.
+    data_start: OnceLock<u64>,
+}
+
+/// Parse the entries of a zip `central_directory`, keyed by file name.
+///
+/// Returns [`None`] if the central directory is invalid.
+fn parse_zip_central_directory(central_directory: &[u8]) -> Option<BTreeMap<String, ZipEntry>> {
+    let mut entries = BTreeMap::new();
+    let mut pos = 0;
+    while central_directory.get(pos..pos + 4) == Some(&ZIP_CENTRAL_HEADER_SIGNATURE) {
+        let header = central_directory.get(pos..pos + ZIP_CENTRAL_HEADER_SIZE)?;
+        let compressed_size = read_u32(header, 20)?;
+        let size = read_u32(header, 24)?;
+        let name_length = usize::from(read_u16(header, 28)?);
+        let extra_length = usize::from(read_u16(header, 30)?);
+        let comment_length = usize::from(read_u16(header, 32)?);
+        let header_offset = read_u32(header, 42)?;
+        let name_start = pos + ZIP_CENTRAL_HEADER_SIZE;
+        let extra_start = name_start + name_length;
+        let name = central_directory.get(name_start..extra_start)?;
+        let extra = central_directory.get(extra_start..extra_start + extra_length)?;
+
+        let mut entry = ZipEntry {
+            flags: read_u16(header, 8)?,
+            compression_method: read_u16(header, 10)?,
+            crc32: read_u32(header, 16)?,
+            compressed_size: u64::from(compressed_size),
+            size: u64::from(size),
+            header_offset: u64::from(header_offset),
+            data_start: OnceLock::new(),
+        };
+
+        // The zip64 extended information extra field holds (in order) the sizes and offset that do not fit in the header
+        let mut extra_pos = 0;
+        while let (Some(id), Some(length)) =
+            (read_u16(extra, extra_pos), read_u16(extra, extra_pos + 2))
+        {
+            let field_start = extra_pos + 4;
+            let field_end = field_start + usize::from(length);
+            if id == ZIP64_EXTRA_FIELD_ID {
+                let mut values = extra
+                    .get(field_start..field_end)?
+                    .chunks_exact(8)
+                    .map(|value| u64::from_le_bytes(value.try_into().unwrap()));
+                if size == u32::MAX {
+                    entry.size = values.next()?;
+                }
+                if compressed_size == u32::MAX {
+                    entry.compressed_size = values.next()?;
+                }
+                if header_offset == u32::MAX {
+                    entry.header_offset = values.next()?;
+                }
+            }
+            extra_pos = field_end;
+        }
+
+        entries.insert(String::from_utf8_lossy(name).into_owned(), entry);
+        pos = extra_start + extra_length + comment_length;
+    }
+    Some(entries)
+}
 
 /// A zip storage adapter.
 pub struct ZipStorageAdapter<TStorage: ?Sized> {
+    storage: Arc<TStorage>,
+    key: StoreKey,
     size: u64,
-    zip_archive: Mutex<ZipArchive<StorageValueIO<TStorage>>>,
+    central_directory: Mutex<Option<Arc<BTreeMap<String, ZipEntry>>>>,
     zip_path: PathBuf,
 }
 
 impl<TStorage: ?Sized + ReadableStorageTraits> ZipStorageAdapter<TStorage> {
     /// Create a new zip storage adapter.
     ///
+    /// The central directory of the zip file is read on first access.
+    ///
     /// # Errors
     ///
-    /// Returns a [`ZipStorageAdapterCreateError`] if the root path of the store is not a valid zip file.
+    /// Returns a [`ZipStorageAdapterCreateError`] if the size of the zip file cannot be determined.
     pub fn new(
         storage: Arc<TStorage>,
         key: StoreKey,
@@ -38,9 +213,11 @@ impl<TStorage: ?Sized + ReadableStorageTraits> ZipStorageAdapter<TStorage> {
 
     /// Create a new zip storage adapter to `path` within the zip file.
     ///
+    /// The central directory of the zip file is read on first access.
+    ///
     /// # Errors
     ///
-    /// Returns a [`ZipStorageAdapterCreateError`] if the root path of the store is not a valid zip file.
+    /// Returns a [`ZipStorageAdapterCreateError`] if the size of the zip file cannot be determined.
     pub fn new_with_path<T: Into<PathBuf>>(
         storage: Arc<TStorage>,
         key: StoreKey,
@@ -52,39 +229,151 @@ impl<TStorage: ?Sized + ReadableStorageTraits> ZipStorageAdapter<TStorage> {
             .ok_or::<ZipStorageAdapterCreateError>(
                 StorageError::UnknownKeySize(key.clone()).into(),
             )?;
-        let storage_io = StorageValueIO::new(storage, key, size);
-        let zip_archive = Mutex::new(
-            ZipArchive::new(storage_io)
-                .map_err(|err| ZipStorageAdapterCreateError::ZipError(err.to_string()))?,
-        );
         Ok(Self {
+            storage,
+            key,
             size,
-            zip_archive,
+            central_directory: Mutex::new(None),
             zip_path,
         })
     }
 
+    fn invalid_zip_error(&self) -> StorageError {
+        StorageError::Other(format!("{} is not a valid zip file", self.key))
+    }
+
+    /// Retrieve `length` bytes of the zip file starting at `offset`.
+    fn get_bytes(&self, offset: u64, length: u64) -> Result<Bytes, StorageError> {
+        self.storage
+            .get_partial_values_key(&self.key, &[ByteRange::FromStart(offset, Some(length))])?
+            .and_then(|mut bytes| bytes.pop())
+            .ok_or_else(|| StorageError::Other(format!("zip file {} not found", self.key)))
+    }
+
+    /// Read the central directory of the zip file.
+    ///
+    /// This retrieves the tail of the zip file, and then the central directory or zip64 end of central directory record if they are not in the tail.
+    fn read_central_directory(&self) -> Result<BTreeMap<String, ZipEntry>, StorageError> {
+        let tail_offset = self.size.saturating_sub(ZIP_TAIL_SIZE);
+        let tail = self.get_bytes(tail_offset, self.size - tail_offset)?;
+        let location = match zip_central_directory_location(&tail, tail_offset) {
+            Some(ZipCentralDirectoryLocation::Zip64Record(record_offset)) => {
+                let record = self.get_bytes(record_offset, ZIP64_EOCD_SIZE as u64)?;
+                zip64_central_directory_location(&record)
+            }
+            location => location,
+        };
+        let Some(ZipCentralDirectoryLocation::Found(offset, size)) = location else {
+            return Err(self.invalid_zip_error());
+        };
+
+        let central_directory = if offset >= tail_offset && offset + size <= self.size {
+            let start = usize::try_from(offset - tail_offset).unwrap();
+            let end = usize::try_from(offset + size - tail_offset).unwrap();
+            tail.slice(start..end)
+        } else {
+            self.get_bytes(offset, size)?
+        };
+        parse_zip_central_directory(&central_directory).ok_or_else(|| self.invalid_zip_error())
+    }
+
+    /// Return the central directory, reading it if this is the first access.
+    fn central_directory(&self) -> Result<Arc<BTreeMap<String, ZipEntry>>, StorageError> {
+        let mut central_directory = self.central_directory.lock();
+        if let Some(central_directory) = central_directory.as_ref() {
+            Ok(central_directory.clone())
+        } else {
+            let entries = Arc::new(self.read_central_directory()?);
+            *central_directory = Some(entries.clone());
+            Ok(entries)
+        }
+    }
+
+    /// Return the offset of the data of `entry`, reading its local file header if this is the first access.
+    fn data_start(&self, entry: &ZipEntry) -> Result<u64, StorageError> {
+        if let Some(data_start) = entry.data_start.get() {
+            return Ok(*data_start);
+        }
+        let header = self.get_bytes(entry.header_offset, ZIP_LOCAL_HEADER_SIZE as u64)?;
+        if header.get(..4) != Some(&ZIP_LOCAL_HEADER_SIGNATURE) {
+            return Err(self.invalid_zip_error());
+        }
+        let (Some(name_length), Some(extra_length)) =
+            (read_u16(&header, 26), read_u16(&header, 28))
+        else {
+            return Err(self.invalid_zip_error());
+        };
+        let data_start = entry.header_offset
+            + ZIP_LOCAL_HEADER_SIZE as u64
+            + u64::from(name_length)
+            + u64::from(extra_length);
+        Ok(*entry.data_start.get_or_init(|| data_start))
+    }
+
+    fn zip_name(&self, key: &StoreKey) -> String {
+        let mut zip_name = self.zip_path.clone();
+        zip_name.push(key.as_str());
+        zip_name.to_string_lossy().into_owned()
+    }
+
     fn get_impl(
         &self,
         key: &StoreKey,
         byte_ranges: &[ByteRange],
     ) -> Result<Option<Vec<Bytes>>, StorageError> {
-        let mut zip_archive = self.zip_archive.lock();
-        let mut zip_name = self.zip_path.clone();
-        zip_name.push(key.as_str());
-
-        let mut file = {
-            let zip_file = zip_archive.by_name(&zip_name.to_string_lossy());
-            match zip_file {
-                Ok(zip_file) => zip_file,
-                Err(err) => match err {
-                    ZipError::FileNotFound => return Ok(None),
-                    _ => return Err(StorageError::Other(err.to_string())),
-                },
-            }
+        let central_directory = self.central_directory()?;
+        let Some(entry) = central_directory.get(&self.zip_name(key)) else {
+            return Ok(None);
         };
-        let size = file.size();
+        let size = entry.size;
+        let data_start = self.data_start(entry)?;
+
+        if entry.compression_method == ZIP_METHOD_STORED && entry.flags & ZIP_FLAG_ENCRYPTED == 0 {
+            // Read uncompressed values directly from the underlying storage
+            let byte_ranges = byte_ranges
+                .iter()
+                .map(|byte_range| {
+                    if byte_range.end(size) > size || byte_range.start(size) > byte_range.end(size)
+                    {
+                        Err(InvalidByteRangeError::new(*byte_range, size))
+                    } else {
+                        Ok(ByteRange::FromStart(
+                            data_start + byte_range.start(size),
+                            Some(byte_range.length(size)),
+                        ))
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            return self.storage.get_partial_values_key(&self.key, &byte_ranges);
+        }
 
+        // Retrieve the local file header and compressed data, and decode them as a stream
+        let mut bytes = self
+            .get_bytes(
+                entry.header_offset,
+                data_start - entry.header_offset + entry.compressed_size,
+            )?
+            .to_vec();
+        if entry.flags & ZIP_FLAG_DATA_DESCRIPTOR != 0 {
+            // The sizes and checksum are in a data descriptor after the data, so write them from the central directory
+            let (Ok(compressed_size), Ok(size)) = (
+                u32::try_from(entry.compressed_size),
+                u32::try_from(entry.size),
+            ) else {
+                return Err(StorageError::Other(format!(
+                    "zip file {} has an unsupported large file with a data descriptor",
+                    self.key
+                )));
+            };
+            bytes[6..8].copy_from_slice(&(entry.flags & !ZIP_FLAG_DATA_DESCRIPTOR).to_le_bytes());
+            bytes[14..18].copy_from_slice(&entry.crc32.to_le_bytes());
+            bytes[18..22].copy_from_slice(&compressed_size.to_le_bytes());
+            bytes[22..26].copy_from_slice(&size.to_le_bytes());
+        }
+        let mut bytes = bytes.as_slice();
+        let mut file = zip::read::read_zipfile_from_stream(&mut bytes)
+            .map_err(|err| StorageError::Other(err.to_string()))?
+            .ok_or_else(|| self.invalid_zip_error())?;
         let out = extract_byte_ranges_read(&mut file, size, byte_ranges)?
             .into_iter()
             .map(Bytes::from)
@@ -110,15 +399,10 @@ impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
     }
 
     fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
-        let mut zip_archive = self.zip_archive.lock();
-        let file = zip_archive.by_name(key.as_str());
-        match file {
-            Ok(file) => Ok(Some(file.compressed_size())),
-            Err(err) => match err {
-                ZipError::FileNotFound => Ok(None),
-                _ => Err(StorageError::Other(err.to_string())),
-            },
-        }
+        Ok(self
+            .central_directory()?
+            .get(&self.zip_name(key))
+            .map(|entry| entry.size))
     }
 }
 
@@ -126,71 +410,43 @@ impl<TStorage: ?Sized + ReadableStorageTraits> ListableStorageTraits
     for ZipStorageAdapter<TStorage>
 {
     fn list(&self) -> Result<StoreKeys, StorageError> {
-        Ok(self
-            .zip_archive
-            .lock()
-            .file_names()
-            .filter_map(|name| self.zip_file_strip_prefix(name))
-            .filter_map(|v| StoreKey::try_from(v).ok())
-            .sorted()
-            .collect())
+        self.list_prefix(&StorePrefix::root())
     }
 
     fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
-        let mut zip_archive = self.zip_archive.lock();
-        let file_names: Vec<String> = zip_archive
-            .file_names()
+        Ok(self
+            .central_directory()?
+            .keys()
             .filter_map(|name| self.zip_file_strip_prefix(name))
-            .map(std::string::ToString::to_string)
-            .collect();
-        Ok(file_names
-            .into_iter()
-            .filter_map(|name| {
-                if name.starts_with(prefix.as_str()) {
-                    let mut zip_name = self.zip_path.clone();
-                    zip_name.push(&name);
-                    if let Ok(file) = zip_archive.by_name(&zip_name.to_string_lossy()) {
-                        if file.is_file() {
-                            let name = name.strip_suffix('/').unwrap_or(&name);
-                            if let Ok(store_key) = StoreKey::try_from(name) {
-                                return Some(store_key);
-                            }
-                        }
-                    }
-                }
-                None
-            })
+            .filter(|name| name.starts_with(prefix.as_str()))
+            .filter_map(|name| StoreKey::try_from(name).ok())
             .sorted()
             .collect())
     }
 
     fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
-        let zip_archive = self.zip_archive.lock();
+        // Prefixes are identified from file names, since zip files do not necessarily have directory entries
         let mut keys: StoreKeys = vec![];
         let mut prefixes: StorePrefixes = vec![];
-        for name in zip_archive
-            .file_names()
+        for name in self
+            .central_directory()?
+            .keys()
             .filter_map(|name| self.zip_file_strip_prefix(name))
         {
-            if name.starts_with(prefix.as_str()) {
-                if name.ends_with('/') {
-                    if let Ok(store_prefix) = StorePrefix::try_from(name) {
-                        if let Some(parent) = store_prefix.parent() {
-                            if &parent == prefix {
-                                prefixes.push(store_prefix);
-                            }
-                        }
-                    }
-                } else if let Ok(store_key) = StoreKey::try_from(name) {
-                    let parent = store_key.parent();
-                    if &parent == prefix {
-                        keys.push(store_key);
-                    }
+            let Some(child) = name.strip_prefix(prefix.as_str()) else {
+                continue;
+            };
+            if let Some((child, _)) = child.split_once('/') {
+                if let Ok(store_prefix) = StorePrefix::new(format!("{}{child}/", prefix.as_str())) {
+                    prefixes.push(store_prefix);
                 }
+            } else if let Ok(store_key) = StoreKey::try_from(name) {
+                keys.push(store_key);
             }
         }
         keys.sort();
         prefixes.sort();
+        prefixes.dedup();
 
         Ok(StoreKeysPrefixes { keys, prefixes })
     }
@@ -200,13 +456,13 @@ impl<TStorage: ?Sized + ReadableStorageTraits> ListableStorageTraits
     }
 
     fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
-        let mut size = 0;
-        for key in self.list_prefix(prefix)? {
-            if let Some(size_key) = self.size_key(&key)? {
-                size += size_key;
-            }
-        }
-        Ok(size)
+        Ok(self
+            .central_directory()?
+            .iter()
+            .filter_map(|(name, entry)| Some((self.zip_file_strip_prefix(name)?, entry)))
+            .filter(|(name, _)| name.starts_with(prefix.as_str()) && StoreKey::new(*name).is_ok())
+            .map(|(_, entry)| entry.size)
+            .sum())
     }
 }
 
@@ -421,4 +677,69 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn zip_zip64_byte_ranges() -> Result<(), Box<dyn Error>> {
+        use crate::storage::{
+            storage_transformer::PerformanceMetricsStorageTransformer,
+            storage_transformer::StorageTransformerExtension, store::MemoryStore,
+        };
+
+        // More than u16::MAX entries requires a zip64 end of central directory record
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let stored = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        let deflated = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        zip.start_file("a/stored", stored.large_file(true))?;
+        zip.write_all(&(0..=255u8).collect::<Vec<_>>())?;
+        zip.start_file("a/deflated", deflated)?;
+        zip.write_all(&[7u8; 1000])?;
+        for i in 0..u16::MAX {
+            zip.start_file(format!("b/{i}"), stored)?;
+        }
+        let zip_bytes = zip.finish()?.into_inner();
+
+        let store = Arc::new(MemoryStore::new());
+        store.set(&"test.zip".try_into()?, zip_bytes.into())?;
+        let performance_metrics = Arc::new(PerformanceMetricsStorageTransformer::new());
+        let storage = performance_metrics
+            .clone()
+            .create_readable_transformer(store);
+        let store = ZipStorageAdapter::new(storage, StoreKey::new("test.zip")?)?;
+        assert_eq!(performance_metrics.reads(), 0);
+
+        assert_eq!(
+            store.list_prefix(&"a/".try_into()?)?,
+            &["a/deflated".try_into()?, "a/stored".try_into()?]
+        );
+        assert_eq!(store.list()?.len(), usize::from(u16::MAX) + 2);
+        assert!(performance_metrics.reads() <= 3);
+
+        assert_eq!(
+            store.get_partial_values_key(
+                &"a/stored".try_into()?,
+                &[
+                    ByteRange::FromStart(10, Some(2)),
+                    ByteRange::FromEnd(0, Some(1))
+                ]
+            )?,
+            Some(vec![vec![10, 11].into(), vec![255].into()])
+        );
+        assert!(store
+            .get_partial_values_key(
+                &"a/stored".try_into()?,
+                &[ByteRange::FromStart(255, Some(2))]
+            )
+            .is_err());
+        assert_eq!(store.size_key(&"a/deflated".try_into()?)?, Some(1000));
+        assert_eq!(
+            store.get(&"a/deflated".try_into()?)?,
+            Some(vec![7u8; 1000].into())
+        );
+        assert_eq!(store.get(&"a/missing".try_into()?)?, None);
+
+        Ok(())
+    }
 }
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{num::NonZeroUsize, sync::Arc};
 
 use async_recursion::async_recursion;
 
@@ -9,15 +9,22 @@ use crate::{
     array::{ArrayMetadata, ChunkKeyEncoding},
     byte_range::ByteRange,
     group::{GroupMetadata, GroupMetadataV3},
+    metadata::{ImplicitGroupPolicy, MetadataJsonFormat},
     node::{Node, NodeMetadata, NodePath},
 };
 
 use super::{
-    data_key, meta_key, meta_key_v2_array, meta_key_v2_attributes, meta_key_v2_group, AsyncBytes,
-    MaybeAsyncBytes, StorageError, StoreKey, StoreKeyRange, StoreKeyStartValue, StoreKeys,
-    StoreKeysPrefixes, StorePrefix, StorePrefixes,
+    data_key, into_pages, meta_key, meta_key_backup, meta_key_v2_array, meta_key_v2_attributes,
+    meta_key_v2_group, store_keys_prefixes_into_pages, AsyncBytes, AsyncStoreKeysPages,
+    AsyncStoreKeysPrefixesPages, AsyncStoreTransaction, AsyncStoreValueWriter,
+    BufferedStoreValueWriter, MaybeAsyncBytes, NonAtomicStoreTransaction, StorageError, StoreKey,
+    StoreKeyRange, StoreKeyStartValue, StoreKeys, StoreKeysPrefixes, StorePrefix, StorePrefixes,
+    StoreValueVersion,
 };
 
+/// The page size used when listing the store for node discovery.
+const DISCOVER_PAGE_SIZE: NonZeroUsize = unsafe { NonZeroUsize::new_unchecked(1000) };
+
 /// Async readable storage traits.
 #[cfg_attr(feature = "async", async_trait::async_trait)]
 pub trait AsyncReadableStorageTraits: Send + Sync {
@@ -74,6 +81,82 @@ pub trait AsyncReadableStorageTraits: Send + Sync {
     /// Returns a [`StorageError`] if there is an underlying storage error.
     async fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError>;
 
+    /// Retrieve the value (bytes) and version associated with a given [`StoreKey`].
+    ///
+    /// Returns [`None`] if the key is not found.
+    ///
+    /// Stores with native value versions (e.g. `ETag`s) should override this method.
+    /// The default implementation derives the version from the value with [`StoreValueVersion::from_content`].
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    async fn get_with_version(
+        &self,
+        key: &StoreKey,
+    ) -> Result<Option<(AsyncBytes, StoreValueVersion)>, StorageError> {
+        Ok(self.get(key).await?.map(|value| {
+            let version = StoreValueVersion::from_content(&value);
+            (value, version)
+        }))
+    }
+
+    /// Return the version of the value at `key`.
+    ///
+    /// Returns [`None`] if the key is not found.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    async fn get_version(&self, key: &StoreKey) -> Result<Option<StoreValueVersion>, StorageError> {
+        Ok(self
+            .get_with_version(key)
+            .await?
+            .map(|(_, version)| version))
+    }
+
+    /// Retrieve the values (bytes) associated with `keys`, coalescing reads of values stored in nearby regions of a common underlying object.
+    ///
+    /// Regions are merged into a single read while the fraction of unrequested bytes in the read does not exceed `max_waste_ratio`.
+    /// Returns [`None`] for missing keys.
+    ///
+    /// Stores with values that share underlying objects (e.g. kerchunk reference stores) should override this method.
+    /// The default implementation concurrently retrieves each value with [`get`](AsyncReadableStorageTraits::get).
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    async fn get_values_coalesced(
+        &self,
+        keys: &[StoreKey],
+        max_waste_ratio: f64,
+    ) -> Result<Vec<MaybeAsyncBytes>, StorageError> {
+        let _ = max_waste_ratio;
+        futures::future::try_join_all(keys.iter().map(|key| self.get(key))).await
+    }
+
+    /// Retrieve partial bytes from multiple store keys, each with a list of byte ranges.
+    ///
+    /// Stores capable of vectored reads should override this method to satisfy the request with fewer round trips.
+    /// The default implementation concurrently calls [`get_partial_values_key`](AsyncReadableStorageTraits::get_partial_values_key) for each key.
+    ///
+    /// # Parameters
+    /// * `keys_byte_ranges`: a list of ([`StoreKey`], [`ByteRange`]s) pairs.
+    ///
+    /// # Output
+    /// A list of values in the order of `keys_byte_ranges`. It will be [`None`] for missing keys.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    async fn get_partial_values_batched(
+        &self,
+        keys_byte_ranges: &[(&StoreKey, &[ByteRange])],
+    ) -> Result<Vec<Option<Vec<AsyncBytes>>>, StorageError> {
+        futures::future::try_join_all(
+            keys_byte_ranges
+                .iter()
+                .map(|(key, byte_ranges)| self.get_partial_values_key(key, byte_ranges)),
+        )
+        .await
+    }
+
     /// A utility method with the same input and output as [`get_partial_values`](AsyncReadableStorageTraits::get_partial_values) that internally calls [`get_partial_values_key`](AsyncReadableStorageTraits::get_partial_values_key) with byte ranges grouped by key.
     ///
     /// Readable storage can use this function in the implementation of [`get_partial_values`](AsyncReadableStorageTraits::get_partial_values) if that is optimal.
@@ -152,6 +235,55 @@ pub trait AsyncListableStorageTraits: Send + Sync {
     ///
     async fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError>;
 
+    /// Retrieve all [`StoreKeys`] with a given [`StorePrefix`] as a stream of pages of at most `page_size` keys.
+    ///
+    /// Unlike [`list_prefix`](AsyncListableStorageTraits::list_prefix), stores can implement this without holding every key in memory.
+    /// Keys are not necessarily sorted across pages.
+    /// The default implementation paginates the output of [`list_prefix`](AsyncListableStorageTraits::list_prefix).
+    ///
+    /// Any [`StorageError`] is returned by the stream.
+    fn list_prefix_pages(
+        &self,
+        prefix: &StorePrefix,
+        page_size: NonZeroUsize,
+    ) -> AsyncStoreKeysPages<'_> {
+        let prefix = prefix.clone();
+        futures::stream::once(async move { self.list_prefix(&prefix).await })
+            .map(move |keys| match keys {
+                Ok(keys) => {
+                    futures::stream::iter(into_pages(keys.into_iter(), page_size).map(Ok)).boxed()
+                }
+                Err(err) => futures::stream::iter([Err(err)]).boxed(),
+            })
+            .flatten()
+            .boxed()
+    }
+
+    /// Retrieve all [`StoreKeys`] and [`StorePrefix`] which are direct children of [`StorePrefix`] as a stream of pages of at most `page_size` keys and prefixes.
+    ///
+    /// Unlike [`list_dir`](AsyncListableStorageTraits::list_dir), stores can implement this without holding every child in memory.
+    /// Keys and prefixes are not necessarily sorted across pages.
+    /// The default implementation paginates the output of [`list_dir`](AsyncListableStorageTraits::list_dir).
+    ///
+    /// Any [`StorageError`] is returned by the stream.
+    fn list_dir_pages(
+        &self,
+        prefix: &StorePrefix,
+        page_size: NonZeroUsize,
+    ) -> AsyncStoreKeysPrefixesPages<'_> {
+        let prefix = prefix.clone();
+        futures::stream::once(async move { self.list_dir(&prefix).await })
+            .map(move |keys_prefixes| match keys_prefixes {
+                Ok(keys_prefixes) => futures::stream::iter(
+                    store_keys_prefixes_into_pages(keys_prefixes, page_size).map(Ok),
+                )
+                .boxed(),
+                Err(err) => futures::stream::iter([Err(err)]).boxed(),
+            })
+            .flatten()
+            .boxed()
+    }
+
     /// Return the size in bytes of all keys under `prefix`.
     ///
     /// # Errors
@@ -184,12 +316,10 @@ pub async fn async_store_set_partial_values<T: AsyncReadableWritableStorageTrait
     key_start_values: &[StoreKeyStartValue<'_>],
     // truncate: bool
 ) -> Result<(), StorageError> {
+    // Group by key (not just consecutive keys), since groups are written concurrently
     let groups = key_start_values
         .iter()
-        .chunk_by(|key_start_value| &key_start_value.key)
-        .into_iter()
-        .map(|(key, group)| (key, group.into_iter().cloned().collect::<Vec<_>>()))
-        .collect::<Vec<_>>();
+        .into_group_map_by(|key_start_value| &key_start_value.key);
     futures::stream::iter(&groups)
         .map(Ok)
         .try_for_each_concurrent(None, |(key, group)| async move {
@@ -202,8 +332,14 @@ pub async fn async_store_set_partial_values<T: AsyncReadableWritableStorageTrait
             let mut vec = Vec::<u8>::from(bytes);
 
             // Expand the store key if needed
-            let end_max =
-                usize::try_from(group.iter().map(StoreKeyStartValue::end).max().unwrap()).unwrap();
+            let end_max = usize::try_from(
+                group
+                    .iter()
+                    .map(|key_start_value| key_start_value.end())
+                    .max()
+                    .unwrap(),
+            )
+            .unwrap();
             if vec.len() < end_max {
                 vec.resize_with(end_max, Default::default);
             }
@@ -268,6 +404,32 @@ pub trait AsyncWritableStorageTraits: Send + Sync {
     /// # Errors
     /// Returns a [`StorageError`] if there is an underlying storage error.
     async fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError>;
+
+    /// Create an [`AsyncStoreValueWriter`] which streams a value to a [`StoreKey`].
+    ///
+    /// Stores can override this to write large values incrementally, such as with multipart uploads.
+    /// The default implementation buffers the value in memory and stores it with [`set`](AsyncWritableStorageTraits::set) when the writer is finished.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if the writer cannot be created.
+    async fn set_streaming(
+        &self,
+        key: &StoreKey,
+    ) -> Result<Box<dyn AsyncStoreValueWriter + '_>, StorageError> {
+        Ok(Box::new(BufferedStoreValueWriter::new(self, key.clone())))
+    }
+
+    /// Begin an [`AsyncStoreTransaction`], a batch of writes which is committed as a unit.
+    ///
+    /// Stores with atomic multi-key writes should override this.
+    /// The default implementation returns a [`NonAtomicStoreTransaction`], which buffers the writes in memory and applies them one at a time with [`set`](AsyncWritableStorageTraits::set) and [`erase`](AsyncWritableStorageTraits::erase) when the transaction is committed.
+    /// **The default commit is not atomic** and can be left partially applied if it fails.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if the transaction cannot be created.
+    async fn begin(&self) -> Result<Box<dyn AsyncStoreTransaction + '_>, StorageError> {
+        Ok(Box::new(NonAtomicStoreTransaction::new(self)))
+    }
 }
 
 /// A supertrait of [`AsyncReadableStorageTraits`] and [`AsyncWritableStorageTraits`].
@@ -306,103 +468,221 @@ impl<T> AsyncReadableWritableListableStorageTraits for T where
 
 /// Asynchronously get the child nodes.
 ///
+/// Child prefixes without metadata are implicit groups, unless they are disallowed by the [`ImplicitGroupPolicy`].
+///
 /// # Errors
 /// Returns a [`StorageError`] if there is an underlying error with the store.
-#[async_recursion]
 pub async fn async_get_child_nodes<TStorage>(
     storage: &Arc<TStorage>,
     path: &NodePath,
 ) -> Result<Vec<Node>, StorageError>
+where
+    TStorage: ?Sized + AsyncReadableStorageTraits + AsyncListableStorageTraits,
+{
+    async_get_child_nodes_opt(storage, path, true).await
+}
+
+/// Asynchronously get the child nodes, and their descendants if `recursive` is true.
+///
+/// The metadata of sibling nodes is retrieved concurrently.
+#[async_recursion]
+pub(crate) async fn async_get_child_nodes_opt<TStorage>(
+    storage: &Arc<TStorage>,
+    path: &NodePath,
+    recursive: bool,
+) -> Result<Vec<Node>, StorageError>
 where
     TStorage: ?Sized + AsyncReadableStorageTraits + AsyncListableStorageTraits,
 {
     let prefixes = async_discover_children(storage, path).await?;
-    let mut nodes: Vec<Node> = Vec::new();
-    // TODO: Asynchronously get metadata of all prefixes
-    for prefix in &prefixes {
-        let key = meta_key(&prefix.try_into()?);
-        let child_metadata = match storage.get(&key).await? {
-            Some(child_metadata) => {
-                let metadata: NodeMetadata = serde_json::from_slice(&child_metadata)
-                    .map_err(|err| StorageError::InvalidMetadata(key, err.to_string()))?;
-                metadata
-            }
+    let implicit_group_policy = ImplicitGroupPolicy::default();
+    let nodes = futures::future::try_join_all(
+        prefixes
+            .iter()
+            .map(|prefix| async_get_child_node(storage, prefix, recursive, implicit_group_policy)),
+    )
+    .await?;
+    Ok(nodes.into_iter().flatten().collect())
+}
+
+/// Asynchronously get the child node at `prefix`, and its descendants if `recursive` is true.
+///
+/// Returns [`None`] if the child has no metadata and `implicit_group_policy` is [`ImplicitGroupPolicy::Error`].
+async fn async_get_child_node<TStorage>(
+    storage: &Arc<TStorage>,
+    prefix: &StorePrefix,
+    recursive: bool,
+    implicit_group_policy: ImplicitGroupPolicy,
+) -> Result<Option<Node>, StorageError>
+where
+    TStorage: ?Sized + AsyncReadableStorageTraits + AsyncListableStorageTraits,
+{
+    let key = meta_key(&prefix.try_into()?);
+    let path: NodePath = prefix.try_into()?;
+    let child_metadata = match storage.get(&key).await? {
+        Some(child_metadata) => {
+            let metadata: NodeMetadata = serde_json::from_slice(&child_metadata)
+                .map_err(|err| StorageError::InvalidMetadata(key, err.to_string()))?;
+            metadata
+        }
+        None => match Node::async_get_metadata_v2(storage, &path).await? {
+            Some(metadata) => metadata,
+            None if implicit_group_policy == ImplicitGroupPolicy::Error => return Ok(None),
             None => NodeMetadata::Group(GroupMetadataV3::default().into()),
-        };
-        let path: NodePath = prefix.try_into()?;
-        let children = match child_metadata {
-            NodeMetadata::Array(_) => Vec::default(),
-            NodeMetadata::Group(_) => async_get_child_nodes(storage, &path).await?,
-        };
-        nodes.push(Node::new_with_metadata(path, child_metadata, children));
-    }
-    Ok(nodes)
+        },
+    };
+    let children = match child_metadata {
+        NodeMetadata::Group(_) if recursive => {
+            async_get_child_nodes_opt(storage, &path, true).await?
+        }
+        NodeMetadata::Array(_) | NodeMetadata::Group(_) => Vec::default(),
+    };
+    Ok(Some(Node::new_with_metadata(
+        path,
+        child_metadata,
+        children,
+    )))
+}
+
+/// Asynchronously get the child nodes of the node at `path` as a stream.
+///
+/// Unlike [`async_get_child_nodes`], the children are discovered incrementally with [`list_dir_pages`](AsyncListableStorageTraits::list_dir_pages) and each child is returned as soon as its metadata is retrieved.
+/// The metadata of the children in a page is retrieved concurrently, so children are not necessarily returned in order.
+/// The descendants of the children are not retrieved.
+///
+/// Any [`StorageError`] is returned by the stream.
+pub fn async_child_nodes_stream<'a, TStorage>(
+    storage: &'a Arc<TStorage>,
+    path: &NodePath,
+) -> futures::stream::BoxStream<'a, Result<Node, StorageError>>
+where
+    TStorage: ?Sized + AsyncReadableStorageTraits + AsyncListableStorageTraits,
+{
+    let prefix: StorePrefix = match path.try_into() {
+        Ok(prefix) => prefix,
+        Err(err) => return futures::stream::iter([Err(err.into())]).boxed(),
+    };
+    let implicit_group_policy = ImplicitGroupPolicy::default();
+    storage
+        .list_dir_pages(&prefix, DISCOVER_PAGE_SIZE)
+        .map_ok(|page| {
+            futures::stream::iter(
+                page.prefixes
+                    .into_iter()
+                    .filter(|v| !v.as_str().starts_with("__"))
+                    .map(Ok),
+            )
+        })
+        .try_flatten()
+        .map_ok(move |prefix| async move {
+            async_get_child_node(storage, &prefix, false, implicit_group_policy).await
+        })
+        .try_buffer_unordered(DISCOVER_PAGE_SIZE.get())
+        .try_filter_map(|node| futures::future::ready(Ok(node)))
+        .boxed()
 }
 
 /// Asynchronously create a group.
 ///
+/// The metadata is written with the default [`MetadataJsonFormat`].
+///
 /// # Errors
 /// Returns a [`StorageError`] if there is an underlying error with the store.
 pub async fn async_create_group(
     storage: &dyn AsyncWritableStorageTraits,
     path: &NodePath,
     group: &GroupMetadata,
+) -> Result<(), StorageError> {
+    async_create_group_opt(storage, path, group, &MetadataJsonFormat::default()).await
+}
+
+/// Asynchronously create a group with a non-default metadata JSON format.
+///
+/// # Errors
+/// Returns a [`StorageError`] if there is an underlying error with the store.
+pub async fn async_create_group_opt(
+    storage: &dyn AsyncWritableStorageTraits,
+    path: &NodePath,
+    group: &GroupMetadata,
+    format: &MetadataJsonFormat,
 ) -> Result<(), StorageError> {
     match group {
         GroupMetadata::V3(group) => {
             let key = meta_key(path);
-            let json = serde_json::to_vec_pretty(group)
+            let json = format
+                .serialize(group)
                 .map_err(|err| StorageError::InvalidMetadata(key.clone(), err.to_string()))?;
             storage.set(&meta_key(path), json.into()).await
         }
         GroupMetadata::V2(group) => {
             let mut group = group.clone();
+            let mut transaction = storage.begin().await?;
 
             if !group.attributes.is_empty() {
                 // Store .zgroup
                 let key = meta_key_v2_attributes(path);
-                let json = serde_json::to_vec_pretty(&group.attributes)
+                let json = format
+                    .serialize(&group.attributes)
                     .map_err(|err| StorageError::InvalidMetadata(key.clone(), err.to_string()))?;
-                storage.set(&key, json.into()).await?;
+                transaction.set(&key, json.into()).await?;
 
                 group.attributes = serde_json::Map::default();
             }
 
             // Store .zarray
             let key = meta_key_v2_group(path);
-            let json = serde_json::to_vec_pretty(&group)
+            let json = format
+                .serialize(&group)
                 .map_err(|err| StorageError::InvalidMetadata(key.clone(), err.to_string()))?;
-            storage.set(&key, json.into()).await?;
-            Ok(())
+            transaction.set(&key, json.into()).await?;
+            transaction.commit().await
         }
     }
 }
 
 /// Asynchronously create an array.
 ///
+/// The metadata is written with the default [`MetadataJsonFormat`].
+///
 /// # Errors
 /// Returns a [`StorageError`] if there is an underlying error with the store.
 pub async fn async_create_array(
     storage: &dyn AsyncWritableStorageTraits,
     path: &NodePath,
     array: &ArrayMetadata,
+) -> Result<(), StorageError> {
+    async_create_array_opt(storage, path, array, &MetadataJsonFormat::default()).await
+}
+
+/// Asynchronously create an array with a non-default metadata JSON format.
+///
+/// # Errors
+/// Returns a [`StorageError`] if there is an underlying error with the store.
+pub async fn async_create_array_opt(
+    storage: &dyn AsyncWritableStorageTraits,
+    path: &NodePath,
+    array: &ArrayMetadata,
+    format: &MetadataJsonFormat,
 ) -> Result<(), StorageError> {
     match array {
         ArrayMetadata::V3(array) => {
             let key = meta_key(path);
-            let json = serde_json::to_vec_pretty(array)
+            let json = format
+                .serialize(array)
                 .map_err(|err| StorageError::InvalidMetadata(key.clone(), err.to_string()))?;
             storage.set(&key, json.into()).await
         }
         ArrayMetadata::V2(array) => {
             let mut array = array.clone();
+            let mut transaction = storage.begin().await?;
 
             if !array.attributes.is_empty() {
                 // Store .zattrs
                 let key = meta_key_v2_attributes(path);
-                let json = serde_json::to_vec_pretty(&array.attributes)
+                let json = format
+                    .serialize(&array.attributes)
                     .map_err(|err| StorageError::InvalidMetadata(key.clone(), err.to_string()))?;
-                storage
+                transaction
                     .set(&meta_key_v2_attributes(path), json.into())
                     .await?;
 
@@ -411,9 +691,11 @@ pub async fn async_create_array(
 
             // Store .zarray
             let key = meta_key_v2_array(path);
-            let json = serde_json::to_vec_pretty(&array)
+            let json = format
+                .serialize(&array)
                 .map_err(|err| StorageError::InvalidMetadata(key.clone(), err.to_string()))?;
-            storage.set(&key, json.into()).await
+            transaction.set(&key, json.into()).await?;
+            transaction.commit().await
         }
     }
 }
@@ -422,6 +704,14 @@ pub async fn async_create_array(
 ///
 /// # Errors
 /// Returns a [`StorageError`] if there is an underlying error with the store.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(path = %array_path, chunk_indices = ?chunk_grid_indices, bytes = chunk_serialised.len())
+    )
+)]
 pub async fn async_store_chunk(
     storage: &dyn AsyncWritableStorageTraits,
     array_path: &NodePath,
@@ -429,12 +719,54 @@ pub async fn async_store_chunk(
     chunk_key_encoding: &ChunkKeyEncoding,
     chunk_serialised: AsyncBytes,
 ) -> Result<(), StorageError> {
+    #[cfg(feature = "metrics")]
+    let start = web_time::Instant::now();
     storage
         .set(
             &data_key(array_path, chunk_grid_indices, chunk_key_encoding),
             chunk_serialised,
         )
         .await?;
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_duration(crate::metrics::Metric::StoreSetDuration, start.elapsed());
+    Ok(())
+}
+
+/// Asynchronously store a chunk with a streaming writer.
+///
+/// Unlike [`async_store_chunk`], the chunk is written with [`AsyncWritableStorageTraits::set_streaming`].
+/// This is preferable for large chunks (e.g. shards), which some stores upload in parts.
+///
+/// # Errors
+/// Returns a [`StorageError`] if there is an underlying error with the store.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(path = %array_path, chunk_indices = ?chunk_grid_indices, bytes = chunk_serialised.len())
+    )
+)]
+pub async fn async_store_chunk_streaming(
+    storage: &dyn AsyncWritableStorageTraits,
+    array_path: &NodePath,
+    chunk_grid_indices: &[u64],
+    chunk_key_encoding: &ChunkKeyEncoding,
+    chunk_serialised: AsyncBytes,
+) -> Result<(), StorageError> {
+    #[cfg(feature = "metrics")]
+    let start = web_time::Instant::now();
+    let mut writer = storage
+        .set_streaming(&data_key(
+            array_path,
+            chunk_grid_indices,
+            chunk_key_encoding,
+        ))
+        .await?;
+    writer.write(chunk_serialised).await?;
+    writer.finish().await?;
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_duration(crate::metrics::Metric::StoreSetDuration, start.elapsed());
     Ok(())
 }
 
@@ -442,19 +774,34 @@ pub async fn async_store_chunk(
 ///
 /// # Errors
 /// Returns a [`StorageError`] if there is an underlying error with the store.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(path = %array_path, chunk_indices = ?chunk_grid_indices, bytes = tracing::field::Empty)
+    )
+)]
 pub async fn async_retrieve_chunk(
     storage: &dyn AsyncReadableStorageTraits,
     array_path: &NodePath,
     chunk_grid_indices: &[u64],
     chunk_key_encoding: &ChunkKeyEncoding,
 ) -> Result<MaybeAsyncBytes, StorageError> {
-    storage
+    #[cfg(feature = "metrics")]
+    let start = web_time::Instant::now();
+    let bytes = storage
         .get(&data_key(
             array_path,
             chunk_grid_indices,
             chunk_key_encoding,
         ))
-        .await
+        .await?;
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_duration(crate::metrics::Metric::StoreGetDuration, start.elapsed());
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("bytes", bytes.as_ref().map(AsyncBytes::len));
+    Ok(bytes)
 }
 
 /// Asynchronously erase metadata.
@@ -468,23 +815,92 @@ pub async fn async_erase_metadata(
     storage.erase(&meta_key(array_path)).await
 }
 
+/// Asynchronously copy the values of metadata `keys` to their [backup keys](super::meta_key_backup).
+///
+/// See [`backup_metadata`](super::backup_metadata).
+///
+/// # Errors
+/// Returns a [`StorageError`] if there is an underlying error with the store.
+pub async fn async_backup_metadata<TStorage: ?Sized + AsyncReadableWritableStorageTraits>(
+    storage: &TStorage,
+    keys: &[StoreKey],
+) -> Result<Vec<Option<StoreValueVersion>>, StorageError> {
+    let mut versions = Vec::with_capacity(keys.len());
+    for key in keys {
+        let key_backup = meta_key_backup(key);
+        if let Some((value, version)) = storage.get_with_version(key).await? {
+            storage.set(&key_backup, value).await?;
+            versions.push(Some(version));
+        } else {
+            storage.erase(&key_backup).await?;
+            versions.push(None);
+        }
+    }
+    Ok(versions)
+}
+
+/// Asynchronously restore the values of metadata `keys` from their [backup keys](super::meta_key_backup).
+///
+/// See [`restore_metadata_backup`](super::restore_metadata_backup).
+///
+/// # Errors
+/// Returns a [`StorageError`] if none of `keys` have a backup, or there is an underlying error with the store.
+pub async fn async_restore_metadata_backup<
+    TStorage: ?Sized + AsyncReadableWritableStorageTraits,
+>(
+    storage: &TStorage,
+    keys: &[StoreKey],
+) -> Result<(), StorageError> {
+    let mut backups = Vec::with_capacity(keys.len());
+    for key in keys {
+        backups.push(storage.get(&meta_key_backup(key)).await?);
+    }
+    if backups.iter().all(Option::is_none) {
+        return Err(StorageError::Other(format!(
+            "there is no metadata backup of {}",
+            keys.iter().map(StoreKey::as_str).join(", ")
+        )));
+    }
+    for (key, backup) in keys.iter().zip(backups) {
+        if let Some(backup) = backup {
+            storage.set(key, backup).await?;
+        } else {
+            storage.erase(key).await?;
+        }
+    }
+    Ok(())
+}
+
 /// Asynchronously erase a chunk.
 ///
 /// # Errors
 /// Returns a [`StorageError`] if there is an underlying error with the store.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(path = %array_path, chunk_indices = ?chunk_grid_indices)
+    )
+)]
 pub async fn async_erase_chunk(
     storage: &dyn AsyncWritableStorageTraits,
     array_path: &NodePath,
     chunk_grid_indices: &[u64],
     chunk_key_encoding: &ChunkKeyEncoding,
 ) -> Result<(), StorageError> {
+    #[cfg(feature = "metrics")]
+    let start = web_time::Instant::now();
     storage
         .erase(&data_key(
             array_path,
             chunk_grid_indices,
             chunk_key_encoding,
         ))
-        .await
+        .await?;
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_duration(crate::metrics::Metric::StoreEraseDuration, start.elapsed());
+    Ok(())
 }
 
 /// Asynchronously retrieve byte ranges from a chunk.
@@ -493,6 +909,14 @@ pub async fn async_erase_chunk(
 ///
 /// # Errors
 /// Returns a [`StorageError`] if there is an underlying error with the store.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(path = %array_path, chunk_indices = ?chunk_grid_indices, byte_ranges = bytes_ranges.len())
+    )
+)]
 pub async fn async_retrieve_partial_values(
     storage: &dyn AsyncReadableStorageTraits,
     array_path: &NodePath,
@@ -505,7 +929,12 @@ pub async fn async_retrieve_partial_values(
         .iter()
         .map(|byte_range| StoreKeyRange::new(key.clone(), *byte_range))
         .collect();
-    storage.get_partial_values(&key_ranges).await
+    #[cfg(feature = "metrics")]
+    let start = web_time::Instant::now();
+    let values = storage.get_partial_values(&key_ranges).await?;
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_duration(crate::metrics::Metric::StoreGetDuration, start.elapsed());
+    Ok(values)
 }
 
 /// Asynchronously discover the children of a node.
@@ -519,15 +948,17 @@ pub async fn async_discover_children<
     path: &NodePath,
 ) -> Result<StorePrefixes, StorageError> {
     let prefix: StorePrefix = path.try_into()?;
-    let children: Result<Vec<_>, _> = storage
-        .list_dir(&prefix)
-        .await?
-        .prefixes()
-        .iter()
-        .filter(|v| !v.as_str().starts_with("__"))
-        .map(|v| StorePrefix::new(v.as_str()))
-        .collect();
-    Ok(children?)
+    let mut children = Vec::new();
+    let mut pages = storage.list_dir_pages(&prefix, DISCOVER_PAGE_SIZE);
+    while let Some(page) = pages.next().await {
+        children.extend(
+            page?
+                .prefixes
+                .into_iter()
+                .filter(|v| !v.as_str().starts_with("__")),
+        );
+    }
+    Ok(children)
 }
 
 /// Asynchronously discover all nodes.
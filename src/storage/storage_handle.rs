@@ -4,13 +4,15 @@ use crate::byte_range::ByteRange;
 
 use super::{
     Bytes, ListableStorageTraits, MaybeBytes, ReadableStorageTraits, ReadableWritableStorageTraits,
-    StorageError, StoreKey, StorePrefix, WritableStorageTraits,
+    StorageError, StoreKey, StorePrefix, StoreTransaction, StoreValueVersion, StoreValueWriter,
+    WritableStorageTraits,
 };
 
 #[cfg(feature = "async")]
 use super::{
     AsyncBytes, AsyncListableStorageTraits, AsyncReadableStorageTraits,
-    AsyncReadableWritableStorageTraits, AsyncWritableStorageTraits, MaybeAsyncBytes,
+    AsyncReadableWritableStorageTraits, AsyncStoreTransaction, AsyncStoreValueWriter,
+    AsyncWritableStorageTraits, MaybeAsyncBytes,
 };
 
 /// A storage handle.
@@ -49,6 +51,25 @@ impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits for Storage
     fn size_key(&self, key: &super::StoreKey) -> Result<Option<u64>, super::StorageError> {
         self.0.size_key(key)
     }
+
+    fn get_with_version(
+        &self,
+        key: &StoreKey,
+    ) -> Result<Option<(Bytes, StoreValueVersion)>, StorageError> {
+        self.0.get_with_version(key)
+    }
+
+    fn get_version(&self, key: &StoreKey) -> Result<Option<StoreValueVersion>, StorageError> {
+        self.0.get_version(key)
+    }
+
+    fn get_values_coalesced(
+        &self,
+        keys: &[StoreKey],
+        max_waste_ratio: f64,
+    ) -> Result<Vec<MaybeBytes>, StorageError> {
+        self.0.get_values_coalesced(keys, max_waste_ratio)
+    }
 }
 
 impl<TStorage: ?Sized + ListableStorageTraits> ListableStorageTraits for StorageHandle<TStorage> {
@@ -70,6 +91,22 @@ impl<TStorage: ?Sized + ListableStorageTraits> ListableStorageTraits for Storage
         self.0.list_dir(prefix)
     }
 
+    fn list_prefix_pages(
+        &self,
+        prefix: &StorePrefix,
+        page_size: std::num::NonZeroUsize,
+    ) -> super::StoreKeysPages<'_> {
+        self.0.list_prefix_pages(prefix, page_size)
+    }
+
+    fn list_dir_pages(
+        &self,
+        prefix: &StorePrefix,
+        page_size: std::num::NonZeroUsize,
+    ) -> super::StoreKeysPrefixesPages<'_> {
+        self.0.list_dir_pages(prefix, page_size)
+    }
+
     fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
         self.0.size_prefix(prefix)
     }
@@ -102,6 +139,17 @@ impl<TStorage: ?Sized + WritableStorageTraits> WritableStorageTraits for Storage
     fn erase_prefix(&self, prefix: &super::StorePrefix) -> Result<(), super::StorageError> {
         self.0.erase_prefix(prefix)
     }
+
+    fn set_streaming(
+        &self,
+        key: &StoreKey,
+    ) -> Result<Box<dyn StoreValueWriter + '_>, StorageError> {
+        self.0.set_streaming(key)
+    }
+
+    fn begin(&self) -> Result<Box<dyn StoreTransaction + '_>, StorageError> {
+        self.0.begin()
+    }
 }
 
 impl<TStorage: ?Sized + ReadableWritableStorageTraits> ReadableWritableStorageTraits
@@ -139,6 +187,25 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits> AsyncReadableStorageTraits
     async fn size_key(&self, key: &super::StoreKey) -> Result<Option<u64>, super::StorageError> {
         self.0.size_key(key).await
     }
+
+    async fn get_with_version(
+        &self,
+        key: &StoreKey,
+    ) -> Result<Option<(AsyncBytes, StoreValueVersion)>, StorageError> {
+        self.0.get_with_version(key).await
+    }
+
+    async fn get_version(&self, key: &StoreKey) -> Result<Option<StoreValueVersion>, StorageError> {
+        self.0.get_version(key).await
+    }
+
+    async fn get_values_coalesced(
+        &self,
+        keys: &[StoreKey],
+        max_waste_ratio: f64,
+    ) -> Result<Vec<MaybeAsyncBytes>, StorageError> {
+        self.0.get_values_coalesced(keys, max_waste_ratio).await
+    }
 }
 
 #[cfg(feature = "async")]
@@ -164,6 +231,22 @@ impl<TStorage: ?Sized + AsyncListableStorageTraits> AsyncListableStorageTraits
         self.0.list_dir(prefix).await
     }
 
+    fn list_prefix_pages(
+        &self,
+        prefix: &super::StorePrefix,
+        page_size: std::num::NonZeroUsize,
+    ) -> super::AsyncStoreKeysPages<'_> {
+        self.0.list_prefix_pages(prefix, page_size)
+    }
+
+    fn list_dir_pages(
+        &self,
+        prefix: &super::StorePrefix,
+        page_size: std::num::NonZeroUsize,
+    ) -> super::AsyncStoreKeysPrefixesPages<'_> {
+        self.0.list_dir_pages(prefix, page_size)
+    }
+
     async fn size_prefix(&self, prefix: &super::StorePrefix) -> Result<u64, super::StorageError> {
         self.0.size_prefix(prefix).await
     }
@@ -200,6 +283,17 @@ impl<TStorage: ?Sized + AsyncWritableStorageTraits> AsyncWritableStorageTraits
     async fn erase_prefix(&self, prefix: &super::StorePrefix) -> Result<(), super::StorageError> {
         self.0.erase_prefix(prefix).await
     }
+
+    async fn set_streaming(
+        &self,
+        key: &StoreKey,
+    ) -> Result<Box<dyn AsyncStoreValueWriter + '_>, StorageError> {
+        self.0.set_streaming(key).await
+    }
+
+    async fn begin(&self) -> Result<Box<dyn AsyncStoreTransaction + '_>, StorageError> {
+        self.0.begin().await
+    }
 }
 
 #[cfg(feature = "async")]
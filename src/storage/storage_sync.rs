@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{num::NonZeroUsize, sync::Arc};
 
 use itertools::Itertools;
 
@@ -6,15 +6,21 @@ use crate::{
     array::{ArrayMetadata, ChunkKeyEncoding},
     byte_range::ByteRange,
     group::{GroupMetadata, GroupMetadataV3},
+    metadata::{ImplicitGroupPolicy, MetadataJsonFormat},
     node::{Node, NodeMetadata, NodePath},
 };
 
 use super::{
-    data_key, meta_key, meta_key_v2_array, meta_key_v2_attributes, meta_key_v2_group, Bytes,
-    MaybeBytes, StorageError, StoreKey, StoreKeyRange, StoreKeyStartValue, StoreKeys,
-    StoreKeysPrefixes, StorePrefix, StorePrefixes,
+    data_key, into_pages, meta_key, meta_key_backup, meta_key_v2_array, meta_key_v2_attributes,
+    meta_key_v2_group, store_keys_prefixes_into_pages, BufferedStoreValueWriter, Bytes, MaybeBytes,
+    NonAtomicStoreTransaction, StorageError, StoreKey, StoreKeyRange, StoreKeyStartValue,
+    StoreKeys, StoreKeysPages, StoreKeysPrefixes, StoreKeysPrefixesPages, StorePrefix,
+    StorePrefixes, StoreTransaction, StoreValueVersion, StoreValueWriter,
 };
 
+/// The page size used when listing the store for node discovery.
+const DISCOVER_PAGE_SIZE: NonZeroUsize = unsafe { NonZeroUsize::new_unchecked(1000) };
+
 /// Readable storage traits.
 pub trait ReadableStorageTraits: Send + Sync {
     /// Retrieve the value (bytes) associated with a given [`StoreKey`].
@@ -66,6 +72,54 @@ pub trait ReadableStorageTraits: Send + Sync {
     /// Returns a [`StorageError`] if there is an underlying storage error.
     fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError>;
 
+    /// Retrieve the value (bytes) and version associated with a given [`StoreKey`].
+    ///
+    /// Returns [`None`] if the key is not found.
+    ///
+    /// Stores with native value versions (e.g. `ETag`s) should override this method.
+    /// The default implementation derives the version from the value with [`StoreValueVersion::from_content`].
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    fn get_with_version(
+        &self,
+        key: &StoreKey,
+    ) -> Result<Option<(Bytes, StoreValueVersion)>, StorageError> {
+        Ok(self.get(key)?.map(|value| {
+            let version = StoreValueVersion::from_content(&value);
+            (value, version)
+        }))
+    }
+
+    /// Return the version of the value at `key`.
+    ///
+    /// Returns [`None`] if the key is not found.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    fn get_version(&self, key: &StoreKey) -> Result<Option<StoreValueVersion>, StorageError> {
+        Ok(self.get_with_version(key)?.map(|(_, version)| version))
+    }
+
+    /// Retrieve the values (bytes) associated with `keys`, coalescing reads of values stored in nearby regions of a common underlying object.
+    ///
+    /// Regions are merged into a single read while the fraction of unrequested bytes in the read does not exceed `max_waste_ratio`.
+    /// Returns [`None`] for missing keys.
+    ///
+    /// Stores with values that share underlying objects (e.g. kerchunk reference stores) should override this method.
+    /// The default implementation retrieves each value independently with [`get`](ReadableStorageTraits::get).
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    fn get_values_coalesced(
+        &self,
+        keys: &[StoreKey],
+        max_waste_ratio: f64,
+    ) -> Result<Vec<MaybeBytes>, StorageError> {
+        let _ = max_waste_ratio;
+        keys.iter().map(|key| self.get(key)).collect()
+    }
+
     /// A utility method with the same input and output as [`get_partial_values`](ReadableStorageTraits::get_partial_values) that internally calls [`get_partial_values_key`](ReadableStorageTraits::get_partial_values_key) with byte ranges grouped by key.
     ///
     /// Readable storage can use this function in the implementation of [`get_partial_values`](ReadableStorageTraits::get_partial_values) if that is optimal.
@@ -135,6 +189,44 @@ pub trait ListableStorageTraits: Send + Sync {
     ///
     fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError>;
 
+    /// Retrieve all [`StoreKeys`] with a given [`StorePrefix`] in pages of at most `page_size` keys.
+    ///
+    /// Unlike [`list_prefix`](ListableStorageTraits::list_prefix), stores can implement this without holding every key in memory.
+    /// Keys are not necessarily sorted across pages.
+    /// The default implementation paginates the output of [`list_prefix`](ListableStorageTraits::list_prefix).
+    ///
+    /// Any [`StorageError`] is returned by the iterator.
+    fn list_prefix_pages(
+        &self,
+        prefix: &StorePrefix,
+        page_size: NonZeroUsize,
+    ) -> StoreKeysPages<'_> {
+        match self.list_prefix(prefix) {
+            Ok(keys) => Box::new(into_pages(keys.into_iter(), page_size).map(Ok)),
+            Err(err) => Box::new(std::iter::once(Err(err))),
+        }
+    }
+
+    /// Retrieve all [`StoreKeys`] and [`StorePrefix`] which are direct children of [`StorePrefix`] in pages of at most `page_size` keys and prefixes.
+    ///
+    /// Unlike [`list_dir`](ListableStorageTraits::list_dir), stores can implement this without holding every child in memory.
+    /// Keys and prefixes are not necessarily sorted across pages.
+    /// The default implementation paginates the output of [`list_dir`](ListableStorageTraits::list_dir).
+    ///
+    /// Any [`StorageError`] is returned by the iterator.
+    fn list_dir_pages(
+        &self,
+        prefix: &StorePrefix,
+        page_size: NonZeroUsize,
+    ) -> StoreKeysPrefixesPages<'_> {
+        match self.list_dir(prefix) {
+            Ok(keys_prefixes) => {
+                Box::new(store_keys_prefixes_into_pages(keys_prefixes, page_size).map(Ok))
+            }
+            Err(err) => Box::new(std::iter::once(Err(err))),
+        }
+    }
+
     /// Return the size in bytes of all keys under `prefix`.
     ///
     /// # Errors
@@ -246,6 +338,32 @@ pub trait WritableStorageTraits: Send + Sync {
     /// # Errors
     /// Returns a [`StorageError`] is the prefix is not in the store, or the erase otherwise fails.
     fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError>;
+
+    /// Create a [`StoreValueWriter`] which streams a value to a [`StoreKey`].
+    ///
+    /// Stores can override this to write large values incrementally, such as with multipart uploads.
+    /// The default implementation buffers the value in memory and stores it with [`set`](WritableStorageTraits::set) when the writer is finished.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if the writer cannot be created.
+    fn set_streaming(
+        &self,
+        key: &StoreKey,
+    ) -> Result<Box<dyn StoreValueWriter + '_>, StorageError> {
+        Ok(Box::new(BufferedStoreValueWriter::new(self, key.clone())))
+    }
+
+    /// Begin a [`StoreTransaction`], a batch of writes which is committed as a unit.
+    ///
+    /// Stores with atomic multi-key writes should override this.
+    /// The default implementation returns a [`NonAtomicStoreTransaction`], which buffers the writes in memory and applies them one at a time with [`set`](WritableStorageTraits::set) and [`erase`](WritableStorageTraits::erase) when the transaction is committed.
+    /// **The default commit is not atomic** and can be left partially applied if it fails.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if the transaction cannot be created.
+    fn begin(&self) -> Result<Box<dyn StoreTransaction + '_>, StorageError> {
+        Ok(Box::new(NonAtomicStoreTransaction::new(self)))
+    }
 }
 
 /// A supertrait of [`ReadableStorageTraits`] and [`WritableStorageTraits`].
@@ -275,28 +393,46 @@ impl<T> ReadableWritableListableStorageTraits for T where
 
 /// Get the child nodes.
 ///
+/// Child prefixes without metadata are implicit groups, unless they are disallowed by the [`ImplicitGroupPolicy`].
+///
 /// # Errors
 /// Returns a [`StorageError`] if there is an underlying error with the store.
 pub fn get_child_nodes<TStorage: ?Sized + ReadableStorageTraits + ListableStorageTraits>(
     storage: &Arc<TStorage>,
     path: &NodePath,
+) -> Result<Vec<Node>, StorageError> {
+    get_child_nodes_opt(storage, path, true)
+}
+
+/// Get the child nodes, and their descendants if `recursive` is true.
+pub(crate) fn get_child_nodes_opt<
+    TStorage: ?Sized + ReadableStorageTraits + ListableStorageTraits,
+>(
+    storage: &Arc<TStorage>,
+    path: &NodePath,
+    recursive: bool,
 ) -> Result<Vec<Node>, StorageError> {
     let prefixes = discover_children(storage, path)?;
+    let implicit_group_policy = ImplicitGroupPolicy::default();
     let mut nodes: Vec<Node> = Vec::new();
     for prefix in &prefixes {
         let key = meta_key(&prefix.try_into()?);
+        let path: NodePath = prefix.try_into()?;
         let child_metadata = match storage.get(&key)? {
             Some(child_metadata) => {
                 let metadata: NodeMetadata = serde_json::from_slice(&child_metadata)
                     .map_err(|err| StorageError::InvalidMetadata(key, err.to_string()))?;
                 metadata
             }
-            None => NodeMetadata::Group(GroupMetadataV3::default().into()),
+            None => match Node::get_metadata_v2(storage, &path)? {
+                Some(metadata) => metadata,
+                None if implicit_group_policy == ImplicitGroupPolicy::Error => continue,
+                None => NodeMetadata::Group(GroupMetadataV3::default().into()),
+            },
         };
-        let path: NodePath = prefix.try_into()?;
         let children = match child_metadata {
-            NodeMetadata::Array(_) => Vec::default(),
-            NodeMetadata::Group(_) => get_child_nodes(storage, &path)?,
+            NodeMetadata::Group(_) if recursive => get_child_nodes_opt(storage, &path, true)?,
+            NodeMetadata::Array(_) | NodeMetadata::Group(_) => Vec::default(),
         };
         nodes.push(Node::new_with_metadata(path, child_metadata, children));
     }
@@ -305,77 +441,116 @@ pub fn get_child_nodes<TStorage: ?Sized + ReadableStorageTraits + ListableStorag
 
 /// Create a group.
 ///
+/// The metadata is written with the default [`MetadataJsonFormat`].
+///
 /// # Errors
 /// Returns a [`StorageError`] if there is an underlying error with the store.
 pub fn create_group(
     storage: &dyn WritableStorageTraits,
     path: &NodePath,
     group: &GroupMetadata,
+) -> Result<(), StorageError> {
+    create_group_opt(storage, path, group, &MetadataJsonFormat::default())
+}
+
+/// Create a group with a non-default metadata JSON format.
+///
+/// # Errors
+/// Returns a [`StorageError`] if there is an underlying error with the store.
+pub fn create_group_opt(
+    storage: &dyn WritableStorageTraits,
+    path: &NodePath,
+    group: &GroupMetadata,
+    format: &MetadataJsonFormat,
 ) -> Result<(), StorageError> {
     match group {
         GroupMetadata::V3(group) => {
             let key = meta_key(path);
-            let json = serde_json::to_vec_pretty(group)
+            let json = format
+                .serialize(group)
                 .map_err(|err| StorageError::InvalidMetadata(key.clone(), err.to_string()))?;
             storage.set(&meta_key(path), json.into())
         }
         GroupMetadata::V2(group) => {
             let mut group = group.clone();
+            let mut transaction = storage.begin()?;
 
             if !group.attributes.is_empty() {
                 // Store .zgroup
                 let key = meta_key_v2_attributes(path);
-                let json = serde_json::to_vec_pretty(&group.attributes)
+                let json = format
+                    .serialize(&group.attributes)
                     .map_err(|err| StorageError::InvalidMetadata(key.clone(), err.to_string()))?;
-                storage.set(&key, json.into())?;
+                transaction.set(&key, json.into())?;
 
                 group.attributes = serde_json::Map::default();
             }
 
             // Store .zarray
             let key = meta_key_v2_group(path);
-            let json = serde_json::to_vec_pretty(&group)
+            let json = format
+                .serialize(&group)
                 .map_err(|err| StorageError::InvalidMetadata(key.clone(), err.to_string()))?;
-            storage.set(&key, json.into())?;
-            Ok(())
+            transaction.set(&key, json.into())?;
+            transaction.commit()
         }
     }
 }
 
 /// Create an array.
 ///
+/// The metadata is written with the default [`MetadataJsonFormat`].
+///
 /// # Errors
 /// Returns a [`StorageError`] if there is an underlying error with the store.
 pub fn create_array(
     storage: &dyn WritableStorageTraits,
     path: &NodePath,
     array: &ArrayMetadata,
+) -> Result<(), StorageError> {
+    create_array_opt(storage, path, array, &MetadataJsonFormat::default())
+}
+
+/// Create an array with a non-default metadata JSON format.
+///
+/// # Errors
+/// Returns a [`StorageError`] if there is an underlying error with the store.
+pub fn create_array_opt(
+    storage: &dyn WritableStorageTraits,
+    path: &NodePath,
+    array: &ArrayMetadata,
+    format: &MetadataJsonFormat,
 ) -> Result<(), StorageError> {
     match array {
         ArrayMetadata::V3(array) => {
             let key = meta_key(path);
-            let json = serde_json::to_vec_pretty(array)
+            let json = format
+                .serialize(array)
                 .map_err(|err| StorageError::InvalidMetadata(key.clone(), err.to_string()))?;
             storage.set(&key, json.into())
         }
         ArrayMetadata::V2(array) => {
             let mut array = array.clone();
+            let mut transaction = storage.begin()?;
 
             if !array.attributes.is_empty() {
                 // Store .zattrs
                 let key = meta_key_v2_attributes(path);
-                let json = serde_json::to_vec_pretty(&array.attributes)
+                let json = format
+                    .serialize(&array.attributes)
                     .map_err(|err| StorageError::InvalidMetadata(key.clone(), err.to_string()))?;
-                storage.set(&meta_key_v2_attributes(path), json.into())?;
+                transaction.set(&meta_key_v2_attributes(path), json.into())?;
 
                 array.attributes = serde_json::Map::default();
             }
 
             // Store .zarray
             let key = meta_key_v2_array(path);
-            let json = serde_json::to_vec_pretty(&array)
+            let json = format
+                .serialize(&array)
                 .map_err(|err| StorageError::InvalidMetadata(key.clone(), err.to_string()))?;
-            storage.set(&key, json.into())
+            transaction.set(&key, json.into())?;
+            transaction.commit()
         }
     }
 }
@@ -384,6 +559,14 @@ pub fn create_array(
 ///
 /// # Errors
 /// Returns a [`StorageError`] if there is an underlying error with the store.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(path = %array_path, chunk_indices = ?chunk_grid_indices, bytes = chunk_serialised.len())
+    )
+)]
 pub fn store_chunk(
     storage: &dyn WritableStorageTraits,
     array_path: &NodePath,
@@ -391,10 +574,50 @@ pub fn store_chunk(
     chunk_key_encoding: &ChunkKeyEncoding,
     chunk_serialised: Bytes,
 ) -> Result<(), StorageError> {
+    #[cfg(feature = "metrics")]
+    let start = web_time::Instant::now();
     storage.set(
         &data_key(array_path, chunk_grid_indices, chunk_key_encoding),
         chunk_serialised,
     )?;
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_duration(crate::metrics::Metric::StoreSetDuration, start.elapsed());
+    Ok(())
+}
+
+/// Store a chunk with a streaming writer.
+///
+/// Unlike [`store_chunk`], the chunk is written with [`WritableStorageTraits::set_streaming`].
+/// This is preferable for large chunks (e.g. shards), which some stores upload in parts.
+///
+/// # Errors
+/// Returns a [`StorageError`] if there is an underlying error with the store.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(path = %array_path, chunk_indices = ?chunk_grid_indices, bytes = chunk_serialised.len())
+    )
+)]
+pub fn store_chunk_streaming(
+    storage: &dyn WritableStorageTraits,
+    array_path: &NodePath,
+    chunk_grid_indices: &[u64],
+    chunk_key_encoding: &ChunkKeyEncoding,
+    chunk_serialised: Bytes,
+) -> Result<(), StorageError> {
+    #[cfg(feature = "metrics")]
+    let start = web_time::Instant::now();
+    let mut writer = storage.set_streaming(&data_key(
+        array_path,
+        chunk_grid_indices,
+        chunk_key_encoding,
+    ))?;
+    writer.write(chunk_serialised)?;
+    writer.finish()?;
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_duration(crate::metrics::Metric::StoreSetDuration, start.elapsed());
     Ok(())
 }
 
@@ -402,17 +625,32 @@ pub fn store_chunk(
 ///
 /// # Errors
 /// Returns a [`StorageError`] if there is an underlying error with the store.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(path = %array_path, chunk_indices = ?chunk_grid_indices, bytes = tracing::field::Empty)
+    )
+)]
 pub fn retrieve_chunk(
     storage: &dyn ReadableStorageTraits,
     array_path: &NodePath,
     chunk_grid_indices: &[u64],
     chunk_key_encoding: &ChunkKeyEncoding,
 ) -> Result<MaybeBytes, StorageError> {
-    storage.get(&data_key(
+    #[cfg(feature = "metrics")]
+    let start = web_time::Instant::now();
+    let bytes = storage.get(&data_key(
         array_path,
         chunk_grid_indices,
         chunk_key_encoding,
-    ))
+    ))?;
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_duration(crate::metrics::Metric::StoreGetDuration, start.elapsed());
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("bytes", bytes.as_ref().map(Bytes::len));
+    Ok(bytes)
 }
 
 /// Erase a chunk.
@@ -421,17 +659,30 @@ pub fn retrieve_chunk(
 ///
 /// # Errors
 /// Returns a [`StorageError`] if there is an underlying error with the store.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(path = %array_path, chunk_indices = ?chunk_grid_indices)
+    )
+)]
 pub fn erase_chunk(
     storage: &dyn WritableStorageTraits,
     array_path: &NodePath,
     chunk_grid_indices: &[u64],
     chunk_key_encoding: &ChunkKeyEncoding,
 ) -> Result<(), StorageError> {
+    #[cfg(feature = "metrics")]
+    let start = web_time::Instant::now();
     storage.erase(&data_key(
         array_path,
         chunk_grid_indices,
         chunk_key_encoding,
-    ))
+    ))?;
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_duration(crate::metrics::Metric::StoreEraseDuration, start.elapsed());
+    Ok(())
 }
 
 /// Erase metadata.
@@ -447,12 +698,75 @@ pub fn erase_metadata(
     storage.erase(&meta_key(array_path))
 }
 
+/// Copy the values of metadata `keys` to their [backup keys](super::meta_key_backup).
+///
+/// The backup of a key without a value is erased.
+/// Returns the versions of the values that were backed up.
+///
+/// # Errors
+/// Returns a [`StorageError`] if there is an underlying error with the store.
+pub fn backup_metadata<TStorage: ?Sized + ReadableWritableStorageTraits>(
+    storage: &TStorage,
+    keys: &[StoreKey],
+) -> Result<Vec<Option<StoreValueVersion>>, StorageError> {
+    keys.iter()
+        .map(|key| {
+            let key_backup = meta_key_backup(key);
+            if let Some((value, version)) = storage.get_with_version(key)? {
+                storage.set(&key_backup, value)?;
+                Ok(Some(version))
+            } else {
+                storage.erase(&key_backup)?;
+                Ok(None)
+            }
+        })
+        .collect()
+}
+
+/// Restore the values of metadata `keys` from their [backup keys](super::meta_key_backup).
+///
+/// Keys without a backup are erased.
+///
+/// # Errors
+/// Returns a [`StorageError`] if none of `keys` have a backup, or there is an underlying error with the store.
+pub fn restore_metadata_backup<TStorage: ?Sized + ReadableWritableStorageTraits>(
+    storage: &TStorage,
+    keys: &[StoreKey],
+) -> Result<(), StorageError> {
+    let backups = keys
+        .iter()
+        .map(|key| storage.get(&meta_key_backup(key)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if backups.iter().all(Option::is_none) {
+        return Err(StorageError::Other(format!(
+            "there is no metadata backup of {}",
+            keys.iter().map(StoreKey::as_str).join(", ")
+        )));
+    }
+    for (key, backup) in keys.iter().zip(backups) {
+        if let Some(backup) = backup {
+            storage.set(key, backup)?;
+        } else {
+            storage.erase(key)?;
+        }
+    }
+    Ok(())
+}
+
 /// Retrieve byte ranges from a chunk.
 ///
 /// Returns [`None`] where keys are not found.
 ///
 /// # Errors
 /// Returns a [`StorageError`] if there is an underlying error with the store.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(path = %array_path, chunk_indices = ?chunk_grid_indices, byte_ranges = bytes_ranges.len())
+    )
+)]
 pub fn retrieve_partial_values(
     storage: &dyn ReadableStorageTraits,
     array_path: &NodePath,
@@ -465,7 +779,12 @@ pub fn retrieve_partial_values(
         .iter()
         .map(|byte_range| StoreKeyRange::new(key.clone(), *byte_range))
         .collect();
-    storage.get_partial_values(&key_ranges)
+    #[cfg(feature = "metrics")]
+    let start = web_time::Instant::now();
+    let values = storage.get_partial_values(&key_ranges)?;
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_duration(crate::metrics::Metric::StoreGetDuration, start.elapsed());
+    Ok(values)
 }
 
 /// Discover the children of a node.
@@ -477,14 +796,16 @@ pub fn discover_children<TStorage: ?Sized + ReadableStorageTraits + ListableStor
     path: &NodePath,
 ) -> Result<StorePrefixes, StorageError> {
     let prefix: StorePrefix = path.try_into()?;
-    let children: Result<Vec<_>, _> = storage
-        .list_dir(&prefix)?
-        .prefixes()
-        .iter()
-        .filter(|v| !v.as_str().starts_with("__"))
-        .map(|v| StorePrefix::new(v.as_str()))
-        .collect();
-    Ok(children?)
+    let mut children = Vec::new();
+    for page in storage.list_dir_pages(&prefix, DISCOVER_PAGE_SIZE) {
+        children.extend(
+            page?
+                .prefixes
+                .into_iter()
+                .filter(|v| !v.as_str().starts_with("__")),
+        );
+    }
+    Ok(children)
 }
 
 /// Discover all nodes.
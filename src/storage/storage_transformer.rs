@@ -1,14 +1,36 @@
-//! Zarr storage transformers. Includes [performance metrics](performance_metrics::PerformanceMetricsStorageTransformer) and [usage log](usage_log::UsageLogStorageTransformer) implementations for internal use.
+//! Zarr storage transformers. Includes [performance metrics](performance_metrics::PerformanceMetricsStorageTransformer), [usage log](usage_log::UsageLogStorageTransformer), [read only](read_only::ReadOnlyStorageTransformer), [write verify](write_verify::WriteVerifyStorageTransformer), [async coalescing](AsyncCoalescingStorageTransformer), [content digest](content_digest::ContentDigestStorageTransformer), and [write-ahead log](write_ahead_log::WriteAheadLogStorageTransformer) implementations for internal use.
 //!
 //! See <https://zarr-specs.readthedocs.io/en/latest/v3/core/v3.0.html#id23>.
 
+#[cfg(feature = "async")]
+mod coalescing;
+#[cfg(feature = "content_digest")]
+mod content_digest;
 mod performance_metrics;
+mod read_only;
 mod storage_transformer_chain;
 mod usage_log;
+mod write_ahead_log;
+mod write_verify;
 
-pub use performance_metrics::PerformanceMetricsStorageTransformer;
+#[cfg(feature = "async")]
+pub use coalescing::{AsyncCoalescingStorageTransformer, AsyncSleepFn};
+#[cfg(feature = "content_digest")]
+pub use content_digest::{
+    ContentDigestAlgorithm, ContentDigestStorageTransformer, CONTENT_DIGEST_PREFIX,
+};
+pub use performance_metrics::{
+    PerformanceMetricsHistogram, PerformanceMetricsHistogramSummary,
+    PerformanceMetricsStorageTransformer,
+};
+pub use read_only::ReadOnlyStorageTransformer;
 pub use storage_transformer_chain::StorageTransformerChain;
-pub use usage_log::UsageLogStorageTransformer;
+pub use usage_log::{
+    UsageLogRecord, UsageLogRequest, UsageLogResponse, UsageLogSink, UsageLogStorageTransformer,
+    UsageLogWriterSink,
+};
+pub use write_ahead_log::{WriteAheadLogStorageTransformer, WRITE_AHEAD_LOG_PREFIX};
+pub use write_verify::WriteVerifyStorageTransformer;
 
 use std::sync::Arc;
 
@@ -28,6 +50,87 @@ use super::{
     AsyncReadableWritableListableStorage, AsyncWritableStorage,
 };
 
+/// A storage operation, corresponding to a method of the storage traits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum StorageOperation {
+    /// Retrieve a value.
+    Get,
+    /// Retrieve byte ranges of a value.
+    GetPartialValuesKey,
+    /// Retrieve byte ranges of one or more values.
+    GetPartialValues,
+    /// Retrieve the size of a value.
+    SizeKey,
+    /// List all keys.
+    List,
+    /// List all keys with a prefix.
+    ListPrefix,
+    /// List the keys and prefixes directly under a prefix.
+    ListDir,
+    /// Retrieve the total size of the store.
+    Size,
+    /// Retrieve the size of all keys under a prefix.
+    SizePrefix,
+    /// Store a value.
+    Set,
+    /// Store byte ranges of one or more values.
+    SetPartialValues,
+    /// Erase a value.
+    Erase,
+    /// Erase multiple values.
+    EraseValues,
+    /// Erase all values under a prefix.
+    ErasePrefix,
+}
+
+impl StorageOperation {
+    /// All storage operations.
+    pub const ALL: [Self; 14] = [
+        Self::Get,
+        Self::GetPartialValuesKey,
+        Self::GetPartialValues,
+        Self::SizeKey,
+        Self::List,
+        Self::ListPrefix,
+        Self::ListDir,
+        Self::Size,
+        Self::SizePrefix,
+        Self::Set,
+        Self::SetPartialValues,
+        Self::Erase,
+        Self::EraseValues,
+        Self::ErasePrefix,
+    ];
+
+    /// Returns the name of the storage trait method corresponding to the operation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Get => "get",
+            Self::GetPartialValuesKey => "get_partial_values_key",
+            Self::GetPartialValues => "get_partial_values",
+            Self::SizeKey => "size_key",
+            Self::List => "list",
+            Self::ListPrefix => "list_prefix",
+            Self::ListDir => "list_dir",
+            Self::Size => "size",
+            Self::SizePrefix => "size_prefix",
+            Self::Set => "set",
+            Self::SetPartialValues => "set_partial_values",
+            Self::Erase => "erase",
+            Self::EraseValues => "erase_values",
+            Self::ErasePrefix => "erase_prefix",
+        }
+    }
+}
+
+impl std::fmt::Display for StorageOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// An [`Arc`] wrapped storage transformer.
 pub type StorageTransformer = Arc<dyn StorageTransformerExtension>;
 
@@ -0,0 +1,567 @@
+//! A storage transformer which coalesces concurrent async partial reads into batched requests.
+
+use crate::{
+    byte_range::ByteRange,
+    metadata::v3::MetadataV3,
+    storage::{
+        AsyncBytes, AsyncListableStorage, AsyncListableStorageTraits, AsyncReadableListableStorage,
+        AsyncReadableStorage, AsyncReadableStorageTraits, AsyncReadableWritableListableStorage,
+        AsyncReadableWritableStorageTraits, AsyncStoreTransaction, AsyncStoreValueWriter,
+        AsyncWritableStorage, AsyncWritableStorageTraits, ListableStorage, MaybeAsyncBytes,
+        ReadableListableStorage, ReadableStorage, ReadableWritableListableStorage,
+        ReadableWritableStorage, StorageError, StoreKey, StoreKeyRange, StoreKeyStartValue,
+        StoreKeys, StoreKeysPrefixes, StorePrefix, StoreValueVersion, WritableStorage,
+    },
+};
+
+use futures::{channel::oneshot, future::BoxFuture};
+use itertools::Itertools;
+use parking_lot::Mutex;
+
+use std::{sync::Arc, time::Duration};
+
+use super::StorageTransformerExtension;
+
+/// A function returning a future that completes after a [`Duration`].
+pub type AsyncSleepFn = Arc<dyn Fn(Duration) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// The async coalescing storage transformer. Batches concurrent partial reads into vectored requests.
+///
+/// Calls to [`get_partial_values_key`](AsyncReadableStorageTraits::get_partial_values_key) and [`get_partial_values`](AsyncReadableStorageTraits::get_partial_values) that arrive within a time window are combined into a single call to [`get_partial_values_batched`](AsyncReadableStorageTraits::get_partial_values_batched) of the underlying storage.
+/// This reduces the number of round trips for stores capable of vectored reads when many small reads are issued concurrently, such as when retrieving the inner chunks of a sharded array.
+/// A batch is requested early once it holds the maximum number of reads.
+///
+/// The window is measured with a user supplied sleep function, so that the transformer is independent of the async runtime.
+/// For example, with `tokio`:
+/// ```rust
+/// # use std::{sync::Arc, time::Duration};
+/// # use zarrs::storage::storage_transformer::AsyncCoalescingStorageTransformer;
+/// let transformer = AsyncCoalescingStorageTransformer::new(Duration::from_millis(2), Arc::new(|duration| {
+///     Box::pin(tokio::time::sleep(duration))
+/// }));
+/// ```
+///
+/// Sync storage and all other operations are passed through unchanged.
+/// This storage transformer is for internal use and will not to be included in `storage_transformers` array metadata.
+pub struct AsyncCoalescingStorageTransformer {
+    window: Duration,
+    max_batch_size: usize,
+    sleep: AsyncSleepFn,
+}
+
+impl core::fmt::Debug for AsyncCoalescingStorageTransformer {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AsyncCoalescingStorageTransformer")
+            .field("window", &self.window)
+            .field("max_batch_size", &self.max_batch_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AsyncCoalescingStorageTransformer {
+    /// Create a new async coalescing storage transformer.
+    ///
+    /// Reads arriving within `window` of the first read of a batch are coalesced, and `sleep` is used to wait for the window to elapse.
+    /// The maximum batch size defaults to 1024 reads.
+    #[must_use]
+    pub fn new(window: Duration, sleep: AsyncSleepFn) -> Self {
+        Self {
+            window,
+            max_batch_size: 1024,
+            sleep,
+        }
+    }
+
+    /// Set the maximum number of reads in a batch.
+    ///
+    /// # Panics
+    /// Panics if `max_batch_size` is zero.
+    #[must_use]
+    pub fn with_max_batch_size(mut self, max_batch_size: usize) -> Self {
+        assert!(max_batch_size > 0, "max_batch_size must be non-zero");
+        self.max_batch_size = max_batch_size;
+        self
+    }
+
+    /// Return the coalescing window.
+    #[must_use]
+    pub const fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Return the maximum number of reads in a batch.
+    #[must_use]
+    pub const fn max_batch_size(&self) -> usize {
+        self.max_batch_size
+    }
+
+    fn create_transformer<TStorage: ?Sized>(
+        &self,
+        storage: Arc<TStorage>,
+    ) -> Arc<AsyncCoalescingStorageTransformerImpl<TStorage>> {
+        Arc::new(AsyncCoalescingStorageTransformerImpl {
+            storage,
+            window: self.window,
+            max_batch_size: self.max_batch_size,
+            sleep: self.sleep.clone(),
+            pending: Mutex::new(None),
+        })
+    }
+}
+
+impl StorageTransformerExtension for AsyncCoalescingStorageTransformer {
+    /// Returns [`None`], since this storage transformer is not intended to be included in array `storage_transformers` metadata.
+    fn create_metadata(&self) -> Option<MetadataV3> {
+        None
+    }
+
+    fn create_readable_transformer(self: Arc<Self>, storage: ReadableStorage) -> ReadableStorage {
+        storage
+    }
+
+    fn create_writable_transformer(self: Arc<Self>, storage: WritableStorage) -> WritableStorage {
+        storage
+    }
+
+    fn create_readable_writable_transformer(
+        self: Arc<Self>,
+        storage: ReadableWritableStorage,
+    ) -> ReadableWritableStorage {
+        storage
+    }
+
+    fn create_listable_transformer(self: Arc<Self>, storage: ListableStorage) -> ListableStorage {
+        storage
+    }
+
+    fn create_readable_listable_transformer(
+        self: Arc<Self>,
+        storage: ReadableListableStorage,
+    ) -> ReadableListableStorage {
+        storage
+    }
+
+    fn create_readable_writable_listable_transformer(
+        self: Arc<Self>,
+        storage: ReadableWritableListableStorage,
+    ) -> ReadableWritableListableStorage {
+        storage
+    }
+
+    fn create_async_readable_transformer(
+        self: Arc<Self>,
+        storage: AsyncReadableStorage,
+    ) -> AsyncReadableStorage {
+        self.create_transformer(storage)
+    }
+
+    fn create_async_writable_transformer(
+        self: Arc<Self>,
+        storage: AsyncWritableStorage,
+    ) -> AsyncWritableStorage {
+        storage
+    }
+
+    fn create_async_listable_transformer(
+        self: Arc<Self>,
+        storage: AsyncListableStorage,
+    ) -> AsyncListableStorage {
+        storage
+    }
+
+    fn create_async_readable_listable_transformer(
+        self: Arc<Self>,
+        storage: AsyncReadableListableStorage,
+    ) -> AsyncReadableListableStorage {
+        self.create_transformer(storage)
+    }
+
+    fn create_async_readable_writable_listable_transformer(
+        self: Arc<Self>,
+        storage: AsyncReadableWritableListableStorage,
+    ) -> AsyncReadableWritableListableStorage {
+        self.create_transformer(storage)
+    }
+}
+
+type PartialValuesResult = Result<Option<Vec<AsyncBytes>>, StorageError>;
+
+/// A batch of reads waiting to be requested.
+struct PendingBatch {
+    id: u64,
+    reads: Vec<(
+        StoreKey,
+        Vec<ByteRange>,
+        oneshot::Sender<PartialValuesResult>,
+    )>,
+}
+
+struct AsyncCoalescingStorageTransformerImpl<TStorage: ?Sized> {
+    storage: Arc<TStorage>,
+    window: Duration,
+    max_batch_size: usize,
+    sleep: AsyncSleepFn,
+    pending: Mutex<Option<PendingBatch>>,
+}
+
+impl<TStorage: ?Sized + AsyncReadableStorageTraits>
+    AsyncCoalescingStorageTransformerImpl<TStorage>
+{
+    /// Add a read to the pending batch.
+    ///
+    /// Returns the id of the batch, the receiver of the read, and the batch if it is full.
+    fn enqueue(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> (
+        u64,
+        oneshot::Receiver<PartialValuesResult>,
+        Option<PendingBatch>,
+    ) {
+        static BATCH_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+        let (sender, receiver) = oneshot::channel();
+        let mut pending = self.pending.lock();
+        let batch = pending.get_or_insert_with(|| PendingBatch {
+            id: BATCH_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+            reads: Vec::new(),
+        });
+        let id = batch.id;
+        batch
+            .reads
+            .push((key.clone(), byte_ranges.to_vec(), sender));
+        let full = if batch.reads.len() >= self.max_batch_size {
+            pending.take()
+        } else {
+            None
+        };
+        (id, receiver, full)
+    }
+
+    /// Take the pending batch if it has not already been requested.
+    fn take(&self, id: u64) -> Option<PendingBatch> {
+        let mut pending = self.pending.lock();
+        if pending.as_ref().is_some_and(|batch| batch.id == id) {
+            pending.take()
+        } else {
+            None
+        }
+    }
+
+    /// Request the reads of a batch from the underlying storage and send their results.
+    async fn request(&self, batch: PendingBatch) {
+        let keys_byte_ranges = batch
+            .reads
+            .iter()
+            .map(|(key, byte_ranges, _)| (key, byte_ranges.as_slice()))
+            .collect::<Vec<_>>();
+        match self
+            .storage
+            .get_partial_values_batched(&keys_byte_ranges)
+            .await
+        {
+            Ok(values) => {
+                for ((_, _, sender), value) in std::iter::zip(batch.reads, values) {
+                    let _ = sender.send(Ok(value));
+                }
+            }
+            Err(err) => {
+                let err = err.to_string();
+                for (_, _, sender) in batch.reads {
+                    let _ = sender.send(Err(StorageError::Other(err.clone())));
+                }
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncReadableStorageTraits> AsyncReadableStorageTraits
+    for AsyncCoalescingStorageTransformerImpl<TStorage>
+{
+    async fn get(&self, key: &StoreKey) -> Result<MaybeAsyncBytes, StorageError> {
+        self.storage.get(key).await
+    }
+
+    async fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<AsyncBytes>>, StorageError> {
+        let (id, receiver, full) = self.enqueue(key, byte_ranges);
+        let receiver = if let Some(batch) = full {
+            self.request(batch).await;
+            receiver
+        } else {
+            // Wait for the window to elapse, unless another read requests the batch first
+            match futures::future::select(receiver, (self.sleep)(self.window)).await {
+                futures::future::Either::Left((result, _)) => {
+                    return match result {
+                        Ok(result) => result,
+                        // The read requesting the batch was cancelled
+                        Err(oneshot::Canceled) => {
+                            self.storage.get_partial_values_key(key, byte_ranges).await
+                        }
+                    };
+                }
+                futures::future::Either::Right(((), receiver)) => {
+                    if let Some(batch) = self.take(id) {
+                        self.request(batch).await;
+                    }
+                    receiver
+                }
+            }
+        };
+        match receiver.await {
+            Ok(result) => result,
+            // The read requesting the batch was cancelled
+            Err(oneshot::Canceled) => self.storage.get_partial_values_key(key, byte_ranges).await,
+        }
+    }
+
+    async fn get_partial_values(
+        &self,
+        key_ranges: &[StoreKeyRange],
+    ) -> Result<Vec<MaybeAsyncBytes>, StorageError> {
+        // Read each key concurrently, so that they are coalesced into the same batch
+        let keys_byte_ranges = key_ranges
+            .iter()
+            .chunk_by(|key_range| &key_range.key)
+            .into_iter()
+            .map(|(key, key_ranges)| {
+                (
+                    key.clone(),
+                    key_ranges
+                        .map(|key_range| key_range.byte_range)
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let values = futures::future::try_join_all(
+            keys_byte_ranges
+                .iter()
+                .map(|(key, byte_ranges)| self.get_partial_values_key(key, byte_ranges)),
+        )
+        .await?;
+        Ok(std::iter::zip(keys_byte_ranges, values)
+            .flat_map(|((_, byte_ranges), values)| {
+                values.map_or_else(
+                    || vec![None; byte_ranges.len()],
+                    |values| values.into_iter().map(Some).collect(),
+                )
+            })
+            .collect())
+    }
+
+    async fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        self.storage.size_key(key).await
+    }
+
+    async fn get_with_version(
+        &self,
+        key: &StoreKey,
+    ) -> Result<Option<(AsyncBytes, StoreValueVersion)>, StorageError> {
+        self.storage.get_with_version(key).await
+    }
+
+    async fn get_version(&self, key: &StoreKey) -> Result<Option<StoreValueVersion>, StorageError> {
+        self.storage.get_version(key).await
+    }
+
+    async fn get_values_coalesced(
+        &self,
+        keys: &[StoreKey],
+        max_waste_ratio: f64,
+    ) -> Result<Vec<MaybeAsyncBytes>, StorageError> {
+        self.storage
+            .get_values_coalesced(keys, max_waste_ratio)
+            .await
+    }
+
+    async fn get_partial_values_batched(
+        &self,
+        keys_byte_ranges: &[(&StoreKey, &[ByteRange])],
+    ) -> Result<Vec<Option<Vec<AsyncBytes>>>, StorageError> {
+        self.storage
+            .get_partial_values_batched(keys_byte_ranges)
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncListableStorageTraits> AsyncListableStorageTraits
+    for AsyncCoalescingStorageTransformerImpl<TStorage>
+{
+    async fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.storage.list().await
+    }
+
+    async fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        self.storage.list_prefix(prefix).await
+    }
+
+    async fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        self.storage.list_dir(prefix).await
+    }
+
+    async fn size(&self) -> Result<u64, StorageError> {
+        self.storage.size().await
+    }
+
+    async fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        self.storage.size_prefix(prefix).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncWritableStorageTraits> AsyncWritableStorageTraits
+    for AsyncCoalescingStorageTransformerImpl<TStorage>
+{
+    async fn set(&self, key: &StoreKey, value: AsyncBytes) -> Result<(), StorageError> {
+        self.storage.set(key, value).await
+    }
+
+    async fn set_partial_values(
+        &self,
+        key_start_values: &[StoreKeyStartValue],
+    ) -> Result<(), StorageError> {
+        self.storage.set_partial_values(key_start_values).await
+    }
+
+    async fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+        self.storage.erase(key).await
+    }
+
+    async fn erase_values(&self, keys: &[StoreKey]) -> Result<(), StorageError> {
+        self.storage.erase_values(keys).await
+    }
+
+    async fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+        self.storage.erase_prefix(prefix).await
+    }
+
+    async fn set_streaming(
+        &self,
+        key: &StoreKey,
+    ) -> Result<Box<dyn AsyncStoreValueWriter + '_>, StorageError> {
+        self.storage.set_streaming(key).await
+    }
+
+    async fn begin(&self) -> Result<Box<dyn AsyncStoreTransaction + '_>, StorageError> {
+        self.storage.begin().await
+    }
+}
+
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncReadableWritableStorageTraits> AsyncReadableWritableStorageTraits
+    for AsyncCoalescingStorageTransformerImpl<TStorage>
+{
+}
+
+#[cfg(all(test, feature = "object_store"))]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::storage::store::AsyncObjectStore;
+
+    use super::*;
+
+    /// An async store which counts batched partial reads.
+    struct CountingStore {
+        store: AsyncObjectStore<object_store::memory::InMemory>,
+        batches: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl AsyncReadableStorageTraits for CountingStore {
+        async fn get_partial_values_key(
+            &self,
+            key: &StoreKey,
+            byte_ranges: &[ByteRange],
+        ) -> Result<Option<Vec<AsyncBytes>>, StorageError> {
+            self.store.get_partial_values_key(key, byte_ranges).await
+        }
+
+        async fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+            self.store.size_key(key).await
+        }
+
+        async fn get_partial_values_batched(
+            &self,
+            keys_byte_ranges: &[(&StoreKey, &[ByteRange])],
+        ) -> Result<Vec<Option<Vec<AsyncBytes>>>, StorageError> {
+            self.batches.fetch_add(1, Ordering::SeqCst);
+            self.store
+                .get_partial_values_batched(keys_byte_ranges)
+                .await
+        }
+    }
+
+    #[tokio::test]
+    async fn async_coalescing() -> Result<(), Box<dyn std::error::Error>> {
+        let store = AsyncObjectStore::new(object_store::memory::InMemory::new());
+        for i in 0..8u8 {
+            store
+                .set(&StoreKey::new(format!("{i}"))?, vec![i; 4].into())
+                .await?;
+        }
+        let store = Arc::new(CountingStore {
+            store,
+            batches: AtomicUsize::new(0),
+        });
+
+        let sleep: AsyncSleepFn = Arc::new(|duration| Box::pin(tokio::time::sleep(duration)));
+        let transformer = Arc::new(
+            AsyncCoalescingStorageTransformer::new(Duration::from_millis(50), sleep)
+                .with_max_batch_size(4),
+        );
+        let storage = transformer.create_async_readable_transformer(store.clone());
+
+        // 8 concurrent reads (one missing) with a batch size of 4 are requested in 2 batches
+        let keys = (0..8u8)
+            .map(|i| StoreKey::new(format!("{}", if i == 7 { 9 } else { i })))
+            .collect::<Result<Vec<_>, _>>()?;
+        let values =
+            futures::future::try_join_all(keys.iter().map(|key| {
+                storage.get_partial_values_key(key, &[ByteRange::FromStart(1, Some(2))])
+            }))
+            .await?;
+        assert_eq!(store.batches.load(Ordering::SeqCst), 2);
+        for (i, value) in values.into_iter().enumerate() {
+            if i == 7 {
+                assert!(value.is_none());
+            } else {
+                let i = u8::try_from(i)?;
+                assert_eq!(value.unwrap(), vec![AsyncBytes::from(vec![i; 2])]);
+            }
+        }
+
+        // A single read is requested after the window elapses
+        let value = storage
+            .get_partial_values_key(&keys[0], &[ByteRange::FromEnd(0, Some(1))])
+            .await?;
+        assert_eq!(value.unwrap(), vec![AsyncBytes::from(vec![0])]);
+        assert_eq!(store.batches.load(Ordering::SeqCst), 3);
+
+        // Ranges of multiple keys are coalesced into one batch
+        let values = storage
+            .get_partial_values(&[
+                StoreKeyRange::new(keys[1].clone(), ByteRange::FromStart(0, Some(1))),
+                StoreKeyRange::new(keys[1].clone(), ByteRange::FromEnd(0, Some(1))),
+                StoreKeyRange::new(keys[2].clone(), ByteRange::FromStart(3, None)),
+            ])
+            .await?;
+        assert_eq!(store.batches.load(Ordering::SeqCst), 4);
+        assert_eq!(
+            values,
+            vec![
+                Some(AsyncBytes::from(vec![1])),
+                Some(AsyncBytes::from(vec![1])),
+                Some(AsyncBytes::from(vec![2])),
+            ]
+        );
+
+        Ok(())
+    }
+}
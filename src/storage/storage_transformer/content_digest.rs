@@ -0,0 +1,615 @@
+//! A storage transformer which records and verifies content digests.
+
+use crate::{
+    byte_range::{extract_byte_ranges, ByteRange},
+    metadata::v3::MetadataV3,
+    storage::{
+        Bytes, ListableStorage, ListableStorageTraits, MaybeBytes, ReadableListableStorage,
+        ReadableStorage, ReadableStorageTraits, ReadableWritableListableStorage,
+        ReadableWritableStorage, ReadableWritableStorageTraits, StorageError, StorageHandle,
+        StoreKey, StoreKeyStartValue, StoreKeys, StoreKeysPrefixes, StorePrefix, WritableStorage,
+        WritableStorageTraits,
+    },
+};
+
+#[cfg(feature = "async")]
+use crate::storage::{
+    AsyncBytes, AsyncListableStorage, AsyncListableStorageTraits, AsyncReadableListableStorage,
+    AsyncReadableStorage, AsyncReadableStorageTraits, AsyncReadableWritableListableStorage,
+    AsyncReadableWritableStorageTraits, AsyncWritableStorage, AsyncWritableStorageTraits,
+    MaybeAsyncBytes,
+};
+
+use std::{fmt::Write, sync::Arc};
+
+use itertools::Itertools;
+use sha2::Digest;
+
+use super::StorageTransformerExtension;
+
+/// The prefix under which content digests are stored.
+///
+/// The digest of the value at `key` is stored at `__zarrs_digest/{key}`.
+/// Node discovery skips prefixes starting with `__`, and the prefix is hidden from listing through the transformer.
+pub const CONTENT_DIGEST_PREFIX: &str = "__zarrs_digest/";
+
+/// A content digest algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum ContentDigestAlgorithm {
+    /// The 64-bit XXH3 hash. Fast, but not cryptographically secure.
+    #[default]
+    Xxh3,
+    /// The SHA-256 hash.
+    Sha256,
+}
+
+impl ContentDigestAlgorithm {
+    /// Returns the name of the algorithm.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Xxh3 => "xxh3",
+            Self::Sha256 => "sha256",
+        }
+    }
+
+    /// Returns the algorithm with `name`, or [`None`] if unsupported.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "xxh3" => Some(Self::Xxh3),
+            "sha256" => Some(Self::Sha256),
+            _ => None,
+        }
+    }
+
+    /// Returns the hex encoded digest of `bytes`.
+    #[must_use]
+    pub fn digest(&self, bytes: &[u8]) -> String {
+        match self {
+            Self::Xxh3 => format!("{:016x}", xxhash_rust::xxh3::xxh3_64(bytes)),
+            Self::Sha256 => sha2::Sha256::digest(bytes).iter().fold(
+                String::with_capacity(64),
+                |mut digest, byte| {
+                    let _ = write!(digest, "{byte:02x}");
+                    digest
+                },
+            ),
+        }
+    }
+}
+
+/// The content digest storage transformer. Records a content digest for every value written and verifies it on read.
+///
+/// This provides end-to-end bit rot detection independent of codec checksums (e.g. `crc32c`).
+/// Digests are stored as `{algorithm}:{hex digest}` in sidecar values under [`CONTENT_DIGEST_PREFIX`].
+/// A value is verified against the algorithm recorded in its sidecar, so the algorithm can be changed without invalidating existing digests.
+///
+/// Reading a value with a mismatched digest fails with [`StorageError::ContentDigestMismatch`].
+/// Values without a digest (e.g. written without this transformer) are passed through unless [`require_digests`](ContentDigestStorageTransformer::require_digests) is enabled.
+/// Partial reads are not verified unless [`verify_partial_reads`](ContentDigestStorageTransformer::verify_partial_reads) is enabled, in which case the entire value is retrieved.
+///
+/// Partially written values are read back to update their digest.
+/// If the underlying storage is not readable, the digests of partially written values are erased instead.
+///
+/// This storage transformer is for internal use and will not to be included in `storage_transformers` array metadata.
+#[derive(Debug, Clone, Default)]
+pub struct ContentDigestStorageTransformer {
+    algorithm: ContentDigestAlgorithm,
+    require_digests: bool,
+    verify_partial_reads: bool,
+}
+
+impl ContentDigestStorageTransformer {
+    /// Create a new content digest storage transformer.
+    #[must_use]
+    pub fn new(algorithm: ContentDigestAlgorithm) -> Self {
+        Self {
+            algorithm,
+            require_digests: false,
+            verify_partial_reads: false,
+        }
+    }
+
+    /// Set whether reading a value without a digest fails. Defaults to false.
+    #[must_use]
+    pub fn require_digests(mut self, require_digests: bool) -> Self {
+        self.require_digests = require_digests;
+        self
+    }
+
+    /// Set whether partial reads retrieve and verify the entire value. Defaults to false.
+    #[must_use]
+    pub fn verify_partial_reads(mut self, verify_partial_reads: bool) -> Self {
+        self.verify_partial_reads = verify_partial_reads;
+        self
+    }
+
+    /// Returns the algorithm used for digests of newly written values.
+    #[must_use]
+    pub const fn algorithm(&self) -> ContentDigestAlgorithm {
+        self.algorithm
+    }
+
+    fn create_transformer<TStorage: ?Sized>(
+        &self,
+        storage: Arc<TStorage>,
+    ) -> Arc<ContentDigestStorageTransformerImpl<TStorage>> {
+        Arc::new(ContentDigestStorageTransformerImpl {
+            storage,
+            config: self.clone(),
+            reader: None,
+            #[cfg(feature = "async")]
+            async_reader: None,
+        })
+    }
+
+    fn create_transformer_with_reader<TStorage: ?Sized + ReadableStorageTraits + 'static>(
+        &self,
+        storage: Arc<TStorage>,
+    ) -> Arc<ContentDigestStorageTransformerImpl<TStorage>> {
+        Arc::new(ContentDigestStorageTransformerImpl {
+            reader: Some(Arc::new(StorageHandle::new(storage.clone()))),
+            storage,
+            config: self.clone(),
+            #[cfg(feature = "async")]
+            async_reader: None,
+        })
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_transformer_with_reader<
+        TStorage: ?Sized + AsyncReadableStorageTraits + 'static,
+    >(
+        &self,
+        storage: Arc<TStorage>,
+    ) -> Arc<ContentDigestStorageTransformerImpl<TStorage>> {
+        Arc::new(ContentDigestStorageTransformerImpl {
+            async_reader: Some(Arc::new(StorageHandle::new(storage.clone()))),
+            storage,
+            config: self.clone(),
+            reader: None,
+        })
+    }
+}
+
+impl StorageTransformerExtension for ContentDigestStorageTransformer {
+    /// Returns [`None`], since this storage transformer is not intended to be included in array `storage_transformers` metadata.
+    fn create_metadata(&self) -> Option<MetadataV3> {
+        None
+    }
+
+    fn create_readable_transformer(self: Arc<Self>, storage: ReadableStorage) -> ReadableStorage {
+        self.create_transformer(storage)
+    }
+
+    fn create_writable_transformer(self: Arc<Self>, storage: WritableStorage) -> WritableStorage {
+        self.create_transformer(storage)
+    }
+
+    fn create_readable_writable_transformer(
+        self: Arc<Self>,
+        storage: ReadableWritableStorage,
+    ) -> ReadableWritableStorage {
+        self.create_transformer_with_reader(storage)
+    }
+
+    fn create_listable_transformer(self: Arc<Self>, storage: ListableStorage) -> ListableStorage {
+        self.create_transformer(storage)
+    }
+
+    fn create_readable_listable_transformer(
+        self: Arc<Self>,
+        storage: ReadableListableStorage,
+    ) -> ReadableListableStorage {
+        self.create_transformer(storage)
+    }
+
+    fn create_readable_writable_listable_transformer(
+        self: Arc<Self>,
+        storage: ReadableWritableListableStorage,
+    ) -> ReadableWritableListableStorage {
+        self.create_transformer_with_reader(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_readable_transformer(
+        self: Arc<Self>,
+        storage: AsyncReadableStorage,
+    ) -> AsyncReadableStorage {
+        self.create_transformer(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_writable_transformer(
+        self: Arc<Self>,
+        storage: AsyncWritableStorage,
+    ) -> AsyncWritableStorage {
+        self.create_transformer(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_listable_transformer(
+        self: Arc<Self>,
+        storage: AsyncListableStorage,
+    ) -> AsyncListableStorage {
+        self.create_transformer(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_readable_listable_transformer(
+        self: Arc<Self>,
+        storage: AsyncReadableListableStorage,
+    ) -> AsyncReadableListableStorage {
+        self.create_transformer(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_readable_writable_listable_transformer(
+        self: Arc<Self>,
+        storage: AsyncReadableWritableListableStorage,
+    ) -> AsyncReadableWritableListableStorage {
+        self.create_async_transformer_with_reader(storage)
+    }
+}
+
+struct ContentDigestStorageTransformerImpl<TStorage: ?Sized> {
+    storage: Arc<TStorage>,
+    config: ContentDigestStorageTransformer,
+    /// A readable handle to `storage`, used to update the digests of partially written values.
+    reader: Option<ReadableStorage>,
+    /// An asynchronous readable handle to `storage`, used to update the digests of partially written values.
+    #[cfg(feature = "async")]
+    async_reader: Option<AsyncReadableStorage>,
+}
+
+/// Returns the key of the content digest of `key`.
+fn digest_key(key: &StoreKey) -> StoreKey {
+    unsafe { StoreKey::new_unchecked(format!("{CONTENT_DIGEST_PREFIX}{}", key.as_str())) }
+}
+
+/// Returns the prefix of the content digests of keys with `prefix`.
+fn digest_prefix(prefix: &StorePrefix) -> StorePrefix {
+    unsafe { StorePrefix::new_unchecked(format!("{CONTENT_DIGEST_PREFIX}{}", prefix.as_str())) }
+}
+
+fn is_digest_key(key: &StoreKey) -> bool {
+    key.as_str().starts_with(CONTENT_DIGEST_PREFIX)
+}
+
+fn check_key(key: &StoreKey) -> Result<(), StorageError> {
+    if is_digest_key(key) {
+        Err(StorageError::Other(format!(
+            "{key} is reserved by the content digest storage transformer"
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn filter_keys(keys: StoreKeys) -> StoreKeys {
+    keys.into_iter().filter(|key| !is_digest_key(key)).collect()
+}
+
+fn filter_keys_prefixes(keys_prefixes: StoreKeysPrefixes) -> StoreKeysPrefixes {
+    StoreKeysPrefixes {
+        keys: keys_prefixes.keys,
+        prefixes: keys_prefixes
+            .prefixes
+            .into_iter()
+            .filter(|prefix| prefix.as_str() != CONTENT_DIGEST_PREFIX)
+            .collect(),
+    }
+}
+
+impl<TStorage: ?Sized> ContentDigestStorageTransformerImpl<TStorage> {
+    /// Returns the encoded content digest of `value`.
+    fn encode_digest(&self, value: &[u8]) -> Bytes {
+        let algorithm = self.config.algorithm;
+        format!("{}:{}", algorithm.name(), algorithm.digest(value))
+            .into_bytes()
+            .into()
+    }
+
+    /// Verify `value` against its encoded content `digest`.
+    fn verify(
+        &self,
+        key: &StoreKey,
+        value: &[u8],
+        digest: Option<&[u8]>,
+    ) -> Result<(), StorageError> {
+        let Some(digest) = digest else {
+            return if self.config.require_digests {
+                Err(StorageError::Other(format!(
+                    "the content digest of {key} is missing"
+                )))
+            } else {
+                Ok(())
+            };
+        };
+        let (algorithm, digest) = std::str::from_utf8(digest)
+            .ok()
+            .and_then(|digest| digest.split_once(':'))
+            .ok_or_else(|| {
+                StorageError::Other(format!("the content digest of {key} is invalid"))
+            })?;
+        let algorithm = ContentDigestAlgorithm::from_name(algorithm).ok_or_else(|| {
+            StorageError::Other(format!(
+                "the content digest algorithm {algorithm} of {key} is not supported"
+            ))
+        })?;
+        if algorithm.digest(value) == digest {
+            Ok(())
+        } else {
+            Err(StorageError::ContentDigestMismatch(key.clone()))
+        }
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
+    for ContentDigestStorageTransformerImpl<TStorage>
+{
+    fn get(&self, key: &StoreKey) -> Result<MaybeBytes, StorageError> {
+        let value = self.storage.get(key)?;
+        if let Some(value) = &value {
+            let digest = self.storage.get(&digest_key(key))?;
+            self.verify(key, value, digest.as_deref())?;
+        }
+        Ok(value)
+    }
+
+    fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<Bytes>>, StorageError> {
+        if self.config.verify_partial_reads {
+            self.get(key)?
+                .map(|value| {
+                    Ok(extract_byte_ranges(&value, byte_ranges)?
+                        .into_iter()
+                        .map(Bytes::from)
+                        .collect())
+                })
+                .transpose()
+        } else {
+            self.storage.get_partial_values_key(key, byte_ranges)
+        }
+    }
+
+    fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        self.storage.size_key(key)
+    }
+}
+
+impl<TStorage: ?Sized + ListableStorageTraits> ListableStorageTraits
+    for ContentDigestStorageTransformerImpl<TStorage>
+{
+    fn list(&self) -> Result<StoreKeys, StorageError> {
+        Ok(filter_keys(self.storage.list()?))
+    }
+
+    fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        Ok(filter_keys(self.storage.list_prefix(prefix)?))
+    }
+
+    fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        Ok(filter_keys_prefixes(self.storage.list_dir(prefix)?))
+    }
+
+    fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        let size = self.storage.size_prefix(prefix)?;
+        if CONTENT_DIGEST_PREFIX.starts_with(prefix.as_str()) {
+            Ok(size
+                - self
+                    .storage
+                    .size_prefix(&digest_prefix(&StorePrefix::root()))?)
+        } else {
+            Ok(size)
+        }
+    }
+}
+
+impl<TStorage: ?Sized + WritableStorageTraits> WritableStorageTraits
+    for ContentDigestStorageTransformerImpl<TStorage>
+{
+    fn set(&self, key: &StoreKey, value: Bytes) -> Result<(), StorageError> {
+        check_key(key)?;
+        let digest = self.encode_digest(&value);
+        self.storage.set(key, value)?;
+        self.storage.set(&digest_key(key), digest)
+    }
+
+    fn set_partial_values(
+        &self,
+        key_start_values: &[StoreKeyStartValue],
+    ) -> Result<(), StorageError> {
+        for key_start_value in key_start_values {
+            check_key(&key_start_value.key)?;
+        }
+        self.storage.set_partial_values(key_start_values)?;
+        for key in key_start_values.iter().map(|ksv| &ksv.key).unique() {
+            match self
+                .reader
+                .as_ref()
+                .map(|reader| reader.get(key))
+                .transpose()?
+            {
+                Some(Some(value)) => self
+                    .storage
+                    .set(&digest_key(key), self.encode_digest(&value))?,
+                Some(None) | None => self.storage.erase(&digest_key(key))?,
+            }
+        }
+        Ok(())
+    }
+
+    fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+        check_key(key)?;
+        self.storage.erase(key)?;
+        self.storage.erase(&digest_key(key))
+    }
+
+    fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+        self.storage.erase_prefix(prefix)?;
+        self.storage.erase_prefix(&digest_prefix(prefix))
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits> ReadableWritableStorageTraits
+    for ContentDigestStorageTransformerImpl<TStorage>
+{
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncReadableStorageTraits> AsyncReadableStorageTraits
+    for ContentDigestStorageTransformerImpl<TStorage>
+{
+    async fn get(&self, key: &StoreKey) -> Result<MaybeAsyncBytes, StorageError> {
+        let value = self.storage.get(key).await?;
+        if let Some(value) = &value {
+            let digest = self.storage.get(&digest_key(key)).await?;
+            self.verify(key, value, digest.as_deref())?;
+        }
+        Ok(value)
+    }
+
+    async fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<AsyncBytes>>, StorageError> {
+        if self.config.verify_partial_reads {
+            self.get(key)
+                .await?
+                .map(|value| {
+                    Ok(extract_byte_ranges(&value, byte_ranges)?
+                        .into_iter()
+                        .map(AsyncBytes::from)
+                        .collect())
+                })
+                .transpose()
+        } else {
+            self.storage.get_partial_values_key(key, byte_ranges).await
+        }
+    }
+
+    async fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        self.storage.size_key(key).await
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncListableStorageTraits> AsyncListableStorageTraits
+    for ContentDigestStorageTransformerImpl<TStorage>
+{
+    async fn list(&self) -> Result<StoreKeys, StorageError> {
+        Ok(filter_keys(self.storage.list().await?))
+    }
+
+    async fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        Ok(filter_keys(self.storage.list_prefix(prefix).await?))
+    }
+
+    async fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        Ok(filter_keys_prefixes(self.storage.list_dir(prefix).await?))
+    }
+
+    async fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        let size = self.storage.size_prefix(prefix).await?;
+        if CONTENT_DIGEST_PREFIX.starts_with(prefix.as_str()) {
+            Ok(size
+                - self
+                    .storage
+                    .size_prefix(&digest_prefix(&StorePrefix::root()))
+                    .await?)
+        } else {
+            Ok(size)
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncWritableStorageTraits> AsyncWritableStorageTraits
+    for ContentDigestStorageTransformerImpl<TStorage>
+{
+    async fn set(&self, key: &StoreKey, value: AsyncBytes) -> Result<(), StorageError> {
+        check_key(key)?;
+        let digest = self.encode_digest(&value);
+        self.storage.set(key, value).await?;
+        self.storage.set(&digest_key(key), digest).await
+    }
+
+    async fn set_partial_values(
+        &self,
+        key_start_values: &[StoreKeyStartValue],
+    ) -> Result<(), StorageError> {
+        for key_start_value in key_start_values {
+            check_key(&key_start_value.key)?;
+        }
+        self.storage.set_partial_values(key_start_values).await?;
+        let keys: Vec<StoreKey> = key_start_values
+            .iter()
+            .map(|key_start_value| key_start_value.key.clone())
+            .unique()
+            .collect();
+        for key in &keys {
+            let value = if let Some(reader) = &self.async_reader {
+                reader.get(key).await?
+            } else {
+                None
+            };
+            if let Some(value) = value {
+                self.storage
+                    .set(&digest_key(key), self.encode_digest(&value))
+                    .await?;
+            } else {
+                self.storage.erase(&digest_key(key)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+        check_key(key)?;
+        self.storage.erase(key).await?;
+        self.storage.erase(&digest_key(key)).await
+    }
+
+    async fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+        self.storage.erase_prefix(prefix).await?;
+        self.storage.erase_prefix(&digest_prefix(prefix)).await
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncReadableWritableStorageTraits> AsyncReadableWritableStorageTraits
+    for ContentDigestStorageTransformerImpl<TStorage>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_digest_algorithms() {
+        assert_eq!(
+            ContentDigestAlgorithm::Sha256.digest(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(ContentDigestAlgorithm::Xxh3.digest(b"").len(), 16);
+        for algorithm in [ContentDigestAlgorithm::Xxh3, ContentDigestAlgorithm::Sha256] {
+            assert_eq!(
+                ContentDigestAlgorithm::from_name(algorithm.name()),
+                Some(algorithm)
+            );
+        }
+        assert_eq!(ContentDigestAlgorithm::from_name("md5"), None);
+    }
+}
@@ -24,12 +24,21 @@ use std::sync::{
     Arc,
 };
 
-use super::StorageTransformerExtension;
+use web_time::Instant;
+
+use super::{StorageOperation, StorageTransformerExtension};
+
+mod histogram;
+
+pub use histogram::{PerformanceMetricsHistogram, PerformanceMetricsHistogramSummary};
 
 /// The performance metrics storage transformer. Accumulates metrics, such as bytes read and written.
 ///
 /// This storage transformer is for internal use and will not to be included in `storage_transformers` array metadata.
 /// It is intended to aid in testing by allowing the application to validate that metrics (e.g., bytes read/written, total read/write operations, lock requests) match expected values for specific operations.
+///
+/// Per-operation latency (in nanoseconds) and bytes transferred are also recorded in [`PerformanceMetricsHistogram`]s.
+/// These can help diagnose IO pathologies, such as many small requests or a long latency tail on a remote store.
 #[derive(Debug, Default)]
 pub struct PerformanceMetricsStorageTransformer {
     bytes_read: AtomicUsize,
@@ -37,6 +46,8 @@ pub struct PerformanceMetricsStorageTransformer {
     reads: AtomicUsize,
     writes: AtomicUsize,
     locks: AtomicUsize,
+    latency: [PerformanceMetricsHistogram; StorageOperation::ALL.len()],
+    bytes: [PerformanceMetricsHistogram; StorageOperation::ALL.len()],
 }
 
 impl PerformanceMetricsStorageTransformer {
@@ -71,6 +82,53 @@ impl PerformanceMetricsStorageTransformer {
         self.locks.load(Ordering::Relaxed)
     }
 
+    /// Returns the latency histogram of `operation`. Latencies are recorded in nanoseconds.
+    #[must_use]
+    pub fn latency_histogram(&self, operation: StorageOperation) -> &PerformanceMetricsHistogram {
+        &self.latency[operation as usize]
+    }
+
+    /// Returns the bytes histogram of `operation`.
+    ///
+    /// A value is recorded for each successful read or write operation, holding the total bytes read or written by that operation.
+    #[must_use]
+    pub fn bytes_histogram(&self, operation: StorageOperation) -> &PerformanceMetricsHistogram {
+        &self.bytes[operation as usize]
+    }
+
+    /// Returns a summary of the latency (in nanoseconds) of `operation`.
+    #[must_use]
+    pub fn latency_summary(
+        &self,
+        operation: StorageOperation,
+    ) -> PerformanceMetricsHistogramSummary {
+        self.latency_histogram(operation).summary()
+    }
+
+    /// Returns a summary of the bytes read or written per `operation`.
+    #[must_use]
+    pub fn bytes_summary(&self, operation: StorageOperation) -> PerformanceMetricsHistogramSummary {
+        self.bytes_histogram(operation).summary()
+    }
+
+    /// Clear all recorded latency and bytes histograms.
+    ///
+    /// The counters (e.g. [`bytes_read`](Self::bytes_read)) are unaffected.
+    pub fn reset_histograms(&self) {
+        for histogram in self.latency.iter().chain(&self.bytes) {
+            histogram.reset();
+        }
+    }
+
+    fn record_latency(&self, operation: StorageOperation, start: Instant) {
+        let elapsed = u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX);
+        self.latency[operation as usize].record(elapsed);
+    }
+
+    fn record_bytes(&self, operation: StorageOperation, bytes: usize) {
+        self.bytes[operation as usize].record(bytes as u64);
+    }
+
     fn create_transformer<TStorage: ?Sized>(
         self: Arc<Self>,
         storage: Arc<TStorage>,
@@ -172,7 +230,10 @@ impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
     for PerformanceMetricsStorageTransformerImpl<TStorage>
 {
     fn get(&self, key: &StoreKey) -> Result<MaybeBytes, StorageError> {
+        let start = Instant::now();
         let value = self.storage.get(key);
+        self.transformer
+            .record_latency(StorageOperation::Get, start);
         let bytes_read = value
             .as_ref()
             .map_or(0, |v| v.as_ref().map_or(0, Bytes::len));
@@ -180,6 +241,10 @@ impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
             .bytes_read
             .fetch_add(bytes_read, Ordering::Relaxed);
         self.transformer.reads.fetch_add(1, Ordering::Relaxed);
+        if value.is_ok() {
+            self.transformer
+                .record_bytes(StorageOperation::Get, bytes_read);
+        }
         value
     }
 
@@ -188,7 +253,11 @@ impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
         key: &StoreKey,
         byte_ranges: &[crate::byte_range::ByteRange],
     ) -> Result<Option<Vec<Bytes>>, StorageError> {
-        let values = self.storage.get_partial_values_key(key, byte_ranges)?;
+        let start = Instant::now();
+        let values = self.storage.get_partial_values_key(key, byte_ranges);
+        self.transformer
+            .record_latency(StorageOperation::GetPartialValuesKey, start);
+        let values = values?;
         if let Some(values) = &values {
             let bytes_read = values.iter().map(Bytes::len).sum();
             self.transformer
@@ -197,6 +266,8 @@ impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
             self.transformer
                 .reads
                 .fetch_add(byte_ranges.len(), Ordering::Relaxed);
+            self.transformer
+                .record_bytes(StorageOperation::GetPartialValuesKey, bytes_read);
         }
         Ok(values)
     }
@@ -205,7 +276,11 @@ impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
         &self,
         key_ranges: &[StoreKeyRange],
     ) -> Result<Vec<MaybeBytes>, StorageError> {
-        let values = self.storage.get_partial_values(key_ranges)?;
+        let start = Instant::now();
+        let values = self.storage.get_partial_values(key_ranges);
+        self.transformer
+            .record_latency(StorageOperation::GetPartialValues, start);
+        let values = values?;
         let bytes_read = values
             .iter()
             .map(|value| value.as_ref().map_or(0, Bytes::len))
@@ -216,11 +291,17 @@ impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
         self.transformer
             .reads
             .fetch_add(key_ranges.len(), Ordering::Relaxed);
+        self.transformer
+            .record_bytes(StorageOperation::GetPartialValues, bytes_read);
         Ok(values)
     }
 
     fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
-        self.storage.size_key(key)
+        let start = Instant::now();
+        let size = self.storage.size_key(key);
+        self.transformer
+            .record_latency(StorageOperation::SizeKey, start);
+        size
     }
 }
 
@@ -228,23 +309,43 @@ impl<TStorage: ?Sized + ListableStorageTraits> ListableStorageTraits
     for PerformanceMetricsStorageTransformerImpl<TStorage>
 {
     fn list(&self) -> Result<StoreKeys, StorageError> {
-        self.storage.list()
+        let start = Instant::now();
+        let keys = self.storage.list();
+        self.transformer
+            .record_latency(StorageOperation::List, start);
+        keys
     }
 
     fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
-        self.storage.list_prefix(prefix)
+        let start = Instant::now();
+        let keys = self.storage.list_prefix(prefix);
+        self.transformer
+            .record_latency(StorageOperation::ListPrefix, start);
+        keys
     }
 
     fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
-        self.storage.list_dir(prefix)
+        let start = Instant::now();
+        let keys_prefixes = self.storage.list_dir(prefix);
+        self.transformer
+            .record_latency(StorageOperation::ListDir, start);
+        keys_prefixes
     }
 
     fn size(&self) -> Result<u64, StorageError> {
-        self.storage.size()
+        let start = Instant::now();
+        let size = self.storage.size();
+        self.transformer
+            .record_latency(StorageOperation::Size, start);
+        size
     }
 
     fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
-        self.storage.size_prefix(prefix)
+        let start = Instant::now();
+        let size = self.storage.size_prefix(prefix);
+        self.transformer
+            .record_latency(StorageOperation::SizePrefix, start);
+        size
     }
 }
 
@@ -252,11 +353,20 @@ impl<TStorage: ?Sized + WritableStorageTraits> WritableStorageTraits
     for PerformanceMetricsStorageTransformerImpl<TStorage>
 {
     fn set(&self, key: &StoreKey, value: Bytes) -> Result<(), StorageError> {
+        let bytes_written = value.len();
         self.transformer
             .bytes_written
-            .fetch_add(value.len(), Ordering::Relaxed);
+            .fetch_add(bytes_written, Ordering::Relaxed);
         self.transformer.writes.fetch_add(1, Ordering::Relaxed);
-        self.storage.set(key, value)
+        let start = Instant::now();
+        let result = self.storage.set(key, value);
+        self.transformer
+            .record_latency(StorageOperation::Set, start);
+        if result.is_ok() {
+            self.transformer
+                .record_bytes(StorageOperation::Set, bytes_written);
+        }
+        result
     }
 
     fn set_partial_values(
@@ -273,19 +383,39 @@ impl<TStorage: ?Sized + WritableStorageTraits> WritableStorageTraits
         self.transformer
             .writes
             .fetch_add(key_start_values.len(), Ordering::Relaxed);
-        self.storage.set_partial_values(key_start_values)
+        let start = Instant::now();
+        let result = self.storage.set_partial_values(key_start_values);
+        self.transformer
+            .record_latency(StorageOperation::SetPartialValues, start);
+        if result.is_ok() {
+            self.transformer
+                .record_bytes(StorageOperation::SetPartialValues, bytes_written);
+        }
+        result
     }
 
     fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
-        self.storage.erase(key)
+        let start = Instant::now();
+        let result = self.storage.erase(key);
+        self.transformer
+            .record_latency(StorageOperation::Erase, start);
+        result
     }
 
     fn erase_values(&self, keys: &[StoreKey]) -> Result<(), StorageError> {
-        self.storage.erase_values(keys)
+        let start = Instant::now();
+        let result = self.storage.erase_values(keys);
+        self.transformer
+            .record_latency(StorageOperation::EraseValues, start);
+        result
     }
 
     fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
-        self.storage.erase_prefix(prefix)
+        let start = Instant::now();
+        let result = self.storage.erase_prefix(prefix);
+        self.transformer
+            .record_latency(StorageOperation::ErasePrefix, start);
+        result
     }
 }
 
@@ -304,7 +434,10 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits> AsyncReadableStorageTraits
     for PerformanceMetricsStorageTransformerImpl<TStorage>
 {
     async fn get(&self, key: &StoreKey) -> Result<MaybeAsyncBytes, StorageError> {
+        let start = Instant::now();
         let value = self.storage.get(key).await;
+        self.transformer
+            .record_latency(StorageOperation::Get, start);
         let bytes_read = value
             .as_ref()
             .map_or(0, |v| v.as_ref().map_or(0, AsyncBytes::len));
@@ -312,6 +445,10 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits> AsyncReadableStorageTraits
             .bytes_read
             .fetch_add(bytes_read, Ordering::Relaxed);
         self.transformer.reads.fetch_add(1, Ordering::Relaxed);
+        if value.is_ok() {
+            self.transformer
+                .record_bytes(StorageOperation::Get, bytes_read);
+        }
         value
     }
 
@@ -320,10 +457,11 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits> AsyncReadableStorageTraits
         key: &StoreKey,
         byte_ranges: &[crate::byte_range::ByteRange],
     ) -> Result<Option<Vec<AsyncBytes>>, StorageError> {
-        let values = self
-            .storage
-            .get_partial_values_key(key, byte_ranges)
-            .await?;
+        let start = Instant::now();
+        let values = self.storage.get_partial_values_key(key, byte_ranges).await;
+        self.transformer
+            .record_latency(StorageOperation::GetPartialValuesKey, start);
+        let values = values?;
         if let Some(values) = &values {
             let bytes_read = values.iter().map(AsyncBytes::len).sum();
             self.transformer
@@ -332,6 +470,8 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits> AsyncReadableStorageTraits
             self.transformer
                 .reads
                 .fetch_add(byte_ranges.len(), Ordering::Relaxed);
+            self.transformer
+                .record_bytes(StorageOperation::GetPartialValuesKey, bytes_read);
         }
         Ok(values)
     }
@@ -340,7 +480,11 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits> AsyncReadableStorageTraits
         &self,
         key_ranges: &[StoreKeyRange],
     ) -> Result<Vec<MaybeAsyncBytes>, StorageError> {
-        let values = self.storage.get_partial_values(key_ranges).await?;
+        let start = Instant::now();
+        let values = self.storage.get_partial_values(key_ranges).await;
+        self.transformer
+            .record_latency(StorageOperation::GetPartialValues, start);
+        let values = values?;
         let bytes_read = values
             .iter()
             .map(|value| value.as_ref().map_or(0, AsyncBytes::len))
@@ -351,11 +495,17 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits> AsyncReadableStorageTraits
         self.transformer
             .reads
             .fetch_add(key_ranges.len(), Ordering::Relaxed);
+        self.transformer
+            .record_bytes(StorageOperation::GetPartialValues, bytes_read);
         Ok(values)
     }
 
     async fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
-        self.storage.size_key(key).await
+        let start = Instant::now();
+        let size = self.storage.size_key(key).await;
+        self.transformer
+            .record_latency(StorageOperation::SizeKey, start);
+        size
     }
 }
 
@@ -365,23 +515,43 @@ impl<TStorage: ?Sized + AsyncListableStorageTraits> AsyncListableStorageTraits
     for PerformanceMetricsStorageTransformerImpl<TStorage>
 {
     async fn list(&self) -> Result<StoreKeys, StorageError> {
-        self.storage.list().await
+        let start = Instant::now();
+        let keys = self.storage.list().await;
+        self.transformer
+            .record_latency(StorageOperation::List, start);
+        keys
     }
 
     async fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
-        self.storage.list_prefix(prefix).await
+        let start = Instant::now();
+        let keys = self.storage.list_prefix(prefix).await;
+        self.transformer
+            .record_latency(StorageOperation::ListPrefix, start);
+        keys
     }
 
     async fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
-        self.storage.list_dir(prefix).await
+        let start = Instant::now();
+        let keys_prefixes = self.storage.list_dir(prefix).await;
+        self.transformer
+            .record_latency(StorageOperation::ListDir, start);
+        keys_prefixes
     }
 
     async fn size(&self) -> Result<u64, StorageError> {
-        self.storage.size().await
+        let start = Instant::now();
+        let size = self.storage.size().await;
+        self.transformer
+            .record_latency(StorageOperation::Size, start);
+        size
     }
 
     async fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
-        self.storage.size_prefix(prefix).await
+        let start = Instant::now();
+        let size = self.storage.size_prefix(prefix).await;
+        self.transformer
+            .record_latency(StorageOperation::SizePrefix, start);
+        size
     }
 }
 
@@ -391,11 +561,20 @@ impl<TStorage: ?Sized + AsyncWritableStorageTraits> AsyncWritableStorageTraits
     for PerformanceMetricsStorageTransformerImpl<TStorage>
 {
     async fn set(&self, key: &StoreKey, value: AsyncBytes) -> Result<(), StorageError> {
+        let bytes_written = value.len();
         self.transformer
             .bytes_written
-            .fetch_add(value.len(), Ordering::Relaxed);
+            .fetch_add(bytes_written, Ordering::Relaxed);
         self.transformer.writes.fetch_add(1, Ordering::Relaxed);
-        self.storage.set(key, value).await
+        let start = Instant::now();
+        let result = self.storage.set(key, value).await;
+        self.transformer
+            .record_latency(StorageOperation::Set, start);
+        if result.is_ok() {
+            self.transformer
+                .record_bytes(StorageOperation::Set, bytes_written);
+        }
+        result
     }
 
     async fn set_partial_values(
@@ -412,19 +591,39 @@ impl<TStorage: ?Sized + AsyncWritableStorageTraits> AsyncWritableStorageTraits
         self.transformer
             .writes
             .fetch_add(key_start_values.len(), Ordering::Relaxed);
-        self.storage.set_partial_values(key_start_values).await
+        let start = Instant::now();
+        let result = self.storage.set_partial_values(key_start_values).await;
+        self.transformer
+            .record_latency(StorageOperation::SetPartialValues, start);
+        if result.is_ok() {
+            self.transformer
+                .record_bytes(StorageOperation::SetPartialValues, bytes_written);
+        }
+        result
     }
 
     async fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
-        self.storage.erase(key).await
+        let start = Instant::now();
+        let result = self.storage.erase(key).await;
+        self.transformer
+            .record_latency(StorageOperation::Erase, start);
+        result
     }
 
     async fn erase_values(&self, keys: &[StoreKey]) -> Result<(), StorageError> {
-        self.storage.erase_values(keys).await
+        let start = Instant::now();
+        let result = self.storage.erase_values(keys).await;
+        self.transformer
+            .record_latency(StorageOperation::EraseValues, start);
+        result
     }
 
     async fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
-        self.storage.erase_prefix(prefix).await
+        let start = Instant::now();
+        let result = self.storage.erase_prefix(prefix).await;
+        self.transformer
+            .record_latency(StorageOperation::ErasePrefix, start);
+        result
     }
 }
 
@@ -0,0 +1,267 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The number of bits of sub-bucket resolution within each power of two.
+const SUB_BUCKET_BITS: u32 = 2;
+
+/// The number of sub-buckets within each power of two.
+const SUB_BUCKETS: u64 = 1 << SUB_BUCKET_BITS;
+
+/// The number of exponents (powers of two) above the linear region.
+const NUM_EXPONENTS: u32 = u64::BITS - (SUB_BUCKET_BITS + 1);
+
+/// Values below this threshold are recorded exactly.
+const LINEAR_LIMIT: u64 = SUB_BUCKETS * 2;
+
+/// The total number of buckets required to cover the full [`u64`] range.
+const NUM_BUCKETS: usize =
+    (1 << (SUB_BUCKET_BITS + 1)) + (NUM_EXPONENTS << SUB_BUCKET_BITS) as usize;
+
+/// A lock-free histogram of [`u64`] values with logarithmic buckets.
+///
+/// Each power of two is divided into four sub-buckets, so quantiles are estimated with a relative error of at most 25%.
+/// Values are linearly interpolated within a bucket and clamped to the observed minimum and maximum.
+pub struct PerformanceMetricsHistogram {
+    buckets: Box<[AtomicU64]>,
+    count: AtomicU64,
+    sum: AtomicU64,
+    min: AtomicU64,
+    max: AtomicU64,
+}
+
+impl core::fmt::Debug for PerformanceMetricsHistogram {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.summary())
+    }
+}
+
+impl Default for PerformanceMetricsHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: (0..NUM_BUCKETS).map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+            sum: AtomicU64::new(0),
+            min: AtomicU64::new(u64::MAX),
+            max: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Returns the bucket index of `value`.
+fn bucket_index(value: u64) -> usize {
+    if value < LINEAR_LIMIT {
+        usize::try_from(value).unwrap()
+    } else {
+        let exponent = value.ilog2();
+        let sub_bucket = (value >> (exponent - SUB_BUCKET_BITS)) & (SUB_BUCKETS - 1);
+        let index =
+            LINEAR_LIMIT + u64::from(exponent - (SUB_BUCKET_BITS + 1)) * SUB_BUCKETS + sub_bucket;
+        usize::try_from(index).unwrap()
+    }
+}
+
+/// Returns the inclusive lower bound and exclusive upper bound of the bucket at `index`.
+fn bucket_bounds(index: usize) -> (u64, u64) {
+    let index = index as u64;
+    if index < LINEAR_LIMIT {
+        (index, index + 1)
+    } else {
+        let exponent = (index - LINEAR_LIMIT) / SUB_BUCKETS + u64::from(SUB_BUCKET_BITS + 1);
+        let sub_bucket = (index - LINEAR_LIMIT) % SUB_BUCKETS;
+        let width = 1u64 << (exponent - u64::from(SUB_BUCKET_BITS));
+        let lower = (1u64 << exponent) + sub_bucket * width;
+        (lower, lower.saturating_add(width))
+    }
+}
+
+impl PerformanceMetricsHistogram {
+    /// Create a new empty histogram.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a value.
+    pub fn record(&self, value: u64) {
+        self.buckets[bucket_index(value)].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(value, Ordering::Relaxed);
+        self.min.fetch_min(value, Ordering::Relaxed);
+        self.max.fetch_max(value, Ordering::Relaxed);
+    }
+
+    /// Returns the number of recorded values.
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Returns the sum of recorded values.
+    pub fn sum(&self) -> u64 {
+        self.sum.load(Ordering::Relaxed)
+    }
+
+    /// Returns the minimum recorded value, or [`None`] if the histogram is empty.
+    pub fn min(&self) -> Option<u64> {
+        (self.count() > 0).then(|| self.min.load(Ordering::Relaxed))
+    }
+
+    /// Returns the maximum recorded value, or [`None`] if the histogram is empty.
+    pub fn max(&self) -> Option<u64> {
+        (self.count() > 0).then(|| self.max.load(Ordering::Relaxed))
+    }
+
+    /// Returns an estimate of the `quantile` (in the range `[0, 1]`) of the recorded values.
+    ///
+    /// Returns [`None`] if the histogram is empty.
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub fn quantile(&self, quantile: f64) -> Option<u64> {
+        let counts: Vec<u64> = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .collect();
+        let count: u64 = counts.iter().sum();
+        if count == 0 {
+            return None;
+        }
+        let (min, max) = (
+            self.min.load(Ordering::Relaxed),
+            self.max.load(Ordering::Relaxed),
+        );
+        let rank = ((quantile.clamp(0.0, 1.0) * count as f64).ceil() as u64).max(1);
+
+        let mut cumulative = 0;
+        for (index, bucket_count) in counts.into_iter().enumerate() {
+            if bucket_count == 0 {
+                continue;
+            }
+            if cumulative + bucket_count >= rank {
+                let (lower, upper) = bucket_bounds(index);
+                let fraction = (rank - cumulative) as f64 / bucket_count as f64;
+                let value = lower + ((upper - lower - 1) as f64 * fraction) as u64;
+                return Some(value.clamp(min, max));
+            }
+            cumulative += bucket_count;
+        }
+        Some(max)
+    }
+
+    /// Returns a summary of the recorded values.
+    pub fn summary(&self) -> PerformanceMetricsHistogramSummary {
+        PerformanceMetricsHistogramSummary {
+            count: self.count(),
+            sum: self.sum(),
+            min: self.min(),
+            max: self.max(),
+            p50: self.quantile(0.50),
+            p95: self.quantile(0.95),
+            p99: self.quantile(0.99),
+        }
+    }
+
+    /// Clear all recorded values.
+    pub fn reset(&self) {
+        for bucket in &*self.buckets {
+            bucket.store(0, Ordering::Relaxed);
+        }
+        self.count.store(0, Ordering::Relaxed);
+        self.sum.store(0, Ordering::Relaxed);
+        self.min.store(u64::MAX, Ordering::Relaxed);
+        self.max.store(0, Ordering::Relaxed);
+    }
+}
+
+/// A summary of a [`PerformanceMetricsHistogram`].
+///
+/// The minimum, maximum, and quantiles are [`None`] if no values were recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PerformanceMetricsHistogramSummary {
+    /// The number of recorded values.
+    pub count: u64,
+    /// The sum of recorded values.
+    pub sum: u64,
+    /// The minimum recorded value.
+    pub min: Option<u64>,
+    /// The maximum recorded value.
+    pub max: Option<u64>,
+    /// The estimated median.
+    pub p50: Option<u64>,
+    /// The estimated 95th percentile.
+    pub p95: Option<u64>,
+    /// The estimated 99th percentile.
+    pub p99: Option<u64>,
+}
+
+impl PerformanceMetricsHistogramSummary {
+    /// Returns the mean of the recorded values, or [`None`] if no values were recorded.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum as f64 / self.count as f64)
+    }
+}
+
+impl std::fmt::Display for PerformanceMetricsHistogramSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fmt = |v: Option<u64>| v.map_or_else(|| "-".to_string(), |v| v.to_string());
+        write!(
+            f,
+            "count={} min={} p50={} p95={} p99={} max={}",
+            self.count,
+            fmt(self.min),
+            fmt(self.p50),
+            fmt(self.p95),
+            fmt(self.p99),
+            fmt(self.max)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets() {
+        for value in (0..10_000).chain([u64::MAX / 2, u64::MAX - 1, u64::MAX]) {
+            let (lower, upper) = bucket_bounds(bucket_index(value));
+            assert!(lower <= value);
+            assert!(value < upper || upper == u64::MAX);
+        }
+        assert_eq!(bucket_index(u64::MAX), NUM_BUCKETS - 1);
+    }
+
+    #[test]
+    fn histogram_quantiles() {
+        let histogram = PerformanceMetricsHistogram::new();
+        assert_eq!(
+            histogram.summary(),
+            PerformanceMetricsHistogramSummary::default()
+        );
+        for value in 1..=1000 {
+            histogram.record(value);
+        }
+        let summary = histogram.summary();
+        assert_eq!(summary.count, 1000);
+        assert_eq!(summary.sum, 500_500);
+        assert_eq!(summary.min, Some(1));
+        assert_eq!(summary.max, Some(1000));
+        assert_eq!(summary.mean(), Some(500.5));
+        let within = |estimate: Option<u64>, expected: u64| {
+            let estimate = estimate.unwrap();
+            estimate.abs_diff(expected) <= expected / 4
+        };
+        assert!(within(summary.p50, 500));
+        assert!(within(summary.p95, 950));
+        assert!(within(summary.p99, 990));
+        assert_eq!(histogram.quantile(1.0), Some(1000));
+        assert_eq!(histogram.quantile(0.0), Some(1));
+
+        histogram.reset();
+        assert_eq!(histogram.count(), 0);
+        assert_eq!(histogram.quantile(0.5), None);
+    }
+}
@@ -0,0 +1,327 @@
+//! A storage transformer which rejects writes.
+
+use crate::{
+    byte_range::ByteRange,
+    metadata::v3::MetadataV3,
+    storage::{
+        Bytes, ListableStorage, ListableStorageTraits, MaybeBytes, ReadableListableStorage,
+        ReadableStorage, ReadableStorageTraits, ReadableWritableListableStorage,
+        ReadableWritableStorage, ReadableWritableStorageTraits, StorageError, StoreKey,
+        StoreKeyRange, StoreKeyStartValue, StoreKeys, StoreKeysPrefixes, StorePrefix,
+        WritableStorage, WritableStorageTraits,
+    },
+};
+
+#[cfg(feature = "async")]
+use crate::storage::{
+    AsyncBytes, AsyncListableStorage, AsyncListableStorageTraits, AsyncReadableListableStorage,
+    AsyncReadableStorage, AsyncReadableStorageTraits, AsyncReadableWritableListableStorage,
+    AsyncReadableWritableStorageTraits, AsyncWritableStorage, AsyncWritableStorageTraits,
+    MaybeAsyncBytes,
+};
+
+use std::sync::Arc;
+
+use super::StorageTransformerExtension;
+
+/// The read only storage transformer. Rejects all write operations.
+///
+/// Write operations (`set`, `set_partial_values`, `erase`, etc.) fail with [`StorageError::ReadOnly`] and are never forwarded to the underlying storage.
+/// Read and list operations are passed through unchanged.
+///
+/// This storage transformer is for internal use and will not to be included in `storage_transformers` array metadata.
+/// It is intended to guard against accidental writes when storage must be writable at the type level, e.g. within a storage transformer chain.
+/// Prefer the [`ReadOnlyStorageAdapter`](crate::storage::storage_adapter::read_only::ReadOnlyStorageAdapter) where writes can be excluded at compile time.
+#[derive(Debug, Default)]
+pub struct ReadOnlyStorageTransformer;
+
+impl ReadOnlyStorageTransformer {
+    /// Create a new read only storage transformer.
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_transformer<TStorage: ?Sized>(
+        storage: Arc<TStorage>,
+    ) -> Arc<ReadOnlyStorageTransformerImpl<TStorage>> {
+        Arc::new(ReadOnlyStorageTransformerImpl { storage })
+    }
+}
+
+impl StorageTransformerExtension for ReadOnlyStorageTransformer {
+    /// Returns [`None`], since this storage transformer is not intended to be included in array `storage_transformers` metadata.
+    fn create_metadata(&self) -> Option<MetadataV3> {
+        None
+    }
+
+    fn create_readable_transformer(self: Arc<Self>, storage: ReadableStorage) -> ReadableStorage {
+        Self::create_transformer(storage)
+    }
+
+    fn create_writable_transformer(self: Arc<Self>, storage: WritableStorage) -> WritableStorage {
+        Self::create_transformer(storage)
+    }
+
+    fn create_readable_writable_transformer(
+        self: Arc<Self>,
+        storage: ReadableWritableStorage,
+    ) -> ReadableWritableStorage {
+        Self::create_transformer(storage)
+    }
+
+    fn create_listable_transformer(self: Arc<Self>, storage: ListableStorage) -> ListableStorage {
+        Self::create_transformer(storage)
+    }
+
+    fn create_readable_listable_transformer(
+        self: Arc<Self>,
+        storage: ReadableListableStorage,
+    ) -> ReadableListableStorage {
+        Self::create_transformer(storage)
+    }
+
+    fn create_readable_writable_listable_transformer(
+        self: Arc<Self>,
+        storage: ReadableWritableListableStorage,
+    ) -> ReadableWritableListableStorage {
+        Self::create_transformer(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_readable_transformer(
+        self: Arc<Self>,
+        storage: AsyncReadableStorage,
+    ) -> AsyncReadableStorage {
+        Self::create_transformer(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_writable_transformer(
+        self: Arc<Self>,
+        storage: AsyncWritableStorage,
+    ) -> AsyncWritableStorage {
+        Self::create_transformer(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_listable_transformer(
+        self: Arc<Self>,
+        storage: AsyncListableStorage,
+    ) -> AsyncListableStorage {
+        Self::create_transformer(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_readable_listable_transformer(
+        self: Arc<Self>,
+        storage: AsyncReadableListableStorage,
+    ) -> AsyncReadableListableStorage {
+        Self::create_transformer(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_readable_writable_listable_transformer(
+        self: Arc<Self>,
+        storage: AsyncReadableWritableListableStorage,
+    ) -> AsyncReadableWritableListableStorage {
+        Self::create_transformer(storage)
+    }
+}
+
+#[derive(Debug)]
+struct ReadOnlyStorageTransformerImpl<TStorage: ?Sized> {
+    storage: Arc<TStorage>,
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
+    for ReadOnlyStorageTransformerImpl<TStorage>
+{
+    fn get(&self, key: &StoreKey) -> Result<MaybeBytes, StorageError> {
+        self.storage.get(key)
+    }
+
+    fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<Bytes>>, StorageError> {
+        self.storage.get_partial_values_key(key, byte_ranges)
+    }
+
+    fn get_partial_values(
+        &self,
+        key_ranges: &[StoreKeyRange],
+    ) -> Result<Vec<MaybeBytes>, StorageError> {
+        self.storage.get_partial_values(key_ranges)
+    }
+
+    fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        self.storage.size_key(key)
+    }
+
+    fn get_values_coalesced(
+        &self,
+        keys: &[StoreKey],
+        max_waste_ratio: f64,
+    ) -> Result<Vec<MaybeBytes>, StorageError> {
+        self.storage.get_values_coalesced(keys, max_waste_ratio)
+    }
+}
+
+impl<TStorage: ?Sized + ListableStorageTraits> ListableStorageTraits
+    for ReadOnlyStorageTransformerImpl<TStorage>
+{
+    fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.storage.list()
+    }
+
+    fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        self.storage.list_prefix(prefix)
+    }
+
+    fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        self.storage.list_dir(prefix)
+    }
+
+    fn size(&self) -> Result<u64, StorageError> {
+        self.storage.size()
+    }
+
+    fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        self.storage.size_prefix(prefix)
+    }
+}
+
+impl<TStorage: ?Sized + WritableStorageTraits> WritableStorageTraits
+    for ReadOnlyStorageTransformerImpl<TStorage>
+{
+    fn set(&self, _key: &StoreKey, _value: Bytes) -> Result<(), StorageError> {
+        Err(StorageError::ReadOnly)
+    }
+
+    fn set_partial_values(
+        &self,
+        _key_start_values: &[StoreKeyStartValue],
+    ) -> Result<(), StorageError> {
+        Err(StorageError::ReadOnly)
+    }
+
+    fn erase(&self, _key: &StoreKey) -> Result<(), StorageError> {
+        Err(StorageError::ReadOnly)
+    }
+
+    fn erase_values(&self, _keys: &[StoreKey]) -> Result<(), StorageError> {
+        Err(StorageError::ReadOnly)
+    }
+
+    fn erase_prefix(&self, _prefix: &StorePrefix) -> Result<(), StorageError> {
+        Err(StorageError::ReadOnly)
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits> ReadableWritableStorageTraits
+    for ReadOnlyStorageTransformerImpl<TStorage>
+{
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncReadableStorageTraits> AsyncReadableStorageTraits
+    for ReadOnlyStorageTransformerImpl<TStorage>
+{
+    async fn get(&self, key: &StoreKey) -> Result<MaybeAsyncBytes, StorageError> {
+        self.storage.get(key).await
+    }
+
+    async fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<AsyncBytes>>, StorageError> {
+        self.storage.get_partial_values_key(key, byte_ranges).await
+    }
+
+    async fn get_partial_values(
+        &self,
+        key_ranges: &[StoreKeyRange],
+    ) -> Result<Vec<MaybeAsyncBytes>, StorageError> {
+        self.storage.get_partial_values(key_ranges).await
+    }
+
+    async fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        self.storage.size_key(key).await
+    }
+
+    async fn get_values_coalesced(
+        &self,
+        keys: &[StoreKey],
+        max_waste_ratio: f64,
+    ) -> Result<Vec<MaybeAsyncBytes>, StorageError> {
+        self.storage
+            .get_values_coalesced(keys, max_waste_ratio)
+            .await
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncListableStorageTraits> AsyncListableStorageTraits
+    for ReadOnlyStorageTransformerImpl<TStorage>
+{
+    async fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.storage.list().await
+    }
+
+    async fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        self.storage.list_prefix(prefix).await
+    }
+
+    async fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        self.storage.list_dir(prefix).await
+    }
+
+    async fn size(&self) -> Result<u64, StorageError> {
+        self.storage.size().await
+    }
+
+    async fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        self.storage.size_prefix(prefix).await
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncWritableStorageTraits> AsyncWritableStorageTraits
+    for ReadOnlyStorageTransformerImpl<TStorage>
+{
+    async fn set(&self, _key: &StoreKey, _value: AsyncBytes) -> Result<(), StorageError> {
+        Err(StorageError::ReadOnly)
+    }
+
+    async fn set_partial_values(
+        &self,
+        _key_start_values: &[StoreKeyStartValue],
+    ) -> Result<(), StorageError> {
+        Err(StorageError::ReadOnly)
+    }
+
+    async fn erase(&self, _key: &StoreKey) -> Result<(), StorageError> {
+        Err(StorageError::ReadOnly)
+    }
+
+    async fn erase_values(&self, _keys: &[StoreKey]) -> Result<(), StorageError> {
+        Err(StorageError::ReadOnly)
+    }
+
+    async fn erase_prefix(&self, _prefix: &StorePrefix) -> Result<(), StorageError> {
+        Err(StorageError::ReadOnly)
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncReadableWritableStorageTraits> AsyncReadableWritableStorageTraits
+    for ReadOnlyStorageTransformerImpl<TStorage>
+{
+}
@@ -1,11 +1,11 @@
-//! A storage transformer which prints function calls.
+//! A storage transformer which logs function calls.
 
 use std::{
     io::Write,
     sync::{Arc, Mutex},
 };
 
-use itertools::Itertools;
+use web_time::{Instant, SystemTime};
 
 use crate::{
     byte_range::ByteRange,
@@ -27,13 +27,23 @@ use crate::storage::{
     MaybeAsyncBytes,
 };
 
-use super::StorageTransformerExtension;
+use super::{StorageOperation, StorageTransformerExtension};
+
+mod usage_log_record;
+mod usage_log_sink;
+
+pub use usage_log_record::{UsageLogRecord, UsageLogRequest, UsageLogResponse};
+pub use usage_log_sink::{UsageLogSink, UsageLogWriterSink};
 
 /// The usage log storage transformer. Logs storage method calls.
 ///
 /// This storage transformer is for internal use and will not to be included in `storage_transformers` array metadata.
 /// It is intended to aid in debugging and optimising performance by revealing storage access patterns.
 ///
+/// Each storage method call emits a [`UsageLogRecord`] to a [`UsageLogSink`].
+/// A record holds the operation, its arguments (keys, byte ranges, etc.), a summary of the response, and timing information.
+/// [`UsageLogStorageTransformer::new`] writes formatted records to a writer, whereas [`UsageLogStorageTransformer::new_with_sink`] accepts any sink.
+///
 /// ### Example (log to stdout)
 /// ```rust
 /// # use std::sync::{Arc, Mutex};
@@ -64,9 +74,23 @@ use super::StorageTransformerExtension;
 /// [23:41:19.891] get(group/array/zarr.json) -> len=Ok(1315)
 /// [23:41:19.892] list() -> [group/array/c/0/0, group/array/c/1/0, group/array/zarr.json, group/zarr.json]
 /// ```
+///
+/// ### Example (collect records)
+/// ```rust
+/// # use std::sync::{mpsc, Arc};
+/// # use zarrs::storage::{store::MemoryStore, ReadableStorageTraits};
+/// # use zarrs::storage::storage_transformer::{StorageOperation, StorageTransformerExtension, UsageLogStorageTransformer};
+/// let (sender, receiver) = mpsc::channel();
+/// let usage_log = Arc::new(UsageLogStorageTransformer::new_with_sink(Arc::new(sender)));
+/// let store = usage_log.create_readable_writable_transformer(Arc::new(MemoryStore::new()));
+/// store.get(&"zarr.json".try_into()?)?;
+/// let record = receiver.recv()?;
+/// assert_eq!(record.operation, StorageOperation::Get);
+/// assert_eq!(record.to_string(), "get(zarr.json) -> len=None");
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
 pub struct UsageLogStorageTransformer {
-    handle: Arc<Mutex<dyn Write + Send + Sync>>,
-    prefix_func: fn() -> String,
+    sink: Arc<dyn UsageLogSink>,
 }
 
 impl core::fmt::Debug for UsageLogStorageTransformer {
@@ -76,12 +100,17 @@ impl core::fmt::Debug for UsageLogStorageTransformer {
 }
 
 impl UsageLogStorageTransformer {
-    /// Create a new usage log storage transformer.
+    /// Create a new usage log storage transformer which writes formatted records to `handle`.
+    ///
+    /// Each line is prefixed by the output of `prefix_func`.
     pub fn new(handle: Arc<Mutex<dyn Write + Send + Sync>>, prefix_func: fn() -> String) -> Self {
-        Self {
-            handle,
-            prefix_func,
-        }
+        Self::new_with_sink(Arc::new(UsageLogWriterSink::new(handle, prefix_func)))
+    }
+
+    /// Create a new usage log storage transformer which emits records to `sink`.
+    #[must_use]
+    pub fn new_with_sink(sink: Arc<dyn UsageLogSink>) -> Self {
+        Self { sink }
     }
 
     fn create_transformer<TStorage: ?Sized>(
@@ -90,8 +119,7 @@ impl UsageLogStorageTransformer {
     ) -> Arc<UsageLogStorageTransformerImpl<TStorage>> {
         Arc::new(UsageLogStorageTransformerImpl {
             storage,
-            prefix_func: self.prefix_func,
-            handle: self.handle.clone(),
+            sink: self.sink.clone(),
         })
     }
 }
@@ -178,20 +206,55 @@ impl StorageTransformerExtension for UsageLogStorageTransformer {
 
 struct UsageLogStorageTransformerImpl<TStorage: ?Sized> {
     storage: Arc<TStorage>,
-    prefix_func: fn() -> String,
-    handle: Arc<Mutex<dyn Write + Send + Sync>>,
+    sink: Arc<dyn UsageLogSink>,
+}
+
+impl<TStorage: ?Sized> UsageLogStorageTransformerImpl<TStorage> {
+    /// Emit a record of a completed storage operation to the sink.
+    fn log<T>(
+        &self,
+        operation: StorageOperation,
+        request: UsageLogRequest,
+        start: (SystemTime, Instant),
+        result: &Result<T, StorageError>,
+        response: impl FnOnce(&T) -> UsageLogResponse,
+    ) -> Result<(), StorageError> {
+        let duration = start.1.elapsed();
+        self.sink.record(UsageLogRecord {
+            operation,
+            request,
+            response: result.as_ref().map(response).map_err(ToString::to_string),
+            start: start.0,
+            duration,
+        })
+    }
+}
+
+fn now() -> (SystemTime, Instant) {
+    (SystemTime::now(), Instant::now())
+}
+
+fn key_start_lengths(
+    key_start_values: &[StoreKeyStartValue],
+) -> Vec<(StoreKey, crate::byte_range::ByteOffset, usize)> {
+    key_start_values
+        .iter()
+        .map(|ksv| (ksv.key.clone(), ksv.start, ksv.value.len()))
+        .collect()
 }
 
 impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
     for UsageLogStorageTransformerImpl<TStorage>
 {
     fn get(&self, key: &StoreKey) -> Result<MaybeBytes, StorageError> {
+        let start = now();
         let result = self.storage.get(key);
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}get({key}) -> len={:?}",
-            (self.prefix_func)(),
-            result.as_ref().map(|v| v.as_ref().map_or(0, Bytes::len))
+        self.log(
+            StorageOperation::Get,
+            UsageLogRequest::Key(key.clone()),
+            start,
+            &result,
+            |v| UsageLogResponse::Length(v.as_ref().map(Bytes::len)),
         )?;
         result
     }
@@ -201,16 +264,14 @@ impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
         key: &StoreKey,
         byte_ranges: &[ByteRange],
     ) -> Result<Option<Vec<Bytes>>, StorageError> {
+        let start = now();
         let result = self.storage.get_partial_values_key(key, byte_ranges);
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}get_partial_values_key({key}, [{}]) -> len={:?}",
-            (self.prefix_func)(),
-            byte_ranges.iter().format(", "),
-            result.as_ref().map(|v| {
-                v.as_ref()
-                    .map_or(vec![], |v| v.iter().map(Bytes::len).collect_vec())
-            })
+        self.log(
+            StorageOperation::GetPartialValuesKey,
+            UsageLogRequest::KeyByteRanges(key.clone(), byte_ranges.to_vec()),
+            start,
+            &result,
+            |v| UsageLogResponse::Lengths(v.as_ref().map(|v| v.iter().map(Bytes::len).collect())),
         )?;
         result
     }
@@ -219,25 +280,31 @@ impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
         &self,
         key_ranges: &[StoreKeyRange],
     ) -> Result<Vec<MaybeBytes>, StorageError> {
+        let start = now();
         let result = self.storage.get_partial_values(key_ranges);
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}get_partial_values([{}]) -> len={:?}",
-            (self.prefix_func)(),
-            key_ranges.iter().format(", "),
-            result
-                .as_ref()
-                .map(|v| { v.iter().map(|v| v.iter().map(Bytes::len).collect_vec()) })
+        self.log(
+            StorageOperation::GetPartialValues,
+            UsageLogRequest::KeyRanges(key_ranges.to_vec()),
+            start,
+            &result,
+            |v| {
+                UsageLogResponse::MaybeLengths(
+                    v.iter().map(|v| v.as_ref().map(Bytes::len)).collect(),
+                )
+            },
         )?;
         result
     }
 
     fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        let start = now();
         let result = self.storage.size_key(key);
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}size_key({key}) -> {result:?}",
-            (self.prefix_func)()
+        self.log(
+            StorageOperation::SizeKey,
+            UsageLogRequest::Key(key.clone()),
+            start,
+            &result,
+            |v| UsageLogResponse::Size(*v),
         )?;
         result
     }
@@ -247,63 +314,66 @@ impl<TStorage: ?Sized + ListableStorageTraits> ListableStorageTraits
     for UsageLogStorageTransformerImpl<TStorage>
 {
     fn list(&self) -> Result<StoreKeys, StorageError> {
+        let start = now();
         let result = self.storage.list();
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}list() -> [{}]",
-            (self.prefix_func)(),
-            result.as_ref().unwrap_or(&vec![]).iter().format(", ")
+        self.log(
+            StorageOperation::List,
+            UsageLogRequest::None,
+            start,
+            &result,
+            |v| UsageLogResponse::Keys(v.clone()),
         )?;
         result
     }
 
     fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        let start = now();
         let result = self.storage.list_prefix(prefix);
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}list_prefix({prefix}) -> [{}]",
-            (self.prefix_func)(),
-            result.as_ref().unwrap_or(&vec![]).iter().format(", ")
+        self.log(
+            StorageOperation::ListPrefix,
+            UsageLogRequest::Prefix(prefix.clone()),
+            start,
+            &result,
+            |v| UsageLogResponse::Keys(v.clone()),
         )?;
         result
     }
 
     fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        let start = now();
         let result = self.storage.list_dir(prefix);
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}list_dir({prefix}) -> (keys:[{}], prefixes:[{}])",
-            (self.prefix_func)(),
-            result.as_ref().map_or(String::new(), |skp| skp
-                .keys()
-                .iter()
-                .format(", ")
-                .to_string()),
-            result.as_ref().map_or(String::new(), |skp| skp
-                .prefixes()
-                .iter()
-                .format(", ")
-                .to_string()),
+        self.log(
+            StorageOperation::ListDir,
+            UsageLogRequest::Prefix(prefix.clone()),
+            start,
+            &result,
+            |v| UsageLogResponse::KeysPrefixes(v.clone()),
         )?;
         result
     }
 
     fn size(&self) -> Result<u64, StorageError> {
+        let start = now();
         let result = self.storage.size();
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}size() -> {result:?}",
-            (self.prefix_func)()
+        self.log(
+            StorageOperation::Size,
+            UsageLogRequest::None,
+            start,
+            &result,
+            |v| UsageLogResponse::Size(Some(*v)),
         )?;
         result
     }
 
     fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
-        let result: Result<u64, StorageError> = self.storage.size_prefix(prefix);
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}size_prefix({prefix}) -> {result:?}",
-            (self.prefix_func)()
+        let start = now();
+        let result = self.storage.size_prefix(prefix);
+        self.log(
+            StorageOperation::SizePrefix,
+            UsageLogRequest::Prefix(prefix.clone()),
+            start,
+            &result,
+            |v| UsageLogResponse::Size(Some(*v)),
         )?;
         result
     }
@@ -314,12 +384,14 @@ impl<TStorage: ?Sized + WritableStorageTraits> WritableStorageTraits
 {
     fn set(&self, key: &StoreKey, value: Bytes) -> Result<(), StorageError> {
         let len = value.len();
+        let start = now();
         let result = self.storage.set(key, value);
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}set({key}, len={}) -> {result:?}",
-            (self.prefix_func)(),
-            len
+        self.log(
+            StorageOperation::Set,
+            UsageLogRequest::KeyLength(key.clone(), len),
+            start,
+            &result,
+            |()| UsageLogResponse::None,
         )?;
         result
     }
@@ -328,42 +400,53 @@ impl<TStorage: ?Sized + WritableStorageTraits> WritableStorageTraits
         &self,
         key_start_values: &[StoreKeyStartValue],
     ) -> Result<(), StorageError> {
+        let start = now();
         let result = self.storage.set_partial_values(key_start_values);
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}set_partial_values({key_start_values:?}) -> {result:?}",
-            (self.prefix_func)()
+        self.log(
+            StorageOperation::SetPartialValues,
+            UsageLogRequest::KeyStartLengths(key_start_lengths(key_start_values)),
+            start,
+            &result,
+            |()| UsageLogResponse::None,
         )?;
         result
     }
 
     fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+        let start = now();
         let result = self.storage.erase(key);
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}erase({key}) -> {result:?}",
-            (self.prefix_func)()
+        self.log(
+            StorageOperation::Erase,
+            UsageLogRequest::Key(key.clone()),
+            start,
+            &result,
+            |()| UsageLogResponse::None,
         )?;
         result
     }
 
     fn erase_values(&self, keys: &[StoreKey]) -> Result<(), StorageError> {
+        let start = now();
         let result = self.storage.erase_values(keys);
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}erase_values([{}]) -> {result:?}",
-            keys.iter().format(", "),
-            (self.prefix_func)()
+        self.log(
+            StorageOperation::EraseValues,
+            UsageLogRequest::Keys(keys.to_vec()),
+            start,
+            &result,
+            |()| UsageLogResponse::None,
         )?;
         result
     }
 
     fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+        let start = now();
         let result = self.storage.erase_prefix(prefix);
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}erase_prefix({prefix}) -> {result:?}",
-            (self.prefix_func)()
+        self.log(
+            StorageOperation::ErasePrefix,
+            UsageLogRequest::Prefix(prefix.clone()),
+            start,
+            &result,
+            |()| UsageLogResponse::None,
         )?;
         result
     }
@@ -372,16 +455,6 @@ impl<TStorage: ?Sized + WritableStorageTraits> WritableStorageTraits
 impl<TStorage: ?Sized + ReadableWritableStorageTraits> ReadableWritableStorageTraits
     for UsageLogStorageTransformerImpl<TStorage>
 {
-    // fn mutex(&self, key: &StoreKey) -> Result<StoreKeyMutex, StorageError> {
-    //     let result = self.storage.mutex(key);
-    //     writeln!(
-    //         self.handle.lock().unwrap(),
-    //         "{}mutex({key}) -> {:?}",
-    //         (self.prefix_func)(),
-    //         result.as_ref().map_or((), |_| ())
-    //     )?;
-    //     result
-    // }
 }
 
 #[cfg(feature = "async")]
@@ -390,14 +463,14 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits> AsyncReadableStorageTraits
     for UsageLogStorageTransformerImpl<TStorage>
 {
     async fn get(&self, key: &StoreKey) -> Result<MaybeAsyncBytes, StorageError> {
+        let start = now();
         let result = self.storage.get(key).await;
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}get({key}) -> len={:?}",
-            (self.prefix_func)(),
-            result
-                .as_ref()
-                .map(|v| v.as_ref().map_or(0, AsyncBytes::len))
+        self.log(
+            StorageOperation::Get,
+            UsageLogRequest::Key(key.clone()),
+            start,
+            &result,
+            |v| UsageLogResponse::Length(v.as_ref().map(AsyncBytes::len)),
         )?;
         result
     }
@@ -407,16 +480,18 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits> AsyncReadableStorageTraits
         key: &StoreKey,
         byte_ranges: &[ByteRange],
     ) -> Result<Option<Vec<AsyncBytes>>, StorageError> {
+        let start = now();
         let result = self.storage.get_partial_values_key(key, byte_ranges).await;
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}get_partial_values_key({key}, [{}]) -> len={:?}",
-            (self.prefix_func)(),
-            byte_ranges.iter().format(", "),
-            result.as_ref().map(|v| {
-                v.as_ref()
-                    .map_or(vec![], |v| v.iter().map(AsyncBytes::len).collect_vec())
-            })
+        self.log(
+            StorageOperation::GetPartialValuesKey,
+            UsageLogRequest::KeyByteRanges(key.clone(), byte_ranges.to_vec()),
+            start,
+            &result,
+            |v| {
+                UsageLogResponse::Lengths(
+                    v.as_ref().map(|v| v.iter().map(AsyncBytes::len).collect()),
+                )
+            },
         )?;
         result
     }
@@ -425,26 +500,31 @@ impl<TStorage: ?Sized + AsyncReadableStorageTraits> AsyncReadableStorageTraits
         &self,
         key_ranges: &[StoreKeyRange],
     ) -> Result<Vec<MaybeAsyncBytes>, StorageError> {
+        let start = now();
         let result = self.storage.get_partial_values(key_ranges).await;
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}get_partial_values([{}]) -> len={:?}",
-            (self.prefix_func)(),
-            key_ranges.iter().format(", "),
-            result.as_ref().map(|v| {
-                v.iter()
-                    .map(|v| v.iter().map(AsyncBytes::len).collect_vec())
-            })
+        self.log(
+            StorageOperation::GetPartialValues,
+            UsageLogRequest::KeyRanges(key_ranges.to_vec()),
+            start,
+            &result,
+            |v| {
+                UsageLogResponse::MaybeLengths(
+                    v.iter().map(|v| v.as_ref().map(AsyncBytes::len)).collect(),
+                )
+            },
         )?;
         result
     }
 
     async fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        let start = now();
         let result = self.storage.size_key(key).await;
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}size_key({key}) -> {result:?}",
-            (self.prefix_func)()
+        self.log(
+            StorageOperation::SizeKey,
+            UsageLogRequest::Key(key.clone()),
+            start,
+            &result,
+            |v| UsageLogResponse::Size(*v),
         )?;
         result
     }
@@ -456,63 +536,66 @@ impl<TStorage: ?Sized + AsyncListableStorageTraits> AsyncListableStorageTraits
     for UsageLogStorageTransformerImpl<TStorage>
 {
     async fn list(&self) -> Result<StoreKeys, StorageError> {
-        let keys = self.storage.list().await;
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}list() -> [{}]",
-            (self.prefix_func)(),
-            keys.as_ref().unwrap_or(&vec![]).iter().format(", "),
+        let start = now();
+        let result = self.storage.list().await;
+        self.log(
+            StorageOperation::List,
+            UsageLogRequest::None,
+            start,
+            &result,
+            |v| UsageLogResponse::Keys(v.clone()),
         )?;
-        keys
+        result
     }
 
     async fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        let start = now();
         let result = self.storage.list_prefix(prefix).await;
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}list_prefix({prefix}) -> [{}]",
-            (self.prefix_func)(),
-            result.as_ref().unwrap_or(&vec![]).iter().format(", ")
+        self.log(
+            StorageOperation::ListPrefix,
+            UsageLogRequest::Prefix(prefix.clone()),
+            start,
+            &result,
+            |v| UsageLogResponse::Keys(v.clone()),
         )?;
         result
     }
 
     async fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        let start = now();
         let result = self.storage.list_dir(prefix).await;
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}list_dir({prefix}) -> (keys:[{}], prefixes:[{}])",
-            (self.prefix_func)(),
-            result.as_ref().map_or(String::new(), |skp| skp
-                .keys()
-                .iter()
-                .format(", ")
-                .to_string()),
-            result.as_ref().map_or(String::new(), |skp| skp
-                .prefixes()
-                .iter()
-                .format(", ")
-                .to_string()),
+        self.log(
+            StorageOperation::ListDir,
+            UsageLogRequest::Prefix(prefix.clone()),
+            start,
+            &result,
+            |v| UsageLogResponse::KeysPrefixes(v.clone()),
         )?;
         result
     }
 
     async fn size(&self) -> Result<u64, StorageError> {
+        let start = now();
         let result = self.storage.size().await;
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}size() -> {result:?}",
-            (self.prefix_func)()
+        self.log(
+            StorageOperation::Size,
+            UsageLogRequest::None,
+            start,
+            &result,
+            |v| UsageLogResponse::Size(Some(*v)),
         )?;
         result
     }
 
     async fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
-        let result: Result<u64, StorageError> = self.storage.size_prefix(prefix).await;
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}size_prefix({prefix}) -> {result:?}",
-            (self.prefix_func)()
+        let start = now();
+        let result = self.storage.size_prefix(prefix).await;
+        self.log(
+            StorageOperation::SizePrefix,
+            UsageLogRequest::Prefix(prefix.clone()),
+            start,
+            &result,
+            |v| UsageLogResponse::Size(Some(*v)),
         )?;
         result
     }
@@ -525,11 +608,14 @@ impl<TStorage: ?Sized + AsyncWritableStorageTraits> AsyncWritableStorageTraits
 {
     async fn set(&self, key: &StoreKey, value: AsyncBytes) -> Result<(), StorageError> {
         let len = value.len();
+        let start = now();
         let result = self.storage.set(key, value).await;
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}set({key}, len={len}) -> {result:?}",
-            (self.prefix_func)()
+        self.log(
+            StorageOperation::Set,
+            UsageLogRequest::KeyLength(key.clone(), len),
+            start,
+            &result,
+            |()| UsageLogResponse::None,
         )?;
         result
     }
@@ -538,42 +624,53 @@ impl<TStorage: ?Sized + AsyncWritableStorageTraits> AsyncWritableStorageTraits
         &self,
         key_start_values: &[StoreKeyStartValue],
     ) -> Result<(), StorageError> {
+        let start = now();
         let result = self.storage.set_partial_values(key_start_values).await;
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}set_partial_values({key_start_values:?}) -> {result:?}",
-            (self.prefix_func)()
+        self.log(
+            StorageOperation::SetPartialValues,
+            UsageLogRequest::KeyStartLengths(key_start_lengths(key_start_values)),
+            start,
+            &result,
+            |()| UsageLogResponse::None,
         )?;
         result
     }
 
     async fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+        let start = now();
         let result = self.storage.erase(key).await;
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}erase({key}) -> {result:?}",
-            (self.prefix_func)()
+        self.log(
+            StorageOperation::Erase,
+            UsageLogRequest::Key(key.clone()),
+            start,
+            &result,
+            |()| UsageLogResponse::None,
         )?;
         result
     }
 
     async fn erase_values(&self, keys: &[StoreKey]) -> Result<(), StorageError> {
+        let start = now();
         let result = self.storage.erase_values(keys).await;
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}erase_values([{}]) -> {result:?}",
-            (self.prefix_func)(),
-            keys.iter().format(", ")
+        self.log(
+            StorageOperation::EraseValues,
+            UsageLogRequest::Keys(keys.to_vec()),
+            start,
+            &result,
+            |()| UsageLogResponse::None,
         )?;
         result
     }
 
     async fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+        let start = now();
         let result = self.storage.erase_prefix(prefix).await;
-        writeln!(
-            self.handle.lock().unwrap(),
-            "{}erase_prefix({prefix}) -> {result:?}",
-            (self.prefix_func)()
+        self.log(
+            StorageOperation::ErasePrefix,
+            UsageLogRequest::Prefix(prefix.clone()),
+            start,
+            &result,
+            |()| UsageLogResponse::None,
         )?;
         result
     }
@@ -584,14 +681,4 @@ impl<TStorage: ?Sized + AsyncWritableStorageTraits> AsyncWritableStorageTraits
 impl<TStorage: ?Sized + AsyncReadableWritableStorageTraits> AsyncReadableWritableStorageTraits
     for UsageLogStorageTransformerImpl<TStorage>
 {
-    // async fn mutex(&self, key: &StoreKey) -> Result<AsyncStoreKeyMutex, StorageError> {
-    //     let result = self.storage.mutex(key).await;
-    //     writeln!(
-    //         self.handle.lock().unwrap(),
-    //         "{}mutex({key}) -> {:?}",
-    //         (self.prefix_func)(),
-    //         result.as_ref().map_or((), |_| ())
-    //     )?;
-    //     result
-    // }
 }
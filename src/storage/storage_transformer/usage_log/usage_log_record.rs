@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use itertools::Itertools;
+use web_time::SystemTime;
+
+use crate::{
+    byte_range::{ByteOffset, ByteRange},
+    storage::{
+        storage_transformer::StorageOperation, StoreKey, StoreKeyRange, StoreKeys,
+        StoreKeysPrefixes, StorePrefix,
+    },
+};
+
+/// A structured record of a storage operation emitted by the [`UsageLogStorageTransformer`](super::UsageLogStorageTransformer).
+#[derive(Debug, Clone)]
+pub struct UsageLogRecord {
+    /// The storage operation.
+    pub operation: StorageOperation,
+    /// The arguments of the request.
+    pub request: UsageLogRequest,
+    /// A summary of the response, or the error message if the operation failed.
+    pub response: Result<UsageLogResponse, String>,
+    /// The time the operation started.
+    pub start: SystemTime,
+    /// The duration of the operation.
+    pub duration: Duration,
+}
+
+impl std::fmt::Display for UsageLogRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}({}) -> ", self.operation, self.request)?;
+        match &self.response {
+            Ok(response) => write!(f, "{response}"),
+            Err(err) => write!(f, "Err({err})"),
+        }
+    }
+}
+
+/// The arguments of a storage request in a [`UsageLogRecord`].
+///
+/// Values being written are not retained, only their lengths.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum UsageLogRequest {
+    /// No arguments.
+    None,
+    /// A store key.
+    Key(StoreKey),
+    /// A store key and a list of byte ranges.
+    KeyByteRanges(StoreKey, Vec<ByteRange>),
+    /// A list of store key ranges.
+    KeyRanges(Vec<StoreKeyRange>),
+    /// A store key and the length of the value being written.
+    KeyLength(StoreKey, usize),
+    /// A list of store keys, offsets, and the lengths of the values being written.
+    KeyStartLengths(Vec<(StoreKey, ByteOffset, usize)>),
+    /// A list of store keys.
+    Keys(StoreKeys),
+    /// A store prefix.
+    Prefix(StorePrefix),
+}
+
+impl std::fmt::Display for UsageLogRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => Ok(()),
+            Self::Key(key) => write!(f, "{key}"),
+            Self::KeyByteRanges(key, byte_ranges) => {
+                write!(f, "{key}, [{}]", byte_ranges.iter().format(", "))
+            }
+            Self::KeyRanges(key_ranges) => write!(f, "[{}]", key_ranges.iter().format(", ")),
+            Self::KeyLength(key, len) => write!(f, "{key}, len={len}"),
+            Self::KeyStartLengths(key_start_lengths) => write!(
+                f,
+                "[{}]",
+                key_start_lengths
+                    .iter()
+                    .format_with(", ", |(key, start, len), f| f(&format_args!(
+                        "{key}:{start}, len={len}"
+                    )))
+            ),
+            Self::Keys(keys) => write!(f, "[{}]", keys.iter().format(", ")),
+            Self::Prefix(prefix) => write!(f, "{prefix}"),
+        }
+    }
+}
+
+/// A summary of the response to a storage request in a [`UsageLogRecord`].
+///
+/// Values being read are not retained, only their lengths.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum UsageLogResponse {
+    /// No response.
+    None,
+    /// The length of a value, or [`None`] if the key was not found.
+    Length(Option<usize>),
+    /// The lengths of a list of byte ranges, or [`None`] if the key was not found.
+    Lengths(Option<Vec<usize>>),
+    /// The lengths of a list of values, [`None`] where a key was not found.
+    MaybeLengths(Vec<Option<usize>>),
+    /// A size in bytes, or [`None`] if the key was not found.
+    Size(Option<u64>),
+    /// A list of store keys.
+    Keys(StoreKeys),
+    /// A list of store keys and prefixes.
+    KeysPrefixes(StoreKeysPrefixes),
+}
+
+impl std::fmt::Display for UsageLogResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "Ok(())"),
+            Self::Length(len) => write!(f, "len={len:?}"),
+            Self::Lengths(lens) => write!(f, "len={lens:?}"),
+            Self::MaybeLengths(lens) => write!(f, "len={lens:?}"),
+            Self::Size(size) => write!(f, "{size:?}"),
+            Self::Keys(keys) => write!(f, "[{}]", keys.iter().format(", ")),
+            Self::KeysPrefixes(keys_prefixes) => write!(
+                f,
+                "(keys:[{}], prefixes:[{}])",
+                keys_prefixes.keys().iter().format(", "),
+                keys_prefixes.prefixes().iter().format(", ")
+            ),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::{
+    io::Write,
+    sync::{mpsc, Arc, Mutex},
+};
+
+use crate::storage::StorageError;
+
+use super::UsageLogRecord;
+
+/// A destination for the [`UsageLogRecord`]s emitted by the [`UsageLogStorageTransformer`](super::UsageLogStorageTransformer).
+///
+/// Implement this trait to forward records to a channel, a file, a tracing subscriber, etc.
+pub trait UsageLogSink: Send + Sync {
+    /// Handle a usage log record.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if the record cannot be handled.
+    /// The error is propagated to the caller of the storage operation.
+    fn record(&self, record: UsageLogRecord) -> Result<(), StorageError>;
+}
+
+/// A usage log sink which writes a formatted line per record to a writer.
+///
+/// Each line is the output of a prefix function followed by the [`Display`](std::fmt::Display) representation of the [`UsageLogRecord`].
+pub struct UsageLogWriterSink {
+    handle: Arc<Mutex<dyn Write + Send + Sync>>,
+    prefix_func: fn() -> String,
+}
+
+impl core::fmt::Debug for UsageLogWriterSink {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "usage log writer sink")
+    }
+}
+
+impl UsageLogWriterSink {
+    /// Create a new usage log writer sink.
+    pub fn new(handle: Arc<Mutex<dyn Write + Send + Sync>>, prefix_func: fn() -> String) -> Self {
+        Self {
+            handle,
+            prefix_func,
+        }
+    }
+}
+
+impl UsageLogSink for UsageLogWriterSink {
+    fn record(&self, record: UsageLogRecord) -> Result<(), StorageError> {
+        writeln!(
+            self.handle.lock().unwrap(),
+            "{}{record}",
+            (self.prefix_func)()
+        )?;
+        Ok(())
+    }
+}
+
+/// Sends records to a channel. A disconnected receiver is ignored.
+impl UsageLogSink for mpsc::Sender<UsageLogRecord> {
+    fn record(&self, record: UsageLogRecord) -> Result<(), StorageError> {
+        let _ = self.send(record);
+        Ok(())
+    }
+}
+
+/// Sends records to a channel, blocking if the buffer is full. A disconnected receiver is ignored.
+impl UsageLogSink for mpsc::SyncSender<UsageLogRecord> {
+    fn record(&self, record: UsageLogRecord) -> Result<(), StorageError> {
+        let _ = self.send(record);
+        Ok(())
+    }
+}
+
+/// Collects records in memory, e.g. for offline replay.
+impl UsageLogSink for Mutex<Vec<UsageLogRecord>> {
+    fn record(&self, record: UsageLogRecord) -> Result<(), StorageError> {
+        self.lock().unwrap().push(record);
+        Ok(())
+    }
+}
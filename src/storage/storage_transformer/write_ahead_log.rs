@@ -0,0 +1,861 @@
+//! A storage transformer which journals multi-key mutations to a write-ahead log.
+
+use crate::{
+    byte_range::ByteRange,
+    metadata::v3::MetadataV3,
+    storage::{
+        Bytes, ListableStorage, ListableStorageTraits, MaybeBytes, ReadableListableStorage,
+        ReadableStorage, ReadableStorageTraits, ReadableWritableListableStorage,
+        ReadableWritableListableStorageTraits, ReadableWritableStorage,
+        ReadableWritableStorageTraits, StorageError, StorageHandle, StoreKey, StoreKeyRange,
+        StoreKeyStartValue, StoreKeys, StoreKeysPrefixes, StorePrefix, WritableStorage,
+        WritableStorageTraits,
+    },
+};
+
+#[cfg(feature = "async")]
+use crate::storage::{
+    AsyncBytes, AsyncListableStorage, AsyncListableStorageTraits, AsyncReadableListableStorage,
+    AsyncReadableStorage, AsyncReadableStorageTraits, AsyncReadableWritableListableStorage,
+    AsyncReadableWritableListableStorageTraits, AsyncReadableWritableStorageTraits,
+    AsyncWritableStorage, AsyncWritableStorageTraits, MaybeAsyncBytes,
+};
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::SystemTime,
+};
+
+use parking_lot::Mutex;
+
+use super::StorageTransformerExtension;
+
+/// The prefix under which write-ahead log records are stored.
+///
+/// Each journaled mutation is stored as a record at `__zarrs_wal/{timestamp}-{process id}-{counter}` until it has been applied.
+/// Node discovery skips prefixes starting with `__`, and the prefix is hidden from listing through the transformer.
+pub const WRITE_AHEAD_LOG_PREFIX: &str = "__zarrs_wal/";
+
+/// The magic bytes at the start of a write-ahead log record.
+const RECORD_MAGIC: &[u8; 4] = b"ZWAL";
+
+const TAG_SET_PARTIAL: u8 = 0;
+const TAG_ERASE: u8 = 1;
+const TAG_ERASE_PREFIX: u8 = 2;
+
+/// The write-ahead log storage transformer. Journals multi-key mutations before applying them, giving crash-consistent writes on stores without atomic multi-key operations.
+///
+/// Mutations spanning multiple keys or writes are journaled:
+///  - [`set_partial_values`](WritableStorageTraits::set_partial_values) with more than one write (e.g. a shard and its index),
+///  - [`erase_values`](WritableStorageTraits::erase_values) with more than one key (e.g. the metadata of a node), and
+///  - [`erase_prefix`](WritableStorageTraits::erase_prefix).
+///
+/// A journaled mutation is written as a record under [`WRITE_AHEAD_LOG_PREFIX`], applied to the underlying storage, and then the record is erased.
+/// If the process crashes while a mutation is being applied, the record remains and the mutation is completed by replaying it when the storage is next opened through the transformer.
+/// Records are replayed before the first operation through the transformer, or explicitly with [`replay`](WriteAheadLogStorageTransformer::replay).
+/// A record which was not completely written is discarded, since its mutation was never applied.
+/// Single value writes and erases are passed through, since their atomicity is up to the store.
+///
+/// Journaling requires readable, writable, and listable storage.
+/// Writes through a transformer created for other storage (e.g. with [`create_writable_transformer`](StorageTransformerExtension::create_writable_transformer)) fail with [`StorageError::Unsupported`] and are never forwarded to the underlying storage.
+/// Records are replayed regardless of which process wrote them, so storage should not be written through multiple write-ahead log transformers concurrently.
+///
+/// This storage transformer is for internal use and will not to be included in `storage_transformers` array metadata.
+#[derive(Debug, Default)]
+pub struct WriteAheadLogStorageTransformer;
+
+impl WriteAheadLogStorageTransformer {
+    /// Create a new write-ahead log storage transformer.
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Replay the write-ahead log records in `storage` and return the number of replayed mutations.
+    ///
+    /// Incomplete records are discarded and not counted.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying error with the store.
+    pub fn replay<TStorage: ?Sized + ReadableWritableListableStorageTraits>(
+        storage: &TStorage,
+    ) -> Result<usize, StorageError> {
+        let mut record_keys = storage.list_prefix(&wal_root())?;
+        record_keys.sort();
+        let mut replayed = 0;
+        for record_key in &record_keys {
+            let operations = storage
+                .get(record_key)?
+                .and_then(|record| decode_record(&record));
+            if let Some(operations) = operations {
+                for operation in &operations {
+                    match operation {
+                        WalOperation::SetPartial { key, start, value } => storage
+                            .set_partial_values(&[StoreKeyStartValue::new(
+                                key.clone(),
+                                *start,
+                                value,
+                            )])?,
+                        WalOperation::Erase(key) => storage.erase(key)?,
+                        WalOperation::ErasePrefix(prefix) => storage.erase_prefix(prefix)?,
+                    }
+                }
+                replayed += 1;
+            }
+            storage.erase(record_key)?;
+        }
+        Ok(replayed)
+    }
+
+    #[cfg(feature = "async")]
+    /// Async variant of [`replay`](WriteAheadLogStorageTransformer::replay).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_replay<TStorage: ?Sized + AsyncReadableWritableListableStorageTraits>(
+        storage: &TStorage,
+    ) -> Result<usize, StorageError> {
+        let mut record_keys = storage.list_prefix(&wal_root()).await?;
+        record_keys.sort();
+        let mut replayed = 0;
+        for record_key in &record_keys {
+            let operations = storage
+                .get(record_key)
+                .await?
+                .and_then(|record| decode_record(&record));
+            if let Some(operations) = operations {
+                for operation in &operations {
+                    match operation {
+                        WalOperation::SetPartial { key, start, value } => {
+                            storage
+                                .set_partial_values(&[StoreKeyStartValue::new(
+                                    key.clone(),
+                                    *start,
+                                    value,
+                                )])
+                                .await?;
+                        }
+                        WalOperation::Erase(key) => storage.erase(key).await?,
+                        WalOperation::ErasePrefix(prefix) => storage.erase_prefix(prefix).await?,
+                    }
+                }
+                replayed += 1;
+            }
+            storage.erase(record_key).await?;
+        }
+        Ok(replayed)
+    }
+
+    fn create_transformer<TStorage: ?Sized>(
+        storage: Arc<TStorage>,
+    ) -> Arc<WriteAheadLogStorageTransformerImpl<TStorage>> {
+        Arc::new(WriteAheadLogStorageTransformerImpl {
+            storage,
+            journal: None,
+            replayed: Mutex::new(false),
+            #[cfg(feature = "async")]
+            async_journal: None,
+            #[cfg(feature = "async")]
+            async_replayed: async_lock::Mutex::new(false),
+        })
+    }
+
+    fn create_transformer_with_journal<
+        TStorage: ?Sized + ReadableWritableListableStorageTraits + 'static,
+    >(
+        storage: Arc<TStorage>,
+    ) -> Arc<WriteAheadLogStorageTransformerImpl<TStorage>> {
+        Arc::new(WriteAheadLogStorageTransformerImpl {
+            journal: Some(Arc::new(StorageHandle::new(storage.clone()))),
+            storage,
+            replayed: Mutex::new(false),
+            #[cfg(feature = "async")]
+            async_journal: None,
+            #[cfg(feature = "async")]
+            async_replayed: async_lock::Mutex::new(false),
+        })
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_transformer_with_journal<
+        TStorage: ?Sized + AsyncReadableWritableListableStorageTraits + 'static,
+    >(
+        storage: Arc<TStorage>,
+    ) -> Arc<WriteAheadLogStorageTransformerImpl<TStorage>> {
+        Arc::new(WriteAheadLogStorageTransformerImpl {
+            async_journal: Some(Arc::new(StorageHandle::new(storage.clone()))),
+            storage,
+            journal: None,
+            replayed: Mutex::new(false),
+            async_replayed: async_lock::Mutex::new(false),
+        })
+    }
+}
+
+impl StorageTransformerExtension for WriteAheadLogStorageTransformer {
+    /// Returns [`None`], since this storage transformer is not intended to be included in array `storage_transformers` metadata.
+    fn create_metadata(&self) -> Option<MetadataV3> {
+        None
+    }
+
+    fn create_readable_transformer(self: Arc<Self>, storage: ReadableStorage) -> ReadableStorage {
+        Self::create_transformer(storage)
+    }
+
+    fn create_writable_transformer(self: Arc<Self>, storage: WritableStorage) -> WritableStorage {
+        Self::create_transformer(storage)
+    }
+
+    fn create_readable_writable_transformer(
+        self: Arc<Self>,
+        storage: ReadableWritableStorage,
+    ) -> ReadableWritableStorage {
+        Self::create_transformer(storage)
+    }
+
+    fn create_listable_transformer(self: Arc<Self>, storage: ListableStorage) -> ListableStorage {
+        Self::create_transformer(storage)
+    }
+
+    fn create_readable_listable_transformer(
+        self: Arc<Self>,
+        storage: ReadableListableStorage,
+    ) -> ReadableListableStorage {
+        Self::create_transformer(storage)
+    }
+
+    fn create_readable_writable_listable_transformer(
+        self: Arc<Self>,
+        storage: ReadableWritableListableStorage,
+    ) -> ReadableWritableListableStorage {
+        Self::create_transformer_with_journal(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_readable_transformer(
+        self: Arc<Self>,
+        storage: AsyncReadableStorage,
+    ) -> AsyncReadableStorage {
+        Self::create_transformer(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_writable_transformer(
+        self: Arc<Self>,
+        storage: AsyncWritableStorage,
+    ) -> AsyncWritableStorage {
+        Self::create_transformer(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_listable_transformer(
+        self: Arc<Self>,
+        storage: AsyncListableStorage,
+    ) -> AsyncListableStorage {
+        Self::create_transformer(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_readable_listable_transformer(
+        self: Arc<Self>,
+        storage: AsyncReadableListableStorage,
+    ) -> AsyncReadableListableStorage {
+        Self::create_transformer(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_readable_writable_listable_transformer(
+        self: Arc<Self>,
+        storage: AsyncReadableWritableListableStorage,
+    ) -> AsyncReadableWritableListableStorage {
+        Self::create_async_transformer_with_journal(storage)
+    }
+}
+
+/// A mutation decoded from a write-ahead log record.
+#[derive(Debug, PartialEq)]
+enum WalOperation {
+    SetPartial {
+        key: StoreKey,
+        start: u64,
+        value: Vec<u8>,
+    },
+    Erase(StoreKey),
+    ErasePrefix(StorePrefix),
+}
+
+/// Encodes mutations as a write-ahead log record.
+///
+/// A record is the [`RECORD_MAGIC`], the number of operations, and then each operation as a tag followed by its length-prefixed fields.
+/// Integers are little endian `u64`.
+#[derive(Default)]
+struct WalRecordEncoder {
+    count: u64,
+    body: Vec<u8>,
+}
+
+impl WalRecordEncoder {
+    fn push_u64(&mut self, value: u64) {
+        self.body.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        self.push_u64(bytes.len() as u64);
+        self.body.extend_from_slice(bytes);
+    }
+
+    fn set_partial(&mut self, key_start_value: &StoreKeyStartValue) {
+        self.count += 1;
+        self.body.push(TAG_SET_PARTIAL);
+        self.push_bytes(key_start_value.key.as_str().as_bytes());
+        self.push_u64(key_start_value.start);
+        self.push_bytes(key_start_value.value);
+    }
+
+    fn erase(&mut self, key: &StoreKey) {
+        self.count += 1;
+        self.body.push(TAG_ERASE);
+        self.push_bytes(key.as_str().as_bytes());
+    }
+
+    fn erase_prefix(&mut self, prefix: &StorePrefix) {
+        self.count += 1;
+        self.body.push(TAG_ERASE_PREFIX);
+        self.push_bytes(prefix.as_str().as_bytes());
+    }
+
+    fn finish(self) -> Bytes {
+        let mut record = Vec::with_capacity(RECORD_MAGIC.len() + 8 + self.body.len());
+        record.extend_from_slice(RECORD_MAGIC);
+        record.extend_from_slice(&self.count.to_le_bytes());
+        record.extend_from_slice(&self.body);
+        record.into()
+    }
+}
+
+/// Decodes the fields of a write-ahead log record.
+struct WalRecordDecoder<'a>(&'a [u8]);
+
+impl<'a> WalRecordDecoder<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (bytes, remaining) = self.0.split_at(len);
+        self.0 = remaining;
+        Some(bytes)
+    }
+
+    fn take_u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn take_bytes(&mut self) -> Option<&'a [u8]> {
+        let len = usize::try_from(self.take_u64()?).ok()?;
+        self.take(len)
+    }
+
+    fn take_string(&mut self) -> Option<String> {
+        String::from_utf8(self.take_bytes()?.to_vec()).ok()
+    }
+}
+
+/// Decode a write-ahead log record. Returns [`None`] if the record is incomplete or invalid.
+fn decode_record(record: &[u8]) -> Option<Vec<WalOperation>> {
+    let mut decoder = WalRecordDecoder(record);
+    if decoder.take(RECORD_MAGIC.len())? != RECORD_MAGIC {
+        return None;
+    }
+    let count = decoder.take_u64()?;
+    let mut operations = Vec::new();
+    for _ in 0..count {
+        let operation = match decoder.take(1)?[0] {
+            TAG_SET_PARTIAL => WalOperation::SetPartial {
+                key: StoreKey::new(decoder.take_string()?).ok()?,
+                start: decoder.take_u64()?,
+                value: decoder.take_bytes()?.to_vec(),
+            },
+            TAG_ERASE => WalOperation::Erase(StoreKey::new(decoder.take_string()?).ok()?),
+            TAG_ERASE_PREFIX => {
+                WalOperation::ErasePrefix(StorePrefix::new(decoder.take_string()?).ok()?)
+            }
+            _ => return None,
+        };
+        operations.push(operation);
+    }
+    decoder.0.is_empty().then_some(operations)
+}
+
+fn wal_root() -> StorePrefix {
+    unsafe { StorePrefix::new_unchecked(WRITE_AHEAD_LOG_PREFIX.to_string()) }
+}
+
+/// Returns a new unique write-ahead log record key, ordered by creation time.
+fn new_record_key() -> StoreKey {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos());
+    unsafe {
+        StoreKey::new_unchecked(format!(
+            "{WRITE_AHEAD_LOG_PREFIX}{nanos:020}-{}-{:020}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ))
+    }
+}
+
+fn is_wal_key(key: &StoreKey) -> bool {
+    key.as_str().starts_with(WRITE_AHEAD_LOG_PREFIX)
+}
+
+fn check_key(key: &StoreKey) -> Result<(), StorageError> {
+    if is_wal_key(key) {
+        Err(StorageError::Other(format!(
+            "{key} is reserved by the write-ahead log storage transformer"
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn filter_keys(keys: StoreKeys) -> StoreKeys {
+    keys.into_iter().filter(|key| !is_wal_key(key)).collect()
+}
+
+fn filter_keys_prefixes(keys_prefixes: StoreKeysPrefixes) -> StoreKeysPrefixes {
+    StoreKeysPrefixes {
+        keys: keys_prefixes.keys,
+        prefixes: keys_prefixes
+            .prefixes
+            .into_iter()
+            .filter(|prefix| prefix.as_str() != WRITE_AHEAD_LOG_PREFIX)
+            .collect(),
+    }
+}
+
+fn unjournaled_write_error() -> StorageError {
+    StorageError::Unsupported(
+        "the write-ahead log storage transformer requires readable, writable, and listable storage"
+            .to_string(),
+    )
+}
+
+struct WriteAheadLogStorageTransformerImpl<TStorage: ?Sized> {
+    storage: Arc<TStorage>,
+    /// A readable, writable, and listable handle to `storage`, used to journal and replay mutations.
+    journal: Option<ReadableWritableListableStorage>,
+    /// True if the write-ahead log has been replayed.
+    replayed: Mutex<bool>,
+    /// An asynchronous readable, writable, and listable handle to `storage`, used to journal and replay mutations.
+    #[cfg(feature = "async")]
+    async_journal: Option<AsyncReadableWritableListableStorage>,
+    /// True if the write-ahead log has been replayed asynchronously.
+    #[cfg(feature = "async")]
+    async_replayed: async_lock::Mutex<bool>,
+}
+
+impl<TStorage: ?Sized> WriteAheadLogStorageTransformerImpl<TStorage> {
+    /// Replay the write-ahead log if it has not been replayed.
+    fn ensure_replayed(&self) -> Result<(), StorageError> {
+        if let Some(journal) = &self.journal {
+            let mut replayed = self.replayed.lock();
+            if !*replayed {
+                WriteAheadLogStorageTransformer::replay(journal.as_ref())?;
+                *replayed = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the journal after replaying the write-ahead log.
+    fn journal_replayed(&self) -> Result<&ReadableWritableListableStorage, StorageError> {
+        let journal = self.journal.as_ref().ok_or_else(unjournaled_write_error)?;
+        self.ensure_replayed()?;
+        Ok(journal)
+    }
+
+    /// Journal the mutation `record`, apply it with `apply`, and then erase the record.
+    fn journaled(
+        &self,
+        record: WalRecordEncoder,
+        apply: impl FnOnce() -> Result<(), StorageError>,
+    ) -> Result<(), StorageError> {
+        let journal = self.journal_replayed()?;
+        let record_key = new_record_key();
+        journal.set(&record_key, record.finish())?;
+        apply()?;
+        journal.erase(&record_key)
+    }
+
+    #[cfg(feature = "async")]
+    /// Async variant of [`ensure_replayed`](Self::ensure_replayed).
+    async fn async_ensure_replayed(&self) -> Result<(), StorageError> {
+        if let Some(journal) = &self.async_journal {
+            let mut replayed = self.async_replayed.lock().await;
+            if !*replayed {
+                WriteAheadLogStorageTransformer::async_replay(journal.as_ref()).await?;
+                *replayed = true;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    /// Async variant of [`journal_replayed`](Self::journal_replayed).
+    async fn async_journal_replayed(
+        &self,
+    ) -> Result<&AsyncReadableWritableListableStorage, StorageError> {
+        let journal = self
+            .async_journal
+            .as_ref()
+            .ok_or_else(unjournaled_write_error)?;
+        self.async_ensure_replayed().await?;
+        Ok(journal)
+    }
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
+    for WriteAheadLogStorageTransformerImpl<TStorage>
+{
+    fn get(&self, key: &StoreKey) -> Result<MaybeBytes, StorageError> {
+        self.ensure_replayed()?;
+        self.storage.get(key)
+    }
+
+    fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<Bytes>>, StorageError> {
+        self.ensure_replayed()?;
+        self.storage.get_partial_values_key(key, byte_ranges)
+    }
+
+    fn get_partial_values(
+        &self,
+        key_ranges: &[StoreKeyRange],
+    ) -> Result<Vec<MaybeBytes>, StorageError> {
+        self.ensure_replayed()?;
+        self.storage.get_partial_values(key_ranges)
+    }
+
+    fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        self.ensure_replayed()?;
+        self.storage.size_key(key)
+    }
+}
+
+impl<TStorage: ?Sized + ListableStorageTraits> ListableStorageTraits
+    for WriteAheadLogStorageTransformerImpl<TStorage>
+{
+    fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.ensure_replayed()?;
+        Ok(filter_keys(self.storage.list()?))
+    }
+
+    fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        self.ensure_replayed()?;
+        Ok(filter_keys(self.storage.list_prefix(prefix)?))
+    }
+
+    fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        self.ensure_replayed()?;
+        Ok(filter_keys_prefixes(self.storage.list_dir(prefix)?))
+    }
+
+    fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        self.ensure_replayed()?;
+        let size = self.storage.size_prefix(prefix)?;
+        if WRITE_AHEAD_LOG_PREFIX.starts_with(prefix.as_str()) {
+            Ok(size - self.storage.size_prefix(&wal_root())?)
+        } else {
+            Ok(size)
+        }
+    }
+}
+
+impl<TStorage: ?Sized + WritableStorageTraits> WritableStorageTraits
+    for WriteAheadLogStorageTransformerImpl<TStorage>
+{
+    fn set(&self, key: &StoreKey, value: Bytes) -> Result<(), StorageError> {
+        check_key(key)?;
+        self.journal_replayed()?;
+        self.storage.set(key, value)
+    }
+
+    fn set_partial_values(
+        &self,
+        key_start_values: &[StoreKeyStartValue],
+    ) -> Result<(), StorageError> {
+        for key_start_value in key_start_values {
+            check_key(&key_start_value.key)?;
+        }
+        if key_start_values.len() > 1 {
+            let mut record = WalRecordEncoder::default();
+            for key_start_value in key_start_values {
+                record.set_partial(key_start_value);
+            }
+            self.journaled(record, || self.storage.set_partial_values(key_start_values))
+        } else {
+            self.journal_replayed()?;
+            self.storage.set_partial_values(key_start_values)
+        }
+    }
+
+    fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+        check_key(key)?;
+        self.journal_replayed()?;
+        self.storage.erase(key)
+    }
+
+    fn erase_values(&self, keys: &[StoreKey]) -> Result<(), StorageError> {
+        for key in keys {
+            check_key(key)?;
+        }
+        if keys.len() > 1 {
+            let mut record = WalRecordEncoder::default();
+            for key in keys {
+                record.erase(key);
+            }
+            self.journaled(record, || self.storage.erase_values(keys))
+        } else {
+            self.journal_replayed()?;
+            self.storage.erase_values(keys)
+        }
+    }
+
+    fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+        let mut record = WalRecordEncoder::default();
+        record.erase_prefix(prefix);
+        self.journaled(record, || self.storage.erase_prefix(prefix))
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits> ReadableWritableStorageTraits
+    for WriteAheadLogStorageTransformerImpl<TStorage>
+{
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncReadableStorageTraits> AsyncReadableStorageTraits
+    for WriteAheadLogStorageTransformerImpl<TStorage>
+{
+    async fn get(&self, key: &StoreKey) -> Result<MaybeAsyncBytes, StorageError> {
+        self.async_ensure_replayed().await?;
+        self.storage.get(key).await
+    }
+
+    async fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<AsyncBytes>>, StorageError> {
+        self.async_ensure_replayed().await?;
+        self.storage.get_partial_values_key(key, byte_ranges).await
+    }
+
+    async fn get_partial_values(
+        &self,
+        key_ranges: &[StoreKeyRange],
+    ) -> Result<Vec<MaybeAsyncBytes>, StorageError> {
+        self.async_ensure_replayed().await?;
+        self.storage.get_partial_values(key_ranges).await
+    }
+
+    async fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        self.async_ensure_replayed().await?;
+        self.storage.size_key(key).await
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncListableStorageTraits> AsyncListableStorageTraits
+    for WriteAheadLogStorageTransformerImpl<TStorage>
+{
+    async fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.async_ensure_replayed().await?;
+        Ok(filter_keys(self.storage.list().await?))
+    }
+
+    async fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        self.async_ensure_replayed().await?;
+        Ok(filter_keys(self.storage.list_prefix(prefix).await?))
+    }
+
+    async fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        self.async_ensure_replayed().await?;
+        Ok(filter_keys_prefixes(self.storage.list_dir(prefix).await?))
+    }
+
+    async fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        self.async_ensure_replayed().await?;
+        let size = self.storage.size_prefix(prefix).await?;
+        if WRITE_AHEAD_LOG_PREFIX.starts_with(prefix.as_str()) {
+            Ok(size - self.storage.size_prefix(&wal_root()).await?)
+        } else {
+            Ok(size)
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncWritableStorageTraits> AsyncWritableStorageTraits
+    for WriteAheadLogStorageTransformerImpl<TStorage>
+{
+    async fn set(&self, key: &StoreKey, value: AsyncBytes) -> Result<(), StorageError> {
+        check_key(key)?;
+        self.async_journal_replayed().await?;
+        self.storage.set(key, value).await
+    }
+
+    async fn set_partial_values(
+        &self,
+        key_start_values: &[StoreKeyStartValue],
+    ) -> Result<(), StorageError> {
+        for key_start_value in key_start_values {
+            check_key(&key_start_value.key)?;
+        }
+        let journal = self.async_journal_replayed().await?;
+        if key_start_values.len() > 1 {
+            let mut record = WalRecordEncoder::default();
+            for key_start_value in key_start_values {
+                record.set_partial(key_start_value);
+            }
+            let record_key = new_record_key();
+            journal.set(&record_key, record.finish()).await?;
+            self.storage.set_partial_values(key_start_values).await?;
+            journal.erase(&record_key).await
+        } else {
+            self.storage.set_partial_values(key_start_values).await
+        }
+    }
+
+    async fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+        check_key(key)?;
+        self.async_journal_replayed().await?;
+        self.storage.erase(key).await
+    }
+
+    async fn erase_values(&self, keys: &[StoreKey]) -> Result<(), StorageError> {
+        for key in keys {
+            check_key(key)?;
+        }
+        let journal = self.async_journal_replayed().await?;
+        if keys.len() > 1 {
+            let mut record = WalRecordEncoder::default();
+            for key in keys {
+                record.erase(key);
+            }
+            let record_key = new_record_key();
+            journal.set(&record_key, record.finish()).await?;
+            self.storage.erase_values(keys).await?;
+            journal.erase(&record_key).await
+        } else {
+            self.storage.erase_values(keys).await
+        }
+    }
+
+    async fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+        let journal = self.async_journal_replayed().await?;
+        let mut record = WalRecordEncoder::default();
+        record.erase_prefix(prefix);
+        let record_key = new_record_key();
+        journal.set(&record_key, record.finish()).await?;
+        self.storage.erase_prefix(prefix).await?;
+        journal.erase(&record_key).await
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncReadableWritableStorageTraits> AsyncReadableWritableStorageTraits
+    for WriteAheadLogStorageTransformerImpl<TStorage>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::store::MemoryStore;
+
+    use super::*;
+
+    #[test]
+    fn write_ahead_log_records() {
+        let mut record = WalRecordEncoder::default();
+        record.set_partial(&StoreKeyStartValue::new(
+            "a".try_into().unwrap(),
+            2,
+            &[1, 2],
+        ));
+        record.erase(&"b".try_into().unwrap());
+        record.erase_prefix(&"c/".try_into().unwrap());
+        let record = record.finish();
+        assert_eq!(
+            decode_record(&record),
+            Some(vec![
+                WalOperation::SetPartial {
+                    key: "a".try_into().unwrap(),
+                    start: 2,
+                    value: vec![1, 2]
+                },
+                WalOperation::Erase("b".try_into().unwrap()),
+                WalOperation::ErasePrefix("c/".try_into().unwrap()),
+            ])
+        );
+        // Incomplete records are invalid
+        assert_eq!(decode_record(&record[..record.len() - 1]), None);
+        assert_eq!(decode_record(&[]), None);
+    }
+
+    #[test]
+    fn write_ahead_log_replay() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Arc::new(MemoryStore::new());
+        store.set(&"a".try_into()?, vec![0, 0, 0, 0].into())?;
+        store.set(&"b".try_into()?, vec![0].into())?;
+        store.set(&"c/d".try_into()?, vec![0].into())?;
+
+        // Simulate a crash after journaling a mutation, and while journaling another
+        let mut record = WalRecordEncoder::default();
+        record.set_partial(&StoreKeyStartValue::new("a".try_into()?, 2, &[1, 2]));
+        record.erase(&"b".try_into()?);
+        store.set(&new_record_key(), record.finish())?;
+        let mut record = WalRecordEncoder::default();
+        record.erase_prefix(&"c/".try_into()?);
+        let record = record.finish();
+        store.set(&new_record_key(), record.slice(..record.len() - 1))?;
+
+        let transformer = Arc::new(WriteAheadLogStorageTransformer::new())
+            .create_readable_writable_listable_transformer(store.clone());
+        assert_eq!(
+            transformer.get(&"a".try_into()?)?,
+            Some(vec![0, 0, 1, 2].into())
+        );
+        assert_eq!(transformer.get(&"b".try_into()?)?, None);
+        assert_eq!(transformer.get(&"c/d".try_into()?)?, Some(vec![0].into()));
+        assert!(store.list_prefix(&wal_root())?.is_empty());
+
+        // Journaled mutations leave no records
+        transformer.set_partial_values(&[
+            StoreKeyStartValue::new("a".try_into()?, 0, &[3]),
+            StoreKeyStartValue::new("c/d".try_into()?, 0, &[3]),
+        ])?;
+        transformer.erase_prefix(&"c/".try_into()?)?;
+        assert_eq!(transformer.list()?, &["a".try_into()?]);
+        assert!(store.list_prefix(&wal_root())?.is_empty());
+        assert!(transformer.set(&new_record_key(), vec![].into()).is_err());
+
+        // Writes to storage which is not listable are not journaled
+        let transformer_writable = Arc::new(WriteAheadLogStorageTransformer::new())
+            .create_writable_transformer(store.clone());
+        assert!(matches!(
+            transformer_writable.set(&"a".try_into()?, vec![0].into()),
+            Err(StorageError::Unsupported(_))
+        ));
+        Ok(())
+    }
+}
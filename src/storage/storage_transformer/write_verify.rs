@@ -0,0 +1,419 @@
+//! A storage transformer which reads back and verifies written values.
+
+use crate::{
+    byte_range::ByteRange,
+    metadata::v3::MetadataV3,
+    storage::{
+        Bytes, ListableStorage, ListableStorageTraits, MaybeBytes, ReadableListableStorage,
+        ReadableStorage, ReadableStorageTraits, ReadableWritableListableStorage,
+        ReadableWritableStorage, ReadableWritableStorageTraits, StorageError, StorageHandle,
+        StoreKey, StoreKeyRange, StoreKeyStartValue, StoreKeys, StoreKeysPrefixes, StorePrefix,
+        WritableStorage, WritableStorageTraits,
+    },
+};
+
+#[cfg(feature = "async")]
+use crate::storage::{
+    AsyncBytes, AsyncListableStorage, AsyncListableStorageTraits, AsyncReadableListableStorage,
+    AsyncReadableStorage, AsyncReadableStorageTraits, AsyncReadableWritableListableStorage,
+    AsyncReadableWritableStorageTraits, AsyncWritableStorage, AsyncWritableStorageTraits,
+    MaybeAsyncBytes,
+};
+
+use std::sync::Arc;
+
+use super::StorageTransformerExtension;
+
+/// The write verify storage transformer. Reads back every written value and verifies it before reporting success.
+///
+/// A value written with `set` is retrieved after it is stored and compared byte-for-byte with the value that was written.
+/// The byte ranges written with `set_partial_values` are retrieved and compared in the same way.
+/// A mismatch fails with [`StorageError::WriteVerificationFailed`], so silent corruption in transit (e.g. an upload to a remote store) is reported as a write error.
+///
+/// Writes require readable storage, since they cannot otherwise be verified.
+/// Writes through a transformer created for write-only storage (e.g. with [`create_writable_transformer`](StorageTransformerExtension::create_writable_transformer)) fail with [`StorageError::Unsupported`] and are never forwarded to the underlying storage.
+/// Read, list, and erase operations are passed through unchanged.
+///
+/// Verification doubles the requests and transferred bytes of every write.
+/// Stores with a read cache or eventually consistent reads may not detect corruption, as the read back value may not come from the underlying storage medium.
+///
+/// This storage transformer is for internal use and will not to be included in `storage_transformers` array metadata.
+#[derive(Debug, Default)]
+pub struct WriteVerifyStorageTransformer;
+
+impl WriteVerifyStorageTransformer {
+    /// Create a new write verify storage transformer.
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_transformer<TStorage: ?Sized>(
+        storage: Arc<TStorage>,
+    ) -> Arc<WriteVerifyStorageTransformerImpl<TStorage>> {
+        Arc::new(WriteVerifyStorageTransformerImpl {
+            storage,
+            reader: None,
+            #[cfg(feature = "async")]
+            async_reader: None,
+        })
+    }
+
+    fn create_transformer_with_reader<TStorage: ?Sized + ReadableStorageTraits + 'static>(
+        storage: Arc<TStorage>,
+    ) -> Arc<WriteVerifyStorageTransformerImpl<TStorage>> {
+        Arc::new(WriteVerifyStorageTransformerImpl {
+            reader: Some(Arc::new(StorageHandle::new(storage.clone()))),
+            storage,
+            #[cfg(feature = "async")]
+            async_reader: None,
+        })
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_transformer_with_reader<
+        TStorage: ?Sized + AsyncReadableStorageTraits + 'static,
+    >(
+        storage: Arc<TStorage>,
+    ) -> Arc<WriteVerifyStorageTransformerImpl<TStorage>> {
+        Arc::new(WriteVerifyStorageTransformerImpl {
+            async_reader: Some(Arc::new(StorageHandle::new(storage.clone()))),
+            storage,
+            reader: None,
+        })
+    }
+}
+
+impl StorageTransformerExtension for WriteVerifyStorageTransformer {
+    /// Returns [`None`], since this storage transformer is not intended to be included in array `storage_transformers` metadata.
+    fn create_metadata(&self) -> Option<MetadataV3> {
+        None
+    }
+
+    fn create_readable_transformer(self: Arc<Self>, storage: ReadableStorage) -> ReadableStorage {
+        Self::create_transformer(storage)
+    }
+
+    fn create_writable_transformer(self: Arc<Self>, storage: WritableStorage) -> WritableStorage {
+        Self::create_transformer(storage)
+    }
+
+    fn create_readable_writable_transformer(
+        self: Arc<Self>,
+        storage: ReadableWritableStorage,
+    ) -> ReadableWritableStorage {
+        Self::create_transformer_with_reader(storage)
+    }
+
+    fn create_listable_transformer(self: Arc<Self>, storage: ListableStorage) -> ListableStorage {
+        Self::create_transformer(storage)
+    }
+
+    fn create_readable_listable_transformer(
+        self: Arc<Self>,
+        storage: ReadableListableStorage,
+    ) -> ReadableListableStorage {
+        Self::create_transformer(storage)
+    }
+
+    fn create_readable_writable_listable_transformer(
+        self: Arc<Self>,
+        storage: ReadableWritableListableStorage,
+    ) -> ReadableWritableListableStorage {
+        Self::create_transformer_with_reader(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_readable_transformer(
+        self: Arc<Self>,
+        storage: AsyncReadableStorage,
+    ) -> AsyncReadableStorage {
+        Self::create_transformer(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_writable_transformer(
+        self: Arc<Self>,
+        storage: AsyncWritableStorage,
+    ) -> AsyncWritableStorage {
+        Self::create_transformer(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_listable_transformer(
+        self: Arc<Self>,
+        storage: AsyncListableStorage,
+    ) -> AsyncListableStorage {
+        Self::create_transformer(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_readable_listable_transformer(
+        self: Arc<Self>,
+        storage: AsyncReadableListableStorage,
+    ) -> AsyncReadableListableStorage {
+        Self::create_transformer(storage)
+    }
+
+    #[cfg(feature = "async")]
+    fn create_async_readable_writable_listable_transformer(
+        self: Arc<Self>,
+        storage: AsyncReadableWritableListableStorage,
+    ) -> AsyncReadableWritableListableStorage {
+        Self::create_async_transformer_with_reader(storage)
+    }
+}
+
+struct WriteVerifyStorageTransformerImpl<TStorage: ?Sized> {
+    storage: Arc<TStorage>,
+    /// A readable handle to `storage`, used to read back written values.
+    reader: Option<ReadableStorage>,
+    /// An asynchronous readable handle to `storage`, used to read back written values.
+    #[cfg(feature = "async")]
+    async_reader: Option<AsyncReadableStorage>,
+}
+
+fn unverifiable_write_error() -> StorageError {
+    StorageError::Unsupported(
+        "the write verify storage transformer requires readable storage to verify writes"
+            .to_string(),
+    )
+}
+
+/// Verify that the value read back from `key` matches the `expected` value.
+fn verify(key: &StoreKey, expected: &[u8], actual: Option<&[u8]>) -> Result<(), StorageError> {
+    if actual == Some(expected) {
+        Ok(())
+    } else {
+        Err(StorageError::WriteVerificationFailed(key.clone()))
+    }
+}
+
+/// Returns the byte range written by `key_start_value`.
+fn written_byte_range(key_start_value: &StoreKeyStartValue) -> ByteRange {
+    ByteRange::FromStart(
+        key_start_value.start,
+        Some(key_start_value.value.len() as u64),
+    )
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits> ReadableStorageTraits
+    for WriteVerifyStorageTransformerImpl<TStorage>
+{
+    fn get(&self, key: &StoreKey) -> Result<MaybeBytes, StorageError> {
+        self.storage.get(key)
+    }
+
+    fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<Bytes>>, StorageError> {
+        self.storage.get_partial_values_key(key, byte_ranges)
+    }
+
+    fn get_partial_values(
+        &self,
+        key_ranges: &[StoreKeyRange],
+    ) -> Result<Vec<MaybeBytes>, StorageError> {
+        self.storage.get_partial_values(key_ranges)
+    }
+
+    fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        self.storage.size_key(key)
+    }
+
+    fn get_values_coalesced(
+        &self,
+        keys: &[StoreKey],
+        max_waste_ratio: f64,
+    ) -> Result<Vec<MaybeBytes>, StorageError> {
+        self.storage.get_values_coalesced(keys, max_waste_ratio)
+    }
+}
+
+impl<TStorage: ?Sized + ListableStorageTraits> ListableStorageTraits
+    for WriteVerifyStorageTransformerImpl<TStorage>
+{
+    fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.storage.list()
+    }
+
+    fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        self.storage.list_prefix(prefix)
+    }
+
+    fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        self.storage.list_dir(prefix)
+    }
+
+    fn size(&self) -> Result<u64, StorageError> {
+        self.storage.size()
+    }
+
+    fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        self.storage.size_prefix(prefix)
+    }
+}
+
+impl<TStorage: ?Sized + WritableStorageTraits> WritableStorageTraits
+    for WriteVerifyStorageTransformerImpl<TStorage>
+{
+    fn set(&self, key: &StoreKey, value: Bytes) -> Result<(), StorageError> {
+        let reader = self.reader.as_ref().ok_or_else(unverifiable_write_error)?;
+        self.storage.set(key, value.clone())?;
+        verify(key, &value, reader.get(key)?.as_deref())
+    }
+
+    fn set_partial_values(
+        &self,
+        key_start_values: &[StoreKeyStartValue],
+    ) -> Result<(), StorageError> {
+        let reader = self.reader.as_ref().ok_or_else(unverifiable_write_error)?;
+        self.storage.set_partial_values(key_start_values)?;
+        for key_start_value in key_start_values {
+            let key = &key_start_value.key;
+            let written = reader
+                .get_partial_values_key(key, &[written_byte_range(key_start_value)])?
+                .map(|mut written| written.remove(0));
+            verify(key, key_start_value.value, written.as_deref())?;
+        }
+        Ok(())
+    }
+
+    fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+        self.storage.erase(key)
+    }
+
+    fn erase_values(&self, keys: &[StoreKey]) -> Result<(), StorageError> {
+        self.storage.erase_values(keys)
+    }
+
+    fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+        self.storage.erase_prefix(prefix)
+    }
+}
+
+impl<TStorage: ?Sized + ReadableWritableStorageTraits> ReadableWritableStorageTraits
+    for WriteVerifyStorageTransformerImpl<TStorage>
+{
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncReadableStorageTraits> AsyncReadableStorageTraits
+    for WriteVerifyStorageTransformerImpl<TStorage>
+{
+    async fn get(&self, key: &StoreKey) -> Result<MaybeAsyncBytes, StorageError> {
+        self.storage.get(key).await
+    }
+
+    async fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<AsyncBytes>>, StorageError> {
+        self.storage.get_partial_values_key(key, byte_ranges).await
+    }
+
+    async fn get_partial_values(
+        &self,
+        key_ranges: &[StoreKeyRange],
+    ) -> Result<Vec<MaybeAsyncBytes>, StorageError> {
+        self.storage.get_partial_values(key_ranges).await
+    }
+
+    async fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        self.storage.size_key(key).await
+    }
+
+    async fn get_values_coalesced(
+        &self,
+        keys: &[StoreKey],
+        max_waste_ratio: f64,
+    ) -> Result<Vec<MaybeAsyncBytes>, StorageError> {
+        self.storage
+            .get_values_coalesced(keys, max_waste_ratio)
+            .await
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncListableStorageTraits> AsyncListableStorageTraits
+    for WriteVerifyStorageTransformerImpl<TStorage>
+{
+    async fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.storage.list().await
+    }
+
+    async fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        self.storage.list_prefix(prefix).await
+    }
+
+    async fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        self.storage.list_dir(prefix).await
+    }
+
+    async fn size(&self) -> Result<u64, StorageError> {
+        self.storage.size().await
+    }
+
+    async fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        self.storage.size_prefix(prefix).await
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncWritableStorageTraits> AsyncWritableStorageTraits
+    for WriteVerifyStorageTransformerImpl<TStorage>
+{
+    async fn set(&self, key: &StoreKey, value: AsyncBytes) -> Result<(), StorageError> {
+        let reader = self
+            .async_reader
+            .as_ref()
+            .ok_or_else(unverifiable_write_error)?;
+        self.storage.set(key, value.clone()).await?;
+        verify(key, &value, reader.get(key).await?.as_deref())
+    }
+
+    async fn set_partial_values(
+        &self,
+        key_start_values: &[StoreKeyStartValue],
+    ) -> Result<(), StorageError> {
+        let reader = self
+            .async_reader
+            .as_ref()
+            .ok_or_else(unverifiable_write_error)?;
+        self.storage.set_partial_values(key_start_values).await?;
+        for key_start_value in key_start_values {
+            let key = &key_start_value.key;
+            let written = reader
+                .get_partial_values_key(key, &[written_byte_range(key_start_value)])
+                .await?
+                .map(|mut written| written.remove(0));
+            verify(key, key_start_value.value, written.as_deref())?;
+        }
+        Ok(())
+    }
+
+    async fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+        self.storage.erase(key).await
+    }
+
+    async fn erase_values(&self, keys: &[StoreKey]) -> Result<(), StorageError> {
+        self.storage.erase_values(keys).await
+    }
+
+    async fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+        self.storage.erase_prefix(prefix).await
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncReadableWritableStorageTraits> AsyncReadableWritableStorageTraits
+    for WriteVerifyStorageTransformerImpl<TStorage>
+{
+}
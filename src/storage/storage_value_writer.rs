@@ -0,0 +1,105 @@
+use super::{Bytes, StorageError, StoreKey, WritableStorageTraits};
+
+#[cfg(feature = "async")]
+use super::{AsyncBytes, AsyncWritableStorageTraits};
+
+/// A writer which streams a value to a store.
+///
+/// Bytes passed to [`write`](StoreValueWriter::write) are appended to the value.
+/// The value is only guaranteed to be stored once the writer is [finished](StoreValueWriter::finish).
+/// Dropping a writer without finishing it abandons the write.
+pub trait StoreValueWriter: Send {
+    /// Append `bytes` to the value.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    fn write(&mut self, bytes: Bytes) -> Result<(), StorageError>;
+
+    /// Finish writing and store the value.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    fn finish(self: Box<Self>) -> Result<(), StorageError>;
+}
+
+/// An async writer which streams a value to a store.
+///
+/// See [`StoreValueWriter`].
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+pub trait AsyncStoreValueWriter: Send {
+    /// Append `bytes` to the value.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    async fn write(&mut self, bytes: AsyncBytes) -> Result<(), StorageError>;
+
+    /// Finish writing and store the value.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    async fn finish(self: Box<Self>) -> Result<(), StorageError>;
+}
+
+/// A store value writer which buffers the value in memory and stores it with a single `set` on finish.
+///
+/// This is the default writer of stores that do not support streaming writes.
+pub struct BufferedStoreValueWriter<'a, TStorage: ?Sized> {
+    storage: &'a TStorage,
+    key: StoreKey,
+    buffer: Vec<Bytes>,
+}
+
+impl<'a, TStorage: ?Sized> BufferedStoreValueWriter<'a, TStorage> {
+    /// Create a new buffered writer for the `key` in `storage`.
+    #[must_use]
+    pub fn new(storage: &'a TStorage, key: StoreKey) -> Self {
+        Self {
+            storage,
+            key,
+            buffer: Vec::new(),
+        }
+    }
+
+    fn take_value(&mut self) -> Bytes {
+        if self.buffer.len() == 1 {
+            self.buffer.pop().unwrap_or_default()
+        } else {
+            Bytes::from(std::mem::take(&mut self.buffer).concat())
+        }
+    }
+}
+
+impl<TStorage: ?Sized + WritableStorageTraits> StoreValueWriter
+    for BufferedStoreValueWriter<'_, TStorage>
+{
+    fn write(&mut self, bytes: Bytes) -> Result<(), StorageError> {
+        if !bytes.is_empty() {
+            self.buffer.push(bytes);
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), StorageError> {
+        let value = self.take_value();
+        self.storage.set(&self.key, value)
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncWritableStorageTraits> AsyncStoreValueWriter
+    for BufferedStoreValueWriter<'_, TStorage>
+{
+    async fn write(&mut self, bytes: AsyncBytes) -> Result<(), StorageError> {
+        if !bytes.is_empty() {
+            self.buffer.push(bytes);
+        }
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<(), StorageError> {
+        let value = self.take_value();
+        self.storage.set(&self.key, value).await
+    }
+}
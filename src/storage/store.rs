@@ -6,6 +6,9 @@
 mod store_async;
 
 mod store_sync;
+
+#[cfg(feature = "kerchunk")]
+mod kerchunk_references;
 // mod store_plugin;
 
 pub use store_sync::filesystem_store::{FilesystemStore, FilesystemStoreCreateError};
@@ -15,6 +18,19 @@ pub use store_sync::memory_store::MemoryStore;
 #[allow(deprecated)]
 pub use store_sync::http_store::{HTTPStore, HTTPStoreCreateError};
 
+#[cfg(all(feature = "io_uring", target_os = "linux"))]
+pub use store_sync::io_uring_store::{IoUringStore, IoUringStoreCreateError};
+
+#[cfg(feature = "kerchunk")]
+pub use kerchunk_references::{KerchunkReference, KerchunkReferences, KerchunkReferencesError};
+#[cfg(all(feature = "kerchunk", feature = "async"))]
+pub use store_async::kerchunk_store::AsyncKerchunkStore;
+#[cfg(feature = "kerchunk")]
+pub use store_sync::kerchunk_store::KerchunkStore;
+
+#[cfg(all(feature = "fetch", target_arch = "wasm32"))]
+pub use store_async::fetch_store::{FetchStore, FetchStoreCreateError};
+
 #[cfg(feature = "object_store")]
 pub use store_async::object_store::AsyncObjectStore;
 
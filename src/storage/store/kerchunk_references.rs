@@ -0,0 +1,451 @@
+//! Kerchunk references.
+//!
+//! See <https://fsspec.github.io/kerchunk/spec.html>.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use base64::Engine;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{
+    byte_range::{ByteLength, ByteOffset, ByteRange, InvalidByteRangeError},
+    storage::{
+        Bytes, MaybeBytes, StorageError, StoreKey, StoreKeyError, StoreKeys, StoreKeysPrefixes,
+        StorePrefix,
+    },
+};
+
+/// A kerchunk reference, which is the value associated with a key in a kerchunk references manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KerchunkReference {
+    /// An inline value.
+    Inline(Bytes),
+    /// An entire target at a URL.
+    Target(String),
+    /// A byte range of a target at a URL.
+    TargetRange {
+        /// The URL of the target.
+        url: String,
+        /// The offset of the value in the target.
+        offset: u64,
+        /// The length of the value.
+        length: u64,
+    },
+}
+
+/// A kerchunk references manifest, mapping store keys to [`KerchunkReference`]s.
+///
+/// Version 0 and version 1 (with `templates`) JSON manifests are supported, but `gen` references are not.
+/// Other manifests (e.g. parquet) can be converted to [`KerchunkReferences`] with its [`FromIterator`] implementation.
+#[derive(Debug, Clone, Default)]
+pub struct KerchunkReferences {
+    references: BTreeMap<StoreKey, KerchunkReference>,
+}
+
+impl FromIterator<(StoreKey, KerchunkReference)> for KerchunkReferences {
+    fn from_iter<T: IntoIterator<Item = (StoreKey, KerchunkReference)>>(iter: T) -> Self {
+        Self {
+            references: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl KerchunkReferences {
+    /// Read kerchunk references from a JSON manifest.
+    ///
+    /// # Errors
+    /// Returns a [`KerchunkReferencesError`] if the manifest is invalid or unsupported.
+    pub fn from_json(json: &[u8]) -> Result<Self, KerchunkReferencesError> {
+        let manifest: serde_json::Map<String, Value> = serde_json::from_slice(json)?;
+
+        let (references, templates) = match manifest.get("version") {
+            None => (&manifest, serde_json::Map::new()),
+            Some(Value::Number(version)) if version.as_u64() == Some(1) => {
+                if manifest
+                    .get("gen")
+                    .is_some_and(|gen| gen.as_array().map_or(true, |gen| !gen.is_empty()))
+                {
+                    return Err(KerchunkReferencesError::Unsupported(
+                        "gen references".to_string(),
+                    ));
+                }
+                let Some(Value::Object(references)) = manifest.get("refs") else {
+                    return Err(KerchunkReferencesError::Unsupported(
+                        "a version 1 manifest without refs".to_string(),
+                    ));
+                };
+                let templates = match manifest.get("templates") {
+                    Some(Value::Object(templates)) => templates.clone(),
+                    None => serde_json::Map::new(),
+                    Some(_) => {
+                        return Err(KerchunkReferencesError::Unsupported(
+                            "templates that are not an object".to_string(),
+                        ))
+                    }
+                };
+                (references, templates)
+            }
+            Some(version) => {
+                return Err(KerchunkReferencesError::Unsupported(format!(
+                    "manifest version {version}"
+                )))
+            }
+        };
+
+        let expand_url = |url: &str| {
+            templates
+                .iter()
+                .fold(url.to_string(), |url, (name, value)| {
+                    url.replace(
+                        &format!("{{{{{name}}}}}"),
+                        value.as_str().unwrap_or_default(),
+                    )
+                })
+        };
+
+        references
+            .iter()
+            .filter(|(key, _)| key.as_str() != "version")
+            .map(|(key, value)| {
+                let invalid = |reason: &str| {
+                    KerchunkReferencesError::InvalidReference(key.clone(), reason.to_string())
+                };
+                let reference = match value {
+                    Value::String(value) => {
+                        if let Some(value) = value.strip_prefix("base64:") {
+                            let value = base64::engine::general_purpose::STANDARD
+                                .decode(value)
+                                .map_err(|_| invalid("invalid base64"))?;
+                            KerchunkReference::Inline(value.into())
+                        } else {
+                            KerchunkReference::Inline(Bytes::copy_from_slice(value.as_bytes()))
+                        }
+                    }
+                    Value::Object(_) => KerchunkReference::Inline(value.to_string().into()),
+                    Value::Array(target) => match target.as_slice() {
+                        [Value::String(url)] => KerchunkReference::Target(expand_url(url)),
+                        [Value::String(url), offset, length] => KerchunkReference::TargetRange {
+                            url: expand_url(url),
+                            offset: offset.as_u64().ok_or_else(|| invalid("invalid offset"))?,
+                            length: length.as_u64().ok_or_else(|| invalid("invalid length"))?,
+                        },
+                        _ => return Err(invalid("invalid target")),
+                    },
+                    _ => return Err(invalid("invalid value")),
+                };
+                Ok((StoreKey::new(key.as_str())?, reference))
+            })
+            .collect()
+    }
+
+    /// Return the reference of `key`.
+    #[must_use]
+    pub fn get(&self, key: &StoreKey) -> Option<&KerchunkReference> {
+        self.references.get(key)
+    }
+
+    /// Return the number of references.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.references.len()
+    }
+
+    /// Returns true if there are no references.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.references.is_empty()
+    }
+
+    /// Return the keys under `prefix` in sorted order.
+    pub(crate) fn list_prefix(&self, prefix: &StorePrefix) -> StoreKeys {
+        self.references
+            .keys()
+            .filter(|key| key.has_prefix(prefix))
+            .cloned()
+            .collect()
+    }
+
+    /// Return the keys and prefixes that are direct children of `prefix`.
+    pub(crate) fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        let mut keys: StoreKeys = vec![];
+        let mut prefixes: BTreeSet<StorePrefix> = BTreeSet::default();
+        for key in self.references.keys().filter(|key| key.has_prefix(prefix)) {
+            let key_strip = key.as_str().strip_prefix(prefix.as_str()).unwrap();
+            if let Some((child, _)) = key_strip.split_once('/') {
+                prefixes.insert(StorePrefix::new(prefix.as_str().to_string() + child + "/")?);
+            } else {
+                keys.push(key.clone());
+            }
+        }
+        Ok(StoreKeysPrefixes {
+            keys,
+            prefixes: prefixes.into_iter().collect(),
+        })
+    }
+}
+
+/// Map `byte_ranges` of a value at `offset` with `length` in a target to byte ranges of the target.
+pub(crate) fn target_byte_ranges(
+    offset: u64,
+    length: u64,
+    byte_ranges: &[ByteRange],
+) -> Result<Vec<ByteRange>, InvalidByteRangeError> {
+    byte_ranges
+        .iter()
+        .map(|byte_range| {
+            let (ByteRange::FromStart(range_offset, range_length)
+            | ByteRange::FromEnd(range_offset, range_length)) = byte_range;
+            if range_offset + range_length.unwrap_or(0) > length {
+                Err(InvalidByteRangeError::new(*byte_range, length))
+            } else {
+                Ok(ByteRange::FromStart(
+                    offset + byte_range.start(length),
+                    Some(byte_range.length(length)),
+                ))
+            }
+        })
+        .collect()
+}
+
+/// A read covering one or more regions of a byte sequence.
+#[derive(Clone, Debug, PartialEq)]
+struct CoalescedRead {
+    /// The offset of the read.
+    offset: ByteOffset,
+    /// The length of the read.
+    length: ByteLength,
+    /// The indices of the regions covered by the read.
+    regions: Vec<usize>,
+}
+
+/// Plan reads of `regions` (offset, length pairs) of a byte sequence, merging regions in offset order into a single read while the fraction of unrequested bytes in the read does not exceed `max_waste_ratio`.
+///
+/// Overlapping and adjacent regions are always merged.
+fn coalesce_regions(
+    regions: &[(ByteOffset, ByteLength)],
+    max_waste_ratio: f64,
+) -> Vec<CoalescedRead> {
+    let mut order: Vec<usize> = (0..regions.len()).collect();
+    order.sort_by_key(|&index| regions[index]);
+
+    let mut reads: Vec<CoalescedRead> = Vec::new();
+    let mut read_requested = 0;
+    for index in order {
+        let (offset, length) = regions[index];
+        let end = offset + length;
+        if let Some(read) = reads.last_mut() {
+            let read_end = read.offset + read.length;
+            let requested = read_requested + end.saturating_sub(read_end.max(offset));
+            let merged_length = end.max(read_end) - read.offset;
+            #[allow(clippy::cast_precision_loss)]
+            let waste_ratio = if merged_length == 0 {
+                0.0
+            } else {
+                (merged_length - requested) as f64 / merged_length as f64
+            };
+            if offset <= read_end || waste_ratio <= max_waste_ratio {
+                read.length = merged_length;
+                read.regions.push(index);
+                read_requested = requested;
+                continue;
+            }
+        }
+        reads.push(CoalescedRead {
+            offset,
+            length,
+            regions: vec![index],
+        });
+        read_requested = length;
+    }
+    reads
+}
+
+/// Coalesced reads of the values of keys referencing byte ranges of the same target.
+#[derive(Debug)]
+pub(crate) struct KerchunkTargetReads<'a> {
+    /// The target URL.
+    pub(crate) url: &'a str,
+    /// The byte ranges of the target to read.
+    pub(crate) byte_ranges: Vec<ByteRange>,
+    /// The key index and range within the read bytes of each value covered by each byte range.
+    values: Vec<Vec<(usize, std::ops::Range<usize>)>>,
+}
+
+impl KerchunkTargetReads<'_> {
+    /// Slice the `bytes` read for each byte range into the covered values of `keys`.
+    pub(crate) fn extract_values(&self, bytes: &[Bytes], values: &mut [MaybeBytes]) {
+        for (bytes, byte_range_values) in bytes.iter().zip(&self.values) {
+            for (index, range) in byte_range_values {
+                values[*index] = Some(bytes.slice(range.clone()));
+            }
+        }
+    }
+}
+
+/// The indices of the keys referencing a target URL and the regions (offset and length) they reference.
+type KerchunkTargetRegions<'a> = (&'a str, Vec<usize>, Vec<(u64, u64)>);
+
+/// Plan coalesced reads of the values of `keys`.
+///
+/// Values referencing byte ranges of a target are grouped by target URL and merged with [`coalesce_regions`] with `max_waste_ratio`.
+/// Returns the indices of the keys that must be read individually and the coalesced reads of each target.
+pub(crate) fn plan_coalesced_reads<'a>(
+    references: &'a KerchunkReferences,
+    keys: &[StoreKey],
+    max_waste_ratio: f64,
+) -> (Vec<usize>, Vec<KerchunkTargetReads<'a>>) {
+    let mut individual = Vec::new();
+    let mut targets: Vec<KerchunkTargetRegions> = Vec::new();
+    for (index, key) in keys.iter().enumerate() {
+        if let Some(KerchunkReference::TargetRange {
+            url,
+            offset,
+            length,
+        }) = references.get(key)
+        {
+            if let Some((_, indices, regions)) = targets
+                .iter_mut()
+                .find(|(target_url, _, _)| *target_url == url.as_str())
+            {
+                indices.push(index);
+                regions.push((*offset, *length));
+            } else {
+                targets.push((url, vec![index], vec![(*offset, *length)]));
+            }
+        } else {
+            individual.push(index);
+        }
+    }
+
+    let target_reads = targets
+        .into_iter()
+        .map(|(url, indices, regions)| {
+            let reads = coalesce_regions(&regions, max_waste_ratio);
+            let byte_ranges = reads
+                .iter()
+                .map(|read| ByteRange::FromStart(read.offset, Some(read.length)))
+                .collect();
+            let values = reads
+                .iter()
+                .map(|read| {
+                    read.regions
+                        .iter()
+                        .map(|&region| {
+                            let (offset, length) = regions[region];
+                            let start = usize::try_from(offset - read.offset).unwrap();
+                            let end = start + usize::try_from(length).unwrap();
+                            (indices[region], start..end)
+                        })
+                        .collect()
+                })
+                .collect();
+            KerchunkTargetReads {
+                url,
+                byte_ranges,
+                values,
+            }
+        })
+        .collect();
+    (individual, target_reads)
+}
+
+/// Target stores of a kerchunk store, identified by URL prefixes.
+#[derive(Debug)]
+pub(crate) struct KerchunkTargets<TStorage> {
+    targets: Vec<(String, TStorage)>,
+}
+
+impl<TStorage> Default for KerchunkTargets<TStorage> {
+    fn default() -> Self {
+        Self { targets: vec![] }
+    }
+}
+
+impl<TStorage> KerchunkTargets<TStorage> {
+    /// Add a target `storage` for URLs starting with `url_prefix`.
+    pub(crate) fn add(&mut self, url_prefix: String, storage: TStorage) {
+        self.targets.push((url_prefix, storage));
+    }
+
+    /// Return the target storage of `url` with the longest matching URL prefix and the key of `url` in that storage.
+    pub(crate) fn resolve(&self, url: &str) -> Result<(&TStorage, StoreKey), StorageError> {
+        let (url_prefix, storage) = self
+            .targets
+            .iter()
+            .filter(|(url_prefix, _)| url.starts_with(url_prefix.as_str()))
+            .max_by_key(|(url_prefix, _)| url_prefix.len())
+            .ok_or_else(|| StorageError::Other(format!("kerchunk target {url} has no store")))?;
+        let key = url[url_prefix.len()..].trim_start_matches('/');
+        Ok((storage, StoreKey::new(key)?))
+    }
+}
+
+/// A kerchunk references error.
+#[derive(Debug, Error)]
+pub enum KerchunkReferencesError {
+    /// The manifest is not valid JSON.
+    #[error(transparent)]
+    InvalidJSON(#[from] serde_json::Error),
+    /// A reference is invalid.
+    #[error("kerchunk reference {0} is invalid: {1}")]
+    InvalidReference(String, String),
+    /// A reference key is not a valid store key.
+    #[error(transparent)]
+    InvalidKey(#[from] StoreKeyError),
+    /// The manifest is unsupported.
+    #[error("kerchunk manifest is unsupported: {0}")]
+    Unsupported(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kerchunk_coalesce_regions() {
+        let regions = [(20, 10), (0, 10), (10, 5), (100, 10), (32, 8)];
+        assert_eq!(
+            coalesce_regions(&regions, 0.0),
+            vec![
+                CoalescedRead {
+                    offset: 0,
+                    length: 15,
+                    regions: vec![1, 2],
+                },
+                CoalescedRead {
+                    offset: 20,
+                    length: 10,
+                    regions: vec![0],
+                },
+                CoalescedRead {
+                    offset: 32,
+                    length: 8,
+                    regions: vec![4],
+                },
+                CoalescedRead {
+                    offset: 100,
+                    length: 10,
+                    regions: vec![3],
+                },
+            ]
+        );
+        assert_eq!(
+            coalesce_regions(&regions, 0.2),
+            vec![
+                CoalescedRead {
+                    offset: 0,
+                    length: 40,
+                    regions: vec![1, 2, 0, 4],
+                },
+                CoalescedRead {
+                    offset: 100,
+                    length: 10,
+                    regions: vec![3],
+                },
+            ]
+        );
+        assert_eq!(coalesce_regions(&regions, 1.0).len(), 1);
+        assert!(coalesce_regions(&[], 0.5).is_empty());
+    }
+}
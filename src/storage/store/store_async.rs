@@ -1,3 +1,9 @@
+#[cfg(all(feature = "fetch", target_arch = "wasm32"))]
+pub mod fetch_store;
+
+#[cfg(feature = "kerchunk")]
+pub mod kerchunk_store;
+
 #[cfg(feature = "object_store")]
 pub mod object_store;
 
@@ -6,7 +12,9 @@ pub mod opendal;
 
 #[cfg(test)]
 mod test_util {
-    use std::error::Error;
+    use std::{error::Error, num::NonZeroUsize};
+
+    use futures::TryStreamExt;
 
     use crate::{
         byte_range::ByteRange,
@@ -46,7 +54,14 @@ mod test_util {
             .set_partial_values(&[StoreKeyStartValue::new("a/b".try_into()?, 0, &[0])])
             .await?;
 
-        store.set(&"a/c".try_into()?, vec![0].into()).await?;
+        let mut writer = store.set_streaming(&"a/c".try_into()?).await?;
+        writer.write(vec![].into()).await?;
+        writer.write(vec![0].into()).await?;
+        writer.finish().await?;
+
+        let mut writer = store.set_streaming(&"abandoned".try_into()?).await?;
+        writer.write(vec![0].into()).await?;
+        drop(writer); // not stored
         store.set(&"a/d/e".try_into()?, vec![].into()).await?;
         store.set(&"a/f/g".try_into()?, vec![].into()).await?;
         store.set(&"a/f/h".try_into()?, vec![].into()).await?;
@@ -122,6 +137,28 @@ mod test_util {
             ),])
             .await
             .is_err());
+        assert_eq!(
+            store
+                .get_partial_values_batched(&[
+                    (
+                        &"a/b".try_into()?,
+                        &[
+                            ByteRange::FromStart(0, Some(2)),
+                            ByteRange::FromEnd(0, None)
+                        ]
+                    ),
+                    (&"notfound".try_into()?, &[ByteRange::FromStart(0, Some(1))]),
+                    (&"i/j/k".try_into()?, &[ByteRange::FromStart(1, Some(1))]),
+                    (&"a/b".try_into()?, &[ByteRange::FromEnd(0, Some(1))]),
+                ])
+                .await?,
+            vec![
+                Some(vec![vec![0, 1].into(), vec![0, 1, 2, 3].into()]),
+                None,
+                Some(vec![vec![1].into()]),
+                Some(vec![vec![3].into()])
+            ]
+        );
 
         assert_eq!(store.size().await?, 7);
         assert_eq!(store.size_prefix(&"a/".try_into()?).await?, 5);
@@ -192,6 +229,33 @@ mod test_util {
                 &["a/d/".try_into()?, "a/f/".try_into()?,]
             );
         }
+
+        {
+            let page_size = NonZeroUsize::new(2).unwrap();
+            let pages: Vec<_> = store
+                .list_prefix_pages(&"a/".try_into()?, page_size)
+                .try_collect()
+                .await?;
+            assert!(pages.iter().all(|page| page.len() <= 2));
+            let mut keys = pages.concat();
+            keys.sort();
+            assert_eq!(keys, store.list_prefix(&"a/".try_into()?).await?);
+
+            let pages: Vec<_> = store
+                .list_dir_pages(&"a/".try_into()?, page_size)
+                .try_collect()
+                .await?;
+            let (mut keys, mut prefixes) = (vec![], vec![]);
+            for page in pages {
+                assert!(page.keys().len() + page.prefixes().len() <= 2);
+                keys.extend(page.keys);
+                prefixes.extend(page.prefixes);
+            }
+            keys.sort();
+            prefixes.sort();
+            assert_eq!(keys, &["a/b".try_into()?, "a/c".try_into()?]);
+            assert_eq!(prefixes, &["a/d/".try_into()?, "a/f/".try_into()?]);
+        }
         Ok(())
     }
 }
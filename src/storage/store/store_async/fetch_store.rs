@@ -0,0 +1,217 @@
+//! An asynchronous store for WebAssembly targets using the [Fetch API](https://developer.mozilla.org/en-US/docs/Web/API/Fetch_API).
+
+use std::{future::Future, str::FromStr};
+
+use futures::channel::oneshot;
+use thiserror::Error;
+use url::Url;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{
+    byte_range::ByteRange,
+    storage::{AsyncBytes, AsyncReadableStorageTraits, MaybeAsyncBytes, StorageError, StoreKey},
+};
+
+impl From<JsValue> for StorageError {
+    fn from(err: JsValue) -> Self {
+        Self::Other(
+            err.as_string()
+                .unwrap_or_else(|| format!("javascript error: {err:?}")),
+        )
+    }
+}
+
+/// An asynchronous HTTP store for WebAssembly targets using the browser [Fetch API](https://developer.mozilla.org/en-US/docs/Web/API/Fetch_API).
+///
+/// Partial values are retrieved with HTTP range requests.
+/// The server must permit cross-origin requests with a `Range` header for stores on another origin.
+///
+/// This store is only available on `wasm32` targets with the `fetch` feature, and can be used from a window or a web worker.
+#[derive(Debug, Clone)]
+pub struct FetchStore {
+    base_url: Url,
+}
+
+impl FetchStore {
+    /// Create a new fetch store at a given `base_url`.
+    ///
+    /// # Errors
+    /// Returns a [`FetchStoreCreateError`] if `base_url` is not a valid URL.
+    pub fn new(base_url: &str) -> Result<Self, FetchStoreCreateError> {
+        let base_url = Url::from_str(base_url)
+            .map_err(|_| FetchStoreCreateError::InvalidBaseURL(base_url.into()))?;
+        Ok(Self { base_url })
+    }
+
+    /// Maps a [`StoreKey`] to a HTTP [`Url`].
+    ///
+    /// # Errors
+    /// Returns an error if the URL is invalid.
+    pub fn key_to_url(&self, key: &StoreKey) -> Result<Url, url::ParseError> {
+        let mut url = self.base_url.as_str().to_string();
+        if !key.as_str().is_empty() {
+            url +=
+                ("/".to_string() + key.as_str().strip_prefix('/').unwrap_or(key.as_str())).as_str();
+        }
+        Url::parse(&url)
+    }
+}
+
+/// Run a (non-[`Send`]) fetch future on the current thread and return a [`Send`] future with its output.
+///
+/// Javascript values cannot be sent between threads, but the storage traits require [`Send`] futures.
+fn spawn_fetch<F, T>(future: F) -> impl Future<Output = Result<T, StorageError>> + Send
+where
+    F: Future<Output = Result<T, StorageError>> + 'static,
+    T: Send + 'static,
+{
+    let (sender, receiver) = oneshot::channel();
+    wasm_bindgen_futures::spawn_local(async move {
+        let _ = sender.send(future.await);
+    });
+    async move {
+        receiver
+            .await
+            .map_err(|_| StorageError::from("the fetch request was cancelled"))?
+    }
+}
+
+/// Send a request with `method` to `url` with an optional `Range` header.
+async fn fetch(
+    method: &str,
+    url: &str,
+    range: Option<&str>,
+) -> Result<web_sys::Response, StorageError> {
+    let init = web_sys::RequestInit::new();
+    init.set_method(method);
+    let request = web_sys::Request::new_with_str_and_init(url, &init)?;
+    if let Some(range) = range {
+        request.headers().set("Range", range)?;
+    }
+
+    let global = js_sys::global();
+    let promise = if let Some(window) = global.dyn_ref::<web_sys::Window>() {
+        window.fetch_with_request(&request)
+    } else if let Some(worker) = global.dyn_ref::<web_sys::WorkerGlobalScope>() {
+        worker.fetch_with_request(&request)
+    } else {
+        return Err(StorageError::from(
+            "the fetch api is not available in this javascript environment",
+        ));
+    };
+    Ok(JsFuture::from(promise).await?.dyn_into()?)
+}
+
+/// Read the body of `response`.
+async fn response_bytes(response: &web_sys::Response) -> Result<AsyncBytes, StorageError> {
+    let buffer = JsFuture::from(response.array_buffer()?).await?;
+    Ok(AsyncBytes::from(js_sys::Uint8Array::new(&buffer).to_vec()))
+}
+
+/// Retrieve a value, or the `start..end` bytes of a value.
+async fn fetch_get(url: Url, range: Option<(u64, u64)>) -> Result<MaybeAsyncBytes, StorageError> {
+    let range_header = range.map(|(start, end)| format!("bytes={start}-{}", end - 1));
+    let response = fetch("GET", url.as_str(), range_header.as_deref()).await?;
+    match (response.status(), range) {
+        (200, None) | (206, Some(_)) => Ok(Some(response_bytes(&response).await?)),
+        (200, Some((start, end))) => {
+            // The server ignored the range request and returned the entire value
+            let bytes = response_bytes(&response).await?;
+            let start = usize::try_from(start).unwrap();
+            let end = usize::try_from(end).unwrap();
+            if end <= bytes.len() {
+                Ok(Some(bytes.slice(start..end)))
+            } else {
+                Err(StorageError::from(
+                    "the http server returned fewer bytes than requested",
+                ))
+            }
+        }
+        (404, _) => Ok(None),
+        (status, _) => Err(StorageError::from(format!(
+            "http unexpected status code: {status}"
+        ))),
+    }
+}
+
+/// Retrieve the size of a value from the `Content-Length` of a `HEAD` request.
+async fn fetch_size(url: Url) -> Result<Option<u64>, StorageError> {
+    let response = fetch("HEAD", url.as_str(), None).await?;
+    match response.status() {
+        200 => {
+            let length = response
+                .headers()
+                .get("Content-Length")?
+                .and_then(|length| u64::from_str(&length).ok())
+                .ok_or_else(|| StorageError::from("content length response is invalid"))?;
+            Ok(Some(length))
+        }
+        404 => Ok(None),
+        status => Err(StorageError::from(format!(
+            "http size_key has status code {status}"
+        ))),
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncReadableStorageTraits for FetchStore {
+    async fn get(&self, key: &StoreKey) -> Result<MaybeAsyncBytes, StorageError> {
+        let url = self
+            .key_to_url(key)
+            .map_err(|err| StorageError::Other(err.to_string()))?;
+        spawn_fetch(fetch_get(url, None)).await
+    }
+
+    async fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<AsyncBytes>>, StorageError> {
+        let url = self
+            .key_to_url(key)
+            .map_err(|err| StorageError::Other(err.to_string()))?;
+
+        // The size is only needed to resolve unbounded or suffix byte ranges
+        let size = if byte_ranges
+            .iter()
+            .all(|byte_range| matches!(byte_range, ByteRange::FromStart(_, Some(_))))
+        {
+            0
+        } else if let Some(size) = self.size_key(key).await? {
+            size
+        } else {
+            return Ok(None);
+        };
+
+        // Each byte range is requested separately, since multipart range responses are not widely supported
+        let futures = byte_ranges.iter().map(|byte_range| {
+            let (start, end) = (byte_range.start(size), byte_range.end(size));
+            let url = url.clone();
+            async move {
+                if start == end {
+                    Ok(Some(AsyncBytes::new()))
+                } else {
+                    spawn_fetch(fetch_get(url, Some((start, end)))).await
+                }
+            }
+        });
+        let bytes = futures::future::try_join_all(futures).await?;
+        Ok(bytes.into_iter().collect())
+    }
+
+    async fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        let url = self
+            .key_to_url(key)
+            .map_err(|err| StorageError::Other(err.to_string()))?;
+        spawn_fetch(fetch_size(url)).await
+    }
+}
+
+/// A fetch store creation error.
+#[derive(Debug, Error)]
+pub enum FetchStoreCreateError {
+    /// The URL is not valid.
+    #[error("base URL {0} is not valid")]
+    InvalidBaseURL(String),
+}
@@ -0,0 +1,212 @@
+//! An asynchronous read-only kerchunk reference store.
+
+use crate::{
+    byte_range::{extract_byte_ranges, ByteRange},
+    storage::{
+        store::kerchunk_references::{plan_coalesced_reads, target_byte_ranges, KerchunkTargets},
+        AsyncBytes, AsyncListableStorageTraits, AsyncReadableStorage, AsyncReadableStorageTraits,
+        MaybeAsyncBytes, StorageError, StoreKey, StoreKeys, StoreKeysPrefixes, StorePrefix,
+    },
+};
+
+use super::super::{KerchunkReference, KerchunkReferences};
+
+/// An asynchronous read-only store of [kerchunk](https://fsspec.github.io/kerchunk/) references.
+///
+/// Each key of the store is a [`KerchunkReference`] to an inline value, an entire target, or a byte range of a target.
+/// Targets are identified by URL, and are read from the store added with [`with_target`](AsyncKerchunkStore::with_target) for the longest matching URL prefix.
+/// This enables `HDF5` and `NetCDF` files indexed by kerchunk to be read as Zarr (V2) hierarchies.
+pub struct AsyncKerchunkStore {
+    references: KerchunkReferences,
+    targets: KerchunkTargets<AsyncReadableStorage>,
+}
+
+impl AsyncKerchunkStore {
+    /// Create a new kerchunk store from kerchunk `references`.
+    #[must_use]
+    pub fn new(references: KerchunkReferences) -> Self {
+        Self {
+            references,
+            targets: KerchunkTargets::default(),
+        }
+    }
+
+    /// Read targets with URLs starting with `url_prefix` from `storage`.
+    ///
+    /// The key of a target in `storage` is the remainder of its URL after `url_prefix`.
+    /// For example, with a `url_prefix` of `s3://bucket/`, the URL `s3://bucket/data/file.nc` is read from the key `data/file.nc`.
+    #[must_use]
+    pub fn with_target(
+        mut self,
+        url_prefix: impl Into<String>,
+        storage: AsyncReadableStorage,
+    ) -> Self {
+        self.targets.add(url_prefix.into(), storage);
+        self
+    }
+
+    /// Return the kerchunk references of the store.
+    #[must_use]
+    pub const fn references(&self) -> &KerchunkReferences {
+        &self.references
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncReadableStorageTraits for AsyncKerchunkStore {
+    async fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<AsyncBytes>>, StorageError> {
+        let (url, byte_ranges) = match self.references.get(key) {
+            None => return Ok(None),
+            Some(KerchunkReference::Inline(value)) => {
+                return Ok(Some(
+                    extract_byte_ranges(value, byte_ranges)?
+                        .into_iter()
+                        .map(AsyncBytes::from)
+                        .collect(),
+                ));
+            }
+            Some(KerchunkReference::Target(url)) => (url, byte_ranges.to_vec()),
+            Some(KerchunkReference::TargetRange {
+                url,
+                offset,
+                length,
+            }) => (url, target_byte_ranges(*offset, *length, byte_ranges)?),
+        };
+        let (storage, target_key) = self.targets.resolve(url)?;
+        storage
+            .get_partial_values_key(&target_key, &byte_ranges)
+            .await?
+            .ok_or_else(|| StorageError::Other(format!("kerchunk target {url} not found")))
+            .map(Some)
+    }
+
+    async fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        match self.references.get(key) {
+            None => Ok(None),
+            Some(KerchunkReference::Inline(value)) => Ok(Some(value.len() as u64)),
+            Some(KerchunkReference::Target(url)) => {
+                let (storage, target_key) = self.targets.resolve(url)?;
+                storage
+                    .size_key(&target_key)
+                    .await?
+                    .ok_or_else(|| StorageError::Other(format!("kerchunk target {url} not found")))
+                    .map(Some)
+            }
+            Some(KerchunkReference::TargetRange { length, .. }) => Ok(Some(*length)),
+        }
+    }
+
+    async fn get_values_coalesced(
+        &self,
+        keys: &[StoreKey],
+        max_waste_ratio: f64,
+    ) -> Result<Vec<MaybeAsyncBytes>, StorageError> {
+        let mut values = vec![None; keys.len()];
+        let (individual, target_reads) =
+            plan_coalesced_reads(&self.references, keys, max_waste_ratio);
+        for index in individual {
+            values[index] = self.get(&keys[index]).await?;
+        }
+        for target_read in target_reads {
+            let (storage, target_key) = self.targets.resolve(target_read.url)?;
+            let bytes = storage
+                .get_partial_values_key(&target_key, &target_read.byte_ranges)
+                .await?
+                .ok_or_else(|| {
+                    StorageError::Other(format!("kerchunk target {} not found", target_read.url))
+                })?;
+            target_read.extract_values(&bytes, &mut values);
+        }
+        Ok(values)
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncListableStorageTraits for AsyncKerchunkStore {
+    async fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.list_prefix(&StorePrefix::root()).await
+    }
+
+    async fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        Ok(self.references.list_prefix(prefix))
+    }
+
+    async fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        self.references.list_dir(prefix)
+    }
+
+    async fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        let mut size = 0;
+        for key in self.list_prefix(prefix).await? {
+            if let Some(size_key) = self.size_key(&key).await? {
+                size += size_key;
+            }
+        }
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "object_store")]
+mod tests {
+    use std::{error::Error, sync::Arc};
+
+    use crate::storage::{store::AsyncObjectStore, AsyncWritableStorageTraits};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn kerchunk() -> Result<(), Box<dyn Error>> {
+        let target = Arc::new(AsyncObjectStore::new(object_store::memory::InMemory::new()));
+        target
+            .set(&"file.nc".try_into()?, vec![0, 1, 2, 3, 4, 5].into())
+            .await?;
+
+        let references = KerchunkReferences::from_json(
+            br#"{"a": "base64:AAE=", "b/c": ["s3://bucket/file.nc", 2, 3], "b/d": ["s3://bucket/file.nc"]}"#,
+        )?;
+        let store = AsyncKerchunkStore::new(references).with_target("s3://bucket", target);
+
+        assert_eq!(store.get(&"a".try_into()?).await?, Some(vec![0, 1].into()));
+        assert_eq!(
+            store.get(&"b/c".try_into()?).await?,
+            Some(vec![2, 3, 4].into())
+        );
+        assert_eq!(
+            store
+                .get_partial_values_key(&"b/c".try_into()?, &[ByteRange::FromEnd(0, Some(1))])
+                .await?,
+            Some(vec![vec![4].into()])
+        );
+        assert_eq!(
+            store
+                .get_values_coalesced(
+                    &[
+                        "b/c".try_into()?,
+                        "a".try_into()?,
+                        "missing".try_into()?,
+                        "b/d".try_into()?
+                    ],
+                    0.0
+                )
+                .await?,
+            vec![
+                Some(vec![2, 3, 4].into()),
+                Some(vec![0, 1].into()),
+                None,
+                Some(vec![0, 1, 2, 3, 4, 5].into())
+            ]
+        );
+        assert_eq!(store.size_key(&"b/d".try_into()?).await?, Some(6));
+        assert_eq!(store.size_prefix(&"b/".try_into()?).await?, 9);
+        assert_eq!(
+            store.list().await?,
+            &["a".try_into()?, "b/c".try_into()?, "b/d".try_into()?]
+        );
+        Ok(())
+    }
+}
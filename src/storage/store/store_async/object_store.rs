@@ -1,12 +1,15 @@
+use std::{collections::HashMap, num::NonZeroUsize};
+
 use futures::{StreamExt, TryStreamExt};
-use object_store::path::Path;
+use object_store::{path::Path, PutPayload, WriteMultipart};
 
 use crate::{
     byte_range::ByteRange,
     storage::{
         AsyncBytes, AsyncListableStorageTraits, AsyncReadableStorageTraits,
-        AsyncReadableWritableStorageTraits, AsyncWritableStorageTraits, MaybeAsyncBytes,
-        StorageError, StoreKey, StoreKeyStartValue, StoreKeys, StoreKeysPrefixes, StorePrefix,
+        AsyncReadableWritableStorageTraits, AsyncStoreKeysPages, AsyncStoreValueWriter,
+        AsyncWritableStorageTraits, MaybeAsyncBytes, StorageError, StoreKey, StoreKeyStartValue,
+        StoreKeys, StoreKeysPrefixes, StorePrefix, StoreValueVersion,
     },
 };
 
@@ -35,6 +38,22 @@ fn handle_result<T>(result: Result<T, object_store::Error>) -> Result<Option<T>,
     }
 }
 
+/// Returns the native version of an object, preferring its version over its `ETag`.
+fn object_version(meta: &object_store::ObjectMeta) -> Option<StoreValueVersion> {
+    meta.version
+        .as_ref()
+        .or(meta.e_tag.as_ref())
+        .map(StoreValueVersion::new)
+}
+
+/// The part size of multipart uploads.
+///
+/// Values at least this large are uploaded in parts by the writer returned by [`AsyncWritableStorageTraits::set_streaming`].
+const MULTIPART_PART_SIZE: usize = 16 * 1024 * 1024;
+
+/// The maximum number of concurrent part uploads of a multipart upload.
+const MULTIPART_MAX_CONCURRENCY: usize = 8;
+
 /// An asynchronous store backed by an [`object_store::ObjectStore`].
 pub struct AsyncObjectStore<T> {
     object_store: T,
@@ -71,38 +90,96 @@ impl<T: object_store::ObjectStore> AsyncReadableStorageTraits for AsyncObjectSto
         }
     }
 
+    async fn get_with_version(
+        &self,
+        key: &StoreKey,
+    ) -> Result<Option<(AsyncBytes, StoreValueVersion)>, StorageError> {
+        let get = handle_result(self.object_store.get(&key_to_path(key)).await)?;
+        if let Some(get) = get {
+            let version = object_version(&get.meta);
+            let bytes = get.bytes().await?;
+            let version = version.unwrap_or_else(|| StoreValueVersion::from_content(&bytes));
+            Ok(Some((bytes, version)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn get_version(&self, key: &StoreKey) -> Result<Option<StoreValueVersion>, StorageError> {
+        let Some(meta) = handle_result(self.object_store.head(&key_to_path(key)).await)? else {
+            return Ok(None);
+        };
+        if let Some(version) = object_version(&meta) {
+            Ok(Some(version))
+        } else {
+            Ok(self
+                .get_with_version(key)
+                .await?
+                .map(|(_, version)| version))
+        }
+    }
+
     async fn get_partial_values_key(
         &self,
         key: &StoreKey,
         byte_ranges: &[ByteRange],
     ) -> Result<Option<Vec<AsyncBytes>>, StorageError> {
-        let Some(size) = self.size_key(key).await? else {
+        // The size is only needed to resolve unbounded or suffix byte ranges
+        let size = if byte_ranges
+            .iter()
+            .all(|byte_range| matches!(byte_range, ByteRange::FromStart(_, Some(_))))
+        {
+            0
+        } else if let Some(size) = self.size_key(key).await? {
+            size
+        } else {
             return Ok(None);
         };
         let ranges = byte_ranges
             .iter()
             .map(|byte_range| byte_range.to_range_usize(size))
             .collect::<Vec<_>>();
+
+        // Empty ranges are not requested, since some object stores reject them
+        let ranges_nonempty = ranges
+            .iter()
+            .filter(|range| !range.is_empty())
+            .cloned()
+            .collect::<Vec<_>>();
+        if ranges_nonempty.is_empty() {
+            return Ok(self
+                .size_key(key)
+                .await?
+                .map(|_| vec![AsyncBytes::new(); ranges.len()]));
+        }
         let get_ranges = self
             .object_store
-            .get_ranges(&key_to_path(key), &ranges)
+            .get_ranges(&key_to_path(key), &ranges_nonempty)
             .await;
         match get_ranges {
-            Ok(get_ranges) => Ok(Some(
-                std::iter::zip(ranges, get_ranges)
-                    .map(|(range, bytes)| {
-                        if range.len() == bytes.len() {
-                            Ok(bytes)
-                        } else {
-                            Err(StorageError::Other(format!(
-                                "Unexpected length of bytes returned, expected {}, got {}",
-                                range.len(),
-                                bytes.len()
-                            )))
-                        }
-                    })
-                    .collect::<Result<_, StorageError>>()?,
-            )),
+            Ok(get_ranges) => {
+                let mut get_ranges = std::iter::zip(ranges_nonempty, get_ranges);
+                Ok(Some(
+                    ranges
+                        .iter()
+                        .map(|range| {
+                            if range.is_empty() {
+                                return Ok(AsyncBytes::new());
+                            }
+                            let (range, bytes) = get_ranges.next().unwrap();
+                            if range.len() == bytes.len() {
+                                Ok(bytes)
+                            } else {
+                                Err(StorageError::Other(format!(
+                                    "Unexpected length of bytes returned, expected {}, got {}",
+                                    range.len(),
+                                    bytes.len()
+                                )))
+                            }
+                        })
+                        .collect::<Result<_, StorageError>>()?,
+                ))
+            }
             Err(err) => {
                 if matches!(err, object_store::Error::NotFound { .. }) {
                     Ok(None)
@@ -113,6 +190,44 @@ impl<T: object_store::ObjectStore> AsyncReadableStorageTraits for AsyncObjectSto
         }
     }
 
+    async fn get_partial_values_batched(
+        &self,
+        keys_byte_ranges: &[(&StoreKey, &[ByteRange])],
+    ) -> Result<Vec<Option<Vec<AsyncBytes>>>, StorageError> {
+        // Merge the byte ranges of each distinct key, so that each object is read with a single `get_ranges` request which coalesces nearby ranges
+        let mut key_indices: HashMap<&StoreKey, usize> = HashMap::new();
+        let mut keys_byte_ranges_merged: Vec<(&StoreKey, Vec<ByteRange>)> = Vec::new();
+        for &(key, byte_ranges) in keys_byte_ranges {
+            let index = *key_indices.entry(key).or_insert_with(|| {
+                keys_byte_ranges_merged.push((key, Vec::new()));
+                keys_byte_ranges_merged.len() - 1
+            });
+            let (_, byte_ranges_merged) = &mut keys_byte_ranges_merged[index];
+            byte_ranges_merged.extend_from_slice(byte_ranges);
+        }
+
+        let values = futures::future::try_join_all(
+            keys_byte_ranges_merged
+                .iter()
+                .map(|(key, byte_ranges)| self.get_partial_values_key(key, byte_ranges)),
+        )
+        .await?;
+
+        // Split the merged values in the order of `keys_byte_ranges`
+        let mut values = values
+            .into_iter()
+            .map(|values| values.map(Vec::into_iter))
+            .collect::<Vec<_>>();
+        Ok(keys_byte_ranges
+            .iter()
+            .map(|&(key, byte_ranges)| {
+                values[key_indices[key]]
+                    .as_mut()
+                    .map(|values| values.take(byte_ranges.len()).collect())
+            })
+            .collect())
+    }
+
     async fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
         Ok(
             handle_result(self.object_store.head(&key_to_path(key)).await)?
@@ -155,6 +270,70 @@ impl<T: object_store::ObjectStore> AsyncWritableStorageTraits for AsyncObjectSto
             .await?;
         Ok(())
     }
+
+    async fn set_streaming(
+        &self,
+        key: &StoreKey,
+    ) -> Result<Box<dyn AsyncStoreValueWriter + '_>, StorageError> {
+        Ok(Box::new(AsyncObjectStoreValueWriter {
+            object_store: &self.object_store,
+            path: key_to_path(key),
+            buffer: Vec::new(),
+            buffer_len: 0,
+            multipart: None,
+        }))
+    }
+}
+
+/// An [`AsyncStoreValueWriter`] for an [`AsyncObjectStore`].
+///
+/// Small values are buffered and stored with a single put.
+/// Once the buffered value reaches [`MULTIPART_PART_SIZE`], a multipart upload is started and the value is uploaded in parts.
+struct AsyncObjectStoreValueWriter<'a, T> {
+    object_store: &'a T,
+    path: Path,
+    buffer: Vec<AsyncBytes>,
+    buffer_len: usize,
+    multipart: Option<WriteMultipart>,
+}
+
+#[async_trait::async_trait]
+impl<T: object_store::ObjectStore> AsyncStoreValueWriter for AsyncObjectStoreValueWriter<'_, T> {
+    async fn write(&mut self, bytes: AsyncBytes) -> Result<(), StorageError> {
+        if let Some(mut multipart) = self.multipart.take() {
+            if let Err(err) = multipart.wait_for_capacity(MULTIPART_MAX_CONCURRENCY).await {
+                // A part failed to upload, so clean up the incomplete upload
+                let _ = multipart.abort().await;
+                return Err(err.into());
+            }
+            multipart.put(bytes);
+            self.multipart = Some(multipart);
+        } else {
+            self.buffer_len += bytes.len();
+            self.buffer.push(bytes);
+            if self.buffer_len >= MULTIPART_PART_SIZE {
+                let upload = self.object_store.put_multipart(&self.path).await?;
+                let mut multipart =
+                    WriteMultipart::new_with_chunk_size(upload, MULTIPART_PART_SIZE);
+                for bytes in std::mem::take(&mut self.buffer) {
+                    multipart.put(bytes);
+                }
+                self.buffer_len = 0;
+                self.multipart = Some(multipart);
+            }
+        }
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<(), StorageError> {
+        if let Some(multipart) = self.multipart.take() {
+            multipart.finish().await?;
+        } else {
+            let payload = PutPayload::from_iter(std::mem::take(&mut self.buffer));
+            self.object_store.put(&self.path, payload).await?;
+        }
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
@@ -204,6 +383,24 @@ impl<T: object_store::ObjectStore> AsyncListableStorageTraits for AsyncObjectSto
         Ok(list)
     }
 
+    fn list_prefix_pages(
+        &self,
+        prefix: &StorePrefix,
+        page_size: NonZeroUsize,
+    ) -> AsyncStoreKeysPages<'_> {
+        let path: object_store::path::Path = prefix.as_str().into();
+        self.object_store
+            .list(Some(&path))
+            .map(|object_meta| {
+                let object_meta = object_meta?;
+                let path: &str = object_meta.location.as_ref();
+                Ok(StoreKey::try_from(path)?)
+            })
+            .try_chunks(page_size.get())
+            .map_err(|err| err.1)
+            .boxed()
+    }
+
     async fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
         let path: object_store::path::Path = prefix.as_str().into();
         let list_result = self.object_store.list_with_delimiter(Some(&path)).await?;
@@ -261,6 +458,8 @@ mod tests {
         super::super::test_util::store_write(&store).await?;
         super::super::test_util::store_read(&store).await?;
         super::super::test_util::store_list(&store).await?;
+        crate::storage::store_test_suite::async_all(&store, &"store_test_suite/".try_into()?)
+            .await?;
         Ok(())
     }
 
@@ -274,6 +473,25 @@ mod tests {
         super::super::test_util::store_write(&store).await?;
         super::super::test_util::store_read(&store).await?;
         super::super::test_util::store_list(&store).await?;
+        crate::storage::store_test_suite::async_all(&store, &"store_test_suite/".try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn memory_set_streaming_multipart() -> Result<(), Box<dyn Error>> {
+        let store = AsyncObjectStore::new(object_store::memory::InMemory::new());
+        let key = "multipart".try_into()?;
+        let part = AsyncBytes::from(vec![1u8; MULTIPART_PART_SIZE / 2 + 1]);
+        let mut writer = store.set_streaming(&key).await?;
+        for _ in 0..3 {
+            writer.write(part.clone()).await?;
+        }
+        writer.finish().await?;
+        let value = store.get(&key).await?.unwrap();
+        assert_eq!(value.len(), part.len() * 3);
+        assert!(value.iter().all(|&byte| byte == 1));
         Ok(())
     }
 }
@@ -223,6 +223,8 @@ mod tests {
         super::super::test_util::store_write(&store).await?;
         super::super::test_util::store_read(&store).await?;
         super::super::test_util::store_list(&store).await?;
+        crate::storage::store_test_suite::async_all(&store, &"store_test_suite/".try_into()?)
+            .await?;
         Ok(())
     }
 
@@ -236,6 +238,8 @@ mod tests {
         super::super::test_util::store_write(&store).await?;
         super::super::test_util::store_read(&store).await?;
         super::super::test_util::store_list(&store).await?;
+        crate::storage::store_test_suite::async_all(&store, &"store_test_suite/".try_into()?)
+            .await?;
         Ok(())
     }
 }
@@ -4,12 +4,18 @@ pub mod memory_store;
 #[cfg(feature = "http")]
 pub mod http_store;
 
+#[cfg(all(feature = "io_uring", target_os = "linux"))]
+pub mod io_uring_store;
+
+#[cfg(feature = "kerchunk")]
+pub mod kerchunk_store;
+
 #[cfg(feature = "opendal")]
 pub mod opendal;
 
 #[cfg(test)]
 mod test_util {
-    use std::error::Error;
+    use std::{error::Error, num::NonZeroUsize};
 
     use crate::{
         byte_range::ByteRange,
@@ -39,7 +45,14 @@ mod test_util {
         store.set_partial_values(&[StoreKeyStartValue::new("a/b".try_into()?, 3, &[3])])?;
         store.set_partial_values(&[StoreKeyStartValue::new("a/b".try_into()?, 0, &[0])])?;
 
-        store.set(&"a/c".try_into()?, vec![0].into())?;
+        let mut writer = store.set_streaming(&"a/c".try_into()?)?;
+        writer.write(vec![].into())?;
+        writer.write(vec![0].into())?;
+        writer.finish()?;
+
+        let mut writer = store.set_streaming(&"abandoned".try_into()?)?;
+        writer.write(vec![0].into())?;
+        drop(writer); // not stored
         store.set(&"a/d/e".try_into()?, vec![].into())?;
         store.set(&"a/f/g".try_into()?, vec![].into())?;
         store.set(&"a/f/h".try_into()?, vec![].into())?;
@@ -168,6 +181,29 @@ mod test_util {
                 &["a/d/".try_into()?, "a/f/".try_into()?,]
             );
         }
+
+        {
+            let page_size = NonZeroUsize::new(2).unwrap();
+            let pages = store
+                .list_prefix_pages(&"a/".try_into()?, page_size)
+                .collect::<Result<Vec<_>, _>>()?;
+            assert!(pages.iter().all(|page| page.len() <= 2));
+            let mut keys = pages.concat();
+            keys.sort();
+            assert_eq!(keys, store.list_prefix(&"a/".try_into()?)?);
+
+            let (mut keys, mut prefixes) = (vec![], vec![]);
+            for page in store.list_dir_pages(&"a/".try_into()?, page_size) {
+                let page = page?;
+                assert!(page.keys().len() + page.prefixes().len() <= 2);
+                keys.extend(page.keys);
+                prefixes.extend(page.prefixes);
+            }
+            keys.sort();
+            prefixes.sort();
+            assert_eq!(keys, &["a/b".try_into()?, "a/c".try_into()?]);
+            assert_eq!(prefixes, &["a/d/".try_into()?, "a/f/".try_into()?]);
+        }
         Ok(())
     }
 }
@@ -3,24 +3,35 @@
 //! See <https://zarr-specs.readthedocs.io/en/latest/v3/stores/filesystem/v1.0.html>.
 
 use crate::{
-    byte_range::{ByteOffset, ByteRange},
+    byte_range::{ByteOffset, ByteRange, InvalidByteRangeError},
     storage::{
-        store_set_partial_values, Bytes, ListableStorageTraits, ReadableStorageTraits,
+        into_pages, store_set_partial_values, Bytes, ListableStorageTraits, ReadableStorageTraits,
         ReadableWritableStorageTraits, StorageError, StoreKey, StoreKeyError, StoreKeyStartValue,
-        StoreKeys, StoreKeysPrefixes, StorePrefix, StorePrefixes, WritableStorageTraits,
+        StoreKeys, StoreKeysPages, StoreKeysPrefixes, StoreKeysPrefixesPages, StorePrefix,
+        StorePrefixes, StoreTransaction, StoreValueWriter, StoreWatchEvents,
+        WatchableStorageTraits, WritableStorageTraits,
     },
 };
 
+#[cfg(feature = "mmap")]
+use crate::byte_range::validate_byte_ranges;
+
+use itertools::Itertools;
 use parking_lot::RwLock;
 use thiserror::Error;
 use walkdir::WalkDir;
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     fs::{File, OpenOptions},
+    hash::{BuildHasher, Hasher},
     io::{Read, Seek, SeekFrom, Write},
+    num::NonZeroUsize,
     path::{Path, PathBuf},
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
 };
 
 // // Register the store.
@@ -47,11 +58,17 @@ use std::{
 /// A synchronous file system store.
 ///
 /// See <https://zarr-specs.readthedocs.io/en/latest/v3/stores/filesystem/v1.0.html>.
+///
+/// Streaming writes and transactions stage values in temporary files alongside their keys, which are not listed as keys.
+/// Their file names start with the reserved prefix `.zarrs-staging.` and end with a random suffix.
 #[derive(Debug)]
 pub struct FilesystemStore {
     base_path: PathBuf,
     sort: bool,
     readonly: bool,
+    advisory_locking: bool,
+    #[cfg(feature = "mmap")]
+    memory_map_min_size: Option<u64>,
     files: Mutex<HashMap<StoreKey, Arc<RwLock<()>>>>,
     // locks: StoreLocks,
 }
@@ -84,6 +101,9 @@ impl FilesystemStore {
             base_path,
             sort: false,
             readonly,
+            advisory_locking: false,
+            #[cfg(feature = "mmap")]
+            memory_map_min_size: None,
             files: Mutex::default(),
         })
         // Self::new_with_locks(base_path, Arc::new(DefaultStoreLocks::default()))
@@ -131,6 +151,32 @@ impl FilesystemStore {
         self
     }
 
+    /// Makes the store acquire advisory locks around read-modify-write operations.
+    ///
+    /// [`set_partial_values`](WritableStorageTraits::set_partial_values) holds an exclusive [`flock`](https://man7.org/linux/man-pages/man2/flock.2.html) on the directory containing a key while it is read, modified, and written.
+    /// This lets multiple processes (each with their own store) write to the same keys without losing updates.
+    /// Advisory locks only coordinate processes that also use them, and are only supported on Unix platforms.
+    #[must_use]
+    pub const fn advisory_locking(mut self) -> Self {
+        self.advisory_locking = true;
+        self
+    }
+
+    /// Makes the store memory map files of at least `min_size` bytes when reading them, rather than reading them into memory.
+    ///
+    /// The returned [`Bytes`] borrow the memory map, so retrieved chunks are decoded directly from the page cache without an intermediate copy.
+    /// This reduces memory traffic for large chunks that are uncompressed or lightly compressed, but is typically slower than reading for small files.
+    ///
+    /// # Safety
+    /// Files must not be modified or truncated while the bytes of a memory mapped read are alive, whether by this store, another store, or another process.
+    /// Otherwise, the bytes may change unexpectedly or accessing them may crash the process.
+    #[cfg(feature = "mmap")]
+    #[must_use]
+    pub const unsafe fn memory_mapped(mut self, min_size: u64) -> Self {
+        self.memory_map_min_size = Some(min_size);
+        self
+    }
+
     /// Maps a [`StoreKey`] to a filesystem [`PathBuf`].
     #[must_use]
     pub fn key_to_fspath(&self, key: &StoreKey) -> PathBuf {
@@ -157,7 +203,13 @@ impl FilesystemStore {
         path
     }
 
-    fn get_file_mutex(&self, key: &StoreKey) -> Arc<RwLock<()>> {
+    /// Returns true if the store is read only.
+    #[cfg(all(feature = "io_uring", target_os = "linux"))]
+    pub(super) const fn is_readonly(&self) -> bool {
+        self.readonly
+    }
+
+    pub(super) fn get_file_mutex(&self, key: &StoreKey) -> Arc<RwLock<()>> {
         let mut files = self.files.lock().unwrap();
         let file = files
             .entry(key.clone())
@@ -178,12 +230,7 @@ impl FilesystemStore {
         let _lock = file.write();
 
         // Create directories
-        let key_path = self.key_to_fspath(key);
-        if let Some(parent) = key_path.parent() {
-            if !parent.exists() {
-                std::fs::create_dir_all(parent)?;
-            }
-        }
+        let key_path = self.create_dir_for_key(key)?;
 
         let mut file = OpenOptions::new()
             .write(true)
@@ -199,6 +246,219 @@ impl FilesystemStore {
 
         Ok(())
     }
+
+    /// Acquire an advisory lock on the directory containing `key`, if advisory locking is enabled.
+    ///
+    /// The lock is released when the returned file is dropped.
+    fn lock_key_dir(&self, key: &StoreKey) -> Result<Option<File>, StorageError> {
+        if !self.advisory_locking {
+            return Ok(None);
+        }
+        let key_path = self.create_dir_for_key(key)?;
+        let dir = key_path.parent().unwrap_or(&self.base_path);
+        Ok(flock_exclusive(dir)?)
+    }
+
+    pub(super) fn create_dir_for_key(&self, key: &StoreKey) -> Result<PathBuf, StorageError> {
+        let key_path = self.key_to_fspath(key);
+        if let Some(parent) = key_path.parent() {
+            if !parent.exists() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        Ok(key_path)
+    }
+}
+
+/// Open `path` and hold an exclusive advisory lock on it.
+#[cfg(unix)]
+fn flock_exclusive(path: &Path) -> std::io::Result<Option<File>> {
+    use std::os::unix::io::AsRawFd;
+
+    let file = File::open(path)?;
+    loop {
+        // SAFETY: the file descriptor is valid while `file` is alive
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } == 0 {
+            return Ok(Some(file));
+        }
+        let err = std::io::Error::last_os_error();
+        if err.kind() != std::io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+}
+
+/// Advisory locks are not supported on this platform.
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+fn flock_exclusive(_path: &Path) -> std::io::Result<Option<File>> {
+    Ok(None)
+}
+
+/// A [`StoreValueWriter`] for a [`FilesystemStore`].
+///
+/// Bytes are written to a temporary file alongside the value, which replaces the value when the writer is finished.
+struct FilesystemStoreValueWriter<'a> {
+    store: &'a FilesystemStore,
+    key: StoreKey,
+    key_path: PathBuf,
+    temp_path: PathBuf,
+    file: Option<File>,
+}
+
+/// The reserved file name prefix of the temporary files staged by [`temp_path_for`].
+const TEMP_FILE_NAME_PREFIX: &str = ".zarrs-staging.";
+
+/// Returns a unique temporary path alongside `key_path` for staging a value.
+///
+/// The file name has a reserved prefix and a random suffix, so it does not collide with the file name of a key.
+fn temp_path_for(key_path: &Path) -> PathBuf {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut temp_name = std::ffi::OsString::from(TEMP_FILE_NAME_PREFIX);
+    temp_name.push(key_path.file_name().unwrap_or_default());
+    temp_name.push(format!(
+        ".{}.{}.{:016x}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed),
+        std::collections::hash_map::RandomState::new()
+            .build_hasher()
+            .finish()
+    ));
+    key_path.with_file_name(temp_name)
+}
+
+/// Returns true if `name` is the name of a temporary file staged by [`temp_path_for`].
+fn is_temp_file_name(name: &std::ffi::OsStr) -> bool {
+    name.to_str().is_some_and(|name| {
+        name.strip_prefix(TEMP_FILE_NAME_PREFIX)
+            .and_then(|name| name.rsplit_once('.'))
+            .is_some_and(|(_, suffix)| {
+                suffix.len() == 16 && suffix.bytes().all(|c| c.is_ascii_hexdigit())
+            })
+    })
+}
+
+impl<'a> FilesystemStoreValueWriter<'a> {
+    fn new(store: &'a FilesystemStore, key: StoreKey) -> Result<Self, StorageError> {
+        let key_path = store.create_dir_for_key(&key)?;
+        let temp_path = temp_path_for(&key_path);
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)?;
+        Ok(Self {
+            store,
+            key,
+            key_path,
+            temp_path,
+            file: Some(file),
+        })
+    }
+}
+
+impl StoreValueWriter for FilesystemStoreValueWriter<'_> {
+    fn write(&mut self, bytes: Bytes) -> Result<(), StorageError> {
+        if let Some(file) = &mut self.file {
+            file.write_all(&bytes)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), StorageError> {
+        if let Some(file) = self.file.take() {
+            drop(file);
+            let file = self.store.get_file_mutex(&self.key);
+            let _lock = file.write();
+            if let Err(err) = std::fs::rename(&self.temp_path, &self.key_path) {
+                let _ = std::fs::remove_file(&self.temp_path);
+                return Err(err.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for FilesystemStoreValueWriter<'_> {
+    fn drop(&mut self) {
+        if self.file.take().is_some() {
+            // The writer was not finished, so abandon the write
+            let _ = std::fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+/// A staged write of a [`FilesystemStoreTransaction`].
+enum FilesystemStoreTransactionOperation {
+    /// A value staged at `temp_path`, which replaces the value at `key_path` on commit.
+    Set {
+        key: StoreKey,
+        key_path: PathBuf,
+        temp_path: PathBuf,
+    },
+    Erase(StoreKey),
+}
+
+/// A [`StoreTransaction`] for a [`FilesystemStore`].
+///
+/// Values are staged in temporary files alongside their keys, which are renamed into place when the transaction is committed.
+/// Each value is replaced atomically, and the transaction is only partially visible while the files are being renamed.
+struct FilesystemStoreTransaction<'a> {
+    store: &'a FilesystemStore,
+    operations: VecDeque<FilesystemStoreTransactionOperation>,
+}
+
+impl StoreTransaction for FilesystemStoreTransaction<'_> {
+    fn set(&mut self, key: &StoreKey, value: Bytes) -> Result<(), StorageError> {
+        let key_path = self.store.create_dir_for_key(key)?;
+        let temp_path = temp_path_for(&key_path);
+        std::fs::write(&temp_path, &value)?;
+        self.operations
+            .push_back(FilesystemStoreTransactionOperation::Set {
+                key: key.clone(),
+                key_path,
+                temp_path,
+            });
+        Ok(())
+    }
+
+    fn erase(&mut self, key: &StoreKey) -> Result<(), StorageError> {
+        self.operations
+            .push_back(FilesystemStoreTransactionOperation::Erase(key.clone()));
+        Ok(())
+    }
+
+    fn commit(mut self: Box<Self>) -> Result<(), StorageError> {
+        while let Some(operation) = self.operations.pop_front() {
+            match operation {
+                FilesystemStoreTransactionOperation::Set {
+                    key,
+                    key_path,
+                    temp_path,
+                } => {
+                    let file = self.store.get_file_mutex(&key);
+                    let _lock = file.write();
+                    if let Err(err) = std::fs::rename(&temp_path, key_path) {
+                        let _ = std::fs::remove_file(&temp_path);
+                        return Err(err.into());
+                    }
+                }
+                FilesystemStoreTransactionOperation::Erase(key) => self.store.erase(&key)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for FilesystemStoreTransaction<'_> {
+    fn drop(&mut self) {
+        // Abandon the values which were not committed
+        for operation in &self.operations {
+            if let FilesystemStoreTransactionOperation::Set { temp_path, .. } = operation {
+                let _ = std::fs::remove_file(temp_path);
+            }
+        }
+    }
 }
 
 impl ReadableStorageTraits for FilesystemStore {
@@ -220,6 +480,22 @@ impl ReadableStorageTraits for FilesystemStore {
             }
         };
 
+        #[cfg(feature = "mmap")]
+        if let Some(memory_map_min_size) = self.memory_map_min_size {
+            let size = file.metadata()?.len();
+            if size > 0 && size >= memory_map_min_size {
+                // SAFETY: The caller of `memory_mapped` guarantees that files are not modified while mapped
+                let bytes = Bytes::from_owner(unsafe { memmap2::Mmap::map(&file)? });
+                validate_byte_ranges(byte_ranges, size)?;
+                return Ok(Some(
+                    byte_ranges
+                        .iter()
+                        .map(|byte_range| bytes.slice(byte_range.to_range_usize(size)))
+                        .collect(),
+                ));
+            }
+        }
+
         let mut out = Vec::with_capacity(byte_ranges.len());
         for byte_range in byte_ranges {
             let bytes = {
@@ -234,11 +510,20 @@ impl ReadableStorageTraits for FilesystemStore {
 
                 // Read
                 match byte_range {
-                    ByteRange::FromStart(_, None) | ByteRange::FromEnd(_, None) => {
+                    ByteRange::FromStart(_, None) => {
                         let mut buffer = Vec::new();
                         file.read_to_end(&mut buffer)?;
                         buffer
                     }
+                    ByteRange::FromEnd(offset, None) => {
+                        let size = file.metadata()?.len();
+                        let length = size
+                            .checked_sub(*offset)
+                            .ok_or(InvalidByteRangeError::new(*byte_range, size))?;
+                        let mut buffer = vec![0; usize::try_from(length).unwrap()];
+                        file.read_exact(&mut buffer)?;
+                        buffer
+                    }
                     ByteRange::FromStart(_, Some(length)) | ByteRange::FromEnd(_, Some(length)) => {
                         let length = usize::try_from(*length).unwrap();
                         let mut buffer = vec![0; length];
@@ -276,7 +561,20 @@ impl WritableStorageTraits for FilesystemStore {
             return Err(StorageError::ReadOnly);
         }
 
-        store_set_partial_values(self, key_start_values)
+        if self.advisory_locking {
+            // Lock and update each key separately, so that locks are never held on multiple directories
+            key_start_values
+                .iter()
+                .chunk_by(|key_start_value| &key_start_value.key)
+                .into_iter()
+                .try_for_each(|(key, group)| {
+                    let group = group.cloned().collect::<Vec<_>>();
+                    let _lock = self.lock_key_dir(key)?;
+                    store_set_partial_values(self, &group)
+                })
+        } else {
+            store_set_partial_values(self, key_start_values)
+        }
     }
 
     fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
@@ -317,6 +615,32 @@ impl WritableStorageTraits for FilesystemStore {
             Ok(())
         }
     }
+
+    fn set_streaming(
+        &self,
+        key: &StoreKey,
+    ) -> Result<Box<dyn StoreValueWriter + '_>, StorageError> {
+        if self.readonly {
+            return Err(StorageError::ReadOnly);
+        }
+
+        Ok(Box::new(FilesystemStoreValueWriter::new(
+            self,
+            key.clone(),
+        )?))
+    }
+
+    /// Begin a transaction which stages values in temporary files and renames them into place when committed.
+    fn begin(&self) -> Result<Box<dyn StoreTransaction + '_>, StorageError> {
+        if self.readonly {
+            return Err(StorageError::ReadOnly);
+        }
+
+        Ok(Box::new(FilesystemStoreTransaction {
+            store: self,
+            operations: VecDeque::new(),
+        }))
+    }
 }
 
 impl ReadableWritableStorageTraits for FilesystemStore {
@@ -331,7 +655,7 @@ impl ListableStorageTraits for FilesystemStore {
             .sort_by_file_name()
             .into_iter()
             .filter_map(std::result::Result::ok)
-            .filter(|v| v.path().is_file())
+            .filter(|v| v.path().is_file() && !is_temp_file_name(v.file_name()))
             .filter_map(|v| self.fspath_to_key(v.path()).ok())
             .collect())
     }
@@ -341,7 +665,7 @@ impl ListableStorageTraits for FilesystemStore {
             .sort_by_file_name()
             .into_iter()
             .filter_map(std::result::Result::ok)
-            .filter(|v| v.path().is_file())
+            .filter(|v| v.path().is_file() && !is_temp_file_name(v.file_name()))
             .filter_map(|v| self.fspath_to_key(v.path()).ok())
             .collect())
     }
@@ -360,7 +684,7 @@ impl ListableStorageTraits for FilesystemStore {
                     prefixes.push(StorePrefix::new(
                         prefix.as_str().to_string() + path.to_str().unwrap() + "/",
                     )?);
-                } else {
+                } else if !is_temp_file_name(path) {
                     keys.push(StoreKey::new(
                         prefix.as_str().to_owned() + path.to_str().unwrap(),
                     )?);
@@ -375,12 +699,84 @@ impl ListableStorageTraits for FilesystemStore {
         Ok(StoreKeysPrefixes { keys, prefixes })
     }
 
+    fn list_prefix_pages(
+        &self,
+        prefix: &StorePrefix,
+        page_size: NonZeroUsize,
+    ) -> StoreKeysPages<'_> {
+        let keys = WalkDir::new(self.prefix_to_fs_path(prefix))
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(std::result::Result::ok)
+            .filter(|v| v.path().is_file() && !is_temp_file_name(v.file_name()))
+            .filter_map(|v| self.fspath_to_key(v.path()).ok());
+        Box::new(into_pages(keys, page_size).map(Ok))
+    }
+
+    fn list_dir_pages(
+        &self,
+        prefix: &StorePrefix,
+        page_size: NonZeroUsize,
+    ) -> StoreKeysPrefixesPages<'_> {
+        if self.sort {
+            // Sorting requires every child in memory
+            return match self.list_dir(prefix) {
+                Ok(keys_prefixes) => Box::new(
+                    crate::storage::store_keys_prefixes_into_pages(keys_prefixes, page_size)
+                        .map(Ok),
+                ),
+                Err(err) => Box::new(std::iter::once(Err(err))),
+            };
+        }
+
+        let Ok(dir) = std::fs::read_dir(self.prefix_to_fs_path(prefix)) else {
+            return Box::new(std::iter::empty());
+        };
+        let prefix = prefix.clone();
+        let children = dir.map(move |entry| -> Result<StoreKeysPrefixes, StorageError> {
+            let fs_path = entry?.path();
+            let path = fs_path.file_name().unwrap();
+            if fs_path.is_dir() {
+                Ok(StoreKeysPrefixes {
+                    keys: vec![],
+                    prefixes: vec![StorePrefix::new(
+                        prefix.as_str().to_string() + path.to_str().unwrap() + "/",
+                    )?],
+                })
+            } else if is_temp_file_name(path) {
+                Ok(StoreKeysPrefixes {
+                    keys: vec![],
+                    prefixes: vec![],
+                })
+            } else {
+                Ok(StoreKeysPrefixes {
+                    keys: vec![StoreKey::new(
+                        prefix.as_str().to_owned() + path.to_str().unwrap(),
+                    )?],
+                    prefixes: vec![],
+                })
+            }
+        });
+        Box::new(into_pages(children, page_size).map(|children| {
+            let mut page = StoreKeysPrefixes {
+                keys: vec![],
+                prefixes: vec![],
+            };
+            for child in children {
+                let child = child?;
+                page.keys.extend(child.keys);
+                page.prefixes.extend(child.prefixes);
+            }
+            Ok(page)
+        }))
+    }
+
     fn size(&self) -> Result<u64, StorageError> {
         Ok(WalkDir::new(&self.base_path)
             .into_iter()
             .filter_map(std::result::Result::ok)
             .filter_map(|v| {
-                if v.path().is_file() {
+                if v.path().is_file() && !is_temp_file_name(v.file_name()) {
                     Some(std::fs::metadata(v.path()).unwrap().len())
                 } else {
                     None
@@ -411,6 +807,287 @@ pub enum FilesystemStoreCreateError {
     InvalidBasePath(PathBuf),
 }
 
+/// The polling interval of [`FilesystemStore::watch`] on platforms without file change notifications.
+#[cfg(not(target_os = "linux"))]
+const WATCH_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+impl WatchableStorageTraits for FilesystemStore {
+    /// Watch for changes to values with keys starting with `prefix`.
+    ///
+    /// On Linux, changes are detected with [`inotify`](https://man7.org/linux/man-pages/man7/inotify.7.html).
+    /// A value is reported as changed when its file is closed after writing, renamed, or deleted.
+    /// The temporary files of in-progress streaming writes are not reported.
+    ///
+    /// On other platforms, the store is polled every second with [`watch_polling`](crate::storage::watch_polling).
+    fn watch(&self, prefix: &StorePrefix) -> Result<StoreWatchEvents<'_>, StorageError> {
+        #[cfg(target_os = "linux")]
+        {
+            Ok(Box::new(inotify::InotifyWatcher::new(self, prefix)?))
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            crate::storage::watch_polling(self, prefix, WATCH_POLL_INTERVAL)
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod inotify {
+    use std::{
+        collections::{HashMap, HashSet, VecDeque},
+        ffi::{CString, OsStr},
+        fs::File,
+        io::Read,
+        mem::size_of,
+        os::unix::{
+            ffi::OsStrExt,
+            io::{AsRawFd, FromRawFd},
+        },
+        path::{Path, PathBuf},
+    };
+
+    use walkdir::WalkDir;
+
+    use crate::storage::{
+        ListableStorageTraits, StorageError, StoreKey, StorePrefix, StoreWatchEvent,
+    };
+
+    use super::{is_temp_file_name, FilesystemStore};
+
+    const WATCH_MASK: u32 = libc::IN_CREATE
+        | libc::IN_CLOSE_WRITE
+        | libc::IN_MOVED_TO
+        | libc::IN_MOVED_FROM
+        | libc::IN_DELETE;
+
+    /// Watches the directories of a [`FilesystemStore`] prefix with inotify.
+    pub(super) struct InotifyWatcher<'a> {
+        store: &'a FilesystemStore,
+        prefix: StorePrefix,
+        prefix_path: PathBuf,
+        inotify: File,
+        /// The directory of each watch descriptor.
+        watches: HashMap<i32, PathBuf>,
+        /// The keys of the values known to exist.
+        keys: HashSet<StoreKey>,
+        pending: VecDeque<StoreWatchEvent>,
+        buffer: Vec<u8>,
+    }
+
+    impl<'a> InotifyWatcher<'a> {
+        pub(super) fn new(
+            store: &'a FilesystemStore,
+            prefix: &StorePrefix,
+        ) -> Result<Self, StorageError> {
+            // SAFETY: inotify_init1 has no preconditions
+            let fd = unsafe { libc::inotify_init1(libc::IN_CLOEXEC) };
+            if fd < 0 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+            // SAFETY: the file descriptor is valid and exclusively owned by the watcher
+            let inotify = unsafe { File::from_raw_fd(fd) };
+
+            let mut watcher = Self {
+                store,
+                prefix: prefix.clone(),
+                prefix_path: store.prefix_to_fs_path(prefix),
+                inotify,
+                watches: HashMap::new(),
+                keys: HashSet::new(),
+                pending: VecDeque::new(),
+                buffer: vec![0; 64 * 1024],
+            };
+
+            // Watch from the closest existing ancestor of the prefix directory, so the prefix directory is watched once it is created
+            let mut path = watcher.prefix_path.clone();
+            while !path.is_dir() {
+                if !path.pop() {
+                    break;
+                }
+            }
+            watcher.watch_dir_all(&path)?;
+            watcher.keys = store.list_prefix(prefix)?.into_iter().collect();
+            Ok(watcher)
+        }
+
+        /// Returns true if `dir` is an ancestor of the prefix directory or within it.
+        fn is_relevant_dir(&self, dir: &Path) -> bool {
+            dir.starts_with(&self.prefix_path) || self.prefix_path.starts_with(dir)
+        }
+
+        /// Watch `dir` and its relevant subdirectories.
+        fn watch_dir_all(&mut self, dir: &Path) -> Result<(), StorageError> {
+            let dirs: Vec<PathBuf> = WalkDir::new(dir)
+                .into_iter()
+                .filter_entry(|entry| {
+                    entry.file_type().is_dir() && self.is_relevant_dir(entry.path())
+                })
+                .filter_map(std::result::Result::ok)
+                .map(walkdir::DirEntry::into_path)
+                .collect();
+            for dir in dirs {
+                self.add_watch(dir)?;
+            }
+            Ok(())
+        }
+
+        fn add_watch(&mut self, dir: PathBuf) -> Result<(), StorageError> {
+            let path = CString::new(dir.as_os_str().as_bytes())
+                .map_err(|err| StorageError::Other(err.to_string()))?;
+            // SAFETY: the inotify file descriptor is valid and the path is nul terminated
+            let wd = unsafe {
+                libc::inotify_add_watch(self.inotify.as_raw_fd(), path.as_ptr(), WATCH_MASK)
+            };
+            if wd < 0 {
+                let err = std::io::Error::last_os_error();
+                // The directory may have been removed since it was found
+                return match err.kind() {
+                    std::io::ErrorKind::NotFound => Ok(()),
+                    _ => Err(err.into()),
+                };
+            }
+            self.watches.insert(wd, dir);
+            Ok(())
+        }
+
+        /// Stop watching `dir` and its subdirectories.
+        fn remove_watches(&mut self, dir: &Path) {
+            let fd = self.inotify.as_raw_fd();
+            self.watches.retain(|wd, path| {
+                if path.starts_with(dir) {
+                    // SAFETY: the inotify file descriptor is valid
+                    unsafe { libc::inotify_rm_watch(fd, *wd) };
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+
+        fn key(&self, path: &Path) -> Option<StoreKey> {
+            let key = self.store.fspath_to_key(path).ok()?;
+            key.has_prefix(&self.prefix).then_some(key)
+        }
+
+        fn value_written(&mut self, path: &Path) {
+            if let Some(key) = self.key(path) {
+                self.pending.push_back(if self.keys.insert(key.clone()) {
+                    StoreWatchEvent::Created(key)
+                } else {
+                    StoreWatchEvent::Modified(key)
+                });
+            }
+        }
+
+        fn value_removed(&mut self, path: &Path) {
+            if let Some(key) = self.key(path) {
+                if self.keys.remove(&key) {
+                    self.pending.push_back(StoreWatchEvent::Erased(key));
+                }
+            }
+        }
+
+        /// Block until inotify events are available and queue the corresponding store watch events.
+        fn read_events(&mut self) -> Result<(), StorageError> {
+            let len = loop {
+                match self.inotify.read(&mut self.buffer) {
+                    Ok(len) => break len,
+                    Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                    Err(err) => return Err(err.into()),
+                }
+            };
+
+            let mut offset = 0;
+            while offset + size_of::<libc::inotify_event>() <= len {
+                // SAFETY: the kernel writes complete events to the buffer
+                let event: libc::inotify_event =
+                    unsafe { std::ptr::read_unaligned(self.buffer.as_ptr().add(offset).cast()) };
+                let name_start = offset + size_of::<libc::inotify_event>();
+                offset = name_start + event.len as usize;
+                // The name is padded with nul bytes
+                let name = self.buffer[name_start..offset]
+                    .split(|&byte| byte == 0)
+                    .next()
+                    .unwrap_or_default();
+                let name = OsStr::from_bytes(name).to_os_string();
+                self.handle_event(event.wd, event.mask, &name)?;
+            }
+            Ok(())
+        }
+
+        fn handle_event(&mut self, wd: i32, mask: u32, name: &OsStr) -> Result<(), StorageError> {
+            if mask & libc::IN_Q_OVERFLOW != 0 {
+                return Err(StorageError::Other(
+                    "the inotify event queue overflowed".to_string(),
+                ));
+            }
+            if mask & libc::IN_IGNORED != 0 {
+                self.watches.remove(&wd);
+                return Ok(());
+            }
+            let Some(dir) = self.watches.get(&wd) else {
+                return Ok(());
+            };
+            let path = dir.join(name);
+
+            if mask & libc::IN_ISDIR != 0 {
+                if mask & (libc::IN_CREATE | libc::IN_MOVED_TO) != 0 && self.is_relevant_dir(&path)
+                {
+                    // Values may have been written to the directory before it was watched
+                    self.watch_dir_all(&path)?;
+                    let files: Vec<PathBuf> = WalkDir::new(&path)
+                        .sort_by_file_name()
+                        .into_iter()
+                        .filter_map(std::result::Result::ok)
+                        .filter(|entry| {
+                            entry.file_type().is_file() && !is_temp_file_name(entry.file_name())
+                        })
+                        .map(walkdir::DirEntry::into_path)
+                        .collect();
+                    for file in files {
+                        self.value_written(&file);
+                    }
+                } else if mask & libc::IN_MOVED_FROM != 0 {
+                    self.remove_watches(&path);
+                    let mut erased: Vec<StoreKey> = self
+                        .keys
+                        .iter()
+                        .filter(|key| self.store.key_to_fspath(key).starts_with(&path))
+                        .cloned()
+                        .collect();
+                    erased.sort();
+                    for key in erased {
+                        self.keys.remove(&key);
+                        self.pending.push_back(StoreWatchEvent::Erased(key));
+                    }
+                }
+            } else if is_temp_file_name(name) {
+                // Ignore the temporary files of streaming writes
+            } else if mask & (libc::IN_CLOSE_WRITE | libc::IN_MOVED_TO) != 0 {
+                self.value_written(&path);
+            } else if mask & (libc::IN_DELETE | libc::IN_MOVED_FROM) != 0 {
+                self.value_removed(&path);
+            }
+            Ok(())
+        }
+    }
+
+    impl Iterator for InotifyWatcher<'_> {
+        type Item = Result<StoreWatchEvent, StorageError>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            loop {
+                if let Some(event) = self.pending.pop_front() {
+                    return Some(Ok(event));
+                }
+                if let Err(err) = self.read_events() {
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -424,6 +1101,187 @@ mod tests {
         super::super::test_util::store_write(&store)?;
         super::super::test_util::store_read(&store)?;
         super::super::test_util::store_list(&store)?;
+        crate::storage::store_test_suite::all(&store, &"store_test_suite/".try_into()?)?;
+        Ok(())
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn filesystem_list_during_streaming_write() -> Result<(), Box<dyn Error>> {
+        let path = tempfile::TempDir::new()?;
+        let store = FilesystemStore::new(path.path())?.sorted();
+        store.set(&"a/b".try_into()?, vec![0].into())?;
+
+        let mut writer = store.set_streaming(&"a/c".try_into()?)?;
+        writer.write(vec![0, 1, 2].into())?;
+        assert_eq!(store.list()?, &["a/b".try_into()?]);
+        assert_eq!(store.list_prefix(&"a/".try_into()?)?, &["a/b".try_into()?]);
+        assert_eq!(
+            store.list_dir(&"a/".try_into()?)?.keys(),
+            &["a/b".try_into()?]
+        );
+        assert_eq!(store.size()?, 1);
+
+        writer.finish()?;
+        assert_eq!(store.list()?, &["a/b".try_into()?, "a/c".try_into()?]);
+        assert_eq!(store.size()?, 4);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn filesystem_list_keys_resembling_temp_files() -> Result<(), Box<dyn Error>> {
+        let path = tempfile::TempDir::new()?;
+        let store = FilesystemStore::new(path.path())?.sorted();
+        let keys: Vec<StoreKey> = [".foo.partial", ".zarrs-staging.foo", "a/.b.partial"]
+            .into_iter()
+            .map(StoreKey::new)
+            .collect::<Result<_, _>>()?;
+        for key in &keys {
+            store.set(key, vec![0].into())?;
+        }
+
+        let mut writer = store.set_streaming(&"a/c".try_into()?)?;
+        writer.write(vec![0, 1, 2].into())?;
+        assert_eq!(store.list()?, keys);
+        assert_eq!(
+            store.list_dir(&"a/".try_into()?)?.keys(),
+            &["a/.b.partial".try_into()?]
+        );
+        assert_eq!(store.size()?, 3);
+        writer.finish()?;
+        assert_eq!(store.list_prefix(&"a/".try_into()?)?.len(), 2);
+        Ok(())
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn filesystem_memory_mapped() -> Result<(), Box<dyn Error>> {
+        let path = tempfile::TempDir::new()?;
+        // SAFETY: Files are not modified while the bytes of a read are alive
+        let store = unsafe { FilesystemStore::new(path.path())?.sorted().memory_mapped(4) };
+        super::super::test_util::store_write(&store)?;
+        super::super::test_util::store_read(&store)?;
+
+        let key = "memory_mapped".try_into()?;
+        store.set(&key, vec![0, 1, 2, 3, 4, 5].into())?;
+        assert_eq!(
+            store.get_partial_values_key(
+                &key,
+                &[
+                    ByteRange::FromStart(1, Some(2)),
+                    ByteRange::FromEnd(0, Some(1))
+                ]
+            )?,
+            Some(vec![vec![1, 2].into(), vec![5].into()])
+        );
+        assert!(store
+            .get_partial_values_key(&key, &[ByteRange::FromStart(4, Some(3))])
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn filesystem_list_dir_pages_unsorted() -> Result<(), Box<dyn Error>> {
+        let path = tempfile::TempDir::new()?;
+        let store = FilesystemStore::new(path.path())?;
+        super::super::test_util::store_write(&store)?;
+        let mut children = vec![];
+        for page in store.list_dir_pages(&"a/".try_into()?, NonZeroUsize::new(3).unwrap()) {
+            let page = page?;
+            assert!(page.keys.len() + page.prefixes.len() <= 3);
+            children.extend(page.keys.iter().map(ToString::to_string));
+            children.extend(page.prefixes.iter().map(ToString::to_string));
+        }
+        children.sort();
+        assert_eq!(children, ["a/b", "a/c", "a/d/", "a/f/"]);
+        assert_eq!(
+            store
+                .list_dir_pages(&"missing/".try_into()?, NonZeroUsize::new(3).unwrap())
+                .count(),
+            0
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn filesystem_advisory_locking() -> Result<(), Box<dyn Error>> {
+        let path = tempfile::TempDir::new()?;
+        let store = FilesystemStore::new(path.path())?
+            .sorted()
+            .advisory_locking();
+        super::super::test_util::store_write(&store)?;
+        super::super::test_util::store_read(&store)?;
+        super::super::test_util::store_list(&store)?;
+        crate::storage::store_test_suite::all(&store, &"store_test_suite/".try_into()?)?;
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    #[cfg_attr(miri, ignore)]
+    fn filesystem_advisory_locking_concurrent() -> Result<(), Box<dyn Error>> {
+        // Separate stores do not share in-process locks, like stores in separate processes
+        const WRITERS: u8 = 8;
+        let path = tempfile::TempDir::new()?;
+        let key: StoreKey = "a/b".try_into()?;
+        std::thread::scope(|scope| {
+            for writer in 0..WRITERS {
+                let store = FilesystemStore::new(path.path())
+                    .unwrap()
+                    .advisory_locking();
+                let key = key.clone();
+                scope.spawn(move || {
+                    for _ in 0..20 {
+                        let value = [writer + 1];
+                        store
+                            .set_partial_values(&[StoreKeyStartValue::new(
+                                key.clone(),
+                                writer.into(),
+                                &value,
+                            )])
+                            .unwrap();
+                    }
+                });
+            }
+        });
+        let store = FilesystemStore::new(path.path())?;
+        assert_eq!(
+            store.get(&key)?,
+            Some((1..=WRITERS).collect::<Vec<u8>>().into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    #[cfg_attr(miri, ignore)]
+    fn filesystem_watch() -> Result<(), Box<dyn Error>> {
+        use crate::storage::StoreWatchEvent;
+
+        let path = tempfile::TempDir::new()?;
+        let store = FilesystemStore::new(path.path())?;
+        store.set(&"a/b".try_into()?, vec![0].into())?;
+
+        let mut events = store.watch(&StorePrefix::root())?;
+        store.set(&"a/b".try_into()?, vec![1].into())?;
+        store.set(&"c".try_into()?, vec![0].into())?;
+        let mut writer = store.set_streaming(&"d".try_into()?)?;
+        writer.write(vec![0].into())?;
+        writer.finish()?;
+        store.erase(&"a/b".try_into()?)?;
+        assert_eq!(
+            events.by_ref().take(4).collect::<Result<Vec<_>, _>>()?,
+            vec![
+                StoreWatchEvent::Modified("a/b".try_into()?),
+                StoreWatchEvent::Created("c".try_into()?),
+                StoreWatchEvent::Created("d".try_into()?),
+                StoreWatchEvent::Erased("a/b".try_into()?),
+            ]
+        );
         Ok(())
     }
 }
@@ -0,0 +1,518 @@
+//! A filesystem store using [`io_uring`](https://man7.org/linux/man-pages/man7/io_uring.7.html) for reads and writes.
+//!
+//! Linux only.
+
+use crate::{
+    byte_range::{ByteRange, InvalidByteRangeError},
+    storage::{
+        store_set_partial_values, Bytes, ListableStorageTraits, ReadableStorageTraits,
+        ReadableWritableStorageTraits, StorageError, StoreKey, StoreKeyStartValue, StoreKeys,
+        StoreKeysPages, StoreKeysPrefixes, StoreKeysPrefixesPages, StorePrefix, StoreValueWriter,
+        WritableStorageTraits,
+    },
+};
+
+use io_uring::{opcode, squeue, types, IoUring};
+use parking_lot::Mutex;
+use thiserror::Error;
+
+use std::{
+    fs::{File, OpenOptions},
+    num::NonZeroUsize,
+    os::unix::io::AsRawFd,
+    path::Path,
+};
+
+use super::filesystem_store::{FilesystemStore, FilesystemStoreCreateError};
+
+/// The maximum length of a single read or write operation.
+///
+/// Longer values are read or written with multiple operations.
+const MAX_OPERATION_LENGTH: usize = 1 << 30;
+
+/// A synchronous file system store using [`io_uring`](https://man7.org/linux/man-pages/man7/io_uring.7.html) for reads and writes.
+///
+/// Keys are mapped to files in the same way as a [`FilesystemStore`], so the two stores can be used interchangeably on the same directory.
+///
+/// All byte ranges requested from a value are read in a single batch of submissions, rather than with a seek and read system call per byte range.
+/// Byte ranges that fit in a registered buffer are read with fixed buffer operations, which avoids mapping the destination buffer in the kernel for each read.
+///
+/// Each concurrent operation uses its own ring from a pool that grows to the number of concurrent operations.
+/// Listing, erasing, partial writes, and streaming writes are delegated to a [`FilesystemStore`].
+#[derive(Debug)]
+pub struct IoUringStore {
+    filesystem: FilesystemStore,
+    queue_depth: u32,
+    registered_buffers: usize,
+    registered_buffer_size: usize,
+    rings: Mutex<Vec<Ring>>,
+}
+
+/// A ring and its registered buffers.
+struct Ring {
+    // The ring is dropped before the buffers it has registered
+    ring: IoUring,
+    buffers: Vec<Box<[u8]>>,
+}
+
+impl std::fmt::Debug for Ring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Ring")
+            .field("buffers", &self.buffers.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Ring {
+    fn new(
+        queue_depth: u32,
+        registered_buffers: usize,
+        registered_buffer_size: usize,
+    ) -> std::io::Result<Self> {
+        let ring = IoUring::new(queue_depth)?;
+        let buffers: Vec<Box<[u8]>> = (0..registered_buffers)
+            .map(|_| vec![0; registered_buffer_size].into_boxed_slice())
+            .collect();
+        if !buffers.is_empty() {
+            let iovecs: Vec<libc::iovec> = buffers
+                .iter()
+                .map(|buffer| libc::iovec {
+                    iov_base: buffer.as_ptr() as *mut libc::c_void,
+                    iov_len: buffer.len(),
+                })
+                .collect();
+            // SAFETY: the buffers are not moved or dropped until after the ring is dropped
+            unsafe { ring.submitter().register_buffers(&iovecs)? };
+        }
+        Ok(Self { ring, buffers })
+    }
+
+    /// Submit `entries` and wait for their completions.
+    ///
+    /// Returns the result of each entry, in the order of `entries`.
+    /// The user data of each entry must be its index in `entries`.
+    ///
+    /// # Safety
+    /// The buffers referenced by `entries` must be valid until this function returns.
+    unsafe fn submit(&mut self, entries: &[squeue::Entry]) -> std::io::Result<Vec<i32>> {
+        let mut results = vec![0; entries.len()];
+        for batch in entries.chunks(self.ring.params().sq_entries() as usize) {
+            // The submission queue is empty, so a batch of at most its capacity always fits
+            self.ring
+                .submission()
+                .push_multiple(batch)
+                .map_err(std::io::Error::other)?;
+            let mut completed = 0;
+            while completed < batch.len() {
+                match self.ring.submit_and_wait(batch.len() - completed) {
+                    Ok(_) => {}
+                    Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(err) => return Err(err),
+                }
+                for entry in self.ring.completion() {
+                    results[usize::try_from(entry.user_data()).unwrap()] = entry.result();
+                    completed += 1;
+                }
+            }
+        }
+        Ok(results)
+    }
+}
+
+/// A pending read of a byte range.
+struct PendingRead {
+    index: usize,
+    offset: u64,
+    buffer: Vec<u8>,
+    filled: usize,
+}
+
+impl IoUringStore {
+    /// Create a new `io_uring` file system store at a given `base_path`.
+    ///
+    /// Each ring has a queue depth of 64 and 16 registered buffers of 64 KiB.
+    ///
+    /// # Errors
+    /// Returns a [`IoUringStoreCreateError`] if `base_directory`:
+    ///   - is not valid, or
+    ///   - it points to an existing file rather than a directory,
+    ///
+    /// or `io_uring` is not supported.
+    pub fn new<P: AsRef<Path>>(base_path: P) -> Result<Self, IoUringStoreCreateError> {
+        Self::new_with_options(base_path, 64, 16, 64 * 1024)
+    }
+
+    /// Create a new `io_uring` file system store at a given `base_path` with non-default ring options.
+    ///
+    /// Each ring has a submission queue with `queue_depth` entries, and `registered_buffers` registered buffers of `registered_buffer_size` bytes.
+    /// Byte ranges longer than `registered_buffer_size` are read directly into their output buffers.
+    ///
+    /// # Errors
+    /// Returns a [`IoUringStoreCreateError`] if `base_directory`:
+    ///   - is not valid, or
+    ///   - it points to an existing file rather than a directory,
+    ///
+    /// or `io_uring` is not supported or the ring options are invalid.
+    pub fn new_with_options<P: AsRef<Path>>(
+        base_path: P,
+        queue_depth: u32,
+        registered_buffers: usize,
+        registered_buffer_size: usize,
+    ) -> Result<Self, IoUringStoreCreateError> {
+        let filesystem = FilesystemStore::new(base_path)?;
+        // Create a ring up front to check that io_uring is supported
+        let ring = Ring::new(queue_depth, registered_buffers, registered_buffer_size)
+            .map_err(IoUringStoreCreateError::IoUringError)?;
+        Ok(Self {
+            filesystem,
+            queue_depth,
+            registered_buffers,
+            registered_buffer_size,
+            rings: Mutex::new(vec![ring]),
+        })
+    }
+
+    /// Makes the store sort directories/files when walking.
+    #[must_use]
+    pub fn sorted(mut self) -> Self {
+        self.filesystem = self.filesystem.sorted();
+        self
+    }
+
+    /// Maps a [`StoreKey`] to a filesystem [`PathBuf`](std::path::PathBuf).
+    #[must_use]
+    pub fn key_to_fspath(&self, key: &StoreKey) -> std::path::PathBuf {
+        self.filesystem.key_to_fspath(key)
+    }
+
+    /// Run `f` with a ring from the pool, creating a new ring if none are available.
+    fn with_ring<T>(&self, f: impl FnOnce(&mut Ring) -> std::io::Result<T>) -> std::io::Result<T> {
+        let ring = self.rings.lock().pop();
+        let mut ring = match ring {
+            Some(ring) => ring,
+            None => Ring::new(
+                self.queue_depth,
+                self.registered_buffers,
+                self.registered_buffer_size,
+            )?,
+        };
+        let result = f(&mut ring);
+        self.rings.lock().push(ring);
+        result
+    }
+
+    /// Read `reads` from `file`.
+    ///
+    /// Returns the bytes of each read, in the order of their indices.
+    fn read(
+        file: &File,
+        ring: &mut Ring,
+        mut reads: Vec<PendingRead>,
+    ) -> std::io::Result<Vec<Vec<u8>>> {
+        let fd = types::Fd(file.as_raw_fd());
+        let mut out = vec![Vec::new(); reads.len()];
+        reads.retain(|read| !read.buffer.is_empty());
+        while !reads.is_empty() {
+            // Reads that fit in a registered buffer use it, the others read directly into their output buffer
+            let mut fixed = Vec::with_capacity(reads.len());
+            let mut entries = Vec::with_capacity(reads.len());
+            for (i, read) in reads.iter_mut().enumerate() {
+                let remaining = (read.buffer.len() - read.filled).min(MAX_OPERATION_LENGTH);
+                let length = u32::try_from(remaining).unwrap();
+                let offset = read.offset + read.filled as u64;
+                let entry = match ring.buffers.get_mut(i) {
+                    Some(buffer) if remaining <= buffer.len() => {
+                        fixed.push(true);
+                        opcode::ReadFixed::new(
+                            fd,
+                            buffer.as_mut_ptr(),
+                            length,
+                            u16::try_from(i).unwrap(),
+                        )
+                        .offset(offset)
+                        .build()
+                    }
+                    _ => {
+                        fixed.push(false);
+                        opcode::Read::new(fd, read.buffer[read.filled..].as_mut_ptr(), length)
+                            .offset(offset)
+                            .build()
+                    }
+                };
+                entries.push(entry.user_data(i as u64));
+            }
+            // SAFETY: the output buffers and registered buffers outlive the submission
+            let results = unsafe { ring.submit(&entries)? };
+
+            let mut pending = Vec::new();
+            for (i, (mut read, result)) in std::iter::zip(reads, results).enumerate() {
+                let length = usize::try_from(result)
+                    .map_err(|_| std::io::Error::from_raw_os_error(-result))?;
+                if length == 0 {
+                    return Err(std::io::ErrorKind::UnexpectedEof.into());
+                }
+                if fixed[i] {
+                    read.buffer[read.filled..read.filled + length]
+                        .copy_from_slice(&ring.buffers[i][..length]);
+                }
+                read.filled += length;
+                if read.filled < read.buffer.len() {
+                    pending.push(read);
+                } else {
+                    out[read.index] = read.buffer;
+                }
+            }
+            reads = pending;
+        }
+        Ok(out)
+    }
+
+    /// Write `value` to `file`.
+    fn write(file: &File, ring: &mut Ring, value: &[u8]) -> std::io::Result<()> {
+        let fd = types::Fd(file.as_raw_fd());
+        let mut written = 0;
+        while written < value.len() {
+            let length = (value.len() - written).min(MAX_OPERATION_LENGTH);
+            let entry = opcode::Write::new(
+                fd,
+                value[written..].as_ptr(),
+                u32::try_from(length).unwrap(),
+            )
+            .offset(written as u64)
+            .build()
+            .user_data(0);
+            // SAFETY: the value outlives the submission
+            let result = unsafe { ring.submit(&[entry])? }[0];
+            let length =
+                usize::try_from(result).map_err(|_| std::io::Error::from_raw_os_error(-result))?;
+            if length == 0 {
+                return Err(std::io::ErrorKind::WriteZero.into());
+            }
+            written += length;
+        }
+        Ok(())
+    }
+}
+
+impl ReadableStorageTraits for IoUringStore {
+    fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<Bytes>>, StorageError> {
+        let file = self.filesystem.get_file_mutex(key);
+        let _lock = file.read();
+
+        let file = match File::open(self.key_to_fspath(key)) {
+            Ok(file) => file,
+            Err(err) => {
+                if err.kind() == std::io::ErrorKind::NotFound {
+                    return Ok(None);
+                }
+                return Err(err.into());
+            }
+        };
+
+        let size = if byte_ranges
+            .iter()
+            .any(|byte_range| !matches!(byte_range, ByteRange::FromStart(_, Some(_))))
+        {
+            file.metadata()?.len()
+        } else {
+            0
+        };
+        let reads = byte_ranges
+            .iter()
+            .enumerate()
+            .map(|(index, byte_range)| {
+                let invalid_byte_range = || InvalidByteRangeError::new(*byte_range, size);
+                let (offset, length) = match *byte_range {
+                    ByteRange::FromStart(offset, Some(length)) => (offset, length),
+                    ByteRange::FromStart(offset, None) => (offset, size.saturating_sub(offset)),
+                    ByteRange::FromEnd(offset, length) => {
+                        let end = size.checked_sub(offset).ok_or_else(invalid_byte_range)?;
+                        let length = length.unwrap_or(end);
+                        (
+                            end.checked_sub(length).ok_or_else(invalid_byte_range)?,
+                            length,
+                        )
+                    }
+                };
+                Ok(PendingRead {
+                    index,
+                    offset,
+                    buffer: vec![0; usize::try_from(length).unwrap()],
+                    filled: 0,
+                })
+            })
+            .collect::<Result<Vec<_>, StorageError>>()?;
+
+        let out = self.with_ring(|ring| Self::read(&file, ring, reads))?;
+        Ok(Some(out.into_iter().map(Bytes::from).collect()))
+    }
+
+    fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        self.filesystem.size_key(key)
+    }
+}
+
+impl WritableStorageTraits for IoUringStore {
+    fn set(&self, key: &StoreKey, value: Bytes) -> Result<(), StorageError> {
+        if self.filesystem.is_readonly() {
+            return Err(StorageError::ReadOnly);
+        }
+
+        let file = self.filesystem.get_file_mutex(key);
+        let _lock = file.write();
+
+        let key_path = self.filesystem.create_dir_for_key(key)?;
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(key_path)?;
+        Ok(self.with_ring(|ring| Self::write(&file, ring, &value))?)
+    }
+
+    fn set_partial_values(
+        &self,
+        key_start_values: &[StoreKeyStartValue],
+    ) -> Result<(), StorageError> {
+        if self.filesystem.is_readonly() {
+            return Err(StorageError::ReadOnly);
+        }
+        store_set_partial_values(self, key_start_values)
+    }
+
+    fn erase(&self, key: &StoreKey) -> Result<(), StorageError> {
+        self.filesystem.erase(key)
+    }
+
+    fn erase_prefix(&self, prefix: &StorePrefix) -> Result<(), StorageError> {
+        self.filesystem.erase_prefix(prefix)
+    }
+
+    fn set_streaming(
+        &self,
+        key: &StoreKey,
+    ) -> Result<Box<dyn StoreValueWriter + '_>, StorageError> {
+        self.filesystem.set_streaming(key)
+    }
+}
+
+impl ReadableWritableStorageTraits for IoUringStore {}
+
+impl ListableStorageTraits for IoUringStore {
+    fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.filesystem.list()
+    }
+
+    fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        self.filesystem.list_prefix(prefix)
+    }
+
+    fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        self.filesystem.list_dir(prefix)
+    }
+
+    fn list_prefix_pages(
+        &self,
+        prefix: &StorePrefix,
+        page_size: NonZeroUsize,
+    ) -> StoreKeysPages<'_> {
+        self.filesystem.list_prefix_pages(prefix, page_size)
+    }
+
+    fn list_dir_pages(
+        &self,
+        prefix: &StorePrefix,
+        page_size: NonZeroUsize,
+    ) -> StoreKeysPrefixesPages<'_> {
+        self.filesystem.list_dir_pages(prefix, page_size)
+    }
+
+    fn size(&self) -> Result<u64, StorageError> {
+        self.filesystem.size()
+    }
+
+    fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        self.filesystem.size_prefix(prefix)
+    }
+}
+
+/// An `io_uring` store creation error.
+#[derive(Debug, Error)]
+pub enum IoUringStoreCreateError {
+    /// A filesystem store creation error.
+    #[error(transparent)]
+    FilesystemStoreCreateError(#[from] FilesystemStoreCreateError),
+    /// An error creating a ring, such as if `io_uring` is not supported.
+    #[error("failed to create io_uring: {0}")]
+    IoUringError(std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    fn io_uring_store(path: &Path) -> Result<Option<IoUringStore>, Box<dyn Error>> {
+        match IoUringStore::new_with_options(path, 4, 2, 2) {
+            Ok(store) => Ok(Some(store.sorted())),
+            // io_uring may be unsupported or disabled, such as in some containers
+            Err(IoUringStoreCreateError::IoUringError(_)) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn io_uring() -> Result<(), Box<dyn Error>> {
+        let path = tempfile::TempDir::new()?;
+        let Some(store) = io_uring_store(path.path())? else {
+            return Ok(());
+        };
+        super::super::test_util::store_write(&store)?;
+        super::super::test_util::store_read(&store)?;
+        super::super::test_util::store_list(&store)?;
+        crate::storage::store_test_suite::all(&store, &"store_test_suite/".try_into()?)?;
+        Ok(())
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn io_uring_batched_reads() -> Result<(), Box<dyn Error>> {
+        let path = tempfile::TempDir::new()?;
+        let Some(store) = io_uring_store(path.path())? else {
+            return Ok(());
+        };
+        let key: StoreKey = "a/b".try_into()?;
+        let value: Vec<u8> = (0..=255).collect();
+        store.set(&key, value.clone().into())?;
+        assert_eq!(
+            FilesystemStore::new(path.path())?.get(&key)?,
+            Some(value.clone().into())
+        );
+
+        // More byte ranges than submission queue entries and registered buffers, some longer than the registered buffers
+        let byte_ranges: Vec<ByteRange> = (0..8)
+            .map(|i| ByteRange::FromStart(i * 3, Some(i)))
+            .chain([
+                ByteRange::FromStart(250, None),
+                ByteRange::FromEnd(0, Some(4)),
+                ByteRange::FromEnd(250, None),
+            ])
+            .collect();
+        let values = store.get_partial_values_key(&key, &byte_ranges)?.unwrap();
+        for (byte_range, bytes) in std::iter::zip(&byte_ranges, values) {
+            assert_eq!(&bytes[..], &value[byte_range.to_range_usize(256)]);
+        }
+        assert!(store
+            .get_partial_values_key(&key, &[ByteRange::FromStart(250, Some(10))])
+            .is_err());
+        assert!(store
+            .get_partial_values_key(&key, &[ByteRange::FromEnd(257, None)])
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,303 @@
+//! A synchronous read-only kerchunk reference store.
+
+use crate::{
+    byte_range::{extract_byte_ranges, ByteRange},
+    storage::{
+        store::kerchunk_references::{plan_coalesced_reads, target_byte_ranges, KerchunkTargets},
+        Bytes, ListableStorageTraits, MaybeBytes, ReadableStorage, ReadableStorageTraits,
+        StorageError, StoreKey, StoreKeys, StoreKeysPrefixes, StorePrefix,
+    },
+};
+
+use super::super::{KerchunkReference, KerchunkReferences};
+
+/// A synchronous read-only store of [kerchunk](https://fsspec.github.io/kerchunk/) references.
+///
+/// Each key of the store is a [`KerchunkReference`] to an inline value, an entire target, or a byte range of a target.
+/// Targets are identified by URL, and are read from the store added with [`with_target`](KerchunkStore::with_target) for the longest matching URL prefix.
+/// This enables `HDF5` and `NetCDF` files indexed by kerchunk to be read as Zarr (V2) hierarchies.
+pub struct KerchunkStore {
+    references: KerchunkReferences,
+    targets: KerchunkTargets<ReadableStorage>,
+}
+
+impl KerchunkStore {
+    /// Create a new kerchunk store from kerchunk `references`.
+    #[must_use]
+    pub fn new(references: KerchunkReferences) -> Self {
+        Self {
+            references,
+            targets: KerchunkTargets::default(),
+        }
+    }
+
+    /// Read targets with URLs starting with `url_prefix` from `storage`.
+    ///
+    /// The key of a target in `storage` is the remainder of its URL after `url_prefix`.
+    /// For example, with a `url_prefix` of `s3://bucket/`, the URL `s3://bucket/data/file.nc` is read from the key `data/file.nc`.
+    #[must_use]
+    pub fn with_target(mut self, url_prefix: impl Into<String>, storage: ReadableStorage) -> Self {
+        self.targets.add(url_prefix.into(), storage);
+        self
+    }
+
+    /// Return the kerchunk references of the store.
+    #[must_use]
+    pub const fn references(&self) -> &KerchunkReferences {
+        &self.references
+    }
+}
+
+impl ReadableStorageTraits for KerchunkStore {
+    fn get_partial_values_key(
+        &self,
+        key: &StoreKey,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<Bytes>>, StorageError> {
+        let (url, byte_ranges) = match self.references.get(key) {
+            None => return Ok(None),
+            Some(KerchunkReference::Inline(value)) => {
+                return Ok(Some(
+                    extract_byte_ranges(value, byte_ranges)?
+                        .into_iter()
+                        .map(Bytes::from)
+                        .collect(),
+                ));
+            }
+            Some(KerchunkReference::Target(url)) => (url, byte_ranges.to_vec()),
+            Some(KerchunkReference::TargetRange {
+                url,
+                offset,
+                length,
+            }) => (url, target_byte_ranges(*offset, *length, byte_ranges)?),
+        };
+        let (storage, target_key) = self.targets.resolve(url)?;
+        storage
+            .get_partial_values_key(&target_key, &byte_ranges)?
+            .ok_or_else(|| StorageError::Other(format!("kerchunk target {url} not found")))
+            .map(Some)
+    }
+
+    fn size_key(&self, key: &StoreKey) -> Result<Option<u64>, StorageError> {
+        match self.references.get(key) {
+            None => Ok(None),
+            Some(KerchunkReference::Inline(value)) => Ok(Some(value.len() as u64)),
+            Some(KerchunkReference::Target(url)) => {
+                let (storage, target_key) = self.targets.resolve(url)?;
+                storage
+                    .size_key(&target_key)?
+                    .ok_or_else(|| StorageError::Other(format!("kerchunk target {url} not found")))
+                    .map(Some)
+            }
+            Some(KerchunkReference::TargetRange { length, .. }) => Ok(Some(*length)),
+        }
+    }
+
+    fn get_values_coalesced(
+        &self,
+        keys: &[StoreKey],
+        max_waste_ratio: f64,
+    ) -> Result<Vec<MaybeBytes>, StorageError> {
+        let mut values = vec![None; keys.len()];
+        let (individual, target_reads) =
+            plan_coalesced_reads(&self.references, keys, max_waste_ratio);
+        for index in individual {
+            values[index] = self.get(&keys[index])?;
+        }
+        for target_read in target_reads {
+            let (storage, target_key) = self.targets.resolve(target_read.url)?;
+            let bytes = storage
+                .get_partial_values_key(&target_key, &target_read.byte_ranges)?
+                .ok_or_else(|| {
+                    StorageError::Other(format!("kerchunk target {} not found", target_read.url))
+                })?;
+            target_read.extract_values(&bytes, &mut values);
+        }
+        Ok(values)
+    }
+}
+
+impl ListableStorageTraits for KerchunkStore {
+    fn list(&self) -> Result<StoreKeys, StorageError> {
+        self.list_prefix(&StorePrefix::root())
+    }
+
+    fn list_prefix(&self, prefix: &StorePrefix) -> Result<StoreKeys, StorageError> {
+        Ok(self.references.list_prefix(prefix))
+    }
+
+    fn list_dir(&self, prefix: &StorePrefix) -> Result<StoreKeysPrefixes, StorageError> {
+        self.references.list_dir(prefix)
+    }
+
+    fn size_prefix(&self, prefix: &StorePrefix) -> Result<u64, StorageError> {
+        let mut size = 0;
+        for key in self.list_prefix(prefix)? {
+            if let Some(size_key) = self.size_key(&key)? {
+                size += size_key;
+            }
+        }
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, sync::Arc};
+
+    use crate::{
+        array::{codec::CodecOptions, Array, DataType, FillValue},
+        array_subset::ArraySubset,
+        storage::{
+            storage_transformer::{
+                PerformanceMetricsStorageTransformer, StorageTransformerExtension,
+            },
+            store::MemoryStore,
+            WritableStorageTraits,
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn kerchunk() -> Result<(), Box<dyn Error>> {
+        // A "file" with a header and two little endian u16 chunks
+        let target = Arc::new(MemoryStore::new());
+        let file: Vec<u8> = [b"HEADER".as_slice(), &[1, 0, 2, 0], &[3, 0, 4, 0]].concat();
+        target.set(&"data/file.nc".try_into()?, file.into())?;
+
+        let references = KerchunkReferences::from_json(
+            br#"{
+                "version": 1,
+                "templates": {"u": "memory://bucket/data/file.nc"},
+                "refs": {
+                    ".zgroup": "{\"zarr_format\": 2}",
+                    "x/.zarray": {"chunks": [2], "compressor": null, "dtype": "<u2", "fill_value": 9, "filters": null, "order": "C", "shape": [6], "zarr_format": 2},
+                    "x/0": ["{{u}}", 6, 4],
+                    "x/1": ["memory://bucket/data/file.nc", 10, 4],
+                    "header": "base64:SEVBREVS",
+                    "file": ["{{u}}"]
+                }
+            }"#,
+        )?;
+        assert_eq!(references.len(), 6);
+        let store = KerchunkStore::new(references).with_target("memory://bucket/", target);
+
+        assert_eq!(
+            store.get(&"header".try_into()?)?,
+            Some(b"HEADER".to_vec().into())
+        );
+        assert_eq!(store.size_key(&"file".try_into()?)?, Some(14));
+        assert_eq!(store.size_key(&"x/1".try_into()?)?, Some(4));
+        assert_eq!(store.get(&"missing".try_into()?)?, None);
+        assert_eq!(
+            store.get_partial_values_key(
+                &"x/1".try_into()?,
+                &[
+                    ByteRange::FromStart(2, None),
+                    ByteRange::FromEnd(2, Some(1))
+                ]
+            )?,
+            Some(vec![vec![4, 0].into(), vec![0].into()])
+        );
+        assert!(store
+            .get_partial_values_key(&"x/1".try_into()?, &[ByteRange::FromStart(2, Some(3))])
+            .is_err());
+
+        let list_dir = store.list_dir(&StorePrefix::root())?;
+        assert_eq!(
+            list_dir.keys(),
+            &[
+                ".zgroup".try_into()?,
+                "file".try_into()?,
+                "header".try_into()?
+            ]
+        );
+        assert_eq!(list_dir.prefixes(), &["x/".try_into()?]);
+        assert_eq!(
+            store.size_prefix(&"x/".try_into()?)?,
+            store.get(&"x/.zarray".try_into()?)?.unwrap().len() as u64 + 8
+        );
+
+        let array = Array::open(Arc::new(store), "/x")?;
+        assert_eq!(array.data_type(), &DataType::UInt16);
+        assert_eq!(array.fill_value(), &FillValue::from(9u16));
+        assert_eq!(
+            array.retrieve_array_subset_elements::<u16>(&ArraySubset::new_with_shape(vec![6]))?,
+            vec![1, 2, 3, 4, 9, 9]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn kerchunk_coalesced() -> Result<(), Box<dyn Error>> {
+        // A "file" with a header and four little endian u16 chunks, with padding after the second chunk
+        let target = Arc::new(MemoryStore::new());
+        let file: Vec<u8> = [
+            b"HEADER".as_slice(),
+            &[1, 0, 2, 0],
+            &[3, 0, 4, 0],
+            &[0, 0],
+            &[5, 0, 6, 0],
+            &[7, 0, 8, 0],
+        ]
+        .concat();
+        target.set(&"file.nc".try_into()?, file.into())?;
+        let performance_metrics = Arc::new(PerformanceMetricsStorageTransformer::new());
+        let target = performance_metrics
+            .clone()
+            .create_readable_transformer(target);
+
+        let references = KerchunkReferences::from_json(
+            br#"{
+                "x/.zarray": {"chunks": [2], "compressor": null, "dtype": "<u2", "fill_value": 0, "filters": null, "order": "C", "shape": [10], "zarr_format": 2},
+                "x/0": ["memory://file.nc", 6, 4],
+                "x/1": ["memory://file.nc", 10, 4],
+                "x/2": ["memory://file.nc", 16, 4],
+                "x/3": ["memory://file.nc", 20, 4]
+            }"#,
+        )?;
+        let store = KerchunkStore::new(references).with_target("memory://", target);
+        let array = Array::open(Arc::new(store), "/x")?;
+
+        let mut reads = Vec::new();
+        for chunk_read_max_waste_ratio in [None, Some(0.0), Some(0.2)] {
+            let options = CodecOptions::builder()
+                .chunk_read_max_waste_ratio(chunk_read_max_waste_ratio)
+                .build();
+            let reads_start = performance_metrics.reads();
+            assert_eq!(
+                array.retrieve_array_subset_elements_opt::<u16>(
+                    &ArraySubset::new_with_shape(vec![10]),
+                    &options
+                )?,
+                vec![1, 2, 3, 4, 5, 6, 7, 8, 0, 0]
+            );
+            assert_eq!(
+                array.retrieve_array_subset_elements_opt::<u16>(
+                    &ArraySubset::new_with_ranges(&[1..7]),
+                    &options
+                )?,
+                vec![2, 3, 4, 5, 6, 7]
+            );
+            reads.push(performance_metrics.reads() - reads_start);
+        }
+        assert_eq!(reads, vec![8, 6, 4]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn kerchunk_missing_target() -> Result<(), Box<dyn Error>> {
+        let references = KerchunkReferences::from_json(br#"{"a": ["s3://bucket/a.nc", 0, 1]}"#)?;
+        let store =
+            KerchunkStore::new(references).with_target("memory://", Arc::new(MemoryStore::new()));
+        assert!(store.get(&"a".try_into()?).is_err());
+        assert!(store.size_key(&"a".try_into()?).is_ok());
+        assert!(
+            KerchunkReferences::from_json(br#"{"version": 1, "refs": {}, "gen": [{}]}"#).is_err()
+        );
+        Ok(())
+    }
+}
@@ -8,7 +8,8 @@ use crate::{
     storage::{
         Bytes, ListableStorageTraits, MaybeBytes, ReadableStorageTraits,
         ReadableWritableStorageTraits, StorageError, StoreKey, StoreKeyStartValue, StoreKeys,
-        StoreKeysPrefixes, StorePrefix, WritableStorageTraits,
+        StoreKeysPrefixes, StorePrefix, StoreTransaction, StoreTransactionOperation,
+        WritableStorageTraits,
     },
 };
 
@@ -172,6 +173,51 @@ impl WritableStorageTraits for MemoryStore {
         }
         Ok(())
     }
+
+    /// Begin a transaction which is applied atomically when committed.
+    fn begin(&self) -> Result<Box<dyn StoreTransaction + '_>, StorageError> {
+        Ok(Box::new(MemoryStoreTransaction {
+            store: self,
+            operations: Vec::new(),
+        }))
+    }
+}
+
+/// A [`StoreTransaction`] for a [`MemoryStore`], which applies its writes atomically.
+struct MemoryStoreTransaction<'a> {
+    store: &'a MemoryStore,
+    operations: Vec<StoreTransactionOperation>,
+}
+
+impl StoreTransaction for MemoryStoreTransaction<'_> {
+    fn set(&mut self, key: &StoreKey, value: Bytes) -> Result<(), StorageError> {
+        self.operations
+            .push(StoreTransactionOperation::Set(key.clone(), value));
+        Ok(())
+    }
+
+    fn erase(&mut self, key: &StoreKey) -> Result<(), StorageError> {
+        self.operations
+            .push(StoreTransactionOperation::Erase(key.clone()));
+        Ok(())
+    }
+
+    fn commit(self: Box<Self>) -> Result<(), StorageError> {
+        let Self { store, operations } = *self;
+        // Values are replaced rather than written in place, so the entire transaction is applied while the map is locked
+        let mut data_map = store.data_map.lock().unwrap();
+        for operation in operations {
+            match operation {
+                StoreTransactionOperation::Set(key, value) => {
+                    data_map.insert(key, Arc::new(RwLock::new(value.to_vec())));
+                }
+                StoreTransactionOperation::Erase(key) => {
+                    data_map.remove(&key);
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl ReadableWritableStorageTraits for MemoryStore {
@@ -242,6 +288,7 @@ mod tests {
         super::super::test_util::store_write(&store)?;
         super::super::test_util::store_read(&store)?;
         super::super::test_util::store_list(&store)?;
+        crate::storage::store_test_suite::all(&store, &"store_test_suite/".try_into()?)?;
         Ok(())
     }
 }
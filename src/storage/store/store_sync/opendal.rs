@@ -214,6 +214,7 @@ mod tests {
         super::super::test_util::store_write(&store)?;
         super::super::test_util::store_read(&store)?;
         super::super::test_util::store_list(&store)?;
+        crate::storage::store_test_suite::all(&store, &"store_test_suite/".try_into()?)?;
         Ok(())
     }
 
@@ -227,6 +228,7 @@ mod tests {
         super::super::test_util::store_write(&store)?;
         super::super::test_util::store_read(&store)?;
         super::super::test_util::store_list(&store)?;
+        crate::storage::store_test_suite::all(&store, &"store_test_suite/".try_into()?)?;
         Ok(())
     }
 }
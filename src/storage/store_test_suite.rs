@@ -0,0 +1,83 @@
+//! A conformance test suite for store implementations.
+//!
+//! Stores that do not behave as expected can fail deep inside codec code, so store implementors are encouraged to run these tests against their stores.
+//! The tests cover:
+//!  - byte range semantics ([`byte_ranges`]),
+//!  - partial and streaming writes ([`set_partial_values`]),
+//!  - listing semantics ([`listing`]),
+//!  - erasing values and prefixes ([`erase`]),
+//!  - transactional batch writes ([`transactions`]), and
+//!  - concurrent reads and writes ([`concurrency`]).
+//!
+//! [`all`] runs every test.
+//! Each test has an `async_` equivalent for asynchronous stores if the `async` feature is enabled.
+//!
+//! Every test writes to and then erases values under a sub-prefix of a `prefix` (e.g. `prefix/byte_ranges/`), so a store does not need to be empty.
+//! A test panics if the store does not behave as expected, and returns an error if a store operation fails unexpectedly.
+//! Keys that are also prefixes of other keys (e.g. `a` and `a/b`) are not tested, since filesystem stores cannot represent them.
+//!
+//! This module requires the `store_test_suite` feature.
+//!
+//! ### Example
+//! ```rust
+//! # use zarrs::storage::{store::MemoryStore, StorePrefix};
+//! # #[cfg(feature = "store_test_suite")]
+//! zarrs::storage::store_test_suite::all(&MemoryStore::new(), &StorePrefix::root())?;
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+mod store_test_suite_sync;
+
+#[cfg(feature = "async")]
+mod store_test_suite_async;
+
+pub use store_test_suite_sync::{
+    all, byte_ranges, concurrency, erase, listing, set_partial_values, transactions,
+};
+
+#[cfg(feature = "async")]
+pub use store_test_suite_async::{
+    async_all, async_byte_ranges, async_concurrency, async_erase, async_listing,
+    async_set_partial_values, async_transactions,
+};
+
+use super::{StoreKey, StoreKeyError, StorePrefix, StorePrefixError};
+
+/// The number of concurrent writers in the [`concurrency`] test.
+const CONCURRENCY: usize = 8;
+
+/// The number of writes by each concurrent writer in the [`concurrency`] test.
+const CONCURRENCY_ITERATIONS: usize = 16;
+
+/// Return the sub-prefix of `prefix` used by the test named `test`.
+fn test_prefix(prefix: &StorePrefix, test: &str) -> Result<StorePrefix, StorePrefixError> {
+    StorePrefix::new(format!("{}{test}/", prefix.as_str()))
+}
+
+/// Return the key `name` under `prefix`.
+fn test_key(prefix: &StorePrefix, name: &str) -> Result<StoreKey, StoreKeyError> {
+    StoreKey::new(format!("{}{name}", prefix.as_str()))
+}
+
+/// Return the keys `names` under `prefix`.
+fn test_keys(prefix: &StorePrefix, names: &[&str]) -> Result<Vec<StoreKey>, StoreKeyError> {
+    names.iter().map(|name| test_key(prefix, name)).collect()
+}
+
+/// Return the prefixes `names` under `prefix`.
+fn test_prefixes(
+    prefix: &StorePrefix,
+    names: &[&str],
+) -> Result<Vec<StorePrefix>, StorePrefixError> {
+    names
+        .iter()
+        .map(|name| StorePrefix::new(format!("{}{name}", prefix.as_str())))
+        .collect()
+}
+
+/// The value written by concurrent writer `writer`.
+///
+/// Values have distinct lengths and contents, so a value written to the wrong key can be detected.
+fn concurrency_value(writer: usize) -> Vec<u8> {
+    vec![u8::try_from(writer).unwrap(); 1024 * (writer + 1)]
+}
@@ -0,0 +1,555 @@
+use std::{error::Error, num::NonZeroUsize};
+
+use itertools::Itertools;
+
+use crate::{
+    byte_range::ByteRange,
+    storage::{
+        ReadableWritableListableStorageTraits, StorageError, StoreKeyRange, StoreKeyStartValue,
+        StorePrefix,
+    },
+};
+
+use super::{
+    concurrency_value, test_key, test_keys, test_prefix, test_prefixes, CONCURRENCY,
+    CONCURRENCY_ITERATIONS,
+};
+
+/// Run every test in the store test suite under `prefix`.
+///
+/// # Errors
+/// Returns an error if a store operation fails unexpectedly.
+///
+/// # Panics
+/// Panics if the store does not behave as expected.
+pub fn all<T: ?Sized + ReadableWritableListableStorageTraits>(
+    store: &T,
+    prefix: &StorePrefix,
+) -> Result<(), Box<dyn Error>> {
+    byte_ranges(store, prefix)?;
+    set_partial_values(store, prefix)?;
+    listing(store, prefix)?;
+    erase(store, prefix)?;
+    transactions(store, prefix)?;
+    concurrency(store, prefix)?;
+    Ok(())
+}
+
+/// Test the retrieval of values and byte ranges of values under `prefix/byte_ranges/`.
+///
+/// # Errors
+/// Returns an error if a store operation fails unexpectedly.
+///
+/// # Panics
+/// Panics if the store does not behave as expected.
+pub fn byte_ranges<T: ?Sized + ReadableWritableListableStorageTraits>(
+    store: &T,
+    prefix: &StorePrefix,
+) -> Result<(), Box<dyn Error>> {
+    let prefix = test_prefix(prefix, "byte_ranges")?;
+    store.erase_prefix(&prefix)?;
+
+    let key = test_key(&prefix, "value")?;
+    let key_empty = test_key(&prefix, "empty")?;
+    let key_missing = test_key(&prefix, "missing")?;
+    let value: Vec<u8> = (0..10).collect();
+    store.set(&key, value.clone().into())?;
+    store.set(&key_empty, vec![].into())?;
+
+    assert_eq!(
+        store.get(&key)?,
+        Some(value.clone().into()),
+        "get returned an incorrect value"
+    );
+    assert_eq!(
+        store.get(&key_empty)?,
+        Some(vec![].into()),
+        "get of an empty value must return an empty value, not None"
+    );
+    assert_eq!(
+        store.get(&key_missing)?,
+        None,
+        "get of a missing key must return None"
+    );
+    assert_eq!(store.size_key(&key)?, Some(10), "size_key is incorrect");
+    assert_eq!(
+        store.size_key(&key_empty)?,
+        Some(0),
+        "size_key of an empty value is incorrect"
+    );
+    assert_eq!(
+        store.size_key(&key_missing)?,
+        None,
+        "size_key of a missing key must return None"
+    );
+
+    let byte_ranges_expected = [
+        (ByteRange::FromStart(0, None), &value[..]),
+        (ByteRange::FromStart(2, Some(3)), &value[2..5]),
+        (ByteRange::FromStart(7, None), &value[7..]),
+        (ByteRange::FromStart(4, Some(0)), &[]),
+        (ByteRange::FromStart(10, Some(0)), &[]),
+        (ByteRange::FromEnd(0, None), &value[..]),
+        (ByteRange::FromEnd(0, Some(3)), &value[7..]),
+        (ByteRange::FromEnd(2, Some(3)), &value[5..8]),
+        (ByteRange::FromEnd(2, None), &value[..8]),
+    ];
+    for (byte_range, expected) in byte_ranges_expected {
+        assert_eq!(
+            store.get_partial_values_key(&key, &[byte_range])?,
+            Some(vec![expected.to_vec().into()]),
+            "byte range {byte_range} is incorrect"
+        );
+    }
+    let (byte_ranges, expected): (Vec<_>, Vec<_>) = byte_ranges_expected
+        .iter()
+        .map(|(byte_range, expected)| (*byte_range, expected.to_vec().into()))
+        .unzip();
+    assert_eq!(
+        store.get_partial_values_key(&key, &byte_ranges)?,
+        Some(expected),
+        "multiple byte ranges must be returned in the requested order"
+    );
+    assert_eq!(
+        store.get_partial_values_key(&key_empty, &[ByteRange::FromStart(0, None)])?,
+        Some(vec![vec![].into()]),
+        "byte range of an empty value is incorrect"
+    );
+    assert_eq!(
+        store.get_partial_values_key(&key_missing, &[ByteRange::FromStart(0, Some(1))])?,
+        None,
+        "byte ranges of a missing key must return None"
+    );
+    assert!(
+        store
+            .get_partial_values_key(&key, &[ByteRange::FromStart(8, Some(5))])
+            .is_err(),
+        "a byte range exceeding the value size must be an error"
+    );
+
+    assert_eq!(
+        store.get_partial_values(&[
+            StoreKeyRange::new(key.clone(), ByteRange::FromStart(1, Some(2))),
+            StoreKeyRange::new(key_missing.clone(), ByteRange::FromStart(0, Some(1))),
+            StoreKeyRange::new(key_empty.clone(), ByteRange::FromEnd(0, None)),
+            StoreKeyRange::new(key.clone(), ByteRange::FromEnd(1, Some(1))),
+        ])?,
+        vec![
+            Some(vec![1, 2].into()),
+            None,
+            Some(vec![].into()),
+            Some(vec![8].into())
+        ],
+        "get_partial_values is incorrect"
+    );
+
+    store.erase_prefix(&prefix)?;
+    Ok(())
+}
+
+/// Test partial and streaming writes under `prefix/set_partial_values/`.
+///
+/// # Errors
+/// Returns an error if a store operation fails unexpectedly.
+///
+/// # Panics
+/// Panics if the store does not behave as expected.
+pub fn set_partial_values<T: ?Sized + ReadableWritableListableStorageTraits>(
+    store: &T,
+    prefix: &StorePrefix,
+) -> Result<(), Box<dyn Error>> {
+    let prefix = test_prefix(prefix, "set_partial_values")?;
+    store.erase_prefix(&prefix)?;
+
+    let key_a = test_key(&prefix, "a")?;
+    let key_b = test_key(&prefix, "b")?;
+    store.set(&key_a, vec![0; 4].into())?;
+
+    store.set_partial_values(&[StoreKeyStartValue::new(key_a.clone(), 1, &[1, 2])])?;
+    assert_eq!(
+        store.get(&key_a)?,
+        Some(vec![0, 1, 2, 0].into()),
+        "set_partial_values within a value is incorrect"
+    );
+
+    store.set_partial_values(&[StoreKeyStartValue::new(key_a.clone(), 0, &[9])])?;
+    assert_eq!(
+        store.get(&key_a)?,
+        Some(vec![9, 1, 2, 0].into()),
+        "set_partial_values must not truncate a value"
+    );
+
+    store.set_partial_values(&[StoreKeyStartValue::new(key_a.clone(), 6, &[6])])?;
+    assert_eq!(
+        store.get(&key_a)?,
+        Some(vec![9, 1, 2, 0, 0, 0, 6].into()),
+        "set_partial_values beyond the end of a value must extend it with zeros"
+    );
+
+    store.set_partial_values(&[
+        StoreKeyStartValue::new(key_a.clone(), 0, &[0]),
+        StoreKeyStartValue::new(key_b.clone(), 1, &[1]),
+        StoreKeyStartValue::new(key_a.clone(), 6, &[7, 8]),
+        StoreKeyStartValue::new(key_b.clone(), 0, &[2]),
+    ])?;
+    assert_eq!(
+        store.get(&key_a)?,
+        Some(vec![0, 1, 2, 0, 0, 0, 7, 8].into()),
+        "set_partial_values with multiple values per key is incorrect"
+    );
+    assert_eq!(
+        store.get(&key_b)?,
+        Some(vec![2, 1].into()),
+        "set_partial_values must create a missing value"
+    );
+
+    let key_streaming = test_key(&prefix, "streaming")?;
+    let mut writer = store.set_streaming(&key_streaming)?;
+    writer.write(vec![0, 1].into())?;
+    writer.write(vec![].into())?;
+    writer.write(vec![2].into())?;
+    writer.finish()?;
+    assert_eq!(
+        store.get(&key_streaming)?,
+        Some(vec![0, 1, 2].into()),
+        "a finished streaming write is incorrect"
+    );
+
+    let key_abandoned = test_key(&prefix, "abandoned")?;
+    let mut writer = store.set_streaming(&key_abandoned)?;
+    writer.write(vec![0].into())?;
+    drop(writer);
+    assert_eq!(
+        store.get(&key_abandoned)?,
+        None,
+        "an unfinished streaming write must not store a value"
+    );
+    assert_eq!(
+        store.list_prefix(&prefix)?,
+        test_keys(&prefix, &["a", "b", "streaming"])?,
+        "an unfinished streaming write must not leave keys in the store"
+    );
+
+    store.erase_prefix(&prefix)?;
+    Ok(())
+}
+
+/// Test listing under `prefix/listing/`.
+///
+/// # Errors
+/// Returns an error if a store operation fails unexpectedly.
+///
+/// # Panics
+/// Panics if the store does not behave as expected.
+pub fn listing<T: ?Sized + ReadableWritableListableStorageTraits>(
+    store: &T,
+    prefix: &StorePrefix,
+) -> Result<(), Box<dyn Error>> {
+    let prefix = test_prefix(prefix, "listing")?;
+    store.erase_prefix(&prefix)?;
+
+    let keys = test_keys(
+        &prefix,
+        &["a/b", "a/c", "a/d/e", "a/f/g", "a/f/h", "ab", "i/j/k"],
+    )?;
+    for (i, key) in keys.iter().enumerate() {
+        store.set(key, vec![0; i].into())?;
+    }
+
+    assert_eq!(
+        store.list_prefix(&prefix)?,
+        keys,
+        "list_prefix must return all keys under a prefix in sorted order"
+    );
+    let list = store.list()?;
+    assert!(
+        keys.iter().all(|key| list.contains(key)),
+        "list must return all keys"
+    );
+    assert!(
+        list.iter().tuple_windows().all(|(a, b)| a < b),
+        "list must return keys in sorted order"
+    );
+    assert_eq!(
+        store.list_prefix(&test_prefixes(&prefix, &["a/"])?[0])?,
+        test_keys(&prefix, &["a/b", "a/c", "a/d/e", "a/f/g", "a/f/h"])?,
+        "list_prefix must only match keys under a prefix, not keys starting with the prefix name"
+    );
+    assert_eq!(
+        store.list_prefix(&test_prefixes(&prefix, &["missing/"])?[0])?,
+        vec![],
+        "list_prefix of a missing prefix must be empty"
+    );
+
+    let list_dir_expected = [
+        ("", vec!["ab"], vec!["a/", "i/"]),
+        ("a/", vec!["a/b", "a/c"], vec!["a/d/", "a/f/"]),
+        ("a/f/", vec!["a/f/g", "a/f/h"], vec![]),
+        ("i/", vec![], vec!["i/j/"]),
+        ("missing/", vec![], vec![]),
+    ];
+    for (dir, keys, prefixes) in list_dir_expected {
+        let dir = test_prefixes(&prefix, &[dir])?.remove(0);
+        let keys = test_keys(&prefix, &keys)?;
+        let prefixes = test_prefixes(&prefix, &prefixes)?;
+        let list_dir = store.list_dir(&dir)?;
+        assert_eq!(
+            list_dir.keys(),
+            &keys,
+            "list_dir of {dir} returned incorrect keys"
+        );
+        assert_eq!(
+            list_dir.prefixes(),
+            &prefixes,
+            "list_dir of {dir} returned incorrect prefixes"
+        );
+
+        let page_size = NonZeroUsize::new(1).unwrap();
+        let (mut keys_pages, mut prefixes_pages) = (vec![], vec![]);
+        for page in store.list_dir_pages(&dir, page_size) {
+            let page = page?;
+            assert!(
+                page.keys().len() + page.prefixes().len() <= 1,
+                "list_dir_pages returned a page exceeding the page size"
+            );
+            keys_pages.extend(page.keys);
+            prefixes_pages.extend(page.prefixes);
+        }
+        keys_pages.sort();
+        prefixes_pages.sort();
+        assert_eq!(
+            (keys_pages, prefixes_pages),
+            (keys, prefixes),
+            "list_dir_pages of {dir} is inconsistent with list_dir"
+        );
+    }
+
+    let page_size = NonZeroUsize::new(2).unwrap();
+    let pages = store
+        .list_prefix_pages(&prefix, page_size)
+        .collect::<Result<Vec<_>, _>>()?;
+    assert!(
+        pages.iter().all(|page| page.len() <= 2),
+        "list_prefix_pages returned a page exceeding the page size"
+    );
+    assert_eq!(
+        pages.concat().into_iter().sorted().collect::<Vec<_>>(),
+        keys,
+        "list_prefix_pages is inconsistent with list_prefix"
+    );
+
+    assert_eq!(
+        store.size_prefix(&prefix)?,
+        (0..keys.len() as u64).sum::<u64>(),
+        "size_prefix is incorrect"
+    );
+    assert_eq!(
+        store.size_prefix(&test_prefixes(&prefix, &["a/"])?[0])?,
+        (0..5).sum::<u64>(),
+        "size_prefix must only include keys under a prefix"
+    );
+
+    store.erase_prefix(&prefix)?;
+    Ok(())
+}
+
+/// Test erasing values and prefixes under `prefix/erase/`.
+///
+/// # Errors
+/// Returns an error if a store operation fails unexpectedly.
+///
+/// # Panics
+/// Panics if the store does not behave as expected.
+pub fn erase<T: ?Sized + ReadableWritableListableStorageTraits>(
+    store: &T,
+    prefix: &StorePrefix,
+) -> Result<(), Box<dyn Error>> {
+    let prefix = test_prefix(prefix, "erase")?;
+    store.erase_prefix(&prefix)?;
+
+    let keys = test_keys(&prefix, &["a", "b", "c", "d/e", "d/f/g", "de"])?;
+    for key in &keys {
+        store.set(key, vec![0].into())?;
+    }
+
+    store.erase(&keys[0])?;
+    assert_eq!(store.get(&keys[0])?, None, "erase must remove a value");
+    store.erase(&keys[0])?;
+    store.erase(&test_key(&prefix, "missing")?)?;
+
+    store.erase_values(&keys[1..3])?;
+    assert_eq!(
+        store.get_partial_values(&[
+            StoreKeyRange::new(keys[1].clone(), ByteRange::FromStart(0, None)),
+            StoreKeyRange::new(keys[2].clone(), ByteRange::FromStart(0, None)),
+        ])?,
+        vec![None, None],
+        "erase_values must remove all values"
+    );
+
+    store.erase_prefix(&test_prefixes(&prefix, &["d/"])?[0])?;
+    assert_eq!(
+        store.list_prefix(&prefix)?,
+        test_keys(&prefix, &["de"])?,
+        "erase_prefix must remove all keys under a prefix, and only those keys"
+    );
+    assert_eq!(
+        store.get(&keys[3])?,
+        None,
+        "erase_prefix must remove all values under a prefix"
+    );
+    store.erase_prefix(&test_prefixes(&prefix, &["missing/"])?[0])?;
+
+    store.erase_prefix(&prefix)?;
+    assert_eq!(
+        store.list_prefix(&prefix)?,
+        vec![],
+        "erase_prefix must remove all keys under a prefix"
+    );
+    Ok(())
+}
+
+/// Test transactional batch writes under `prefix/transactions/`.
+///
+/// # Errors
+/// Returns an error if a store operation fails unexpectedly.
+///
+/// # Panics
+/// Panics if the store does not behave as expected.
+pub fn transactions<T: ?Sized + ReadableWritableListableStorageTraits>(
+    store: &T,
+    prefix: &StorePrefix,
+) -> Result<(), Box<dyn Error>> {
+    let prefix = test_prefix(prefix, "transactions")?;
+    store.erase_prefix(&prefix)?;
+
+    let keys = test_keys(&prefix, &["a", "b", "c/d", "e", "f"])?;
+    store.set(&keys[0], vec![0].into())?;
+    store.set(&keys[1], vec![0].into())?;
+
+    let mut transaction = store.begin()?;
+    transaction.set(&keys[0], vec![1].into())?;
+    transaction.erase(&keys[1])?;
+    transaction.set(&keys[2], vec![2].into())?;
+    transaction.set(&keys[3], vec![3].into())?;
+    transaction.erase(&keys[3])?;
+    transaction.set(&keys[4], vec![4].into())?;
+    transaction.set(&keys[4], vec![5].into())?;
+    assert_eq!(
+        (store.get(&keys[0])?, store.get(&keys[2])?),
+        (Some(vec![0].into()), None),
+        "the writes of a transaction must not be visible before it is committed"
+    );
+    assert_eq!(
+        store
+            .list_prefix(&prefix)?
+            .into_iter()
+            .sorted()
+            .collect::<Vec<_>>(),
+        vec![keys[0].clone(), keys[1].clone()],
+        "the staged values of a transaction must not be listed before it is committed"
+    );
+    transaction.commit()?;
+    assert_eq!(
+        store.get_partial_values(
+            &keys
+                .iter()
+                .map(|key| StoreKeyRange::new(key.clone(), ByteRange::FromStart(0, None)))
+                .collect::<Vec<_>>()
+        )?,
+        vec![
+            Some(vec![1].into()),
+            None,
+            Some(vec![2].into()),
+            None,
+            Some(vec![5].into())
+        ],
+        "a committed transaction must apply its writes in order"
+    );
+
+    let mut transaction = store.begin()?;
+    transaction.set(&keys[1], vec![1].into())?;
+    drop(transaction);
+    assert_eq!(
+        store.get(&keys[1])?,
+        None,
+        "dropping a transaction must abandon its writes"
+    );
+    assert_eq!(
+        store
+            .list_prefix(&prefix)?
+            .into_iter()
+            .sorted()
+            .collect::<Vec<_>>(),
+        vec![keys[0].clone(), keys[2].clone(), keys[4].clone()],
+        "a transaction must not leave staged values in the store"
+    );
+
+    store.erase_prefix(&prefix)?;
+    Ok(())
+}
+
+/// Test concurrent writes and reads under `prefix/concurrency/`.
+///
+/// Concurrent writers each repeatedly write and read their own key, and read a shared key.
+///
+/// # Errors
+/// Returns an error if a store operation fails unexpectedly.
+///
+/// # Panics
+/// Panics if the store does not behave as expected.
+pub fn concurrency<T: ?Sized + ReadableWritableListableStorageTraits>(
+    store: &T,
+    prefix: &StorePrefix,
+) -> Result<(), Box<dyn Error>> {
+    let prefix = test_prefix(prefix, "concurrency")?;
+    store.erase_prefix(&prefix)?;
+
+    let key_shared = test_key(&prefix, "shared")?;
+    let keys = (0..CONCURRENCY)
+        .map(|writer| test_key(&prefix, &writer.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let values = (0..CONCURRENCY).map(concurrency_value).collect::<Vec<_>>();
+    let value_shared = concurrency_value(CONCURRENCY);
+    store.set(&key_shared, value_shared.clone().into())?;
+
+    std::thread::scope(|scope| {
+        let writers = (0..CONCURRENCY)
+            .map(|writer| {
+                let (key_shared, key, value, value_shared) =
+                    (&key_shared, &keys[writer], &values[writer], &value_shared);
+                scope.spawn(move || -> Result<(), StorageError> {
+                    for _ in 0..CONCURRENCY_ITERATIONS {
+                        store.set(key, value.clone().into())?;
+                        assert_eq!(
+                            store.get(key)?,
+                            Some(value.clone().into()),
+                            "a concurrent write to one key must not affect another"
+                        );
+                        assert_eq!(
+                            store.get(key_shared)?,
+                            Some(value_shared.clone().into()),
+                            "a concurrent read is incorrect"
+                        );
+                    }
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+        writers
+            .into_iter()
+            .try_for_each(|writer| writer.join().unwrap())
+    })?;
+
+    let mut keys_expected = keys.clone();
+    keys_expected.push(key_shared);
+    keys_expected.sort();
+    assert_eq!(
+        store.list_prefix(&prefix)?,
+        keys_expected,
+        "concurrent writes must all be listed"
+    );
+
+    store.erase_prefix(&prefix)?;
+    Ok(())
+}
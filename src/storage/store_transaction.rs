@@ -0,0 +1,161 @@
+use super::{Bytes, StorageError, StoreKey, WritableStorageTraits};
+
+#[cfg(feature = "async")]
+use super::{AsyncBytes, AsyncWritableStorageTraits};
+
+/// A write operation of a store transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreTransactionOperation {
+    /// Store a value at a key.
+    Set(StoreKey, Bytes),
+    /// Erase a key.
+    Erase(StoreKey),
+}
+
+impl StoreTransactionOperation {
+    /// Returns the key of the operation.
+    #[must_use]
+    pub const fn key(&self) -> &StoreKey {
+        match self {
+            Self::Set(key, _) | Self::Erase(key) => key,
+        }
+    }
+}
+
+/// A batch of writes which is committed to a store as a unit.
+///
+/// Writes are applied in order when the transaction is [committed](StoreTransaction::commit), and are not visible before then.
+/// Stores with atomic multi-key writes apply the transaction atomically, and others minimise the window in which a partially applied transaction is visible.
+/// The default transaction of a store, a [`NonAtomicStoreTransaction`], is **not** atomic.
+/// Dropping a transaction without committing it abandons its writes.
+pub trait StoreTransaction: Send {
+    /// Store `value` at `key` when the transaction is committed.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    fn set(&mut self, key: &StoreKey, value: Bytes) -> Result<(), StorageError>;
+
+    /// Erase `key` when the transaction is committed.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    fn erase(&mut self, key: &StoreKey) -> Result<(), StorageError>;
+
+    /// Commit the writes of the transaction.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    fn commit(self: Box<Self>) -> Result<(), StorageError>;
+}
+
+/// An async batch of writes which is committed to a store as a unit.
+///
+/// See [`StoreTransaction`].
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+pub trait AsyncStoreTransaction: Send {
+    /// Store `value` at `key` when the transaction is committed.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    async fn set(&mut self, key: &StoreKey, value: AsyncBytes) -> Result<(), StorageError>;
+
+    /// Erase `key` when the transaction is committed.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    async fn erase(&mut self, key: &StoreKey) -> Result<(), StorageError>;
+
+    /// Commit the writes of the transaction.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    async fn commit(self: Box<Self>) -> Result<(), StorageError>;
+}
+
+/// A best-effort store transaction which buffers writes in memory and applies them one at a time on commit.
+///
+/// This is the default transaction of stores that do not support atomic multi-key writes.
+///
+/// **The commit is not atomic**.
+/// The writes of the transaction are visible to readers as they are applied, and a failure or crash part way through a commit leaves the transaction partially applied with no rollback.
+/// Only the isolation of writes before the commit and abandoning them on drop are guaranteed.
+pub struct NonAtomicStoreTransaction<'a, TStorage: ?Sized> {
+    storage: &'a TStorage,
+    operations: Vec<StoreTransactionOperation>,
+}
+
+impl<'a, TStorage: ?Sized> NonAtomicStoreTransaction<'a, TStorage> {
+    /// Create a new buffered transaction for `storage`.
+    #[must_use]
+    pub fn new(storage: &'a TStorage) -> Self {
+        Self {
+            storage,
+            operations: Vec::new(),
+        }
+    }
+}
+
+impl<TStorage: ?Sized + WritableStorageTraits> StoreTransaction
+    for NonAtomicStoreTransaction<'_, TStorage>
+{
+    fn set(&mut self, key: &StoreKey, value: Bytes) -> Result<(), StorageError> {
+        self.operations
+            .push(StoreTransactionOperation::Set(key.clone(), value));
+        Ok(())
+    }
+
+    fn erase(&mut self, key: &StoreKey) -> Result<(), StorageError> {
+        self.operations
+            .push(StoreTransactionOperation::Erase(key.clone()));
+        Ok(())
+    }
+
+    fn commit(self: Box<Self>) -> Result<(), StorageError> {
+        let Self {
+            storage,
+            operations,
+        } = *self;
+        for operation in operations {
+            match operation {
+                StoreTransactionOperation::Set(key, value) => storage.set(&key, value)?,
+                StoreTransactionOperation::Erase(key) => storage.erase(&key)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<TStorage: ?Sized + AsyncWritableStorageTraits> AsyncStoreTransaction
+    for NonAtomicStoreTransaction<'_, TStorage>
+{
+    async fn set(&mut self, key: &StoreKey, value: AsyncBytes) -> Result<(), StorageError> {
+        self.operations
+            .push(StoreTransactionOperation::Set(key.clone(), value));
+        Ok(())
+    }
+
+    async fn erase(&mut self, key: &StoreKey) -> Result<(), StorageError> {
+        self.operations
+            .push(StoreTransactionOperation::Erase(key.clone()));
+        Ok(())
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), StorageError> {
+        let Self {
+            storage,
+            operations,
+        } = *self;
+        for operation in operations {
+            match operation {
+                StoreTransactionOperation::Set(key, value) => {
+                    storage.set(&key, value).await?;
+                }
+                StoreTransactionOperation::Erase(key) => storage.erase(&key).await?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+//! Store value version history.
+
+use std::{collections::BTreeMap, time::SystemTime};
+
+use super::{
+    AsyncBytes, AsyncListableStorageTraits, AsyncReadableStorageTraits, StorageError, StoreKey,
+    StorePrefix, StoreValueVersion,
+};
+use crate::byte_range::ByteRange;
+
+/// A historical version of a value in a store.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoreValueVersionInfo {
+    version: StoreValueVersion,
+    last_modified: SystemTime,
+    erased: bool,
+}
+
+impl StoreValueVersionInfo {
+    /// Create a new store value version info.
+    ///
+    /// `erased` is true if the version is a marker recording that the value was erased (e.g. an S3 delete marker).
+    #[must_use]
+    pub const fn new(version: StoreValueVersion, last_modified: SystemTime, erased: bool) -> Self {
+        Self {
+            version,
+            last_modified,
+            erased,
+        }
+    }
+
+    /// Returns the version.
+    #[must_use]
+    pub const fn version(&self) -> &StoreValueVersion {
+        &self.version
+    }
+
+    /// Returns the time the version was written.
+    #[must_use]
+    pub const fn last_modified(&self) -> SystemTime {
+        self.last_modified
+    }
+
+    /// Returns true if the version records that the value was erased.
+    #[must_use]
+    pub const fn is_erased(&self) -> bool {
+        self.erased
+    }
+}
+
+/// Returns the version current at `timestamp` from `versions` ordered oldest first.
+///
+/// Returns [`None`] if the value did not exist or was erased at `timestamp`.
+#[must_use]
+pub fn version_at(
+    versions: &[StoreValueVersionInfo],
+    timestamp: SystemTime,
+) -> Option<&StoreValueVersion> {
+    versions
+        .iter()
+        .rev()
+        .find(|version| version.last_modified <= timestamp)
+        .filter(|version| !version.erased)
+        .map(StoreValueVersionInfo::version)
+}
+
+/// Async storage traits for stores that retain the version history of values, such as S3 buckets with object versioning.
+///
+/// See [`AsyncTimeTravelStorageAdapter`](crate::storage::storage_adapter::time_travel::AsyncTimeTravelStorageAdapter) for reading a store as it existed at an earlier time.
+#[async_trait::async_trait]
+pub trait AsyncVersionHistoryStorageTraits:
+    AsyncReadableStorageTraits + AsyncListableStorageTraits
+{
+    /// Returns the versions of the value at `key`, oldest first.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if the store does not retain version history or there is an underlying storage error.
+    async fn list_versions(
+        &self,
+        key: &StoreKey,
+    ) -> Result<Vec<StoreValueVersionInfo>, StorageError>;
+
+    /// Returns the versions of the values with keys starting with `prefix`, each oldest first.
+    ///
+    /// Keys of erased values are included if the store retains their versions.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if the store does not retain version history or there is an underlying storage error.
+    async fn list_prefix_versions(
+        &self,
+        prefix: &StorePrefix,
+    ) -> Result<BTreeMap<StoreKey, Vec<StoreValueVersionInfo>>, StorageError>;
+
+    /// Retrieve partial bytes from a list of byte ranges of `version` of the value at `key`.
+    ///
+    /// Returns [`None`] if the version is not found.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    async fn get_partial_values_key_version(
+        &self,
+        key: &StoreKey,
+        version: &StoreValueVersion,
+        byte_ranges: &[ByteRange],
+    ) -> Result<Option<Vec<AsyncBytes>>, StorageError>;
+
+    /// Return the size in bytes of `version` of the value at `key`.
+    ///
+    /// Returns [`None`] if the version is not found.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying storage error.
+    async fn size_key_version(
+        &self,
+        key: &StoreKey,
+        version: &StoreValueVersion,
+    ) -> Result<Option<u64>, StorageError>;
+}
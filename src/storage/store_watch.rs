@@ -0,0 +1,268 @@
+//! Store change notifications.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::Duration,
+};
+
+use super::{
+    ListableStorageTraits, ReadableStorageTraits, StorageError, StoreKey, StorePrefix,
+    StoreValueVersion,
+};
+
+#[cfg(feature = "async")]
+use super::{AsyncListableStorageTraits, AsyncReadableStorageTraits};
+
+/// A change to a value in a store.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StoreWatchEvent {
+    /// A value was created.
+    Created(StoreKey),
+    /// An existing value was modified.
+    Modified(StoreKey),
+    /// A value was erased.
+    Erased(StoreKey),
+}
+
+impl StoreWatchEvent {
+    /// Returns the key of the changed value.
+    #[must_use]
+    pub const fn key(&self) -> &StoreKey {
+        match self {
+            Self::Created(key) | Self::Modified(key) | Self::Erased(key) => key,
+        }
+    }
+}
+
+/// A blocking iterator over [`StoreWatchEvent`]s.
+pub type StoreWatchEvents<'a> =
+    Box<dyn Iterator<Item = Result<StoreWatchEvent, StorageError>> + Send + 'a>;
+
+#[cfg(feature = "async")]
+/// A stream of [`StoreWatchEvent`]s.
+pub type AsyncStoreWatchEvents<'a> =
+    futures::stream::BoxStream<'a, Result<StoreWatchEvent, StorageError>>;
+
+/// Watchable storage traits.
+pub trait WatchableStorageTraits: Send + Sync {
+    /// Watch for changes to values with keys starting with `prefix`.
+    ///
+    /// Only changes made after the watch is created are reported.
+    /// The returned iterator blocks until the next change and does not end.
+    ///
+    /// Any listable store can be watched by polling with [`watch_polling`].
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if the watch cannot be created.
+    fn watch(&self, prefix: &StorePrefix) -> Result<StoreWatchEvents<'_>, StorageError>;
+}
+
+/// Detects changes to values in a store by comparing the versions of values between successive polls.
+///
+/// Values are listed with [`list_prefix`](ListableStorageTraits::list_prefix) and compared by their [`StoreValueVersion`].
+/// Stores without native value versions (such as `ETag`s) derive versions from the content of values, so each poll reads every value under the prefix.
+#[derive(Debug, Clone)]
+pub struct StorePoller {
+    prefix: StorePrefix,
+    versions: BTreeMap<StoreKey, StoreValueVersion>,
+}
+
+impl StorePoller {
+    /// Create a new store poller for values with keys starting with `prefix`.
+    ///
+    /// The first poll reports every existing value as [`Created`](StoreWatchEvent::Created).
+    #[must_use]
+    pub fn new(prefix: StorePrefix) -> Self {
+        Self {
+            prefix,
+            versions: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the prefix of the polled values.
+    #[must_use]
+    pub const fn prefix(&self) -> &StorePrefix {
+        &self.prefix
+    }
+
+    /// Poll `storage` and return the changes since the previous poll in key order.
+    ///
+    /// # Errors
+    /// Returns a [`StorageError`] if there is an underlying error with the store.
+    pub fn poll<TStorage: ?Sized + ReadableStorageTraits + ListableStorageTraits>(
+        &mut self,
+        storage: &TStorage,
+    ) -> Result<Vec<StoreWatchEvent>, StorageError> {
+        let mut versions = BTreeMap::new();
+        for key in storage.list_prefix(&self.prefix)? {
+            // The value may have been erased since it was listed
+            if let Some(version) = storage.get_version(&key)? {
+                versions.insert(key, version);
+            }
+        }
+        Ok(self.update(versions))
+    }
+
+    #[cfg(feature = "async")]
+    /// Async variant of [`poll`](StorePoller::poll).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn async_poll<
+        TStorage: ?Sized + AsyncReadableStorageTraits + AsyncListableStorageTraits,
+    >(
+        &mut self,
+        storage: &TStorage,
+    ) -> Result<Vec<StoreWatchEvent>, StorageError> {
+        let mut versions = BTreeMap::new();
+        for key in storage.list_prefix(&self.prefix).await? {
+            if let Some(version) = storage.get_version(&key).await? {
+                versions.insert(key, version);
+            }
+        }
+        Ok(self.update(versions))
+    }
+
+    /// Replace the known versions with `versions` and return the changes.
+    fn update(&mut self, versions: BTreeMap<StoreKey, StoreValueVersion>) -> Vec<StoreWatchEvent> {
+        let previous = std::mem::replace(&mut self.versions, versions);
+        let mut events: Vec<StoreWatchEvent> = self
+            .versions
+            .iter()
+            .filter_map(|(key, version)| match previous.get(key) {
+                None => Some(StoreWatchEvent::Created(key.clone())),
+                Some(previous_version) if previous_version != version => {
+                    Some(StoreWatchEvent::Modified(key.clone()))
+                }
+                Some(_) => None,
+            })
+            .collect();
+        events.extend(
+            previous
+                .into_keys()
+                .filter(|key| !self.versions.contains_key(key))
+                .map(StoreWatchEvent::Erased),
+        );
+        events.sort_by(|a, b| a.key().cmp(b.key()));
+        events
+    }
+}
+
+/// Watch for changes to values in `storage` with keys starting with `prefix` by polling the store every `interval`.
+///
+/// Only changes made after this function is called are reported.
+/// The returned iterator blocks until the next change and does not end.
+/// See [`StorePoller`].
+///
+/// # Errors
+/// Returns a [`StorageError`] if the initial state of the store cannot be retrieved.
+pub fn watch_polling<'a, TStorage: ?Sized + ReadableStorageTraits + ListableStorageTraits>(
+    storage: &'a TStorage,
+    prefix: &StorePrefix,
+    interval: Duration,
+) -> Result<StoreWatchEvents<'a>, StorageError> {
+    let mut poller = StorePoller::new(prefix.clone());
+    poller.poll(storage)?;
+    let mut pending = VecDeque::new();
+    Ok(Box::new(std::iter::from_fn(move || loop {
+        if let Some(event) = pending.pop_front() {
+            return Some(Ok(event));
+        }
+        std::thread::sleep(interval);
+        match poller.poll(storage) {
+            Ok(events) => pending.extend(events),
+            Err(err) => return Some(Err(err)),
+        }
+    })))
+}
+
+#[cfg(feature = "async")]
+/// Watch for changes to values in `storage` with keys starting with `prefix` by polling the store on each item of `ticks`.
+///
+/// The async API is runtime agnostic, so the polling schedule is supplied by the caller (e.g. a stream over a `tokio::time::Interval`).
+/// Only changes made after this function is called are reported.
+/// The returned stream ends when `ticks` ends.
+/// See [`StorePoller`].
+///
+/// # Errors
+/// Returns a [`StorageError`] if the initial state of the store cannot be retrieved.
+pub async fn async_watch_polling<'a, TStorage, TTicks>(
+    storage: &'a TStorage,
+    prefix: &StorePrefix,
+    ticks: TTicks,
+) -> Result<AsyncStoreWatchEvents<'a>, StorageError>
+where
+    TStorage: ?Sized + AsyncReadableStorageTraits + AsyncListableStorageTraits,
+    TTicks: futures::Stream + Send + 'a,
+{
+    use futures::{StreamExt, TryStreamExt};
+
+    let mut poller = StorePoller::new(prefix.clone());
+    poller.async_poll(storage).await?;
+    Ok(futures::stream::try_unfold(
+        (poller, ticks.boxed()),
+        move |(mut poller, mut ticks)| async move {
+            if ticks.next().await.is_none() {
+                return Ok::<_, StorageError>(None);
+            }
+            let events = poller.async_poll(storage).await?;
+            Ok(Some((events, (poller, ticks))))
+        },
+    )
+    .map_ok(|events| futures::stream::iter(events.into_iter().map(Ok)))
+    .try_flatten()
+    .boxed())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::storage::{store::MemoryStore, WritableStorageTraits};
+
+    use super::*;
+
+    #[test]
+    fn store_poller() -> Result<(), Box<dyn Error>> {
+        let store = MemoryStore::new();
+        store.set(&"a/b".try_into()?, vec![0].into())?;
+        store.set(&"a/c".try_into()?, vec![0].into())?;
+        store.set(&"d".try_into()?, vec![0].into())?;
+
+        let mut poller = StorePoller::new("a/".try_into()?);
+        assert_eq!(
+            poller.poll(&store)?,
+            vec![
+                StoreWatchEvent::Created("a/b".try_into()?),
+                StoreWatchEvent::Created("a/c".try_into()?)
+            ]
+        );
+        assert!(poller.poll(&store)?.is_empty());
+
+        store.set(&"a/b".try_into()?, vec![1].into())?;
+        store.erase(&"a/c".try_into()?)?;
+        store.set(&"a/e".try_into()?, vec![0].into())?;
+        store.set(&"d".try_into()?, vec![1].into())?;
+        assert_eq!(
+            poller.poll(&store)?,
+            vec![
+                StoreWatchEvent::Modified("a/b".try_into()?),
+                StoreWatchEvent::Erased("a/c".try_into()?),
+                StoreWatchEvent::Created("a/e".try_into()?)
+            ]
+        );
+        assert!(poller.poll(&store)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn store_watch_polling() -> Result<(), Box<dyn Error>> {
+        let store = MemoryStore::new();
+        store.set(&"a/b".try_into()?, vec![0].into())?;
+        let mut events = watch_polling(&store, &"a/".try_into()?, Duration::from_millis(1))?;
+        store.set(&"a/b".try_into()?, vec![1].into())?;
+        assert_eq!(
+            events.next().transpose()?,
+            Some(StoreWatchEvent::Modified("a/b".try_into()?))
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,565 @@
+//! TIFF and OME-TIFF ingestion.
+//!
+//! [`ingest_tiff`] reads a tiled (or stripped) TIFF image and stores it in a Zarr hierarchy:
+//!  - a group is created at the given path, and
+//!  - an array `0` is created in the group with one chunk per tile (or strip) of each page.
+//!
+//! A plain TIFF is stored with shape `[pages, y, x]` (or `[pages, y, x, samples]` with multiple samples per pixel).
+//! An OME-TIFF with a single sample per pixel is stored with shape `[t, c, z, y, x]`, and the OME-XML `Pixels` metadata is mapped to an OME-NGFF `multiscales` attribute of the group.
+//!
+//! Tiles are decoded and re-encoded with the codecs of the array by default.
+//! If [`TiffIngestOptions::copy_encoded_tiles`] is enabled, tiles that are uncompressed or `zstd` compressed (without a predictor) are copied into chunks without decoding.
+//! The array uses the `bytes` codec with the byte order of the TIFF file, and the `zstd` codec for `zstd` compressed tiles.
+//!
+//! This module requires the `tiff` feature.
+//!
+//! ### Example
+//! ```rust,no_run
+//! # use std::sync::Arc;
+//! use zarrs::tiff::{ingest_tiff, TiffIngestOptions};
+//! # let store = Arc::new(zarrs::storage::store::MemoryStore::new());
+//! let group = ingest_tiff("image.ome.tif".as_ref(), store, "/image", &TiffIngestOptions::default())?;
+//! let multiscales = group.ome_multiscales()?;
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::Path,
+    sync::Arc,
+};
+
+use thiserror::Error;
+use tiff::{
+    decoder::{Decoder, DecodingResult},
+    tags::Tag,
+    TiffError,
+};
+
+use crate::{
+    array::{
+        codec::{BytesCodec, BytesToBytesCodecTraits},
+        ArrayBuilder, ArrayCreateError, ArrayError, ArrayShape, DataType, Endianness, FillValue,
+    },
+    group::{Group, GroupBuilder, GroupCreateError},
+    ome::{Axis, AxisType, MultiscaleBuilder, OmeMetadataError},
+    storage::{ReadableWritableStorageTraits, StorageError},
+};
+
+/// The TIFF `Compression` tag value of uncompressed data.
+const COMPRESSION_NONE: u32 = 1;
+
+/// The TIFF `Compression` tag value of `zstd` compressed data.
+#[cfg_attr(not(feature = "zstd"), allow(dead_code))]
+const COMPRESSION_ZSTD: u32 = 50000;
+
+/// A TIFF ingestion error.
+#[derive(Debug, Error)]
+pub enum TiffIngestError {
+    /// An IO error.
+    #[error(transparent)]
+    IOError(#[from] std::io::Error),
+    /// A TIFF decoding error.
+    #[error(transparent)]
+    TiffError(#[from] TiffError),
+    /// The TIFF image is unsupported.
+    #[error("unsupported TIFF image: {_0}")]
+    Unsupported(String),
+    /// The pages of the TIFF image are inconsistent.
+    #[error("TIFF page {_0} is inconsistent with the first page")]
+    InconsistentPage(usize),
+    /// An array creation error.
+    #[error(transparent)]
+    ArrayCreateError(#[from] ArrayCreateError),
+    /// An array error.
+    #[error(transparent)]
+    ArrayError(#[from] ArrayError),
+    /// A group creation error.
+    #[error(transparent)]
+    GroupCreateError(#[from] GroupCreateError),
+    /// A storage error.
+    #[error(transparent)]
+    StorageError(#[from] StorageError),
+    /// An OME metadata error.
+    #[error(transparent)]
+    OmeMetadataError(#[from] OmeMetadataError),
+}
+
+/// Options for [`ingest_tiff`].
+#[derive(Debug, Clone, Default)]
+pub struct TiffIngestOptions {
+    bytes_to_bytes_codecs: Vec<Box<dyn BytesToBytesCodecTraits>>,
+    copy_encoded_tiles: bool,
+}
+
+impl TiffIngestOptions {
+    /// Return the bytes to bytes codecs of the array.
+    #[must_use]
+    pub fn bytes_to_bytes_codecs(&self) -> &[Box<dyn BytesToBytesCodecTraits>] {
+        &self.bytes_to_bytes_codecs
+    }
+
+    /// Set the bytes to bytes codecs of the array.
+    ///
+    /// These are ignored for `zstd` compressed TIFF images if [`copy_encoded_tiles`](TiffIngestOptions::copy_encoded_tiles) is enabled.
+    pub fn set_bytes_to_bytes_codecs(
+        &mut self,
+        bytes_to_bytes_codecs: Vec<Box<dyn BytesToBytesCodecTraits>>,
+    ) -> &mut Self {
+        self.bytes_to_bytes_codecs = bytes_to_bytes_codecs;
+        self
+    }
+
+    /// Return whether compatibly encoded tiles are copied into chunks without decoding.
+    #[must_use]
+    pub fn copy_encoded_tiles(&self) -> bool {
+        self.copy_encoded_tiles
+    }
+
+    /// Set whether compatibly encoded tiles are copied into chunks without decoding.
+    pub fn set_copy_encoded_tiles(&mut self, copy_encoded_tiles: bool) -> &mut Self {
+        self.copy_encoded_tiles = copy_encoded_tiles;
+        self
+    }
+}
+
+/// The layout of a TIFF page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PageLayout {
+    width: u32,
+    height: u32,
+    chunk_width: u32,
+    chunk_height: u32,
+    samples_per_pixel: u16,
+}
+
+impl PageLayout {
+    fn read<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<Self, TiffIngestError> {
+        let (width, height) = decoder.dimensions()?;
+        let (chunk_width, chunk_height) = decoder.chunk_dimensions();
+        let samples_per_pixel = tag_u32(decoder, Tag::SamplesPerPixel, 1)?;
+        if tag_u32(decoder, Tag::PlanarConfiguration, 1)? != 1 && samples_per_pixel > 1 {
+            return Err(TiffIngestError::Unsupported(
+                "planar configuration is not chunky".to_string(),
+            ));
+        }
+        Ok(Self {
+            width,
+            height,
+            chunk_width,
+            chunk_height,
+            samples_per_pixel: u16::try_from(samples_per_pixel)
+                .map_err(|_| TiffIngestError::Unsupported("samples per pixel".to_string()))?,
+        })
+    }
+
+    fn chunks_across(&self) -> u32 {
+        self.width.div_ceil(self.chunk_width)
+    }
+
+    fn num_chunks(&self) -> u32 {
+        self.chunks_across() * self.height.div_ceil(self.chunk_height)
+    }
+}
+
+/// The OME-XML `Pixels` metadata of an OME-TIFF.
+#[derive(Debug, Clone)]
+struct OmePixels {
+    dimension_order: Vec<char>,
+    size_z: u64,
+    size_c: u64,
+    size_t: u64,
+    physical_size: [Option<(f64, String)>; 3],
+}
+
+impl OmePixels {
+    /// Parse the `Pixels` element of OME-XML.
+    fn parse(ome_xml: &str, layout: &PageLayout) -> Option<Self> {
+        if !ome_xml.contains("<OME") {
+            return None;
+        }
+        let start = ome_xml.find("<Pixels ")?;
+        let end = start + ome_xml[start..].find('>')?;
+        let pixels = &ome_xml[start..end];
+        let attribute = |name: &str| {
+            let start = pixels.find(&format!(" {name}=\""))? + name.len() + 3;
+            let end = start + pixels[start..].find('"')?;
+            Some(&pixels[start..end])
+        };
+        let size = |name: &str| attribute(name).and_then(|size| size.parse::<u64>().ok());
+
+        let dimension_order: Vec<char> = attribute("DimensionOrder")?.chars().collect();
+        if dimension_order.len() != 5 || dimension_order[..2] != ['X', 'Y'] {
+            return None;
+        }
+        if size("SizeX")? != u64::from(layout.width) || size("SizeY")? != u64::from(layout.height) {
+            return None;
+        }
+        let physical_size = ["Z", "Y", "X"].map(|dimension| {
+            let size = attribute(&format!("PhysicalSize{dimension}"))?
+                .parse::<f64>()
+                .ok()?;
+            let unit = attribute(&format!("PhysicalSize{dimension}Unit")).unwrap_or("µm");
+            Some((size, unit.to_string()))
+        });
+        Some(Self {
+            dimension_order: dimension_order[2..].to_vec(),
+            size_z: size("SizeZ")?,
+            size_c: size("SizeC")?,
+            size_t: size("SizeT")?,
+            physical_size,
+        })
+    }
+
+    /// Return the `[t, c, z]` indices of a page.
+    fn page_indices(&self, page: u64) -> [u64; 3] {
+        let mut indices = [0; 3];
+        let mut remainder = page;
+        for dimension in &self.dimension_order {
+            let (index, size) = match dimension {
+                'T' => (0, self.size_t),
+                'C' => (1, self.size_c),
+                _ => (2, self.size_z),
+            };
+            indices[index] = remainder % size;
+            remainder /= size;
+        }
+        indices
+    }
+
+    fn multiscale_builder(&self) -> MultiscaleBuilder {
+        let unit = |physical_size: &Option<(f64, String)>| {
+            Some(
+                match physical_size
+                    .as_ref()
+                    .map_or("µm", |(_, unit)| unit.as_str())
+                {
+                    "nm" => "nanometer",
+                    "mm" => "millimeter",
+                    "cm" => "centimeter",
+                    "m" => "meter",
+                    _ => "micrometer",
+                }
+                .to_string(),
+            )
+        };
+        let [size_z, size_y, size_x] = &self.physical_size;
+        let mut builder = MultiscaleBuilder::new(vec![
+            Axis::new("t", AxisType::Time, None),
+            Axis::new("c", AxisType::Channel, None),
+            Axis::new("z", AxisType::Space, unit(size_z)),
+            Axis::new("y", AxisType::Space, unit(size_y)),
+            Axis::new("x", AxisType::Space, unit(size_x)),
+        ]);
+        let scale = |physical_size: &Option<(f64, String)>| {
+            physical_size.as_ref().map_or(1.0, |(size, _)| *size)
+        };
+        builder.dataset(
+            "0",
+            vec![1.0, 1.0, scale(size_z), scale(size_y), scale(size_x)],
+        );
+        builder
+    }
+}
+
+/// Return the value of a TIFF tag as a [`u32`], or `default` if it is absent.
+fn tag_u32<R: Read + Seek>(
+    decoder: &mut Decoder<R>,
+    tag: Tag,
+    default: u32,
+) -> Result<u32, TiffIngestError> {
+    Ok(decoder
+        .find_tag(tag)?
+        .map(tiff::decoder::ifd::Value::into_u32)
+        .transpose()?
+        .unwrap_or(default))
+}
+
+/// Convert a decoded TIFF chunk to its data type and native endian bytes.
+fn decoding_result_bytes(result: DecodingResult) -> Result<(DataType, Vec<u8>), TiffIngestError> {
+    Ok(match result {
+        DecodingResult::U8(values) => (DataType::UInt8, values),
+        DecodingResult::U16(values) => (DataType::UInt16, bytemuck::cast_slice(&values).to_vec()),
+        DecodingResult::U32(values) => (DataType::UInt32, bytemuck::cast_slice(&values).to_vec()),
+        DecodingResult::U64(values) => (DataType::UInt64, bytemuck::cast_slice(&values).to_vec()),
+        DecodingResult::I8(values) => (DataType::Int8, bytemuck::cast_slice(&values).to_vec()),
+        DecodingResult::I16(values) => (DataType::Int16, bytemuck::cast_slice(&values).to_vec()),
+        DecodingResult::I32(values) => (DataType::Int32, bytemuck::cast_slice(&values).to_vec()),
+        DecodingResult::I64(values) => (DataType::Int64, bytemuck::cast_slice(&values).to_vec()),
+        DecodingResult::F32(values) => (DataType::Float32, bytemuck::cast_slice(&values).to_vec()),
+        DecodingResult::F64(values) => (DataType::Float64, bytemuck::cast_slice(&values).to_vec()),
+        #[allow(unreachable_patterns)]
+        _ => {
+            return Err(TiffIngestError::Unsupported(
+                "unsupported sample format".to_string(),
+            ))
+        }
+    })
+}
+
+/// Pad the bytes of a decoded edge chunk with `data_width` x `data_height` pixels to the full chunk size.
+fn pad_chunk(
+    bytes: &[u8],
+    pixel_size: usize,
+    (data_width, data_height): (usize, usize),
+    (chunk_width, chunk_height): (usize, usize),
+) -> Vec<u8> {
+    let mut padded = vec![0; chunk_width * chunk_height * pixel_size];
+    for (row, data_row) in padded
+        .chunks_exact_mut(chunk_width * pixel_size)
+        .zip(bytes.chunks_exact(data_width * pixel_size))
+        .take(data_height)
+    {
+        row[..data_width * pixel_size].copy_from_slice(data_row);
+    }
+    padded
+}
+
+/// Ingest the TIFF or OME-TIFF image at `tiff_path` into `storage` as a group at `group_path` with an array `0`.
+///
+/// See the [module documentation](crate::tiff) for the layout of the array and group.
+///
+/// # Errors
+/// Returns a [`TiffIngestError`] if
+///  - the TIFF image cannot be read or is unsupported (e.g. a bit depth that is not a multiple of 8 or planar samples),
+///  - the pages of the TIFF image have a different layout or data type, or
+///  - there is an array, group, or storage error.
+///
+/// # Panics
+/// Panics if the size of a chunk exceeds [`usize::MAX`].
+#[allow(clippy::too_many_lines)]
+pub fn ingest_tiff<TStorage: ?Sized + ReadableWritableStorageTraits + 'static>(
+    tiff_path: &Path,
+    storage: Arc<TStorage>,
+    group_path: &str,
+    options: &TiffIngestOptions,
+) -> Result<Group<TStorage>, TiffIngestError> {
+    let mut file = File::open(tiff_path)?;
+    let mut byte_order = [0u8; 2];
+    file.read_exact(&mut byte_order)?;
+    let endianness = if &byte_order == b"MM" {
+        Endianness::Big
+    } else {
+        Endianness::Little
+    };
+    let mut decoder = Decoder::new(BufReader::new(File::open(tiff_path)?))?;
+
+    // Inspect the first page
+    let layout = PageLayout::read(&mut decoder)?;
+    let (data_type, _) = decoding_result_bytes(decoder.read_chunk(0)?)?;
+    let mut num_pages = 1;
+    while decoder.more_images() {
+        decoder.next_image()?;
+        num_pages += 1;
+    }
+    let ome_pixels = if layout.samples_per_pixel == 1 {
+        let mut decoder = Decoder::new(BufReader::new(File::open(tiff_path)?))?;
+        decoder
+            .get_tag_ascii_string(Tag::ImageDescription)
+            .ok()
+            .and_then(|ome_xml| OmePixels::parse(&ome_xml, &layout))
+            .filter(|pixels| pixels.size_t * pixels.size_c * pixels.size_z == num_pages)
+    } else {
+        None
+    };
+
+    // Create the array
+    let chunk_height = u64::from(layout.chunk_height);
+    let chunk_width = u64::from(layout.chunk_width);
+    let (shape, chunk_shape, dimension_names): (ArrayShape, ArrayShape, Vec<&str>) =
+        if let Some(pixels) = &ome_pixels {
+            (
+                vec![
+                    pixels.size_t,
+                    pixels.size_c,
+                    pixels.size_z,
+                    u64::from(layout.height),
+                    u64::from(layout.width),
+                ],
+                vec![1, 1, 1, chunk_height, chunk_width],
+                vec!["t", "c", "z", "y", "x"],
+            )
+        } else if layout.samples_per_pixel > 1 {
+            (
+                vec![
+                    num_pages,
+                    u64::from(layout.height),
+                    u64::from(layout.width),
+                    u64::from(layout.samples_per_pixel),
+                ],
+                vec![
+                    1,
+                    chunk_height,
+                    chunk_width,
+                    u64::from(layout.samples_per_pixel),
+                ],
+                vec!["i", "y", "x", "s"],
+            )
+        } else {
+            (
+                vec![num_pages, u64::from(layout.height), u64::from(layout.width)],
+                vec![1, chunk_height, chunk_width],
+                vec!["i", "y", "x"],
+            )
+        };
+    let element_size = data_type.fixed_size().unwrap();
+    let mut decoder = Decoder::new(BufReader::new(File::open(tiff_path)?))?;
+    let compression = tag_u32(&mut decoder, Tag::Compression, COMPRESSION_NONE)?;
+    let copy_compression =
+        if options.copy_encoded_tiles && tag_u32(&mut decoder, Tag::Predictor, 1)? == 1 {
+            match compression {
+                COMPRESSION_NONE if options.bytes_to_bytes_codecs.is_empty() => Some(compression),
+                #[cfg(feature = "zstd")]
+                COMPRESSION_ZSTD => Some(compression),
+                _ => None,
+            }
+        } else {
+            None
+        };
+    let bytes_to_bytes_codecs: Vec<Box<dyn BytesToBytesCodecTraits>> = match copy_compression {
+        #[cfg(feature = "zstd")]
+        Some(COMPRESSION_ZSTD) => vec![Box::new(crate::array::codec::ZstdCodec::new(3, false))],
+        _ => options.bytes_to_bytes_codecs.clone(),
+    };
+    let array = ArrayBuilder::new(
+        shape,
+        data_type.clone(),
+        chunk_shape
+            .try_into()
+            .map_err(|_| TiffIngestError::Unsupported("empty chunks".to_string()))?,
+        FillValue::new(vec![0; element_size]),
+    )
+    .array_to_bytes_codec(Box::new(BytesCodec::new(
+        (element_size > 1).then_some(endianness),
+    )))
+    .bytes_to_bytes_codecs(bytes_to_bytes_codecs)
+    .dimension_names(Some(dimension_names))
+    .build(
+        storage.clone(),
+        &if group_path.ends_with('/') {
+            format!("{group_path}0")
+        } else {
+            format!("{group_path}/0")
+        },
+    )?;
+    array.store_metadata()?;
+
+    // Store the chunks of each page
+    let pixel_size = element_size * usize::from(layout.samples_per_pixel);
+    let chunk_size = (
+        usize::try_from(layout.chunk_width).unwrap(),
+        usize::try_from(layout.chunk_height).unwrap(),
+    );
+    for page in 0..num_pages {
+        if page > 0 {
+            decoder.next_image()?;
+            if PageLayout::read(&mut decoder)? != layout {
+                return Err(TiffIngestError::InconsistentPage(
+                    usize::try_from(page).unwrap(),
+                ));
+            }
+        }
+        let page_copy = copy_compression.is_some()
+            && Some(tag_u32(&mut decoder, Tag::Compression, COMPRESSION_NONE)?) == copy_compression
+            && tag_u32(&mut decoder, Tag::Predictor, 1)? == 1;
+        let (offsets, byte_counts) = if page_copy {
+            let (offsets_tag, byte_counts_tag) = if decoder.find_tag(Tag::TileOffsets)?.is_some() {
+                (Tag::TileOffsets, Tag::TileByteCounts)
+            } else {
+                (Tag::StripOffsets, Tag::StripByteCounts)
+            };
+            (
+                decoder.get_tag_u64_vec(offsets_tag)?,
+                decoder.get_tag_u64_vec(byte_counts_tag)?,
+            )
+        } else {
+            (vec![], vec![])
+        };
+
+        let page_indices = ome_pixels
+            .as_ref()
+            .map_or_else(|| vec![page], |pixels| pixels.page_indices(page).to_vec());
+        for chunk_index in 0..layout.num_chunks() {
+            let mut chunk_indices = page_indices.clone();
+            chunk_indices.push(u64::from(chunk_index / layout.chunks_across()));
+            chunk_indices.push(u64::from(chunk_index % layout.chunks_across()));
+            if layout.samples_per_pixel > 1 {
+                chunk_indices.push(0);
+            }
+
+            let (data_width, data_height) = decoder.chunk_data_dimensions(chunk_index);
+            let data_size = (
+                usize::try_from(data_width).unwrap(),
+                usize::try_from(data_height).unwrap(),
+            );
+            let chunk = usize::try_from(chunk_index).unwrap();
+            if page_copy && data_size == chunk_size && chunk < offsets.len() {
+                if byte_counts[chunk] == 0 {
+                    continue;
+                }
+                let mut encoded = vec![0; usize::try_from(byte_counts[chunk]).unwrap()];
+                file.seek(SeekFrom::Start(offsets[chunk]))?;
+                file.read_exact(&mut encoded)?;
+                array.store_encoded_chunk(&chunk_indices, encoded.into())?;
+            } else {
+                let (chunk_data_type, bytes) =
+                    decoding_result_bytes(decoder.read_chunk(chunk_index)?)?;
+                if chunk_data_type != data_type {
+                    return Err(TiffIngestError::InconsistentPage(
+                        usize::try_from(page).unwrap(),
+                    ));
+                }
+                let bytes = if data_size == chunk_size {
+                    bytes
+                } else {
+                    pad_chunk(&bytes, pixel_size, data_size, chunk_size)
+                };
+                array.store_chunk(&chunk_indices, bytes)?;
+            }
+        }
+    }
+
+    // Create the group
+    let mut group = GroupBuilder::new().build(storage, group_path)?;
+    if let Some(pixels) = &ome_pixels {
+        group.set_ome_multiscales(&[pixels.multiscale_builder().build()?])?;
+    }
+    group.store_metadata()?;
+    Ok(group)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiff_ome_pixels() {
+        let layout = PageLayout {
+            width: 4,
+            height: 3,
+            chunk_width: 2,
+            chunk_height: 2,
+            samples_per_pixel: 1,
+        };
+        let ome_xml = r#"<?xml version="1.0"?><OME><Image ID="Image:0"><Pixels ID="Pixels:0" DimensionOrder="XYCZT" SizeX="4" SizeY="3" SizeZ="2" SizeC="3" SizeT="1" PhysicalSizeX="0.5" PhysicalSizeY="0.25" PhysicalSizeXUnit="nm" Type="uint16"></Pixels></Image></OME>"#;
+        let pixels = OmePixels::parse(ome_xml, &layout).unwrap();
+        assert_eq!(pixels.page_indices(0), [0, 0, 0]);
+        assert_eq!(pixels.page_indices(1), [0, 1, 0]);
+        assert_eq!(pixels.page_indices(4), [0, 1, 1]);
+        let multiscale = pixels.multiscale_builder().build().unwrap();
+        assert_eq!(multiscale.axes[4].unit.as_deref(), Some("nanometer"));
+        assert_eq!(multiscale.axes[3].unit.as_deref(), Some("micrometer"));
+
+        assert!(
+            OmePixels::parse(&ome_xml.replace("SizeX=\"4\"", "SizeX=\"5\""), &layout).is_none()
+        );
+        assert!(OmePixels::parse("plain description", &layout).is_none());
+    }
+
+    #[test]
+    fn tiff_pad_chunk() {
+        let padded = pad_chunk(&[1, 2, 3, 4], 1, (2, 2), (3, 3));
+        assert_eq!(padded, vec![1, 2, 0, 3, 4, 0, 0, 0, 0]);
+    }
+}
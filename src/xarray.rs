@@ -0,0 +1,526 @@
+//! xarray conventions.
+//!
+//! [xarray](https://docs.xarray.dev/en/stable/internals/zarr-encoding-spec.html) stores a dataset as a group of arrays, where:
+//!  - the dimension names of each array are stored in its `_ARRAY_DIMENSIONS` attribute (or its `dimension_names` in Zarr V3),
+//!  - an array named after its only dimension is the (index) coordinate of that dimension, and
+//!  - the non-index coordinates of an array are listed in its space-separated `coordinates` attribute.
+//!
+//! [`Array::xarray_dimensions`], [`Array::set_xarray_dimensions`], [`Array::xarray_coordinates`], and [`Array::set_xarray_coordinates`] get and set these attributes.
+//! [`XarrayDataset`] models the arrays of a group as variables with named dimensions, and maps coordinate labels to index ranges with [`XarrayDataset::label_range`].
+//!
+//! This module requires the `xarray` feature.
+//!
+//! ### Example
+//! ```rust
+//! # use std::sync::Arc;
+//! # use zarrs::array::{ArrayBuilder, DataType, FillValue};
+//! # use zarrs::group::GroupBuilder;
+//! use zarrs::xarray::XarrayDataset;
+//! # let store = Arc::new(zarrs::storage::store::MemoryStore::new());
+//! # let group = GroupBuilder::new().build(store.clone(), "/")?;
+//! # group.store_metadata()?;
+//! # let mut time = ArrayBuilder::new(vec![4], DataType::Float64, vec![4].try_into()?, FillValue::from(0.0f64)).build(store.clone(), "/time")?;
+//! # time.set_xarray_dimensions(&["time"])?;
+//! # time.store_metadata()?;
+//! # time.store_array_subset_elements(&zarrs::array_subset::ArraySubset::new_with_ranges(&[0..4]), &[0.0f64, 0.5, 1.0, 1.5])?;
+//! # let mut temperature = ArrayBuilder::new(vec![4, 2], DataType::Float32, vec![4, 2].try_into()?, FillValue::from(0.0f32)).build(store.clone(), "/temperature")?;
+//! # temperature.set_xarray_dimensions(&["time", "station"])?;
+//! # temperature.store_metadata()?;
+//! let dataset = XarrayDataset::open(&group)?;
+//! assert_eq!(dataset.dimensions()["time"], 4);
+//! let time_range = dataset.label_range("time", 0.5f64..=1.0)?;
+//! let subset = dataset.variable_subset("temperature", &[("time", time_range)])?;
+//! let temperature = dataset.variable("temperature").unwrap().array();
+//! let elements = temperature.retrieve_array_subset_elements::<f32>(&subset)?;
+//! assert_eq!(elements.len(), 4);
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    ops::{Range, RangeInclusive},
+};
+
+use thiserror::Error;
+
+use crate::{
+    array::{Array, ArrayCreateError, ArrayError, ElementOwned},
+    array_subset::ArraySubset,
+    group::Group,
+    storage::{ListableStorageTraits, ReadableStorageTraits},
+};
+
+/// The xarray dimension names attribute.
+pub const XARRAY_DIMENSIONS_ATTRIBUTE: &str = "_ARRAY_DIMENSIONS";
+
+/// The xarray (CF) non-index coordinates attribute.
+pub const XARRAY_COORDINATES_ATTRIBUTE: &str = "coordinates";
+
+/// An xarray conventions error.
+#[derive(Debug, Error)]
+pub enum XarrayError {
+    /// The dimension names of an array are invalid.
+    #[error("invalid xarray dimensions of {_0}: {_1}")]
+    InvalidDimensions(String, String),
+    /// A dimension has inconsistent lengths across variables.
+    #[error("dimension {_0} has inconsistent lengths {_1} and {_2}")]
+    InconsistentDimension(String, u64, u64),
+    /// The variable does not exist.
+    #[error("unknown variable {_0}")]
+    UnknownVariable(String),
+    /// The dimension does not exist.
+    #[error("unknown dimension {_0}")]
+    UnknownDimension(String),
+    /// The dimension has no index coordinate.
+    #[error("dimension {_0} has no index coordinate")]
+    MissingCoordinate(String),
+    /// A serialisation or deserialisation error.
+    #[error(transparent)]
+    SerdeError(#[from] serde_json::Error),
+    /// An array creation error.
+    #[error(transparent)]
+    ArrayCreateError(#[from] ArrayCreateError),
+    /// An array error.
+    #[error(transparent)]
+    ArrayError(#[from] ArrayError),
+}
+
+impl<TStorage: ?Sized> Array<TStorage> {
+    fn xarray_name(&self) -> String {
+        self.path()
+            .as_str()
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    /// Get the xarray dimension names of the array.
+    ///
+    /// The `_ARRAY_DIMENSIONS` attribute is preferred, otherwise the dimension names of the array are used if they are all set.
+    ///
+    /// # Errors
+    /// Returns [`XarrayError`] if the `_ARRAY_DIMENSIONS` attribute is present but invalid.
+    pub fn xarray_dimensions(&self) -> Result<Option<Vec<String>>, XarrayError> {
+        if let Some(dimensions) = self.attributes().get(XARRAY_DIMENSIONS_ATTRIBUTE) {
+            let dimensions: Vec<String> = serde_json::from_value(dimensions.clone())?;
+            if dimensions.len() != self.dimensionality() {
+                return Err(XarrayError::InvalidDimensions(
+                    self.xarray_name(),
+                    format!(
+                        "{} dimension names for an array with {} dimensions",
+                        dimensions.len(),
+                        self.dimensionality()
+                    ),
+                ));
+            }
+            Ok(Some(dimensions))
+        } else {
+            Ok(self.dimension_names().as_ref().and_then(|dimension_names| {
+                dimension_names
+                    .iter()
+                    .map(|dimension_name| dimension_name.as_str().map(ToString::to_string))
+                    .collect()
+            }))
+        }
+    }
+
+    /// Set the xarray `_ARRAY_DIMENSIONS` attribute of the array.
+    ///
+    /// # Errors
+    /// Returns [`XarrayError::InvalidDimensions`] if the number of `dimensions` does not match the dimensionality of the array, or a dimension name is repeated.
+    pub fn set_xarray_dimensions(&mut self, dimensions: &[&str]) -> Result<&mut Self, XarrayError> {
+        if dimensions.len() != self.dimensionality() {
+            return Err(XarrayError::InvalidDimensions(
+                self.xarray_name(),
+                format!(
+                    "{} dimension names for an array with {} dimensions",
+                    dimensions.len(),
+                    self.dimensionality()
+                ),
+            ));
+        }
+        for (i, dimension) in dimensions.iter().enumerate() {
+            if dimensions[..i].contains(dimension) {
+                return Err(XarrayError::InvalidDimensions(
+                    self.xarray_name(),
+                    format!("dimension {dimension} is repeated"),
+                ));
+            }
+        }
+        self.attributes_mut().insert(
+            XARRAY_DIMENSIONS_ATTRIBUTE.to_string(),
+            serde_json::to_value(dimensions)?,
+        );
+        Ok(self)
+    }
+
+    /// Get the non-index coordinate names of the array from its `coordinates` attribute.
+    ///
+    /// Returns an empty vector if the attribute is absent.
+    ///
+    /// # Errors
+    /// Returns [`XarrayError`] if the `coordinates` attribute is not a string.
+    pub fn xarray_coordinates(&self) -> Result<Vec<String>, XarrayError> {
+        self.attributes()
+            .get(XARRAY_COORDINATES_ATTRIBUTE)
+            .map_or(Ok(vec![]), |coordinates| {
+                let coordinates: String = serde_json::from_value(coordinates.clone())?;
+                Ok(coordinates
+                    .split_whitespace()
+                    .map(ToString::to_string)
+                    .collect())
+            })
+    }
+
+    /// Set the `coordinates` attribute of the array to the space-separated non-index `coordinates`.
+    pub fn set_xarray_coordinates(&mut self, coordinates: &[&str]) -> &mut Self {
+        self.attributes_mut().insert(
+            XARRAY_COORDINATES_ATTRIBUTE.to_string(),
+            coordinates.join(" ").into(),
+        );
+        self
+    }
+}
+
+/// An xarray variable: an array with named dimensions.
+#[derive(Debug)]
+pub struct XarrayVariable<TStorage: ?Sized> {
+    array: Array<TStorage>,
+    dimensions: Vec<String>,
+    coordinates: Vec<String>,
+}
+
+impl<TStorage: ?Sized> XarrayVariable<TStorage> {
+    /// Return the underlying array.
+    #[must_use]
+    pub const fn array(&self) -> &Array<TStorage> {
+        &self.array
+    }
+
+    /// Return the dimension names of the variable.
+    #[must_use]
+    pub fn dimensions(&self) -> &[String] {
+        &self.dimensions
+    }
+
+    /// Return the non-index coordinate names of the variable.
+    #[must_use]
+    pub fn coordinates(&self) -> &[String] {
+        &self.coordinates
+    }
+
+    /// Return the index of `dimension` in the dimensions of the variable.
+    #[must_use]
+    pub fn dimension_index(&self, dimension: &str) -> Option<usize> {
+        self.dimensions
+            .iter()
+            .position(|variable_dimension| variable_dimension == dimension)
+    }
+}
+
+/// An xarray dataset: the variables of a group and their named dimensions.
+#[derive(Debug)]
+pub struct XarrayDataset<TStorage: ?Sized> {
+    dimensions: BTreeMap<String, u64>,
+    variables: BTreeMap<String, XarrayVariable<TStorage>>,
+}
+
+impl<TStorage: ?Sized + ReadableStorageTraits + ListableStorageTraits + 'static>
+    XarrayDataset<TStorage>
+{
+    /// Open the xarray dataset in `group`.
+    ///
+    /// Child arrays without dimension names are not variables of the dataset.
+    ///
+    /// # Errors
+    /// Returns [`XarrayError`] if array metadata is invalid, dimension names are invalid, dimensions have inconsistent lengths, or there is an underlying store error.
+    pub fn open(group: &Group<TStorage>) -> Result<Self, XarrayError> {
+        let mut dimensions = BTreeMap::new();
+        let mut variables = BTreeMap::new();
+        for array in group.child_arrays()? {
+            let Some(variable_dimensions) = array.xarray_dimensions()? else {
+                continue;
+            };
+            for (dimension, &length) in std::iter::zip(&variable_dimensions, array.shape()) {
+                match dimensions.get(dimension) {
+                    Some(&existing) if existing != length => {
+                        return Err(XarrayError::InconsistentDimension(
+                            dimension.clone(),
+                            existing,
+                            length,
+                        ));
+                    }
+                    Some(_) => {}
+                    None => {
+                        dimensions.insert(dimension.clone(), length);
+                    }
+                }
+            }
+            let coordinates = array.xarray_coordinates()?;
+            variables.insert(
+                array.xarray_name(),
+                XarrayVariable {
+                    array,
+                    dimensions: variable_dimensions,
+                    coordinates,
+                },
+            );
+        }
+        Ok(Self {
+            dimensions,
+            variables,
+        })
+    }
+
+    /// Return the lengths of the dimensions of the dataset.
+    #[must_use]
+    pub const fn dimensions(&self) -> &BTreeMap<String, u64> {
+        &self.dimensions
+    }
+
+    /// Return the variables of the dataset.
+    #[must_use]
+    pub const fn variables(&self) -> &BTreeMap<String, XarrayVariable<TStorage>> {
+        &self.variables
+    }
+
+    /// Return the variable named `name`.
+    #[must_use]
+    pub fn variable(&self, name: &str) -> Option<&XarrayVariable<TStorage>> {
+        self.variables.get(name)
+    }
+
+    /// Return the index coordinate of `dimension`: the variable named `dimension` with `dimension` as its only dimension.
+    #[must_use]
+    pub fn coordinate(&self, dimension: &str) -> Option<&XarrayVariable<TStorage>> {
+        self.variables
+            .get(dimension)
+            .filter(|variable| variable.dimensions == [dimension])
+    }
+
+    /// Return an iterator over the names of the data variables: variables that are neither index coordinates nor non-index coordinates of another variable.
+    pub fn data_variables(&self) -> impl Iterator<Item = &str> {
+        self.variables.keys().map(String::as_str).filter(|name| {
+            self.coordinate(name).is_none()
+                && !self.variables.values().any(|variable| {
+                    variable
+                        .coordinates
+                        .iter()
+                        .any(|coordinate| coordinate == name)
+                })
+        })
+    }
+
+    /// Retrieve the values of the index coordinate of `dimension`.
+    ///
+    /// # Errors
+    /// Returns [`XarrayError`] if `dimension` has no index coordinate, or the coordinate cannot be retrieved as elements of type `T`.
+    pub fn coordinate_values<T: ElementOwned>(
+        &self,
+        dimension: &str,
+    ) -> Result<Vec<T>, XarrayError> {
+        let coordinate = self.coordinate_or_err(dimension)?;
+        let subset = ArraySubset::new_with_shape(coordinate.array.shape().to_vec());
+        Ok(coordinate.array.retrieve_array_subset_elements(&subset)?)
+    }
+
+    /// Return the index of the first element of the index coordinate of `dimension` equal to `label`.
+    ///
+    /// # Errors
+    /// Returns [`XarrayError`] if `dimension` has no index coordinate, or the coordinate cannot be retrieved as elements of type `T`.
+    pub fn label_index<T: ElementOwned + PartialEq>(
+        &self,
+        dimension: &str,
+        label: &T,
+    ) -> Result<Option<u64>, XarrayError> {
+        Ok(self
+            .coordinate_values::<T>(dimension)?
+            .iter()
+            .position(|value| value == label)
+            .map(|index| index as u64))
+    }
+
+    /// Return the index range of the index coordinate of `dimension` with labels in `labels`.
+    ///
+    /// The range spans the first to last matching element, so the coordinate is expected to be monotonic.
+    /// An empty range is returned if no labels match.
+    ///
+    /// # Errors
+    /// Returns [`XarrayError`] if `dimension` has no index coordinate, or the coordinate cannot be retrieved as elements of type `T`.
+    pub fn label_range<T: ElementOwned + PartialOrd>(
+        &self,
+        dimension: &str,
+        labels: RangeInclusive<T>,
+    ) -> Result<Range<u64>, XarrayError> {
+        let values = self.coordinate_values::<T>(dimension)?;
+        let first = values.iter().position(|value| labels.contains(value));
+        let last = values.iter().rposition(|value| labels.contains(value));
+        Ok(match (first, last) {
+            (Some(first), Some(last)) => first as u64..last as u64 + 1,
+            _ => 0..0,
+        })
+    }
+
+    /// Return the array subset of `variable` with the index `ranges` of named dimensions.
+    ///
+    /// Dimensions of the variable that are not in `ranges` span their full length.
+    ///
+    /// # Errors
+    /// Returns [`XarrayError`] if `variable` does not exist or a dimension in `ranges` is not a dimension of the variable.
+    pub fn variable_subset(
+        &self,
+        variable: &str,
+        ranges: &[(&str, Range<u64>)],
+    ) -> Result<ArraySubset, XarrayError> {
+        let variable = self
+            .variables
+            .get(variable)
+            .ok_or_else(|| XarrayError::UnknownVariable(variable.to_string()))?;
+        let mut subset_ranges = variable
+            .array
+            .shape()
+            .iter()
+            .map(|&length| 0..length)
+            .collect::<Vec<_>>();
+        for (dimension, range) in ranges {
+            let index = variable
+                .dimension_index(dimension)
+                .ok_or_else(|| XarrayError::UnknownDimension((*dimension).to_string()))?;
+            subset_ranges[index] = range.clone();
+        }
+        Ok(ArraySubset::new_with_ranges(&subset_ranges))
+    }
+
+    fn coordinate_or_err(&self, dimension: &str) -> Result<&XarrayVariable<TStorage>, XarrayError> {
+        if !self.dimensions.contains_key(dimension) {
+            return Err(XarrayError::UnknownDimension(dimension.to_string()));
+        }
+        self.coordinate(dimension)
+            .ok_or_else(|| XarrayError::MissingCoordinate(dimension.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        array::{ArrayBuilder, DataType, DimensionName, FillValue},
+        group::GroupBuilder,
+        storage::store::MemoryStore,
+    };
+
+    use super::*;
+
+    #[test]
+    fn xarray_array_attributes() {
+        let store = Arc::new(MemoryStore::new());
+        let mut array = ArrayBuilder::new(
+            vec![3, 2],
+            DataType::Float32,
+            vec![3, 2].try_into().unwrap(),
+            FillValue::from(0.0f32),
+        )
+        .build(store, "/temperature")
+        .unwrap();
+        assert_eq!(array.xarray_dimensions().unwrap(), None);
+
+        array.set_dimension_names(Some(vec![
+            DimensionName::new("time"),
+            DimensionName::new("station"),
+        ]));
+        assert_eq!(
+            array.xarray_dimensions().unwrap(),
+            Some(vec!["time".to_string(), "station".to_string()])
+        );
+
+        array.set_xarray_dimensions(&["t", "s"]).unwrap();
+        assert_eq!(
+            array.attributes()["_ARRAY_DIMENSIONS"],
+            serde_json::json!(["t", "s"])
+        );
+        assert_eq!(
+            array.xarray_dimensions().unwrap(),
+            Some(vec!["t".to_string(), "s".to_string()])
+        );
+        assert!(array.set_xarray_dimensions(&["t"]).is_err());
+        assert!(array.set_xarray_dimensions(&["t", "t"]).is_err());
+
+        assert!(array.xarray_coordinates().unwrap().is_empty());
+        array.set_xarray_coordinates(&["lat", "lon"]);
+        assert_eq!(array.attributes()["coordinates"], "lat lon");
+        assert_eq!(
+            array.xarray_coordinates().unwrap(),
+            vec!["lat".to_string(), "lon".to_string()]
+        );
+    }
+
+    #[test]
+    fn xarray_dataset() {
+        let store = Arc::new(MemoryStore::new());
+        let group = GroupBuilder::new().build(store.clone(), "/").unwrap();
+        group.store_metadata().unwrap();
+
+        let build = |path: &str, shape: Vec<u64>, dimensions: &[&str]| {
+            let mut array = ArrayBuilder::new(
+                shape.clone(),
+                DataType::Int32,
+                shape.try_into().unwrap(),
+                FillValue::from(0i32),
+            )
+            .build(store.clone(), path)
+            .unwrap();
+            array.set_xarray_dimensions(dimensions).unwrap();
+            array
+        };
+        let x = build("/x", vec![5], &["x"]);
+        x.store_metadata().unwrap();
+        x.store_array_subset_elements(
+            &ArraySubset::new_with_ranges(&[0..5]),
+            &[10, 20, 30, 40, 50],
+        )
+        .unwrap();
+        let mut data = build("/data", vec![2, 5], &["y", "x"]);
+        data.set_xarray_coordinates(&["label"]);
+        data.store_metadata().unwrap();
+        build("/label", vec![5], &["x"]).store_metadata().unwrap();
+
+        let dataset = XarrayDataset::open(&group).unwrap();
+        assert_eq!(
+            dataset.dimensions(),
+            &BTreeMap::from([("x".to_string(), 5), ("y".to_string(), 2)])
+        );
+        assert!(dataset.coordinate("x").is_some());
+        assert!(dataset.coordinate("label").is_none());
+        assert_eq!(dataset.data_variables().collect::<Vec<_>>(), vec!["data"]);
+
+        assert_eq!(dataset.label_index("x", &30i32).unwrap(), Some(2));
+        assert_eq!(dataset.label_index("x", &35i32).unwrap(), None);
+        assert_eq!(dataset.label_range("x", 15i32..=40).unwrap(), 1..4);
+        assert_eq!(dataset.label_range("x", 60i32..=70).unwrap(), 0..0);
+        assert!(matches!(
+            dataset.label_range("y", 0i32..=1),
+            Err(XarrayError::MissingCoordinate(_))
+        ));
+        assert!(matches!(
+            dataset.label_range("z", 0i32..=1),
+            Err(XarrayError::UnknownDimension(_))
+        ));
+
+        assert_eq!(
+            dataset.variable_subset("data", &[("x", 1..4)]).unwrap(),
+            ArraySubset::new_with_ranges(&[0..2, 1..4])
+        );
+        assert!(dataset.variable_subset("data", &[("z", 1..4)]).is_err());
+        assert!(dataset.variable_subset("missing", &[]).is_err());
+
+        build("/inconsistent", vec![3], &["x"])
+            .store_metadata()
+            .unwrap();
+        assert!(matches!(
+            XarrayDataset::open(&group),
+            Err(XarrayError::InconsistentDimension(..))
+        ));
+    }
+}
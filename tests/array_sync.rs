@@ -1,6 +1,9 @@
 #![cfg(feature = "ndarray")]
 
-use zarrs::array::{Array, ArrayBuilder, ArrayCodecTraits, DataType, FillValue};
+use zarrs::array::{
+    Array, ArrayBuilder, ArrayBytes, ArrayCodecTraits, ArrayError, DataType, Element, ElementOwned,
+    FillValue,
+};
 use zarrs::array_subset::ArraySubset;
 use zarrs::storage::store::MemoryStore;
 
@@ -371,3 +374,74 @@ fn array_binary() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+#[rustfmt::skip]
+fn array_sync_read_ndarray_elements() -> Result<(), Box<dyn std::error::Error>> {
+    let store = std::sync::Arc::new(MemoryStore::default());
+    let array = ArrayBuilder::new(vec![3, 3], DataType::Float64, vec![2, 2].try_into().unwrap(), FillValue::from(-1.0f64))
+        .build(store.clone(), "/f64")?;
+    array.store_array_subset_elements::<f64>(&ArraySubset::new_with_ranges(&[0..2, 0..3]), &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0])?;
+    assert_eq!(array.retrieve_array_subset_ndarray::<f64>(&ArraySubset::new_with_ranges(&[1..3, 0..3]))?, ndarray::array![[3.0, 4.0, 5.0], [-1.0, -1.0, -1.0]].into_dyn());
+    assert_eq!(array.retrieve_chunks_ndarray::<f64>(&ArraySubset::new_with_ranges(&[0..1, 1..2]))?, ndarray::array![[2.0, -1.0], [5.0, -1.0]].into_dyn());
+
+    let array = ArrayBuilder::new(vec![3, 3], DataType::Bool, vec![2, 2].try_into().unwrap(), FillValue::from(false))
+        .build(store, "/bool")?;
+    array.store_array_subset_elements::<bool>(&ArraySubset::new_with_ranges(&[1..2, 0..3]), &[true, false, true])?;
+    assert_eq!(array.retrieve_array_subset_ndarray::<bool>(&ArraySubset::new_with_ranges(&[0..3, 1..3]))?, ndarray::array![[false, false], [false, true], [false, false]].into_dyn());
+
+    Ok(())
+}
+
+/// An element without a native bytes representation.
+#[derive(Clone, Debug, PartialEq)]
+struct Celsius(f64);
+
+impl Element for Celsius {
+    fn validate_data_type(data_type: &DataType) -> Result<(), ArrayError> {
+        f64::validate_data_type(data_type)
+    }
+
+    fn into_array_bytes<'a>(
+        data_type: &DataType,
+        elements: &'a [Self],
+    ) -> Result<ArrayBytes<'a>, ArrayError> {
+        let elements = elements.iter().map(|element| element.0).collect::<Vec<_>>();
+        Ok(f64::into_array_bytes(data_type, &elements)?.into_owned())
+    }
+}
+
+impl ElementOwned for Celsius {
+    fn from_array_bytes(
+        data_type: &DataType,
+        bytes: ArrayBytes<'_>,
+    ) -> Result<Vec<Self>, ArrayError> {
+        Ok(f64::from_array_bytes(data_type, bytes)?
+            .into_iter()
+            .map(Celsius)
+            .collect())
+    }
+}
+
+#[test]
+#[rustfmt::skip]
+fn array_sync_read_ndarray_non_native_elements() -> Result<(), Box<dyn std::error::Error>> {
+    let store = std::sync::Arc::new(MemoryStore::default());
+    let array = ArrayBuilder::new(vec![3, 3], DataType::Float64, vec![2, 2].try_into().unwrap(), FillValue::from(-1.0f64))
+        .build(store, "/f64")?;
+    array.store_array_subset_elements::<f64>(&ArraySubset::new_with_ranges(&[0..2, 0..3]), &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0])?;
+
+    // Elements are converted rather than decoded in place
+    assert!(Celsius::native_bytes_representation(array.data_type()).is_none());
+    assert_eq!(
+        array.retrieve_array_subset_ndarray::<Celsius>(&ArraySubset::new_with_ranges(&[1..3, 1..3]))?,
+        ndarray::array![[Celsius(4.0), Celsius(5.0)], [Celsius(-1.0), Celsius(-1.0)]].into_dyn()
+    );
+    let mut output = vec![Celsius(0.0); 4];
+    assert!(matches!(
+        array.retrieve_array_subset_into_view(&ArraySubset::new_with_ranges(&[0..2, 0..2]), &mut output, &[2, 2], &[0, 0]),
+        Err(ArrayError::IncompatibleElementType)
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "derive")]
+
+use std::sync::Arc;
+
+use zarrs::{
+    array::{ArrayBuilder, ArrayError, DataType, Element, FillValue, ZarrsElement},
+    array_subset::ArraySubset,
+    storage::store::MemoryStore,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, ZarrsElement)]
+#[repr(C)]
+struct Record {
+    id: u32,
+    value: f32,
+}
+
+unsafe impl bytemuck::Zeroable for Record {}
+unsafe impl bytemuck::Pod for Record {}
+
+#[test]
+fn element_derive() -> Result<(), Box<dyn std::error::Error>> {
+    assert!(Record::validate_data_type(&DataType::RawBits(8)).is_ok());
+    assert!(matches!(
+        Record::validate_data_type(&DataType::UInt64),
+        Err(ArrayError::IncompatibleElementType)
+    ));
+
+    let store = Arc::new(MemoryStore::new());
+    let array = ArrayBuilder::new(
+        vec![4],
+        DataType::RawBits(8),
+        vec![2].try_into()?,
+        FillValue::new(vec![0; 8]),
+    )
+    .build(store, "/records")?;
+
+    let records: Vec<Record> = (0..4)
+        .map(|id| Record {
+            id,
+            value: id as f32 * 0.5,
+        })
+        .collect();
+    array.store_array_subset_elements(&ArraySubset::new_with_shape(vec![4]), &records)?;
+    assert_eq!(
+        array.retrieve_array_subset_elements::<Record>(&ArraySubset::new_with_ranges(&[1..3]))?,
+        records[1..3]
+    );
+    assert_eq!(
+        array.retrieve_chunk_elements::<Record>(&[0])?,
+        records[0..2]
+    );
+    Ok(())
+}
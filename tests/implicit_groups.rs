@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use zarrs::{
+    array::{Array, ArrayBuilder, DataType, FillValue},
+    config::global_config_mut,
+    group::{Group, GroupBuilder, GroupCreateError},
+    metadata::ImplicitGroupPolicy,
+    node::{Node, NodeCreateError},
+    storage::{store::MemoryStore, ReadableStorageTraits},
+};
+
+// The implicit group policy is global, so the policies are tested sequentially in a single test
+#[test]
+fn implicit_group_policy() -> Result<(), Box<dyn std::error::Error>> {
+    let store = Arc::new(MemoryStore::new());
+    let builder = ArrayBuilder::new(
+        vec![2, 2],
+        DataType::UInt8,
+        vec![1, 1].try_into()?,
+        FillValue::from(0u8),
+    );
+    builder
+        .build(store.clone(), "/a/b/array")?
+        .store_metadata()?;
+
+    // Allow
+    global_config_mut().set_implicit_group_policy(ImplicitGroupPolicy::Allow);
+    assert_eq!(
+        Node::open(&store, "/")?.hierarchy_tree(),
+        "/\n  a\n    b\n      array [2, 2] uint8\n"
+    );
+    Group::open(store.clone(), "/a")?;
+    builder.build(store.clone(), "/x/array")?.store_metadata()?;
+    assert!(store.get(&"x/zarr.json".try_into()?)?.is_none());
+    assert!(store.get(&"x/array/zarr.json".try_into()?)?.is_some());
+
+    // Error
+    global_config_mut().set_implicit_group_policy(ImplicitGroupPolicy::Error);
+    assert!(matches!(
+        Node::open(&store, "/a"),
+        Err(NodeCreateError::MissingMetadata)
+    ));
+    assert!(matches!(
+        Group::open(store.clone(), "/a"),
+        Err(GroupCreateError::MissingMetadata)
+    ));
+    let mut root = GroupBuilder::new().build(store.clone(), "/")?;
+    root.attributes_mut()
+        .insert("key".to_string(), "value".into());
+    assert!(builder
+        .build(store.clone(), "/array")?
+        .store_metadata()
+        .is_err());
+    root.store_metadata()?;
+    assert_eq!(Node::open(&store, "/")?.hierarchy_tree(), "/\n");
+    Array::open(store.clone(), "/a/b/array")?;
+    builder.build(store.clone(), "/array")?.store_metadata()?;
+    assert!(builder
+        .build(store.clone(), "/y/array")?
+        .store_metadata()
+        .is_err());
+    assert!(store.get(&"y/array/zarr.json".try_into()?)?.is_none());
+    assert!(GroupBuilder::new()
+        .build(store.clone(), "/a/b")?
+        .create_array("c", &builder)
+        .is_err());
+    assert!(store.get(&"a/b/c/zarr.json".try_into()?)?.is_none());
+
+    // Create on write
+    global_config_mut().set_implicit_group_policy(ImplicitGroupPolicy::CreateOnWrite);
+    let group = Group::open(store.clone(), "/a/b")?;
+    assert!(store.get(&"a/zarr.json".try_into()?)?.is_none());
+    group.create_array("c", &builder)?;
+    assert!(store.get(&"a/zarr.json".try_into()?)?.is_some());
+    assert!(store.get(&"a/b/zarr.json".try_into()?)?.is_some());
+    builder
+        .build(store.clone(), "/d/e/array")?
+        .store_metadata()?;
+    assert!(store.get(&"d/zarr.json".try_into()?)?.is_some());
+    assert!(store.get(&"d/e/zarr.json".try_into()?)?.is_some());
+    assert_eq!(
+        Group::open(store.clone(), "/")?.attributes().get("key"),
+        Some(&"value".into())
+    );
+
+    global_config_mut().set_implicit_group_policy(ImplicitGroupPolicy::Error);
+    assert_eq!(
+        Node::open(&store, "/")?.hierarchy_tree(),
+        "/\n  a\n    b\n      array [2, 2] uint8\n      c [2, 2] uint8\n  array [2, 2] uint8\n  d\n    e\n      array [2, 2] uint8\n"
+    );
+
+    global_config_mut().set_implicit_group_policy(ImplicitGroupPolicy::Allow);
+    Ok(())
+}
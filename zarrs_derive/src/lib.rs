@@ -0,0 +1,112 @@
+//! Derive macros for the [`zarrs`](https://docs.rs/zarrs) crate.
+//!
+//! The macros in this crate are re-exported by `zarrs` with the `derive` feature and should be used through `zarrs`.
+
+#![warn(clippy::pedantic)]
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{
+    parse_macro_input, punctuated::Punctuated, spanned::Spanned, Data, DeriveInput, Error, Meta,
+    Token,
+};
+
+/// Derive `Element`, `ElementOwned`, and `ElementFixedLength` for a plain-old-data struct.
+///
+/// Elements map to the `r*` raw bits data type with the size of the struct (e.g. `r64` for an 8 byte struct).
+/// The struct must
+///  - have a stable layout (`#[repr(C)]` or `#[repr(transparent)]`),
+///  - have no generic parameters, and
+///  - implement [`bytemuck::Pod`](https://docs.rs/bytemuck/latest/bytemuck/trait.Pod.html) (i.e. be [`Copy`], contain no padding, and be valid for any bit pattern).
+///
+/// Elements are stored with the in-memory representation of the struct, so the byte order of multi-byte fields is the native byte order.
+#[proc_macro_derive(ZarrsElement)]
+pub fn derive_zarrs_element(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive_element(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Returns true if the struct has a `#[repr(C)]` or `#[repr(transparent)]` attribute.
+fn has_stable_layout(input: &DeriveInput) -> Result<bool, Error> {
+    for attr in &input.attrs {
+        if attr.path().is_ident("repr") {
+            let reprs = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+            if reprs
+                .iter()
+                .any(|repr| repr.path().is_ident("C") || repr.path().is_ident("transparent"))
+            {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+fn derive_element(input: &DeriveInput) -> Result<proc_macro2::TokenStream, Error> {
+    if !matches!(input.data, Data::Struct(_)) {
+        return Err(Error::new(
+            input.span(),
+            "ZarrsElement can only be derived for structs",
+        ));
+    }
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "ZarrsElement cannot be derived for structs with generic parameters",
+        ));
+    }
+    if !has_stable_layout(input)? {
+        return Err(Error::new(
+            input.ident.span(),
+            "ZarrsElement requires #[repr(C)] or #[repr(transparent)]",
+        ));
+    }
+
+    let ident = &input.ident;
+    Ok(quote! {
+        impl ::zarrs::array::Element for #ident {
+            fn validate_data_type(
+                data_type: &::zarrs::array::DataType,
+            ) -> ::core::result::Result<(), ::zarrs::array::ArrayError> {
+                if data_type == &::zarrs::array::DataType::RawBits(::core::mem::size_of::<Self>()) {
+                    ::core::result::Result::Ok(())
+                } else {
+                    ::core::result::Result::Err(::zarrs::array::ArrayError::IncompatibleElementType)
+                }
+            }
+
+            fn into_array_bytes<'a>(
+                data_type: &::zarrs::array::DataType,
+                elements: &'a [Self],
+            ) -> ::core::result::Result<::zarrs::array::ArrayBytes<'a>, ::zarrs::array::ArrayError> {
+                <Self as ::zarrs::array::Element>::validate_data_type(data_type)?;
+                ::core::result::Result::Ok(::zarrs::array::ArrayBytes::new_flen(
+                    ::zarrs::array::transmute_to_bytes(elements),
+                ))
+            }
+        }
+
+        impl ::zarrs::array::ElementOwned for #ident {
+            fn from_array_bytes(
+                data_type: &::zarrs::array::DataType,
+                bytes: ::zarrs::array::ArrayBytes<'_>,
+            ) -> ::core::result::Result<::std::vec::Vec<Self>, ::zarrs::array::ArrayError> {
+                <Self as ::zarrs::array::Element>::validate_data_type(data_type)?;
+                let bytes = bytes.into_fixed()?;
+                ::core::result::Result::Ok(::zarrs::array::convert_from_bytes_slice::<Self>(&bytes))
+            }
+
+            fn native_bytes_representation(
+                data_type: &::zarrs::array::DataType,
+            ) -> ::core::option::Option<::zarrs::array::NativeBytesRepresentation<Self>> {
+                <Self as ::zarrs::array::Element>::validate_data_type(data_type)
+                    .is_ok()
+                    .then(::zarrs::array::NativeBytesRepresentation::new)
+            }
+        }
+
+        impl ::zarrs::array::ElementFixedLength for #ident {}
+    })
+}